@@ -4,6 +4,7 @@
 
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 
+use cli_ide_base::emitter::Emitter;
 use cli_ide_base::event::Event;
 
 /// Benchmark event emit with varying subscriber counts.
@@ -78,6 +79,33 @@ fn bench_event_map(c: &mut Criterion) {
     });
 }
 
+/// Benchmark synchronous emit with varying listener counts.
+fn bench_emitter_emit_to_listeners(c: &mut Criterion) {
+    let mut group = c.benchmark_group("emitter_emit");
+
+    for listener_count in [1, 4, 16, 64] {
+        group.throughput(Throughput::Elements(listener_count as u64));
+        group.bench_with_input(
+            BenchmarkId::new("listeners", listener_count),
+            &listener_count,
+            |b, &count| {
+                let emitter: Emitter<i32> = Emitter::new();
+                let _listeners: Vec<_> = (0..count)
+                    .map(|_| {
+                        emitter.listen(|value| {
+                            black_box(*value);
+                        })
+                    })
+                    .collect();
+
+                b.iter(|| emitter.emit(black_box(42)));
+            },
+        );
+    }
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_event_emit_to_subscribers,
@@ -85,5 +113,6 @@ criterion_group!(
     bench_event_new,
     bench_event_round_trip,
     bench_event_map,
+    bench_emitter_emit_to_listeners,
 );
 criterion_main!(benches);
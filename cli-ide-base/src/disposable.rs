@@ -0,0 +1,112 @@
+//! Disposable resources, following VS Code's `IDisposable` pattern.
+//!
+//! A [`Disposable`] wraps a cleanup action that runs exactly once, either
+//! when [`Disposable::dispose`] is called or when the value is dropped.
+//! Returning a `Disposable` from a registration function lets callers tie
+//! the registration's lifetime to a value they own.
+//!
+//! ```ignore
+//! let registration = Disposable::new(|| println!("released"));
+//! drop(registration); // prints "released"
+//! ```
+
+use std::fmt;
+use std::sync::Mutex;
+
+/// A cleanup action that runs once on dispose or drop.
+pub struct Disposable {
+    action: Mutex<Option<Box<dyn FnOnce() + Send>>>,
+}
+
+impl Disposable {
+    /// Create a disposable that runs `action` when disposed.
+    pub fn new<F>(action: F) -> Self
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        Self {
+            action: Mutex::new(Some(Box::new(action))),
+        }
+    }
+
+    /// Create a disposable that does nothing.
+    pub fn none() -> Self {
+        Self {
+            action: Mutex::new(None),
+        }
+    }
+
+    /// Run the cleanup action now.
+    ///
+    /// Calling this more than once has no further effect.
+    pub fn dispose(&self) {
+        let action = self.action.lock().expect("disposable lock poisoned").take();
+        if let Some(action) = action {
+            action();
+        }
+    }
+
+    /// Check whether the cleanup action has already run.
+    pub fn is_disposed(&self) -> bool {
+        self.action
+            .lock()
+            .expect("disposable lock poisoned")
+            .is_none()
+    }
+}
+
+impl Drop for Disposable {
+    fn drop(&mut self) {
+        self.dispose();
+    }
+}
+
+impl fmt::Debug for Disposable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Disposable")
+            .field("disposed", &self.is_disposed())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn counting() -> (Arc<AtomicUsize>, Disposable) {
+        let count = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&count);
+        let disposable = Disposable::new(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+        (count, disposable)
+    }
+
+    #[test]
+    fn test_dispose_runs_action_once() {
+        let (count, disposable) = counting();
+
+        disposable.dispose();
+        disposable.dispose();
+        assert!(disposable.is_disposed());
+        drop(disposable);
+
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_drop_runs_action() {
+        let (count, disposable) = counting();
+        assert!(!disposable.is_disposed());
+
+        drop(disposable);
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_none_is_already_disposed() {
+        assert!(Disposable::none().is_disposed());
+    }
+}
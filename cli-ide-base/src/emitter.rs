@@ -0,0 +1,309 @@
+//! A synchronous event emitter.
+//!
+//! [`Emitter<T>`] is the lightweight counterpart of [`Event`](crate::Event):
+//! listeners are callbacks invoked inline, on the emitting thread, in
+//! registration order. There are no channels or threads, which makes it a
+//! better fit for UI events that are produced and consumed on the render
+//! loop.
+//!
+//! ```ignore
+//! let emitter: Emitter<i32> = Emitter::new();
+//! let _listener = emitter.listen(|value| println!("got {value}"));
+//! emitter.emit(42); // prints "got 42" before returning
+//! ```
+//!
+//! [`listen`](Emitter::listen) returns a [`Disposable`] that removes the
+//! listener when dropped. Derived emitters from [`map`](Emitter::map) and
+//! [`filter`](Emitter::filter) remove their upstream listener once every
+//! clone of the derived emitter is dropped.
+
+use std::sync::{Arc, Mutex};
+
+use crate::disposable::Disposable;
+
+type Listener<T> = Arc<dyn Fn(&T) + Send + Sync>;
+
+/// The registered listeners of an emitter, keyed by registration id.
+struct Listeners<T> {
+    next_id: u64,
+    entries: Vec<(u64, Listener<T>)>,
+}
+
+type SharedListeners<T> = Arc<Mutex<Listeners<T>>>;
+
+/// Invoke every listener with `value`.
+///
+/// The listener list is snapshotted first so listeners may register,
+/// dispose or emit without deadlocking.
+fn notify<T>(listeners: &SharedListeners<T>, value: &T) {
+    let snapshot: Vec<Listener<T>> = listeners
+        .lock()
+        .expect("listener lock poisoned")
+        .entries
+        .iter()
+        .map(|(_, listener)| Arc::clone(listener))
+        .collect();
+    for listener in snapshot {
+        listener(value);
+    }
+}
+
+/// An event source whose listeners are called synchronously on emit.
+pub struct Emitter<T: 'static> {
+    listeners: SharedListeners<T>,
+    /// For derived emitters, the listener registered on the upstream
+    /// emitter. Shared by clones.
+    upstream: Option<Arc<Disposable>>,
+}
+
+impl<T: 'static> Clone for Emitter<T> {
+    fn clone(&self) -> Self {
+        Self {
+            listeners: Arc::clone(&self.listeners),
+            upstream: self.upstream.clone(),
+        }
+    }
+}
+
+impl<T: 'static> Default for Emitter<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: 'static> Emitter<T> {
+    /// Create a new `Emitter` with no listeners.
+    pub fn new() -> Self {
+        Self {
+            listeners: Arc::new(Mutex::new(Listeners {
+                next_id: 0,
+                entries: Vec::new(),
+            })),
+            upstream: None,
+        }
+    }
+
+    /// Call every listener with `value`, in registration order.
+    ///
+    /// Listeners added or removed during the emit take effect on the next one.
+    pub fn emit(&self, value: T) {
+        notify(&self.listeners, &value);
+    }
+
+    /// Register a listener.
+    ///
+    /// The listener stays registered until the returned [`Disposable`] is
+    /// disposed or dropped.
+    #[must_use = "dropping the returned Disposable removes the listener"]
+    pub fn listen<F>(&self, listener: F) -> Disposable
+    where
+        F: Fn(&T) + Send + Sync + 'static,
+    {
+        let id = self.register(Arc::new(listener));
+        let listeners = Arc::downgrade(&self.listeners);
+        Disposable::new(move || {
+            if let Some(listeners) = listeners.upgrade() {
+                remove(&listeners, id);
+            }
+        })
+    }
+
+    /// Number of currently registered listeners.
+    pub fn listener_count(&self) -> usize {
+        self.listeners
+            .lock()
+            .expect("listener lock poisoned")
+            .entries
+            .len()
+    }
+
+    /// Remove every listener and, for a derived emitter, detach from the
+    /// upstream emitter. Affects every clone.
+    pub fn dispose(&self) {
+        self.listeners
+            .lock()
+            .expect("listener lock poisoned")
+            .entries
+            .clear();
+        if let Some(upstream) = &self.upstream {
+            upstream.dispose();
+        }
+    }
+
+    /// Derive an emitter that emits `f(value)` for every value.
+    pub fn map<U, F>(self, f: F) -> Emitter<U>
+    where
+        U: 'static,
+        F: Fn(&T) -> U + Send + Sync + 'static,
+    {
+        self.derive(move |value, downstream| notify(downstream, &f(value)))
+    }
+
+    /// Derive an emitter that only emits values satisfying `predicate`.
+    pub fn filter<F>(self, predicate: F) -> Emitter<T>
+    where
+        F: Fn(&T) -> bool + Send + Sync + 'static,
+    {
+        self.derive(move |value, downstream| {
+            if predicate(value) {
+                notify(downstream, value);
+            }
+        })
+    }
+
+    /// Add a listener entry and return its id.
+    fn register(&self, listener: Listener<T>) -> u64 {
+        let mut listeners = self.listeners.lock().expect("listener lock poisoned");
+        let id = listeners.next_id;
+        listeners.next_id += 1;
+        listeners.entries.push((id, listener));
+        id
+    }
+
+    /// Build a derived emitter fed by `forward` from an upstream listener.
+    ///
+    /// The upstream listener only holds the downstream listener list, so
+    /// dropping every clone of the derived emitter removes it.
+    fn derive<U, F>(self, forward: F) -> Emitter<U>
+    where
+        U: 'static,
+        F: Fn(&T, &SharedListeners<U>) + Send + Sync + 'static,
+    {
+        let mut downstream = Emitter::<U>::new();
+        let downstream_listeners = Arc::clone(&downstream.listeners);
+        let id = self.register(Arc::new(move |value: &T| {
+            forward(value, &downstream_listeners)
+        }));
+
+        // Owning the whole upstream emitter keeps earlier stages alive.
+        let upstream = self;
+        downstream.upstream = Some(Arc::new(Disposable::new(move || {
+            upstream.unregister(id);
+        })));
+        downstream
+    }
+
+    /// Remove the listener registered under `id`.
+    fn unregister(&self, id: u64) {
+        remove(&self.listeners, id);
+    }
+}
+
+/// Remove the listener registered under `id`.
+fn remove<T>(listeners: &SharedListeners<T>, id: u64) {
+    listeners
+        .lock()
+        .expect("listener lock poisoned")
+        .entries
+        .retain(|(entry_id, _)| *entry_id != id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn recorder<T: Clone + Send + 'static>() -> (Arc<Mutex<Vec<T>>>, impl Fn(&T) + Send + Sync) {
+        let values = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&values);
+        (values, move |value: &T| {
+            sink.lock().unwrap().push(value.clone())
+        })
+    }
+
+    #[test]
+    fn test_listeners_called_inline_in_order() {
+        let emitter: Emitter<i32> = Emitter::new();
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let first = Arc::clone(&order);
+        let second = Arc::clone(&order);
+        let _a = emitter.listen(move |v| first.lock().unwrap().push(("a", *v)));
+        let _b = emitter.listen(move |v| second.lock().unwrap().push(("b", *v)));
+
+        emitter.emit(1);
+
+        // No waiting: listeners already ran
+        assert_eq!(*order.lock().unwrap(), vec![("a", 1), ("b", 1)]);
+    }
+
+    #[test]
+    fn test_emit_does_not_spawn_threads() {
+        let emitter: Emitter<()> = Emitter::new();
+        let caller = thread::current().id();
+        let seen = Arc::new(Mutex::new(None));
+        let sink = Arc::clone(&seen);
+        let _listener =
+            emitter.listen(move |_| *sink.lock().unwrap() = Some(thread::current().id()));
+
+        emitter.emit(());
+        assert_eq!(*seen.lock().unwrap(), Some(caller));
+    }
+
+    #[test]
+    fn test_dropping_disposable_removes_listener() {
+        let emitter: Emitter<i32> = Emitter::new();
+        let (values, listener) = recorder();
+        let registration = emitter.listen(listener);
+
+        emitter.emit(1);
+        drop(registration);
+        emitter.emit(2);
+
+        assert_eq!(*values.lock().unwrap(), vec![1]);
+        assert_eq!(emitter.listener_count(), 0);
+    }
+
+    #[test]
+    fn test_listener_may_dispose_itself_during_emit() {
+        let emitter: Emitter<i32> = Emitter::new();
+        let slot: Arc<Mutex<Option<Disposable>>> = Arc::new(Mutex::new(None));
+        let inner = Arc::clone(&slot);
+        let registration = emitter.listen(move |_| {
+            inner.lock().unwrap().take();
+        });
+        *slot.lock().unwrap() = Some(registration);
+
+        emitter.emit(1);
+        assert_eq!(emitter.listener_count(), 0);
+    }
+
+    #[test]
+    fn test_map_and_filter_chain() {
+        let emitter: Emitter<i32> = Emitter::new();
+        let derived = emitter.clone().map(|x| x * 10).filter(|x| *x > 10);
+        let (values, listener) = recorder();
+        let _listener = derived.listen(listener);
+
+        emitter.emit(1);
+        emitter.emit(2);
+        emitter.emit(3);
+
+        assert_eq!(*values.lock().unwrap(), vec![20, 30]);
+    }
+
+    #[test]
+    fn test_dropping_derived_emitter_detaches_upstream() {
+        let emitter: Emitter<i32> = Emitter::new();
+        let derived = emitter.clone().map(|x| x + 1).filter(|_| true);
+        assert_eq!(emitter.listener_count(), 1);
+
+        drop(derived);
+        assert_eq!(emitter.listener_count(), 0);
+    }
+
+    #[test]
+    fn test_dispose_detaches_all_clones() {
+        let emitter: Emitter<i32> = Emitter::new();
+        let derived = emitter.clone().filter(|_| true);
+        let clone = derived.clone();
+        let (values, listener) = recorder();
+        let _listener = clone.listen(listener);
+
+        derived.dispose();
+        emitter.emit(1);
+
+        assert!(values.lock().unwrap().is_empty());
+        assert_eq!(emitter.listener_count(), 0);
+        assert_eq!(clone.listener_count(), 0);
+    }
+}
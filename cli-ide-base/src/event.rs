@@ -5,7 +5,10 @@
 //! distinct from load-balancing where each message goes to only one consumer.
 //!
 //! It supports functional transformations such as `map`, `filter`, and `debounce`
-//! to build event pipelines, similar to VS Code's event API.
+//! to build event pipelines, similar to VS Code's event API, and batching
+//! combinators (`buffer`, `buffer_time`, `batch`) that coalesce bursts of
+//! values into `Vec`s. Two events can be combined with `merge` (either
+//! source's values) and `zip` (values paired in order).
 //!
 //! # Broadcast Semantics
 //!
@@ -21,25 +24,96 @@
 //! event.emit(42);
 //! // Both sub1 and sub2 receive 42
 //! ```
-
+//!
+//! # Lifetimes
+//!
+//! [`subscribe`](Event::subscribe) returns a [`Subscription`] that unregisters
+//! itself when dropped or disposed. Derived events such as those returned by
+//! `map`, `filter` and `debounce` own their stage of the pipeline: once every
+//! clone of a derived event and every subscription to it is dropped (or
+//! [`dispose`](Event::dispose) is called) the stage unsubscribes from its
+//! upstream event and its forwarding thread exits, so
+//! `event.map(f).subscribe()` keeps receiving without holding on to the
+//! mapped event. Combined events own both of their upstream events the same
+//! way.
+
+use std::collections::VecDeque;
+use std::ops::Deref;
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::{Duration, Instant};
 
-use crossbeam::channel::{unbounded, Receiver, Sender};
+use crossbeam::channel::{unbounded, Receiver, RecvTimeoutError, Select, Sender};
+
+use crate::disposable::Disposable;
+
+/// The registered senders of an event, keyed by subscription id.
+struct Subscribers<T> {
+    next_id: u64,
+    senders: Vec<(u64, Sender<T>)>,
+}
+
+type SharedSubscribers<T> = Arc<Mutex<Subscribers<T>>>;
+
+/// Send `value` to every subscriber, dropping disconnected ones.
+fn broadcast<T: Clone>(subscribers: &SharedSubscribers<T>, value: T) {
+    let mut subs = subscribers.lock().expect("subscriber lock poisoned");
+    // Retain only subscribers that successfully receive the message
+    subs.senders
+        .retain(|(_, sender)| sender.send(value.clone()).is_ok());
+}
+
+/// A registration on an [`Event`].
+///
+/// Dereferences to the underlying [`Receiver`], so values are read with the
+/// usual `recv`, `try_recv` and `iter` methods. Dropping the subscription (or
+/// calling [`dispose`](Subscription::dispose)) unregisters it from the event.
+pub struct Subscription<T> {
+    receiver: Receiver<T>,
+    registration: Disposable,
+    /// For subscriptions to derived events, the stage's registration on its
+    /// upstream event, kept alive as long as the subscription is.
+    _upstream: Option<Arc<Disposable>>,
+}
+
+impl<T> Subscription<T> {
+    /// Unregister from the event.
+    ///
+    /// Values already delivered can still be received; no new values arrive.
+    pub fn dispose(&self) {
+        self.registration.dispose();
+    }
+
+    /// Check whether the subscription has been disposed.
+    pub fn is_disposed(&self) -> bool {
+        self.registration.is_disposed()
+    }
+}
+
+impl<T> Deref for Subscription<T> {
+    type Target = Receiver<T>;
+
+    fn deref(&self) -> &Receiver<T> {
+        &self.receiver
+    }
+}
 
 /// An event stream producing values of type `T` with broadcast semantics.
 ///
 /// Each call to [`subscribe`](Event::subscribe) creates a new independent channel.
 /// When [`emit`](Event::emit) is called, the value is broadcast to **all** subscribers.
 pub struct Event<T: Clone + Send + 'static> {
-    subscribers: Arc<Mutex<Vec<Sender<T>>>>,
+    subscribers: SharedSubscribers<T>,
+    /// For derived events, the registration on the upstream event. Shared by
+    /// clones so the stage lives as long as any clone does.
+    upstream: Option<Arc<Disposable>>,
 }
 
 impl<T: Clone + Send + 'static> Clone for Event<T> {
     fn clone(&self) -> Self {
         Self {
             subscribers: Arc::clone(&self.subscribers),
+            upstream: self.upstream.clone(),
         }
     }
 }
@@ -54,7 +128,11 @@ impl<T: Clone + Send + 'static> Event<T> {
     /// Create a new `Event` with no subscribers.
     pub fn new() -> Self {
         Self {
-            subscribers: Arc::new(Mutex::new(Vec::new())),
+            subscribers: Arc::new(Mutex::new(Subscribers {
+                next_id: 0,
+                senders: Vec::new(),
+            })),
+            upstream: None,
         }
     }
 
@@ -63,21 +141,53 @@ impl<T: Clone + Send + 'static> Event<T> {
     /// The value is cloned for each subscriber. Subscribers whose channels have
     /// been disconnected (receiver dropped) are automatically removed.
     pub fn emit(&self, value: T) {
-        let mut subs = self.subscribers.lock().expect("subscriber lock poisoned");
-        // Retain only subscribers that successfully receive the message
-        subs.retain(|sender| sender.send(value.clone()).is_ok());
+        broadcast(&self.subscribers, value);
     }
 
     /// Create a new subscription to this event.
     ///
-    /// Returns a [`Receiver`] that will receive all values emitted after this
-    /// call. Each subscriber gets its own independent channel, ensuring true
-    /// broadcast semantics where every subscriber receives every event.
-    pub fn subscribe(&self) -> Receiver<T> {
-        let (sender, receiver) = unbounded();
-        let mut subs = self.subscribers.lock().expect("subscriber lock poisoned");
-        subs.push(sender);
-        receiver
+    /// Returns a [`Subscription`] that will receive all values emitted after
+    /// this call. Each subscriber gets its own independent channel, ensuring
+    /// true broadcast semantics where every subscriber receives every event.
+    pub fn subscribe(&self) -> Subscription<T> {
+        let (id, receiver) = self.register();
+        let subscribers = Arc::downgrade(&self.subscribers);
+        let registration = Disposable::new(move || {
+            if let Some(subscribers) = subscribers.upgrade() {
+                let mut subs = subscribers.lock().expect("subscriber lock poisoned");
+                subs.senders.retain(|(sub_id, _)| *sub_id != id);
+            }
+        });
+        Subscription {
+            receiver,
+            registration,
+            _upstream: self.upstream.clone(),
+        }
+    }
+
+    /// Number of currently registered subscribers.
+    pub fn subscriber_count(&self) -> usize {
+        self.subscribers
+            .lock()
+            .expect("subscriber lock poisoned")
+            .senders
+            .len()
+    }
+
+    /// Tear down this event.
+    ///
+    /// All subscribers are unregistered (their receivers disconnect once
+    /// drained) and, for a derived event, the stage unsubscribes from its
+    /// upstream event so its forwarding thread exits. Affects every clone.
+    pub fn dispose(&self) {
+        self.subscribers
+            .lock()
+            .expect("subscriber lock poisoned")
+            .senders
+            .clear();
+        if let Some(upstream) = &self.upstream {
+            upstream.dispose();
+        }
     }
 
     /// Apply a mapping function to each value in the stream, returning a new event.
@@ -88,18 +198,11 @@ impl<T: Clone + Send + 'static> Event<T> {
         U: Clone + Send + 'static,
         F: Fn(T) -> U + Send + Sync + 'static,
     {
-        let downstream = Event::<U>::new();
-        let downstream_clone = downstream.clone();
-        let upstream_receiver = self.subscribe();
-
-        thread::spawn(move || {
+        self.derive(move |upstream_receiver, downstream| {
             for val in upstream_receiver.iter() {
-                let mapped = f(val);
-                downstream_clone.emit(mapped);
+                broadcast(&downstream, f(val));
             }
-        });
-
-        downstream
+        })
     }
 
     /// Filter events based on a predicate.
@@ -109,19 +212,13 @@ impl<T: Clone + Send + 'static> Event<T> {
     where
         F: Fn(&T) -> bool + Send + Sync + 'static,
     {
-        let downstream = Event::<T>::new();
-        let downstream_clone = downstream.clone();
-        let upstream_receiver = self.subscribe();
-
-        thread::spawn(move || {
+        self.derive(move |upstream_receiver, downstream| {
             for val in upstream_receiver.iter() {
                 if predicate(&val) {
-                    downstream_clone.emit(val);
+                    broadcast(&downstream, val);
                 }
             }
-        });
-
-        downstream
+        })
     }
 
     /// Emit values at most once every `duration` (throttle/debounce).
@@ -129,11 +226,7 @@ impl<T: Clone + Send + 'static> Event<T> {
     /// The first value is always emitted. Subsequent values are only emitted
     /// if at least `duration` has passed since the last emission.
     pub fn debounce(self, duration: Duration) -> Event<T> {
-        let downstream = Event::<T>::new();
-        let downstream_clone = downstream.clone();
-        let upstream_receiver = self.subscribe();
-
-        thread::spawn(move || {
+        self.derive(move |upstream_receiver, downstream| {
             let mut last_emit: Option<Instant> = None;
             for val in upstream_receiver.iter() {
                 let now = Instant::now();
@@ -142,14 +235,223 @@ impl<T: Clone + Send + 'static> Event<T> {
                     None => true,
                 };
                 if should_send {
-                    downstream_clone.emit(val);
+                    broadcast(&downstream, val);
                     last_emit = Some(now);
                 }
             }
-        });
+        })
+    }
+
+    /// Collect values into batches of `count`, emitting each batch once it
+    /// is full.
+    ///
+    /// Values in an incomplete batch are dropped when the stage is torn
+    /// down.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` is zero.
+    pub fn buffer(self, count: usize) -> Event<Vec<T>> {
+        assert!(count > 0, "buffer count must be positive");
+        self.batched(count, None)
+    }
 
+    /// Collect the values arriving within `window` of the first one into a
+    /// batch, emitting it when the window closes.
+    ///
+    /// The next value after a batch opens a new window, so no empty batches
+    /// are emitted. Useful for coalescing bursts of file changes or
+    /// keystrokes into one redraw.
+    pub fn buffer_time(self, window: Duration) -> Event<Vec<T>> {
+        self.batched(usize::MAX, Some(window))
+    }
+
+    /// Collect values into batches, emitting a batch when it holds
+    /// `max_len` values or when `window` has passed since its first value,
+    /// whichever comes first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_len` is zero.
+    pub fn batch(self, max_len: usize, window: Duration) -> Event<Vec<T>> {
+        assert!(max_len > 0, "batch length must be positive");
+        self.batched(max_len, Some(window))
+    }
+
+    /// Derive an event emitting batches of at most `max_len` values, closed
+    /// early after `window` from their first value if given.
+    fn batched(self, max_len: usize, window: Option<Duration>) -> Event<Vec<T>> {
+        self.derive(move |upstream_receiver, downstream| {
+            let mut batch = Vec::new();
+            let mut deadline: Option<Instant> = None;
+            loop {
+                let received = match deadline {
+                    Some(deadline) => upstream_receiver.recv_deadline(deadline),
+                    None => upstream_receiver
+                        .recv()
+                        .map_err(|_| RecvTimeoutError::Disconnected),
+                };
+                match received {
+                    Ok(value) => {
+                        if batch.is_empty() {
+                            deadline = window.map(|window| Instant::now() + window);
+                        }
+                        batch.push(value);
+                        if batch.len() < max_len {
+                            continue;
+                        }
+                    }
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+                broadcast(&downstream, std::mem::take(&mut batch));
+                deadline = None;
+            }
+        })
+    }
+
+    /// Combine this event with `other` into one stream of both events'
+    /// values.
+    ///
+    /// Values are emitted in the order they arrive; values from the same
+    /// source keep their order. The merged event ends once both sources
+    /// are torn down.
+    pub fn merge(self, other: Event<T>) -> Event<T> {
+        self.combine(other, |first, second, downstream| {
+            let mut select = Select::new();
+            let first_index = select.recv(&first);
+            select.recv(&second);
+            let mut open = 2;
+            while open > 0 {
+                let operation = select.select();
+                let index = operation.index();
+                let receiver = if index == first_index {
+                    &first
+                } else {
+                    &second
+                };
+                match operation.recv(receiver) {
+                    Ok(value) => broadcast(&downstream, value),
+                    Err(_) => {
+                        select.remove(index);
+                        open -= 1;
+                    }
+                }
+            }
+        })
+    }
+
+    /// Pair each value of this event with the value of `other` at the same
+    /// position, emitting `(T, U)` tuples.
+    ///
+    /// Values from the faster source wait until the slower one catches up.
+    /// The zipped event ends once either source is torn down and its
+    /// waiting values are paired.
+    pub fn zip<U>(self, other: Event<U>) -> Event<(T, U)>
+    where
+        U: Clone + Send + 'static,
+    {
+        self.combine(other, |first, second, downstream| {
+            let mut select = Select::new();
+            let first_index = select.recv(&first);
+            select.recv(&second);
+            let mut firsts = VecDeque::new();
+            let mut seconds = VecDeque::new();
+            let (mut first_open, mut second_open) = (true, true);
+            while (first_open || !firsts.is_empty()) && (second_open || !seconds.is_empty()) {
+                let operation = select.select();
+                let index = operation.index();
+                let received = if index == first_index {
+                    operation.recv(&first).map(|value| firsts.push_back(value))
+                } else {
+                    operation
+                        .recv(&second)
+                        .map(|value| seconds.push_back(value))
+                };
+                if received.is_err() {
+                    select.remove(index);
+                    if index == first_index {
+                        first_open = false;
+                    } else {
+                        second_open = false;
+                    }
+                }
+                let paired = firsts.len().min(seconds.len());
+                for pair in firsts.drain(..paired).zip(seconds.drain(..paired)) {
+                    broadcast(&downstream, pair);
+                }
+            }
+        })
+    }
+
+    /// Register a raw sender and return its id and receiver.
+    fn register(&self) -> (u64, Receiver<T>) {
+        let (sender, receiver) = unbounded();
+        let mut subs = self.subscribers.lock().expect("subscriber lock poisoned");
+        let id = subs.next_id;
+        subs.next_id += 1;
+        subs.senders.push((id, sender));
+        (id, receiver)
+    }
+
+    /// Build a derived event whose values are produced by `forward` on a
+    /// dedicated thread.
+    ///
+    /// The thread only holds the downstream subscriber list, not the derived
+    /// event itself, so dropping every clone of the derived event releases the
+    /// upstream registration. That disconnects the thread's receiver and the
+    /// thread exits.
+    fn derive<U, F>(self, forward: F) -> Event<U>
+    where
+        U: Clone + Send + 'static,
+        F: FnOnce(Receiver<T>, SharedSubscribers<U>) + Send + 'static,
+    {
+        let mut downstream = Event::<U>::new();
+        let downstream_subscribers = Arc::clone(&downstream.subscribers);
+        let (id, upstream_receiver) = self.register();
+
+        thread::spawn(move || forward(upstream_receiver, downstream_subscribers));
+
+        // Owning the whole upstream event keeps earlier stages of the
+        // pipeline alive.
+        let upstream = self;
+        downstream.upstream = Some(Arc::new(Disposable::new(move || {
+            upstream.unregister(id);
+        })));
+        downstream
+    }
+
+    /// Build an event whose values are produced by `forward` from this
+    /// event's values and `other`'s, on a dedicated thread.
+    ///
+    /// Like [`derive`](Event::derive), but the stage owns a registration on
+    /// both upstream events and releases both when torn down.
+    fn combine<U, V, F>(self, other: Event<U>, forward: F) -> Event<V>
+    where
+        U: Clone + Send + 'static,
+        V: Clone + Send + 'static,
+        F: FnOnce(Receiver<T>, Receiver<U>, SharedSubscribers<V>) + Send + 'static,
+    {
+        let mut downstream = Event::<V>::new();
+        let downstream_subscribers = Arc::clone(&downstream.subscribers);
+        let (id, receiver) = self.register();
+        let (other_id, other_receiver) = other.register();
+
+        thread::spawn(move || forward(receiver, other_receiver, downstream_subscribers));
+
+        let upstream = self;
+        downstream.upstream = Some(Arc::new(Disposable::new(move || {
+            upstream.unregister(id);
+            other.unregister(other_id);
+        })));
         downstream
     }
+
+    /// Remove the sender registered under `id`.
+    fn unregister(&self, id: u64) {
+        let mut subs = self.subscribers.lock().expect("subscriber lock poisoned");
+        subs.senders.retain(|(sub_id, _)| *sub_id != id);
+    }
 }
 
 #[cfg(test)]
@@ -270,6 +572,20 @@ mod tests {
         assert_eq!(val, 10);
     }
 
+    #[test]
+    fn test_subscription_keeps_a_temporary_derived_event_alive() {
+        let event: Event<i32> = Event::new();
+        let receiver = event.clone().map(|x| x + 1).subscribe();
+
+        event.emit(1);
+        let val = receiver.recv_timeout(Duration::from_millis(100)).unwrap();
+        assert_eq!(val, 2);
+        assert_eq!(event.subscriber_count(), 1);
+
+        drop(receiver);
+        assert_eq!(event.subscriber_count(), 0);
+    }
+
     #[test]
     fn test_event_map_broadcast() {
         // Verify that mapped events also broadcast to multiple subscribers
@@ -419,4 +735,225 @@ mod tests {
         let val = sub.recv_timeout(Duration::from_millis(100)).unwrap();
         assert_eq!(val, 42);
     }
+
+    #[test]
+    fn test_buffer_emits_full_batches() {
+        let event: Event<i32> = Event::new();
+        let buffered = event.clone().buffer(3);
+        let receiver = buffered.subscribe();
+
+        for value in 1..=7 {
+            event.emit(value);
+        }
+        let timeout = Duration::from_millis(500);
+        assert_eq!(receiver.recv_timeout(timeout), Ok(vec![1, 2, 3]));
+        assert_eq!(receiver.recv_timeout(timeout), Ok(vec![4, 5, 6]));
+        assert!(receiver.recv_timeout(Duration::from_millis(50)).is_err());
+    }
+
+    #[test]
+    fn test_buffer_time_coalesces_bursts() {
+        let event: Event<i32> = Event::new();
+        let buffered = event.clone().buffer_time(Duration::from_millis(60));
+        let receiver = buffered.subscribe();
+
+        event.emit(1);
+        event.emit(2);
+        event.emit(3);
+        assert_eq!(
+            receiver.recv_timeout(Duration::from_millis(500)),
+            Ok(vec![1, 2, 3])
+        );
+
+        // Quiet periods produce no empty batches
+        assert!(receiver.recv_timeout(Duration::from_millis(100)).is_err());
+        event.emit(4);
+        assert_eq!(
+            receiver.recv_timeout(Duration::from_millis(500)),
+            Ok(vec![4])
+        );
+    }
+
+    #[test]
+    fn test_batch_closes_on_length_or_window() {
+        let event: Event<i32> = Event::new();
+        let batched = event.clone().batch(2, Duration::from_millis(60));
+        let receiver = batched.subscribe();
+
+        event.emit(1);
+        event.emit(2);
+        event.emit(3);
+        let timeout = Duration::from_millis(500);
+        assert_eq!(receiver.recv_timeout(timeout), Ok(vec![1, 2]));
+        assert_eq!(receiver.recv_timeout(timeout), Ok(vec![3]));
+    }
+
+    #[test]
+    fn test_dropping_buffered_event_unsubscribes() {
+        let event: Event<i32> = Event::new();
+        let buffered = event.clone().buffer_time(Duration::from_secs(60));
+        event.emit(1);
+        assert_eq!(event.subscriber_count(), 1);
+
+        drop(buffered);
+        assert_eq!(event.subscriber_count(), 0);
+    }
+
+    #[test]
+    fn test_merge_forwards_both_sources() {
+        let first: Event<i32> = Event::new();
+        let second: Event<i32> = Event::new();
+        let merged = first.clone().merge(second.clone());
+        let receiver = merged.subscribe();
+
+        first.emit(1);
+        second.emit(10);
+        first.emit(2);
+        let timeout = Duration::from_millis(500);
+        let mut received: Vec<i32> = (0..3)
+            .map(|_| receiver.recv_timeout(timeout).unwrap())
+            .collect();
+        received.sort_unstable();
+        assert_eq!(received, vec![1, 2, 10]);
+
+        // One source going away leaves the other connected
+        first.dispose();
+        second.emit(20);
+        assert_eq!(receiver.recv_timeout(timeout), Ok(20));
+    }
+
+    #[test]
+    fn test_zip_pairs_values_in_order() {
+        let sizes: Event<(u16, u16)> = Event::new();
+        let focus: Event<&'static str> = Event::new();
+        let zipped = sizes.clone().zip(focus.clone());
+        let receiver = zipped.subscribe();
+
+        sizes.emit((80, 24));
+        sizes.emit((100, 30));
+        focus.emit("editor");
+        let timeout = Duration::from_millis(500);
+        assert_eq!(receiver.recv_timeout(timeout), Ok(((80, 24), "editor")));
+        assert!(receiver.recv_timeout(Duration::from_millis(50)).is_err());
+
+        focus.emit("terminal");
+        assert_eq!(receiver.recv_timeout(timeout), Ok(((100, 30), "terminal")));
+    }
+
+    #[test]
+    fn test_dropping_combined_event_unsubscribes_both_sources() {
+        let first: Event<i32> = Event::new();
+        let second: Event<i32> = Event::new();
+        let merged = first.clone().merge(second.clone());
+        let zipped = first.clone().zip(second.clone());
+        assert_eq!(first.subscriber_count(), 2);
+        assert_eq!(second.subscriber_count(), 2);
+
+        drop(merged);
+        drop(zipped);
+        assert_eq!(first.subscriber_count(), 0);
+        assert_eq!(second.subscriber_count(), 0);
+    }
+
+    /// Sets a flag when dropped; moved into pipeline closures to observe
+    /// when their forwarding thread exits.
+    struct DropFlag(Arc<std::sync::atomic::AtomicBool>);
+
+    impl Drop for DropFlag {
+        fn drop(&mut self) {
+            self.0.store(true, std::sync::atomic::Ordering::SeqCst);
+        }
+    }
+
+    fn wait_for(flag: &std::sync::atomic::AtomicBool) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(1);
+        while Instant::now() < deadline {
+            if flag.load(std::sync::atomic::Ordering::SeqCst) {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        false
+    }
+
+    #[test]
+    fn test_dropped_subscription_unregisters() {
+        let event: Event<i32> = Event::new();
+        let sub1 = event.subscribe();
+        let _sub2 = event.subscribe();
+        assert_eq!(event.subscriber_count(), 2);
+
+        drop(sub1);
+        assert_eq!(event.subscriber_count(), 1);
+    }
+
+    #[test]
+    fn test_disposed_subscription_keeps_delivered_values() {
+        let event: Event<i32> = Event::new();
+        let sub = event.subscribe();
+
+        event.emit(1);
+        sub.dispose();
+        event.emit(2);
+
+        assert!(sub.is_disposed());
+        assert_eq!(event.subscriber_count(), 0);
+        assert_eq!(sub.try_recv(), Ok(1));
+        assert!(sub.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_dropping_derived_event_stops_forwarding_thread() {
+        let event: Event<i32> = Event::new();
+        let stopped = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let flag = DropFlag(Arc::clone(&stopped));
+
+        let mapped = event.clone().map(move |x| {
+            let _ = &flag;
+            x
+        });
+        assert_eq!(event.subscriber_count(), 1);
+
+        drop(mapped);
+        assert_eq!(event.subscriber_count(), 0);
+        assert!(wait_for(&stopped), "forwarding thread should exit");
+    }
+
+    #[test]
+    fn test_pipeline_stays_alive_while_final_event_lives() {
+        let event: Event<i32> = Event::new();
+        let pipeline = event.clone().map(|x| x * 2).filter(|x| *x > 2);
+        let receiver = pipeline.subscribe();
+
+        event.emit(1);
+        event.emit(2);
+        assert_eq!(receiver.recv_timeout(Duration::from_millis(100)), Ok(4));
+
+        drop(receiver);
+        drop(pipeline);
+        assert_eq!(event.subscriber_count(), 0);
+    }
+
+    #[test]
+    fn test_dispose_tears_down_all_clones() {
+        let event: Event<i32> = Event::new();
+        let stopped = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let flag = DropFlag(Arc::clone(&stopped));
+        let filtered = event.clone().filter(move |_| {
+            let _ = &flag;
+            true
+        });
+        let clone = filtered.clone();
+        let receiver = clone.subscribe();
+
+        filtered.dispose();
+
+        assert_eq!(event.subscriber_count(), 0);
+        assert_eq!(clone.subscriber_count(), 0);
+        assert!(wait_for(&stopped), "forwarding thread should exit");
+        assert_eq!(
+            receiver.recv_timeout(Duration::from_millis(50)),
+            Err(crossbeam::channel::RecvTimeoutError::Disconnected)
+        );
+    }
 }
@@ -0,0 +1,526 @@
+//! Syntax highlighting primitives.
+//!
+//! A [`Highlighter`] tokenizes one line at a time. Constructs that span
+//! lines, such as block comments, are carried from the end of one line to
+//! the start of the next in an opaque [`LineState`]. Line-at-a-time
+//! tokenizing lets [`HighlightCache`] keep the spans of every line and only
+//! re-tokenize lines whose text or start state changed since the last pass.
+//!
+//! [`LexicalHighlighter`] is a small built-in tokenizer for keywords,
+//! strings, comments and numbers. Parser-backed highlighters (tree-sitter,
+//! syntect) can implement the same trait.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+use std::path::Path;
+
+/// Kind of a highlighted token. Themes map each kind to a style.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    /// A reserved word, e.g. `fn`.
+    Keyword,
+    /// A type name, e.g. `String`.
+    Type,
+    /// A string literal.
+    String,
+    /// A comment.
+    Comment,
+    /// A numeric literal.
+    Number,
+}
+
+/// A highlighted byte range within one line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighlightSpan {
+    /// Byte range within the line.
+    pub range: Range<usize>,
+    /// What the range holds.
+    pub kind: TokenKind,
+}
+
+/// Tokenizer state at a line boundary, e.g. "inside a block comment".
+///
+/// The value is private to the highlighter that produced it; the default
+/// state is the start of a document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct LineState(pub u32);
+
+/// Tokenizes text into highlighted spans, one line at a time.
+pub trait Highlighter {
+    /// Highlight `line` (without its line break), starting in `state`.
+    ///
+    /// Returns the spans in order and the state at the end of the line.
+    fn highlight_line(&self, line: &str, state: LineState) -> (Vec<HighlightSpan>, LineState);
+}
+
+/// The highlighted spans of one line and what they were computed from.
+#[derive(Debug, Clone)]
+struct CachedLine {
+    /// Hash of the line's text.
+    hash: u64,
+    /// State the line was tokenized from.
+    start: LineState,
+    /// State after the line.
+    end: LineState,
+    /// Spans of the line.
+    spans: Vec<HighlightSpan>,
+}
+
+/// Highlighted spans of a document, kept between passes.
+///
+/// [`HighlightCache::highlight`] re-tokenizes a line only if its text or
+/// its start state changed, so an edit re-tokenizes the edited line and any
+/// lines whose start state it changed (e.g. after opening a block comment).
+#[derive(Debug, Default)]
+pub struct HighlightCache {
+    /// Cached lines, in document order.
+    lines: Vec<CachedLine>,
+    /// Number of lines tokenized by the last pass.
+    tokenized: usize,
+}
+
+impl HighlightCache {
+    /// Create an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bring the cache up to date with `text`.
+    ///
+    /// Lines are split on `\n`; a trailing `\r` is not part of the line.
+    pub fn highlight(&mut self, highlighter: &dyn Highlighter, text: &str) {
+        let mut state = LineState::default();
+        let mut tokenized = 0;
+        let mut count = 0;
+        for (index, raw_line) in text.split('\n').enumerate() {
+            let line = raw_line.strip_suffix('\r').unwrap_or(raw_line);
+            let hash = hash_line(line);
+            count += 1;
+            if let Some(cached) = self.lines.get(index) {
+                if cached.hash == hash && cached.start == state {
+                    state = cached.end;
+                    continue;
+                }
+            }
+            let (spans, end) = highlighter.highlight_line(line, state);
+            tokenized += 1;
+            let cached = CachedLine {
+                hash,
+                start: state,
+                end,
+                spans,
+            };
+            if index < self.lines.len() {
+                self.lines[index] = cached;
+            } else {
+                self.lines.push(cached);
+            }
+            state = end;
+        }
+        self.lines.truncate(count);
+        self.tokenized = tokenized;
+    }
+
+    /// Get the spans of a line as of the last pass; empty past the end.
+    pub fn line(&self, index: usize) -> &[HighlightSpan] {
+        self.lines
+            .get(index)
+            .map_or(&[], |line| line.spans.as_slice())
+    }
+
+    /// Number of lines the last pass tokenized rather than reused.
+    pub fn last_tokenized(&self) -> usize {
+        self.tokenized
+    }
+
+    /// Forget every line, e.g. after switching highlighters.
+    pub fn clear(&mut self) {
+        self.lines.clear();
+        self.tokenized = 0;
+    }
+}
+
+/// Hash a line's text for change detection.
+fn hash_line(line: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    line.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// State inside a block comment.
+const IN_BLOCK_COMMENT: u32 = 1;
+/// First state inside a string; `IN_STRING + i` is inside the `i`th
+/// string delimiter.
+const IN_STRING: u32 = 2;
+
+/// String delimiters of a language.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StringDelimiter {
+    /// Opening and closing delimiter, e.g. `"`.
+    pub quote: &'static str,
+    /// Whether the string may continue on the next line.
+    pub multi_line: bool,
+}
+
+/// A keyword-based tokenizer configured per language.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LexicalHighlighter {
+    /// Language identifier, e.g. `rust`.
+    pub language: &'static str,
+    /// Reserved words.
+    pub keywords: &'static [&'static str],
+    /// Whether identifiers starting with an uppercase letter are types.
+    pub capitalized_types: bool,
+    /// Line comment prefix, e.g. `//`.
+    pub line_comment: Option<&'static str>,
+    /// Block comment delimiters, e.g. `/*` and `*/`.
+    pub block_comment: Option<(&'static str, &'static str)>,
+    /// String delimiters, longest first.
+    pub strings: &'static [StringDelimiter],
+}
+
+impl LexicalHighlighter {
+    /// Rust.
+    pub fn rust() -> Self {
+        Self {
+            language: "rust",
+            keywords: &[
+                "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else",
+                "enum", "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match",
+                "mod", "move", "mut", "pub", "ref", "return", "self", "static", "struct", "super",
+                "trait", "true", "type", "unsafe", "use", "where", "while",
+            ],
+            capitalized_types: true,
+            line_comment: Some("//"),
+            block_comment: Some(("/*", "*/")),
+            strings: &[StringDelimiter {
+                quote: "\"",
+                multi_line: true,
+            }],
+        }
+    }
+
+    /// Python.
+    pub fn python() -> Self {
+        Self {
+            language: "python",
+            keywords: &[
+                "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class",
+                "continue", "def", "del", "elif", "else", "except", "finally", "for", "from",
+                "global", "if", "import", "in", "is", "lambda", "nonlocal", "not", "or", "pass",
+                "raise", "return", "try", "while", "with", "yield",
+            ],
+            capitalized_types: true,
+            line_comment: Some("#"),
+            block_comment: None,
+            strings: &[
+                StringDelimiter {
+                    quote: "\"\"\"",
+                    multi_line: true,
+                },
+                StringDelimiter {
+                    quote: "'''",
+                    multi_line: true,
+                },
+                StringDelimiter {
+                    quote: "\"",
+                    multi_line: false,
+                },
+                StringDelimiter {
+                    quote: "'",
+                    multi_line: false,
+                },
+            ],
+        }
+    }
+
+    /// TOML.
+    pub fn toml() -> Self {
+        Self {
+            language: "toml",
+            keywords: &["true", "false"],
+            capitalized_types: false,
+            line_comment: Some("#"),
+            block_comment: None,
+            strings: &[
+                StringDelimiter {
+                    quote: "\"\"\"",
+                    multi_line: true,
+                },
+                StringDelimiter {
+                    quote: "\"",
+                    multi_line: false,
+                },
+                StringDelimiter {
+                    quote: "'",
+                    multi_line: false,
+                },
+            ],
+        }
+    }
+
+    /// Look up the built-in highlighter for a language identifier.
+    pub fn for_language(language: &str) -> Option<Self> {
+        match language {
+            "rust" => Some(Self::rust()),
+            "python" => Some(Self::python()),
+            "toml" => Some(Self::toml()),
+            _ => None,
+        }
+    }
+
+    /// Guess the language identifier of a file from its extension.
+    pub fn language_for_path(path: &Path) -> Option<&'static str> {
+        match path.extension()?.to_str()? {
+            "rs" => Some("rust"),
+            "py" => Some("python"),
+            "toml" => Some("toml"),
+            _ => None,
+        }
+    }
+
+    /// Find the end of a string closed by `quote` in `rest`, skipping
+    /// backslash escapes. Returns the byte offset just past the quote.
+    fn string_end(rest: &str, quote: &str) -> Option<usize> {
+        let mut chars = rest.char_indices();
+        while let Some((index, c)) = chars.next() {
+            if c == '\\' {
+                chars.next();
+            } else if rest[index..].starts_with(quote) {
+                return Some(index + quote.len());
+            }
+        }
+        None
+    }
+
+    /// Length of the identifier or number at the start of `rest`.
+    fn word_len(rest: &str) -> usize {
+        let is_number = rest.starts_with(|c: char| c.is_ascii_digit());
+        let mut len = 0;
+        let mut chars = rest.char_indices().peekable();
+        while let Some((index, c)) = chars.next() {
+            let decimal_point = is_number
+                && c == '.'
+                && chars.peek().is_some_and(|&(_, next)| next.is_ascii_digit());
+            if !(c.is_alphanumeric() || c == '_' || decimal_point) {
+                break;
+            }
+            len = index + c.len_utf8();
+        }
+        len
+    }
+}
+
+impl Highlighter for LexicalHighlighter {
+    fn highlight_line(&self, line: &str, state: LineState) -> (Vec<HighlightSpan>, LineState) {
+        let mut spans: Vec<HighlightSpan> = Vec::new();
+        let mut push = |range: Range<usize>, kind: TokenKind| match spans.last_mut() {
+            Some(last) if last.range.end == range.start && last.kind == kind => {
+                last.range.end = range.end;
+            }
+            _ => spans.push(HighlightSpan { range, kind }),
+        };
+
+        let mut state = state.0;
+        let mut pos = 0;
+        while pos < line.len() {
+            let rest = &line[pos..];
+
+            // Continue a construct left open by an earlier line or token
+            if state == IN_BLOCK_COMMENT {
+                let close = self.block_comment.map_or("*/", |(_, close)| close);
+                match rest.find(close) {
+                    Some(index) => {
+                        push(pos..pos + index + close.len(), TokenKind::Comment);
+                        state = 0;
+                        pos += index + close.len();
+                    }
+                    None => {
+                        push(pos..line.len(), TokenKind::Comment);
+                        pos = line.len();
+                    }
+                }
+                continue;
+            }
+            if let Some(delimiter) = state
+                .checked_sub(IN_STRING)
+                .and_then(|index| self.strings.get(index as usize))
+            {
+                let end = Self::string_end(rest, delimiter.quote).map(|i| pos + i);
+                push(pos..end.unwrap_or(line.len()), TokenKind::String);
+                if let Some(end) = end {
+                    state = 0;
+                    pos = end;
+                } else {
+                    pos = line.len();
+                }
+                continue;
+            }
+
+            if self
+                .line_comment
+                .is_some_and(|prefix| rest.starts_with(prefix))
+            {
+                push(pos..line.len(), TokenKind::Comment);
+                break;
+            }
+            if let Some((open, _)) = self
+                .block_comment
+                .filter(|(open, _)| rest.starts_with(open))
+            {
+                push(pos..pos + open.len(), TokenKind::Comment);
+                state = IN_BLOCK_COMMENT;
+                pos += open.len();
+                continue;
+            }
+            if let Some(index) = self
+                .strings
+                .iter()
+                .position(|delimiter| rest.starts_with(delimiter.quote))
+            {
+                let quote = self.strings[index].quote;
+                push(pos..pos + quote.len(), TokenKind::String);
+                state = IN_STRING + index as u32;
+                pos += quote.len();
+                continue;
+            }
+
+            let c = rest.chars().next().expect("rest is not empty");
+            if c.is_alphanumeric() || c == '_' {
+                let len = Self::word_len(rest);
+                let word = &rest[..len];
+                let kind = if c.is_ascii_digit() {
+                    Some(TokenKind::Number)
+                } else if self.keywords.contains(&word) {
+                    Some(TokenKind::Keyword)
+                } else if self.capitalized_types && c.is_uppercase() {
+                    Some(TokenKind::Type)
+                } else {
+                    None
+                };
+                if let Some(kind) = kind {
+                    push(pos..pos + len, kind);
+                }
+                pos += len;
+            } else {
+                pos += c.len_utf8();
+            }
+        }
+
+        // Single-line strings end with the line even if unterminated
+        let unterminated_single_line = state
+            .checked_sub(IN_STRING)
+            .and_then(|index| self.strings.get(index as usize))
+            .is_some_and(|delimiter| !delimiter.multi_line);
+        if unterminated_single_line {
+            state = 0;
+        }
+        (spans, LineState(state))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds<'a>(highlighter: &LexicalHighlighter, line: &'a str) -> Vec<(&'a str, TokenKind)> {
+        let (spans, _) = highlighter.highlight_line(line, LineState::default());
+        spans
+            .into_iter()
+            .map(|span| (&line[span.range], span.kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_rust_tokens() {
+        let rust = LexicalHighlighter::rust();
+        assert_eq!(
+            kinds(&rust, r#"let s: String = "a\"b"; // done"#),
+            vec![
+                ("let", TokenKind::Keyword),
+                ("String", TokenKind::Type),
+                (r#""a\"b""#, TokenKind::String),
+                ("// done", TokenKind::Comment),
+            ]
+        );
+        assert_eq!(
+            kinds(&rust, "x = 1.5 + y1..2"),
+            vec![("1.5", TokenKind::Number), ("2", TokenKind::Number)]
+        );
+    }
+
+    #[test]
+    fn test_block_comment_spans_lines() {
+        let rust = LexicalHighlighter::rust();
+        let (spans, state) = rust.highlight_line("a /* open", LineState::default());
+        assert_eq!(spans[0].range, 2..9);
+        assert_ne!(state, LineState::default());
+
+        let (spans, state) = rust.highlight_line("still */ fn", state);
+        assert_eq!(
+            spans,
+            vec![
+                HighlightSpan {
+                    range: 0..8,
+                    kind: TokenKind::Comment
+                },
+                HighlightSpan {
+                    range: 9..11,
+                    kind: TokenKind::Keyword
+                },
+            ]
+        );
+        assert_eq!(state, LineState::default());
+    }
+
+    #[test]
+    fn test_single_line_string_ends_with_line() {
+        let python = LexicalHighlighter::python();
+        let (_, state) = python.highlight_line("x = 'open", LineState::default());
+        assert_eq!(state, LineState::default());
+
+        let (_, state) = python.highlight_line("doc = \"\"\"open", LineState::default());
+        let (spans, state) = python.highlight_line("still\"\"\" def", state);
+        assert_eq!(spans[0].range, 0..8);
+        assert_eq!(spans[1].kind, TokenKind::Keyword);
+        assert_eq!(state, LineState::default());
+    }
+
+    #[test]
+    fn test_cache_retokenizes_only_changed_lines() {
+        let rust = LexicalHighlighter::rust();
+        let mut cache = HighlightCache::new();
+        cache.highlight(&rust, "fn a() {}\nlet b = 1;\nlet c = 2;");
+        assert_eq!(cache.last_tokenized(), 3);
+
+        cache.highlight(&rust, "fn a() {}\nlet b = 10;\nlet c = 2;");
+        assert_eq!(cache.last_tokenized(), 1);
+        assert_eq!(cache.line(1)[1].range, 8..10);
+
+        // Opening a block comment changes the start state of later lines
+        cache.highlight(&rust, "/* fn a() {}\nlet b = 10;\nlet c = 2;");
+        assert_eq!(cache.last_tokenized(), 3);
+        assert_eq!(cache.line(2)[0].kind, TokenKind::Comment);
+
+        cache.highlight(&rust, "/* fn a() {}");
+        assert_eq!(cache.line(1), &[]);
+    }
+
+    #[test]
+    fn test_language_lookup() {
+        assert_eq!(
+            LexicalHighlighter::language_for_path(Path::new("src/main.rs")),
+            Some("rust")
+        );
+        assert_eq!(
+            LexicalHighlighter::language_for_path(Path::new("README")),
+            None
+        );
+        assert_eq!(
+            LexicalHighlighter::for_language("toml").unwrap().language,
+            "toml"
+        );
+        assert!(LexicalHighlighter::for_language("cobol").is_none());
+    }
+}
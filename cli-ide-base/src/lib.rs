@@ -2,9 +2,22 @@
 //!
 //! This crate provides reusable types such as the event system used across the
 //! IDE. The event system is inspired by VS Code's event abstractions and
-//! includes basic transformations like `map`, `filter`, and `debounce`.
+//! includes basic transformations like `map`, `filter`, and `debounce`, plus a
+//! `Disposable` handle for releasing registrations. `Emitter` is a
+//! synchronous alternative whose listeners run inline, without threads.
+//! The `highlight` module defines the `Highlighter` trait behind editor
+//! syntax highlighting. With the `test-support` feature, `test_support`
+//! provides the temp fixtures the other crates' tests share.
 
+pub mod disposable;
+pub mod emitter;
 pub mod event;
+pub mod highlight;
+#[cfg(any(test, feature = "test-support"))]
+#[doc(hidden)]
+pub mod test_support;
 
-// Re-export Event for convenience
-pub use event::Event;
+// Re-export common types for convenience
+pub use disposable::Disposable;
+pub use emitter::Emitter;
+pub use event::{Event, Subscription};
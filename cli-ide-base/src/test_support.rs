@@ -0,0 +1,52 @@
+//! Fixtures shared by the tests of the workspace crates.
+//!
+//! Compiled for this crate's tests and, through the `test-support` feature,
+//! for the tests of crates enabling it in their dev-dependencies. Not part
+//! of the public API.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A path in the system temp directory for the fixture `name`, unique to
+/// the test process.
+pub fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("paradiddle-{}-{name}", std::process::id()))
+}
+
+/// An empty directory at [`temp_path`]`(name)`, replacing whatever a
+/// previous run left there.
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = temp_path(name);
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("temp directory should be creatable");
+    dir
+}
+
+/// Removes fixture files and directories when dropped, so a test leaves
+/// nothing behind even when one of its assertions fails.
+pub struct Cleanup(Vec<PathBuf>);
+
+impl Cleanup {
+    /// Remove `paths` when the returned guard is dropped. Paths that no
+    /// longer exist by then are skipped.
+    pub fn new<P: AsRef<Path>>(paths: impl IntoIterator<Item = P>) -> Self {
+        Self(
+            paths
+                .into_iter()
+                .map(|path| path.as_ref().to_path_buf())
+                .collect(),
+        )
+    }
+}
+
+impl Drop for Cleanup {
+    fn drop(&mut self) {
+        for path in &self.0 {
+            let _ = if path.is_dir() {
+                fs::remove_dir_all(path)
+            } else {
+                fs::remove_file(path)
+            };
+        }
+    }
+}
@@ -2,18 +2,58 @@
 //!
 //! This program sets up a terminal using `crossterm` and runs an interactive
 //! event loop using `ratatui`. Press `q` or `Esc` to quit, `Tab` to switch focus.
+//! Click a pane to focus it and use the scroll wheel to scroll the focused pane.
+//! Pass a file path to open it in the editor. The terminal's window title
+//! shows the focused file and the workspace, and is restored on exit.
+//! Pass `--safe-mode` to start without the user's settings and
+//! saved session, e.g. to recover from a broken configuration. Pass
+//! `--record <file>` to record the session's input events to a file, and
+//! `--replay <file>` to feed a recording back in as it was timed. Pass
+//! `--folder <dir>`, as often as needed, to add further root folders to the
+//! workspace, `--max-fps <n>` to cap the frame rate and `--tick-ms <n>` to
+//! set how often the event loop ticks.
 
+use std::ffi::OsString;
 use std::io::{self, Stdout};
-use std::time::Duration;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
 
-use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::event::{
+    self, DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, EnableBracketedPaste,
+    EnableFocusChange, EnableMouseCapture,
+};
 use crossterm::execute;
-use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::style::Print;
+use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen, SetTitle};
 use ratatui::backend::CrosstermBackend;
 use ratatui::Terminal;
 
+use cli_ide_example_plugin::GreeterPlugin;
+use cli_ide_platform::clipboard::clipboard_service::ClipboardService;
+use cli_ide_platform::config::config_service::ConfigService;
+use cli_ide_platform::environment::environment_service::EnvironmentService;
+use cli_ide_platform::logging::log_service::LogService;
+use cli_ide_platform::logging::tracing_layer;
+use cli_ide_platform::opener::opener_service::OpenerService;
+use cli_ide_platform::session::session_service::SessionService;
+use cli_ide_platform::tick::tick_scheduler::DEFAULT_TICK_INTERVAL;
 use cli_ide_workbench::app::App;
-use cli_ide_workbench::input::{AppEvent, AppKey};
+use cli_ide_workbench::frame_rate::{FrameRate, DEFAULT_MAX_FPS};
+use cli_ide_workbench::input::AppEvent;
+use cli_ide_workbench::layout_preset::LayoutPreset;
+use cli_ide_workbench::plugin::Plugin;
+use cli_ide_workbench::recording::{EventRecorder, Replay};
+use cli_ide_workbench::window::StatusBarUpdate;
+
+/// Save the window title on the terminal's title stack (XTWINOPS 22).
+///
+/// Terminals cannot be asked for their title portably, so the original is
+/// pushed on startup and popped on exit; terminals without a title stack
+/// ignore both.
+const PUSH_TITLE: &str = "\x1b[22;0t";
+
+/// Restore the window title saved with [`PUSH_TITLE`] (XTWINOPS 23).
+const POP_TITLE: &str = "\x1b[23;0t";
 
 /// RAII guard for terminal cleanup.
 ///
@@ -28,7 +68,14 @@ impl TerminalGuard {
     fn new() -> io::Result<Self> {
         terminal::enable_raw_mode()?;
         let mut stdout = io::stdout();
-        execute!(stdout, EnterAlternateScreen)?;
+        execute!(
+            stdout,
+            Print(PUSH_TITLE),
+            EnterAlternateScreen,
+            EnableMouseCapture,
+            EnableBracketedPaste,
+            EnableFocusChange
+        )?;
         let backend = CrosstermBackend::new(stdout);
         let terminal = Terminal::new(backend)?;
         Ok(Self { terminal })
@@ -44,77 +91,359 @@ impl Drop for TerminalGuard {
     fn drop(&mut self) {
         // Best effort cleanup - ignore errors during drop
         let _ = terminal::disable_raw_mode();
-        let _ = execute!(self.terminal.backend_mut(), LeaveAlternateScreen);
+        let _ = execute!(
+            self.terminal.backend_mut(),
+            LeaveAlternateScreen,
+            DisableMouseCapture,
+            DisableBracketedPaste,
+            DisableFocusChange,
+            Print(POP_TITLE)
+        );
         let _ = self.terminal.show_cursor();
     }
 }
 
-/// Convert a crossterm key event to our internal AppKey.
-fn translate_key(code: KeyCode) -> AppKey {
-    match code {
-        KeyCode::Char('q') | KeyCode::Char('Q') => AppKey::Q,
-        KeyCode::Esc => AppKey::Esc,
-        KeyCode::Tab => AppKey::Tab,
-        KeyCode::Enter => AppKey::Enter,
-        KeyCode::Backspace => AppKey::Backspace,
-        KeyCode::Up => AppKey::Up,
-        KeyCode::Down => AppKey::Down,
-        KeyCode::Left => AppKey::Left,
-        KeyCode::Right => AppKey::Right,
-        KeyCode::Char(c) => AppKey::Char(c),
-        _ => AppKey::Other,
+/// How often the configuration file is checked for changes.
+const CONFIG_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How often the repository's `HEAD` and index are checked for changes.
+const GIT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How often the files the TODO list shows are checked for changes.
+const TODO_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The plugins compiled into the demo.
+fn compiled_in_plugins() -> Vec<Box<dyn Plugin>> {
+    vec![Box::new(GreeterPlugin)]
+}
+
+/// Load the compiled-in plugins into the app.
+///
+/// Contributions that cannot be applied are reported in the status bar;
+/// the rest of the plugin is still loaded.
+fn load_plugins(app: &mut App) {
+    let problems = app.load_plugins(compiled_in_plugins());
+    if !problems.is_empty() {
+        app.status_bar_updates().emit(StatusBarUpdate::SetSegment {
+            id: "plugins".to_string(),
+            text: format!("Plugins: {}", problems.join("; ")),
+        });
     }
 }
 
+/// Load the user settings, the keybindings file and the workspace settings
+/// into the app, later files winning, and reload them when a file changes.
+///
+/// A configuration that cannot be read is reported in the status bar rather
+/// than aborting startup.
+fn load_config(app: &mut App) {
+    let workspace = ConfigService::workspace_path(app.workspace_root());
+    let paths = [
+        ConfigService::default_path(),
+        ConfigService::default_keybindings_path(),
+        Some(workspace),
+    ];
+    match ConfigService::load_layered(paths.into_iter().flatten()) {
+        Ok(service) => {
+            app.set_config_service(service);
+            app.watch_config(CONFIG_POLL_INTERVAL);
+        }
+        Err(error) => app.status_bar_updates().emit(StatusBarUpdate::SetSegment {
+            id: "config".to_string(),
+            text: format!("Config: {error}"),
+        }),
+    }
+}
+
+/// Load the user's snippets, which the snippet manager saves back to the
+/// same file.
+///
+/// Snippets that cannot be read are reported in the status bar.
+fn load_snippets(app: &mut App) {
+    let Some(path) = ConfigService::default_snippets_path() else {
+        return;
+    };
+    if let Err(error) = app.load_snippets(path) {
+        app.status_bar_updates().emit(StatusBarUpdate::SetSegment {
+            id: "snippets".to_string(),
+            text: format!("Snippets: {error}"),
+        });
+    }
+}
+
+/// Arrange the windows as the workspace's `.paradiddle.toml` declares in
+/// its `[layout]` section, if it has one.
+///
+/// A preset that cannot be read or fully applied is reported in the status
+/// bar; whatever could be applied is kept.
+fn apply_workspace_layout(app: &mut App) {
+    let path = ConfigService::workspace_path(app.workspace_root());
+    if !path.exists() {
+        return;
+    }
+    let problems = match ConfigService::load(&path)
+        .map_err(|error| error.to_string())
+        .and_then(|service| LayoutPreset::from_config(&service.config()))
+    {
+        Ok(Some(preset)) => app.apply_layout_preset(&preset),
+        Ok(None) => Vec::new(),
+        Err(error) => vec![error],
+    };
+    if !problems.is_empty() {
+        app.status_bar_updates().emit(StatusBarUpdate::SetSegment {
+            id: "layout".to_string(),
+            text: format!("Layout: {}", problems.join("; ")),
+        });
+    }
+}
+
+/// Send `tracing` events to the app's log service and append them to the
+/// default log file.
+///
+/// A log file that cannot be opened is reported in the status bar; the
+/// records are still shown in the log viewer.
+fn start_logging(app: &App) {
+    let logs = app.logs();
+    if let Some(path) = LogService::default_path() {
+        if let Err(error) = logs.set_file(&path) {
+            app.status_bar_updates().emit(StatusBarUpdate::SetSegment {
+                id: "log".to_string(),
+                text: format!("Log: cannot open {}: {error}", path.display()),
+            });
+        }
+    }
+    // Only fails if a subscriber is already installed, which main never does
+    let _ = tracing_layer::install(logs);
+}
+
+/// Command-line arguments.
+struct Args {
+    /// Start without the user's settings and saved session.
+    safe_mode: bool,
+    /// File to record the input events to.
+    record: Option<PathBuf>,
+    /// Recording to replay.
+    replay: Option<PathBuf>,
+    /// Further root folders of the workspace.
+    folders: Vec<PathBuf>,
+    /// Most frames drawn a second.
+    max_fps: u32,
+    /// Time between two ticks of the event loop.
+    tick_interval: Duration,
+    /// File to open in the editor.
+    path: Option<OsString>,
+}
+
+impl Args {
+    /// Parse the program's arguments: `--safe-mode`, `--record <file>`,
+    /// `--replay <file>`, `--folder <dir>`, `--max-fps <n>`, `--tick-ms <n>`
+    /// and a file path.
+    fn parse() -> Result<Self, String> {
+        let mut args = Self {
+            safe_mode: false,
+            record: None,
+            replay: None,
+            folders: Vec::new(),
+            max_fps: DEFAULT_MAX_FPS,
+            tick_interval: DEFAULT_TICK_INTERVAL,
+            path: None,
+        };
+        let mut iter = std::env::args_os().skip(1);
+        while let Some(arg) = iter.next() {
+            if arg == "--safe-mode" {
+                args.safe_mode = true;
+            } else if arg == "--record" || arg == "--replay" {
+                let file = iter
+                    .next()
+                    .ok_or_else(|| format!("{} expects a file", arg.to_string_lossy()))?;
+                if arg == "--record" {
+                    args.record = Some(file.into());
+                } else {
+                    args.replay = Some(file.into());
+                }
+            } else if arg == "--folder" {
+                let dir = iter.next().ok_or("--folder expects a directory")?;
+                args.folders.push(dir.into());
+            } else if arg == "--max-fps" {
+                args.max_fps = iter
+                    .next()
+                    .and_then(|fps| fps.to_str()?.parse().ok())
+                    .filter(|&fps| fps > 0)
+                    .ok_or("--max-fps expects a positive number")?;
+            } else if arg == "--tick-ms" {
+                args.tick_interval = iter
+                    .next()
+                    .and_then(|ms| ms.to_str()?.parse().ok())
+                    .filter(|&ms| ms > 0)
+                    .map(Duration::from_millis)
+                    .ok_or("--tick-ms expects a positive number")?;
+            } else if args.path.is_none() {
+                args.path = Some(arg);
+            }
+        }
+        Ok(args)
+    }
+}
+
+/// Create the app from the session saved for the current directory, and
+/// save the session there when the app quits.
+///
+/// A session that cannot be read is reported in the status bar and the
+/// app starts fresh.
+fn restore_session() -> App {
+    let root = std::env::current_dir().unwrap_or_else(|_| ".".into());
+    let Some(path) = SessionService::default_path(&root) else {
+        return App::new();
+    };
+    let sessions = SessionService::new(path);
+    let (mut app, error) = match sessions.load() {
+        Ok(Some(session)) if session.root == root => (App::from_session(&session), None),
+        Ok(_) => (App::new(), None),
+        Err(error) => (App::new(), Some(error)),
+    };
+    if let Some(error) = error {
+        app.status_bar_updates().emit(StatusBarUpdate::SetSegment {
+            id: "session".to_string(),
+            text: format!("Session: {error}"),
+        });
+    }
+    app.set_session_service(sessions);
+    app
+}
+
 /// Run the main application loop.
-fn run_app(guard: &mut TerminalGuard, app: &mut App) -> io::Result<()> {
+///
+/// The screen is drawn only when the app needs a redraw, at most as often
+/// as `frame_rate` allows, and the app ticks as often as its
+/// `TickScheduler` says, whether or not input arrives. Input events are
+/// written to `recorder`, if given, and the events of `replay` are fed to
+/// the app as they fall due, alongside live input. A recording that
+/// cannot be written is reported in the status bar and stopped.
+fn run_app(
+    guard: &mut TerminalGuard,
+    app: &mut App,
+    mut frame_rate: FrameRate,
+    mut recorder: Option<EventRecorder>,
+    mut replay: Option<Replay>,
+) -> io::Result<()> {
     let terminal = guard.terminal();
+    let ticks = app.ticks();
+    let mut title = String::new();
+    let replay_started = Instant::now();
+    let mut record = |app: &mut App, event: AppEvent| {
+        if let Some(error) = recorder.as_mut().and_then(|r| r.record(event).err()) {
+            recorder = None;
+            app.status_bar_updates().emit(StatusBarUpdate::SetSegment {
+                id: "recording".to_string(),
+                text: format!("Recording: {error}"),
+            });
+        }
+    };
+    // Replays start from the size the recording started with
+    let size = terminal.size()?;
+    record(app, AppEvent::Resize(size.width, size.height));
 
     loop {
-        // Render the current state
-        terminal.draw(|frame| {
-            let area = frame.area();
-            app.handle_event(AppEvent::Resize(area.width, area.height));
-            app.render(frame, area);
-        })?;
+        // Render the current state, unless it is already on screen
+        if app.needs_redraw() && frame_rate.is_due(Instant::now()) {
+            terminal.draw(|frame| {
+                let area = frame.area();
+                app.handle_event(AppEvent::Resize(area.width, area.height));
+                app.render(frame, area);
+            })?;
+            frame_rate.frame_drawn(Instant::now());
+        }
+
+        // Set the window title (OSC 0) when the focused file changes
+        let new_title = app.window_title();
+        if new_title != title {
+            execute!(terminal.backend_mut(), SetTitle(&new_title))?;
+            title = new_title;
+        }
 
         // Check if we should quit
         if !app.is_running() {
             break;
         }
 
-        // Poll for events with a timeout
-        if event::poll(Duration::from_millis(100))? {
-            match event::read()? {
-                Event::Key(key_event) => {
-                    // Only handle key press events (not release)
-                    if key_event.kind == KeyEventKind::Press {
-                        let app_key = translate_key(key_event.code);
-                        app.handle_event(AppEvent::Key(app_key));
-                    }
-                }
-                Event::Resize(width, height) => {
-                    app.handle_event(AppEvent::Resize(width, height));
-                }
-                _ => {
-                    // Ignore mouse events and other event types for now
-                }
+        // Feed the replayed events that are due
+        if let Some(replay) = replay.as_mut() {
+            while let Some(event) = replay.next_due(replay_started.elapsed()) {
+                app.handle_event(event);
+            }
+        }
+
+        // Wait for input until the next frame or tick is due
+        let now = Instant::now();
+        let timeout = frame_rate.wait(app.needs_redraw(), ticks.time_until_tick(now), now);
+        if event::poll(timeout)? {
+            // Key releases and plain mouse moves become `AppEvent::Other`
+            let event = AppEvent::from(event::read()?);
+            if event != AppEvent::Other {
+                record(app, event.clone());
+                app.handle_event(event);
             }
         }
+
+        // Tick when due, even during a stream of input, so that timers
+        // such as the expiry of notification toasts and the periodic
+        // callbacks run
+        if ticks.take_tick(Instant::now()) {
+            app.handle_event(AppEvent::Tick);
+        }
     }
 
     Ok(())
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Read the arguments and the recording before taking over the terminal,
+    // so their errors print normally
+    let args = Args::parse()?;
+    let replay = args.replay.as_ref().map(Replay::load).transpose()?;
+    let recorder = args
+        .record
+        .as_ref()
+        .map(EventRecorder::create)
+        .transpose()?;
+
     // Set up terminal with RAII guard for cleanup
     let mut guard = TerminalGuard::new()?;
 
-    // Create the application
-    let mut app = App::new();
+    // Create the application; safe mode skips everything the user
+    // configured or installed
+    let mut app = if args.safe_mode {
+        App::new()
+    } else {
+        restore_session()
+    };
+    // Added before the settings load so each folder's settings are read
+    for folder in args.folders {
+        app.add_workspace_folder(folder);
+    }
+    start_logging(&app);
+    load_plugins(&mut app);
+    if args.safe_mode {
+        app.set_safe_mode(true);
+    } else {
+        load_config(&mut app);
+        load_snippets(&mut app);
+        apply_workspace_layout(&mut app);
+    }
+    app.set_clipboard_service(ClipboardService::system());
+    app.set_opener_service(OpenerService::system());
+    app.set_environment_service(EnvironmentService::from_env());
+    app.refresh_branch();
+    app.watch_git(GIT_POLL_INTERVAL);
+    app.watch_todos(TODO_POLL_INTERVAL);
+    if let Some(path) = args.path {
+        app.open_file(path)?;
+    }
 
     // Run the event loop
-    run_app(&mut guard, &mut app)?;
+    app.ticks().set_interval(args.tick_interval);
+    let frame_rate = FrameRate::new(args.max_fps);
+    run_app(&mut guard, &mut app, frame_rate, recorder, replay)?;
 
     // Guard's Drop impl handles terminal restoration
     Ok(())
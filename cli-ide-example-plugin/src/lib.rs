@@ -0,0 +1,154 @@
+//! An example plugin for the Paradiddle.rs workbench.
+//!
+//! [`GreeterPlugin`] shows what a third-party crate can contribute through
+//! [`Plugin::activate`]:
+//!
+//! - the command `example.sayHello` ("Example: Say Hello"), which posts a
+//!   notification through the `NotificationService` it resolves;
+//! - a `Greeting` window, shown and hidden by `example.toggleGreeting`
+//!   ("Example: Toggle Greeting"), listing how many commands are
+//!   registered;
+//! - the global keybinding `H` for `example.sayHello`.
+//!
+//! The demo compiles it in and loads it at startup.
+
+use cli_ide_platform::command::command_service::CommandService;
+use cli_ide_platform::di::service_container::ServiceContainer;
+use cli_ide_platform::notification::notification_service::{
+    NotificationService, NotificationSeverity,
+};
+use cli_ide_workbench::input::AppKey;
+use cli_ide_workbench::plugin::{Plugin, WorkbenchRegistry};
+use cli_ide_workbench::theme::Theme;
+use cli_ide_workbench::window::{pane_borders, Window};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Paragraph};
+
+/// ID of the command posting the greeting.
+pub const SAY_HELLO_COMMAND: &str = "example.sayHello";
+
+/// ID of the command showing and hiding the greeting window.
+pub const TOGGLE_GREETING_COMMAND: &str = "example.toggleGreeting";
+
+/// Source the greeting notifications are posted under.
+pub const NOTIFICATION_SOURCE: &str = "Greeter";
+
+/// Contributes a greeting command, window and keybinding.
+#[derive(Debug, Default)]
+pub struct GreeterPlugin;
+
+impl Plugin for GreeterPlugin {
+    fn id(&self) -> &str {
+        "example.greeter"
+    }
+
+    fn activate(&mut self, services: &ServiceContainer, registry: &mut WorkbenchRegistry) {
+        if let Some(notifications) = services.resolve::<NotificationService>() {
+            registry.register_command(SAY_HELLO_COMMAND, "Example: Say Hello", move || {
+                notifications.notify(
+                    NOTIFICATION_SOURCE,
+                    NotificationSeverity::Info,
+                    "Hello from the example plugin!",
+                );
+            });
+            registry.register_keybinding(AppKey::Char('H'), SAY_HELLO_COMMAND);
+        }
+        registry.register_window(
+            TOGGLE_GREETING_COMMAND,
+            "Example: Toggle Greeting",
+            |services| {
+                let commands = services
+                    .resolve::<CommandService>()
+                    .map_or(0, |commands| commands.commands().len());
+                Box::new(GreetingWindow::new(commands))
+            },
+        );
+    }
+}
+
+/// A read-only pane greeting the user.
+pub struct GreetingWindow {
+    /// Number of commands registered when the window was opened.
+    commands: usize,
+    /// Whether to draw with only a top border.
+    compact: bool,
+    /// Styles to draw with.
+    theme: Theme,
+}
+
+impl GreetingWindow {
+    /// Create a greeting mentioning `commands` registered commands.
+    pub fn new(commands: usize) -> Self {
+        Self {
+            commands,
+            compact: false,
+            theme: Theme::default(),
+        }
+    }
+
+    /// The lines the window shows.
+    pub fn lines(&self) -> Vec<String> {
+        vec![
+            "Hello from the example plugin!".to_string(),
+            String::new(),
+            format!("{} commands are registered.", self.commands),
+            format!("Press H or run `{SAY_HELLO_COMMAND}` for a notification."),
+        ]
+    }
+}
+
+impl Window for GreetingWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.render_with_focus(frame, area, false);
+    }
+
+    fn render_with_focus(&mut self, frame: &mut Frame, area: Rect, focused: bool) {
+        let border_type = if focused {
+            BorderType::Thick
+        } else {
+            BorderType::Plain
+        };
+        let title = if focused { "Greeting [*]" } else { "Greeting" };
+        let block = Block::default()
+            .title(title)
+            .borders(pane_borders(self.compact))
+            .border_type(border_type)
+            .border_style(self.theme.border_style(focused));
+        let lines: Vec<Line> = self.lines().into_iter().map(Line::from).collect();
+        let paragraph = Paragraph::new(lines).style(self.theme.text).block(block);
+        frame.render_widget(paragraph, area);
+    }
+
+    fn set_compact(&mut self, compact: bool) {
+        self.compact = compact;
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_activate_contributes_a_command_window_and_keybinding() {
+        let services = ServiceContainer::new();
+        services.register(NotificationService::new());
+        let mut registry = WorkbenchRegistry::new();
+        GreeterPlugin.activate(&services, &mut registry);
+
+        let ids: Vec<&str> = registry.commands().iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, [SAY_HELLO_COMMAND]);
+        (registry.commands()[0].handler)();
+        let notifications = services.resolve::<NotificationService>().unwrap();
+        let posted = notifications.notifications();
+        assert_eq!(posted.len(), 1);
+        assert_eq!(posted[0].source, NOTIFICATION_SOURCE);
+
+        assert_eq!(registry.windows()[0].id, TOGGLE_GREETING_COMMAND);
+        assert_eq!(registry.keybindings()[0].key, AppKey::Char('H'));
+        assert_eq!(registry.keybindings()[0].command, SAY_HELLO_COMMAND);
+    }
+}
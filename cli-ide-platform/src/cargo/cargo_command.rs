@@ -0,0 +1,55 @@
+//! The cargo commands the workbench runs.
+//!
+//! A [`CargoCommand`] turns into a [`ProcessSpec`] for the
+//! [`ProcessService`](crate::process::process_service::ProcessService). Its
+//! diagnostics are read with a
+//! [`RustcMatcher`](crate::problems::rustc_matcher::RustcMatcher).
+
+use std::path::Path;
+
+use crate::process::process_service::ProcessSpec;
+
+/// A cargo subcommand run on the whole workspace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CargoCommand {
+    /// `cargo build`
+    Build,
+    /// `cargo test`
+    Test,
+    /// `cargo run`
+    Run,
+    /// `cargo clippy`
+    Clippy,
+}
+
+impl CargoCommand {
+    /// The cargo subcommand, e.g. `build`.
+    pub fn subcommand(self) -> &'static str {
+        match self {
+            CargoCommand::Build => "build",
+            CargoCommand::Test => "test",
+            CargoCommand::Run => "run",
+            CargoCommand::Clippy => "clippy",
+        }
+    }
+
+    /// The command to run in `root`.
+    ///
+    /// Colors are turned off, since the output is shown and matched as
+    /// plain text.
+    pub fn spec(self, root: &Path) -> ProcessSpec {
+        ProcessSpec::new("cargo", [self.subcommand(), "--color=never"], root)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spec_runs_cargo_without_colors_in_root() {
+        let spec = CargoCommand::Clippy.spec(Path::new("/work"));
+        assert_eq!(spec.command_line(), "cargo clippy --color=never");
+        assert_eq!(spec.cwd, Path::new("/work"));
+    }
+}
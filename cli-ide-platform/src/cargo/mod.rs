@@ -0,0 +1,3 @@
+//! Cargo integration.
+
+pub mod cargo_command;
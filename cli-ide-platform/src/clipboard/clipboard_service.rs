@@ -0,0 +1,279 @@
+//! Copying and pasting text through the system clipboard.
+//!
+//! The `ClipboardService` keeps the text last copied in the IDE and, when it
+//! has a [`ClipboardProvider`] for the system clipboard, copies to and pastes
+//! from that too. [`ClipboardService::system`] looks for the clipboard tools
+//! of the host with [`CommandClipboard::detect`]: `pbcopy`/`pbpaste` on
+//! macOS, `wl-copy`/`wl-paste` under Wayland, `xclip` or `xsel` under X11
+//! and `clip`/PowerShell on Windows. Without one, e.g. in headless tests or
+//! over SSH, and whenever the system clipboard fails, the service falls back
+//! to its in-memory copy, so copy and paste keep working within the IDE.
+
+use std::env;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+use std::sync::{Mutex, MutexGuard};
+
+/// Access to a clipboard shared with other applications.
+pub trait ClipboardProvider: Send + Sync {
+    /// Name of the clipboard, e.g. `xclip`, for reporting.
+    fn name(&self) -> &str;
+
+    /// Read the clipboard's text.
+    fn read(&self) -> io::Result<String>;
+
+    /// Replace the clipboard's text.
+    fn write(&self, text: &str) -> io::Result<()>;
+}
+
+/// A system clipboard reached through command line tools: one command
+/// prints the clipboard, another replaces it with its standard input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandClipboard {
+    /// Name of the clipboard, for reporting.
+    name: String,
+    /// Program and arguments replacing the clipboard with their input.
+    copy: Vec<String>,
+    /// Program and arguments printing the clipboard.
+    paste: Vec<String>,
+}
+
+impl CommandClipboard {
+    /// Create a clipboard copying with the `copy` command line and pasting
+    /// with the `paste` one; both start with the program.
+    pub fn new(name: impl Into<String>, copy: &[&str], paste: &[&str]) -> Self {
+        Self {
+            name: name.into(),
+            copy: copy.iter().map(ToString::to_string).collect(),
+            paste: paste.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    /// Find the clipboard tools of the host, if any are installed.
+    ///
+    /// Wayland is preferred over X11 when both displays are set.
+    pub fn detect() -> Option<Self> {
+        let candidates = if cfg!(target_os = "macos") {
+            vec![Self::new("pbcopy", &["pbcopy"], &["pbpaste"])]
+        } else if cfg!(windows) {
+            vec![Self::new(
+                "clip",
+                &["clip"],
+                &["powershell", "-NoProfile", "-Command", "Get-Clipboard"],
+            )]
+        } else {
+            let mut candidates = Vec::new();
+            if env::var_os("WAYLAND_DISPLAY").is_some() {
+                candidates.push(Self::new("wl-clipboard", &["wl-copy"], &["wl-paste", "-n"]));
+            }
+            if env::var_os("DISPLAY").is_some() {
+                candidates.push(Self::new(
+                    "xclip",
+                    &["xclip", "-selection", "clipboard"],
+                    &["xclip", "-selection", "clipboard", "-o"],
+                ));
+                candidates.push(Self::new(
+                    "xsel",
+                    &["xsel", "--clipboard", "--input"],
+                    &["xsel", "--clipboard", "--output"],
+                ));
+            }
+            candidates
+        };
+        candidates
+            .into_iter()
+            .find(|clipboard| on_path(&clipboard.copy[0]) && on_path(&clipboard.paste[0]))
+    }
+
+    /// A command running `command_line`.
+    fn command(command_line: &[String]) -> Command {
+        let mut command = Command::new(&command_line[0]);
+        command.args(&command_line[1..]);
+        command
+    }
+}
+
+impl ClipboardProvider for CommandClipboard {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn read(&self) -> io::Result<String> {
+        let output = Self::command(&self.paste)
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()?;
+        if !output.status.success() {
+            return Err(io::Error::other(format!(
+                "{} exited with {}",
+                self.paste[0], output.status
+            )));
+        }
+        String::from_utf8(output.stdout)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    fn write(&self, text: &str) -> io::Result<()> {
+        let mut child = Self::command(&self.copy)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        let status = child.wait()?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(io::Error::other(format!(
+                "{} exited with {status}",
+                self.copy[0]
+            )))
+        }
+    }
+}
+
+/// Check whether `program` is an executable file in a `PATH` directory.
+pub(crate) fn on_path(program: &str) -> bool {
+    let Some(path) = env::var_os("PATH") else {
+        return false;
+    };
+    env::split_paths(&path).any(|dir| {
+        let candidate = dir.join(program);
+        candidate.is_file() || (cfg!(windows) && candidate.with_extension("exe").is_file())
+    })
+}
+
+/// Copies and pastes text, through the system clipboard when there is one.
+#[derive(Default)]
+pub struct ClipboardService {
+    /// The system clipboard, if one was found.
+    system: Option<Box<dyn ClipboardProvider>>,
+    /// The text last copied in the IDE.
+    text: Mutex<String>,
+}
+
+impl ClipboardService {
+    /// Create a service that only keeps copied text in memory.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a service that copies to and pastes from `provider`.
+    pub fn with_provider(provider: Box<dyn ClipboardProvider>) -> Self {
+        Self {
+            system: Some(provider),
+            text: Mutex::default(),
+        }
+    }
+
+    /// Create a service using the host's clipboard tools, or keeping copied
+    /// text in memory if there are none.
+    pub fn system() -> Self {
+        match CommandClipboard::detect() {
+            Some(clipboard) => Self::with_provider(Box::new(clipboard)),
+            None => Self::new(),
+        }
+    }
+
+    /// Get the name of the system clipboard, or `None` if copied text is
+    /// only kept in memory.
+    pub fn provider_name(&self) -> Option<&str> {
+        self.system.as_deref().map(ClipboardProvider::name)
+    }
+
+    /// Lock the copied text, ignoring poisoning: it is a plain string.
+    fn locked(&self) -> MutexGuard<'_, String> {
+        self.text.lock().unwrap_or_else(|error| error.into_inner())
+    }
+
+    /// Copy `text`.
+    ///
+    /// The text is always kept in memory; the error reports that the system
+    /// clipboard could not be written, so only the IDE can paste it.
+    pub fn copy(&self, text: impl Into<String>) -> io::Result<()> {
+        let text = text.into();
+        let written = self
+            .system
+            .as_ref()
+            .map_or(Ok(()), |system| system.write(&text));
+        *self.locked() = text;
+        written
+    }
+
+    /// Get the text to paste: the system clipboard's, or the text last
+    /// copied in the IDE if there is no system clipboard or it cannot be
+    /// read.
+    pub fn paste(&self) -> String {
+        match self.system.as_ref().map(|system| system.read()) {
+            Some(Ok(text)) => text,
+            Some(Err(error)) => {
+                tracing::warn!("cannot read the system clipboard: {error}");
+                self.locked().clone()
+            }
+            None => self.locked().clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A system clipboard that either shares its text or fails.
+    struct FakeClipboard {
+        text: Mutex<Option<String>>,
+    }
+
+    impl ClipboardProvider for FakeClipboard {
+        fn name(&self) -> &str {
+            "fake"
+        }
+
+        fn read(&self) -> io::Result<String> {
+            self.text
+                .lock()
+                .unwrap()
+                .clone()
+                .ok_or_else(|| io::Error::other("no display"))
+        }
+
+        fn write(&self, text: &str) -> io::Result<()> {
+            let mut shared = self.text.lock().unwrap();
+            match shared.as_mut() {
+                Some(shared) => {
+                    *shared = text.to_string();
+                    Ok(())
+                }
+                None => Err(io::Error::other("no display")),
+            }
+        }
+    }
+
+    #[test]
+    fn test_copied_text_is_pasted_from_memory_without_a_system_clipboard() {
+        let service = ClipboardService::new();
+        assert_eq!(service.provider_name(), None);
+        assert_eq!(service.paste(), "");
+        service.copy("fn main() {}").unwrap();
+        assert_eq!(service.paste(), "fn main() {}");
+    }
+
+    #[test]
+    fn test_system_clipboard_is_used_and_falls_back_to_memory() {
+        let service = ClipboardService::with_provider(Box::new(FakeClipboard {
+            text: Mutex::new(Some("from another app".to_string())),
+        }));
+        assert_eq!(service.provider_name(), Some("fake"));
+        assert_eq!(service.paste(), "from another app");
+        service.copy("copied").unwrap();
+        assert_eq!(service.paste(), "copied");
+
+        let failing = ClipboardService::with_provider(Box::new(FakeClipboard {
+            text: Mutex::new(None),
+        }));
+        assert!(failing.copy("kept").is_err());
+        assert_eq!(failing.paste(), "kept");
+    }
+}
@@ -0,0 +1,3 @@
+//! Copying and pasting text through the system clipboard.
+
+pub mod clipboard_service;
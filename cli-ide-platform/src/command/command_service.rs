@@ -0,0 +1,219 @@
+//! Named command registry.
+//!
+//! The `CommandService` lets components register commands under a stable
+//! identifier (e.g. `workbench.action.quit`) together with a human-readable
+//! title and a handler. Other components — keybindings, the command palette,
+//! menus — execute commands by identifier without knowing who provides them.
+//! This mirrors VS Code's `CommandsRegistry`.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+/// A command handler.
+///
+/// Handlers must be `Send + Sync` so the service can be shared through the
+/// `ServiceContainer`.
+pub type CommandHandler = Arc<dyn Fn() + Send + Sync>;
+
+/// Metadata describing a registered command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandInfo {
+    /// Stable identifier used to execute the command.
+    pub id: String,
+    /// Human-readable title shown in the command palette.
+    pub title: String,
+}
+
+/// Errors returned when executing a command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandError {
+    /// No command is registered under the given identifier.
+    NotFound(String),
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::NotFound(id) => write!(f, "command not found: {}", id),
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+/// A registered command.
+struct Command {
+    title: String,
+    handler: CommandHandler,
+}
+
+/// Registry of named commands.
+#[derive(Default)]
+pub struct CommandService {
+    commands: RwLock<BTreeMap<String, Command>>,
+}
+
+impl CommandService {
+    /// Create a new, empty command registry.
+    pub fn new() -> Self {
+        Self {
+            commands: RwLock::new(BTreeMap::new()),
+        }
+    }
+
+    /// Register a command.
+    ///
+    /// If a command with the same identifier exists it is replaced.
+    pub fn register<F>(&self, id: impl Into<String>, title: impl Into<String>, handler: F)
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        let mut commands = self.commands.write().expect("command lock poisoned");
+        commands.insert(
+            id.into(),
+            Command {
+                title: title.into(),
+                handler: Arc::new(handler),
+            },
+        );
+    }
+
+    /// Unregister a command.
+    ///
+    /// Returns `true` if a command was removed.
+    pub fn unregister(&self, id: &str) -> bool {
+        let mut commands = self.commands.write().expect("command lock poisoned");
+        commands.remove(id).is_some()
+    }
+
+    /// Check whether a command is registered.
+    pub fn contains(&self, id: &str) -> bool {
+        let commands = self.commands.read().expect("command lock poisoned");
+        commands.contains_key(id)
+    }
+
+    /// Execute a command by identifier.
+    ///
+    /// The handler runs after the registry lock is released, so handlers may
+    /// themselves register or execute commands.
+    pub fn execute(&self, id: &str) -> Result<(), CommandError> {
+        let handler = {
+            let commands = self.commands.read().expect("command lock poisoned");
+            commands
+                .get(id)
+                .map(|command| Arc::clone(&command.handler))
+                .ok_or_else(|| CommandError::NotFound(id.to_string()))?
+        };
+        handler();
+        Ok(())
+    }
+
+    /// List all registered commands, ordered by identifier.
+    pub fn commands(&self) -> Vec<CommandInfo> {
+        let commands = self.commands.read().expect("command lock poisoned");
+        commands
+            .iter()
+            .map(|(id, command)| CommandInfo {
+                id: id.clone(),
+                title: command.title.clone(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_register_and_execute() {
+        let service = CommandService::new();
+        let count = Arc::new(AtomicUsize::new(0));
+        let count_clone = Arc::clone(&count);
+
+        service.register("test.increment", "Increment", move || {
+            count_clone.fetch_add(1, Ordering::SeqCst);
+        });
+
+        service.execute("test.increment").unwrap();
+        service.execute("test.increment").unwrap();
+
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_execute_unknown_command() {
+        let service = CommandService::new();
+
+        let result = service.execute("missing");
+        assert_eq!(result, Err(CommandError::NotFound("missing".to_string())));
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "command not found: missing"
+        );
+    }
+
+    #[test]
+    fn test_commands_listed_in_id_order() {
+        let service = CommandService::new();
+        service.register("b.second", "Second", || {});
+        service.register("a.first", "First", || {});
+
+        let commands = service.commands();
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[0].id, "a.first");
+        assert_eq!(commands[0].title, "First");
+        assert_eq!(commands[1].id, "b.second");
+    }
+
+    #[test]
+    fn test_register_replaces_existing() {
+        let service = CommandService::new();
+        service.register("cmd", "Old", || {});
+        service.register("cmd", "New", || {});
+
+        let commands = service.commands();
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].title, "New");
+    }
+
+    #[test]
+    fn test_unregister() {
+        let service = CommandService::new();
+        service.register("cmd", "Command", || {});
+
+        assert!(service.contains("cmd"));
+        assert!(service.unregister("cmd"));
+        assert!(!service.contains("cmd"));
+        assert!(!service.unregister("cmd"));
+    }
+
+    #[test]
+    fn test_handler_can_reenter_service() {
+        let service = Arc::new(CommandService::new());
+        let inner = Arc::clone(&service);
+        service.register("outer", "Outer", move || {
+            inner.register("registered.by.outer", "Inner", || {});
+        });
+
+        service.execute("outer").unwrap();
+        assert!(service.contains("registered.by.outer"));
+    }
+
+    #[test]
+    fn test_resolve_from_container() {
+        use crate::di::service_container::ServiceContainer;
+
+        let container = ServiceContainer::new();
+        container.register(CommandService::new());
+
+        let service = container.resolve::<CommandService>().unwrap();
+        service.register("cmd", "Command", || {});
+        assert!(container
+            .resolve::<CommandService>()
+            .unwrap()
+            .contains("cmd"));
+    }
+}
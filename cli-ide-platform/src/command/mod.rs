@@ -0,0 +1,3 @@
+//! Command registry.
+
+pub mod command_service;
@@ -0,0 +1,1475 @@
+//! User configuration service.
+//!
+//! The `ConfigService` loads the user's settings file at startup (by default
+//! `~/.config/paradiddle/config.toml`), exposes the settings through typed
+//! getters and broadcasts a [`ConfigChanged`] event whenever the settings are
+//! reloaded, so components can react without polling the file themselves.
+//!
+//! The file format is a subset of TOML: `[section]` headers, `key = value`
+//! pairs and `#` comments. Values are booleans, integers or strings, either
+//! basic (`"..."`, with escapes) or literal (`'...'`, handy for regexes).
+//! Keys are bare (`line_length`) or quoted (`"?"`), which lets keybinding
+//! tables use any key name:
+//!
+//! ```toml
+//! [editor]
+//! wrap = false
+//! indent = 2
+//!
+//! [keybindings]
+//! "?" = "showCommands"
+//!
+//! [keybindings.editor]
+//! e = "expandBracketSelection"
+//! x = "deleteCharacter when mode == 'insert'"
+//! ```
+//!
+//! Other TOML syntax (arrays, inline tables, arrays of tables, multi-line
+//! strings, dotted keys and floats) is rejected with an error naming it
+//! rather than skipped.
+//!
+//! A settings file ending in `.json` is read as JSON instead: nested
+//! objects are sections and subsections, and the values are the same
+//! booleans, integers and strings, so the example above is
+//!
+//! ```json
+//! {
+//!   "editor": { "wrap": false, "indent": 2 },
+//!   "keybindings": {
+//!     "?": "showCommands",
+//!     "editor": { "e": "expandBracketSelection" }
+//!   }
+//! }
+//! ```
+//!
+//! A setting is addressed by its section and key joined with a dot, e.g.
+//! `editor.indent`.
+//!
+//! Settings can be layered from several files with
+//! [`ConfigService::load_layered`], e.g. the user settings, the keybindings
+//! file and the workspace settings, each file replacing the settings of the
+//! ones before it.
+//!
+//! A workspace with several root folders can add each folder's
+//! `.paradiddle.toml` with [`ConfigService::add_folder`]. Those settings
+//! only apply to the folder's files: [`ConfigService::config_for`] lays the
+//! settings of the folder holding a file over the others.
+
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use cli_ide_base::Event;
+use serde::de::{self, Deserialize, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+
+/// Section holding keybindings. `[keybindings.<context>]` sections hold
+/// bindings that only apply in one context.
+pub const KEYBINDINGS_SECTION: &str = "keybindings";
+
+/// Section holding abbreviations. `[abbreviations.<language>]` sections
+/// hold abbreviations that only expand in files of one language.
+pub const ABBREVIATIONS_SECTION: &str = "abbreviations";
+
+/// Section holding problem matchers, one `[problem_matchers.<name>]`
+/// subsection per matcher.
+pub const PROBLEM_MATCHERS_SECTION: &str = "problem_matchers";
+
+/// Section holding tasks, one `[tasks.<name>]` subsection per task.
+pub const TASKS_SECTION: &str = "tasks";
+
+/// Name of the keybindings file, next to the user settings file.
+pub const KEYBINDINGS_FILE: &str = "keybindings.toml";
+
+/// Name of the user snippets file, next to the user settings file. It
+/// holds `[abbreviations]` sections only.
+pub const SNIPPETS_FILE: &str = "snippets.toml";
+
+/// Name of the user settings file written as JSON, used when there is no
+/// TOML one.
+pub const JSON_CONFIG_FILE: &str = "config.json";
+
+/// Name of the workspace settings file, at the workspace root.
+pub const WORKSPACE_CONFIG_FILE: &str = ".paradiddle.toml";
+
+/// A setting value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigValue {
+    /// `true` or `false`.
+    Bool(bool),
+    /// A signed integer.
+    Integer(i64),
+    /// A double-quoted string.
+    String(String),
+}
+
+impl ConfigValue {
+    /// Name of the value's type, as used in error messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            ConfigValue::Bool(_) => "a boolean",
+            ConfigValue::Integer(_) => "an integer",
+            ConfigValue::String(_) => "a string",
+        }
+    }
+}
+
+impl fmt::Display for ConfigValue {
+    /// Format the value as it would be written in a `.paradiddle` file:
+    /// strings are not quoted.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigValue::Bool(value) => write!(f, "{value}"),
+            ConfigValue::Integer(value) => write!(f, "{value}"),
+            ConfigValue::String(value) => f.write_str(value),
+        }
+    }
+}
+
+/// Errors produced while loading or reading the configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    /// The configuration file could not be read.
+    Io {
+        /// Path of the file.
+        path: PathBuf,
+        /// Description of the I/O error.
+        message: String,
+    },
+    /// The configuration file is not valid.
+    Parse {
+        /// Zero-based line the error was found on.
+        line: usize,
+        /// Description of the problem.
+        message: String,
+    },
+    /// A setting has a value of the wrong type.
+    Type {
+        /// The setting, e.g. `editor.wrap`.
+        key: String,
+        /// The expected type, e.g. `a boolean`.
+        expected: &'static str,
+        /// The type found in the file.
+        found: &'static str,
+    },
+    /// A file of a layered configuration is not valid.
+    InFile {
+        /// Path of the file.
+        path: PathBuf,
+        /// What is wrong with it.
+        error: Box<ConfigError>,
+    },
+    /// A required setting is missing.
+    Missing {
+        /// The setting, e.g. `problem_matchers.eslint.regex`.
+        key: String,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io { path, message } => {
+                write!(f, "cannot read {}: {}", path.display(), message)
+            }
+            ConfigError::Parse { line, message } => write!(f, "line {}: {}", line + 1, message),
+            ConfigError::Type {
+                key,
+                expected,
+                found,
+            } => write!(f, "`{key}` expects {expected}, got {found}"),
+            ConfigError::InFile { path, error } => write!(f, "{}: {error}", path.display()),
+            ConfigError::Missing { key } => write!(f, "`{key}` is required"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// A keybinding from a `[keybindings]` section.
+///
+/// Key and action names are left uninterpreted; the workbench resolves them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeybindingConfig {
+    /// Context the binding applies in, or `None` for a global binding.
+    pub context: Option<String>,
+    /// Key name, e.g. `e` or `esc`.
+    pub key: String,
+    /// Action name, e.g. `expandBracketSelection`.
+    pub action: String,
+    /// Condition the binding applies under, e.g. `mode == 'insert'`, from
+    /// a `when` after the action name.
+    pub when: Option<String>,
+}
+
+/// An abbreviation from an `[abbreviations]` section.
+///
+/// The language is left uninterpreted; the editor matches it against the
+/// language of the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AbbreviationConfig {
+    /// Language the abbreviation expands in, or `None` for every file.
+    pub language: Option<String>,
+    /// Word that expands, e.g. `teh`.
+    pub word: String,
+    /// Text the word expands to, e.g. `the`.
+    pub expansion: String,
+}
+
+/// A problem matcher from a `[problem_matchers.<name>]` section.
+///
+/// The regex and severity are left uninterpreted; the problem matchers
+/// compile them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProblemMatcherConfig {
+    /// Name of the matcher, e.g. `eslint`.
+    pub name: String,
+    /// Regex matching one line of output.
+    pub regex: String,
+    /// Severity of problems whose line has no severity, if set.
+    pub severity: Option<String>,
+}
+
+/// A task from a `[tasks.<name>]` section.
+///
+/// The output is left uninterpreted; the task runner parses it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskConfig {
+    /// Name of the task, e.g. `lint`.
+    pub name: String,
+    /// Shell command to run, e.g. `cargo clippy --color=never`.
+    pub command: String,
+    /// Where the output is shown, if set.
+    pub output: Option<String>,
+}
+
+/// A setting and where it was defined.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry {
+    section: String,
+    key: String,
+    value: ConfigValue,
+}
+
+/// Parsed settings, in file order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Config {
+    entries: Vec<Entry>,
+}
+
+impl Config {
+    /// Create an empty configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse the contents of a configuration file.
+    pub fn parse(contents: &str) -> Result<Self, ConfigError> {
+        let mut config = Self::new();
+        let mut section = String::new();
+        for (line, raw) in contents.lines().enumerate() {
+            let error = |message: String| ConfigError::Parse { line, message };
+            let text = strip_comment(raw).trim();
+            if text.is_empty() {
+                continue;
+            }
+            if text.starts_with("[[") {
+                return Err(error("arrays of tables are not supported".to_string()));
+            }
+            if let Some(header) = text.strip_prefix('[') {
+                let name = header
+                    .strip_suffix(']')
+                    .ok_or_else(|| error("unterminated section header".to_string()))?
+                    .trim();
+                if name.is_empty() || !name.split('.').all(is_bare_key) {
+                    return Err(error(format!("invalid section name `{name}`")));
+                }
+                section = name.to_string();
+                continue;
+            }
+            let (key, rest) = parse_key(text).map_err(error)?;
+            let value = rest
+                .trim_start()
+                .strip_prefix('=')
+                .ok_or_else(|| error(format!("expected `=` after `{key}`")))?;
+            let value = parse_value(value.trim()).map_err(error)?;
+            if config.entry(&section, &key).is_some() {
+                return Err(error(format!("duplicate setting `{key}`")));
+            }
+            config.entries.push(Entry {
+                section: section.clone(),
+                key,
+                value,
+            });
+        }
+        Ok(config)
+    }
+
+    /// Parse the contents of a JSON configuration file.
+    ///
+    /// The contents must be an object. Members holding objects are sections
+    /// (and, nested, subsections) and the others are settings, so
+    /// `{"editor": {"wrap": true}}` sets `editor.wrap`.
+    pub fn parse_json(contents: &str) -> Result<Self, ConfigError> {
+        let root: JsonNode = serde_json::from_str(contents).map_err(|error| {
+            let message = error.to_string();
+            let message = match message.rsplit_once(" at line ") {
+                Some((message, _)) => message.to_string(),
+                None => message,
+            };
+            ConfigError::Parse {
+                line: error.line().saturating_sub(1),
+                message,
+            }
+        })?;
+        let JsonNode::Object(members) = root else {
+            return Err(ConfigError::Parse {
+                line: 0,
+                message: "expected an object".to_string(),
+            });
+        };
+        let mut config = Self::new();
+        config.add_json_section("", members)?;
+        Ok(config)
+    }
+
+    /// Add the `members` of the JSON object for `section`, recursing into
+    /// subsections.
+    fn add_json_section(
+        &mut self,
+        section: &str,
+        members: Vec<(String, JsonNode)>,
+    ) -> Result<(), ConfigError> {
+        for (key, node) in members {
+            let name = if section.is_empty() {
+                key.clone()
+            } else {
+                format!("{section}.{key}")
+            };
+            match node {
+                JsonNode::Value(value) => {
+                    if self.entry(section, &key).is_some() {
+                        return Err(ConfigError::Parse {
+                            line: 0,
+                            message: format!("duplicate setting `{name}`"),
+                        });
+                    }
+                    self.entries.push(Entry {
+                        section: section.to_string(),
+                        key,
+                        value,
+                    });
+                }
+                JsonNode::Object(members) => {
+                    if !is_bare_key(&key) {
+                        return Err(ConfigError::Parse {
+                            line: 0,
+                            message: format!("invalid section name `{name}`"),
+                        });
+                    }
+                    self.add_json_section(&name, members)?;
+                }
+                JsonNode::Unsupported(found) => {
+                    return Err(ConfigError::Type {
+                        key: name,
+                        expected: "a boolean, an integer, a string or an object",
+                        found,
+                    })
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Get a setting by its dotted name, e.g. `editor.wrap`.
+    ///
+    /// Top-level settings (before any section header) have no dot.
+    pub fn get(&self, name: &str) -> Option<&ConfigValue> {
+        let (section, key) = name.rsplit_once('.').unwrap_or(("", name));
+        self.entry(section, key)
+    }
+
+    /// Get a boolean setting.
+    pub fn get_bool(&self, name: &str) -> Result<Option<bool>, ConfigError> {
+        match self.get(name) {
+            None => Ok(None),
+            Some(ConfigValue::Bool(value)) => Ok(Some(*value)),
+            Some(other) => Err(type_error(name, "a boolean", other)),
+        }
+    }
+
+    /// Get an integer setting.
+    pub fn get_integer(&self, name: &str) -> Result<Option<i64>, ConfigError> {
+        match self.get(name) {
+            None => Ok(None),
+            Some(ConfigValue::Integer(value)) => Ok(Some(*value)),
+            Some(other) => Err(type_error(name, "an integer", other)),
+        }
+    }
+
+    /// Get a string setting.
+    pub fn get_string(&self, name: &str) -> Result<Option<&str>, ConfigError> {
+        match self.get(name) {
+            None => Ok(None),
+            Some(ConfigValue::String(value)) => Ok(Some(value)),
+            Some(other) => Err(type_error(name, "a string", other)),
+        }
+    }
+
+    /// Get the settings of one section as `(key, value)` pairs, in file order.
+    ///
+    /// Subsections are not included.
+    pub fn section<'a>(
+        &'a self,
+        section: &'a str,
+    ) -> impl Iterator<Item = (&'a str, &'a ConfigValue)> + 'a {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.section == section)
+            .map(|entry| (entry.key.as_str(), &entry.value))
+    }
+
+    /// Get the keybindings from the `[keybindings]` section and its
+    /// `[keybindings.<context>]` subsections, in file order.
+    ///
+    /// Every binding must map to a string action name, optionally followed
+    /// by `when` and a condition, e.g. `"quit when !editorFocused"`.
+    pub fn keybindings(&self) -> Result<Vec<KeybindingConfig>, ConfigError> {
+        let mut bindings = Vec::new();
+        for entry in &self.entries {
+            let context = if entry.section == KEYBINDINGS_SECTION {
+                None
+            } else if let Some(context) = entry
+                .section
+                .strip_prefix(KEYBINDINGS_SECTION)
+                .and_then(|rest| rest.strip_prefix('.'))
+            {
+                Some(context.to_string())
+            } else {
+                continue;
+            };
+            let ConfigValue::String(action) = &entry.value else {
+                let name = format!("{}.{}", entry.section, entry.key);
+                return Err(type_error(&name, "a string", &entry.value));
+            };
+            let (action, when) = match action.split_once(" when ") {
+                Some((action, when)) => (action.trim(), Some(when.trim().to_string())),
+                None => (action.trim(), None),
+            };
+            bindings.push(KeybindingConfig {
+                context,
+                key: entry.key.clone(),
+                action: action.to_string(),
+                when,
+            });
+        }
+        Ok(bindings)
+    }
+
+    /// Get the problem matchers from the `[problem_matchers.<name>]`
+    /// sections, in file order.
+    ///
+    /// Every matcher must have a string `regex`; `severity` is an optional
+    /// string.
+    pub fn problem_matchers(&self) -> Result<Vec<ProblemMatcherConfig>, ConfigError> {
+        self.subsections(PROBLEM_MATCHERS_SECTION)
+            .into_iter()
+            .map(|name| {
+                let setting = |key: &str| format!("{PROBLEM_MATCHERS_SECTION}.{name}.{key}");
+                let regex =
+                    self.get_string(&setting("regex"))?
+                        .ok_or_else(|| ConfigError::Missing {
+                            key: setting("regex"),
+                        })?;
+                let severity = self.get_string(&setting("severity"))?;
+                Ok(ProblemMatcherConfig {
+                    name: name.to_string(),
+                    regex: regex.to_string(),
+                    severity: severity.map(str::to_string),
+                })
+            })
+            .collect()
+    }
+    /// Get the abbreviations from the `[abbreviations]` section and its
+    /// `[abbreviations.<language>]` subsections, in file order.
+    ///
+    /// Every abbreviation must map to a string expansion.
+    pub fn abbreviations(&self) -> Result<Vec<AbbreviationConfig>, ConfigError> {
+        let mut abbreviations = Vec::new();
+        for entry in &self.entries {
+            let language = if entry.section == ABBREVIATIONS_SECTION {
+                None
+            } else if let Some(language) = entry
+                .section
+                .strip_prefix(ABBREVIATIONS_SECTION)
+                .and_then(|rest| rest.strip_prefix('.'))
+            {
+                Some(language.to_string())
+            } else {
+                continue;
+            };
+            let ConfigValue::String(expansion) = &entry.value else {
+                let name = format!("{}.{}", entry.section, entry.key);
+                return Err(type_error(&name, "a string", &entry.value));
+            };
+            abbreviations.push(AbbreviationConfig {
+                language,
+                word: entry.key.clone(),
+                expansion: expansion.clone(),
+            });
+        }
+        Ok(abbreviations)
+    }
+
+    /// Get the tasks from the `[tasks.<name>]` sections, in file order.
+    ///
+    /// Every task must have a string `command`; `output` is an optional
+    /// string.
+    pub fn tasks(&self) -> Result<Vec<TaskConfig>, ConfigError> {
+        self.subsections(TASKS_SECTION)
+            .into_iter()
+            .map(|name| {
+                let setting = |key: &str| format!("{TASKS_SECTION}.{name}.{key}");
+                let command =
+                    self.get_string(&setting("command"))?
+                        .ok_or_else(|| ConfigError::Missing {
+                            key: setting("command"),
+                        })?;
+                let output = self.get_string(&setting("output"))?;
+                Ok(TaskConfig {
+                    name: name.to_string(),
+                    command: command.to_string(),
+                    output: output.map(str::to_string),
+                })
+            })
+            .collect()
+    }
+
+    /// Names of the `[<section>.<name>]` subsections, in file order.
+    pub fn subsections(&self, section: &str) -> Vec<&str> {
+        let mut names: Vec<&str> = Vec::new();
+        for entry in &self.entries {
+            let Some(name) = entry
+                .section
+                .strip_prefix(section)
+                .and_then(|rest| rest.strip_prefix('.'))
+            else {
+                continue;
+            };
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        names
+    }
+
+    /// Lay `other` over these settings: its settings replace those with the
+    /// same section and key and the rest are added after them.
+    pub fn merge(&mut self, other: Config) {
+        for entry in other.entries {
+            match self
+                .entries
+                .iter_mut()
+                .find(|own| own.section == entry.section && own.key == entry.key)
+            {
+                Some(own) => own.value = entry.value,
+                None => self.entries.push(entry),
+            }
+        }
+    }
+
+    /// Check whether the configuration has no settings.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn entry(&self, section: &str, key: &str) -> Option<&ConfigValue> {
+        self.entries
+            .iter()
+            .find(|entry| entry.section == section && entry.key == key)
+            .map(|entry| &entry.value)
+    }
+}
+
+/// Emitted by [`ConfigService`] after the settings change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigChanged {
+    /// The new settings.
+    pub config: Config,
+}
+
+/// The settings of a workspace folder.
+#[derive(Debug, Clone)]
+struct FolderLayer {
+    /// The folder.
+    root: PathBuf,
+    /// Settings read from the folder's settings file.
+    config: Config,
+}
+
+/// Holds the user's settings and notifies listeners when they change.
+///
+/// The settings can be layered from several files, e.g. the user settings,
+/// the keybindings file and the workspace settings, later files winning.
+/// Settings of workspace folders apply only to the files in them.
+#[derive(Default)]
+pub struct ConfigService {
+    /// Files the settings are loaded from, lowest precedence first.
+    paths: Vec<PathBuf>,
+    /// Current settings.
+    config: RwLock<Config>,
+    /// Settings of the workspace folders, in the order they were added.
+    folders: RwLock<Vec<FolderLayer>>,
+    /// Fired after the settings are replaced.
+    on_did_change: Event<ConfigChanged>,
+}
+
+impl ConfigService {
+    /// Create a service with the given settings and no backing file.
+    pub fn new(config: Config) -> Self {
+        Self {
+            paths: Vec::new(),
+            config: RwLock::new(config),
+            folders: RwLock::new(Vec::new()),
+            on_did_change: Event::new(),
+        }
+    }
+
+    /// Load settings from `path`.
+    ///
+    /// A missing file is not an error: the service starts empty and picks
+    /// the file up on the next [`reload`](ConfigService::reload).
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, ConfigError> {
+        Self::load_layered([path.into()])
+    }
+
+    /// Load settings from `paths`, each file's settings replacing those of
+    /// the files before it.
+    ///
+    /// Missing files are skipped, as with [`load`](ConfigService::load).
+    /// Errors name the file they were found in when there are several.
+    pub fn load_layered(paths: impl IntoIterator<Item = PathBuf>) -> Result<Self, ConfigError> {
+        let paths: Vec<PathBuf> = paths.into_iter().collect();
+        let config = read_layers(&paths)?;
+        Ok(Self {
+            paths,
+            ..Self::new(config)
+        })
+    }
+
+    /// The default settings file: `$XDG_CONFIG_HOME/paradiddle/config.toml`,
+    /// falling back to `$HOME/.config/paradiddle/config.toml`. If only a
+    /// `config.json` exists there, it is used instead.
+    ///
+    /// Returns `None` if neither variable is set.
+    pub fn default_path() -> Option<PathBuf> {
+        let base = env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        let toml = base.join("paradiddle").join("config.toml");
+        let json = toml.with_file_name(JSON_CONFIG_FILE);
+        Some(if !toml.exists() && json.exists() {
+            json
+        } else {
+            toml
+        })
+    }
+
+    /// The default keybindings file, `keybindings.toml` next to the
+    /// [default settings file](ConfigService::default_path).
+    pub fn default_keybindings_path() -> Option<PathBuf> {
+        Self::default_path().map(|path| path.with_file_name(KEYBINDINGS_FILE))
+    }
+
+    /// The default user snippets file, `snippets.toml` next to the
+    /// [default settings file](ConfigService::default_path).
+    pub fn default_snippets_path() -> Option<PathBuf> {
+        Self::default_path().map(|path| path.with_file_name(SNIPPETS_FILE))
+    }
+
+    /// The workspace settings file of the workspace at `root`:
+    /// `<root>/.paradiddle.toml`, shared by everyone working in it.
+    pub fn workspace_path(root: &Path) -> PathBuf {
+        root.join(WORKSPACE_CONFIG_FILE)
+    }
+
+    /// Get the first file the settings are loaded from, if any.
+    pub fn path(&self) -> Option<&Path> {
+        self.paths.first().map(PathBuf::as_path)
+    }
+
+    /// Get the files the settings are loaded from, lowest precedence first.
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// Get a snapshot of the current settings.
+    pub fn config(&self) -> Config {
+        self.config.read().expect("config lock poisoned").clone()
+    }
+
+    /// Get a boolean setting.
+    pub fn get_bool(&self, name: &str) -> Result<Option<bool>, ConfigError> {
+        self.config
+            .read()
+            .expect("config lock poisoned")
+            .get_bool(name)
+    }
+
+    /// Get an integer setting.
+    pub fn get_integer(&self, name: &str) -> Result<Option<i64>, ConfigError> {
+        self.config
+            .read()
+            .expect("config lock poisoned")
+            .get_integer(name)
+    }
+
+    /// Get a string setting.
+    pub fn get_string(&self, name: &str) -> Result<Option<String>, ConfigError> {
+        let config = self.config.read().expect("config lock poisoned");
+        Ok(config.get_string(name)?.map(str::to_string))
+    }
+
+    /// Add the settings of the workspace folder at `root`, read from its
+    /// [workspace settings file](ConfigService::workspace_path), and notify
+    /// listeners.
+    ///
+    /// Adding a folder again re-reads its settings. A missing file is not
+    /// an error; on error the folder is not added.
+    pub fn add_folder(&self, root: impl Into<PathBuf>) -> Result<(), ConfigError> {
+        let root = root.into();
+        let config = read_folder(&root)?;
+        {
+            let mut folders = self.folders.write().expect("config lock poisoned");
+            match folders.iter_mut().find(|folder| folder.root == root) {
+                Some(folder) => folder.config = config,
+                None => folders.push(FolderLayer { root, config }),
+            }
+        }
+        self.update(self.config());
+        Ok(())
+    }
+
+    /// Drop the settings of the workspace folder at `root` and notify
+    /// listeners.
+    ///
+    /// Returns whether the folder had been added.
+    pub fn remove_folder(&self, root: &Path) -> bool {
+        let removed = {
+            let mut folders = self.folders.write().expect("config lock poisoned");
+            let count = folders.len();
+            folders.retain(|folder| folder.root != root);
+            folders.len() != count
+        };
+        if removed {
+            self.update(self.config());
+        }
+        removed
+    }
+
+    /// Get the workspace folders with settings, in the order they were
+    /// added.
+    pub fn folders(&self) -> Vec<PathBuf> {
+        let folders = self.folders.read().expect("config lock poisoned");
+        folders.iter().map(|folder| folder.root.clone()).collect()
+    }
+
+    /// Get the settings files of the workspace folders.
+    pub fn folder_paths(&self) -> Vec<PathBuf> {
+        self.folders()
+            .iter()
+            .map(|root| Self::workspace_path(root))
+            .collect()
+    }
+
+    /// Get the settings of the workspace folder holding `path`, if any.
+    ///
+    /// When folders are nested, the innermost one holding `path` wins.
+    pub fn folder_config(&self, path: &Path) -> Option<Config> {
+        let folders = self.folders.read().expect("config lock poisoned");
+        folders
+            .iter()
+            .filter(|folder| path.starts_with(&folder.root))
+            .max_by_key(|folder| folder.root.components().count())
+            .map(|folder| folder.config.clone())
+    }
+
+    /// Get the settings that apply to the file at `path`: the settings of
+    /// its workspace folder laid over the others.
+    pub fn config_for(&self, path: &Path) -> Config {
+        let mut config = self.config();
+        if let Some(folder) = self.folder_config(path) {
+            config.merge(folder);
+        }
+        config
+    }
+
+    /// Re-read the settings files, those of the workspace folders
+    /// included, and notify listeners.
+    ///
+    /// On error the previous settings are kept and no event is fired. Does
+    /// nothing for a service without a backing file or folder.
+    pub fn reload(&self) -> Result<(), ConfigError> {
+        let roots = self.folders();
+        if self.paths.is_empty() && roots.is_empty() {
+            return Ok(());
+        }
+        let config = if self.paths.is_empty() {
+            self.config()
+        } else {
+            read_layers(&self.paths)?
+        };
+        let folders = roots
+            .into_iter()
+            .map(|root| read_folder(&root).map(|config| FolderLayer { root, config }))
+            .collect::<Result<Vec<_>, _>>()?;
+        *self.folders.write().expect("config lock poisoned") = folders;
+        self.update(config);
+        Ok(())
+    }
+
+    /// Replace the settings and notify listeners.
+    pub fn update(&self, config: Config) {
+        *self.config.write().expect("config lock poisoned") = config.clone();
+        self.on_did_change.emit(ConfigChanged { config });
+    }
+
+    /// Event fired after the settings change.
+    pub fn on_did_change(&self) -> &Event<ConfigChanged> {
+        &self.on_did_change
+    }
+}
+
+/// Read and parse a settings file, treating a missing file as empty.
+fn read_config(path: &Path) -> Result<Config, ConfigError> {
+    match fs::read_to_string(path) {
+        Ok(contents)
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "json") =>
+        {
+            Config::parse_json(&contents)
+        }
+        Ok(contents) => Config::parse(&contents),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Config::new()),
+        Err(error) => Err(ConfigError::Io {
+            path: path.to_path_buf(),
+            message: error.to_string(),
+        }),
+    }
+}
+
+/// Read the settings file of the workspace folder at `root`, naming the
+/// file in errors.
+fn read_folder(root: &Path) -> Result<Config, ConfigError> {
+    let path = ConfigService::workspace_path(root);
+    read_config(&path).map_err(|error| match error {
+        ConfigError::Io { .. } => error,
+        _ => ConfigError::InFile {
+            path,
+            error: Box::new(error),
+        },
+    })
+}
+
+/// Read and merge the settings files `paths`, later files winning.
+///
+/// Errors name their file when there are several.
+fn read_layers(paths: &[PathBuf]) -> Result<Config, ConfigError> {
+    let mut config = Config::new();
+    for path in paths {
+        let layer = read_config(path).map_err(|error| match error {
+            ConfigError::Io { .. } => error,
+            _ if paths.len() == 1 => error,
+            _ => ConfigError::InFile {
+                path: path.clone(),
+                error: Box::new(error),
+            },
+        })?;
+        config.merge(layer);
+    }
+    Ok(config)
+}
+
+fn type_error(name: &str, expected: &'static str, found: &ConfigValue) -> ConfigError {
+    ConfigError::Type {
+        key: name.to_string(),
+        expected,
+        found: found.type_name(),
+    }
+}
+
+/// Whether `key` is a non-empty TOML bare key.
+fn is_bare_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Remove a trailing `#` comment, ignoring `#` inside basic and literal
+/// strings.
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut escaped = false;
+    for (index, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quote == Some('"') => escaped = true,
+            '"' | '\'' if quote.is_none() => quote = Some(c),
+            _ if quote == Some(c) => quote = None,
+            '#' if quote.is_none() => return &line[..index],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// Parse a bare or quoted key at the start of `text`, returning the key and
+/// the remaining text.
+fn parse_key(text: &str) -> Result<(String, &str), String> {
+    if text.starts_with('"') {
+        return parse_string(text);
+    }
+    let end = text
+        .find(|c: char| c.is_whitespace() || c == '=')
+        .unwrap_or(text.len());
+    let key = &text[..end];
+    if key.contains('.') && key.split('.').all(is_bare_key) {
+        return Err(format!(
+            "dotted keys are not supported; put `{key}` in a section"
+        ));
+    }
+    if !is_bare_key(key) {
+        return Err(format!("invalid key `{key}`"));
+    }
+    Ok((key.to_string(), &text[end..]))
+}
+
+/// Parse a value that makes up the rest of a line.
+fn parse_value(text: &str) -> Result<ConfigValue, String> {
+    match text {
+        "" => return Err("missing value".to_string()),
+        "true" => return Ok(ConfigValue::Bool(true)),
+        "false" => return Ok(ConfigValue::Bool(false)),
+        _ => {}
+    }
+    if text.starts_with("\"\"\"") || text.starts_with("'''") {
+        return Err("multi-line strings are not supported".to_string());
+    }
+    if text.starts_with('[') {
+        return Err("arrays are not supported".to_string());
+    }
+    if text.starts_with('{') {
+        return Err("inline tables are not supported".to_string());
+    }
+    if let Some(literal) = text.strip_prefix('\'') {
+        let (value, rest) = literal
+            .split_once('\'')
+            .ok_or_else(|| "unterminated string".to_string())?;
+        if !rest.trim().is_empty() {
+            return Err(format!("unexpected `{}` after string", rest.trim()));
+        }
+        return Ok(ConfigValue::String(value.to_string()));
+    }
+    if text.starts_with('"') {
+        let (value, rest) = parse_string(text)?;
+        if !rest.trim().is_empty() {
+            return Err(format!("unexpected `{}` after string", rest.trim()));
+        }
+        return Ok(ConfigValue::String(value));
+    }
+    let number = text.replace('_', "");
+    if let Ok(integer) = number.parse() {
+        return Ok(ConfigValue::Integer(integer));
+    }
+    if number.parse::<f64>().is_ok() && number.contains(|c: char| c.is_ascii_digit()) {
+        return Err(format!("floats are not supported: `{text}`"));
+    }
+    Err(format!("invalid value `{text}`"))
+}
+
+/// Parse a double-quoted string at the start of `text`, returning its
+/// contents and the remaining text.
+fn parse_string(text: &str) -> Result<(String, &str), String> {
+    let mut value = String::new();
+    let mut chars = text.char_indices().skip(1);
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '"' => return Ok((value, &text[index + 1..])),
+            '\\' => {
+                let escaped = match chars.next().map(|(_, c)| c) {
+                    Some('"') => '"',
+                    Some('\\') => '\\',
+                    Some('n') => '\n',
+                    Some('t') => '\t',
+                    Some(other) => return Err(format!("unknown escape `\\{other}`")),
+                    None => break,
+                };
+                value.push(escaped);
+            }
+            _ => value.push(c),
+        }
+    }
+    Err("unterminated string".to_string())
+}
+
+/// A value of a JSON settings file, object members kept in file order.
+enum JsonNode {
+    /// A boolean, integer or string.
+    Value(ConfigValue),
+    /// An object's members.
+    Object(Vec<(String, JsonNode)>),
+    /// A value settings cannot hold, named as in errors.
+    Unsupported(&'static str),
+}
+
+impl<'de> Deserialize<'de> for JsonNode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(JsonNodeVisitor)
+    }
+}
+
+struct JsonNodeVisitor;
+
+impl<'de> Visitor<'de> for JsonNodeVisitor {
+    type Value = JsonNode;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a JSON value")
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<JsonNode, E> {
+        Ok(JsonNode::Value(ConfigValue::Bool(value)))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<JsonNode, E> {
+        Ok(JsonNode::Value(ConfigValue::Integer(value)))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<JsonNode, E> {
+        Ok(match i64::try_from(value) {
+            Ok(value) => JsonNode::Value(ConfigValue::Integer(value)),
+            Err(_) => JsonNode::Unsupported("an integer out of range"),
+        })
+    }
+
+    fn visit_f64<E: de::Error>(self, _: f64) -> Result<JsonNode, E> {
+        Ok(JsonNode::Unsupported("a float"))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<JsonNode, E> {
+        Ok(JsonNode::Value(ConfigValue::String(value.to_string())))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<JsonNode, E> {
+        Ok(JsonNode::Unsupported("null"))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<JsonNode, A::Error> {
+        while seq.next_element::<IgnoredAny>()?.is_some() {}
+        Ok(JsonNode::Unsupported("an array"))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<JsonNode, A::Error> {
+        let mut members = Vec::new();
+        while let Some(member) = map.next_entry()? {
+            members.push(member);
+        }
+        Ok(JsonNode::Object(members))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cli_ide_base::test_support::temp_path;
+
+    #[test]
+    fn test_parse_sections_and_values() {
+        let config = Config::parse(
+            "# user settings\nversion = 1\n\n[editor]\nwrap = false # soft wrap\nindent = 2\n\
+             [files]\nauto_save = \"onFocusChange\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(config.get_integer("version"), Ok(Some(1)));
+        assert_eq!(config.get_bool("editor.wrap"), Ok(Some(false)));
+        assert_eq!(config.get_integer("editor.indent"), Ok(Some(2)));
+        assert_eq!(
+            config.get_string("files.auto_save"),
+            Ok(Some("onFocusChange"))
+        );
+        assert_eq!(config.get_bool("editor.tabs"), Ok(None));
+    }
+
+    #[test]
+    fn test_typed_getter_reports_mismatch() {
+        let config = Config::parse("[editor]\nwrap = \"yes\"").unwrap();
+        let error = config.get_bool("editor.wrap").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "`editor.wrap` expects a boolean, got a string"
+        );
+    }
+
+    #[test]
+    fn test_parse_errors_report_line() {
+        let cases = [
+            ("[editor\nwrap = true", 0, "unterminated section header"),
+            ("[editor]\nwrap true", 1, "expected `=` after `wrap`"),
+            ("wrap = yes", 0, "invalid value `yes`"),
+            ("name = \"open", 0, "unterminated string"),
+            ("[a]\nx = 1\nx = 2", 2, "duplicate setting `x`"),
+            ("[[tasks]]\nx = 1", 0, "arrays of tables are not supported"),
+            ("x = { a = 1 }", 0, "inline tables are not supported"),
+            ("x = [1, 2]", 0, "arrays are not supported"),
+            (
+                "x = \"\"\"\nmore\"\"\"",
+                0,
+                "multi-line strings are not supported",
+            ),
+            ("x = '''a'''", 0, "multi-line strings are not supported"),
+            ("x = 1.5", 0, "floats are not supported: `1.5`"),
+            (
+                "editor.wrap = true",
+                0,
+                "dotted keys are not supported; put `editor.wrap` in a section",
+            ),
+        ];
+        for (contents, line, message) in cases {
+            assert_eq!(
+                Config::parse(contents),
+                Err(ConfigError::Parse {
+                    line,
+                    message: message.to_string()
+                }),
+                "{contents:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_literal_strings_keep_hashes() {
+        let config = Config::parse("x = 'a#b' # comment").unwrap();
+        assert_eq!(config.get_string("x"), Ok(Some("a#b")));
+    }
+
+    #[test]
+    fn test_parse_json_sections_in_file_order() {
+        let config = Config::parse_json(
+            r#"{
+                "version": 1,
+                "editor": { "wrap": false, "rust": { "indent": 4 } },
+                "keybindings": { "?": "showCommands", "editor": { "e": "expandBracketSelection" } }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(config.get_integer("version"), Ok(Some(1)));
+        assert_eq!(config.get_bool("editor.wrap"), Ok(Some(false)));
+        assert_eq!(config.get_integer("editor.rust.indent"), Ok(Some(4)));
+        let bindings: Vec<_> = config
+            .keybindings()
+            .unwrap()
+            .into_iter()
+            .map(|binding| (binding.context, binding.key))
+            .collect();
+        assert_eq!(
+            bindings,
+            vec![
+                (None, "?".to_string()),
+                (Some("editor".to_string()), "e".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_load_reads_json_files() {
+        let path = temp_path("json-config.json");
+        fs::write(&path, r#"{"editor": {"indent": 3}}"#).unwrap();
+        let service = ConfigService::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(service.get_integer("editor.indent"), Ok(Some(3)));
+    }
+
+    #[test]
+    fn test_parse_json_reports_errors() {
+        assert_eq!(
+            Config::parse_json("{\n\"editor\": {\"wrap\": tru}}"),
+            Err(ConfigError::Parse {
+                line: 1,
+                message: "expected ident".to_string()
+            })
+        );
+        assert_eq!(
+            Config::parse_json("[1]"),
+            Err(ConfigError::Parse {
+                line: 0,
+                message: "expected an object".to_string()
+            })
+        );
+        assert_eq!(
+            Config::parse_json(r#"{"editor": {"rulers": [80, 100]}}"#),
+            Err(ConfigError::Type {
+                key: "editor.rulers".to_string(),
+                expected: "a boolean, an integer, a string or an object",
+                found: "an array",
+            })
+        );
+        assert!(Config::parse_json(r#"{"a": 1, "a": 2}"#).is_err());
+    }
+
+    #[test]
+    fn test_section_lists_own_settings() {
+        let config = Config::parse("[editor]\nwrap = true\n[editor.extra]\nx = 1\n").unwrap();
+        let settings: Vec<String> = config
+            .section("editor")
+            .map(|(key, value)| format!("{key}={value}"))
+            .collect();
+        assert_eq!(settings, vec!["wrap=true"]);
+    }
+
+    #[test]
+    fn test_keybindings_with_contexts() {
+        let config = Config::parse(
+            "[keybindings]\n\"?\" = \"showCommands\"\n\"#\" = \"quit\" # comment\n\
+             [keybindings.editor]\ne = \"expandBracketSelection\"\n\
+             x = \"quit when mode == 'insert' && !a\"\n[editor]\nwrap = true\n",
+        )
+        .unwrap();
+
+        let bindings = config.keybindings().unwrap();
+        let summary: Vec<(Option<&str>, &str, &str)> = bindings
+            .iter()
+            .map(|b| (b.context.as_deref(), b.key.as_str(), b.action.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (None, "?", "showCommands"),
+                (None, "#", "quit"),
+                (Some("editor"), "e", "expandBracketSelection"),
+                (Some("editor"), "x", "quit"),
+            ]
+        );
+        assert_eq!(bindings[2].when, None);
+        assert_eq!(bindings[3].when.as_deref(), Some("mode == 'insert' && !a"));
+    }
+
+    #[test]
+    fn test_keybinding_must_be_string() {
+        let config = Config::parse("[keybindings]\ne = 1").unwrap();
+        assert!(matches!(
+            config.keybindings(),
+            Err(ConfigError::Type { key, .. }) if key == "keybindings.e"
+        ));
+    }
+
+    #[test]
+    fn test_problem_matchers_by_section() {
+        let config = Config::parse(
+            "[problem_matchers.eslint]\nregex = '^(?P<file>[^:]+):(?P<line>\\d+) (?P<message>.*)$'\n\
+             severity = \"warning\"\n[problem_matchers.tsc]\nregex = \"x\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config.problem_matchers().unwrap(),
+            vec![
+                ProblemMatcherConfig {
+                    name: "eslint".to_string(),
+                    regex: r"^(?P<file>[^:]+):(?P<line>\d+) (?P<message>.*)$".to_string(),
+                    severity: Some("warning".to_string()),
+                },
+                ProblemMatcherConfig {
+                    name: "tsc".to_string(),
+                    regex: "x".to_string(),
+                    severity: None,
+                },
+            ]
+        );
+
+        let config = Config::parse("[problem_matchers.eslint]\nseverity = \"error\"\n").unwrap();
+        assert_eq!(
+            config.problem_matchers().unwrap_err().to_string(),
+            "`problem_matchers.eslint.regex` is required"
+        );
+    }
+
+    #[test]
+    fn test_tasks_by_section() {
+        let config = Config::parse(
+            "[tasks.lint]\ncommand = \"cargo clippy\"\noutput = \"terminal\"\n\
+             [tasks.test]\ncommand = \"cargo test\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config.tasks().unwrap(),
+            vec![
+                TaskConfig {
+                    name: "lint".to_string(),
+                    command: "cargo clippy".to_string(),
+                    output: Some("terminal".to_string()),
+                },
+                TaskConfig {
+                    name: "test".to_string(),
+                    command: "cargo test".to_string(),
+                    output: None,
+                },
+            ]
+        );
+
+        let config = Config::parse("[tasks.lint]\noutput = \"output\"\n").unwrap();
+        assert_eq!(
+            config.tasks().unwrap_err().to_string(),
+            "`tasks.lint.command` is required"
+        );
+    }
+
+    #[test]
+    fn test_abbreviations_with_languages() {
+        let config = Config::parse(
+            "[abbreviations]\nteh = \"the\"\n\
+             [abbreviations.rust]\nfn = \"fn () {\\n}\"\n",
+        )
+        .unwrap();
+        let abbreviations = config.abbreviations().unwrap();
+        let summary: Vec<(Option<&str>, &str, &str)> = abbreviations
+            .iter()
+            .map(|a| (a.language.as_deref(), a.word.as_str(), a.expansion.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![(None, "teh", "the"), (Some("rust"), "fn", "fn () {\n}")]
+        );
+
+        let config = Config::parse("[abbreviations]\nteh = true").unwrap();
+        assert!(matches!(
+            config.abbreviations(),
+            Err(ConfigError::Type { key, .. }) if key == "abbreviations.teh"
+        ));
+    }
+
+    #[test]
+    fn test_merge_replaces_and_adds_settings() {
+        let mut config = Config::parse("[editor]\nwrap = true\nindent = 2\n").unwrap();
+        config.merge(Config::parse("[editor]\nindent = 4\n[keybindings]\nx = \"quit\"\n").unwrap());
+        assert_eq!(config.get_bool("editor.wrap"), Ok(Some(true)));
+        assert_eq!(config.get_integer("editor.indent"), Ok(Some(4)));
+        assert_eq!(config.get_string("keybindings.x"), Ok(Some("quit")));
+    }
+
+    #[test]
+    fn test_layered_files_reload_together() {
+        let user = temp_path("layered-user.toml");
+        let workspace = temp_path("layered-workspace.toml");
+        fs::write(&user, "[editor]\nwrap = true\nindent = 2\n").unwrap();
+        let service = ConfigService::load_layered([user.clone(), workspace.clone()]).unwrap();
+        assert_eq!(service.paths(), [user.clone(), workspace.clone()]);
+        assert_eq!(service.get_integer("editor.indent"), Ok(Some(2)));
+
+        fs::write(&workspace, "[editor]\nindent = 4\n").unwrap();
+        service.reload().unwrap();
+        assert_eq!(service.get_bool("editor.wrap"), Ok(Some(true)));
+        assert_eq!(service.get_integer("editor.indent"), Ok(Some(4)));
+
+        fs::write(&workspace, "[editor\n").unwrap();
+        assert_eq!(
+            service.reload().unwrap_err().to_string(),
+            format!(
+                "{}: line 1: unterminated section header",
+                workspace.display()
+            )
+        );
+        fs::remove_file(&user).unwrap();
+        fs::remove_file(&workspace).unwrap();
+    }
+
+    #[test]
+    fn test_load_missing_file_is_empty() {
+        let service = ConfigService::load(temp_path("missing-config.toml")).unwrap();
+        assert!(service.config().is_empty());
+        assert!(service.path().is_some());
+    }
+
+    #[test]
+    fn test_reload_fires_config_changed() {
+        let path = temp_path("reload-config.toml");
+        fs::write(&path, "[editor]\nwrap = true\n").unwrap();
+        let service = ConfigService::load(&path).unwrap();
+        let changes = service.on_did_change().subscribe();
+        assert_eq!(service.get_bool("editor.wrap"), Ok(Some(true)));
+
+        fs::write(&path, "[editor]\nwrap = false\n").unwrap();
+        service.reload().unwrap();
+        assert_eq!(service.get_bool("editor.wrap"), Ok(Some(false)));
+        let change = changes.try_recv().unwrap();
+        assert_eq!(change.config.get_bool("editor.wrap"), Ok(Some(false)));
+
+        fs::write(&path, "[editor\n").unwrap();
+        assert!(service.reload().is_err());
+        assert_eq!(service.get_bool("editor.wrap"), Ok(Some(false)));
+        assert!(changes.try_recv().is_err());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_folder_settings_apply_to_their_files() {
+        let root = temp_path("folders");
+        let nested = root.join("nested");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(
+            ConfigService::workspace_path(&root),
+            "[editor]\nindent = 4\n",
+        )
+        .unwrap();
+        let service =
+            ConfigService::new(Config::parse("[editor]\nwrap = true\nindent = 2\n").unwrap());
+        let changes = service.on_did_change().subscribe();
+        service.add_folder(&root).unwrap();
+        service.add_folder(&nested).unwrap();
+        assert!(changes.try_recv().is_ok());
+        assert_eq!(service.folders(), [root.clone(), nested.clone()]);
+        assert_eq!(
+            service.folder_paths(),
+            [
+                root.join(WORKSPACE_CONFIG_FILE),
+                nested.join(WORKSPACE_CONFIG_FILE)
+            ]
+        );
+
+        let in_root = service.config_for(&root.join("a.rs"));
+        assert_eq!(in_root.get_integer("editor.indent"), Ok(Some(4)));
+        assert_eq!(in_root.get_bool("editor.wrap"), Ok(Some(true)));
+        assert_eq!(service.get_integer("editor.indent"), Ok(Some(2)));
+        assert!(service
+            .folder_config(Path::new("/elsewhere/a.rs"))
+            .is_none());
+
+        // The innermost folder wins, and a reload re-reads the folders
+        fs::write(
+            ConfigService::workspace_path(&nested),
+            "[editor]\nindent = 8\n",
+        )
+        .unwrap();
+        service.reload().unwrap();
+        let in_nested = service.config_for(&nested.join("b.rs"));
+        assert_eq!(in_nested.get_integer("editor.indent"), Ok(Some(8)));
+
+        fs::write(ConfigService::workspace_path(&nested), "[editor\n").unwrap();
+        assert_eq!(
+            service.reload().unwrap_err().to_string(),
+            format!(
+                "{}: line 1: unterminated section header",
+                nested.join(WORKSPACE_CONFIG_FILE).display()
+            )
+        );
+        assert!(service.remove_folder(&nested));
+        assert!(!service.remove_folder(&nested));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,3 @@
+//! User configuration.
+
+pub mod config_service;
@@ -0,0 +1,360 @@
+//! Context keys and the `when` expressions evaluated against them.
+//!
+//! The [`ContextKeyService`] holds named values describing the state of the
+//! workbench, e.g. `editorFocused = true` or `mode = "insert"`. The
+//! workbench keeps its own keys up to date and features may set more. A
+//! [`ContextKeyExpr`], parsed from a `when` clause like VS Code's, tests
+//! them:
+//!
+//! ```text
+//! editorFocused && mode == 'insert'
+//! !terminalFocused || editorLangId != rust
+//! ```
+//!
+//! A bare key is true when its value is `true` or a non-empty string, and
+//! false when it is `false`, empty or not set. `==` and `!=` compare a key
+//! with a quoted or bare value; `true` and `false` compare with booleans.
+//! `!` binds tighter than `&&`, which binds tighter than `||`, and
+//! parentheses group.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// The value of a context key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContextValue {
+    /// A flag, e.g. `editorFocused`.
+    Bool(bool),
+    /// A name, e.g. the editing `mode`.
+    String(String),
+}
+
+impl ContextValue {
+    /// Check whether the value counts as true on its own.
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            ContextValue::Bool(value) => *value,
+            ContextValue::String(value) => !value.is_empty(),
+        }
+    }
+}
+
+impl From<bool> for ContextValue {
+    fn from(value: bool) -> Self {
+        ContextValue::Bool(value)
+    }
+}
+
+impl From<&str> for ContextValue {
+    fn from(value: &str) -> Self {
+        ContextValue::String(value.to_string())
+    }
+}
+
+impl From<String> for ContextValue {
+    fn from(value: String) -> Self {
+        ContextValue::String(value)
+    }
+}
+
+impl fmt::Display for ContextValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContextValue::Bool(value) => write!(f, "{value}"),
+            ContextValue::String(value) => f.write_str(value),
+        }
+    }
+}
+
+/// Holds the context keys `when` expressions are evaluated against.
+#[derive(Debug, Default)]
+pub struct ContextKeyService {
+    /// The keys set, by name.
+    keys: RwLock<HashMap<String, ContextValue>>,
+}
+
+impl ContextKeyService {
+    /// Create a service with no keys set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lock the keys for reading, ignoring poisoning: they are plain values.
+    fn read(&self) -> RwLockReadGuard<'_, HashMap<String, ContextValue>> {
+        self.keys.read().unwrap_or_else(|error| error.into_inner())
+    }
+
+    /// Lock the keys for writing, ignoring poisoning.
+    fn write(&self) -> RwLockWriteGuard<'_, HashMap<String, ContextValue>> {
+        self.keys.write().unwrap_or_else(|error| error.into_inner())
+    }
+
+    /// Set the key `name` to `value`, replacing its previous value.
+    pub fn set(&self, name: impl Into<String>, value: impl Into<ContextValue>) {
+        self.write().insert(name.into(), value.into());
+    }
+
+    /// Unset the key `name`, returning its value if it was set.
+    pub fn remove(&self, name: &str) -> Option<ContextValue> {
+        self.write().remove(name)
+    }
+
+    /// Get the value of the key `name`, if it is set.
+    pub fn get(&self, name: &str) -> Option<ContextValue> {
+        self.read().get(name).cloned()
+    }
+
+    /// Get every key set and its value, by name.
+    pub fn keys(&self) -> Vec<(String, ContextValue)> {
+        let mut keys: Vec<(String, ContextValue)> = self
+            .read()
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        keys.sort_by(|a, b| a.0.cmp(&b.0));
+        keys
+    }
+
+    /// Evaluate `expr` against the keys as they are now.
+    pub fn evaluate(&self, expr: &ContextKeyExpr) -> bool {
+        expr.evaluate(&self.read())
+    }
+}
+
+/// A parsed `when` expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContextKeyExpr {
+    /// The key is truthy.
+    Has(String),
+    /// The key equals the value.
+    Equals(String, String),
+    /// The key does not equal the value.
+    NotEquals(String, String),
+    /// The expression is false.
+    Not(Box<ContextKeyExpr>),
+    /// Both expressions are true.
+    And(Box<ContextKeyExpr>, Box<ContextKeyExpr>),
+    /// Either expression is true.
+    Or(Box<ContextKeyExpr>, Box<ContextKeyExpr>),
+}
+
+/// A `when` expression that could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextKeyExprError {
+    /// Byte offset in the expression the problem was found at.
+    pub offset: usize,
+    /// Description of the problem.
+    pub message: String,
+}
+
+impl fmt::Display for ContextKeyExprError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "column {}: {}", self.offset + 1, self.message)
+    }
+}
+
+impl std::error::Error for ContextKeyExprError {}
+
+impl ContextKeyExpr {
+    /// Parse a `when` expression.
+    pub fn parse(text: &str) -> Result<Self, ContextKeyExprError> {
+        let mut parser = Parser { text, offset: 0 };
+        let expr = parser.or()?;
+        parser.skip_whitespace();
+        if parser.offset < text.len() {
+            return Err(parser.error("expected `&&`, `||` or the end"));
+        }
+        Ok(expr)
+    }
+
+    /// Evaluate the expression against `keys`.
+    fn evaluate(&self, keys: &HashMap<String, ContextValue>) -> bool {
+        match self {
+            ContextKeyExpr::Has(key) => keys.get(key).is_some_and(ContextValue::is_truthy),
+            ContextKeyExpr::Equals(key, value) => keys
+                .get(key)
+                .is_some_and(|found| found.to_string() == *value),
+            ContextKeyExpr::NotEquals(key, value) => keys
+                .get(key)
+                .is_none_or(|found| found.to_string() != *value),
+            ContextKeyExpr::Not(expr) => !expr.evaluate(keys),
+            ContextKeyExpr::And(left, right) => left.evaluate(keys) && right.evaluate(keys),
+            ContextKeyExpr::Or(left, right) => left.evaluate(keys) || right.evaluate(keys),
+        }
+    }
+}
+
+impl fmt::Display for ContextKeyExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContextKeyExpr::Has(key) => f.write_str(key),
+            ContextKeyExpr::Equals(key, value) => write!(f, "{key} == '{value}'"),
+            ContextKeyExpr::NotEquals(key, value) => write!(f, "{key} != '{value}'"),
+            ContextKeyExpr::Not(expr) => match expr.as_ref() {
+                ContextKeyExpr::And(..) | ContextKeyExpr::Or(..) => write!(f, "!({expr})"),
+                _ => write!(f, "!{expr}"),
+            },
+            ContextKeyExpr::And(left, right) => {
+                let group = |expr: &ContextKeyExpr| match expr {
+                    ContextKeyExpr::Or(..) => format!("({expr})"),
+                    _ => expr.to_string(),
+                };
+                write!(f, "{} && {}", group(left), group(right))
+            }
+            ContextKeyExpr::Or(left, right) => write!(f, "{left} || {right}"),
+        }
+    }
+}
+
+/// A recursive-descent parser over a `when` expression.
+struct Parser<'a> {
+    /// The expression.
+    text: &'a str,
+    /// Byte offset of the next character to read.
+    offset: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// An error at the current offset.
+    fn error(&self, message: impl Into<String>) -> ContextKeyExprError {
+        ContextKeyExprError {
+            offset: self.offset,
+            message: message.into(),
+        }
+    }
+
+    /// The text left to read.
+    fn rest(&self) -> &'a str {
+        &self.text[self.offset..]
+    }
+
+    /// Skip spaces and tabs.
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.offset += rest.len() - rest.trim_start().len();
+    }
+
+    /// Read `token` if the text continues with it.
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        if self.rest().starts_with(token) {
+            self.offset += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// `and ('||' and)*`
+    fn or(&mut self) -> Result<ContextKeyExpr, ContextKeyExprError> {
+        let mut expr = self.and()?;
+        while self.eat("||") {
+            expr = ContextKeyExpr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    /// `unary ('&&' unary)*`
+    fn and(&mut self) -> Result<ContextKeyExpr, ContextKeyExprError> {
+        let mut expr = self.unary()?;
+        while self.eat("&&") {
+            expr = ContextKeyExpr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    /// `'!' unary | '(' or ')' | key (('==' | '!=') value)?`
+    fn unary(&mut self) -> Result<ContextKeyExpr, ContextKeyExprError> {
+        self.skip_whitespace();
+        if !self.rest().starts_with("!=") && self.eat("!") {
+            return Ok(ContextKeyExpr::Not(Box::new(self.unary()?)));
+        }
+        if self.eat("(") {
+            let expr = self.or()?;
+            if !self.eat(")") {
+                return Err(self.error("expected `)`"));
+            }
+            return Ok(expr);
+        }
+        let key = self.word().ok_or_else(|| self.error("expected a key"))?;
+        if self.eat("==") {
+            Ok(ContextKeyExpr::Equals(key, self.value()?))
+        } else if self.eat("!=") {
+            Ok(ContextKeyExpr::NotEquals(key, self.value()?))
+        } else {
+            Ok(ContextKeyExpr::Has(key))
+        }
+    }
+
+    /// A quoted or bare value.
+    fn value(&mut self) -> Result<String, ContextKeyExprError> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        if let Some(quote) = rest.chars().next().filter(|c| matches!(c, '\'' | '"')) {
+            let Some(end) = rest[1..].find(quote) else {
+                return Err(self.error("unterminated string"));
+            };
+            let value = rest[1..=end].to_string();
+            self.offset += end + 2;
+            return Ok(value);
+        }
+        self.word().ok_or_else(|| self.error("expected a value"))
+    }
+
+    /// A key name or bare value: letters, digits and `_ . - :`.
+    fn word(&mut self) -> Option<String> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '.' | '-' | ':')))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return None;
+        }
+        self.offset += len;
+        Some(rest[..len].to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expressions_evaluate_against_the_keys() {
+        let service = ContextKeyService::new();
+        service.set("editorFocused", true);
+        service.set("mode", "insert");
+        let holds = |text: &str| service.evaluate(&ContextKeyExpr::parse(text).unwrap());
+
+        assert!(holds("editorFocused && mode == 'insert'"));
+        assert!(holds("mode == insert && editorFocused == true"));
+        assert!(!holds("editorFocused && mode != \"insert\""));
+        assert!(!holds("terminalFocused"));
+        assert!(holds("!terminalFocused && terminalFocused != true"));
+        assert!(holds(
+            "terminalFocused || !(mode == normal || !editorFocused)"
+        ));
+        service.set("editorFocused", false);
+        assert!(!holds("editorFocused"));
+        assert_eq!(service.remove("mode"), Some(ContextValue::from("insert")));
+        assert!(!holds("mode == insert"));
+    }
+
+    #[test]
+    fn test_parse_errors_and_display() {
+        let expr = ContextKeyExpr::parse("!(a || b) && c == 'x y'").unwrap();
+        assert_eq!(expr.to_string(), "!(a || b) && c == 'x y'");
+        assert_eq!(
+            ContextKeyExpr::parse("a && (b").unwrap_err().to_string(),
+            "column 8: expected `)`"
+        );
+        assert_eq!(
+            ContextKeyExpr::parse("a b").unwrap_err().to_string(),
+            "column 3: expected `&&`, `||` or the end"
+        );
+        assert!(ContextKeyExpr::parse("mode == 'insert").is_err());
+        assert!(ContextKeyExpr::parse("").is_err());
+    }
+}
@@ -0,0 +1,4 @@
+//! Context keys describing the workbench and the `when` expressions
+//! testing them.
+
+pub mod context_key_service;
@@ -5,15 +5,58 @@
 //! mirrors the dependency injection patterns described in the
 //! [ARCHITECTURE_ENHANCED](https://github.com/christophermanahan/paradiddle/blob/main/docs/architecture/rust-ide-plans.md)
 //! document and provides a foundation for more advanced service registries later on【6955392274892†L521-L533】.
+//!
+//! Services can also be registered under a trait object type with
+//! [`ServiceContainer::register_trait`], so consumers resolve the
+//! abstraction (`resolve_trait::<dyn Logger>()`) without naming the
+//! implementation.
+//!
+//! Expensive services can be registered as factories instead of values:
+//! [`ServiceContainer::register_factory`] builds the service on its first
+//! resolve and hands out that instance from then on, while
+//! [`ServiceContainer::register_transient`] builds a new one every time.
 
 use std::any::{Any, TypeId};
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// A registered service, type-erased.
+type Service = Arc<dyn Any + Send + Sync>;
+
+/// Builds a service, resolving its dependencies from the container.
+type Factory = Arc<dyn Fn(&ServiceContainer) -> Service + Send + Sync>;
+
+/// How a registered service is obtained.
+#[derive(Clone)]
+enum Registration {
+    /// A service registered as a value.
+    Instance(Service),
+    /// A service built by `factory` on first resolve and kept in `instance`.
+    Lazy {
+        factory: Factory,
+        instance: Arc<OnceLock<Service>>,
+    },
+    /// A service built by the factory on every resolve.
+    Transient(Factory),
+}
+
+impl Registration {
+    /// Get the service, building it if needed.
+    fn service(&self, container: &ServiceContainer) -> Service {
+        match self {
+            Registration::Instance(service) => Arc::clone(service),
+            Registration::Lazy { factory, instance } => {
+                Arc::clone(instance.get_or_init(|| factory(container)))
+            }
+            Registration::Transient(factory) => factory(container),
+        }
+    }
+}
 
 /// A simple dependency injection container.
 #[derive(Default)]
 pub struct ServiceContainer {
-    services: RwLock<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>,
+    services: RwLock<HashMap<TypeId, Registration>>,
 }
 
 impl ServiceContainer {
@@ -29,17 +72,81 @@ impl ServiceContainer {
     /// The service must be `Send` and `Sync` so it can be shared safely across
     /// threads.
     pub fn register<T: Any + Send + Sync>(&self, service: T) {
-        let mut services = self.services.write().expect("container lock poisoned");
-        services.insert(TypeId::of::<T>(), Arc::new(service));
+        self.insert::<T>(Registration::Instance(Arc::new(service)));
+    }
+
+    /// Register a factory building the service of type `T` when it is
+    /// first resolved.
+    ///
+    /// The factory runs at most once, even if several threads resolve `T`
+    /// at the same time, and every resolve returns the same instance. It
+    /// may resolve other services from the container, but not `T` itself.
+    pub fn register_factory<T, F>(&self, factory: F)
+    where
+        T: Any + Send + Sync,
+        F: Fn(&ServiceContainer) -> T + Send + Sync + 'static,
+    {
+        self.insert::<T>(Registration::Lazy {
+            factory: Arc::new(move |container| Arc::new(factory(container))),
+            instance: Arc::new(OnceLock::new()),
+        });
+    }
+
+    /// Register a factory building a new service of type `T` on every
+    /// resolve.
+    pub fn register_transient<T, F>(&self, factory: F)
+    where
+        T: Any + Send + Sync,
+        F: Fn(&ServiceContainer) -> T + Send + Sync + 'static,
+    {
+        self.insert::<T>(Registration::Transient(Arc::new(move |container| {
+            Arc::new(factory(container))
+        })));
     }
 
     /// Resolve a previously registered service of type `T`.
     /// Returns `Some(Arc<T>)` if found, otherwise `None`.
+    ///
+    /// A service registered with a factory is built by it as needed.
     pub fn resolve<T: Any + Send + Sync>(&self) -> Option<Arc<T>> {
-        let services = self.services.read().expect("container lock poisoned");
-        services
-            .get(&TypeId::of::<T>())
-            .and_then(|service| service.clone().downcast::<T>().ok())
+        self.service(TypeId::of::<T>())
+            .and_then(|service| service.downcast::<T>().ok())
+    }
+
+    /// Register `service` under the interface `I`, usually a trait object
+    /// type such as `dyn Logger`.
+    ///
+    /// The implementation is only reachable through
+    /// [`resolve_trait::<I>`](ServiceContainer::resolve_trait), not by its
+    /// concrete type. Registering another implementation of `I` replaces it.
+    pub fn register_trait<I: ?Sized + Send + Sync + 'static>(&self, service: Arc<I>) {
+        self.insert::<I>(Registration::Instance(Arc::new(service)));
+    }
+
+    /// Resolve the implementation registered under the interface `I`.
+    /// Returns `Some(Arc<I>)` if found, otherwise `None`.
+    pub fn resolve_trait<I: ?Sized + Send + Sync + 'static>(&self) -> Option<Arc<I>> {
+        self.service(TypeId::of::<I>())
+            .and_then(|service| service.downcast_ref::<Arc<I>>().cloned())
+    }
+
+    /// Register how the service under `K` is obtained, replacing any
+    /// previous registration.
+    fn insert<K: ?Sized + 'static>(&self, registration: Registration) {
+        let mut services = self.services.write().expect("container lock poisoned");
+        services.insert(TypeId::of::<K>(), registration);
+    }
+
+    /// Get the service registered under `id`, building it if needed.
+    ///
+    /// The lock is released before a factory runs, so factories can
+    /// resolve their own dependencies.
+    fn service(&self, id: TypeId) -> Option<Service> {
+        let registration = {
+            let services = self.services.read().expect("container lock poisoned");
+            services.get(&id)?.clone()
+        };
+        Some(registration.service(self))
     }
 }
 
@@ -113,6 +220,127 @@ mod tests {
         assert_eq!(resolved.value, 99);
     }
 
+    trait Greeter: Send + Sync {
+        fn greet(&self) -> String;
+    }
+
+    struct English;
+
+    impl Greeter for English {
+        fn greet(&self) -> String {
+            "hello".to_string()
+        }
+    }
+
+    struct French;
+
+    impl Greeter for French {
+        fn greet(&self) -> String {
+            "bonjour".to_string()
+        }
+    }
+
+    #[test]
+    fn test_register_and_resolve_trait() {
+        let container = ServiceContainer::new();
+        assert!(container.resolve_trait::<dyn Greeter>().is_none());
+
+        container.register_trait::<dyn Greeter>(Arc::new(English));
+        let greeter = container.resolve_trait::<dyn Greeter>().unwrap();
+        assert_eq!(greeter.greet(), "hello");
+
+        // The implementation is not registered as its concrete type
+        assert!(container.resolve::<English>().is_none());
+
+        container.register_trait::<dyn Greeter>(Arc::new(French));
+        let greeter = container.resolve_trait::<dyn Greeter>().unwrap();
+        assert_eq!(greeter.greet(), "bonjour");
+    }
+
+    #[test]
+    fn test_trait_and_concrete_registrations_are_independent() {
+        let container = ServiceContainer::new();
+        let shared = Arc::new(TestService { value: 7 });
+        container.register(TestService { value: 1 });
+        container.register_trait::<dyn Any + Send + Sync>(shared);
+
+        assert_eq!(container.resolve::<TestService>().unwrap().value, 1);
+        let any = container.resolve_trait::<dyn Any + Send + Sync>().unwrap();
+        assert_eq!(any.downcast_ref::<TestService>().unwrap().value, 7);
+    }
+
+    #[test]
+    fn test_factory_runs_once_on_first_resolve() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let container = ServiceContainer::new();
+        let built = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&built);
+        container.register(AnotherService {
+            name: "dependency".to_string(),
+        });
+        container.register_factory(move |container| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            let dependency = container.resolve::<AnotherService>().unwrap();
+            TestService {
+                value: dependency.name.len() as i32,
+            }
+        });
+        assert_eq!(built.load(Ordering::SeqCst), 0);
+
+        let first = container.resolve::<TestService>().unwrap();
+        let second = container.resolve::<TestService>().unwrap();
+        assert_eq!(first.value, 10);
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(built.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_transient_builds_a_new_instance_per_resolve() {
+        use std::sync::atomic::{AtomicI32, Ordering};
+
+        let container = ServiceContainer::new();
+        let next = AtomicI32::new(0);
+        container.register_transient(move |_| TestService {
+            value: next.fetch_add(1, Ordering::SeqCst),
+        });
+
+        let first = container.resolve::<TestService>().unwrap();
+        let second = container.resolve::<TestService>().unwrap();
+        assert_eq!((first.value, second.value), (0, 1));
+        assert!(!Arc::ptr_eq(&first, &second));
+
+        // Registering a value replaces the factory
+        container.register(TestService { value: 42 });
+        assert_eq!(container.resolve::<TestService>().unwrap().value, 42);
+    }
+
+    #[test]
+    fn test_factory_builds_once_across_threads() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::thread;
+
+        let container = Arc::new(ServiceContainer::new());
+        let built = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&built);
+        container.register_factory(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            thread::sleep(std::time::Duration::from_millis(10));
+            TestService { value: 5 }
+        });
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let container = Arc::clone(&container);
+                thread::spawn(move || container.resolve::<TestService>().unwrap())
+            })
+            .collect();
+        let services: Vec<Arc<TestService>> =
+            handles.into_iter().map(|h| h.join().unwrap()).collect();
+        assert!(services.iter().all(|s| Arc::ptr_eq(s, &services[0])));
+        assert_eq!(built.load(Ordering::SeqCst), 1);
+    }
+
     #[test]
     fn test_thread_safety() {
         use std::thread;
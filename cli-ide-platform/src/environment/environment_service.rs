@@ -0,0 +1,220 @@
+//! Detecting what the terminal the IDE runs in can display.
+//!
+//! Terminals do not report their capabilities directly; the conventions
+//! are environment variables. The `EnvironmentService` keeps a snapshot of
+//! the variables that matter (`TERM`, `COLORTERM`, `TERM_PROGRAM`,
+//! `NO_COLOR`, the locale and the multiplexer markers) and derives
+//! [`TerminalCapabilities`] from them. [`EnvironmentService::from_env`]
+//! reads the process environment; [`EnvironmentService::new`] takes the
+//! variables, so tests and the workbench's default service do not depend on
+//! the terminal they run in.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+
+/// Environment variables the service reads.
+pub const VARIABLES: [&str; 10] = [
+    "TERM",
+    "COLORTERM",
+    "TERM_PROGRAM",
+    "TERM_PROGRAM_VERSION",
+    "NO_COLOR",
+    "LC_ALL",
+    "LC_CTYPE",
+    "LANG",
+    "TMUX",
+    "STY",
+];
+
+/// How many colors the terminal can show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorSupport {
+    /// No colors: `TERM=dumb` or `NO_COLOR` is set.
+    Monochrome,
+    /// The 16 ANSI colors.
+    Ansi16,
+    /// The 256-color palette.
+    Ansi256,
+    /// 24-bit RGB colors.
+    TrueColor,
+}
+
+impl fmt::Display for ColorSupport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ColorSupport::Monochrome => "monochrome",
+            ColorSupport::Ansi16 => "16 colors",
+            ColorSupport::Ansi256 => "256 colors",
+            ColorSupport::TrueColor => "24-bit color",
+        })
+    }
+}
+
+/// What the terminal is detected to support.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TerminalCapabilities {
+    /// Value of `TERM`, if set.
+    pub term: Option<String>,
+    /// Terminal emulator from `TERM_PROGRAM`, with its version if known.
+    pub program: Option<String>,
+    /// Colors the terminal can show.
+    pub color: ColorSupport,
+    /// Whether the locale is UTF-8, so box-drawing and other non-ASCII
+    /// characters can be printed.
+    pub unicode: bool,
+    /// Terminal multiplexer between the IDE and the terminal, e.g. `tmux`.
+    pub multiplexer: Option<String>,
+}
+
+/// Keeps the environment variables describing the terminal and reports
+/// its capabilities.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EnvironmentService {
+    /// The variables among [`VARIABLES`] that are set, by name.
+    variables: BTreeMap<String, String>,
+}
+
+impl EnvironmentService {
+    /// Create a service seeing the variables `variables`; names not in
+    /// [`VARIABLES`] are ignored.
+    pub fn new<K, V>(variables: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        let variables = variables
+            .into_iter()
+            .map(|(name, value)| (name.into(), value.into()))
+            .filter(|(name, _)| VARIABLES.contains(&name.as_str()))
+            .collect();
+        Self { variables }
+    }
+
+    /// Create a service seeing the variables of this process.
+    pub fn from_env() -> Self {
+        Self::new(
+            VARIABLES
+                .iter()
+                .filter_map(|&name| env::var(name).ok().map(|value| (name, value))),
+        )
+    }
+
+    /// Get the variables seen, by name.
+    pub fn variables(&self) -> &BTreeMap<String, String> {
+        &self.variables
+    }
+
+    /// Get the value of the variable `name`, unless it is unset.
+    pub fn variable(&self, name: &str) -> Option<&str> {
+        self.variables.get(name).map(String::as_str)
+    }
+
+    /// Work out what the terminal supports from the variables.
+    pub fn capabilities(&self) -> TerminalCapabilities {
+        let term = self.variable("TERM").map(str::to_string);
+        let program = self.variable("TERM_PROGRAM").map(|program| {
+            match self.variable("TERM_PROGRAM_VERSION") {
+                Some(version) => format!("{program} {version}"),
+                None => program.to_string(),
+            }
+        });
+        let multiplexer = if self.variable("TMUX").is_some() {
+            Some("tmux".to_string())
+        } else if self.variable("STY").is_some()
+            || term
+                .as_deref()
+                .is_some_and(|term| term.starts_with("screen"))
+        {
+            Some("screen".to_string())
+        } else {
+            None
+        };
+        TerminalCapabilities {
+            color: self.color_support(),
+            unicode: self.is_utf8_locale(),
+            term,
+            program,
+            multiplexer,
+        }
+    }
+
+    /// Work out the colors the terminal can show.
+    ///
+    /// `NO_COLOR` wins over everything; then `COLORTERM` and terminals
+    /// known to support 24-bit color, then the `TERM` name.
+    fn color_support(&self) -> ColorSupport {
+        let term = self.variable("TERM").unwrap_or_default();
+        if self
+            .variable("NO_COLOR")
+            .is_some_and(|value| !value.is_empty())
+            || term == "dumb"
+        {
+            return ColorSupport::Monochrome;
+        }
+        let colorterm = self.variable("COLORTERM").unwrap_or_default();
+        let true_color_program = matches!(
+            self.variable("TERM_PROGRAM"),
+            Some("iTerm.app" | "WezTerm" | "vscode" | "ghostty")
+        );
+        if matches!(colorterm, "truecolor" | "24bit") || true_color_program {
+            ColorSupport::TrueColor
+        } else if term.contains("256color") {
+            ColorSupport::Ansi256
+        } else {
+            ColorSupport::Ansi16
+        }
+    }
+
+    /// Check whether the effective locale, the first of `LC_ALL`,
+    /// `LC_CTYPE` and `LANG` that is set, uses UTF-8.
+    fn is_utf8_locale(&self) -> bool {
+        ["LC_ALL", "LC_CTYPE", "LANG"]
+            .iter()
+            .find_map(|&name| self.variable(name).filter(|value| !value.is_empty()))
+            .is_some_and(|locale| {
+                let locale = locale.to_ascii_lowercase();
+                locale.contains("utf-8") || locale.contains("utf8")
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capabilities_follow_the_terminal_conventions() {
+        let service = EnvironmentService::new([
+            ("TERM", "xterm-256color"),
+            ("LANG", "en_US.UTF-8"),
+            ("TERM_PROGRAM", "Apple_Terminal"),
+            ("TERM_PROGRAM_VERSION", "455"),
+            ("HOME", "/home/user"),
+        ]);
+        assert_eq!(service.variable("HOME"), None, "not a terminal variable");
+        let capabilities = service.capabilities();
+        assert_eq!(capabilities.color, ColorSupport::Ansi256);
+        assert!(capabilities.unicode);
+        assert_eq!(capabilities.program.as_deref(), Some("Apple_Terminal 455"));
+        assert_eq!(capabilities.multiplexer, None);
+
+        let tmux = EnvironmentService::new([
+            ("TERM", "screen"),
+            ("COLORTERM", "truecolor"),
+            ("TMUX", "/tmp/tmux-1000/default,1,0"),
+            ("LC_ALL", "C"),
+            ("LANG", "en_US.UTF-8"),
+        ]);
+        let capabilities = tmux.capabilities();
+        assert_eq!(capabilities.color, ColorSupport::TrueColor);
+        assert!(!capabilities.unicode, "LC_ALL overrides LANG");
+        assert_eq!(capabilities.multiplexer.as_deref(), Some("tmux"));
+
+        let plain = EnvironmentService::new([("TERM", "xterm-256color"), ("NO_COLOR", "1")]);
+        assert_eq!(plain.capabilities().color, ColorSupport::Monochrome);
+        let unknown = EnvironmentService::default().capabilities();
+        assert_eq!(unknown.color, ColorSupport::Ansi16);
+        assert_eq!(unknown.term, None);
+    }
+}
@@ -0,0 +1,3 @@
+//! What the IDE can tell about the terminal and host it runs in.
+
+pub mod environment_service;
@@ -0,0 +1,712 @@
+//! File operations for the explorer.
+//!
+//! `FileOperations` creates, renames, moves and deletes files and folders
+//! after checking the new name and refusing to overwrite anything. Moves and
+//! deletions also come in bulk variants for multi-selections. Deleted entries
+//! are moved to the trash following the freedesktop.org trash specification
+//! (`$XDG_DATA_HOME/Trash`), so they can be restored; entries on another
+//! file system than the trash are copied there and then removed. Every completed
+//! operation fires a [`FileOperationEvent`] so components holding paths,
+//! such as open buffers, can follow renames and deletions.
+
+use std::env;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use cli_ide_base::Event;
+
+/// Errors produced by file operations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileOperationError {
+    /// The name is empty, contains a path separator or is `.`/`..`.
+    InvalidName(String),
+    /// Something already exists at the target path.
+    AlreadyExists(PathBuf),
+    /// The source path does not exist.
+    NotFound(PathBuf),
+    /// No trash directory is configured.
+    NoTrash,
+    /// A folder cannot be moved into itself or one of its subfolders.
+    IntoItself(PathBuf),
+    /// The file system reported an error.
+    Io {
+        /// Path the operation failed on.
+        path: PathBuf,
+        /// Description of the I/O error.
+        message: String,
+    },
+}
+
+impl fmt::Display for FileOperationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileOperationError::InvalidName(name) => write!(f, "`{name}` is not a valid name"),
+            FileOperationError::AlreadyExists(path) => {
+                write!(f, "{} already exists", path.display())
+            }
+            FileOperationError::NotFound(path) => write!(f, "{} does not exist", path.display()),
+            FileOperationError::NoTrash => write!(f, "no trash directory is available"),
+            FileOperationError::IntoItself(path) => {
+                write!(f, "cannot move {} into itself", path.display())
+            }
+            FileOperationError::Io { path, message } => {
+                write!(f, "{}: {}", path.display(), message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FileOperationError {}
+
+impl FileOperationError {
+    /// Wrap an I/O error on `path`.
+    fn io(path: &Path, error: io::Error) -> Self {
+        FileOperationError::Io {
+            path: path.to_path_buf(),
+            message: error.to_string(),
+        }
+    }
+}
+
+/// Fired by [`FileOperations`] after an operation completes.
+///
+/// Paths are absolute and canonical where the entry exists.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileOperationEvent {
+    /// A file or folder was created.
+    Created(PathBuf),
+    /// A file or folder was renamed or moved.
+    Renamed {
+        /// The old path.
+        from: PathBuf,
+        /// The new path.
+        to: PathBuf,
+    },
+    /// A file or folder was moved to the trash.
+    Deleted(PathBuf),
+}
+
+/// Check that `name` can name a single directory entry.
+pub fn validate_name(name: &str) -> Result<(), FileOperationError> {
+    let invalid = name.trim().is_empty()
+        || name == "."
+        || name == ".."
+        || name.contains(['/', '\0'])
+        || name.contains(std::path::MAIN_SEPARATOR);
+    if invalid {
+        return Err(FileOperationError::InvalidName(name.to_string()));
+    }
+    Ok(())
+}
+
+/// Creates, renames, moves and deletes files, firing an event after each.
+pub struct FileOperations {
+    /// Trash directory deleted entries are moved to, if any.
+    trash_dir: Option<PathBuf>,
+    /// Fired after each completed operation.
+    on_did_run: Event<FileOperationEvent>,
+}
+
+impl Default for FileOperations {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FileOperations {
+    /// Create file operations using the user's trash directory.
+    pub fn new() -> Self {
+        Self {
+            trash_dir: Self::default_trash_dir(),
+            on_did_run: Event::new(),
+        }
+    }
+
+    /// Create file operations that move deleted entries to `trash_dir`.
+    pub fn with_trash_dir(trash_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            trash_dir: Some(trash_dir.into()),
+            on_did_run: Event::new(),
+        }
+    }
+
+    /// The user's trash: `$XDG_DATA_HOME/Trash`, falling back to
+    /// `~/.local/share/Trash`.
+    ///
+    /// Returns `None` if neither variable is set.
+    pub fn default_trash_dir() -> Option<PathBuf> {
+        let base = env::var_os("XDG_DATA_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| {
+                env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share"))
+            })?;
+        Some(base.join("Trash"))
+    }
+
+    /// Get the trash directory, if any.
+    pub fn trash_dir(&self) -> Option<&Path> {
+        self.trash_dir.as_deref()
+    }
+
+    /// Event fired after each completed operation.
+    pub fn on_did_run(&self) -> &Event<FileOperationEvent> {
+        &self.on_did_run
+    }
+
+    /// Create an empty file named `name` in `parent`.
+    pub fn create_file(&self, parent: &Path, name: &str) -> Result<PathBuf, FileOperationError> {
+        let path = self.new_entry_path(parent, name)?;
+        OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .map_err(|error| conflict_or_io(&path, error))?;
+        self.on_did_run
+            .emit(FileOperationEvent::Created(path.clone()));
+        Ok(path)
+    }
+
+    /// Create a folder named `name` in `parent`.
+    pub fn create_folder(&self, parent: &Path, name: &str) -> Result<PathBuf, FileOperationError> {
+        let path = self.new_entry_path(parent, name)?;
+        fs::create_dir(&path).map_err(|error| conflict_or_io(&path, error))?;
+        self.on_did_run
+            .emit(FileOperationEvent::Created(path.clone()));
+        Ok(path)
+    }
+
+    /// Rename the entry at `path` to `new_name`, keeping its folder.
+    ///
+    /// Changing only the case of the name is allowed; any other existing
+    /// entry with the new name is a conflict.
+    pub fn rename(&self, path: &Path, new_name: &str) -> Result<PathBuf, FileOperationError> {
+        validate_name(new_name)?;
+        let from = existing(path)?;
+        let parent = from.parent().unwrap_or(Path::new("/"));
+        let to = parent.join(new_name);
+        if to == from {
+            return Ok(to);
+        }
+        let same_entry = fs::canonicalize(&to).is_ok_and(|target| target == from);
+        if to.symlink_metadata().is_ok() && !same_entry {
+            return Err(FileOperationError::AlreadyExists(to));
+        }
+        fs::rename(&from, &to).map_err(|error| FileOperationError::io(&from, error))?;
+        self.on_did_run.emit(FileOperationEvent::Renamed {
+            from,
+            to: to.clone(),
+        });
+        Ok(to)
+    }
+
+    /// Move the entry at `path` into the folder `dir`, keeping its name.
+    ///
+    /// Fires [`FileOperationEvent::Renamed`], since a move is a rename that
+    /// changes the folder.
+    pub fn move_into(&self, path: &Path, dir: &Path) -> Result<PathBuf, FileOperationError> {
+        let from = existing(path)?;
+        let dir = fs::canonicalize(dir).map_err(|error| match error.kind() {
+            io::ErrorKind::NotFound => FileOperationError::NotFound(dir.to_path_buf()),
+            _ => FileOperationError::io(dir, error),
+        })?;
+        if dir.starts_with(&from) {
+            return Err(FileOperationError::IntoItself(from));
+        }
+        let Some(name) = from.file_name() else {
+            return Err(FileOperationError::IntoItself(from));
+        };
+        let to = dir.join(name);
+        if to == from {
+            return Ok(to);
+        }
+        if to.symlink_metadata().is_ok() {
+            return Err(FileOperationError::AlreadyExists(to));
+        }
+        fs::rename(&from, &to).map_err(|error| FileOperationError::io(&from, error))?;
+        self.on_did_run.emit(FileOperationEvent::Renamed {
+            from,
+            to: to.clone(),
+        });
+        Ok(to)
+    }
+
+    /// Move each of `paths` into `dir`, continuing past failures.
+    ///
+    /// Entries inside another selected folder move along with it and are
+    /// not moved separately. Returns one result per remaining path.
+    pub fn move_all(
+        &self,
+        paths: &[PathBuf],
+        dir: &Path,
+    ) -> Vec<Result<PathBuf, FileOperationError>> {
+        outermost(paths)
+            .into_iter()
+            .map(|path| self.move_into(path, dir))
+            .collect()
+    }
+
+    /// Move each of `paths` to the trash, continuing past failures.
+    ///
+    /// Entries inside another selected folder are trashed along with it and
+    /// are not trashed separately. Returns one result per remaining path.
+    pub fn delete_all(&self, paths: &[PathBuf]) -> Vec<Result<PathBuf, FileOperationError>> {
+        outermost(paths)
+            .into_iter()
+            .map(|path| self.delete(path))
+            .collect()
+    }
+
+    /// Move the entry at `path` to the trash, returning where it went.
+    ///
+    /// Writes a `.trashinfo` record next to it so it can be restored.
+    pub fn delete(&self, path: &Path) -> Result<PathBuf, FileOperationError> {
+        let from = existing(path)?;
+        let trash = self
+            .trash_dir
+            .as_deref()
+            .ok_or(FileOperationError::NoTrash)?;
+        let files = trash.join("files");
+        let info = trash.join("info");
+        for dir in [&files, &info] {
+            fs::create_dir_all(dir).map_err(|error| FileOperationError::io(dir, error))?;
+        }
+
+        let name = from
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "root".to_string());
+        // Reserve a unique name by creating its info file first
+        let (trashed, info_path) = (0..)
+            .map(|n| {
+                let unique = if n == 0 {
+                    name.clone()
+                } else {
+                    format!("{name}.{n}")
+                };
+                (
+                    files.join(&unique),
+                    info.join(format!("{unique}.trashinfo")),
+                )
+            })
+            .find(|(trashed, info_path)| {
+                trashed.symlink_metadata().is_err()
+                    && OpenOptions::new()
+                        .write(true)
+                        .create_new(true)
+                        .open(info_path)
+                        .is_ok()
+            })
+            .expect("some trash name is free");
+
+        let record = format!(
+            "[Trash Info]\nPath={}\nDeletionDate={}\n",
+            escape_path(&from),
+            deletion_date(SystemTime::now())
+        );
+        let moved = fs::write(&info_path, record)
+            .map_err(|error| FileOperationError::io(&info_path, error))
+            .and_then(|()| move_entry(&from, &trashed, |from, to| fs::rename(from, to)));
+        if let Err(error) = moved {
+            let _ = fs::remove_file(&info_path);
+            return Err(error);
+        }
+        self.on_did_run.emit(FileOperationEvent::Deleted(from));
+        Ok(trashed)
+    }
+
+    /// Validate `name` and build its absolute path in `parent`, failing if
+    /// something already exists there.
+    fn new_entry_path(&self, parent: &Path, name: &str) -> Result<PathBuf, FileOperationError> {
+        validate_name(name)?;
+        let path = existing(parent)?.join(name);
+        if path.symlink_metadata().is_ok() {
+            return Err(FileOperationError::AlreadyExists(path));
+        }
+        Ok(path)
+    }
+}
+
+/// Make an existing path absolute and canonical.
+///
+/// Only the parent is resolved, so a symlink names the link itself rather
+/// than its target.
+fn existing(path: &Path) -> Result<PathBuf, FileOperationError> {
+    let to_error = |error: io::Error| match error.kind() {
+        io::ErrorKind::NotFound => FileOperationError::NotFound(path.to_path_buf()),
+        _ => FileOperationError::io(path, error),
+    };
+    path.symlink_metadata().map_err(to_error)?;
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => {
+            let parent = if parent.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                parent
+            };
+            Ok(fs::canonicalize(parent).map_err(to_error)?.join(name))
+        }
+        _ => fs::canonicalize(path).map_err(to_error),
+    }
+}
+
+/// Move the entry at `from` to `to` with `rename`, copying it and removing
+/// the original when `rename` reports that they are on different file
+/// systems.
+///
+/// A failed copy is removed again and leaves `from` as it was. If the
+/// original cannot be removed after a complete copy, the copy is kept so
+/// nothing is lost.
+fn move_entry(
+    from: &Path,
+    to: &Path,
+    rename: impl Fn(&Path, &Path) -> io::Result<()>,
+) -> Result<(), FileOperationError> {
+    match rename(from, to) {
+        Err(error) if error.kind() == io::ErrorKind::CrossesDevices => {}
+        moved => return moved.map_err(|error| FileOperationError::io(from, error)),
+    }
+    if let Err(error) = copy_entry(from, to) {
+        let _ = remove_entry(to);
+        return Err(FileOperationError::Io {
+            path: from.to_path_buf(),
+            message: format!("could not copy it to another file system: {error}"),
+        });
+    }
+    remove_entry(from).map_err(|error| FileOperationError::Io {
+        path: from.to_path_buf(),
+        message: format!(
+            "copied to {} but could not remove it: {error}",
+            to.display()
+        ),
+    })
+}
+
+/// Copy the entry at `from` to `to`, recursing into folders and copying
+/// symlinks as links.
+fn copy_entry(from: &Path, to: &Path) -> io::Result<()> {
+    let metadata = from.symlink_metadata()?;
+    if metadata.is_dir() {
+        fs::create_dir(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_entry(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        fs::set_permissions(to, metadata.permissions())
+    } else if metadata.is_symlink() {
+        copy_symlink(&fs::read_link(from)?, to)
+    } else {
+        fs::copy(from, to).map(|_| ())
+    }
+}
+
+#[cfg(unix)]
+fn copy_symlink(target: &Path, to: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(target, to)
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(_target: &Path, _to: &Path) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "cannot copy a symbolic link",
+    ))
+}
+
+/// Remove the entry at `path`, with its contents if it is a folder.
+fn remove_entry(path: &Path) -> io::Result<()> {
+    if path.symlink_metadata()?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// Drop the paths that lie inside another of `paths`, keeping order.
+fn outermost(paths: &[PathBuf]) -> Vec<&PathBuf> {
+    paths
+        .iter()
+        .filter(|path| {
+            !paths
+                .iter()
+                .any(|other| other != *path && path.starts_with(other))
+        })
+        .collect()
+}
+
+/// Report an `AlreadyExists` I/O error as a conflict.
+fn conflict_or_io(path: &Path, error: io::Error) -> FileOperationError {
+    match error.kind() {
+        io::ErrorKind::AlreadyExists => FileOperationError::AlreadyExists(path.to_path_buf()),
+        _ => FileOperationError::io(path, error),
+    }
+}
+
+/// Percent-encode a path for the `Path=` key of a `.trashinfo` file.
+fn escape_path(path: &Path) -> String {
+    let mut escaped = String::new();
+    for &byte in path.to_string_lossy().as_bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-_.~".contains(&byte) {
+            escaped.push(char::from(byte));
+        } else {
+            escaped.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    escaped
+}
+
+/// Format `time` as `YYYY-MM-DDThh:mm:ss` (UTC) for a `.trashinfo` file.
+fn deletion_date(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let (days, rest) = (seconds / 86_400, seconds % 86_400);
+    // Civil-from-days conversion (Howard Hinnant's algorithm)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}",
+        rest / 3_600,
+        rest % 3_600 / 60,
+        rest % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cli_ide_base::test_support;
+    use std::time::Duration;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        fs::canonicalize(test_support::temp_dir(name)).unwrap()
+    }
+
+    #[test]
+    fn test_validate_name() {
+        assert!(validate_name("main.rs").is_ok());
+        for name in ["", " ", ".", "..", "a/b", "a\0b"] {
+            assert_eq!(
+                validate_name(name),
+                Err(FileOperationError::InvalidName(name.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_create_file_and_folder_refuse_conflicts() {
+        let dir = temp_dir("ops-create");
+        let operations = FileOperations::with_trash_dir(dir.join("trash"));
+        let events = operations.on_did_run().subscribe();
+
+        let file = operations.create_file(&dir, "a.txt").unwrap();
+        let folder = operations.create_folder(&dir, "src").unwrap();
+        assert!(file.is_file());
+        assert!(folder.is_dir());
+        assert_eq!(
+            operations.create_folder(&dir, "a.txt"),
+            Err(FileOperationError::AlreadyExists(file.clone()))
+        );
+
+        let received: Vec<FileOperationEvent> = events.try_iter().collect();
+        assert_eq!(
+            received,
+            vec![
+                FileOperationEvent::Created(file),
+                FileOperationEvent::Created(folder)
+            ]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_rename_checks_conflicts() {
+        let dir = temp_dir("ops-rename");
+        let operations = FileOperations::with_trash_dir(dir.join("trash"));
+        let old = operations.create_file(&dir, "old.txt").unwrap();
+        operations.create_file(&dir, "taken.txt").unwrap();
+        let events = operations.on_did_run().subscribe();
+
+        assert_eq!(
+            operations.rename(&old, "taken.txt"),
+            Err(FileOperationError::AlreadyExists(dir.join("taken.txt")))
+        );
+        let new = operations.rename(&old, "new.txt").unwrap();
+        assert!(new.is_file());
+        assert!(!old.exists());
+        assert_eq!(
+            events.try_recv().unwrap(),
+            FileOperationEvent::Renamed {
+                from: old.clone(),
+                to: new
+            }
+        );
+        assert_eq!(
+            operations.rename(&old, "x"),
+            Err(FileOperationError::NotFound(old))
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_move_into_refuses_conflicts_and_own_subfolders() {
+        let dir = temp_dir("ops-move");
+        let operations = FileOperations::with_trash_dir(dir.join("trash"));
+        let src = operations.create_folder(&dir, "src").unwrap();
+        let nested = operations.create_folder(&src, "nested").unwrap();
+        let file = operations.create_file(&dir, "a.txt").unwrap();
+        operations.create_file(&nested, "a.txt").unwrap();
+        let events = operations.on_did_run().subscribe();
+
+        assert_eq!(
+            operations.move_into(&src, &nested),
+            Err(FileOperationError::IntoItself(src.clone()))
+        );
+        assert_eq!(
+            operations.move_into(&file, &nested),
+            Err(FileOperationError::AlreadyExists(nested.join("a.txt")))
+        );
+        let moved = operations.move_into(&file, &src).unwrap();
+        assert_eq!(moved, src.join("a.txt"));
+        assert!(moved.is_file());
+        assert_eq!(
+            events.try_recv().unwrap(),
+            FileOperationEvent::Renamed {
+                from: file,
+                to: moved
+            }
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_bulk_operations_skip_nested_paths_and_continue_past_errors() {
+        let dir = temp_dir("ops-bulk");
+        let operations = FileOperations::with_trash_dir(dir.join("trash"));
+        let target = operations.create_folder(&dir, "target").unwrap();
+        let src = operations.create_folder(&dir, "src").unwrap();
+        let inner = operations.create_file(&src, "lib.rs").unwrap();
+        let file = operations.create_file(&dir, "a.txt").unwrap();
+        let missing = dir.join("missing.txt");
+
+        let results = operations.move_all(&[inner, src.clone(), missing.clone(), file], &target);
+        assert_eq!(
+            results,
+            vec![
+                Ok(target.join("src")),
+                Err(FileOperationError::NotFound(missing)),
+                Ok(target.join("a.txt")),
+            ]
+        );
+        assert!(target.join("src").join("lib.rs").is_file());
+
+        let results = operations.delete_all(&[target.join("src").join("lib.rs"), target.clone()]);
+        assert_eq!(results.len(), 1);
+        assert!(results[0].is_ok());
+        assert!(!target.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_delete_moves_to_trash_with_info() {
+        let dir = temp_dir("ops-delete");
+        let operations = FileOperations::with_trash_dir(dir.join("trash"));
+        let first = operations.create_file(&dir, "gone.txt").unwrap();
+        fs::write(&first, "bye").unwrap();
+
+        let trashed = operations.delete(&first).unwrap();
+        assert!(!first.exists());
+        assert_eq!(fs::read_to_string(&trashed).unwrap(), "bye");
+        let info = fs::read_to_string(dir.join("trash/info/gone.txt.trashinfo")).unwrap();
+        assert!(info.starts_with("[Trash Info]\n"));
+        assert!(info.contains(&format!("Path={}\n", escape_path(&first))));
+
+        // A second entry with the same name gets a unique trash name
+        operations.create_file(&dir, "gone.txt").unwrap();
+        let again = operations.delete(&first).unwrap();
+        assert_eq!(again, dir.join("trash/files/gone.txt.1"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_delete_symlink_trashes_link_not_target() {
+        let dir = temp_dir("ops-symlink");
+        let operations = FileOperations::with_trash_dir(dir.join("trash"));
+        let target = operations.create_file(&dir, "target.txt").unwrap();
+        std::os::unix::fs::symlink(&target, dir.join("link")).unwrap();
+
+        let trashed = operations.delete(&dir.join("link")).unwrap();
+        assert_eq!(trashed, dir.join("trash/files/link"));
+        assert!(target.is_file());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_move_entry_copies_across_file_systems() {
+        let dir = temp_dir("ops-cross-device");
+        let _cleanup = test_support::Cleanup::new([&dir]);
+        let crosses = |_: &Path, _: &Path| Err(io::Error::from(io::ErrorKind::CrossesDevices));
+        let folder = dir.join("src");
+        fs::create_dir_all(folder.join("nested")).unwrap();
+        fs::write(folder.join("nested/lib.rs"), "fn main() {}").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink("nested/lib.rs", folder.join("link")).unwrap();
+
+        let trashed = dir.join("trashed");
+        move_entry(&folder, &trashed, crosses).unwrap();
+        assert!(!folder.exists());
+        assert_eq!(
+            fs::read_to_string(trashed.join("nested/lib.rs")).unwrap(),
+            "fn main() {}"
+        );
+        #[cfg(unix)]
+        assert_eq!(
+            fs::read_link(trashed.join("link")).unwrap(),
+            Path::new("nested/lib.rs")
+        );
+
+        // A failed copy names the entry, is cleaned up and keeps the original
+        let file = dir.join("kept.txt");
+        fs::write(&file, "kept").unwrap();
+        let error = move_entry(&file, &dir.join("missing/kept.txt"), crosses).unwrap_err();
+        let FileOperationError::Io { path, message } = &error else {
+            panic!("expected an I/O error, got {error:?}");
+        };
+        assert_eq!(path, &file);
+        assert!(message.starts_with("could not copy it to another file system"));
+        assert!(error.to_string().starts_with(&file.display().to_string()));
+        assert_eq!(fs::read_to_string(&file).unwrap(), "kept");
+    }
+
+    #[test]
+    fn test_delete_without_trash_fails() {
+        let dir = temp_dir("ops-no-trash");
+        let operations = FileOperations {
+            trash_dir: None,
+            on_did_run: Event::new(),
+        };
+        let file = operations.create_file(&dir, "kept.txt").unwrap();
+        assert_eq!(operations.delete(&file), Err(FileOperationError::NoTrash));
+        assert!(file.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_trash_info_formatting() {
+        assert_eq!(escape_path(Path::new("/a b/ü.rs")), "/a%20b/%C3%BC.rs");
+        let time = UNIX_EPOCH + Duration::from_secs(951_782_400 + 3_661);
+        assert_eq!(deletion_date(time), "2000-02-29T01:01:01");
+    }
+}
@@ -0,0 +1,207 @@
+//! Polling file watcher.
+//!
+//! The `FileWatcher` checks a single file's modification time and size on a
+//! background thread and emits a [`FileChanged`] event whenever either
+//! changes, or the file is created or removed. Polling keeps the watcher
+//! free of platform-specific notification APIs; the interval trades latency
+//! for wake-ups.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
+
+use cli_ide_base::Event;
+
+/// How a watched file changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileChangeKind {
+    /// The file appeared.
+    Created,
+    /// The file's contents or metadata changed.
+    Modified,
+    /// The file disappeared.
+    Removed,
+}
+
+/// Emitted by [`FileWatcher`] when the watched file changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChanged {
+    /// The watched path.
+    pub path: PathBuf,
+    /// What happened to it.
+    pub kind: FileChangeKind,
+}
+
+/// The observable state of a file: modification time and size, or `None`
+/// if it does not exist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileStamp(Option<(SystemTime, u64)>);
+
+impl FileStamp {
+    /// Read the current stamp of `path`.
+    pub fn of(path: &Path) -> Self {
+        let stamp = fs::metadata(path).ok().map(|metadata| {
+            (
+                metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                metadata.len(),
+            )
+        });
+        Self(stamp)
+    }
+
+    /// Describe how the file changed between `self` and a later stamp.
+    ///
+    /// Returns `None` if the stamps are equal.
+    pub fn change_to(&self, later: &FileStamp) -> Option<FileChangeKind> {
+        match (self.0, later.0) {
+            (None, None) => None,
+            (None, Some(_)) => Some(FileChangeKind::Created),
+            (Some(_), None) => Some(FileChangeKind::Removed),
+            (Some(before), Some(after)) => (before != after).then_some(FileChangeKind::Modified),
+        }
+    }
+}
+
+/// Watches one file by polling it on a background thread.
+///
+/// Dropping the watcher (or calling [`dispose`](FileWatcher::dispose))
+/// stops the thread.
+pub struct FileWatcher {
+    /// The watched path.
+    path: PathBuf,
+    /// Fired from the polling thread when the file changes.
+    on_did_change: Event<FileChanged>,
+    /// Set to stop the polling thread.
+    stop: Arc<AtomicBool>,
+    /// The polling thread, until disposed.
+    thread: Option<JoinHandle<()>>,
+}
+
+impl FileWatcher {
+    /// Start watching `path`, checking it every `interval`.
+    ///
+    /// The file does not need to exist yet.
+    pub fn new(path: impl Into<PathBuf>, interval: Duration) -> Self {
+        let path = path.into();
+        let on_did_change = Event::new();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let thread = {
+            let path = path.clone();
+            let event = on_did_change.clone();
+            let stop = Arc::clone(&stop);
+            let mut stamp = FileStamp::of(&path);
+            thread::spawn(move || loop {
+                thread::park_timeout(interval);
+                if stop.load(Ordering::SeqCst) {
+                    break;
+                }
+                let current = FileStamp::of(&path);
+                if let Some(kind) = stamp.change_to(&current) {
+                    event.emit(FileChanged {
+                        path: path.clone(),
+                        kind,
+                    });
+                }
+                stamp = current;
+            })
+        };
+
+        Self {
+            path,
+            on_did_change,
+            stop,
+            thread: Some(thread),
+        }
+    }
+
+    /// Get the watched path.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Event fired when the watched file changes.
+    ///
+    /// Values are sent from the polling thread.
+    pub fn on_did_change(&self) -> &Event<FileChanged> {
+        &self.on_did_change
+    }
+
+    /// Stop watching. Safe to call more than once.
+    pub fn dispose(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            thread.thread().unpark();
+            let _ = thread.join();
+        }
+    }
+
+    /// Check whether the watcher has been disposed.
+    pub fn is_disposed(&self) -> bool {
+        self.thread.is_none()
+    }
+}
+
+impl Drop for FileWatcher {
+    fn drop(&mut self) {
+        self.dispose();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cli_ide_base::test_support::temp_path;
+
+    #[test]
+    fn test_stamp_changes() {
+        let path = temp_path("stamp.txt");
+        let missing = FileStamp::of(&path);
+        assert_eq!(missing.change_to(&missing), None);
+
+        fs::write(&path, "a").unwrap();
+        let created = FileStamp::of(&path);
+        assert_eq!(missing.change_to(&created), Some(FileChangeKind::Created));
+
+        fs::write(&path, "abc").unwrap();
+        let modified = FileStamp::of(&path);
+        assert_eq!(created.change_to(&modified), Some(FileChangeKind::Modified));
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(
+            modified.change_to(&FileStamp::of(&path)),
+            Some(FileChangeKind::Removed)
+        );
+    }
+
+    #[test]
+    fn test_watcher_reports_modification() {
+        let path = temp_path("watched.txt");
+        fs::write(&path, "one").unwrap();
+        let watcher = FileWatcher::new(&path, Duration::from_millis(5));
+        let changes = watcher.on_did_change().subscribe();
+
+        fs::write(&path, "two two").unwrap();
+        let change = changes.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(
+            change,
+            FileChanged {
+                path: path.clone(),
+                kind: FileChangeKind::Modified
+            }
+        );
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_dispose_stops_thread() {
+        let mut watcher = FileWatcher::new(temp_path("disposed.txt"), Duration::from_secs(60));
+        assert!(!watcher.is_disposed());
+        watcher.dispose();
+        watcher.dispose();
+        assert!(watcher.is_disposed());
+    }
+}
@@ -0,0 +1,585 @@
+//! `.gitignore`/`.ignore` awareness.
+//!
+//! [`IgnoreRules`] parses one ignore file. An [`IgnoreMatcher`] stacks the
+//! rules of a directory and its ancestors the way git does: rules in deeper
+//! directories take precedence, and within a file the last matching pattern
+//! wins, so `!pattern` can re-include something an earlier line excluded.
+//! `.ignore` rules are applied after `.gitignore` rules in the same
+//! directory, and `.git` itself is always ignored.
+//!
+//! Below the workspace's own ignore files come, as in git, the repository's
+//! `.git/info/exclude` and then the user's global excludes file
+//! ([`global_excludes_file`]): `core.excludesFile` from the git
+//! configuration, or `$XDG_CONFIG_HOME/git/ignore`.
+//!
+//! [`walk_files`] lists the files of a workspace, skipping ignored entries
+//! and never descending into ignored directories.
+//!
+//! Known gaps compared to git:
+//!
+//! - Only the entry itself is matched. A path inside an ignored directory
+//!   is not reported ignored unless a pattern matches it too, and
+//!   `!dir/file` re-includes it when asked directly, where git keeps a file
+//!   of an excluded directory excluded. Walkers get git's behaviour by not
+//!   descending into ignored directories; callers checking a single path
+//!   must check its ancestors.
+//! - Character classes support ranges and `!`/`^` negation, but not POSIX
+//!   classes such as `[[:digit:]]`.
+//! - Trailing spaces are always trimmed, even when escaped with `\`.
+//! - Matching is case-sensitive; `core.ignoreCase` is not read.
+//! - `core.excludesFile` is only read from the user's configuration, not
+//!   from the repository's `.git/config` or files it includes.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Ignore files read in each directory, in increasing precedence.
+pub const IGNORE_FILES: &[&str] = &[".gitignore", ".ignore"];
+
+/// Entry names that are always ignored.
+const ALWAYS_IGNORED: &[&str] = &[".git"];
+
+/// One line of an ignore file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct IgnorePattern {
+    /// The glob, without `!`, leading `/` or trailing `/`.
+    glob: Vec<char>,
+    /// Whether the pattern re-includes matches (`!pattern`).
+    negated: bool,
+    /// Whether the pattern only matches directories (`pattern/`).
+    dir_only: bool,
+    /// Whether the pattern matches the whole relative path rather than the
+    /// name at any depth.
+    anchored: bool,
+}
+
+impl IgnorePattern {
+    /// Parse a line, returning `None` for blank lines and comments.
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let anchored = line.contains('/');
+        let line = line.strip_prefix('/').unwrap_or(line);
+        if line.is_empty() {
+            return None;
+        }
+        Some(Self {
+            glob: line.chars().collect(),
+            negated,
+            dir_only,
+            anchored,
+        })
+    }
+
+    /// Check whether the pattern matches `relative` (`/`-separated).
+    fn matches(&self, relative: &str, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        let subject = if self.anchored {
+            relative
+        } else {
+            relative.rsplit('/').next().unwrap_or(relative)
+        };
+        glob_match(&self.glob, &subject.chars().collect::<Vec<_>>())
+    }
+}
+
+/// Match `text` against a gitignore-style glob.
+///
+/// `*` and `?` do not match `/`; `**` matches across directories and
+/// `[...]` matches a character class.
+fn glob_match(pattern: &[char], text: &[char]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        ['*', '*', rest @ ..] => {
+            if let ['/', after @ ..] = rest {
+                // `**/` matches zero or more whole directories
+                (0..=text.len())
+                    .filter(|&i| i == 0 || text[i - 1] == '/')
+                    .any(|i| glob_match(after, &text[i..]))
+            } else {
+                (0..=text.len()).any(|i| glob_match(rest, &text[i..]))
+            }
+        }
+        ['*', rest @ ..] => (0..=text.len())
+            .take_while(|&i| i == 0 || text[i - 1] != '/')
+            .any(|i| glob_match(rest, &text[i..])),
+        ['?', rest @ ..] => match text {
+            [c, text_rest @ ..] if *c != '/' => glob_match(rest, text_rest),
+            _ => false,
+        },
+        ['[', rest @ ..] => match class_end(rest) {
+            Some(end) => match text {
+                [c, text_rest @ ..] if *c != '/' && class_matches(&rest[..end], *c) => {
+                    glob_match(&rest[end + 1..], text_rest)
+                }
+                _ => false,
+            },
+            None => literal_match('[', rest, text),
+        },
+        ['\\', escaped, rest @ ..] => literal_match(*escaped, rest, text),
+        [c, rest @ ..] => literal_match(*c, rest, text),
+    }
+}
+
+/// Match a literal character, then the rest of the pattern.
+fn literal_match(c: char, rest: &[char], text: &[char]) -> bool {
+    match text {
+        [first, text_rest @ ..] if *first == c => glob_match(rest, text_rest),
+        _ => false,
+    }
+}
+
+/// Index of the `]` closing a character class that starts at `class`.
+fn class_end(class: &[char]) -> Option<usize> {
+    // A `]` right after `[` or `[!` is part of the class
+    let start = usize::from(matches!(class.first(), Some('!' | '^')));
+    (start + 1..class.len()).find(|&i| class[i] == ']')
+}
+
+/// Check whether `c` is in a character class such as `a-z_` or `!0-9`.
+fn class_matches(class: &[char], c: char) -> bool {
+    let (negated, class) = match class {
+        ['!' | '^', rest @ ..] => (true, rest),
+        _ => (false, class),
+    };
+    let mut found = false;
+    let mut i = 0;
+    while i < class.len() {
+        if i + 2 < class.len() && class[i + 1] == '-' {
+            found |= (class[i]..=class[i + 2]).contains(&c);
+            i += 3;
+        } else {
+            found |= class[i] == c;
+            i += 1;
+        }
+    }
+    found != negated
+}
+
+/// The patterns of the ignore files in one directory.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IgnoreRules {
+    /// Directory the patterns are relative to.
+    base: PathBuf,
+    /// Patterns in file order.
+    patterns: Vec<IgnorePattern>,
+}
+
+impl IgnoreRules {
+    /// Parse ignore file contents whose patterns are relative to `base`.
+    pub fn parse(base: impl Into<PathBuf>, text: &str) -> Self {
+        Self {
+            base: base.into(),
+            patterns: text.lines().filter_map(IgnorePattern::parse).collect(),
+        }
+    }
+
+    /// Read the ignore file at `path`, whose patterns are relative to
+    /// `base`. A missing file contributes no rules.
+    pub fn from_file(base: impl Into<PathBuf>, path: &Path) -> Self {
+        Self::parse(base, &fs::read_to_string(path).unwrap_or_default())
+    }
+
+    /// Read the [`IGNORE_FILES`] in `dir`. Missing files contribute no
+    /// rules.
+    pub fn from_dir(dir: &Path) -> Self {
+        let text: Vec<String> = IGNORE_FILES
+            .iter()
+            .filter_map(|name| fs::read_to_string(dir.join(name)).ok())
+            .collect();
+        Self::parse(dir, &text.join("\n"))
+    }
+
+    /// Get the directory the patterns are relative to.
+    pub fn base(&self) -> &Path {
+        &self.base
+    }
+
+    /// Check whether there are no patterns.
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Decide whether `path` is ignored by these rules.
+    ///
+    /// Returns `Some(true)` if the last matching pattern ignores it,
+    /// `Some(false)` if it re-includes it and `None` if no pattern matches
+    /// or `path` is outside the base directory.
+    pub fn matched(&self, path: &Path, is_dir: bool) -> Option<bool> {
+        let relative = path.strip_prefix(&self.base).ok()?;
+        let relative = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        self.patterns
+            .iter()
+            .rev()
+            .find(|pattern| pattern.matches(&relative, is_dir))
+            .map(|pattern| !pattern.negated)
+    }
+}
+
+/// The ignore rules in effect for a directory: its own and its ancestors'
+/// up to a root.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IgnoreMatcher {
+    /// Rules from the root down, deepest last.
+    layers: Vec<IgnoreRules>,
+}
+
+impl IgnoreMatcher {
+    /// A matcher with the rules of `root`, over those of the repository's
+    /// `.git/info/exclude` and the [global excludes
+    /// file](global_excludes_file).
+    pub fn new(root: &Path) -> Self {
+        Self::with_excludes(root, global_excludes_file().as_deref())
+    }
+
+    /// A matcher with the rules of `root`, over those of the repository's
+    /// `.git/info/exclude` and of `excludes_file`, if given.
+    ///
+    /// Patterns in both exclude files are relative to the top of the
+    /// repository holding `root`, or to `root` outside a repository.
+    pub fn with_excludes(root: &Path, excludes_file: Option<&Path>) -> Self {
+        let top = root
+            .ancestors()
+            .find(|dir| dir.join(".git").is_dir())
+            .unwrap_or(root);
+        let mut layers = Vec::new();
+        let files = [
+            excludes_file.map(Path::to_path_buf),
+            Some(top.join(".git").join("info").join("exclude")),
+        ];
+        for file in files.iter().flatten() {
+            let rules = IgnoreRules::from_file(top, file);
+            if !rules.is_empty() {
+                layers.push(rules);
+            }
+        }
+        Self { layers }.with_dir(root)
+    }
+
+    /// A matcher for `dir`, with the rules of every directory from `root`
+    /// down to it.
+    pub fn for_dir(root: &Path, dir: &Path) -> Self {
+        let mut matcher = Self::new(root);
+        if let Ok(relative) = dir.strip_prefix(root) {
+            let mut current = root.to_path_buf();
+            for component in relative.components() {
+                current.push(component);
+                matcher = matcher.with_dir(&current);
+            }
+        }
+        matcher
+    }
+
+    /// The matcher for a subdirectory: these rules plus the ones in `dir`.
+    pub fn with_dir(&self, dir: &Path) -> Self {
+        let mut layers = self.layers.clone();
+        let rules = IgnoreRules::from_dir(dir);
+        if !rules.is_empty() {
+            layers.push(rules);
+        }
+        Self { layers }
+    }
+
+    /// Check whether the entry at `path` is ignored.
+    ///
+    /// Only the entry itself is checked; walkers skip the contents of
+    /// ignored directories.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let always = path
+            .file_name()
+            .is_some_and(|name| ALWAYS_IGNORED.iter().any(|ignored| name == *ignored));
+        always
+            || self
+                .layers
+                .iter()
+                .rev()
+                .find_map(|rules| rules.matched(path, is_dir))
+                .unwrap_or(false)
+    }
+}
+
+/// The user's global excludes file: `core.excludesFile` from
+/// `$XDG_CONFIG_HOME/git/config` or `~/.gitconfig` (the latter winning, as
+/// in git), or `$XDG_CONFIG_HOME/git/ignore` if it is not set.
+///
+/// `$XDG_CONFIG_HOME` defaults to `~/.config`. Returns `None` if neither
+/// it nor `$HOME` is set. The file need not exist.
+pub fn global_excludes_file() -> Option<PathBuf> {
+    let home = env::var_os("HOME").map(PathBuf::from);
+    let config_home = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home.as_ref().map(|home| home.join(".config")));
+    let git_configs = [
+        config_home
+            .as_ref()
+            .map(|dir| dir.join("git").join("config")),
+        home.as_ref().map(|home| home.join(".gitconfig")),
+    ];
+    let configured = git_configs
+        .iter()
+        .rev()
+        .flatten()
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .find_map(|text| excludes_file_setting(&text));
+    match configured {
+        Some(path) => match (path.strip_prefix("~/"), &home) {
+            (Some(rest), Some(home)) => Some(home.join(rest)),
+            _ => Some(PathBuf::from(path)),
+        },
+        None => config_home.map(|dir| dir.join("git").join("ignore")),
+    }
+}
+
+/// The `core.excludesFile` setting of git configuration `text`, if set.
+///
+/// Section and key names are case-insensitive, and the last setting wins.
+fn excludes_file_setting(text: &str) -> Option<String> {
+    let mut in_core = false;
+    let mut setting = None;
+    for line in text.lines().map(str::trim) {
+        if let Some(section) = line.strip_prefix('[') {
+            in_core = section
+                .trim_end_matches(']')
+                .trim()
+                .eq_ignore_ascii_case("core");
+        } else if let Some((key, value)) = line.split_once('=').filter(|_| in_core) {
+            if key.trim().eq_ignore_ascii_case("excludesfile") {
+                setting = Some(value.trim().trim_matches('"').to_string());
+            }
+        }
+    }
+    setting
+}
+
+/// List the files under `root` that are not ignored, in path order.
+///
+/// Ignored directories are not entered and symbolic links are not
+/// followed. Stops after `limit` files.
+pub fn walk_files(root: &Path, limit: usize) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![(root.to_path_buf(), IgnoreMatcher::new(root))];
+    while let Some((dir, matcher)) = pending.pop() {
+        let mut entries = fs::read_dir(&dir)?
+            .filter_map(Result::ok)
+            .map(|entry| {
+                let is_dir = entry.file_type().is_ok_and(|kind| kind.is_dir());
+                (entry.path(), is_dir)
+            })
+            .collect::<Vec<_>>();
+        entries.sort();
+        // Subdirectories are pushed in reverse so they pop in path order
+        let mut subdirs = Vec::new();
+        for (path, is_dir) in entries {
+            if matcher.is_ignored(&path, is_dir) {
+                continue;
+            }
+            if is_dir {
+                subdirs.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+        for subdir in subdirs.into_iter().rev() {
+            let child = matcher.with_dir(&subdir);
+            pending.push((subdir, child));
+        }
+        if files.len() >= limit {
+            break;
+        }
+    }
+    files.sort();
+    files.truncate(limit);
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cli_ide_base::test_support::temp_dir;
+
+    fn glob(pattern: &str, text: &str) -> bool {
+        glob_match(
+            &pattern.chars().collect::<Vec<_>>(),
+            &text.chars().collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob("*.rs", "main.rs"));
+        assert!(!glob("*.rs", "src/main.rs"));
+        assert!(glob("src/**/*.rs", "src/main.rs"));
+        assert!(glob("src/**/*.rs", "src/a/b/main.rs"));
+        assert!(glob("target/**", "target/debug/x"));
+        assert!(!glob("target/**", "target"));
+        assert!(glob("file?.[ch]", "file1.c"));
+        assert!(!glob("file?.[!ch]", "file1.c"));
+        assert!(glob("[a-c]x", "bx"));
+        assert!(glob("\\#notes", "#notes"));
+    }
+
+    #[test]
+    fn test_rules_last_match_wins_and_negation() {
+        let rules = IgnoreRules::parse("/repo", "# logs\n*.log\n!keep.log\nbuild/\n/top.txt\n");
+        let check = |path: &str, is_dir| rules.matched(Path::new(path), is_dir);
+        assert_eq!(check("/repo/a/debug.log", false), Some(true));
+        assert_eq!(check("/repo/a/keep.log", false), Some(false));
+        assert_eq!(check("/repo/x/build", true), Some(true));
+        assert_eq!(
+            check("/repo/x/build", false),
+            None,
+            "build/ only matches dirs"
+        );
+        assert_eq!(check("/repo/top.txt", false), Some(true));
+        assert_eq!(
+            check("/repo/sub/top.txt", false),
+            None,
+            "/top.txt is anchored"
+        );
+        assert_eq!(check("/elsewhere/debug.log", false), None);
+    }
+
+    #[test]
+    fn test_double_star_slash_matches_any_depth() {
+        let rules = IgnoreRules::parse("/repo", "**/logs\na/**/b\n**/cache/\n");
+        let check = |path: &str, is_dir| rules.matched(Path::new(path), is_dir);
+        assert_eq!(check("/repo/logs", false), Some(true));
+        assert_eq!(check("/repo/x/y/logs", true), Some(true));
+        assert_eq!(check("/repo/a/b", false), Some(true));
+        assert_eq!(check("/repo/a/x/y/b", false), Some(true));
+        assert_eq!(check("/repo/x/a/b", false), None, "a/**/b is anchored");
+        assert_eq!(check("/repo/x/cache", true), Some(true));
+        assert_eq!(check("/repo/x/cache", false), None);
+    }
+
+    #[test]
+    fn test_negation_in_a_subdirectory() {
+        let root = temp_dir("ignore-negation");
+        fs::create_dir_all(root.join("sub/deeper")).unwrap();
+        fs::write(root.join(".gitignore"), "*.log\nbuild/\n").unwrap();
+        fs::write(root.join("sub/.gitignore"), "!/keep.log\n!build/\n").unwrap();
+
+        let sub = IgnoreMatcher::for_dir(&root, &root.join("sub"));
+        assert!(!sub.is_ignored(&root.join("sub/keep.log"), false));
+        assert!(!sub.is_ignored(&root.join("sub/build"), true));
+        assert!(sub.is_ignored(&root.join("sub/other.log"), false));
+        let deeper = IgnoreMatcher::for_dir(&root, &root.join("sub/deeper"));
+        assert!(
+            deeper.is_ignored(&root.join("sub/deeper/keep.log"), false),
+            "/keep.log is anchored to sub"
+        );
+        let top = IgnoreMatcher::new(&root);
+        assert!(top.is_ignored(&root.join("keep.log"), false));
+        assert!(top.is_ignored(&root.join("build"), true));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_info_exclude_and_global_excludes_sit_below_ignore_files() {
+        let repo = temp_dir("ignore-excludes");
+        let global = temp_dir("ignore-excludes-global").join("ignore");
+        fs::create_dir_all(repo.join(".git/info")).unwrap();
+        fs::create_dir_all(repo.join("app")).unwrap();
+        fs::write(repo.join(".git/info/exclude"), "*.local\n/app/secret\n").unwrap();
+        fs::write(&global, "*.swp\n*.bak\n").unwrap();
+        fs::write(repo.join("app/.gitignore"), "!wanted.bak\n").unwrap();
+
+        let matcher = IgnoreMatcher::with_excludes(&repo.join("app"), Some(&global));
+        let app = repo.join("app");
+        assert!(matcher.is_ignored(&app.join("notes.local"), false));
+        assert!(matcher.is_ignored(&app.join("secret"), false));
+        assert!(matcher.is_ignored(&app.join(".main.rs.swp"), false));
+        assert!(matcher.is_ignored(&app.join("old.bak"), false));
+        assert!(!matcher.is_ignored(&app.join("wanted.bak"), false));
+        assert!(!matcher.is_ignored(&app.join("main.rs"), false));
+        fs::remove_dir_all(&repo).unwrap();
+        fs::remove_dir_all(global.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_excludes_file_setting() {
+        let config = "[user]\n\tname = A\n[Core]\n\texcludesFile = ~/.gitignore_global\n\
+                      [alias]\n\texcludesfile = no\n";
+        assert_eq!(
+            excludes_file_setting(config),
+            Some("~/.gitignore_global".to_string())
+        );
+        assert_eq!(
+            excludes_file_setting("[core]\nexcludesfile = \"/a b\"\n"),
+            Some("/a b".to_string())
+        );
+        assert_eq!(excludes_file_setting("[core]\nautocrlf = true\n"), None);
+    }
+
+    #[test]
+    fn test_deeper_rules_take_precedence() {
+        let root = temp_dir("ignore-layers");
+        fs::create_dir_all(root.join("sub")).unwrap();
+        fs::write(root.join(".gitignore"), "*.tmp\n").unwrap();
+        fs::write(root.join("sub/.ignore"), "!wanted.tmp\n").unwrap();
+
+        let matcher = IgnoreMatcher::for_dir(&root, &root.join("sub"));
+        assert!(matcher.is_ignored(&root.join("sub/other.tmp"), false));
+        assert!(!matcher.is_ignored(&root.join("sub/wanted.tmp"), false));
+        assert!(matcher.is_ignored(&root.join(".git"), true));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_walk_files_skips_ignored_entries() {
+        let root = temp_dir("ignore-walk");
+        for dir in ["src", "target/debug", ".git", "docs"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        for file in [
+            "src/main.rs",
+            "target/debug/app",
+            ".git/HEAD",
+            "docs/a.md",
+            "x.log",
+        ] {
+            fs::write(root.join(file), "").unwrap();
+        }
+        fs::write(
+            root.join(".gitignore"),
+            "target/\n!target/debug/app\n*.log\n",
+        )
+        .unwrap();
+
+        let files = walk_files(&root, usize::MAX).unwrap();
+        assert_eq!(
+            files,
+            vec![
+                root.join(".gitignore"),
+                root.join("docs/a.md"),
+                root.join("src/main.rs"),
+            ]
+        );
+        assert_eq!(walk_files(&root, 1).unwrap().len(), 1);
+
+        // A file of an excluded directory stays excluded while walking, even
+        // though asking for it alone finds the pattern re-including it
+        let app = root.join("target/debug/app");
+        assert!(!IgnoreMatcher::with_excludes(&root, None).is_ignored(&app, false));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
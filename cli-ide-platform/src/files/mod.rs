@@ -0,0 +1,5 @@
+//! File system services.
+
+pub mod file_operations;
+pub mod file_watcher;
+pub mod ignore;
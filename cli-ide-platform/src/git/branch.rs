@@ -0,0 +1,88 @@
+//! Branches as listed by `git for-each-ref`.
+//!
+//! [`Repository::branches`](super::repository::Repository::branches) lists
+//! the local and remote-tracking [`Branch`]es, which
+//! [`Repository::checkout`](super::repository::Repository::checkout)
+//! switches to.
+
+/// The `--format` of `git for-each-ref` that [`parse_branches`] reads.
+pub(super) const BRANCH_FORMAT: &str = "--format=%(HEAD)%1f%(refname)%1f%(refname:short)";
+
+/// A local or remote-tracking branch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Branch {
+    /// Short name, e.g. `main` or `origin/main`.
+    pub name: String,
+    /// Whether this is a remote-tracking branch.
+    pub remote: bool,
+    /// Whether `HEAD` points at this branch.
+    pub current: bool,
+}
+
+impl Branch {
+    /// The name of the local branch checking this branch out creates:
+    /// the name without the remote for remote-tracking branches.
+    pub fn local_name(&self) -> &str {
+        if self.remote {
+            self.name
+                .split_once('/')
+                .map_or(self.name.as_str(), |(_, name)| name)
+        } else {
+            &self.name
+        }
+    }
+}
+
+/// Parse branches printed with [`BRANCH_FORMAT`], local branches first.
+///
+/// Symbolic refs such as `origin/HEAD` are skipped.
+pub(super) fn parse_branches(output: &str) -> Vec<Branch> {
+    let mut branches: Vec<Branch> = output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\x1f');
+            let head = fields.next()?;
+            let refname = fields.next()?;
+            let name = fields.next()?;
+            let remote = refname.starts_with("refs/remotes/");
+            if remote && refname.ends_with("/HEAD") {
+                return None;
+            }
+            Some(Branch {
+                name: name.to_string(),
+                remote,
+                current: head == "*",
+            })
+        })
+        .collect();
+    branches.sort_by_key(|branch| branch.remote);
+    branches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_branches_lists_local_first_without_symbolic_refs() {
+        let output = "\x1frefs/remotes/origin/HEAD\x1forigin\n\
+                      \x1frefs/remotes/origin/main\x1forigin/main\n\
+                      *\x1frefs/heads/main\x1fmain\n\
+                      \x1frefs/heads/topic\x1ftopic\n";
+        let branches = parse_branches(output);
+        let names: Vec<(&str, bool, bool)> = branches
+            .iter()
+            .map(|branch| (branch.name.as_str(), branch.remote, branch.current))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("main", false, true),
+                ("topic", false, false),
+                ("origin/main", true, false),
+            ]
+        );
+        assert_eq!(branches[2].local_name(), "main");
+        assert_eq!(branches[1].local_name(), "topic");
+    }
+}
@@ -0,0 +1,583 @@
+//! Unified diffs as produced by `git diff`.
+//!
+//! [`FileDiff::parse`] reads the diff of one file into its header and
+//! [`Hunk`]s; [`FileDiff::compare`] computes one between two texts without
+//! git, e.g. for an unsaved buffer. Each hunk can be turned back into a patch of its own with
+//! [`FileDiff::hunk_patch`], which is how single hunks are staged, unstaged
+//! and reverted with `git apply`. [`Hunk::side_by_side`] pairs removed and
+//! added lines for a two-column view, and [`FileDiff::line_changes`] marks
+//! the changed lines of the new side for an editor gutter.
+
+/// Whether a diff line is unchanged, added or removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffLineKind {
+    /// The line is the same on both sides.
+    Context,
+    /// The line only exists on the new side.
+    Added,
+    /// The line only exists on the old side.
+    Removed,
+}
+
+impl DiffLineKind {
+    /// The character starting the line in a unified diff.
+    pub fn prefix(self) -> char {
+        match self {
+            DiffLineKind::Context => ' ',
+            DiffLineKind::Added => '+',
+            DiffLineKind::Removed => '-',
+        }
+    }
+}
+
+/// How a line of the new side of a diff changed, as shown in a gutter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineChangeKind {
+    /// The line is new.
+    Added,
+    /// The line replaces a removed line.
+    Modified,
+    /// Lines were removed below this one, or above the first line.
+    Removed,
+}
+
+/// A changed line of the new side of a diff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineChange {
+    /// One-based line number on the new side.
+    pub line: usize,
+    /// How the line changed.
+    pub kind: LineChangeKind,
+}
+
+/// Lines of unchanged context around the changes of a computed hunk, as
+/// `git diff` shows by default.
+pub const CONTEXT_LINES: usize = 3;
+
+/// One line of a hunk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffLine {
+    /// Whether the line is unchanged, added or removed.
+    pub kind: DiffLineKind,
+    /// Text of the line, without the prefix and line ending.
+    pub text: String,
+    /// One-based line number on the old side, unless the line was added.
+    pub old_line: Option<usize>,
+    /// One-based line number on the new side, unless the line was removed.
+    pub new_line: Option<usize>,
+    /// Whether the line is the last of its file and has no line ending.
+    pub no_newline: bool,
+}
+
+/// A run of changed lines with their surrounding context.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    /// One-based first line on the old side.
+    pub old_start: usize,
+    /// Number of lines on the old side.
+    pub old_len: usize,
+    /// One-based first line on the new side.
+    pub new_start: usize,
+    /// Number of lines on the new side.
+    pub new_len: usize,
+    /// Text after the range header, usually the enclosing function.
+    pub section: String,
+    /// Lines of the hunk in order.
+    pub lines: Vec<DiffLine>,
+}
+
+impl Hunk {
+    /// The `@@ -a,b +c,d @@` line starting the hunk.
+    pub fn header(&self) -> String {
+        let mut header = format!(
+            "@@ -{},{} +{},{} @@",
+            self.old_start, self.old_len, self.new_start, self.new_len
+        );
+        if !self.section.is_empty() {
+            header.push(' ');
+            header.push_str(&self.section);
+        }
+        header
+    }
+
+    /// Number of added and removed lines.
+    pub fn changes(&self) -> (usize, usize) {
+        let count = |kind| self.lines.iter().filter(|line| line.kind == kind).count();
+        (count(DiffLineKind::Added), count(DiffLineKind::Removed))
+    }
+
+    /// The lines as rows of old and new side.
+    ///
+    /// Context lines fill both sides. Removed lines are paired with the
+    /// added lines that follow them; the shorter run is padded with `None`.
+    pub fn side_by_side(&self) -> Vec<(Option<&DiffLine>, Option<&DiffLine>)> {
+        let mut rows = Vec::new();
+        let mut removed = Vec::new();
+        let mut added = Vec::new();
+        for line in &self.lines {
+            match line.kind {
+                DiffLineKind::Removed => {
+                    if !added.is_empty() {
+                        flush(&mut rows, &mut removed, &mut added);
+                    }
+                    removed.push(line);
+                }
+                DiffLineKind::Added => added.push(line),
+                DiffLineKind::Context => {
+                    flush(&mut rows, &mut removed, &mut added);
+                    rows.push((Some(line), Some(line)));
+                }
+            }
+        }
+        flush(&mut rows, &mut removed, &mut added);
+        rows
+    }
+}
+
+/// The changes to one file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileDiff {
+    /// Lines before the first hunk: `diff --git`, modes, `---` and `+++`.
+    pub header: Vec<String>,
+    /// Hunks in file order.
+    pub hunks: Vec<Hunk>,
+}
+
+impl FileDiff {
+    /// Parse the unified diff of a single file.
+    ///
+    /// Lines before the first hunk are kept as the header; anything after
+    /// a second `diff --git` line is ignored. Malformed hunk headers end
+    /// the diff.
+    pub fn parse(text: &str) -> Self {
+        let mut diff = FileDiff::default();
+        let (mut old_line, mut new_line) = (0, 0);
+        for line in text.lines() {
+            if line.starts_with("diff --git ") && !diff.header.is_empty() {
+                break;
+            }
+            if line.starts_with("@@") {
+                let Some(hunk) = parse_hunk_header(line) else {
+                    break;
+                };
+                old_line = hunk.old_start;
+                new_line = hunk.new_start;
+                diff.hunks.push(hunk);
+                continue;
+            }
+            let Some(hunk) = diff.hunks.last_mut() else {
+                diff.header.push(line.to_string());
+                continue;
+            };
+            if line.starts_with('\\') {
+                if let Some(last) = hunk.lines.last_mut() {
+                    last.no_newline = true;
+                }
+                continue;
+            }
+            let (kind, text) = match line.split_at(line.len().min(1)) {
+                ("+", text) => (DiffLineKind::Added, text),
+                ("-", text) => (DiffLineKind::Removed, text),
+                (" ", text) => (DiffLineKind::Context, text),
+                // Some tools drop the space of empty context lines
+                ("", _) => (DiffLineKind::Context, ""),
+                _ => break,
+            };
+            let old = (kind != DiffLineKind::Added).then_some(old_line);
+            let new = (kind != DiffLineKind::Removed).then_some(new_line);
+            old_line += usize::from(old.is_some());
+            new_line += usize::from(new.is_some());
+            hunk.lines.push(DiffLine {
+                kind,
+                text: text.to_string(),
+                old_line: old,
+                new_line: new,
+                no_newline: false,
+            });
+        }
+        diff
+    }
+
+    /// Compare `old` with `new` line by line, grouping the changes into
+    /// hunks with [`CONTEXT_LINES`] of context like `git diff`.
+    ///
+    /// The diff has no header; a missing final line break is not a change.
+    pub fn compare(old: &str, new: &str) -> Self {
+        let old: Vec<&str> = old.lines().collect();
+        let new: Vec<&str> = new.lines().collect();
+        let (mut old_line, mut new_line) = (1, 1);
+        let lines: Vec<DiffLine> = edit_script(&old, &new)
+            .into_iter()
+            .map(|kind| {
+                let text = match kind {
+                    DiffLineKind::Added => new[new_line - 1],
+                    _ => old[old_line - 1],
+                };
+                let line = DiffLine {
+                    kind,
+                    text: text.to_string(),
+                    old_line: (kind != DiffLineKind::Added).then_some(old_line),
+                    new_line: (kind != DiffLineKind::Removed).then_some(new_line),
+                    no_newline: false,
+                };
+                old_line += usize::from(line.old_line.is_some());
+                new_line += usize::from(line.new_line.is_some());
+                line
+            })
+            .collect();
+
+        // Changes closer than twice the context share a hunk
+        let changed: Vec<usize> = (0..lines.len())
+            .filter(|&index| lines[index].kind != DiffLineKind::Context)
+            .collect();
+        let mut ranges: Vec<(usize, usize)> = Vec::new();
+        for index in changed {
+            let start = index.saturating_sub(CONTEXT_LINES);
+            let end = (index + CONTEXT_LINES + 1).min(lines.len());
+            match ranges.last_mut() {
+                Some(last) if start <= last.1 => last.1 = end,
+                _ => ranges.push((start, end)),
+            }
+        }
+
+        let hunks = ranges
+            .into_iter()
+            .map(|(start, end)| {
+                // An empty side starts at the line before it, as in git
+                let side = |number: fn(&DiffLine) -> Option<usize>| {
+                    let before = lines[..start].iter().filter_map(number).count();
+                    let len = lines[start..end].iter().filter_map(number).count();
+                    (if len == 0 { before } else { before + 1 }, len)
+                };
+                let (old_start, old_len) = side(|line| line.old_line);
+                let (new_start, new_len) = side(|line| line.new_line);
+                let lines = lines[start..end].to_vec();
+                Hunk {
+                    old_start,
+                    old_len,
+                    new_start,
+                    new_len,
+                    section: String::new(),
+                    lines,
+                }
+            })
+            .collect();
+        FileDiff {
+            header: Vec::new(),
+            hunks,
+        }
+    }
+
+    /// The changed lines of the new side, in line order.
+    ///
+    /// Added lines that follow removed ones, paired as in
+    /// [`Hunk::side_by_side`], are modified; the rest are added. Removed
+    /// lines without a replacement mark the line before them, or the first
+    /// line when the removal is at the start of the file.
+    pub fn line_changes(&self) -> Vec<LineChange> {
+        let mut changes: Vec<LineChange> = Vec::new();
+        for hunk in &self.hunks {
+            let mut previous = hunk.new_start.saturating_sub(1);
+            for row in hunk.side_by_side() {
+                let change = match row {
+                    (_, Some(new)) if new.kind == DiffLineKind::Context => None,
+                    (Some(_), Some(new)) => new.new_line.map(|line| LineChange {
+                        line,
+                        kind: LineChangeKind::Modified,
+                    }),
+                    (None, Some(new)) => new.new_line.map(|line| LineChange {
+                        line,
+                        kind: LineChangeKind::Added,
+                    }),
+                    (Some(_), None) => Some(LineChange {
+                        line: previous.max(1),
+                        kind: LineChangeKind::Removed,
+                    }),
+                    (None, None) => None,
+                };
+                if let Some(new_line) = row.1.and_then(|new| new.new_line) {
+                    previous = new_line;
+                }
+                // A run of removed lines marks its line once, and never
+                // over a line that changed itself
+                if let Some(change) = change {
+                    if changes.last().map(|last| last.line) != Some(change.line) {
+                        changes.push(change);
+                    }
+                }
+            }
+        }
+        changes
+    }
+
+    /// Check whether the diff has no hunks.
+    pub fn is_empty(&self) -> bool {
+        self.hunks.is_empty()
+    }
+
+    /// Check whether the diff creates the file.
+    pub fn is_new_file(&self) -> bool {
+        self.header
+            .iter()
+            .any(|line| line.starts_with("new file mode") || line == "--- /dev/null")
+    }
+
+    /// Check whether git reported the file as binary.
+    pub fn is_binary(&self) -> bool {
+        self.header
+            .iter()
+            .any(|line| line.starts_with("Binary files "))
+    }
+
+    /// A patch holding the header and only the hunk at `index`, for
+    /// `git apply`.
+    pub fn hunk_patch(&self, index: usize) -> Option<String> {
+        let hunk = self.hunks.get(index)?;
+        let mut patch = String::new();
+        for line in &self.header {
+            patch.push_str(line);
+            patch.push('\n');
+        }
+        patch.push_str(&hunk.header());
+        patch.push('\n');
+        for line in &hunk.lines {
+            patch.push(line.kind.prefix());
+            patch.push_str(&line.text);
+            patch.push('\n');
+            if line.no_newline {
+                patch.push_str("\\ No newline at end of file\n");
+            }
+        }
+        Some(patch)
+    }
+}
+
+/// The shortest edit turning `old` into `new`, one kind per line, found
+/// with Myers' algorithm.
+fn edit_script(old: &[&str], new: &[&str]) -> Vec<DiffLineKind> {
+    let (n, m) = (old.len(), new.len());
+    // Diagonal k = x - y is stored at k + offset, so it never goes negative
+    let offset = n + m + 1;
+    let mut furthest = vec![0; 2 * offset + 1];
+    // The furthest x on the diagonals -d - 1..=d + 1 before each round d
+    let mut trace: Vec<(usize, Vec<usize>)> = Vec::new();
+    'search: for d in 0..offset {
+        let low = offset - d - 1;
+        trace.push((low, furthest[low..=offset + d + 1].to_vec()));
+        for diagonal in (offset - d..=offset + d).step_by(2) {
+            let mut x = if diagonal == offset - d
+                || (diagonal != offset + d && furthest[diagonal - 1] < furthest[diagonal + 1])
+            {
+                furthest[diagonal + 1]
+            } else {
+                furthest[diagonal - 1] + 1
+            };
+            let mut y = x + offset - diagonal;
+            while x < n && y < m && old[x] == new[y] {
+                x += 1;
+                y += 1;
+            }
+            furthest[diagonal] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    let mut script = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, (low, before)) in trace.iter().enumerate().rev() {
+        if d == 0 {
+            script.extend(std::iter::repeat_n(DiffLineKind::Context, x));
+            break;
+        }
+        let furthest = |diagonal: usize| before[diagonal - low];
+        let diagonal = x + offset - y;
+        let down = diagonal == offset - d
+            || (diagonal != offset + d && furthest(diagonal - 1) < furthest(diagonal + 1));
+        let previous = if down { diagonal + 1 } else { diagonal - 1 };
+        let previous_x = furthest(previous);
+        let previous_y = previous_x + offset - previous;
+        while x > previous_x && y > previous_y {
+            script.push(DiffLineKind::Context);
+            x -= 1;
+            y -= 1;
+        }
+        script.push(if down {
+            DiffLineKind::Added
+        } else {
+            DiffLineKind::Removed
+        });
+        (x, y) = (previous_x, previous_y);
+    }
+    script.reverse();
+    script
+}
+
+/// Move the pending removed and added lines into `rows`, pairing them up.
+fn flush<'a>(
+    rows: &mut Vec<(Option<&'a DiffLine>, Option<&'a DiffLine>)>,
+    removed: &mut Vec<&'a DiffLine>,
+    added: &mut Vec<&'a DiffLine>,
+) {
+    let len = removed.len().max(added.len());
+    let mut removed = removed.drain(..);
+    let mut added = added.drain(..);
+    rows.extend((0..len).map(|_| (removed.next(), added.next())));
+}
+
+/// Parse `@@ -a,b +c,d @@ section`; a missing length means one line.
+fn parse_hunk_header(line: &str) -> Option<Hunk> {
+    let rest = line.strip_prefix("@@ -")?;
+    let (ranges, section) = rest.split_once(" @@")?;
+    let (old, new) = ranges.split_once(" +")?;
+    let range = |range: &str| -> Option<(usize, usize)> {
+        match range.split_once(',') {
+            Some((start, len)) => Some((start.parse().ok()?, len.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_len) = range(old)?;
+    let (new_start, new_len) = range(new)?;
+    Some(Hunk {
+        old_start,
+        old_len,
+        new_start,
+        new_len,
+        section: section.trim_start().to_string(),
+        lines: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,3 @@ fn main() {
+ one
+-two
++TWO
+ three
+@@ -10,2 +10,3 @@
+ ten
+-eleven
++ELEVEN
++twelve
+\\ No newline at end of file
+";
+
+    #[test]
+    fn test_parse_reads_header_hunks_and_line_numbers() {
+        let diff = FileDiff::parse(DIFF);
+        assert_eq!(diff.header.len(), 4);
+        assert_eq!(diff.hunks.len(), 2);
+
+        let first = &diff.hunks[0];
+        assert_eq!(first.header(), "@@ -1,3 +1,3 @@ fn main() {");
+        assert_eq!(first.changes(), (1, 1));
+        let numbers: Vec<_> = first
+            .lines
+            .iter()
+            .map(|line| (line.old_line, line.new_line))
+            .collect();
+        assert_eq!(
+            numbers,
+            vec![
+                (Some(1), Some(1)),
+                (Some(2), None),
+                (None, Some(2)),
+                (Some(3), Some(3)),
+            ]
+        );
+        assert!(diff.hunks[1].lines.last().unwrap().no_newline);
+        assert!(!diff.is_new_file());
+    }
+
+    #[test]
+    fn test_hunk_patch_keeps_header_and_one_hunk() {
+        let diff = FileDiff::parse(DIFF);
+        let patch = diff.hunk_patch(1).unwrap();
+        assert!(patch.starts_with("diff --git a/src/lib.rs b/src/lib.rs\n"));
+        assert!(!patch.contains("TWO"));
+        assert!(patch.ends_with(
+            "@@ -10,2 +10,3 @@\n ten\n-eleven\n+ELEVEN\n+twelve\n\\ No newline at end of file\n"
+        ));
+        assert!(diff.hunk_patch(2).is_none());
+    }
+
+    #[test]
+    fn test_line_changes_mark_the_new_side() {
+        let changes: Vec<(usize, LineChangeKind)> = FileDiff::parse(DIFF)
+            .line_changes()
+            .into_iter()
+            .map(|change| (change.line, change.kind))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                (2, LineChangeKind::Modified),
+                (11, LineChangeKind::Modified),
+                (12, LineChangeKind::Added),
+            ]
+        );
+
+        let removed = FileDiff::parse("@@ -1,5 +1,2 @@\n one\n-two\n-three\n four\n-five\n");
+        let changes: Vec<(usize, LineChangeKind)> = removed
+            .line_changes()
+            .into_iter()
+            .map(|change| (change.line, change.kind))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![(1, LineChangeKind::Removed), (2, LineChangeKind::Removed)]
+        );
+    }
+
+    #[test]
+    fn test_compare_groups_changes_into_hunks_with_context() {
+        let old: String = (1..=12).map(|line| format!("line {line}\n")).collect();
+        let new = old
+            .replace("line 2\n", "line two\n")
+            .replace("line 11\n", "")
+            .replace("line 12\n", "line 12\nline 13\n");
+        let diff = FileDiff::compare(&old, &new);
+        assert!(diff.header.is_empty());
+        let headers: Vec<String> = diff.hunks.iter().map(Hunk::header).collect();
+        assert_eq!(headers, ["@@ -1,5 +1,5 @@", "@@ -8,5 +8,5 @@"]);
+        let changes: Vec<(char, &str)> = diff.hunks[1]
+            .lines
+            .iter()
+            .filter(|line| line.kind != DiffLineKind::Context)
+            .map(|line| (line.kind.prefix(), line.text.as_str()))
+            .collect();
+        assert_eq!(changes, [('-', "line 11"), ('+', "line 13")]);
+        assert_eq!(diff.hunks[1].lines[5].new_line, Some(12));
+
+        assert!(FileDiff::compare(&old, &old).hunks.is_empty());
+        let created = FileDiff::compare("", "one\ntwo");
+        assert_eq!(created.hunks[0].header(), "@@ -0,0 +1,2 @@");
+    }
+
+    #[test]
+    fn test_side_by_side_pairs_removed_with_added_lines() {
+        let diff = FileDiff::parse(DIFF);
+        let rows: Vec<(Option<&str>, Option<&str>)> = diff.hunks[1]
+            .side_by_side()
+            .into_iter()
+            .map(|(old, new)| (old.map(|l| l.text.as_str()), new.map(|l| l.text.as_str())))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                (Some("ten"), Some("ten")),
+                (Some("eleven"), Some("ELEVEN")),
+                (None, Some("twelve")),
+            ]
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//! The git state the editor shows: the current branch, the status of files
+//! and the changed lines of each, for gutter markers.
+//!
+//! `GitService` asks git on every call rather than caching, since git
+//! itself changes the state behind the IDE's back. Instead it carries an
+//! event of file changes that should refresh what is shown:
+//! [`GitService::watched_paths`] names the repository files to watch
+//! (`HEAD` and the index, which commits, checkouts and staging rewrite),
+//! and [`GitService::file_changed`] announces changes the IDE makes itself,
+//! such as saving a file. Listeners usually batch the event, as a save can
+//! be followed by several writes to the index.
+
+use std::path::{Path, PathBuf};
+
+use cli_ide_base::Event;
+
+use super::diff::LineChange;
+use super::repository::{DiffBase, GitError, Repository, StatusEntry};
+use crate::files::file_watcher::{FileChangeKind, FileChanged};
+
+/// Reports the git state of files and announces changes to it.
+#[derive(Default)]
+pub struct GitService {
+    /// Fired when a file changes in a way that may change its git state.
+    on_did_change_files: Event<FileChanged>,
+}
+
+impl GitService {
+    /// Create a service.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Find the repository containing `path`, a file or directory.
+    pub fn repository(&self, path: &Path) -> Result<Repository, GitError> {
+        let dir = if path.is_dir() {
+            path
+        } else {
+            path.parent().unwrap_or(path)
+        };
+        Repository::discover(dir)
+    }
+
+    /// Name the branch checked out in the repository containing `root`, or
+    /// `None` outside a repository.
+    pub fn branch(&self, root: &Path) -> Option<String> {
+        self.repository(root)
+            .and_then(|repository| repository.head())
+            .ok()
+    }
+
+    /// Get how the file at `path` differs from `HEAD`, or `None` if it is
+    /// unchanged, ignored or outside a repository.
+    pub fn file_status(&self, path: &Path) -> Option<StatusEntry> {
+        let status = self
+            .repository(path)
+            .and_then(|repository| repository.status());
+        status.ok()?.into_iter().find(|entry| entry.path == path)
+    }
+
+    /// Read the lines of the file at `path` that differ from the index,
+    /// the changes not yet staged; an untracked file is entirely added.
+    ///
+    /// Fails if the file is outside a repository or ignored by it, so a
+    /// gutter can tell such files from unchanged ones.
+    pub fn line_changes(&self, path: &Path) -> Result<Vec<LineChange>, GitError> {
+        let repository = self.repository(path)?;
+        if repository.is_ignored(path)? {
+            return Err(GitError::Unsupported("the file is ignored by git"));
+        }
+        let diff = repository.diff(path, DiffBase::Index)?;
+        Ok(diff.line_changes())
+    }
+
+    /// The files of the repository containing `root` whose changes should
+    /// refresh the git state: `HEAD` and the index.
+    pub fn watched_paths(&self, root: &Path) -> Vec<PathBuf> {
+        self.repository(root)
+            .and_then(|repository| repository.git_dir())
+            .map(|dir| vec![dir.join("HEAD"), dir.join("index")])
+            .unwrap_or_default()
+    }
+
+    /// Announce that the IDE changed the file at `path`, e.g. by saving it.
+    pub fn file_changed(&self, path: impl Into<PathBuf>) {
+        self.on_did_change_files.emit(FileChanged {
+            path: path.into(),
+            kind: FileChangeKind::Modified,
+        });
+    }
+
+    /// Event fired by [`file_changed`](GitService::file_changed).
+    pub fn on_did_change_files(&self) -> &Event<FileChanged> {
+        &self.on_did_change_files
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::process::Command;
+
+    use cli_ide_base::test_support::{temp_dir, Cleanup};
+
+    use super::*;
+    use crate::git::diff::LineChangeKind;
+    use crate::git::repository::ChangeKind;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    #[test]
+    fn test_service_reports_branch_status_and_changed_lines() {
+        let dir = temp_dir("service");
+        let _cleanup = Cleanup::new([&dir]);
+        let dir = fs::canonicalize(dir).unwrap();
+        git(&dir, &["init", "-q", "-b", "main"]);
+        git(&dir, &["config", "user.name", "Test"]);
+        git(&dir, &["config", "user.email", "test@example.com"]);
+        fs::write(dir.join(".gitignore"), "*.log\n").unwrap();
+        fs::write(dir.join("a.txt"), "1\n2\n3\n").unwrap();
+        git(&dir, &["add", "."]);
+        git(&dir, &["commit", "-q", "-m", "init"]);
+
+        let service = GitService::new();
+        let path = dir.join("a.txt");
+        assert_eq!(service.branch(&dir).as_deref(), Some("main"));
+        assert_eq!(service.file_status(&path), None);
+        assert_eq!(service.line_changes(&path), Ok(Vec::new()));
+
+        fs::write(&path, "1\ntwo\n3\n4\n").unwrap();
+        let status = service.file_status(&path).unwrap();
+        assert_eq!(status.unstaged, Some(ChangeKind::Modified));
+        let changes: Vec<(usize, LineChangeKind)> = service
+            .line_changes(&path)
+            .unwrap()
+            .into_iter()
+            .map(|change| (change.line, change.kind))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![(2, LineChangeKind::Modified), (4, LineChangeKind::Added)]
+        );
+
+        fs::write(dir.join("build.log"), "noise\n").unwrap();
+        assert!(service.line_changes(&dir.join("build.log")).is_err());
+        assert_eq!(
+            service.watched_paths(&dir),
+            vec![dir.join(".git/HEAD"), dir.join(".git/index")]
+        );
+
+        let changes = service.on_did_change_files().subscribe();
+        service.file_changed(&path);
+        assert_eq!(changes.try_recv().unwrap().path, path);
+    }
+}
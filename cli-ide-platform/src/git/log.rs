@@ -0,0 +1,146 @@
+//! Commit history as read by `git log`.
+//!
+//! [`Repository::log`](super::repository::Repository::log) lists
+//! [`Commit`]s newest first and
+//! [`Repository::commit`](super::repository::Repository::commit) reads one
+//! of them with its message and changed files as a [`CommitDetail`].
+
+use std::path::{Path, PathBuf};
+
+use super::repository::ChangeKind;
+
+/// The `--format` of `git log` and `git show` that [`parse_commits`] reads:
+/// fields separated by a unit separator, records by a record separator.
+pub(super) const COMMIT_FORMAT: &str = "--format=%H%x1f%h%x1f%an%x1f%ad%x1f%s%x1f%b%x1e";
+
+/// A commit as listed in the history.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Commit {
+    /// Full object name.
+    pub id: String,
+    /// Abbreviated object name.
+    pub short_id: String,
+    /// Name of the author.
+    pub author: String,
+    /// Author date, `YYYY-MM-DD`.
+    pub date: String,
+    /// First line of the message.
+    pub subject: String,
+}
+
+/// A file changed by a commit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitFile {
+    /// Absolute path of the file after the commit.
+    pub path: PathBuf,
+    /// How the commit changed it.
+    pub change: ChangeKind,
+}
+
+/// A commit with its full message and changed files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitDetail {
+    /// The commit.
+    pub commit: Commit,
+    /// Message after the subject, without surrounding blank lines.
+    pub body: String,
+    /// Changed files in path order. Merges list none.
+    pub files: Vec<CommitFile>,
+}
+
+/// Parse commits printed with [`COMMIT_FORMAT`], with their bodies.
+pub(super) fn parse_commits(output: &str) -> Vec<(Commit, String)> {
+    output
+        .split('\x1e')
+        .filter_map(|record| {
+            let mut fields = record.trim_start_matches('\n').split('\x1f');
+            let mut field = || fields.next().map(str::to_string);
+            let commit = Commit {
+                id: field()?,
+                short_id: field()?,
+                author: field()?,
+                date: field()?,
+                subject: field()?,
+            };
+            let body = field()?.trim().to_string();
+            Some((commit, body))
+        })
+        .collect()
+}
+
+/// Parse `git diff-tree -z --name-status` output into files under `root`.
+pub(super) fn parse_name_status(root: &Path, output: &str) -> Vec<CommitFile> {
+    let mut files = Vec::new();
+    let mut fields = output.split('\0').filter(|field| !field.is_empty());
+    while let Some(status) = fields.next() {
+        // Renames and copies name the old path before the new one
+        if matches!(status.as_bytes().first(), Some(b'R' | b'C')) {
+            fields.next();
+        }
+        let Some(path) = fields.next() else {
+            break;
+        };
+        let change = match status.as_bytes().first() {
+            Some(b'A') => ChangeKind::Added,
+            Some(b'D') => ChangeKind::Deleted,
+            Some(b'R' | b'C') => ChangeKind::Renamed,
+            Some(b'T') => ChangeKind::TypeChanged,
+            _ => ChangeKind::Modified,
+        };
+        files.push(CommitFile {
+            path: root.join(path),
+            change,
+        });
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_commits_reads_fields_and_body() {
+        let output = "aaaa\x1fa\x1fAda\x1f2026-01-02\x1fFirst\x1f\n\nMore words.\n\n\x1e\n\
+                      bbbb\x1fb\x1fBo\x1f2026-01-01\x1fSecond\x1f\x1e\n";
+        let commits = parse_commits(output);
+        assert_eq!(commits.len(), 2);
+        assert_eq!(
+            commits[0].0,
+            Commit {
+                id: "aaaa".to_string(),
+                short_id: "a".to_string(),
+                author: "Ada".to_string(),
+                date: "2026-01-02".to_string(),
+                subject: "First".to_string(),
+            }
+        );
+        assert_eq!(commits[0].1, "More words.");
+        assert_eq!(commits[1].0.subject, "Second");
+        assert_eq!(commits[1].1, "");
+    }
+
+    #[test]
+    fn test_parse_name_status_follows_renames() {
+        let output = "M\0src/lib.rs\0R100\0old.rs\0new.rs\0D\0gone.rs\0";
+        let files = parse_name_status(Path::new("/repo"), output);
+        assert_eq!(
+            files,
+            vec![
+                CommitFile {
+                    path: PathBuf::from("/repo/gone.rs"),
+                    change: ChangeKind::Deleted,
+                },
+                CommitFile {
+                    path: PathBuf::from("/repo/new.rs"),
+                    change: ChangeKind::Renamed,
+                },
+                CommitFile {
+                    path: PathBuf::from("/repo/src/lib.rs"),
+                    change: ChangeKind::Modified,
+                },
+            ]
+        );
+    }
+}
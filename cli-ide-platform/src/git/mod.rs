@@ -0,0 +1,8 @@
+//! Git integration.
+
+pub mod branch;
+pub mod diff;
+pub mod git_service;
+pub mod log;
+pub mod repository;
+pub mod stash;
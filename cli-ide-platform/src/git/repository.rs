@@ -0,0 +1,688 @@
+//! Access to a git repository through the `git` command.
+//!
+//! [`Repository::discover`] finds the repository containing a directory.
+//! [`status`](Repository::status) lists the changed files and
+//! [`diff`](Repository::diff) reads the changes of one of them against the
+//! index or `HEAD`. Single hunks of such a diff are staged, unstaged or
+//! reverted with [`apply_hunk`](Repository::apply_hunk), which feeds the
+//! hunk to `git apply`. [`log`](Repository::log) and
+//! [`commit`](Repository::commit) read the history, and
+//! [`file_at`](Repository::file_at) a file as it was at a commit.
+//! [`branches`](Repository::branches) lists the branches to
+//! [`checkout`](Repository::checkout) or branch off with
+//! [`create_branch`](Repository::create_branch). Local changes are set
+//! aside with [`stash`](Repository::stash) and brought back with
+//! [`pop_stash`](Repository::pop_stash).
+
+use std::fmt;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use super::branch::{self, Branch, BRANCH_FORMAT};
+use super::diff::FileDiff;
+use super::log::{self, Commit, CommitDetail, COMMIT_FORMAT};
+use super::stash::{self, Stash, STASH_FORMAT};
+
+/// Errors produced by git commands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GitError {
+    /// The directory is not inside a git work tree.
+    NotARepository(PathBuf),
+    /// The `git` command could not be run.
+    Unavailable(String),
+    /// A git command failed.
+    Failed {
+        /// The git subcommand, e.g. `apply`.
+        command: String,
+        /// What git printed to stderr.
+        message: String,
+    },
+    /// The hunk does not exist in the diff.
+    NoSuchHunk(usize),
+    /// The hunk cannot take this action, e.g. reverting a staged hunk.
+    Unsupported(&'static str),
+}
+
+impl fmt::Display for GitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GitError::NotARepository(path) => {
+                write!(f, "{} is not in a git repository", path.display())
+            }
+            GitError::Unavailable(message) => write!(f, "cannot run git: {message}"),
+            GitError::Failed { command, message } => write!(f, "git {command}: {message}"),
+            GitError::NoSuchHunk(index) => write!(f, "there is no hunk {}", index + 1),
+            GitError::Unsupported(reason) => f.write_str(reason),
+        }
+    }
+}
+
+impl std::error::Error for GitError {}
+
+/// How a file changed, as reported by `git status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ChangeKind {
+    /// The contents changed.
+    Modified,
+    /// The file is new.
+    Added,
+    /// The file was deleted.
+    Deleted,
+    /// The file was renamed or copied.
+    Renamed,
+    /// The file's type changed, e.g. to a symlink.
+    TypeChanged,
+    /// The file is not tracked.
+    Untracked,
+    /// The file has merge conflicts.
+    Conflicted,
+}
+
+impl ChangeKind {
+    /// The letter `git status --short` shows for the change.
+    pub fn letter(self) -> char {
+        match self {
+            ChangeKind::Modified => 'M',
+            ChangeKind::Added => 'A',
+            ChangeKind::Deleted => 'D',
+            ChangeKind::Renamed => 'R',
+            ChangeKind::TypeChanged => 'T',
+            ChangeKind::Untracked => 'U',
+            ChangeKind::Conflicted => '!',
+        }
+    }
+
+    /// Map a `git status --porcelain` status letter.
+    fn from_letter(letter: u8) -> Option<Self> {
+        match letter {
+            b'M' => Some(ChangeKind::Modified),
+            b'A' => Some(ChangeKind::Added),
+            b'D' => Some(ChangeKind::Deleted),
+            b'R' | b'C' => Some(ChangeKind::Renamed),
+            b'T' => Some(ChangeKind::TypeChanged),
+            b'?' => Some(ChangeKind::Untracked),
+            b'U' => Some(ChangeKind::Conflicted),
+            _ => None,
+        }
+    }
+}
+
+/// A changed file and how it differs in the index and the work tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusEntry {
+    /// Absolute path of the file.
+    pub path: PathBuf,
+    /// Change between `HEAD` and the index, if staged.
+    pub staged: Option<ChangeKind>,
+    /// Change between the index and the work tree, if any.
+    pub unstaged: Option<ChangeKind>,
+}
+
+/// What a diff compares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DiffBase {
+    /// The work tree against the index: the unstaged changes.
+    Index,
+    /// The index against `HEAD`: the staged changes.
+    Head,
+}
+
+/// What to do with a single hunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HunkAction {
+    /// Add an unstaged hunk to the index.
+    Stage,
+    /// Remove a staged hunk from the index.
+    Unstage,
+    /// Undo an unstaged hunk in the work tree.
+    Revert,
+}
+
+/// A git work tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repository {
+    /// Top-level directory of the work tree.
+    root: PathBuf,
+}
+
+impl Repository {
+    /// Find the repository whose work tree contains `path`.
+    pub fn discover(path: impl AsRef<Path>) -> Result<Self, GitError> {
+        let path = path.as_ref();
+        let output =
+            run_git(path, &["rev-parse", "--show-toplevel"], None, &[0]).map_err(|error| {
+                match error {
+                    GitError::Failed { .. } => GitError::NotARepository(path.to_path_buf()),
+                    error => error,
+                }
+            })?;
+        Ok(Self {
+            root: PathBuf::from(output.trim_end_matches('\n')),
+        })
+    }
+
+    /// Get the top-level directory of the work tree.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Get the directory git keeps the repository's own files in, such as
+    /// `HEAD` and the index: usually `.git` under the root.
+    pub fn git_dir(&self) -> Result<PathBuf, GitError> {
+        let output = self.git(&["rev-parse", "--absolute-git-dir"])?;
+        Ok(PathBuf::from(output.trim_end_matches('\n')))
+    }
+
+    /// Check whether git ignores the file at `path`.
+    pub fn is_ignored(&self, path: &Path) -> Result<bool, GitError> {
+        let relative = self.relative(path);
+        // `check-ignore` exits with 1 when the path is not ignored
+        let output = run_git(
+            &self.root,
+            &["check-ignore", "--", &relative],
+            None,
+            &[0, 1],
+        )?;
+        Ok(!output.is_empty())
+    }
+
+    /// List the changed and untracked files, in path order.
+    pub fn status(&self) -> Result<Vec<StatusEntry>, GitError> {
+        let output = self.git(&["status", "--porcelain=v1", "-z", "--untracked-files=all"])?;
+        let mut entries = Vec::new();
+        let mut fields = output.split('\0').filter(|field| !field.is_empty());
+        while let Some(field) = fields.next() {
+            let Some((status, path)) = field.split_at_checked(3) else {
+                continue;
+            };
+            let status = status.as_bytes();
+            let conflicted = matches!(
+                (status[0], status[1]),
+                (b'U', _) | (_, b'U') | (b'A', b'A') | (b'D', b'D')
+            );
+            // Renames and copies are followed by the original path
+            if matches!(status[0], b'R' | b'C') {
+                fields.next();
+            }
+            let (staged, unstaged) = if conflicted {
+                (None, Some(ChangeKind::Conflicted))
+            } else if status[0] == b'?' {
+                (None, Some(ChangeKind::Untracked))
+            } else {
+                (
+                    ChangeKind::from_letter(status[0]),
+                    ChangeKind::from_letter(status[1]),
+                )
+            };
+            entries.push(StatusEntry {
+                path: self.root.join(path),
+                staged,
+                unstaged,
+            });
+        }
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(entries)
+    }
+
+    /// Read the changes to `path` against `base`.
+    ///
+    /// An untracked file diffs as entirely added against the index.
+    pub fn diff(&self, path: &Path, base: DiffBase) -> Result<FileDiff, GitError> {
+        let relative = self.relative(path);
+        let mut args = vec!["diff", "--no-color", "--no-ext-diff", "-U3"];
+        if base == DiffBase::Head {
+            args.push("--cached");
+        }
+        args.extend(["--", relative.as_str()]);
+        let diff = FileDiff::parse(&self.git(&args)?);
+        if !diff.header.is_empty() || base == DiffBase::Head || !self.is_untracked(&relative)? {
+            return Ok(diff);
+        }
+        // `--no-index` exits with 1 when the files differ
+        let args = [
+            "diff",
+            "--no-color",
+            "--no-ext-diff",
+            "--no-index",
+            "--",
+            "/dev/null",
+            relative.as_str(),
+        ];
+        let output = run_git(&self.root, &args, None, &[0, 1])?;
+        Ok(FileDiff::parse(&output))
+    }
+
+    /// Stage, unstage or revert the hunk at `index` of `diff`, a diff of
+    /// one file against `base`.
+    ///
+    /// Only hunks of unstaged changes ([`DiffBase::Index`]) can be staged or
+    /// reverted, and only hunks of staged changes ([`DiffBase::Head`]) can
+    /// be unstaged. Reverting a hunk of an untracked file is refused, as it
+    /// would delete the file.
+    pub fn apply_hunk(
+        &self,
+        diff: &FileDiff,
+        base: DiffBase,
+        index: usize,
+        action: HunkAction,
+    ) -> Result<(), GitError> {
+        let patch = diff.hunk_patch(index).ok_or(GitError::NoSuchHunk(index))?;
+        let args: &[&str] = match (action, base) {
+            (HunkAction::Stage, DiffBase::Index) => &["apply", "--cached", "-"],
+            (HunkAction::Unstage, DiffBase::Head) => &["apply", "--cached", "--reverse", "-"],
+            (HunkAction::Revert, DiffBase::Index) if !diff.is_new_file() => {
+                &["apply", "--reverse", "-"]
+            }
+            (HunkAction::Revert, DiffBase::Index) => {
+                return Err(GitError::Unsupported("untracked files cannot be reverted"));
+            }
+            (HunkAction::Stage, DiffBase::Head) => {
+                return Err(GitError::Unsupported("the hunk is already staged"));
+            }
+            (HunkAction::Revert, DiffBase::Head) => {
+                return Err(GitError::Unsupported(
+                    "unstage the hunk before reverting it",
+                ));
+            }
+            (HunkAction::Unstage, DiffBase::Index) => {
+                return Err(GitError::Unsupported("the hunk is not staged"));
+            }
+        };
+        run_git(&self.root, args, Some(&patch), &[0]).map(|_| ())
+    }
+
+    /// List up to `limit` commits reachable from `HEAD`, newest first.
+    ///
+    /// A repository without commits has an empty history.
+    pub fn log(&self, limit: usize) -> Result<Vec<Commit>, GitError> {
+        if !self.has_commits()? {
+            return Ok(Vec::new());
+        }
+        let limit = format!("--max-count={limit}");
+        let output = self.git(&["log", "--date=short", COMMIT_FORMAT, &limit])?;
+        Ok(log::parse_commits(&output)
+            .into_iter()
+            .map(|(commit, _)| commit)
+            .collect())
+    }
+
+    /// Read the commit named by `revision` with its message and the files
+    /// it changed.
+    pub fn commit(&self, revision: &str) -> Result<CommitDetail, GitError> {
+        let output = self.git(&["show", "-s", "--date=short", COMMIT_FORMAT, revision, "--"])?;
+        let (commit, body) = log::parse_commits(&output)
+            .into_iter()
+            .next()
+            .ok_or_else(|| GitError::Failed {
+                command: "show".to_string(),
+                message: format!("cannot read commit {revision}"),
+            })?;
+        let files = self.git(&[
+            "diff-tree",
+            "--no-commit-id",
+            "--name-status",
+            "-r",
+            "-z",
+            "--root",
+            &commit.id,
+        ])?;
+        let files = log::parse_name_status(&self.root, &files);
+        Ok(CommitDetail {
+            commit,
+            body,
+            files,
+        })
+    }
+
+    /// Read `path` as it was at `revision`.
+    pub fn file_at(&self, revision: &str, path: &Path) -> Result<String, GitError> {
+        let object = format!("{revision}:{}", self.relative(path));
+        self.git(&["show", &object])
+    }
+
+    /// List the local branches, then the remote-tracking branches, each in
+    /// name order.
+    pub fn branches(&self) -> Result<Vec<Branch>, GitError> {
+        let output = self.git(&["for-each-ref", BRANCH_FORMAT, "refs/heads", "refs/remotes"])?;
+        Ok(branch::parse_branches(&output))
+    }
+
+    /// Name the current branch, or the abbreviated commit if `HEAD` is
+    /// detached.
+    pub fn head(&self) -> Result<String, GitError> {
+        let output = run_git(
+            &self.root,
+            &["symbolic-ref", "--short", "--quiet", "HEAD"],
+            None,
+            &[0, 1],
+        )?;
+        let name = if output.is_empty() {
+            self.git(&["rev-parse", "--short", "HEAD"])?
+        } else {
+            output
+        };
+        Ok(name.trim_end_matches('\n').to_string())
+    }
+
+    /// Switch the work tree to `branch`.
+    ///
+    /// A remote-tracking branch is checked out as a new local branch of the
+    /// same name that tracks it. Git refuses if local changes would be
+    /// overwritten.
+    pub fn checkout(&self, branch: &Branch) -> Result<(), GitError> {
+        let args: &[&str] = if branch.remote {
+            &["switch", "--track", &branch.name]
+        } else {
+            &["switch", &branch.name]
+        };
+        self.git(args).map(|_| ())
+    }
+
+    /// Create a branch named `name` at `HEAD` and switch to it.
+    pub fn create_branch(&self, name: &str) -> Result<(), GitError> {
+        self.git(&["switch", "--create", name]).map(|_| ())
+    }
+
+    /// List the stashes, newest first.
+    pub fn stashes(&self) -> Result<Vec<Stash>, GitError> {
+        let output = self.git(&["stash", "list", STASH_FORMAT])?;
+        Ok(stash::parse_stashes(&output))
+    }
+
+    /// Stash the local changes, including untracked files, with `message`
+    /// or git's default description if it is empty.
+    ///
+    /// Returns the new stash, or `None` if there was nothing to stash.
+    pub fn stash(&self, message: &str) -> Result<Option<Stash>, GitError> {
+        let before = self.stash_top()?;
+        let mut args = vec!["stash", "push", "--include-untracked"];
+        if !message.is_empty() {
+            args.extend(["--message", message]);
+        }
+        self.git(&args)?;
+        if self.stash_top()? == before {
+            return Ok(None);
+        }
+        Ok(self.stashes()?.into_iter().next())
+    }
+
+    /// Apply `stash` to the work tree and drop it.
+    ///
+    /// If applying conflicts, git keeps the stash and the error says why.
+    pub fn pop_stash(&self, stash: &Stash) -> Result<(), GitError> {
+        self.git(&["stash", "pop", &stash.name()]).map(|_| ())
+    }
+
+    /// The object name of the newest stash, or an empty string if there
+    /// are none.
+    fn stash_top(&self) -> Result<String, GitError> {
+        run_git(
+            &self.root,
+            &["rev-parse", "--quiet", "--verify", "refs/stash"],
+            None,
+            &[0, 1],
+        )
+    }
+
+    /// Check whether `HEAD` points at a commit.
+    fn has_commits(&self) -> Result<bool, GitError> {
+        let output = run_git(
+            &self.root,
+            &["rev-parse", "--verify", "--quiet", "HEAD"],
+            None,
+            &[0, 1],
+        )?;
+        Ok(!output.is_empty())
+    }
+
+    /// Run git in the work tree, expecting success.
+    fn git(&self, args: &[&str]) -> Result<String, GitError> {
+        run_git(&self.root, args, None, &[0])
+    }
+
+    /// `path` relative to the work tree, with `/` separators.
+    fn relative(&self, path: &Path) -> String {
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        relative.to_string_lossy().replace('\\', "/")
+    }
+
+    /// Check whether git does not track the file at `relative`.
+    fn is_untracked(&self, relative: &str) -> Result<bool, GitError> {
+        let output = self.git(&["ls-files", "--", relative])?;
+        Ok(output.is_empty())
+    }
+}
+
+/// Run git in `dir` with `stdin` as input, returning its output if it exits
+/// with one of the `ok` codes.
+fn run_git(dir: &Path, args: &[&str], stdin: Option<&str>, ok: &[i32]) -> Result<String, GitError> {
+    let unavailable = |error: io::Error| GitError::Unavailable(error.to_string());
+    let mut child = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .stdin(if stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(unavailable)?;
+    if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+        pipe.write_all(input.as_bytes()).map_err(unavailable)?;
+    }
+    let output = child.wait_with_output().map_err(unavailable)?;
+    if output.status.code().is_some_and(|code| ok.contains(&code)) {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(GitError::Failed {
+            command: args.first().copied().unwrap_or_default().to_string(),
+            message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cli_ide_base::test_support::{temp_dir, Cleanup};
+    use std::fs;
+
+    /// A fresh repository with one committed file, `a.txt`, and the guard
+    /// removing it.
+    fn repository(name: &str) -> (Repository, Cleanup) {
+        let dir = temp_dir(&format!("git-{name}"));
+        let cleanup = Cleanup::new([&dir]);
+        let dir = fs::canonicalize(dir).unwrap();
+        for args in [
+            &["init", "-q"][..],
+            &["config", "user.name", "Test"],
+            &["config", "user.email", "test@example.com"],
+        ] {
+            run_git(&dir, args, None, &[0]).unwrap();
+        }
+        fs::write(dir.join("a.txt"), "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n").unwrap();
+        run_git(&dir, &["add", "a.txt"], None, &[0]).unwrap();
+        run_git(&dir, &["commit", "-q", "-m", "init"], None, &[0]).unwrap();
+        (Repository::discover(&dir).unwrap(), cleanup)
+    }
+
+    #[test]
+    fn test_status_reports_staged_unstaged_and_untracked() {
+        let (repo, _cleanup) = repository("status");
+        fs::write(repo.root().join("a.txt"), "changed\n").unwrap();
+        fs::write(repo.root().join("new.txt"), "new\n").unwrap();
+        repo.git(&["add", "a.txt"]).unwrap();
+        fs::write(repo.root().join("a.txt"), "changed again\n").unwrap();
+
+        let status = repo.status().unwrap();
+        assert_eq!(
+            status,
+            vec![
+                StatusEntry {
+                    path: repo.root().join("a.txt"),
+                    staged: Some(ChangeKind::Modified),
+                    unstaged: Some(ChangeKind::Modified),
+                },
+                StatusEntry {
+                    path: repo.root().join("new.txt"),
+                    staged: None,
+                    unstaged: Some(ChangeKind::Untracked),
+                },
+            ]
+        );
+        assert!(matches!(
+            Repository::discover("/"),
+            Err(GitError::NotARepository(_))
+        ));
+    }
+
+    #[test]
+    fn test_hunks_stage_unstage_and_revert_independently() {
+        let (repo, _cleanup) = repository("hunks");
+        let path = repo.root().join("a.txt");
+        fs::write(&path, "one\n2\n3\n4\n5\n6\n7\n8\n9\nten\n").unwrap();
+        let diff = repo.diff(&path, DiffBase::Index).unwrap();
+        assert_eq!(diff.hunks.len(), 2);
+
+        // Stage the second hunk only, then take it back out
+        repo.apply_hunk(&diff, DiffBase::Index, 1, HunkAction::Stage)
+            .unwrap();
+        let staged = repo.diff(&path, DiffBase::Head).unwrap();
+        assert_eq!(staged.hunks.len(), 1);
+        assert_eq!(staged.hunks[0].lines.last().unwrap().text, "ten");
+        assert_eq!(repo.diff(&path, DiffBase::Index).unwrap().hunks.len(), 1);
+        assert_eq!(
+            repo.apply_hunk(&staged, DiffBase::Head, 0, HunkAction::Revert),
+            Err(GitError::Unsupported(
+                "unstage the hunk before reverting it"
+            ))
+        );
+        repo.apply_hunk(&staged, DiffBase::Head, 0, HunkAction::Unstage)
+            .unwrap();
+        assert!(repo.diff(&path, DiffBase::Head).unwrap().is_empty());
+
+        // Revert the first hunk in the work tree
+        repo.apply_hunk(&diff, DiffBase::Index, 0, HunkAction::Revert)
+            .unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "1\n2\n3\n4\n5\n6\n7\n8\n9\nten\n"
+        );
+    }
+
+    #[test]
+    fn test_untracked_file_diffs_as_added_and_can_be_staged() {
+        let (repo, _cleanup) = repository("untracked");
+        let path = repo.root().join("new.txt");
+        fs::write(&path, "new\n").unwrap();
+
+        let diff = repo.diff(&path, DiffBase::Index).unwrap();
+        assert!(diff.is_new_file());
+        assert_eq!(diff.hunks[0].changes(), (1, 0));
+        assert_eq!(
+            repo.apply_hunk(&diff, DiffBase::Index, 0, HunkAction::Revert),
+            Err(GitError::Unsupported("untracked files cannot be reverted"))
+        );
+        repo.apply_hunk(&diff, DiffBase::Index, 0, HunkAction::Stage)
+            .unwrap();
+        let status = repo.status().unwrap();
+        assert_eq!(status[0].staged, Some(ChangeKind::Added));
+        assert!(path.exists());
+    }
+
+    #[test]
+    fn test_log_lists_commits_and_reads_files_at_them() {
+        let (repo, _cleanup) = repository("log");
+        let path = repo.root().join("a.txt");
+        fs::write(&path, "changed\n").unwrap();
+        fs::write(repo.root().join("b.txt"), "b\n").unwrap();
+        repo.git(&["add", "a.txt", "b.txt"]).unwrap();
+        repo.git(&["commit", "-q", "-m", "change\n\nWith a body."])
+            .unwrap();
+
+        let log = repo.log(10).unwrap();
+        let subjects: Vec<&str> = log.iter().map(|c| c.subject.as_str()).collect();
+        assert_eq!(subjects, vec!["change", "init"]);
+        assert_eq!(log[0].author, "Test");
+        assert_eq!(repo.log(1).unwrap().len(), 1);
+
+        let detail = repo.commit(&log[0].short_id).unwrap();
+        assert_eq!(detail.commit, log[0]);
+        assert_eq!(detail.body, "With a body.");
+        let changes: Vec<ChangeKind> = detail.files.iter().map(|f| f.change).collect();
+        assert_eq!(changes, vec![ChangeKind::Modified, ChangeKind::Added]);
+        assert_eq!(detail.files[0].path, path);
+
+        // The root commit lists its files as added
+        let root = repo.commit(&log[1].id).unwrap();
+        assert_eq!(root.files[0].change, ChangeKind::Added);
+        assert_eq!(
+            repo.file_at(&log[1].id, &path).unwrap(),
+            "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n"
+        );
+        assert_eq!(repo.file_at(&log[0].id, &path).unwrap(), "changed\n");
+        assert!(repo
+            .file_at(&log[1].id, &repo.root().join("b.txt"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_branches_are_created_listed_and_checked_out() {
+        let (repo, _cleanup) = repository("branches");
+        let main = repo.head().unwrap();
+        repo.create_branch("topic").unwrap();
+        assert_eq!(repo.head().unwrap(), "topic");
+
+        let branches = repo.branches().unwrap();
+        let current: Vec<(&str, bool)> = branches
+            .iter()
+            .map(|branch| (branch.name.as_str(), branch.current))
+            .collect();
+        let mut expected = vec![(main.as_str(), false), ("topic", true)];
+        expected.sort();
+        assert_eq!(current, expected);
+
+        let main = branches.iter().find(|branch| !branch.current).unwrap();
+        repo.checkout(main).unwrap();
+        assert_eq!(repo.head().unwrap(), main.name);
+        assert!(matches!(
+            repo.create_branch("topic"),
+            Err(GitError::Failed { .. })
+        ));
+
+        // A detached HEAD is named by its commit
+        repo.git(&["switch", "--detach", "topic"]).unwrap();
+        let commit = repo.git(&["rev-parse", "--short", "HEAD"]).unwrap();
+        assert_eq!(repo.head().unwrap(), commit.trim_end());
+    }
+
+    #[test]
+    fn test_stash_and_pop_restore_changes() {
+        let (repo, _cleanup) = repository("stash");
+        let path = repo.root().join("a.txt");
+        assert_eq!(repo.stash("").unwrap(), None);
+
+        fs::write(&path, "changed\n").unwrap();
+        fs::write(repo.root().join("new.txt"), "new\n").unwrap();
+        let first = repo.stash("first").unwrap().unwrap();
+        assert_eq!(first.index, 0);
+        assert!(first.message.ends_with(": first"));
+        assert!(repo.status().unwrap().is_empty());
+
+        fs::write(&path, "again\n").unwrap();
+        repo.stash("").unwrap().unwrap();
+        let stashes = repo.stashes().unwrap();
+        assert_eq!(stashes.len(), 2);
+        assert!(stashes[1].message.ends_with(": first"));
+
+        // Pop the older stash; the newer one moves nowhere
+        repo.pop_stash(&stashes[1]).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "changed\n");
+        assert!(repo.root().join("new.txt").exists());
+        assert_eq!(repo.stashes().unwrap().len(), 1);
+    }
+}
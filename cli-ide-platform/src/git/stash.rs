@@ -0,0 +1,65 @@
+//! Stashes as listed by `git stash list`.
+//!
+//! [`Repository::stashes`](super::repository::Repository::stashes) lists
+//! the [`Stash`]es, newest first, which
+//! [`Repository::pop_stash`](super::repository::Repository::pop_stash)
+//! applies and drops.
+
+/// The `--format` of `git stash list` that [`parse_stashes`] reads.
+pub(super) const STASH_FORMAT: &str = "--format=%gd%x1f%s";
+
+/// A stash entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stash {
+    /// Position in the stash list, newest first.
+    pub index: usize,
+    /// Description, e.g. `On main: message` or `WIP on main: 1a2b3c4 subject`.
+    pub message: String,
+}
+
+impl Stash {
+    /// The reflog name git knows the stash by, e.g. `stash@{0}`.
+    pub fn name(&self) -> String {
+        format!("stash@{{{}}}", self.index)
+    }
+}
+
+/// Parse stashes printed with [`STASH_FORMAT`].
+pub(super) fn parse_stashes(output: &str) -> Vec<Stash> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (name, message) = line.split_once('\x1f')?;
+            let index = name.strip_prefix("stash@{")?.strip_suffix('}')?;
+            Some(Stash {
+                index: index.parse().ok()?,
+                message: message.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stashes_reads_index_and_message() {
+        let output = "stash@{0}\x1fWIP on main: 1a2b3c4 init\nstash@{1}\x1fOn main: wip\n";
+        let stashes = parse_stashes(output);
+        assert_eq!(
+            stashes,
+            vec![
+                Stash {
+                    index: 0,
+                    message: "WIP on main: 1a2b3c4 init".to_string(),
+                },
+                Stash {
+                    index: 1,
+                    message: "On main: wip".to_string(),
+                },
+            ]
+        );
+        assert_eq!(stashes[1].name(), "stash@{1}");
+    }
+}
@@ -1,7 +1,45 @@
 //! Platform layer for the CLI IDE.
 //!
 //! This crate provides abstractions for dependency injection and other
-//! platform‑specific services.  For now it defines a simple service
-//! container inspired by VS Code’s instantiation system【6955392274892†L521-L533】.
+//! platform‑specific services.  It defines a simple service
+//! container inspired by VS Code’s instantiation system【6955392274892†L521-L533】,
+//! a command registry that components contribute named commands to,
+//! a configuration service that loads user settings, a context key
+//! service that `when` clauses are evaluated against, a file watcher
+//! that reports changes on disk, a task service that runs background
+//! work off the render thread, access to git repositories and a git
+//! service that reports the branch and changed lines of files, a process
+//! service that streams the output of child processes, the cargo
+//! commands and problem matchers built on it, a log service that
+//! `tracing` events are written to, named output channels that features
+//! write their output to, a session service that saves the workbench
+//! state between launches, a search service that searches the workspace
+//! in the background, a progress service that long-running work
+//! reports its progress to, a notification service that collects
+//! notifications and filters which of them interrupt the user, a
+//! clipboard service that copies and pastes through the system clipboard,
+//! an opener service that opens URLs in the user's browser, an
+//! environment service that reports what the terminal supports, and a
+//! tick scheduler that paces the event loop's ticks and runs periodic
+//! callbacks on them.
 
+pub mod cargo;
+pub mod clipboard;
+pub mod command;
+pub mod config;
+pub mod context;
 pub mod di;
+pub mod environment;
+pub mod files;
+pub mod git;
+pub mod logging;
+pub mod notification;
+pub mod opener;
+pub mod output;
+pub mod problems;
+pub mod process;
+pub mod progress;
+pub mod search;
+pub mod session;
+pub mod task;
+pub mod tick;
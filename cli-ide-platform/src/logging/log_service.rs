@@ -0,0 +1,360 @@
+//! A log of structured records, kept in memory and appended to a file.
+//!
+//! Components write [`LogRecord`]s to the `LogService`, either directly with
+//! [`LogService::log`] or through the `tracing` macros once the
+//! [`LogLayer`](super::tracing_layer::LogLayer) is installed. Records below
+//! the service's [`LogLevel`] are discarded. The service keeps the newest
+//! [`MAX_RECORDS`] records for viewers that open late, appends every record
+//! to its log file, if it has one, and fires
+//! [`on_did_log`](LogService::on_did_log) for viewers that are open.
+
+use std::collections::VecDeque;
+use std::env;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{Mutex, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use cli_ide_base::Event;
+
+/// Records kept in memory before the oldest are dropped.
+pub const MAX_RECORDS: usize = 1_000;
+
+/// How important a record is, from most to least.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum LogLevel {
+    /// Something failed.
+    Error,
+    /// Something looks wrong but work goes on.
+    Warn,
+    /// A notable step, such as a command starting.
+    #[default]
+    Info,
+    /// Detail useful when debugging.
+    Debug,
+    /// Very detailed tracing.
+    Trace,
+}
+
+impl LogLevel {
+    /// The level's name, as written in the log and in settings.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+            LogLevel::Trace => "trace",
+        }
+    }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Error returned when parsing an unknown log level.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseLogLevelError(String);
+
+impl fmt::Display for ParseLogLevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown log level '{}' (expected 'error', 'warn', 'info', 'debug' or 'trace')",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseLogLevelError {}
+
+impl FromStr for LogLevel {
+    type Err = ParseLogLevelError;
+
+    /// Parse a level name, ignoring case.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "error" => Ok(LogLevel::Error),
+            "warn" => Ok(LogLevel::Warn),
+            "info" => Ok(LogLevel::Info),
+            "debug" => Ok(LogLevel::Debug),
+            "trace" => Ok(LogLevel::Trace),
+            _ => Err(ParseLogLevelError(value.to_string())),
+        }
+    }
+}
+
+/// One entry in the log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogRecord {
+    /// When the record was written.
+    pub time: SystemTime,
+    /// How important the record is.
+    pub level: LogLevel,
+    /// Component that wrote the record, e.g. `cli_ide_workbench::app`.
+    pub target: String,
+    /// What happened.
+    pub message: String,
+    /// Extra key-value pairs, in the order they were given.
+    pub fields: Vec<(String, String)>,
+}
+
+impl LogRecord {
+    /// A record written now, without fields.
+    pub fn new(level: LogLevel, target: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            time: SystemTime::now(),
+            level,
+            target: target.into(),
+            message: message.into(),
+            fields: Vec::new(),
+        }
+    }
+
+    /// Add a key-value pair.
+    pub fn with_field(mut self, key: impl Into<String>, value: impl fmt::Display) -> Self {
+        self.fields.push((key.into(), value.to_string()));
+        self
+    }
+}
+
+impl fmt::Display for LogRecord {
+    /// Format the record as a log file line, e.g.
+    /// `2024-05-01T12:00:00.000Z  INFO cli_ide_workbench::app: cargo build started cwd=/ws`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:>5} {}: {}",
+            Timestamp(self.time),
+            self.level.as_str().to_ascii_uppercase(),
+            self.target,
+            self.message
+        )?;
+        for (key, value) in &self.fields {
+            write!(f, " {key}={value}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats a time as an RFC 3339 UTC timestamp with milliseconds.
+struct Timestamp(SystemTime);
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let since_epoch = self.0.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let seconds = since_epoch.as_secs();
+        let (year, month, day) = civil_from_days(seconds / 86_400);
+        let seconds_of_day = seconds % 86_400;
+        write!(
+            f,
+            "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+            seconds_of_day / 3_600,
+            seconds_of_day % 3_600 / 60,
+            seconds_of_day % 60,
+            since_epoch.subsec_millis()
+        )
+    }
+}
+
+/// Convert days since 1970-01-01 to a (year, month, day) date.
+///
+/// Howard Hinnant's `civil_from_days`, restricted to dates after the epoch.
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    (year, month, day)
+}
+
+/// The log file and where it lives.
+struct LogFile {
+    path: PathBuf,
+    file: File,
+}
+
+/// Collects log records and hands them to the log file and viewers.
+pub struct LogService {
+    /// Least important level that is recorded.
+    level: RwLock<LogLevel>,
+    /// The newest records, oldest first.
+    records: Mutex<VecDeque<LogRecord>>,
+    /// File every record is appended to, if any.
+    file: Mutex<Option<LogFile>>,
+    /// Fired after a record is written.
+    on_did_log: Event<LogRecord>,
+}
+
+impl Default for LogService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogService {
+    /// Create a service that keeps records in memory only, at
+    /// [`LogLevel::Info`].
+    pub fn new() -> Self {
+        Self {
+            level: RwLock::new(LogLevel::default()),
+            records: Mutex::new(VecDeque::new()),
+            file: Mutex::new(None),
+            on_did_log: Event::new(),
+        }
+    }
+
+    /// The default log file: `$XDG_STATE_HOME/paradiddle/paradiddle.log`,
+    /// falling back to `$HOME/.local/state/paradiddle/paradiddle.log`.
+    ///
+    /// Returns `None` if neither variable is set.
+    pub fn default_path() -> Option<PathBuf> {
+        let base = env::var_os("XDG_STATE_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| {
+                env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("state"))
+            })?;
+        Some(base.join("paradiddle").join("paradiddle.log"))
+    }
+
+    /// Append records to the file at `path` from now on, creating it and
+    /// its parent directories if needed.
+    ///
+    /// On error the previous file, if any, is kept.
+    pub fn set_file(&self, path: impl Into<PathBuf>) -> io::Result<()> {
+        let path = path.into();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        *self.file.lock().expect("log file lock poisoned") = Some(LogFile { path, file });
+        Ok(())
+    }
+
+    /// Get the file records are appended to, if any.
+    pub fn path(&self) -> Option<PathBuf> {
+        self.file
+            .lock()
+            .expect("log file lock poisoned")
+            .as_ref()
+            .map(|file| file.path.clone())
+    }
+
+    /// Get the least important level that is recorded.
+    pub fn level(&self) -> LogLevel {
+        *self.level.read().expect("log level lock poisoned")
+    }
+
+    /// Record only records at `level` or more important from now on.
+    pub fn set_level(&self, level: LogLevel) {
+        *self.level.write().expect("log level lock poisoned") = level;
+    }
+
+    /// Check whether a record at `level` would be recorded.
+    pub fn enabled(&self, level: LogLevel) -> bool {
+        level <= self.level()
+    }
+
+    /// Write a record, unless its level is disabled.
+    ///
+    /// A failure to write the log file is not reported: logging must never
+    /// take the IDE down.
+    pub fn log(&self, record: LogRecord) {
+        if !self.enabled(record.level) {
+            return;
+        }
+        if let Some(file) = self.file.lock().expect("log file lock poisoned").as_mut() {
+            let _ = writeln!(file.file, "{record}");
+        }
+        {
+            let mut records = self.records.lock().expect("log records lock poisoned");
+            if records.len() == MAX_RECORDS {
+                records.pop_front();
+            }
+            records.push_back(record.clone());
+        }
+        self.on_did_log.emit(record);
+    }
+
+    /// Get the records kept in memory, oldest first.
+    pub fn records(&self) -> Vec<LogRecord> {
+        self.records
+            .lock()
+            .expect("log records lock poisoned")
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Event fired after a record is written.
+    pub fn on_did_log(&self) -> &Event<LogRecord> {
+        &self.on_did_log
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use cli_ide_base::test_support::{temp_dir, Cleanup};
+
+    use super::*;
+
+    #[test]
+    fn test_records_below_the_level_are_dropped() {
+        let service = LogService::new();
+        let events = service.on_did_log().subscribe();
+        service.log(LogRecord::new(LogLevel::Debug, "test", "hidden"));
+        service.log(LogRecord::new(LogLevel::Warn, "test", "shown"));
+        assert_eq!(service.records().len(), 1);
+        assert_eq!(events.try_iter().count(), 1);
+
+        service.set_level(LogLevel::Trace);
+        service.log(LogRecord::new(LogLevel::Debug, "test", "shown"));
+        assert_eq!(service.records().len(), 2);
+    }
+
+    #[test]
+    fn test_records_are_appended_to_the_file() {
+        let dir = temp_dir("log");
+        let _cleanup = Cleanup::new([&dir]);
+        let path = dir.join("logs").join("test.log");
+        let service = LogService::new();
+        service.set_file(&path).unwrap();
+        assert_eq!(service.path(), Some(path.clone()));
+
+        let mut record = LogRecord::new(LogLevel::Error, "cli_ide_workbench::app", "save failed")
+            .with_field("path", "/ws/a.rs");
+        record.time = UNIX_EPOCH + Duration::from_millis(1_714_564_800_250);
+        service.log(record);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "2024-05-01T12:00:00.250Z ERROR cli_ide_workbench::app: save failed path=/ws/a.rs\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_levels() {
+        assert_eq!("WARN".parse(), Ok(LogLevel::Warn));
+        assert_eq!("trace".parse(), Ok(LogLevel::Trace));
+        let error = "loud".parse::<LogLevel>().unwrap_err();
+        assert!(error.to_string().contains("'loud'"));
+    }
+}
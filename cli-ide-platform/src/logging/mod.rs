@@ -0,0 +1,4 @@
+//! Structured logging and the `tracing` integration that feeds it.
+
+pub mod log_service;
+pub mod tracing_layer;
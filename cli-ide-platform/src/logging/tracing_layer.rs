@@ -0,0 +1,137 @@
+//! Routes `tracing` events into a [`LogService`].
+//!
+//! Once [`install`] has run, the `tracing` macros anywhere in the process
+//! write to the service:
+//!
+//! ```ignore
+//! tracing::warn!(path = %path.display(), "save failed: {error}");
+//! ```
+//!
+//! The event's message becomes the record's message and its other fields
+//! become the record's fields. Spans are not recorded.
+
+use std::fmt;
+use std::sync::Arc;
+
+use tracing::field::{Field, Visit};
+use tracing::subscriber::{Interest, SetGlobalDefaultError};
+use tracing::{Level, Metadata, Subscriber};
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::registry::Registry;
+
+use super::log_service::{LogLevel, LogRecord, LogService};
+
+/// A `tracing` layer that writes events to a [`LogService`].
+pub struct LogLayer {
+    service: Arc<LogService>,
+}
+
+impl LogLayer {
+    /// Create a layer writing to `service`.
+    pub fn new(service: Arc<LogService>) -> Self {
+        Self { service }
+    }
+}
+
+/// Convert a `tracing` level to a log level.
+fn log_level(level: &Level) -> LogLevel {
+    match *level {
+        Level::ERROR => LogLevel::Error,
+        Level::WARN => LogLevel::Warn,
+        Level::INFO => LogLevel::Info,
+        Level::DEBUG => LogLevel::Debug,
+        Level::TRACE => LogLevel::Trace,
+    }
+}
+
+impl<S: Subscriber> Layer<S> for LogLayer {
+    fn register_callsite(&self, _metadata: &'static Metadata<'static>) -> Interest {
+        // The service's level can change at any time, so `tracing` must not
+        // cache whether a call site is enabled
+        Interest::sometimes()
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>, _ctx: Context<'_, S>) -> bool {
+        metadata.is_event() && self.service.enabled(log_level(metadata.level()))
+    }
+
+    fn on_event(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let mut visitor = RecordVisitor::default();
+        event.record(&mut visitor);
+        let mut record = LogRecord::new(
+            log_level(metadata.level()),
+            metadata.target(),
+            visitor.message,
+        );
+        record.fields = visitor.fields;
+        self.service.log(record);
+    }
+}
+
+/// Collects an event's message and fields.
+#[derive(Default)]
+struct RecordVisitor {
+    message: String,
+    fields: Vec<(String, String)>,
+}
+
+impl RecordVisitor {
+    fn push(&mut self, field: &Field, value: String) {
+        if field.name() == "message" {
+            self.message = value;
+        } else {
+            self.fields.push((field.name().to_string(), value));
+        }
+    }
+}
+
+impl Visit for RecordVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.push(field, value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.push(field, format!("{value:?}"));
+    }
+}
+
+/// Make `service` the destination of every `tracing` event in the process.
+///
+/// Fails if a global subscriber is already set.
+pub fn install(service: Arc<LogService>) -> Result<(), SetGlobalDefaultError> {
+    tracing::subscriber::set_global_default(Registry::default().with(LogLayer::new(service)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracing_macros_write_records() {
+        let service = Arc::new(LogService::new());
+        let subscriber = Registry::default().with(LogLayer::new(Arc::clone(&service)));
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(path = "/ws/a.rs", lines = 3, "opened {}", "a.rs");
+            tracing::debug!("not recorded at the default level");
+        });
+
+        let records = service.records();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].level, LogLevel::Info);
+        assert_eq!(records[0].message, "opened a.rs");
+        assert_eq!(
+            records[0].fields,
+            [
+                ("path".to_string(), "/ws/a.rs".to_string()),
+                ("lines".to_string(), "3".to_string()),
+            ]
+        );
+        assert!(records[0].target.ends_with("tracing_layer::tests"));
+
+        service.set_level(LogLevel::Debug);
+        let subscriber = Registry::default().with(LogLayer::new(Arc::clone(&service)));
+        tracing::subscriber::with_default(subscriber, || tracing::debug!("now recorded"));
+        assert_eq!(service.records().len(), 2);
+    }
+}
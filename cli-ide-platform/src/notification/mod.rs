@@ -0,0 +1,3 @@
+//! Notifications shown to the user and the rules that silence them.
+
+pub mod notification_service;
@@ -0,0 +1,343 @@
+//! Notifications and the rules that silence them.
+//!
+//! Features tell the user about something that happened with
+//! [`NotificationService::notify`]: a message from a named source, such as
+//! `Cargo` or `Git`, with a [`NotificationSeverity`]. The workbench shows
+//! each notification as a toast until it is dismissed, unless the
+//! [`NotificationFilter`] suppresses it: its source or severity is muted,
+//! or "do not disturb" is on and it is not an error. Toasts dismiss
+//! themselves after [`TOAST_DURATION`] when the workbench calls
+//! [`NotificationService::expire`] on its periodic tick. Suppressed and
+//! dismissed notifications are not lost: like every other, they are kept
+//! for the notification center, which lists the newest
+//! [`MAX_NOTIFICATIONS`].
+
+use std::collections::{BTreeSet, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// Notifications kept for the notification center before the oldest are
+/// dropped.
+pub const MAX_NOTIFICATIONS: usize = 100;
+
+/// How long a notification is shown as a toast before it expires.
+pub const TOAST_DURATION: Duration = Duration::from_secs(5);
+
+/// How important a notification is, from most to least.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum NotificationSeverity {
+    /// Something failed.
+    Error,
+    /// Something looks wrong but work goes on.
+    Warning,
+    /// Something finished or changed.
+    Info,
+}
+
+impl NotificationSeverity {
+    /// The severity's name, as written in settings.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NotificationSeverity::Error => "error",
+            NotificationSeverity::Warning => "warning",
+            NotificationSeverity::Info => "info",
+        }
+    }
+}
+
+impl fmt::Display for NotificationSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Error returned when parsing an unknown notification severity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseSeverityError(String);
+
+impl fmt::Display for ParseSeverityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown severity '{}' (expected 'error', 'warning' or 'info')",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseSeverityError {}
+
+impl FromStr for NotificationSeverity {
+    type Err = ParseSeverityError;
+
+    /// Parse a severity name, ignoring case.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "error" => Ok(NotificationSeverity::Error),
+            "warning" => Ok(NotificationSeverity::Warning),
+            "info" => Ok(NotificationSeverity::Info),
+            _ => Err(ParseSeverityError(name.to_string())),
+        }
+    }
+}
+
+/// Identifies a notification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NotificationId(pub u64);
+
+/// Something the user was told about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+    /// Identity of the notification.
+    pub id: NotificationId,
+    /// What sent it, e.g. `Cargo`.
+    pub source: String,
+    /// How important it is.
+    pub severity: NotificationSeverity,
+    /// What happened.
+    pub message: String,
+    /// When it was sent.
+    pub time: Instant,
+    /// Whether the filter kept it from being shown as a toast.
+    pub suppressed: bool,
+}
+
+/// Rules deciding which notifications are shown as toasts.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NotificationFilter {
+    /// Suppress everything but errors.
+    pub do_not_disturb: bool,
+    /// Sources whose notifications are suppressed.
+    pub muted_sources: BTreeSet<String>,
+    /// Severities whose notifications are suppressed.
+    pub muted_severities: BTreeSet<NotificationSeverity>,
+}
+
+impl NotificationFilter {
+    /// Check whether a notification from `source` with `severity` is shown.
+    pub fn allows(&self, source: &str, severity: NotificationSeverity) -> bool {
+        !(self.do_not_disturb && severity != NotificationSeverity::Error
+            || self.muted_sources.contains(source)
+            || self.muted_severities.contains(&severity))
+    }
+}
+
+/// Notifications and the filter, behind the service's lock.
+#[derive(Debug, Default)]
+struct State {
+    /// Source of notification identities.
+    next_id: u64,
+    /// The newest notifications, oldest first, and whether each was
+    /// dismissed as a toast.
+    notifications: VecDeque<(Notification, bool)>,
+    /// Rules applied to new notifications.
+    filter: NotificationFilter,
+}
+
+/// Collects notifications for the notification center and decides which
+/// are shown as toasts.
+#[derive(Debug, Default)]
+pub struct NotificationService {
+    /// Notifications and the filter.
+    state: Mutex<State>,
+}
+
+impl NotificationService {
+    /// Create a service with no notifications and nothing muted.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lock the state, ignoring poisoning: notifications are plain records.
+    fn locked(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|error| error.into_inner())
+    }
+
+    /// Tell the user about something `source` did.
+    ///
+    /// The notification is kept for the notification center and, unless
+    /// the filter suppresses it, shown as a toast.
+    pub fn notify(
+        &self,
+        source: impl Into<String>,
+        severity: NotificationSeverity,
+        message: impl Into<String>,
+    ) -> NotificationId {
+        let source = source.into();
+        let mut state = self.locked();
+        state.next_id += 1;
+        let id = NotificationId(state.next_id);
+        let suppressed = !state.filter.allows(&source, severity);
+        if state.notifications.len() == MAX_NOTIFICATIONS {
+            state.notifications.pop_front();
+        }
+        state.notifications.push_back((
+            Notification {
+                id,
+                source,
+                severity,
+                message: message.into(),
+                time: Instant::now(),
+                suppressed,
+            },
+            false,
+        ));
+        id
+    }
+
+    /// Get the kept notifications, oldest first, suppressed ones included.
+    pub fn notifications(&self) -> Vec<Notification> {
+        self.locked()
+            .notifications
+            .iter()
+            .map(|(notification, _)| notification.clone())
+            .collect()
+    }
+
+    /// Get the identity of the newest notification, if any, to tell
+    /// whether notifications arrived since it was last asked.
+    pub fn last_id(&self) -> Option<NotificationId> {
+        self.locked()
+            .notifications
+            .back()
+            .map(|(notification, _)| notification.id)
+    }
+
+    /// Get the notifications shown as toasts, oldest first: those that
+    /// were neither suppressed nor dismissed.
+    pub fn toasts(&self) -> Vec<Notification> {
+        self.locked()
+            .notifications
+            .iter()
+            .filter(|(notification, dismissed)| !dismissed && !notification.suppressed)
+            .map(|(notification, _)| notification.clone())
+            .collect()
+    }
+
+    /// Dismiss the toasts shown for [`TOAST_DURATION`] or longer at `now`,
+    /// returning their identities.
+    pub fn expire(&self, now: Instant) -> Vec<NotificationId> {
+        let mut state = self.locked();
+        state
+            .notifications
+            .iter_mut()
+            .filter(|(notification, dismissed)| {
+                !*dismissed && now.saturating_duration_since(notification.time) >= TOAST_DURATION
+            })
+            .map(|(notification, dismissed)| {
+                *dismissed = true;
+                notification.id
+            })
+            .collect()
+    }
+
+    /// Dismiss every toast.
+    pub fn dismiss_all(&self) {
+        for (_, dismissed) in self.locked().notifications.iter_mut() {
+            *dismissed = true;
+        }
+    }
+
+    /// Stop showing the notification `id` as a toast; it stays in the
+    /// notification center.
+    pub fn dismiss(&self, id: NotificationId) {
+        if let Some((_, dismissed)) = self
+            .locked()
+            .notifications
+            .iter_mut()
+            .find(|(notification, _)| notification.id == id)
+        {
+            *dismissed = true;
+        }
+    }
+
+    /// Remove every notification.
+    pub fn clear(&self) {
+        self.locked().notifications.clear();
+    }
+
+    /// Get the rules applied to new notifications.
+    pub fn filter(&self) -> NotificationFilter {
+        self.locked().filter.clone()
+    }
+
+    /// Replace the rules applied to new notifications.
+    pub fn set_filter(&self, filter: NotificationFilter) {
+        self.locked().filter = filter;
+    }
+
+    /// Check whether "do not disturb" is on.
+    pub fn do_not_disturb(&self) -> bool {
+        self.locked().filter.do_not_disturb
+    }
+
+    /// Turn "do not disturb" on or off.
+    pub fn set_do_not_disturb(&self, on: bool) {
+        self.locked().filter.do_not_disturb = on;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filtered_notifications_are_kept_but_not_toasted() {
+        let service = NotificationService::new();
+        service.set_filter(NotificationFilter {
+            muted_sources: BTreeSet::from(["Git".to_string()]),
+            muted_severities: BTreeSet::from([NotificationSeverity::Info]),
+            ..NotificationFilter::default()
+        });
+        service.notify("Git", NotificationSeverity::Error, "push failed");
+        service.notify("Cargo", NotificationSeverity::Info, "build finished");
+        let warning = service.notify("Cargo", NotificationSeverity::Warning, "2 warnings");
+        assert_eq!(service.last_id(), Some(warning));
+        let toasts = service.toasts();
+        assert_eq!(toasts.len(), 1);
+        assert_eq!(toasts[0].id, warning);
+        assert_eq!(service.notifications().len(), 3);
+
+        service.dismiss(warning);
+        assert!(service.toasts().is_empty());
+        assert!(!service.notifications()[2].suppressed);
+
+        service.set_filter(NotificationFilter::default());
+        service.set_do_not_disturb(true);
+        service.notify("Cargo", NotificationSeverity::Warning, "1 warning");
+        service.notify("Cargo", NotificationSeverity::Error, "build failed");
+        let toasts = service.toasts();
+        assert_eq!(toasts.len(), 1, "errors get through do not disturb");
+        assert_eq!(toasts[0].message, "build failed");
+
+        service.clear();
+        assert!(service.notifications().is_empty());
+    }
+
+    #[test]
+    fn test_toasts_expire_after_their_duration() {
+        let service = NotificationService::new();
+        let first = service.notify("Cargo", NotificationSeverity::Info, "build started");
+        let second = service.notify("Cargo", NotificationSeverity::Error, "build failed");
+        let sent = service.notifications()[1].time;
+
+        assert!(service.expire(sent).is_empty());
+        assert_eq!(service.toasts().len(), 2);
+        assert_eq!(service.expire(sent + TOAST_DURATION), [first, second]);
+        assert!(service.toasts().is_empty());
+        assert!(service.expire(sent + TOAST_DURATION * 2).is_empty());
+        assert_eq!(service.notifications().len(), 2, "kept for the center");
+
+        service.notify("Git", NotificationSeverity::Warning, "detached HEAD");
+        service.dismiss_all();
+        assert!(service.toasts().is_empty());
+    }
+
+    #[test]
+    fn test_severities_parse_ignoring_case() {
+        assert_eq!("Warning".parse(), Ok(NotificationSeverity::Warning));
+        assert!("fatal".parse::<NotificationSeverity>().is_err());
+    }
+}
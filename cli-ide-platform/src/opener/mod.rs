@@ -0,0 +1,3 @@
+//! Opening links outside the IDE, e.g. URLs in the user's browser.
+
+pub mod opener_service;
@@ -0,0 +1,155 @@
+//! Opening URLs in the user's browser.
+//!
+//! The `OpenerService` hands URLs to a [`UrlOpener`]. [`OpenerService::system`]
+//! looks for the host's opener with [`CommandOpener::detect`]: `open` on
+//! macOS, `start` through `cmd` on Windows and `xdg-open` elsewhere.
+//! Without one, e.g. in headless tests or over SSH, opening fails with an
+//! error the caller can report.
+
+use std::io;
+use std::process::{Command, Stdio};
+
+use crate::clipboard::clipboard_service::on_path;
+
+/// Something that opens URLs, usually in the user's browser.
+pub trait UrlOpener: Send + Sync {
+    /// Name of the opener, e.g. `xdg-open`, for reporting.
+    fn name(&self) -> &str;
+
+    /// Open `url`.
+    fn open(&self, url: &str) -> io::Result<()>;
+}
+
+/// An opener reached through a command line tool taking the URL as its
+/// last argument.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandOpener {
+    /// Program and arguments the URL is appended to.
+    command: Vec<String>,
+}
+
+impl CommandOpener {
+    /// Create an opener running `command`, which starts with the program,
+    /// with the URL appended.
+    pub fn new(command: &[&str]) -> Self {
+        Self {
+            command: command.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    /// Find the opener of the host, if one is installed.
+    pub fn detect() -> Option<Self> {
+        let opener = if cfg!(target_os = "macos") {
+            Self::new(&["open"])
+        } else if cfg!(windows) {
+            Self::new(&["cmd", "/C", "start", ""])
+        } else {
+            Self::new(&["xdg-open"])
+        };
+        on_path(&opener.command[0]).then_some(opener)
+    }
+}
+
+impl UrlOpener for CommandOpener {
+    fn name(&self) -> &str {
+        &self.command[0]
+    }
+
+    fn open(&self, url: &str) -> io::Result<()> {
+        let status = Command::new(&self.command[0])
+            .args(&self.command[1..])
+            .arg(url)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(io::Error::other(format!(
+                "{} exited with {status}",
+                self.command[0]
+            )))
+        }
+    }
+}
+
+/// Opens URLs through the host's opener when there is one.
+#[derive(Default)]
+pub struct OpenerService {
+    /// The host's opener, if one was found.
+    opener: Option<Box<dyn UrlOpener>>,
+}
+
+impl OpenerService {
+    /// Create a service that cannot open URLs.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a service that opens URLs with `opener`.
+    pub fn with_opener(opener: Box<dyn UrlOpener>) -> Self {
+        Self {
+            opener: Some(opener),
+        }
+    }
+
+    /// Create a service using the host's opener, or one that cannot open
+    /// URLs if there is none.
+    pub fn system() -> Self {
+        match CommandOpener::detect() {
+            Some(opener) => Self::with_opener(Box::new(opener)),
+            None => Self::new(),
+        }
+    }
+
+    /// Get the name of the opener, or `None` if URLs cannot be opened.
+    pub fn opener_name(&self) -> Option<&str> {
+        self.opener.as_deref().map(UrlOpener::name)
+    }
+
+    /// Open `url`, failing if there is no opener or it fails.
+    pub fn open(&self, url: &str) -> io::Result<()> {
+        match &self.opener {
+            Some(opener) => opener.open(url),
+            None => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "no program to open links with",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// An opener remembering the URLs it opened.
+    struct FakeOpener(Arc<Mutex<Vec<String>>>);
+
+    impl UrlOpener for FakeOpener {
+        fn name(&self) -> &str {
+            "fake"
+        }
+
+        fn open(&self, url: &str) -> io::Result<()> {
+            self.0.lock().unwrap().push(url.to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_urls_open_through_the_opener_or_fail_without_one() {
+        let opened = Arc::new(Mutex::new(Vec::new()));
+        let service = OpenerService::with_opener(Box::new(FakeOpener(Arc::clone(&opened))));
+        assert_eq!(service.opener_name(), Some("fake"));
+        service.open("https://example.com").unwrap();
+        assert_eq!(*opened.lock().unwrap(), vec!["https://example.com"]);
+
+        let headless = OpenerService::new();
+        assert_eq!(headless.opener_name(), None);
+        let error = headless.open("https://example.com").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::Unsupported);
+    }
+}
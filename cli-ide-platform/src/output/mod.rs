@@ -0,0 +1,3 @@
+//! Named output channels that features write their output to.
+
+pub mod output_service;
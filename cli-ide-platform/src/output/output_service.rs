@@ -0,0 +1,167 @@
+//! Named output channels.
+//!
+//! Like VS Code's `OutputChannel`, a feature asks the `OutputService` for a
+//! channel by name, e.g. `Cargo` or `Git`, and appends lines to it without
+//! knowing whether anyone is looking. Viewers list the channels with
+//! [`OutputService::channel_names`] and read the lines of the one shown.
+//! Each channel keeps its newest [`MAX_CHANNEL_LINES`] lines.
+
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Lines a channel keeps before the oldest are dropped.
+pub const MAX_CHANNEL_LINES: usize = 10_000;
+
+/// A named stream of output lines.
+///
+/// Clones write to the same channel.
+#[derive(Debug, Clone)]
+pub struct OutputChannel {
+    /// Name shown in the channel list.
+    name: Arc<str>,
+    /// The newest lines, oldest first.
+    lines: Arc<Mutex<VecDeque<String>>>,
+}
+
+impl OutputChannel {
+    /// Create an empty channel named `name`.
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.into(),
+            lines: Arc::default(),
+        }
+    }
+
+    /// Get the channel's name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Lock the lines, recovering them if a writer panicked.
+    fn locked(&self) -> MutexGuard<'_, VecDeque<String>> {
+        self.lines.lock().unwrap_or_else(|error| error.into_inner())
+    }
+
+    /// Append a line at the end.
+    pub fn append_line(&self, line: impl Into<String>) {
+        let mut lines = self.locked();
+        if lines.len() == MAX_CHANNEL_LINES {
+            lines.pop_front();
+        }
+        lines.push_back(line.into());
+    }
+
+    /// Remove every line.
+    pub fn clear(&self) {
+        self.locked().clear();
+    }
+
+    /// Number of lines kept.
+    pub fn len(&self) -> usize {
+        self.locked().len()
+    }
+
+    /// Check whether the channel has no lines.
+    pub fn is_empty(&self) -> bool {
+        self.locked().is_empty()
+    }
+
+    /// Get the lines in `range`, clamped to the lines kept.
+    pub fn lines_in(&self, range: Range<usize>) -> Vec<String> {
+        let lines = self.locked();
+        let end = range.end.min(lines.len());
+        let start = range.start.min(end);
+        lines.range(start..end).cloned().collect()
+    }
+
+    /// Get every line kept, oldest first.
+    pub fn lines(&self) -> Vec<String> {
+        self.locked().iter().cloned().collect()
+    }
+}
+
+/// Creates output channels and lists them for viewers.
+#[derive(Debug, Default)]
+pub struct OutputService {
+    /// The channels, in the order they were created.
+    channels: Mutex<Vec<OutputChannel>>,
+}
+
+impl OutputService {
+    /// Create a service with no channels.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the channel named `name`, creating it if it does not exist.
+    pub fn channel(&self, name: &str) -> OutputChannel {
+        let mut channels = self
+            .channels
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+        if let Some(channel) = channels.iter().find(|channel| channel.name() == name) {
+            return channel.clone();
+        }
+        let channel = OutputChannel::new(name);
+        channels.push(channel.clone());
+        channel
+    }
+
+    /// Get the channel named `name`, if it exists.
+    pub fn get(&self, name: &str) -> Option<OutputChannel> {
+        self.channels
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .iter()
+            .find(|channel| channel.name() == name)
+            .cloned()
+    }
+
+    /// Get the names of the channels, in the order they were created.
+    pub fn channel_names(&self) -> Vec<String> {
+        self.channels
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .iter()
+            .map(|channel| channel.name().to_string())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channels_are_shared_by_name() {
+        let service = OutputService::new();
+        let cargo = service.channel("Cargo");
+        service
+            .channel("Git")
+            .append_line("fatal: not a git repository");
+        service.channel("Cargo").append_line("Compiling demo");
+        assert_eq!(cargo.lines(), ["Compiling demo"]);
+        assert_eq!(service.channel_names(), ["Cargo", "Git"]);
+        assert!(service.get("Tasks").is_none());
+
+        cargo.clear();
+        assert!(service.get("Cargo").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_oldest_lines_are_dropped_past_the_limit() {
+        let channel = OutputService::new().channel("Output");
+        for index in 0..=MAX_CHANNEL_LINES {
+            channel.append_line(index.to_string());
+        }
+        assert_eq!(channel.len(), MAX_CHANNEL_LINES);
+        assert_eq!(channel.lines_in(0..2), ["1", "2"]);
+        assert_eq!(
+            channel
+                .lines_in(MAX_CHANNEL_LINES - 1..MAX_CHANNEL_LINES + 5)
+                .len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,6 @@
+//! Problems reported by compilers and linters, and the matchers that read
+//! them from command output.
+
+pub mod problem;
+pub mod problem_matcher;
+pub mod rustc_matcher;
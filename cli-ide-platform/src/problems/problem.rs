@@ -0,0 +1,85 @@
+//! A diagnostic at a location in a file.
+
+use std::fmt;
+use std::path::PathBuf;
+
+/// How serious a problem is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// The build fails because of it.
+    Error,
+    /// The build succeeds, but something looks wrong.
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        })
+    }
+}
+
+/// A problem found in a file, such as a compiler error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    /// Absolute path of the file.
+    pub path: PathBuf,
+    /// Line of the problem, starting at 1.
+    pub line: usize,
+    /// Column of the problem in characters, starting at 1.
+    pub column: usize,
+    /// How serious the problem is.
+    pub severity: Severity,
+    /// What is wrong.
+    pub message: String,
+    /// Diagnostic code, e.g. `E0425`, if there is one.
+    pub code: Option<String>,
+}
+
+/// Count the errors and warnings among `problems`, e.g. `1 error, 2
+/// warnings`, or `No problems`.
+pub fn summarize(problems: &[Problem]) -> String {
+    if problems.is_empty() {
+        return "No problems".to_string();
+    }
+    let count = |severity: Severity| {
+        let count = problems
+            .iter()
+            .filter(|problem| problem.severity == severity)
+            .count();
+        let plural = if count == 1 { "" } else { "s" };
+        format!("{count} {severity}{plural}")
+    };
+    format!("{}, {}", count(Severity::Error), count(Severity::Warning))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problem(severity: Severity) -> Problem {
+        Problem {
+            path: PathBuf::from("/ws/src/lib.rs"),
+            line: 1,
+            column: 1,
+            severity,
+            message: "oops".to_string(),
+            code: None,
+        }
+    }
+
+    #[test]
+    fn test_summarize_counts_errors_and_warnings() {
+        assert_eq!(summarize(&[]), "No problems");
+        assert_eq!(
+            summarize(&[
+                problem(Severity::Warning),
+                problem(Severity::Error),
+                problem(Severity::Warning),
+            ]),
+            "1 error, 2 warnings"
+        );
+    }
+}
@@ -0,0 +1,283 @@
+//! Problem matchers: turn lines of command output into problems.
+//!
+//! A [`ProblemMatcher`] is fed output one line at a time. Besides the
+//! built-in [`RustcMatcher`](super::rustc_matcher::RustcMatcher), users
+//! describe other compilers and linters with a [`ProblemPattern`]: a regex
+//! matching a single line, whose named groups say where the parts of the
+//! problem are:
+//!
+//! | Group | Required | Content |
+//! |-------|----------|---------|
+//! | `file` | yes | Path, relative to the directory the command ran in or absolute |
+//! | `line` | yes | Line number, starting at 1 |
+//! | `column` | no | Column number, starting at 1 (default 1) |
+//! | `severity` | no | `error` or `warning` (case-insensitive) |
+//! | `message` | yes | What is wrong |
+//! | `code` | no | Diagnostic code |
+//!
+//! For example, `^(?P<file>[^:]+):(?P<line>\d+):(?P<column>\d+): (?P<message>.*)$`
+//! matches `src/app.ts:3:7: missing semicolon`.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+
+use super::problem::{Problem, Severity};
+use super::rustc_matcher::RustcMatcher;
+
+/// Named groups a pattern must have.
+const REQUIRED_GROUPS: [&str; 3] = ["file", "line", "message"];
+
+/// Reads problems from command output, line by line.
+pub trait ProblemMatcher: Send {
+    /// Feed the next line of output, returning the problem it completes.
+    fn feed(&mut self, line: &str) -> Option<Problem>;
+}
+
+impl ProblemMatcher for RustcMatcher {
+    fn feed(&mut self, line: &str) -> Option<Problem> {
+        RustcMatcher::feed(self, line)
+    }
+}
+
+/// Error returned when a problem pattern is not valid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProblemPatternError {
+    /// The regex does not compile.
+    Regex {
+        /// Name of the pattern.
+        name: String,
+        /// Description of the regex error.
+        message: String,
+    },
+    /// The regex lacks a required named group.
+    MissingGroup {
+        /// Name of the pattern.
+        name: String,
+        /// The missing group, e.g. `file`.
+        group: &'static str,
+    },
+    /// The default severity is not `error` or `warning`.
+    Severity {
+        /// Name of the pattern.
+        name: String,
+        /// The severity given.
+        severity: String,
+    },
+}
+
+impl fmt::Display for ProblemPatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProblemPatternError::Regex { name, message } => {
+                write!(f, "problem matcher `{name}`: {message}")
+            }
+            ProblemPatternError::MissingGroup { name, group } => {
+                write!(f, "problem matcher `{name}`: no `(?P<{group}>...)` group")
+            }
+            ProblemPatternError::Severity { name, severity } => write!(
+                f,
+                "problem matcher `{name}`: unknown severity '{severity}' \
+                 (expected 'error' or 'warning')"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ProblemPatternError {}
+
+/// A regex describing one line of a tool's output that reports a problem.
+#[derive(Debug, Clone)]
+pub struct ProblemPattern {
+    /// Name of the pattern, e.g. `eslint`.
+    name: String,
+    /// The compiled regex.
+    regex: Regex,
+    /// Severity of problems whose line has no `severity` group match.
+    severity: Severity,
+}
+
+impl ProblemPattern {
+    /// Compile a pattern named `name`.
+    ///
+    /// Problems without a recognised `severity` group get `severity`.
+    pub fn new(
+        name: impl Into<String>,
+        regex: &str,
+        severity: Severity,
+    ) -> Result<Self, ProblemPatternError> {
+        let name = name.into();
+        let regex = match Regex::new(regex) {
+            Ok(regex) => regex,
+            Err(error) => {
+                return Err(ProblemPatternError::Regex {
+                    name,
+                    message: error.to_string(),
+                })
+            }
+        };
+        let groups: Vec<&str> = regex.capture_names().flatten().collect();
+        if let Some(group) = REQUIRED_GROUPS
+            .into_iter()
+            .find(|group| !groups.contains(group))
+        {
+            return Err(ProblemPatternError::MissingGroup { name, group });
+        }
+        Ok(Self {
+            name,
+            regex,
+            severity,
+        })
+    }
+
+    /// Compile a pattern whose default severity is given by name, `error`
+    /// when `None`.
+    pub fn parse(
+        name: impl Into<String>,
+        regex: &str,
+        severity: Option<&str>,
+    ) -> Result<Self, ProblemPatternError> {
+        let name = name.into();
+        let severity = match severity {
+            None => Severity::Error,
+            Some(text) => match parse_severity(text) {
+                Some(severity) => severity,
+                None => {
+                    return Err(ProblemPatternError::Severity {
+                        name,
+                        severity: text.to_string(),
+                    })
+                }
+            },
+        };
+        Self::new(name, regex, severity)
+    }
+
+    /// Get the name of the pattern.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Create a matcher for output of a command run in `root`.
+    pub fn matcher(&self, root: impl Into<PathBuf>) -> PatternMatcher {
+        PatternMatcher {
+            pattern: self.clone(),
+            root: root.into(),
+        }
+    }
+
+    /// Read the problem on `line`, resolving relative paths against `root`.
+    fn match_line(&self, line: &str, root: &Path) -> Option<Problem> {
+        let captures = self.regex.captures(line)?;
+        let text = |group: &str| captures.name(group).map(|found| found.as_str());
+        let number = |group: &str| text(group)?.parse::<usize>().ok();
+        let path = text("file")?.trim();
+        if path.is_empty() {
+            return None;
+        }
+        Some(Problem {
+            path: root.join(path),
+            line: number("line")?,
+            column: number("column").unwrap_or(1),
+            severity: text("severity")
+                .and_then(parse_severity)
+                .unwrap_or(self.severity),
+            message: text("message")?.trim().to_string(),
+            code: text("code")
+                .filter(|code| !code.is_empty())
+                .map(str::to_string),
+        })
+    }
+}
+
+/// Parse a severity such as `error`, `Warning` or `W`.
+fn parse_severity(text: &str) -> Option<Severity> {
+    match text.trim().to_ascii_lowercase().as_str() {
+        "error" | "err" | "e" | "fatal" => Some(Severity::Error),
+        "warning" | "warn" | "w" => Some(Severity::Warning),
+        _ => None,
+    }
+}
+
+/// Reads problems matching a [`ProblemPattern`] from command output.
+#[derive(Debug, Clone)]
+pub struct PatternMatcher {
+    /// The pattern problem lines match.
+    pattern: ProblemPattern,
+    /// Directory relative paths in the output are resolved against.
+    root: PathBuf,
+}
+
+impl ProblemMatcher for PatternMatcher {
+    fn feed(&mut self, line: &str) -> Option<Problem> {
+        self.pattern.match_line(line, &self.root)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_named_groups_become_problem_fields() {
+        let pattern = ProblemPattern::new(
+            "gcc",
+            r"^(?P<file>[^:]+):(?P<line>\d+):(?P<column>\d+): (?P<severity>\w+): (?P<message>.*?)(?: \[(?P<code>[^\]]+)\])?$",
+            Severity::Error,
+        )
+        .unwrap();
+        let mut matcher = pattern.matcher("/ws");
+
+        assert_eq!(
+            matcher.feed("src/main.c:3:7: warning: unused variable 'x' [-Wunused-variable]"),
+            Some(Problem {
+                path: PathBuf::from("/ws/src/main.c"),
+                line: 3,
+                column: 7,
+                severity: Severity::Warning,
+                message: "unused variable 'x'".to_string(),
+                code: Some("-Wunused-variable".to_string()),
+            })
+        );
+        assert_eq!(
+            matcher
+                .feed("/abs/lib.c:10:1: note: declared here")
+                .map(|problem| (problem.path, problem.severity)),
+            Some((PathBuf::from("/abs/lib.c"), Severity::Error))
+        );
+        assert_eq!(matcher.feed("make: *** [all] Error 1"), None);
+    }
+
+    #[test]
+    fn test_column_defaults_to_one() {
+        let pattern = ProblemPattern::parse(
+            "lint",
+            r"^(?P<file>\S+) line (?P<line>\d+): (?P<message>.*)$",
+            Some("warning"),
+        )
+        .unwrap();
+        let problem = pattern
+            .matcher("/ws")
+            .feed("a.sh line 4: quote this")
+            .unwrap();
+        assert_eq!((problem.line, problem.column), (4, 1));
+        assert_eq!(problem.severity, Severity::Warning);
+    }
+
+    #[test]
+    fn test_invalid_patterns_are_rejected() {
+        let error = ProblemPattern::new("x", "(", Severity::Error).unwrap_err();
+        assert!(matches!(error, ProblemPatternError::Regex { .. }));
+
+        let error =
+            ProblemPattern::new("x", r"(?P<file>.*):(?P<line>\d+)", Severity::Error).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "problem matcher `x`: no `(?P<message>...)` group"
+        );
+
+        let error = ProblemPattern::parse("x", "", Some("loud")).unwrap_err();
+        assert!(matches!(error, ProblemPatternError::Severity { .. }));
+    }
+}
@@ -0,0 +1,172 @@
+//! Problems read from rustc's human-readable diagnostics.
+//!
+//! rustc (and so cargo and clippy) print a diagnostic as a header line
+//! followed by its primary location:
+//!
+//! ```text
+//! error[E0425]: cannot find value `x` in this scope
+//!  --> src/main.rs:2:5
+//! ```
+//!
+//! [`RustcMatcher`] is fed the output one line at a time and turns each
+//! such pair into a [`Problem`]. Headers without a location, such as
+//! `error: could not compile`, produce nothing.
+
+use std::path::{Path, PathBuf};
+
+use super::problem::{Problem, Severity};
+
+/// Reads problems from rustc output, line by line.
+#[derive(Debug, Clone)]
+pub struct RustcMatcher {
+    /// Directory relative paths in the output are resolved against.
+    root: PathBuf,
+    /// The last header, waiting for its location.
+    pending: Option<Header>,
+}
+
+/// A diagnostic header line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Header {
+    severity: Severity,
+    code: Option<String>,
+    message: String,
+}
+
+impl RustcMatcher {
+    /// Create a matcher for output of a command run in `root`.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            pending: None,
+        }
+    }
+
+    /// Get the directory relative paths are resolved against.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Feed the next line of output, returning the problem it completes.
+    pub fn feed(&mut self, line: &str) -> Option<Problem> {
+        if let Some(header) = parse_header(line) {
+            self.pending = Some(header);
+            return None;
+        }
+        if !line.starts_with(char::is_whitespace) {
+            // Any other unindented line, e.g. `note: ...`, ends the
+            // diagnostic, so a later location is not attributed to it.
+            self.pending = None;
+            return None;
+        }
+        let location = line.trim_start().strip_prefix("--> ")?;
+        let (path, line_number, column) = parse_location(location)?;
+        let header = self.pending.take()?;
+        Some(Problem {
+            path: self.root.join(path),
+            line: line_number,
+            column,
+            severity: header.severity,
+            message: header.message,
+            code: header.code,
+        })
+    }
+}
+
+/// Parse `error[E0425]: message` or `warning: message`.
+fn parse_header(line: &str) -> Option<Header> {
+    let (severity, rest) = if let Some(rest) = line.strip_prefix("error") {
+        (Severity::Error, rest)
+    } else if let Some(rest) = line.strip_prefix("warning") {
+        (Severity::Warning, rest)
+    } else {
+        return None;
+    };
+    let (code, rest) = match rest.strip_prefix('[') {
+        Some(rest) => {
+            let (code, rest) = rest.split_once(']')?;
+            (Some(code.to_string()), rest)
+        }
+        None => (None, rest),
+    };
+    let message = rest.strip_prefix(": ")?;
+    Some(Header {
+        severity,
+        code,
+        message: message.to_string(),
+    })
+}
+
+/// Parse `src/main.rs:2:5` into the path, line and column.
+fn parse_location(location: &str) -> Option<(&str, usize, usize)> {
+    let (rest, column) = location.rsplit_once(':')?;
+    let (path, line) = rest.rsplit_once(':')?;
+    Some((path, line.parse().ok()?, column.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed_all(matcher: &mut RustcMatcher, output: &str) -> Vec<Problem> {
+        output
+            .lines()
+            .filter_map(|line| matcher.feed(line))
+            .collect()
+    }
+
+    #[test]
+    fn test_errors_and_warnings_with_locations_become_problems() {
+        let output = "\
+   Compiling demo v0.1.0 (/work/demo)
+warning: unused variable: `y`
+ --> src/lib.rs:3:9
+  |
+3 |     let y = 1;
+  |         ^ help: if this is intentional, prefix it with an underscore: `_y`
+  |
+  = note: `#[warn(unused_variables)]` on by default
+
+error[E0425]: cannot find value `x` in this scope
+  --> src/main.rs:12:5
+   |
+12 |     x
+   |     ^ not found in this scope
+
+error: could not compile `demo` (bin \"demo\") due to 1 previous error
+";
+        let mut matcher = RustcMatcher::new("/work/demo");
+        assert_eq!(
+            feed_all(&mut matcher, output),
+            vec![
+                Problem {
+                    path: PathBuf::from("/work/demo/src/lib.rs"),
+                    line: 3,
+                    column: 9,
+                    severity: Severity::Warning,
+                    message: "unused variable: `y`".to_string(),
+                    code: None,
+                },
+                Problem {
+                    path: PathBuf::from("/work/demo/src/main.rs"),
+                    line: 12,
+                    column: 5,
+                    severity: Severity::Error,
+                    message: "cannot find value `x` in this scope".to_string(),
+                    code: Some("E0425".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_locations_of_notes_are_not_attributed_to_earlier_headers() {
+        let output = "\
+warning: `demo` (lib) generated 1 warning
+note: the lint level is defined here
+ --> src/lib.rs:1:9
+";
+        let mut matcher = RustcMatcher::new("/work");
+        assert!(feed_all(&mut matcher, output).is_empty());
+    }
+}
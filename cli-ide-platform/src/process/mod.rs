@@ -0,0 +1,3 @@
+//! Child processes.
+
+pub mod process_service;
@@ -0,0 +1,434 @@
+//! Child processes whose output streams into the App.
+//!
+//! The `ProcessService` runs commands such as `cargo build` as child
+//! processes. [`ProcessService::spawn`] returns a [`ProcessHandle`] on which
+//! the process's stdout and stderr arrive line by line as
+//! [`ProcessEvent::Output`], followed by exactly one
+//! [`ProcessEvent::Exited`]. The App loop polls the handle without blocking;
+//! [`ProcessHandle::kill`] stops the process early. The service keeps a
+//! registry of the processes still running, so the App can tell the user
+//! what quitting would kill.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use cli_ide_base::{Event, Subscription};
+
+use crate::task::task_service::CancellationToken;
+
+/// How often a running process is checked for exit or a kill request.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Identifies a spawned process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ProcessId(pub u64);
+
+impl fmt::Display for ProcessId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "process #{}", self.0)
+    }
+}
+
+/// A command to run: the program, its arguments and its working directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessSpec {
+    /// Program to run, looked up on `PATH`.
+    pub program: String,
+    /// Arguments passed to the program.
+    pub args: Vec<String>,
+    /// Directory the program runs in.
+    pub cwd: PathBuf,
+}
+
+impl ProcessSpec {
+    /// Run `program` with `args` in `cwd`.
+    pub fn new<I, S>(program: impl Into<String>, args: I, cwd: impl Into<PathBuf>) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            program: program.into(),
+            args: args.into_iter().map(Into::into).collect(),
+            cwd: cwd.into(),
+        }
+    }
+
+    /// The command line, e.g. `cargo build`.
+    pub fn command_line(&self) -> String {
+        std::iter::once(self.program.as_str())
+            .chain(self.args.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// A process that was spawned and has not ended yet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunningProcess {
+    /// Identity of the process.
+    pub id: ProcessId,
+    /// The command line it was started with, e.g. `cargo build`.
+    pub command_line: String,
+}
+
+/// Which output stream a line was written to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputStream {
+    /// Standard output.
+    Stdout,
+    /// Standard error.
+    Stderr,
+}
+
+/// How a process ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProcessExit {
+    /// Exit code, if the process exited normally.
+    pub code: Option<i32>,
+    /// Whether the process was killed through its handle or the service.
+    pub killed: bool,
+}
+
+impl ProcessExit {
+    /// Whether the process exited with code 0.
+    pub fn success(&self) -> bool {
+        self.code == Some(0)
+    }
+}
+
+/// Something a running process did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProcessEvent {
+    /// The process wrote a line, without its line ending.
+    Output {
+        /// The stream the line was written to.
+        stream: OutputStream,
+        /// The line; invalid UTF-8 is replaced.
+        line: String,
+    },
+    /// The process ended. Always the last event.
+    Exited(ProcessExit),
+}
+
+/// A spawned process: its identity, its kill switch and the subscription
+/// its output is delivered on.
+///
+/// Dropping the handle does not kill the process.
+pub struct ProcessHandle {
+    /// Identity of the process.
+    id: ProcessId,
+    /// Token the process watcher checks to kill the process.
+    token: CancellationToken,
+    /// Receives the output and then the exit.
+    events: Subscription<ProcessEvent>,
+}
+
+impl ProcessHandle {
+    /// Get the process's identity.
+    pub fn id(&self) -> ProcessId {
+        self.id
+    }
+
+    /// Kill the process; it will end with a [`ProcessExit`] marked
+    /// `killed`.
+    pub fn kill(&self) {
+        self.token.cancel();
+    }
+
+    /// Take the next event if there is one, without blocking.
+    pub fn try_event(&self) -> Option<ProcessEvent> {
+        self.events.try_recv().ok()
+    }
+
+    /// Wait up to `timeout` for the next event.
+    pub fn next_event(&self, timeout: Duration) -> Option<ProcessEvent> {
+        self.events.recv_timeout(timeout).ok()
+    }
+}
+
+/// Runs child processes and streams their output.
+///
+/// Dropping the service (or calling [`dispose`](ProcessService::dispose))
+/// kills the processes still running.
+pub struct ProcessService {
+    /// Threads watching the spawned processes, until they are joined.
+    watchers: Mutex<Vec<JoinHandle<()>>>,
+    /// Source of process identities.
+    next_id: AtomicU64,
+    /// Command lines of the processes spawned and not yet ended.
+    running: Arc<Mutex<BTreeMap<ProcessId, String>>>,
+    /// Set when the service is disposed, killing every process.
+    shutdown: CancellationToken,
+}
+
+impl Default for ProcessService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProcessService {
+    /// Create a service with no processes.
+    pub fn new() -> Self {
+        Self {
+            watchers: Mutex::new(Vec::new()),
+            next_id: AtomicU64::new(1),
+            running: Arc::default(),
+            shutdown: CancellationToken::new(),
+        }
+    }
+
+    /// Start `spec` with piped stdout and stderr and no stdin.
+    ///
+    /// Output is read on background threads. Once the process has ended and
+    /// both streams are closed, the handle receives
+    /// [`ProcessEvent::Exited`]; a killed process does not wait for its
+    /// streams, since children it started may still hold them open.
+    pub fn spawn(&self, spec: &ProcessSpec) -> io::Result<ProcessHandle> {
+        if self.is_disposed() {
+            return Err(io::Error::other("the process service is disposed"));
+        }
+        let mut child = Command::new(&spec.program)
+            .args(&spec.args)
+            .current_dir(&spec.cwd)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        let id = ProcessId(self.next_id.fetch_add(1, Ordering::SeqCst));
+        let token = CancellationToken::new();
+        let events = Event::new();
+        let handle = ProcessHandle {
+            id,
+            token: token.clone(),
+            events: events.subscribe(),
+        };
+
+        let mut readers = Vec::new();
+        if let Some(stdout) = child.stdout.take() {
+            readers.push(Self::read_lines(stdout, OutputStream::Stdout, &events));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            readers.push(Self::read_lines(stderr, OutputStream::Stderr, &events));
+        }
+
+        let running = Arc::clone(&self.running);
+        let shutdown = self.shutdown.clone();
+        lock(&running).insert(id, spec.command_line());
+        let watcher = thread::spawn(move || {
+            let mut killed = false;
+            let status = loop {
+                if token.is_cancelled() || shutdown.is_cancelled() {
+                    let _ = child.kill();
+                    killed = true;
+                    break child.wait().ok();
+                }
+                match child.try_wait() {
+                    Ok(Some(status)) => break Some(status),
+                    Ok(None) => thread::sleep(POLL_INTERVAL),
+                    Err(_) => break None,
+                }
+            };
+            if !killed {
+                for reader in readers {
+                    let _ = reader.join();
+                }
+            }
+            lock(&running).remove(&id);
+            events.emit(ProcessEvent::Exited(ProcessExit {
+                code: status.and_then(|status| status.code()),
+                killed,
+            }));
+        });
+
+        let mut watchers = self
+            .watchers
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+        watchers.retain(|watcher| !watcher.is_finished());
+        watchers.push(watcher);
+        Ok(handle)
+    }
+
+    /// Emit every line of `source` as output of `stream` until it closes.
+    fn read_lines(
+        source: impl Read + Send + 'static,
+        stream: OutputStream,
+        events: &Event<ProcessEvent>,
+    ) -> JoinHandle<()> {
+        let events = events.clone();
+        thread::spawn(move || {
+            let mut reader = BufReader::new(source);
+            let mut line = Vec::new();
+            loop {
+                line.clear();
+                match reader.read_until(b'\n', &mut line) {
+                    Ok(0) | Err(_) => return,
+                    Ok(_) => {
+                        let text = String::from_utf8_lossy(&line);
+                        let text = text.trim_end_matches(['\n', '\r']).to_string();
+                        events.emit(ProcessEvent::Output { stream, line: text });
+                    }
+                }
+            }
+        })
+    }
+
+    /// Number of processes spawned and not yet ended.
+    pub fn running(&self) -> usize {
+        lock(&self.running).len()
+    }
+
+    /// Check whether the process `id` is still running.
+    pub fn is_running(&self, id: ProcessId) -> bool {
+        lock(&self.running).contains_key(&id)
+    }
+
+    /// Get the processes spawned and not yet ended, oldest first.
+    pub fn running_processes(&self) -> Vec<RunningProcess> {
+        lock(&self.running)
+            .iter()
+            .map(|(&id, command_line)| RunningProcess {
+                id,
+                command_line: command_line.clone(),
+            })
+            .collect()
+    }
+
+    /// Kill every running process and wait for them to end. Safe to call
+    /// more than once.
+    pub fn dispose(&self) {
+        self.shutdown.cancel();
+        let watchers = std::mem::take(
+            &mut *self
+                .watchers
+                .lock()
+                .unwrap_or_else(|error| error.into_inner()),
+        );
+        for watcher in watchers {
+            let _ = watcher.join();
+        }
+    }
+
+    /// Check whether the service has been disposed.
+    pub fn is_disposed(&self) -> bool {
+        self.shutdown.is_cancelled()
+    }
+}
+
+/// Lock the registry of running processes, ignoring poisoning: it is only
+/// ever updated by single inserts and removals.
+fn lock(
+    running: &Mutex<BTreeMap<ProcessId, String>>,
+) -> std::sync::MutexGuard<'_, BTreeMap<ProcessId, String>> {
+    running.lock().unwrap_or_else(|error| error.into_inner())
+}
+
+impl Drop for ProcessService {
+    fn drop(&mut self) {
+        self.dispose();
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn shell(script: &str) -> ProcessSpec {
+        ProcessSpec::new("sh", ["-c", script], std::env::temp_dir())
+    }
+
+    /// Collect events until the process exits.
+    fn events_until_exit(handle: &ProcessHandle) -> Vec<ProcessEvent> {
+        let mut events = Vec::new();
+        while let Some(event) = handle.next_event(TIMEOUT) {
+            let exited = matches!(event, ProcessEvent::Exited(_));
+            events.push(event);
+            if exited {
+                break;
+            }
+        }
+        events
+    }
+
+    #[test]
+    fn test_output_lines_arrive_before_the_exit() {
+        let service = ProcessService::new();
+        let handle = service
+            .spawn(&shell("echo one; echo two >&2; printf three; exit 3"))
+            .unwrap();
+        let events = events_until_exit(&handle);
+
+        let output = |stream| {
+            events
+                .iter()
+                .filter_map(|event| match event {
+                    ProcessEvent::Output { stream: s, line } if *s == stream => Some(line.as_str()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(output(OutputStream::Stdout), ["one", "three"]);
+        assert_eq!(output(OutputStream::Stderr), ["two"]);
+        assert_eq!(
+            events.last(),
+            Some(&ProcessEvent::Exited(ProcessExit {
+                code: Some(3),
+                killed: false,
+            }))
+        );
+        assert_eq!(service.running(), 0);
+    }
+
+    #[test]
+    fn test_killed_process_reports_the_kill() {
+        let service = ProcessService::new();
+        let handle = service.spawn(&shell("echo started; sleep 30")).unwrap();
+        assert_eq!(
+            handle.next_event(TIMEOUT),
+            Some(ProcessEvent::Output {
+                stream: OutputStream::Stdout,
+                line: "started".to_string(),
+            })
+        );
+        assert_eq!(
+            service.running_processes(),
+            [RunningProcess {
+                id: handle.id(),
+                command_line: "sh -c echo started; sleep 30".to_string(),
+            }]
+        );
+        assert!(service.is_running(handle.id()));
+
+        handle.kill();
+        let exit = events_until_exit(&handle).pop();
+        assert!(matches!(
+            exit,
+            Some(ProcessEvent::Exited(ProcessExit { killed: true, .. }))
+        ));
+        assert!(!service.is_running(handle.id()));
+    }
+
+    #[test]
+    fn test_spawn_fails_for_missing_program_and_after_dispose() {
+        let service = ProcessService::new();
+        let missing = ProcessSpec::new("paradiddle-no-such-program", [""; 0], ".");
+        assert!(service.spawn(&missing).is_err());
+
+        service.dispose();
+        service.dispose();
+        assert!(service.is_disposed());
+        assert!(service.spawn(&shell("true")).is_err());
+    }
+}
@@ -0,0 +1,3 @@
+//! Progress reporting for long-running operations.
+
+pub mod progress_service;
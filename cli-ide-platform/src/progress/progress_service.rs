@@ -0,0 +1,285 @@
+//! Progress of long-running operations.
+//!
+//! Work that takes a while reports through a [`Progress`] started on the
+//! [`ProgressService`]: a percentage once it knows how much is left
+//! (determinate), none while it does not (indeterminate), and a message
+//! saying what it is doing. The workbench reads [`ProgressService::active`]
+//! every frame and draws each operation in the status bar or as a toast,
+//! depending on its [`ProgressLocation`]. An operation ends when its
+//! `Progress` is dropped.
+//!
+//! Operations started with a [`CancellationToken`], such as tasks spawned
+//! with [`ProgressService::spawn`], can be cancelled from the UI with
+//! [`ProgressService::cancel`]. As for tasks, cancellation is cooperative:
+//! the work has to check its token.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::task::task_service::{CancellationToken, TaskHandle, TaskService};
+
+/// Identifies a running operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ProgressId(pub u64);
+
+/// Where the workbench shows an operation's progress.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProgressLocation {
+    /// A segment of the status bar, for background work.
+    StatusBar,
+    /// A toast over the bottom right of the workbench, for work the user
+    /// is waiting for.
+    Notification,
+}
+
+/// The progress of a running operation, as last reported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgressItem {
+    /// Identity of the operation.
+    pub id: ProgressId,
+    /// What the operation is, e.g. `Indexing`.
+    pub title: String,
+    /// Where it is shown.
+    pub location: ProgressLocation,
+    /// What it is doing now, if it said.
+    pub message: Option<String>,
+    /// How much is done, from 0 to 100, or `None` while indeterminate.
+    pub percentage: Option<u8>,
+    /// Whether it can be cancelled.
+    pub cancellable: bool,
+}
+
+/// Running operations, shared by the service and the operations' reporters.
+#[derive(Debug, Default)]
+struct State {
+    /// Source of operation identities.
+    next_id: u64,
+    /// Running operations and the tokens that cancel them.
+    items: BTreeMap<ProgressId, (ProgressItem, Option<CancellationToken>)>,
+}
+
+/// Lock the shared state, ignoring poisoning: reports are plain updates.
+fn lock(state: &Mutex<State>) -> MutexGuard<'_, State> {
+    state.lock().unwrap_or_else(|error| error.into_inner())
+}
+
+/// Reports the progress of one operation; the operation ends when it is
+/// dropped.
+///
+/// Reporters can be moved to the thread doing the work.
+#[derive(Debug)]
+pub struct Progress {
+    /// Identity of the operation.
+    id: ProgressId,
+    /// Where reports are written.
+    state: Arc<Mutex<State>>,
+    /// Token that cancels the operation, if it can be cancelled.
+    token: Option<CancellationToken>,
+}
+
+impl Progress {
+    /// Get the identity of the operation.
+    pub fn id(&self) -> ProgressId {
+        self.id
+    }
+
+    /// Say what the operation is doing now.
+    pub fn report(&self, message: impl Into<String>) {
+        self.update(|item| item.message = Some(message.into()));
+    }
+
+    /// Set how much is done, from 0 to 100, making the progress
+    /// determinate.
+    pub fn report_percentage(&self, percentage: u8) {
+        self.update(|item| item.percentage = Some(percentage.min(100)));
+    }
+
+    /// Set how much is done as `done` of `total` units, e.g. files.
+    pub fn report_fraction(&self, done: usize, total: usize) {
+        let percentage = (done.min(total) * 100).checked_div(total).unwrap_or(100);
+        self.report_percentage(u8::try_from(percentage).unwrap_or(100));
+    }
+
+    /// Get the token that cancels the operation, if it can be cancelled.
+    pub fn token(&self) -> Option<&CancellationToken> {
+        self.token.as_ref()
+    }
+
+    /// Check whether cancellation was requested.
+    pub fn is_cancelled(&self) -> bool {
+        self.token
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+    }
+
+    /// End the operation. Same as dropping the reporter.
+    pub fn done(self) {}
+
+    fn update(&self, change: impl FnOnce(&mut ProgressItem)) {
+        if let Some((item, _)) = lock(&self.state).items.get_mut(&self.id) {
+            change(item);
+        }
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        lock(&self.state).items.remove(&self.id);
+    }
+}
+
+/// Tracks the running operations and cancels them on request.
+#[derive(Debug, Default)]
+pub struct ProgressService {
+    /// Running operations, shared with their reporters.
+    state: Arc<Mutex<State>>,
+}
+
+impl ProgressService {
+    /// Create a service with no running operations.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start an operation that cannot be cancelled; its progress is
+    /// indeterminate until it reports a percentage.
+    pub fn start(&self, title: impl Into<String>, location: ProgressLocation) -> Progress {
+        Self::start_in(&self.state, title.into(), location, None)
+    }
+
+    /// Start an operation that [`cancel`](ProgressService::cancel) stops by
+    /// cancelling `token`.
+    pub fn start_cancellable(
+        &self,
+        title: impl Into<String>,
+        location: ProgressLocation,
+        token: CancellationToken,
+    ) -> Progress {
+        Self::start_in(&self.state, title.into(), location, Some(token))
+    }
+
+    /// Spawn `work` on `tasks`, reporting its progress as a cancellable
+    /// operation named `title` while it runs.
+    ///
+    /// The operation is tied to the task's cancellation token: cancelling
+    /// it cancels the task, and the task's [`TaskHandle`] cancels it.
+    pub fn spawn<T, F>(
+        &self,
+        tasks: &TaskService,
+        title: impl Into<String>,
+        location: ProgressLocation,
+        work: F,
+    ) -> TaskHandle<T>
+    where
+        T: Clone + Send + 'static,
+        F: FnOnce(&Progress) -> T + Send + 'static,
+    {
+        let title = title.into();
+        let state = Arc::clone(&self.state);
+        tasks.spawn(title.clone(), move |token| {
+            let progress = Self::start_in(&state, title, location, Some(token.clone()));
+            work(&progress)
+        })
+    }
+
+    /// Get the running operations, oldest first.
+    pub fn active(&self) -> Vec<ProgressItem> {
+        lock(&self.state)
+            .items
+            .values()
+            .map(|(item, _)| item.clone())
+            .collect()
+    }
+
+    /// Ask the operation `id` to stop.
+    ///
+    /// Returns `false` if it is not running or cannot be cancelled.
+    pub fn cancel(&self, id: ProgressId) -> bool {
+        match lock(&self.state).items.get(&id) {
+            Some((_, Some(token))) => {
+                token.cancel();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn start_in(
+        state: &Arc<Mutex<State>>,
+        title: String,
+        location: ProgressLocation,
+        token: Option<CancellationToken>,
+    ) -> Progress {
+        let mut locked = lock(state);
+        locked.next_id += 1;
+        let id = ProgressId(locked.next_id);
+        let item = ProgressItem {
+            id,
+            title,
+            location,
+            message: None,
+            percentage: None,
+            cancellable: token.is_some(),
+        };
+        locked.items.insert(id, (item, token.clone()));
+        Progress {
+            id,
+            state: Arc::clone(state),
+            token,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::task_service::TaskOutcome;
+    use std::sync::mpsc::channel;
+    use std::time::Duration;
+
+    #[test]
+    fn test_reports_update_the_item_until_it_is_dropped() {
+        let service = ProgressService::new();
+        let progress = service.start("Indexing", ProgressLocation::StatusBar);
+        assert_eq!(service.active()[0].percentage, None);
+        assert!(!service.cancel(progress.id()), "not cancellable");
+
+        progress.report("src/app.rs");
+        progress.report_fraction(3, 4);
+        let item = &service.active()[0];
+        assert_eq!(item.title, "Indexing");
+        assert_eq!(item.message.as_deref(), Some("src/app.rs"));
+        assert_eq!(item.percentage, Some(75));
+
+        progress.done();
+        assert!(service.active().is_empty());
+    }
+
+    #[test]
+    fn test_cancelling_a_spawned_operation_cancels_its_task() {
+        let tasks = TaskService::new(1);
+        let service = ProgressService::new();
+        let (started_tx, started) = channel();
+        let handle = service.spawn(
+            &tasks,
+            "Long job",
+            ProgressLocation::Notification,
+            move |progress| {
+                started_tx.send(()).unwrap();
+                while !progress.is_cancelled() {
+                    std::thread::sleep(Duration::from_millis(1));
+                }
+            },
+        );
+        started.recv_timeout(Duration::from_secs(5)).unwrap();
+        let item = service.active().remove(0);
+        assert!(item.cancellable);
+
+        assert!(service.cancel(item.id));
+        assert_eq!(
+            handle.wait(Duration::from_secs(5)),
+            Some(TaskOutcome::Cancelled)
+        );
+        assert!(service.active().is_empty());
+    }
+}
@@ -0,0 +1,3 @@
+//! Full-text search across the workspace.
+
+pub mod search_service;
@@ -0,0 +1,447 @@
+//! Workspace text search on a background thread.
+//!
+//! The `SearchService` searches the contents of the files under a directory
+//! for a [`SearchQuery`], literal or regex, skipping ignored files.
+//! [`SearchService::search`] returns a [`SearchHandle`] on which the matches
+//! of each file arrive as [`SearchEvent::Matches`] as soon as the file is
+//! searched, followed by exactly one [`SearchEvent::Finished`]. The App
+//! loop polls the handle without blocking, so results show up while the
+//! search is still running; [`SearchHandle::cancel`] abandons it.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use cli_ide_base::{Event, Subscription};
+use regex::{Regex, RegexBuilder};
+
+use crate::files::ignore;
+use crate::task::task_service::CancellationToken;
+
+/// Identifies a started search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SearchId(pub u64);
+
+impl fmt::Display for SearchId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "search #{}", self.0)
+    }
+}
+
+/// What to search for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchQuery {
+    /// The text, or the regex when `is_regex` is set.
+    pub pattern: String,
+    /// Whether `pattern` is a regex rather than literal text.
+    pub is_regex: bool,
+    /// Whether letters must match in case.
+    pub case_sensitive: bool,
+}
+
+impl SearchQuery {
+    /// A case-sensitive search for the literal `text`.
+    pub fn literal(text: impl Into<String>) -> Self {
+        Self {
+            pattern: text.into(),
+            is_regex: false,
+            case_sensitive: true,
+        }
+    }
+
+    /// A case-sensitive search for the regex `pattern`.
+    pub fn regex(pattern: impl Into<String>) -> Self {
+        Self {
+            pattern: pattern.into(),
+            is_regex: true,
+            case_sensitive: true,
+        }
+    }
+
+    /// Compile the query into the regex lines are matched against.
+    pub fn compile(&self) -> Result<Regex, SearchQueryError> {
+        if self.pattern.is_empty() {
+            return Err(SearchQueryError::Empty);
+        }
+        let pattern = if self.is_regex {
+            self.pattern.clone()
+        } else {
+            regex::escape(&self.pattern)
+        };
+        RegexBuilder::new(&pattern)
+            .case_insensitive(!self.case_sensitive)
+            .build()
+            .map_err(|error| SearchQueryError::Regex(error.to_string()))
+    }
+}
+
+/// Error returned when a query cannot be searched for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchQueryError {
+    /// The query is empty.
+    Empty,
+    /// The regex does not compile; holds the regex error.
+    Regex(String),
+}
+
+impl fmt::Display for SearchQueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchQueryError::Empty => write!(f, "nothing to search for"),
+            SearchQueryError::Regex(message) => write!(f, "invalid regex: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for SearchQueryError {}
+
+/// One match of the query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextMatch {
+    /// Byte range of the match in the file.
+    pub range: Range<usize>,
+    /// Zero-based line number.
+    pub line: usize,
+    /// Text of the line, without its line ending.
+    pub line_text: String,
+    /// Byte range of the match within `line_text`.
+    pub column: Range<usize>,
+}
+
+/// Find the matches of `regex` in `text`, in document order.
+///
+/// Lines are matched one at a time, so a match never spans lines; empty
+/// matches are skipped.
+pub fn find_matches(text: &str, regex: &Regex) -> Vec<TextMatch> {
+    let mut matches = Vec::new();
+    let mut line_start = 0;
+    for (line, raw) in text.split('\n').enumerate() {
+        let line_text = raw.strip_suffix('\r').unwrap_or(raw);
+        for found in regex.find_iter(line_text) {
+            if found.is_empty() {
+                continue;
+            }
+            matches.push(TextMatch {
+                range: line_start + found.start()..line_start + found.end(),
+                line,
+                line_text: line_text.to_string(),
+                column: found.range(),
+            });
+        }
+        line_start += raw.len() + 1;
+    }
+    matches
+}
+
+/// Something a running search did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchEvent {
+    /// A file was searched and has matches.
+    Matches {
+        /// Path of the file.
+        path: PathBuf,
+        /// The matches, in document order.
+        matches: Vec<TextMatch>,
+    },
+    /// The search ended. Always the last event.
+    Finished(SearchSummary),
+}
+
+/// How a search ended.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SearchSummary {
+    /// Number of files searched.
+    pub files_searched: usize,
+    /// Whether the search was cancelled before searching every file.
+    pub cancelled: bool,
+    /// Why the workspace could not be listed, if it could not.
+    pub error: Option<String>,
+}
+
+/// A started search: its identity, its cancellation token and the
+/// subscription its results are delivered on.
+///
+/// Dropping the handle does not cancel the search.
+pub struct SearchHandle {
+    /// Identity of the search.
+    id: SearchId,
+    /// Token the search checks between files.
+    token: CancellationToken,
+    /// Receives the matches and then the summary.
+    events: Subscription<SearchEvent>,
+}
+
+impl SearchHandle {
+    /// Get the search's identity.
+    pub fn id(&self) -> SearchId {
+        self.id
+    }
+
+    /// Get the token the search checks between files.
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    /// Stop the search; it will end with a [`SearchSummary`] marked
+    /// `cancelled`.
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+
+    /// Take the next event if there is one, without blocking.
+    pub fn try_event(&self) -> Option<SearchEvent> {
+        self.events.try_recv().ok()
+    }
+
+    /// Wait up to `timeout` for the next event.
+    pub fn next_event(&self, timeout: Duration) -> Option<SearchEvent> {
+        self.events.recv_timeout(timeout).ok()
+    }
+}
+
+/// Searches workspaces on background threads.
+///
+/// Dropping the service (or calling [`dispose`](SearchService::dispose))
+/// cancels the searches still running and waits for them.
+pub struct SearchService {
+    /// Threads running searches, until they are joined.
+    searches: Mutex<Vec<JoinHandle<()>>>,
+    /// Source of search identities.
+    next_id: AtomicU64,
+    /// Set when the service is disposed, cancelling every search.
+    shutdown: CancellationToken,
+}
+
+impl Default for SearchService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SearchService {
+    /// Create a service with no searches.
+    pub fn new() -> Self {
+        Self {
+            searches: Mutex::new(Vec::new()),
+            next_id: AtomicU64::new(1),
+            shutdown: CancellationToken::new(),
+        }
+    }
+
+    /// Start searching the files under `root` that are not ignored for
+    /// `query`.
+    ///
+    /// Files in `open` are searched as given there, so unsaved edits are
+    /// found; other files are read from disk, skipping any that are not
+    /// UTF-8. At most `limit` files are searched, in path order.
+    pub fn search(
+        &self,
+        root: &Path,
+        query: &SearchQuery,
+        open: HashMap<PathBuf, String>,
+        limit: usize,
+    ) -> Result<SearchHandle, SearchQueryError> {
+        self.search_folders(&[root.to_path_buf()], query, open, limit)
+    }
+
+    /// Start searching the files under each of `roots` in turn, as
+    /// [`search`](SearchService::search) does under one.
+    ///
+    /// At most `limit` files are searched in all. A folder that cannot be
+    /// listed is reported in the summary and the others are still searched.
+    pub fn search_folders(
+        &self,
+        roots: &[PathBuf],
+        query: &SearchQuery,
+        open: HashMap<PathBuf, String>,
+        limit: usize,
+    ) -> Result<SearchHandle, SearchQueryError> {
+        let regex = query.compile()?;
+        let id = SearchId(self.next_id.fetch_add(1, Ordering::SeqCst));
+        let token = CancellationToken::new();
+        let events = Event::new();
+        let handle = SearchHandle {
+            id,
+            token: token.clone(),
+            events: events.subscribe(),
+        };
+
+        let roots = roots.to_vec();
+        let shutdown = self.shutdown.clone();
+        let search = thread::spawn(move || {
+            let cancelled = || token.is_cancelled() || shutdown.is_cancelled();
+            let mut summary = SearchSummary::default();
+            let several = roots.len() > 1;
+            for root in &roots {
+                let remaining = limit.saturating_sub(summary.files_searched);
+                if remaining == 0 || summary.cancelled {
+                    break;
+                }
+                let paths = match ignore::walk_files(root, remaining) {
+                    Ok(paths) => paths,
+                    Err(error) if several => {
+                        summary
+                            .error
+                            .get_or_insert_with(|| format!("{}: {error}", root.display()));
+                        continue;
+                    }
+                    Err(error) => {
+                        summary.error = Some(error.to_string());
+                        continue;
+                    }
+                };
+                for path in paths {
+                    if cancelled() {
+                        summary.cancelled = true;
+                        break;
+                    }
+                    summary.files_searched += 1;
+                    let text = match open.get(&path) {
+                        Some(text) => text.clone(),
+                        None => match fs::read_to_string(&path) {
+                            Ok(text) => text,
+                            Err(_) => continue,
+                        },
+                    };
+                    let matches = find_matches(&text, &regex);
+                    if !matches.is_empty() {
+                        events.emit(SearchEvent::Matches { path, matches });
+                    }
+                }
+            }
+            events.emit(SearchEvent::Finished(summary));
+        });
+
+        let mut searches = self
+            .searches
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+        searches.retain(|search| !search.is_finished());
+        searches.push(search);
+        Ok(handle)
+    }
+
+    /// Cancel every running search and wait for them to end. Safe to call
+    /// more than once.
+    pub fn dispose(&self) {
+        self.shutdown.cancel();
+        let searches = std::mem::take(
+            &mut *self
+                .searches
+                .lock()
+                .unwrap_or_else(|error| error.into_inner()),
+        );
+        for search in searches {
+            let _ = search.join();
+        }
+    }
+}
+
+impl Drop for SearchService {
+    fn drop(&mut self) {
+        self.dispose();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cli_ide_base::test_support::{temp_dir, Cleanup};
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Collect events until the search finishes.
+    fn events_until_finished(handle: &SearchHandle) -> (Vec<PathBuf>, SearchSummary) {
+        let mut paths = Vec::new();
+        while let Some(event) = handle.next_event(TIMEOUT) {
+            match event {
+                SearchEvent::Matches { path, .. } => paths.push(path),
+                SearchEvent::Finished(summary) => return (paths, summary),
+            }
+        }
+        panic!("the search did not finish");
+    }
+
+    #[test]
+    fn test_find_matches_per_line() {
+        let text = "let foo = 1;\r\nFoo(foo);\n";
+        let regex = SearchQuery::regex(r"f\w+").compile().unwrap();
+        let found: Vec<(usize, Range<usize>)> = find_matches(text, &regex)
+            .into_iter()
+            .map(|found| (found.line, found.column))
+            .collect();
+        assert_eq!(found, vec![(0, 4..7), (1, 4..7)]);
+
+        let mut query = SearchQuery::literal("foo(");
+        query.case_sensitive = false;
+        let matches = find_matches(text, &query.compile().unwrap());
+        assert_eq!(matches.len(), 1);
+        assert_eq!(&text[matches[0].range.clone()], "Foo(");
+        assert_eq!(matches[0].line_text, "Foo(foo);");
+
+        let empty = SearchQuery::regex("x*").compile().unwrap();
+        assert!(find_matches(text, &empty).is_empty());
+    }
+
+    #[test]
+    fn test_invalid_queries_are_rejected() {
+        assert_eq!(
+            SearchQuery::literal("").compile().unwrap_err(),
+            SearchQueryError::Empty
+        );
+        let error = SearchQuery::regex("(").compile().unwrap_err();
+        assert!(error.to_string().starts_with("invalid regex: "));
+        // A literal query is not a regex
+        assert!(SearchQuery::literal("(").compile().is_ok());
+    }
+
+    #[test]
+    fn test_search_streams_matches_and_prefers_open_text() {
+        let root = temp_dir("search-svc");
+        let _cleanup = Cleanup::new([&root]);
+        fs::create_dir(root.join("ignored")).unwrap();
+        fs::write(root.join(".gitignore"), "ignored/\n").unwrap();
+        fs::write(root.join("a.txt"), "needle").unwrap();
+        fs::write(root.join("b.txt"), "hay").unwrap();
+        fs::write(root.join("ignored/c.txt"), "needle").unwrap();
+        let open = HashMap::from([(root.join("b.txt"), "unsaved needle".to_string())]);
+
+        let service = SearchService::new();
+        let handle = service
+            .search(&root, &SearchQuery::literal("needle"), open, 100)
+            .unwrap();
+        let (paths, summary) = events_until_finished(&handle);
+        assert_eq!(paths, vec![root.join("a.txt"), root.join("b.txt")]);
+        assert_eq!(summary.files_searched, 3);
+        assert!(!summary.cancelled);
+    }
+
+    #[test]
+    fn test_search_folders_spans_every_root() {
+        let base = temp_dir("search-roots");
+        let _cleanup = Cleanup::new([&base]);
+        fs::create_dir(base.join("one")).unwrap();
+        fs::create_dir(base.join("two")).unwrap();
+        fs::write(base.join("one/a.txt"), "needle").unwrap();
+        fs::write(base.join("two/b.txt"), "needle").unwrap();
+        let roots = [base.join("one"), base.join("missing"), base.join("two")];
+
+        let service = SearchService::new();
+        let handle = service
+            .search_folders(&roots, &SearchQuery::literal("needle"), HashMap::new(), 100)
+            .unwrap();
+        let (paths, summary) = events_until_finished(&handle);
+        assert_eq!(paths, vec![base.join("one/a.txt"), base.join("two/b.txt")]);
+        assert_eq!(summary.files_searched, 2);
+        assert!(summary
+            .error
+            .unwrap()
+            .starts_with(&base.join("missing").display().to_string()));
+    }
+}
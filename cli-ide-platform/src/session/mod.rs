@@ -0,0 +1,4 @@
+//! Saving the workbench state on quit and restoring it on the next launch.
+
+pub mod session_format;
+pub mod session_service;
@@ -0,0 +1,559 @@
+//! The saved state of a workbench and its file format.
+//!
+//! A session file is line based. The first line that is not blank or a `#`
+//! comment names the format version; each following line is a directive
+//! and its arguments:
+//!
+//! ```text
+//! # Paradiddle session
+//! version 1
+//! root /work/demo
+//! folder /work/docs
+//! size 120 40
+//! focus 0
+//! layout h(0@30 v(1 2)@50)
+//! window editor
+//! tab 0 42 /work/demo/src/main.rs
+//! tab 10 0 /work/demo/README.md
+//! pinned
+//! active 1
+//! window terminal
+//! window explorer
+//! ```
+//!
+//! `folder` lines name further root folders of a workspace that has several.
+//! `window` lines list the windows, numbered from 0 in file order; `tab`
+//! and `active` lines belong to the window before them. A tab records the
+//! first visible line, the cursor's byte offset and the path, which runs to
+//! the end of the line; a `pinned` line pins the tab before it. `layout`
+//! nests windows in horizontal (`h`) and vertical (`v`) splits, a child
+//! followed by `@` and its weight when a split is not even, and `focus`
+//! names the focused window.
+//!
+//! Directives a build does not know are skipped, so later builds can add
+//! some without breaking earlier ones. Incompatible changes bump
+//! [`SESSION_VERSION`]; a file from a newer version is rejected with
+//! [`SessionError::Version`] instead of being misread.
+
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Version of the session format this build writes.
+pub const SESSION_VERSION: u32 = 1;
+
+/// Errors produced while loading or saving a session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionError {
+    /// The session file could not be read or written.
+    Io {
+        /// Path of the file.
+        path: PathBuf,
+        /// Description of the I/O error.
+        message: String,
+    },
+    /// The session file is not valid.
+    Parse {
+        /// Zero-based line the error was found on.
+        line: usize,
+        /// Description of the problem.
+        message: String,
+    },
+    /// The session was written in a format version this build cannot read.
+    Version {
+        /// The version in the file.
+        found: u32,
+    },
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionError::Io { path, message } => {
+                write!(f, "cannot access {}: {}", path.display(), message)
+            }
+            SessionError::Parse { line, message } => write!(f, "line {}: {}", line + 1, message),
+            SessionError::Version { found } => write!(
+                f,
+                "session format version {found} is not supported (expected {SESSION_VERSION} or earlier)"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+/// How a split arranges its children.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SessionSplit {
+    /// Children side by side, left to right.
+    Horizontal,
+    /// Children stacked, top to bottom.
+    Vertical,
+}
+
+/// A node of the saved layout tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionLayout {
+    /// The window at this index of [`Session::windows`].
+    Window(usize),
+    /// A split between `children`.
+    Split {
+        /// Direction children are laid out in.
+        direction: SessionSplit,
+        /// Child nodes, in layout order.
+        children: Vec<SessionLayout>,
+        /// Share of the area of each child, relative to the others; one per
+        /// child.
+        weights: Vec<u16>,
+    },
+}
+
+impl SessionLayout {
+    /// Create a split dividing its area evenly between `children`.
+    pub fn even_split(direction: SessionSplit, children: Vec<SessionLayout>) -> Self {
+        SessionLayout::Split {
+            direction,
+            weights: vec![1; children.len()],
+            children,
+        }
+    }
+
+    /// Get the window indices in layout order.
+    pub fn windows(&self) -> Vec<usize> {
+        match self {
+            SessionLayout::Window(index) => vec![*index],
+            SessionLayout::Split { children, .. } => {
+                children.iter().flat_map(SessionLayout::windows).collect()
+            }
+        }
+    }
+
+    /// Check that the tree only places windows numbered below `count`, and
+    /// none of them twice.
+    pub fn fits(&self, count: usize) -> bool {
+        let mut indices = self.windows();
+        indices.sort_unstable();
+        indices.dedup();
+        indices.len() == self.windows().len() && indices.iter().all(|&index| index < count)
+    }
+}
+
+impl FromStr for SessionLayout {
+    type Err = String;
+
+    /// Parse a tree written as in a session file, e.g. `h(0 v(1 2))` or
+    /// `h(0@30 1@50)`.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        parse_layout(text)
+    }
+}
+
+impl fmt::Display for SessionLayout {
+    /// Format the tree as in a session file, e.g. `h(0 v(1 2))`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionLayout::Window(index) => write!(f, "{index}"),
+            SessionLayout::Split {
+                direction,
+                children,
+                weights,
+            } => {
+                let letter = match direction {
+                    SessionSplit::Horizontal => 'h',
+                    SessionSplit::Vertical => 'v',
+                };
+                let even = weights.windows(2).all(|pair| pair[0] == pair[1]);
+                write!(f, "{letter}(")?;
+                for (position, child) in children.iter().enumerate() {
+                    if position > 0 {
+                        f.write_str(" ")?;
+                    }
+                    write!(f, "{child}")?;
+                    if let Some(weight) = weights.get(position).filter(|_| !even) {
+                        write!(f, "@{weight}")?;
+                    }
+                }
+                f.write_str(")")
+            }
+        }
+    }
+}
+
+/// A file open in an editor tab.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionTab {
+    /// Path of the file.
+    pub path: PathBuf,
+    /// Byte offset of the cursor.
+    pub cursor: usize,
+    /// Index of the first visible line.
+    pub scroll: u16,
+    /// Whether the tab is pinned.
+    pub pinned: bool,
+}
+
+/// A saved window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionWindow {
+    /// Kind of window, e.g. `editor` or `explorer`. The workbench decides
+    /// which kinds exist.
+    pub kind: String,
+    /// Tabs of an editor, in display order.
+    pub tabs: Vec<SessionTab>,
+    /// Index of the active tab.
+    pub active: usize,
+}
+
+impl SessionWindow {
+    /// A window of `kind` without tabs.
+    pub fn new(kind: impl Into<String>) -> Self {
+        Self {
+            kind: kind.into(),
+            tabs: Vec::new(),
+            active: 0,
+        }
+    }
+}
+
+/// The saved state of a workbench.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Session {
+    /// Workspace root directory.
+    pub root: PathBuf,
+    /// Further root folders of a workspace with several.
+    pub folders: Vec<PathBuf>,
+    /// Terminal size as (width, height), if known.
+    pub size: Option<(u16, u16)>,
+    /// Index of the focused window, if any.
+    pub focused: Option<usize>,
+    /// Layout of the windows, if any is open.
+    pub layout: Option<SessionLayout>,
+    /// The windows, numbered by their position.
+    pub windows: Vec<SessionWindow>,
+}
+
+impl Session {
+    /// Parse the contents of a session file.
+    pub fn parse(contents: &str) -> Result<Self, SessionError> {
+        let mut lines = contents
+            .lines()
+            .enumerate()
+            .map(|(number, line)| (number, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+        let Some((line, header)) = lines.next() else {
+            return Err(SessionError::Parse {
+                line: 0,
+                message: "empty session".to_string(),
+            });
+        };
+        let version = header
+            .strip_prefix("version ")
+            .and_then(|version| version.trim().parse::<u32>().ok())
+            .filter(|&version| version > 0)
+            .ok_or_else(|| SessionError::Parse {
+                line,
+                message: "expected `version <number>`".to_string(),
+            })?;
+        if version > SESSION_VERSION {
+            return Err(SessionError::Version { found: version });
+        }
+
+        let mut session = Session::default();
+        let mut layout_line = 0;
+        for (line, text) in lines {
+            let error = |message: String| SessionError::Parse { line, message };
+            let (directive, rest) = text.split_once(' ').unwrap_or((text, ""));
+            let rest = rest.trim();
+            match directive {
+                "root" => session.root = PathBuf::from(rest),
+                "folder" if !rest.is_empty() => session.folders.push(PathBuf::from(rest)),
+                "size" => {
+                    let size = rest
+                        .split_once(' ')
+                        .and_then(|(width, height)| {
+                            Some((width.parse().ok()?, height.trim().parse().ok()?))
+                        })
+                        .ok_or_else(|| error(format!("invalid size `{rest}`")))?;
+                    session.size = Some(size);
+                }
+                "focus" => {
+                    let index = rest
+                        .parse()
+                        .map_err(|_| error(format!("invalid window `{rest}`")))?;
+                    session.focused = Some(index);
+                }
+                "layout" => {
+                    session.layout = Some(parse_layout(rest).map_err(error)?);
+                    layout_line = line;
+                }
+                "window" if !rest.is_empty() => session.windows.push(SessionWindow::new(rest)),
+                "tab" | "active" | "pinned" => {
+                    let window = session
+                        .windows
+                        .last_mut()
+                        .ok_or_else(|| error(format!("`{directive}` before any `window`")))?;
+                    if directive == "active" {
+                        window.active = rest
+                            .parse()
+                            .map_err(|_| error(format!("invalid tab `{rest}`")))?;
+                    } else if directive == "pinned" {
+                        window
+                            .tabs
+                            .last_mut()
+                            .ok_or_else(|| error("`pinned` before any `tab`".to_string()))?
+                            .pinned = true;
+                    } else {
+                        window.tabs.push(parse_tab(rest).map_err(error)?);
+                    }
+                }
+                // Written by a later build of the same version
+                _ => {}
+            }
+        }
+
+        let count = session.windows.len();
+        if let Some(layout) = &session.layout {
+            if !layout.fits(count) {
+                return Err(SessionError::Parse {
+                    line: layout_line,
+                    message: format!("layout `{layout}` does not match the {count} windows"),
+                });
+            }
+        }
+        session.focused = session.focused.filter(|&index| index < count);
+        Ok(session)
+    }
+}
+
+impl fmt::Display for Session {
+    /// Format the session as a session file.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# Paradiddle session")?;
+        writeln!(f, "version {SESSION_VERSION}")?;
+        writeln!(f, "root {}", self.root.display())?;
+        for folder in &self.folders {
+            writeln!(f, "folder {}", folder.display())?;
+        }
+        if let Some((width, height)) = self.size {
+            writeln!(f, "size {width} {height}")?;
+        }
+        if let Some(focused) = self.focused {
+            writeln!(f, "focus {focused}")?;
+        }
+        if let Some(layout) = &self.layout {
+            writeln!(f, "layout {layout}")?;
+        }
+        for window in &self.windows {
+            writeln!(f, "window {}", window.kind)?;
+            for tab in &window.tabs {
+                writeln!(
+                    f,
+                    "tab {} {} {}",
+                    tab.scroll,
+                    tab.cursor,
+                    tab.path.display()
+                )?;
+                if tab.pinned {
+                    writeln!(f, "pinned")?;
+                }
+            }
+            if !window.tabs.is_empty() {
+                writeln!(f, "active {}", window.active)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parse `scroll cursor path`.
+fn parse_tab(text: &str) -> Result<SessionTab, String> {
+    let invalid = || format!("invalid tab `{text}`");
+    let mut parts = text.splitn(3, ' ');
+    let scroll = parts.next().and_then(|scroll| scroll.parse().ok());
+    let cursor = parts.next().and_then(|cursor| cursor.parse().ok());
+    let path = parts.next().filter(|path| !path.is_empty());
+    match (scroll, cursor, path) {
+        (Some(scroll), Some(cursor), Some(path)) => Ok(SessionTab {
+            path: PathBuf::from(path),
+            cursor,
+            scroll,
+            pinned: false,
+        }),
+        _ => Err(invalid()),
+    }
+}
+
+/// Parse a layout tree such as `h(0 v(1 2))` or `h(0@30 1@50)`.
+fn parse_layout(text: &str) -> Result<SessionLayout, String> {
+    let mut tokens = tokenize(text).into_iter().peekable();
+    let layout = parse_node(&mut tokens)?;
+    match tokens.next() {
+        None => Ok(layout),
+        Some(token) => Err(format!("unexpected `{token}` in layout")),
+    }
+}
+
+/// Split a layout into numbers, split letters with their `(`, `)` and
+/// `@` weights.
+fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    for c in text.chars() {
+        match c {
+            '(' => {
+                current.push(c);
+                tokens.push(std::mem::take(&mut current));
+            }
+            ')' | ' ' | '@' => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+                if c == ')' {
+                    tokens.push(")".to_string());
+                } else if c == '@' {
+                    current.push(c);
+                }
+            }
+            _ => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+fn parse_node(
+    tokens: &mut std::iter::Peekable<std::vec::IntoIter<String>>,
+) -> Result<SessionLayout, String> {
+    let token = tokens
+        .next()
+        .ok_or_else(|| "unexpected end of layout".to_string())?;
+    let direction = match token.as_str() {
+        "h(" => SessionSplit::Horizontal,
+        "v(" => SessionSplit::Vertical,
+        _ => {
+            return token
+                .parse()
+                .map(SessionLayout::Window)
+                .map_err(|_| format!("unexpected `{token}` in layout"));
+        }
+    };
+    let mut children = Vec::new();
+    let mut weights = Vec::new();
+    while tokens.peek().map(String::as_str) != Some(")") {
+        children.push(parse_node(tokens)?);
+        let weight = match tokens.next_if(|token| token.starts_with('@')) {
+            Some(token) => token[1..]
+                .parse()
+                .ok()
+                .filter(|&weight| weight > 0)
+                .ok_or_else(|| format!("invalid weight `{token}` in layout"))?,
+            None => 1,
+        };
+        weights.push(weight);
+    }
+    tokens.next();
+    if children.len() < 2 {
+        return Err("a split needs at least two children".to_string());
+    }
+    Ok(SessionLayout::Split {
+        direction,
+        children,
+        weights,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Session {
+        let mut editor = SessionWindow::new("editor");
+        editor.tabs = vec![
+            SessionTab {
+                path: PathBuf::from("/work/demo/src/main.rs"),
+                cursor: 42,
+                scroll: 0,
+                pinned: true,
+            },
+            SessionTab {
+                path: PathBuf::from("/work/demo/my notes.md"),
+                cursor: 0,
+                scroll: 10,
+                pinned: false,
+            },
+        ];
+        editor.active = 1;
+        Session {
+            root: PathBuf::from("/work/demo"),
+            folders: vec![PathBuf::from("/work/docs")],
+            size: Some((120, 40)),
+            focused: Some(2),
+            layout: Some(SessionLayout::Split {
+                direction: SessionSplit::Horizontal,
+                children: vec![
+                    SessionLayout::Window(0),
+                    SessionLayout::even_split(
+                        SessionSplit::Vertical,
+                        vec![SessionLayout::Window(1), SessionLayout::Window(2)],
+                    ),
+                ],
+                weights: vec![30, 50],
+            }),
+            windows: vec![
+                editor,
+                SessionWindow::new("terminal"),
+                SessionWindow::new("explorer"),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_session_round_trips_through_text() {
+        let session = sample();
+        let text = session.to_string();
+        assert!(text.contains("layout h(0@30 v(1 2)@50)\n"));
+        assert!(text.contains("tab 0 42 /work/demo/src/main.rs\npinned\n"));
+        assert!(text.contains("tab 10 0 /work/demo/my notes.md\n"));
+        assert_eq!(Session::parse(&text), Ok(session));
+    }
+
+    #[test]
+    fn test_unknown_directives_are_skipped() {
+        let session =
+            Session::parse("version 1\nroot /ws\ntheme dark\nwindow editor\nzoom 2\n").unwrap();
+        assert_eq!(session.root, PathBuf::from("/ws"));
+        assert_eq!(session.windows, vec![SessionWindow::new("editor")]);
+    }
+
+    #[test]
+    fn test_newer_versions_and_bad_files_are_rejected() {
+        assert_eq!(
+            Session::parse("version 2\nwhatever\n"),
+            Err(SessionError::Version { found: 2 })
+        );
+        assert!(matches!(
+            Session::parse("root /ws\n"),
+            Err(SessionError::Parse { line: 0, .. })
+        ));
+        assert!(matches!(
+            Session::parse("version 1\nwindow editor\nlayout h(0 1)\n"),
+            Err(SessionError::Parse { line: 2, .. })
+        ));
+        assert!(matches!(
+            Session::parse("version 1\nwindow a\nwindow b\nlayout h(0@0 1)\n"),
+            Err(SessionError::Parse { line: 3, .. })
+        ));
+        assert!(matches!(
+            Session::parse("version 1\ntab 0 0 /a.rs\n"),
+            Err(SessionError::Parse { line: 1, .. })
+        ));
+        assert!(matches!(
+            Session::parse("version 1\nwindow editor\npinned\n"),
+            Err(SessionError::Parse { line: 2, .. })
+        ));
+    }
+}
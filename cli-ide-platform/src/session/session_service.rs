@@ -0,0 +1,129 @@
+//! Reads and writes a workspace's session file.
+//!
+//! The `SessionService` stores one [`Session`] per workspace, by default in
+//! `$XDG_STATE_HOME/paradiddle/sessions/`. The workbench saves its state
+//! when it quits and loads it on the next launch. A session that cannot be
+//! read, for example one written by a newer build, is reported as an error
+//! for the caller to log; it never aborts startup.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::session_format::{Session, SessionError};
+
+/// Loads and saves the session of a workspace.
+#[derive(Debug, Clone)]
+pub struct SessionService {
+    /// The session file.
+    path: PathBuf,
+}
+
+impl SessionService {
+    /// Create a service storing the session in the file at `path`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// The default session file for the workspace at `root`:
+    /// `$XDG_STATE_HOME/paradiddle/sessions/<hash of root>.session`,
+    /// falling back to `$HOME/.local/state` for the state directory.
+    ///
+    /// Returns `None` if neither variable is set.
+    pub fn default_path(root: &Path) -> Option<PathBuf> {
+        let base = env::var_os("XDG_STATE_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| {
+                env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("state"))
+            })?;
+        let name = format!(
+            "{:016x}.session",
+            fnv1a(root.as_os_str().as_encoded_bytes())
+        );
+        Some(base.join("paradiddle").join("sessions").join(name))
+    }
+
+    /// Get the session file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read the saved session.
+    ///
+    /// Returns `Ok(None)` if no session was saved yet.
+    pub fn load(&self) -> Result<Option<Session>, SessionError> {
+        match fs::read_to_string(&self.path) {
+            Ok(contents) => Session::parse(&contents).map(Some),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(self.io_error(&error)),
+        }
+    }
+
+    /// Write `session`, replacing the saved one.
+    ///
+    /// The file is written next to the old one and renamed over it, so a
+    /// crash while saving leaves the previous session intact.
+    pub fn save(&self, session: &Session) -> Result<(), SessionError> {
+        let write = || -> io::Result<()> {
+            if let Some(parent) = self.path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let temporary = self.path.with_extension("session.tmp");
+            fs::write(&temporary, session.to_string())?;
+            fs::rename(&temporary, &self.path)
+        };
+        write().map_err(|error| self.io_error(&error))
+    }
+
+    fn io_error(&self, error: &io::Error) -> SessionError {
+        SessionError::Io {
+            path: self.path.clone(),
+            message: error.to_string(),
+        }
+    }
+}
+
+/// The 64-bit FNV-1a hash of `bytes`, stable across builds unlike the
+/// standard library's hasher.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::session_format::SessionWindow;
+
+    #[test]
+    fn test_save_then_load() {
+        let dir = env::temp_dir().join(format!("paradiddle-session-{}", std::process::id()));
+        let service = SessionService::new(dir.join("nested").join("a.session"));
+        assert_eq!(service.load(), Ok(None));
+
+        let session = Session {
+            root: PathBuf::from("/ws"),
+            windows: vec![SessionWindow::new("editor")],
+            ..Session::default()
+        };
+        service.save(&session).unwrap();
+        assert_eq!(service.load(), Ok(Some(session)));
+
+        fs::write(service.path(), "version 99\n").unwrap();
+        assert_eq!(service.load(), Err(SessionError::Version { found: 99 }));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_default_path_depends_on_the_root() {
+        let a = SessionService::default_path(Path::new("/work/a"));
+        let b = SessionService::default_path(Path::new("/work/b"));
+        if let (Some(a), Some(b)) = (a, b) {
+            assert_ne!(a, b);
+            assert_eq!(a.extension().unwrap(), "session");
+        }
+    }
+}
@@ -0,0 +1,4 @@
+//! Background tasks.
+
+pub mod task_runner;
+pub mod task_service;
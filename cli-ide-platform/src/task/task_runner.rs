@@ -0,0 +1,269 @@
+//! Shell commands run as tasks, such as `cargo build` or `make lint`.
+//!
+//! A [`TaskDefinition`] names a shell command, e.g. one from a
+//! `[tasks.<name>]` section of the configuration. The [`TaskRunner`] keeps
+//! the configured tasks and starts them in the background through the
+//! [`ProcessService`]. The [`TaskRun`] it returns feeds the output through
+//! problem matchers as the App polls it, so the App can show the output
+//! as it arrives and list the problems found in it.
+//!
+//! Unlike the work of the
+//! [`TaskService`](super::task_service::TaskService), a task is a child
+//! process, run by `sh -c` in the workspace root.
+
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use crate::problems::problem::Problem;
+use crate::problems::problem_matcher::ProblemMatcher;
+use crate::process::process_service::{
+    ProcessEvent, ProcessExit, ProcessHandle, ProcessService, ProcessSpec,
+};
+
+/// Where the output of a task is shown.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TaskOutput {
+    /// A dedicated channel of the output pane.
+    #[default]
+    Output,
+    /// The terminal.
+    Terminal,
+}
+
+/// Error returned when parsing an unknown task output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseTaskOutputError(String);
+
+impl fmt::Display for ParseTaskOutputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown task output `{}`, expected `output` or `terminal`",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseTaskOutputError {}
+
+impl FromStr for TaskOutput {
+    type Err = ParseTaskOutputError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "output" => Ok(TaskOutput::Output),
+            "terminal" => Ok(TaskOutput::Terminal),
+            _ => Err(ParseTaskOutputError(s.to_string())),
+        }
+    }
+}
+
+/// A named shell command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskDefinition {
+    /// Name of the task, e.g. `lint`.
+    pub name: String,
+    /// Shell command, e.g. `cargo clippy --color=never`.
+    pub command: String,
+    /// Where the output is shown.
+    pub output: TaskOutput,
+}
+
+impl TaskDefinition {
+    /// A task running `command`, shown in the output pane.
+    pub fn new(name: impl Into<String>, command: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            command: command.into(),
+            output: TaskOutput::default(),
+        }
+    }
+
+    /// The process running the command through `sh -c` in `root`.
+    pub fn spec(&self, root: &Path) -> ProcessSpec {
+        ProcessSpec::new("sh", ["-c", self.command.as_str()], root)
+    }
+}
+
+/// What a running task did since it was last polled.
+#[derive(Debug, Default)]
+pub struct TaskProgress {
+    /// Lines of output, stdout and stderr interleaved.
+    pub lines: Vec<String>,
+    /// Problems the matchers found in the lines.
+    pub problems: Vec<Problem>,
+    /// How the task ended, once it has.
+    pub exit: Option<ProcessExit>,
+}
+
+/// A started task: its process and the matchers reading its output.
+pub struct TaskRun {
+    /// Title the task is reported under, e.g. `cargo build`.
+    title: String,
+    /// The process running the command.
+    process: ProcessHandle,
+    /// Matchers fed every line of output.
+    matchers: Vec<Box<dyn ProblemMatcher>>,
+}
+
+impl TaskRun {
+    /// Get the title the task is reported under.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Kill the task; the next polls report it as killed.
+    pub fn kill(&self) {
+        self.process.kill();
+    }
+
+    /// Take the output that has arrived and the problems in it, without
+    /// blocking.
+    pub fn poll(&mut self) -> TaskProgress {
+        let mut progress = TaskProgress::default();
+        while let Some(event) = self.process.try_event() {
+            match event {
+                ProcessEvent::Output { line, .. } => {
+                    progress.problems.extend(
+                        self.matchers
+                            .iter_mut()
+                            .filter_map(|matcher| matcher.feed(&line)),
+                    );
+                    progress.lines.push(line);
+                }
+                ProcessEvent::Exited(exit) => {
+                    progress.exit = Some(exit);
+                    break;
+                }
+            }
+        }
+        progress
+    }
+}
+
+/// Keeps the configured tasks and starts tasks as child processes.
+pub struct TaskRunner {
+    /// Service the tasks run on.
+    processes: Arc<ProcessService>,
+    /// Configured tasks, in configuration order.
+    tasks: Mutex<Vec<TaskDefinition>>,
+}
+
+impl TaskRunner {
+    /// Create a runner with no tasks, starting processes on `processes`.
+    pub fn new(processes: Arc<ProcessService>) -> Self {
+        Self {
+            processes,
+            tasks: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Replace the configured tasks.
+    pub fn set_tasks(&self, tasks: Vec<TaskDefinition>) {
+        *self.tasks.lock().unwrap_or_else(|error| error.into_inner()) = tasks;
+    }
+
+    /// Get the configured tasks.
+    pub fn tasks(&self) -> Vec<TaskDefinition> {
+        self.tasks
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .clone()
+    }
+
+    /// Get the configured task named `name`, if any.
+    pub fn task(&self, name: &str) -> Option<TaskDefinition> {
+        self.tasks().into_iter().find(|task| task.name == name)
+    }
+
+    /// Start `task` in `root`, reading problems with `matchers`.
+    pub fn run(
+        &self,
+        task: &TaskDefinition,
+        root: &Path,
+        matchers: Vec<Box<dyn ProblemMatcher>>,
+    ) -> io::Result<TaskRun> {
+        self.start(task.name.clone(), &task.spec(root), matchers)
+    }
+
+    /// Start `spec` as a task reported as `title`, reading problems with
+    /// `matchers`.
+    pub fn start(
+        &self,
+        title: impl Into<String>,
+        spec: &ProcessSpec,
+        matchers: Vec<Box<dyn ProblemMatcher>>,
+    ) -> io::Result<TaskRun> {
+        Ok(TaskRun {
+            title: title.into(),
+            process: self.processes.spawn(spec)?,
+            matchers,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+    use crate::problems::problem::Severity;
+    use crate::problems::problem_matcher::ProblemPattern;
+
+    #[test]
+    fn test_parse_task_outputs() {
+        assert_eq!("output".parse(), Ok(TaskOutput::Output));
+        assert_eq!("terminal".parse(), Ok(TaskOutput::Terminal));
+        assert_eq!(
+            "pane".parse::<TaskOutput>().unwrap_err().to_string(),
+            "unknown task output `pane`, expected `output` or `terminal`"
+        );
+    }
+
+    #[test]
+    fn test_run_streams_output_and_problems_until_exit() {
+        let root = std::env::temp_dir();
+        let runner = TaskRunner::new(Arc::new(ProcessService::new()));
+        runner.set_tasks(vec![TaskDefinition::new(
+            "lint",
+            "echo checking; echo 'src/a.ts:3: missing semicolon' >&2; exit 2",
+        )]);
+        let task = runner.task("lint").unwrap();
+        assert_eq!(task.spec(&root).program, "sh");
+        assert!(runner.task("build").is_none());
+
+        let pattern = ProblemPattern::parse(
+            "lint",
+            r"^(?P<file>[^:]+):(?P<line>\d+): (?P<message>.*)$",
+            Some("warning"),
+        )
+        .unwrap();
+        let mut run = runner
+            .run(&task, &root, vec![Box::new(pattern.matcher(&root))])
+            .unwrap();
+        assert_eq!(run.title(), "lint");
+
+        let mut lines = Vec::new();
+        let mut problems = Vec::new();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let exit = loop {
+            assert!(Instant::now() < deadline, "timed out");
+            let progress = run.poll();
+            lines.extend(progress.lines);
+            problems.extend(progress.problems);
+            if let Some(exit) = progress.exit {
+                break exit;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        };
+        assert_eq!(exit.code, Some(2));
+        lines.sort();
+        assert_eq!(lines, ["checking", "src/a.ts:3: missing semicolon"]);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].path, root.join("src/a.ts"));
+        assert_eq!(problems[0].severity, Severity::Warning);
+    }
+}
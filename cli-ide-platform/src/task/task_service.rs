@@ -0,0 +1,412 @@
+//! Background task scheduler.
+//!
+//! The `TaskService` runs work such as file IO, parsing and searches on a
+//! small pool of worker threads, so the render loop never blocks on it.
+//! [`TaskService::spawn`] returns a [`TaskHandle`] whose result arrives as
+//! an event the App loop polls without blocking, and whose
+//! [`CancellationToken`] lets the caller abandon work that is no longer
+//! needed (e.g. a search superseded by a newer query). Cancellation is
+//! cooperative: long-running work should check the token it is given; a
+//! cancelled task never delivers a result. [`TaskService::pending_tasks`]
+//! names the tasks that have not ended, e.g. to warn before quitting.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use cli_ide_base::{Event, Subscription};
+
+/// Flag shared between a task and its owner, set to ask the task to stop.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Create a token that is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask the work holding this token to stop.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    /// Check whether cancellation was requested.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Identifies a spawned task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TaskId(pub u64);
+
+impl fmt::Display for TaskId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "task #{}", self.0)
+    }
+}
+
+/// How a task ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaskOutcome<T> {
+    /// The work ran to completion and produced a result.
+    Completed(T),
+    /// The task was cancelled before or while it ran.
+    Cancelled,
+    /// The work panicked.
+    Failed(String),
+}
+
+/// Emitted by the [`TaskService`] when any task ends, so the App can show
+/// progress without knowing each task's result type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskFinished {
+    /// The task that ended.
+    pub id: TaskId,
+    /// Name given when the task was spawned.
+    pub name: String,
+    /// Whether the task was cancelled instead of completing.
+    pub cancelled: bool,
+}
+
+/// A task that was spawned and has not ended yet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingTask {
+    /// Identity of the task.
+    pub id: TaskId,
+    /// Name given when the task was spawned.
+    pub name: String,
+}
+
+/// A spawned task: its identity, its cancellation token and the
+/// subscription its outcome is delivered on.
+///
+/// Dropping the handle does not cancel the task.
+pub struct TaskHandle<T: Clone + Send + 'static> {
+    /// Identity of the task.
+    id: TaskId,
+    /// Token the task's work checks.
+    token: CancellationToken,
+    /// Receives the outcome exactly once.
+    outcome: Subscription<TaskOutcome<T>>,
+}
+
+impl<T: Clone + Send + 'static> TaskHandle<T> {
+    /// Get the task's identity.
+    pub fn id(&self) -> TaskId {
+        self.id
+    }
+
+    /// Get the task's cancellation token.
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    /// Ask the task to stop; it will finish with [`TaskOutcome::Cancelled`].
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+
+    /// Take the outcome if the task has ended, without blocking.
+    pub fn try_outcome(&self) -> Option<TaskOutcome<T>> {
+        self.outcome.try_recv().ok()
+    }
+
+    /// Wait up to `timeout` for the task to end.
+    pub fn wait(&self, timeout: Duration) -> Option<TaskOutcome<T>> {
+        self.outcome.recv_timeout(timeout).ok()
+    }
+}
+
+/// A unit of work queued for the workers.
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Runs tasks on a fixed pool of worker threads in the order they were
+/// spawned.
+///
+/// Dropping the service (or calling [`dispose`](TaskService::dispose))
+/// cancels queued tasks and waits for running ones to return.
+pub struct TaskService {
+    /// Queue the workers take jobs from, until disposed.
+    queue: Mutex<Option<Sender<Job>>>,
+    /// The worker threads, until disposed.
+    workers: Mutex<Vec<JoinHandle<()>>>,
+    /// Source of task identities.
+    next_id: AtomicU64,
+    /// Names of the tasks spawned and not yet ended.
+    pending: Arc<Mutex<BTreeMap<TaskId, String>>>,
+    /// Set when the service is disposed, cancelling every task.
+    shutdown: CancellationToken,
+    /// Fired from the worker threads when a task ends.
+    on_did_finish: Event<TaskFinished>,
+}
+
+impl Default for TaskService {
+    /// A service with one worker per available CPU, at most four.
+    fn default() -> Self {
+        let workers = thread::available_parallelism().map_or(1, |count| count.get().min(4));
+        Self::new(workers)
+    }
+}
+
+impl TaskService {
+    /// Start a service with `workers` threads (at least one).
+    pub fn new(workers: usize) -> Self {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let workers = (0..workers.max(1))
+            .map(|_| {
+                let receiver = Arc::clone(&receiver);
+                thread::spawn(move || Self::work(&receiver))
+            })
+            .collect();
+        Self {
+            queue: Mutex::new(Some(sender)),
+            workers: Mutex::new(workers),
+            next_id: AtomicU64::new(1),
+            pending: Arc::default(),
+            shutdown: CancellationToken::new(),
+            on_did_finish: Event::new(),
+        }
+    }
+
+    /// Run jobs until the queue is closed.
+    fn work(receiver: &Mutex<Receiver<Job>>) {
+        loop {
+            let job = match receiver.lock() {
+                Ok(receiver) => receiver.recv(),
+                Err(_) => return,
+            };
+            match job {
+                Ok(job) => job(),
+                Err(_) => return,
+            }
+        }
+    }
+
+    /// Queue `work` to run on a worker thread.
+    ///
+    /// `work` receives the task's cancellation token and should return
+    /// early once it is cancelled. The outcome is delivered on the returned
+    /// handle; a task that is cancelled before it starts, or whose token is
+    /// cancelled by the time it returns, ends with
+    /// [`TaskOutcome::Cancelled`] and its result is discarded. Tasks
+    /// spawned after the service is disposed end cancelled immediately.
+    pub fn spawn<T, F>(&self, name: impl Into<String>, work: F) -> TaskHandle<T>
+    where
+        T: Clone + Send + 'static,
+        F: FnOnce(&CancellationToken) -> T + Send + 'static,
+    {
+        let id = TaskId(self.next_id.fetch_add(1, Ordering::SeqCst));
+        let name = name.into();
+        let token = CancellationToken::new();
+        let outcome_event = Event::new();
+        let handle = TaskHandle {
+            id,
+            token: token.clone(),
+            outcome: outcome_event.subscribe(),
+        };
+
+        let pending = Arc::clone(&self.pending);
+        lock(&pending).insert(id, name.clone());
+        let shutdown = self.shutdown.clone();
+        let on_did_finish = self.on_did_finish.clone();
+        let job: Job = Box::new(move || {
+            let cancelled = || token.is_cancelled() || shutdown.is_cancelled();
+            let outcome = if cancelled() {
+                TaskOutcome::Cancelled
+            } else {
+                match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| work(&token))) {
+                    Ok(_) if cancelled() => TaskOutcome::Cancelled,
+                    Ok(result) => TaskOutcome::Completed(result),
+                    Err(panic) => TaskOutcome::Failed(panic_message(panic.as_ref())),
+                }
+            };
+            let finished = TaskFinished {
+                id,
+                name,
+                cancelled: matches!(outcome, TaskOutcome::Cancelled),
+            };
+            lock(&pending).remove(&id);
+            outcome_event.emit(outcome);
+            on_did_finish.emit(finished);
+        });
+
+        let queue = self.queue.lock().unwrap_or_else(|error| error.into_inner());
+        let rejected = match queue.as_ref() {
+            Some(queue) => queue.send(job).err().map(|error| error.0),
+            None => Some(job),
+        };
+        drop(queue);
+        if let Some(job) = rejected {
+            // Only a disposed service rejects jobs; the job sees the
+            // shutdown and just reports the cancellation.
+            job();
+        }
+        handle
+    }
+
+    /// Number of tasks spawned and not yet ended.
+    pub fn pending(&self) -> usize {
+        lock(&self.pending).len()
+    }
+
+    /// Get the tasks spawned and not yet ended, oldest first.
+    pub fn pending_tasks(&self) -> Vec<PendingTask> {
+        lock(&self.pending)
+            .iter()
+            .map(|(&id, name)| PendingTask {
+                id,
+                name: name.clone(),
+            })
+            .collect()
+    }
+
+    /// Event fired when any task ends.
+    ///
+    /// Values are sent from the worker threads.
+    pub fn on_did_finish(&self) -> &Event<TaskFinished> {
+        &self.on_did_finish
+    }
+
+    /// Cancel every task, drop the queue and wait for the workers to stop.
+    /// Safe to call more than once.
+    pub fn dispose(&self) {
+        self.shutdown.cancel();
+        let queue = self
+            .queue
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .take();
+        drop(queue);
+        let workers = std::mem::take(
+            &mut *self
+                .workers
+                .lock()
+                .unwrap_or_else(|error| error.into_inner()),
+        );
+        for worker in workers {
+            let _ = worker.join();
+        }
+    }
+
+    /// Check whether the service has been disposed.
+    pub fn is_disposed(&self) -> bool {
+        self.shutdown.is_cancelled()
+    }
+}
+
+impl Drop for TaskService {
+    fn drop(&mut self) {
+        self.dispose();
+    }
+}
+
+/// Lock the registry of pending tasks, ignoring poisoning: it is only ever
+/// updated by single inserts and removals.
+fn lock(
+    pending: &Mutex<BTreeMap<TaskId, String>>,
+) -> std::sync::MutexGuard<'_, BTreeMap<TaskId, String>> {
+    pending.lock().unwrap_or_else(|error| error.into_inner())
+}
+
+/// The message of a caught panic.
+fn panic_message(panic: &(dyn std::any::Any + Send)) -> String {
+    panic
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "task panicked".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::channel;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn test_results_arrive_on_the_handle_and_finish_event() {
+        let service = TaskService::new(2);
+        let finished = service.on_did_finish().subscribe();
+        let handle = service.spawn("sum", |_| (1..=10).sum::<u32>());
+
+        assert_eq!(handle.wait(TIMEOUT), Some(TaskOutcome::Completed(55)));
+        assert_eq!(
+            finished.recv_timeout(TIMEOUT).unwrap(),
+            TaskFinished {
+                id: handle.id(),
+                name: "sum".to_string(),
+                cancelled: false,
+            }
+        );
+        assert_eq!(service.pending(), 0);
+        assert_eq!(handle.try_outcome(), None, "the outcome is delivered once");
+    }
+
+    #[test]
+    fn test_cancelled_tasks_deliver_no_result() {
+        let service = TaskService::new(1);
+        let (started_tx, started) = channel();
+        let running = service.spawn("loop", move |token| {
+            started_tx.send(()).unwrap();
+            while !token.is_cancelled() {
+                thread::sleep(Duration::from_millis(1));
+            }
+            "partial"
+        });
+        // Queued behind the running task on the single worker
+        let queued = service.spawn("queued", |_| "never");
+        started.recv_timeout(TIMEOUT).unwrap();
+        let names: Vec<String> = service
+            .pending_tasks()
+            .into_iter()
+            .map(|task| task.name)
+            .collect();
+        assert_eq!(names, ["loop", "queued"]);
+
+        queued.cancel();
+        running.cancel();
+        assert_eq!(running.wait(TIMEOUT), Some(TaskOutcome::Cancelled));
+        assert_eq!(queued.wait(TIMEOUT), Some(TaskOutcome::Cancelled));
+    }
+
+    #[test]
+    fn test_panicking_task_fails_without_killing_workers() {
+        let service = TaskService::new(1);
+        let failed = service.spawn("boom", |_| -> u8 { panic!("boom") });
+        assert_eq!(
+            failed.wait(TIMEOUT),
+            Some(TaskOutcome::Failed("boom".to_string()))
+        );
+        let next = service.spawn("next", |_| 7u8);
+        assert_eq!(next.wait(TIMEOUT), Some(TaskOutcome::Completed(7)));
+    }
+
+    #[test]
+    fn test_dispose_cancels_queued_and_later_tasks() {
+        let service = TaskService::new(1);
+        let (release_tx, release) = channel::<()>();
+        let blocking = service.spawn("block", move |_| release.recv_timeout(TIMEOUT).is_ok());
+        let queued = service.spawn("queued", |_| 1);
+        release_tx.send(()).unwrap();
+
+        service.dispose();
+        service.dispose();
+        assert!(service.is_disposed());
+        assert!(matches!(
+            blocking.wait(TIMEOUT),
+            Some(TaskOutcome::Completed(true) | TaskOutcome::Cancelled)
+        ));
+        assert_eq!(queued.wait(TIMEOUT), Some(TaskOutcome::Cancelled));
+        let late = service.spawn("late", |_| 2);
+        assert_eq!(late.try_outcome(), Some(TaskOutcome::Cancelled));
+        assert_eq!(service.pending(), 0);
+    }
+}
@@ -0,0 +1,3 @@
+//! Ticks of the event loop and the periodic work they run.
+
+pub mod tick_scheduler;
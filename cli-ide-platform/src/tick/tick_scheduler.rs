@@ -0,0 +1,249 @@
+//! Ticks of the event loop and periodic callbacks.
+//!
+//! The event loop asks the [`TickScheduler`] when the next tick is due
+//! ([`TickScheduler::time_until_tick`]) and sends the App a tick once
+//! [`TickScheduler::take_tick`] says so, whether or not input arrived
+//! meanwhile. On each tick the App calls [`TickScheduler::run_due`], which
+//! runs the registered callbacks whose own interval has passed: a blinking
+//! cursor, a plugin polling a file, a timeout. A callback's interval is
+//! therefore only as precise as the tick interval.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// How often the event loop ticks unless configured otherwise.
+pub const DEFAULT_TICK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The shortest tick interval, so a loop never spins.
+pub const MIN_TICK_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Identifies a registered callback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TickId(pub u64);
+
+/// Work run periodically on ticks.
+type Callback = Box<dyn FnMut(Instant) + Send>;
+
+/// A registered callback and when it runs.
+struct Periodic {
+    /// How often it runs.
+    interval: Duration,
+    /// When it runs next.
+    next_due: Instant,
+    /// The work; shared so it runs without the state locked, letting it
+    /// register or unregister callbacks.
+    callback: Arc<Mutex<Callback>>,
+}
+
+/// The tick clock and the registered callbacks.
+struct State {
+    /// The time between two ticks.
+    interval: Duration,
+    /// When the last tick was taken, if one was.
+    last_tick: Option<Instant>,
+    /// Source of callback identities.
+    next_id: u64,
+    /// Registered callbacks, oldest first.
+    callbacks: BTreeMap<TickId, Periodic>,
+}
+
+/// Lock `mutex`, ignoring poisoning: a panicking callback leaves the
+/// schedule intact.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|error| error.into_inner())
+}
+
+/// Paces the ticks of the event loop and runs periodic callbacks on them.
+pub struct TickScheduler {
+    /// The clock and the callbacks.
+    state: Mutex<State>,
+}
+
+impl Default for TickScheduler {
+    fn default() -> Self {
+        Self::new(DEFAULT_TICK_INTERVAL)
+    }
+}
+
+impl std::fmt::Debug for TickScheduler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = lock(&self.state);
+        f.debug_struct("TickScheduler")
+            .field("interval", &state.interval)
+            .field("callbacks", &state.callbacks.len())
+            .finish()
+    }
+}
+
+impl TickScheduler {
+    /// Tick every `interval`, at least every [`MIN_TICK_INTERVAL`].
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            state: Mutex::new(State {
+                interval: interval.max(MIN_TICK_INTERVAL),
+                last_tick: None,
+                next_id: 0,
+                callbacks: BTreeMap::new(),
+            }),
+        }
+    }
+
+    /// Get the time between two ticks.
+    pub fn interval(&self) -> Duration {
+        lock(&self.state).interval
+    }
+
+    /// Tick every `interval` from now on, at least every
+    /// [`MIN_TICK_INTERVAL`].
+    pub fn set_interval(&self, interval: Duration) {
+        lock(&self.state).interval = interval.max(MIN_TICK_INTERVAL);
+    }
+
+    /// How long the event loop may wait at `now` before the next tick is
+    /// due.
+    pub fn time_until_tick(&self, now: Instant) -> Duration {
+        let state = lock(&self.state);
+        state.last_tick.map_or(Duration::ZERO, |last| {
+            (last + state.interval).saturating_duration_since(now)
+        })
+    }
+
+    /// Take the tick due at `now`, if one is.
+    ///
+    /// Returns `true` at most once an interval; the event loop then sends
+    /// the App a tick.
+    pub fn take_tick(&self, now: Instant) -> bool {
+        let mut state = lock(&self.state);
+        let due = state
+            .last_tick
+            .is_none_or(|last| now.saturating_duration_since(last) >= state.interval);
+        if due {
+            state.last_tick = Some(now);
+        }
+        due
+    }
+
+    /// Run `callback` on the first tick at least `interval` from now, and
+    /// every `interval` after that, until it is unregistered.
+    ///
+    /// The callback is given the time of the tick.
+    pub fn register(
+        &self,
+        interval: Duration,
+        callback: impl FnMut(Instant) + Send + 'static,
+    ) -> TickId {
+        let mut state = lock(&self.state);
+        let id = TickId(state.next_id);
+        state.next_id += 1;
+        state.callbacks.insert(
+            id,
+            Periodic {
+                interval,
+                next_due: Instant::now() + interval,
+                callback: Arc::new(Mutex::new(Box::new(callback))),
+            },
+        );
+        id
+    }
+
+    /// Stop running the callback `id`.
+    ///
+    /// Returns `false` if it is not registered.
+    pub fn unregister(&self, id: TickId) -> bool {
+        lock(&self.state).callbacks.remove(&id).is_some()
+    }
+
+    /// Get the number of registered callbacks.
+    pub fn len(&self) -> usize {
+        lock(&self.state).callbacks.len()
+    }
+
+    /// Check whether no callback is registered.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Run the callbacks due at `now`, oldest first, and schedule each
+    /// again an interval later.
+    ///
+    /// A callback that fell behind, e.g. because ticks were slower than
+    /// its interval, runs once rather than catching up. Returns how many
+    /// callbacks ran.
+    pub fn run_due(&self, now: Instant) -> usize {
+        let due: Vec<Arc<Mutex<Callback>>> = lock(&self.state)
+            .callbacks
+            .values_mut()
+            .filter(|periodic| periodic.next_due <= now)
+            .map(|periodic| {
+                periodic.next_due = now + periodic.interval;
+                Arc::clone(&periodic.callback)
+            })
+            .collect();
+        for callback in &due {
+            (lock(callback))(now);
+        }
+        due.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_ticks_are_spaced_by_the_interval() {
+        let ticks = TickScheduler::new(Duration::from_millis(50));
+        let start = Instant::now();
+        assert_eq!(ticks.time_until_tick(start), Duration::ZERO);
+        assert!(ticks.take_tick(start));
+        assert!(!ticks.take_tick(start + Duration::from_millis(10)));
+        assert_eq!(
+            ticks.time_until_tick(start + Duration::from_millis(10)),
+            Duration::from_millis(40)
+        );
+        assert!(ticks.take_tick(start + Duration::from_millis(50)));
+
+        ticks.set_interval(Duration::ZERO);
+        assert_eq!(ticks.interval(), MIN_TICK_INTERVAL);
+    }
+
+    #[test]
+    fn test_callbacks_run_at_their_own_interval_until_unregistered() {
+        let ticks = Arc::new(TickScheduler::default());
+        let fast = Arc::new(AtomicUsize::new(0));
+        let slow = Arc::new(AtomicUsize::new(0));
+        let start = Instant::now();
+        let fast_id = ticks.register(Duration::from_millis(100), {
+            let fast = Arc::clone(&fast);
+            move |_| {
+                fast.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+        // A callback may change the schedule while it runs
+        ticks.register(Duration::from_millis(300), {
+            let slow = Arc::clone(&slow);
+            let scheduler = Arc::clone(&ticks);
+            move |_| {
+                slow.fetch_add(1, Ordering::SeqCst);
+                scheduler.unregister(fast_id);
+            }
+        });
+        assert_eq!(ticks.len(), 2);
+        assert_eq!(ticks.run_due(start), 0, "nothing is due yet");
+
+        for tick in 1..=2 {
+            ticks.run_due(start + Duration::from_millis(100 * tick + 1));
+        }
+        assert_eq!(fast.load(Ordering::SeqCst), 2);
+        assert_eq!(slow.load(Ordering::SeqCst), 0);
+
+        // Both are due, and the slow one stops the fast one
+        assert_eq!(ticks.run_due(start + Duration::from_millis(301)), 2);
+        ticks.run_due(start + Duration::from_millis(1_000));
+        assert_eq!(fast.load(Ordering::SeqCst), 3);
+        assert_eq!(slow.load(Ordering::SeqCst), 2, "no catching up");
+        assert!(!ticks.unregister(fast_id));
+    }
+}
@@ -0,0 +1,158 @@
+//! Abbreviations expanded as they are typed in the editor.
+//!
+//! An abbreviation maps a word to the text replacing it, from a typo fix
+//! (`teh` → `the`) to a snippet of boilerplate. In Insert mode, typing a
+//! character that cannot be part of a word (a space, punctuation or
+//! `Enter`) right after an abbreviation expands it. Abbreviations are
+//! global or scoped to one language; a scoped one wins over a global one
+//! with the same word.
+//!
+//! An expansion spanning several lines is indented like the line it is
+//! typed on. `$0` in an expansion marks where the cursor goes; the
+//! character that expanded it is then not inserted.
+
+use std::collections::HashMap;
+
+/// Marks where the cursor goes in an expansion.
+const CURSOR_MARKER: &str = "$0";
+
+/// Check whether `c` can be part of an abbreviation.
+pub fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Text replacing an abbreviation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expansion {
+    /// Text to insert, indented, without the cursor marker.
+    pub text: String,
+    /// Byte offset in `text` the cursor goes to, if the expansion marks one.
+    pub cursor: Option<usize>,
+}
+
+impl Expansion {
+    /// Expand `template`, indenting every line after the first with
+    /// `indent`.
+    fn new(template: &str, indent: &str) -> Self {
+        let mut text = template.replace('\n', &format!("\n{indent}"));
+        let cursor = text.find(CURSOR_MARKER);
+        if let Some(cursor) = cursor {
+            text.replace_range(cursor..cursor + CURSOR_MARKER.len(), "");
+        }
+        Self { text, cursor }
+    }
+}
+
+/// Global and per-language abbreviations.
+///
+/// Language identifiers are free-form strings (e.g. `"rust"`), matched
+/// against the language of the edited file.
+#[derive(Debug, Clone, Default)]
+pub struct Abbreviations {
+    /// Abbreviations expanding in every file, by word.
+    global: HashMap<String, String>,
+    /// Abbreviations expanding in files of one language, by language and
+    /// word.
+    languages: HashMap<String, HashMap<String, String>>,
+}
+
+impl Abbreviations {
+    /// Create an empty set of abbreviations.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Expand `word` to `expansion` in files of `language`, or in every
+    /// file if `language` is `None`.
+    pub fn insert(
+        &mut self,
+        language: Option<&str>,
+        word: impl Into<String>,
+        expansion: impl Into<String>,
+    ) {
+        let words = match language {
+            Some(language) => self.languages.entry(language.to_string()).or_default(),
+            None => &mut self.global,
+        };
+        words.insert(word.into(), expansion.into());
+    }
+
+    /// Check whether there are no abbreviations.
+    pub fn is_empty(&self) -> bool {
+        self.global.is_empty() && self.languages.values().all(HashMap::is_empty)
+    }
+
+    /// Get the expansion of `word` in files of `language`, preferring the
+    /// abbreviations scoped to the language.
+    pub fn get(&self, word: &str, language: Option<&str>) -> Option<&str> {
+        language
+            .and_then(|language| self.languages.get(language))
+            .and_then(|words| words.get(word))
+            .or_else(|| self.global.get(word))
+            .map(String::as_str)
+    }
+
+    /// Expand the abbreviation `line` ends with, if it ends with one, in a
+    /// file of `language`.
+    ///
+    /// `line` is the text of the line before the cursor. Returns the
+    /// length in bytes of the abbreviation and the text replacing it.
+    pub fn expand(&self, line: &str, language: Option<&str>) -> Option<(usize, Expansion)> {
+        let start = line
+            .char_indices()
+            .rev()
+            .take_while(|&(_, c)| is_word_char(c))
+            .last()
+            .map(|(index, _)| index)?;
+        let word = &line[start..];
+        let template = self.get(word, language)?;
+        let indent_end = line
+            .find(|c: char| !c.is_whitespace())
+            .unwrap_or(line.len());
+        Some((word.len(), Expansion::new(template, &line[..indent_end])))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_language_abbreviations_win_over_global_ones() {
+        let mut abbreviations = Abbreviations::new();
+        assert!(abbreviations.is_empty());
+        abbreviations.insert(None, "teh", "the");
+        abbreviations.insert(None, "pr", "print");
+        abbreviations.insert(Some("rust"), "pr", "println!(\"$0\");");
+        assert_eq!(abbreviations.get("teh", Some("rust")), Some("the"));
+        assert_eq!(abbreviations.get("pr", None), Some("print"));
+        assert_eq!(abbreviations.get("pr", Some("python")), Some("print"));
+        assert_eq!(
+            abbreviations.get("pr", Some("rust")),
+            Some("println!(\"$0\");")
+        );
+
+        assert_eq!(
+            abbreviations.expand("say teh", None),
+            Some((
+                3,
+                Expansion {
+                    text: "the".to_string(),
+                    cursor: None,
+                }
+            ))
+        );
+        assert_eq!(abbreviations.expand("sayteh", None), None, "a whole word");
+        assert_eq!(abbreviations.expand("teh ", None), None);
+    }
+
+    #[test]
+    fn test_expansions_are_indented_and_mark_the_cursor() {
+        let mut abbreviations = Abbreviations::new();
+        abbreviations.insert(Some("rust"), "test", "#[test]\nfn $0() {\n}");
+        let (length, expansion) = abbreviations.expand("    test", Some("rust")).unwrap();
+        assert_eq!(length, 4);
+        assert_eq!(expansion.text, "#[test]\n    fn () {\n    }");
+        assert_eq!(expansion.cursor, Some(15));
+    }
+}
@@ -3,13 +3,1101 @@
 //! The `App` struct owns the application state and windows, providing a
 //! testable interface that is decoupled from terminal I/O.
 
-use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use cli_ide_base::{Emitter, Event, Subscription};
+use cli_ide_platform::cargo::cargo_command::CargoCommand;
+use cli_ide_platform::clipboard::clipboard_service::ClipboardService;
+use cli_ide_platform::command::command_service::{CommandError, CommandService};
+use cli_ide_platform::config::config_service::{Config, ConfigChanged, ConfigService, ConfigValue};
+use cli_ide_platform::context::context_key_service::{ContextKeyExpr, ContextKeyService};
+use cli_ide_platform::di::service_container::ServiceContainer;
+use cli_ide_platform::environment::environment_service::EnvironmentService;
+use cli_ide_platform::files::file_operations::{
+    FileOperationError, FileOperationEvent, FileOperations,
+};
+use cli_ide_platform::files::file_watcher::{FileChanged, FileWatcher};
+use cli_ide_platform::files::ignore;
+use cli_ide_platform::git::branch::Branch;
+use cli_ide_platform::git::diff::FileDiff;
+use cli_ide_platform::git::git_service::GitService;
+use cli_ide_platform::git::repository::{DiffBase, GitError, HunkAction, Repository};
+use cli_ide_platform::git::stash::Stash;
+use cli_ide_platform::logging::log_service::{LogRecord, LogService};
+use cli_ide_platform::notification::notification_service::{
+    NotificationFilter, NotificationId, NotificationService, NotificationSeverity,
+};
+use cli_ide_platform::opener::opener_service::OpenerService;
+use cli_ide_platform::output::output_service::OutputService;
+use cli_ide_platform::problems::problem::{self, Problem};
+use cli_ide_platform::problems::problem_matcher::{ProblemMatcher, ProblemPattern};
+use cli_ide_platform::problems::rustc_matcher::RustcMatcher;
+use cli_ide_platform::process::process_service::{ProcessService, ProcessSpec};
+use cli_ide_platform::progress::progress_service::{
+    Progress, ProgressItem, ProgressLocation, ProgressService,
+};
+use cli_ide_platform::search::search_service::{
+    SearchEvent, SearchHandle, SearchQuery, SearchService,
+};
+use cli_ide_platform::session::session_format::{
+    Session, SessionError, SessionLayout, SessionTab, SessionWindow,
+};
+use cli_ide_platform::session::session_service::SessionService;
+use cli_ide_platform::task::task_runner::{TaskDefinition, TaskOutput, TaskRun, TaskRunner};
+use cli_ide_platform::task::task_service::TaskService;
+use cli_ide_platform::tick::tick_scheduler::TickScheduler;
+use ratatui::layout::{Position, Rect};
+use ratatui::style::Modifier;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
 use ratatui::Frame;
 
-use crate::focus::FocusManager;
-use crate::input::{AppEvent, AppKey};
-use crate::keybinding::{Action, KeybindingRouter};
-use crate::window::{EditorWindow, TerminalWindow, Window, WindowId};
+use crate::abbreviation::{self, Abbreviations};
+use crate::auto_save::AutoSave;
+use crate::buffer_manager::{BufferEvent, BufferManager, BufferUri, SharedBuffer};
+use crate::buffer_options::{self, OptionOverrides};
+use crate::closed_editors::{ClosedEditor, ClosedEditors};
+use crate::code_action::{CodeAction, CodeActionProvider, EditError, TextDocuments, WorkspaceEdit};
+use crate::command_line::{self, ExCommand};
+use crate::completion::{PathCompleter, WordCompleter};
+use crate::context_menu::{ContextMenus, MenuContext, MenuLocation};
+use crate::definition::DefinitionProvider;
+use crate::density::Density;
+use crate::dialog::{Dialog, DialogResult};
+use crate::document_link::{self, DocumentLinkProvider, LinkTarget};
+use crate::editor_group::EditorTab;
+use crate::export::ExportFormat;
+use crate::focus::{FocusChanged, FocusManager};
+use crate::input::{AppEvent, AppKey, MouseButton, MouseEvent, MouseEventKind};
+use crate::keybinding::{
+    Action, BindingScope, EditingMode, KeybindingMode, KeybindingRouter, WindowContext,
+};
+use crate::layout::{LayoutNode, PaneDirection, SplitDirection};
+use crate::layout_preset::LayoutPreset;
+use crate::plugin::{Plugin, WorkbenchRegistry};
+use crate::rename::RenameProvider;
+use crate::scrollbar;
+use crate::search;
+use crate::snippet::{self, Snippets};
+use crate::theme::Theme;
+use crate::todo;
+use crate::tutorial::{Tutorial, TutorialState};
+use crate::window::{
+    pane_borders, CommandLineWindow, CommandPaletteWindow, ConfirmWindow, ContextMenuOutcome,
+    ContextMenuWindow, DiagnosticsWindow, DiffSubject, DiffWindow, EditorWindow, FileTreeOutcome,
+    FileTreeWindow, FindWindow, GitLogOutcome, GitLogWindow, GitPanelOutcome, GitPanelWindow,
+    InputBoxWindow, InputOutcome, KeybindingEntry, KeybindingsWindow, LogWindow,
+    MarkdownPreviewWindow, MirrorWindow, NotificationToastWindow, NotificationsWindow,
+    OutputWindow, PaletteOutcome, PeekWindow, ProblemsOutcome, ProblemsWindow, ProgressToastWindow,
+    QuickPickOutcome, QuickPickWindow, RegexTesterOutcome, RegexTesterWindow, RenameInputWindow,
+    RenameOutcome, SearchOutcome, SearchResultsOutcome, SearchResultsWindow, SearchWindow,
+    SnippetsOutcome, SnippetsWindow, StatusBarAlignment, StatusBarItem, StatusBarUpdate,
+    StatusBarWindow, TerminalReportWindow, TerminalWindow, TodosOutcome, TodosWindow,
+    TutorialWindow, UndoTreeOutcome, UndoTreeWindow, Window, WindowId, INPUT_BOX_HEIGHT,
+    NOTIFICATION_HEIGHT, TOAST_HEIGHT, TOAST_WIDTH, TUTORIAL_HEIGHT, TUTORIAL_WIDTH,
+};
+use crate::window_manager::WindowManager;
+use crate::word_count::WordCounter;
+use crate::workspace::{self, WorkspaceFolders};
+
+/// Commands registered by the workbench itself, as `(id, title, action)`.
+///
+/// Each command's handler requests its action from the App.
+const BUILTIN_COMMANDS: &[(&str, &str, Action)] = &[
+    ("workbench.action.quit", "Quit", Action::Quit),
+    (
+        "workbench.action.toggleFocus",
+        "Toggle Focus",
+        Action::ToggleFocus,
+    ),
+    (
+        "workbench.action.focusNextWindow",
+        "Focus Next Window",
+        Action::FocusNext,
+    ),
+    (
+        "workbench.action.focusPreviousWindow",
+        "Focus Previous Window",
+        Action::FocusPrev,
+    ),
+    (
+        "workbench.action.focusAboveWindow",
+        "Focus Window Above",
+        Action::FocusDirection(PaneDirection::Up),
+    ),
+    (
+        "workbench.action.focusBelowWindow",
+        "Focus Window Below",
+        Action::FocusDirection(PaneDirection::Down),
+    ),
+    (
+        "workbench.action.focusLeftWindow",
+        "Focus Window Left",
+        Action::FocusDirection(PaneDirection::Left),
+    ),
+    (
+        "workbench.action.focusRightWindow",
+        "Focus Window Right",
+        Action::FocusDirection(PaneDirection::Right),
+    ),
+    (
+        "workbench.action.closeWindow",
+        "Close Focused Window",
+        Action::CloseWindow,
+    ),
+    (
+        "workbench.action.showCommands",
+        "Show All Commands",
+        Action::OpenCommandPalette,
+    ),
+    (
+        "workbench.action.openCommandLine",
+        "Open Command Line",
+        Action::OpenCommandLine,
+    ),
+    (
+        "editor.action.showContextMenu",
+        "Show Context Menu",
+        Action::ShowContextMenu,
+    ),
+    (
+        "editor.action.expandBracketSelection",
+        "Expand Bracket Selection",
+        Action::ExpandBracketSelection,
+    ),
+    (
+        "editor.action.shrinkBracketSelection",
+        "Shrink Bracket Selection",
+        Action::ShrinkBracketSelection,
+    ),
+    (
+        "editor.action.quickFix",
+        "Quick Fix...",
+        Action::ShowCodeActions,
+    ),
+    (
+        "editor.action.peekDefinition",
+        "Peek Definition",
+        Action::PeekDefinition,
+    ),
+    (
+        "editor.action.rename",
+        "Rename Symbol",
+        Action::RenameSymbol,
+    ),
+    ("editor.action.openLink", "Open Link", Action::OpenLink),
+    ("actions.find", "Find", Action::Find),
+    (
+        "editor.action.nextMatchFindAction",
+        "Find Next",
+        Action::FindNext,
+    ),
+    (
+        "editor.action.previousMatchFindAction",
+        "Find Previous",
+        Action::FindPrevious,
+    ),
+    ("editor.action.replaceOne", "Replace", Action::ReplaceOne),
+    (
+        "editor.action.replaceAll",
+        "Replace All",
+        Action::ReplaceAll,
+    ),
+    ("undo", "Undo", Action::Undo),
+    ("redo", "Redo", Action::Redo),
+    (
+        "editor.action.scrollPageUp",
+        "Scroll Page Up",
+        Action::ScrollPageUp,
+    ),
+    (
+        "editor.action.scrollPageDown",
+        "Scroll Page Down",
+        Action::ScrollPageDown,
+    ),
+    (
+        "editor.action.scrollLineUp",
+        "Scroll Line Up",
+        Action::ScrollLineUp,
+    ),
+    (
+        "editor.action.scrollLineDown",
+        "Scroll Line Down",
+        Action::ScrollLineDown,
+    ),
+    (
+        "workbench.action.toggleTheme",
+        "Toggle Color Theme",
+        Action::ToggleTheme,
+    ),
+    (
+        "workbench.action.files.newUntitledFile",
+        "New Untitled File",
+        Action::NewUntitledFile,
+    ),
+    ("workbench.action.files.save", "Save", Action::Save),
+    (
+        "workbench.action.files.exportAsAnsi",
+        "Export as ANSI Text",
+        Action::ExportAsAnsi,
+    ),
+    (
+        "workbench.action.files.exportAsHtml",
+        "Export as HTML",
+        Action::ExportAsHtml,
+    ),
+    (
+        "workbench.action.quickOpen",
+        "Go to File...",
+        Action::QuickOpen,
+    ),
+    (
+        "workbench.view.explorer",
+        "Toggle Explorer",
+        Action::ToggleExplorer,
+    ),
+    ("explorer.newFile", "New File...", Action::ExplorerNewFile),
+    (
+        "explorer.newFolder",
+        "New Folder...",
+        Action::ExplorerNewFolder,
+    ),
+    ("renameFile", "Rename...", Action::ExplorerRename),
+    ("deleteFile", "Delete", Action::ExplorerDelete),
+    (
+        "explorer.moveFiles",
+        "Move Marked Files Here",
+        Action::ExplorerMove,
+    ),
+    (
+        "workbench.files.action.refreshFilesExplorer",
+        "Refresh Explorer",
+        Action::ExplorerRefresh,
+    ),
+    (
+        "workbench.action.addRootFolder",
+        "Add Folder to Workspace...",
+        Action::AddRootFolder,
+    ),
+    (
+        "workbench.action.removeRootFolder",
+        "Remove Folder from Workspace",
+        Action::RemoveRootFolder,
+    ),
+    (
+        "workbench.view.search",
+        "Toggle Search",
+        Action::ToggleSearch,
+    ),
+    (
+        "search.action.replaceAll",
+        "Replace in Files",
+        Action::ReplaceInFiles,
+    ),
+    (
+        "workbench.action.findInFiles",
+        "Find in Files",
+        Action::FindInFiles,
+    ),
+    ("workbench.action.nextEditor", "Next Tab", Action::NextTab),
+    (
+        "workbench.action.previousEditor",
+        "Previous Tab",
+        Action::PrevTab,
+    ),
+    (
+        "workbench.action.closeActiveEditor",
+        "Close Tab",
+        Action::CloseTab,
+    ),
+    (
+        "workbench.action.reopenClosedEditor",
+        "Reopen Closed Editor",
+        Action::ReopenClosedEditor,
+    ),
+    (
+        "workbench.action.closeOtherEditors",
+        "Close Other Tabs",
+        Action::CloseOtherTabs,
+    ),
+    (
+        "workbench.action.togglePinnedEditor",
+        "Pin/Unpin Tab",
+        Action::TogglePinnedTab,
+    ),
+    (
+        "workbench.action.moveEditorLeftInGroup",
+        "Move Tab Left",
+        Action::MoveTabLeft,
+    ),
+    (
+        "workbench.action.moveEditorRightInGroup",
+        "Move Tab Right",
+        Action::MoveTabRight,
+    ),
+    (
+        "workbench.action.moveEditorToNextGroup",
+        "Move Tab to Next Group",
+        Action::MoveTabToNextGroup,
+    ),
+    (
+        "workbench.action.splitEditorRight",
+        "Split Editor Right",
+        Action::SplitEditorRight,
+    ),
+    (
+        "workbench.action.splitEditorDown",
+        "Split Editor Down",
+        Action::SplitEditorDown,
+    ),
+    (
+        "workbench.action.increaseViewWidth",
+        "Increase View Width",
+        Action::IncreaseViewWidth,
+    ),
+    (
+        "workbench.action.decreaseViewWidth",
+        "Decrease View Width",
+        Action::DecreaseViewWidth,
+    ),
+    (
+        "workbench.action.increaseViewHeight",
+        "Increase View Height",
+        Action::IncreaseViewHeight,
+    ),
+    (
+        "workbench.action.decreaseViewHeight",
+        "Decrease View Height",
+        Action::DecreaseViewHeight,
+    ),
+    (
+        "workbench.action.toggleRegexTester",
+        "Toggle Regex Tester",
+        Action::ToggleRegexTester,
+    ),
+    (
+        "workbench.action.showKeybindings",
+        "Show Keybindings",
+        Action::ShowKeybindings,
+    ),
+    (
+        "workbench.action.toggleSnippets",
+        "Toggle Snippets",
+        Action::ToggleSnippets,
+    ),
+    (
+        "workbench.action.toggleTodos",
+        "Toggle TODO List",
+        Action::ToggleTodos,
+    ),
+    (
+        "workbench.action.toggleUndoTree",
+        "Toggle Undo Tree",
+        Action::ToggleUndoTree,
+    ),
+    (
+        "workbench.view.scm",
+        "Toggle Git Panel",
+        Action::ToggleGitPanel,
+    ),
+    ("git.refresh", "Refresh Git Panel", Action::GitRefresh),
+    ("git.stageSelectedRanges", "Stage Hunk", Action::StageHunk),
+    (
+        "git.unstageSelectedRanges",
+        "Unstage Hunk",
+        Action::UnstageHunk,
+    ),
+    (
+        "git.revertSelectedRanges",
+        "Revert Hunk",
+        Action::RevertHunk,
+    ),
+    (
+        "diffEditor.switchLayout",
+        "Switch Diff Layout",
+        Action::ToggleDiffLayout,
+    ),
+    (
+        "workbench.files.action.compareWithSaved",
+        "Compare Active File with Saved",
+        Action::CompareWithSaved,
+    ),
+    (
+        "git.compareWithHead",
+        "Compare Active File with HEAD",
+        Action::CompareWithHead,
+    ),
+    (
+        "workbench.files.action.compareWithClipboard",
+        "Compare Active File with Clipboard",
+        Action::CompareWithClipboard,
+    ),
+    (
+        "workbench.files.action.compareFileWith",
+        "Compare Active File With...",
+        Action::CompareWithFile,
+    ),
+    (
+        "workbench.view.gitHistory",
+        "Toggle Git History",
+        Action::ToggleGitLog,
+    ),
+    ("git.checkout", "Checkout to...", Action::CheckoutBranch),
+    ("git.stash", "Stash Changes", Action::StashChanges),
+    (
+        "git.stashPopLatest",
+        "Pop Latest Stash",
+        Action::PopLatestStash,
+    ),
+    ("git.stashPop", "Pop Stash...", Action::PopStash),
+    ("cargo.build", "Cargo: Build", Action::CargoBuild),
+    ("cargo.test", "Cargo: Test", Action::CargoTest),
+    ("cargo.run", "Cargo: Run", Action::CargoRun),
+    ("cargo.clippy", "Cargo: Clippy", Action::CargoClippy),
+    ("cargo.stop", "Cargo: Stop", Action::CargoStop),
+    (
+        "workbench.action.tasks.runTask",
+        "Tasks: Run Task",
+        Action::RunTask,
+    ),
+    (
+        "workbench.actions.view.problems",
+        "Toggle Problems",
+        Action::ToggleProblems,
+    ),
+    (
+        "workbench.actions.view.diagnostics",
+        "Toggle Diagnostics",
+        Action::ToggleDiagnostics,
+    ),
+    (
+        "editor.action.marker.next",
+        "Go to Next Problem",
+        Action::NextDiagnostic,
+    ),
+    (
+        "editor.action.marker.prev",
+        "Go to Previous Problem",
+        Action::PreviousDiagnostic,
+    ),
+    (
+        "workbench.action.toggleOutput",
+        "Toggle Output",
+        Action::ToggleOutput,
+    ),
+    (
+        "workbench.action.toggleLogs",
+        "Toggle Logs",
+        Action::ToggleLogs,
+    ),
+    (
+        "workbench.action.toggleTerminalCapabilities",
+        "Toggle Terminal Capabilities",
+        Action::ToggleTerminalCapabilities,
+    ),
+    (
+        "workbench.action.togglePresentationMirror",
+        "Toggle Presentation Mirror",
+        Action::ToggleMirror,
+    ),
+    (
+        "workbench.action.toggleMarkdownPreview",
+        "Toggle Markdown Preview",
+        Action::ToggleMarkdownPreview,
+    ),
+    (
+        "workbench.action.toggleScrollbars",
+        "Toggle Scrollbars",
+        Action::ToggleScrollbars,
+    ),
+    (
+        "workbench.action.toggleWordCount",
+        "Toggle Word Count",
+        Action::ToggleWordCount,
+    ),
+    (
+        "workbench.actions.view.toggleCompactMode",
+        "Toggle Compact Mode",
+        Action::ToggleCompactMode,
+    ),
+    (
+        "workbench.actions.view.toggleDensity",
+        "Toggle UI Density",
+        Action::ToggleDensity,
+    ),
+    (
+        "workbench.action.cancelProgress",
+        "Cancel Running Operation",
+        Action::CancelProgress,
+    ),
+    (
+        "workbench.notifications.toggleList",
+        "Toggle Notifications",
+        Action::ToggleNotifications,
+    ),
+    (
+        "workbench.notifications.toggleDoNotDisturbMode",
+        "Toggle Do Not Disturb Mode",
+        Action::ToggleDoNotDisturb,
+    ),
+    (
+        "workbench.notifications.clearAll",
+        "Clear All Notifications",
+        Action::ClearNotifications,
+    ),
+    (
+        "workbench.notifications.hideToasts",
+        "Hide Notification Toasts",
+        Action::HideNotificationToasts,
+    ),
+    (
+        "workbench.action.toggleTutorial",
+        "Toggle Tutorial",
+        Action::ToggleTutorial,
+    ),
+    (
+        "workbench.action.toggleModalEditing",
+        "Toggle Modal Editing",
+        Action::ToggleModalEditing,
+    ),
+    (
+        "modalEditing.normalMode",
+        "Modal Editing: Normal Mode",
+        Action::EnterNormalMode,
+    ),
+    (
+        "modalEditing.insertMode",
+        "Modal Editing: Insert Mode",
+        Action::EnterInsertMode,
+    ),
+    (
+        "modalEditing.visualMode",
+        "Modal Editing: Visual Mode",
+        Action::EnterVisualMode,
+    ),
+    ("cursorLeft", "Move Cursor Left", Action::CursorLeft),
+    ("cursorDown", "Move Cursor Down", Action::CursorDown),
+    ("cursorUp", "Move Cursor Up", Action::CursorUp),
+    ("cursorRight", "Move Cursor Right", Action::CursorRight),
+    ("cursorLeftSelect", "Select Left", Action::SelectLeft),
+    ("cursorDownSelect", "Select Down", Action::SelectDown),
+    ("cursorUpSelect", "Select Up", Action::SelectUp),
+    ("cursorRightSelect", "Select Right", Action::SelectRight),
+    ("deleteRight", "Delete Character", Action::DeleteCharacter),
+    ("deleteLeft", "Delete Left", Action::DeleteLeft),
+    ("editor.action.clipboardCopyAction", "Copy", Action::Copy),
+    ("editor.action.clipboardCutAction", "Cut", Action::Cut),
+    ("editor.action.clipboardPasteAction", "Paste", Action::Paste),
+];
+
+/// Status bar segment reporting auto-save failures.
+const AUTO_SAVE_SEGMENT: &str = "autoSave";
+
+/// Status bar segment reporting failed saves.
+const SAVE_SEGMENT: &str = "save";
+
+/// Status bar segment reporting files that could not be listed or opened.
+const FILES_SEGMENT: &str = "files";
+
+/// Most files quick open lists.
+const MAX_QUICK_OPEN_FILES: usize = 10_000;
+
+/// Status bar segment reporting workspace search and replace results.
+const SEARCH_SEGMENT: &str = "search";
+
+/// Most files a workspace search reads.
+const MAX_SEARCH_FILES: usize = 10_000;
+
+/// Status bar segment showing the progress of running operations.
+const PROGRESS_SEGMENT: &str = "progress";
+
+/// Status bar item saying "do not disturb" is on; clicking it opens the
+/// notification center.
+const NOTIFICATIONS_ITEM: &str = "notifications";
+
+/// Status bar segment reporting git failures and hunk actions.
+const GIT_SEGMENT: &str = "git";
+
+/// Status bar segment naming the current git branch.
+const BRANCH_SEGMENT: &str = "branch";
+
+/// How long file changes are collected before the git state is read
+/// again, so a burst of changes refreshes it once.
+const GIT_REFRESH_DELAY: Duration = Duration::from_millis(300);
+
+/// Most commits the git history lists.
+const MAX_LOG_COMMITS: usize = 1_000;
+
+/// Status bar segment reporting the state of the last task or cargo
+/// command.
+const TASK_SEGMENT: &str = "task";
+
+/// Status bar segment showing the problem the editor went to.
+const DIAGNOSTIC_SEGMENT: &str = "diagnostic";
+
+/// Status bar segment counting the lines, words and characters of the
+/// editor's document or selection.
+const WORD_COUNT_SEGMENT: &str = "wordCount";
+
+/// Output channel cargo commands write their output to.
+pub const CARGO_CHANNEL: &str = "Cargo";
+
+/// Output channel configured tasks write their output to, unless they
+/// write to the terminal.
+pub const TASKS_CHANNEL: &str = "Tasks";
+
+/// Output channel failed git operations are written to.
+pub const GIT_CHANNEL: &str = "Git";
+
+/// Status bar segment reporting configuration problems.
+const CONFIG_SEGMENT: &str = "config";
+
+/// Configuration setting holding the auto-save mode.
+const AUTO_SAVE_SETTING: &str = "files.auto_save";
+
+/// Configuration setting showing the scrollbars of scrollable windows.
+const SCROLLBARS_SETTING: &str = "workbench.scrollbars";
+
+/// Configuration setting showing the word count in the status bar.
+const WORD_COUNT_SETTING: &str = "workbench.word_count";
+
+/// Configuration setting naming the color theme: a built-in theme or a
+/// theme file.
+const THEME_SETTING: &str = "workbench.theme";
+
+/// Configuration section mapping window contexts, e.g. `terminal`, to the
+/// theme their panes use instead of the active one.
+const PANE_THEMES_SECTION: &str = "workbench.pane_themes";
+
+/// Configuration setting drawing editors without focus with a dimmed theme.
+const DIM_UNFOCUSED_EDITORS_SETTING: &str = "workbench.dim_unfocused_editors";
+
+/// Configuration setting holding the UI density.
+const DENSITY_SETTING: &str = "workbench.density";
+
+/// Configuration setting holding the least important level that is logged.
+const LOG_LEVEL_SETTING: &str = "log.level";
+
+/// Configuration setting turning "do not disturb" on at startup.
+const DO_NOT_DISTURB_SETTING: &str = "notifications.do_not_disturb";
+
+/// Configuration setting holding the comma-separated sources whose
+/// notifications are muted.
+const MUTED_SOURCES_SETTING: &str = "notifications.muted_sources";
+
+/// Configuration setting holding the comma-separated severities whose
+/// notifications are muted.
+const MUTED_SEVERITIES_SETTING: &str = "notifications.muted_severities";
+
+/// Height of the status bar, in rows.
+const STATUS_BAR_HEIGHT: u16 = 1;
+
+/// Cells the pane resize commands grow or shrink the focused pane by.
+const PANE_RESIZE_STEP: i32 = 4;
+
+/// Source of snippet manager errors.
+const SNIPPETS_SOURCE: &str = "Snippets";
+
+/// Height of the safe mode banner, in rows.
+const SAFE_MODE_BANNER_HEIGHT: u16 = 1;
+
+/// Text of the safe mode banner.
+const SAFE_MODE_BANNER: &str = "Safe Mode: user settings and the saved session are not loaded";
+
+/// The open quick-fix menu and the actions it lists.
+struct QuickFixMenu {
+    /// Picker over the action titles.
+    picker: QuickPickWindow,
+    /// Actions in the same order as the picker items.
+    actions: Vec<CodeAction>,
+}
+
+/// The open context menu and where it was opened.
+struct OpenContextMenu {
+    /// The menu of commands.
+    menu: ContextMenuWindow,
+    /// Cell at which the menu's top left corner was requested.
+    anchor: Position,
+}
+
+/// A pane edge being dragged with the mouse.
+#[derive(Debug, Clone, Copy)]
+struct SplitDrag {
+    /// The pane whose left or top edge is dragged.
+    id: WindowId,
+    /// [`SplitDirection::Horizontal`] for the left edge,
+    /// [`SplitDirection::Vertical`] for the top one.
+    direction: SplitDirection,
+    /// Column (or row) the edge is at.
+    position: u16,
+}
+
+/// The open quick-open picker and the files it lists.
+struct QuickOpenMenu {
+    /// Picker over the workspace-relative paths.
+    picker: QuickPickWindow,
+    /// Files in the same order as the picker items.
+    paths: Vec<PathBuf>,
+}
+
+/// An item of the branch picker.
+enum BranchPick {
+    /// Prompt for the name of a new branch.
+    Create,
+    /// Check out this branch.
+    Checkout(Branch),
+}
+
+/// The open branch picker and the branches it lists.
+struct BranchMenu {
+    /// Picker over the branch labels.
+    picker: QuickPickWindow,
+    /// Items in the same order as the picker items.
+    picks: Vec<BranchPick>,
+}
+
+/// The open stash picker and the stashes it lists.
+struct StashMenu {
+    /// Picker over the stash descriptions.
+    picker: QuickPickWindow,
+    /// Stashes in the same order as the picker items.
+    stashes: Vec<Stash>,
+}
+
+/// The open task picker and the tasks it lists.
+struct TaskMenu {
+    /// Picker over the task names and commands.
+    picker: QuickPickWindow,
+    /// Tasks in the same order as the picker items.
+    tasks: Vec<TaskDefinition>,
+}
+
+/// A running task, such as a cargo command, and where its output goes.
+struct RunningTask {
+    /// The task's process and the matchers reading its output.
+    run: TaskRun,
+    /// Where the output is shown.
+    output: TaskOutput,
+    /// Output channel the output is appended to, also the source of the
+    /// notification sent when the task ends.
+    channel: &'static str,
+}
+
+/// An open input box and what its value is for.
+struct PendingInput {
+    /// The input overlay.
+    input: InputBoxWindow,
+    /// What accepting the value does.
+    purpose: InputPurpose,
+}
+
+/// What an input box asks for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum InputPurpose {
+    /// A path to save the given editor's buffer to.
+    SaveAs(WindowId),
+    /// A path to export the given editor's text to in the given format.
+    Export(WindowId, ExportFormat),
+    /// A file to compare the given editor's document with.
+    CompareWith(WindowId),
+    /// The name of a new file in the given folder.
+    NewFile(PathBuf),
+    /// The name of a new folder in the given folder.
+    NewFolder(PathBuf),
+    /// A new name for the given file or folder.
+    Rename(PathBuf),
+    /// The name of a new branch to create and check out.
+    NewBranch,
+    /// The message of a stash of the local changes.
+    StashMessage,
+    /// A folder to add as a root of the workspace.
+    RootFolder,
+    /// The word of a new snippet, after the language it expands in, if
+    /// any.
+    NewSnippet,
+    /// The body of the snippet for the given language and word.
+    SnippetBody(Option<String>, String),
+}
+
+/// A request to quit, passed to the listeners of
+/// [`App::on_quit_requested`] before the App asks anything.
+///
+/// A listener that must not be interrupted, e.g. one writing a file,
+/// calls [`QuitRequested::veto`] and the App keeps running.
+#[derive(Debug, Clone, Default)]
+pub struct QuitRequested {
+    /// Why listeners refused, in the order they did; shared by clones.
+    vetoes: Arc<Mutex<Vec<String>>>,
+}
+
+impl QuitRequested {
+    /// Refuse to quit, saying why.
+    pub fn veto(&self, reason: impl Into<String>) {
+        self.vetoes
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .push(reason.into());
+    }
+
+    /// Get why listeners refused, in the order they did.
+    pub fn vetoes(&self) -> Vec<String> {
+        self.vetoes
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .clone()
+    }
+}
+
+/// An open modal dialog and what its answer is for.
+struct OpenDialog {
+    /// The dialog overlay.
+    dialog: Dialog,
+    /// What the answer decides.
+    purpose: DialogPurpose,
+}
+
+/// What a modal dialog asks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DialogPurpose {
+    /// Whether to quit although processes or tasks are running.
+    ForceQuit,
+    /// Whether to save the modified files before quitting.
+    SaveBeforeQuit,
+}
+
+/// The title of the built-in command running `action`, or the action's
+/// name if no command runs it.
+fn action_title(action: Action) -> String {
+    BUILTIN_COMMANDS
+        .iter()
+        .find(|&&(_, _, command)| command == action)
+        .map_or_else(|| format!("{action:?}"), |&(_, title, _)| title.to_string())
+}
+
+/// The status bar text saying "do not disturb" is on, with how many
+/// notifications it muted, or nothing while it is off.
+fn do_not_disturb_text(notifications: &NotificationService) -> String {
+    if !notifications.do_not_disturb() {
+        return String::new();
+    }
+    let muted = notifications
+        .notifications()
+        .iter()
+        .filter(|notification| notification.suppressed)
+        .count();
+    match muted {
+        0 => "Do Not Disturb".to_string(),
+        muted => format!("Do Not Disturb ({muted} muted)"),
+    }
+}
+
+/// The status bar text for running operations: the newest one, with its
+/// percentage and message, and how many others are running.
+fn progress_text(items: &[ProgressItem]) -> Option<String> {
+    let newest = items.last()?;
+    let mut text = newest.title.clone();
+    match newest.percentage {
+        Some(percentage) => text.push_str(&format!(" {percentage}%")),
+        None => text.push_str("..."),
+    }
+    if let Some(message) = &newest.message {
+        text.push_str(&format!(" {message}"));
+    }
+    if items.len() > 1 {
+        text.push_str(&format!(" (+{})", items.len() - 1));
+    }
+    Some(text)
+}
+
+/// Read the notification filter from the `notifications.*` settings.
+fn notification_filter(config: &Config) -> Result<NotificationFilter, String> {
+    let list = |name: &str| -> Result<Vec<String>, String> {
+        let value = config.get_string(name).map_err(|error| error.to_string())?;
+        Ok(value
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(str::to_string)
+            .collect())
+    };
+    let muted_severities = list(MUTED_SEVERITIES_SETTING)?
+        .iter()
+        .map(|name| name.parse())
+        .collect::<Result<_, _>>()
+        .map_err(|error| format!("`{MUTED_SEVERITIES_SETTING}`: {error}"))?;
+    Ok(NotificationFilter {
+        do_not_disturb: config
+            .get_bool(DO_NOT_DISTURB_SETTING)
+            .map_err(|error| error.to_string())?
+            .unwrap_or(false),
+        muted_sources: list(MUTED_SOURCES_SETTING)?.into_iter().collect(),
+        muted_severities,
+    })
+}
+
+/// Convert a saved layout to a layout tree of the restored windows, where
+/// `ids[index]` is the window restoring saved window `index`.
+fn restore_layout(layout: &SessionLayout, ids: &[Option<WindowId>]) -> Option<LayoutNode> {
+    match layout {
+        SessionLayout::Window(index) => ids.get(*index).copied().flatten().map(LayoutNode::Window),
+        SessionLayout::Split {
+            direction,
+            children,
+            weights,
+        } => {
+            let (mut children, weights): (Vec<LayoutNode>, Vec<u16>) = children
+                .iter()
+                .zip(weights.iter().chain(std::iter::repeat(&1)))
+                .filter_map(|(child, &weight)| Some((restore_layout(child, ids)?, weight.max(1))))
+                .unzip();
+            match children.len() {
+                0 | 1 => children.pop(),
+                _ => Some(LayoutNode::Split {
+                    direction: (*direction).into(),
+                    children,
+                    weights,
+                }),
+            }
+        }
+    }
+}
+
+/// Convert a layout tree to a saved layout, keeping the windows listed in
+/// `indices` with their saved index.
+fn session_layout(layout: &LayoutNode, indices: &[(WindowId, usize)]) -> Option<SessionLayout> {
+    match layout {
+        LayoutNode::Window(id) => indices
+            .iter()
+            .find(|(window, _)| window == id)
+            .map(|&(_, index)| SessionLayout::Window(index)),
+        LayoutNode::Split {
+            direction,
+            children,
+            weights,
+        } => {
+            let (mut children, weights): (Vec<SessionLayout>, Vec<u16>) = children
+                .iter()
+                .zip(weights)
+                .filter_map(|(child, &weight)| Some((session_layout(child, indices)?, weight)))
+                .unzip();
+            match children.len() {
+                0 | 1 => children.pop(),
+                _ => Some(SessionLayout::Split {
+                    direction: (*direction).into(),
+                    children,
+                    weights,
+                }),
+            }
+        }
+    }
+}
+
+/// The documents workspace edits apply to: every file open in the buffer
+/// manager, plus the main editor's own unshared buffers.
+struct OpenDocuments<'a> {
+    buffers: &'a mut BufferManager,
+    editor: &'a mut EditorWindow,
+}
+
+impl TextDocuments for OpenDocuments<'_> {
+    fn text(&self, path: &Path) -> Option<String> {
+        TextDocuments::text(self.buffers, path).or_else(|| TextDocuments::text(self.editor, path))
+    }
+
+    fn set_text(&mut self, path: &Path, text: String) -> Result<(), EditError> {
+        if self.buffers.contains(path) {
+            TextDocuments::set_text(self.buffers, path, text)
+        } else {
+            TextDocuments::set_text(self.editor, path, text)
+        }
+    }
+}
+
+/// Watchers on a set of files, such as the configuration files, and their
+/// pending change events.
+struct FileWatches {
+    /// How often the files are checked.
+    interval: Duration,
+    /// One watcher per file; each keeps its polling thread alive.
+    watchers: Vec<FileWatcher>,
+    /// Changes not yet reloaded, one subscription per watcher.
+    changes: Vec<Subscription<FileChanged>>,
+}
+
+impl FileWatches {
+    /// Watch `paths`, checking every `interval`.
+    fn new(paths: Vec<PathBuf>, interval: Duration) -> Self {
+        let watchers: Vec<FileWatcher> = paths
+            .into_iter()
+            .map(|path| FileWatcher::new(path, interval))
+            .collect();
+        let changes = watchers
+            .iter()
+            .map(|watcher| watcher.on_did_change().subscribe())
+            .collect();
+        Self {
+            interval,
+            watchers,
+            changes,
+        }
+    }
+
+    /// Check whether any of the files are watched as `paths` are.
+    fn watches(&self, paths: &[PathBuf]) -> bool {
+        self.watchers
+            .iter()
+            .map(FileWatcher::path)
+            .eq(paths.iter().map(PathBuf::as_path))
+    }
+
+    /// Take the pending changes, returning whether any file changed.
+    fn take_changes(&self) -> bool {
+        !self.take_changed_paths().is_empty()
+    }
+
+    /// Take the pending changes, returning the files that changed, in
+    /// watch order.
+    fn take_changed_paths(&self) -> Vec<PathBuf> {
+        // Drain every subscription, not just up to the first change
+        self.watchers
+            .iter()
+            .zip(&self.changes)
+            .filter(|(_, changes)| changes.try_iter().count() > 0)
+            .map(|(watcher, _)| watcher.path().to_path_buf())
+            .collect()
+    }
+}
+
+/// File changes that may change the git state, batched by
+/// [`GIT_REFRESH_DELAY`].
+struct GitChanges {
+    /// Watchers on the repository's `HEAD` and index, if watching.
+    watchers: Vec<FileWatcher>,
+    /// Batches not yet processed.
+    batches: Subscription<Vec<FileChanged>>,
+}
+
+impl GitChanges {
+    /// Batch the changes announced to `git` and those the `watchers` see.
+    fn new(git: &GitService, watchers: Vec<FileWatcher>) -> Self {
+        let batches = watchers
+            .iter()
+            .map(|watcher| watcher.on_did_change().clone())
+            .fold(git.on_did_change_files().clone(), Event::merge)
+            .buffer_time(GIT_REFRESH_DELAY)
+            .subscribe();
+        Self { watchers, batches }
+    }
+}
 
 /// Which pane currently has focus.
 ///
@@ -37,24 +1125,221 @@ impl FocusedPane {
 /// Owns the windows and manages application lifecycle. Can be driven by
 /// `AppEvent`s for testing without a real terminal.
 pub struct App {
-    /// The editor window (left pane)
-    editor: EditorWindow,
-    /// The terminal window (right pane)
-    terminal: TerminalWindow,
-    /// Editor window ID
+    /// Open windows and their layout
+    windows: WindowManager,
+    /// ID of the main editor window, which is never closed
     editor_id: WindowId,
-    /// Terminal window ID
+    /// ID of the built-in terminal window
     terminal_id: WindowId,
+    /// ID of the file explorer, while open
+    explorer_id: Option<WindowId>,
+    /// ID of the search panel, while open
+    search_id: Option<WindowId>,
+    /// ID of the Find in Files panel, while open
+    search_results_id: Option<WindowId>,
+    /// The workspace search streaming into the Find in Files panel, if any
+    workspace_search: Option<SearchHandle>,
+    /// Progress of the running workspace search, shown in the status bar
+    /// until it ends.
+    search_progress: Option<Progress>,
+    /// ID of the TODO list, while open
+    todos_id: Option<WindowId>,
+    /// The workspace scan streaming into the TODO list, if any
+    todo_scan: Option<SearchHandle>,
+    /// Progress of the running TODO scan, shown in the status bar until it
+    /// ends.
+    todo_progress: Option<Progress>,
+    /// Buffer events, such as saves, not yet applied to the TODO list
+    todo_buffer_events: Subscription<BufferEvent>,
+    /// Watchers on the files the TODO list shows, if watching
+    todo_watch: Option<FileWatches>,
+    /// ID of the regex tester, while open
+    regex_tester_id: Option<WindowId>,
+    /// ID of the git panel, while open
+    git_panel_id: Option<WindowId>,
+    /// ID of the diff view, while open
+    diff_id: Option<WindowId>,
+    /// ID of the git history, while open
+    git_log_id: Option<WindowId>,
+    /// ID of the output pane, while open
+    output_id: Option<WindowId>,
+    /// ID of the Problems panel, while open
+    problems_id: Option<WindowId>,
+    /// ID of the Diagnostics panel, while open
+    diagnostics_id: Option<WindowId>,
+    /// ID of the log viewer, while open
+    logs_id: Option<WindowId>,
+    /// ID of the notification center, while open
+    notifications_id: Option<WindowId>,
+    /// ID of the terminal capability report, while open
+    terminal_report_id: Option<WindowId>,
+    /// ID of the keybindings list, while open
+    keybindings_id: Option<WindowId>,
+    /// ID of the presentation mirror, while open
+    mirror_id: Option<WindowId>,
+    /// The editor the mirror follows: the last focused one
+    mirror_source: WindowId,
+    /// ID of the Markdown preview, while open
+    markdown_preview_id: Option<WindowId>,
+    /// The editor whose document the Markdown preview shows: the last
+    /// focused one
+    markdown_preview_source: WindowId,
+    /// ID of the undo tree viewer, while open
+    undo_tree_id: Option<WindowId>,
+    /// The editor whose history the undo tree shows: the last focused one
+    undo_tree_source: WindowId,
+    /// Log records not yet shown in the log viewer
+    log_records: Subscription<LogRecord>,
+    /// The task or cargo command running, if any
+    task: Option<RunningTask>,
+    /// Problems reported by the last cargo command
+    problems: Vec<Problem>,
+    /// Problem matchers from the configuration
+    problem_patterns: Vec<ProblemPattern>,
+    /// Abbreviations from the configuration and the user's snippets,
+    /// expanded as they are typed
+    abbreviations: Abbreviations,
+    /// Abbreviations from the configuration alone
+    config_abbreviations: Abbreviations,
+    /// The user's snippets and the file they are kept in
+    snippets: Snippets,
+    /// ID of the snippet manager, while open
+    snippets_id: Option<WindowId>,
+    /// Open files, shared between the editors showing them
+    buffers: BufferManager,
+    /// Completed file operations not yet applied to the open buffers
+    file_operation_events: Subscription<FileOperationEvent>,
+    /// Buffer events, such as saves, not yet announced to the git service
+    buffer_events: Subscription<BufferEvent>,
+    /// Changes that may change the git state of the open files
+    git_changes: GitChanges,
     /// Focus manager
     focus_manager: FocusManager,
+    /// Focus changes not yet processed by auto-save
+    focus_changes: Subscription<FocusChanged>,
+    /// When editors are saved automatically
+    auto_save: AutoSave,
+    /// Whether scrollable windows show a scrollbar on their right border
+    scrollbars: bool,
+    /// Whether the status bar shows the word count of the editor
+    word_count: bool,
+    /// Counts of the editor's document, kept up to date as it changes
+    word_counter: WordCounter,
+    /// How panes are drawn unless their compact mode was toggled
+    density: Density,
+    /// Compact mode of the panes toggled on their own, overriding the
+    /// density
+    compact_panes: HashMap<WindowId, bool>,
     /// Keybinding router
     keybinding_router: KeybindingRouter,
+    /// Bindings applied from the configuration, with the binding each one
+    /// replaced, in the order they were applied
+    config_bindings: Vec<(Option<WindowContext>, AppKey, Option<Action>)>,
+    /// Whether the terminal window the App runs in has focus, as of the
+    /// last focus event
+    window_focused: bool,
+    /// Conditional bindings applied from the configuration
+    config_when_bindings: Vec<(Option<WindowContext>, AppKey, ContextKeyExpr)>,
+    /// Workspace option layer from the configuration
+    workspace_options: OptionOverrides,
+    /// Option layers of the configuration's `[editor.<language>]`
+    /// sections, by language
+    language_options: HashMap<String, OptionOverrides>,
+    /// Configuration reloads not yet applied
+    config_changes: Option<Subscription<ConfigChanged>>,
+    /// Watcher reloading the configuration when its files change
+    config_watch: Option<FileWatches>,
+    /// Theme file named by the configuration, if any
+    theme_file: Option<PathBuf>,
+    /// Themes of single panes, over the context themes
+    pane_themes: HashMap<WindowId, Theme>,
+    /// Themes of every pane of a context, over the active theme
+    context_themes: HashMap<WindowContext, Theme>,
+    /// Whether editors without focus are drawn with a dimmed theme
+    dim_unfocused_editors: bool,
+    /// The theme each window was last given
+    applied_themes: HashMap<WindowId, Theme>,
+    /// Shared services (command registry, ...)
+    services: Arc<ServiceContainer>,
+    /// Source of the actions command handlers request
+    actions: Event<Action>,
+    /// Actions requested by command handlers, applied after each command runs
+    action_requests: Subscription<Action>,
+    /// Loaded plugins
+    plugins: Vec<Box<dyn Plugin>>,
+    /// What the loaded plugins contribute
+    plugin_registry: WorkbenchRegistry,
+    /// Open plugin windows, by index in the registry's windows
+    plugin_windows: HashMap<usize, WindowId>,
+    /// Whether the safe mode banner is shown
+    safe_mode: bool,
+    /// The command palette overlay, when open
+    command_palette: Option<CommandPaletteWindow>,
+    /// The ex-style command line, when open
+    command_line: Option<CommandLineWindow>,
+    /// Lines run on the command line, oldest first
+    command_line_history: Vec<String>,
+    /// Recently closed editor tabs, for reopening them
+    closed_editors: ClosedEditors,
+    /// Source of code actions for the quick-fix menu
+    code_action_provider: Option<Box<dyn CodeActionProvider>>,
+    /// The quick-fix menu overlay, when open
+    quick_fix: Option<QuickFixMenu>,
+    /// Commands offered by each context menu
+    context_menus: ContextMenus,
+    /// The context menu overlay, when open
+    context_menu: Option<OpenContextMenu>,
+    /// The pane edge being dragged, while the left button is held on it
+    split_drag: Option<SplitDrag>,
+    /// The input box overlay, when open
+    input_box: Option<PendingInput>,
+    /// The quick-open picker overlay, when open
+    quick_open: Option<QuickOpenMenu>,
+    /// The branch picker overlay, when open
+    branch_picker: Option<BranchMenu>,
+    /// The stash picker overlay, when open
+    stash_picker: Option<StashMenu>,
+    /// The task picker overlay, when open
+    task_picker: Option<TaskMenu>,
+    /// The modal dialog, when open
+    dialog: Option<OpenDialog>,
+    /// Progress through the guided tutorial, while it runs
+    tutorial: Option<Tutorial>,
+    /// Mode of the editor's modal editing layer, when it is on
+    editing_mode: Option<EditingMode>,
+    /// Byte offsets where Visual mode started and where its cursor is
+    visual_span: (usize, usize),
+    /// Root folders of the workspace, which quick open, search and the
+    /// explorer span
+    workspace: WorkspaceFolders,
+    /// Source of definitions for peek definition
+    definition_provider: Option<Box<dyn DefinitionProvider>>,
+    /// Source of renames for the inline rename input
+    rename_provider: Option<Box<dyn RenameProvider>>,
+    /// Source of document links, instead of detecting them in the text
+    document_link_provider: Option<Box<dyn DocumentLinkProvider>>,
+    /// Editor and hash of the path and text the provider's links were last
+    /// requested for
+    document_links_requested: Option<(WindowId, u64)>,
+    /// The status bar along the bottom row
+    status_bar: StatusBarWindow,
+    /// Source of status bar segment updates, shared with other components
+    status_bar_updates: Event<StatusBarUpdate>,
+    /// Pending status bar segment updates
+    status_bar_requests: Subscription<StatusBarUpdate>,
+    /// Listeners asked before quitting, which may veto it
+    quit_requested: Emitter<QuitRequested>,
     /// Whether the app is still running
     running: bool,
     /// Current terminal width
     width: u16,
     /// Current terminal height
     height: u16,
+    /// Whether the state changed since the last render
+    dirty: bool,
+    /// The newest notification when they were last checked, to redraw
+    /// for notifications posted from other threads
+    last_notification: Option<NotificationId>,
 }
 
 impl Default for App {
@@ -66,23 +1351,175 @@ impl Default for App {
 impl App {
     /// Create a new App with default windows.
     pub fn new() -> Self {
-        let editor_id = WindowId::new();
-        let terminal_id = WindowId::new();
+        let mut windows = WindowManager::new();
+        let editor_id = windows.open(Box::new(EditorWindow::default()));
+        let terminal_id = windows.open(Box::new(TerminalWindow::default()));
 
         // Start with editor focused
         let focus_manager = FocusManager::with_focus(editor_id);
+        let focus_changes = focus_manager.on_focus_changed().subscribe();
 
-        Self {
-            editor: EditorWindow::default(),
-            terminal: TerminalWindow::default(),
+        // Built-in commands request actions through an event so handlers
+        // don't need mutable access to the App.
+        let actions = Event::<Action>::new();
+        let action_requests = actions.subscribe();
+        let commands = CommandService::new();
+        for &(id, title, action) in BUILTIN_COMMANDS {
+            let actions = actions.clone();
+            commands.register(id, title, move || actions.emit(action));
+        }
+        let services = Arc::new(ServiceContainer::new());
+        services.register(commands);
+        services.register(Theme::default());
+        let file_operations = FileOperations::new();
+        let file_operation_events = file_operations.on_did_run().subscribe();
+        services.register(file_operations);
+        services.register(TaskService::default());
+        services.register(ProcessService::new());
+        services.register_factory(|services| {
+            TaskRunner::new(
+                services
+                    .resolve::<ProcessService>()
+                    .expect("the App registers a process service"),
+            )
+        });
+        services.register(SearchService::new());
+        services.register(OutputService::new());
+        services.register(ProgressService::new());
+        services.register(NotificationService::new());
+        services.register(ClipboardService::new());
+        services.register(OpenerService::new());
+        services.register(ContextKeyService::new());
+        services.register(EnvironmentService::default());
+        services.register(TickScheduler::default());
+        let git = GitService::new();
+        let git_changes = GitChanges::new(&git, Vec::new());
+        services.register(git);
+        let buffers = BufferManager::new();
+        let buffer_events = buffers.on_buffer_event().subscribe();
+        let todo_buffer_events = buffers.on_buffer_event().subscribe();
+        let logs = LogService::new();
+        let log_records = logs.on_did_log().subscribe();
+        services.register(logs);
+
+        let status_bar_updates = Event::<StatusBarUpdate>::new();
+        let status_bar_requests = status_bar_updates.subscribe();
+
+        let mut app = Self {
+            windows,
             editor_id,
             terminal_id,
+            explorer_id: None,
+            search_id: None,
+            search_results_id: None,
+            workspace_search: None,
+            search_progress: None,
+            todos_id: None,
+            todo_scan: None,
+            todo_progress: None,
+            todo_buffer_events,
+            todo_watch: None,
+            regex_tester_id: None,
+            git_panel_id: None,
+            diff_id: None,
+            git_log_id: None,
+            output_id: None,
+            problems_id: None,
+            diagnostics_id: None,
+            logs_id: None,
+            notifications_id: None,
+            terminal_report_id: None,
+            keybindings_id: None,
+            mirror_id: None,
+            mirror_source: editor_id,
+            markdown_preview_id: None,
+            markdown_preview_source: editor_id,
+            undo_tree_id: None,
+            undo_tree_source: editor_id,
+            log_records,
+            task: None,
+            problems: Vec::new(),
+            problem_patterns: Vec::new(),
+            abbreviations: Abbreviations::new(),
+            config_abbreviations: Abbreviations::new(),
+            snippets: Snippets::new(),
+            snippets_id: None,
+            buffers,
+            file_operation_events,
+            buffer_events,
+            git_changes,
             focus_manager,
+            focus_changes,
+            auto_save: AutoSave::default(),
+            scrollbars: false,
+            word_count: false,
+            word_counter: WordCounter::new(),
+            density: Density::default(),
+            compact_panes: HashMap::new(),
             keybinding_router: KeybindingRouter::new(),
+            config_bindings: Vec::new(),
+            config_when_bindings: Vec::new(),
+            window_focused: true,
+            workspace_options: OptionOverrides::new(),
+            language_options: HashMap::new(),
+            config_changes: None,
+            config_watch: None,
+            theme_file: None,
+            pane_themes: HashMap::new(),
+            context_themes: HashMap::new(),
+            dim_unfocused_editors: false,
+            applied_themes: HashMap::new(),
+            services,
+            actions,
+            action_requests,
+            plugins: Vec::new(),
+            plugin_registry: WorkbenchRegistry::new(),
+            plugin_windows: HashMap::new(),
+            safe_mode: false,
+            command_palette: None,
+            command_line: None,
+            command_line_history: Vec::new(),
+            closed_editors: ClosedEditors::new(),
+            code_action_provider: None,
+            quick_fix: None,
+            context_menus: ContextMenus::builtin(),
+            context_menu: None,
+            split_drag: None,
+            input_box: None,
+            quick_open: None,
+            branch_picker: None,
+            stash_picker: None,
+            task_picker: None,
+            dialog: None,
+            tutorial: None,
+            editing_mode: None,
+            visual_span: (0, 0),
+            workspace: WorkspaceFolders::new(
+                env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
+            ),
+            definition_provider: None,
+            rename_provider: None,
+            document_link_provider: None,
+            document_links_requested: None,
+            status_bar: StatusBarWindow::new(),
+            status_bar_updates,
+            status_bar_requests,
+            quit_requested: Emitter::new(),
             running: true,
             width: 80,
             height: 24,
-        }
+            dirty: true,
+            last_notification: None,
+        };
+        let notifications = app.notifications();
+        app.add_status_bar_item(
+            StatusBarItem::new(NOTIFICATIONS_ITEM, StatusBarAlignment::Right, move || {
+                do_not_disturb_text(&notifications)
+            })
+            .with_command("workbench.notifications.toggleList"),
+        );
+        app.refresh_status_bar();
+        app
     }
 
     /// Create a new App with specified initial size.
@@ -90,264 +1527,10998 @@ impl App {
         let mut app = Self::new();
         app.width = width;
         app.height = height;
+        app.refresh_status_bar();
         app
     }
 
-    /// Check if the app is still running.
-    pub fn is_running(&self) -> bool {
-        self.running
-    }
-
-    /// Get the current focused pane.
+    /// Create an App showing the windows, files and layout saved in
+    /// `session`.
     ///
-    /// Returns the FocusedPane enum for backward compatibility.
-    pub fn focused(&self) -> FocusedPane {
-        match self.focus_manager.focused() {
-            Some(id) if id == self.editor_id => FocusedPane::Editor,
-            Some(id) if id == self.terminal_id => FocusedPane::Terminal,
-            _ => FocusedPane::Editor, // Default to editor if unknown
+    /// Files that no longer exist, windows of unknown kinds and windows that
+    /// cannot open any more (such as the git panel outside a repository)
+    /// are left out; the layout closes up around them. The terminal size
+    /// is used until the first resize event.
+    pub fn from_session(session: &Session) -> Self {
+        let mut app = Self::new();
+        app.workspace = WorkspaceFolders::new(session.root.clone());
+        for folder in &session.folders {
+            app.workspace.add(folder.clone());
+        }
+        if let Some((width, height)) = session.size {
+            app.width = width;
+            app.height = height;
         }
-    }
 
-    /// Get the focused window ID.
-    pub fn focused_id(&self) -> Option<WindowId> {
-        self.focus_manager.focused()
-    }
+        let mut ids = Vec::with_capacity(session.windows.len());
+        // The first saved editor and terminal reuse the App's own
+        let mut main_editor_restored = false;
+        let mut terminal_restored = false;
+        for window in &session.windows {
+            let id = match window.kind.as_str() {
+                "editor" => {
+                    let id = if main_editor_restored {
+                        app.open_window(Box::new(EditorWindow::default()))
+                    } else {
+                        main_editor_restored = true;
+                        app.editor_id
+                    };
+                    app.restore_tabs(id, window);
+                    Some(id)
+                }
+                "terminal" if !terminal_restored => {
+                    terminal_restored = true;
+                    Some(app.terminal_id)
+                }
+                kind => app.restore_panel(kind),
+            };
+            ids.push(id);
+        }
+        if !terminal_restored {
+            app.close_window(app.terminal_id);
+        }
+        app.refresh_line_changes();
 
-    /// Get the editor window ID.
-    pub fn editor_id(&self) -> WindowId {
-        self.editor_id
+        if let Some(layout) = session
+            .layout
+            .as_ref()
+            .and_then(|layout| restore_layout(layout, &ids))
+        {
+            app.windows.set_layout(layout);
+        }
+        let focused = session.focused.and_then(|index| ids[index]);
+        app.focus_manager
+            .set_focus(focused.unwrap_or(app.editor_id));
+        // Restoring is not a focus change the user made
+        app.focus_changes.try_iter().for_each(drop);
+        app.refresh_status_bar();
+        app
     }
 
-    /// Get the terminal window ID.
-    pub fn terminal_id(&self) -> WindowId {
-        self.terminal_id
+    /// Open the panel saved as `kind`, returning its ID, unless it is
+    /// already open or cannot open.
+    fn restore_panel(&mut self, kind: &str) -> Option<WindowId> {
+        match kind {
+            "explorer" if self.explorer_id.is_none() => {
+                self.toggle_explorer();
+                self.explorer_id
+            }
+            "search" if self.search_id.is_none() => {
+                self.toggle_search();
+                self.search_id
+            }
+            "findInFiles" if self.search_results_id.is_none() => {
+                self.toggle_search_results();
+                self.search_results_id
+            }
+            "todos" if self.todos_id.is_none() => {
+                self.toggle_todos();
+                self.todos_id
+            }
+            "regexTester" if self.regex_tester_id.is_none() => {
+                self.toggle_regex_tester();
+                self.regex_tester_id
+            }
+            "undoTree" if self.undo_tree_id.is_none() => {
+                self.toggle_undo_tree();
+                self.undo_tree_id
+            }
+            "gitPanel" if self.git_panel_id.is_none() => {
+                self.toggle_git_panel();
+                self.git_panel_id
+            }
+            "gitLog" if self.git_log_id.is_none() => {
+                self.toggle_git_log();
+                self.git_log_id
+            }
+            "output" if self.output_id.is_none() => {
+                self.toggle_output();
+                self.output_id
+            }
+            "problems" if self.problems_id.is_none() => {
+                self.toggle_problems();
+                self.problems_id
+            }
+            "diagnostics" if self.diagnostics_id.is_none() => {
+                self.toggle_diagnostics();
+                self.diagnostics_id
+            }
+            "logs" if self.logs_id.is_none() => {
+                self.toggle_logs();
+                self.logs_id
+            }
+            "notifications" if self.notifications_id.is_none() => {
+                self.toggle_notifications();
+                self.notifications_id
+            }
+            "terminalCapabilities" if self.terminal_report_id.is_none() => {
+                self.toggle_terminal_report();
+                self.terminal_report_id
+            }
+            "keybindings" if self.keybindings_id.is_none() => {
+                self.toggle_keybindings();
+                self.keybindings_id
+            }
+            "snippets" if self.snippets_id.is_none() => {
+                self.toggle_snippets();
+                self.snippets_id
+            }
+            "mirror" if self.mirror_id.is_none() => {
+                self.toggle_mirror();
+                self.mirror_id
+            }
+            "markdownPreview" if self.markdown_preview_id.is_none() => {
+                self.toggle_markdown_preview();
+                self.markdown_preview_id
+            }
+            _ => None,
+        }
     }
 
-    /// Get a reference to the focus manager.
-    pub fn focus_manager(&self) -> &FocusManager {
-        &self.focus_manager
+    /// Open the files of a saved editor in the editor `id`, restoring each
+    /// tab's cursor, scroll position and pin and the active tab.
+    fn restore_tabs(&mut self, id: WindowId, window: &SessionWindow) {
+        let mut opened = Vec::new();
+        let mut active = 0;
+        for (index, tab) in window.tabs.iter().enumerate() {
+            match self.buffers.open(&tab.path) {
+                Ok(buffer) => {
+                    if index <= window.active {
+                        active = opened.len();
+                    }
+                    opened.push((buffer, tab));
+                }
+                Err(error) => {
+                    tracing::warn!(path = %tab.path.display(), "cannot restore tab: {error}");
+                }
+            }
+        }
+        if let Some(editor) = self.windows.window_mut::<EditorWindow>(id) {
+            for (index, (buffer, tab)) in opened.into_iter().enumerate() {
+                if index == 0 {
+                    editor.set_buffer(buffer);
+                } else {
+                    editor.open_buffer(buffer);
+                }
+                if tab.pinned {
+                    editor.toggle_pinned_tab();
+                }
+                editor.set_selection(tab.cursor..tab.cursor);
+                editor.set_scroll_offset(usize::from(tab.scroll));
+            }
+            editor.activate_tab(active);
+        }
+        self.buffers.close_unused();
     }
 
-    /// Get a reference to the keybinding router.
-    pub fn keybinding_router(&self) -> &KeybindingRouter {
-        &self.keybinding_router
+    /// Capture the open windows, their files and the layout as a session.
+    ///
+    /// Windows that cannot be restored, such as the diff view, are left
+    /// out, and so are editor tabs without a file.
+    pub fn session(&self) -> Session {
+        let mut session = Session {
+            root: self.workspace.primary().to_path_buf(),
+            folders: self.workspace.extra_roots().to_vec(),
+            size: Some((self.width, self.height)),
+            ..Session::default()
+        };
+        let mut indices = Vec::new();
+        for id in self.windows.ids() {
+            let Some(window) = self.session_window(id) else {
+                continue;
+            };
+            if self.focused_id() == Some(id) {
+                session.focused = Some(session.windows.len());
+            }
+            indices.push((id, session.windows.len()));
+            session.windows.push(window);
+        }
+        session.layout = self
+            .windows
+            .layout()
+            .and_then(|layout| session_layout(layout, &indices));
+        session
     }
 
-    /// Get a mutable reference to the keybinding router.
-    pub fn keybinding_router_mut(&mut self) -> &mut KeybindingRouter {
-        &mut self.keybinding_router
+    /// Describe the window `id` for a session, if it can be restored.
+    fn session_window(&self, id: WindowId) -> Option<SessionWindow> {
+        if let Some(editor) = self.windows.window::<EditorWindow>(id) {
+            let group = editor.group();
+            let mut window = SessionWindow::new("editor");
+            for (index, tab) in group.tabs().iter().enumerate() {
+                let Some(path) = tab.buffer.borrow().path().map(Path::to_path_buf) else {
+                    continue;
+                };
+                if index == group.active_index() {
+                    window.active = window.tabs.len();
+                }
+                window.tabs.push(SessionTab {
+                    path,
+                    cursor: tab.selection.start,
+                    scroll: u16::try_from(tab.viewport.top_line).unwrap_or(u16::MAX),
+                    pinned: tab.pinned,
+                });
+            }
+            return Some(window);
+        }
+        let kind = self
+            .panel_kinds()
+            .into_iter()
+            .find(|(panel, _)| *panel == Some(id))
+            .map(|(_, kind)| kind)?;
+        Some(SessionWindow::new(kind))
     }
 
-    /// Get the current terminal dimensions.
-    pub fn size(&self) -> (u16, u16) {
-        (self.width, self.height)
+    /// The terminal and the panels, with the kind a session saves them as
+    /// and their ID if open.
+    fn panel_kinds(&self) -> [(Option<WindowId>, &'static str); 19] {
+        [
+            (Some(self.terminal_id), "terminal"),
+            (self.explorer_id, "explorer"),
+            (self.search_id, "search"),
+            (self.search_results_id, "findInFiles"),
+            (self.todos_id, "todos"),
+            (self.regex_tester_id, "regexTester"),
+            (self.undo_tree_id, "undoTree"),
+            (self.git_panel_id, "gitPanel"),
+            (self.git_log_id, "gitLog"),
+            (self.output_id, "output"),
+            (self.problems_id, "problems"),
+            (self.diagnostics_id, "diagnostics"),
+            (self.logs_id, "logs"),
+            (self.notifications_id, "notifications"),
+            (self.terminal_report_id, "terminalCapabilities"),
+            (self.keybindings_id, "keybindings"),
+            (self.snippets_id, "snippets"),
+            (self.mirror_id, "mirror"),
+            (self.markdown_preview_id, "markdownPreview"),
+        ]
     }
 
-    /// Handle an application event.
+    /// Arrange the windows as `preset` declares, on top of whatever was
+    /// restored: open the windows it lists, close the others, open its
+    /// files in the main editor and focus its window.
     ///
-    /// This is the main entry point for input handling. Events are processed
-    /// and may update application state.
-    pub fn handle_event(&mut self, event: AppEvent) {
-        match event {
-            AppEvent::Key(key) => self.handle_key(key),
-            AppEvent::Resize(w, h) => {
-                self.width = w;
-                self.height = h;
+    /// Windows already open are reused. Returns the problems applying it,
+    /// such as windows of unknown kinds or files that cannot be read; the
+    /// rest of the preset is still applied.
+    pub fn apply_layout_preset(&mut self, preset: &LayoutPreset) -> Vec<String> {
+        let mut problems = Vec::new();
+        let mut ids: Vec<Option<WindowId>> = Vec::with_capacity(preset.windows.len());
+        let mut main_editor_placed = false;
+        for kind in &preset.windows {
+            let id = match kind.as_str() {
+                "editor" if !main_editor_placed => {
+                    main_editor_placed = true;
+                    Some(self.editor_id)
+                }
+                "editor" => Some(self.open_window(Box::new(EditorWindow::default()))),
+                "terminal" if !self.windows.contains(self.terminal_id) => {
+                    self.terminal_id = self.open_window(Box::new(TerminalWindow::default()));
+                    Some(self.terminal_id)
+                }
+                kind => self
+                    .panel_kinds()
+                    .into_iter()
+                    .find(|&(panel, panel_kind)| {
+                        panel_kind == kind && panel.is_some() && !ids.contains(&panel)
+                    })
+                    .and_then(|(panel, _)| panel)
+                    .or_else(|| self.restore_panel(kind)),
+            };
+            if id.is_none() {
+                problems.push(format!("cannot open window `{kind}`"));
             }
-            AppEvent::Tick => {
-                // Currently unused; placeholder for future animations/polling
+            ids.push(id);
+        }
+        for id in self.windows.ids() {
+            if !ids.contains(&Some(id)) {
+                self.close_window(id);
             }
         }
-    }
 
-    /// Handle a key press using the keybinding router.
-    fn handle_key(&mut self, key: AppKey) {
-        if let Some(action) = self.keybinding_router.dispatch(key) {
-            self.execute_action(action);
+        self.focus_manager.set_focus(self.editor_id);
+        for file in &preset.files {
+            let path = self.workspace.primary().join(file);
+            if let Err(error) = self.open_file(&path) {
+                problems.push(format!("cannot open {}: {error}", file.display()));
+            }
         }
-        // Keys not bound to actions are ignored (could be forwarded to focused window)
-    }
 
-    /// Execute an action.
-    fn execute_action(&mut self, action: Action) {
-        match action {
-            Action::Quit => {
-                self.running = false;
-            }
-            Action::ToggleFocus => {
-                self.toggle_focus();
-            }
-            Action::FocusNext => {
-                self.toggle_focus(); // With only 2 windows, next == toggle
-            }
-            Action::FocusPrev => {
-                self.toggle_focus(); // With only 2 windows, prev == toggle
-            }
-            Action::None => {
-                // Do nothing
-            }
+        if let Some(layout) = restore_layout(&preset.layout, &ids) {
+            self.windows.set_layout(layout);
         }
+        let focused = preset
+            .focus
+            .and_then(|index| ids.get(index).copied().flatten());
+        self.focus_manager
+            .set_focus(focused.unwrap_or(self.editor_id));
+        // Like restoring a session, applying a preset is not a focus change
+        // the user made
+        self.focus_changes.try_iter().for_each(drop);
+        self.refresh_status_bar();
+        problems
     }
 
-    /// Toggle focus between editor and terminal.
-    fn toggle_focus(&mut self) {
-        let current = self.focus_manager.focused();
-        let next = match current {
-            Some(id) if id == self.editor_id => self.terminal_id,
-            _ => self.editor_id,
-        };
-        self.focus_manager.set_focus(next);
+    /// Save the state of the App with the registered session service, if
+    /// any.
+    pub fn save_session(&self) -> Result<(), SessionError> {
+        match self.services.resolve::<SessionService>() {
+            Some(sessions) => sessions.save(&self.session()),
+            None => Ok(()),
+        }
     }
 
-    /// Render the application to a frame.
+    /// Show or hide the banner saying the App was started in safe mode,
+    /// without the user's settings and saved session.
     ///
-    /// Uses the stored dimensions to create a layout and renders both windows.
-    /// The focused window gets a visual indicator.
-    pub fn render(&mut self, frame: &mut Frame, area: Rect) {
-        let chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
-            .split(area);
+    /// Loading those is up to the binary; the App only shows that they were
+    /// skipped.
+    pub fn set_safe_mode(&mut self, safe_mode: bool) {
+        self.safe_mode = safe_mode;
+    }
 
-        // Render with focus indicators
-        let editor_focused = self.focus_manager.is_focused(self.editor_id);
-        let terminal_focused = self.focus_manager.is_focused(self.terminal_id);
+    /// Check whether the App was started in safe mode.
+    pub fn safe_mode(&self) -> bool {
+        self.safe_mode
+    }
+
+    /// Use `service` to save the session when the App quits.
+    pub fn set_session_service(&mut self, service: SessionService) {
+        self.services.register(service);
+    }
 
-        self.editor
-            .render_with_focus(frame, chunks[0], editor_focused);
-        self.terminal
-            .render_with_focus(frame, chunks[1], terminal_focused);
+    /// Check if the app is still running.
+    pub fn is_running(&self) -> bool {
+        self.running
     }
 
-    /// Get the layout rects for the current size.
+    /// Check whether the screen is out of date: the state changed since
+    /// the last [`App::render`], a window reports new content, or
+    /// background work shown on screen (a task, a workspace search or a
+    /// progress report) is running.
     ///
-    /// Useful for testing to verify layout calculations.
-    pub fn layout_rects(&self, area: Rect) -> (Rect, Rect) {
-        let chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
-            .split(area);
-        (chunks[0], chunks[1])
+    /// Event loops skip drawing while this is `false`.
+    pub fn needs_redraw(&self) -> bool {
+        self.dirty
+            || self.task.is_some()
+            || self.workspace_search.is_some()
+            || self.todo_scan.is_some()
+            || !self.progress().active().is_empty()
+            || self.windows.ids().into_iter().any(|id| {
+                self.windows
+                    .get(id)
+                    .is_some_and(|window| window.needs_redraw())
+            })
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Mark the screen out of date, e.g. after changing the App outside
+    /// [`App::handle_event`].
+    pub fn invalidate(&mut self) {
+        self.dirty = true;
+    }
 
-    #[test]
-    fn test_app_new() {
-        let app = App::new();
-        assert!(app.is_running());
-        assert_eq!(app.focused(), FocusedPane::Editor);
-        assert_eq!(app.size(), (80, 24));
+    /// Get the modal dialog, if open.
+    pub fn dialog(&self) -> Option<&Dialog> {
+        self.dialog.as_ref().map(|open| &open.dialog)
     }
 
-    #[test]
-    fn test_app_with_size() {
-        let app = App::with_size(120, 40);
-        assert_eq!(app.size(), (120, 40));
+    /// Open `dialog` over the workbench; its answer is handled according
+    /// to `purpose`.
+    fn open_dialog(&mut self, mut dialog: Dialog, purpose: DialogPurpose) {
+        dialog.set_theme(&self.theme());
+        self.dialog = Some(OpenDialog { dialog, purpose });
     }
 
-    #[test]
-    fn test_quit_on_q() {
+    /// Act on the answer to a dialog asked for `purpose`.
+    fn finish_dialog(&mut self, purpose: DialogPurpose, result: DialogResult) {
+        match (purpose, result) {
+            (DialogPurpose::ForceQuit, DialogResult::Yes) => self.request_save_before_quit(),
+            (DialogPurpose::SaveBeforeQuit, DialogResult::Yes) if self.save_modified_files() => {
+                self.quit();
+            }
+            (DialogPurpose::SaveBeforeQuit, DialogResult::No) => self.quit(),
+            _ => {}
+        }
+    }
+
+    /// Get the paths of open files with unsaved changes, sorted.
+    pub fn modified_files(&self) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = self
+            .buffers
+            .paths()
+            .into_iter()
+            .filter(|path| {
+                self.buffers
+                    .get(path)
+                    .is_some_and(|buffer| buffer.borrow().is_dirty())
+            })
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    /// Save every modified file, reporting the first failure in the
+    /// notification center. Returns whether all of them were saved.
+    fn save_modified_files(&mut self) -> bool {
+        for path in self.modified_files() {
+            if let Err(error) = self.buffers.save(&path) {
+                tracing::error!(path = %path.display(), "cannot save: {error}");
+                self.notifications().notify(
+                    "Editor",
+                    NotificationSeverity::Error,
+                    format!("Cannot save {}: {error}", path.display()),
+                );
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Describe the child processes and background tasks still running,
+    /// processes first, each oldest first.
+    pub fn running_work(&self) -> Vec<String> {
+        let processes = self
+            .processes()
+            .running_processes()
+            .into_iter()
+            .map(|process| format!("{} ({})", process.command_line, process.id));
+        let tasks = self
+            .tasks()
+            .pending_tasks()
+            .into_iter()
+            .map(|task| format!("{} ({})", task.name, task.id));
+        processes.chain(tasks).collect()
+    }
+
+    /// Get the emitter asked before quitting.
+    ///
+    /// Its listeners run when the user asks to quit, before any dialog; a
+    /// listener calling [`QuitRequested::veto`] cancels the quit, and the
+    /// reasons are shown as a warning notification.
+    pub fn on_quit_requested(&self) -> &Emitter<QuitRequested> {
+        &self.quit_requested
+    }
+
+    /// Quit, unless a listener of [`App::on_quit_requested`] vetoes it,
+    /// asking first whether to quit while processes or tasks are running
+    /// and then whether to save modified files.
+    pub fn request_quit(&mut self) {
+        let request = QuitRequested::default();
+        self.quit_requested.emit(request.clone());
+        let vetoes = request.vetoes();
+        if !vetoes.is_empty() {
+            self.notifications().notify(
+                "Workbench",
+                NotificationSeverity::Warning,
+                format!("Quit cancelled: {}", vetoes.join("; ")),
+            );
+            return;
+        }
+        let running = self.running_work();
+        if running.is_empty() {
+            self.request_save_before_quit();
+        } else {
+            let title = format!("Quit? {} operation(s) still running", running.len());
+            let dialog = ConfirmWindow::new(title, running)
+                .with_warning("Quitting stops them.")
+                .with_hint("Enter: force quit  Esc: cancel");
+            self.open_dialog(Dialog::Confirm(Box::new(dialog)), DialogPurpose::ForceQuit);
+        }
+    }
+
+    /// Quit, asking first whether to save modified files.
+    fn request_save_before_quit(&mut self) {
+        let modified = self.modified_files();
+        if modified.is_empty() {
+            self.quit();
+            return;
+        }
+        let items = modified
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect();
+        let dialog = ConfirmWindow::new("Save changes before quitting?", items)
+            .with_warning("Unsaved changes are lost if you don't save.")
+            .with_hint("y: save  n: discard  Esc: cancel");
+        self.open_dialog(
+            Dialog::Confirm(Box::new(dialog)),
+            DialogPurpose::SaveBeforeQuit,
+        );
+    }
+
+    /// Get the mode of modal editing, or `None` while it is off.
+    pub fn editing_mode(&self) -> Option<EditingMode> {
+        self.editing_mode
+    }
+
+    /// Turn modal editing on, in Normal mode, or off.
+    pub fn toggle_modal_editing(&mut self) {
+        if self.editing_mode.is_some() {
+            self.set_editing_mode(EditingMode::Normal);
+            self.editing_mode = None;
+        } else {
+            self.editing_mode = Some(EditingMode::Normal);
+        }
+    }
+
+    /// Switch modal editing to `mode`; does nothing while it is off.
+    ///
+    /// Visual mode starts selecting at the cursor; leaving it collapses the
+    /// selection to where its cursor is.
+    fn set_editing_mode(&mut self, mode: EditingMode) {
+        let Some(current) = self.editing_mode else {
+            return;
+        };
+        let id = self.target_editor();
+        if let Some(editor) = self.windows.window_mut::<EditorWindow>(id) {
+            if current == EditingMode::Visual && mode != EditingMode::Visual {
+                let (_, head) = self.visual_span;
+                editor.set_selection(head..head);
+            } else if current != EditingMode::Visual && mode == EditingMode::Visual {
+                let head = editor.selection().start;
+                self.visual_span = (head, head);
+                editor.select_inclusive(head, head);
+            } else if mode == EditingMode::Insert {
+                let start = editor.selection().start;
+                editor.set_selection(start..start);
+            }
+        }
+        self.editing_mode = Some(mode);
+    }
+
+    /// The modal editing mode keys are dispatched in: `None` unless modal
+    /// editing is on and an editor has focus.
+    fn active_editing_mode(&self) -> Option<EditingMode> {
+        self.editing_mode
+            .filter(|_| self.focused_context() == Some(WindowContext::Editor))
+    }
+
+    /// Move the target editor's cursor by `lines` and `columns`, extending
+    /// the selection in Visual mode.
+    fn move_editor_cursor(&mut self, lines: isize, columns: isize) {
+        let id = self.target_editor();
+        let Some(editor) = self.windows.window_mut::<EditorWindow>(id) else {
+            return;
+        };
+        if self.editing_mode == Some(EditingMode::Visual) {
+            let (anchor, head) = self.visual_span;
+            let head = editor.moved_offset(head, lines, columns);
+            self.visual_span = (anchor, head);
+            editor.select_inclusive(anchor, head);
+        } else {
+            editor.move_cursor(lines, columns);
+        }
+    }
+
+    /// Move the head of the target editor's selection by `lines` and
+    /// `columns`, keeping its anchor; in Visual mode this is a cursor move,
+    /// which already extends the selection.
+    fn extend_editor_selection(&mut self, lines: isize, columns: isize) {
+        if self.editing_mode == Some(EditingMode::Visual) {
+            self.move_editor_cursor(lines, columns);
+            return;
+        }
+        let id = self.target_editor();
+        if let Some(editor) = self.windows.window_mut::<EditorWindow>(id) {
+            editor.extend_selection(lines, columns);
+        }
+    }
+
+    /// Type `key` into the target editor in Insert mode: characters are
+    /// inserted, `Enter` breaks the line and `Backspace` deletes backward.
+    fn type_key(&mut self, key: AppKey) {
+        let id = self.target_editor();
+        let Some(editor) = self.windows.window_mut::<EditorWindow>(id) else {
+            return;
+        };
+        // A character that ends a word expands the abbreviation before it
+        let ends_word = match key {
+            AppKey::Char(c) => !abbreviation::is_word_char(c),
+            AppKey::Enter => true,
+            _ => false,
+        };
+        if ends_word
+            && editor
+                .expand_abbreviation(&self.abbreviations)
+                .is_some_and(|expansion| expansion.cursor.is_some())
+        {
+            return;
+        }
+        match key {
+            AppKey::Char(c) => editor.insert_text(c.encode_utf8(&mut [0; 4])),
+            AppKey::Q => editor.insert_text("q"),
+            AppKey::Enter => editor.insert_text("\n"),
+            AppKey::Backspace => editor.delete_backward(),
+            _ => {}
+        }
+    }
+
+    /// Save the session and stop running, leaving running processes and
+    /// tasks to be stopped when their services are dropped.
+    pub fn quit(&mut self) {
+        self.dialog = None;
+        if let Err(error) = self.save_session() {
+            tracing::warn!("cannot save the session: {error}");
+        }
+        self.running = false;
+    }
+
+    /// Get the current focused pane.
+    ///
+    /// Returns the FocusedPane enum for backward compatibility.
+    pub fn focused(&self) -> FocusedPane {
+        match self.focus_manager.focused() {
+            Some(id) if id == self.editor_id => FocusedPane::Editor,
+            Some(id) if id == self.terminal_id => FocusedPane::Terminal,
+            _ => FocusedPane::Editor, // Default to editor if unknown
+        }
+    }
+
+    /// Get a reference to the editor window.
+    pub fn editor(&self) -> &EditorWindow {
+        self.windows
+            .window(self.editor_id)
+            .expect("the editor window is never closed")
+    }
+
+    /// Get a reference to the terminal window, unless it was closed.
+    pub fn terminal(&self) -> Option<&TerminalWindow> {
+        self.windows.window(self.terminal_id)
+    }
+
+    /// Get a mutable reference to the editor window.
+    pub fn editor_mut(&mut self) -> &mut EditorWindow {
+        self.windows
+            .window_mut(self.editor_id)
+            .expect("the editor window is never closed")
+    }
+
+    /// Get the window manager.
+    pub fn windows(&self) -> &WindowManager {
+        &self.windows
+    }
+
+    /// Open a window as a new column on the right and focus it.
+    ///
+    /// The window is given the current theme and drawn as the density
+    /// sets.
+    pub fn open_window(&mut self, mut window: Box<dyn Window>) -> WindowId {
+        window.set_theme(&self.theme());
+        window.set_compact(self.density.is_compact());
+        let id = self.windows.open(window);
+        self.focus_manager.set_focus(id);
+        id
+    }
+
+    /// Close a window, moving focus to the next window if it had focus.
+    ///
+    /// The main editor window cannot be closed. Returns whether a window
+    /// was closed.
+    pub fn close_window(&mut self, id: WindowId) -> bool {
+        if id == self.editor_id || !self.windows.contains(id) {
+            return false;
+        }
+        if self.focus_manager.is_focused(id) {
+            self.windows.focus_next(&mut self.focus_manager);
+        }
+        let closed = self.windows.close(id).is_some();
+        self.compact_panes.remove(&id);
+        if self.explorer_id == Some(id) {
+            self.explorer_id = None;
+        }
+        if self.search_id == Some(id) {
+            self.search_id = None;
+        }
+        if self.search_results_id == Some(id) {
+            self.search_results_id = None;
+            if let Some(search) = self.workspace_search.take() {
+                search.cancel();
+            }
+            self.search_progress = None;
+        }
+        if self.todos_id == Some(id) {
+            self.todos_id = None;
+            if let Some(scan) = self.todo_scan.take() {
+                scan.cancel();
+            }
+            self.todo_progress = None;
+            self.rewatch_todos();
+        }
+        if self.regex_tester_id == Some(id) {
+            self.regex_tester_id = None;
+        }
+        if self.terminal_report_id == Some(id) {
+            self.terminal_report_id = None;
+        }
+        if self.keybindings_id == Some(id) {
+            self.keybindings_id = None;
+        }
+        if self.snippets_id == Some(id) {
+            self.snippets_id = None;
+        }
+        if self.mirror_id == Some(id) {
+            self.mirror_id = None;
+        }
+        if self.markdown_preview_id == Some(id) {
+            self.markdown_preview_id = None;
+        }
+        if self.undo_tree_id == Some(id) {
+            self.undo_tree_id = None;
+        }
+        self.plugin_windows.retain(|_, window| *window != id);
+        if self.diagnostics_id == Some(id) {
+            self.diagnostics_id = None;
+        }
+        if self.git_panel_id == Some(id) {
+            self.git_panel_id = None;
+        }
+        if self.diff_id == Some(id) {
+            self.diff_id = None;
+        }
+        if self.git_log_id == Some(id) {
+            self.git_log_id = None;
+        }
+        self.buffers.close_unused();
+        closed
+    }
+
+    /// Get the active color theme.
+    pub fn theme(&self) -> Arc<Theme> {
+        self.services
+            .resolve::<Theme>()
+            .expect("the App registers a theme")
+    }
+
+    /// Switch the color theme of every window.
+    ///
+    /// The theme replaces the one registered in the [`ServiceContainer`].
+    /// Panes with a theme of their own (see [`App::pane_theme`]) get it
+    /// back on the next render.
+    pub fn set_theme(&mut self, theme: Theme) {
+        for id in self.windows.ids() {
+            if let Some(window) = self.windows.get_mut(id) {
+                window.set_theme(&theme);
+            }
+        }
+        self.status_bar.set_theme(&theme);
+        self.services.register(theme);
+        self.applied_themes.clear();
+    }
+
+    /// Draw the pane `id` with `theme` instead of the theme of its context
+    /// or the active one, or go back to those with `None`.
+    pub fn set_pane_theme(&mut self, id: WindowId, theme: Option<Theme>) {
+        match theme {
+            Some(theme) => self.pane_themes.insert(id, theme),
+            None => self.pane_themes.remove(&id),
+        };
+    }
+
+    /// Draw every pane of `context` with `theme` instead of the active
+    /// theme, or go back to it with `None`.
+    ///
+    /// The `[workbench.pane_themes]` settings set these, e.g.
+    /// `terminal = "light"`.
+    pub fn set_context_theme(&mut self, context: WindowContext, theme: Option<Theme>) {
+        match theme {
+            Some(theme) => self.context_themes.insert(context, theme),
+            None => self.context_themes.remove(&context),
+        };
+    }
+
+    /// Draw editors without focus with a dimmed variant of their theme
+    /// ([`Theme::dimmed`]), as the `workbench.dim_unfocused_editors`
+    /// setting does.
+    pub fn set_dim_unfocused_editors(&mut self, dim: bool) {
+        self.dim_unfocused_editors = dim;
+    }
+
+    /// Get the theme the pane `id` is drawn with: its own theme, else the
+    /// theme of its context, else the active theme, dimmed if it is an
+    /// editor without focus and unfocused editors are dimmed.
+    pub fn pane_theme(&self, id: WindowId) -> Theme {
+        let context = self.windows.get(id).and_then(|window| window.context());
+        let theme = self
+            .pane_themes
+            .get(&id)
+            .or_else(|| context.and_then(|context| self.context_themes.get(&context)))
+            .cloned()
+            .unwrap_or_else(|| (*self.theme()).clone());
+        let unfocused = self.focus_manager.focused() != Some(id);
+        if self.dim_unfocused_editors && unfocused && context == Some(WindowContext::Editor) {
+            theme.dimmed()
+        } else {
+            theme
+        }
+    }
+
+    /// Give every window the theme it is drawn with, if it changed since
+    /// it was last given one, e.g. because focus moved to another editor.
+    fn refresh_pane_themes(&mut self) {
+        let ids = self.windows.ids();
+        self.applied_themes.retain(|id, _| ids.contains(id));
+        self.pane_themes.retain(|id, _| ids.contains(id));
+        for id in ids {
+            let theme = self.pane_theme(id);
+            if self.applied_themes.get(&id) == Some(&theme) {
+                continue;
+            }
+            if let Some(window) = self.windows.get_mut(id) {
+                window.set_theme(&theme);
+            }
+            self.applied_themes.insert(id, theme);
+        }
+    }
+
+    /// Get the open files.
+    pub fn buffers(&self) -> &BufferManager {
+        &self.buffers
+    }
+
+    /// Open the file at `path` in a new tab of the focused editor, or of
+    /// the main editor if another kind of window has focus.
+    ///
+    /// A file that is already open is shown from its existing buffer, so
+    /// every editor showing it shares edits and dirty state; if the editor
+    /// already has a tab for it, that tab is activated. A buffer no editor
+    /// shows any more is closed.
+    pub fn open_file(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        let buffer = self.buffers.open(path)?;
+        self.show_buffer(buffer);
+        Ok(())
+    }
+
+    /// Create an empty untitled buffer and show it like
+    /// [`App::open_file`].
+    ///
+    /// The buffer has no path until it is saved, which prompts for one.
+    pub fn new_untitled(&mut self) -> BufferUri {
+        let (uri, buffer) = self.buffers.new_untitled();
+        self.show_buffer(buffer);
+        uri
+    }
+
+    /// Show `buffer` in a tab of the target editor and close buffers no
+    /// editor shows any more.
+    ///
+    /// The editor's options are resolved again for the file, so the
+    /// settings of the workspace folder holding it apply.
+    fn show_buffer(&mut self, buffer: SharedBuffer) {
+        let id = self.target_editor();
+        let path = buffer.borrow().path().map(Path::to_path_buf);
+        if let Some(editor) = self.windows.window_mut::<EditorWindow>(id) {
+            editor.open_buffer(buffer);
+            editor.set_diagnostics(&self.problems);
+        }
+        let problems = self.resolve_editor_options(id);
+        if !problems.is_empty() {
+            self.report_config_problems(&problems);
+        }
+        self.buffers.close_unused();
+        if let Some(path) = path {
+            self.closed_editors.forget(&path);
+            self.refresh_file_line_changes(&path);
+        }
+    }
+
+    /// Remember a closed tab's file and cursor for
+    /// [`App::reopen_closed_editor`]. Untitled tabs are not remembered.
+    fn remember_closed_tab(&mut self, tab: &EditorTab) {
+        let Some(path) = tab.buffer.borrow().path().map(Path::to_path_buf) else {
+            return;
+        };
+        let (line, column) = tab.cursor_position();
+        self.closed_editors
+            .push(ClosedEditor { path, line, column });
+    }
+
+    /// Move the active tab of the target editor to the next editor in
+    /// layout order, keeping its selection, scroll position and pin, and
+    /// focus that editor.
+    ///
+    /// Without another editor, the target editor is split to the right
+    /// first. The target editor keeps its tab if it is the only one,
+    /// unless it is a split editor, which is closed instead. Returns the
+    /// editor the tab moved to.
+    pub fn move_tab_to_next_group(&mut self) -> Option<WindowId> {
+        let source = self.target_editor();
+        let editors: Vec<WindowId> = self
+            .windows
+            .ids()
+            .into_iter()
+            .filter(|&id| self.windows.window::<EditorWindow>(id).is_some())
+            .collect();
+        let position = editors.iter().position(|&id| id == source)?;
+        let target = editors[(position + 1) % editors.len()];
+        if target == source {
+            let target = self.split_editor(SplitDirection::Horizontal)?;
+            if let Some(editor) = self.windows.window_mut::<EditorWindow>(source) {
+                editor.close_tab();
+            }
+            return Some(target);
+        }
+        let editor = self.windows.window_mut::<EditorWindow>(source)?;
+        let tab = match editor.close_tab() {
+            Some(tab) => tab,
+            None => {
+                let tab = editor.group().active().clone();
+                if source != self.editor_id {
+                    self.close_window(source);
+                }
+                tab
+            }
+        };
+        if let Some(editor) = self.windows.window_mut::<EditorWindow>(target) {
+            editor.insert_tab(tab);
+            editor.set_diagnostics(&self.problems);
+        }
+        self.focus_manager.set_focus(target);
+        let problems = self.resolve_editor_options(target);
+        if !problems.is_empty() {
+            self.report_config_problems(&problems);
+        }
+        Some(target)
+    }
+
+    /// Open the most recently closed editor tab again, with the cursor
+    /// where it was.
+    ///
+    /// Files that can no longer be opened, e.g. because they were deleted,
+    /// are skipped. Returns whether a tab was reopened.
+    pub fn reopen_closed_editor(&mut self) -> bool {
+        while let Some(closed) = self.closed_editors.pop() {
+            match self.open_file(&closed.path) {
+                Ok(()) => {
+                    let id = self.target_editor();
+                    if let Some(editor) = self.windows.window_mut::<EditorWindow>(id) {
+                        editor.set_cursor_position(closed.line, closed.column);
+                    }
+                    return true;
+                }
+                Err(error) => {
+                    tracing::warn!(path = %closed.path.display(), "cannot reopen: {error}");
+                }
+            }
+        }
+        false
+    }
+
+    /// Get the recently closed editor tabs, oldest first.
+    pub fn closed_editors(&self) -> &ClosedEditors {
+        &self.closed_editors
+    }
+
+    /// Show the target editor's document in a new editor next to it,
+    /// splitting its area in `direction`, and focus the new editor.
+    ///
+    /// Both editors show the same buffer, so an edit in one is seen in the
+    /// other, but each keeps its own cursor and scroll position. Returns
+    /// the new editor's ID. The new editor is drawn compact if the split
+    /// one is.
+    pub fn split_editor(&mut self, direction: SplitDirection) -> Option<WindowId> {
+        let target = self.target_editor();
+        let view = self.windows.window::<EditorWindow>(target)?.split_view();
+        let id = self.windows.split(target, direction, Box::new(view))?;
+        if let Some(&compact) = self.compact_panes.get(&target) {
+            self.compact_panes.insert(id, compact);
+        }
+        self.focus_manager.set_focus(id);
+        Some(id)
+    }
+
+    /// The focused editor, or the main editor if another kind of window
+    /// has focus.
+    fn target_editor(&self) -> WindowId {
+        self.focused_id()
+            .filter(|&id| self.windows.window::<EditorWindow>(id).is_some())
+            .unwrap_or(self.editor_id)
+    }
+
+    /// Get the workspace root directory, the primary root of a workspace
+    /// with several.
+    ///
+    /// Defaults to the current directory.
+    pub fn workspace_root(&self) -> &Path {
+        self.workspace.primary()
+    }
+
+    /// Get the root folders of the workspace.
+    pub fn workspace_folders(&self) -> &WorkspaceFolders {
+        &self.workspace
+    }
+
+    /// The title for the host terminal's window and tab: the active tab of
+    /// the focused editor, marked `●` while it has unsaved changes, and the
+    /// workspace folder, e.g. `● main.rs - paradiddle - Paradiddle`.
+    pub fn window_title(&self) -> String {
+        let workspace = workspace::folder_name(self.workspace.primary());
+        let Some(editor) = self.windows.window::<EditorWindow>(self.target_editor()) else {
+            return format!("{workspace} - Paradiddle");
+        };
+        let dirty = if editor.is_dirty() { "● " } else { "" };
+        let file = editor.group().active().title();
+        format!("{dirty}{file} - {workspace} - Paradiddle")
+    }
+
+    /// Set the workspace root directory, replacing the primary root of a
+    /// workspace with several.
+    ///
+    /// An open explorer keeps showing its root until it is reopened.
+    pub fn set_workspace_root(&mut self, root: impl Into<PathBuf>) {
+        self.workspace.set_primary(root);
+    }
+
+    /// Add `root` as a further root folder of the workspace.
+    ///
+    /// An open explorer shows it at once and search and quick open span it
+    /// from their next search. The `[editor]` settings of its
+    /// `.paradiddle.toml` apply to its files, laid over the other settings.
+    /// Returns `false` if it already is a root.
+    pub fn add_workspace_folder(&mut self, root: impl Into<PathBuf>) -> bool {
+        let root = root.into();
+        if !self.workspace.add(root.clone()) {
+            return false;
+        }
+        let canonical = fs::canonicalize(&root).unwrap_or(root);
+        self.refresh_workspace_panels();
+        if let Some(explorer) = self.explorer_mut() {
+            explorer.add_root(canonical.clone());
+        }
+        if let Some(service) = self.services.resolve::<ConfigService>() {
+            match service.add_folder(&canonical) {
+                Ok(()) => self.rewatch_config(),
+                Err(error) => self.report_config_problems(&[error.to_string()]),
+            }
+        }
+        true
+    }
+
+    /// Remove the root folder `root` from the workspace, along with its
+    /// settings.
+    ///
+    /// Returns `false` if it is not a root or is the primary root, which
+    /// cannot be removed.
+    pub fn remove_workspace_folder(&mut self, root: &Path) -> bool {
+        if !self.workspace.remove(root) {
+            return false;
+        }
+        let canonical = fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+        self.refresh_workspace_panels();
+        if let Some(explorer) = self.explorer_mut() {
+            explorer.remove_root(&canonical);
+        }
+        if let Some(service) = self.services.resolve::<ConfigService>() {
+            service.remove_folder(&canonical);
+            self.rewatch_config();
+        }
+        true
+    }
+
+    /// Point open search panels at the current workspace roots.
+    fn refresh_workspace_panels(&mut self) {
+        let canonical = self.workspace.canonical();
+        if let Some(panel) = self.search_mut() {
+            panel.set_folders(canonical);
+        }
+        let folders = self.workspace.clone();
+        if let Some(panel) = self.search_results_mut() {
+            panel.set_folders(folders);
+        }
+    }
+
+    /// Get the quick-open picker, if open.
+    pub fn quick_open(&self) -> Option<&QuickPickWindow> {
+        self.quick_open.as_ref().map(|menu| &menu.picker)
+    }
+
+    /// Get the branch picker, if open.
+    pub fn branch_picker(&self) -> Option<&QuickPickWindow> {
+        self.branch_picker.as_ref().map(|menu| &menu.picker)
+    }
+
+    /// Get the stash picker, if open.
+    pub fn stash_picker(&self) -> Option<&QuickPickWindow> {
+        self.stash_picker.as_ref().map(|menu| &menu.picker)
+    }
+
+    /// Get the task picker, if open.
+    pub fn task_picker(&self) -> Option<&QuickPickWindow> {
+        self.task_picker.as_ref().map(|menu| &menu.picker)
+    }
+
+    /// Get the file operations service.
+    ///
+    /// Open buffers follow files it renames when the App next handles an
+    /// event.
+    pub fn file_operations(&self) -> Arc<FileOperations> {
+        self.services
+            .resolve::<FileOperations>()
+            .expect("the App registers file operations")
+    }
+
+    /// Get the service that runs background work off the render thread.
+    pub fn tasks(&self) -> Arc<TaskService> {
+        self.services
+            .resolve::<TaskService>()
+            .expect("the App registers a task service")
+    }
+
+    /// Get the service that runs child processes such as cargo.
+    pub fn processes(&self) -> Arc<ProcessService> {
+        self.services
+            .resolve::<ProcessService>()
+            .expect("the App registers a process service")
+    }
+
+    /// Get the service running configured tasks such as `cargo test`.
+    pub fn task_runner(&self) -> Arc<TaskRunner> {
+        self.services
+            .resolve::<TaskRunner>()
+            .expect("the App registers a task runner")
+    }
+
+    /// Get the service holding the output channels.
+    pub fn outputs(&self) -> Arc<OutputService> {
+        self.services
+            .resolve::<OutputService>()
+            .expect("the App registers an output service")
+    }
+
+    /// Get the service long-running work reports its progress to.
+    pub fn progress(&self) -> Arc<ProgressService> {
+        self.services
+            .resolve::<ProgressService>()
+            .expect("the App registers a progress service")
+    }
+
+    /// Get the service features notify the user through.
+    pub fn notifications(&self) -> Arc<NotificationService> {
+        self.services
+            .resolve::<NotificationService>()
+            .expect("the App registers a notification service")
+    }
+
+    /// Get the service copying and pasting text.
+    pub fn clipboard(&self) -> Arc<ClipboardService> {
+        self.services
+            .resolve::<ClipboardService>()
+            .expect("the App registers a clipboard service")
+    }
+
+    /// Check whether the terminal window the App runs in has focus, as of
+    /// the last [`AppEvent::FocusGained`] or [`AppEvent::FocusLost`].
+    ///
+    /// Terminals that do not report focus changes leave it `true`.
+    pub fn is_window_focused(&self) -> bool {
+        self.window_focused
+    }
+
+    /// Get the context keys `when` clauses of keybindings are evaluated
+    /// against.
+    ///
+    /// The App sets its own keys before dispatching each key (see
+    /// [`App::update_context_keys`]); features may set more.
+    pub fn context_keys(&self) -> Arc<ContextKeyService> {
+        self.services
+            .resolve::<ContextKeyService>()
+            .expect("the App registers a context key service")
+    }
+
+    /// Get the scheduler pacing the event loop's ticks, which runs the
+    /// periodic callbacks registered with it on every tick.
+    pub fn ticks(&self) -> Arc<TickScheduler> {
+        self.services
+            .resolve::<TickScheduler>()
+            .expect("the App registers a tick scheduler")
+    }
+
+    /// Get the service describing the terminal the IDE runs in.
+    pub fn environment(&self) -> Arc<EnvironmentService> {
+        self.services
+            .resolve::<EnvironmentService>()
+            .expect("the App registers an environment service")
+    }
+
+    /// Describe the terminal with `service`, e.g. one reading the process
+    /// environment, instead of the App's default that sees no variables.
+    pub fn set_environment_service(&mut self, service: EnvironmentService) {
+        self.services.register(service);
+    }
+
+    /// Copy and paste with `service`, e.g. one using the system clipboard,
+    /// instead of the in-memory clipboard the App starts with.
+    pub fn set_clipboard_service(&mut self, service: ClipboardService) {
+        self.services.register(service);
+    }
+
+    /// Get the service opening URLs outside the IDE.
+    pub fn opener(&self) -> Arc<OpenerService> {
+        self.services
+            .resolve::<OpenerService>()
+            .expect("the App registers an opener service")
+    }
+
+    /// Open URLs with `service`, e.g. one using the host's browser, instead
+    /// of the App's default that cannot open them.
+    pub fn set_opener_service(&mut self, service: OpenerService) {
+        self.services.register(service);
+    }
+
+    /// Copy the target editor's selection, deleting it too if `cut`.
+    ///
+    /// Does nothing while the selection is empty. Leaves Visual mode. If
+    /// the system clipboard cannot be written, a warning says the text can
+    /// only be pasted within the IDE.
+    fn copy_selection(&mut self, cut: bool) {
+        if let Some(text) = self
+            .focus_manager
+            .focused()
+            .and_then(|id| self.windows.get(id))
+            .and_then(|window| window.selected_text())
+        {
+            self.copy_text(text);
+            return;
+        }
+        let id = self.target_editor();
+        let Some(editor) = self.windows.window_mut::<EditorWindow>(id) else {
+            return;
+        };
+        let text = editor.selected_text();
+        if text.is_empty() {
+            return;
+        }
+        let visual = self.editing_mode == Some(EditingMode::Visual);
+        if cut {
+            editor.delete_forward();
+        } else if visual {
+            let start = editor.selection().start;
+            editor.set_selection(start..start);
+        }
+        if visual {
+            self.editing_mode = Some(EditingMode::Normal);
+        }
+        self.copy_text(text);
+    }
+
+    /// Copy `text` to the clipboard, warning when only the IDE has it.
+    fn copy_text(&self, text: String) {
+        let copied = self.clipboard().copy(text);
+        if let Err(error) = copied {
+            self.notifications().notify(
+                "Clipboard",
+                NotificationSeverity::Warning,
+                format!("Copied within the IDE only: {error}"),
+            );
+        }
+    }
+
+    /// Paste the clipboard's text into the focused terminal, or over the
+    /// target editor's selection. Leaves Visual mode.
+    fn paste(&mut self) {
+        let text = self.clipboard().paste();
+        self.paste_text(&text);
+    }
+
+    /// Handle text pasted into the terminal the App runs in.
+    ///
+    /// With no overlay open, the text goes where [`Action::Paste`] puts the
+    /// clipboard. An overlay such as an input box gets it typed in, leaving
+    /// out line breaks and other control characters.
+    fn handle_paste(&mut self, text: &str) {
+        match self.keybinding_mode() {
+            KeybindingMode::Normal | KeybindingMode::Insert | KeybindingMode::Visual => {
+                self.paste_text(text);
+            }
+            _ => {
+                for c in text.chars().filter(|c| !c.is_control()) {
+                    self.handle_key(AppKey::Char(c));
+                }
+            }
+        }
+    }
+
+    /// Paste `text` into the focused terminal, or over the target editor's
+    /// selection.
+    fn paste_text(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        if self.focused_context() == Some(WindowContext::Terminal) {
+            let terminal = self
+                .focus_manager
+                .focused()
+                .and_then(|id| self.windows.window_mut::<TerminalWindow>(id));
+            if let Some(terminal) = terminal {
+                terminal.paste(text);
+            }
+            return;
+        }
+        let id = self.target_editor();
+        if let Some(editor) = self.windows.window_mut::<EditorWindow>(id) {
+            editor.insert_text(text);
+        }
+        if self.editing_mode == Some(EditingMode::Visual) {
+            self.editing_mode = Some(EditingMode::Normal);
+        }
+    }
+
+    /// Get the guided tutorial's progress, while it runs.
+    pub fn tutorial(&self) -> Option<&Tutorial> {
+        self.tutorial.as_ref()
+    }
+
+    /// Start the guided tutorial, or stop it if it is running.
+    ///
+    /// Returns whether it runs afterwards.
+    pub fn toggle_tutorial(&mut self) -> bool {
+        if self.tutorial.take().is_some() {
+            return false;
+        }
+        self.tutorial = Some(Tutorial::new(self.tutorial_state()));
+        true
+    }
+
+    /// Describe what the tutorial checks its steps against.
+    fn tutorial_state(&self) -> TutorialState {
+        TutorialState {
+            focused: self.focused_context(),
+            palette_open: self.command_palette.is_some(),
+            editor_path: self
+                .windows
+                .window::<EditorWindow>(self.target_editor())
+                .and_then(EditorWindow::path),
+            searched: self
+                .search_results()
+                .and_then(SearchResultsWindow::searched)
+                .map(|query| query.pattern.clone()),
+        }
+    }
+
+    /// Move the tutorial on when the user completed its current step,
+    /// ending it with a notification after the last one.
+    fn process_tutorial(&mut self) {
+        let state = self.tutorial_state();
+        let Some(tutorial) = self.tutorial.as_mut() else {
+            return;
+        };
+        if tutorial.update(&state) && tutorial.is_finished() {
+            self.tutorial = None;
+            self.notifications().notify(
+                "Tutorial",
+                NotificationSeverity::Info,
+                "Tutorial complete: every command is in the palette (:)",
+            );
+        }
+    }
+
+    /// Turn "do not disturb" on or off.
+    ///
+    /// Returns whether it is on afterwards.
+    pub fn toggle_do_not_disturb(&mut self) -> bool {
+        let notifications = self.notifications();
+        let on = !notifications.do_not_disturb();
+        notifications.set_do_not_disturb(on);
+        on
+    }
+
+    /// Cancel the newest running operation that can be cancelled.
+    ///
+    /// Returns whether there was one.
+    pub fn cancel_progress(&mut self) -> bool {
+        let progress = self.progress();
+        progress
+            .active()
+            .iter()
+            .rev()
+            .find(|item| item.cancellable)
+            .is_some_and(|item| progress.cancel(item.id))
+    }
+
+    /// Get the service that searches the workspace in the background.
+    pub fn searches(&self) -> Arc<SearchService> {
+        self.services
+            .resolve::<SearchService>()
+            .expect("the App registers a search service")
+    }
+
+    /// Get the service log records are written to.
+    ///
+    /// Install it with
+    /// [`install`](cli_ide_platform::logging::tracing_layer::install) to
+    /// receive `tracing` events.
+    pub fn logs(&self) -> Arc<LogService> {
+        self.services
+            .resolve::<LogService>()
+            .expect("the App registers a log service")
+    }
+
+    /// Replace the file operations service, e.g. to use another trash
+    /// directory.
+    pub fn set_file_operations(&mut self, operations: FileOperations) {
+        self.file_operation_events = operations.on_did_run().subscribe();
+        self.services.register(operations);
+    }
+
+    /// Apply completed file operations to the open buffers and the
+    /// explorer.
+    ///
+    /// Buffers of renamed files and folders move with them. Buffers of
+    /// deleted files stay open with their text, so it can be saved again.
+    fn process_file_operations(&mut self) {
+        let mut changed = false;
+        while let Ok(event) = self.file_operation_events.try_recv() {
+            changed = true;
+            match event {
+                FileOperationEvent::Created(path) => self.rescan_todos(&path),
+                FileOperationEvent::Renamed { from, to } => {
+                    self.buffers.rename_path(&from, &to);
+                    if let Some(list) = self.todos_mut() {
+                        list.remove(&from);
+                    }
+                    self.rescan_todos(&to);
+                }
+                FileOperationEvent::Deleted(path) => {
+                    if let Some(list) = self.todos_mut() {
+                        list.remove(&path);
+                    }
+                }
+            }
+        }
+        if changed {
+            self.dirty = true;
+            if let Some(explorer) = self.explorer_mut() {
+                explorer.refresh();
+            }
+        }
+    }
+
+    /// Get the file explorer, if open.
+    pub fn explorer(&self) -> Option<&FileTreeWindow> {
+        self.windows.window(self.explorer_id?)
+    }
+
+    /// Get the file explorer ID, if open.
+    pub fn explorer_id(&self) -> Option<WindowId> {
+        self.explorer_id
+    }
+
+    /// Open the file explorer on the workspace roots as a new column and
+    /// focus it, or close it if it is open.
+    ///
+    /// Returns whether the explorer is open afterwards.
+    pub fn toggle_explorer(&mut self) -> bool {
+        if let Some(id) = self.explorer_id.take() {
+            self.close_window(id);
+            return false;
+        }
+        let roots = self.workspace.canonical().roots().to_vec();
+        self.explorer_id = Some(self.open_window(Box::new(FileTreeWindow::with_roots(roots))));
+        true
+    }
+
+    /// Get the file explorer mutably, if open.
+    fn explorer_mut(&mut self) -> Option<&mut FileTreeWindow> {
+        self.windows.window_mut(self.explorer_id?)
+    }
+
+    /// Get the search panel, if open.
+    pub fn search_panel(&self) -> Option<&SearchWindow> {
+        self.windows.window(self.search_id?)
+    }
+
+    /// Get the search panel ID, if open.
+    pub fn search_id(&self) -> Option<WindowId> {
+        self.search_id
+    }
+
+    /// Open the search panel on the workspace roots as a new column and
+    /// focus it, or close it if it is open.
+    ///
+    /// Returns whether the search panel is open afterwards.
+    pub fn toggle_search(&mut self) -> bool {
+        if let Some(id) = self.search_id.take() {
+            self.close_window(id);
+            return false;
+        }
+        let panel = SearchWindow::with_folders(self.workspace.canonical());
+        self.search_id = Some(self.open_window(Box::new(panel)));
+        true
+    }
+
+    /// Get the search panel mutably, if open.
+    fn search_mut(&mut self) -> Option<&mut SearchWindow> {
+        self.windows.window_mut(self.search_id?)
+    }
+
+    /// Get the Find in Files panel, if open.
+    pub fn search_results(&self) -> Option<&SearchResultsWindow> {
+        self.windows.window(self.search_results_id?)
+    }
+
+    /// Get the Find in Files panel ID, if open.
+    pub fn search_results_id(&self) -> Option<WindowId> {
+        self.search_results_id
+    }
+
+    /// Open the Find in Files panel on the workspace roots as a new column
+    /// and focus it, or close it if it is open.
+    ///
+    /// Returns whether the panel is open afterwards.
+    pub fn toggle_search_results(&mut self) -> bool {
+        if let Some(id) = self.search_results_id {
+            self.close_window(id);
+            return false;
+        }
+        let panel = SearchResultsWindow::with_folders(self.workspace.clone());
+        self.search_results_id = Some(self.open_window(Box::new(panel)));
+        true
+    }
+
+    /// Get the Find in Files panel mutably, if open.
+    fn search_results_mut(&mut self) -> Option<&mut SearchResultsWindow> {
+        self.windows.window_mut(self.search_results_id?)
+    }
+
+    /// Whether the Find in Files panel has focus.
+    fn search_results_focused(&self) -> bool {
+        self.search_results_id.is_some() && self.focus_manager.focused() == self.search_results_id
+    }
+
+    /// Search the workspace for `query` in the background, streaming the
+    /// matches into the Find in Files panel, which is opened if needed.
+    ///
+    /// A search still running is cancelled. Open files are searched as they
+    /// are in their buffers.
+    pub fn find_in_files(&mut self, query: SearchQuery) {
+        if let Some(search) = self.workspace_search.take() {
+            search.cancel();
+        }
+        self.search_progress = None;
+        if self.search_results_id.is_none() {
+            self.toggle_search_results();
+        }
+        let Some(roots) = self
+            .search_results()
+            .map(|panel| panel.folders().roots().to_vec())
+        else {
+            return;
+        };
+        let open = self
+            .buffers
+            .paths()
+            .into_iter()
+            .filter_map(|path| {
+                let text = self.buffers.get(&path)?.borrow().text().to_string();
+                Some((path, text))
+            })
+            .collect();
+        match self
+            .searches()
+            .search_folders(&roots, &query, open, MAX_SEARCH_FILES)
+        {
+            Ok(search) => {
+                if let Some(panel) = self.search_results_mut() {
+                    panel.start(query);
+                }
+                self.search_progress = Some(self.progress().start_cancellable(
+                    "Searching",
+                    ProgressLocation::StatusBar,
+                    search.token().clone(),
+                ));
+                self.workspace_search = Some(search);
+            }
+            Err(error) => {
+                if let Some(panel) = self.search_results_mut() {
+                    panel.fail(query, error.to_string());
+                }
+            }
+        }
+    }
+
+    /// Check whether a workspace search is running.
+    pub fn is_searching(&self) -> bool {
+        self.workspace_search.is_some()
+    }
+
+    /// Move the matches found by the running workspace search into the
+    /// Find in Files panel.
+    fn process_search_events(&mut self) {
+        let Some(search) = self.workspace_search.as_ref() else {
+            return;
+        };
+        let mut events = Vec::new();
+        while let Some(event) = search.try_event() {
+            let finished = matches!(event, SearchEvent::Finished(_));
+            events.push(event);
+            if finished {
+                self.workspace_search = None;
+                self.search_progress = None;
+                break;
+            }
+        }
+        self.dirty |= !events.is_empty();
+        let Some(panel) = self.search_results_mut() else {
+            return;
+        };
+        for event in events {
+            match event {
+                SearchEvent::Matches { path, matches } => panel.add_matches(path, matches),
+                SearchEvent::Finished(summary) => {
+                    if let Some(error) = &summary.error {
+                        tracing::warn!("workspace search failed: {error}");
+                    }
+                    panel.finish(summary);
+                }
+            }
+        }
+    }
+
+    /// Get the TODO list, if open.
+    pub fn todos(&self) -> Option<&TodosWindow> {
+        self.windows.window(self.todos_id?)
+    }
+
+    /// Open the TODO list as a new column, focus it and scan the workspace
+    /// for TODO comments, or close it if it is open.
+    ///
+    /// Returns whether the list is open afterwards.
+    pub fn toggle_todos(&mut self) -> bool {
+        if let Some(id) = self.todos_id {
+            self.close_window(id);
+            return false;
+        }
+        let list = TodosWindow::new(self.workspace.canonical());
+        self.todos_id = Some(self.open_window(Box::new(list)));
+        self.scan_todos();
+        true
+    }
+
+    /// Get the TODO list mutably, if open.
+    fn todos_mut(&mut self) -> Option<&mut TodosWindow> {
+        self.windows.window_mut(self.todos_id?)
+    }
+
+    /// Whether the TODO list has focus.
+    fn todos_focused(&self) -> bool {
+        self.todos_id.is_some() && self.focus_manager.focused() == self.todos_id
+    }
+
+    /// Scan the roots of the TODO list for TODO comments in the
+    /// background, streaming them into the list.
+    ///
+    /// A scan still running is cancelled. Open files are scanned as they
+    /// are in their buffers.
+    fn scan_todos(&mut self) {
+        if let Some(scan) = self.todo_scan.take() {
+            scan.cancel();
+        }
+        self.todo_progress = None;
+        let Some(roots) = self.todos().map(|list| list.folders().roots().to_vec()) else {
+            return;
+        };
+        let open = self
+            .buffers
+            .paths()
+            .into_iter()
+            .filter_map(|path| {
+                let text = self.buffers.get(&path)?.borrow().text().to_string();
+                Some((path, text))
+            })
+            .collect();
+        match self
+            .searches()
+            .search_folders(&roots, &todo::query(), open, MAX_SEARCH_FILES)
+        {
+            Ok(scan) => {
+                if let Some(list) = self.todos_mut() {
+                    list.start();
+                }
+                self.todo_progress = Some(self.progress().start_cancellable(
+                    "Scanning TODOs",
+                    ProgressLocation::StatusBar,
+                    scan.token().clone(),
+                ));
+                self.todo_scan = Some(scan);
+            }
+            Err(error) => {
+                if let Some(list) = self.todos_mut() {
+                    list.fail(error.to_string());
+                }
+            }
+        }
+    }
+
+    /// Scan the file at `path` again, or the files under it if it is a
+    /// folder, and update the TODO list with what they hold now.
+    ///
+    /// Open files are scanned as they are in their buffers; files outside
+    /// the roots of the list are left out.
+    fn rescan_todos(&mut self, path: &Path) {
+        let Some(list) = self.todos() else {
+            return;
+        };
+        if list.folders().root_of(path).is_none() {
+            return;
+        }
+        let paths = if path.is_dir() {
+            ignore::walk_files(path, MAX_SEARCH_FILES).unwrap_or_default()
+        } else {
+            vec![path.to_path_buf()]
+        };
+        for path in paths {
+            let text = match self.buffers.get(&path) {
+                Some(buffer) => Some(buffer.borrow().text().to_string()),
+                None => fs::read_to_string(&path).ok(),
+            };
+            let items = text.as_deref().map(todo::find_todos).unwrap_or_default();
+            if let Some(list) = self.todos_mut() {
+                list.set_file(path, items);
+            }
+        }
+    }
+
+    /// Scan files again as they are saved, renamed or closed, or change on
+    /// disk, and move the comments found by the running TODO scan into the
+    /// TODO list.
+    fn process_todos(&mut self) {
+        let mut changed = self
+            .todo_watch
+            .as_ref()
+            .map(FileWatches::take_changed_paths)
+            .unwrap_or_default();
+        for event in self.todo_buffer_events.try_iter() {
+            match event {
+                BufferEvent::Saved(uri) | BufferEvent::Closed(uri) => {
+                    changed.extend(uri.path().map(Path::to_path_buf))
+                }
+                BufferEvent::Renamed { from, to } => {
+                    changed.extend(from.path().map(Path::to_path_buf));
+                    changed.extend(to.path().map(Path::to_path_buf));
+                }
+                BufferEvent::Opened(_) => {}
+            }
+        }
+        for path in changed {
+            self.dirty = true;
+            self.rescan_todos(&path);
+        }
+        self.process_todo_scan();
+        self.rewatch_todos();
+    }
+
+    /// Move the comments found by the running TODO scan into the TODO list.
+    fn process_todo_scan(&mut self) {
+        let Some(scan) = self.todo_scan.as_ref() else {
+            return;
+        };
+        let mut events = Vec::new();
+        while let Some(event) = scan.try_event() {
+            let finished = matches!(event, SearchEvent::Finished(_));
+            events.push(event);
+            if finished {
+                self.todo_scan = None;
+                self.todo_progress = None;
+                break;
+            }
+        }
+        self.dirty |= !events.is_empty();
+        let Some(list) = self.todos_mut() else {
+            return;
+        };
+        for event in events {
+            match event {
+                SearchEvent::Matches { path, matches } => {
+                    list.set_file(path, todo::from_matches(matches));
+                }
+                SearchEvent::Finished(summary) => {
+                    if let Some(error) = &summary.error {
+                        tracing::warn!("TODO scan failed: {error}");
+                    }
+                    list.finish(summary);
+                }
+            }
+        }
+    }
+
+    /// Rescan the files the TODO list shows whenever they change on disk,
+    /// e.g. in another editor or a formatter, checking every `interval`.
+    ///
+    /// Checking out another branch or otherwise changing the repository's
+    /// `HEAD` or index scans the whole workspace again, if the App is
+    /// watching git (see [`watch_git`](Self::watch_git)).
+    pub fn watch_todos(&mut self, interval: Duration) {
+        self.todo_watch = Some(FileWatches::new(Vec::new(), interval));
+        self.rewatch_todos();
+    }
+
+    /// Watch the files the TODO list now shows, if watching: none while the
+    /// list is closed. Waits for a running scan to end.
+    fn rewatch_todos(&mut self) {
+        let Some(watch) = &self.todo_watch else {
+            return;
+        };
+        if self.todo_scan.is_some() {
+            return;
+        }
+        let files = self.todos().map(TodosWindow::files);
+        let watched = watch.watchers.iter().map(FileWatcher::path);
+        let current = match files {
+            Some(files) => watched.eq(files.keys().map(PathBuf::as_path)),
+            None => watch.watchers.is_empty(),
+        };
+        if !current {
+            let paths = files.map(|files| files.keys().cloned().collect());
+            self.todo_watch = Some(FileWatches::new(paths.unwrap_or_default(), watch.interval));
+        }
+    }
+
+    /// Handle keys for the focused TODO list, returning whether the key was
+    /// consumed.
+    fn handle_todos_key(&mut self, key: AppKey) -> bool {
+        let Some(id) = self.todos_id else {
+            return false;
+        };
+        let Some(list) = self.todos_mut() else {
+            return false;
+        };
+        match list.handle_key(key) {
+            TodosOutcome::Ignored => return false,
+            TodosOutcome::Handled => {}
+            TodosOutcome::Open(path, range) => self.open_match(&path, range),
+            TodosOutcome::Refresh => self.scan_todos(),
+            TodosOutcome::Close => {
+                self.close_window(id);
+            }
+        }
+        true
+    }
+
+    /// Get the regex tester, if open.
+    pub fn regex_tester(&self) -> Option<&RegexTesterWindow> {
+        self.windows.window(self.regex_tester_id?)
+    }
+
+    /// Open the regex tester as a new column and focus it, or close it if
+    /// it is open.
+    ///
+    /// Returns whether the regex tester is open afterwards.
+    pub fn toggle_regex_tester(&mut self) -> bool {
+        if let Some(id) = self.regex_tester_id.take() {
+            self.close_window(id);
+            return false;
+        }
+        self.regex_tester_id = Some(self.open_window(Box::new(RegexTesterWindow::new())));
+        true
+    }
+
+    /// Whether the regex tester has focus.
+    fn regex_tester_focused(&self) -> bool {
+        self.regex_tester_id.is_some() && self.focus_manager.focused() == self.regex_tester_id
+    }
+
+    /// Get the git panel, if open.
+    pub fn git_panel(&self) -> Option<&GitPanelWindow> {
+        self.windows.window(self.git_panel_id?)
+    }
+
+    /// Get the git panel ID, if open.
+    pub fn git_panel_id(&self) -> Option<WindowId> {
+        self.git_panel_id
+    }
+
+    /// Open the git panel for the repository containing the workspace root
+    /// as a new column and focus it, or close it if it is open.
+    ///
+    /// Returns whether the git panel is open afterwards. Outside a
+    /// repository it does not open and the status bar says why.
+    pub fn toggle_git_panel(&mut self) -> bool {
+        if let Some(id) = self.git_panel_id.take() {
+            self.close_window(id);
+            return false;
+        }
+        let repository = match self.repository() {
+            Ok(repository) => repository,
+            Err(error) => {
+                self.report_git_error(&error);
+                return false;
+            }
+        };
+        let panel = GitPanelWindow::new(repository.root());
+        self.git_panel_id = Some(self.open_window(Box::new(panel)));
+        self.refresh_git();
+        true
+    }
+
+    /// Get the git panel mutably, if open.
+    fn git_panel_mut(&mut self) -> Option<&mut GitPanelWindow> {
+        self.windows.window_mut(self.git_panel_id?)
+    }
+
+    /// Get the diff view, if open.
+    pub fn diff_view(&self) -> Option<&DiffWindow> {
+        self.windows.window(self.diff_id?)
+    }
+
+    /// Show the changes to `path` against `base` in the diff view and focus
+    /// it, opening the view as a new column if needed.
+    pub fn open_diff(&mut self, path: &Path, base: DiffBase) -> Result<(), GitError> {
+        let repository = self.repository()?;
+        let diff = repository.diff(path, base)?;
+        match self.diff_id {
+            Some(id) => {
+                if let Some(view) = self.windows.window_mut::<DiffWindow>(id) {
+                    view.show(path, base, diff);
+                }
+                self.focus_manager.set_focus(id);
+            }
+            None => {
+                let view = DiffWindow::new(repository.root(), path, base, diff);
+                self.diff_id = Some(self.open_window(Box::new(view)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Show the changes from the text of `old` to the text of `new` in the
+    /// diff view and focus it, opening the view as a new column if needed.
+    ///
+    /// Each side is the file's open buffer, with its unsaved changes, or
+    /// the file on disk.
+    pub fn compare_buffers(&mut self, old: &Path, new: &Path) -> io::Result<()> {
+        let old_text = self.text_of(old)?;
+        let new_text = self.text_of(new)?;
+        let subject = DiffSubject::Compare {
+            old: self.relative_name(old),
+            new: self.relative_name(new),
+        };
+        self.show_comparison(new, subject, &old_text, &new_text);
+        Ok(())
+    }
+
+    /// Compare the file on disk with the target editor's buffer, showing
+    /// the unsaved changes in the diff view.
+    fn compare_with_saved(&mut self) {
+        let editor = self
+            .windows
+            .window::<EditorWindow>(self.target_editor())
+            .expect("the target editor is open");
+        let Some(path) = editor.path() else {
+            self.report_files_error("Save the buffer before comparing it".to_string());
+            return;
+        };
+        let text = editor.buffer().borrow().text().to_string();
+        match fs::read_to_string(&path) {
+            Ok(saved) => {
+                let name = self.relative_name(&path);
+                let subject = DiffSubject::Compare {
+                    old: format!("{name} (Saved)"),
+                    new: name,
+                };
+                self.show_comparison(&path, subject, &saved, &text);
+            }
+            Err(error) => self.report_files_error(format!("Cannot compare with saved: {error}")),
+        }
+    }
+
+    /// Compare the target editor's file as committed in `HEAD` with its
+    /// buffer, including the staged and unsaved changes.
+    fn compare_with_head(&mut self) {
+        let editor = self
+            .windows
+            .window::<EditorWindow>(self.target_editor())
+            .expect("the target editor is open");
+        let Some(path) = editor.path() else {
+            self.report_git("Save the buffer before comparing it".to_string());
+            return;
+        };
+        let text = editor.buffer().borrow().text().to_string();
+        match self
+            .repository()
+            .and_then(|repository| repository.file_at("HEAD", &path))
+        {
+            Ok(committed) => {
+                let name = self.relative_name(&path);
+                let subject = DiffSubject::Compare {
+                    old: format!("{name} (HEAD)"),
+                    new: name,
+                };
+                self.show_comparison(&path, subject, &committed, &text);
+            }
+            Err(error) => self.report_git_error(&error),
+        }
+    }
+
+    /// Compare the clipboard's text with the target editor's document,
+    /// showing what the document adds to it.
+    fn compare_with_clipboard(&mut self) {
+        let id = self.target_editor();
+        let Some((path, name, text)) = self.editor_document(id) else {
+            return;
+        };
+        let clipboard = self.clipboard().paste();
+        let subject = DiffSubject::Compare {
+            old: "Clipboard".to_string(),
+            new: name,
+        };
+        self.show_comparison(&path, subject, &clipboard, &text);
+    }
+
+    /// Prompt for a file to compare the target editor's document with,
+    /// relative to the workspace root.
+    fn prompt_compare_with(&mut self) {
+        let id = self.target_editor();
+        let paths = PathCompleter::new(self.workspace.primary());
+        self.input_box = Some(PendingInput {
+            input: InputBoxWindow::new("Compare Active File With", "").with_completer(paths),
+            purpose: InputPurpose::CompareWith(id),
+        });
+    }
+
+    /// Show the changes from `other`, its open buffer or the file on disk,
+    /// to the document of the editor `id` in the diff view.
+    pub fn compare_editor_with_file(&mut self, id: WindowId, other: &Path) -> io::Result<()> {
+        let old = self.text_of(other)?;
+        let Some((path, name, text)) = self.editor_document(id) else {
+            return Ok(());
+        };
+        let subject = DiffSubject::Compare {
+            old: self.relative_name(other),
+            new: name,
+        };
+        self.show_comparison(&path, subject, &old, &text);
+        Ok(())
+    }
+
+    /// The path of the editor `id`'s document, or its untitled name, with
+    /// the name to show and the text, unsaved changes included.
+    fn editor_document(&self, id: WindowId) -> Option<(PathBuf, String, String)> {
+        let editor = self.windows.window::<EditorWindow>(id)?;
+        let text = editor.buffer().borrow().text().to_string();
+        let name = self.document_name(editor);
+        let path = editor.path().unwrap_or_else(|| PathBuf::from(&name));
+        Some((path, name, text))
+    }
+
+    /// The name of `editor`'s document: its path relative to the workspace
+    /// root, or its untitled name.
+    fn document_name(&self, editor: &EditorWindow) -> String {
+        match (editor.path(), self.buffers.uri_of(editor.buffer())) {
+            (Some(path), _) => self.relative_name(&path),
+            (None, Some(BufferUri::Untitled(name))) => name,
+            (None, _) => "Untitled".to_string(),
+        }
+    }
+
+    /// Show the changes from `old` to `new`, two versions of `path`, in the
+    /// diff view and focus it, opening the view as a new column if needed.
+    fn show_comparison(&mut self, path: &Path, subject: DiffSubject, old: &str, new: &str) {
+        let diff = FileDiff::compare(old, new);
+        match self.diff_id {
+            Some(id) => {
+                if let Some(view) = self.windows.window_mut::<DiffWindow>(id) {
+                    view.show(path, subject, diff);
+                }
+                self.focus_manager.set_focus(id);
+            }
+            None => {
+                let view =
+                    DiffWindow::new(self.workspace.primary().to_path_buf(), path, subject, diff);
+                self.diff_id = Some(self.open_window(Box::new(view)));
+            }
+        }
+    }
+
+    /// The text of `path`: its open buffer, or the file on disk.
+    fn text_of(&self, path: &Path) -> io::Result<String> {
+        match self.buffers.get(path) {
+            Some(buffer) => Ok(buffer.borrow().text().to_string()),
+            None => fs::read_to_string(path),
+        }
+    }
+
+    /// `path` relative to the workspace root, for display.
+    fn relative_name(&self, path: &Path) -> String {
+        path.strip_prefix(self.workspace.primary())
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    }
+
+    /// Get the diff view mutably, if open.
+    fn diff_view_mut(&mut self) -> Option<&mut DiffWindow> {
+        self.windows.window_mut(self.diff_id?)
+    }
+
+    /// Get the git history, if open.
+    pub fn git_log(&self) -> Option<&GitLogWindow> {
+        self.windows.window(self.git_log_id?)
+    }
+
+    /// Open the git history of the repository containing the workspace
+    /// root as a new column and focus it, or close it if it is open.
+    ///
+    /// Returns whether the history is open afterwards. Outside a
+    /// repository it does not open and the status bar says why.
+    pub fn toggle_git_log(&mut self) -> bool {
+        if let Some(id) = self.git_log_id.take() {
+            self.close_window(id);
+            return false;
+        }
+        let repository = match self.repository() {
+            Ok(repository) => repository,
+            Err(error) => {
+                self.report_git_error(&error);
+                return false;
+            }
+        };
+        let log = GitLogWindow::new(repository.root());
+        self.git_log_id = Some(self.open_window(Box::new(log)));
+        self.refresh_git();
+        true
+    }
+
+    /// Get the git history mutably, if open.
+    fn git_log_mut(&mut self) -> Option<&mut GitLogWindow> {
+        self.windows.window_mut(self.git_log_id?)
+    }
+
+    /// Open the file at `path` as it was at `revision` in a read-only tab
+    /// of the target editor and focus the editor.
+    pub fn open_revision(&mut self, revision: &str, path: &Path) -> Result<(), GitError> {
+        let text = self.repository()?.file_at(revision, path)?;
+        let id = self.target_editor();
+        let buffer = self.buffers.open_revision(revision, path, text);
+        self.show_buffer(buffer);
+        self.focus_manager.set_focus(id);
+        Ok(())
+    }
+
+    /// Get the output pane, if open.
+    pub fn output_pane(&self) -> Option<&OutputWindow> {
+        self.windows.window(self.output_id?)
+    }
+
+    /// Get the output pane ID, if open.
+    pub fn output_id(&self) -> Option<WindowId> {
+        self.output_id
+    }
+
+    /// Open the output pane as a new column and focus it, or close it if it
+    /// is open.
+    ///
+    /// Returns whether the output pane is open afterwards.
+    pub fn toggle_output(&mut self) -> bool {
+        if let Some(id) = self.output_id.take() {
+            self.close_window(id);
+            return false;
+        }
+        let output = OutputWindow::new(self.outputs());
+        self.output_id = Some(self.open_window(Box::new(output)));
+        true
+    }
+
+    /// Show the output channel named `name` in the output pane, opening
+    /// the pane as a new column if needed.
+    pub fn show_output(&mut self, name: &str) {
+        if self.output_id.is_none() {
+            self.toggle_output();
+        }
+        if let Some(output) = self.output_pane_mut() {
+            output.show(name);
+        }
+    }
+
+    /// Whether the output pane has focus.
+    fn output_focused(&self) -> bool {
+        self.output_id.is_some() && self.focus_manager.focused() == self.output_id
+    }
+
+    /// Get the terminal capability report, if open.
+    pub fn terminal_report(&self) -> Option<&TerminalReportWindow> {
+        self.windows.window(self.terminal_report_id?)
+    }
+
+    /// Open the terminal capability report as a new column and focus it,
+    /// or close it if it is open.
+    ///
+    /// The report shows what the environment service detected when it
+    /// opened, next to test patterns showing what the terminal really draws.
+    ///
+    /// Returns whether the report is open afterwards.
+    pub fn toggle_terminal_report(&mut self) -> bool {
+        if let Some(id) = self.terminal_report_id.take() {
+            self.close_window(id);
+            return false;
+        }
+        let environment = self.environment();
+        let report =
+            TerminalReportWindow::new(environment.capabilities(), environment.variables().clone());
+        self.terminal_report_id = Some(self.open_window(Box::new(report)));
+        true
+    }
+
+    /// Get the keybindings list, if open.
+    pub fn keybindings(&self) -> Option<&KeybindingsWindow> {
+        self.windows.window(self.keybindings_id?)
+    }
+
+    /// Open the list of the router's bindings as a new column and focus
+    /// it, or close it if it is open.
+    ///
+    /// The list shows the bindings when it opened, each action by its
+    /// command title, and marks the ones hiding a binding of the same key
+    /// in a wider scope (see [`KeybindingRouter::conflicts`]).
+    ///
+    /// Returns whether the list is open afterwards.
+    pub fn toggle_keybindings(&mut self) -> bool {
+        if let Some(id) = self.keybindings_id.take() {
+            self.close_window(id);
+            return false;
+        }
+        let router = &self.keybinding_router;
+        let conflicts = router.conflicts();
+        let entries = router
+            .bindings()
+            .into_iter()
+            .map(|binding| KeybindingEntry {
+                scope: binding.scope.label(),
+                key: binding.key.label(),
+                action: action_title(binding.action),
+                shadows: conflicts
+                    .iter()
+                    .find(|conflict| conflict.binding == binding)
+                    .map(|conflict| {
+                        let shadowed = conflict.shadowed;
+                        match shadowed.scope {
+                            BindingScope::Global => action_title(shadowed.action),
+                            scope => {
+                                format!("{}: {}", scope.label(), action_title(shadowed.action))
+                            }
+                        }
+                    }),
+            })
+            .collect();
+        self.keybindings_id = Some(self.open_window(Box::new(KeybindingsWindow::new(entries))));
+        true
+    }
+
+    /// Get the user's snippets.
+    pub fn snippets(&self) -> &Snippets {
+        &self.snippets
+    }
+
+    /// Read the user's snippets from the file at `path`, which the snippet
+    /// manager then saves them to, and expand them as they are typed.
+    ///
+    /// On error the snippets are left as they were.
+    pub fn load_snippets(&mut self, path: impl Into<PathBuf>) -> Result<(), String> {
+        self.snippets = Snippets::load(path)?;
+        self.refresh_snippets();
+        Ok(())
+    }
+
+    /// Get the snippet manager, if open.
+    pub fn snippets_window(&self) -> Option<&SnippetsWindow> {
+        self.windows.window(self.snippets_id?)
+    }
+
+    /// Open the snippet manager as a new column and focus it, or close it
+    /// if it is open.
+    ///
+    /// Returns whether the manager is open afterwards.
+    pub fn toggle_snippets(&mut self) -> bool {
+        if let Some(id) = self.snippets_id.take() {
+            self.close_window(id);
+            return false;
+        }
+        let window = SnippetsWindow::new(self.snippets.snippets());
+        self.snippets_id = Some(self.open_window(Box::new(window)));
+        true
+    }
+
+    /// Whether the snippet manager has focus.
+    fn snippets_focused(&self) -> bool {
+        self.snippets_id.is_some() && self.focus_manager.focused() == self.snippets_id
+    }
+
+    /// Handle keys for the focused snippet manager.
+    ///
+    /// Adding and editing a snippet prompt for its word and body; deleting
+    /// saves right away. Returns whether the key was consumed; `Tab` falls
+    /// through so focus can still move, and `Esc` closes the manager.
+    fn handle_snippets_key(&mut self, key: AppKey) -> bool {
+        let Some(id) = self.snippets_id else {
+            return false;
+        };
+        let Some(window) = self.windows.window_mut::<SnippetsWindow>(id) else {
+            return false;
+        };
+        match window.handle_key(key) {
+            SnippetsOutcome::Ignored => false,
+            SnippetsOutcome::Handled => true,
+            SnippetsOutcome::New => {
+                self.prompt_new_snippet();
+                true
+            }
+            SnippetsOutcome::Edit(index) => {
+                if let Some(snippet) = self.snippets.get(index).cloned() {
+                    self.prompt_snippet_body(snippet.language, snippet.word, &snippet.body);
+                }
+                true
+            }
+            SnippetsOutcome::Delete(index) => {
+                if self.snippets.remove(index).is_some() {
+                    self.save_snippets();
+                    self.refresh_snippets();
+                }
+                true
+            }
+            SnippetsOutcome::Close => {
+                self.close_window(id);
+                self.snippets_id = None;
+                true
+            }
+        }
+    }
+
+    /// Ask for the word of a new snippet, after the language it expands in
+    /// if it does not expand in every file.
+    fn prompt_new_snippet(&mut self) {
+        self.input_box = Some(PendingInput {
+            input: InputBoxWindow::new("New Snippet: [language] word", ""),
+            purpose: InputPurpose::NewSnippet,
+        });
+    }
+
+    /// Ask for the body of the snippet for `word` in `language`, starting
+    /// from `body`. The prompt writes line breaks as `\n`.
+    fn prompt_snippet_body(&mut self, language: Option<String>, word: String, body: &str) {
+        let title = match &language {
+            Some(language) => format!("Snippet {word} ({language})"),
+            None => format!("Snippet {word}"),
+        };
+        self.input_box = Some(PendingInput {
+            input: InputBoxWindow::new(title, snippet::escape_body(body)),
+            purpose: InputPurpose::SnippetBody(language, word),
+        });
+    }
+
+    /// Expand `word` to `body` in files of `language`, or in every file,
+    /// save the snippets and select the snippet in the manager.
+    pub fn set_snippet(
+        &mut self,
+        language: Option<&str>,
+        word: &str,
+        body: &str,
+    ) -> Result<(), String> {
+        self.snippets.set(language, word, body)?;
+        self.save_snippets();
+        self.refresh_snippets();
+        if let Some(window) = self
+            .snippets_id
+            .and_then(|id| self.windows.window_mut::<SnippetsWindow>(id))
+        {
+            window.select(language, word);
+        }
+        Ok(())
+    }
+
+    /// Write the snippets to their file, posting an error notification if
+    /// that fails; they stay in effect either way.
+    fn save_snippets(&mut self) {
+        if let Err(error) = self.snippets.save() {
+            let path = self.snippets.path().unwrap_or(Path::new("")).display();
+            self.notifications().notify(
+                SNIPPETS_SOURCE,
+                NotificationSeverity::Error,
+                format!("Cannot save snippets to {path}: {error}"),
+            );
+        }
+    }
+
+    /// Expand the current snippets as they are typed and show them in the
+    /// snippet manager.
+    fn refresh_snippets(&mut self) {
+        self.refresh_abbreviations();
+        if let Some(window) = self
+            .snippets_id
+            .and_then(|id| self.windows.window_mut::<SnippetsWindow>(id))
+        {
+            window.set_snippets(self.snippets.snippets());
+        }
+    }
+
+    /// Combine the abbreviations from the configuration with the user's
+    /// snippets, which win for the same word and language.
+    fn refresh_abbreviations(&mut self) {
+        let mut abbreviations = self.config_abbreviations.clone();
+        self.snippets.add_to(&mut abbreviations);
+        self.abbreviations = abbreviations;
+    }
+
+    /// Get the presentation mirror, if open.
+    pub fn mirror(&self) -> Option<&MirrorWindow> {
+        self.windows.window(self.mirror_id?)
+    }
+
+    /// Open the presentation mirror as a new column, or close it if it is
+    /// open. Focus stays where it was, so the presenter keeps typing in the
+    /// editor the mirror follows.
+    ///
+    /// Returns whether the mirror is open afterwards.
+    pub fn toggle_mirror(&mut self) -> bool {
+        if let Some(id) = self.mirror_id.take() {
+            self.close_window(id);
+            return false;
+        }
+        let focused = self.focused_id();
+        self.mirror_id = Some(self.open_window(Box::new(MirrorWindow::new())));
+        if let Some(id) = focused {
+            self.focus_manager.set_focus(id);
+        }
+        self.process_mirror();
+        true
+    }
+
+    /// Make the mirror follow the focused editor, or the last focused one
+    /// while another kind of window has focus.
+    fn process_mirror(&mut self) {
+        let Some(mirror_id) = self.mirror_id else {
+            return;
+        };
+        if let Some(id) = self
+            .focused_id()
+            .filter(|&id| self.windows.window::<EditorWindow>(id).is_some())
+        {
+            self.mirror_source = id;
+        }
+        let source = match self.windows.window::<EditorWindow>(self.mirror_source) {
+            Some(source) => source.split_view(),
+            None => {
+                self.mirror_source = self.editor_id;
+                self.editor().split_view()
+            }
+        };
+        if let Some(mirror) = self.windows.window_mut::<MirrorWindow>(mirror_id) {
+            mirror.follow(&source);
+        }
+    }
+
+    /// Get the Markdown preview, if open.
+    pub fn markdown_preview(&self) -> Option<&MarkdownPreviewWindow> {
+        self.windows.window(self.markdown_preview_id?)
+    }
+
+    /// Open the Markdown preview of the focused editor's document as a new
+    /// column and focus it, or close it if it is open.
+    ///
+    /// Returns whether the preview is open afterwards.
+    pub fn toggle_markdown_preview(&mut self) -> bool {
+        if let Some(id) = self.markdown_preview_id.take() {
+            self.close_window(id);
+            return false;
+        }
+        self.markdown_preview_source = self.target_editor();
+        self.markdown_preview_id = Some(self.open_window(Box::new(MarkdownPreviewWindow::new())));
+        self.process_markdown_preview();
+        true
+    }
+
+    /// Show the document of the focused editor in the Markdown preview, or
+    /// of the last focused one while another kind of window has focus.
+    fn process_markdown_preview(&mut self) {
+        let Some(preview_id) = self.markdown_preview_id else {
+            return;
+        };
+        if let Some(id) = self
+            .focused_id()
+            .filter(|&id| self.windows.window::<EditorWindow>(id).is_some())
+        {
+            self.markdown_preview_source = id;
+        }
+        if self
+            .windows
+            .window::<EditorWindow>(self.markdown_preview_source)
+            .is_none()
+        {
+            self.markdown_preview_source = self.editor_id;
+        }
+        let Some(editor) = self
+            .windows
+            .window::<EditorWindow>(self.markdown_preview_source)
+        else {
+            return;
+        };
+        let name = self.document_name(editor);
+        let buffer = Rc::clone(editor.buffer());
+        if let Some(preview) = self.windows.window_mut::<MarkdownPreviewWindow>(preview_id) {
+            preview.follow(&name, buffer.borrow().text());
+        }
+    }
+
+    /// Handle keys for the focused Markdown preview, which moves its
+    /// cursor and selects lines and characters. Returns whether the key was
+    /// consumed.
+    fn handle_markdown_preview_key(&mut self, key: AppKey) -> bool {
+        self.markdown_preview_id
+            .and_then(|id| self.windows.window_mut::<MarkdownPreviewWindow>(id))
+            .is_some_and(|preview| preview.handle_key(key))
+    }
+
+    /// Get the undo tree viewer, if open.
+    pub fn undo_tree(&self) -> Option<&UndoTreeWindow> {
+        self.windows.window(self.undo_tree_id?)
+    }
+
+    /// Open the undo tree of the focused editor's document as a new column
+    /// and focus it, or close it if it is open.
+    ///
+    /// Returns whether the viewer is open afterwards.
+    pub fn toggle_undo_tree(&mut self) -> bool {
+        if let Some(id) = self.undo_tree_id.take() {
+            self.close_window(id);
+            return false;
+        }
+        self.undo_tree_source = self.target_editor();
+        self.undo_tree_id = Some(self.open_window(Box::new(UndoTreeWindow::new())));
+        self.process_undo_tree();
+        true
+    }
+
+    /// Whether the undo tree viewer has focus.
+    fn undo_tree_focused(&self) -> bool {
+        self.undo_tree_id.is_some() && self.focus_manager.focused() == self.undo_tree_id
+    }
+
+    /// Show the undo history of the focused editor's document in the undo
+    /// tree viewer, or of the last focused one while another kind of
+    /// window has focus.
+    fn process_undo_tree(&mut self) {
+        let Some(tree_id) = self.undo_tree_id else {
+            return;
+        };
+        if let Some(id) = self
+            .focused_id()
+            .filter(|&id| self.windows.window::<EditorWindow>(id).is_some())
+        {
+            self.undo_tree_source = id;
+        }
+        if self
+            .windows
+            .window::<EditorWindow>(self.undo_tree_source)
+            .is_none()
+        {
+            self.undo_tree_source = self.editor_id;
+        }
+        let Some(editor) = self.windows.window::<EditorWindow>(self.undo_tree_source) else {
+            return;
+        };
+        let name = self.document_name(editor);
+        let rows = editor.buffer().borrow().undo_tree();
+        if let Some(tree) = self.windows.window_mut::<UndoTreeWindow>(tree_id) {
+            tree.set_tree(&name, rows);
+        }
+    }
+
+    /// Handle keys for the focused undo tree viewer.
+    ///
+    /// `Enter` brings the shown document to the state under the cursor.
+    /// Returns whether the key was consumed; `Tab` falls through so focus
+    /// can still move, and `Esc` closes the viewer.
+    fn handle_undo_tree_key(&mut self, key: AppKey) -> bool {
+        let Some(id) = self.undo_tree_id else {
+            return false;
+        };
+        let Some(tree) = self.windows.window_mut::<UndoTreeWindow>(id) else {
+            return false;
+        };
+        match tree.handle_key(key) {
+            UndoTreeOutcome::Ignored => false,
+            UndoTreeOutcome::Handled => true,
+            UndoTreeOutcome::GoTo(state) => {
+                let source = self.undo_tree_source;
+                if let Some(editor) = self.windows.window_mut::<EditorWindow>(source) {
+                    editor.go_to_undo_state(state);
+                }
+                true
+            }
+            UndoTreeOutcome::Close => {
+                self.close_window(id);
+                self.undo_tree_id = None;
+                true
+            }
+        }
+    }
+
+    /// Get the log viewer, if open.
+    pub fn log_window(&self) -> Option<&LogWindow> {
+        self.windows.window(self.logs_id?)
+    }
+
+    /// Get the log viewer ID, if open.
+    pub fn logs_id(&self) -> Option<WindowId> {
+        self.logs_id
+    }
+
+    /// Open the log viewer as a new column and focus it, or close it if it
+    /// is open.
+    ///
+    /// The viewer starts with the records the log service kept and follows
+    /// new ones while it is open.
+    ///
+    /// Returns whether the log viewer is open afterwards.
+    pub fn toggle_logs(&mut self) -> bool {
+        if let Some(id) = self.logs_id.take() {
+            self.close_window(id);
+            return false;
+        }
+        // The kept records include any still queued for the viewer
+        self.log_records.try_iter().for_each(drop);
+        let records = self.logs().records();
+        self.logs_id = Some(self.open_window(Box::new(LogWindow::new(records))));
+        true
+    }
+
+    /// Move new log records into the log viewer, if open.
+    fn process_log_records(&mut self) {
+        let records: Vec<LogRecord> = self.log_records.try_iter().collect();
+        self.dirty |= !records.is_empty();
+        let Some(logs) = self
+            .logs_id
+            .and_then(|id| self.windows.window_mut::<LogWindow>(id))
+        else {
+            return;
+        };
+        for record in records {
+            logs.append(record);
+        }
+    }
+
+    /// Get the notification center, if open.
+    pub fn notifications_window(&self) -> Option<&NotificationsWindow> {
+        self.windows.window(self.notifications_id?)
+    }
+
+    /// Get the notification center ID, if open.
+    pub fn notifications_id(&self) -> Option<WindowId> {
+        self.notifications_id
+    }
+
+    /// Open the notification center as a new column and focus it, or close
+    /// it if it is open.
+    ///
+    /// The center lists every notification kept, including those the
+    /// filter suppressed, and follows new ones while it is open.
+    ///
+    /// Returns whether the notification center is open afterwards.
+    pub fn toggle_notifications(&mut self) -> bool {
+        if let Some(id) = self.notifications_id.take() {
+            self.close_window(id);
+            return false;
+        }
+        let center = NotificationsWindow::new(self.notifications().notifications());
+        self.notifications_id = Some(self.open_window(Box::new(center)));
+        self.process_notifications();
+        true
+    }
+
+    /// Show the current notifications in the notification center, if open.
+    fn process_notifications(&mut self) {
+        let service = self.notifications();
+        let Some(center) = self
+            .notifications_id
+            .and_then(|id| self.windows.window_mut::<NotificationsWindow>(id))
+        else {
+            return;
+        };
+        center.set_notifications(service.notifications());
+        center.set_do_not_disturb(service.do_not_disturb());
+    }
+
+    /// Get the output pane mutably, if open.
+    fn output_pane_mut(&mut self) -> Option<&mut OutputWindow> {
+        self.windows.window_mut(self.output_id?)
+    }
+
+    /// Get the Problems panel, if open.
+    pub fn problems_panel(&self) -> Option<&ProblemsWindow> {
+        self.windows.window(self.problems_id?)
+    }
+
+    /// Get the Problems panel ID, if open.
+    pub fn problems_id(&self) -> Option<WindowId> {
+        self.problems_id
+    }
+
+    /// Get the problems reported by the last cargo command.
+    pub fn problems(&self) -> &[Problem] {
+        &self.problems
+    }
+
+    /// Get the Diagnostics panel, if open.
+    pub fn diagnostics_panel(&self) -> Option<&DiagnosticsWindow> {
+        self.windows.window(self.diagnostics_id?)
+    }
+
+    /// Get the Diagnostics panel ID, if open.
+    pub fn diagnostics_id(&self) -> Option<WindowId> {
+        self.diagnostics_id
+    }
+
+    /// Open the Diagnostics panel, the problems grouped by file, as a new
+    /// column and focus it, or close it if it is open.
+    ///
+    /// Returns whether the Diagnostics panel is open afterwards.
+    pub fn toggle_diagnostics(&mut self) -> bool {
+        if let Some(id) = self.diagnostics_id.take() {
+            self.close_window(id);
+            return false;
+        }
+        let root = self.canonical_root();
+        let mut panel = DiagnosticsWindow::new(root);
+        panel.set_problems(self.problems.clone());
+        self.diagnostics_id = Some(self.open_window(Box::new(panel)));
+        true
+    }
+
+    /// Get the Diagnostics panel mutably, if open.
+    fn diagnostics_panel_mut(&mut self) -> Option<&mut DiagnosticsWindow> {
+        self.windows.window_mut(self.diagnostics_id?)
+    }
+
+    /// Open the Problems panel as a new column and focus it, or close it if
+    /// it is open.
+    ///
+    /// Returns whether the Problems panel is open afterwards.
+    pub fn toggle_problems(&mut self) -> bool {
+        if let Some(id) = self.problems_id.take() {
+            self.close_window(id);
+            return false;
+        }
+        let root = self.canonical_root();
+        let mut panel = ProblemsWindow::new(root);
+        panel.set_problems(self.problems.clone());
+        self.problems_id = Some(self.open_window(Box::new(panel)));
+        true
+    }
+
+    /// Get the Problems panel mutably, if open.
+    fn problems_panel_mut(&mut self) -> Option<&mut ProblemsWindow> {
+        self.windows.window_mut(self.problems_id?)
+    }
+
+    /// Run `command` in the workspace root, killing the task or cargo
+    /// command that is still running.
+    ///
+    /// The output pane is cleared, or opened, and shows the output as it
+    /// arrives; problems found in it replace those of the last command.
+    pub fn run_cargo(&mut self, command: CargoCommand) {
+        let root = self.canonical_root();
+        let spec = command.spec(&root);
+        let title = format!("cargo {}", command.subcommand());
+        self.start_task(title, &spec, TaskOutput::Output, CARGO_CHANNEL);
+    }
+
+    /// Run the configured task named `name` like a cargo command, its
+    /// output shown in the `Tasks` channel of the output pane or in the
+    /// terminal.
+    pub fn run_task(&mut self, name: &str) -> Result<(), String> {
+        let task = self
+            .task_runner()
+            .task(name)
+            .ok_or_else(|| format!("No task named `{name}`"))?;
+        self.start_configured_task(&task);
+        Ok(())
+    }
+
+    /// Open the task picker over the configured tasks.
+    fn open_task_picker(&mut self) {
+        let tasks = self.task_runner().tasks();
+        if tasks.is_empty() {
+            self.report_task("No tasks are configured".to_string());
+            return;
+        }
+        let items = tasks
+            .iter()
+            .map(|task| format!("{}: {}", task.name, task.command))
+            .collect();
+        self.task_picker = Some(TaskMenu {
+            picker: QuickPickWindow::new("Run Task", items),
+            tasks,
+        });
+    }
+
+    /// Run the configured `task` in the workspace root.
+    fn start_configured_task(&mut self, task: &TaskDefinition) {
+        let root = self.canonical_root();
+        let spec = task.spec(&root);
+        self.start_task(task.name.clone(), &spec, task.output, TASKS_CHANNEL);
+    }
+
+    /// Start `spec` as the task reported as `title`, killing the one still
+    /// running, and clear the problems and the output it is shown in.
+    fn start_task(
+        &mut self,
+        title: String,
+        spec: &ProcessSpec,
+        output: TaskOutput,
+        channel: &'static str,
+    ) {
+        if let Some(task) = self.task.take() {
+            task.run.kill();
+        }
+        let matchers = self.problem_matchers(&spec.cwd);
+        let run = match self.task_runner().start(title.as_str(), spec, matchers) {
+            Ok(run) => run,
+            Err(error) => {
+                tracing::error!("cannot run {}: {error}", spec.command_line());
+                self.report_task(format!("Cannot run {}: {error}", spec.command_line()));
+                return;
+            }
+        };
+        tracing::info!(cwd = %spec.cwd.display(), "{} started", spec.command_line());
+        let header = format!("> {}", spec.command_line());
+        match output {
+            TaskOutput::Output => {
+                let lines = self.outputs().channel(channel);
+                lines.clear();
+                lines.append_line(header);
+                self.show_output(channel);
+            }
+            TaskOutput::Terminal => {
+                let terminal = self.show_terminal();
+                terminal.clear();
+                terminal.append_line(&header);
+            }
+        }
+        self.set_problems(Vec::new());
+        self.report_task(format!("{title}: running"));
+        self.task = Some(RunningTask {
+            run,
+            output,
+            channel,
+        });
+    }
+
+    /// The workspace root with symbolic links resolved, as tasks run in.
+    fn canonical_root(&self) -> PathBuf {
+        let root = self.workspace.primary();
+        fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf())
+    }
+
+    /// Get the terminal, reopening it if it was closed.
+    fn show_terminal(&mut self) -> &mut TerminalWindow {
+        if !self.windows.contains(self.terminal_id) {
+            self.terminal_id = self.open_window(Box::new(TerminalWindow::default()));
+        }
+        self.windows
+            .window_mut(self.terminal_id)
+            .expect("the terminal is open")
+    }
+
+    /// Create the problem matchers for output of a command run in `root`:
+    /// rustc's, then those from the configuration.
+    fn problem_matchers(&self, root: &Path) -> Vec<Box<dyn ProblemMatcher>> {
+        let mut matchers: Vec<Box<dyn ProblemMatcher>> = vec![Box::new(RustcMatcher::new(root))];
+        for pattern in &self.problem_patterns {
+            matchers.push(Box::new(pattern.matcher(root)));
+        }
+        matchers
+    }
+
+    /// Kill the running task or cargo command, if any.
+    pub fn stop_task(&mut self) {
+        match &self.task {
+            Some(task) => task.run.kill(),
+            None => self.report_task("No task is running".to_string()),
+        }
+    }
+
+    /// Check whether a task or cargo command is running.
+    pub fn is_task_running(&self) -> bool {
+        self.task.is_some()
+    }
+
+    /// Move the output of the running task into the output pane or the
+    /// terminal and the problems found in it into the Problems panel, and
+    /// report the result once it exits.
+    fn process_task_output(&mut self) {
+        let Some(task) = self.task.as_mut() else {
+            return;
+        };
+        let progress = task.run.poll();
+        let title = task.run.title().to_string();
+        let (output, channel) = (task.output, task.channel);
+        let mut lines = progress.lines;
+        if !progress.problems.is_empty() {
+            let mut problems = std::mem::take(&mut self.problems);
+            problems.extend(progress.problems);
+            self.set_problems(problems);
+        }
+        let outcome = progress.exit.map(|exit| {
+            if exit.killed {
+                "stopped".to_string()
+            } else if exit.success() {
+                "finished".to_string()
+            } else {
+                match exit.code {
+                    Some(code) => format!("failed with exit code {code}"),
+                    None => "failed".to_string(),
+                }
+            }
+        });
+        if let Some(outcome) = &outcome {
+            tracing::info!("{title} {outcome}");
+            lines.push(format!("{title} {outcome}"));
+        }
+        match output {
+            TaskOutput::Output => {
+                let channel = self.outputs().channel(channel);
+                for line in lines {
+                    channel.append_line(line);
+                }
+            }
+            TaskOutput::Terminal if !lines.is_empty() => {
+                let terminal = self.show_terminal();
+                for line in &lines {
+                    terminal.append_line(line);
+                }
+            }
+            TaskOutput::Terminal => {}
+        }
+        let (Some(exit), Some(outcome)) = (progress.exit, outcome) else {
+            return;
+        };
+        // The last frame shows the outcome
+        self.task = None;
+        self.dirty = true;
+        let severity = if exit.killed || exit.success() {
+            NotificationSeverity::Info
+        } else {
+            NotificationSeverity::Error
+        };
+        let problems = if self.problems.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", problem::summarize(&self.problems))
+        };
+        self.notifications()
+            .notify(channel, severity, format!("{title} {outcome}{problems}"));
+        self.report_task(format!("{title} {outcome}{problems}"));
+    }
+
+    /// Replace the problems, e.g. with those of a linter, and show them in
+    /// the Problems and Diagnostics panels, if open, and underlined in the
+    /// editors.
+    pub fn set_problems(&mut self, problems: Vec<Problem>) {
+        self.problems = problems;
+        let problems = self.problems.clone();
+        if let Some(panel) = self.problems_panel_mut() {
+            panel.set_problems(problems.clone());
+        }
+        if let Some(panel) = self.diagnostics_panel_mut() {
+            panel.set_problems(problems.clone());
+        }
+        for id in self.windows.ids() {
+            if let Some(editor) = self.windows.window_mut::<EditorWindow>(id) {
+                editor.set_diagnostics(&problems);
+            }
+        }
+    }
+
+    /// Move the cursor of the target editor to the next problem in its
+    /// document, or the previous one, and show the problem's message in
+    /// the status bar.
+    fn go_to_diagnostic(&mut self, forward: bool) {
+        let id = self.target_editor();
+        let Some(editor) = self.windows.window_mut::<EditorWindow>(id) else {
+            return;
+        };
+        let text = match editor.go_to_diagnostic(forward) {
+            Some(problem) => format!("{}: {}", problem.severity, problem.message),
+            None => "No problems in this file".to_string(),
+        };
+        self.status_bar_updates.emit(StatusBarUpdate::SetSegment {
+            id: DIAGNOSTIC_SEGMENT.to_string(),
+            text,
+        });
+    }
+
+    /// Show the state of the task or cargo command in the status bar.
+    fn report_task(&self, text: String) {
+        self.status_bar_updates.emit(StatusBarUpdate::SetSegment {
+            id: TASK_SEGMENT.to_string(),
+            text,
+        });
+    }
+
+    /// The repository containing the workspace root.
+    fn repository(&self) -> Result<Repository, GitError> {
+        Repository::discover(self.workspace.primary())
+    }
+
+    /// Read the git status into the git panel, the history into the git
+    /// history and the diff view's file into the diff view, for those that
+    /// are open.
+    fn refresh_git(&mut self) {
+        if self.git_panel_id.is_none() && self.diff_id.is_none() && self.git_log_id.is_none() {
+            return;
+        }
+        let repository = self.repository();
+        if self.git_panel_id.is_some() {
+            let status = repository
+                .as_ref()
+                .map_err(ToString::to_string)
+                .and_then(|repository| repository.status().map_err(|error| error.to_string()));
+            if let Some(panel) = self.git_panel_mut() {
+                panel.set_status(status);
+            }
+        }
+        if self.git_log_id.is_some() {
+            let commits = repository
+                .as_ref()
+                .map_err(ToString::to_string)
+                .and_then(|repository| {
+                    repository
+                        .log(MAX_LOG_COMMITS)
+                        .map_err(|error| error.to_string())
+                });
+            if let Some(log) = self.git_log_mut() {
+                log.set_commits(commits);
+            }
+        }
+        // A comparison of two texts is not refreshed from git
+        let Some((path, base)) = self
+            .diff_view()
+            .and_then(|view| Some((view.path().to_path_buf(), view.base()?)))
+        else {
+            return;
+        };
+        match repository.and_then(|repository| repository.diff(&path, base)) {
+            Ok(diff) => {
+                if let Some(view) = self.diff_view_mut() {
+                    view.set_diff(diff);
+                }
+            }
+            Err(error) => self.report_git_error(&error),
+        }
+    }
+
+    /// Stage, unstage or revert the hunk selected in the diff view, then
+    /// refresh the git views.
+    ///
+    /// Reverting changes the file on disk, so it is refused while the file
+    /// has unsaved changes, as is any action on a comparison of two texts;
+    /// an open buffer of the file is reloaded.
+    fn apply_hunk(&mut self, action: HunkAction) {
+        let Some(view) = self.diff_view() else {
+            return;
+        };
+        let Some(hunk) = view.selected_hunk() else {
+            return;
+        };
+        let Some(base) = view.base() else {
+            self.status_bar_updates.emit(StatusBarUpdate::SetSegment {
+                id: GIT_SEGMENT.to_string(),
+                text: "Only git changes can be staged, unstaged or reverted".to_string(),
+            });
+            return;
+        };
+        let path = view.path().to_path_buf();
+        let name = path.file_name().map_or_else(
+            || path.display().to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+        let buffer = self.buffers.get(&path);
+        if action == HunkAction::Revert
+            && buffer
+                .as_ref()
+                .is_some_and(|buffer| buffer.borrow().is_dirty())
+        {
+            self.status_bar_updates.emit(StatusBarUpdate::SetSegment {
+                id: GIT_SEGMENT.to_string(),
+                text: format!("Save {name} before reverting its changes"),
+            });
+            return;
+        }
+        let applied = self
+            .repository()
+            .and_then(|repository| repository.apply_hunk(view.diff(), base, hunk, action));
+        if let Err(error) = applied {
+            self.report_git_error(&error);
+            return;
+        }
+        let done = match action {
+            HunkAction::Stage => "Staged",
+            HunkAction::Unstage => "Unstaged",
+            HunkAction::Revert => "Reverted",
+        };
+        let mut text = format!("{done} hunk {} of {name}", hunk + 1);
+        if action == HunkAction::Revert {
+            if let Some(Err(error)) = buffer.map(|buffer| buffer.borrow_mut().reload()) {
+                text = format!("{text}, but cannot reload it: {error}");
+            }
+        }
+        self.status_bar_updates.emit(StatusBarUpdate::SetSegment {
+            id: GIT_SEGMENT.to_string(),
+            text,
+        });
+        self.refresh_line_changes();
+        self.refresh_git();
+    }
+
+    /// Show the current branch of the repository containing the workspace
+    /// root in the status bar, or remove the segment outside a repository.
+    pub fn refresh_branch(&self) {
+        let update = match self.git().branch(self.workspace.primary()) {
+            Some(head) => StatusBarUpdate::SetSegment {
+                id: BRANCH_SEGMENT.to_string(),
+                text: format!("⎇ {head}"),
+            },
+            None => StatusBarUpdate::RemoveSegment(BRANCH_SEGMENT.to_string()),
+        };
+        self.status_bar_updates.emit(update);
+    }
+
+    /// Mark the changed lines of every file open in an editor in the
+    /// gutters, reading them from git.
+    ///
+    /// Files outside a repository, or ignored by it, have no gutter.
+    pub fn refresh_line_changes(&mut self) {
+        let mut paths: Vec<PathBuf> = Vec::new();
+        for id in self.windows.ids() {
+            if let Some(editor) = self.windows.window::<EditorWindow>(id) {
+                for tab in editor.group().tabs() {
+                    if let Some(path) = tab.buffer.borrow().path() {
+                        if !paths.iter().any(|known| known == path) {
+                            paths.push(path.to_path_buf());
+                        }
+                    }
+                }
+            }
+        }
+        for path in paths {
+            self.refresh_file_line_changes(&path);
+        }
+    }
+
+    /// Mark the changed lines of the file at `path` in the gutters of the
+    /// editors showing it.
+    fn refresh_file_line_changes(&mut self, path: &Path) {
+        let changes = self.git().line_changes(path).ok();
+        for id in self.windows.ids() {
+            if let Some(editor) = self.windows.window_mut::<EditorWindow>(id) {
+                editor.set_line_changes(path, changes.clone());
+            }
+        }
+    }
+
+    /// Refresh the branch, the gutters and the git views whenever the
+    /// repository's `HEAD` or index changes, checking every `interval`,
+    /// as well as after saves.
+    ///
+    /// Changes are collected for [`GIT_REFRESH_DELAY`] and handled on the
+    /// App's next event after that. Returns `false` if the workspace is not
+    /// in a repository.
+    pub fn watch_git(&mut self, interval: Duration) -> bool {
+        let git = self.git();
+        let paths = git.watched_paths(self.workspace.primary());
+        if paths.is_empty() {
+            return false;
+        }
+        let watchers = paths
+            .into_iter()
+            .map(|path| FileWatcher::new(path, interval))
+            .collect();
+        self.git_changes = GitChanges::new(&git, watchers);
+        true
+    }
+
+    /// Get the service reading the git state of files.
+    pub fn git(&self) -> Arc<GitService> {
+        self.services
+            .resolve::<GitService>()
+            .expect("the App registers a git service")
+    }
+
+    /// Open the branch picker over the local and remote-tracking branches,
+    /// with an item to create a new branch first.
+    fn open_branch_picker(&mut self) {
+        let branches = match self
+            .repository()
+            .and_then(|repository| repository.branches())
+        {
+            Ok(branches) => branches,
+            Err(error) => {
+                self.report_git_error(&error);
+                return;
+            }
+        };
+        let mut items = vec!["+ Create New Branch...".to_string()];
+        let mut picks = vec![BranchPick::Create];
+        for branch in branches {
+            items.push(if branch.current {
+                format!("{} (current)", branch.name)
+            } else if branch.remote {
+                format!("{} (remote)", branch.name)
+            } else {
+                branch.name.clone()
+            });
+            picks.push(BranchPick::Checkout(branch));
+        }
+        self.branch_picker = Some(BranchMenu {
+            picker: QuickPickWindow::new("Checkout to", items),
+            picks,
+        });
+    }
+
+    /// Create a branch named `name` at `HEAD` and check it out.
+    fn create_branch(&mut self, name: &str) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("a branch name is required".to_string());
+        }
+        self.repository()
+            .and_then(|repository| repository.create_branch(name))
+            .map_err(|error| error.to_string())?;
+        self.after_work_tree_change();
+        Ok(())
+    }
+
+    /// Open the stash picker over the stashes, newest first.
+    fn open_stash_picker(&mut self) {
+        let stashes = match self
+            .repository()
+            .and_then(|repository| repository.stashes())
+        {
+            Ok(stashes) => stashes,
+            Err(error) => {
+                self.report_git_error(&error);
+                return;
+            }
+        };
+        if stashes.is_empty() {
+            self.report_git("There are no stashes".to_string());
+            return;
+        }
+        let items = stashes
+            .iter()
+            .map(|stash| format!("{}: {}", stash.name(), stash.message))
+            .collect();
+        self.stash_picker = Some(StashMenu {
+            picker: QuickPickWindow::new("Pop Stash", items),
+            stashes,
+        });
+    }
+
+    /// Stash the local changes with `message`, or git's default one if it
+    /// is empty, and report the result.
+    fn stash_changes(&mut self, message: &str) {
+        match self
+            .repository()
+            .and_then(|repository| repository.stash(message))
+        {
+            Ok(Some(stash)) => {
+                self.report_git(format!("Stashed changes as {}", stash.name()));
+                self.after_work_tree_change();
+            }
+            Ok(None) => self.report_git("There are no local changes to stash".to_string()),
+            Err(error) => self.report_git_error(&error),
+        }
+    }
+
+    /// Apply and drop `stash`, and report the result.
+    fn pop_stash(&mut self, stash: &Stash) {
+        match self
+            .repository()
+            .and_then(|repository| repository.pop_stash(stash))
+        {
+            Ok(()) => {
+                self.report_git(format!("Popped {}: {}", stash.name(), stash.message));
+                self.after_work_tree_change();
+            }
+            Err(error) => self.report_git_error(&error),
+        }
+    }
+
+    /// Follow a change of the work tree by git, such as a checkout: reload
+    /// the open files that have no unsaved changes, then refresh the branch
+    /// segment, the gutters and the git views.
+    fn after_work_tree_change(&mut self) {
+        for path in self.buffers.paths() {
+            let Some(buffer) = self.buffers.get(&path) else {
+                continue;
+            };
+            let mut buffer = buffer.borrow_mut();
+            // A file the branch does not have keeps its last contents
+            if !buffer.is_dirty() && path.exists() {
+                let _ = buffer.reload();
+            }
+        }
+        self.refresh_branch();
+        self.refresh_line_changes();
+        self.refresh_git();
+    }
+
+    /// Show the result of a git command in the status bar.
+    fn report_git(&self, text: String) {
+        self.status_bar_updates.emit(StatusBarUpdate::SetSegment {
+            id: GIT_SEGMENT.to_string(),
+            text,
+        });
+    }
+
+    /// Show a git failure in the status bar.
+    fn report_git_error(&self, error: &GitError) {
+        tracing::warn!("git failed: {error}");
+        self.outputs()
+            .channel(GIT_CHANNEL)
+            .append_line(error.to_string());
+        self.notifications()
+            .notify(GIT_CHANNEL, NotificationSeverity::Error, error.to_string());
+        self.report_git(format!("Git: {error}"));
+    }
+
+    /// Get the input box, if open.
+    pub fn input_box(&self) -> Option<&InputBoxWindow> {
+        self.input_box.as_ref().map(|pending| &pending.input)
+    }
+
+    /// Get the focused window ID.
+    pub fn focused_id(&self) -> Option<WindowId> {
+        self.focus_manager.focused()
+    }
+
+    /// Get the editor window ID.
+    pub fn editor_id(&self) -> WindowId {
+        self.editor_id
+    }
+
+    /// Get the terminal window ID.
+    pub fn terminal_id(&self) -> WindowId {
+        self.terminal_id
+    }
+
+    /// Get the keybinding context of the focused window, if any.
+    pub fn focused_context(&self) -> Option<WindowContext> {
+        let id = self.focus_manager.focused()?;
+        self.windows.get(id)?.context()
+    }
+
+    /// Get a reference to the focus manager.
+    pub fn focus_manager(&self) -> &FocusManager {
+        &self.focus_manager
+    }
+
+    /// Get a reference to the keybinding router.
+    pub fn keybinding_router(&self) -> &KeybindingRouter {
+        &self.keybinding_router
+    }
+
+    /// Get a mutable reference to the keybinding router.
+    pub fn keybinding_router_mut(&mut self) -> &mut KeybindingRouter {
+        &mut self.keybinding_router
+    }
+
+    /// Get the shared service container.
+    pub fn services(&self) -> &Arc<ServiceContainer> {
+        &self.services
+    }
+
+    /// Check whether the command palette is open.
+    pub fn is_command_palette_open(&self) -> bool {
+        self.command_palette.is_some()
+    }
+
+    /// Get the command palette, if open.
+    pub fn command_palette(&self) -> Option<&CommandPaletteWindow> {
+        self.command_palette.as_ref()
+    }
+
+    /// Get the command line, if open.
+    pub fn command_line(&self) -> Option<&CommandLineWindow> {
+        self.command_line.as_ref()
+    }
+
+    /// Run a command line, without its `:`, as if it was typed on the
+    /// command line. See [`command_line`](crate::command_line) for the
+    /// commands.
+    pub fn run_ex_command(&mut self, line: &str) -> Result<(), String> {
+        match command_line::parse(line)? {
+            ExCommand::Run(id) => self.execute_command(&id).map_err(|error| error.to_string()),
+            ExCommand::Edit(path) => {
+                let path = self.workspace.primary().join(path);
+                self.open_file(&path)
+                    .map_err(|error| format!("Cannot open {}: {error}", path.display()))
+            }
+            ExCommand::Write { path, quit } => {
+                match path {
+                    Some(path) => {
+                        let path = self.workspace.primary().join(path);
+                        self.save_editor_as(self.target_editor(), &path.to_string_lossy())
+                            .map_err(|error| format!("Cannot save {}: {error}", path.display()))?;
+                    }
+                    None => self
+                        .execute_command("workbench.action.files.save")
+                        .map_err(|error| error.to_string())?,
+                }
+                if quit {
+                    self.execute_command("workbench.action.quit")
+                        .map_err(|error| error.to_string())?;
+                }
+                Ok(())
+            }
+            ExCommand::Set(layer) => {
+                let target = self.target_editor();
+                if let Some(editor) = self.windows.window_mut::<EditorWindow>(target) {
+                    let options = editor.options().overridden_by(&layer);
+                    editor.set_options(options);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Get the auto-save mode.
+    pub fn auto_save(&self) -> AutoSave {
+        self.auto_save
+    }
+
+    /// Set when editors are saved automatically.
+    pub fn set_auto_save(&mut self, auto_save: AutoSave) {
+        self.auto_save = auto_save;
+    }
+
+    /// Whether scrollable windows show a scrollbar.
+    pub fn scrollbars(&self) -> bool {
+        self.scrollbars
+    }
+
+    /// Show or hide the scrollbars of scrollable windows.
+    pub fn set_scrollbars(&mut self, scrollbars: bool) {
+        self.scrollbars = scrollbars;
+    }
+
+    /// Whether the status bar shows the word count of the editor.
+    pub fn word_count(&self) -> bool {
+        self.word_count
+    }
+
+    /// Show or hide the word count of the editor in the status bar.
+    pub fn set_word_count(&mut self, word_count: bool) {
+        self.word_count = word_count;
+    }
+
+    /// Get the UI density.
+    pub fn density(&self) -> Density {
+        self.density
+    }
+
+    /// Set how panes are drawn unless their compact mode was toggled on
+    /// their own.
+    pub fn set_density(&mut self, density: Density) {
+        self.density = density;
+        self.apply_density();
+    }
+
+    /// Whether the window `id` is drawn compact.
+    pub fn is_compact(&self, id: WindowId) -> bool {
+        self.compact_panes
+            .get(&id)
+            .copied()
+            .unwrap_or(self.density.is_compact())
+    }
+
+    /// Draw the window `id` compact, or with all its borders and
+    /// decorations again, whatever the density.
+    pub fn toggle_compact(&mut self, id: WindowId) {
+        let compact = !self.is_compact(id);
+        self.compact_panes.insert(id, compact);
+        if let Some(window) = self.windows.get_mut(id) {
+            window.set_compact(compact);
+        }
+    }
+
+    /// Tell every window whether it is drawn compact.
+    fn apply_density(&mut self) {
+        for id in self.windows.ids() {
+            let compact = self.is_compact(id);
+            if let Some(window) = self.windows.get_mut(id) {
+                window.set_compact(compact);
+            }
+        }
+    }
+
+    /// Get the workspace option layer applied from the configuration.
+    pub fn workspace_options(&self) -> &OptionOverrides {
+        &self.workspace_options
+    }
+
+    /// Apply the settings from `service` and reapply them whenever it fires
+    /// [`ConfigChanged`].
+    ///
+    /// The service is registered in the [`ServiceContainer`] so other
+    /// components can read settings and subscribe to reloads. Problems with
+    /// the settings are reported in the status bar's `config` segment.
+    pub fn set_config_service(&mut self, service: ConfigService) {
+        let mut problems = Vec::new();
+        for root in self.workspace.canonical().extra_roots() {
+            if let Err(error) = service.add_folder(root) {
+                problems.push(error.to_string());
+            }
+        }
+        self.config_changes = Some(service.on_did_change().subscribe());
+        let config = service.config();
+        self.services.register(service);
+        problems.extend(self.apply_config(&config));
+        self.report_config_problems(&problems);
+        self.refresh_status_bar();
+    }
+
+    /// Reload the configuration whenever one of its files, or the theme
+    /// file it names, changes, checking every `interval`.
+    ///
+    /// The reload happens on the App's next event; settings that fail to
+    /// parse are reported in the status bar and the previous settings stay
+    /// in effect. Returns `false` if no configuration service with a backing
+    /// file is set.
+    pub fn watch_config(&mut self, interval: Duration) -> bool {
+        let paths = self.config_files();
+        if paths.is_empty() {
+            return false;
+        }
+        self.config_watch = Some(FileWatches::new(paths, interval));
+        true
+    }
+
+    /// The files the configuration is read from: those of the
+    /// configuration service, its workspace folders included, and the theme
+    /// file, if any.
+    fn config_files(&self) -> Vec<PathBuf> {
+        let Some(service) = self.services.resolve::<ConfigService>() else {
+            return Vec::new();
+        };
+        let mut paths = service.paths().to_vec();
+        paths.extend(service.folder_paths());
+        paths.extend(self.theme_file.clone());
+        paths
+    }
+
+    /// Watch the files the configuration is now read from, if watching,
+    /// after a reload named another theme file.
+    fn rewatch_config(&mut self) {
+        let paths = self.config_files();
+        if let Some(watch) = &self.config_watch {
+            if !watch.watches(&paths) {
+                self.config_watch = Some(FileWatches::new(paths, watch.interval));
+            }
+        }
+    }
+
+    /// The theme `name` stands for: a built-in theme, or else the theme
+    /// file at that path, relative to the folder of the settings file.
+    ///
+    /// Returns the theme and the path of its file, if it has one.
+    fn resolve_theme(&self, name: &str) -> Result<(Theme, Option<PathBuf>), String> {
+        if let Some(theme) = Theme::builtin(name) {
+            return Ok((theme, None));
+        }
+        let folder = self
+            .services
+            .resolve::<ConfigService>()
+            .and_then(|service| service.path().and_then(Path::parent).map(Path::to_path_buf))
+            .unwrap_or_default();
+        let path = folder.join(name);
+        Theme::load(&path).map(|theme| (theme, Some(path)))
+    }
+
+    /// Apply user settings.
+    ///
+    /// The `[editor]` section becomes the workspace option layer of every
+    /// editor, `files.auto_save` sets the auto-save mode, `workbench.theme`
+    /// switches the theme and `[keybindings]` / `[keybindings.<context>]`
+    /// entries are registered with the keybinding router, those with a
+    /// `when` clause as conditional bindings. Bindings from a
+    /// previous call are undone first, restoring whatever they replaced, so
+    /// applying a reloaded configuration drops removed bindings.
+    ///
+    /// Invalid settings are skipped; a message for each is returned.
+    pub fn apply_config(&mut self, config: &Config) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        match OptionOverrides::from_config(config) {
+            Ok(overrides) => self.workspace_options = overrides,
+            Err(message) => problems.push(message),
+        }
+        match OptionOverrides::languages_from_config(config) {
+            Ok(languages) => self.language_options = languages,
+            Err(message) => problems.push(message),
+        }
+        for id in self.windows.ids() {
+            problems.extend(self.resolve_editor_options(id));
+        }
+
+        match config.get_string(AUTO_SAVE_SETTING) {
+            Ok(mode) => match mode.map(str::parse).transpose() {
+                Ok(mode) => self.auto_save = mode.unwrap_or_default(),
+                Err(error) => problems.push(format!("`{AUTO_SAVE_SETTING}`: {error}")),
+            },
+            Err(error) => problems.push(error.to_string()),
+        }
+
+        match config.get_bool(SCROLLBARS_SETTING) {
+            Ok(scrollbars) => self.scrollbars = scrollbars.unwrap_or(false),
+            Err(error) => problems.push(error.to_string()),
+        }
+
+        match config.get_bool(WORD_COUNT_SETTING) {
+            Ok(word_count) => self.word_count = word_count.unwrap_or(false),
+            Err(error) => problems.push(error.to_string()),
+        }
+
+        match config.get_string(THEME_SETTING) {
+            Ok(Some(name)) => match self.resolve_theme(name) {
+                Ok((theme, file)) => {
+                    self.theme_file = file;
+                    if *self.theme() != theme {
+                        self.set_theme(theme);
+                    }
+                }
+                Err(error) => problems.push(format!("`{THEME_SETTING}`: {error}")),
+            },
+            Ok(None) => self.theme_file = None,
+            Err(error) => problems.push(error.to_string()),
+        }
+
+        self.context_themes.clear();
+        for (name, value) in config.section(PANE_THEMES_SECTION) {
+            let Some(context) = WindowContext::from_name(name) else {
+                problems.push(format!("`{PANE_THEMES_SECTION}`: unknown context `{name}`"));
+                continue;
+            };
+            let ConfigValue::String(value) = value else {
+                problems.push(format!("`{PANE_THEMES_SECTION}.{name}` expects a string"));
+                continue;
+            };
+            match self.resolve_theme(value) {
+                Ok((theme, _)) => {
+                    self.context_themes.insert(context, theme);
+                }
+                Err(error) => problems.push(format!("`{PANE_THEMES_SECTION}.{name}`: {error}")),
+            }
+        }
+
+        match config.get_bool(DIM_UNFOCUSED_EDITORS_SETTING) {
+            Ok(dim) => self.dim_unfocused_editors = dim.unwrap_or(false),
+            Err(error) => problems.push(error.to_string()),
+        }
+
+        match config.get_string(DENSITY_SETTING) {
+            Ok(density) => match density.map(str::parse).transpose() {
+                Ok(density) => self.set_density(density.unwrap_or_default()),
+                Err(error) => problems.push(format!("`{DENSITY_SETTING}`: {error}")),
+            },
+            Err(error) => problems.push(error.to_string()),
+        }
+
+        match notification_filter(config) {
+            Ok(filter) => self.notifications().set_filter(filter),
+            Err(message) => problems.push(message),
+        }
+
+        match config.get_string(LOG_LEVEL_SETTING) {
+            Ok(level) => match level.map(str::parse).transpose() {
+                Ok(level) => self.logs().set_level(level.unwrap_or_default()),
+                Err(error) => problems.push(format!("`{LOG_LEVEL_SETTING}`: {error}")),
+            },
+            Err(error) => problems.push(error.to_string()),
+        }
+
+        self.problem_patterns.clear();
+        match config.problem_matchers() {
+            Ok(matchers) => {
+                for matcher in matchers {
+                    match ProblemPattern::parse(
+                        matcher.name,
+                        &matcher.regex,
+                        matcher.severity.as_deref(),
+                    ) {
+                        Ok(pattern) => self.problem_patterns.push(pattern),
+                        Err(error) => problems.push(error.to_string()),
+                    }
+                }
+            }
+            Err(error) => problems.push(error.to_string()),
+        }
+
+        match config.tasks() {
+            Ok(configs) => {
+                let mut tasks = Vec::new();
+                for task in configs {
+                    let output = match task.output.as_deref().map(str::parse).transpose() {
+                        Ok(output) => output.unwrap_or_default(),
+                        Err(error) => {
+                            problems.push(format!("`tasks.{}.output`: {error}", task.name));
+                            continue;
+                        }
+                    };
+                    tasks.push(TaskDefinition {
+                        output,
+                        ..TaskDefinition::new(task.name, task.command)
+                    });
+                }
+                self.task_runner().set_tasks(tasks);
+            }
+            Err(error) => {
+                self.task_runner().set_tasks(Vec::new());
+                problems.push(error.to_string());
+            }
+        }
+
+        self.config_abbreviations = Abbreviations::new();
+        match config.abbreviations() {
+            Ok(abbreviations) => {
+                for abbreviation in abbreviations {
+                    if !abbreviation.word.chars().all(abbreviation::is_word_char) {
+                        problems.push(format!(
+                            "abbreviation `{}` is not a word",
+                            abbreviation.word
+                        ));
+                        continue;
+                    }
+                    self.config_abbreviations.insert(
+                        abbreviation.language.as_deref(),
+                        abbreviation.word,
+                        abbreviation.expansion,
+                    );
+                }
+            }
+            Err(error) => problems.push(error.to_string()),
+        }
+        self.refresh_abbreviations();
+
+        for (context, key, previous) in self.config_bindings.drain(..).rev() {
+            match (context, previous) {
+                (None, Some(action)) => self.keybinding_router.register_global(key, action),
+                (None, None) => {
+                    self.keybinding_router.unregister_global(key);
+                }
+                (Some(context), Some(action)) => self
+                    .keybinding_router
+                    .register_for_context(context, key, action),
+                (Some(context), None) => {
+                    self.keybinding_router.unregister_for_context(context, key);
+                }
+            }
+        }
+        for (context, key, when) in self.config_when_bindings.drain(..) {
+            self.keybinding_router.unregister_when(context, key, &when);
+        }
+        let bindings = match config.keybindings() {
+            Ok(bindings) => bindings,
+            Err(error) => {
+                problems.push(error.to_string());
+                Vec::new()
+            }
+        };
+        for binding in bindings {
+            let context = match binding.context.as_deref() {
+                None => None,
+                Some(name) => match WindowContext::from_name(name) {
+                    Some(context) => Some(context),
+                    None => {
+                        problems.push(format!("unknown keybinding context `{name}`"));
+                        continue;
+                    }
+                },
+            };
+            let Some(key) = buffer_options::parse_key(&binding.key) else {
+                problems.push(format!("unknown key `{}`", binding.key));
+                continue;
+            };
+            let Some(action) = buffer_options::parse_action(&binding.action) else {
+                problems.push(format!("unknown action `{}`", binding.action));
+                continue;
+            };
+            if let Some(when) = &binding.when {
+                match ContextKeyExpr::parse(when) {
+                    Ok(when) => {
+                        self.keybinding_router
+                            .register_when(context, key, when.clone(), action);
+                        self.config_when_bindings.push((context, key, when));
+                    }
+                    Err(error) => {
+                        problems.push(format!("`when` clause of key `{}`: {error}", binding.key));
+                    }
+                }
+                continue;
+            }
+            let previous = match context {
+                None => self.keybinding_router.global_bindings().get(&key).copied(),
+                Some(context) => self
+                    .keybinding_router
+                    .context_bindings(context)
+                    .and_then(|bindings| bindings.get(&key))
+                    .copied(),
+            };
+            match context {
+                None => self.keybinding_router.register_global(key, action),
+                Some(context) => self
+                    .keybinding_router
+                    .register_for_context(context, key, action),
+            }
+            self.config_bindings.push((context, key, previous));
+        }
+
+        problems
+    }
+
+    /// Resolve the options of the editor `id` for the file it shows: the
+    /// workspace option layer, the `[editor]` settings of the workspace
+    /// folder holding the file and the file's modeline, each `[editor]`
+    /// section overridden by the `[editor.<language>]` section for the
+    /// file's language.
+    ///
+    /// Returns a message for each problem; nothing is done for other
+    /// windows.
+    fn resolve_editor_options(&mut self, id: WindowId) -> Vec<String> {
+        let Some((path, language)) = self
+            .windows
+            .window::<EditorWindow>(id)
+            .map(|editor| (editor.path(), editor.effective_language()))
+        else {
+            return Vec::new();
+        };
+        let mut problems = Vec::new();
+        let folder = path
+            .zip(self.services.resolve::<ConfigService>())
+            .and_then(|(path, service)| {
+                let path = fs::canonicalize(&path).unwrap_or(path);
+                service.folder_config(&path)
+            })
+            .and_then(|config| {
+                let overrides = OptionOverrides::from_config(&config).and_then(|overrides| {
+                    let languages = OptionOverrides::languages_from_config(&config)?;
+                    Ok(
+                        match language.as_ref().and_then(|name| languages.get(name)) {
+                            Some(layer) => overrides.merged_with(layer),
+                            None => overrides,
+                        },
+                    )
+                });
+                match overrides {
+                    Ok(overrides) => Some(overrides),
+                    Err(message) => {
+                        problems.push(message);
+                        None
+                    }
+                }
+            });
+        let workspace = match language.and_then(|name| self.language_options.get(&name)) {
+            Some(layer) => self.workspace_options.merged_with(layer),
+            None => self.workspace_options.clone(),
+        };
+        if let Some(editor) = self.windows.window_mut::<EditorWindow>(id) {
+            if let Err(error) = editor.resolve_options(&workspace, folder.as_ref()) {
+                problems.push(format!("modeline {error}"));
+            }
+        }
+        problems
+    }
+
+    /// Reload the configuration if its file changed, then apply reloads
+    /// since the last call.
+    fn process_config_changes(&mut self) {
+        let file_changed = self
+            .config_watch
+            .as_ref()
+            .is_some_and(FileWatches::take_changes);
+        if file_changed {
+            if let Some(service) = self.services.resolve::<ConfigService>() {
+                if let Err(error) = service.reload() {
+                    self.report_config_problems(&[error.to_string()]);
+                }
+            }
+        }
+
+        let Some(changes) = &self.config_changes else {
+            return;
+        };
+        let Some(change) = changes.try_iter().last() else {
+            return;
+        };
+        let problems = self.apply_config(&change.config);
+        self.report_config_problems(&problems);
+        self.rewatch_config();
+        self.dirty = true;
+    }
+
+    /// Show configuration problems in the status bar, or clear the segment
+    /// if there are none.
+    fn report_config_problems(&self, problems: &[String]) {
+        for problem in problems {
+            tracing::warn!("config: {problem}");
+        }
+        let update = match problems {
+            [] => StatusBarUpdate::RemoveSegment(CONFIG_SEGMENT.to_string()),
+            [problem] => StatusBarUpdate::SetSegment {
+                id: CONFIG_SEGMENT.to_string(),
+                text: format!("Config: {problem}"),
+            },
+            [problem, rest @ ..] => StatusBarUpdate::SetSegment {
+                id: CONFIG_SEGMENT.to_string(),
+                text: format!("Config: {problem} (+{} more)", rest.len()),
+            },
+        };
+        self.status_bar_updates.emit(update);
+    }
+
+    /// Get the status bar.
+    ///
+    /// Its contents reflect the state after the most recent event.
+    pub fn status_bar(&self) -> &StatusBarWindow {
+        &self.status_bar
+    }
+
+    /// Add `item` to the status bar, replacing an item with the same id.
+    ///
+    /// Unlike segments, items compute their own text whenever the status
+    /// bar refreshes, and clicking one runs its command.
+    pub fn add_status_bar_item(&mut self, item: StatusBarItem) {
+        self.status_bar.add_item(item);
+    }
+
+    /// Remove the status bar item `id`, returning whether it was present.
+    pub fn remove_status_bar_item(&mut self, id: &str) -> bool {
+        self.status_bar.remove_item(id)
+    }
+
+    /// Run the command of the status bar item at `column`, if it has one.
+    fn click_status_bar(&mut self, column: u16) {
+        let Some(command) = self
+            .status_bar
+            .item_at(column, self.width)
+            .and_then(|item| item.command.clone())
+        else {
+            return;
+        };
+        if let Err(error) = self.execute_command(&command) {
+            tracing::warn!("cannot run the status bar command {command}: {error}");
+        }
+    }
+
+    /// Get the event on which components publish status bar segments.
+    ///
+    /// Emitted updates are applied when the App next handles an event or
+    /// renders.
+    pub fn status_bar_updates(&self) -> Event<StatusBarUpdate> {
+        self.status_bar_updates.clone()
+    }
+
+    /// Get the layer that currently receives keys.
+    pub fn keybinding_mode(&self) -> KeybindingMode {
+        if self.dialog.is_some() {
+            KeybindingMode::Dialog
+        } else if self.command_palette.is_some() {
+            KeybindingMode::CommandPalette
+        } else if self.quick_fix.is_some() {
+            KeybindingMode::QuickFix
+        } else if self.context_menu.is_some() {
+            KeybindingMode::ContextMenu
+        } else if self.quick_open.is_some() {
+            KeybindingMode::QuickOpen
+        } else if self.branch_picker.is_some() {
+            KeybindingMode::BranchPicker
+        } else if self.stash_picker.is_some() {
+            KeybindingMode::StashPicker
+        } else if self.task_picker.is_some() {
+            KeybindingMode::TaskPicker
+        } else if self.command_line.is_some() {
+            KeybindingMode::CommandLine
+        } else if self.input_box.is_some() {
+            KeybindingMode::Input
+        } else if self.editor().rename_input().is_some() {
+            KeybindingMode::Rename
+        } else if self.focused_context() == Some(WindowContext::Editor)
+            && self
+                .windows
+                .window::<EditorWindow>(self.target_editor())
+                .and_then(EditorWindow::find)
+                .is_some_and(FindWindow::is_editing)
+        {
+            KeybindingMode::Find
+        } else if self.focused_context() == Some(WindowContext::Editor)
+            && self.editor().peek().is_some()
+        {
+            KeybindingMode::Peek
+        } else if self.focused_context() == Some(WindowContext::Explorer)
+            && self
+                .explorer()
+                .is_some_and(|explorer| explorer.filter().is_some())
+        {
+            KeybindingMode::Filter
+        } else if (self.focused_context() == Some(WindowContext::Search)
+            && self.search_panel().is_some_and(SearchWindow::is_editing))
+            || (self.search_results_focused()
+                && self
+                    .search_results()
+                    .is_some_and(SearchResultsWindow::is_editing))
+        {
+            KeybindingMode::Search
+        } else if self.regex_tester_focused() {
+            KeybindingMode::Input
+        } else if self.active_editing_mode() == Some(EditingMode::Insert) {
+            KeybindingMode::Insert
+        } else if self.active_editing_mode() == Some(EditingMode::Visual) {
+            KeybindingMode::Visual
+        } else {
+            KeybindingMode::Normal
+        }
+    }
+
+    /// Set the provider used to compute code actions for the quick-fix menu.
+    pub fn set_code_action_provider(&mut self, provider: Box<dyn CodeActionProvider>) {
+        self.code_action_provider = Some(provider);
+    }
+
+    /// Get the quick-fix menu, if open.
+    pub fn quick_fix_menu(&self) -> Option<&QuickPickWindow> {
+        self.quick_fix.as_ref().map(|menu| &menu.picker)
+    }
+
+    /// Set the provider used to resolve definitions for peek definition.
+    pub fn set_definition_provider(&mut self, provider: Box<dyn DefinitionProvider>) {
+        self.definition_provider = Some(provider);
+    }
+
+    /// Set the provider used to validate and compute symbol renames.
+    pub fn set_rename_provider(&mut self, provider: Box<dyn RenameProvider>) {
+        self.rename_provider = Some(provider);
+    }
+
+    /// Set the provider of document links, e.g. a language server's
+    /// `textDocument/documentLink`, used instead of detecting links in the
+    /// text of documents with a path.
+    pub fn set_document_link_provider(&mut self, provider: Box<dyn DocumentLinkProvider>) {
+        self.document_link_provider = Some(provider);
+        self.document_links_requested = None;
+    }
+
+    /// Apply a workspace edit to the open documents.
+    ///
+    /// Nothing is modified if any part of the edit cannot be applied.
+    pub fn apply_workspace_edit(&mut self, edit: &WorkspaceEdit) -> Result<(), EditError> {
+        let editor = self
+            .windows
+            .window_mut::<EditorWindow>(self.editor_id)
+            .expect("the editor window is never closed");
+        edit.apply(&mut OpenDocuments {
+            buffers: &mut self.buffers,
+            editor,
+        })
+    }
+
+    /// Activate `plugins` and apply what they contribute: register their
+    /// commands, a command showing and hiding each of their windows, and
+    /// their keybindings.
+    ///
+    /// Load plugins before applying the configuration, so the user's
+    /// keybindings win over theirs. Returns the problems found, such as a
+    /// command ID already taken or a keybinding running an unknown command;
+    /// the rest of the contributions are still applied.
+    pub fn load_plugins(&mut self, plugins: Vec<Box<dyn Plugin>>) -> Vec<String> {
+        let mut problems = Vec::new();
+        let commands = self
+            .services
+            .resolve::<CommandService>()
+            .expect("CommandService is registered in App::new");
+        for mut plugin in plugins {
+            let first_command = self.plugin_registry.commands().len();
+            let first_window = self.plugin_registry.windows().len();
+            let first_binding = self.plugin_registry.keybindings().len();
+            let services = Arc::clone(&self.services);
+            plugin.activate(&services, &mut self.plugin_registry);
+
+            for command in &self.plugin_registry.commands()[first_command..] {
+                if commands.contains(&command.id) {
+                    problems.push(format!(
+                        "plugin `{}`: command `{}` is already registered",
+                        plugin.id(),
+                        command.id
+                    ));
+                    continue;
+                }
+                let handler = Arc::clone(&command.handler);
+                commands.register(&command.id, &command.title, move || handler());
+            }
+            let windows = self.plugin_registry.windows().iter().enumerate();
+            for (index, window) in windows.skip(first_window) {
+                if commands.contains(&window.id) {
+                    problems.push(format!(
+                        "plugin `{}`: command `{}` is already registered",
+                        plugin.id(),
+                        window.id
+                    ));
+                    continue;
+                }
+                let actions = self.actions.clone();
+                commands.register(&window.id, &window.title, move || {
+                    actions.emit(Action::TogglePluginWindow(index))
+                });
+            }
+            let bindings = self.plugin_registry.keybindings().iter().enumerate();
+            for (index, binding) in bindings.skip(first_binding) {
+                if !commands.contains(&binding.command) {
+                    problems.push(format!(
+                        "plugin `{}`: unknown command `{}`",
+                        plugin.id(),
+                        binding.command
+                    ));
+                    continue;
+                }
+                let action = Action::RunPluginCommand(index);
+                match binding.context {
+                    None => self.keybinding_router.register_global(binding.key, action),
+                    Some(context) => {
+                        self.keybinding_router
+                            .register_for_context(context, binding.key, action)
+                    }
+                }
+            }
+            self.plugins.push(plugin);
+        }
+        problems
+    }
+
+    /// Get the IDs of the loaded plugins, in load order.
+    pub fn plugin_ids(&self) -> Vec<&str> {
+        self.plugins.iter().map(|plugin| plugin.id()).collect()
+    }
+
+    /// Open the plugin window with `index` in the registry as a new column
+    /// and focus it, or close it if it is open.
+    ///
+    /// Returns whether the window is open afterwards.
+    fn toggle_plugin_window(&mut self, index: usize) -> bool {
+        if let Some(id) = self.plugin_windows.remove(&index) {
+            self.close_window(id);
+            return false;
+        }
+        let Some(contribution) = self.plugin_registry.windows().get(index) else {
+            return false;
+        };
+        let window = (contribution.factory)(&self.services);
+        let id = self.open_window(window);
+        self.plugin_windows.insert(index, id);
+        true
+    }
+
+    /// Execute a registered command by identifier.
+    ///
+    /// Any actions requested by the command's handler are applied before
+    /// this returns.
+    pub fn execute_command(&mut self, id: &str) -> Result<(), CommandError> {
+        let commands = self
+            .services
+            .resolve::<CommandService>()
+            .expect("CommandService is registered in App::new");
+        let result = commands.execute(id);
+        while let Ok(action) = self.action_requests.try_recv() {
+            self.execute_action(action);
+        }
+        self.process_focus_changes();
+        result
+    }
+
+    /// Get the current terminal dimensions.
+    pub fn size(&self) -> (u16, u16) {
+        (self.width, self.height)
+    }
+
+    /// Handle an application event.
+    ///
+    /// This is the main entry point for input handling. Events are processed
+    /// and may update application state.
+    pub fn handle_event(&mut self, event: AppEvent) {
+        match event {
+            AppEvent::Key(key) => {
+                self.dirty = true;
+                self.handle_key(key);
+            }
+            AppEvent::Mouse(mouse) => {
+                self.dirty = true;
+                self.handle_mouse(mouse);
+            }
+            AppEvent::Paste(text) => {
+                self.dirty = true;
+                self.handle_paste(&text);
+            }
+            AppEvent::FocusGained => self.window_focused = true,
+            AppEvent::FocusLost => {
+                self.window_focused = false;
+                if self.auto_save == AutoSave::OnFocusChange {
+                    if let Some(id) = self.focus_manager.focused() {
+                        self.auto_save_editor(id);
+                    }
+                }
+            }
+            AppEvent::Other => {}
+            AppEvent::Resize(w, h) => {
+                self.dirty |= (w, h) != (self.width, self.height);
+                self.width = w;
+                self.height = h;
+            }
+            AppEvent::Tick => {
+                // Periodic callbacks may change what is shown
+                self.dirty |= self.ticks().run_due(Instant::now()) > 0;
+                let notifications = self.notifications();
+                self.dirty |= !notifications.expire(Instant::now()).is_empty();
+                let last = notifications.last_id();
+                self.dirty |= last != self.last_notification;
+                self.last_notification = last;
+            }
+        }
+        self.process_config_changes();
+        self.process_file_operations();
+        self.process_task_output();
+        self.process_search_events();
+        self.process_todos();
+        self.process_log_records();
+        self.process_notifications();
+        self.process_focus_changes();
+        self.process_buffer_changes();
+        self.process_git_changes();
+        self.process_mirror();
+        self.process_markdown_preview();
+        self.process_undo_tree();
+        self.process_tutorial();
+        self.refresh_status_bar();
+    }
+
+    /// Let every editor follow the changes other editors made to the
+    /// documents it shows.
+    fn process_buffer_changes(&mut self) {
+        for id in self.windows.ids() {
+            if let Some(editor) = self.windows.window_mut::<EditorWindow>(id) {
+                editor.follow_changes();
+            }
+        }
+    }
+
+    /// Announce saves to the git service, and read the git state again
+    /// once a batch of changes to it arrives: the branch, the gutters and
+    /// the open git views.
+    fn process_git_changes(&mut self) {
+        let git = self.git();
+        for event in self.buffer_events.try_iter() {
+            let uri = match event {
+                BufferEvent::Saved(uri) | BufferEvent::Renamed { to: uri, .. } => uri,
+                BufferEvent::Opened(_) | BufferEvent::Closed(_) => continue,
+            };
+            if let Some(path) = uri.path() {
+                git.file_changed(path);
+            }
+        }
+        let changes: Vec<FileChanged> = self.git_changes.batches.try_iter().flatten().collect();
+        if changes.is_empty() {
+            return;
+        }
+        self.dirty = true;
+        self.refresh_branch();
+        self.refresh_line_changes();
+        self.refresh_git();
+        // A checkout, reset or stash may have changed any file
+        let work_tree_changed = changes.iter().any(|change| {
+            self.git_changes
+                .watchers
+                .iter()
+                .any(|watcher| watcher.path() == change.path)
+        });
+        if work_tree_changed {
+            self.scan_todos();
+        }
+    }
+
+    /// Handle focus changes since the last call.
+    ///
+    /// With [`AutoSave::OnFocusChange`], a dirty editor that lost focus is
+    /// saved. A failed save is reported in the status bar's `autoSave`
+    /// segment, which is cleared by the next successful save.
+    fn process_focus_changes(&mut self) {
+        while let Ok(change) = self.focus_changes.try_recv() {
+            if self.auto_save != AutoSave::OnFocusChange {
+                continue;
+            }
+            if let Some(id) = change.previous {
+                self.auto_save_editor(id);
+            }
+        }
+    }
+
+    /// Save the window `id` if it is a dirty editor with a file, reporting
+    /// a failure in the status bar's `autoSave` segment.
+    fn auto_save_editor(&mut self, id: WindowId) {
+        let Some(editor) = self.windows.window_mut::<EditorWindow>(id) else {
+            return;
+        };
+        let Some(path) = editor.path().filter(|_| editor.is_dirty()) else {
+            return;
+        };
+        let saved = if self.buffers.contains(&path) {
+            self.buffers.save(&path)
+        } else {
+            editor.save()
+        };
+        let update = match saved {
+            Ok(()) => StatusBarUpdate::RemoveSegment(AUTO_SAVE_SEGMENT.to_string()),
+            Err(error) => {
+                tracing::error!(path = %path.display(), "auto-save failed: {error}");
+                StatusBarUpdate::SetSegment {
+                    id: AUTO_SAVE_SEGMENT.to_string(),
+                    text: format!("Auto-save failed: {error}"),
+                }
+            }
+        };
+        self.status_bar_updates.emit(update);
+    }
+
+    /// Name the focused window, as the status bar shows it.
+    fn focused_label(&self) -> &'static str {
+        match self.focused_context() {
+            Some(context) => context.label(),
+            None if self.regex_tester_focused() => "Regex Tester",
+            None if self.undo_tree_focused() => "Undo Tree",
+            None if self.snippets_focused() => "Snippets",
+            None if self.todos_focused() => "TODOs",
+            None => "",
+        }
+    }
+
+    /// Update the status bar from the App state and apply pending segment
+    /// updates.
+    fn refresh_status_bar(&mut self) {
+        while let Ok(update) = self.status_bar_requests.try_recv() {
+            self.status_bar.apply(update);
+            self.dirty = true;
+        }
+        self.status_bar.set_focused(self.focused_label());
+        self.status_bar.set_mode(self.keybinding_mode().label());
+        let cursor = self
+            .windows
+            .window::<EditorWindow>(self.target_editor())
+            .map(EditorWindow::cursor_position);
+        self.status_bar.set_cursor(cursor);
+        self.status_bar.set_size(self.width, self.height);
+        let progress: Vec<ProgressItem> = self
+            .progress()
+            .active()
+            .into_iter()
+            .filter(|item| item.location == ProgressLocation::StatusBar)
+            .collect();
+        self.status_bar.apply(match progress_text(&progress) {
+            Some(text) => StatusBarUpdate::SetSegment {
+                id: PROGRESS_SEGMENT.to_string(),
+                text,
+            },
+            None => StatusBarUpdate::RemoveSegment(PROGRESS_SEGMENT.to_string()),
+        });
+        let editor = self
+            .windows
+            .window::<EditorWindow>(self.target_editor())
+            .filter(|_| self.word_count);
+        self.status_bar.apply(match editor {
+            Some(editor) => StatusBarUpdate::SetSegment {
+                id: WORD_COUNT_SEGMENT.to_string(),
+                text: self
+                    .word_counter
+                    .update(editor.buffer(), editor.selection())
+                    .to_string(),
+            },
+            None => StatusBarUpdate::RemoveSegment(WORD_COUNT_SEGMENT.to_string()),
+        });
+        self.status_bar.refresh_items();
+    }
+
+    /// Handle a key press using the keybinding router.
+    ///
+    /// While the command palette or quick-fix menu is open it receives every
+    /// key. Otherwise,
+    /// the editor buffer's local bindings (when the editor is focused) win over
+    /// bindings for the focused window's context, which win over globals.
+    /// With modal editing on, the focused editor's mode bindings win over
+    /// all of them, and Insert mode types the keys they leave unbound.
+    fn handle_key(&mut self, key: AppKey) {
+        if let Some(open) = self.dialog.as_mut() {
+            if let Some(result) = open.dialog.handle_key(key) {
+                let purpose = open.purpose;
+                self.dialog = None;
+                self.finish_dialog(purpose, result);
+            }
+            return;
+        }
+
+        if let Some(palette) = self.command_palette.as_mut() {
+            match palette.handle_key(key) {
+                PaletteOutcome::Pending => {}
+                PaletteOutcome::Dismissed => self.command_palette = None,
+                PaletteOutcome::Execute(id) => {
+                    self.command_palette = None;
+                    // The palette only lists registered commands
+                    let _ = self.execute_command(&id);
+                }
+            }
+            return;
+        }
+
+        if let Some(menu) = self.quick_fix.as_mut() {
+            match menu.picker.handle_key(key) {
+                QuickPickOutcome::Pending => {}
+                QuickPickOutcome::Dismissed => self.quick_fix = None,
+                QuickPickOutcome::Accept(index) => {
+                    let action = menu.actions.swap_remove(index);
+                    self.quick_fix = None;
+                    // A failed edit leaves the documents untouched
+                    let _ = self.apply_workspace_edit(&action.edit);
+                }
+            }
+            return;
+        }
+
+        if let Some(open) = self.context_menu.as_mut() {
+            match open.menu.handle_key(key) {
+                ContextMenuOutcome::Pending => {}
+                ContextMenuOutcome::Dismissed => self.context_menu = None,
+                ContextMenuOutcome::Execute(id) => {
+                    self.context_menu = None;
+                    self.run_menu_command(&id);
+                }
+            }
+            return;
+        }
+
+        if let Some(menu) = self.quick_open.as_mut() {
+            match menu.picker.handle_key(key) {
+                QuickPickOutcome::Pending => {}
+                QuickPickOutcome::Dismissed => self.quick_open = None,
+                QuickPickOutcome::Accept(index) => {
+                    let path = menu.paths.swap_remove(index);
+                    self.quick_open = None;
+                    if let Err(error) = self.open_file(&path) {
+                        self.report_files_error(format!("Cannot open {}: {error}", path.display()));
+                    }
+                }
+            }
+            return;
+        }
+
+        if let Some(menu) = self.branch_picker.as_mut() {
+            match menu.picker.handle_key(key) {
+                QuickPickOutcome::Pending => {}
+                QuickPickOutcome::Dismissed => self.branch_picker = None,
+                QuickPickOutcome::Accept(index) => {
+                    let pick = menu.picks.swap_remove(index);
+                    let names =
+                        WordCompleter::new(menu.picks.iter().filter_map(|pick| match pick {
+                            BranchPick::Checkout(branch) => Some(branch.local_name()),
+                            BranchPick::Create => None,
+                        }));
+                    self.branch_picker = None;
+                    match pick {
+                        BranchPick::Create => {
+                            self.input_box = Some(PendingInput {
+                                input: InputBoxWindow::new("New Branch", "").with_completer(names),
+                                purpose: InputPurpose::NewBranch,
+                            });
+                        }
+                        BranchPick::Checkout(branch) => {
+                            let checked_out = self
+                                .repository()
+                                .and_then(|repository| repository.checkout(&branch));
+                            match checked_out {
+                                Ok(()) => self.after_work_tree_change(),
+                                Err(error) => self.report_git_error(&error),
+                            }
+                        }
+                    }
+                }
+            }
+            return;
+        }
+
+        if let Some(menu) = self.stash_picker.as_mut() {
+            match menu.picker.handle_key(key) {
+                QuickPickOutcome::Pending => {}
+                QuickPickOutcome::Dismissed => self.stash_picker = None,
+                QuickPickOutcome::Accept(index) => {
+                    let stash = menu.stashes.swap_remove(index);
+                    self.stash_picker = None;
+                    self.pop_stash(&stash);
+                }
+            }
+            return;
+        }
+
+        if let Some(menu) = self.task_picker.as_mut() {
+            match menu.picker.handle_key(key) {
+                QuickPickOutcome::Pending => {}
+                QuickPickOutcome::Dismissed => self.task_picker = None,
+                QuickPickOutcome::Accept(index) => {
+                    let task = menu.tasks.swap_remove(index);
+                    self.task_picker = None;
+                    self.start_configured_task(&task);
+                }
+            }
+            return;
+        }
+
+        if self.command_line.is_some() {
+            self.handle_command_line_key(key);
+            return;
+        }
+
+        if self.input_box.is_some() {
+            self.handle_input_key(key);
+            return;
+        }
+
+        if self.editor().rename_input().is_some() {
+            self.handle_rename_key(key);
+            return;
+        }
+
+        let context = self.focused_context();
+        if context == Some(WindowContext::Editor) && self.handle_find_key(key) {
+            return;
+        }
+        if context == Some(WindowContext::Editor) && self.handle_peek_key(key) {
+            return;
+        }
+        if context == Some(WindowContext::Explorer) && self.handle_explorer_key(key) {
+            return;
+        }
+        if context == Some(WindowContext::Search) && self.handle_search_key(key) {
+            return;
+        }
+        if context == Some(WindowContext::Git) && self.handle_git_panel_key(key) {
+            return;
+        }
+        if context == Some(WindowContext::Diff) && self.handle_diff_key(key) {
+            return;
+        }
+        if context == Some(WindowContext::GitLog) && self.handle_git_log_key(key) {
+            return;
+        }
+        if context == Some(WindowContext::Problems) && self.handle_problems_key(key) {
+            return;
+        }
+        if context == Some(WindowContext::Logs) && self.handle_logs_key(key) {
+            return;
+        }
+        if context == Some(WindowContext::MarkdownPreview) && self.handle_markdown_preview_key(key)
+        {
+            return;
+        }
+        if self.regex_tester_focused() && self.handle_regex_tester_key(key) {
+            return;
+        }
+        if self.undo_tree_focused() && self.handle_undo_tree_key(key) {
+            return;
+        }
+        if self.snippets_focused() && self.handle_snippets_key(key) {
+            return;
+        }
+        if self.search_results_focused() && self.handle_search_results_key(key) {
+            return;
+        }
+        if self.todos_focused() && self.handle_todos_key(key) {
+            return;
+        }
+        if self.output_focused()
+            && self
+                .output_pane_mut()
+                .is_some_and(|output| output.handle_key(key))
+        {
+            return;
+        }
+        self.update_context_keys();
+        let conditional = self
+            .keybinding_router
+            .dispatch_when(&self.context_keys(), context, key);
+        let action = if conditional.is_some() {
+            conditional
+        } else if let Some(mode) = self.active_editing_mode() {
+            let action = self.keybinding_router.dispatch_in_mode(
+                mode,
+                &self.editor().options().keybindings,
+                key,
+            );
+            if action.is_none() && mode == EditingMode::Insert {
+                self.type_key(key);
+            }
+            action
+        } else if context == Some(WindowContext::Editor) {
+            self.keybinding_router.dispatch_with_buffer(
+                &self.editor().options().keybindings,
+                context,
+                key,
+            )
+        } else {
+            self.keybinding_router.dispatch_in_context(context, key)
+        };
+        if let Some(action) = action {
+            self.execute_action(action);
+        }
+        // Keys not bound to actions are ignored (could be forwarded to focused window)
+    }
+
+    /// Set the App's context keys from the focus and the editor:
+    ///
+    /// - `<context>Focused`, e.g. `editorFocused` or `gitLogFocused`, for
+    ///   each window context, true for the focused window's
+    /// - `mode`: `normal`, `insert` or `visual` while modal editing is on
+    ///   and an editor has focus, unset otherwise
+    /// - `editorLangId`: the editor's language, unset without one
+    /// - `editorHasSelection`: whether the editor has a selection
+    /// - `windowFocused`: whether the terminal window the App runs in has
+    ///   focus
+    pub fn update_context_keys(&self) {
+        let keys = self.context_keys();
+        keys.set("windowFocused", self.window_focused);
+        let focused = self.focused_context();
+        for context in WindowContext::ALL {
+            keys.set(
+                format!("{}Focused", context.name()),
+                focused == Some(context),
+            );
+        }
+        match self.active_editing_mode() {
+            Some(mode) => keys.set("mode", mode.label().to_lowercase()),
+            None => {
+                keys.remove("mode");
+            }
+        }
+        let editor = self.editor();
+        match editor.effective_language() {
+            Some(language) => keys.set("editorLangId", language),
+            None => {
+                keys.remove("editorLangId");
+            }
+        }
+        keys.set("editorHasSelection", !editor.selection().is_empty());
+    }
+
+    /// Handle a key while the focused editor's find input is open,
+    /// returning whether it was consumed.
+    ///
+    /// While the input edits its query it takes every key except `Tab`;
+    /// otherwise it only takes `Esc`, which closes it, and leaves `n`/`N`
+    /// to their bindings.
+    fn handle_find_key(&mut self, key: AppKey) -> bool {
+        let id = self.target_editor();
+        let Some(editor) = self.windows.window_mut::<EditorWindow>(id) else {
+            return false;
+        };
+        let Some(find) = editor.find() else {
+            return false;
+        };
+        if find.is_editing() && key != AppKey::Tab {
+            editor.handle_find_key(key);
+            true
+        } else if key == AppKey::Esc {
+            editor.close_find();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Handle a key while the command line is open.
+    ///
+    /// An accepted line is remembered and run. If running it fails, the
+    /// command line stays open and shows the error.
+    fn handle_command_line_key(&mut self, key: AppKey) {
+        let Some(outcome) = self
+            .command_line
+            .as_mut()
+            .map(|command_line| command_line.handle_key(key))
+        else {
+            return;
+        };
+        match outcome {
+            InputOutcome::Pending => {}
+            InputOutcome::Dismissed => self.command_line = None,
+            InputOutcome::Accept(line) => {
+                let Some(mut command_line) = self.command_line.take() else {
+                    return;
+                };
+                if line.trim().is_empty() {
+                    return;
+                }
+                if self.command_line_history.last() != Some(&line) {
+                    self.command_line_history.push(line.clone());
+                }
+                if let Err(error) = self.run_ex_command(&line) {
+                    command_line.set_error(Some(error));
+                    self.command_line = Some(command_line);
+                }
+            }
+        }
+    }
+
+    /// Handle a key while the input box is open.
+    ///
+    /// If acting on the accepted value fails, the input stays open and
+    /// shows the error.
+    fn handle_input_key(&mut self, key: AppKey) {
+        let Some(pending) = self.input_box.as_mut() else {
+            return;
+        };
+        match pending.input.handle_key(key) {
+            InputOutcome::Pending => {}
+            InputOutcome::Dismissed => self.input_box = None,
+            InputOutcome::Accept(value) => {
+                // Taken while acting, so acting can open the next prompt
+                let Some(mut pending) = self.input_box.take() else {
+                    return;
+                };
+                let result = match pending.purpose.clone() {
+                    InputPurpose::SaveAs(id) => self
+                        .save_editor_as(id, &value)
+                        .map_err(|error| error.to_string()),
+                    InputPurpose::Export(id, format) => self
+                        .export_editor(id, format, &value)
+                        .map_err(|error| error.to_string()),
+                    InputPurpose::CompareWith(id) => {
+                        let path = self.workspace.primary().join(value.trim());
+                        self.compare_editor_with_file(id, &path)
+                            .map_err(|error| format!("Cannot read {}: {error}", path.display()))
+                    }
+                    InputPurpose::NewFile(dir) => self.create_explorer_entry(&dir, &value, false),
+                    InputPurpose::NewFolder(dir) => self.create_explorer_entry(&dir, &value, true),
+                    InputPurpose::Rename(path) => self.rename_explorer_entry(&path, &value),
+                    InputPurpose::NewBranch => self.create_branch(&value),
+                    InputPurpose::StashMessage => {
+                        self.stash_changes(value.trim());
+                        Ok(())
+                    }
+                    InputPurpose::RootFolder => self.add_root_folder_from_input(&value),
+                    InputPurpose::NewSnippet => snippet::parse_trigger(&value)
+                        .map(|(language, word)| self.prompt_snippet_body(language, word, "")),
+                    InputPurpose::SnippetBody(language, word) => {
+                        let body = snippet::unescape_body(&value);
+                        self.set_snippet(language.as_deref(), &word, &body)
+                    }
+                };
+                if let Err(error) = result {
+                    pending.input.set_error(Some(error));
+                    self.input_box = Some(pending);
+                }
+            }
+        }
+    }
+
+    /// Handle a key while the editor's rename input is open.
+    ///
+    /// The name is revalidated after every edit. Accepting applies the
+    /// provider's rename edit; if computing or applying it fails, the input
+    /// stays open and shows the error.
+    fn handle_rename_key(&mut self, key: AppKey) {
+        // Borrow the window manager directly so the provider stays readable
+        let Some(input) = self
+            .windows
+            .window_mut::<EditorWindow>(self.editor_id)
+            .and_then(EditorWindow::rename_input_mut)
+        else {
+            return;
+        };
+        match input.handle_key(key) {
+            RenameOutcome::Pending => {
+                let error = self
+                    .rename_provider
+                    .as_ref()
+                    .and_then(|provider| provider.validate_name(input.name()).err());
+                input.set_error(error);
+            }
+            RenameOutcome::Dismissed => self.editor_mut().close_rename(),
+            RenameOutcome::Accept(name) => {
+                let offset = input.range().start;
+                match self.rename_edit(offset, &name) {
+                    Ok(()) => self.editor_mut().close_rename(),
+                    Err(error) => {
+                        if let Some(input) = self.editor_mut().rename_input_mut() {
+                            input.set_error(Some(error));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Compute and apply the edit renaming the symbol at `offset`.
+    fn rename_edit(&mut self, offset: usize, name: &str) -> Result<(), String> {
+        let (Some(provider), Some(path)) = (&self.rename_provider, self.editor().path()) else {
+            return Err("Rename is not available".to_string());
+        };
+        let edit = provider.rename(&path, &self.editor().text(), offset, name)?;
+        self.apply_workspace_edit(&edit)
+            .map_err(|error| error.to_string())
+    }
+
+    /// Handle keys for the editor's peek window, if one is open.
+    ///
+    /// `Esc` closes the peek and `Up`/`Down` scroll it. Returns whether the
+    /// key was consumed; other keys fall through to the normal bindings.
+    fn handle_peek_key(&mut self, key: AppKey) -> bool {
+        let Some(peek) = self.editor_mut().peek_mut() else {
+            return false;
+        };
+        match key {
+            AppKey::Esc => self.editor_mut().close_peek(),
+            AppKey::Up => peek.scroll_by(-1),
+            AppKey::Down => peek.scroll_by(1),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Handle keys for the focused explorer.
+    ///
+    /// Navigation, marking and filter keys are handled by the tree; `Enter`
+    /// on a file opens it in the editor and focuses the editor. Returns
+    /// whether the key was consumed; other keys fall through to the normal
+    /// bindings.
+    fn handle_explorer_key(&mut self, key: AppKey) -> bool {
+        let Some(explorer) = self.explorer_mut() else {
+            return false;
+        };
+        match explorer.handle_key(key) {
+            FileTreeOutcome::Ignored => return false,
+            FileTreeOutcome::Handled => {}
+            FileTreeOutcome::Open(path) => {
+                let id = self.target_editor();
+                match self.open_file(&path) {
+                    Ok(()) => self.focus_manager.set_focus(id),
+                    Err(error) => {
+                        self.report_files_error(format!("Cannot open {}: {error}", path.display()));
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /// Handle keys for the focused git panel.
+    ///
+    /// `Enter` on a file opens its diff in the diff view. Returns whether
+    /// the key was consumed; other keys fall through to the normal bindings.
+    fn handle_git_panel_key(&mut self, key: AppKey) -> bool {
+        let Some(panel) = self.git_panel_mut() else {
+            return false;
+        };
+        match panel.handle_key(key) {
+            GitPanelOutcome::Ignored => return false,
+            GitPanelOutcome::Handled => {}
+            GitPanelOutcome::Open(path, base) => {
+                if let Err(error) = self.open_diff(&path, base) {
+                    self.report_git_error(&error);
+                }
+            }
+        }
+        true
+    }
+
+    /// Handle hunk selection keys for the focused diff view. Returns whether
+    /// the key was consumed.
+    fn handle_diff_key(&mut self, key: AppKey) -> bool {
+        self.diff_view_mut()
+            .is_some_and(|view| view.handle_key(key))
+    }
+
+    /// Handle keys for the focused git history.
+    ///
+    /// `Enter` on a commit shows its details and `Enter` on one of its files
+    /// opens the file as it was at the commit. Returns whether the key was
+    /// consumed; other keys fall through to the normal bindings.
+    fn handle_git_log_key(&mut self, key: AppKey) -> bool {
+        let Some(log) = self.git_log_mut() else {
+            return false;
+        };
+        match log.handle_key(key) {
+            GitLogOutcome::Ignored => return false,
+            GitLogOutcome::Handled => {}
+            GitLogOutcome::ShowCommit(id) => {
+                match self
+                    .repository()
+                    .and_then(|repository| repository.commit(&id))
+                {
+                    Ok(detail) => {
+                        if let Some(log) = self.git_log_mut() {
+                            log.show_commit(detail);
+                        }
+                    }
+                    Err(error) => self.report_git_error(&error),
+                }
+            }
+            GitLogOutcome::OpenFile { revision, path } => {
+                if let Err(error) = self.open_revision(&revision, &path) {
+                    self.report_git_error(&error);
+                }
+            }
+        }
+        true
+    }
+
+    /// Handle keys for the focused Problems or Diagnostics panel.
+    ///
+    /// `Enter` on a problem opens its file in the editor with the cursor at
+    /// the problem. Returns whether the key was consumed; other keys fall
+    /// through to the normal bindings.
+    fn handle_problems_key(&mut self, key: AppKey) -> bool {
+        let focused = self.focus_manager.focused();
+        let outcome = if focused.is_some() && focused == self.diagnostics_id {
+            match self.diagnostics_panel_mut() {
+                Some(panel) => panel.handle_key(key),
+                None => return false,
+            }
+        } else {
+            match self.problems_panel_mut() {
+                Some(panel) => panel.handle_key(key),
+                None => return false,
+            }
+        };
+        match outcome {
+            ProblemsOutcome::Ignored => return false,
+            ProblemsOutcome::Handled => {}
+            ProblemsOutcome::Open(problem) => self.open_problem(&problem),
+        }
+        true
+    }
+
+    /// Handle keys for the focused log viewer, which moves its cursor and
+    /// marks records. Returns whether the key was consumed.
+    fn handle_logs_key(&mut self, key: AppKey) -> bool {
+        self.logs_id
+            .and_then(|id| self.windows.window_mut::<LogWindow>(id))
+            .is_some_and(|logs| logs.handle_key(key))
+    }
+
+    /// Open the file of `problem` in the target editor, move the cursor to
+    /// the problem and focus the editor.
+    pub fn open_problem(&mut self, problem: &Problem) {
+        let id = self.target_editor();
+        match self.open_file(&problem.path) {
+            Ok(()) => {
+                if let Some(editor) = self.windows.window_mut::<EditorWindow>(id) {
+                    editor.set_cursor_position(problem.line, problem.column);
+                }
+                self.focus_manager.set_focus(id);
+            }
+            Err(error) => {
+                self.report_files_error(format!("Cannot open {}: {error}", problem.path.display()))
+            }
+        }
+    }
+
+    /// Handle keys for the focused regex tester.
+    ///
+    /// Returns whether the key was consumed; `Tab` falls through so focus
+    /// can still move, and `Esc` closes the tester.
+    fn handle_regex_tester_key(&mut self, key: AppKey) -> bool {
+        let Some(id) = self.regex_tester_id else {
+            return false;
+        };
+        let Some(tester) = self.windows.window_mut::<RegexTesterWindow>(id) else {
+            return false;
+        };
+        match tester.handle_key(key) {
+            RegexTesterOutcome::Ignored => false,
+            RegexTesterOutcome::Handled => true,
+            RegexTesterOutcome::Close => {
+                self.close_window(id);
+                self.regex_tester_id = None;
+                true
+            }
+        }
+    }
+
+    /// Handle keys for the focused search panel.
+    ///
+    /// `Enter` in a field searches the workspace and `Enter` on a result
+    /// opens it in the editor with the match selected. Returns whether the
+    /// key was consumed; other keys fall through to the normal bindings.
+    fn handle_search_key(&mut self, key: AppKey) -> bool {
+        let Some(panel) = self.search_mut() else {
+            return false;
+        };
+        match panel.handle_key(key) {
+            SearchOutcome::Ignored => return false,
+            SearchOutcome::Handled => {}
+            SearchOutcome::Search(query) => self.run_search(&query),
+            SearchOutcome::Open(path, range) => self.open_match(&path, range),
+        }
+        true
+    }
+
+    /// Handle keys for the focused Find in Files panel.
+    ///
+    /// `Enter` in the query or an option starts a search and `Enter` on a
+    /// result opens it in the editor with the match selected. Returns
+    /// whether the key was consumed; other keys fall through to the normal
+    /// bindings.
+    fn handle_search_results_key(&mut self, key: AppKey) -> bool {
+        let Some(panel) = self.search_results_mut() else {
+            return false;
+        };
+        match panel.handle_key(key) {
+            SearchResultsOutcome::Ignored => return false,
+            SearchResultsOutcome::Handled => {}
+            SearchResultsOutcome::Search(query) => self.find_in_files(query),
+            SearchResultsOutcome::Open(path, range) => self.open_match(&path, range),
+        }
+        true
+    }
+
+    /// Open `path` in the target editor with `range` selected and focus it.
+    fn open_match(&mut self, path: &Path, range: std::ops::Range<usize>) {
+        let id = self.target_editor();
+        match self.open_file(path) {
+            Ok(()) => {
+                if let Some(editor) = self.windows.window_mut::<EditorWindow>(id) {
+                    editor.set_selection(range);
+                }
+                self.focus_manager.set_focus(id);
+            }
+            Err(error) => {
+                self.report_files_error(format!("Cannot open {}: {error}", path.display()));
+            }
+        }
+    }
+
+    /// Search the workspace for `query` and show the results in the search
+    /// panel.
+    ///
+    /// Open files are searched as they are in their buffers.
+    fn run_search(&mut self, query: &str) {
+        let Some(roots) = self
+            .search_panel()
+            .map(|panel| panel.folders().roots().to_vec())
+        else {
+            return;
+        };
+        let found = roots.iter().try_fold(Vec::new(), |mut results, root| {
+            results.extend(search::search_workspace(
+                root,
+                query,
+                &self.buffers,
+                MAX_SEARCH_FILES,
+            )?);
+            io::Result::Ok(results)
+        });
+        match found {
+            Ok(results) => {
+                if let Some(panel) = self.search_mut() {
+                    panel.set_results(query, results);
+                }
+                self.status_bar_updates
+                    .emit(StatusBarUpdate::RemoveSegment(SEARCH_SEGMENT.to_string()));
+            }
+            Err(error) => self.status_bar_updates.emit(StatusBarUpdate::SetSegment {
+                id: SEARCH_SEGMENT.to_string(),
+                text: format!("Search failed: {error}"),
+            }),
+        }
+    }
+
+    /// Replace the search panel's included matches and search again.
+    ///
+    /// Matches in open files are replaced in their buffers, which are left
+    /// unsaved. Other files are opened, edited, saved and closed again.
+    /// Nothing is replaced if any file has changed since the search in a
+    /// way that invalidates its matches.
+    fn replace_in_files(&mut self) {
+        let Some(panel) = self.search_panel() else {
+            return;
+        };
+        let Some(query) = panel.searched().map(str::to_string) else {
+            return;
+        };
+        let edit = panel.replace_edit();
+        if edit.changes.is_empty() {
+            return;
+        }
+        let count: usize = edit.changes.values().map(Vec::len).sum();
+        let text = match self.apply_edit_to_files(&edit) {
+            Ok(()) => format!(
+                "Replaced {} in {}",
+                search::counted(count, "occurrence"),
+                search::counted(edit.changes.len(), "file")
+            ),
+            Err(error) => format!("Replace failed: {error}"),
+        };
+        self.run_search(&query);
+        self.status_bar_updates.emit(StatusBarUpdate::SetSegment {
+            id: SEARCH_SEGMENT.to_string(),
+            text,
+        });
+    }
+
+    /// Apply `edit` to files whether or not they are open.
+    ///
+    /// Files that are not open are loaded into the buffer manager for the
+    /// edit, then saved and closed.
+    fn apply_edit_to_files(&mut self, edit: &WorkspaceEdit) -> Result<(), String> {
+        let mut loaded = Vec::new();
+        let mut result = Ok(());
+        for path in edit.changes.keys() {
+            if self.buffers.contains(path) || TextDocuments::text(self.editor(), path).is_some() {
+                continue;
+            }
+            if let Err(error) = self.buffers.open(path) {
+                result = Err(format!("{}: {error}", path.display()));
+                break;
+            }
+            loaded.push(path);
+        }
+        if result.is_ok() {
+            result = self
+                .apply_workspace_edit(edit)
+                .map_err(|error| error.to_string());
+        }
+        if result.is_ok() {
+            for path in loaded {
+                if let Err(error) = self.buffers.save(path) {
+                    result = result.and(Err(format!("{}: {error}", path.display())));
+                }
+            }
+        }
+        self.buffers.close_unused();
+        result
+    }
+
+    /// Handle a mouse event.
+    ///
+    /// Clicking a pane focuses it, right-clicking one also opens its context
+    /// menu, clicking a notification toast dismisses it, and scroll wheel
+    /// events scroll the focused window. Pane
+    /// positions are computed from the stored dimensions. Mouse input is
+    /// ignored while a modal overlay is open; while a context menu is open,
+    /// clicking an item runs it and clicking elsewhere dismisses the menu.
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        if self.dialog.is_some()
+            || self.command_palette.is_some()
+            || self.quick_fix.is_some()
+            || self.quick_open.is_some()
+            || self.branch_picker.is_some()
+            || self.stash_picker.is_some()
+            || self.task_picker.is_some()
+            || self.command_line.is_some()
+            || self.input_box.is_some()
+            || self.editor().rename_input().is_some()
+        {
+            return;
+        }
+        if self.context_menu.is_some() && !self.click_context_menu(mouse) {
+            return;
+        }
+
+        match mouse.kind {
+            MouseEventKind::Down(button) => {
+                let status_bar = Self::status_bar_rect(Rect::new(0, 0, self.width, self.height));
+                if mouse.row == status_bar.y {
+                    self.click_status_bar(mouse.column);
+                } else if self.click_toast(Position::new(mouse.column, mouse.row)) {
+                    // The click does not reach the pane under the toast
+                } else if let Some(id) = self.window_at(mouse.column, mouse.row) {
+                    self.focus_manager.set_focus(id);
+                    let position = Position::new(mouse.column, mouse.row);
+                    match button {
+                        MouseButton::Right => self.open_context_menu_at(id, position),
+                        MouseButton::Left => {
+                            if !self.grab_split_edge(id, position) {
+                                self.click_window(id, position, false);
+                            }
+                        }
+                        MouseButton::Middle => {}
+                    }
+                }
+            }
+            MouseEventKind::Drag(MouseButton::Left) => {
+                if self.split_drag.is_some() {
+                    self.drag_split_edge(Position::new(mouse.column, mouse.row));
+                } else if let Some(id) = self.window_at(mouse.column, mouse.row) {
+                    self.click_window(id, Position::new(mouse.column, mouse.row), true);
+                }
+            }
+            MouseEventKind::ScrollUp => self.scroll_focused(-1),
+            MouseEventKind::ScrollDown => self.scroll_focused(1),
+            MouseEventKind::Up(_) => self.split_drag = None,
+            MouseEventKind::Drag(_) => {}
+        }
+    }
+
+    /// Handle a mouse event while the context menu is open, returning
+    /// whether the event goes on to the panes.
+    ///
+    /// Clicking an item runs its command; a click outside the menu closes
+    /// it, and a right click there goes on to open the menu of what is
+    /// under it.
+    fn click_context_menu(&mut self, mouse: MouseEvent) -> bool {
+        let MouseEventKind::Down(button) = mouse.kind else {
+            return false;
+        };
+        let Some(open) = self.context_menu.as_ref() else {
+            return true;
+        };
+        let position = Position::new(mouse.column, mouse.row);
+        let area = open.menu.area(
+            open.anchor,
+            self.panes_rect(Rect::new(0, 0, self.width, self.height)),
+        );
+        if !area.contains(position) {
+            self.context_menu = None;
+            return button == MouseButton::Right;
+        }
+        if button == MouseButton::Left {
+            if let Some(command) = open.menu.command_at(area, position) {
+                let command = command.id.clone();
+                self.context_menu = None;
+                self.run_menu_command(&command);
+            }
+        }
+        false
+    }
+
+    /// Open the context menu for a right click on window `id` at
+    /// `position`.
+    ///
+    /// A click on an editor tab activates the tab and opens the tab menu; a
+    /// click on an explorer entry moves the explorer's cursor to it first.
+    /// Windows without a menu are left alone.
+    fn open_context_menu_at(&mut self, id: WindowId, position: Position) {
+        let area = self
+            .window_rects(Rect::new(0, 0, self.width, self.height))
+            .into_iter()
+            .find(|&(window, _)| window == id)
+            .map_or(Rect::default(), |(_, area)| area);
+        let location = match self.windows.get(id).and_then(|window| window.context()) {
+            Some(WindowContext::Editor) => {
+                let editor = self.windows.window_mut::<EditorWindow>(id);
+                match editor
+                    .as_ref()
+                    .and_then(|editor| editor.tab_at(area, position))
+                {
+                    Some(tab) => {
+                        if let Some(editor) = editor {
+                            editor.activate_tab(tab);
+                        }
+                        MenuLocation::EditorTab
+                    }
+                    None => MenuLocation::Editor,
+                }
+            }
+            Some(WindowContext::Explorer) => {
+                if let Some(explorer) = self.windows.window_mut::<FileTreeWindow>(id) {
+                    explorer.select_row_at(area, position);
+                }
+                MenuLocation::Explorer
+            }
+            Some(WindowContext::Terminal) => MenuLocation::Terminal,
+            _ => return,
+        };
+        self.open_context_menu(location, position);
+    }
+
+    /// Open the context menu of the focused window from the keyboard.
+    ///
+    /// The editor's menu opens below its cursor and other menus at the top
+    /// left of their window.
+    fn show_context_menu(&mut self) {
+        let Some(id) = self.focus_manager.focused() else {
+            return;
+        };
+        let Some(area) = self
+            .window_rects(Rect::new(0, 0, self.width, self.height))
+            .into_iter()
+            .find(|&(window, _)| window == id)
+            .map(|(_, area)| area)
+        else {
+            return;
+        };
+        let inner = Rect {
+            x: area.x + 1,
+            y: area.y + 1,
+            width: area.width.saturating_sub(2),
+            height: area.height.saturating_sub(2),
+        };
+        let (location, anchor) = match self.focused_context() {
+            Some(WindowContext::Editor) => {
+                let anchor =
+                    self.windows
+                        .window::<EditorWindow>(id)
+                        .map_or(inner.as_position(), |editor| {
+                            let (line, column) = editor.cursor_position();
+                            // The row below the cursor, past the tab strip
+                            let tabs = usize::from(editor.group().len() > 1);
+                            let row = (line + tabs).saturating_sub(editor.viewport().top_line);
+                            let cell = |offset: usize, start: u16| {
+                                start.saturating_add(u16::try_from(offset).unwrap_or(u16::MAX))
+                            };
+                            Position::new(cell(column - 1, inner.x), cell(row, inner.y))
+                        });
+                (MenuLocation::Editor, anchor)
+            }
+            Some(WindowContext::Explorer) => (MenuLocation::Explorer, inner.as_position()),
+            Some(WindowContext::Terminal) => (MenuLocation::Terminal, inner.as_position()),
+            _ => return,
+        };
+        self.open_context_menu(location, anchor);
+    }
+
+    /// Open the menu at `location` with its top left corner at `anchor`.
+    ///
+    /// The menu offers the registered commands its items name whose
+    /// conditions hold for the target editor; nothing opens if there are
+    /// none.
+    pub fn open_context_menu(&mut self, location: MenuLocation, anchor: Position) {
+        let editor = self.windows.window::<EditorWindow>(self.target_editor());
+        let context = MenuContext {
+            has_selection: editor.is_some_and(|editor| !editor.selection().is_empty()),
+            has_path: editor.is_some_and(|editor| editor.path().is_some()),
+            tabs: editor.map_or(0, |editor| editor.group().len()),
+        };
+        let commands = self
+            .services
+            .resolve::<CommandService>()
+            .expect("CommandService is registered in App::new")
+            .commands();
+        let commands = self.context_menus.resolve(location, &context, &commands);
+        if commands.is_empty() {
+            return;
+        }
+        self.context_menu = Some(OpenContextMenu {
+            menu: ContextMenuWindow::new(commands),
+            anchor,
+        });
+    }
+
+    /// Run `command`, chosen from a context menu.
+    fn run_menu_command(&mut self, command: &str) {
+        if let Err(error) = self.execute_command(command) {
+            tracing::warn!("cannot run the menu command {command}: {error}");
+        }
+    }
+
+    /// Get the open context menu, if any.
+    pub fn context_menu(&self) -> Option<&ContextMenuWindow> {
+        self.context_menu.as_ref().map(|open| &open.menu)
+    }
+
+    /// Get the commands offered by each context menu.
+    pub fn context_menus(&self) -> &ContextMenus {
+        &self.context_menus
+    }
+
+    /// Get the commands offered by each context menu, to add or remove
+    /// items.
+    pub fn context_menus_mut(&mut self) -> &mut ContextMenus {
+        &mut self.context_menus
+    }
+
+    /// Dismiss the notification toast drawn at `position`, returning
+    /// whether there is a toast there. Operation toasts only swallow the
+    /// click.
+    fn click_toast(&mut self, position: Position) -> bool {
+        let area = self.panes_rect(Rect::new(0, 0, self.width, self.height));
+        let Some((_, id, _)) = self
+            .toasts(area)
+            .into_iter()
+            .find(|(_, _, toast_area)| toast_area.contains(position))
+        else {
+            return false;
+        };
+        if let Some(id) = id {
+            self.notifications().dismiss(id);
+        }
+        true
+    }
+
+    /// Find the window under the given cell, if any.
+    pub fn window_at(&self, column: u16, row: u16) -> Option<WindowId> {
+        let area = self.panes_rect(Rect::new(0, 0, self.width, self.height));
+        self.windows.window_at(area, Position::new(column, row))
+    }
+
+    /// Handle a left click, or with `extend` a drag, at `position` in the
+    /// window `id`: on its scrollbar it scrolls, inside its border it
+    /// selects at the cell under it.
+    fn click_window(&mut self, id: WindowId, position: Position, extend: bool) {
+        if self.click_scrollbar(id, position) {
+            return;
+        }
+        let area = self.panes_rect(Rect::new(0, 0, self.width, self.height));
+        let Some(rect) = self
+            .windows
+            .rects(area)
+            .into_iter()
+            .find(|&(open, _)| open == id)
+            .map(|(_, rect)| rect)
+        else {
+            return;
+        };
+        let inner = Block::default()
+            .borders(pane_borders(self.is_compact(id)))
+            .inner(rect);
+        if !inner.contains(position) {
+            return;
+        }
+        if let Some(window) = self.windows.get_mut(id) {
+            window.select_at(position.y - inner.y, position.x - inner.x, extend);
+        }
+    }
+
+    /// Start dragging the edge under a left click at `position` on window
+    /// `id`, if the click is on the pane's left or top border and another
+    /// pane is on the other side of it. Returns whether it was.
+    fn grab_split_edge(&mut self, id: WindowId, position: Position) -> bool {
+        let area = self.panes_rect(Rect::new(0, 0, self.width, self.height));
+        let Some(rect) = self
+            .windows
+            .rects(area)
+            .into_iter()
+            .find(|&(open, _)| open == id)
+            .map(|(_, rect)| rect)
+        else {
+            return false;
+        };
+        let borders = pane_borders(self.is_compact(id));
+        let (direction, edge) =
+            if borders.contains(Borders::LEFT) && position.x == rect.x && rect.x > area.x {
+                (SplitDirection::Horizontal, position.x)
+            } else if borders.contains(Borders::TOP) && position.y == rect.y && rect.y > area.y {
+                (SplitDirection::Vertical, position.y)
+            } else {
+                return false;
+            };
+        self.split_drag = Some(SplitDrag {
+            id,
+            direction,
+            position: edge,
+        });
+        true
+    }
+
+    /// Move the dragged pane edge towards `position`, as far as the panes
+    /// either side of it allow.
+    fn drag_split_edge(&mut self, position: Position) {
+        let Some(mut drag) = self.split_drag else {
+            return;
+        };
+        let target = match drag.direction {
+            SplitDirection::Horizontal => position.x,
+            SplitDirection::Vertical => position.y,
+        };
+        let area = self.panes_rect(Rect::new(0, 0, self.width, self.height));
+        let moved = self.windows.move_edge(
+            area,
+            drag.id,
+            drag.direction,
+            i32::from(target) - i32::from(drag.position),
+        );
+        drag.position = drag.position.saturating_add_signed(moved as i16);
+        self.split_drag = Some(drag);
+    }
+
+    /// Grow the focused pane by `delta` cells along `direction`, or shrink
+    /// it if negative, taking the room from or giving it to its neighbor.
+    /// Nothing happens if no split in `direction` holds it.
+    fn resize_focused_pane(&mut self, direction: SplitDirection, delta: i32) {
+        let Some(id) = self.focused_id() else {
+            return;
+        };
+        let area = self.panes_rect(Rect::new(0, 0, self.width, self.height));
+        self.windows.resize(area, id, direction, delta);
+    }
+
+    /// Move focus to the pane beside the focused one in `direction`, if
+    /// there is one.
+    fn focus_direction(&mut self, direction: PaneDirection) {
+        let Some(id) = self.focused_id() else {
+            return;
+        };
+        let area = self.panes_rect(Rect::new(0, 0, self.width, self.height));
+        if let Some(neighbor) = self.windows.neighbor(area, id, direction) {
+            self.focus_manager.set_focus(neighbor);
+        }
+    }
+
+    /// Scroll the window `id` to the part of its content matching a click
+    /// at `position` on its scrollbar, if scrollbars are shown and the click
+    /// is on one. Returns whether it was. Compact windows have no scrollbar.
+    fn click_scrollbar(&mut self, id: WindowId, position: Position) -> bool {
+        if !self.scrollbars || self.is_compact(id) {
+            return false;
+        }
+        let area = self.panes_rect(Rect::new(0, 0, self.width, self.height));
+        let Some(track) = self
+            .windows
+            .rects(area)
+            .into_iter()
+            .find(|&(open, _)| open == id)
+            .and_then(|(_, rect)| scrollbar::track(rect))
+            .filter(|track| track.contains(position))
+        else {
+            return false;
+        };
+        let Some(scroll) = self
+            .windows
+            .get_mut(id)
+            .and_then(|window| window.scroll_position())
+        else {
+            return true;
+        };
+        let rows = usize::from(track.height);
+        let row = usize::from(position.y - track.y);
+        let offset = scrollbar::offset_at(scroll, rows, rows, row);
+        let lines = i64::try_from(offset).unwrap_or(i64::MAX)
+            - i64::try_from(scroll.offset).unwrap_or(i64::MAX);
+        if let Some(window) = self.windows.get_mut(id) {
+            window.scroll_by(i32::try_from(lines).unwrap_or(if lines < 0 {
+                i32::MIN
+            } else {
+                i32::MAX
+            }));
+        }
+        true
+    }
+
+    /// Lines a page scroll moves the window `id`: its height inside the
+    /// border less one line of context, at least one.
+    fn page_of(&self, id: WindowId) -> i32 {
+        let area = self.panes_rect(Rect::new(0, 0, self.width, self.height));
+        let height = self
+            .windows
+            .rects(area)
+            .into_iter()
+            .find(|&(open, _)| open == id)
+            .map_or(0, |(_, rect)| rect.height.saturating_sub(3));
+        i32::from(height.max(1))
+    }
+
+    /// Scroll the focused window by `lines`.
+    fn scroll_focused(&mut self, lines: i32) {
+        if let Some(window) = self
+            .focus_manager
+            .focused()
+            .and_then(|id| self.windows.get_mut(id))
+        {
+            window.scroll_by(lines);
+        }
+    }
+
+    /// Execute an action.
+    fn execute_action(&mut self, action: Action) {
+        match action {
+            Action::Quit => {
+                self.request_quit();
+            }
+            Action::ToggleFocus | Action::FocusNext => {
+                self.windows.focus_next(&mut self.focus_manager);
+            }
+            Action::FocusPrev => {
+                self.windows.focus_prev(&mut self.focus_manager);
+            }
+            Action::FocusDirection(direction) => {
+                self.focus_direction(direction);
+            }
+            Action::CloseWindow => {
+                if let Some(id) = self.focused_id() {
+                    self.close_window(id);
+                }
+            }
+            Action::OpenCommandPalette => {
+                self.open_command_palette();
+            }
+            Action::OpenCommandLine => {
+                self.open_command_line();
+            }
+            Action::ShowContextMenu => {
+                self.show_context_menu();
+            }
+            Action::ExpandBracketSelection => {
+                self.editor_mut().expand_selection();
+            }
+            Action::ShrinkBracketSelection => {
+                self.editor_mut().shrink_selection();
+            }
+            Action::ShowCodeActions => {
+                self.open_quick_fix();
+            }
+            Action::PeekDefinition => {
+                self.peek_definition();
+            }
+            Action::OpenLink => {
+                self.open_link();
+            }
+            Action::RenameSymbol => {
+                self.open_rename();
+            }
+            Action::ToggleTheme => {
+                self.set_theme(self.theme().next_builtin());
+            }
+            Action::NewUntitledFile => {
+                self.new_untitled();
+            }
+            Action::Save => {
+                self.save_editor(self.target_editor());
+            }
+            Action::ExportAsAnsi => {
+                self.prompt_export(self.target_editor(), ExportFormat::Ansi);
+            }
+            Action::ExportAsHtml => {
+                self.prompt_export(self.target_editor(), ExportFormat::Html);
+            }
+            Action::QuickOpen => {
+                self.open_quick_open();
+            }
+            Action::CheckoutBranch => {
+                self.open_branch_picker();
+            }
+            Action::StashChanges => {
+                self.input_box = Some(PendingInput {
+                    input: InputBoxWindow::new("Stash Message", ""),
+                    purpose: InputPurpose::StashMessage,
+                });
+            }
+            Action::PopLatestStash => {
+                match self
+                    .repository()
+                    .and_then(|repository| repository.stashes())
+                {
+                    Ok(stashes) => match stashes.first() {
+                        Some(stash) => self.pop_stash(stash),
+                        None => self.report_git("There are no stashes".to_string()),
+                    },
+                    Err(error) => self.report_git_error(&error),
+                }
+            }
+            Action::PopStash => {
+                self.open_stash_picker();
+            }
+            Action::ToggleExplorer => {
+                self.toggle_explorer();
+            }
+            Action::ExplorerNewFile => {
+                self.prompt_explorer_name(false);
+            }
+            Action::ExplorerNewFolder => {
+                self.prompt_explorer_name(true);
+            }
+            Action::ExplorerRename => {
+                self.prompt_explorer_rename();
+            }
+            Action::ExplorerDelete => {
+                self.delete_explorer_targets();
+            }
+            Action::ExplorerMove => {
+                self.move_explorer_marks();
+            }
+            Action::ExplorerRefresh => {
+                if let Some(explorer) = self.explorer_mut() {
+                    explorer.refresh();
+                }
+            }
+            Action::AddRootFolder => {
+                self.prompt_root_folder();
+            }
+            Action::RemoveRootFolder => {
+                self.remove_selected_root_folder();
+            }
+            Action::ToggleSearch => {
+                self.toggle_search();
+            }
+            Action::FindInFiles => {
+                self.toggle_search_results();
+            }
+            Action::ReplaceInFiles => {
+                self.replace_in_files();
+            }
+            Action::ToggleRegexTester => {
+                self.toggle_regex_tester();
+            }
+            Action::ShowKeybindings => {
+                self.toggle_keybindings();
+            }
+            Action::ToggleSnippets => {
+                self.toggle_snippets();
+            }
+            Action::ToggleTodos => {
+                self.toggle_todos();
+            }
+            Action::ToggleUndoTree => {
+                self.toggle_undo_tree();
+            }
+            Action::ToggleGitPanel => {
+                self.toggle_git_panel();
+            }
+            Action::GitRefresh => {
+                self.refresh_branch();
+                self.refresh_line_changes();
+                self.refresh_git();
+            }
+            Action::StageHunk => {
+                self.apply_hunk(HunkAction::Stage);
+            }
+            Action::UnstageHunk => {
+                self.apply_hunk(HunkAction::Unstage);
+            }
+            Action::RevertHunk => {
+                self.apply_hunk(HunkAction::Revert);
+            }
+            Action::ToggleDiffLayout => {
+                if let Some(view) = self.diff_view_mut() {
+                    view.toggle_layout();
+                }
+            }
+            Action::CompareWithSaved => {
+                self.compare_with_saved();
+            }
+            Action::CompareWithHead => {
+                self.compare_with_head();
+            }
+            Action::CompareWithClipboard => {
+                self.compare_with_clipboard();
+            }
+            Action::CompareWithFile => {
+                self.prompt_compare_with();
+            }
+            Action::ToggleGitLog => {
+                self.toggle_git_log();
+            }
+            Action::CargoBuild => {
+                self.run_cargo(CargoCommand::Build);
+            }
+            Action::CargoTest => {
+                self.run_cargo(CargoCommand::Test);
+            }
+            Action::CargoRun => {
+                self.run_cargo(CargoCommand::Run);
+            }
+            Action::CargoClippy => {
+                self.run_cargo(CargoCommand::Clippy);
+            }
+            Action::CargoStop => {
+                self.stop_task();
+            }
+            Action::RunTask => {
+                self.open_task_picker();
+            }
+            Action::TogglePluginWindow(index) => {
+                self.toggle_plugin_window(index);
+            }
+            Action::RunPluginCommand(index) => {
+                let id = self.plugin_registry.keybindings()[index].command.clone();
+                if let Err(error) = self.execute_command(&id) {
+                    tracing::warn!("cannot run `{id}`: {error}");
+                }
+            }
+            Action::ToggleProblems => {
+                self.toggle_problems();
+            }
+            Action::ToggleDiagnostics => {
+                self.toggle_diagnostics();
+            }
+            Action::NextDiagnostic => {
+                self.go_to_diagnostic(true);
+            }
+            Action::PreviousDiagnostic => {
+                self.go_to_diagnostic(false);
+            }
+            Action::ToggleOutput => {
+                self.toggle_output();
+            }
+            Action::ToggleTerminalCapabilities => {
+                self.toggle_terminal_report();
+            }
+            Action::ToggleMirror => {
+                self.toggle_mirror();
+            }
+            Action::ToggleMarkdownPreview => {
+                self.toggle_markdown_preview();
+            }
+            Action::ToggleScrollbars => {
+                self.scrollbars = !self.scrollbars;
+            }
+            Action::ToggleWordCount => {
+                self.word_count = !self.word_count;
+            }
+            Action::ToggleCompactMode => {
+                if let Some(id) = self.focused_id() {
+                    self.toggle_compact(id);
+                }
+            }
+            Action::ToggleDensity => {
+                self.set_density(if self.density.is_compact() {
+                    Density::Comfortable
+                } else {
+                    Density::Compact
+                });
+            }
+            Action::ToggleLogs => {
+                self.toggle_logs();
+            }
+            Action::CancelProgress => {
+                self.cancel_progress();
+            }
+            Action::ToggleNotifications => {
+                self.toggle_notifications();
+            }
+            Action::ToggleDoNotDisturb => {
+                self.toggle_do_not_disturb();
+            }
+            Action::ClearNotifications => {
+                self.notifications().clear();
+            }
+            Action::HideNotificationToasts => {
+                self.notifications().dismiss_all();
+            }
+            Action::ToggleTutorial => {
+                self.toggle_tutorial();
+            }
+            Action::ToggleModalEditing => {
+                self.toggle_modal_editing();
+            }
+            Action::EnterNormalMode => {
+                self.set_editing_mode(EditingMode::Normal);
+            }
+            Action::EnterInsertMode => {
+                self.set_editing_mode(EditingMode::Insert);
+            }
+            Action::EnterVisualMode => {
+                self.set_editing_mode(EditingMode::Visual);
+            }
+            Action::CursorLeft => self.move_editor_cursor(0, -1),
+            Action::CursorDown => self.move_editor_cursor(1, 0),
+            Action::CursorUp => self.move_editor_cursor(-1, 0),
+            Action::CursorRight => self.move_editor_cursor(0, 1),
+            Action::SelectLeft => self.extend_editor_selection(0, -1),
+            Action::SelectDown => self.extend_editor_selection(1, 0),
+            Action::SelectUp => self.extend_editor_selection(-1, 0),
+            Action::SelectRight => self.extend_editor_selection(0, 1),
+            Action::Copy => self.copy_selection(false),
+            Action::Cut => self.copy_selection(true),
+            Action::Paste => self.paste(),
+            Action::DeleteCharacter | Action::DeleteLeft => {
+                let id = self.target_editor();
+                if let Some(editor) = self.windows.window_mut::<EditorWindow>(id) {
+                    if action == Action::DeleteLeft {
+                        editor.delete_backward();
+                    } else {
+                        editor.delete_forward();
+                    }
+                }
+                if self.editing_mode == Some(EditingMode::Visual) {
+                    self.editing_mode = Some(EditingMode::Normal);
+                }
+            }
+            Action::Find
+            | Action::FindNext
+            | Action::FindPrevious
+            | Action::ReplaceOne
+            | Action::ReplaceAll
+            | Action::Undo
+            | Action::Redo => {
+                let id = self.target_editor();
+                if let Some(editor) = self.windows.window_mut::<EditorWindow>(id) {
+                    match action {
+                        Action::Find => editor.open_find(),
+                        Action::FindNext => {
+                            editor.find_next();
+                        }
+                        Action::FindPrevious => {
+                            editor.find_previous();
+                        }
+                        Action::ReplaceOne => {
+                            editor.replace_one();
+                        }
+                        Action::ReplaceAll => {
+                            editor.replace_all();
+                        }
+                        Action::Undo => {
+                            editor.undo();
+                        }
+                        _ => {
+                            editor.redo();
+                        }
+                    }
+                }
+            }
+            Action::ScrollPageUp
+            | Action::ScrollPageDown
+            | Action::ScrollLineUp
+            | Action::ScrollLineDown => {
+                // Other windows scroll without a cursor to move
+                if let Some(focused) = self
+                    .focused_id()
+                    .filter(|&id| self.windows.window::<EditorWindow>(id).is_none())
+                {
+                    let lines = match action {
+                        Action::ScrollPageUp => -self.page_of(focused),
+                        Action::ScrollPageDown => self.page_of(focused),
+                        Action::ScrollLineUp => -1,
+                        _ => 1,
+                    };
+                    self.scroll_focused(lines);
+                    return;
+                }
+                let id = self.target_editor();
+                if let Some(editor) = self.windows.window_mut::<EditorWindow>(id) {
+                    match action {
+                        Action::ScrollPageUp => editor.scroll_page(false),
+                        Action::ScrollPageDown => editor.scroll_page(true),
+                        Action::ScrollLineUp => editor.scroll_lines(-1),
+                        _ => editor.scroll_lines(1),
+                    }
+                }
+            }
+            Action::NextTab | Action::PrevTab | Action::CloseTab => {
+                let id = self.target_editor();
+                if let Some(editor) = self.windows.window_mut::<EditorWindow>(id) {
+                    match action {
+                        Action::NextTab => editor.next_tab(),
+                        Action::PrevTab => editor.prev_tab(),
+                        _ => {
+                            if let Some(tab) = editor.close_tab() {
+                                self.remember_closed_tab(&tab);
+                            }
+                        }
+                    }
+                }
+                self.buffers.close_unused();
+            }
+            Action::ReopenClosedEditor => {
+                self.reopen_closed_editor();
+            }
+            Action::CloseOtherTabs => {
+                let id = self.target_editor();
+                if let Some(editor) = self.windows.window_mut::<EditorWindow>(id) {
+                    for tab in editor.close_other_tabs() {
+                        self.remember_closed_tab(&tab);
+                    }
+                }
+                self.buffers.close_unused();
+            }
+            Action::TogglePinnedTab | Action::MoveTabLeft | Action::MoveTabRight => {
+                let id = self.target_editor();
+                if let Some(editor) = self.windows.window_mut::<EditorWindow>(id) {
+                    match action {
+                        Action::TogglePinnedTab => {
+                            editor.toggle_pinned_tab();
+                        }
+                        Action::MoveTabLeft => {
+                            editor.move_tab(-1);
+                        }
+                        _ => {
+                            editor.move_tab(1);
+                        }
+                    }
+                }
+            }
+            Action::MoveTabToNextGroup => {
+                self.move_tab_to_next_group();
+            }
+            Action::SplitEditorRight => {
+                self.split_editor(SplitDirection::Horizontal);
+            }
+            Action::SplitEditorDown => {
+                self.split_editor(SplitDirection::Vertical);
+            }
+            Action::IncreaseViewWidth => {
+                self.resize_focused_pane(SplitDirection::Horizontal, PANE_RESIZE_STEP);
+            }
+            Action::DecreaseViewWidth => {
+                self.resize_focused_pane(SplitDirection::Horizontal, -PANE_RESIZE_STEP);
+            }
+            Action::IncreaseViewHeight => {
+                self.resize_focused_pane(SplitDirection::Vertical, PANE_RESIZE_STEP);
+            }
+            Action::DecreaseViewHeight => {
+                self.resize_focused_pane(SplitDirection::Vertical, -PANE_RESIZE_STEP);
+            }
+            Action::None => {
+                // Do nothing
+            }
+        }
+    }
+
+    /// Save an editor's buffer, or prompt for a path if it has none.
+    ///
+    /// A failed save is reported in the status bar's `save` segment, which
+    /// is cleared by the next successful save.
+    fn save_editor(&mut self, id: WindowId) {
+        let Some(editor) = self.windows.window_mut::<EditorWindow>(id) else {
+            return;
+        };
+        if editor.buffer().borrow().is_read_only() {
+            self.report_save(Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "the buffer is read-only",
+            )));
+            return;
+        }
+        let Some(path) = editor.path() else {
+            let name = match self.buffers.uri_of(editor.buffer()) {
+                Some(BufferUri::Untitled(name)) => name,
+                _ => String::new(),
+            };
+            // Relative paths are saved relative to the working directory
+            let paths = PathCompleter::new(".");
+            self.input_box = Some(PendingInput {
+                input: InputBoxWindow::new("Save As", name).with_completer(paths),
+                purpose: InputPurpose::SaveAs(id),
+            });
+            return;
+        };
+        let saved = if self.buffers.contains(&path) {
+            self.buffers.save(&path)
+        } else {
+            editor.save()
+        };
+        self.report_save(saved);
+    }
+
+    /// Save an editor's buffer to `path`, which it keeps from then on.
+    fn save_editor_as(&mut self, id: WindowId, path: &str) -> io::Result<()> {
+        if path.trim().is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "a file name is required",
+            ));
+        }
+        let buffer = self
+            .windows
+            .window::<EditorWindow>(id)
+            .map(|editor| SharedBuffer::clone(editor.buffer()))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "the editor was closed"))?;
+        self.buffers.save_as(&buffer, path)?;
+        self.report_save(Ok(()));
+        Ok(())
+    }
+
+    /// Ask for the path to export the editor `id` to in `format`,
+    /// suggesting its file's path with the format's extension added.
+    fn prompt_export(&mut self, id: WindowId, format: ExportFormat) {
+        let Some(editor) = self.windows.window::<EditorWindow>(id) else {
+            return;
+        };
+        let name = match (editor.path(), self.buffers.uri_of(editor.buffer())) {
+            (Some(path), _) => path.display().to_string(),
+            (None, Some(BufferUri::Untitled(name))) => name,
+            (None, _) => "Untitled".to_string(),
+        };
+        // Relative paths are written relative to the working directory
+        let paths = PathCompleter::new(".");
+        self.input_box = Some(PendingInput {
+            input: InputBoxWindow::new(
+                format!("Export as {}", format.name()),
+                format!("{name}.{}", format.extension()),
+            )
+            .with_completer(paths),
+            purpose: InputPurpose::Export(id, format),
+        });
+    }
+
+    /// Write the selection of the editor `id`, or its whole document if
+    /// nothing is selected, highlighted in `format` to the file `path`.
+    ///
+    /// The file is replaced if it exists. Success is announced in the
+    /// notification center.
+    pub fn export_editor(
+        &mut self,
+        id: WindowId,
+        format: ExportFormat,
+        path: &str,
+    ) -> io::Result<()> {
+        if path.trim().is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "a file name is required",
+            ));
+        }
+        let exported = self
+            .windows
+            .window_mut::<EditorWindow>(id)
+            .map(|editor| editor.export(format))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "the editor was closed"))?;
+        fs::write(path, exported)?;
+        self.notifications().notify(
+            "Editor",
+            NotificationSeverity::Info,
+            format!("Exported as {} to {path}", format.name()),
+        );
+        Ok(())
+    }
+
+    /// Show a failed save in the status bar, or clear the segment after a
+    /// successful one.
+    fn report_save(&self, saved: io::Result<()>) {
+        let update = match saved {
+            Ok(()) => StatusBarUpdate::RemoveSegment(SAVE_SEGMENT.to_string()),
+            Err(error) => {
+                tracing::error!("save failed: {error}");
+                StatusBarUpdate::SetSegment {
+                    id: SAVE_SEGMENT.to_string(),
+                    text: format!("Save failed: {error}"),
+                }
+            }
+        };
+        self.status_bar_updates.emit(update);
+    }
+
+    /// Open the quick-open picker over the files of every workspace root.
+    ///
+    /// Files ignored by `.gitignore` or `.ignore` are not listed. Typing
+    /// filters the list by fuzzy matching the relative paths, which start
+    /// with the root's folder name when the workspace has several.
+    fn open_quick_open(&mut self) {
+        let paths = match self.workspace.walk_files(MAX_QUICK_OPEN_FILES) {
+            Ok(paths) => paths,
+            Err(error) => {
+                self.report_files_error(format!("Cannot list the workspace: {error}"));
+                return;
+            }
+        };
+        let items = paths
+            .iter()
+            .map(|path| self.workspace.label(path))
+            .collect();
+        self.quick_open = Some(QuickOpenMenu {
+            picker: QuickPickWindow::new("Go to File", items),
+            paths,
+        });
+    }
+
+    /// Prompt for the name of a new file or folder in the explorer's folder
+    /// under the cursor.
+    fn prompt_explorer_name(&mut self, folder: bool) {
+        let Some(dir) = self.explorer().map(FileTreeWindow::target_dir) else {
+            return;
+        };
+        let paths = PathCompleter::new(&dir);
+        let (title, purpose) = if folder {
+            ("New Folder", InputPurpose::NewFolder(dir))
+        } else {
+            ("New File", InputPurpose::NewFile(dir))
+        };
+        self.input_box = Some(PendingInput {
+            input: InputBoxWindow::new(title, "").with_completer(paths),
+            purpose,
+        });
+    }
+
+    /// Prompt for a folder to add as a root of the workspace, relative to
+    /// the primary root.
+    fn prompt_root_folder(&mut self) {
+        let paths = PathCompleter::new(self.workspace.primary());
+        self.input_box = Some(PendingInput {
+            input: InputBoxWindow::new("Add Folder to Workspace", "").with_completer(paths),
+            purpose: InputPurpose::RootFolder,
+        });
+    }
+
+    /// Add the folder `value` names, relative to the primary root, as a
+    /// root of the workspace.
+    fn add_root_folder_from_input(&mut self, value: &str) -> Result<(), String> {
+        let path = self.workspace.primary().join(value.trim());
+        if !path.is_dir() {
+            return Err(format!("{} is not a folder", path.display()));
+        }
+        let root = fs::canonicalize(&path).unwrap_or(path);
+        if self.workspace.canonical().roots().contains(&root) {
+            return Err(format!("{} is already in the workspace", root.display()));
+        }
+        self.add_workspace_folder(root);
+        Ok(())
+    }
+
+    /// Remove the workspace root under the explorer's cursor.
+    ///
+    /// The primary root cannot be removed; other rows are ignored.
+    fn remove_selected_root_folder(&mut self) {
+        let Some(path) = self
+            .explorer()
+            .and_then(FileTreeWindow::selected)
+            .map(|row| row.path.clone())
+        else {
+            return;
+        };
+        let canonical = self.workspace.canonical();
+        let Some(index) = canonical.roots().iter().position(|root| *root == path) else {
+            return;
+        };
+        let root = self.workspace.roots()[index].clone();
+        self.remove_workspace_folder(&root);
+    }
+
+    /// Prompt for a new name for the explorer entry under the cursor.
+    fn prompt_explorer_rename(&mut self) {
+        let Some(path) = self
+            .explorer()
+            .and_then(FileTreeWindow::selected)
+            .map(|row| row.path.clone())
+        else {
+            return;
+        };
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let paths = PathCompleter::new(path.parent().unwrap_or(Path::new("/")));
+        self.input_box = Some(PendingInput {
+            input: InputBoxWindow::new("Rename", name).with_completer(paths),
+            purpose: InputPurpose::Rename(path),
+        });
+    }
+
+    /// Create a file or folder named `name` in `dir` and reveal it in the
+    /// explorer. A new file is also opened in the editor.
+    fn create_explorer_entry(
+        &mut self,
+        dir: &Path,
+        name: &str,
+        folder: bool,
+    ) -> Result<(), String> {
+        let operations = self.file_operations();
+        let created = if folder {
+            operations.create_folder(dir, name)
+        } else {
+            operations.create_file(dir, name)
+        };
+        let path = created.map_err(|error| error.to_string())?;
+        if let Some(explorer) = self.explorer_mut() {
+            explorer.refresh();
+            explorer.reveal(&path);
+        }
+        if !folder {
+            self.open_file(&path).map_err(|error| error.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Rename the entry at `path` to `name` and reveal it in the explorer.
+    fn rename_explorer_entry(&mut self, path: &Path, name: &str) -> Result<(), String> {
+        let renamed = self
+            .file_operations()
+            .rename(path, name)
+            .map_err(|error| error.to_string())?;
+        if let Some(explorer) = self.explorer_mut() {
+            explorer.refresh();
+            explorer.reveal(&renamed);
+        }
+        Ok(())
+    }
+
+    /// Move the explorer's marked entries, or the one under the cursor, to
+    /// the trash.
+    fn delete_explorer_targets(&mut self) {
+        let Some(explorer) = self.explorer_mut() else {
+            return;
+        };
+        let targets = explorer.targets();
+        explorer.clear_marks();
+        let results = self.file_operations().delete_all(&targets);
+        self.report_file_results("Delete", &results);
+    }
+
+    /// Move the explorer's marked entries into the folder under the cursor.
+    fn move_explorer_marks(&mut self) {
+        let Some(explorer) = self.explorer_mut() else {
+            return;
+        };
+        let marked = explorer.selection().marked();
+        if marked.is_empty() {
+            self.report_files_error("Mark entries with Space to move them".to_string());
+            return;
+        }
+        let dir = explorer.target_dir();
+        explorer.clear_marks();
+        let results = self.file_operations().move_all(&marked, &dir);
+        self.report_file_results("Move", &results);
+    }
+
+    /// Show the first failure of a bulk file operation in the status bar,
+    /// or clear the segment if every entry succeeded.
+    fn report_file_results(&self, verb: &str, results: &[Result<PathBuf, FileOperationError>]) {
+        let errors: Vec<&FileOperationError> = results
+            .iter()
+            .filter_map(|result| result.as_ref().err())
+            .collect();
+        let update = match errors.as_slice() {
+            [] => StatusBarUpdate::RemoveSegment(FILES_SEGMENT.to_string()),
+            [error] => StatusBarUpdate::SetSegment {
+                id: FILES_SEGMENT.to_string(),
+                text: format!("{verb} failed: {error}"),
+            },
+            [error, rest @ ..] => StatusBarUpdate::SetSegment {
+                id: FILES_SEGMENT.to_string(),
+                text: format!("{verb} failed: {error} (+{} more)", rest.len()),
+            },
+        };
+        self.status_bar_updates.emit(update);
+    }
+
+    /// Show a file listing or opening problem in the status bar.
+    fn report_files_error(&self, message: String) {
+        self.status_bar_updates.emit(StatusBarUpdate::SetSegment {
+            id: FILES_SEGMENT.to_string(),
+            text: message,
+        });
+    }
+
+    /// Open the command line, completing the currently registered
+    /// commands.
+    fn open_command_line(&mut self) {
+        let commands = self
+            .services
+            .resolve::<CommandService>()
+            .expect("CommandService is registered in App::new");
+        let ids = commands
+            .commands()
+            .into_iter()
+            .map(|command| command.id)
+            .collect();
+        let mut command_line = CommandLineWindow::new(
+            ids,
+            self.workspace.primary().to_path_buf(),
+            self.command_line_history.clone(),
+        );
+        command_line.set_theme(&self.theme());
+        self.command_line = Some(command_line);
+    }
+
+    /// Open the command palette over the currently registered commands.
+    fn open_command_palette(&mut self) {
+        let commands = self
+            .services
+            .resolve::<CommandService>()
+            .expect("CommandService is registered in App::new");
+        self.command_palette = Some(CommandPaletteWindow::new(commands.commands()));
+    }
+
+    /// Open the quick-fix menu for the editor selection.
+    ///
+    /// Does nothing if there is no provider, the editor has no path, or no
+    /// actions are available.
+    fn open_quick_fix(&mut self) {
+        let (Some(provider), Some(path)) = (&self.code_action_provider, self.editor().path())
+        else {
+            return;
+        };
+        let actions =
+            provider.code_actions(&path, &self.editor().text(), self.editor().selection());
+        if actions.is_empty() {
+            return;
+        }
+        let titles = actions.iter().map(|action| action.title.clone()).collect();
+        self.quick_fix = Some(QuickFixMenu {
+            picker: QuickPickWindow::new("Quick Fix", titles),
+            actions,
+        });
+    }
+
+    /// Open the inline rename input for the symbol at the editor selection.
+    ///
+    /// Does nothing if there is no provider, the editor has no path, or the
+    /// provider's `prepare_rename` rejects the symbol.
+    fn open_rename(&mut self) {
+        let (Some(provider), Some(path)) = (&self.rename_provider, self.editor().path()) else {
+            return;
+        };
+        let Ok(prepared) = provider.prepare_rename(
+            &path,
+            &self.editor().text(),
+            self.editor().selection().start,
+        ) else {
+            return;
+        };
+        let mut input = RenameInputWindow::new(prepared.range, prepared.placeholder);
+        input.set_error(provider.validate_name(input.name()).err());
+        self.editor_mut().open_rename(input);
+    }
+
+    /// Peek the definition of the symbol at the editor selection.
+    ///
+    /// Definitions in the editor's own document are shown from the buffer;
+    /// other documents are read from disk. Does nothing if there is no
+    /// provider, the editor has no path, or the definition can't be resolved.
+    fn peek_definition(&mut self) {
+        let (Some(provider), Some(path)) = (&self.definition_provider, self.editor().path()) else {
+            return;
+        };
+        let Some(location) = provider.definition(
+            &path,
+            &self.editor().text(),
+            self.editor().selection().start,
+        ) else {
+            return;
+        };
+        let text = if location.path == path {
+            self.editor().text().to_string()
+        } else {
+            match std::fs::read_to_string(&location.path) {
+                Ok(text) => text,
+                Err(_) => return,
+            }
+        };
+        self.editor_mut().open_peek(PeekWindow::new(location, text));
+    }
+
+    /// Open the link under the target editor's cursor: a URL with the
+    /// [`OpenerService`], a file in the editor.
+    ///
+    /// Links come from the document link provider for documents with a
+    /// path, and are detected in the text otherwise. Relative paths are
+    /// relative to the directory of the editor's document, or to the
+    /// workspace root for untitled ones. Does nothing if there is no link
+    /// under the cursor.
+    fn open_link(&mut self) {
+        let id = self.target_editor();
+        let Some(editor) = self.windows.window::<EditorWindow>(id) else {
+            return;
+        };
+        let path = editor.path();
+        let link = match (&self.document_link_provider, &path) {
+            (Some(provider), Some(path)) => document_link::link_at(
+                &provider.document_links(path, &editor.text()),
+                editor.selection_head(),
+            )
+            .cloned(),
+            _ => editor.link_under_cursor(),
+        };
+        let Some(link) = link else {
+            return;
+        };
+        match link.target {
+            LinkTarget::Url(url) => {
+                if let Err(error) = self.opener().open(&url) {
+                    self.notifications().notify(
+                        "Links",
+                        NotificationSeverity::Warning,
+                        format!("Cannot open {url}: {error}"),
+                    );
+                }
+            }
+            LinkTarget::File(target) => {
+                let base = path
+                    .as_deref()
+                    .and_then(Path::parent)
+                    .unwrap_or_else(|| self.workspace_root());
+                let target = base.join(target);
+                match self.open_file(&target) {
+                    Ok(()) => self.focus_manager.set_focus(id),
+                    Err(error) => {
+                        self.report_files_error(format!(
+                            "Cannot open {}: {error}",
+                            target.display()
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Ask the document link provider for the links of the target editor's
+    /// document when its path or text changed since the last request.
+    ///
+    /// Other editors, and documents without a path, detect their links in
+    /// the text.
+    fn refresh_document_links(&mut self) {
+        let Some(provider) = &self.document_link_provider else {
+            return;
+        };
+        let id = self.target_editor();
+        let Some(editor) = self.windows.window_mut::<EditorWindow>(id) else {
+            return;
+        };
+        let Some(path) = editor.path() else {
+            return;
+        };
+        let text = editor.text();
+        let mut hasher = DefaultHasher::new();
+        path.hash(&mut hasher);
+        text.hash(&mut hasher);
+        let requested = (id, hasher.finish());
+        if self.document_links_requested != Some(requested) {
+            editor.set_document_links(Some(provider.document_links(&path, &text)));
+            self.document_links_requested = Some(requested);
+        }
+    }
+
+    /// Render the application to a frame.
+    ///
+    /// Renders the open windows from the layout tree above a single-row
+    /// status bar. The focused window gets a visual indicator.
+    pub fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.refresh_status_bar();
+        self.refresh_document_links();
+        self.refresh_pane_themes();
+        let panes = self.panes_rect(area);
+        self.windows
+            .render(frame, panes, self.focus_manager.focused());
+        if self.scrollbars {
+            self.render_scrollbars(frame, panes);
+        }
+        self.status_bar.render(frame, Self::status_bar_rect(area));
+
+        let theme = self.theme();
+        let banner = self.banner_rect(area);
+        if banner.height > 0 {
+            let style = theme.warning.add_modifier(Modifier::REVERSED);
+            frame.render_widget(Paragraph::new(SAFE_MODE_BANNER).style(style), banner);
+        }
+        if let Some(palette) = self.command_palette.as_mut() {
+            let palette_area = Self::palette_area(area);
+            palette.set_theme(&theme);
+            frame.render_widget(Clear, palette_area);
+            palette.render(frame, palette_area);
+        }
+        if let Some(menu) = self.quick_fix.as_mut() {
+            let menu_area = Self::palette_area(area);
+            frame.render_widget(Clear, menu_area);
+            menu.picker.render(frame, menu_area);
+        }
+        if let Some(open) = self.context_menu.as_mut() {
+            let menu_area = open.menu.area(open.anchor, panes);
+            open.menu.set_theme(&theme);
+            frame.render_widget(Clear, menu_area);
+            open.menu.render(frame, menu_area);
+        }
+        if let Some(menu) = self.quick_open.as_mut() {
+            let menu_area = Self::palette_area(area);
+            frame.render_widget(Clear, menu_area);
+            menu.picker.render(frame, menu_area);
+        }
+        if let Some(menu) = self.branch_picker.as_mut() {
+            let menu_area = Self::palette_area(area);
+            frame.render_widget(Clear, menu_area);
+            menu.picker.render(frame, menu_area);
+        }
+        if let Some(menu) = self.stash_picker.as_mut() {
+            let menu_area = Self::palette_area(area);
+            frame.render_widget(Clear, menu_area);
+            menu.picker.render(frame, menu_area);
+        }
+        if let Some(menu) = self.task_picker.as_mut() {
+            let menu_area = Self::palette_area(area);
+            frame.render_widget(Clear, menu_area);
+            menu.picker.render(frame, menu_area);
+        }
+        if let Some(pending) = self.input_box.as_mut() {
+            let input_area = Rect {
+                height: INPUT_BOX_HEIGHT.min(area.height),
+                ..Self::palette_area(area)
+            };
+            pending.input.set_theme(&theme);
+            frame.render_widget(Clear, input_area);
+            pending.input.render(frame, input_area);
+        }
+        if let Some(command_line) = self.command_line.as_mut() {
+            let status_bar = Self::status_bar_rect(area);
+            frame.render_widget(Clear, status_bar);
+            command_line.render(frame, status_bar);
+        }
+        if let Some(open) = self.dialog.as_mut() {
+            let palette_area = Self::palette_area(area);
+            let dialog_area = Rect {
+                height: open.dialog.preferred_height(palette_area.height),
+                ..palette_area
+            };
+            frame.render_widget(Clear, dialog_area);
+            open.dialog.render(frame, dialog_area);
+        }
+        self.render_tutorial(frame, panes);
+        self.render_toasts(frame, panes);
+        self.dirty = false;
+    }
+
+    /// Draw the scrollbar of every scrollable window laid out in `area`
+    /// over its right border. Compact windows have no right border and no
+    /// scrollbar.
+    fn render_scrollbars(&self, frame: &mut Frame, area: Rect) {
+        let theme = self.theme();
+        let focused = self.focus_manager.focused();
+        for (id, rect) in self.windows.rects(area) {
+            if self.is_compact(id) {
+                continue;
+            }
+            let Some(position) = self
+                .windows
+                .get(id)
+                .and_then(|window| window.scroll_position())
+            else {
+                continue;
+            };
+            let viewport = usize::from(rect.height.saturating_sub(2));
+            let style = theme.border_style(focused == Some(id));
+            scrollbar::render(frame.buffer_mut(), rect, position, viewport, style);
+        }
+    }
+
+    /// Draw the tutorial panel, while it runs, over the top right of
+    /// `area`.
+    fn render_tutorial(&self, frame: &mut Frame, area: Rect) {
+        let Some(tutorial) = &self.tutorial else {
+            return;
+        };
+        let width = TUTORIAL_WIDTH.min(area.width);
+        let panel_area = Rect {
+            x: area.right() - width,
+            y: area.y,
+            width,
+            height: TUTORIAL_HEIGHT.min(area.height),
+        };
+        let mut panel = TutorialWindow::new(tutorial.clone());
+        panel.set_theme(&self.theme());
+        frame.render_widget(Clear, panel_area);
+        panel.render(frame, panel_area);
+    }
+
+    /// Draw the toasts laid out by [`App::toasts`] over `area`.
+    fn render_toasts(&self, frame: &mut Frame, area: Rect) {
+        for (mut toast, _, toast_area) in self.toasts(area) {
+            toast.set_theme(&self.theme());
+            frame.render_widget(Clear, toast_area);
+            toast.render(frame, toast_area);
+        }
+    }
+
+    /// Lay out a toast for each running operation shown as a notification
+    /// and each notification shown as a toast, stacked up from the bottom
+    /// right of `area`: notifications below operations, newest at the
+    /// bottom. Each comes with the notification it shows, if any.
+    fn toasts(&self, area: Rect) -> Vec<(Box<dyn Window>, Option<NotificationId>, Rect)> {
+        let mut toasts: Vec<(Box<dyn Window>, Option<NotificationId>, u16)> = Vec::new();
+        for item in self.progress().active() {
+            if item.location == ProgressLocation::Notification {
+                toasts.push((Box::new(ProgressToastWindow::new(item)), None, TOAST_HEIGHT));
+            }
+        }
+        for notification in self.notifications().toasts() {
+            let id = notification.id;
+            toasts.push((
+                Box::new(NotificationToastWindow::new(notification)),
+                Some(id),
+                NOTIFICATION_HEIGHT,
+            ));
+        }
+        let width = TOAST_WIDTH.min(area.width);
+        let mut bottom = area.bottom();
+        let mut laid_out = Vec::new();
+        for (toast, id, height) in toasts.into_iter().rev() {
+            if bottom < area.y + height {
+                break;
+            }
+            bottom -= height;
+            let toast_area = Rect {
+                x: area.right() - width,
+                y: bottom,
+                width,
+                height,
+            };
+            laid_out.push((toast, id, toast_area));
+        }
+        laid_out
+    }
+
+    /// Compute the overlay area for the command palette, quick-fix menu
+    /// and quick-open picker.
+    /// Input boxes use its top rows.
+    ///
+    /// The overlay is horizontally centered near the top of the screen.
+    fn palette_area(area: Rect) -> Rect {
+        let width = (area.width * 3 / 5).max(30).min(area.width);
+        let height = area.height.min(12);
+        Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height).min(1),
+            width,
+            height,
+        }
+    }
+
+    /// Get the editor and terminal rects for the given area.
+    ///
+    /// A closed terminal gets an empty rect. Useful for testing to verify
+    /// layout calculations; see [`App::window_rects`] for every window.
+    pub fn layout_rects(&self, area: Rect) -> (Rect, Rect) {
+        let rects = self.window_rects(area);
+        let rect_of = |id| {
+            rects
+                .iter()
+                .find(|(window, _)| *window == id)
+                .map_or(Rect::default(), |(_, rect)| *rect)
+        };
+        (rect_of(self.editor_id), rect_of(self.terminal_id))
+    }
+
+    /// Get the area of every open window, in layout order.
+    ///
+    /// The windows share the area between the safe mode banner, if shown,
+    /// and the status bar.
+    pub fn window_rects(&self, area: Rect) -> Vec<(WindowId, Rect)> {
+        self.windows.rects(self.panes_rect(area))
+    }
+
+    /// Get the area shared by the windows: all of `area` between the safe
+    /// mode banner, if shown, and the status bar.
+    fn panes_rect(&self, area: Rect) -> Rect {
+        let banner = self.banner_rect(area).height;
+        Rect {
+            y: area.y + banner,
+            height: area
+                .height
+                .saturating_sub(STATUS_BAR_HEIGHT)
+                .saturating_sub(banner),
+            ..area
+        }
+    }
+
+    /// Get the safe mode banner row: the top row of `area` in safe mode,
+    /// nothing otherwise.
+    fn banner_rect(&self, area: Rect) -> Rect {
+        let height = if self.safe_mode {
+            SAFE_MODE_BANNER_HEIGHT.min(area.height.saturating_sub(STATUS_BAR_HEIGHT))
+        } else {
+            0
+        };
+        Rect { height, ..area }
+    }
+
+    /// Get the status bar row: the bottom row of `area`.
+    pub fn status_bar_rect(area: Rect) -> Rect {
+        let height = STATUS_BAR_HEIGHT.min(area.height);
+        Rect {
+            y: area.bottom() - height,
+            height,
+            ..area
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tutorial::TutorialStep;
+    use crate::window::DiffLayout;
+    use cli_ide_base::test_support::{temp_dir, temp_path, Cleanup};
+    use cli_ide_platform::context::context_key_service::ContextValue;
+    use cli_ide_platform::problems::problem::Severity;
+    use cli_ide_platform::task::task_service::TaskOutcome;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_app_new() {
+        let app = App::new();
+        assert!(app.is_running());
+        assert_eq!(app.focused(), FocusedPane::Editor);
+        assert_eq!(app.size(), (80, 24));
+    }
+
+    #[test]
+    fn test_app_with_size() {
+        let app = App::with_size(120, 40);
+        assert_eq!(app.size(), (120, 40));
+    }
+
+    #[test]
+    fn test_quit_on_q() {
+        let mut app = App::new();
+        assert!(app.is_running());
+
+        app.handle_event(AppEvent::Key(AppKey::Q));
+
+        assert!(!app.is_running());
+    }
+
+    #[test]
+    fn test_quit_on_esc() {
+        let mut app = App::new();
+        assert!(app.is_running());
+
+        app.handle_event(AppEvent::Key(AppKey::Esc));
+
+        assert!(!app.is_running());
+    }
+
+    #[test]
+    fn test_quit_asks_for_confirmation_while_work_is_running() {
+        let mut app = App::new();
+        let (started_tx, started) = std::sync::mpsc::channel();
+        let job = app.tasks().spawn("Indexing", move |token| {
+            started_tx.send(()).unwrap();
+            while !token.is_cancelled() {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+        });
+        started.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        app.handle_event(AppEvent::Key(AppKey::Q));
+        assert!(app.is_running());
+        assert_eq!(app.keybinding_mode(), KeybindingMode::Dialog);
+        let Some(Dialog::Confirm(dialog)) = app.dialog() else {
+            panic!("confirmation should open");
+        };
+        assert_eq!(dialog.items(), [format!("Indexing ({})", job.id())]);
+
+        // Esc keeps working instead of quitting
+        app.handle_event(AppEvent::Key(AppKey::Esc));
+        assert!(app.is_running());
+        assert!(app.dialog().is_none());
+
+        app.handle_event(AppEvent::Key(AppKey::Q));
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        assert!(!app.is_running());
+        job.cancel();
+    }
+
+    #[test]
+    fn test_quit_requested_listeners_can_veto_quitting() {
+        let mut app = App::new();
+        let listener = app
+            .on_quit_requested()
+            .listen(|request| request.veto("indexing is writing its cache"));
+
+        app.handle_event(AppEvent::Key(AppKey::Q));
+        assert!(app.is_running());
+        assert!(app.dialog().is_none());
+        let warning = app.notifications().notifications().pop().unwrap();
+        assert_eq!(warning.severity, NotificationSeverity::Warning);
+        assert_eq!(
+            warning.message,
+            "Quit cancelled: indexing is writing its cache"
+        );
+
+        drop(listener);
+        app.handle_event(AppEvent::Key(AppKey::Q));
+        assert!(!app.is_running());
+    }
+
+    #[test]
+    fn test_quit_asks_to_save_modified_files() {
+        let first = temp_path("quit_save_first.txt");
+        let second = temp_path("quit_save_second.txt");
+        std::fs::write(&first, "one").unwrap();
+        std::fs::write(&second, "two").unwrap();
+        let mut app = App::new();
+        app.open_file(&first).unwrap();
+        app.open_file(&second).unwrap();
+        app.editor_mut().insert_text("!");
+
+        app.handle_event(AppEvent::Key(AppKey::Q));
+        let Some(Dialog::Confirm(dialog)) = app.dialog() else {
+            panic!("save prompt should open");
+        };
+        assert_eq!(dialog.items(), [second.display().to_string()]);
+        app.handle_event(AppEvent::Key(AppKey::Esc));
+        assert!(app.is_running());
+
+        app.handle_event(AppEvent::Key(AppKey::Q));
+        app.handle_event(AppEvent::Key(AppKey::Char('y')));
+        assert!(!app.is_running());
+        assert_eq!(std::fs::read_to_string(&second).unwrap(), "!two");
+        assert_eq!(std::fs::read_to_string(&first).unwrap(), "one");
+
+        let mut app = App::new();
+        app.open_file(&first).unwrap();
+        app.editor_mut().insert_text("?");
+        app.handle_event(AppEvent::Key(AppKey::Q));
+        app.handle_event(AppEvent::Key(AppKey::Char('n')));
+        assert!(!app.is_running());
+        assert_eq!(std::fs::read_to_string(&first).unwrap(), "one");
+
+        std::fs::remove_file(first).unwrap();
+        std::fs::remove_file(second).unwrap();
+    }
+
+    #[test]
+    fn test_resize_updates_dimensions() {
+        let mut app = App::new();
+        assert_eq!(app.size(), (80, 24));
+
+        app.handle_event(AppEvent::Resize(100, 50));
+
+        assert_eq!(app.size(), (100, 50));
+    }
+
+    #[test]
+    fn test_focus_toggle() {
+        let mut app = App::new();
+        assert_eq!(app.focused(), FocusedPane::Editor);
+
+        app.handle_event(AppEvent::Key(AppKey::Tab));
+        assert_eq!(app.focused(), FocusedPane::Terminal);
+
+        app.handle_event(AppEvent::Key(AppKey::Tab));
+        assert_eq!(app.focused(), FocusedPane::Editor);
+    }
+
+    #[test]
+    fn test_focused_pane_toggle() {
+        assert_eq!(FocusedPane::Editor.toggle(), FocusedPane::Terminal);
+        assert_eq!(FocusedPane::Terminal.toggle(), FocusedPane::Editor);
+    }
+
+    #[test]
+    fn test_tick_does_not_change_state() {
+        let mut app = App::new();
+        let running_before = app.is_running();
+        let focused_before = app.focused();
+        let size_before = app.size();
+
+        app.handle_event(AppEvent::Tick);
+
+        assert_eq!(app.is_running(), running_before);
+        assert_eq!(app.focused(), focused_before);
+        assert_eq!(app.size(), size_before);
+    }
+
+    #[test]
+    fn test_window_ids_are_unique() {
+        let app = App::new();
+        assert_ne!(app.editor_id(), app.terminal_id());
+    }
+
+    #[test]
+    fn test_focused_id_tracks_editor() {
+        let app = App::new();
+        assert_eq!(app.focused_id(), Some(app.editor_id()));
+    }
+
+    #[test]
+    fn test_focused_id_tracks_terminal() {
+        let mut app = App::new();
+        app.handle_event(AppEvent::Key(AppKey::Tab));
+        assert_eq!(app.focused_id(), Some(app.terminal_id()));
+    }
+
+    #[test]
+    fn test_focus_manager_accessible() {
+        let app = App::new();
+        assert!(app.focus_manager().is_focused(app.editor_id()));
+    }
+
+    #[test]
+    fn test_keybinding_router_accessible() {
+        let app = App::new();
+        assert!(app.keybinding_router().is_globally_bound(AppKey::Q));
+    }
+
+    #[test]
+    fn test_focused_context_follows_focus() {
+        let mut app = App::new();
+        assert_eq!(app.focused_context(), Some(WindowContext::Editor));
+
+        app.handle_event(AppEvent::Key(AppKey::Tab));
+        assert_eq!(app.focused_context(), Some(WindowContext::Terminal));
+    }
+
+    #[test]
+    fn test_context_binding_only_applies_when_focused() {
+        let mut app = App::new();
+        app.keybinding_router_mut().register_for_context(
+            WindowContext::Terminal,
+            AppKey::Char('x'),
+            Action::Quit,
+        );
+
+        // Editor focused: 'x' is unbound
+        app.handle_event(AppEvent::Key(AppKey::Char('x')));
+        assert!(app.is_running());
+
+        // Terminal focused: 'x' quits
+        app.handle_event(AppEvent::Key(AppKey::Tab));
+        app.handle_event(AppEvent::Key(AppKey::Char('x')));
+        assert!(!app.is_running());
+    }
+
+    #[test]
+    fn test_builtin_commands_registered() {
+        let app = App::new();
+        let commands = app.services().resolve::<CommandService>().unwrap();
+        assert!(commands.contains("workbench.action.quit"));
+        assert!(commands.contains("workbench.action.toggleFocus"));
+    }
+
+    #[test]
+    fn test_execute_command_applies_action() {
+        let mut app = App::new();
+        app.execute_command("workbench.action.toggleFocus").unwrap();
+        assert_eq!(app.focused(), FocusedPane::Terminal);
+
+        app.execute_command("workbench.action.quit").unwrap();
+        assert!(!app.is_running());
+    }
+
+    #[test]
+    fn test_execute_unknown_command() {
+        let mut app = App::new();
+        assert!(app.execute_command("does.not.exist").is_err());
+        assert!(app.is_running());
+    }
+
+    #[test]
+    fn test_smart_select_commands_drive_editor() {
+        let mut app = App::new();
+        // "Welcome to Paradiddle.rs!" — place the cursor inside "Welcome"
+        app.editor_mut().set_selection(2..2);
+
+        app.execute_command("editor.action.expandBracketSelection")
+            .unwrap();
+        assert_eq!(app.editor().selected_text(), "Welcome");
+
+        app.execute_command("editor.action.shrinkBracketSelection")
+            .unwrap();
+        assert_eq!(app.editor().selection(), 2..2);
+    }
+
+    #[test]
+    fn test_palette_captures_keys() {
+        let mut app = App::new();
+        app.handle_event(AppEvent::Key(AppKey::Char(':')));
+        assert!(app.is_command_palette_open());
+
+        // Q is typed into the palette rather than quitting
+        app.handle_event(AppEvent::Key(AppKey::Q));
+        assert!(app.is_running());
+        assert_eq!(app.command_palette().unwrap().query(), "q");
+
+        // Esc closes the palette rather than quitting
+        app.handle_event(AppEvent::Key(AppKey::Esc));
+        assert!(!app.is_command_palette_open());
+        assert!(app.is_running());
+    }
+
+    #[test]
+    fn test_palette_executes_selected_command() {
+        let mut app = App::new();
+        app.handle_event(AppEvent::Key(AppKey::Char(':')));
+        for c in "toggle".chars() {
+            app.handle_event(AppEvent::Key(AppKey::Char(c)));
+        }
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+
+        assert!(!app.is_command_palette_open());
+        assert_eq!(app.focused(), FocusedPane::Terminal);
+    }
+
+    #[test]
+    fn test_keybinding_router_mutable() {
+        let mut app = App::new();
+        app.keybinding_router_mut()
+            .register_global(AppKey::Char('x'), Action::Quit);
+        assert!(app.keybinding_router().is_globally_bound(AppKey::Char('x')));
+    }
+
+    #[test]
+    fn test_click_focuses_pane_under_cursor() {
+        use crate::input::MouseButton;
+
+        let mut app = App::with_size(80, 24);
+        let click = |column| {
+            AppEvent::Mouse(MouseEvent::new(
+                MouseEventKind::Down(MouseButton::Left),
+                column,
+                5,
+            ))
+        };
+
+        app.handle_event(click(60));
+        assert_eq!(app.focused(), FocusedPane::Terminal);
+        app.handle_event(click(10));
+        assert_eq!(app.focused(), FocusedPane::Editor);
+    }
+
+    #[test]
+    fn test_window_at_outside_layout() {
+        let app = App::with_size(80, 24);
+        assert_eq!(app.window_at(0, 0), Some(app.editor_id()));
+        assert_eq!(app.window_at(79, 22), Some(app.terminal_id()));
+        assert_eq!(app.window_at(79, 23), None, "status bar row");
+        assert_eq!(app.window_at(80, 0), None);
+        assert_eq!(app.window_at(0, 24), None);
+    }
+
+    #[test]
+    fn test_scroll_targets_focused_window() {
+        let mut app = App::new();
+        *app.editor_mut() = EditorWindow::with_text("a\nb\nc");
+        let scroll_down = AppEvent::Mouse(MouseEvent::new(MouseEventKind::ScrollDown, 60, 5));
+
+        app.handle_event(scroll_down.clone());
+        assert_eq!(app.editor().scroll_offset(), 1);
+
+        // Scrolling is clamped to the last line
+        for _ in 0..5 {
+            app.handle_event(scroll_down.clone());
+        }
+        assert_eq!(app.editor().scroll_offset(), 2);
+        assert_eq!(app.terminal().unwrap().scroll_offset(), 0);
+
+        app.handle_event(AppEvent::Mouse(MouseEvent::new(
+            MouseEventKind::ScrollUp,
+            0,
+            0,
+        )));
+        assert_eq!(app.editor().scroll_offset(), 1);
+    }
+
+    #[test]
+    fn test_mouse_ignored_while_palette_open() {
+        use crate::input::MouseButton;
+
+        let mut app = App::with_size(80, 24);
+        app.handle_event(AppEvent::Key(AppKey::Char(':')));
+        app.handle_event(AppEvent::Mouse(MouseEvent::new(
+            MouseEventKind::Down(MouseButton::Left),
+            60,
+            5,
+        )));
+        assert_eq!(app.focused(), FocusedPane::Editor);
+        assert!(app.is_command_palette_open());
+    }
+
+    /// Offers one action that renames `x` in the editor and one that edits
+    /// a file that is not open.
+    struct RenameActions;
+
+    impl CodeActionProvider for RenameActions {
+        fn code_actions(
+            &self,
+            path: &std::path::Path,
+            text: &str,
+            _range: std::ops::Range<usize>,
+        ) -> Vec<CodeAction> {
+            use crate::code_action::TextEdit;
+
+            let start = text.find('x').unwrap();
+            let mut rename = WorkspaceEdit::new();
+            rename.push(path, TextEdit::new(start..start + 1, "count"));
+            let mut elsewhere = rename.clone();
+            elsewhere.push("other.rs", TextEdit::insert(0, "x"));
+            vec![
+                CodeAction {
+                    title: "Rename to count".to_string(),
+                    kind: Some("quickfix".to_string()),
+                    edit: rename,
+                },
+                CodeAction {
+                    title: "Rename everywhere".to_string(),
+                    kind: None,
+                    edit: elsewhere,
+                },
+            ]
+        }
+    }
+
+    fn app_with_quick_fix() -> App {
+        let mut app = App::new();
+        app.editor_mut().set_text("let x = 1;");
+        app.editor_mut().set_path(Some("main.rs".into()));
+        app.set_code_action_provider(Box::new(RenameActions));
+        app
+    }
+
+    #[test]
+    fn test_quick_fix_applies_selected_action() {
+        let mut app = app_with_quick_fix();
+
+        app.handle_event(AppEvent::Key(AppKey::Char('.')));
+        assert_eq!(app.quick_fix_menu().unwrap().matches().len(), 2);
+
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        assert!(app.quick_fix_menu().is_none());
+        assert_eq!(app.editor().text(), "let count = 1;");
+    }
+
+    #[test]
+    fn test_quick_fix_edit_with_unknown_file_is_not_applied() {
+        let mut app = app_with_quick_fix();
+
+        app.handle_event(AppEvent::Key(AppKey::Char('.')));
+        app.handle_event(AppEvent::Key(AppKey::Down));
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+
+        assert!(app.quick_fix_menu().is_none());
+        assert_eq!(app.editor().text(), "let x = 1;");
+    }
+
+    #[test]
+    fn test_quick_fix_esc_dismisses_without_quitting() {
+        let mut app = app_with_quick_fix();
+
+        app.execute_command("editor.action.quickFix").unwrap();
+        app.handle_event(AppEvent::Key(AppKey::Esc));
+
+        assert!(app.quick_fix_menu().is_none());
+        assert!(app.is_running());
+        assert_eq!(app.editor().text(), "let x = 1;");
+    }
+
+    #[test]
+    fn test_quick_fix_needs_provider_and_path() {
+        let mut app = App::new();
+        app.handle_event(AppEvent::Key(AppKey::Char('.')));
+        assert!(app.quick_fix_menu().is_none());
+
+        app.set_code_action_provider(Box::new(RenameActions));
+        app.handle_event(AppEvent::Key(AppKey::Char('.')));
+        assert!(app.quick_fix_menu().is_none(), "editor has no path");
+    }
+
+    /// Opens URLs by remembering them.
+    struct RecordingOpener(Arc<Mutex<Vec<String>>>);
+
+    impl cli_ide_platform::opener::opener_service::UrlOpener for RecordingOpener {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        fn open(&self, url: &str) -> io::Result<()> {
+            self.0.lock().unwrap().push(url.to_string());
+            Ok(())
+        }
+    }
+
+    /// Links every `[1]` to a footnote URL.
+    struct FootnoteLinks;
+
+    impl DocumentLinkProvider for FootnoteLinks {
+        fn document_links(
+            &self,
+            _path: &std::path::Path,
+            text: &str,
+        ) -> Vec<crate::document_link::DocumentLink> {
+            text.match_indices("[1]")
+                .map(|(start, _)| crate::document_link::DocumentLink {
+                    range: start..start + 3,
+                    target: LinkTarget::Url("https://example.com/1".to_string()),
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_open_link_opens_urls_and_relative_files() {
+        let target = temp_path("link-target.txt");
+        std::fs::write(&target, "linked").unwrap();
+        let name = target.file_name().unwrap().to_str().unwrap();
+        let text = format!("see https://example.com or ./{name}");
+        let (mut app, source) = app_with_open_file("link-source.md", &text);
+        let _cleanup = Cleanup::new([&target, &source]);
+        let opened = Arc::new(Mutex::new(Vec::new()));
+        app.set_opener_service(OpenerService::with_opener(Box::new(RecordingOpener(
+            Arc::clone(&opened),
+        ))));
+
+        app.editor_mut().set_selection(0..0);
+        app.execute_command("editor.action.openLink").unwrap();
+        assert!(
+            opened.lock().unwrap().is_empty(),
+            "no link under the cursor"
+        );
+
+        app.editor_mut().set_selection(8..8);
+        app.execute_command("editor.action.openLink").unwrap();
+        assert_eq!(*opened.lock().unwrap(), vec!["https://example.com"]);
+
+        let offset = text.find("./").unwrap() + 2;
+        app.editor_mut().set_selection(offset..offset);
+        app.execute_command("editor.action.openLink").unwrap();
+        assert_eq!(app.editor().text(), "linked");
+        assert_eq!(
+            app.editor().path(),
+            Some(std::fs::canonicalize(&target).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_document_link_provider_replaces_detected_links() {
+        let mut app = App::new();
+        app.editor_mut()
+            .set_text("see [1], not https://example.com");
+        app.editor_mut().set_path(Some("notes.md".into()));
+        app.set_document_link_provider(Box::new(FootnoteLinks));
+        let opened = Arc::new(Mutex::new(Vec::new()));
+        app.set_opener_service(OpenerService::with_opener(Box::new(RecordingOpener(
+            Arc::clone(&opened),
+        ))));
+
+        let backend = ratatui::backend::TestBackend::new(80, 24);
+        let mut terminal = ratatui::Terminal::new(backend).unwrap();
+        terminal
+            .draw(|frame| app.render(frame, frame.area()))
+            .unwrap();
+        let links = app.editor().document_links();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].range, 4..7);
+
+        app.editor_mut().set_selection(5..5);
+        app.execute_command("editor.action.openLink").unwrap();
+        app.editor_mut().set_selection(20..20);
+        app.execute_command("editor.action.openLink").unwrap();
+        assert_eq!(*opened.lock().unwrap(), vec!["https://example.com/1"]);
+    }
+
+    /// Resolves every symbol to the `fn target` in the same document.
+    struct SameFileDefinition;
+
+    impl DefinitionProvider for SameFileDefinition {
+        fn definition(
+            &self,
+            path: &std::path::Path,
+            text: &str,
+            _offset: usize,
+        ) -> Option<crate::definition::Location> {
+            let start = text.find("fn target")?;
+            Some(crate::definition::Location::new(path, start..start + 9))
+        }
+    }
+
+    fn app_with_definitions() -> App {
+        let mut app = App::new();
+        app.editor_mut()
+            .set_text("target();\n\nfn target() {\n    body();\n}");
+        app.editor_mut().set_path(Some("main.rs".into()));
+        app.set_definition_provider(Box::new(SameFileDefinition));
+        app
+    }
+
+    #[test]
+    fn test_peek_definition_opens_peek_at_target() {
+        let mut app = app_with_definitions();
+
+        app.execute_command("editor.action.peekDefinition").unwrap();
+
+        let peek = app.editor().peek().expect("peek should be open");
+        assert_eq!(peek.title(), "main.rs:3");
+        assert_eq!(peek.scroll_offset(), 1);
+    }
+
+    #[test]
+    fn test_peek_arrow_keys_scroll_and_esc_closes_without_quitting() {
+        let mut app = app_with_definitions();
+        app.execute_command("editor.action.peekDefinition").unwrap();
+
+        app.handle_event(AppEvent::Key(AppKey::Down));
+        assert_eq!(app.editor().peek().unwrap().scroll_offset(), 2);
+        app.handle_event(AppEvent::Key(AppKey::Up));
+        app.handle_event(AppEvent::Key(AppKey::Up));
+        assert_eq!(app.editor().peek().unwrap().scroll_offset(), 0);
+
+        app.handle_event(AppEvent::Key(AppKey::Esc));
+        assert!(app.editor().peek().is_none());
+        assert!(app.is_running());
+
+        app.handle_event(AppEvent::Key(AppKey::Esc));
+        assert!(!app.is_running(), "Esc quits once the peek is closed");
+    }
+
+    #[test]
+    fn test_peek_keys_only_apply_while_editor_focused() {
+        let mut app = app_with_definitions();
+        app.execute_command("editor.action.peekDefinition").unwrap();
+
+        app.handle_event(AppEvent::Key(AppKey::Tab));
+        app.handle_event(AppEvent::Key(AppKey::Esc));
+
+        assert!(!app.is_running());
+        assert!(app.editor().peek().is_some());
+    }
+
+    #[test]
+    fn test_peek_definition_needs_provider_and_path() {
+        let mut app = App::new();
+        app.editor_mut().set_text("fn target() {}");
+        app.execute_command("editor.action.peekDefinition").unwrap();
+        assert!(app.editor().peek().is_none());
+
+        app.set_definition_provider(Box::new(SameFileDefinition));
+        app.execute_command("editor.action.peekDefinition").unwrap();
+        assert!(app.editor().peek().is_none(), "editor has no path");
+    }
+
+    #[test]
+    fn test_status_bar_tracks_focus_mode_and_size() {
+        let mut app = App::with_size(100, 30);
+        assert_eq!(
+            app.status_bar().left_text(),
+            "Editor | NORMAL | Ln 1, Col 1"
+        );
+        assert_eq!(app.status_bar().right_text(), "100x30");
+
+        app.handle_event(AppEvent::Key(AppKey::Char(':')));
+        assert_eq!(app.keybinding_mode(), KeybindingMode::CommandPalette);
+        assert!(app.status_bar().left_text().contains("PALETTE"));
+
+        app.handle_event(AppEvent::Key(AppKey::Esc));
+        app.handle_event(AppEvent::Key(AppKey::Tab));
+        app.handle_event(AppEvent::Resize(90, 20));
+        assert!(app
+            .status_bar()
+            .left_text()
+            .starts_with("Terminal | NORMAL"));
+        assert_eq!(app.status_bar().right_text(), "90x20");
+    }
+
+    #[test]
+    fn test_status_bar_shows_editor_cursor() {
+        let mut app = App::new();
+        app.editor_mut().set_text("fn main() {\n    let é = 1;\n}");
+        let offset = app.editor().text().find('=').unwrap();
+        app.editor_mut().set_selection(offset..offset);
+        assert_eq!(app.editor().cursor_position(), (2, 11));
+
+        app.handle_event(AppEvent::Tick);
+        assert!(app.status_bar().left_text().ends_with("Ln 2, Col 11"));
+    }
+
+    #[test]
+    fn test_peek_mode_only_while_editor_focused() {
+        let mut app = app_with_definitions();
+        app.execute_command("editor.action.peekDefinition").unwrap();
+        assert_eq!(app.keybinding_mode(), KeybindingMode::Peek);
+
+        app.handle_event(AppEvent::Key(AppKey::Tab));
+        assert_eq!(app.keybinding_mode(), KeybindingMode::Normal);
+    }
+
+    #[test]
+    fn test_status_bar_segments_applied_on_next_event() {
+        let mut app = App::new();
+        let updates = app.status_bar_updates();
+        updates.emit(StatusBarUpdate::SetSegment {
+            id: "lsp".to_string(),
+            text: "rust-analyzer".to_string(),
+        });
+        assert!(app.status_bar().segments().is_empty());
+
+        app.handle_event(AppEvent::Tick);
+        assert_eq!(app.status_bar().right_text(), "rust-analyzer | 80x24");
+
+        updates.emit(StatusBarUpdate::RemoveSegment("lsp".to_string()));
+        app.handle_event(AppEvent::Tick);
+        assert!(app.status_bar().segments().is_empty());
+    }
+
+    /// Renames every whole-word occurrence of the identifier at the offset
+    /// within the same document; refuses to rename keywords.
+    struct SameFileRename;
+
+    impl SameFileRename {
+        fn word_at(text: &str, offset: usize) -> std::ops::Range<usize> {
+            let is_word = |c: char| c.is_alphanumeric() || c == '_';
+            let start = text[..offset].rfind(|c| !is_word(c)).map_or(0, |i| i + 1);
+            let end = text[offset..]
+                .find(|c| !is_word(c))
+                .map_or(text.len(), |i| offset + i);
+            start..end
+        }
+    }
+
+    impl RenameProvider for SameFileRename {
+        fn prepare_rename(
+            &self,
+            _path: &std::path::Path,
+            text: &str,
+            offset: usize,
+        ) -> Result<crate::rename::PrepareRename, String> {
+            let range = Self::word_at(text, offset);
+            match &text[range.clone()] {
+                "" | "let" => Err("Not a symbol".to_string()),
+                name => Ok(crate::rename::PrepareRename {
+                    placeholder: name.to_string(),
+                    range,
+                }),
+            }
+        }
+
+        fn rename(
+            &self,
+            path: &std::path::Path,
+            text: &str,
+            offset: usize,
+            new_name: &str,
+        ) -> Result<WorkspaceEdit, String> {
+            use crate::code_action::TextEdit;
+
+            let range = Self::word_at(text, offset);
+            let old = &text[range];
+            let mut edit = WorkspaceEdit::new();
+            for (start, _) in text.match_indices(old) {
+                if Self::word_at(text, start) == (start..start + old.len()) {
+                    edit.push(path, TextEdit::new(start..start + old.len(), new_name));
+                }
+            }
+            Ok(edit)
+        }
+    }
+
+    fn app_with_rename() -> App {
+        let mut app = App::new();
+        app.editor_mut().set_text("let x = 1;\nlet y = x + xx;");
+        app.editor_mut().set_path(Some("main.rs".into()));
+        app.editor_mut().set_selection(4..4);
+        app.set_rename_provider(Box::new(SameFileRename));
+        app
+    }
+
+    fn type_text(app: &mut App, text: &str) {
+        for c in text.chars() {
+            app.handle_event(AppEvent::Key(AppKey::Char(c)));
+        }
+    }
+
+    #[test]
+    fn test_rename_applies_edit_to_every_occurrence() {
+        let mut app = app_with_rename();
+
+        app.execute_command("editor.action.rename").unwrap();
+        let input = app.editor().rename_input().expect("rename should open");
+        assert_eq!(input.name(), "x");
+        assert_eq!(input.range(), 4..5);
+        assert_eq!(app.keybinding_mode(), KeybindingMode::Rename);
+
+        type_text(&mut app, "count");
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+
+        assert!(app.editor().rename_input().is_none());
+        assert_eq!(app.editor().text(), "let count = 1;\nlet y = count + xx;");
+    }
+
+    #[test]
+    fn test_find_replace_and_undo_in_the_editor() {
+        let mut app = App::new();
+        app.editor_mut().set_text("let a = 1;\nlet b = a + a;");
+        app.editor_mut().set_selection(0..0);
+
+        app.handle_event(AppEvent::Key(AppKey::Char('/')));
+        assert_eq!(app.keybinding_mode(), KeybindingMode::Find);
+        type_text(&mut app, "a");
+        let find = app.editor().find().expect("find should open");
+        assert_eq!(find.matches(), [4..5, 19..20, 23..24]);
+        assert_eq!(app.editor().decorations().len(), 3);
+        assert_eq!(app.editor().selection(), 4..5);
+
+        // Enter leaves the matches highlighted for n/N
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        assert_eq!(app.keybinding_mode(), KeybindingMode::Normal);
+        assert_eq!(app.editor().selection(), 19..20);
+        type_text(&mut app, "n");
+        assert_eq!(app.editor().selection(), 23..24);
+        type_text(&mut app, "nnN");
+        assert_eq!(app.editor().selection(), 4..5);
+
+        type_text(&mut app, "/");
+        app.handle_event(AppEvent::Key(AppKey::Down));
+        type_text(&mut app, "xy");
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        assert_eq!(app.editor().text(), "let xy = 1;\nlet b = a + a;");
+        assert_eq!(app.editor().selection(), 20..21);
+
+        app.handle_event(AppEvent::Key(AppKey::Up));
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        type_text(&mut app, "R");
+        assert_eq!(app.editor().text(), "let xy = 1;\nlet b = xy + xy;");
+        assert!(app.editor().find().unwrap().matches().is_empty());
+
+        // Replace all is one step of the undo history
+        type_text(&mut app, "u");
+        assert_eq!(app.editor().text(), "let xy = 1;\nlet b = a + a;");
+        type_text(&mut app, "u");
+        assert_eq!(app.editor().text(), "let a = 1;\nlet b = a + a;");
+        assert_eq!(app.editor().selection(), 4..5);
+        type_text(&mut app, "U");
+        assert_eq!(app.editor().text(), "let xy = 1;\nlet b = a + a;");
+
+        app.handle_event(AppEvent::Key(AppKey::Esc));
+        assert!(app.is_running());
+        assert!(app.editor().find().is_none());
+        assert!(app.editor().decorations().is_empty());
+    }
+
+    #[test]
+    fn test_editor_pages_and_scrolls_the_cursor_into_view() {
+        let mut app = App::new();
+        let text: Vec<String> = (1..=1000).map(|line| format!("line {line}")).collect();
+        app.editor_mut().set_text(text.join("\n"));
+        app.editor_mut().set_selection(0..0);
+        let backend = ratatui::backend::TestBackend::new(80, 24);
+        let mut terminal = ratatui::Terminal::new(backend).unwrap();
+        let mut draw = |app: &mut App| {
+            terminal
+                .draw(|frame| app.render(frame, frame.area()))
+                .unwrap();
+            let buffer = terminal.backend().buffer();
+            (1..30)
+                .map(|x| buffer.cell((x, 1)).unwrap().symbol().to_string())
+                .collect::<String>()
+        };
+        draw(&mut app);
+        let page = app.editor().viewport().page();
+        assert!(page > 1);
+
+        app.handle_event(AppEvent::Key(AppKey::PageDown));
+        assert_eq!(app.editor().viewport().top_line, page);
+        assert_eq!(app.editor().cursor_position(), (page + 1, 1));
+        assert!(draw(&mut app).starts_with(&format!("line {} ", page + 1)));
+
+        // Line scrolling leaves the cursor where it is
+        app.execute_command("editor.action.scrollLineDown").unwrap();
+        assert_eq!(app.editor().viewport().top_line, page + 1);
+        assert_eq!(app.editor().cursor_position(), (page + 1, 1));
+        app.handle_event(AppEvent::Key(AppKey::PageUp));
+        assert_eq!(app.editor().cursor_position(), (1, 1));
+        assert_eq!(
+            app.editor().viewport().top_line,
+            0,
+            "the cursor stays visible"
+        );
+
+        app.editor_mut().set_cursor_position(500, 3);
+        let viewport = app.editor().viewport();
+        assert!(viewport.lines().contains(&499));
+        assert_eq!(viewport.lines().end, 500, "scrolls as little as possible");
+        assert!(draw(&mut app).starts_with(&format!("line {} ", 501 - viewport.height)));
+    }
+
+    #[test]
+    fn test_modal_editing_moves_in_normal_mode_and_types_in_insert_mode() {
+        let mut app = App::new();
+        app.editor_mut().set_text("abc\ndef");
+        app.editor_mut().set_selection(0..0);
+        let keys = |app: &mut App, keys: &str| {
+            for c in keys.chars() {
+                app.handle_event(AppEvent::Key(AppKey::Char(c)));
+            }
+        };
+
+        app.execute_command("workbench.action.toggleModalEditing")
+            .unwrap();
+        assert_eq!(app.editing_mode(), Some(EditingMode::Normal));
+        keys(&mut app, "jl");
+        assert_eq!(app.editor().cursor_position(), (2, 2));
+        assert_eq!(app.editor().text(), "abc\ndef");
+
+        keys(&mut app, "ihjkl");
+        assert_eq!(app.keybinding_mode(), KeybindingMode::Insert);
+        assert!(app.status_bar().left_text().contains("INSERT"));
+        app.handle_event(AppEvent::Key(AppKey::Q));
+        assert_eq!(app.editor().text(), "abc\ndhjklqef");
+        assert!(app.is_running(), "q types in Insert mode");
+
+        app.handle_event(AppEvent::Key(AppKey::Esc));
+        assert_eq!(app.editing_mode(), Some(EditingMode::Normal));
+        app.handle_event(AppEvent::Key(AppKey::Esc));
+        assert!(app.is_running(), "Esc does not quit in Normal mode");
+
+        app.editor_mut().set_cursor_position(1, 1);
+        keys(&mut app, "vll");
+        assert_eq!(app.keybinding_mode(), KeybindingMode::Visual);
+        assert_eq!(app.editor().selected_text(), "abc");
+        keys(&mut app, "x");
+        assert_eq!(app.editor().text(), "\ndhjklqef");
+        assert_eq!(app.editing_mode(), Some(EditingMode::Normal));
+
+        app.execute_command("workbench.action.toggleModalEditing")
+            .unwrap();
+        assert_eq!(app.editing_mode(), None);
+        keys(&mut app, "j");
+        assert_eq!(app.editor().text(), "\ndhjklqef");
+        assert_eq!(app.editor().cursor_position(), (1, 1));
+    }
+
+    #[test]
+    fn test_insert_mode_types_q_in_its_case() {
+        let mut app = App::new();
+        app.editor_mut().set_text("");
+        app.execute_command("workbench.action.toggleModalEditing")
+            .unwrap();
+        app.handle_event(AppEvent::Key(AppKey::Char('i')));
+        for c in "Qq Q".chars() {
+            app.handle_event(AppEvent::Key(AppKey::from_char(c)));
+        }
+        assert_eq!(app.editor().text(), "Qq Q");
+        assert!(app.is_running());
+    }
+
+    #[test]
+    fn test_cursor_steps_over_grapheme_clusters_and_keeps_screen_columns() {
+        let mut app = App::new();
+        // Two wide ideographs, and `e` with a combining acute accent
+        app.editor_mut().set_text("漢字x\nabcde\ne\u{301}z");
+        let second = "漢字x\n".len();
+        let third = second + "abcde\n".len();
+
+        app.editor_mut().set_selection(second + 4..second + 4);
+        app.editor_mut().move_cursor(-1, 0);
+        assert_eq!(app.editor().selection(), 6..6, "x is four cells in");
+        app.editor_mut().move_cursor(1, 0);
+        assert_eq!(app.editor().selection(), second + 4..second + 4);
+        app.editor_mut().set_selection(second + 3..second + 3);
+        app.editor_mut().move_cursor(-1, 0);
+        assert_eq!(app.editor().selection(), 3..3, "the second cell of 字");
+        app.editor_mut().move_cursor(0, -1);
+        assert_eq!(app.editor().selection(), 0..0);
+
+        app.editor_mut().move_cursor(2, 1);
+        let after_accent = third + "e\u{301}".len();
+        assert_eq!(app.editor().selection(), after_accent..after_accent);
+        app.editor_mut().move_cursor(0, -1);
+        app.editor_mut().delete_forward();
+        assert_eq!(app.editor().text(), "漢字x\nabcde\nz");
+        app.editor_mut().set_selection(3..3);
+        app.editor_mut().delete_backward();
+        assert_eq!(app.editor().text(), "字x\nabcde\nz");
+        app.editor_mut().select_inclusive(0, 0);
+        assert_eq!(app.editor().selected_text(), "字");
+    }
+
+    #[test]
+    fn test_abbreviations_expand_as_words_end_in_insert_mode() {
+        let mut app = App::new();
+        app.editor_mut().set_text("");
+        let config = Config::parse(
+            "[abbreviations]\nteh = \"the\"\n\"a-b\" = \"c\"\n\
+             [abbreviations.rust]\nteh = \"THE\"\nfun = \"fn $0() {\\n}\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            app.apply_config(&config),
+            vec!["abbreviation `a-b` is not a word".to_string()]
+        );
+        let keys = |app: &mut App, keys: &str| {
+            for c in keys.chars() {
+                app.handle_event(AppEvent::Key(AppKey::Char(c)));
+            }
+        };
+        app.execute_command("workbench.action.toggleModalEditing")
+            .unwrap();
+        keys(&mut app, "iteh teh.tehx ");
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        assert_eq!(app.editor().text(), "the the.tehx \n");
+
+        app.editor_mut().set_text("");
+        app.editor_mut().set_language(Some("rust".to_string()));
+        keys(&mut app, "  teh fun main");
+        assert_eq!(app.editor().text(), "  THE fn main() {\n  }");
+        for _ in "main".chars() {
+            app.editor_mut().undo();
+        }
+        assert!(app.editor_mut().undo(), "the expansion is one step");
+        assert_eq!(app.editor().text(), "  THE fun");
+    }
+
+    #[test]
+    fn test_plugins_contribute_commands_windows_and_keybindings() {
+        struct Counter(Arc<Mutex<usize>>);
+        impl Plugin for Counter {
+            fn id(&self) -> &str {
+                "test.counter"
+            }
+
+            fn activate(&mut self, _: &ServiceContainer, registry: &mut WorkbenchRegistry) {
+                let count = Arc::clone(&self.0);
+                registry.register_command("test.count", "Test: Count", move || {
+                    *count.lock().unwrap() += 1;
+                });
+                registry.register_command("workbench.action.quit", "Test: Quit", || {});
+                registry.register_window("test.toggleTally", "Test: Toggle Tally", |_| {
+                    Box::new(EditorWindow::with_text("tally"))
+                });
+                registry.register_keybinding(AppKey::Char('C'), "test.count");
+                registry.register_keybinding(AppKey::Char('X'), "test.missing");
+            }
+        }
+
+        let mut app = App::new();
+        let count = Arc::new(Mutex::new(0));
+        let problems = app.load_plugins(vec![Box::new(Counter(Arc::clone(&count)))]);
+        assert_eq!(
+            problems,
+            [
+                "plugin `test.counter`: command `workbench.action.quit` is already registered",
+                "plugin `test.counter`: unknown command `test.missing`",
+            ]
+        );
+        assert_eq!(app.plugin_ids(), ["test.counter"]);
+
+        app.execute_command("test.count").unwrap();
+        app.handle_event(AppEvent::Key(AppKey::Char('C')));
+        assert_eq!(*count.lock().unwrap(), 2);
+        app.execute_command("workbench.action.quit").unwrap();
+        assert!(!app.is_running(), "the built-in command is kept");
+
+        let windows = app.windows.len();
+        app.execute_command("test.toggleTally").unwrap();
+        assert_eq!(app.windows.len(), windows + 1);
+        let tally = app.focused_id().unwrap();
+        assert_eq!(
+            app.windows.window::<EditorWindow>(tally).unwrap().text(),
+            "tally"
+        );
+        app.execute_command("test.toggleTally").unwrap();
+        assert_eq!(app.windows.len(), windows);
+    }
+
+    #[test]
+    fn test_safe_mode_shows_a_banner_above_the_windows() {
+        let mut app = App::new();
+        app.handle_event(AppEvent::Resize(80, 24));
+        let area = Rect::new(0, 0, 80, 24);
+        let (editor, _) = app.layout_rects(area);
+        assert_eq!(editor.y, 0);
+
+        app.set_safe_mode(true);
+        assert!(app.safe_mode());
+        let (editor, _) = app.layout_rects(area);
+        assert_eq!(editor.y, 1, "the banner takes the top row");
+        let backend = ratatui::backend::TestBackend::new(80, 24);
+        let mut terminal = ratatui::Terminal::new(backend).unwrap();
+        terminal
+            .draw(|frame| app.render(frame, frame.area()))
+            .unwrap();
+        let top: String = (0..80)
+            .map(|x| terminal.backend().buffer()[(x, 0)].symbol().to_string())
+            .collect();
+        assert!(top.starts_with("Safe Mode:"), "{top}");
+
+        // Clicks land on the windows below the banner
+        use crate::input::MouseButton;
+        app.handle_event(AppEvent::Key(AppKey::Tab));
+        assert_ne!(app.focused_id(), Some(app.editor_id()));
+        app.handle_event(AppEvent::Mouse(MouseEvent::new(
+            MouseEventKind::Down(MouseButton::Left),
+            editor.x + 1,
+            editor.y + 1,
+        )));
+        assert_eq!(app.focused_id(), Some(app.editor_id()));
+    }
+
+    #[test]
+    fn test_focus_moves_between_panes_by_direction_and_close_collapses_the_pane() {
+        let mut app = App::new();
+        app.handle_event(AppEvent::Resize(80, 24));
+        app.focus_manager.set_focus(app.editor_id());
+        let below = app.split_editor(SplitDirection::Vertical).unwrap();
+        assert_eq!(app.focused_id(), Some(below));
+
+        app.execute_command("workbench.action.focusAboveWindow")
+            .unwrap();
+        assert_eq!(app.focused_id(), Some(app.editor_id()));
+        app.execute_command("workbench.action.focusAboveWindow")
+            .unwrap();
+        assert_eq!(app.focused_id(), Some(app.editor_id()), "no pane above");
+        app.execute_command("workbench.action.focusRightWindow")
+            .unwrap();
+        assert_eq!(app.focused_id(), Some(app.terminal_id()));
+        app.focus_manager.set_focus(below);
+        app.execute_command("workbench.action.focusBelowWindow")
+            .unwrap();
+        assert_eq!(app.focused_id(), Some(below));
+
+        app.execute_command("workbench.action.closeWindow").unwrap();
+        assert!(!app.windows().contains(below));
+        let area = Rect::new(0, 0, 80, 23);
+        assert_eq!(
+            app.windows().rects(area),
+            vec![
+                (app.editor_id(), Rect::new(0, 0, 40, 23)),
+                (app.terminal_id(), Rect::new(40, 0, 40, 23)),
+            ]
+        );
+        // The main editor stays open
+        app.focus_manager.set_focus(app.editor_id());
+        app.execute_command("workbench.action.closeWindow").unwrap();
+        assert!(app.windows().contains(app.editor_id()));
+    }
+
+    #[test]
+    fn test_panes_resize_with_keys_and_edge_drags_and_keep_their_size() {
+        use crate::input::MouseButton;
+        use crate::layout::MIN_PANE_SIZE;
+        let mut app = App::new();
+        app.handle_event(AppEvent::Resize(80, 24));
+        let area = Rect::new(0, 0, 80, 23);
+        let widths = |app: &App| -> Vec<u16> {
+            app.windows()
+                .rects(area)
+                .iter()
+                .map(|(_, rect)| rect.width)
+                .collect()
+        };
+        assert_eq!(widths(&app), vec![40, 40]);
+
+        app.focus_manager.set_focus(app.editor_id());
+        app.handle_event(AppEvent::Key(AppKey::Char('>')));
+        assert_eq!(widths(&app), vec![44, 36]);
+        app.handle_event(AppEvent::Key(AppKey::Char('+')));
+        assert_eq!(widths(&app), vec![44, 36], "no split stacks the editor");
+
+        // Dragging the terminal's left border moves the edge with the mouse
+        let mouse = |app: &mut App, kind, column| {
+            app.handle_event(AppEvent::Mouse(MouseEvent::new(kind, column, 5)));
+        };
+        mouse(&mut app, MouseEventKind::Down(MouseButton::Left), 44);
+        assert_eq!(app.focused_id(), Some(app.terminal_id()));
+        mouse(&mut app, MouseEventKind::Drag(MouseButton::Left), 30);
+        assert_eq!(widths(&app), vec![30, 50]);
+        mouse(&mut app, MouseEventKind::Drag(MouseButton::Left), 1);
+        assert_eq!(widths(&app), vec![MIN_PANE_SIZE, 80 - MIN_PANE_SIZE]);
+        mouse(&mut app, MouseEventKind::Up(MouseButton::Left), 1);
+        mouse(&mut app, MouseEventKind::Drag(MouseButton::Left), 20);
+        assert_eq!(widths(&app), vec![MIN_PANE_SIZE, 80 - MIN_PANE_SIZE]);
+
+        app.handle_event(AppEvent::Key(AppKey::Char('<')));
+        assert_eq!(widths(&app), vec![7, 73], "the terminal is last");
+
+        // The proportions are saved with the session
+        let mut restored = App::from_session(&app.session());
+        restored.handle_event(AppEvent::Resize(80, 24));
+        assert_eq!(widths(&restored), vec![7, 73]);
+    }
+
+    #[test]
+    fn test_split_editor_shares_the_document_with_its_own_cursor() {
+        let path = temp_path("split.rs");
+        let _cleanup = Cleanup::new([&path]);
+        std::fs::write(&path, "one\ntwo").unwrap();
+        let mut app = App::new();
+        app.open_file(&path).unwrap();
+        app.editor_mut().set_cursor_position(2, 1);
+
+        app.execute_command("workbench.action.splitEditorRight")
+            .unwrap();
+        let split = app.focused_id().unwrap();
+        assert_ne!(split, app.editor_id());
+        assert_eq!(
+            app.windows().layout(),
+            Some(&LayoutNode::even_split(
+                SplitDirection::Horizontal,
+                vec![
+                    LayoutNode::Window(app.editor_id()),
+                    LayoutNode::Window(split),
+                    LayoutNode::Window(app.terminal_id()),
+                ]
+            ))
+        );
+        let view = app.windows.window_mut::<EditorWindow>(split).unwrap();
+        assert_eq!(view.path(), Some(path.clone()));
+        assert_eq!(view.cursor_position(), (2, 1));
+
+        view.set_cursor_position(1, 1);
+        view.insert_text("zero\n");
+        app.handle_event(AppEvent::Tick);
+        assert_eq!(app.editor().text(), "zero\none\ntwo");
+        assert!(app.editor().is_dirty());
+        assert_eq!(app.editor().cursor_position(), (3, 1), "stays on `two`");
+        assert_eq!(
+            app.status_bar().left_text().split(" | ").last(),
+            Some("Ln 2, Col 1")
+        );
+
+        app.execute_command("workbench.action.splitEditorDown")
+            .unwrap();
+        let below = app.focused_id().unwrap();
+        assert!(app.close_window(below));
+        assert!(app.close_window(split));
+        assert_eq!(app.buffers().len(), 1, "the main editor still shows it");
+    }
+
+    #[test]
+    fn test_context_menus_offer_the_commands_that_apply() {
+        let mut app = App::with_size(80, 24);
+        app.editor_mut().set_text("fn main() {}");
+        let right_click = |app: &mut App, column, row| {
+            app.handle_event(AppEvent::Mouse(MouseEvent::new(
+                MouseEventKind::Down(MouseButton::Right),
+                column,
+                row,
+            )));
+        };
+        let titles = |app: &App| -> Vec<String> {
+            app.context_menu()
+                .unwrap()
+                .commands()
+                .iter()
+                .map(|command| command.title.clone())
+                .collect()
+        };
+
+        right_click(&mut app, 60, 5);
+        assert_eq!(app.focused(), FocusedPane::Terminal);
+        assert_eq!(app.keybinding_mode(), KeybindingMode::ContextMenu);
+        assert_eq!(
+            titles(&app),
+            ["Paste", "Cancel Running Operation", "Toggle Output"]
+        );
+        // A right click elsewhere opens the menu of what is under it
+        right_click(&mut app, 10, 5);
+        assert_eq!(app.focused(), FocusedPane::Editor);
+        assert!(!titles(&app).contains(&"Copy".to_string()), "no selection");
+        app.handle_event(AppEvent::Key(AppKey::Esc));
+        assert!(app.context_menu().is_none());
+        assert!(app.is_running());
+
+        app.editor_mut().set_selection(3..7);
+        app.handle_event(AppEvent::Key(AppKey::Char('M')));
+        assert_eq!(titles(&app)[..3], ["Cut", "Copy", "Paste"]);
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        assert!(app.context_menu().is_none());
+        assert_eq!(app.editor().text(), "fn () {}");
+    }
+
+    #[test]
+    fn test_tab_menu_acts_on_the_clicked_tab() {
+        let first = temp_path("menu-first.rs");
+        let second = temp_path("menu-second.rs");
+        let _cleanup = Cleanup::new([&first, &second]);
+        std::fs::write(&first, "").unwrap();
+        std::fs::write(&second, "").unwrap();
+        let mut app = App::with_size(80, 24);
+        app.open_file(&first).unwrap();
+        app.open_file(&second).unwrap();
+        app.editor_mut().activate_tab(1);
+        let click = |app: &mut App, button, column, row| {
+            app.handle_event(AppEvent::Mouse(MouseEvent::new(
+                MouseEventKind::Down(button),
+                column,
+                row,
+            )));
+        };
+
+        // The untitled, first and second tabs share the 38 cells of the
+        // strip along the top of the editor, 12 each
+        assert_eq!(app.editor().group().len(), 3);
+        click(&mut app, MouseButton::Right, 30, 1);
+        assert_eq!(app.editor().path(), Some(second.clone()));
+        let menu = app.context_menu().unwrap();
+        assert_eq!(menu.commands()[0].id, "workbench.action.closeActiveEditor");
+        assert!(menu
+            .commands()
+            .iter()
+            .any(|command| command.id == "workbench.action.nextEditor"));
+
+        // Clicking the first item, just inside the menu's border, runs it
+        click(&mut app, MouseButton::Left, 32, 2);
+        assert!(app.context_menu().is_none());
+        assert_eq!(app.editor().group().len(), 2);
+        assert_eq!(app.editor().path(), Some(first.clone()));
+    }
+
+    #[test]
+    fn test_shift_arrows_extend_the_selection_from_its_anchor() {
+        let mut app = App::new();
+        app.editor_mut().set_text("abc\ndef");
+        app.editor_mut().set_selection(1..1);
+        let press = |app: &mut App, key: AppKey| app.handle_event(AppEvent::Key(key));
+
+        press(&mut app, AppKey::ShiftRight);
+        press(&mut app, AppKey::ShiftDown);
+        assert_eq!(app.editor().selected_text(), "bc\nde");
+        assert_eq!(app.editor().selection_anchor(), 1);
+        assert_eq!(app.editor().cursor_position(), (2, 3));
+        assert!(app.status_bar().left_text().ends_with("Ln 2, Col 3"));
+
+        // Moving the head back past the anchor flips the selection
+        press(&mut app, AppKey::ShiftUp);
+        press(&mut app, AppKey::ShiftLeft);
+        press(&mut app, AppKey::ShiftLeft);
+        assert_eq!(app.editor().selected_text(), "a");
+        assert_eq!(app.editor().selection_head(), 0);
+
+        press(&mut app, AppKey::Backspace);
+        assert_eq!(app.editor().text(), "bc\ndef");
+        press(&mut app, AppKey::Backspace);
+        assert_eq!(app.editor().text(), "bc\ndef", "nothing before the cursor");
+
+        press(&mut app, AppKey::ShiftRight);
+        press(&mut app, AppKey::ShiftRight);
+        press(&mut app, AppKey::Char('d'));
+        assert_eq!(app.editor().text(), "\ndef");
+        assert_eq!(app.clipboard().paste(), "bc");
+
+        press(&mut app, AppKey::Down);
+        press(&mut app, AppKey::Right);
+        assert_eq!(app.editor().cursor_position(), (2, 2));
+        assert!(app.editor().selection().is_empty());
+    }
+
+    #[test]
+    fn test_copy_cut_and_paste_through_the_clipboard() {
+        let mut app = App::new();
+        app.editor_mut().set_text("one two");
+        app.editor_mut().set_selection(0..3);
+        app.handle_event(AppEvent::Key(AppKey::Char('y')));
+        assert_eq!(app.clipboard().paste(), "one");
+        assert_eq!(
+            app.editor().selection(),
+            0..3,
+            "copying keeps the selection"
+        );
+
+        app.editor_mut().set_selection(3..7);
+        app.handle_event(AppEvent::Key(AppKey::Char('d')));
+        assert_eq!(app.editor().text(), "one");
+        assert_eq!(app.clipboard().paste(), " two");
+
+        app.editor_mut().set_selection(0..0);
+        app.handle_event(AppEvent::Key(AppKey::Char('p')));
+        assert_eq!(app.editor().text(), " twoone");
+        assert_eq!(app.editor().selection(), 4..4);
+
+        app.handle_event(AppEvent::Key(AppKey::Tab));
+        assert_eq!(app.focused(), FocusedPane::Terminal);
+        app.handle_event(AppEvent::Key(AppKey::Char('p')));
+        assert_eq!(app.terminal().unwrap().input(), " two");
+        assert_eq!(app.editor().text(), " twoone");
+    }
+
+    #[test]
+    fn test_window_title_follows_the_focused_file() {
+        let (mut app, path) = app_with_open_file("title.rs", "fn main() {}\n");
+        app.set_workspace_root("/projects/paradiddle");
+        let file = path.file_name().unwrap().to_string_lossy().into_owned();
+        assert_eq!(
+            app.window_title(),
+            format!("{file} - paradiddle - Paradiddle")
+        );
+
+        app.editor_mut().set_selection(0..0);
+        app.editor_mut().insert_text("// ");
+        assert_eq!(
+            app.window_title(),
+            format!("● {file} - paradiddle - Paradiddle")
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_rename_validates_while_typing() {
+        let mut app = app_with_rename();
+        app.execute_command("editor.action.rename").unwrap();
+
+        type_text(&mut app, "2x");
+        let error = app.editor().rename_input().unwrap().error();
+        assert_eq!(error, Some("'2x' is not a valid identifier"));
+
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        assert!(
+            app.editor().rename_input().is_some(),
+            "invalid name kept open"
+        );
+
+        app.handle_event(AppEvent::Key(AppKey::Backspace));
+        app.handle_event(AppEvent::Key(AppKey::Backspace));
+        assert!(app.editor().rename_input().unwrap().error().is_some());
+        app.handle_event(AppEvent::Key(AppKey::Q));
+        assert_eq!(app.editor().rename_input().unwrap().error(), None);
+
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        assert!(app.is_running(), "q is typed, not quit");
+        assert_eq!(app.editor().text(), "let q = 1;\nlet y = q + xx;");
+    }
+
+    #[test]
+    fn test_rename_esc_dismisses_without_quitting() {
+        let mut app = app_with_rename();
+        app.execute_command("editor.action.rename").unwrap();
+
+        app.handle_event(AppEvent::Key(AppKey::Tab));
+        assert_eq!(app.focused(), FocusedPane::Editor, "input captures Tab");
+        app.handle_event(AppEvent::Key(AppKey::Esc));
+
+        assert!(app.editor().rename_input().is_none());
+        assert!(app.is_running());
+        assert_eq!(app.editor().text(), "let x = 1;\nlet y = x + xx;");
+    }
+
+    #[test]
+    fn test_rename_not_opened_when_prepare_rejects() {
+        let mut app = app_with_rename();
+        app.editor_mut().set_selection(0..0);
+
+        app.execute_command("editor.action.rename").unwrap();
+        assert!(app.editor().rename_input().is_none());
+    }
+
+    #[test]
+    fn test_open_window_focuses_it_and_joins_focus_cycle() {
+        let mut app = App::new();
+        let third = app.open_window(Box::new(TerminalWindow::default()));
+        assert_eq!(app.focused_id(), Some(third));
+        assert_eq!(
+            app.windows().ids(),
+            vec![app.editor_id(), app.terminal_id(), third]
+        );
+
+        app.handle_event(AppEvent::Key(AppKey::Tab));
+        assert_eq!(app.focused_id(), Some(app.editor_id()), "wraps to first");
+        app.handle_event(AppEvent::Key(AppKey::Tab));
+        assert_eq!(app.focused_id(), Some(app.terminal_id()));
+
+        app.execute_command("workbench.action.focusPreviousWindow")
+            .unwrap();
+        assert_eq!(app.focused_id(), Some(app.editor_id()));
+        app.execute_command("workbench.action.focusPreviousWindow")
+            .unwrap();
+        assert_eq!(app.focused_id(), Some(third));
+    }
+
+    #[test]
+    fn test_close_window_moves_focus_and_reclaims_space() {
+        let mut app = App::with_size(90, 25);
+        let third = app.open_window(Box::new(TerminalWindow::default()));
+        assert_eq!(app.window_at(89, 0), Some(third));
+
+        assert!(app.close_window(third));
+        assert!(!app.close_window(third), "already closed");
+        assert_eq!(app.focused_id(), Some(app.editor_id()));
+        assert_eq!(app.window_at(89, 0), Some(app.terminal_id()));
+
+        let terminal = app.terminal_id();
+        assert!(app.close_window(terminal));
+        assert!(app.terminal().is_none());
+        assert_eq!(app.window_at(89, 0), Some(app.editor_id()));
+    }
+
+    #[test]
+    fn test_editor_window_cannot_be_closed() {
+        let mut app = App::new();
+        let editor = app.editor_id();
+        assert!(!app.close_window(editor));
+        assert!(app.windows().contains(editor));
+    }
+
+    #[test]
+    fn test_window_without_context_uses_global_bindings() {
+        let mut app = App::new();
+        app.open_window(Box::new(QuickPickWindow::new("Pick", Vec::new())));
+        assert_eq!(app.focused_context(), None);
+
+        // `.` is only bound in the editor context
+        app.handle_event(AppEvent::Key(AppKey::Char('.')));
+        assert!(app.quick_fix_menu().is_none());
+        app.handle_event(AppEvent::Key(AppKey::Tab));
+        assert_eq!(app.focused_id(), Some(app.editor_id()));
+    }
+
+    /// A path in the temp directory unique to this test.
+    fn app_with_open_file(name: &str, text: &str) -> (App, std::path::PathBuf) {
+        let path = temp_path(name);
+        std::fs::write(&path, text).unwrap();
+        let mut app = App::new();
+        app.editor_mut().open_file(&path).unwrap();
+        assert!(!app.editor().is_dirty());
+        (app, path)
+    }
+
+    #[test]
+    fn test_auto_save_on_focus_change_saves_dirty_editor() {
+        let (mut app, path) = app_with_open_file("autosave-on.txt", "before");
+        app.set_auto_save(AutoSave::OnFocusChange);
+        app.editor_mut().set_text("after");
+        assert!(app.editor().is_dirty());
+
+        app.handle_event(AppEvent::Key(AppKey::Tab));
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "after");
+        assert!(!app.editor().is_dirty());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_pasted_text_and_window_focus_events() {
+        let (mut app, path) = app_with_open_file("paste-event.txt", "");
+        app.set_auto_save(AutoSave::OnFocusChange);
+        app.handle_event(AppEvent::Paste("fn main() {\n}".to_string()));
+        assert_eq!(app.editor().text(), "fn main() {\n}");
+
+        // A lost window focus auto-saves the focused editor
+        app.handle_event(AppEvent::FocusLost);
+        assert!(!app.is_window_focused());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "fn main() {\n}");
+        app.handle_event(AppEvent::FocusGained);
+        assert!(app.is_window_focused());
+
+        // An overlay gets the text typed in, without line breaks
+        app.handle_event(AppEvent::Key(AppKey::Char(':')));
+        app.handle_event(AppEvent::Paste("sa\nve".to_string()));
+        assert_eq!(app.command_palette().unwrap().query(), "save");
+        app.handle_event(AppEvent::Other);
+        assert!(app.command_palette().is_some());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_auto_save_off_by_default() {
+        let (mut app, path) = app_with_open_file("autosave-off.txt", "before");
+        assert_eq!(app.auto_save(), AutoSave::Off);
+        app.editor_mut().set_text("after");
+
+        app.handle_event(AppEvent::Key(AppKey::Tab));
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "before");
+        assert!(app.editor().is_dirty());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_auto_save_skips_clean_and_pathless_editors() {
+        let (mut app, path) = app_with_open_file("autosave-clean.txt", "same");
+        app.set_auto_save(AutoSave::OnFocusChange);
+        std::fs::write(&path, "changed on disk").unwrap();
+
+        app.handle_event(AppEvent::Key(AppKey::Tab));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "changed on disk");
+        std::fs::remove_file(path).unwrap();
+
+        let mut app = App::new();
+        app.set_auto_save(AutoSave::OnFocusChange);
+        app.editor_mut().set_text("scratch");
+        app.handle_event(AppEvent::Key(AppKey::Tab));
+        assert!(app.editor().is_dirty(), "nowhere to save");
+    }
+
+    #[test]
+    fn test_auto_save_failure_reported_in_status_bar() {
+        let mut app = App::new();
+        app.set_auto_save(AutoSave::OnFocusChange);
+        app.editor_mut()
+            .set_path(Some(temp_path("missing-dir").join("file.txt")));
+        app.editor_mut().set_text("unsaved");
+
+        app.execute_command("workbench.action.focusNextWindow")
+            .unwrap();
+        app.handle_event(AppEvent::Tick);
+
+        assert!(app.editor().is_dirty());
+        assert!(app
+            .status_bar()
+            .right_text()
+            .starts_with("Auto-save failed:"));
+    }
+
+    #[test]
+    fn test_open_file_twice_shares_buffer_between_editors() {
+        let path = temp_path("shared-buffer.txt");
+        std::fs::write(&path, "shared").unwrap();
+        let mut app = App::new();
+        app.open_file(&path).unwrap();
+        let second = app.open_window(Box::new(EditorWindow::default()));
+        app.open_file(&path).unwrap();
+        assert_eq!(app.buffers().len(), 1);
+
+        app.editor_mut().set_text("edited");
+        let second_editor = app.windows().window::<EditorWindow>(second).unwrap();
+        assert_eq!(second_editor.text(), "edited");
+        assert!(second_editor.is_dirty());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_buffer_closed_when_last_editor_closes() {
+        use crate::buffer_manager::BufferEvent;
+
+        let first = temp_path("buffer-first.txt");
+        let second = temp_path("buffer-second.txt");
+        std::fs::write(&first, "1").unwrap();
+        std::fs::write(&second, "2").unwrap();
+        let mut app = App::new();
+        let events = app.buffers().on_buffer_event().subscribe();
+
+        app.open_file(&first).unwrap();
+        let window = app.open_window(Box::new(EditorWindow::default()));
+        app.open_file(&second).unwrap();
+        app.open_file(&first).unwrap();
+        app.close_window(window);
+
+        let first = std::fs::canonicalize(&first).unwrap();
+        let second = std::fs::canonicalize(&second).unwrap();
+        let received: Vec<BufferEvent> = events.try_iter().collect();
+        assert_eq!(
+            received,
+            vec![
+                BufferEvent::Opened(BufferUri::File(first.clone())),
+                BufferEvent::Opened(BufferUri::File(second.clone())),
+                BufferEvent::Closed(BufferUri::File(second.clone())),
+            ]
+        );
+        assert_eq!(app.buffers().paths(), vec![first.clone()]);
+        std::fs::remove_file(&first).unwrap();
+        std::fs::remove_file(&second).unwrap();
+    }
+
+    #[test]
+    fn test_auto_save_of_managed_buffer_fires_saved() {
+        use crate::buffer_manager::BufferEvent;
+
+        let path = temp_path("buffer-autosave.txt");
+        std::fs::write(&path, "before").unwrap();
+        let mut app = App::new();
+        app.set_auto_save(AutoSave::OnFocusChange);
+        app.open_file(&path).unwrap();
+        let events = app.buffers().on_buffer_event().subscribe();
+
+        app.editor_mut().set_text("after");
+        app.handle_event(AppEvent::Key(AppKey::Tab));
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "after");
+        assert_eq!(
+            events.try_recv().unwrap(),
+            BufferEvent::Saved(BufferUri::File(std::fs::canonicalize(&path).unwrap()))
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_open_buffer_follows_file_operation_rename() {
+        let path = temp_path("renamed-before.txt");
+        std::fs::write(&path, "kept").unwrap();
+        let mut app = App::new();
+        app.open_file(&path).unwrap();
+
+        let renamed = app
+            .file_operations()
+            .rename(
+                &path,
+                &format!("paradiddle-{}-renamed-after.txt", std::process::id()),
+            )
+            .unwrap();
+        app.handle_event(AppEvent::Tick);
+
+        assert_eq!(app.editor().path(), Some(renamed.clone()));
+        assert_eq!(app.buffers().paths(), vec![renamed.clone()]);
+        app.editor_mut().set_text("edited");
+        app.execute_command("workbench.action.files.save").unwrap();
+        assert_eq!(std::fs::read_to_string(&renamed).unwrap(), "edited");
+        std::fs::remove_file(&renamed).unwrap();
+    }
+
+    #[test]
+    fn test_quick_open_lists_unignored_files_and_opens_pick() {
+        let root = temp_dir("quick-open");
+        let _cleanup = Cleanup::new([&root]);
+        std::fs::create_dir(root.join("src")).unwrap();
+        std::fs::create_dir(root.join("target")).unwrap();
+        std::fs::write(root.join("src/main.rs"), "fn main() {}").unwrap();
+        std::fs::write(root.join("src/lib.rs"), "").unwrap();
+        std::fs::write(root.join("target/out.rs"), "").unwrap();
+        std::fs::write(root.join(".gitignore"), "target/\n").unwrap();
+        let mut app = App::new();
+        app.set_workspace_root(&root);
+
+        app.execute_command("workbench.action.quickOpen").unwrap();
+        assert_eq!(app.keybinding_mode(), KeybindingMode::QuickOpen);
+        assert_eq!(app.quick_open().unwrap().matches().len(), 3);
+
+        type_text(&mut app, "main");
+        assert_eq!(app.quick_open().unwrap().matches().len(), 1);
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+
+        assert!(app.quick_open().is_none());
+        assert_eq!(app.editor().text(), "fn main() {}");
+    }
+
+    #[test]
+    fn test_tutorial_follows_the_user_through_its_steps() {
+        let root = temp_dir("tutorial");
+        let _cleanup = Cleanup::new([&root]);
+        std::fs::write(root.join("main.rs"), "fn main() {}").unwrap();
+        let mut app = App::new();
+        app.set_workspace_root(&root);
+        assert!(app
+            .editor()
+            .text()
+            .contains("Press ? for a guided tutorial"));
+
+        app.handle_event(AppEvent::Key(AppKey::Char('?')));
+        let step = |app: &App| app.tutorial().and_then(Tutorial::current);
+        assert_eq!(step(&app), Some(TutorialStep::SwitchFocus));
+
+        app.handle_event(AppEvent::Key(AppKey::Tab));
+        assert_eq!(step(&app), Some(TutorialStep::OpenPalette));
+        app.handle_event(AppEvent::Key(AppKey::Char(':')));
+        assert_eq!(step(&app), Some(TutorialStep::OpenFile));
+
+        type_text(&mut app, "Go to File");
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        assert_eq!(step(&app), Some(TutorialStep::OpenFile), "no file yet");
+        type_text(&mut app, "main");
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        assert_eq!(step(&app), Some(TutorialStep::Search));
+
+        app.find_in_files(SearchQuery::literal("main"));
+        app.handle_event(AppEvent::Tick);
+        assert!(app.tutorial().is_none(), "finished");
+        assert!(app
+            .notifications()
+            .notifications()
+            .iter()
+            .any(|notification| notification.source == "Tutorial"));
+
+        // Toggling again stops the tutorial early
+        app.execute_command("workbench.action.toggleTutorial")
+            .unwrap();
+        assert!(app.tutorial().is_some());
+        app.execute_command("workbench.action.toggleTutorial")
+            .unwrap();
+        assert!(app.tutorial().is_none());
+    }
+
+    /// An App with the explorer open on a workspace holding `src/main.rs`,
+    /// `notes.txt` and an ignored `target/`, trashing into `<name>-trash`,
+    /// and the guard removing both.
+    fn app_with_explorer(name: &str) -> (App, std::path::PathBuf, Cleanup) {
+        let root = temp_dir(name);
+        let trash = temp_path(&format!("{name}-trash"));
+        let _ = std::fs::remove_dir_all(&trash);
+        let cleanup = Cleanup::new([&root, &trash]);
+        std::fs::create_dir(root.join("src")).unwrap();
+        std::fs::create_dir(root.join("target")).unwrap();
+        std::fs::write(root.join("src/main.rs"), "fn main() {}").unwrap();
+        std::fs::write(root.join("notes.txt"), "").unwrap();
+        std::fs::write(root.join(".gitignore"), "target/\n").unwrap();
+        let root = std::fs::canonicalize(root).unwrap();
+        let mut app = App::new();
+        app.set_file_operations(FileOperations::with_trash_dir(trash));
+        app.set_workspace_root(&root);
+        app.execute_command("workbench.view.explorer").unwrap();
+        (app, root, cleanup)
+    }
+
+    fn explorer_rows(app: &App) -> Vec<String> {
+        app.explorer()
+            .unwrap()
+            .rows()
+            .iter()
+            .map(|row| row.path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect()
+    }
+
+    fn files_segment(app: &App) -> Option<&str> {
+        app.status_bar()
+            .segments()
+            .iter()
+            .find(|(id, _)| id == FILES_SEGMENT)
+            .map(|(_, text)| text.as_str())
+    }
+
+    #[test]
+    fn test_explorer_toggles_and_opens_file_into_editor() {
+        let (mut app, _, _cleanup) = app_with_explorer("explorer-open");
+        let id = app.explorer_id().unwrap();
+        assert_eq!(app.focused_id(), Some(id));
+        assert_eq!(app.focused_context(), Some(WindowContext::Explorer));
+        assert_eq!(explorer_rows(&app), vec!["src", ".gitignore", "notes.txt"]);
+
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        app.handle_event(AppEvent::Key(AppKey::Down));
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        assert_eq!(app.editor().text(), "fn main() {}");
+        assert_eq!(app.focused_id(), Some(app.editor_id()));
+
+        // Focus cycling reaches the explorer again
+        app.handle_event(AppEvent::Key(AppKey::Tab));
+        app.handle_event(AppEvent::Key(AppKey::Tab));
+        assert_eq!(app.focused_id(), Some(id));
+
+        assert!(!app.toggle_explorer());
+        assert!(app.explorer().is_none());
+        assert!(!app.windows().contains(id));
+    }
+
+    #[test]
+    fn test_explorer_filter_mode_captures_keys() {
+        let (mut app, _, _cleanup) = app_with_explorer("explorer-filter");
+        app.handle_event(AppEvent::Key(AppKey::Char('/')));
+        assert_eq!(app.keybinding_mode(), KeybindingMode::Filter);
+
+        type_text(&mut app, "note");
+        app.handle_event(AppEvent::Key(AppKey::Q));
+        assert!(app.is_running());
+        assert_eq!(app.explorer().unwrap().filter(), Some("noteq"));
+        app.handle_event(AppEvent::Key(AppKey::Backspace));
+        assert_eq!(explorer_rows(&app), vec!["notes.txt"]);
+
+        app.handle_event(AppEvent::Key(AppKey::Esc));
+        assert_eq!(app.keybinding_mode(), KeybindingMode::Normal);
+        assert!(app.is_running());
+    }
+
+    #[test]
+    fn test_explorer_creates_and_renames_through_input_box() {
+        let (mut app, root, _cleanup) = app_with_explorer("explorer-create");
+        app.handle_event(AppEvent::Key(AppKey::Char('a')));
+        assert_eq!(app.input_box().unwrap().title(), "New File");
+        type_text(&mut app, "lib.rs");
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+
+        let created = root.join("src/lib.rs");
+        assert!(created.is_file());
+        assert_eq!(app.editor().path(), Some(created.clone()));
+        assert_eq!(
+            app.explorer().unwrap().selected().unwrap().path,
+            created.clone()
+        );
+
+        // Conflicts keep the input open with the error
+        app.handle_event(AppEvent::Key(AppKey::Char('A')));
+        type_text(&mut app, "main.rs");
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        assert!(app.input_box().unwrap().error().is_some());
+        app.handle_event(AppEvent::Key(AppKey::Esc));
+        assert!(app.is_running());
+
+        app.handle_event(AppEvent::Key(AppKey::Char('r')));
+        assert_eq!(app.input_box().unwrap().value(), "lib.rs");
+        type_text(&mut app, "util.rs");
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        assert!(app.input_box().is_none());
+        assert_eq!(app.editor().path(), Some(root.join("src/util.rs")));
+    }
+
+    #[test]
+    fn test_explorer_bulk_moves_and_deletes_marked_entries() {
+        let (mut app, root, _cleanup) = app_with_explorer("explorer-bulk");
+        // Mark .gitignore and notes.txt, then move them into src
+        app.handle_event(AppEvent::Key(AppKey::Down));
+        app.handle_event(AppEvent::Key(AppKey::ShiftDown));
+        app.handle_event(AppEvent::Key(AppKey::Up));
+        app.handle_event(AppEvent::Key(AppKey::Up));
+        app.handle_event(AppEvent::Key(AppKey::Char('m')));
+        assert!(root.join("src/notes.txt").is_file());
+        assert!(root.join("src/.gitignore").is_file());
+        // The moved .gitignore no longer hides target/
+        assert_eq!(explorer_rows(&app), vec!["src", "target"]);
+
+        app.handle_event(AppEvent::Key(AppKey::Char('m')));
+        assert!(files_segment(&app).is_some_and(|text| text.contains("Mark")));
+
+        app.handle_event(AppEvent::Key(AppKey::Char('d')));
+        let trash = temp_path("explorer-bulk-trash");
+        assert!(!root.join("src").exists());
+        assert!(trash.join("files/src/main.rs").is_file());
+        assert_eq!(explorer_rows(&app), vec!["target"]);
+        assert_eq!(files_segment(&app), None);
+    }
+
+    #[test]
+    fn test_task_service_is_registered() {
+        let app = App::new();
+        let task = app.tasks().spawn("answer", |_| 42);
+        assert_eq!(
+            task.wait(Duration::from_secs(5)),
+            Some(TaskOutcome::Completed(42))
+        );
+    }
+
+    #[test]
+    fn test_progress_shows_in_the_status_bar_and_toasts_and_cancels() {
+        let mut app = App::new();
+        let indexing = app
+            .progress()
+            .start("Indexing", ProgressLocation::StatusBar);
+        indexing.report_fraction(1, 4);
+        indexing.report("a.rs");
+        let (started_tx, started) = std::sync::mpsc::channel();
+        let job = app.progress().spawn(
+            &app.tasks(),
+            "Long job",
+            ProgressLocation::Notification,
+            move |progress| {
+                started_tx.send(()).unwrap();
+                while !progress.is_cancelled() {
+                    std::thread::sleep(Duration::from_millis(1));
+                }
+            },
+        );
+        started.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        let backend = ratatui::backend::TestBackend::new(80, 24);
+        let mut terminal = ratatui::Terminal::new(backend).unwrap();
+        let progress_segment = |app: &App| {
+            app.status_bar()
+                .segments()
+                .iter()
+                .find(|(id, _)| id == PROGRESS_SEGMENT)
+                .map(|(_, text)| text.clone())
+        };
+        terminal
+            .draw(|frame| app.render(frame, frame.area()))
+            .unwrap();
+        assert_eq!(progress_segment(&app).as_deref(), Some("Indexing 25% a.rs"));
+        let toast_title: String = (40..80)
+            .map(|x| terminal.backend().buffer()[(x, 19)].symbol().to_string())
+            .collect();
+        assert!(toast_title.contains("Long job"), "{toast_title}");
+
+        app.execute_command("workbench.action.cancelProgress")
+            .unwrap();
+        assert_eq!(
+            job.wait(Duration::from_secs(5)),
+            Some(TaskOutcome::Cancelled)
+        );
+        indexing.done();
+        terminal
+            .draw(|frame| app.render(frame, frame.area()))
+            .unwrap();
+        assert_eq!(progress_segment(&app), None);
+    }
+
+    #[test]
+    fn test_muted_notifications_skip_toasts_but_reach_the_center() {
+        let mut app = App::new();
+        let config = Config::parse(
+            "[notifications]\nmuted_sources = \"Git, Tasks\"\nmuted_severities = \"info\"\n",
+        )
+        .unwrap();
+        assert!(app.apply_config(&config).is_empty());
+        let notifications = app.notifications();
+        notifications.notify("Git", NotificationSeverity::Error, "push failed");
+        notifications.notify("Cargo", NotificationSeverity::Info, "cargo build finished");
+        notifications.notify("Cargo", NotificationSeverity::Warning, "1 warning");
+
+        let backend = ratatui::backend::TestBackend::new(80, 24);
+        let mut terminal = ratatui::Terminal::new(backend).unwrap();
+        terminal
+            .draw(|frame| app.render(frame, frame.area()))
+            .unwrap();
+        let row = |terminal: &ratatui::Terminal<ratatui::backend::TestBackend>, y| {
+            (40..80)
+                .map(|x| terminal.backend().buffer()[(x, y)].symbol().to_string())
+                .collect::<String>()
+        };
+        assert!(row(&terminal, 20).contains("Cargo"));
+        assert!(row(&terminal, 21).contains("1 warning"));
+        assert!(!row(&terminal, 17).contains("Git"), "one toast only");
+
+        app.execute_command("workbench.notifications.toggleDoNotDisturbMode")
+            .unwrap();
+        notifications.notify("Cargo", NotificationSeverity::Warning, "2 warnings");
+        app.handle_event(AppEvent::Tick);
+        assert_eq!(
+            app.status_bar().item_text(NOTIFICATIONS_ITEM),
+            Some("Do Not Disturb (3 muted)")
+        );
+
+        // Clicking the status bar item opens the notification center
+        use crate::input::MouseButton;
+        let column = 80 - 2 - u16::try_from(app.status_bar().right_text().len()).unwrap();
+        app.handle_event(AppEvent::Mouse(MouseEvent::new(
+            MouseEventKind::Down(MouseButton::Left),
+            column + 1,
+            23,
+        )));
+        let center = app.notifications_window().expect("center should open");
+        assert_eq!(center.notifications().len(), 4);
+        assert!(center.notifications()[0].suppressed);
+
+        app.execute_command("workbench.notifications.clearAll")
+            .unwrap();
+        app.handle_event(AppEvent::Tick);
+        assert!(app
+            .notifications_window()
+            .unwrap()
+            .notifications()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_toasts_are_dismissed_by_clicks_and_hide_command() {
+        let mut app = App::with_size(80, 24);
+        let notifications = app.notifications();
+        notifications.notify("Cargo", NotificationSeverity::Info, "build finished");
+        let newest = notifications.notify("Git", NotificationSeverity::Warning, "detached");
+        app.handle_event(AppEvent::Tick);
+        assert_eq!(notifications.toasts().len(), 2, "not expired yet");
+
+        // The newest toast is the bottom one, above the status bar
+        app.handle_event(AppEvent::Mouse(MouseEvent::new(
+            MouseEventKind::Down(MouseButton::Left),
+            60,
+            21,
+        )));
+        assert_eq!(
+            app.focused(),
+            FocusedPane::Editor,
+            "the click stops at the toast"
+        );
+        let toasts = notifications.toasts();
+        assert_eq!(toasts.len(), 1);
+        assert_ne!(toasts[0].id, newest);
+
+        app.execute_command("workbench.notifications.hideToasts")
+            .unwrap();
+        assert!(notifications.toasts().is_empty());
+        assert_eq!(notifications.notifications().len(), 2);
+    }
+
+    #[test]
+    fn test_open_files_get_tabs_that_cycle_and_close() {
+        let first = temp_path("tabs-first.txt");
+        let second = temp_path("tabs-second.txt");
+        std::fs::write(&first, "first").unwrap();
+        std::fs::write(&second, "second").unwrap();
+        let mut app = App::new();
+        app.open_file(&first).unwrap();
+        app.open_file(&second).unwrap();
+        assert_eq!(app.editor().group().len(), 3, "welcome text, first, second");
+        assert_eq!(app.editor().text(), "second");
+
+        // Opening an open file activates its tab
+        app.open_file(&first).unwrap();
+        assert_eq!(app.editor().group().len(), 3);
+        assert_eq!(app.editor().text(), "first");
+        app.handle_event(AppEvent::Key(AppKey::Char(']')));
+        assert_eq!(app.editor().text(), "second");
+        app.handle_event(AppEvent::Key(AppKey::Char(']')));
+        app.handle_event(AppEvent::Key(AppKey::Char('[')));
+        assert_eq!(app.editor().text(), "second");
+
+        // Tabs keep their own selection
+        app.editor_mut().set_selection(1..3);
+        app.execute_command("workbench.action.previousEditor")
+            .unwrap();
+        assert_eq!(app.editor().selection(), 0..0);
+        app.execute_command("workbench.action.nextEditor").unwrap();
+        assert_eq!(app.editor().selection(), 1..3);
+
+        // Closing a tab closes its buffer
+        app.handle_event(AppEvent::Key(AppKey::Char('w')));
+        assert_eq!(app.editor().group().len(), 2);
+        assert!(!app.buffers().contains(&second));
+        assert!(app.buffers().contains(&first));
+        std::fs::remove_file(&first).unwrap();
+        std::fs::remove_file(&second).unwrap();
+    }
+
+    #[test]
+    fn test_export_writes_the_highlighted_document_or_selection() {
+        let path = temp_path("export.rs");
+        let target = format!("{}.html", path.display());
+        let ansi = temp_path("export.ans");
+        let _cleanup = Cleanup::new([&path, Path::new(&target), &ansi]);
+        std::fs::write(&path, "fn main() {}\nlet x = 1;\n").unwrap();
+        let mut app = App::new();
+        app.open_file(&path).unwrap();
+
+        app.execute_command("workbench.action.files.exportAsHtml")
+            .unwrap();
+        assert_eq!(app.input_box().map(InputBoxWindow::value), Some(&*target));
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        assert!(app.input_box().is_none());
+        let html = std::fs::read_to_string(&target).unwrap();
+        assert!(html.contains("<title>"), "{html}");
+        assert!(html.contains(">fn</span>"), "{html}");
+        assert!(html.contains(">let</span>"), "the whole document: {html}");
+
+        app.editor_mut().set_selection(0..2);
+        let id = app.target_editor();
+        app.export_editor(id, ExportFormat::Ansi, &ansi.to_string_lossy())
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&ansi).unwrap(),
+            "\x1b[0;35mfn\x1b[0m\n",
+            "only the selection"
+        );
+        assert!(app.export_editor(id, ExportFormat::Ansi, " ").is_err());
+    }
+
+    #[test]
+    fn test_closed_tabs_reopen_newest_first_at_their_cursor() {
+        let first = temp_path("reopen-first.txt");
+        let second = temp_path("reopen-second.txt");
+        let gone = temp_path("reopen-gone.txt");
+        std::fs::write(&first, "first").unwrap();
+        std::fs::write(&second, "one\ntwo words").unwrap();
+        std::fs::write(&gone, "gone").unwrap();
+        let mut app = App::new();
+        app.open_file(&gone).unwrap();
+        app.handle_event(AppEvent::Key(AppKey::Char('w')));
+        std::fs::remove_file(&gone).unwrap();
+        app.open_file(&first).unwrap();
+        app.open_file(&second).unwrap();
+        app.editor_mut().set_cursor_position(2, 5);
+        app.handle_event(AppEvent::Key(AppKey::Char('w')));
+        app.open_file(&first).unwrap();
+        app.execute_command("workbench.action.closeActiveEditor")
+            .unwrap();
+        assert_eq!(app.editor().group().len(), 1);
+        assert_eq!(app.closed_editors().editors().len(), 3);
+
+        app.handle_event(AppEvent::Key(AppKey::Char('W')));
+        assert_eq!(app.editor().path(), Some(first.clone()));
+        app.execute_command("workbench.action.reopenClosedEditor")
+            .unwrap();
+        assert_eq!(app.editor().path(), Some(second.clone()));
+        assert_eq!(app.editor().cursor_position(), (2, 5));
+
+        // The deleted file is skipped, leaving nothing to reopen
+        assert!(!app.reopen_closed_editor());
+        assert!(app.closed_editors().is_empty());
+        assert_eq!(app.editor().group().len(), 3);
+        std::fs::remove_file(&first).unwrap();
+        std::fs::remove_file(&second).unwrap();
+    }
+
+    #[test]
+    fn test_pinned_tabs_move_and_survive_close_others_and_sessions() {
+        let root = temp_dir("pinned-tabs");
+        let _cleanup = Cleanup::new([&root]);
+        let [a, b, c] = ["a.rs", "b.rs", "c.rs"].map(|name| root.join(name));
+        for path in [&a, &b, &c] {
+            std::fs::write(path, "fn x() {}\n").unwrap();
+        }
+        let tabs = |app: &App, id: WindowId| -> Vec<String> {
+            let editor = app.windows().window::<EditorWindow>(id).unwrap();
+            editor.group().tabs().iter().map(EditorTab::title).collect()
+        };
+        let mut app = App::new();
+        app.set_workspace_root(&root);
+        app.open_file(&a).unwrap();
+        app.open_file(&b).unwrap();
+        app.open_file(&c).unwrap();
+        app.execute_command("workbench.action.togglePinnedEditor")
+            .unwrap();
+        assert_eq!(
+            tabs(&app, app.editor_id()),
+            ["c.rs", "Untitled", "a.rs", "b.rs"]
+        );
+
+        // `{` and `}` move a tab among the unpinned tabs only
+        app.open_file(&b).unwrap();
+        app.handle_event(AppEvent::Key(AppKey::Char('{')));
+        app.handle_event(AppEvent::Key(AppKey::Char('{')));
+        assert_eq!(
+            tabs(&app, app.editor_id()),
+            ["c.rs", "b.rs", "Untitled", "a.rs"]
+        );
+
+        let session = App::from_session(&Session::parse(&app.session().to_string()).unwrap());
+        assert_eq!(
+            tabs(&session, session.editor_id()),
+            ["c.rs", "b.rs", "a.rs"]
+        );
+        assert!(session.editor().group().tabs()[0].pinned);
+        assert_eq!(session.editor().group().pinned_count(), 1);
+
+        app.execute_command("workbench.action.closeOtherEditors")
+            .unwrap();
+        assert_eq!(tabs(&app, app.editor_id()), ["c.rs", "b.rs"]);
+        assert_eq!(app.closed_editors().editors().len(), 1, "a.rs");
+
+        // Without a second editor, the tab moves into a new one on the right
+        let second = app.move_tab_to_next_group().unwrap();
+        assert_eq!(app.focused_id(), Some(second));
+        assert_eq!(tabs(&app, app.editor_id()), ["c.rs"]);
+        assert_eq!(tabs(&app, second), ["b.rs"]);
+        app.focus_manager.set_focus(app.editor_id());
+        app.execute_command("workbench.action.moveEditorToNextGroup")
+            .unwrap();
+        assert_eq!(tabs(&app, second), ["c.rs", "b.rs"], "pinned first");
+        assert_eq!(tabs(&app, app.editor_id()), ["c.rs"], "the last tab stays");
+    }
+
+    fn search_segment(app: &App) -> Option<&str> {
+        app.status_bar()
+            .segments()
+            .iter()
+            .find(|(id, _)| id == SEARCH_SEGMENT)
+            .map(|(_, text)| text.as_str())
+    }
+
+    #[test]
+    fn test_search_replaces_included_matches_across_files() {
+        let root = temp_dir("search-replace");
+        let _cleanup = Cleanup::new([&root]);
+        std::fs::write(root.join("a.txt"), "foo bar foo").unwrap();
+        std::fs::write(root.join("b.txt"), "foo").unwrap();
+        let root = std::fs::canonicalize(root).unwrap();
+        let mut app = App::new();
+        app.set_workspace_root(&root);
+        app.open_file(root.join("b.txt")).unwrap();
+
+        app.execute_command("workbench.view.search").unwrap();
+        assert_eq!(app.focused_context(), Some(WindowContext::Search));
+        assert_eq!(app.keybinding_mode(), KeybindingMode::Search);
+        type_text(&mut app, "foo");
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        let panel = app.search_panel().unwrap();
+        assert_eq!(panel.results().len(), 2);
+
+        // Replace with "baz", leaving out the second match in a.txt
+        app.handle_event(AppEvent::Key(AppKey::Down));
+        type_text(&mut app, "baz");
+        app.handle_event(AppEvent::Key(AppKey::Esc));
+        assert_eq!(app.keybinding_mode(), KeybindingMode::Normal);
+        app.handle_event(AppEvent::Key(AppKey::Down));
+        app.handle_event(AppEvent::Key(AppKey::Down));
+        type_text(&mut app, " R");
+        assert_eq!(
+            search_segment(&app),
+            Some("Replaced 2 occurrences in 2 files")
+        );
+
+        // The closed file is saved; the open one is edited in its buffer
+        assert_eq!(
+            std::fs::read_to_string(root.join("a.txt")).unwrap(),
+            "baz bar foo"
+        );
+        assert!(!app.buffers().contains(root.join("a.txt")));
+        assert_eq!(std::fs::read_to_string(root.join("b.txt")).unwrap(), "foo");
+        assert_eq!(app.editor().text(), "baz");
+        assert!(app.editor().is_dirty());
+
+        // The results are refreshed, and Enter opens the remaining match
+        let panel = app.search_panel().unwrap();
+        assert_eq!(panel.results().len(), 1);
+        assert_eq!(panel.results()[0].matches[0].range, 8..11);
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        assert_eq!(app.focused_id(), Some(app.editor_id()));
+        assert_eq!(app.editor().path(), Some(root.join("a.txt")));
+        assert_eq!(app.editor().selection(), 8..11);
+
+        assert!(!app.toggle_search());
+        assert!(app.search_panel().is_none());
+    }
+
+    #[test]
+    fn test_find_in_files_streams_regex_matches_and_opens_them() {
+        let root = temp_dir("find-in-files");
+        let _cleanup = Cleanup::new([&root]);
+        std::fs::write(root.join("a.txt"), "fn main() {}\nfn helper() {}").unwrap();
+        std::fs::write(root.join("b.txt"), "no functions").unwrap();
+        let root = std::fs::canonicalize(root).unwrap();
+        let mut app = App::new();
+        app.set_workspace_root(&root);
+
+        app.execute_command("workbench.action.findInFiles").unwrap();
+        assert_eq!(app.keybinding_mode(), KeybindingMode::Search);
+        type_text(&mut app, r"fn \w+");
+        app.handle_event(AppEvent::Key(AppKey::Down));
+        type_text(&mut app, " ");
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        assert!(app.search_results().unwrap().searched().unwrap().is_regex);
+        tick_until(&mut app, |app| !app.is_searching());
+
+        let panel = app.search_results().unwrap();
+        assert!(!panel.is_searching());
+        assert_eq!(panel.results().len(), 1);
+        assert_eq!(panel.results()[0].1.len(), 2);
+
+        // Enter on the second match jumps the editor to it
+        app.handle_event(AppEvent::Key(AppKey::Esc));
+        assert_eq!(app.keybinding_mode(), KeybindingMode::Normal);
+        app.handle_event(AppEvent::Key(AppKey::Down));
+        app.handle_event(AppEvent::Key(AppKey::Down));
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        assert_eq!(app.focused_id(), Some(app.editor_id()));
+        assert_eq!(app.editor().path(), Some(root.join("a.txt")));
+        assert_eq!(app.editor().selection(), 13..22);
+
+        // An invalid regex is reported in the panel
+        app.find_in_files(SearchQuery::regex("("));
+        assert!(!app.is_searching());
+        assert!(!app.search_results().unwrap().is_searching());
+
+        assert!(!app.toggle_search_results());
+        assert!(app.search_results().is_none());
+    }
+
+    #[test]
+    fn test_todo_list_scans_the_workspace_and_follows_saves() {
+        let root = temp_dir("todos");
+        let _cleanup = Cleanup::new([&root]);
+        std::fs::write(root.join("a.rs"), "fn a() {}\n// TODO: test a\n").unwrap();
+        std::fs::write(root.join("b.rs"), "let TODO = 1;\nlet todo = \"TODO\";\n").unwrap();
+        let root = std::fs::canonicalize(root).unwrap();
+        let mut app = App::new();
+        app.set_workspace_root(&root);
+
+        app.execute_command("workbench.action.toggleTodos").unwrap();
+        assert_eq!(app.focused_label(), "TODOs");
+        tick_until(&mut app, |app| !app.todos().unwrap().is_scanning());
+        let files: Vec<&PathBuf> = app.todos().unwrap().files().keys().collect();
+        assert_eq!(files, vec![&root.join("a.rs")]);
+
+        // Enter on the comment jumps the editor to its tag
+        app.handle_event(AppEvent::Key(AppKey::Down));
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        assert_eq!(app.focused_id(), Some(app.editor_id()));
+        assert_eq!(app.editor().path(), Some(root.join("a.rs")));
+        assert_eq!(app.editor().selection(), 13..17);
+
+        // Saving rescans the file alone
+        app.editor_mut().set_text("// FIXME: broken\n");
+        app.execute_command("workbench.action.files.save").unwrap();
+        app.handle_event(AppEvent::Tick);
+        let items = &app.todos().unwrap().files()[&root.join("a.rs")];
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].text, "FIXME: broken");
+
+        assert!(!app.toggle_todos());
+        assert!(app.todos().is_none());
+    }
+
+    #[test]
+    fn test_todo_list_follows_changes_made_outside_the_ide() {
+        let (root, _cleanup) = git_repository("todos-watched", "// TODO: first\n");
+        let mut app = App::new();
+        app.set_workspace_root(&root);
+        app.watch_todos(Duration::from_millis(5));
+        assert!(app.watch_git(Duration::from_millis(5)));
+        app.execute_command("workbench.action.toggleTodos").unwrap();
+        tick_until(&mut app, |app| !app.todos().unwrap().is_scanning());
+        let texts = |app: &App, name: &str| -> Vec<String> {
+            let files = app.todos().unwrap().files();
+            let items = files.get(&root.join(name));
+            items.map_or_else(Vec::new, |items| {
+                items.iter().map(|item| item.text.clone()).collect()
+            })
+        };
+        assert_eq!(texts(&app, "a.txt"), ["TODO: first"]);
+
+        // Another editor rewrites a listed file
+        std::fs::write(root.join("a.txt"), "// FIXME: rewritten\n").unwrap();
+        tick_until(&mut app, |app| texts(app, "a.txt") == ["FIXME: rewritten"]);
+        std::fs::remove_file(root.join("a.txt")).unwrap();
+        tick_until(&mut app, |app| app.todos().unwrap().is_empty());
+
+        // Staging changes the index, which scans the whole workspace again
+        std::fs::write(root.join("b.txt"), "// HACK: staged\n").unwrap();
+        let status = std::process::Command::new("git")
+            .arg("-C")
+            .arg(&root)
+            .args(["add", "b.txt"])
+            .status()
+            .unwrap();
+        assert!(status.success());
+        tick_until(&mut app, |app| texts(app, "b.txt") == ["HACK: staged"]);
+    }
+
+    #[test]
+    fn test_root_folders_share_explorer_search_and_their_own_settings() {
+        let base = temp_dir("multi-root");
+        let _cleanup = Cleanup::new([&base]);
+        std::fs::create_dir(base.join("app")).unwrap();
+        std::fs::create_dir(base.join("lib")).unwrap();
+        let base = std::fs::canonicalize(base).unwrap();
+        let (app_root, lib_root) = (base.join("app"), base.join("lib"));
+        std::fs::write(app_root.join("main.rs"), "fn main() { needle() }").unwrap();
+        std::fs::write(lib_root.join("lib.rs"), "pub fn needle() {}").unwrap();
+        std::fs::write(lib_root.join(".paradiddle.toml"), "[editor]\nindent = 8\n").unwrap();
+        let mut app = App::new();
+        app.set_workspace_root(&app_root);
+        app.set_config_service(ConfigService::new(
+            Config::parse("[editor]\nindent = 2\n").unwrap(),
+        ));
+        assert!(app.add_workspace_folder(&lib_root));
+        assert!(!app.add_workspace_folder(&lib_root));
+
+        app.toggle_explorer();
+        assert_eq!(
+            app.explorer().unwrap().roots(),
+            [app_root.clone(), lib_root.clone()]
+        );
+
+        // Quick open lists the files of every root, named after their root
+        app.execute_command("workbench.action.quickOpen").unwrap();
+        assert_eq!(app.quick_open().unwrap().matches().len(), 3);
+        type_text(&mut app, "lib/lib");
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        assert_eq!(app.editor().path(), Some(lib_root.join("lib.rs")));
+
+        // A root's settings apply to its files only
+        assert_eq!(app.editor().options().indent_width, 8);
+        app.open_file(app_root.join("main.rs")).unwrap();
+        assert_eq!(app.editor().options().indent_width, 2);
+
+        app.find_in_files(SearchQuery::literal("needle"));
+        tick_until(&mut app, |app| !app.is_searching());
+        let found: Vec<_> = app
+            .search_results()
+            .unwrap()
+            .results()
+            .iter()
+            .map(|(path, _)| path.clone())
+            .collect();
+        assert_eq!(found, [app_root.join("main.rs"), lib_root.join("lib.rs")]);
+
+        let restored = App::from_session(&app.session());
+        assert_eq!(
+            restored.workspace_folders().roots(),
+            [app_root.clone(), lib_root.clone()]
+        );
+        assert!(app.remove_workspace_folder(&lib_root));
+        assert!(!app.remove_workspace_folder(&app_root), "the primary root");
+        assert_eq!(app.explorer().unwrap().roots(), [app_root]);
+    }
+
+    /// A git repository in the temp directory with `a.txt` committed, and
+    /// the guard removing it.
+    fn git_repository(name: &str, text: &str) -> (std::path::PathBuf, Cleanup) {
+        let root = temp_dir(name);
+        let cleanup = Cleanup::new([&root]);
+        let root = std::fs::canonicalize(root).unwrap();
+        std::fs::write(root.join("a.txt"), text).unwrap();
+        for args in [
+            &["init", "-q"][..],
+            &["add", "a.txt"],
+            &[
+                "-c",
+                "user.name=Test",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "-q",
+                "-m",
+                "init",
+            ],
+        ] {
+            let status = std::process::Command::new("git")
+                .arg("-C")
+                .arg(&root)
+                .args(args)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        }
+        (root, cleanup)
+    }
+
+    fn git_segment(app: &App) -> Option<&str> {
+        app.status_bar()
+            .segments()
+            .iter()
+            .find(|(id, _)| id == GIT_SEGMENT)
+            .map(|(_, text)| text.as_str())
+    }
+
+    #[test]
+    fn test_diff_view_stages_and_reverts_single_hunks() {
+        let (root, _cleanup) = git_repository("git-hunks", "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n");
+        let path = root.join("a.txt");
+        std::fs::write(&path, "one\n2\n3\n4\n5\n6\n7\n8\n9\nten\n").unwrap();
+        let mut app = App::new();
+        app.set_workspace_root(&root);
+        app.open_file(&path).unwrap();
+
+        app.execute_command("workbench.view.scm").unwrap();
+        assert_eq!(app.focused_context(), Some(WindowContext::Git));
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        assert_eq!(app.focused_context(), Some(WindowContext::Diff));
+        let view = app.diff_view().unwrap();
+        assert_eq!(view.base(), Some(DiffBase::Index));
+        assert_eq!(view.diff().hunks.len(), 2);
+
+        // Stage the first hunk; the second one moves up and stays selected
+        app.handle_event(AppEvent::Key(AppKey::Char('s')));
+        assert_eq!(git_segment(&app), Some("Staged hunk 1 of a.txt"));
+        assert_eq!(app.diff_view().unwrap().diff().hunks.len(), 1);
+        let entry = &app.git_panel().unwrap().entries()[0];
+        assert!(entry.staged.is_some() && entry.unstaged.is_some());
+
+        // Reverting is refused while the open buffer has unsaved changes
+        app.editor_mut().set_text("unsaved");
+        app.handle_event(AppEvent::Key(AppKey::Char('x')));
+        assert_eq!(
+            git_segment(&app),
+            Some("Save a.txt before reverting its changes")
+        );
+        app.editor_mut()
+            .set_text(std::fs::read_to_string(&path).unwrap());
+        app.buffers().save(&path).unwrap();
+
+        // Reverting the other hunk reloads the open buffer
+        app.handle_event(AppEvent::Key(AppKey::Char('x')));
+        let reverted = "one\n2\n3\n4\n5\n6\n7\n8\n9\n10\n";
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), reverted);
+        assert_eq!(app.editor().text(), reverted);
+        assert!(!app.editor().is_dirty());
+        assert!(app.diff_view().unwrap().diff().is_empty());
+        let entry = &app.git_panel().unwrap().entries()[0];
+        assert!(entry.staged.is_some() && entry.unstaged.is_none());
+
+        app.handle_event(AppEvent::Key(AppKey::Char('v')));
+        assert_eq!(app.diff_view().unwrap().layout(), DiffLayout::SideBySide);
+    }
+
+    #[test]
+    fn test_compare_commands_diff_the_buffer_with_saved_and_head() {
+        let (root, _cleanup) = git_repository("git-compare", "one\ntwo\n");
+        let path = root.join("a.txt");
+        std::fs::write(&path, "one\ntwo\nthree\n").unwrap();
+        let mut app = App::new();
+        app.set_workspace_root(&root);
+        app.open_file(&path).unwrap();
+        app.editor_mut().set_text("ONE\ntwo\nthree\n");
+
+        app.execute_command("workbench.files.action.compareWithSaved")
+            .unwrap();
+        assert_eq!(app.focused_context(), Some(WindowContext::Diff));
+        let view = app.diff_view().unwrap();
+        assert_eq!(view.base(), None);
+        assert_eq!(view.diff().hunks[0].header(), "@@ -1,3 +1,3 @@");
+
+        app.execute_command("git.compareWithHead").unwrap();
+        let view = app.diff_view().unwrap();
+        assert_eq!(view.diff().hunks[0].header(), "@@ -1,2 +1,3 @@");
+        // Comparisons are not git changes to stage
+        app.handle_event(AppEvent::Key(AppKey::Char('s')));
+        assert_eq!(
+            git_segment(&app),
+            Some("Only git changes can be staged, unstaged or reverted")
+        );
+
+        let other = root.join("b.txt");
+        std::fs::write(&other, "one\n").unwrap();
+        app.compare_buffers(&other, &path).unwrap();
+        let view = app.diff_view().unwrap();
+        assert_eq!(
+            view.subject(),
+            &DiffSubject::Compare {
+                old: "b.txt".to_string(),
+                new: "a.txt".to_string(),
+            }
+        );
+        assert_eq!(view.diff().hunks[0].header(), "@@ -1,1 +1,3 @@");
+    }
+
+    #[test]
+    fn test_compare_with_clipboard_and_a_chosen_file() {
+        let (mut app, path) = app_with_open_file("compare-with.txt", "one\ntwo\n");
+        let other = temp_path("compare-other.txt");
+        let _cleanup = Cleanup::new([&path, &other]);
+        app.set_workspace_root(std::env::temp_dir());
+        app.editor_mut().set_text("one\nTWO\n");
+        app.clipboard().copy("one\n").unwrap();
+
+        app.execute_command("workbench.files.action.compareWithClipboard")
+            .unwrap();
+        assert_eq!(app.focused_context(), Some(WindowContext::Diff));
+        let view = app.diff_view().unwrap();
+        assert_eq!(
+            view.subject(),
+            &DiffSubject::Compare {
+                old: "Clipboard".to_string(),
+                new: app.relative_name(&path),
+            }
+        );
+        assert_eq!(view.diff().hunks[0].header(), "@@ -1,1 +1,2 @@");
+
+        std::fs::write(&other, "zero\none\nTWO\n").unwrap();
+        app.focus_manager.set_focus(app.editor_id());
+        app.execute_command("workbench.files.action.compareFileWith")
+            .unwrap();
+        for c in "missing.txt".chars() {
+            app.handle_event(AppEvent::Key(AppKey::Char(c)));
+        }
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        let error = app.input_box().and_then(InputBoxWindow::error);
+        assert!(error.is_some_and(|error| error.starts_with("Cannot read")));
+        while !app.input_box().unwrap().value().is_empty() {
+            app.handle_event(AppEvent::Key(AppKey::Backspace));
+        }
+        let name = other.file_name().unwrap().to_str().unwrap();
+        for c in name.chars() {
+            app.handle_event(AppEvent::Key(AppKey::Char(c)));
+        }
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        assert!(app.input_box().is_none());
+        let view = app.diff_view().unwrap();
+        assert_eq!(
+            view.subject(),
+            &DiffSubject::Compare {
+                old: name.to_string(),
+                new: app.relative_name(&path),
+            }
+        );
+        assert_eq!(view.diff().hunks[0].header(), "@@ -1,3 +1,2 @@");
+    }
+
+    #[test]
+    fn test_git_panel_does_not_open_outside_a_repository() {
+        let mut app = App::new();
+        app.set_workspace_root("/");
+        assert!(!app.toggle_git_panel());
+        assert!(app.git_panel().is_none());
+        app.handle_event(AppEvent::Tick);
+        assert_eq!(git_segment(&app), Some("Git: / is not in a git repository"));
+    }
+
+    #[test]
+    fn test_output_pane_switches_between_channels() {
+        let mut app = App::new();
+        app.set_workspace_root("/");
+        app.outputs()
+            .channel(CARGO_CHANNEL)
+            .append_line("Compiling demo");
+        app.toggle_git_panel();
+
+        app.execute_command("workbench.action.toggleOutput")
+            .unwrap();
+        let output = app.output_pane().unwrap();
+        assert_eq!(output.channel(), Some(CARGO_CHANNEL));
+        assert_eq!(output.lines(), ["Compiling demo"]);
+
+        // Enter drops down the channel list; Q does not quit while it is open
+        for key in [AppKey::Enter, AppKey::Q, AppKey::Down, AppKey::Enter] {
+            app.handle_event(AppEvent::Key(key));
+        }
+        assert!(app.is_running());
+        let output = app.output_pane().unwrap();
+        assert_eq!(output.channel(), Some(GIT_CHANNEL));
+        assert_eq!(output.lines(), ["/ is not in a git repository"]);
+    }
+
+    #[test]
+    fn test_saves_and_git_changes_refresh_gutter_and_branch() {
+        use cli_ide_platform::git::diff::LineChangeKind;
+
+        let (root, _cleanup) = git_repository("git-gutter", "1\n2\n3\n");
+        let path = root.join("a.txt");
+        let mut app = App::new();
+        app.set_workspace_root(&root);
+        app.open_file(&path).unwrap();
+        assert_eq!(app.editor().line_changes(), Some(&[][..]));
+        assert!(app.watch_git(Duration::from_millis(5)));
+
+        app.editor_mut().set_text("1\ntwo\n3\n4\n");
+        app.execute_command("workbench.action.files.save").unwrap();
+        let status = std::process::Command::new("git")
+            .arg("-C")
+            .arg(&root)
+            .args(["switch", "-q", "-c", "topic"])
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let gutter = |app: &App| -> Vec<(usize, LineChangeKind)> {
+            app.editor()
+                .line_changes()
+                .unwrap_or_default()
+                .iter()
+                .map(|change| (change.line, change.kind))
+                .collect()
+        };
+        let expected = vec![(2, LineChangeKind::Modified), (4, LineChangeKind::Added)];
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while (gutter(&app) != expected || !branch_is(&app, "⎇ topic")) && Instant::now() < deadline
+        {
+            std::thread::sleep(Duration::from_millis(20));
+            app.handle_event(AppEvent::Tick);
+        }
+        assert_eq!(gutter(&app), expected);
+        assert!(branch_is(&app, "⎇ topic"));
+    }
+
+    fn branch_is(app: &App, text: &str) -> bool {
+        app.status_bar()
+            .segments()
+            .iter()
+            .any(|(id, segment)| id == BRANCH_SEGMENT && segment == text)
+    }
+
+    #[test]
+    fn test_branch_picker_creates_and_checks_out_branches() {
+        let (root, _cleanup) = git_repository("git-branches", "old\n");
+        let path = root.join("a.txt");
+        let mut app = App::new();
+        app.set_workspace_root(&root);
+        app.open_file(&path).unwrap();
+        app.refresh_branch();
+        app.handle_event(AppEvent::Tick);
+        let branch_segment = |app: &App| {
+            app.status_bar()
+                .segments()
+                .iter()
+                .find(|(id, _)| id == BRANCH_SEGMENT)
+                .map(|(_, text)| text.clone())
+        };
+        let main = branch_segment(&app).unwrap();
+        let main = main.strip_prefix("⎇ ").unwrap().to_string();
+
+        // The first item creates a branch from the name typed next
+        app.execute_command("git.checkout").unwrap();
+        assert_eq!(app.keybinding_mode(), KeybindingMode::BranchPicker);
+        assert_eq!(app.branch_picker().unwrap().matches().len(), 2);
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        for c in "topic".chars() {
+            app.handle_event(AppEvent::Key(AppKey::Char(c)));
+        }
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        assert!(app.input_box().is_none());
+        app.handle_event(AppEvent::Tick);
+        assert_eq!(branch_segment(&app).as_deref(), Some("⎇ topic"));
+
+        std::fs::write(&path, "new\n").unwrap();
+        let status = std::process::Command::new("git")
+            .arg("-C")
+            .arg(&root)
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(["commit", "-q", "-a", "-m", "update"])
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        // Checking out the first branch reloads the unchanged open file
+        app.execute_command("git.checkout").unwrap();
+        for c in main.chars() {
+            app.handle_event(AppEvent::Key(AppKey::Char(c)));
+        }
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        assert!(app.branch_picker().is_none());
+        app.handle_event(AppEvent::Tick);
+        assert_eq!(branch_segment(&app), Some(format!("⎇ {main}")));
+        assert_eq!(app.editor().text(), "old\n");
+    }
+
+    #[test]
+    fn test_stash_and_pop_local_changes() {
+        let (root, _cleanup) = git_repository("git-stash", "old\n");
+        let path = root.join("a.txt");
+        std::fs::write(&path, "new\n").unwrap();
+        let mut app = App::new();
+        app.set_workspace_root(&root);
+        app.open_file(&path).unwrap();
+
+        // Stashing reloads the open file from the clean work tree
+        app.execute_command("git.stash").unwrap();
+        for c in "wip".chars() {
+            app.handle_event(AppEvent::Key(AppKey::Char(c)));
+        }
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        app.handle_event(AppEvent::Tick);
+        assert_eq!(git_segment(&app), Some("Stashed changes as stash@{0}"));
+        assert_eq!(app.editor().text(), "old\n");
+
+        app.execute_command("git.stash").unwrap();
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        app.handle_event(AppEvent::Tick);
+        assert_eq!(
+            git_segment(&app),
+            Some("There are no local changes to stash")
+        );
+
+        // Popping from the picker brings the changes back
+        app.execute_command("git.stashPop").unwrap();
+        assert_eq!(app.keybinding_mode(), KeybindingMode::StashPicker);
+        assert_eq!(app.stash_picker().unwrap().matches().len(), 1);
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        assert!(app.stash_picker().is_none());
+        app.handle_event(AppEvent::Tick);
+        assert_eq!(app.editor().text(), "new\n");
+
+        app.execute_command("git.stashPopLatest").unwrap();
+        app.handle_event(AppEvent::Tick);
+        assert_eq!(git_segment(&app), Some("There are no stashes"));
+    }
+
+    #[test]
+    fn test_git_history_opens_files_read_only_at_a_commit() {
+        let (root, _cleanup) = git_repository("git-log", "old\n");
+        let path = root.join("a.txt");
+        std::fs::write(&path, "new\n").unwrap();
+        let status = std::process::Command::new("git")
+            .arg("-C")
+            .arg(&root)
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(["commit", "-q", "-a", "-m", "update"])
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let mut app = App::new();
+        app.set_workspace_root(&root);
+
+        app.execute_command("workbench.view.gitHistory").unwrap();
+        assert_eq!(app.focused_context(), Some(WindowContext::GitLog));
+        let log = app.git_log().unwrap();
+        let subjects: Vec<&str> = log.commits().iter().map(|c| c.subject.as_str()).collect();
+        assert_eq!(subjects, vec!["update", "init"]);
+        let short_id = log.commits()[1].short_id.clone();
+
+        // Show the first commit, then open its only file
+        app.handle_event(AppEvent::Key(AppKey::Down));
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        let detail = app.git_log().unwrap().detail().unwrap();
+        assert_eq!(detail.commit.subject, "init");
+        assert_eq!(detail.files[0].path, path);
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        assert_eq!(app.focused(), FocusedPane::Editor);
+        assert_eq!(app.editor().text(), "old\n");
+        assert_eq!(
+            app.editor().group().active().title(),
+            format!("a.txt @ {short_id}")
+        );
+
+        // Saving is refused rather than writing the old text to the file
+        app.execute_command("workbench.action.files.save").unwrap();
+        app.handle_event(AppEvent::Tick);
+        assert!(app.input_box().is_none());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new\n");
+        let save = app
+            .status_bar()
+            .segments()
+            .iter()
+            .find(|(id, _)| id == SAVE_SEGMENT)
+            .map(|(_, text)| text.as_str());
+        assert_eq!(save, Some("Save failed: the buffer is read-only"));
+    }
+
+    #[test]
+    fn test_cargo_build_streams_output_and_fills_problems() {
+        let root = temp_dir("cargo-build");
+        let _cleanup = Cleanup::new([&root]);
+        std::fs::create_dir(root.join("src")).unwrap();
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"broken\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[workspace]\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("src/main.rs"), "fn main() {\n    missing();\n}\n").unwrap();
+        let mut app = App::new();
+        app.set_workspace_root(&root);
+
+        app.execute_command("cargo.build").unwrap();
+        assert!(app.is_task_running());
+        assert_eq!(app.output_pane().unwrap().channel(), Some(CARGO_CHANNEL));
+        assert_eq!(
+            app.output_pane()
+                .unwrap()
+                .lines()
+                .first()
+                .map(String::as_str),
+            Some("> cargo build --color=never")
+        );
+        let deadline = std::time::Instant::now() + Duration::from_secs(120);
+        while app.is_task_running() {
+            assert!(std::time::Instant::now() < deadline, "timed out");
+            std::thread::sleep(Duration::from_millis(10));
+            app.handle_event(AppEvent::Tick);
+        }
+        let output = app.output_pane().unwrap().lines();
+        assert!(output
+            .iter()
+            .any(|line| line.contains("cannot find function")));
+        assert_eq!(
+            output.last().map(String::as_str),
+            Some("cargo build failed with exit code 101")
+        );
+        let cargo_segment = app
+            .status_bar()
+            .segments()
+            .iter()
+            .find(|(id, _)| id == TASK_SEGMENT)
+            .map(|(_, text)| text.clone());
+        assert_eq!(
+            cargo_segment.as_deref(),
+            Some("cargo build failed with exit code 101 (1 error, 0 warnings)")
+        );
+
+        // The Problems panel lists the error and Enter jumps to it
+        assert_eq!(app.problems().len(), 1);
+        assert!(app.toggle_problems());
+        assert_eq!(app.focused_context(), Some(WindowContext::Problems));
+        assert_eq!(app.problems_panel().unwrap().problems(), app.problems());
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        assert_eq!(app.focused_context(), Some(WindowContext::Editor));
+        assert_eq!(app.editor().cursor_position(), (2, 5));
+    }
+
+    #[test]
+    fn test_configured_tasks_stream_output_and_fill_problems() {
+        let root = temp_dir("tasks");
+        let _cleanup = Cleanup::new([&root]);
+        std::fs::create_dir(root.join("src")).unwrap();
+        std::fs::write(root.join("src/lib.rs"), "one\ntwo\nthree\n").unwrap();
+        let mut app = App::new();
+        app.set_workspace_root(&root);
+        let config = Config::parse(
+            "[problem_matchers.lint]\n\
+             regex = '^(?P<file>[^:]+):(?P<line>\\d+):(?P<column>\\d+): (?P<message>.*)$'\n\
+             [tasks.lint]\ncommand = \"echo linting; echo 'src/lib.rs:3:2: bad' >&2; exit 1\"\n\
+             [tasks.hello]\ncommand = \"echo hello\"\noutput = \"terminal\"\n",
+        )
+        .unwrap();
+        assert!(app.apply_config(&config).is_empty());
+        let wait = |app: &mut App| {
+            let deadline = std::time::Instant::now() + Duration::from_secs(10);
+            while app.is_task_running() {
+                assert!(std::time::Instant::now() < deadline, "timed out");
+                std::thread::sleep(Duration::from_millis(10));
+                app.handle_event(AppEvent::Tick);
+            }
+        };
+
+        // The picker lists the tasks and runs the selected one
+        app.execute_command("workbench.action.tasks.runTask")
+            .unwrap();
+        assert_eq!(app.keybinding_mode(), KeybindingMode::TaskPicker);
+        assert_eq!(app.task_picker().unwrap().matches().len(), 2);
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        assert!(app.task_picker().is_none());
+        wait(&mut app);
+        let output = app.output_pane().unwrap();
+        assert_eq!(output.channel(), Some(TASKS_CHANNEL));
+        assert!(output.lines().iter().any(|line| line == "linting"));
+        assert_eq!(
+            output.lines().last().map(String::as_str),
+            Some("lint failed with exit code 1")
+        );
+
+        // The problem is listed and Enter in the Problems panel jumps to it
+        assert_eq!(app.problems().len(), 1);
+        assert!(app.toggle_problems());
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        assert_eq!(app.focused_context(), Some(WindowContext::Editor));
+        assert_eq!(app.editor().cursor_position(), (3, 2));
+
+        // A task can write to the terminal instead
+        app.run_task("hello").unwrap();
+        wait(&mut app);
+        let terminal = app.terminal().unwrap().output();
+        assert!(terminal.starts_with("> sh -c echo hello\nhello\n"));
+        assert!(app.problems().is_empty());
+        assert_eq!(
+            app.run_task("deploy"),
+            Err("No task named `deploy`".to_string())
+        );
+    }
+
+    #[test]
+    fn test_regex_tester_takes_typed_keys_until_closed() {
+        let mut app = App::new();
+        let editor_text = app.editor().text();
+        app.execute_command("workbench.action.toggleRegexTester")
+            .unwrap();
+        assert_eq!(app.focused_id(), app.regex_tester_id);
+        assert_eq!(app.keybinding_mode(), KeybindingMode::Input);
+
+        // Keys bound in Normal mode are text here
+        type_text(&mut app, "q+");
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        type_text(&mut app, "qqq x q");
+        let tester = app.regex_tester().unwrap();
+        assert_eq!(tester.pattern(), "q+");
+        assert_eq!(tester.match_count(), 2);
+        assert_eq!(app.editor().text(), editor_text);
+
+        app.handle_event(AppEvent::Key(AppKey::Esc));
+        assert!(app.regex_tester().is_none());
+        assert_eq!(app.focused_id(), Some(app.editor_id()));
+        assert_eq!(app.keybinding_mode(), KeybindingMode::Normal);
+    }
+
+    #[test]
+    fn test_undo_tree_goes_back_to_an_undone_branch() {
         let mut app = App::new();
-        assert!(app.is_running());
+        app.editor_mut().set_text("");
+        app.editor_mut().insert_text("one");
+        app.editor_mut().insert_text(" more");
+        app.editor_mut().undo();
+        app.editor_mut().insert_text(" two");
+        app.execute_command("workbench.action.toggleUndoTree")
+            .unwrap();
+        assert_eq!(app.focused_id(), app.undo_tree_id);
+        let tree = app.undo_tree().expect("undo tree should open");
+        let labels: Vec<&str> = tree.rows().iter().map(|row| row.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            [
+                "Original",
+                "Insert \"one\"",
+                "Insert \" more\"",
+                "Insert \" two\""
+            ]
+        );
+        assert_eq!(tree.selected(), Some(3));
 
-        app.handle_event(AppEvent::Key(AppKey::Q));
+        // Enter on the undone branch brings its text back
+        app.handle_event(AppEvent::Key(AppKey::Up));
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        assert_eq!(app.editor().text(), "one more");
+        assert!(app.undo_tree().unwrap().rows()[2].current);
+        assert!(app.editor_mut().undo());
+        assert_eq!(app.editor().text(), "one");
 
-        assert!(!app.is_running());
+        app.handle_event(AppEvent::Key(AppKey::Esc));
+        assert!(app.undo_tree().is_none());
+        assert_eq!(app.focused_id(), Some(app.editor_id()));
     }
 
     #[test]
-    fn test_quit_on_esc() {
+    fn test_new_untitled_opens_in_focused_editor() {
         let mut app = App::new();
-        assert!(app.is_running());
+        app.execute_command("workbench.action.files.newUntitledFile")
+            .unwrap();
+        assert_eq!(
+            app.buffers().uris(),
+            vec![BufferUri::Untitled("Untitled-1".to_string())]
+        );
+        assert_eq!(app.editor().text(), "");
+        assert_eq!(app.editor().path(), None);
+
+        app.editor_mut().set_text("scratch");
+        assert!(app.editor().is_dirty());
+        app.execute_command("workbench.action.files.newUntitledFile")
+            .unwrap();
+        // The first buffer has unsaved changes, so it stays open
+        assert_eq!(app.buffers().len(), 2);
+    }
+
+    #[test]
+    fn test_saving_untitled_prompts_for_path() {
+        let path = temp_path("untitled-save.txt");
+        let mut app = App::new();
+        app.new_untitled();
+        app.editor_mut().set_text("notes");
+
+        app.execute_command("workbench.action.files.save").unwrap();
+        assert_eq!(app.keybinding_mode(), KeybindingMode::Input);
+        assert_eq!(app.input_box().unwrap().value(), "Untitled-1");
+
+        type_text(&mut app, path.to_str().unwrap());
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+
+        assert!(app.input_box().is_none());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "notes");
+        assert!(!app.editor().is_dirty());
+        let path = std::fs::canonicalize(&path).unwrap();
+        assert_eq!(app.editor().path(), Some(path.clone()));
+        assert_eq!(app.buffers().uris(), vec![BufferUri::File(path.clone())]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_failed_save_as_keeps_prompt_open_with_error() {
+        let mut app = App::new();
+        app.new_untitled();
+        app.editor_mut().set_text("notes");
+        app.execute_command("workbench.action.files.save").unwrap();
+
+        let path = temp_path("missing-dir").join("file.txt");
+        type_text(&mut app, path.to_str().unwrap());
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+
+        let input = app.input_box().expect("prompt stays open");
+        assert!(input.error().is_some());
+        assert!(app.editor().is_dirty());
 
         app.handle_event(AppEvent::Key(AppKey::Esc));
+        assert!(app.input_box().is_none());
+        assert!(app.is_running(), "Esc closes the prompt, not the app");
+    }
 
-        assert!(!app.is_running());
+    #[test]
+    fn test_save_writes_file_backed_editor() {
+        let path = temp_path("save-command.txt");
+        std::fs::write(&path, "before").unwrap();
+        let mut app = App::new();
+        app.open_file(&path).unwrap();
+        app.editor_mut().set_text("after");
+
+        app.execute_command("workbench.action.files.save").unwrap();
+        assert!(app.input_box().is_none());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "after");
+        assert!(!app.editor().is_dirty());
+        std::fs::remove_file(&path).unwrap();
     }
 
     #[test]
-    fn test_resize_updates_dimensions() {
+    fn test_configured_problem_matchers_read_cargo_output() {
         let mut app = App::new();
-        assert_eq!(app.size(), (80, 24));
+        let config = Config::parse(
+            "[problem_matchers.todo]\n\
+             regex = '^(?P<file>\\S+):(?P<line>\\d+): TODO (?P<message>.*)$'\n\
+             severity = \"warning\"\n\
+             [problem_matchers.broken]\nregex = '(?P<file>.*)'\n",
+        )
+        .unwrap();
+        let problems = app.apply_config(&config);
+        assert_eq!(
+            problems,
+            ["problem matcher `broken`: no `(?P<line>...)` group"]
+        );
 
-        app.handle_event(AppEvent::Resize(100, 50));
+        let output = "\
+error[E0425]: cannot find value `x` in this scope
+ --> src/main.rs:2:5
+src/lib.rs:7: TODO remove the fallback
+";
+        let mut matchers = app.problem_matchers(Path::new("/ws"));
+        let found: Vec<(PathBuf, usize, Severity)> = output
+            .lines()
+            .flat_map(|line| {
+                matchers
+                    .iter_mut()
+                    .filter_map(|matcher| matcher.feed(line))
+                    .collect::<Vec<_>>()
+            })
+            .map(|problem| (problem.path, problem.line, problem.severity))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (PathBuf::from("/ws/src/main.rs"), 2, Severity::Error),
+                (PathBuf::from("/ws/src/lib.rs"), 7, Severity::Warning),
+            ]
+        );
 
-        assert_eq!(app.size(), (100, 50));
+        app.apply_config(&Config::new());
+        assert_eq!(app.problem_matchers(Path::new("/ws")).len(), 1);
     }
 
     #[test]
-    fn test_focus_toggle() {
+    fn test_diagnostics_are_grouped_underlined_and_navigable() {
+        let root = temp_dir("diagnostics");
+        let _cleanup = Cleanup::new([&root]);
+        let (a, b) = (root.join("a.rs"), root.join("b.rs"));
+        std::fs::write(&a, "fn a() {\n    x;\n    y;\n}\n").unwrap();
+        std::fs::write(&b, "fn b() {}\n").unwrap();
+        let problem = |path: &Path, line, column, severity| Problem {
+            path: path.to_path_buf(),
+            line,
+            column,
+            severity,
+            message: format!("problem at {line}"),
+            code: None,
+        };
+
         let mut app = App::new();
-        assert_eq!(app.focused(), FocusedPane::Editor);
+        app.set_workspace_root(&root);
+        app.open_file(&a).unwrap();
+        app.set_problems(vec![
+            problem(&b, 1, 4, Severity::Warning),
+            problem(&a, 3, 5, Severity::Warning),
+            problem(&a, 2, 5, Severity::Error),
+        ]);
+        assert_eq!(app.editor().diagnostics().len(), 2);
 
-        app.handle_event(AppEvent::Key(AppKey::Tab));
-        assert_eq!(app.focused(), FocusedPane::Terminal);
+        // `e` and `E` go through the file's problems, wrapping around
+        let diagnostic_segment = |app: &App| {
+            app.status_bar()
+                .segments()
+                .iter()
+                .find(|(id, _)| id == DIAGNOSTIC_SEGMENT)
+                .map(|(_, text)| text.clone())
+        };
+        app.handle_event(AppEvent::Key(AppKey::Char('e')));
+        assert_eq!(app.editor().cursor_position(), (2, 5));
+        assert_eq!(
+            diagnostic_segment(&app).as_deref(),
+            Some("error: problem at 2")
+        );
+        app.handle_event(AppEvent::Key(AppKey::Char('e')));
+        app.handle_event(AppEvent::Key(AppKey::Char('e')));
+        assert_eq!(app.editor().cursor_position(), (2, 5));
+        app.handle_event(AppEvent::Key(AppKey::Char('E')));
+        assert_eq!(app.editor().cursor_position(), (3, 5));
 
-        app.handle_event(AppEvent::Key(AppKey::Tab));
-        assert_eq!(app.focused(), FocusedPane::Editor);
+        // The panel groups the problems by file and opens them
+        app.execute_command("workbench.actions.view.diagnostics")
+            .unwrap();
+        let files: Vec<usize> = app
+            .diagnostics_panel()
+            .unwrap()
+            .files()
+            .iter()
+            .map(|(_, problems)| problems.len())
+            .collect();
+        assert_eq!(files, [2, 1]);
+        for key in [AppKey::Down, AppKey::Down, AppKey::Down, AppKey::Down] {
+            app.handle_event(AppEvent::Key(key));
+        }
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        assert_eq!(app.focused_id(), Some(app.editor_id()));
+        assert_eq!(app.editor().buffer().borrow().path(), Some(b.as_path()));
+        assert_eq!(app.editor().cursor_position(), (1, 4));
+        assert_eq!(app.editor().diagnostics().len(), 1);
+
+        app.set_problems(Vec::new());
+        assert!(app.editor().diagnostics().is_empty());
+        assert!(app.diagnostics_panel().unwrap().files().is_empty());
+        assert!(!app.toggle_diagnostics());
     }
 
     #[test]
-    fn test_focused_pane_toggle() {
-        assert_eq!(FocusedPane::Editor.toggle(), FocusedPane::Terminal);
-        assert_eq!(FocusedPane::Terminal.toggle(), FocusedPane::Editor);
+    fn test_session_restores_files_layout_and_focus() {
+        let root = temp_dir("session-restore");
+        let _cleanup = Cleanup::new([&root]);
+        let (a, b) = (root.join("a.rs"), root.join("b.rs"));
+        std::fs::write(&a, "fn a() {}\n").unwrap();
+        std::fs::write(&b, "fn b() {}\nfn c() {}\n").unwrap();
+
+        let mut app = App::with_size(120, 40);
+        app.set_workspace_root(&root);
+        app.open_file(&a).unwrap();
+        app.open_file(&b).unwrap();
+        app.editor_mut().set_selection(10..10);
+        app.toggle_logs();
+        app.toggle_explorer();
+        app.close_window(app.terminal_id());
+        let session = app.session();
+
+        let kinds: Vec<&str> = session
+            .windows
+            .iter()
+            .map(|window| window.kind.as_str())
+            .collect();
+        assert_eq!(kinds, ["editor", "logs", "explorer"]);
+        assert_eq!(session.focused, Some(2));
+        assert_eq!(session.windows[0].tabs.len(), 2);
+        assert_eq!(session.windows[0].active, 1);
+
+        std::fs::remove_file(&a).unwrap();
+        let restored = App::from_session(&Session::parse(&session.to_string()).unwrap());
+        assert_eq!(restored.workspace_root(), root);
+        assert_eq!(restored.size(), (120, 40));
+        assert!(restored.terminal().is_none());
+        let explorer = restored.explorer_id().unwrap();
+        assert_eq!(
+            restored.windows().ids(),
+            [restored.editor_id(), restored.logs_id().unwrap(), explorer]
+        );
+        assert_eq!(restored.focused_id(), Some(explorer));
+        assert_eq!(restored.editor().group().len(), 1, "a.rs was deleted");
+        assert_eq!(restored.editor().path(), Some(b.clone()));
+        assert_eq!(restored.editor().selection(), 10..10);
     }
 
     #[test]
-    fn test_tick_does_not_change_state() {
+    fn test_session_skips_unknown_windows() {
+        let session = Session::parse(
+            "version 1\nroot /nowhere\nfocus 1\nlayout v(0 h(1 2))\n\
+             window hologram\nwindow terminal\nwindow editor\n",
+        )
+        .unwrap();
+        let app = App::from_session(&session);
+        assert_eq!(
+            app.windows().layout(),
+            Some(&LayoutNode::even_split(
+                SplitDirection::Horizontal,
+                vec![
+                    LayoutNode::Window(app.terminal_id()),
+                    LayoutNode::Window(app.editor_id()),
+                ]
+            ))
+        );
+        assert_eq!(app.focused_id(), Some(app.terminal_id()));
+    }
+
+    #[test]
+    fn test_layout_preset_arranges_windows_and_opens_files() {
+        let path = temp_path("preset.rs");
+        std::fs::write(&path, "fn main() {}").unwrap();
+        let missing = temp_path("preset-missing.rs");
+        let config = Config::parse(&format!(
+            "[layout]\nwindows = \"explorer editor hologram\"\nsplit = \"v(0 h(1 2))\"\n\
+             files = \"{}, {}\"\nfocus = 0\n",
+            path.display(),
+            missing.display()
+        ))
+        .unwrap();
+        let preset = LayoutPreset::from_config(&config).unwrap().unwrap();
+
         let mut app = App::new();
-        let running_before = app.is_running();
-        let focused_before = app.focused();
-        let size_before = app.size();
+        let problems = app.apply_layout_preset(&preset);
+        assert_eq!(problems.len(), 2, "{problems:?}");
+        assert_eq!(problems[0], "cannot open window `hologram`");
+        assert!(problems[1].starts_with(&format!("cannot open {}", missing.display())));
+
+        let explorer = app.explorer_id().expect("explorer opens");
+        assert_eq!(
+            app.windows().layout(),
+            Some(&LayoutNode::even_split(
+                SplitDirection::Vertical,
+                vec![
+                    LayoutNode::Window(explorer),
+                    LayoutNode::Window(app.editor_id()),
+                ]
+            )),
+            "the terminal is not part of the preset"
+        );
+        assert_eq!(app.editor().path(), Some(path.clone()));
+        assert_eq!(app.focused_id(), Some(explorer));
+
+        // Applying it again reuses the open windows
+        app.apply_layout_preset(&preset);
+        assert_eq!(app.explorer_id(), Some(explorer));
+        assert_eq!(app.windows().ids().len(), 2);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_mirror_follows_the_focused_editor() {
+        let mut app = App::new();
+        app.editor_mut().set_text("one\ntwo\nthree");
+        app.execute_command("workbench.action.togglePresentationMirror")
+            .unwrap();
+        assert_eq!(app.focused_id(), Some(app.editor_id()));
+        let mirror = app.mirror().expect("mirror should open");
+        assert_eq!(mirror.view().text(), "one\ntwo\nthree");
+
+        app.handle_event(AppEvent::Key(AppKey::Down));
+        let mirror = app.mirror().unwrap();
+        assert_eq!(mirror.view().cursor_position(), (2, 1));
+        // Typing into the focused mirror edits nothing
+        app.focus_manager.set_focus(app.mirror_id.unwrap());
+        app.handle_event(AppEvent::Key(AppKey::Char('x')));
+        assert_eq!(app.editor().text(), "one\ntwo\nthree");
+        app.focus_manager.set_focus(app.editor_id());
 
+        // A split becomes the source while focused, and stays it afterwards
+        let split = app.split_editor(SplitDirection::Vertical).unwrap();
+        let path = temp_path("mirror.txt");
+        std::fs::write(&path, "split").unwrap();
+        let buffer = app.buffers.open(&path).unwrap();
+        app.windows
+            .window_mut::<EditorWindow>(split)
+            .unwrap()
+            .set_buffer(buffer);
         app.handle_event(AppEvent::Tick);
+        app.focus_manager.set_focus(app.terminal_id());
+        app.handle_event(AppEvent::Tick);
+        assert_eq!(app.mirror().unwrap().view().text(), "split");
+        assert_eq!(app.session().windows.len(), 4);
 
-        assert_eq!(app.is_running(), running_before);
-        assert_eq!(app.focused(), focused_before);
-        assert_eq!(app.size(), size_before);
+        // Closing the source falls back to the main editor
+        app.close_window(split);
+        app.handle_event(AppEvent::Tick);
+        assert_eq!(app.mirror().unwrap().view().text(), "one\ntwo\nthree");
+        app.execute_command("workbench.action.togglePresentationMirror")
+            .unwrap();
+        assert!(app.mirror().is_none());
+        std::fs::remove_file(&path).unwrap();
     }
 
     #[test]
-    fn test_window_ids_are_unique() {
-        let app = App::new();
-        assert_ne!(app.editor_id(), app.terminal_id());
+    fn test_markdown_preview_follows_the_editor_and_copies_rendered_text() {
+        let mut app = App::new();
+        app.editor_mut().set_text("# Notes\n\n- a **bold** item\n");
+        app.execute_command("workbench.action.toggleMarkdownPreview")
+            .unwrap();
+        assert_eq!(app.focused_context(), Some(WindowContext::MarkdownPreview));
+        let lines = |app: &App| app.markdown_preview().unwrap().lines();
+        assert_eq!(lines(&app), ["Notes", "", "• a bold item"]);
+
+        // `Shift+Right` selects rendered characters and `y` copies them
+        for _ in 0..5 {
+            app.handle_event(AppEvent::Key(AppKey::ShiftRight));
+        }
+        app.handle_event(AppEvent::Key(AppKey::Char('y')));
+        assert_eq!(app.clipboard().paste(), "Notes");
+
+        // Edits to the last focused editor show up
+        app.editor_mut().set_text("> quoted\n");
+        app.handle_event(AppEvent::Tick);
+        assert_eq!(lines(&app), ["│ quoted"]);
+
+        app.execute_command("workbench.action.toggleMarkdownPreview")
+            .unwrap();
+        assert!(app.markdown_preview().is_none());
     }
 
     #[test]
-    fn test_focused_id_tracks_editor() {
-        let app = App::new();
-        assert_eq!(app.focused_id(), Some(app.editor_id()));
+    fn test_quit_saves_the_session() {
+        let path = temp_path("quit.session");
+        let mut app = App::new();
+        app.set_session_service(SessionService::new(&path));
+        app.handle_event(AppEvent::Key(AppKey::Q));
+        assert!(!app.is_running());
+
+        let saved = SessionService::new(&path).load().unwrap().unwrap();
+        assert_eq!(saved, app.session());
+        std::fs::remove_file(&path).unwrap();
     }
 
     #[test]
-    fn test_focused_id_tracks_terminal() {
+    fn test_show_keybindings_lists_bindings_by_scope_and_marks_hidden_ones() {
+        let mut app = App::new();
+        app.keybinding_router_mut().register_for_context(
+            WindowContext::Terminal,
+            AppKey::Char(':'),
+            Action::OpenCommandLine,
+        );
+        app.execute_command("workbench.action.showKeybindings")
+            .unwrap();
+        let list = app.keybindings().expect("keybindings should open");
+        let entry = |scope: &str, key: &str| {
+            list.entries()
+                .iter()
+                .find(|entry| entry.scope == scope && entry.key == key)
+                .cloned()
+                .unwrap()
+        };
+        assert_eq!(entry("Global", "Esc").action, "Quit");
+        assert_eq!(entry("Global", ":").action, "Show All Commands");
+        assert_eq!(entry("Global", ":").shadows, None);
+        let hiding = entry("Terminal", ":");
+        assert_eq!(hiding.action, "Open Command Line");
+        assert_eq!(hiding.shadows.as_deref(), Some("Show All Commands"));
+        assert_eq!(
+            entry("Normal mode", "x").shadows,
+            None,
+            "the editor does not bind x"
+        );
+        assert_eq!(list.entries()[0].scope, "Global");
+
+        app.execute_command("workbench.action.showKeybindings")
+            .unwrap();
+        assert!(app.keybindings().is_none());
+    }
+
+    #[test]
+    fn test_snippets_added_in_the_manager_are_saved_and_expand() {
+        let path = temp_path("snippets-manager.toml");
+        let _cleanup = Cleanup::new([&path]);
+        let mut app = App::new();
+        app.load_snippets(&path).unwrap();
+        let keys = |app: &mut App, keys: &str| {
+            for c in keys.chars() {
+                app.handle_event(AppEvent::Key(AppKey::Char(c)));
+            }
+        };
+        app.execute_command("workbench.action.toggleSnippets")
+            .unwrap();
+        assert_eq!(app.focused_label(), "Snippets");
+
+        keys(&mut app, "arust fn");
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        keys(&mut app, "fn $0() {\\n}");
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        assert!(app.input_box().is_none());
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "[abbreviations.rust]\nfn = \"fn $0() {\\n}\"\n"
+        );
+        assert_eq!(app.snippets_window().unwrap().selected(), Some(0));
+        assert_eq!(
+            app.abbreviations.get("fn", Some("rust")),
+            Some("fn $0() {\n}")
+        );
+
+        // Reloading the settings keeps the snippets
+        let config = Config::parse("[abbreviations]\nteh = \"the\"\n").unwrap();
+        app.apply_config(&config);
+        assert_eq!(app.abbreviations.get("teh", None), Some("the"));
+        assert!(app.abbreviations.get("fn", Some("rust")).is_some());
+
+        keys(&mut app, "d");
+        assert!(app.snippets().snippets().is_empty());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "");
+        assert_eq!(app.abbreviations.get("fn", Some("rust")), None);
+    }
+
+    #[test]
+    fn test_terminal_report_shows_the_detected_capabilities() {
+        use cli_ide_platform::environment::environment_service::ColorSupport;
+
+        let mut app = App::new();
+        app.set_environment_service(EnvironmentService::new([
+            ("TERM", "xterm-256color"),
+            ("LANG", "C.UTF-8"),
+        ]));
+        app.execute_command("workbench.action.toggleTerminalCapabilities")
+            .unwrap();
+        let report = app.terminal_report().expect("report should open");
+        assert_eq!(report.capabilities().color, ColorSupport::Ansi256);
+        assert!(report.capabilities().unicode);
+        assert_eq!(app.session().windows.len(), 3, "editor, terminal, report");
+
+        app.execute_command("workbench.action.toggleTerminalCapabilities")
+            .unwrap();
+        assert!(app.terminal_report().is_none());
+    }
+
+    #[test]
+    fn test_log_viewer_shows_kept_and_new_records() {
+        use cli_ide_platform::logging::log_service::LogLevel;
+
+        let mut app = App::new();
+        let config = Config::parse("[log]\nlevel = \"debug\"\n").unwrap();
+        assert!(app.apply_config(&config).is_empty());
+        assert_eq!(app.logs().level(), LogLevel::Debug);
+
+        app.logs()
+            .log(LogRecord::new(LogLevel::Debug, "test", "before"));
+        app.handle_event(AppEvent::Key(AppKey::Char('L')));
+        assert_eq!(app.focused_id(), app.logs_id());
+        app.logs()
+            .log(LogRecord::new(LogLevel::Warn, "test", "after"));
+        app.logs()
+            .log(LogRecord::new(LogLevel::Trace, "test", "dropped"));
+        app.handle_event(AppEvent::Tick);
+
+        let messages: Vec<&str> = app
+            .log_window()
+            .unwrap()
+            .records()
+            .iter()
+            .map(|record| record.message.as_str())
+            .collect();
+        assert_eq!(messages, ["before", "after"]);
+
+        app.execute_command("workbench.action.toggleLogs").unwrap();
+        assert!(app.log_window().is_none());
+    }
+
+    #[test]
+    fn test_scrollbars_scroll_windows_by_click_and_page_keys() {
+        use cli_ide_platform::logging::log_service::LogLevel;
+
+        let mut app = App::with_size(80, 24);
+        let text: String = (1..=100).map(|line| format!("line {line}\n")).collect();
+        *app.editor_mut() = EditorWindow::with_text(text);
+        app.execute_command("workbench.action.toggleScrollbars")
+            .unwrap();
+        assert!(app.scrollbars());
+
+        let area = app.panes_rect(Rect::new(0, 0, 80, 24));
+        let track_of = |app: &App, id: WindowId| {
+            app.windows
+                .rects(area)
+                .into_iter()
+                .find(|&(open, _)| open == id)
+                .and_then(|(_, rect)| scrollbar::track(rect))
+                .unwrap()
+        };
+        let click = |app: &mut App, column: u16, row: u16| {
+            app.handle_event(AppEvent::Mouse(MouseEvent::new(
+                MouseEventKind::Down(MouseButton::Left),
+                column,
+                row,
+            )));
+        };
+        let editor = track_of(&app, app.editor_id());
+        click(&mut app, editor.x, editor.bottom() - 1);
+        assert_eq!(
+            usize::from(app.editor().scroll_offset()),
+            app.editor().scroll_position().unwrap().content - usize::from(editor.height)
+        );
+        click(&mut app, editor.x, editor.y);
+        assert_eq!(app.editor().scroll_offset(), 0);
+
+        for index in 0..60 {
+            app.logs().log(LogRecord::new(
+                LogLevel::Info,
+                "test",
+                format!("record {index}"),
+            ));
+        }
+        app.handle_event(AppEvent::Key(AppKey::Char('L')));
+        let logs = app.logs_id().unwrap();
+        let offset = |app: &App| {
+            app.windows
+                .get(logs)
+                .unwrap()
+                .scroll_position()
+                .unwrap()
+                .offset
+        };
+        let bottom = offset(&app);
+        app.handle_event(AppEvent::Key(AppKey::PageUp));
+        assert_eq!(
+            offset(&app),
+            bottom - usize::try_from(app.page_of(logs)).unwrap()
+        );
+        app.handle_event(AppEvent::Key(AppKey::PageDown));
+        assert_eq!(offset(&app), bottom);
+
+        let track = track_of(&app, logs);
+        click(&mut app, track.x, track.y);
+        assert_eq!(offset(&app), 0);
+
+        app.set_scrollbars(false);
+        click(&mut app, track.x, track.bottom() - 1);
+        assert_eq!(
+            offset(&app),
+            0,
+            "the border is not a scrollbar while they are hidden"
+        );
+    }
+
+    #[test]
+    fn test_read_only_panes_copy_selected_rows_and_text() {
+        use cli_ide_platform::logging::log_service::LogLevel;
+
+        let mut app = App::with_size(120, 30);
+        let records: Vec<LogRecord> = ["one", "two", "three"]
+            .into_iter()
+            .map(|message| LogRecord::new(LogLevel::Info, "test", message))
+            .collect();
+        for record in &records {
+            app.logs().log(record.clone());
+        }
+        app.handle_event(AppEvent::Key(AppKey::Char('L')));
+        assert_eq!(app.focused_context(), Some(WindowContext::Logs));
+        assert_eq!(app.log_window().unwrap().selected(), records.last());
+
+        // Keys select records and `y` copies them
+        app.handle_event(AppEvent::Key(AppKey::ShiftUp));
+        app.handle_event(AppEvent::Key(AppKey::Char('y')));
+        assert_eq!(
+            app.clipboard().paste(),
+            format!("{}\n{}", records[1], records[2])
+        );
+        assert!(!app.log_window().unwrap().is_following());
+
+        // Shift+Left/Shift+Right select characters of the cursor's record
+        app.handle_event(AppEvent::Key(AppKey::Down));
+        for _ in 0..3 {
+            app.handle_event(AppEvent::Key(AppKey::ShiftRight));
+        }
+        app.handle_event(AppEvent::Key(AppKey::Char('y')));
+        assert_eq!(app.clipboard().paste(), records[2].to_string()[..3]);
+
+        // A click and drag select the characters between them
+        app.set_problems(
+            (1..=3)
+                .map(|line| Problem {
+                    path: PathBuf::from("/ws/src/lib.rs"),
+                    line,
+                    column: 1,
+                    severity: Severity::Warning,
+                    message: format!("warning {line}"),
+                    code: None,
+                })
+                .collect(),
+        );
+        app.toggle_problems();
+        let area = app.panes_rect(Rect::new(0, 0, 120, 30));
+        let rect = app
+            .windows
+            .rects(area)
+            .into_iter()
+            .find(|&(id, _)| Some(id) == app.problems_id())
+            .map(|(_, rect)| rect)
+            .unwrap();
+        let mouse = |kind, row: u16, column: u16| {
+            AppEvent::Mouse(MouseEvent::new(kind, rect.x + 1 + column, rect.y + 1 + row))
+        };
+        // Row 0 is the summary line
+        app.handle_event(mouse(MouseEventKind::Down(MouseButton::Left), 2, 21));
+        app.handle_event(mouse(MouseEventKind::Drag(MouseButton::Left), 3, 8));
+        app.handle_event(AppEvent::Key(AppKey::Char('y')));
+        assert_eq!(app.clipboard().paste(), "warning 2\nW /ws/sr");
+
+        // Without a drag, the rows are copied
+        app.handle_event(mouse(MouseEventKind::Down(MouseButton::Left), 1, 21));
+        app.handle_event(AppEvent::Key(AppKey::ShiftDown));
+        app.handle_event(AppEvent::Key(AppKey::Char('y')));
+        assert_eq!(
+            app.clipboard().paste(),
+            "W /ws/src/lib.rs:1:1 warning 1\nW /ws/src/lib.rs:2:1 warning 2"
+        );
+    }
+
+    #[test]
+    fn test_command_line_runs_ex_commands() {
+        let root = temp_dir("command-line");
+        let _cleanup = Cleanup::new([&root]);
+        std::fs::create_dir(root.join("src")).unwrap();
+        std::fs::write(root.join("src/main.rs"), "fn main() {}\n").unwrap();
+        let root = std::fs::canonicalize(root).unwrap();
         let mut app = App::new();
+        app.set_workspace_root(&root);
+        app.execute_command("workbench.action.toggleModalEditing")
+            .unwrap();
+
+        // `:` opens the command line in Normal mode and `Tab` completes
+        app.handle_event(AppEvent::Key(AppKey::Char(':')));
+        assert_eq!(app.keybinding_mode(), KeybindingMode::CommandLine);
+        type_text(&mut app, "e src/ma");
         app.handle_event(AppEvent::Key(AppKey::Tab));
-        assert_eq!(app.focused_id(), Some(app.terminal_id()));
+        assert_eq!(app.command_line().unwrap().value(), "e src/main.rs");
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        assert!(app.command_line().is_none());
+        assert_eq!(app.editor().path(), Some(root.join("src/main.rs")));
+
+        app.handle_event(AppEvent::Key(AppKey::Char(':')));
+        type_text(&mut app, "set wrap indent=2");
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        assert!(app.editor().options().wrap);
+        assert_eq!(app.editor().options().indent_width, 2);
+
+        // A failing line keeps the command line open with the error
+        app.handle_event(AppEvent::Key(AppKey::Char(':')));
+        type_text(&mut app, "frob");
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        assert_eq!(
+            app.command_line().unwrap().error(),
+            Some("Not an editor command: frob")
+        );
+        app.handle_event(AppEvent::Key(AppKey::Esc));
+        assert!(app.command_line().is_none());
+
+        // Earlier lines are recalled
+        app.handle_event(AppEvent::Key(AppKey::Char(':')));
+        app.handle_event(AppEvent::Key(AppKey::Up));
+        app.handle_event(AppEvent::Key(AppKey::Up));
+        assert_eq!(app.command_line().unwrap().value(), "set wrap indent=2");
+        app.handle_event(AppEvent::Key(AppKey::Esc));
+
+        app.handle_event(AppEvent::Key(AppKey::Char(':')));
+        type_text(&mut app, "w copy.rs");
+        app.handle_event(AppEvent::Key(AppKey::Enter));
+        assert_eq!(
+            std::fs::read_to_string(root.join("copy.rs")).unwrap(),
+            "fn main() {}\n"
+        );
     }
 
     #[test]
-    fn test_focus_manager_accessible() {
-        let app = App::new();
-        assert!(app.focus_manager().is_focused(app.editor_id()));
+    fn test_panes_use_their_own_themes_and_unfocused_editors_dim() {
+        let mut app = App::with_size(80, 24);
+        let config = Config::parse(
+            "[workbench]\ndim_unfocused_editors = true\n\
+             [workbench.pane_themes]\nterminal = \"light\"\nnowhere = \"light\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            app.apply_config(&config),
+            vec!["`workbench.pane_themes`: unknown context `nowhere`"]
+        );
+        let editor = app.editor_id();
+        app.focus_manager.set_focus(editor);
+        assert_eq!(app.pane_theme(editor), Theme::dark());
+        assert_eq!(app.pane_theme(app.terminal_id()), Theme::light());
+
+        let split = app.split_editor(SplitDirection::Vertical).unwrap();
+        assert_eq!(app.focused_id(), Some(split));
+        assert_eq!(app.pane_theme(editor), Theme::dark().dimmed());
+        assert_eq!(app.pane_theme(split), Theme::dark());
+        app.set_pane_theme(editor, Some(Theme::light()));
+        assert_eq!(app.pane_theme(editor), Theme::light().dimmed());
+
+        let area = app.panes_rect(Rect::new(0, 0, 80, 24));
+        let rects = app.windows.rects(area);
+        let corner = |id: WindowId| {
+            let (_, rect) = rects.iter().find(|&&(open, _)| open == id).unwrap();
+            (rect.x, rect.y)
+        };
+        let backend = ratatui::backend::TestBackend::new(80, 24);
+        let mut terminal = ratatui::Terminal::new(backend).unwrap();
+        terminal
+            .draw(|frame| app.render(frame, frame.area()))
+            .unwrap();
+        let dim = |id: WindowId| {
+            terminal.backend().buffer()[corner(id)]
+                .modifier
+                .contains(Modifier::DIM)
+        };
+        assert!(dim(editor));
+        assert!(!dim(split));
+
+        app.set_pane_theme(editor, None);
+        app.set_dim_unfocused_editors(false);
+        assert_eq!(app.pane_theme(editor), Theme::dark());
     }
 
     #[test]
-    fn test_keybinding_router_accessible() {
-        let app = App::new();
-        assert!(app.keybinding_router().is_globally_bound(AppKey::Q));
+    fn test_density_and_compact_mode_drop_pane_borders() {
+        let mut app = App::with_size(80, 24);
+        let config = Config::parse("[workbench]\ndensity = \"compact\"\n").unwrap();
+        assert!(app.apply_config(&config).is_empty());
+        assert_eq!(app.density(), Density::Compact);
+        assert!(app.editor().is_compact());
+
+        let area = app.panes_rect(Rect::new(0, 0, 80, 24));
+        let left_edge = |app: &mut App, id: WindowId| {
+            let rect = app
+                .windows
+                .rects(area)
+                .into_iter()
+                .find(|&(open, _)| open == id)
+                .map(|(_, rect)| rect)
+                .unwrap();
+            let backend = ratatui::backend::TestBackend::new(80, 24);
+            let mut terminal = ratatui::Terminal::new(backend).unwrap();
+            terminal
+                .draw(|frame| app.render(frame, frame.area()))
+                .unwrap();
+            terminal.backend().buffer()[(rect.x, rect.y + 1)]
+                .symbol()
+                .to_string()
+        };
+        let editor = app.editor_id();
+        assert_eq!(left_edge(&mut app, editor), "W", "no side border");
+
+        // New panes follow the density until toggled on their own
+        app.handle_event(AppEvent::Key(AppKey::Char('L')));
+        let logs = app.logs_id().unwrap();
+        assert!(app.is_compact(logs));
+        app.execute_command("workbench.actions.view.toggleCompactMode")
+            .unwrap();
+        assert!(!app.is_compact(logs));
+        assert_eq!(left_edge(&mut app, logs), "┃", "the focused border is back");
+
+        app.execute_command("workbench.actions.view.toggleDensity")
+            .unwrap();
+        assert_eq!(app.density(), Density::Comfortable);
+        assert!(!app.editor().is_compact());
+        app.execute_command("workbench.actions.view.toggleDensity")
+            .unwrap();
+        assert!(app.editor().is_compact());
+        assert!(!app.is_compact(logs), "the toggled pane keeps its mode");
+
+        // Closing a pane forgets its mode
+        app.handle_event(AppEvent::Key(AppKey::Char('L')));
+        app.handle_event(AppEvent::Key(AppKey::Char('L')));
+        assert!(app.is_compact(app.logs_id().unwrap()));
+
+        let config = Config::parse("[workbench]\ndensity = \"cozy\"\n").unwrap();
+        assert_eq!(
+            app.apply_config(&config),
+            ["`workbench.density`: unknown density 'cozy' (expected 'comfortable' or 'compact')"]
+        );
     }
 
     #[test]
-    fn test_keybinding_router_mutable() {
+    fn test_apply_config_sets_options_auto_save_and_bindings() {
         let mut app = App::new();
-        app.keybinding_router_mut()
-            .register_global(AppKey::Char('x'), Action::Quit);
+        let config = Config::parse(
+            "[editor]\nwrap = false\nindent = 2\n[files]\nauto_save = \"onFocusChange\"\n\
+             [keybindings]\n\"?\" = \"showCommands\"\n[keybindings.editor]\ne = \"expandBracketSelection\"\n",
+        )
+        .unwrap();
+
+        assert!(app.apply_config(&config).is_empty());
+        assert!(!app.editor().options().wrap);
+        assert_eq!(app.editor().options().indent_width, 2);
+        assert_eq!(app.auto_save(), AutoSave::OnFocusChange);
+        assert_eq!(
+            app.keybinding_router().dispatch(AppKey::Char('?')),
+            Some(Action::OpenCommandPalette)
+        );
+        assert_eq!(
+            app.keybinding_router()
+                .dispatch_in_context(Some(WindowContext::Editor), AppKey::Char('e')),
+            Some(Action::ExpandBracketSelection)
+        );
+    }
+
+    #[test]
+    fn test_when_clause_bindings_follow_the_context_keys() {
+        let mut app = App::new();
+        let config = Config::parse(
+            "[keybindings]\nK = \"toggleLogs when editorFocused && mode == 'insert'\"\n\
+             J = \"quit when (\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            app.apply_config(&config),
+            ["`when` clause of key `J`: column 2: expected a key"]
+        );
+
+        app.editor_mut().set_text("");
+        app.handle_event(AppEvent::Key(AppKey::Char('K')));
+        assert!(app.logs_id().is_none(), "modal editing is off");
+        app.toggle_modal_editing();
+        app.handle_event(AppEvent::Key(AppKey::Char('i')));
+        app.handle_event(AppEvent::Key(AppKey::Char('K')));
+        assert!(app.logs_id().is_some());
+        assert_eq!(app.editor().text(), "", "the binding wins over typing");
+        app.update_context_keys();
+        assert_eq!(
+            app.context_keys().get("editorFocused"),
+            Some(ContextValue::Bool(false)),
+            "the logs have focus"
+        );
+
+        app.handle_event(AppEvent::Key(AppKey::Char('L')));
+        app.apply_config(&Config::default());
+        assert!(app.keybinding_router().conditional_bindings().is_empty());
+        app.handle_event(AppEvent::Key(AppKey::Char('K')));
+        assert_eq!(app.editor().text(), "K");
+        assert_eq!(
+            app.context_keys().get("mode"),
+            Some(ContextValue::from("insert"))
+        );
+    }
+
+    #[test]
+    fn test_reapplying_config_restores_replaced_bindings() {
+        let mut app = App::new();
+        let rebind = Config::parse("[keybindings]\nq = \"none\"\nx = \"quit\"\n").unwrap();
+        app.apply_config(&rebind);
+        assert_eq!(
+            app.keybinding_router().dispatch(AppKey::Q),
+            Some(Action::None)
+        );
+
+        app.apply_config(&Config::new());
+        assert_eq!(
+            app.keybinding_router().dispatch(AppKey::Q),
+            Some(Action::Quit)
+        );
+        assert!(!app.keybinding_router().is_globally_bound(AppKey::Char('x')));
+    }
+
+    #[test]
+    fn test_apply_config_skips_invalid_settings() {
+        let mut app = App::new();
+        let config = Config::parse(
+            "[files]\nauto_save = \"always\"\n[keybindings]\nx = \"fly\"\ny = \"quit\"\n\
+             [keybindings.panel]\nz = \"quit\"\n",
+        )
+        .unwrap();
+
+        let problems = app.apply_config(&config);
+        assert_eq!(problems.len(), 3, "{problems:?}");
+        assert!(problems[1].contains("unknown action `fly`"));
+        assert!(problems[2].contains("unknown keybinding context `panel`"));
+        assert_eq!(
+            app.keybinding_router().dispatch(AppKey::Char('y')),
+            Some(Action::Quit)
+        );
+    }
+
+    #[test]
+    fn test_config_service_changes_applied_on_next_event() {
+        let mut app = App::new();
+        app.set_config_service(ConfigService::new(
+            Config::parse("[keybindings]\nx = \"quit\"\n").unwrap(),
+        ));
+        assert!(app.keybinding_router().is_globally_bound(AppKey::Char('x')));
+
+        let service = app.services().resolve::<ConfigService>().unwrap();
+        service.update(Config::parse("[keybindings]\nx = \"fly\"\n").unwrap());
+        app.handle_event(AppEvent::Tick);
+
+        assert!(!app.keybinding_router().is_globally_bound(AppKey::Char('x')));
+        assert_eq!(
+            app.status_bar().right_text(),
+            "Config: unknown action `fly` | 80x24"
+        );
+
+        service.update(Config::new());
+        app.handle_event(AppEvent::Tick);
+        assert!(app.status_bar().segments().is_empty());
+    }
+
+    /// Handle ticks until `done` holds, failing after a few seconds.
+    fn tick_until(app: &mut App, done: impl Fn(&App) -> bool) {
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while !done(app) {
+            assert!(std::time::Instant::now() < deadline, "timed out");
+            std::thread::sleep(Duration::from_millis(5));
+            app.handle_event(AppEvent::Tick);
+        }
+    }
+
+    #[test]
+    fn test_watched_config_reloads_when_file_changes() {
+        let path = temp_path("watched-config.toml");
+        std::fs::write(&path, "").unwrap();
+        let mut app = App::new();
+        assert!(!app.watch_config(Duration::from_millis(5)));
+        app.set_config_service(ConfigService::load(&path).unwrap());
+        assert!(app.watch_config(Duration::from_millis(5)));
+
+        std::fs::write(&path, "[keybindings]\nx = \"quit\"\n").unwrap();
+        tick_until(&mut app, |app| {
+            app.keybinding_router().is_globally_bound(AppKey::Char('x'))
+        });
+
+        std::fs::write(&path, "[keybindings\n").unwrap();
+        tick_until(&mut app, |app| !app.status_bar().segments().is_empty());
+        assert!(app
+            .status_bar()
+            .right_text()
+            .starts_with("Config: line 1: unterminated section header"));
         assert!(app.keybinding_router().is_globally_bound(AppKey::Char('x')));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_watched_layers_and_theme_file_hot_apply() {
+        let user = temp_path("hot-user.toml");
+        let workspace = temp_path("hot-workspace.toml");
+        let theme = temp_path("hot-theme.toml");
+        let theme_name = theme.file_name().unwrap().to_str().unwrap();
+        std::fs::write(&user, format!("[workbench]\ntheme = \"{theme_name}\"\n")).unwrap();
+        std::fs::write(&theme, "name = \"hot\"\nbase = \"light\"\n").unwrap();
+        let mut app = App::new();
+        let service = ConfigService::load_layered([user.clone(), workspace.clone()]).unwrap();
+        app.set_config_service(service);
+        assert_eq!(app.theme().name, "hot");
+        assert_eq!(app.theme().text, Theme::light().text);
+        assert!(app.watch_config(Duration::from_millis(5)));
+
+        std::fs::write(&workspace, "[keybindings]\nx = \"quit\"\n").unwrap();
+        tick_until(&mut app, |app| {
+            app.keybinding_router().is_globally_bound(AppKey::Char('x'))
+        });
+
+        std::fs::write(&theme, "name = \"hot\"\n[styles]\nkeyword = \"red\"\n").unwrap();
+        tick_until(&mut app, |app| {
+            app.theme().keyword.fg == Some(ratatui::style::Color::Red)
+        });
+        assert_eq!(app.theme().text, Theme::dark().text);
+
+        std::fs::write(&user, "[workbench]\ntheme = \"light\"\n").unwrap();
+        tick_until(&mut app, |app| app.theme().name == "light");
+        for path in [user, workspace, theme] {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_toggle_theme_command_restyles_windows() {
+        let mut app = App::new();
+        assert_eq!(app.theme().name, "dark");
+
+        app.execute_command("workbench.action.toggleTheme").unwrap();
+        assert_eq!(app.theme().name, "light");
+        assert_eq!(
+            app.services().resolve::<Theme>().unwrap().name,
+            "light",
+            "resolvable through the container"
+        );
+
+        let backend = ratatui::backend::TestBackend::new(80, 24);
+        let mut terminal = ratatui::Terminal::new(backend).unwrap();
+        terminal
+            .draw(|frame| app.render(frame, frame.area()))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let editor_border = buffer.cell((0, 0)).unwrap();
+        let status_bar = buffer.cell((79, 23)).unwrap();
+        assert_eq!(editor_border.fg, Theme::light().focused_border.fg.unwrap());
+        assert_eq!(status_bar.bg, Theme::light().status_bar.bg.unwrap());
+
+        app.execute_command("workbench.action.toggleTheme").unwrap();
+        assert_eq!(app.theme().name, "dark");
+    }
+
+    #[test]
+    fn test_opened_windows_get_current_theme() {
+        let mut app = App::new();
+        app.set_theme(Theme::light());
+        let id = app.open_window(Box::new(TerminalWindow::default()));
+        app.focus_manager.set_focus(app.editor_id());
+
+        let backend = ratatui::backend::TestBackend::new(90, 10);
+        let mut terminal = ratatui::Terminal::new(backend).unwrap();
+        terminal
+            .draw(|frame| app.render(frame, frame.area()))
+            .unwrap();
+        let (_, rect) = app
+            .window_rects(Rect::new(0, 0, 90, 10))
+            .into_iter()
+            .find(|&(window, _)| window == id)
+            .unwrap();
+        let border = terminal.backend().buffer().cell((rect.x, rect.y)).unwrap();
+        assert_eq!(border.fg, Theme::light().border.fg.unwrap());
+    }
+
+    #[test]
+    fn test_only_changes_need_a_redraw() {
+        let mut app = App::with_size(80, 24);
+        assert!(app.needs_redraw(), "nothing drawn yet");
+        crate::harness::render_to_string(&mut app, 80, 24);
+        assert!(!app.needs_redraw());
+
+        app.handle_event(AppEvent::Tick);
+        app.handle_event(AppEvent::Resize(80, 24));
+        assert!(!app.needs_redraw(), "idle");
+
+        app.handle_event(AppEvent::Key(AppKey::Tab));
+        assert!(app.needs_redraw());
+        crate::harness::render_to_string(&mut app, 80, 24);
+
+        // A notification posted outside the App shows on the next tick
+        app.notifications()
+            .notify("Plugin", NotificationSeverity::Info, "done");
+        assert!(!app.needs_redraw());
+        app.handle_event(AppEvent::Tick);
+        assert!(app.needs_redraw());
+        crate::harness::render_to_string(&mut app, 80, 24);
+
+        app.status_bar_updates().emit(StatusBarUpdate::SetSegment {
+            id: "plugin".to_string(),
+            text: "busy".to_string(),
+        });
+        app.handle_event(AppEvent::Tick);
+        assert!(app.needs_redraw());
+        crate::harness::render_to_string(&mut app, 80, 24);
+
+        app.invalidate();
+        assert!(app.needs_redraw());
+    }
+    #[test]
+    fn test_ticks_run_the_registered_callbacks() {
+        let mut app = App::with_size(80, 24);
+        let ran = Arc::new(AtomicUsize::new(0));
+        let id = app.ticks().register(Duration::ZERO, {
+            let ran = Arc::clone(&ran);
+            move |_| {
+                ran.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+        crate::harness::render_to_string(&mut app, 80, 24);
+        app.handle_event(AppEvent::Tick);
+        assert_eq!(ran.load(Ordering::SeqCst), 1);
+        assert!(
+            app.needs_redraw(),
+            "the callback may have changed the screen"
+        );
+
+        crate::harness::render_to_string(&mut app, 80, 24);
+        assert!(app.ticks().unregister(id));
+        app.handle_event(AppEvent::Tick);
+        assert_eq!(ran.load(Ordering::SeqCst), 1);
+        assert!(!app.needs_redraw());
+    }
+
+    #[test]
+    fn test_word_count_follows_the_document_and_the_selection() {
+        let word_count = |app: &App| {
+            app.status_bar()
+                .segments()
+                .iter()
+                .find(|(id, _)| id == WORD_COUNT_SEGMENT)
+                .map(|(_, text)| text.clone())
+        };
+        let mut app = App::new();
+        app.editor_mut().set_text("one two\nthree");
+        app.handle_event(AppEvent::Tick);
+        assert_eq!(word_count(&app), None, "off by default");
+
+        app.execute_command("workbench.action.toggleWordCount")
+            .unwrap();
+        app.handle_event(AppEvent::Tick);
+        assert_eq!(
+            word_count(&app).as_deref(),
+            Some("2 lines, 3 words, 13 chars, 1 min read")
+        );
+
+        app.editor_mut().set_text("one");
+        app.handle_event(AppEvent::Tick);
+        assert_eq!(
+            word_count(&app).as_deref(),
+            Some("1 line, 1 word, 3 chars, 1 min read")
+        );
+        app.editor_mut().set_text("one two");
+        app.editor_mut().set_selection(4..7);
+        app.handle_event(AppEvent::Tick);
+        assert_eq!(
+            word_count(&app).as_deref(),
+            Some("Selected: 1 line, 1 word, 3 chars")
+        );
+
+        app.execute_command("workbench.action.toggleWordCount")
+            .unwrap();
+        app.handle_event(AppEvent::Tick);
+        assert_eq!(word_count(&app), None);
     }
 }
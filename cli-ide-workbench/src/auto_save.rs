@@ -0,0 +1,61 @@
+//! Auto-save settings.
+//!
+//! The App listens to [`FocusChanged`](crate::focus::FocusChanged) events and,
+//! depending on the [`AutoSave`] mode, saves a dirty editor as soon as focus
+//! moves away from it.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// When editors are saved automatically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AutoSave {
+    /// Never save automatically.
+    #[default]
+    Off,
+    /// Save a dirty editor with a path when it loses focus.
+    OnFocusChange,
+}
+
+/// Error returned when parsing an unknown auto-save mode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseAutoSaveError(String);
+
+impl fmt::Display for ParseAutoSaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown auto-save mode '{}' (expected 'off' or 'onFocusChange')",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseAutoSaveError {}
+
+impl FromStr for AutoSave {
+    type Err = ParseAutoSaveError;
+
+    /// Parse a setting value: `off` or `onFocusChange`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "off" => Ok(AutoSave::Off),
+            "onFocusChange" => Ok(AutoSave::OnFocusChange),
+            _ => Err(ParseAutoSaveError(value.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_modes() {
+        assert_eq!("off".parse(), Ok(AutoSave::Off));
+        assert_eq!("onFocusChange".parse(), Ok(AutoSave::OnFocusChange));
+
+        let error = "always".parse::<AutoSave>().unwrap_err();
+        assert!(error.to_string().contains("'always'"));
+    }
+}
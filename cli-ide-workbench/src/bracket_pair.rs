@@ -0,0 +1,208 @@
+//! Bracket pair colorization.
+//!
+//! Brackets are colored by how deeply they are nested, so the two brackets
+//! of a pair share a color and the pairs around them differ. The nesting
+//! carries from line to line: [`BracketPairCache`] keeps, for every line,
+//! the brackets open at its start and the brackets it holds, and like
+//! [`HighlightCache`] it only scans a line again when its text, its
+//! strings and comments or the brackets open before it changed. Brackets
+//! inside strings and comments, as the highlighter found them, are left
+//! out.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+
+use cli_ide_base::highlight::{HighlightCache, TokenKind};
+
+/// The bracket pairs colored, as opening and closing characters.
+pub const BRACKET_PAIRS: [(char, char); 3] = [('(', ')'), ('[', ']'), ('{', '}')];
+
+/// A bracket in a line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BracketSpan {
+    /// Byte offset of the bracket in the line.
+    pub index: usize,
+    /// Number of pairs around the bracket's pair, or `None` for a closing
+    /// bracket that closes nothing open.
+    pub depth: Option<usize>,
+}
+
+/// The brackets of one line and what they were found from.
+#[derive(Debug, Clone)]
+struct CachedLine {
+    /// Hash of the line's text.
+    hash: u64,
+    /// Byte ranges of the line's strings and comments.
+    skipped: Vec<Range<usize>>,
+    /// Closing brackets expected at the start of the line, innermost last.
+    start: Vec<char>,
+    /// Closing brackets expected after the line, innermost last.
+    end: Vec<char>,
+    /// The brackets of the line, in order.
+    brackets: Vec<BracketSpan>,
+}
+
+/// The brackets of a document and their depths, kept between passes.
+#[derive(Debug, Default)]
+pub struct BracketPairCache {
+    /// Cached lines, in document order.
+    lines: Vec<CachedLine>,
+    /// Number of lines scanned by the last pass.
+    scanned: usize,
+}
+
+impl BracketPairCache {
+    /// Create an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bring the cache up to date with `text`, leaving out the strings and
+    /// comments in `tokens` if the document is highlighted.
+    ///
+    /// Lines are split on `\n`; a trailing `\r` is not part of the line.
+    pub fn update(&mut self, text: &str, tokens: Option<&HighlightCache>) {
+        let mut open: Vec<char> = Vec::new();
+        let mut scanned = 0;
+        let mut count = 0;
+        for (index, raw_line) in text.split('\n').enumerate() {
+            let line = raw_line.strip_suffix('\r').unwrap_or(raw_line);
+            let hash = hash_line(line);
+            let skipped: Vec<Range<usize>> = tokens
+                .map(|tokens| tokens.line(index))
+                .unwrap_or_default()
+                .iter()
+                .filter(|span| matches!(span.kind, TokenKind::String | TokenKind::Comment))
+                .map(|span| span.range.clone())
+                .collect();
+            count += 1;
+            if let Some(cached) = self.lines.get(index) {
+                if cached.hash == hash && cached.skipped == skipped && cached.start == open {
+                    open.clone_from(&cached.end);
+                    continue;
+                }
+            }
+            let start = open.clone();
+            let brackets = scan_line(line, &skipped, &mut open);
+            scanned += 1;
+            let cached = CachedLine {
+                hash,
+                skipped,
+                start,
+                end: open.clone(),
+                brackets,
+            };
+            if index < self.lines.len() {
+                self.lines[index] = cached;
+            } else {
+                self.lines.push(cached);
+            }
+        }
+        self.lines.truncate(count);
+        self.scanned = scanned;
+    }
+
+    /// Get the brackets of a line as of the last pass; empty past the end.
+    pub fn line(&self, index: usize) -> &[BracketSpan] {
+        self.lines
+            .get(index)
+            .map_or(&[], |line| line.brackets.as_slice())
+    }
+
+    /// Number of lines the last pass scanned rather than reused.
+    pub fn last_scanned(&self) -> usize {
+        self.scanned
+    }
+
+    /// Forget every line.
+    pub fn clear(&mut self) {
+        self.lines.clear();
+        self.scanned = 0;
+    }
+}
+
+/// Find the brackets of `line` outside the `skipped` ranges, updating the
+/// closing brackets expected in `open`.
+fn scan_line(line: &str, skipped: &[Range<usize>], open: &mut Vec<char>) -> Vec<BracketSpan> {
+    let mut brackets = Vec::new();
+    for (index, c) in line.char_indices() {
+        if skipped.iter().any(|range| range.contains(&index)) {
+            continue;
+        }
+        if let Some(&(_, close)) = BRACKET_PAIRS.iter().find(|(opening, _)| *opening == c) {
+            brackets.push(BracketSpan {
+                index,
+                depth: Some(open.len()),
+            });
+            open.push(close);
+        } else if BRACKET_PAIRS.iter().any(|&(_, close)| close == c) {
+            let depth = if open.last() == Some(&c) {
+                open.pop();
+                Some(open.len())
+            } else {
+                None
+            };
+            brackets.push(BracketSpan { index, depth });
+        }
+    }
+    brackets
+}
+
+/// Hash a line's text for change detection.
+fn hash_line(line: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    line.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cli_ide_base::highlight::LexicalHighlighter;
+
+    fn depths(cache: &BracketPairCache, line: usize) -> Vec<(usize, Option<usize>)> {
+        cache
+            .line(line)
+            .iter()
+            .map(|bracket| (bracket.index, bracket.depth))
+            .collect()
+    }
+
+    #[test]
+    fn test_depths_carry_across_lines_and_skip_strings() {
+        let text = "fn f(a: [u8]) {\n    g(\"(\", b)];\n}";
+        let mut tokens = HighlightCache::new();
+        tokens.highlight(&LexicalHighlighter::rust(), text);
+        let mut cache = BracketPairCache::new();
+        cache.update(text, Some(&tokens));
+        assert_eq!(
+            depths(&cache, 0),
+            vec![
+                (4, Some(0)),
+                (8, Some(1)),
+                (11, Some(1)),
+                (12, Some(0)),
+                (14, Some(0))
+            ]
+        );
+        assert_eq!(
+            depths(&cache, 1),
+            vec![(5, Some(1)), (12, Some(1)), (13, None)],
+            "the bracket in the string is skipped and `]` closes nothing"
+        );
+        assert_eq!(depths(&cache, 2), vec![(0, Some(0))]);
+        assert_eq!(cache.last_scanned(), 3);
+
+        // An edit rescans the edited line and the lines its brackets shift
+        let text = "fn f(a: [u8]) {\n    g(\"(\", b)];\n}\nx";
+        tokens.highlight(&LexicalHighlighter::rust(), text);
+        cache.update(text, Some(&tokens));
+        assert_eq!(cache.last_scanned(), 1);
+        let text = "fn f(a: [u8]) { {\n    g(\"(\", b)];\n}\nx";
+        tokens.highlight(&LexicalHighlighter::rust(), text);
+        cache.update(text, Some(&tokens));
+        assert_eq!(cache.last_scanned(), 4);
+        assert_eq!(depths(&cache, 2), vec![(0, Some(1))]);
+    }
+}
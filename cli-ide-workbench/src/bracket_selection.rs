@@ -0,0 +1,295 @@
+//! Bracket selection expansion.
+//!
+//! Expanding a selection grows it to the smallest enclosing range the
+//! [`SelectionRangeProvider`] knows of; shrinking walks back through the
+//! previous expansions. [`BracketSelectionProvider`] knows words, bracket
+//! pairs and lines (word → inside of the brackets → brackets → lines →
+//! document), leaving out brackets inside strings and comments as the
+//! highlighter found them. It does not parse the language, so it has no
+//! expression or statement steps.
+//!
+//! All ranges are byte offsets into the buffer text.
+
+use std::ops::Range;
+
+use cli_ide_base::highlight::{HighlightCache, TokenKind};
+
+/// Computes the next larger range enclosing a selection.
+pub trait SelectionRangeProvider {
+    /// Return the smallest range that strictly contains `selection`, or
+    /// `None` if the selection already covers the whole document.
+    ///
+    /// `tokens` are the highlighted spans of `text`, if it is highlighted.
+    fn expand(
+        &self,
+        text: &str,
+        tokens: Option<&HighlightCache>,
+        selection: Range<usize>,
+    ) -> Option<Range<usize>>;
+}
+
+/// A language-agnostic provider based on words, brackets and lines.
+///
+/// Candidate ranges are the word under the selection, the inside and
+/// outside of every enclosing `()`, `[]` or `{}` pair outside strings and
+/// comments, the trimmed lines spanned by the selection, and the whole
+/// document.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BracketSelectionProvider;
+
+impl BracketSelectionProvider {
+    /// Range of the identifier-like word touching `offset`, if any.
+    fn word_at(text: &str, offset: usize) -> Option<Range<usize>> {
+        let is_word = |c: char| c.is_alphanumeric() || c == '_';
+        let start = text[..offset]
+            .char_indices()
+            .rev()
+            .take_while(|&(_, c)| is_word(c))
+            .last()
+            .map_or(offset, |(i, _)| i);
+        let end = text[offset..]
+            .char_indices()
+            .take_while(|&(_, c)| is_word(c))
+            .last()
+            .map_or(offset, |(i, c)| offset + i + c.len_utf8());
+        (start < end).then_some(start..end)
+    }
+
+    /// Byte ranges of the strings and comments in `tokens`, in order.
+    fn skipped(text: &str, tokens: &HighlightCache) -> Vec<Range<usize>> {
+        let mut skipped = Vec::new();
+        let mut start = 0;
+        for (index, line) in text.split('\n').enumerate() {
+            skipped.extend(
+                tokens
+                    .line(index)
+                    .iter()
+                    .filter(|span| matches!(span.kind, TokenKind::String | TokenKind::Comment))
+                    .map(|span| start + span.range.start..start + span.range.end),
+            );
+            start += line.len() + 1;
+        }
+        skipped
+    }
+
+    /// All matched bracket pairs outside the `skipped` ranges as
+    /// `(open, close)` byte offsets.
+    fn bracket_pairs(text: &str, skipped: &[Range<usize>]) -> Vec<(usize, usize)> {
+        let mut stack: Vec<(char, usize)> = Vec::new();
+        let mut pairs = Vec::new();
+        for (i, c) in text.char_indices() {
+            if skipped.iter().any(|range| range.contains(&i)) {
+                continue;
+            }
+            match c {
+                '(' | '[' | '{' => stack.push((c, i)),
+                ')' | ']' | '}' => {
+                    let open = match c {
+                        ')' => '(',
+                        ']' => '[',
+                        _ => '{',
+                    };
+                    if let Some(pos) = stack.iter().rposition(|&(o, _)| o == open) {
+                        let (_, start) = stack[pos];
+                        stack.truncate(pos);
+                        pairs.push((start, i));
+                    }
+                }
+                _ => {}
+            }
+        }
+        pairs
+    }
+
+    /// The non-whitespace part of the lines spanned by `selection`.
+    fn trimmed_lines(text: &str, selection: &Range<usize>) -> Range<usize> {
+        let start = text[..selection.start].rfind('\n').map_or(0, |i| i + 1);
+        let end = text[selection.end..]
+            .find('\n')
+            .map_or(text.len(), |i| selection.end + i);
+        let line = &text[start..end];
+        let leading = line.len() - line.trim_start().len();
+        let trailing = line.len() - line.trim_end().len();
+        if leading + trailing >= line.len() {
+            return start..end;
+        }
+        (start + leading)..(end - trailing)
+    }
+}
+
+impl SelectionRangeProvider for BracketSelectionProvider {
+    fn expand(
+        &self,
+        text: &str,
+        tokens: Option<&HighlightCache>,
+        selection: Range<usize>,
+    ) -> Option<Range<usize>> {
+        let mut candidates = Vec::new();
+
+        if let Some(word) = Self::word_at(text, selection.start) {
+            candidates.push(word);
+        }
+        let skipped = tokens.map_or_else(Vec::new, |tokens| Self::skipped(text, tokens));
+        for (open, close) in Self::bracket_pairs(text, &skipped) {
+            candidates.push((open + 1)..close);
+            candidates.push(open..(close + 1));
+        }
+        candidates.push(Self::trimmed_lines(text, &selection));
+        candidates.push(0..text.len());
+
+        candidates
+            .into_iter()
+            .filter(|c| c.start <= selection.start && c.end >= selection.end && *c != selection)
+            .min_by_key(|c| c.len())
+    }
+}
+
+/// Tracks expansion history so that shrinking retraces previous steps.
+#[derive(Debug, Clone, Default)]
+pub struct SelectionExpansion {
+    /// Selections that were active before each expansion, oldest first.
+    history: Vec<Range<usize>>,
+}
+
+impl SelectionExpansion {
+    /// Create an empty expansion history.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Expand `current` using `provider`, remembering it for [`shrink`](Self::shrink).
+    ///
+    /// Returns `current` unchanged when it cannot grow further.
+    pub fn expand(
+        &mut self,
+        provider: &dyn SelectionRangeProvider,
+        text: &str,
+        tokens: Option<&HighlightCache>,
+        current: Range<usize>,
+    ) -> Range<usize> {
+        match provider.expand(text, tokens, current.clone()) {
+            Some(next) => {
+                self.history.push(current);
+                next
+            }
+            None => current,
+        }
+    }
+
+    /// Return the selection that was active before the last expansion.
+    pub fn shrink(&mut self) -> Option<Range<usize>> {
+        self.history.pop()
+    }
+
+    /// Forget the expansion history (e.g. after the cursor moves).
+    pub fn reset(&mut self) {
+        self.history.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand_all(text: &str, start: usize) -> Vec<&str> {
+        let provider = BracketSelectionProvider;
+        let mut selection = start..start;
+        let mut steps = Vec::new();
+        while let Some(next) = provider.expand(text, None, selection.clone()) {
+            steps.push(&text[next.clone()]);
+            selection = next;
+        }
+        steps
+    }
+
+    #[test]
+    fn test_expands_word_then_brackets_then_line() {
+        let text = "fn main() {\n    let x = foo(bar, baz);\n}";
+        let offset = text.find("bar").unwrap() + 1;
+
+        let steps = expand_all(text, offset);
+
+        assert_eq!(steps[0], "bar");
+        assert_eq!(steps[1], "bar, baz");
+        assert_eq!(steps[2], "(bar, baz)");
+        assert_eq!(steps[3], "let x = foo(bar, baz);");
+        assert_eq!(steps[4], "\n    let x = foo(bar, baz);\n");
+        assert_eq!(steps[5], "{\n    let x = foo(bar, baz);\n}");
+        assert_eq!(*steps.last().unwrap(), text);
+    }
+
+    #[test]
+    fn test_whole_document_does_not_expand() {
+        let provider = BracketSelectionProvider;
+        let text = "abc";
+        assert_eq!(provider.expand(text, None, 0..3), None);
+    }
+
+    #[test]
+    fn test_cursor_outside_word_selects_line() {
+        let provider = BracketSelectionProvider;
+        let text = "  a  b  ";
+        // Offset 4 sits between the spaces around 'b'
+        assert_eq!(provider.expand(text, None, 4..4), Some(2..6));
+    }
+
+    #[test]
+    fn test_mismatched_brackets_are_ignored() {
+        let provider = BracketSelectionProvider;
+        let text = "(a]";
+        assert_eq!(provider.expand(text, None, 1..2), Some(0..3));
+    }
+
+    #[test]
+    fn test_brackets_in_strings_and_comments_are_skipped() {
+        use cli_ide_base::highlight::LexicalHighlighter;
+
+        let provider = BracketSelectionProvider;
+        let text = "f(a, \")\" /* ) */, b);";
+        let mut tokens = HighlightCache::new();
+        tokens.highlight(&LexicalHighlighter::rust(), text);
+        let a = text.find('a').unwrap();
+
+        let inside = provider.expand(text, Some(&tokens), a..a + 1).unwrap();
+        assert_eq!(&text[inside], "a, \")\" /* ) */, b");
+
+        // Without tokens the `)` in the string closes the pair
+        let inside = provider.expand(text, None, a..a + 1).unwrap();
+        assert_eq!(&text[inside], "a, \"");
+    }
+
+    #[test]
+    fn test_shrink_retraces_expansion() {
+        let provider = BracketSelectionProvider;
+        let text = "f(a)";
+        let mut expansion = SelectionExpansion::new();
+
+        let first = expansion.expand(&provider, text, None, 2..2);
+        assert_eq!(first, 2..3);
+        let second = expansion.expand(&provider, text, None, first.clone());
+        assert_eq!(second, 1..4);
+
+        assert_eq!(expansion.shrink(), Some(first));
+        assert_eq!(expansion.shrink(), Some(2..2));
+        assert_eq!(expansion.shrink(), None);
+    }
+
+    #[test]
+    fn test_expand_at_limit_keeps_selection() {
+        let provider = BracketSelectionProvider;
+        let mut expansion = SelectionExpansion::new();
+
+        assert_eq!(expansion.expand(&provider, "ab", None, 0..2), 0..2);
+        assert_eq!(expansion.shrink(), None);
+    }
+
+    #[test]
+    fn test_reset_clears_history() {
+        let provider = BracketSelectionProvider;
+        let mut expansion = SelectionExpansion::new();
+        expansion.expand(&provider, "word", None, 1..1);
+
+        expansion.reset();
+        assert_eq!(expansion.shrink(), None);
+    }
+}
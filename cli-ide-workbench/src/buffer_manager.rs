@@ -0,0 +1,1073 @@
+//! Open text buffers.
+//!
+//! A [`TextBuffer`] holds the contents of one document. Editors hold it
+//! through a [`SharedBuffer`], so several editors showing the same file see
+//! the same text and dirty state. The [`BufferManager`] tracks every open
+//! file: opening a path that is already open returns the existing buffer,
+//! and [`BufferEvent`]s announce buffers being opened, closed and saved so
+//! components such as language clients and tab bars can follow along.
+//!
+//! Buffers are identified by a [`BufferUri`]. Besides files, the manager
+//! holds untitled scratch buffers (`untitled:Untitled-1`), which have no
+//! path until they are first saved with [`BufferManager::save_as`], and
+//! read-only revisions of files at a git commit (`git:a.rs@1a2b3c4`).
+//!
+//! Edits made through [`TextBuffer::edit`] are recorded in the buffer's
+//! undo history, so every editor showing the buffer undoes the same steps.
+//! The history is an [`UndoTree`]: editing after undoing starts a new
+//! branch, and [`TextBuffer::go_to_state`] returns to any earlier state.
+//! Every change to a buffer's text is announced as a [`TextChange`] on
+//! [`TextBuffer::on_did_change`], which editors showing the same buffer
+//! use to keep their cursors in place when another view edits it.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use cli_ide_base::Event;
+
+use crate::code_action::{self, EditError, TextDocuments, TextEdit};
+use crate::undo_tree::{UndoMove, UndoTree, UndoTreeRow};
+
+/// Characters of inserted or deleted text an undo tree row shows.
+const MAX_CHANGE_LABEL: usize = 16;
+
+/// A change recorded in a buffer's undo history.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct UndoStep {
+    /// Edits making the change, in offsets of the text before it.
+    redo: Vec<TextEdit>,
+    /// Edits reverting the change, in offsets of the text after it.
+    undo: Vec<TextEdit>,
+}
+
+/// A change to the text of a buffer: `range` of the text before the change
+/// was replaced with `new_len` bytes.
+///
+/// The changes of one edit are announced in order, each in offsets of the
+/// text the previous ones left.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextChange {
+    /// Replaced byte range.
+    pub range: Range<usize>,
+    /// Length of the replacement text in bytes.
+    pub new_len: usize,
+}
+
+impl TextChange {
+    /// Map `offset` in the text before the change to the text after it.
+    ///
+    /// Offsets before the change stay put and offsets after it move with
+    /// the text following it; offsets inside the replaced range stay put
+    /// unless that is past the replacement, in which case they move to its
+    /// end.
+    pub fn shift(&self, offset: usize) -> usize {
+        if offset >= self.range.end {
+            offset - self.range.len() + self.new_len
+        } else if offset > self.range.start {
+            offset.min(self.range.start + self.new_len)
+        } else {
+            offset
+        }
+    }
+}
+
+/// The contents of a document and its save state.
+#[derive(Default)]
+pub struct TextBuffer {
+    /// Contents of the buffer.
+    text: String,
+    /// File the buffer is saved to, if any.
+    path: Option<PathBuf>,
+    /// Whether the text has changed since it was loaded or saved.
+    dirty: bool,
+    /// Name shown for a buffer without a path, e.g. `a.rs @ 1a2b3c4`.
+    name: Option<String>,
+    /// Whether the buffer refuses to be saved.
+    read_only: bool,
+    /// The states the text has been in and the changes between them.
+    history: UndoTree<UndoStep>,
+    /// Fired for every change to the text.
+    on_did_change: Event<TextChange>,
+}
+
+impl fmt::Debug for TextBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TextBuffer")
+            .field("path", &self.path)
+            .field("name", &self.name)
+            .field("dirty", &self.dirty)
+            .field("read_only", &self.read_only)
+            .field("len", &self.text.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl TextBuffer {
+    /// Create an unsaved buffer holding `text`.
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Create a read-only buffer holding `text`, shown as `name`.
+    pub fn read_only(text: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            name: Some(name.into()),
+            read_only: true,
+            ..Self::default()
+        }
+    }
+
+    /// Load a clean buffer from the file at `path`.
+    pub fn from_file(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let text = fs::read_to_string(&path)?;
+        Ok(Self {
+            text,
+            path: Some(path),
+            ..Self::default()
+        })
+    }
+
+    /// Get the contents.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Replace the contents, marking the buffer dirty if they changed.
+    ///
+    /// Replacing the contents with different text clears the undo history.
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        let text = text.into();
+        if text != self.text {
+            self.dirty = true;
+            self.clear_history();
+            self.replace_all(text);
+        }
+    }
+
+    /// Replace the whole text and announce it.
+    fn replace_all(&mut self, text: String) {
+        let change = TextChange {
+            range: 0..self.text.len(),
+            new_len: text.len(),
+        };
+        self.text = text;
+        self.on_did_change.emit(change);
+    }
+
+    /// Get the event fired for every change to the text, e.g. by another
+    /// editor showing the buffer.
+    pub fn on_did_change(&self) -> &Event<TextChange> {
+        &self.on_did_change
+    }
+
+    /// Apply `edits` as one step of the undo history and mark the buffer
+    /// dirty.
+    ///
+    /// Edits refer to offsets in the current text and must not overlap.
+    /// Returns the ranges the replacement texts occupy afterwards, in
+    /// order of their position in the text.
+    pub fn edit(&mut self, edits: &[TextEdit]) -> Result<Vec<Range<usize>>, EditError> {
+        let mut redo = edits.to_vec();
+        redo.sort_by_key(|edit| (edit.range.start, edit.range.end));
+        let undo = self.apply(&redo)?;
+        let ranges = undo.iter().map(|edit| edit.range.clone()).collect();
+        self.history.push(UndoStep { redo, undo });
+        Ok(ranges)
+    }
+
+    /// Check whether there is a change to undo.
+    pub fn can_undo(&self) -> bool {
+        self.history.can_undo()
+    }
+
+    /// Check whether there is an undone change to redo.
+    pub fn can_redo(&self) -> bool {
+        self.history.can_redo()
+    }
+
+    /// Revert the most recent change made with [`edit`](TextBuffer::edit).
+    ///
+    /// Returns the range of the first restored text, or `None` if there is
+    /// nothing to undo.
+    pub fn undo(&mut self) -> Option<Range<usize>> {
+        let edits = self.history.undo()?.undo.clone();
+        self.replay(&edits)
+    }
+
+    /// Make the most recently undone change again, on the branch last
+    /// visited.
+    ///
+    /// Returns the range of the first replacement text, or `None` if there
+    /// is nothing to redo.
+    pub fn redo(&mut self) -> Option<Range<usize>> {
+        let edits = self.history.redo()?.redo.clone();
+        self.replay(&edits)
+    }
+
+    /// Get the state of the undo history the text is in; the text as
+    /// loaded is state 0.
+    pub fn undo_state(&self) -> usize {
+        self.history.current()
+    }
+
+    /// List the states of the undo history as an outline, each labeled
+    /// with the change leading to it.
+    pub fn undo_tree(&self) -> Vec<UndoTreeRow> {
+        self.history.rows(describe_step)
+    }
+
+    /// Bring the text to `state` of the undo history, undoing and redoing
+    /// the changes on the way, across branches if need be.
+    ///
+    /// Returns the range of the first text the last change restored, or
+    /// `None` if the text is already in `state` or there is no such state.
+    pub fn go_to_state(&mut self, state: usize) -> Option<Range<usize>> {
+        let mut restored = None;
+        loop {
+            let edits = match self.history.step_towards(state)? {
+                UndoMove::Undo(step) => step.undo.clone(),
+                UndoMove::Redo(step) => step.redo.clone(),
+            };
+            restored = self.replay(&edits).or(restored);
+            if self.history.current() == state {
+                return restored;
+            }
+        }
+    }
+
+    /// Apply recorded `edits`, returning the range of the first restored
+    /// text.
+    fn replay(&mut self, edits: &[TextEdit]) -> Option<Range<usize>> {
+        let restored = self
+            .apply(edits)
+            .expect("recorded edits apply to the text they were recorded for");
+        restored.first().map(|edit| edit.range.clone())
+    }
+
+    /// Apply sorted, non-overlapping `edits` and mark the buffer dirty,
+    /// returning the edits that revert them.
+    fn apply(&mut self, edits: &[TextEdit]) -> Result<Vec<TextEdit>, EditError> {
+        let path = self.path.clone().unwrap_or_default();
+        let text = code_action::apply_edits(&path, &self.text, edits)?;
+        let mut inserted = 0;
+        let mut removed = 0;
+        let mut changes = Vec::with_capacity(edits.len());
+        let inverse = edits
+            .iter()
+            .map(|edit| {
+                let start = edit.range.start + inserted - removed;
+                inserted += edit.new_text.len();
+                removed += edit.range.len();
+                changes.push(TextChange {
+                    range: start..start + edit.range.len(),
+                    new_len: edit.new_text.len(),
+                });
+                TextEdit::new(
+                    start..start + edit.new_text.len(),
+                    &self.text[edit.range.clone()],
+                )
+            })
+            .collect();
+        self.text = text;
+        self.dirty = true;
+        for change in changes {
+            self.on_did_change.emit(change);
+        }
+        Ok(inverse)
+    }
+
+    /// Forget the undo and redo history.
+    fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    /// Get the file the buffer is saved to, if any.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Set the file the buffer is saved to.
+    pub fn set_path(&mut self, path: Option<PathBuf>) {
+        self.path = path;
+    }
+
+    /// Get the name shown for the buffer when it has no path, if any.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Check whether the buffer refuses to be saved.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Check whether the buffer has unsaved changes.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Read the contents from the buffer's path again and mark it clean.
+    ///
+    /// Fails with [`io::ErrorKind::NotFound`] if the buffer has no path.
+    pub fn reload(&mut self) -> io::Result<()> {
+        let path = self
+            .path
+            .as_ref()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "the buffer has no path"))?;
+        let text = fs::read_to_string(path)?;
+        if text != self.text {
+            self.replace_all(text);
+        }
+        self.dirty = false;
+        self.clear_history();
+        Ok(())
+    }
+
+    /// Write the buffer to its path and mark it clean.
+    ///
+    /// Fails with [`io::ErrorKind::PermissionDenied`] if the buffer is
+    /// read-only and [`io::ErrorKind::NotFound`] if it has no path.
+    pub fn save(&mut self) -> io::Result<()> {
+        if self.read_only {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "the buffer is read-only",
+            ));
+        }
+        let path = self
+            .path
+            .as_ref()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "the buffer has no path"))?;
+        fs::write(path, &self.text)?;
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+/// A buffer shared between the [`BufferManager`] and the editors showing it.
+pub type SharedBuffer = Rc<RefCell<TextBuffer>>;
+
+/// Prefix of the names given to untitled buffers.
+const UNTITLED_PREFIX: &str = "Untitled-";
+
+/// Identifies a managed buffer.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BufferUri {
+    /// A file, by normalized path.
+    File(PathBuf),
+    /// An untitled scratch buffer, by name, e.g. `Untitled-1`.
+    Untitled(String),
+    /// A file as it was at a git revision, read-only.
+    Revision {
+        /// The revision, e.g. an abbreviated commit name.
+        revision: String,
+        /// Absolute path of the file in the work tree.
+        path: PathBuf,
+    },
+}
+
+impl BufferUri {
+    /// Get the file path of a file buffer.
+    ///
+    /// Untitled buffers and revisions have none: saving must not write to
+    /// the work tree file a revision was read from.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            BufferUri::File(path) => Some(path),
+            BufferUri::Untitled(_) | BufferUri::Revision { .. } => None,
+        }
+    }
+
+    /// Check whether this is an untitled buffer.
+    pub fn is_untitled(&self) -> bool {
+        matches!(self, BufferUri::Untitled(_))
+    }
+}
+
+impl fmt::Display for BufferUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BufferUri::File(path) => write!(f, "file://{}", path.display()),
+            BufferUri::Untitled(name) => write!(f, "untitled:{name}"),
+            BufferUri::Revision { revision, path } => {
+                write!(f, "git:{}@{revision}", path.display())
+            }
+        }
+    }
+}
+
+/// Lifecycle events of managed buffers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BufferEvent {
+    /// A buffer was opened or created.
+    Opened(BufferUri),
+    /// A buffer was closed.
+    Closed(BufferUri),
+    /// A buffer was written to disk.
+    Saved(BufferUri),
+    /// A buffer got a new identity, e.g. an untitled buffer saved to a file.
+    Renamed {
+        /// The previous identity.
+        from: BufferUri,
+        /// The new identity.
+        to: BufferUri,
+    },
+}
+
+/// Tracks the open buffers, one shared buffer per file.
+#[derive(Default)]
+pub struct BufferManager {
+    /// Open buffers keyed by URI; file paths are normalized.
+    buffers: BTreeMap<BufferUri, SharedBuffer>,
+    /// Fired when a buffer is opened, closed, saved or renamed.
+    on_buffer_event: Event<BufferEvent>,
+}
+
+impl BufferManager {
+    /// Create a manager with no open buffers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open the file at `path`, or return its buffer if it is already open.
+    ///
+    /// Paths are compared after canonicalization, so `./a.rs` and `a.rs`
+    /// share a buffer. Fires [`BufferEvent::Opened`] when the file is loaded.
+    pub fn open(&mut self, path: impl AsRef<Path>) -> io::Result<SharedBuffer> {
+        let uri = file_uri(path.as_ref());
+        if let Some(buffer) = self.buffers.get(&uri) {
+            return Ok(Rc::clone(buffer));
+        }
+        let path = uri.path().expect("file URIs have a path");
+        let buffer = Rc::new(RefCell::new(TextBuffer::from_file(path)?));
+        self.buffers.insert(uri.clone(), Rc::clone(&buffer));
+        self.on_buffer_event.emit(BufferEvent::Opened(uri));
+        Ok(buffer)
+    }
+
+    /// Create an empty untitled buffer and fire [`BufferEvent::Opened`].
+    ///
+    /// It is named `Untitled-N` with the lowest `N` not in use.
+    pub fn new_untitled(&mut self) -> (BufferUri, SharedBuffer) {
+        self.insert_untitled(TextBuffer::default())
+    }
+
+    /// Recreate an untitled buffer holding `text`, e.g. from a restored
+    /// session, and fire [`BufferEvent::Opened`].
+    ///
+    /// The buffer keeps `name` unless it is already in use, in which case it
+    /// gets a fresh name. Non-empty text is restored as unsaved changes.
+    pub fn restore_untitled(
+        &mut self,
+        name: &str,
+        text: impl Into<String>,
+    ) -> (BufferUri, SharedBuffer) {
+        let mut buffer = TextBuffer::default();
+        buffer.set_text(text);
+        let uri = BufferUri::Untitled(name.to_string());
+        if self.buffers.contains_key(&uri) {
+            return self.insert_untitled(buffer);
+        }
+        let buffer = Rc::new(RefCell::new(buffer));
+        self.buffers.insert(uri.clone(), Rc::clone(&buffer));
+        self.on_buffer_event.emit(BufferEvent::Opened(uri.clone()));
+        (uri, buffer)
+    }
+
+    /// Open `text`, the contents of the file at `path` at `revision`, as a
+    /// read-only buffer, or return the buffer if that revision is open.
+    ///
+    /// The buffer is named after the file and the revision. Fires
+    /// [`BufferEvent::Opened`] when the buffer is created.
+    pub fn open_revision(
+        &mut self,
+        revision: &str,
+        path: &Path,
+        text: impl Into<String>,
+    ) -> SharedBuffer {
+        let uri = BufferUri::Revision {
+            revision: revision.to_string(),
+            path: path.to_path_buf(),
+        };
+        if let Some(buffer) = self.buffers.get(&uri) {
+            return Rc::clone(buffer);
+        }
+        let file_name = path.file_name().unwrap_or(path.as_os_str());
+        let name = format!("{} @ {revision}", file_name.to_string_lossy());
+        let buffer = Rc::new(RefCell::new(TextBuffer::read_only(text, name)));
+        self.buffers.insert(uri.clone(), Rc::clone(&buffer));
+        self.on_buffer_event.emit(BufferEvent::Opened(uri));
+        buffer
+    }
+
+    /// Track `buffer` under the lowest free untitled name.
+    fn insert_untitled(&mut self, buffer: TextBuffer) -> (BufferUri, SharedBuffer) {
+        let uri = (1..)
+            .map(|n| BufferUri::Untitled(format!("{UNTITLED_PREFIX}{n}")))
+            .find(|uri| !self.buffers.contains_key(uri))
+            .expect("some untitled name is free");
+        let buffer = Rc::new(RefCell::new(buffer));
+        self.buffers.insert(uri.clone(), Rc::clone(&buffer));
+        self.on_buffer_event.emit(BufferEvent::Opened(uri.clone()));
+        (uri, buffer)
+    }
+
+    /// Get the buffer of an open file.
+    pub fn get(&self, path: impl AsRef<Path>) -> Option<SharedBuffer> {
+        self.get_uri(&file_uri(path.as_ref()))
+    }
+
+    /// Get an open buffer by URI.
+    pub fn get_uri(&self, uri: &BufferUri) -> Option<SharedBuffer> {
+        self.buffers.get(uri).cloned()
+    }
+
+    /// Find the URI under which `buffer` is tracked, if it is.
+    pub fn uri_of(&self, buffer: &SharedBuffer) -> Option<BufferUri> {
+        self.buffers
+            .iter()
+            .find(|(_, tracked)| Rc::ptr_eq(tracked, buffer))
+            .map(|(uri, _)| uri.clone())
+    }
+
+    /// Check whether a file is open.
+    pub fn contains(&self, path: impl AsRef<Path>) -> bool {
+        self.buffers.contains_key(&file_uri(path.as_ref()))
+    }
+
+    /// Get the paths of the open files, in path order.
+    ///
+    /// Untitled buffers are not included; see [`BufferManager::uris`].
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.buffers
+            .keys()
+            .filter_map(|uri| uri.path().map(Path::to_path_buf))
+            .collect()
+    }
+
+    /// Get the URIs of every open buffer, files first in path order, then
+    /// untitled buffers by name, then revisions.
+    pub fn uris(&self) -> Vec<BufferUri> {
+        self.buffers.keys().cloned().collect()
+    }
+
+    /// Number of open buffers, including untitled ones.
+    pub fn len(&self) -> usize {
+        self.buffers.len()
+    }
+
+    /// Check whether no buffer is open.
+    pub fn is_empty(&self) -> bool {
+        self.buffers.is_empty()
+    }
+
+    /// Save an open file and fire [`BufferEvent::Saved`].
+    ///
+    /// Fails with [`io::ErrorKind::NotFound`] if the file is not open.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let uri = file_uri(path.as_ref());
+        let buffer = self
+            .buffers
+            .get(&uri)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "the file is not open"))?;
+        buffer.borrow_mut().save()?;
+        self.on_buffer_event.emit(BufferEvent::Saved(uri));
+        Ok(())
+    }
+
+    /// Write `buffer` to `path` and track it as that file from now on.
+    ///
+    /// This is how untitled buffers get their first path. A buffer that was
+    /// tracked under another URI fires [`BufferEvent::Renamed`]; an untracked
+    /// one fires [`BufferEvent::Opened`]. Either way [`BufferEvent::Saved`]
+    /// follows. Fails with [`io::ErrorKind::AlreadyExists`] if another buffer
+    /// already holds `path` and [`io::ErrorKind::PermissionDenied`] if the
+    /// buffer is read-only, and leaves the buffer unchanged if writing fails.
+    pub fn save_as(
+        &mut self,
+        buffer: &SharedBuffer,
+        path: impl AsRef<Path>,
+    ) -> io::Result<BufferUri> {
+        let path = path.as_ref();
+        if buffer.borrow().is_read_only() {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "the buffer is read-only",
+            ));
+        }
+        let previous = self.uri_of(buffer);
+        if let Some(existing) = self.get(path) {
+            if !Rc::ptr_eq(&existing, buffer) {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    "the file is open in another buffer",
+                ));
+            }
+        }
+        fs::write(path, buffer.borrow().text())?;
+        let uri = file_uri(path);
+        {
+            let mut buffer = buffer.borrow_mut();
+            buffer.set_path(uri.path().map(Path::to_path_buf));
+            buffer.dirty = false;
+        }
+        match previous {
+            Some(previous) if previous == uri => {}
+            Some(previous) => {
+                self.buffers.remove(&previous);
+                self.buffers.insert(uri.clone(), Rc::clone(buffer));
+                self.on_buffer_event.emit(BufferEvent::Renamed {
+                    from: previous,
+                    to: uri.clone(),
+                });
+            }
+            None => {
+                self.buffers.insert(uri.clone(), Rc::clone(buffer));
+                self.on_buffer_event.emit(BufferEvent::Opened(uri.clone()));
+            }
+        }
+        self.on_buffer_event.emit(BufferEvent::Saved(uri.clone()));
+        Ok(uri)
+    }
+
+    /// Follow a file or folder that moved from `from` to `to` on disk.
+    ///
+    /// Every open file at or below `from` is re-keyed to the matching path
+    /// below `to` and fires [`BufferEvent::Renamed`]; editors showing it pick
+    /// up the new path. Returns the new URIs.
+    pub fn rename_path(&mut self, from: &Path, to: &Path) -> Vec<BufferUri> {
+        let to = match file_uri(to) {
+            BufferUri::File(path) => path,
+            _ => unreachable!("file_uri returns file URIs"),
+        };
+        let moved: Vec<(BufferUri, PathBuf)> = self
+            .buffers
+            .keys()
+            .filter_map(|uri| {
+                let suffix = uri.path()?.strip_prefix(from).ok()?;
+                let target = if suffix.as_os_str().is_empty() {
+                    to.clone()
+                } else {
+                    to.join(suffix)
+                };
+                Some((uri.clone(), target))
+            })
+            .collect();
+        let mut renamed = Vec::new();
+        for (previous, path) in moved {
+            let buffer = self.buffers.remove(&previous).expect("key was just listed");
+            buffer.borrow_mut().set_path(Some(path.clone()));
+            let uri = BufferUri::File(path);
+            self.buffers.insert(uri.clone(), buffer);
+            self.on_buffer_event.emit(BufferEvent::Renamed {
+                from: previous,
+                to: uri.clone(),
+            });
+            renamed.push(uri);
+        }
+        renamed
+    }
+
+    /// Close an open file and fire [`BufferEvent::Closed`].
+    ///
+    /// Editors still holding the buffer keep their copy, but it is no longer
+    /// tracked. Returns `false` if the file was not open.
+    pub fn close(&mut self, path: impl AsRef<Path>) -> bool {
+        self.close_uri(&file_uri(path.as_ref()))
+    }
+
+    /// Close an open buffer by URI and fire [`BufferEvent::Closed`].
+    ///
+    /// Returns `false` if the buffer was not open.
+    pub fn close_uri(&mut self, uri: &BufferUri) -> bool {
+        if self.buffers.remove(uri).is_none() {
+            return false;
+        }
+        self.on_buffer_event.emit(BufferEvent::Closed(uri.clone()));
+        true
+    }
+
+    /// Close every buffer no editor holds any more, returning their URIs.
+    ///
+    /// Buffers with unsaved changes stay open so their edits are not lost;
+    /// this keeps dirty untitled buffers around for session restore.
+    pub fn close_unused(&mut self) -> Vec<BufferUri> {
+        let unused: Vec<BufferUri> = self
+            .buffers
+            .iter()
+            .filter(|(_, buffer)| Rc::strong_count(buffer) == 1 && !buffer.borrow().is_dirty())
+            .map(|(uri, _)| uri.clone())
+            .collect();
+        for uri in &unused {
+            self.close_uri(uri);
+        }
+        unused
+    }
+
+    /// Event fired when a buffer is opened, closed, saved or renamed.
+    pub fn on_buffer_event(&self) -> &Event<BufferEvent> {
+        &self.on_buffer_event
+    }
+}
+
+impl TextDocuments for BufferManager {
+    fn text(&self, path: &Path) -> Option<String> {
+        self.get(path)
+            .map(|buffer| buffer.borrow().text().to_string())
+    }
+
+    fn set_text(&mut self, path: &Path, text: String) -> Result<(), EditError> {
+        let buffer = self
+            .get(path)
+            .ok_or_else(|| EditError::UnknownDocument(path.to_path_buf()))?;
+        buffer.borrow_mut().set_text(text);
+        Ok(())
+    }
+}
+
+/// Describe a change for the undo tree, e.g. `Insert "fn main"` or
+/// `Replace "a" with "b"`.
+fn describe_step(step: &UndoStep) -> String {
+    let [redo] = step.redo.as_slice() else {
+        return format!("{} edits", step.redo.len());
+    };
+    let inserted = &redo.new_text;
+    let deleted = step.undo.first().map_or("", |undo| undo.new_text.as_str());
+    match (deleted.is_empty(), inserted.is_empty()) {
+        (true, _) => format!("Insert {}", quote_change(inserted)),
+        (false, true) => format!("Delete {}", quote_change(deleted)),
+        (false, false) => format!(
+            "Replace {} with {}",
+            quote_change(deleted),
+            quote_change(inserted)
+        ),
+    }
+}
+
+/// Quote `text` for an undo tree label, shortened to
+/// [`MAX_CHANGE_LABEL`] characters and with new lines shown as `⏎`.
+fn quote_change(text: &str) -> String {
+    let mut quoted: String = text
+        .chars()
+        .take(MAX_CHANGE_LABEL)
+        .map(|c| if c == '\n' { '⏎' } else { c })
+        .collect();
+    if text.chars().nth(MAX_CHANGE_LABEL).is_some() {
+        quoted.push('…');
+    }
+    format!("\"{quoted}\"")
+}
+
+/// The URI of the file at `path`.
+///
+/// The path is canonicalized when possible so different spellings of the
+/// same file compare equal.
+fn file_uri(path: &Path) -> BufferUri {
+    BufferUri::File(fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cli_ide_base::test_support::{temp_dir, temp_path, Cleanup};
+
+    fn temp_file(name: &str, text: &str) -> PathBuf {
+        let path = temp_path(name);
+        fs::write(&path, text).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_open_same_path_returns_shared_buffer() {
+        let path = temp_file("buffers-shared.txt", "one");
+        let _cleanup = Cleanup::new([&path]);
+        let mut manager = BufferManager::new();
+        let events = manager.on_buffer_event().subscribe();
+
+        let first = manager.open(&path).unwrap();
+        let second = manager.open(&path).unwrap();
+        assert!(Rc::ptr_eq(&first, &second));
+        assert_eq!(manager.len(), 1);
+
+        first.borrow_mut().set_text("two");
+        assert_eq!(second.borrow().text(), "two");
+        assert!(second.borrow().is_dirty());
+
+        let opened: Vec<BufferEvent> = events.try_iter().collect();
+        assert_eq!(opened, vec![BufferEvent::Opened(file_uri(&path))]);
+    }
+
+    #[test]
+    fn test_edits_can_be_undone_and_redone() {
+        let mut buffer = TextBuffer::new("let a = a + a;");
+        let ranges = buffer
+            .edit(&[TextEdit::new(12..13, "bb"), TextEdit::new(8..9, "bb")])
+            .unwrap();
+        assert_eq!(buffer.text(), "let a = bb + bb;");
+        assert_eq!(ranges, vec![8..10, 13..15]);
+        assert!(buffer.is_dirty());
+
+        assert_eq!(buffer.undo(), Some(8..9));
+        assert_eq!(buffer.text(), "let a = a + a;");
+        assert!(buffer.can_redo());
+        assert_eq!(buffer.redo(), Some(8..10));
+        assert_eq!(buffer.text(), "let a = bb + bb;");
+
+        // Replacing the whole text starts a new history
+        buffer.set_text("fn main() {}");
+        assert!(!buffer.can_undo());
+        assert_eq!(buffer.undo(), None);
+    }
+
+    #[test]
+    fn test_undo_tree_keeps_undone_branches_and_goes_to_any_state() {
+        let mut buffer = TextBuffer::new("one");
+        buffer.edit(&[TextEdit::new(3..3, " two")]).unwrap();
+        buffer.undo();
+        buffer.edit(&[TextEdit::new(0..3, "1\n")]).unwrap();
+        assert_eq!(buffer.text(), "1\n");
+        assert_eq!(buffer.undo_state(), 2);
+
+        let rows = buffer.undo_tree();
+        let labels: Vec<&str> = rows.iter().map(|row| row.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            ["Original", "Insert \" two\"", "Replace \"one\" with \"1⏎\""]
+        );
+        assert_eq!(rows[1].depth, 1, "state 0 branches");
+
+        assert_eq!(buffer.go_to_state(1), Some(3..7));
+        assert_eq!(buffer.text(), "one two");
+        assert_eq!(buffer.go_to_state(1), None);
+        buffer.undo();
+        buffer.redo();
+        assert_eq!(buffer.text(), "one two", "redo follows the visited branch");
+        assert_eq!(buffer.go_to_state(0), Some(3..3));
+        assert_eq!(buffer.text(), "one");
+    }
+
+    #[test]
+    fn test_changes_are_announced_in_order() {
+        let mut buffer = TextBuffer::new("let a = a + a;");
+        let changes = buffer.on_did_change().subscribe();
+        buffer
+            .edit(&[TextEdit::new(12..13, "bb"), TextEdit::new(8..9, "bb")])
+            .unwrap();
+        buffer.undo();
+        buffer.set_text("x");
+        buffer.set_text("x");
+        let change = |range: Range<usize>, new_len| TextChange { range, new_len };
+        assert_eq!(
+            changes.try_iter().collect::<Vec<_>>(),
+            [
+                change(8..9, 2),
+                change(13..14, 2),
+                change(8..10, 1),
+                change(12..14, 1),
+                change(0..14, 1),
+            ]
+        );
+
+        // The cursor after the second `a` stays after it
+        let offset = [change(8..9, 2), change(13..14, 2)]
+            .iter()
+            .fold(13, |offset, change| change.shift(offset));
+        assert_eq!(offset, 15);
+        assert_eq!(change(8..9, 2).shift(8), 8);
+        assert_eq!(change(0..14, 1).shift(10), 1);
+    }
+
+    #[test]
+    fn test_save_and_close_fire_events() {
+        let path = temp_file("buffers-save.txt", "old");
+        let _cleanup = Cleanup::new([&path]);
+        let mut manager = BufferManager::new();
+        let buffer = manager.open(&path).unwrap();
+        let events = manager.on_buffer_event().subscribe();
+
+        buffer.borrow_mut().set_text("new");
+        manager.save(&path).unwrap();
+        assert!(!buffer.borrow().is_dirty());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+
+        assert!(manager.close(&path));
+        assert!(!manager.close(&path));
+        let uri = file_uri(&path);
+        let received: Vec<BufferEvent> = events.try_iter().collect();
+        assert_eq!(
+            received,
+            vec![BufferEvent::Saved(uri.clone()), BufferEvent::Closed(uri)]
+        );
+    }
+
+    #[test]
+    fn test_close_unused_keeps_held_buffers() {
+        let held = temp_file("buffers-held.txt", "");
+        let dropped = temp_file("buffers-dropped.txt", "");
+        let _cleanup = Cleanup::new([&held, &dropped]);
+        let mut manager = BufferManager::new();
+        let _editor = manager.open(&held).unwrap();
+        drop(manager.open(&dropped).unwrap());
+
+        assert_eq!(manager.close_unused(), vec![file_uri(&dropped)]);
+        assert!(manager.contains(&held));
+    }
+
+    #[test]
+    fn test_rename_path_moves_buffers_below_folder() {
+        let dir = temp_dir("buffers-rename");
+        let _cleanup = Cleanup::new([&dir]);
+        fs::create_dir(dir.join("old")).unwrap();
+        let dir = fs::canonicalize(dir).unwrap();
+        fs::write(dir.join("old/a.rs"), "a").unwrap();
+        fs::write(dir.join("other.rs"), "b").unwrap();
+        let mut manager = BufferManager::new();
+        let buffer = manager.open(dir.join("old/a.rs")).unwrap();
+        manager.open(dir.join("other.rs")).unwrap();
+        let events = manager.on_buffer_event().subscribe();
+
+        fs::rename(dir.join("old"), dir.join("new")).unwrap();
+        let renamed = manager.rename_path(&dir.join("old"), &dir.join("new"));
+
+        let moved = BufferUri::File(dir.join("new/a.rs"));
+        assert_eq!(renamed, vec![moved.clone()]);
+        assert_eq!(buffer.borrow().path(), Some(dir.join("new/a.rs").as_path()));
+        assert!(manager.contains(dir.join("new/a.rs")));
+        assert_eq!(
+            events.try_recv().unwrap(),
+            BufferEvent::Renamed {
+                from: BufferUri::File(dir.join("old/a.rs")),
+                to: moved
+            }
+        );
+        assert_eq!(manager.len(), 2);
+    }
+
+    #[test]
+    fn test_close_unused_keeps_dirty_buffers() {
+        let mut manager = BufferManager::new();
+        let (scratch, buffer) = manager.new_untitled();
+        buffer.borrow_mut().set_text("notes");
+        drop(buffer);
+        let (empty, _) = manager.new_untitled();
+
+        assert_eq!(manager.close_unused(), vec![empty]);
+        assert_eq!(manager.uris(), vec![scratch]);
+    }
+
+    #[test]
+    fn test_untitled_names_reuse_lowest_free_number() {
+        let mut manager = BufferManager::new();
+        let (first, _) = manager.new_untitled();
+        let (second, _) = manager.new_untitled();
+        assert_eq!(first, BufferUri::Untitled("Untitled-1".to_string()));
+        assert_eq!(second.to_string(), "untitled:Untitled-2");
+
+        manager.close_uri(&first);
+        assert_eq!(manager.new_untitled().0, first);
+        assert!(manager.paths().is_empty());
+    }
+
+    #[test]
+    fn test_restore_untitled_keeps_name_and_unsaved_text() {
+        let mut manager = BufferManager::new();
+        let (uri, buffer) = manager.restore_untitled("Untitled-3", "draft");
+        assert_eq!(uri, BufferUri::Untitled("Untitled-3".to_string()));
+        assert_eq!(buffer.borrow().text(), "draft");
+        assert!(buffer.borrow().is_dirty());
+
+        let (renamed, _) = manager.restore_untitled("Untitled-3", "");
+        assert_eq!(renamed, BufferUri::Untitled("Untitled-1".to_string()));
+    }
+
+    #[test]
+    fn test_save_as_turns_untitled_into_file() {
+        let path = temp_path("buffers-save-as.txt");
+        let _cleanup = Cleanup::new([&path]);
+        let mut manager = BufferManager::new();
+        let (untitled, buffer) = manager.new_untitled();
+        buffer.borrow_mut().set_text("scratch");
+        let events = manager.on_buffer_event().subscribe();
+
+        let uri = manager.save_as(&buffer, &path).unwrap();
+        assert_eq!(uri, file_uri(&path));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "scratch");
+        assert!(!buffer.borrow().is_dirty());
+        assert_eq!(buffer.borrow().path(), uri.path());
+        assert!(Rc::ptr_eq(&manager.get(&path).unwrap(), &buffer));
+        assert_eq!(manager.uris(), vec![uri.clone()]);
+
+        let received: Vec<BufferEvent> = events.try_iter().collect();
+        assert_eq!(
+            received,
+            vec![
+                BufferEvent::Renamed {
+                    from: untitled,
+                    to: uri.clone()
+                },
+                BufferEvent::Saved(uri)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_save_as_rejects_path_open_in_another_buffer() {
+        let path = temp_file("buffers-taken.txt", "original");
+        let _cleanup = Cleanup::new([&path]);
+        let mut manager = BufferManager::new();
+        manager.open(&path).unwrap();
+        let (_, buffer) = manager.new_untitled();
+        buffer.borrow_mut().set_text("other");
+
+        let error = manager.save_as(&buffer, &path).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "original");
+        assert!(buffer.borrow().is_dirty());
+    }
+
+    #[test]
+    fn test_revisions_are_shared_and_read_only() {
+        let path = temp_file("buffers-revision.txt", "now");
+        let _cleanup = Cleanup::new([&path]);
+        let mut manager = BufferManager::new();
+        let first = manager.open_revision("1a2b3c4", &path, "then");
+        let second = manager.open_revision("1a2b3c4", &path, "then");
+        assert!(Rc::ptr_eq(&first, &second));
+        assert_eq!(manager.len(), 1);
+        assert!(manager.paths().is_empty());
+        let name = format!("{} @ 1a2b3c4", path.file_name().unwrap().to_string_lossy());
+        assert_eq!(first.borrow().name(), Some(name.as_str()));
+
+        let error = first.borrow_mut().save().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+        let error = manager.save_as(&first, &path).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "now");
+    }
+
+    #[test]
+    fn test_workspace_edits_apply_to_open_buffers() {
+        let path = temp_file("buffers-edit.txt", "hello");
+        let _cleanup = Cleanup::new([&path]);
+        let mut manager = BufferManager::new();
+        let buffer = manager.open(&path).unwrap();
+
+        TextDocuments::set_text(&mut manager, &path, "bye".to_string()).unwrap();
+        assert_eq!(buffer.borrow().text(), "bye");
+        assert_eq!(
+            TextDocuments::set_text(&mut manager, Path::new("/nope"), String::new()),
+            Err(EditError::UnknownDocument(PathBuf::from("/nope")))
+        );
+    }
+}
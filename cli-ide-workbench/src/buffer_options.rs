@@ -0,0 +1,660 @@
+//! Buffer-local options and keybinding overrides.
+//!
+//! Options such as wrapping and indentation can be set in three layers,
+//! from lowest to highest precedence:
+//!
+//! 1. workspace configuration,
+//! 2. a `.paradiddle` file next to the buffer,
+//! 3. a modeline inside the buffer itself.
+//!
+//! Each layer is an [`OptionOverrides`] where unset fields fall through to
+//! the layer below; [`BufferOptions::resolve`] merges the layers on top of
+//! the built-in defaults.
+//!
+//! Both the `.paradiddle` file and the modeline use the same settings:
+//!
+//! | Setting           | Example                 | Meaning                         |
+//! |-------------------|-------------------------|---------------------------------|
+//! | `wrap` / `nowrap` | `wrap`                  | Soft-wrap long lines            |
+//! | `indent=<n>`      | `indent=2`              | Indentation width in columns    |
+//! | `tabs` / `spaces` | `tabs`                  | Indent with tabs or spaces      |
+//! | `line_length=<n>` | `line_length=80`        | Soft line-length limit          |
+//! | `bracket_pairs` / `nobracket_pairs` | `nobracket_pairs` | Color bracket pairs by depth |
+//! | `color_swatches` / `nocolor_swatches` | `nocolor_swatches` | Show swatches by colors |
+//! | `key.<key>=<act>` | `key.e=expandBracketSelection` | Bind a key for this buffer only |
+//!
+//! A `.paradiddle` file has one `name = value` setting per line (bare
+//! flags like `wrap` are allowed) and `#` comments. A modeline is a line in
+//! the first or last [`MODELINE_SCAN_LINES`] lines of the buffer containing
+//! `paradiddle:` followed by whitespace-separated settings, e.g.
+//! `// paradiddle: nowrap indent=2 key.e=expandBracketSelection`.
+//!
+//! The workspace layer comes from the `[editor]` section of the user
+//! configuration (see [`OptionOverrides::from_config`]), which takes the
+//! same settings as `name = value` pairs. `[editor.<language>]` sections
+//! override it for files of one language (see
+//! [`OptionOverrides::languages_from_config`]).
+
+use std::collections::HashMap;
+use std::fmt;
+
+use cli_ide_platform::config::config_service::Config;
+
+use crate::input::AppKey;
+use crate::keybinding::Action;
+use crate::layout::PaneDirection;
+
+/// Marker that introduces a modeline.
+pub const MODELINE_MARKER: &str = "paradiddle:";
+
+/// Number of lines at the start and end of a buffer searched for a modeline.
+pub const MODELINE_SCAN_LINES: usize = 5;
+
+/// Configuration section holding the workspace layer.
+pub const EDITOR_SECTION: &str = "editor";
+
+/// Default indentation width.
+const DEFAULT_INDENT_WIDTH: usize = 4;
+
+/// Error produced when an options layer cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OptionsError {
+    /// Zero-based line the error was found on.
+    pub line: usize,
+    /// Description of the problem.
+    pub message: String,
+}
+
+impl fmt::Display for OptionsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line + 1, self.message)
+    }
+}
+
+impl std::error::Error for OptionsError {}
+
+/// One layer of option overrides. `None` fields are left to lower layers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OptionOverrides {
+    /// Whether long lines are soft-wrapped.
+    pub wrap: Option<bool>,
+    /// Indentation width in columns.
+    pub indent_width: Option<usize>,
+    /// Whether indentation uses tabs instead of spaces.
+    pub use_tabs: Option<bool>,
+    /// Soft line-length limit in characters.
+    pub line_length: Option<usize>,
+    /// Whether bracket pairs are colored by nesting depth.
+    pub bracket_pairs: Option<bool>,
+    /// Whether color values and image data URIs get inline swatches.
+    pub color_swatches: Option<bool>,
+    /// Buffer-local keybindings.
+    pub keybindings: HashMap<AppKey, Action>,
+}
+
+impl OptionOverrides {
+    /// Create an empty layer that overrides nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse the contents of a `.paradiddle` file.
+    pub fn parse_config(contents: &str) -> Result<Self, OptionsError> {
+        let mut overrides = Self::new();
+        for (line, raw) in contents.lines().enumerate() {
+            let setting = raw.split('#').next().unwrap_or("").trim();
+            if setting.is_empty() {
+                continue;
+            }
+            let (name, value) = match setting.split_once('=') {
+                Some((name, value)) => (name.trim(), Some(value.trim())),
+                None => (setting, None),
+            };
+            overrides
+                .apply_setting(name, value)
+                .map_err(|message| OptionsError { line, message })?;
+        }
+        Ok(overrides)
+    }
+
+    /// Build the workspace layer from the `[editor]` section of the user
+    /// configuration.
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        let mut overrides = Self::new();
+        for (name, value) in config.section(EDITOR_SECTION) {
+            overrides
+                .apply_setting(name, Some(&value.to_string()))
+                .map_err(|message| format!("[{EDITOR_SECTION}] {message}"))?;
+        }
+        Ok(overrides)
+    }
+
+    /// Build a layer for each `[editor.<language>]` section of the user
+    /// configuration, keyed by language.
+    pub fn languages_from_config(config: &Config) -> Result<HashMap<String, Self>, String> {
+        let mut languages = HashMap::new();
+        for language in config.subsections(EDITOR_SECTION) {
+            let section = format!("{EDITOR_SECTION}.{language}");
+            let mut overrides = Self::new();
+            for (name, value) in config.section(&section) {
+                overrides
+                    .apply_setting(name, Some(&value.to_string()))
+                    .map_err(|message| format!("[{section}] {message}"))?;
+            }
+            languages.insert(language.to_string(), overrides);
+        }
+        Ok(languages)
+    }
+
+    /// Find and parse a modeline in `text`.
+    ///
+    /// Only the first and last [`MODELINE_SCAN_LINES`] lines are searched;
+    /// the first modeline found wins. Returns `Ok(None)` when there is none.
+    pub fn parse_modeline(text: &str) -> Result<Option<Self>, OptionsError> {
+        let lines: Vec<&str> = text.lines().collect();
+        let tail_start = lines.len().saturating_sub(MODELINE_SCAN_LINES);
+        let candidates = (0..lines.len().min(MODELINE_SCAN_LINES))
+            .chain(tail_start.max(MODELINE_SCAN_LINES)..lines.len());
+
+        for line in candidates {
+            let Some((_, settings)) = lines[line].split_once(MODELINE_MARKER) else {
+                continue;
+            };
+            return Self::parse_settings(settings)
+                .map(Some)
+                .map_err(|message| OptionsError { line, message });
+        }
+        Ok(None)
+    }
+
+    /// Parse whitespace-separated settings as written in a modeline or
+    /// after `:set`, e.g. `nowrap indent=2`.
+    pub fn parse_settings(settings: &str) -> Result<Self, String> {
+        let mut overrides = Self::new();
+        for setting in settings.split_whitespace() {
+            let (name, value) = match setting.split_once('=') {
+                Some((name, value)) => (name, Some(value)),
+                None => (setting, None),
+            };
+            overrides.apply_setting(name, value)?;
+        }
+        Ok(overrides)
+    }
+
+    /// Return a new layer with `higher` applied on top of `self`.
+    pub fn merged_with(&self, higher: &OptionOverrides) -> OptionOverrides {
+        let mut keybindings = self.keybindings.clone();
+        keybindings.extend(higher.keybindings.iter().map(|(&k, &a)| (k, a)));
+        OptionOverrides {
+            wrap: higher.wrap.or(self.wrap),
+            indent_width: higher.indent_width.or(self.indent_width),
+            use_tabs: higher.use_tabs.or(self.use_tabs),
+            line_length: higher.line_length.or(self.line_length),
+            bracket_pairs: higher.bracket_pairs.or(self.bracket_pairs),
+            color_swatches: higher.color_swatches.or(self.color_swatches),
+            keybindings,
+        }
+    }
+
+    /// Apply a single `name[=value]` setting.
+    fn apply_setting(&mut self, name: &str, value: Option<&str>) -> Result<(), String> {
+        if let Some(key_name) = name.strip_prefix("key.") {
+            let key = parse_key(key_name).ok_or_else(|| format!("unknown key `{key_name}`"))?;
+            let value = value.ok_or_else(|| format!("`{name}` needs an action"))?;
+            let action = parse_action(value).ok_or_else(|| format!("unknown action `{value}`"))?;
+            self.keybindings.insert(key, action);
+            return Ok(());
+        }
+
+        match (name, value) {
+            ("wrap", None) => self.wrap = Some(true),
+            ("nowrap", None) => self.wrap = Some(false),
+            ("wrap", Some(v)) => self.wrap = Some(parse_bool(name, v)?),
+            ("tabs", None) => self.use_tabs = Some(true),
+            ("spaces", None) => self.use_tabs = Some(false),
+            ("tabs", Some(v)) => self.use_tabs = Some(parse_bool(name, v)?),
+            ("bracket_pairs", None) => self.bracket_pairs = Some(true),
+            ("nobracket_pairs", None) => self.bracket_pairs = Some(false),
+            ("bracket_pairs", Some(v)) => self.bracket_pairs = Some(parse_bool(name, v)?),
+            ("color_swatches", None) => self.color_swatches = Some(true),
+            ("nocolor_swatches", None) => self.color_swatches = Some(false),
+            ("color_swatches", Some(v)) => self.color_swatches = Some(parse_bool(name, v)?),
+            ("indent", Some(v)) => self.indent_width = Some(parse_width(name, v)?),
+            ("line_length", Some(v)) => self.line_length = Some(parse_width(name, v)?),
+            ("indent" | "line_length", None) => return Err(format!("`{name}` needs a value")),
+            _ => return Err(format!("unknown setting `{name}`")),
+        }
+        Ok(())
+    }
+}
+
+/// Fully resolved options for a buffer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BufferOptions {
+    /// Whether long lines are soft-wrapped.
+    pub wrap: bool,
+    /// Indentation width in columns.
+    pub indent_width: usize,
+    /// Whether indentation uses tabs instead of spaces.
+    pub use_tabs: bool,
+    /// Soft line-length limit, if any.
+    pub line_length: Option<usize>,
+    /// Whether bracket pairs are colored by nesting depth.
+    pub bracket_pairs: bool,
+    /// Whether color values and image data URIs get inline swatches.
+    pub color_swatches: bool,
+    /// Buffer-local keybindings, consulted before context and global bindings.
+    pub keybindings: HashMap<AppKey, Action>,
+}
+
+impl Default for BufferOptions {
+    fn default() -> Self {
+        Self {
+            wrap: false,
+            indent_width: DEFAULT_INDENT_WIDTH,
+            use_tabs: false,
+            line_length: None,
+            bracket_pairs: true,
+            color_swatches: true,
+            keybindings: HashMap::new(),
+        }
+    }
+}
+
+impl BufferOptions {
+    /// Resolve options from the workspace, `.paradiddle` file and modeline
+    /// layers, in increasing order of precedence.
+    pub fn resolve(
+        workspace: &OptionOverrides,
+        project_file: Option<&OptionOverrides>,
+        modeline: Option<&OptionOverrides>,
+    ) -> Self {
+        let mut merged = workspace.clone();
+        for layer in [project_file, modeline].into_iter().flatten() {
+            merged = merged.merged_with(layer);
+        }
+
+        let defaults = Self::default();
+        Self {
+            wrap: merged.wrap.unwrap_or(defaults.wrap),
+            indent_width: merged.indent_width.unwrap_or(defaults.indent_width),
+            use_tabs: merged.use_tabs.unwrap_or(defaults.use_tabs),
+            line_length: merged.line_length.or(defaults.line_length),
+            bracket_pairs: merged.bracket_pairs.unwrap_or(defaults.bracket_pairs),
+            color_swatches: merged.color_swatches.unwrap_or(defaults.color_swatches),
+            keybindings: merged.keybindings,
+        }
+    }
+
+    /// Return these options with the ones `layer` sets replaced.
+    pub fn overridden_by(&self, layer: &OptionOverrides) -> Self {
+        let mut keybindings = self.keybindings.clone();
+        keybindings.extend(layer.keybindings.iter().map(|(&k, &a)| (k, a)));
+        Self {
+            wrap: layer.wrap.unwrap_or(self.wrap),
+            indent_width: layer.indent_width.unwrap_or(self.indent_width),
+            use_tabs: layer.use_tabs.unwrap_or(self.use_tabs),
+            line_length: layer.line_length.or(self.line_length),
+            bracket_pairs: layer.bracket_pairs.unwrap_or(self.bracket_pairs),
+            color_swatches: layer.color_swatches.unwrap_or(self.color_swatches),
+            keybindings,
+        }
+    }
+
+    /// The string inserted for one level of indentation.
+    pub fn indent_unit(&self) -> String {
+        if self.use_tabs {
+            "\t".to_string()
+        } else {
+            " ".repeat(self.indent_width)
+        }
+    }
+}
+
+fn parse_bool(name: &str, value: &str) -> Result<bool, String> {
+    match value {
+        "true" | "on" | "yes" => Ok(true),
+        "false" | "off" | "no" => Ok(false),
+        _ => Err(format!("`{name}` expects true or false, got `{value}`")),
+    }
+}
+
+fn parse_width(name: &str, value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(width) if width > 0 => Ok(width),
+        _ => Err(format!("`{name}` expects a positive number, got `{value}`")),
+    }
+}
+
+/// Parse a key name as used in `key.<name>` settings.
+pub(crate) fn parse_key(name: &str) -> Option<AppKey> {
+    let key = match name {
+        "q" => AppKey::Q,
+        "esc" => AppKey::Esc,
+        "tab" => AppKey::Tab,
+        "up" => AppKey::Up,
+        "down" => AppKey::Down,
+        "left" => AppKey::Left,
+        "right" => AppKey::Right,
+        "shift-up" => AppKey::ShiftUp,
+        "shift-down" => AppKey::ShiftDown,
+        "shift-left" => AppKey::ShiftLeft,
+        "shift-right" => AppKey::ShiftRight,
+        "pageup" => AppKey::PageUp,
+        "pagedown" => AppKey::PageDown,
+        "enter" => AppKey::Enter,
+        "backspace" => AppKey::Backspace,
+        _ => {
+            let single = |name: &str| {
+                let mut chars = name.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Some(c),
+                    _ => None,
+                }
+            };
+            if let Some(key) = name.strip_prefix("ctrl-") {
+                AppKey::Ctrl(single(key)?)
+            } else if let Some(key) = name.strip_prefix("alt-") {
+                AppKey::Alt(single(key)?)
+            } else {
+                AppKey::Char(single(name)?)
+            }
+        }
+    };
+    Some(key)
+}
+
+/// Parse an action name as used in `key.<name>=<action>` settings.
+pub(crate) fn parse_action(name: &str) -> Option<Action> {
+    let action = match name {
+        "quit" => Action::Quit,
+        "toggleFocus" => Action::ToggleFocus,
+        "focusNext" => Action::FocusNext,
+        "focusUp" => Action::FocusDirection(PaneDirection::Up),
+        "focusDown" => Action::FocusDirection(PaneDirection::Down),
+        "focusLeft" => Action::FocusDirection(PaneDirection::Left),
+        "focusRight" => Action::FocusDirection(PaneDirection::Right),
+        "closeWindow" => Action::CloseWindow,
+        "focusPrev" => Action::FocusPrev,
+        "showCommands" => Action::OpenCommandPalette,
+        "openCommandLine" => Action::OpenCommandLine,
+        "showContextMenu" => Action::ShowContextMenu,
+        "expandBracketSelection" => Action::ExpandBracketSelection,
+        "shrinkBracketSelection" => Action::ShrinkBracketSelection,
+        "quickFix" => Action::ShowCodeActions,
+        "peekDefinition" => Action::PeekDefinition,
+        "openLink" => Action::OpenLink,
+        "rename" => Action::RenameSymbol,
+        "find" => Action::Find,
+        "findNext" => Action::FindNext,
+        "findPrevious" => Action::FindPrevious,
+        "replaceOne" => Action::ReplaceOne,
+        "replaceAll" => Action::ReplaceAll,
+        "undo" => Action::Undo,
+        "redo" => Action::Redo,
+        "scrollPageUp" => Action::ScrollPageUp,
+        "scrollPageDown" => Action::ScrollPageDown,
+        "scrollLineUp" => Action::ScrollLineUp,
+        "scrollLineDown" => Action::ScrollLineDown,
+        "toggleTheme" => Action::ToggleTheme,
+        "newUntitledFile" => Action::NewUntitledFile,
+        "save" => Action::Save,
+        "exportAsAnsi" => Action::ExportAsAnsi,
+        "exportAsHtml" => Action::ExportAsHtml,
+        "quickOpen" => Action::QuickOpen,
+        "toggleExplorer" => Action::ToggleExplorer,
+        "newFile" => Action::ExplorerNewFile,
+        "newFolder" => Action::ExplorerNewFolder,
+        "renameFile" => Action::ExplorerRename,
+        "deleteFile" => Action::ExplorerDelete,
+        "moveFiles" => Action::ExplorerMove,
+        "refreshExplorer" => Action::ExplorerRefresh,
+        "addRootFolder" => Action::AddRootFolder,
+        "removeRootFolder" => Action::RemoveRootFolder,
+        "toggleSearch" => Action::ToggleSearch,
+        "replaceInFiles" => Action::ReplaceInFiles,
+        "findInFiles" => Action::FindInFiles,
+        "nextTab" => Action::NextTab,
+        "prevTab" => Action::PrevTab,
+        "closeTab" => Action::CloseTab,
+        "reopenClosedEditor" => Action::ReopenClosedEditor,
+        "closeOtherTabs" => Action::CloseOtherTabs,
+        "togglePinnedTab" => Action::TogglePinnedTab,
+        "moveTabLeft" => Action::MoveTabLeft,
+        "moveTabRight" => Action::MoveTabRight,
+        "moveTabToNextGroup" => Action::MoveTabToNextGroup,
+        "splitEditorRight" => Action::SplitEditorRight,
+        "splitEditorDown" => Action::SplitEditorDown,
+        "increaseViewWidth" => Action::IncreaseViewWidth,
+        "decreaseViewWidth" => Action::DecreaseViewWidth,
+        "increaseViewHeight" => Action::IncreaseViewHeight,
+        "decreaseViewHeight" => Action::DecreaseViewHeight,
+        "toggleRegexTester" => Action::ToggleRegexTester,
+        "toggleUndoTree" => Action::ToggleUndoTree,
+        "showKeybindings" => Action::ShowKeybindings,
+        "toggleSnippets" => Action::ToggleSnippets,
+        "toggleTodos" => Action::ToggleTodos,
+        "toggleGitPanel" => Action::ToggleGitPanel,
+        "refreshGitPanel" => Action::GitRefresh,
+        "stageHunk" => Action::StageHunk,
+        "unstageHunk" => Action::UnstageHunk,
+        "revertHunk" => Action::RevertHunk,
+        "toggleDiffLayout" => Action::ToggleDiffLayout,
+        "compareWithSaved" => Action::CompareWithSaved,
+        "compareWithHead" => Action::CompareWithHead,
+        "compareWithClipboard" => Action::CompareWithClipboard,
+        "compareWithFile" => Action::CompareWithFile,
+        "toggleGitLog" => Action::ToggleGitLog,
+        "checkoutBranch" => Action::CheckoutBranch,
+        "stash" => Action::StashChanges,
+        "stashPopLatest" => Action::PopLatestStash,
+        "stashPop" => Action::PopStash,
+        "cargoBuild" => Action::CargoBuild,
+        "cargoTest" => Action::CargoTest,
+        "cargoRun" => Action::CargoRun,
+        "cargoClippy" => Action::CargoClippy,
+        "cargoStop" => Action::CargoStop,
+        "runTask" => Action::RunTask,
+        "toggleProblems" => Action::ToggleProblems,
+        "toggleDiagnostics" => Action::ToggleDiagnostics,
+        "nextDiagnostic" => Action::NextDiagnostic,
+        "previousDiagnostic" => Action::PreviousDiagnostic,
+        "toggleOutput" => Action::ToggleOutput,
+        "toggleLogs" => Action::ToggleLogs,
+        "toggleTerminalCapabilities" => Action::ToggleTerminalCapabilities,
+        "togglePresentationMirror" => Action::ToggleMirror,
+        "toggleMarkdownPreview" => Action::ToggleMarkdownPreview,
+        "toggleScrollbars" => Action::ToggleScrollbars,
+        "toggleWordCount" => Action::ToggleWordCount,
+        "toggleCompactMode" => Action::ToggleCompactMode,
+        "toggleDensity" => Action::ToggleDensity,
+        "cancelProgress" => Action::CancelProgress,
+        "toggleNotifications" => Action::ToggleNotifications,
+        "toggleDoNotDisturb" => Action::ToggleDoNotDisturb,
+        "clearNotifications" => Action::ClearNotifications,
+        "hideToasts" => Action::HideNotificationToasts,
+        "toggleTutorial" => Action::ToggleTutorial,
+        "toggleModalEditing" => Action::ToggleModalEditing,
+        "normalMode" => Action::EnterNormalMode,
+        "insertMode" => Action::EnterInsertMode,
+        "visualMode" => Action::EnterVisualMode,
+        "cursorLeft" => Action::CursorLeft,
+        "cursorDown" => Action::CursorDown,
+        "cursorUp" => Action::CursorUp,
+        "cursorRight" => Action::CursorRight,
+        "selectLeft" => Action::SelectLeft,
+        "selectDown" => Action::SelectDown,
+        "selectUp" => Action::SelectUp,
+        "selectRight" => Action::SelectRight,
+        "deleteCharacter" => Action::DeleteCharacter,
+        "deleteLeft" => Action::DeleteLeft,
+        "copy" => Action::Copy,
+        "cut" => Action::Cut,
+        "paste" => Action::Paste,
+        "none" => Action::None,
+        _ => return None,
+    };
+    Some(action)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config_file() {
+        let overrides = OptionOverrides::parse_config(
+            "# project settings\nwrap = false\nindent = 2\ntabs\nkey.e = expandBracketSelection\n",
+        )
+        .unwrap();
+
+        assert_eq!(overrides.wrap, Some(false));
+        assert_eq!(overrides.indent_width, Some(2));
+        assert_eq!(overrides.use_tabs, Some(true));
+        assert_eq!(overrides.line_length, None);
+        assert_eq!(
+            overrides.keybindings.get(&AppKey::Char('e')),
+            Some(&Action::ExpandBracketSelection)
+        );
+    }
+
+    #[test]
+    fn test_from_config_editor_section() {
+        let config = Config::parse("[editor]\nwrap = false\nindent = 2\ntabs = true\n").unwrap();
+        let overrides = OptionOverrides::from_config(&config).unwrap();
+        assert_eq!(overrides.wrap, Some(false));
+        assert_eq!(overrides.indent_width, Some(2));
+        assert_eq!(overrides.use_tabs, Some(true));
+
+        let config = Config::parse("[editor]\nindent = 0\n").unwrap();
+        assert_eq!(
+            OptionOverrides::from_config(&config).unwrap_err(),
+            "[editor] `indent` expects a positive number, got `0`"
+        );
+    }
+
+    #[test]
+    fn test_language_sections_override_the_editor_section() {
+        let config = Config::parse(
+            "[editor]\nbracket_pairs = true\n\
+             [editor.markdown]\nbracket_pairs = false\nwrap = true\n",
+        )
+        .unwrap();
+        let workspace = OptionOverrides::from_config(&config).unwrap();
+        let languages = OptionOverrides::languages_from_config(&config).unwrap();
+        assert_eq!(languages.len(), 1);
+        let options =
+            BufferOptions::resolve(&workspace.merged_with(&languages["markdown"]), None, None);
+        assert!(!options.bracket_pairs);
+        assert!(options.wrap);
+        assert!(BufferOptions::resolve(&workspace, None, None).bracket_pairs);
+
+        let config = Config::parse("[editor.rust]\nbracket_pairs = 1\n").unwrap();
+        assert_eq!(
+            OptionOverrides::languages_from_config(&config).unwrap_err(),
+            "[editor.rust] `bracket_pairs` expects true or false, got `1`"
+        );
+    }
+
+    #[test]
+    fn test_parse_config_reports_line() {
+        let err = OptionOverrides::parse_config("wrap\nindent = zero").unwrap_err();
+        assert_eq!(err.line, 1);
+        assert_eq!(
+            err.to_string(),
+            "line 2: `indent` expects a positive number, got `zero`"
+        );
+    }
+
+    #[test]
+    fn test_parse_config_rejects_unknown_setting() {
+        let err = OptionOverrides::parse_config("colour = red").unwrap_err();
+        assert_eq!(err.message, "unknown setting `colour`");
+    }
+
+    #[test]
+    fn test_parse_modeline_at_top() {
+        let text = "// paradiddle: nowrap indent=2 key.q=none\nfn main() {}";
+        let overrides = OptionOverrides::parse_modeline(text).unwrap().unwrap();
+
+        assert_eq!(overrides.wrap, Some(false));
+        assert_eq!(overrides.indent_width, Some(2));
+        assert_eq!(overrides.keybindings.get(&AppKey::Q), Some(&Action::None));
+    }
+
+    #[test]
+    fn test_parse_modeline_at_bottom() {
+        let mut text = "line\n".repeat(20);
+        text.push_str("# paradiddle: line_length=72");
+        let overrides = OptionOverrides::parse_modeline(&text).unwrap().unwrap();
+        assert_eq!(overrides.line_length, Some(72));
+    }
+
+    #[test]
+    fn test_modeline_in_middle_is_ignored() {
+        let text = format!("{}paradiddle: wrap\n{}", "a\n".repeat(10), "b\n".repeat(10));
+        assert_eq!(OptionOverrides::parse_modeline(&text).unwrap(), None);
+    }
+
+    #[test]
+    fn test_invalid_modeline_is_an_error() {
+        let err = OptionOverrides::parse_modeline("x\n-- paradiddle: key.f1=quit").unwrap_err();
+        assert_eq!(err.line, 1);
+        assert_eq!(err.message, "unknown key `f1`");
+    }
+
+    #[test]
+    fn test_resolve_precedence() {
+        let workspace =
+            OptionOverrides::parse_config("wrap\nindent = 8\nline_length = 100").unwrap();
+        let project = OptionOverrides::parse_config("indent = 2\nkey.e = quit").unwrap();
+        let modeline =
+            OptionOverrides::parse_modeline("paradiddle: nowrap key.e=expandBracketSelection")
+                .unwrap()
+                .unwrap();
+
+        let options = BufferOptions::resolve(&workspace, Some(&project), Some(&modeline));
+
+        assert!(!options.wrap);
+        assert_eq!(options.indent_width, 2);
+        assert_eq!(options.line_length, Some(100));
+        assert!(!options.use_tabs);
+        assert_eq!(
+            options.keybindings.get(&AppKey::Char('e')),
+            Some(&Action::ExpandBracketSelection)
+        );
+    }
+
+    #[test]
+    fn test_settings_override_resolved_options() {
+        let options = BufferOptions {
+            line_length: Some(100),
+            ..BufferOptions::default()
+        };
+        let layer = OptionOverrides::parse_settings("wrap indent=2").unwrap();
+        let options = options.overridden_by(&layer);
+        assert!(options.wrap);
+        assert_eq!(options.indent_width, 2);
+        assert_eq!(options.line_length, Some(100), "unset options are kept");
+        assert_eq!(
+            OptionOverrides::parse_settings("wrap=maybe"),
+            Err("`wrap` expects true or false, got `maybe`".to_string())
+        );
+    }
+
+    #[test]
+    fn test_resolve_defaults() {
+        let options = BufferOptions::resolve(&OptionOverrides::new(), None, None);
+        assert_eq!(options, BufferOptions::default());
+        assert_eq!(options.indent_unit(), "    ");
+    }
+
+    #[test]
+    fn test_indent_unit_with_tabs() {
+        let options = BufferOptions {
+            use_tabs: true,
+            ..BufferOptions::default()
+        };
+        assert_eq!(options.indent_unit(), "\t");
+    }
+}
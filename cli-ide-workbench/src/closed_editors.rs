@@ -0,0 +1,100 @@
+//! Recently closed editors, for reopening them.
+//!
+//! Closing a tab pushes its file and cursor onto [`ClosedEditors`]; the
+//! "Reopen Closed Editor" command pops the most recent one and opens it
+//! again with the cursor where it was, like a browser's reopen-tab.
+
+use std::path::{Path, PathBuf};
+
+/// How many closed editors are remembered.
+pub const MAX_CLOSED_EDITORS: usize = 20;
+
+/// A closed editor tab.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClosedEditor {
+    /// The file the tab showed.
+    pub path: PathBuf,
+    /// One-based line of the cursor.
+    pub line: usize,
+    /// One-based character column of the cursor.
+    pub column: usize,
+}
+
+/// The most recently closed editors, newest last.
+#[derive(Debug, Clone, Default)]
+pub struct ClosedEditors {
+    /// Closed editors, oldest first, at most [`MAX_CLOSED_EDITORS`].
+    editors: Vec<ClosedEditor>,
+}
+
+impl ClosedEditors {
+    /// Create an empty list.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remember a closed editor.
+    ///
+    /// An earlier entry for the same file is dropped, so a file is
+    /// reopened once, at its latest cursor. The oldest entry is forgotten
+    /// once there are more than [`MAX_CLOSED_EDITORS`].
+    pub fn push(&mut self, editor: ClosedEditor) {
+        self.editors.retain(|other| other.path != editor.path);
+        self.editors.push(editor);
+        if self.editors.len() > MAX_CLOSED_EDITORS {
+            self.editors.remove(0);
+        }
+    }
+
+    /// Take the most recently closed editor.
+    pub fn pop(&mut self) -> Option<ClosedEditor> {
+        self.editors.pop()
+    }
+
+    /// Forget the entry for `path`, e.g. once the file is open again.
+    pub fn forget(&mut self, path: &Path) {
+        self.editors.retain(|editor| editor.path != path);
+    }
+
+    /// Get the closed editors, oldest first.
+    pub fn editors(&self) -> &[ClosedEditor] {
+        &self.editors
+    }
+
+    /// Check whether no closed editor is remembered.
+    pub fn is_empty(&self) -> bool {
+        self.editors.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn closed(path: &str, line: usize) -> ClosedEditor {
+        ClosedEditor {
+            path: PathBuf::from(path),
+            line,
+            column: 1,
+        }
+    }
+
+    #[test]
+    fn test_pop_returns_the_newest_and_a_file_is_kept_once() {
+        let mut editors = ClosedEditors::new();
+        editors.push(closed("a.rs", 1));
+        editors.push(closed("b.rs", 2));
+        editors.push(closed("a.rs", 3));
+        assert_eq!(editors.pop(), Some(closed("a.rs", 3)));
+        assert_eq!(editors.pop(), Some(closed("b.rs", 2)));
+        assert_eq!(editors.pop(), None);
+
+        for line in 0..=MAX_CLOSED_EDITORS {
+            editors.push(closed(&format!("{line}.rs"), line));
+        }
+        assert_eq!(editors.editors().len(), MAX_CLOSED_EDITORS);
+        assert_eq!(editors.editors()[0].path, PathBuf::from("1.rs"));
+        editors.forget(Path::new("5.rs"));
+        assert_eq!(editors.editors().len(), MAX_CLOSED_EDITORS - 1);
+    }
+}
@@ -0,0 +1,240 @@
+//! Code actions (quick fixes) and workspace edits.
+//!
+//! A [`CodeActionProvider`] (typically backed by a language server's
+//! `textDocument/codeAction`) offers [`CodeAction`]s for a range of a
+//! document. Each action carries a [`WorkspaceEdit`] that may touch several
+//! files; edits are applied through the [`TextDocuments`] trait so the
+//! component that owns open buffers decides how each file is updated.
+//!
+//! All ranges are byte offsets into the document text.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Replace `range` of a document with `new_text`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    /// Byte range to replace; empty for an insertion.
+    pub range: Range<usize>,
+    /// Replacement text.
+    pub new_text: String,
+}
+
+impl TextEdit {
+    /// Create an edit replacing `range` with `new_text`.
+    pub fn new(range: Range<usize>, new_text: impl Into<String>) -> Self {
+        Self {
+            range,
+            new_text: new_text.into(),
+        }
+    }
+
+    /// Create an edit inserting `text` at `offset`.
+    pub fn insert(offset: usize, text: impl Into<String>) -> Self {
+        Self::new(offset..offset, text)
+    }
+}
+
+/// Error produced when a workspace edit cannot be applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EditError {
+    /// No open or loadable document exists at the path.
+    UnknownDocument(PathBuf),
+    /// An edit range is out of bounds or not on a char boundary.
+    InvalidRange(PathBuf, Range<usize>),
+    /// Two edits to the same document overlap.
+    OverlappingEdits(PathBuf),
+}
+
+impl fmt::Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EditError::UnknownDocument(path) => {
+                write!(f, "no document at {}", path.display())
+            }
+            EditError::InvalidRange(path, range) => write!(
+                f,
+                "invalid range {}..{} in {}",
+                range.start,
+                range.end,
+                path.display()
+            ),
+            EditError::OverlappingEdits(path) => {
+                write!(f, "overlapping edits in {}", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for EditError {}
+
+/// Apply `edits` to `text`, returning the new text.
+///
+/// Edits refer to offsets in the original text and must not overlap.
+pub fn apply_edits(path: &Path, text: &str, edits: &[TextEdit]) -> Result<String, EditError> {
+    let mut sorted: Vec<&TextEdit> = edits.iter().collect();
+    sorted.sort_by_key(|edit| (edit.range.start, edit.range.end));
+
+    let mut result = String::with_capacity(text.len());
+    let mut copied_to = 0;
+    for edit in sorted {
+        let range = &edit.range;
+        if range.start > range.end
+            || range.end > text.len()
+            || !text.is_char_boundary(range.start)
+            || !text.is_char_boundary(range.end)
+        {
+            return Err(EditError::InvalidRange(path.to_path_buf(), range.clone()));
+        }
+        if range.start < copied_to {
+            return Err(EditError::OverlappingEdits(path.to_path_buf()));
+        }
+        result.push_str(&text[copied_to..range.start]);
+        result.push_str(&edit.new_text);
+        copied_to = range.end;
+    }
+    result.push_str(&text[copied_to..]);
+    Ok(result)
+}
+
+/// Access to the documents a workspace edit can modify.
+///
+/// Implemented by whatever owns the open buffers (the App for its editor,
+/// or a buffer manager that can also load files that are not open).
+pub trait TextDocuments {
+    /// Get the current text of the document at `path`.
+    fn text(&self, path: &Path) -> Option<String>;
+
+    /// Replace the text of the document at `path`.
+    fn set_text(&mut self, path: &Path, text: String) -> Result<(), EditError>;
+}
+
+/// A set of edits across one or more documents.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorkspaceEdit {
+    /// Edits per document path.
+    pub changes: BTreeMap<PathBuf, Vec<TextEdit>>,
+}
+
+impl WorkspaceEdit {
+    /// Create an empty workspace edit.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an edit for the document at `path`.
+    pub fn push(&mut self, path: impl Into<PathBuf>, edit: TextEdit) {
+        self.changes.entry(path.into()).or_default().push(edit);
+    }
+
+    /// Apply every change to `documents`.
+    ///
+    /// All documents are checked before any is modified, so an invalid edit
+    /// leaves every document untouched.
+    pub fn apply(&self, documents: &mut dyn TextDocuments) -> Result<(), EditError> {
+        let mut updated = Vec::with_capacity(self.changes.len());
+        for (path, edits) in &self.changes {
+            let text = documents
+                .text(path)
+                .ok_or_else(|| EditError::UnknownDocument(path.clone()))?;
+            updated.push((path, apply_edits(path, &text, edits)?));
+        }
+        for (path, text) in updated {
+            documents.set_text(path, text)?;
+        }
+        Ok(())
+    }
+}
+
+/// A fix or refactoring offered for part of a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeAction {
+    /// Title shown in the quick-fix menu.
+    pub title: String,
+    /// Kind of action (e.g. `"quickfix"`, `"refactor.extract"`), if known.
+    pub kind: Option<String>,
+    /// The edit performed when the action is applied.
+    pub edit: WorkspaceEdit,
+}
+
+/// Computes code actions for a range of a document.
+pub trait CodeActionProvider {
+    /// Return the actions available for `range` of the document at `path`.
+    fn code_actions(&self, path: &Path, text: &str, range: Range<usize>) -> Vec<CodeAction>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    struct Documents(HashMap<PathBuf, String>);
+
+    impl TextDocuments for Documents {
+        fn text(&self, path: &Path) -> Option<String> {
+            self.0.get(path).cloned()
+        }
+
+        fn set_text(&mut self, path: &Path, text: String) -> Result<(), EditError> {
+            self.0.insert(path.to_path_buf(), text);
+            Ok(())
+        }
+    }
+
+    fn documents() -> Documents {
+        Documents(HashMap::from([
+            (PathBuf::from("a.rs"), "let x = 1;".to_string()),
+            (PathBuf::from("b.rs"), "use x;".to_string()),
+        ]))
+    }
+
+    #[test]
+    fn test_apply_edits_uses_original_offsets() {
+        let edits = [TextEdit::new(4..5, "count"), TextEdit::insert(0, "// hi\n")];
+        let result = apply_edits(Path::new("a.rs"), "let x = 1;", &edits).unwrap();
+        assert_eq!(result, "// hi\nlet count = 1;");
+    }
+
+    #[test]
+    fn test_apply_edits_rejects_overlap_and_bad_ranges() {
+        let path = Path::new("a.rs");
+        let overlapping = [TextEdit::new(0..3, "a"), TextEdit::new(2..4, "b")];
+        assert_eq!(
+            apply_edits(path, "abcdef", &overlapping),
+            Err(EditError::OverlappingEdits(path.to_path_buf()))
+        );
+        assert_eq!(
+            apply_edits(path, "é", &[TextEdit::insert(1, "x")]),
+            Err(EditError::InvalidRange(path.to_path_buf(), 1..1))
+        );
+    }
+
+    #[test]
+    fn test_workspace_edit_spans_files() {
+        let mut docs = documents();
+        let mut edit = WorkspaceEdit::new();
+        edit.push("a.rs", TextEdit::new(4..5, "y"));
+        edit.push("b.rs", TextEdit::new(4..5, "y"));
+
+        edit.apply(&mut docs).unwrap();
+
+        assert_eq!(docs.0[Path::new("a.rs")], "let y = 1;");
+        assert_eq!(docs.0[Path::new("b.rs")], "use y;");
+    }
+
+    #[test]
+    fn test_failed_workspace_edit_changes_nothing() {
+        let mut docs = documents();
+        let mut edit = WorkspaceEdit::new();
+        edit.push("a.rs", TextEdit::new(4..5, "y"));
+        edit.push("missing.rs", TextEdit::insert(0, "x"));
+
+        assert_eq!(
+            edit.apply(&mut docs),
+            Err(EditError::UnknownDocument(PathBuf::from("missing.rs")))
+        );
+        assert_eq!(docs.0[Path::new("a.rs")], "let x = 1;");
+    }
+}
@@ -0,0 +1,124 @@
+//! Inline color and image swatches.
+//!
+//! CSS-like hex colors (`#rgb`, `#rgba`, `#rrggbb` and `#rrggbbaa`) and
+//! short `data:image/...` URIs in the buffer get a one-cell swatch drawn
+//! just before them: a block in the color itself, or an image marker. The
+//! swatches are [`Decoration`]s like any other, with the
+//! [`DecorationKind::ColorSwatch`] and [`DecorationKind::ImageSwatch`]
+//! kinds, so the editor draws them along with search matches.
+
+use ratatui::style::Color;
+
+use crate::window::{Decoration, DecorationKind};
+
+/// Longest data URI, in bytes, that gets a swatch; longer ones are usually
+/// pasted blobs rather than values worth marking.
+pub const MAX_DATA_URI_LEN: usize = 1024;
+
+/// Prefix of the data URIs that get a swatch.
+const DATA_URI_PREFIX: &str = "data:image/";
+
+/// Find the swatches of `line`, whose first byte is at `offset` in the text.
+///
+/// Hex colors must stand on their own: a `#` after a word character (as in
+/// `a#b`) or digits followed by more word characters are not colors, and
+/// all-digit values like `#123` are left alone since they are more often
+/// issue numbers than colors. A data URI runs to the next whitespace,
+/// quote or closing parenthesis.
+pub fn find_swatches(line: &str, offset: usize) -> Vec<Decoration> {
+    let bytes = line.as_bytes();
+    let mut swatches = Vec::new();
+    let mut index = 0;
+    while index < bytes.len() {
+        let preceded_by_word =
+            index > 0 && (bytes[index - 1].is_ascii_alphanumeric() || bytes[index - 1] == b'_');
+        if bytes[index] == b'#' && !preceded_by_word {
+            let digits = bytes[index + 1..]
+                .iter()
+                .take_while(|byte| byte.is_ascii_alphanumeric() || **byte == b'_')
+                .count();
+            let value = &line[index + 1..index + 1 + digits];
+            if let Some(color) = parse_hex_color(value) {
+                swatches.push(Decoration {
+                    range: offset + index..offset + index + 1 + digits,
+                    kind: DecorationKind::ColorSwatch(color),
+                });
+            }
+            index += 1 + digits;
+            continue;
+        }
+        if bytes[index] == b'd' && !preceded_by_word && line[index..].starts_with(DATA_URI_PREFIX) {
+            let len = line[index..]
+                .find(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | ')'))
+                .unwrap_or(line.len() - index);
+            if len <= MAX_DATA_URI_LEN {
+                swatches.push(Decoration {
+                    range: offset + index..offset + index + len,
+                    kind: DecorationKind::ImageSwatch,
+                });
+            }
+            index += len;
+            continue;
+        }
+        index += 1;
+    }
+    swatches
+}
+
+/// Parse the hex digits of a color, without the `#`. The alpha channel of
+/// the four- and eight-digit forms is ignored.
+fn parse_hex_color(value: &str) -> Option<Color> {
+    if !matches!(value.len(), 3 | 4 | 6 | 8)
+        || !value.bytes().all(|byte| byte.is_ascii_hexdigit())
+        || value.bytes().all(|byte| byte.is_ascii_digit())
+    {
+        return None;
+    }
+    let channel = |digits: &str| u8::from_str_radix(digits, 16).ok();
+    if value.len() <= 4 {
+        let short = |i: usize| channel(&value[i..=i]).map(|c| c * 0x11);
+        Some(Color::Rgb(short(0)?, short(1)?, short(2)?))
+    } else {
+        Some(Color::Rgb(
+            channel(&value[0..2])?,
+            channel(&value[2..4])?,
+            channel(&value[4..6])?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn swatches(line: &str) -> Vec<(std::ops::Range<usize>, DecorationKind)> {
+        find_swatches(line, 10)
+            .into_iter()
+            .map(|swatch| (swatch.range, swatch.kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_hex_colors_and_data_uris_get_swatches() {
+        assert_eq!(
+            swatches("color: #ff8000; background: #0af8;"),
+            vec![
+                (17..24, DecorationKind::ColorSwatch(Color::Rgb(255, 128, 0))),
+                (38..43, DecorationKind::ColorSwatch(Color::Rgb(0, 170, 255))),
+            ]
+        );
+        assert_eq!(
+            swatches("see #123, a#abc, #abcde, #badcafe1x, #zzz"),
+            vec![]
+        );
+        assert_eq!(
+            swatches("url(data:image/png;base64,iVBORw0K) data:text/plain,hi"),
+            vec![(14..44, DecorationKind::ImageSwatch)]
+        );
+        let long = format!(
+            "src=\"data:image/png;base64,{}\"",
+            "A".repeat(MAX_DATA_URI_LEN)
+        );
+        assert_eq!(swatches(&long), vec![]);
+    }
+}
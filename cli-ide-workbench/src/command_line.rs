@@ -0,0 +1,251 @@
+//! Ex-style commands typed on the command line.
+//!
+//! The command line opens with `:` in modal Normal mode, or with `Open
+//! Command Line` from the palette, and takes vim's short commands:
+//!
+//! | Command                | Effect                                          |
+//! |------------------------|-------------------------------------------------|
+//! | `:w` / `:write [path]` | Save the focused editor, or save it as `path`   |
+//! | `:e` / `:edit path`    | Open `path`, relative to the workspace root     |
+//! | `:set settings`        | Set buffer options, e.g. `:set nowrap indent=2` |
+//! | `:q` / `:quit`         | Quit                                            |
+//! | `:wq`                  | Save, then quit                                 |
+//! | `:sp` / `:vs`          | Split the editor down / right                   |
+//! | `:bn` / `:bp` / `:bd`  | Next tab / previous tab / close tab             |
+//! | `:<command ID>`        | Run any registered command                      |
+//!
+//! A command can be shortened to any prefix of its name as long as its
+//! abbreviation, so `:wri` is `:write`. [`parse`] turns a line into an
+//! [`ExCommand`] for the App to run and [`ExCompleter`] lists the lines
+//! `Tab` cycles through: command names, file paths after `:e` and `:w`, and
+//! setting names after `:set`.
+
+use std::path::PathBuf;
+
+use crate::buffer_options::OptionOverrides;
+use crate::completion::{Completer, PathCompleter, WordCompleter};
+
+/// Commands run through the command registry, as `(name, abbreviation,
+/// command ID)`.
+const ALIASES: &[(&str, &str, &str)] = &[
+    ("quit", "q", "workbench.action.quit"),
+    ("split", "sp", "workbench.action.splitEditorDown"),
+    ("vsplit", "vs", "workbench.action.splitEditorRight"),
+    ("bnext", "bn", "workbench.action.nextEditor"),
+    ("bprevious", "bp", "workbench.action.previousEditor"),
+    ("bdelete", "bd", "workbench.action.closeActiveEditor"),
+];
+
+/// Commands taking arguments, as `(name, abbreviation)`.
+const BUILTINS: &[(&str, &str)] = &[("write", "w"), ("wq", "wq"), ("edit", "e"), ("set", "se")];
+
+/// Settings completed after `:set`.
+const SETTINGS: &[&str] = &[
+    "indent=",
+    "line_length=",
+    "nowrap",
+    "spaces",
+    "tabs",
+    "wrap",
+];
+
+/// A parsed command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExCommand {
+    /// Run the registered command with this ID.
+    Run(String),
+    /// Open the file at the path, relative to the workspace root.
+    Edit(PathBuf),
+    /// Save the focused editor, as the path if there is one, and quit
+    /// afterwards if `quit` is set.
+    Write {
+        /// Path to save as, relative to the workspace root.
+        path: Option<PathBuf>,
+        /// Whether to quit after saving.
+        quit: bool,
+    },
+    /// Set these options on the focused editor's buffer.
+    Set(OptionOverrides),
+}
+
+/// Parse a command line, without its `:`.
+///
+/// The first word names the command and the rest is its argument. A word
+/// with a `.` in it is taken as a command ID.
+pub fn parse(line: &str) -> Result<ExCommand, String> {
+    let line = line.trim();
+    let (name, argument) = match line.split_once(char::is_whitespace) {
+        Some((name, argument)) => (name, argument.trim()),
+        None => (line, ""),
+    };
+    if name.is_empty() {
+        return Err("No command".to_string());
+    }
+    if name.contains('.') {
+        return no_argument(name, argument).map(|()| ExCommand::Run(name.to_string()));
+    }
+    let path = (!argument.is_empty()).then(|| PathBuf::from(argument));
+    match resolve(name) {
+        Some("write") => Ok(ExCommand::Write { path, quit: false }),
+        Some("wq") => Ok(ExCommand::Write { path, quit: true }),
+        Some("edit") => path
+            .map(ExCommand::Edit)
+            .ok_or_else(|| "`edit` needs a file".to_string()),
+        Some("set") if argument.is_empty() => Err("`set` needs a setting".to_string()),
+        Some("set") => OptionOverrides::parse_settings(argument).map(ExCommand::Set),
+        Some(full) => {
+            let (_, _, id) = ALIASES
+                .iter()
+                .find(|(alias, _, _)| *alias == full)
+                .expect("resolve returns known names");
+            no_argument(full, argument).map(|()| ExCommand::Run(id.to_string()))
+        }
+        None => Err(format!("Not an editor command: {name}")),
+    }
+}
+
+/// The full name of the command `name` abbreviates, if any.
+fn resolve(name: &str) -> Option<&'static str> {
+    let aliases = ALIASES.iter().map(|&(full, short, _)| (full, short));
+    BUILTINS
+        .iter()
+        .copied()
+        .chain(aliases)
+        .find(|(full, short)| full.starts_with(name) && name.starts_with(short))
+        .map(|(full, _)| full)
+}
+
+/// Reject an argument given to a command that takes none.
+fn no_argument(name: &str, argument: &str) -> Result<(), String> {
+    if argument.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("`{name}` takes no argument"))
+    }
+}
+
+/// Completes whole command lines.
+///
+/// The first word completes to command names and the IDs of the registered
+/// commands. The last word after `edit` and `write` completes to the files
+/// and folders in the workspace root, and after `set` to setting names.
+pub struct ExCompleter {
+    /// Command names and IDs.
+    names: WordCompleter,
+    /// Paths in the workspace root.
+    paths: PathCompleter,
+    /// Setting names.
+    settings: WordCompleter,
+}
+
+impl ExCompleter {
+    /// Complete `commands`, the IDs of the registered commands, and paths
+    /// in `root`.
+    pub fn new(commands: &[String], root: impl Into<PathBuf>) -> Self {
+        let names = BUILTINS
+            .iter()
+            .map(|&(full, _)| full)
+            .chain(ALIASES.iter().map(|&(full, _, _)| full))
+            .chain(commands.iter().map(String::as_str));
+        Self {
+            names: WordCompleter::new(names),
+            paths: PathCompleter::new(root),
+            settings: WordCompleter::new(SETTINGS.iter().copied()),
+        }
+    }
+}
+
+impl Completer for ExCompleter {
+    fn complete(&self, line: &str) -> Vec<String> {
+        let Some((name, argument)) = line.split_once(' ') else {
+            return self.names.complete(line);
+        };
+        let word_start =
+            line.len() - argument.len() + argument.rfind(' ').map_or(0, |index| index + 1);
+        let (head, word) = line.split_at(word_start);
+        let words = match resolve(name) {
+            Some("edit" | "write" | "wq") => self.paths.complete(word),
+            Some("set") => self.settings.complete(word),
+            _ => Vec::new(),
+        };
+        words
+            .into_iter()
+            .map(|word| format!("{head}{word}"))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cli_ide_base::test_support::{temp_dir, Cleanup};
+
+    #[test]
+    fn test_parse_commands_and_abbreviations() {
+        let write = |path: Option<&str>, quit| ExCommand::Write {
+            path: path.map(PathBuf::from),
+            quit,
+        };
+        assert_eq!(parse("w"), Ok(write(None, false)));
+        assert_eq!(parse("wri out.txt"), Ok(write(Some("out.txt"), false)));
+        assert_eq!(parse("wq"), Ok(write(None, true)));
+        assert_eq!(
+            parse("e src/main.rs"),
+            Ok(ExCommand::Edit("src/main.rs".into()))
+        );
+        assert_eq!(
+            parse(" q "),
+            Ok(ExCommand::Run("workbench.action.quit".to_string()))
+        );
+        assert_eq!(
+            parse("vs"),
+            Ok(ExCommand::Run(
+                "workbench.action.splitEditorRight".to_string()
+            ))
+        );
+        assert_eq!(
+            parse("workbench.action.toggleFocus"),
+            Ok(ExCommand::Run("workbench.action.toggleFocus".to_string()))
+        );
+        let Ok(ExCommand::Set(layer)) = parse("set nowrap indent=2") else {
+            panic!("`set` is parsed");
+        };
+        assert_eq!(layer.wrap, Some(false));
+        assert_eq!(layer.indent_width, Some(2));
+    }
+
+    #[test]
+    fn test_invalid_command_lines_are_reported() {
+        assert_eq!(
+            parse("frob"),
+            Err("Not an editor command: frob".to_string())
+        );
+        assert_eq!(parse("v"), Err("Not an editor command: v".to_string()));
+        assert_eq!(parse("e"), Err("`edit` needs a file".to_string()));
+        assert_eq!(parse("q now"), Err("`quit` takes no argument".to_string()));
+        assert_eq!(parse("set"), Err("`set` needs a setting".to_string()));
+        assert_eq!(parse("set bold"), Err("unknown setting `bold`".to_string()));
+    }
+
+    #[test]
+    fn test_complete_names_paths_and_settings() {
+        let commands = vec!["workbench.action.quit".to_string()];
+        let completer = ExCompleter::new(&commands, "/");
+        assert_eq!(completer.complete("s"), ["set", "split"]);
+        assert_eq!(completer.complete("work"), ["workbench.action.quit"]);
+        assert_eq!(completer.complete("set wrap no"), ["set wrap nowrap"]);
+
+        let root = temp_dir("ex");
+        let _cleanup = Cleanup::new([&root]);
+        std::fs::create_dir(root.join("src")).unwrap();
+        std::fs::write(root.join("src/main.rs"), "").unwrap();
+        std::fs::write(root.join("setup.sh"), "").unwrap();
+        std::fs::write(root.join(".secret"), "").unwrap();
+        let completer = ExCompleter::new(&commands, &root);
+        assert_eq!(completer.complete("e s"), ["e setup.sh", "e src/"]);
+        assert_eq!(completer.complete("e src/"), ["e src/main.rs"]);
+        assert_eq!(completer.complete("e "), ["e setup.sh", "e src/"]);
+        assert_eq!(completer.complete("e ."), ["e .secret"]);
+        assert!(completer.complete("q ").is_empty());
+    }
+}
@@ -0,0 +1,288 @@
+//! Completion of typed input: paths, command names, setting keys and branch
+//! names.
+//!
+//! A [`Completer`] lists the values completing what has been typed, e.g. a
+//! [`PathCompleter`] the files in a folder or a [`WordCompleter`] command IDs
+//! or branch names. An input keeps a [`Completion`]: `Tab` steps through the
+//! candidates with [`Completion::next`], and [`render_suggestions`] draws
+//! them in a small popup while there are several. The command line, input
+//! boxes (prompt dialogs included) and the command palette share them.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Borders, Clear, Paragraph};
+use unicode_width::UnicodeWidthStr;
+
+use crate::theme::Theme;
+
+/// Most candidates the popup shows at once.
+const MAX_SUGGESTIONS: usize = 8;
+
+/// Lists the values completing typed input.
+pub trait Completer {
+    /// The whole values completing `input`, in the order `Tab` cycles
+    /// through them.
+    fn complete(&self, input: &str) -> Vec<String>;
+}
+
+impl<F: Fn(&str) -> Vec<String>> Completer for F {
+    fn complete(&self, input: &str) -> Vec<String> {
+        self(input)
+    }
+}
+
+/// Completes input to one of a list of words, such as command IDs, setting
+/// keys or branch names.
+#[derive(Debug, Clone, Default)]
+pub struct WordCompleter {
+    /// The words, sorted and without duplicates.
+    words: Vec<String>,
+}
+
+impl WordCompleter {
+    /// Complete to `words`.
+    pub fn new<I, S>(words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut words: Vec<String> = words.into_iter().map(Into::into).collect();
+        words.sort();
+        words.dedup();
+        Self { words }
+    }
+}
+
+impl Completer for WordCompleter {
+    fn complete(&self, input: &str) -> Vec<String> {
+        self.words
+            .iter()
+            .filter(|word| word.starts_with(input))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Completes input to the paths of files and folders, relative to a folder
+/// unless the input is absolute.
+///
+/// Folders end in `/`, so the next `Tab` completes inside them. Hidden
+/// entries are only listed once the input names them.
+#[derive(Debug, Clone)]
+pub struct PathCompleter {
+    /// Folder relative paths start in.
+    root: PathBuf,
+}
+
+impl PathCompleter {
+    /// Complete paths relative to `root`.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl Completer for PathCompleter {
+    fn complete(&self, input: &str) -> Vec<String> {
+        paths(&self.root, input)
+    }
+}
+
+/// The paths in `root` starting with `prefix`, sorted, folders ending in
+/// `/`.
+fn paths(root: &Path, prefix: &str) -> Vec<String> {
+    let (folder, start) = match prefix.rfind('/') {
+        Some(index) => prefix.split_at(index + 1),
+        None => ("", prefix),
+    };
+    let Ok(entries) = fs::read_dir(root.join(folder)) else {
+        return Vec::new();
+    };
+    let mut candidates: Vec<String> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            if !name.starts_with(start) || (name.starts_with('.') && !start.starts_with('.')) {
+                return None;
+            }
+            let slash = if entry.path().is_dir() { "/" } else { "" };
+            Some(format!("{folder}{name}{slash}"))
+        })
+        .collect();
+    candidates.sort();
+    candidates
+}
+
+/// `Tab` cycling through the completions of an input.
+///
+/// The first `Tab` lists the candidates completing the input as typed;
+/// later ones step through them until the input is edited and
+/// [`reset`](Completion::reset) is called.
+#[derive(Debug, Clone, Default)]
+pub struct Completion {
+    /// Values `Tab` cycles through, while there are several.
+    candidates: Vec<String>,
+    /// Index of the value shown.
+    index: Option<usize>,
+}
+
+impl Completion {
+    /// The value the input completes to next, if any.
+    ///
+    /// A single candidate is taken as typed, so the next `Tab` completes
+    /// from it, e.g. into a folder.
+    pub fn next(&mut self, input: &str, completer: &dyn Completer) -> Option<String> {
+        if self.candidates.is_empty() {
+            self.candidates = completer.complete(input);
+        }
+        let next = self
+            .index
+            .map_or(0, |index| (index + 1) % self.candidates.len().max(1));
+        let value = self.candidates.get(next)?.clone();
+        if self.candidates.len() == 1 {
+            self.reset();
+        } else {
+            self.index = Some(next);
+        }
+        Some(value)
+    }
+
+    /// Forget the candidates, after the input was edited.
+    pub fn reset(&mut self) {
+        self.candidates.clear();
+        self.index = None;
+    }
+
+    /// Get the values `Tab` cycles through, while there are several.
+    pub fn candidates(&self) -> &[String] {
+        &self.candidates
+    }
+
+    /// Get the index of the value shown among the candidates.
+    pub fn selected(&self) -> Option<usize> {
+        self.index
+    }
+}
+
+/// The part of `candidate` the popup shows: the text after its last space
+/// or `/`, keeping a folder's trailing `/`.
+pub fn label(candidate: &str) -> &str {
+    let name = candidate.strip_suffix('/').unwrap_or(candidate);
+    let start = name.rfind([' ', '/']).map_or(0, |index| index + 1);
+    &candidate[start..]
+}
+
+/// Draw the candidates of `completion`, while there are several, in a
+/// popup starting at the left of `anchor`: below it, or above it when
+/// `above` is set or there is no room below. Each candidate is shown as
+/// `label` returns it, e.g. with [`label`].
+pub fn render_suggestions(
+    frame: &mut Frame,
+    anchor: Rect,
+    above: bool,
+    completion: &Completion,
+    label: fn(&str) -> &str,
+    theme: &Theme,
+) {
+    let candidates = completion.candidates();
+    if candidates.len() < 2 {
+        return;
+    }
+    let bounds = frame.area();
+    let shown = candidates.len().min(MAX_SUGGESTIONS);
+    let height = u16::try_from(shown).unwrap_or(u16::MAX) + 2;
+    let widest = candidates
+        .iter()
+        .map(|candidate| label(candidate).width())
+        .max()
+        .unwrap_or(0);
+    let width = u16::try_from(widest)
+        .unwrap_or(u16::MAX)
+        .saturating_add(4)
+        .min(bounds.right().saturating_sub(anchor.x));
+    let below = bounds.bottom().saturating_sub(anchor.bottom());
+    let y = if !above && below >= height {
+        anchor.bottom()
+    } else {
+        anchor.y.saturating_sub(height).max(bounds.y)
+    };
+    let area = Rect {
+        x: anchor.x,
+        y,
+        width,
+        height: height.min(bounds.bottom().saturating_sub(y)),
+    };
+    if area.width < 3 || area.height < 3 {
+        return;
+    }
+
+    // Scroll so the shown value is visible
+    let selected = completion.selected().unwrap_or(0);
+    let first = selected.saturating_sub(shown - 1);
+    let lines: Vec<Line> = candidates
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(shown)
+        .map(|(index, candidate)| {
+            let style = if completion.selected() == Some(index) {
+                theme.selection
+            } else {
+                theme.text
+            };
+            Line::styled(format!(" {} ", label(candidate)), style)
+        })
+        .collect();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(theme.border_style(false));
+    frame.render_widget(Clear, area);
+    frame.render_widget(Paragraph::new(lines).style(theme.text).block(block), area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cli_ide_base::test_support::{temp_dir, Cleanup};
+
+    #[test]
+    fn test_tab_cycles_candidates_and_takes_a_single_one_as_typed() {
+        let words = WordCompleter::new(["main", "master", "feature", "main"]);
+        let mut completion = Completion::default();
+        assert_eq!(completion.next("ma", &words), Some("main".to_string()));
+        assert_eq!(completion.candidates(), ["main", "master"]);
+        assert_eq!(completion.next("main", &words), Some("master".to_string()));
+        assert_eq!(completion.next("master", &words), Some("main".to_string()));
+        assert_eq!(completion.selected(), Some(0));
+
+        completion.reset();
+        assert_eq!(completion.next("f", &words), Some("feature".to_string()));
+        assert!(completion.candidates().is_empty(), "nothing left to cycle");
+        assert_eq!(completion.next("x", &words), None);
+    }
+
+    #[test]
+    fn test_paths_complete_into_folders() {
+        let root = temp_dir("complete");
+        let _cleanup = Cleanup::new([&root]);
+        fs::create_dir(root.join("src")).unwrap();
+        fs::write(root.join("src/main.rs"), "").unwrap();
+        fs::write(root.join("setup.sh"), "").unwrap();
+        fs::write(root.join(".secret"), "").unwrap();
+        let paths = PathCompleter::new(&root);
+        assert_eq!(paths.complete("s"), ["setup.sh", "src/"]);
+        assert_eq!(paths.complete("src/"), ["src/main.rs"]);
+        assert_eq!(paths.complete("."), [".secret"]);
+        let absolute = format!("{}/se", root.display());
+        assert_eq!(
+            paths.complete(&absolute),
+            [format!("{}/setup.sh", root.display())]
+        );
+
+        assert_eq!(label("e src/main.rs"), "main.rs");
+        assert_eq!(label("src/"), "src/");
+        assert_eq!(label("set"), "set");
+    }
+}
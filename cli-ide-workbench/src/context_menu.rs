@@ -0,0 +1,336 @@
+//! Context menus offered by right-clicking, or pressing `M`, in a pane.
+//!
+//! [`ContextMenus`] lists, for each [`MenuLocation`], the commands its menu
+//! offers by identifier. The titles come from the command registry when the
+//! menu opens, so a menu never offers a command that is not registered, and
+//! each item's [`MenuWhen`] condition hides it where it does not apply, e.g.
+//! `Copy` without a selection. This mirrors VS Code's `MenuRegistry` with
+//! its `when` clauses.
+
+use std::collections::BTreeMap;
+
+use cli_ide_platform::command::command_service::CommandInfo;
+
+/// Where a context menu opens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MenuLocation {
+    /// The text of an editor.
+    Editor,
+    /// An editor's tab strip.
+    EditorTab,
+    /// The file explorer.
+    Explorer,
+    /// The terminal pane.
+    Terminal,
+}
+
+/// Condition under which a menu item is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum MenuWhen {
+    /// The item is always shown.
+    #[default]
+    Always,
+    /// The editor's selection is not empty.
+    EditorHasSelection,
+    /// The editor's document is a file on disk.
+    EditorHasPath,
+    /// The editor has more than one tab.
+    MultipleTabs,
+}
+
+/// State of the workbench the [`MenuWhen`] conditions are checked against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MenuContext {
+    /// Whether the target editor's selection is not empty.
+    pub has_selection: bool,
+    /// Whether the target editor's document has a path.
+    pub has_path: bool,
+    /// Number of tabs in the target editor.
+    pub tabs: usize,
+}
+
+impl MenuWhen {
+    /// Check whether the condition holds in `context`.
+    pub fn holds(self, context: &MenuContext) -> bool {
+        match self {
+            MenuWhen::Always => true,
+            MenuWhen::EditorHasSelection => context.has_selection,
+            MenuWhen::EditorHasPath => context.has_path,
+            MenuWhen::MultipleTabs => context.tabs > 1,
+        }
+    }
+}
+
+/// A command offered by a context menu.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MenuItem {
+    /// Identifier of the command run when the item is chosen.
+    pub command: String,
+    /// Condition under which the item is shown.
+    pub when: MenuWhen,
+}
+
+impl MenuItem {
+    /// Create an item running `command` that is always shown.
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            command: command.into(),
+            when: MenuWhen::Always,
+        }
+    }
+
+    /// Show the item only while `when` holds.
+    pub fn when(mut self, when: MenuWhen) -> Self {
+        self.when = when;
+        self
+    }
+}
+
+/// Items of the built-in menus, in the order they are shown.
+const BUILTIN_ITEMS: &[(MenuLocation, &str, MenuWhen)] = &[
+    (
+        MenuLocation::Editor,
+        "editor.action.clipboardCutAction",
+        MenuWhen::EditorHasSelection,
+    ),
+    (
+        MenuLocation::Editor,
+        "editor.action.clipboardCopyAction",
+        MenuWhen::EditorHasSelection,
+    ),
+    (
+        MenuLocation::Editor,
+        "editor.action.clipboardPasteAction",
+        MenuWhen::Always,
+    ),
+    (
+        MenuLocation::Editor,
+        "editor.action.quickFix",
+        MenuWhen::EditorHasPath,
+    ),
+    (
+        MenuLocation::Editor,
+        "editor.action.peekDefinition",
+        MenuWhen::EditorHasPath,
+    ),
+    (
+        MenuLocation::Editor,
+        "editor.action.rename",
+        MenuWhen::EditorHasPath,
+    ),
+    (
+        MenuLocation::Editor,
+        "editor.action.expandBracketSelection",
+        MenuWhen::Always,
+    ),
+    (MenuLocation::Editor, "actions.find", MenuWhen::Always),
+    (
+        MenuLocation::Editor,
+        "workbench.action.showCommands",
+        MenuWhen::Always,
+    ),
+    (
+        MenuLocation::EditorTab,
+        "workbench.action.closeActiveEditor",
+        MenuWhen::Always,
+    ),
+    (
+        MenuLocation::EditorTab,
+        "workbench.action.closeOtherEditors",
+        MenuWhen::MultipleTabs,
+    ),
+    (
+        MenuLocation::EditorTab,
+        "workbench.action.togglePinnedEditor",
+        MenuWhen::Always,
+    ),
+    (
+        MenuLocation::EditorTab,
+        "workbench.action.files.save",
+        MenuWhen::Always,
+    ),
+    (
+        MenuLocation::EditorTab,
+        "workbench.action.splitEditorRight",
+        MenuWhen::Always,
+    ),
+    (
+        MenuLocation::EditorTab,
+        "workbench.action.splitEditorDown",
+        MenuWhen::Always,
+    ),
+    (
+        MenuLocation::EditorTab,
+        "workbench.action.nextEditor",
+        MenuWhen::MultipleTabs,
+    ),
+    (
+        MenuLocation::EditorTab,
+        "workbench.action.previousEditor",
+        MenuWhen::MultipleTabs,
+    ),
+    (MenuLocation::Explorer, "explorer.newFile", MenuWhen::Always),
+    (
+        MenuLocation::Explorer,
+        "explorer.newFolder",
+        MenuWhen::Always,
+    ),
+    (MenuLocation::Explorer, "renameFile", MenuWhen::Always),
+    (MenuLocation::Explorer, "deleteFile", MenuWhen::Always),
+    (
+        MenuLocation::Explorer,
+        "explorer.moveFiles",
+        MenuWhen::Always,
+    ),
+    (
+        MenuLocation::Explorer,
+        "workbench.files.action.refreshFilesExplorer",
+        MenuWhen::Always,
+    ),
+    (
+        MenuLocation::Terminal,
+        "editor.action.clipboardPasteAction",
+        MenuWhen::Always,
+    ),
+    (
+        MenuLocation::Terminal,
+        "workbench.action.cancelProgress",
+        MenuWhen::Always,
+    ),
+    (
+        MenuLocation::Terminal,
+        "workbench.action.toggleOutput",
+        MenuWhen::Always,
+    ),
+];
+
+/// The items of every context menu.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ContextMenus {
+    /// Items of each menu, in the order they are shown.
+    items: BTreeMap<MenuLocation, Vec<MenuItem>>,
+}
+
+impl ContextMenus {
+    /// Create menus without any items.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create the built-in menus.
+    pub fn builtin() -> Self {
+        let mut menus = Self::new();
+        for &(location, command, when) in BUILTIN_ITEMS {
+            menus.add(location, MenuItem::new(command).when(when));
+        }
+        menus
+    }
+
+    /// Append `item` to the menu at `location`, replacing an item running
+    /// the same command.
+    pub fn add(&mut self, location: MenuLocation, item: MenuItem) {
+        let items = self.items.entry(location).or_default();
+        match items.iter_mut().find(|old| old.command == item.command) {
+            Some(old) => *old = item,
+            None => items.push(item),
+        }
+    }
+
+    /// Remove the item running `command` from the menu at `location`,
+    /// returning whether it was present.
+    pub fn remove(&mut self, location: MenuLocation, command: &str) -> bool {
+        let Some(items) = self.items.get_mut(&location) else {
+            return false;
+        };
+        let before = items.len();
+        items.retain(|item| item.command != command);
+        items.len() != before
+    }
+
+    /// Get the items of the menu at `location`.
+    pub fn items(&self, location: MenuLocation) -> &[MenuItem] {
+        self.items.get(&location).map_or(&[], Vec::as_slice)
+    }
+
+    /// Get the commands the menu at `location` shows in `context`, with
+    /// their titles from `commands`, the registered commands.
+    ///
+    /// Items whose condition does not hold or whose command is not
+    /// registered are left out.
+    pub fn resolve(
+        &self,
+        location: MenuLocation,
+        context: &MenuContext,
+        commands: &[CommandInfo],
+    ) -> Vec<CommandInfo> {
+        self.items(location)
+            .iter()
+            .filter(|item| item.when.holds(context))
+            .filter_map(|item| {
+                commands
+                    .iter()
+                    .find(|command| command.id == item.command)
+                    .cloned()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(id: &str, title: &str) -> CommandInfo {
+        CommandInfo {
+            id: id.to_string(),
+            title: title.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_resolve_filters_by_condition_and_registry() {
+        let mut menus = ContextMenus::new();
+        menus.add(
+            MenuLocation::Editor,
+            MenuItem::new("copy").when(MenuWhen::EditorHasSelection),
+        );
+        menus.add(MenuLocation::Editor, MenuItem::new("paste"));
+        menus.add(MenuLocation::Editor, MenuItem::new("missing"));
+        let commands = [command("copy", "Copy"), command("paste", "Paste")];
+
+        let titles = |context: &MenuContext| -> Vec<String> {
+            menus
+                .resolve(MenuLocation::Editor, context, &commands)
+                .into_iter()
+                .map(|command| command.title)
+                .collect()
+        };
+        assert_eq!(titles(&MenuContext::default()), ["Paste"]);
+        let selected = MenuContext {
+            has_selection: true,
+            ..MenuContext::default()
+        };
+        assert_eq!(titles(&selected), ["Copy", "Paste"]);
+        assert!(menus
+            .resolve(MenuLocation::Terminal, &selected, &commands)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_add_replaces_and_remove_drops_items() {
+        let mut menus = ContextMenus::builtin();
+        let explorer = menus.items(MenuLocation::Explorer).len();
+        menus.add(
+            MenuLocation::Explorer,
+            MenuItem::new("explorer.newFile").when(MenuWhen::EditorHasPath),
+        );
+        assert_eq!(menus.items(MenuLocation::Explorer).len(), explorer);
+        assert_eq!(
+            menus.items(MenuLocation::Explorer)[0].when,
+            MenuWhen::EditorHasPath
+        );
+        assert!(menus.remove(MenuLocation::Explorer, "explorer.newFile"));
+        assert!(!menus.remove(MenuLocation::Explorer, "explorer.newFile"));
+        assert_eq!(menus.items(MenuLocation::Explorer).len(), explorer - 1);
+    }
+}
@@ -0,0 +1,34 @@
+//! Go-to/peek definition support.
+//!
+//! A [`DefinitionProvider`] (typically backed by a language server's
+//! `textDocument/definition`) resolves the symbol at an offset to the
+//! [`Location`] where it is defined.
+
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// A range within a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    /// Path of the document.
+    pub path: PathBuf,
+    /// Byte range within the document.
+    pub range: Range<usize>,
+}
+
+impl Location {
+    /// Create a location.
+    pub fn new(path: impl Into<PathBuf>, range: Range<usize>) -> Self {
+        Self {
+            path: path.into(),
+            range,
+        }
+    }
+}
+
+/// Resolves the definition of the symbol at an offset.
+pub trait DefinitionProvider {
+    /// Return the definition of the symbol at `offset` in the document at
+    /// `path`, or `None` if there is no symbol or it cannot be resolved.
+    fn definition(&self, path: &Path, text: &str, offset: usize) -> Option<Location>;
+}
@@ -0,0 +1,72 @@
+//! UI density settings.
+//!
+//! Terminal cells have a fixed size, so a pane cannot be zoomed the way a
+//! graphical editor scales its font. Instead a pane can be drawn compact:
+//! with only its top border, which keeps the title, and without gutters,
+//! underlined problems or a scrollbar, leaving more cells for content. The
+//! [`Density`] setting decides how panes are drawn unless a pane's compact
+//! mode was toggled on its own.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// How panes are drawn by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Density {
+    /// Panes have all their borders and decorations.
+    #[default]
+    Comfortable,
+    /// Panes are drawn compact.
+    Compact,
+}
+
+impl Density {
+    /// Check whether panes are drawn compact.
+    pub fn is_compact(self) -> bool {
+        self == Density::Compact
+    }
+}
+
+/// Error returned when parsing an unknown density.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDensityError(String);
+
+impl fmt::Display for ParseDensityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown density '{}' (expected 'comfortable' or 'compact')",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseDensityError {}
+
+impl FromStr for Density {
+    type Err = ParseDensityError;
+
+    /// Parse a setting value: `comfortable` or `compact`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "comfortable" => Ok(Density::Comfortable),
+            "compact" => Ok(Density::Compact),
+            _ => Err(ParseDensityError(value.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_densities() {
+        assert_eq!("comfortable".parse(), Ok(Density::Comfortable));
+        assert_eq!("compact".parse(), Ok(Density::Compact));
+        assert!(Density::Compact.is_compact());
+
+        let error = "cozy".parse::<Density>().unwrap_err();
+        assert!(error.to_string().contains("'cozy'"));
+    }
+}
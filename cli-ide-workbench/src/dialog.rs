@@ -0,0 +1,139 @@
+//! Modal dialogs asking the user something.
+//!
+//! A [`Dialog`] is a confirmation ([`ConfirmWindow`]), a one-line prompt
+//! ([`InputBoxWindow`]) or a filterable list ([`QuickPickWindow`]) drawn
+//! over the workbench. While one is open it captures every key, and it
+//! closes with a [`DialogResult`]: the answer, or
+//! [`DialogResult::Cancelled`] when the user pressed `Esc`. The App keeps
+//! what the answer is for next to the open dialog and acts on the result,
+//! so a feature needing an answer (e.g. "save before quit?") only opens a
+//! dialog and handles its result.
+
+use ratatui::prelude::*;
+
+use crate::input::AppKey;
+use crate::theme::Theme;
+use crate::window::{
+    ConfirmOutcome, ConfirmWindow, InputBoxWindow, InputOutcome, QuickPickOutcome, QuickPickWindow,
+    Window, INPUT_BOX_HEIGHT,
+};
+
+/// How a dialog was closed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DialogResult {
+    /// A confirmation was answered yes.
+    Yes,
+    /// A confirmation was answered no.
+    No,
+    /// A prompt was accepted with this text.
+    Text(String),
+    /// The item at this index into a pick's items was chosen.
+    Picked(usize),
+    /// The dialog was dismissed without an answer.
+    Cancelled,
+}
+
+/// A modal question.
+pub enum Dialog {
+    /// A yes/no question.
+    Confirm(Box<ConfirmWindow>),
+    /// A request for a line of text.
+    Prompt(Box<InputBoxWindow>),
+    /// A choice from a filterable list.
+    Pick(QuickPickWindow),
+}
+
+impl Dialog {
+    /// Handle a key press, returning the result once the dialog closes.
+    pub fn handle_key(&mut self, key: AppKey) -> Option<DialogResult> {
+        match self {
+            Dialog::Confirm(confirm) => match confirm.handle_key(key) {
+                ConfirmOutcome::Pending => None,
+                ConfirmOutcome::Yes => Some(DialogResult::Yes),
+                ConfirmOutcome::No => Some(DialogResult::No),
+                ConfirmOutcome::Cancel => Some(DialogResult::Cancelled),
+            },
+            Dialog::Prompt(input) => match input.handle_key(key) {
+                InputOutcome::Pending => None,
+                InputOutcome::Accept(text) => Some(DialogResult::Text(text)),
+                InputOutcome::Dismissed => Some(DialogResult::Cancelled),
+            },
+            Dialog::Pick(picker) => match picker.handle_key(key) {
+                QuickPickOutcome::Pending => None,
+                QuickPickOutcome::Accept(index) => Some(DialogResult::Picked(index)),
+                QuickPickOutcome::Dismissed => Some(DialogResult::Cancelled),
+            },
+        }
+    }
+
+    /// Rows the dialog needs, including its border, at most `available`.
+    pub fn preferred_height(&self, available: u16) -> u16 {
+        match self {
+            Dialog::Confirm(confirm) => confirm.preferred_height(),
+            Dialog::Prompt(_) => INPUT_BOX_HEIGHT,
+            Dialog::Pick(_) => available,
+        }
+        .min(available)
+    }
+
+    /// Get the dialog as a window, to draw it.
+    fn window_mut(&mut self) -> &mut dyn Window {
+        match self {
+            Dialog::Confirm(confirm) => confirm.as_mut(),
+            Dialog::Prompt(input) => input.as_mut(),
+            Dialog::Pick(picker) => picker,
+        }
+    }
+}
+
+impl Window for Dialog {
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.window_mut().render(frame, area);
+    }
+
+    fn render_with_focus(&mut self, frame: &mut Frame, area: Rect, focused: bool) {
+        self.window_mut().render_with_focus(frame, area, focused);
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.window_mut().set_theme(theme);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_each_kind_of_dialog_closes_with_its_answer() {
+        let mut confirm = Dialog::Confirm(Box::new(ConfirmWindow::new(
+            "Save?",
+            vec!["a.rs".to_string()],
+        )));
+        assert_eq!(confirm.handle_key(AppKey::Char('x')), None);
+        assert_eq!(
+            confirm.handle_key(AppKey::Char('n')),
+            Some(DialogResult::No)
+        );
+        assert_eq!(confirm.handle_key(AppKey::Enter), Some(DialogResult::Yes));
+        assert_eq!(confirm.preferred_height(20), 5);
+
+        let mut prompt = Dialog::Prompt(Box::new(InputBoxWindow::new("Name", "")));
+        assert_eq!(prompt.handle_key(AppKey::Char('q')), None);
+        assert_eq!(
+            prompt.handle_key(AppKey::Enter),
+            Some(DialogResult::Text("q".to_string()))
+        );
+
+        let mut pick = Dialog::Pick(QuickPickWindow::new(
+            "Branch",
+            vec!["main".to_string(), "topic".to_string()],
+        ));
+        assert_eq!(pick.handle_key(AppKey::Char('t')), None);
+        assert_eq!(
+            pick.handle_key(AppKey::Enter),
+            Some(DialogResult::Picked(1))
+        );
+        assert_eq!(pick.handle_key(AppKey::Esc), Some(DialogResult::Cancelled));
+    }
+}
@@ -0,0 +1,151 @@
+//! Links in documents.
+//!
+//! A [`DocumentLinkProvider`] (typically backed by a language server's
+//! `textDocument/documentLink`) finds the links of a document. Without one,
+//! [`detect_links`] finds `http://` and `https://` URLs and relative file
+//! paths starting with `./` or `../`. The editor underlines links, and
+//! `Open Link` opens the one under the cursor: URLs in the browser, files
+//! in the editor.
+
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Schemes of the URLs [`detect_links`] finds.
+const URL_SCHEMES: [&str; 2] = ["https://", "http://"];
+
+/// Prefixes of the relative paths [`detect_links`] finds.
+const PATH_PREFIXES: [&str; 2] = ["./", "../"];
+
+/// Characters that trail a link in prose rather than belong to it.
+const TRAILING_PUNCTUATION: &[char] = &['.', ',', ';', ':', '!', '?'];
+
+/// What a link points at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkTarget {
+    /// A URL, opened in the browser.
+    Url(String),
+    /// A file, opened in the editor; a relative path is relative to the
+    /// directory of the document holding the link.
+    File(PathBuf),
+}
+
+/// A link in a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentLink {
+    /// Byte range of the link in the document.
+    pub range: Range<usize>,
+    /// What the link points at.
+    pub target: LinkTarget,
+}
+
+/// Finds the links of a document.
+pub trait DocumentLinkProvider {
+    /// Return the links of the document at `path` with `text`, in order.
+    fn document_links(&self, path: &Path, text: &str) -> Vec<DocumentLink>;
+}
+
+/// Find the URLs and relative file paths of `line`, whose first byte is at
+/// `offset` in the text.
+///
+/// A link starts after a character that is not part of a word and runs to
+/// the next whitespace, quote or angle bracket. Trailing punctuation, and
+/// a closing parenthesis without an opening one in the link, are left out,
+/// so links in prose and in Markdown's `[text](url)` end where expected.
+pub fn detect_links(line: &str, offset: usize) -> Vec<DocumentLink> {
+    let mut links = Vec::new();
+    let mut index = 0;
+    while index < line.len() {
+        let rest = &line[index..];
+        let starts_word = line[..index]
+            .chars()
+            .next_back()
+            .is_none_or(|c| !(c.is_alphanumeric() || matches!(c, '_' | '.' | '/')));
+        let is_url = URL_SCHEMES.iter().any(|scheme| rest.starts_with(scheme));
+        let is_path = PATH_PREFIXES.iter().any(|prefix| rest.starts_with(prefix));
+        if starts_word && (is_url || is_path) {
+            let len = link_len(rest);
+            let prefix_len = rest.find('/').map_or(0, |slash| slash + 1);
+            if len > prefix_len + usize::from(is_url) {
+                let text = &rest[..len];
+                links.push(DocumentLink {
+                    range: offset + index..offset + index + len,
+                    target: if is_url {
+                        LinkTarget::Url(text.to_string())
+                    } else {
+                        LinkTarget::File(PathBuf::from(text))
+                    },
+                });
+            }
+            index += len.max(1);
+            continue;
+        }
+        index += rest.chars().next().map_or(1, char::len_utf8);
+    }
+    links
+}
+
+/// Length in bytes of the link at the start of `text`.
+fn link_len(text: &str) -> usize {
+    let end = text
+        .find(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '`' | '<' | '>'))
+        .unwrap_or(text.len());
+    let mut link = &text[..end];
+    loop {
+        link = link.trim_end_matches(TRAILING_PUNCTUATION);
+        if link.ends_with(')') && link.matches('(').count() < link.matches(')').count() {
+            link = &link[..link.len() - 1];
+        } else {
+            return link.len();
+        }
+    }
+}
+
+/// Find the links of every line of `text`.
+pub fn detect_document_links(text: &str) -> Vec<DocumentLink> {
+    let mut links = Vec::new();
+    let mut line_start = 0;
+    for line in text.split('\n') {
+        links.extend(detect_links(line, line_start));
+        line_start += line.len() + 1;
+    }
+    links
+}
+
+/// The link of `links` under `offset`, including the offset just past its
+/// end, where the cursor is after typing it.
+pub fn link_at(links: &[DocumentLink], offset: usize) -> Option<&DocumentLink> {
+    links
+        .iter()
+        .find(|link| link.range.start <= offset && offset <= link.range.end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_links_finds_urls_and_relative_paths() {
+        let line = "See https://example.com/a_(b). and [docs](./docs/DEV.md), not a/./b";
+        let links = detect_links(line, 100);
+        assert_eq!(
+            links,
+            vec![
+                DocumentLink {
+                    range: 104..129,
+                    target: LinkTarget::Url("https://example.com/a_(b)".to_string()),
+                },
+                DocumentLink {
+                    range: 142..155,
+                    target: LinkTarget::File(PathBuf::from("./docs/DEV.md")),
+                },
+            ]
+        );
+        assert!(detect_links("http:// and ../ alone", 0).is_empty());
+
+        let text = "one\n<http://a.io> two";
+        let links = detect_document_links(text);
+        assert_eq!(links[0].range, 5..16);
+        assert_eq!(link_at(&links, 16), Some(&links[0]));
+        assert_eq!(link_at(&links, 17), None);
+    }
+}
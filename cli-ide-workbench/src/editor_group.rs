@@ -0,0 +1,441 @@
+//! Open documents of an editor pane.
+//!
+//! An [`EditorGroup`] is the ordered list of tabs an editor shows, one of
+//! them active. Pinned tabs come first and are left open by
+//! [`EditorGroup::close_others`]. Each [`EditorTab`] pairs a document (the [`SharedBuffer`]
+//! from the [`BufferManager`](crate::buffer_manager::BufferManager)) with
+//! the editor's view of it: selection, scroll position and the git changes
+//! marked in its gutter. The document is shared and the view state is not,
+//! so two editors can show the same buffer at different places. Each tab
+//! follows the [`TextChange`]s of its buffer, so edits made in another
+//! editor keep its selection on the same text.
+
+use std::fmt;
+use std::ops::Range;
+use std::rc::Rc;
+
+use cli_ide_base::Subscription;
+use cli_ide_platform::git::diff::LineChange;
+use cli_ide_platform::problems::problem::Problem;
+
+use crate::buffer_manager::{SharedBuffer, TextChange};
+use crate::viewport::Viewport;
+
+/// A document open in an editor and where the editor is in it.
+pub struct EditorTab {
+    /// The document, shared with other editors showing it.
+    pub buffer: SharedBuffer,
+    /// Selection as a byte range (empty when nothing is selected).
+    pub selection: Range<usize>,
+    /// Whether the head of the selection, the end that moves when it is
+    /// extended, is its end rather than its start; the anchor is the other
+    /// end.
+    pub head_at_end: bool,
+    /// The lines of the document the editor shows.
+    pub viewport: Viewport,
+    /// Lines of the file changed since it was last staged, marked in the
+    /// gutter; `None` hides the gutter, e.g. outside a git repository.
+    pub line_changes: Option<Vec<LineChange>>,
+    /// Problems reported in the file, underlined where they start.
+    pub diagnostics: Vec<Problem>,
+    /// Whether the tab is pinned: kept left of the unpinned tabs and left
+    /// open when the other tabs are closed.
+    pub pinned: bool,
+    /// Changes to the document not followed yet.
+    changes: Subscription<TextChange>,
+}
+
+impl EditorTab {
+    /// Create a tab showing `buffer` from its start.
+    pub fn new(buffer: SharedBuffer) -> Self {
+        let changes = buffer.borrow().on_did_change().subscribe();
+        Self {
+            buffer,
+            selection: 0..0,
+            head_at_end: false,
+            viewport: Viewport::default(),
+            line_changes: None,
+            diagnostics: Vec::new(),
+            pinned: false,
+            changes,
+        }
+    }
+
+    /// Move the selection along with the changes made to the document
+    /// since the last call, e.g. by another editor showing it. Returns
+    /// whether there were any.
+    pub fn follow_changes(&mut self) -> bool {
+        let mut changed = false;
+        for change in self.changes.try_iter() {
+            let start = change.shift(self.selection.start);
+            let end = change.shift(self.selection.end).max(start);
+            self.selection = start..end;
+            changed = true;
+        }
+        changed
+    }
+
+    /// Drop the changes made to the document so far without following
+    /// them, e.g. after an edit that places the selection itself.
+    pub fn skip_changes(&mut self) {
+        self.changes.try_iter().for_each(drop);
+    }
+
+    /// Get the one-based `(line, column)` of the cursor (the selection
+    /// head). Columns count characters, not bytes.
+    pub fn cursor_position(&self) -> (usize, usize) {
+        let head = if self.head_at_end {
+            self.selection.end
+        } else {
+            self.selection.start
+        };
+        let buffer = self.buffer.borrow();
+        let text = buffer.text();
+        let mut head = head.min(text.len());
+        while !text.is_char_boundary(head) {
+            head -= 1;
+        }
+        let before = &text[..head];
+        let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+        (
+            before.matches('\n').count() + 1,
+            before[line_start..].chars().count() + 1,
+        )
+    }
+
+    /// The label shown in the tab strip: the file name, the buffer's name
+    /// if it has no path, or `Untitled`.
+    pub fn title(&self) -> String {
+        let buffer = self.buffer.borrow();
+        match buffer.path().and_then(|path| path.file_name()) {
+            Some(name) => name.to_string_lossy().into_owned(),
+            None => buffer.name().unwrap_or("Untitled").to_string(),
+        }
+    }
+}
+
+impl Clone for EditorTab {
+    /// Copy the view state; the copy follows changes made from now on.
+    fn clone(&self) -> Self {
+        Self {
+            buffer: Rc::clone(&self.buffer),
+            selection: self.selection.clone(),
+            head_at_end: self.head_at_end,
+            viewport: self.viewport,
+            line_changes: self.line_changes.clone(),
+            diagnostics: self.diagnostics.clone(),
+            pinned: self.pinned,
+            changes: self.buffer.borrow().on_did_change().subscribe(),
+        }
+    }
+}
+
+impl fmt::Debug for EditorTab {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EditorTab")
+            .field("buffer", &self.buffer)
+            .field("selection", &self.selection)
+            .field("head_at_end", &self.head_at_end)
+            .field("viewport", &self.viewport)
+            .field("line_changes", &self.line_changes)
+            .field("diagnostics", &self.diagnostics)
+            .field("pinned", &self.pinned)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Ordered tabs with one of them active. There is always at least one tab.
+#[derive(Debug, Clone)]
+pub struct EditorGroup {
+    /// Tabs in display order.
+    tabs: Vec<EditorTab>,
+    /// Index of the active tab.
+    active: usize,
+}
+
+impl EditorGroup {
+    /// Create a group with a single tab showing `buffer`.
+    pub fn new(buffer: SharedBuffer) -> Self {
+        Self {
+            tabs: vec![EditorTab::new(buffer)],
+            active: 0,
+        }
+    }
+
+    /// Get the tabs in display order.
+    pub fn tabs(&self) -> &[EditorTab] {
+        &self.tabs
+    }
+
+    /// Get the tabs in display order mutably.
+    pub fn tabs_mut(&mut self) -> &mut [EditorTab] {
+        &mut self.tabs
+    }
+
+    /// Number of tabs.
+    pub fn len(&self) -> usize {
+        self.tabs.len()
+    }
+
+    /// Always `false`: a group keeps at least one tab.
+    pub fn is_empty(&self) -> bool {
+        self.tabs.is_empty()
+    }
+
+    /// Get the index of the active tab.
+    pub fn active_index(&self) -> usize {
+        self.active
+    }
+
+    /// Get the active tab.
+    pub fn active(&self) -> &EditorTab {
+        &self.tabs[self.active]
+    }
+
+    /// Get the active tab mutably.
+    pub fn active_mut(&mut self) -> &mut EditorTab {
+        &mut self.tabs[self.active]
+    }
+
+    /// Move every tab's selection along with the changes made to its
+    /// document. Returns whether the active tab's document changed.
+    pub fn follow_changes(&mut self) -> bool {
+        let mut active_changed = false;
+        for (index, tab) in self.tabs.iter_mut().enumerate() {
+            let changed = tab.follow_changes();
+            active_changed |= changed && index == self.active;
+        }
+        active_changed
+    }
+
+    /// Find the tab showing `buffer`.
+    pub fn position(&self, buffer: &SharedBuffer) -> Option<usize> {
+        self.tabs
+            .iter()
+            .position(|tab| Rc::ptr_eq(&tab.buffer, buffer))
+    }
+
+    /// Get the number of pinned tabs, which come first.
+    pub fn pinned_count(&self) -> usize {
+        self.tabs.iter().take_while(|tab| tab.pinned).count()
+    }
+
+    /// Activate the tab showing `buffer`, or open a new tab for it after
+    /// the active one and the pinned tabs. Returns the index of the tab.
+    pub fn open(&mut self, buffer: SharedBuffer) -> usize {
+        self.insert(EditorTab::new(buffer))
+    }
+
+    /// Add `tab`, e.g. one moved from another group, and activate it.
+    ///
+    /// A pinned tab goes after the other pinned tabs, an unpinned one
+    /// after the active tab and the pinned tabs. If a tab already shows
+    /// its document, that tab is activated instead. Returns the index of
+    /// the tab.
+    pub fn insert(&mut self, tab: EditorTab) -> usize {
+        if let Some(index) = self.position(&tab.buffer) {
+            self.active = index;
+            return index;
+        }
+        let pinned = self.pinned_count();
+        let index = if tab.pinned {
+            pinned
+        } else {
+            (self.active + 1).max(pinned)
+        };
+        self.tabs.insert(index, tab);
+        self.active = index;
+        index
+    }
+
+    /// Pin the active tab, moving it after the other pinned tabs, or unpin
+    /// it, moving it before the other unpinned tabs. Returns whether it is
+    /// pinned now.
+    pub fn toggle_pinned(&mut self) -> bool {
+        let mut tab = self.tabs.remove(self.active);
+        tab.pinned = !tab.pinned;
+        let pinned = tab.pinned;
+        self.active = self.pinned_count();
+        self.tabs.insert(self.active, tab);
+        pinned
+    }
+
+    /// Move the active tab `offset` places right, or left if negative,
+    /// staying among the pinned or among the unpinned tabs. Returns
+    /// whether it moved.
+    pub fn move_active(&mut self, offset: isize) -> bool {
+        let pinned = self.pinned_count();
+        let allowed = if self.active().pinned {
+            0..pinned
+        } else {
+            pinned..self.tabs.len()
+        };
+        let Some(index) = self
+            .active
+            .checked_add_signed(offset)
+            .filter(|index| allowed.contains(index) && *index != self.active)
+        else {
+            return false;
+        };
+        let tab = self.tabs.remove(self.active);
+        self.tabs.insert(index, tab);
+        self.active = index;
+        true
+    }
+
+    /// Show `buffer` in the active tab instead of its current document.
+    ///
+    /// If another tab already shows `buffer`, that tab is activated and
+    /// the active one is closed instead, so no buffer has two tabs.
+    pub fn replace_active(&mut self, buffer: SharedBuffer) {
+        match self.position(&buffer) {
+            Some(index) if index != self.active => {
+                self.close_active();
+                self.active = self.position(&buffer).unwrap_or(self.active);
+            }
+            _ => {
+                let pinned = self.active().pinned;
+                self.tabs[self.active] = EditorTab::new(buffer);
+                self.active_mut().pinned = pinned;
+            }
+        }
+    }
+
+    /// Activate the tab at `index` (clamped).
+    pub fn activate(&mut self, index: usize) {
+        self.active = index.min(self.tabs.len() - 1);
+    }
+
+    /// Activate the next tab, wrapping around.
+    pub fn next(&mut self) {
+        self.active = (self.active + 1) % self.tabs.len();
+    }
+
+    /// Activate the previous tab, wrapping around.
+    pub fn prev(&mut self) {
+        self.active = (self.active + self.tabs.len() - 1) % self.tabs.len();
+    }
+
+    /// Close the active tab and activate its right neighbour, or the left
+    /// one if it was the last.
+    ///
+    /// The only tab cannot be closed. Returns the closed tab.
+    pub fn close_active(&mut self) -> Option<EditorTab> {
+        if self.tabs.len() == 1 {
+            return None;
+        }
+        let closed = self.tabs.remove(self.active);
+        self.active = self.active.min(self.tabs.len() - 1);
+        Some(closed)
+    }
+
+    /// Close every tab except the active one and the pinned ones. Returns
+    /// the closed tabs.
+    pub fn close_others(&mut self) -> Vec<EditorTab> {
+        let active = self.active;
+        let mut closed = Vec::new();
+        for (index, tab) in std::mem::take(&mut self.tabs).into_iter().enumerate() {
+            if index == active {
+                self.active = self.tabs.len();
+            }
+            if index == active || tab.pinned {
+                self.tabs.push(tab);
+            } else {
+                closed.push(tab);
+            }
+        }
+        closed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer_manager::TextBuffer;
+    use std::cell::RefCell;
+
+    fn buffer(text: &str) -> SharedBuffer {
+        Rc::new(RefCell::new(TextBuffer::new(text)))
+    }
+
+    fn texts(group: &EditorGroup) -> Vec<String> {
+        group
+            .tabs()
+            .iter()
+            .map(|tab| tab.buffer.borrow().text().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_open_inserts_after_active_and_reuses_tabs() {
+        let a = buffer("a");
+        let mut group = EditorGroup::new(Rc::clone(&a));
+        group.open(buffer("b"));
+        group.activate(0);
+        assert_eq!(group.open(buffer("c")), 1);
+        assert_eq!(texts(&group), vec!["a", "c", "b"]);
+
+        assert_eq!(group.open(Rc::clone(&a)), 0);
+        assert_eq!(group.len(), 3);
+    }
+
+    #[test]
+    fn test_cycling_wraps_and_closing_keeps_one_tab() {
+        let mut group = EditorGroup::new(buffer("a"));
+        group.open(buffer("b"));
+        group.next();
+        assert_eq!(group.active_index(), 0);
+        group.prev();
+        assert_eq!(group.active_index(), 1);
+
+        let closed = group.close_active().unwrap();
+        assert_eq!(closed.buffer.borrow().text(), "b");
+        assert_eq!(group.active_index(), 0);
+        assert!(group.close_active().is_none());
+    }
+
+    #[test]
+    fn test_pinned_tabs_stay_first_and_open_when_closing_others() {
+        let mut group = EditorGroup::new(buffer("a"));
+        group.open(buffer("b"));
+        group.open(buffer("c"));
+        assert!(group.toggle_pinned());
+        assert_eq!(texts(&group), vec!["c", "a", "b"]);
+        assert_eq!(group.active_index(), 0);
+        assert!(!group.move_active(1), "pinned tabs stay first");
+
+        group.open(buffer("d"));
+        assert_eq!(texts(&group), vec!["c", "d", "a", "b"], "after the pins");
+        assert!(group.move_active(2));
+        assert!(!group.move_active(1), "already last");
+        assert!(group.move_active(-1));
+        assert_eq!(texts(&group), vec!["c", "a", "d", "b"]);
+
+        let closed = group.close_others();
+        let closed: Vec<String> = closed
+            .iter()
+            .map(|tab| tab.buffer.borrow().text().to_string())
+            .collect();
+        assert_eq!(closed, vec!["a", "b"]);
+        assert_eq!(texts(&group), vec!["c", "d"]);
+        assert_eq!(group.active_index(), 1);
+
+        group.activate(0);
+        assert!(!group.toggle_pinned());
+        assert_eq!(group.pinned_count(), 0);
+    }
+
+    #[test]
+    fn test_replace_active_keeps_one_tab_per_buffer() {
+        let b = buffer("b");
+        let mut group = EditorGroup::new(buffer("a"));
+        group.open(Rc::clone(&b));
+        group.open(buffer("c"));
+
+        group.replace_active(buffer("d"));
+        assert_eq!(texts(&group), vec!["a", "b", "d"]);
+        group.replace_active(Rc::clone(&b));
+        assert_eq!(texts(&group), vec!["a", "b"]);
+        assert_eq!(group.active_index(), 1);
+    }
+}
@@ -0,0 +1,374 @@
+//! Exporting highlighted text, e.g. to paste into a terminal or a web page.
+//!
+//! [`highlight`] tokenizes text with the editor's [`Highlighter`] and
+//! styles it with the [`Theme`], into the same ratatui lines the editor
+//! draws. [`to_ansi`] writes such lines as SGR escape sequences that `cat`
+//! or `less -R` show in color, and [`to_html`] as a standalone page with
+//! inline styles.
+
+use std::fmt::Write as _;
+use std::ops::Range;
+
+use cli_ide_base::highlight::{Highlighter, LineState};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+
+use crate::theme::Theme;
+
+/// What highlighted text is exported as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExportFormat {
+    /// Text with ANSI escape sequences.
+    Ansi,
+    /// An HTML page.
+    Html,
+}
+
+impl ExportFormat {
+    /// Get the name of the format, e.g. for a prompt.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Ansi => "ANSI Text",
+            Self::Html => "HTML",
+        }
+    }
+
+    /// Get the file extension exports in this format get.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Ansi => "ans",
+            Self::Html => "html",
+        }
+    }
+}
+
+/// Highlight the lines of `text` within `range` and style them with
+/// `theme`.
+///
+/// Tokenizing starts at the start of `text`, so a selection starting
+/// inside a block comment is still colored as a comment. A final new line
+/// does not start another line.
+pub fn highlight(
+    text: &str,
+    range: Range<usize>,
+    highlighter: Option<&dyn Highlighter>,
+    theme: &Theme,
+) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    let mut state = LineState::default();
+    let mut line_start = 0;
+    for raw_line in text.split_inclusive('\n') {
+        let start = line_start;
+        line_start += raw_line.len();
+        let line = raw_line.strip_suffix('\n').unwrap_or(raw_line);
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let (tokens, next) = match highlighter {
+            Some(highlighter) => highlighter.highlight_line(line, state),
+            None => (Vec::new(), state),
+        };
+        state = next;
+        if line_start <= range.start || start >= range.end {
+            continue;
+        }
+        let shown = range.start.saturating_sub(start)..(range.end - start).min(line.len());
+        let mut spans = Vec::new();
+        let mut position = shown.start;
+        for token in &tokens {
+            let token_range = token.range.start.max(shown.start)..token.range.end.min(shown.end);
+            if token_range.is_empty() {
+                continue;
+            }
+            if position < token_range.start {
+                spans.push(Span::styled(
+                    line[position..token_range.start].to_string(),
+                    theme.text,
+                ));
+            }
+            spans.push(Span::styled(
+                line[token_range.clone()].to_string(),
+                theme.text.patch(theme.token_style(token.kind)),
+            ));
+            position = token_range.end;
+        }
+        if position < shown.end {
+            spans.push(Span::styled(
+                line[position..shown.end].to_string(),
+                theme.text,
+            ));
+        }
+        lines.push(Line::from(spans));
+    }
+    lines
+}
+
+/// Write `lines` as text with ANSI escape sequences, each line ending with
+/// a reset and a new line.
+pub fn to_ansi(lines: &[Line]) -> String {
+    let mut out = String::new();
+    for line in lines {
+        for span in &line.spans {
+            let _ = write!(out, "\x1b[{}m{}", sgr(span.style), span.content);
+        }
+        out.push_str("\x1b[0m\n");
+    }
+    out
+}
+
+/// Write `lines` as an HTML page titled `title`, the text in a `<pre>`
+/// colored like `base` and each span with its own inline style.
+pub fn to_html(lines: &[Line], title: &str, base: Style) -> String {
+    let mut out = String::new();
+    out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    let _ = writeln!(out, "<title>{}</title>", escape_html(title));
+    out.push_str("</head>\n<body>\n");
+    let page = Style::default()
+        .fg(base.fg.unwrap_or(Color::Gray))
+        .bg(base.bg.unwrap_or(Color::Black));
+    let _ = write!(out, "<pre style=\"{}\">", css(page));
+    for line in lines {
+        for span in &line.spans {
+            let text = escape_html(&span.content);
+            match css(span.style) {
+                style if style.is_empty() => out.push_str(&text),
+                style => {
+                    let _ = write!(out, "<span style=\"{style}\">{text}</span>");
+                }
+            }
+        }
+        out.push('\n');
+    }
+    out.push_str("</pre>\n</body>\n</html>\n");
+    out
+}
+
+/// Highlight `range` of `text` and write it in `format`; `title` names an
+/// HTML page.
+pub fn export(
+    text: &str,
+    range: Range<usize>,
+    highlighter: Option<&dyn Highlighter>,
+    theme: &Theme,
+    format: ExportFormat,
+    title: &str,
+) -> String {
+    let lines = highlight(text, range, highlighter, theme);
+    match format {
+        ExportFormat::Ansi => to_ansi(&lines),
+        ExportFormat::Html => to_html(&lines, title, theme.text),
+    }
+}
+
+/// The SGR parameters selecting `style`, starting from a reset.
+fn sgr(style: Style) -> String {
+    let mut codes = vec!["0".to_string()];
+    for (modifier, code) in [
+        (Modifier::BOLD, "1"),
+        (Modifier::DIM, "2"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::REVERSED, "7"),
+    ] {
+        if style.add_modifier.contains(modifier) {
+            codes.push(code.to_string());
+        }
+    }
+    if let Some(color) = style.fg {
+        codes.push(sgr_color(color, false));
+    }
+    if let Some(color) = style.bg {
+        codes.push(sgr_color(color, true));
+    }
+    codes.join(";")
+}
+
+/// The SGR parameters selecting `color` as the foreground, or the
+/// background if `background`.
+fn sgr_color(color: Color, background: bool) -> String {
+    let code: u8 = match color {
+        Color::Reset => 39,
+        Color::Black => 30,
+        Color::Red => 31,
+        Color::Green => 32,
+        Color::Yellow => 33,
+        Color::Blue => 34,
+        Color::Magenta => 35,
+        Color::Cyan => 36,
+        Color::Gray => 37,
+        Color::DarkGray => 90,
+        Color::LightRed => 91,
+        Color::LightGreen => 92,
+        Color::LightYellow => 93,
+        Color::LightBlue => 94,
+        Color::LightMagenta => 95,
+        Color::LightCyan => 96,
+        Color::White => 97,
+        Color::Indexed(index) => {
+            return format!("{};5;{index}", if background { 48 } else { 38 });
+        }
+        Color::Rgb(r, g, b) => {
+            return format!("{};2;{r};{g};{b}", if background { 48 } else { 38 });
+        }
+    };
+    if background {
+        (code + 10).to_string()
+    } else {
+        code.to_string()
+    }
+}
+
+/// The inline CSS drawing text in `style`; empty for the default style.
+fn css(style: Style) -> String {
+    let (mut fg, mut bg) = (style.fg.and_then(rgb), style.bg.and_then(rgb));
+    if style.add_modifier.contains(Modifier::REVERSED) {
+        (fg, bg) = (bg, fg);
+    }
+    let mut rules = Vec::new();
+    if let Some((r, g, b)) = fg {
+        rules.push(format!("color: #{r:02x}{g:02x}{b:02x}"));
+    }
+    if let Some((r, g, b)) = bg {
+        rules.push(format!("background-color: #{r:02x}{g:02x}{b:02x}"));
+    }
+    for (modifier, rule) in [
+        (Modifier::BOLD, "font-weight: bold"),
+        (Modifier::DIM, "opacity: 0.6"),
+        (Modifier::ITALIC, "font-style: italic"),
+        (Modifier::UNDERLINED, "text-decoration: underline"),
+    ] {
+        if style.add_modifier.contains(modifier) {
+            rules.push(rule.to_string());
+        }
+    }
+    rules.join("; ")
+}
+
+/// The xterm palette's value of `color`; `None` for the terminal's default.
+fn rgb(color: Color) -> Option<(u8, u8, u8)> {
+    /// The 16 named colors, in index order.
+    const NAMED: [(u8, u8, u8); 16] = [
+        (0x00, 0x00, 0x00),
+        (0x80, 0x00, 0x00),
+        (0x00, 0x80, 0x00),
+        (0x80, 0x80, 0x00),
+        (0x00, 0x00, 0x80),
+        (0x80, 0x00, 0x80),
+        (0x00, 0x80, 0x80),
+        (0xc0, 0xc0, 0xc0),
+        (0x80, 0x80, 0x80),
+        (0xff, 0x00, 0x00),
+        (0x00, 0xff, 0x00),
+        (0xff, 0xff, 0x00),
+        (0x00, 0x00, 0xff),
+        (0xff, 0x00, 0xff),
+        (0x00, 0xff, 0xff),
+        (0xff, 0xff, 0xff),
+    ];
+    let index = match color {
+        Color::Reset => return None,
+        Color::Rgb(r, g, b) => return Some((r, g, b)),
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::Gray => 7,
+        Color::DarkGray => 8,
+        Color::LightRed => 9,
+        Color::LightGreen => 10,
+        Color::LightYellow => 11,
+        Color::LightBlue => 12,
+        Color::LightMagenta => 13,
+        Color::LightCyan => 14,
+        Color::White => 15,
+        Color::Indexed(index) => index,
+    };
+    Some(match index {
+        0..=15 => NAMED[usize::from(index)],
+        16..=231 => {
+            let level = |value: u8| if value == 0 { 0 } else { 55 + value * 40 };
+            let cube = index - 16;
+            (level(cube / 36), level(cube / 6 % 6), level(cube % 6))
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            (gray, gray, gray)
+        }
+    })
+}
+
+/// Escape the characters HTML gives a meaning to.
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cli_ide_base::highlight::LexicalHighlighter;
+
+    fn rust() -> LexicalHighlighter {
+        LexicalHighlighter::for_language("rust").unwrap()
+    }
+
+    #[test]
+    fn test_selection_keeps_the_highlighting_of_the_whole_document() {
+        let theme = Theme::dark();
+        let text = "/* a\nb */ fn x() {}\n";
+        let lines = highlight(text, 7..text.len(), Some(&rust()), &theme);
+        assert_eq!(lines.len(), 1);
+        let spans: Vec<(&str, Style)> = lines[0]
+            .spans
+            .iter()
+            .map(|span| (span.content.as_ref(), span.style))
+            .collect();
+        assert_eq!(
+            spans,
+            [
+                ("*/", theme.text.patch(theme.comment)),
+                (" ", theme.text),
+                ("fn", theme.text.patch(theme.keyword)),
+                (" x() {}", theme.text),
+            ]
+        );
+        assert!(highlight("", 0..0, None, &theme).is_empty());
+        assert_eq!(highlight("a\nb", 0..3, None, &theme).len(), 2);
+    }
+
+    #[test]
+    fn test_ansi_and_html_carry_the_styles() {
+        let theme = Theme::dark();
+        let ansi = export("fn <", 0..4, Some(&rust()), &theme, ExportFormat::Ansi, "");
+        assert_eq!(ansi, "\x1b[0;35mfn\x1b[0;37m <\x1b[0m\n");
+
+        let html = export(
+            "fn <",
+            0..4,
+            Some(&rust()),
+            &theme,
+            ExportFormat::Html,
+            "a & b",
+        );
+        assert!(html.contains("<title>a &amp; b</title>"), "{html}");
+        assert!(html.contains(
+            "<pre style=\"color: #c0c0c0; background-color: #000000\">\
+             <span style=\"color: #800080\">fn</span>\
+             <span style=\"color: #c0c0c0\"> &lt;</span>\n</pre>"
+        ));
+
+        assert_eq!(sgr_color(Color::Indexed(22), true), "48;5;22");
+        assert_eq!(rgb(Color::Indexed(22)), Some((0, 95, 0)));
+        assert_eq!(rgb(Color::Indexed(255)), Some((238, 238, 238)));
+    }
+}
@@ -0,0 +1,90 @@
+//! Pacing the redraws of an event loop.
+//!
+//! The App reports whether its screen is out of date
+//! ([`App::needs_redraw`](crate::app::App::needs_redraw)); a [`FrameRate`]
+//! decides when the loop may draw it, at most a maximum number of frames a
+//! second, and how long to wait for input meanwhile. An idle App is not
+//! redrawn at all.
+
+use std::time::{Duration, Instant};
+
+/// Frames a second a [`FrameRate`] allows unless configured otherwise.
+pub const DEFAULT_MAX_FPS: u32 = 60;
+
+/// Limits how often an event loop draws.
+#[derive(Debug, Clone)]
+pub struct FrameRate {
+    /// The shortest time between two frames.
+    interval: Duration,
+    /// When the last frame was drawn, if one was.
+    last_frame: Option<Instant>,
+}
+
+impl Default for FrameRate {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_FPS)
+    }
+}
+
+impl FrameRate {
+    /// Allow at most `max_fps` frames a second, at least one.
+    pub fn new(max_fps: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(1) / max_fps.max(1),
+            last_frame: None,
+        }
+    }
+
+    /// Get the shortest time between two frames.
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Check whether a frame may be drawn at `now`.
+    pub fn is_due(&self, now: Instant) -> bool {
+        self.last_frame
+            .is_none_or(|last| now.saturating_duration_since(last) >= self.interval)
+    }
+
+    /// Note that a frame was drawn at `now`.
+    pub fn frame_drawn(&mut self, now: Instant) {
+        self.last_frame = Some(now);
+    }
+
+    /// How long to wait for input at `now` before looping again: until the
+    /// next frame is due if the screen needs a redraw, `idle` otherwise.
+    pub fn wait(&self, needs_redraw: bool, idle: Duration, now: Instant) -> Duration {
+        if !needs_redraw {
+            return idle;
+        }
+        self.last_frame.map_or(Duration::ZERO, |last| {
+            (last + self.interval)
+                .saturating_duration_since(now)
+                .min(idle)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frames_are_spaced_by_the_interval() {
+        let idle = Duration::from_millis(100);
+        let mut rate = FrameRate::new(50);
+        assert_eq!(rate.interval(), Duration::from_millis(20));
+        let start = Instant::now();
+        assert!(rate.is_due(start));
+        assert_eq!(rate.wait(true, idle, start), Duration::ZERO);
+
+        rate.frame_drawn(start);
+        let later = start + Duration::from_millis(5);
+        assert!(!rate.is_due(later));
+        assert_eq!(rate.wait(true, idle, later), Duration::from_millis(15));
+        assert_eq!(rate.wait(false, idle, later), idle, "nothing to draw");
+        assert!(rate.is_due(start + Duration::from_millis(20)));
+
+        assert_eq!(FrameRate::new(0).interval(), Duration::from_secs(1));
+    }
+}
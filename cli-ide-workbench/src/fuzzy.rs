@@ -0,0 +1,109 @@
+//! Fuzzy matching for pickers such as the command palette.
+//!
+//! A query matches a candidate when every query character appears in the
+//! candidate in order (case-insensitive). Matches are scored so that
+//! consecutive characters and characters at word starts rank higher.
+
+/// Score awarded for each matched character.
+const MATCH_SCORE: i64 = 1;
+/// Bonus for a match immediately following the previous match.
+const CONSECUTIVE_BONUS: i64 = 4;
+/// Bonus for a match at the start of a word.
+const WORD_START_BONUS: i64 = 3;
+
+/// Score `candidate` against `query`.
+///
+/// Returns `None` if the query does not match. An empty query matches every
+/// candidate with a score of zero. Higher scores are better matches.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let mut score = 0;
+    let mut previous_match: Option<usize> = None;
+    let mut candidate_chars = candidate.chars().enumerate().peekable();
+    let mut previous_char: Option<char> = None;
+
+    for query_char in query.chars().flat_map(char::to_lowercase) {
+        loop {
+            let (index, c) = candidate_chars.next()?;
+            let before = previous_char.replace(c);
+            if !c.to_lowercase().eq(std::iter::once(query_char)) {
+                continue;
+            }
+
+            score += MATCH_SCORE;
+            if previous_match.is_some_and(|prev| prev + 1 == index) {
+                score += CONSECUTIVE_BONUS;
+            }
+            if before.is_none_or(|b| !b.is_alphanumeric()) {
+                score += WORD_START_BONUS;
+            }
+            previous_match = Some(index);
+            break;
+        }
+    }
+
+    Some(score)
+}
+
+/// Filter and rank `items` by how well `key(item)` matches `query`.
+///
+/// Returns the indices of matching items, best match first. Items with
+/// equal scores keep their original order.
+pub fn fuzzy_filter<T, F>(query: &str, items: &[T], key: F) -> Vec<usize>
+where
+    F: Fn(&T) -> &str,
+{
+    let mut matches: Vec<(usize, i64)> = items
+        .iter()
+        .enumerate()
+        .filter_map(|(index, item)| fuzzy_score(query, key(item)).map(|score| (index, score)))
+        .collect();
+    matches.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+    matches.into_iter().map(|(index, _)| index).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_query_matches_everything() {
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+    }
+
+    #[test]
+    fn test_subsequence_matches() {
+        assert!(fuzzy_score("tgf", "Toggle Focus").is_some());
+        assert!(fuzzy_score("quit", "Quit").is_some());
+    }
+
+    #[test]
+    fn test_out_of_order_does_not_match() {
+        assert!(fuzzy_score("tiuq", "Quit").is_none());
+        assert!(fuzzy_score("xyz", "Quit").is_none());
+    }
+
+    #[test]
+    fn test_case_insensitive() {
+        assert_eq!(fuzzy_score("QUIT", "quit"), fuzzy_score("quit", "Quit"));
+    }
+
+    #[test]
+    fn test_consecutive_and_word_start_rank_higher() {
+        let prefix = fuzzy_score("foc", "Focus Next").unwrap();
+        let scattered = fuzzy_score("foc", "Info Picker").unwrap();
+        assert!(prefix > scattered);
+    }
+
+    #[test]
+    fn test_filter_orders_by_score() {
+        let items = ["Info Picker", "Focus Next", "Quit"];
+        let result = fuzzy_filter("foc", &items, |s| s);
+        assert_eq!(result, vec![1, 0]);
+    }
+
+    #[test]
+    fn test_filter_empty_query_keeps_order() {
+        let items = ["b", "a", "c"];
+        assert_eq!(fuzzy_filter("", &items, |s| s), vec![0, 1, 2]);
+    }
+}
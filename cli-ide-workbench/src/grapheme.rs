@@ -0,0 +1,83 @@
+//! Grapheme clusters and the cells they take on screen.
+//!
+//! What the user sees as one character can be several `char`s: `e` and a
+//! combining accent, a flag made of two regional indicators, or an emoji
+//! joined from several. The editor moves the cursor and deletes by
+//! grapheme cluster so it never stops inside one, and measures lines in
+//! terminal cells, as ratatui draws them, so wide characters (CJK, emoji)
+//! keep columns aligned when the cursor moves between lines.
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Byte offset of the grapheme boundary after `index` in `text`, or the end
+/// of the text.
+pub fn next_boundary(text: &str, index: usize) -> usize {
+    text[index..]
+        .graphemes(true)
+        .next()
+        .map_or(text.len(), |grapheme| index + grapheme.len())
+}
+
+/// Byte offset of the grapheme boundary before `index` in `text`, or the
+/// start of the text.
+pub fn previous_boundary(text: &str, index: usize) -> usize {
+    text[..index]
+        .graphemes(true)
+        .next_back()
+        .map_or(0, |grapheme| index - grapheme.len())
+}
+
+/// Number of cells `grapheme` takes on screen.
+pub fn width(grapheme: &str) -> usize {
+    grapheme.width()
+}
+
+/// Number of cells the text of `line` before `index` takes on screen.
+pub fn display_column(line: &str, index: usize) -> usize {
+    line[..index].graphemes(true).map(width).sum()
+}
+
+/// Byte offset of the grapheme of `line` covering the screen `column`, or
+/// the end of the line if it is narrower.
+///
+/// A column in the second cell of a wide character lands on that
+/// character.
+pub fn index_at_display_column(line: &str, column: usize) -> usize {
+    let mut cells = 0;
+    for (index, grapheme) in line.grapheme_indices(true) {
+        cells += width(grapheme);
+        if cells > column {
+            return index;
+        }
+    }
+    line.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_boundaries_and_columns_follow_clusters_and_widths() {
+        // `e` with a combining acute accent, a wide ideograph and a family
+        // emoji joined from three.
+        let line = "ae\u{301}漢👨\u{200d}👩\u{200d}👧z";
+        let accent_end = 1 + 1 + 2;
+        assert_eq!(next_boundary(line, 1), accent_end);
+        assert_eq!(previous_boundary(line, accent_end), 1);
+        let family = accent_end + 3;
+        let z = line.len() - 1;
+        assert_eq!(next_boundary(line, family), z);
+        assert_eq!(previous_boundary(line, z), family);
+        assert_eq!(next_boundary(line, line.len()), line.len());
+        assert_eq!(previous_boundary(line, 0), 0);
+
+        assert_eq!(display_column(line, accent_end), 2);
+        assert_eq!(display_column(line, family), 4);
+        assert_eq!(index_at_display_column(line, 2), accent_end);
+        assert_eq!(index_at_display_column(line, 3), accent_end);
+        assert_eq!(index_at_display_column(line, 4), family);
+        assert_eq!(index_at_display_column(line, 100), line.len());
+    }
+}
@@ -0,0 +1,233 @@
+//! Driving an App without a terminal, for tests.
+//!
+//! [`AppHarness`] owns an [`App`] and an offscreen terminal. Tests feed it
+//! keys and text as a user would type them and read back what the screen
+//! shows, so plugins and downstream crates can test their UI without a TTY:
+//!
+//! ```
+//! use cli_ide_workbench::harness::AppHarness;
+//! use cli_ide_workbench::input::AppKey;
+//!
+//! let mut harness = AppHarness::new();
+//! harness.type_str(":toggle").assert_contains("Command Palette");
+//! harness.press(AppKey::Esc).assert_not_contains("Command Palette");
+//! ```
+
+use ratatui::backend::TestBackend;
+use ratatui::buffer::Buffer;
+use ratatui::Terminal;
+
+use crate::app::App;
+use crate::input::{AppEvent, AppKey};
+
+/// Width of the screen of [`AppHarness::new`].
+pub const DEFAULT_WIDTH: u16 = 80;
+
+/// Height of the screen of [`AppHarness::new`].
+pub const DEFAULT_HEIGHT: u16 = 24;
+
+/// An App with an offscreen terminal, driven by simulated input.
+///
+/// The screen is rendered whenever it is read, so it always shows the
+/// App's state after the last event.
+pub struct AppHarness {
+    /// The App under test.
+    app: App,
+    /// Offscreen terminal the App is rendered to.
+    terminal: Terminal<TestBackend>,
+}
+
+impl Default for AppHarness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AppHarness {
+    /// Drive a new App on a [`DEFAULT_WIDTH`] by [`DEFAULT_HEIGHT`] screen.
+    pub fn new() -> Self {
+        Self::with_app(App::new(), DEFAULT_WIDTH, DEFAULT_HEIGHT)
+    }
+
+    /// Drive `app`, set up by the test, on a `width` by `height` screen.
+    pub fn with_app(app: App, width: u16, height: u16) -> Self {
+        let terminal =
+            Terminal::new(TestBackend::new(width, height)).expect("the test backend cannot fail");
+        let mut harness = Self { app, terminal };
+        harness.send(AppEvent::Resize(width, height));
+        harness
+    }
+
+    /// Get the App.
+    pub fn app(&self) -> &App {
+        &self.app
+    }
+
+    /// Get the App mutably, e.g. to open a file.
+    pub fn app_mut(&mut self) -> &mut App {
+        &mut self.app
+    }
+
+    /// Stop driving the App and get it back.
+    pub fn into_app(self) -> App {
+        self.app
+    }
+
+    /// Resize the screen, as a terminal resize would.
+    pub fn resize(&mut self, width: u16, height: u16) -> &mut Self {
+        self.terminal.backend_mut().resize(width, height);
+        self.send(AppEvent::Resize(width, height))
+    }
+
+    /// Feed `event` to the App.
+    pub fn send(&mut self, event: AppEvent) -> &mut Self {
+        self.app.handle_event(event);
+        self
+    }
+
+    /// Press `key`.
+    pub fn press(&mut self, key: AppKey) -> &mut Self {
+        self.send(AppEvent::Key(key))
+    }
+
+    /// Press the keys typing `text`, one character at a time.
+    ///
+    /// Characters arrive as the terminal delivers them: `q` as
+    /// [`AppKey::Q`], a new line as [`AppKey::Enter`] and a tab as
+    /// [`AppKey::Tab`].
+    pub fn type_str(&mut self, text: &str) -> &mut Self {
+        for c in text.chars() {
+            let key = match c {
+                '\n' => AppKey::Enter,
+                c => AppKey::from_char(c),
+            };
+            self.press(key);
+        }
+        self
+    }
+
+    /// Let time pass, as the event loop does while no input arrives.
+    pub fn tick(&mut self) -> &mut Self {
+        self.send(AppEvent::Tick)
+    }
+
+    /// Render the App and get the screen.
+    pub fn buffer(&mut self) -> &Buffer {
+        let app = &mut self.app;
+        self.terminal
+            .draw(|frame| {
+                let area = frame.area();
+                app.render(frame, area);
+            })
+            .expect("the test backend cannot fail");
+        self.terminal.backend().buffer()
+    }
+
+    /// Render the App and get the screen as text, one line per row, each
+    /// ending with a new line.
+    pub fn snapshot(&mut self) -> String {
+        buffer_to_string(self.buffer())
+    }
+
+    /// Render the App and get row `row` of the screen, without trailing
+    /// spaces.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the screen has no such row.
+    pub fn line(&mut self, row: u16) -> String {
+        let snapshot = self.snapshot();
+        let line = snapshot
+            .lines()
+            .nth(usize::from(row))
+            .unwrap_or_else(|| panic!("the screen has no row {row}"));
+        line.trim_end().to_string()
+    }
+
+    /// Assert that the screen shows `text`, printing the screen if not.
+    #[track_caller]
+    pub fn assert_contains(&mut self, text: &str) -> &mut Self {
+        let snapshot = self.snapshot();
+        assert!(
+            snapshot.contains(text),
+            "the screen does not show {text:?}:\n{snapshot}"
+        );
+        self
+    }
+
+    /// Assert that the screen does not show `text`, printing the screen if
+    /// it does.
+    #[track_caller]
+    pub fn assert_not_contains(&mut self, text: &str) -> &mut Self {
+        let snapshot = self.snapshot();
+        assert!(
+            !snapshot.contains(text),
+            "the screen shows {text:?}:\n{snapshot}"
+        );
+        self
+    }
+
+    /// Assert that row `row` of the screen reads `expected`, ignoring
+    /// trailing spaces.
+    #[track_caller]
+    pub fn assert_line(&mut self, row: u16, expected: &str) -> &mut Self {
+        let line = self.line(row);
+        assert_eq!(
+            line,
+            expected.trim_end(),
+            "row {row} differs:\n{}",
+            self.snapshot()
+        );
+        self
+    }
+}
+
+/// Render `app` on a `width` by `height` screen and get it as text, as
+/// [`AppHarness::snapshot`] does, for tests holding the App themselves.
+pub fn render_to_string(app: &mut App, width: u16, height: u16) -> String {
+    let mut terminal =
+        Terminal::new(TestBackend::new(width, height)).expect("the test backend cannot fail");
+    terminal
+        .draw(|frame| {
+            let area = frame.area();
+            app.render(frame, area);
+        })
+        .expect("the test backend cannot fail");
+    buffer_to_string(terminal.backend().buffer())
+}
+
+/// The text of `buffer`, one line per row, each ending with a new line.
+pub fn buffer_to_string(buffer: &Buffer) -> String {
+    let area = buffer.area;
+    let mut result = String::new();
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            result.push_str(buffer[(x, y)].symbol());
+        }
+        result.push('\n');
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_type_str_delivers_keys_as_a_terminal_would() {
+        let mut harness = AppHarness::new();
+        // `q` arrives as AppKey::Q, which the palette takes as text
+        harness.type_str(":quit\n");
+        assert!(!harness.app().is_running());
+
+        let mut harness = AppHarness::with_app(App::new(), 40, 10);
+        assert_eq!(harness.app().size(), (40, 10));
+        assert_eq!(harness.snapshot().lines().count(), 10);
+        harness.resize(60, 12);
+        assert_eq!(harness.app().size(), (60, 12));
+        assert_eq!(
+            harness.snapshot().lines().next().unwrap().chars().count(),
+            60
+        );
+    }
+}
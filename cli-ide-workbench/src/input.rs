@@ -2,6 +2,10 @@
 //!
 //! This module provides a terminal-agnostic input representation, decoupling
 //! the application core from specific terminal libraries like crossterm.
+//!
+//! With the `crossterm` feature, crossterm events convert to [`AppEvent`]s
+//! with `AppEvent::from`, so a frontend built on crossterm does not have to
+//! translate them itself.
 
 /// Application-level key representation.
 ///
@@ -22,10 +26,22 @@ pub enum AppKey {
     Down,
     Left,
     Right,
+    /// Arrow keys with Shift held, which extend a list or editor selection
+    ShiftUp,
+    ShiftDown,
+    ShiftLeft,
+    ShiftRight,
+    /// Page Up and Page Down keys
+    PageUp,
+    PageDown,
     /// Enter/Return key
     Enter,
     /// Backspace key
     Backspace,
+    /// A character key with Ctrl held, e.g. `Ctrl('s')`
+    Ctrl(char),
+    /// A character key with Alt held, e.g. `Alt('x')`
+    Alt(char),
     /// Any other key we don't specifically handle
     Other,
 }
@@ -33,21 +49,105 @@ pub enum AppKey {
 /// Application-level event representation.
 ///
 /// Decoupled from crossterm events to enable testing without a TTY.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AppEvent {
     /// A key was pressed
     Key(AppKey),
+    /// A mouse button, drag or scroll wheel event
+    Mouse(MouseEvent),
+    /// Text was pasted into the terminal (bracketed paste)
+    Paste(String),
+    /// The terminal window gained focus
+    FocusGained,
+    /// The terminal window lost focus
+    FocusLost,
     /// Terminal was resized to (width, height)
     Resize(u16, u16),
     /// Tick event for periodic updates (optional, for animations/polling)
     Tick,
+    /// Any other event we don't specifically handle, e.g. a key release or
+    /// a plain mouse move
+    Other,
+}
+
+/// A mouse button.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+}
+
+/// What happened in a mouse event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseEventKind {
+    /// A button was pressed (a click)
+    Down(MouseButton),
+    /// A button was released
+    Up(MouseButton),
+    /// The mouse moved while a button was held
+    Drag(MouseButton),
+    /// The scroll wheel moved up (towards the start of the content)
+    ScrollUp,
+    /// The scroll wheel moved down (towards the end of the content)
+    ScrollDown,
+}
+
+/// A mouse event at a terminal cell.
+///
+/// Decoupled from crossterm's mouse event in the same way as [`AppKey`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MouseEvent {
+    /// What happened
+    pub kind: MouseEventKind,
+    /// Zero-based column of the cell under the cursor
+    pub column: u16,
+    /// Zero-based row of the cell under the cursor
+    pub row: u16,
+}
+
+impl MouseEvent {
+    /// Create a mouse event at the given cell.
+    pub fn new(kind: MouseEventKind, column: u16, row: u16) -> Self {
+        Self { kind, column, row }
+    }
 }
 
 impl AppKey {
+    /// Name of the key as lists of bindings show it, e.g. `Shift+Up`.
+    pub fn label(self) -> String {
+        let label = match self {
+            AppKey::Q => "q",
+            AppKey::Esc => "Esc",
+            AppKey::Tab => "Tab",
+            AppKey::Char(' ') => "Space",
+            AppKey::Char(c) => return c.to_string(),
+            AppKey::Up => "Up",
+            AppKey::Down => "Down",
+            AppKey::Left => "Left",
+            AppKey::Right => "Right",
+            AppKey::ShiftUp => "Shift+Up",
+            AppKey::ShiftDown => "Shift+Down",
+            AppKey::ShiftLeft => "Shift+Left",
+            AppKey::ShiftRight => "Shift+Right",
+            AppKey::PageUp => "PageUp",
+            AppKey::PageDown => "PageDown",
+            AppKey::Enter => "Enter",
+            AppKey::Backspace => "Backspace",
+            AppKey::Ctrl(c) => return format!("Ctrl+{c}"),
+            AppKey::Alt(c) => return format!("Alt+{c}"),
+            AppKey::Other => "Other",
+        };
+        label.to_string()
+    }
+
     /// Create an AppKey from a character.
+    ///
+    /// Only a lowercase `q` is [`AppKey::Q`]; `Q` stays a character so it
+    /// can be typed.
     pub fn from_char(c: char) -> Self {
         match c {
-            'q' | 'Q' => AppKey::Q,
+            'q' => AppKey::Q,
             '\t' => AppKey::Tab,
             '\n' | '\r' => AppKey::Enter,
             c => AppKey::Char(c),
@@ -55,6 +155,88 @@ impl AppKey {
     }
 }
 
+#[cfg(feature = "crossterm")]
+mod from_crossterm {
+    use super::{AppEvent, AppKey, MouseButton, MouseEvent, MouseEventKind};
+    use crossterm::event::{
+        Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton as CrosstermMouseButton,
+        MouseEvent as CrosstermMouseEvent, MouseEventKind as CrosstermMouseEventKind,
+    };
+
+    impl From<KeyEvent> for AppKey {
+        /// Convert a key press. Shift selects the arrow keys' Shift
+        /// variants and Ctrl or Alt with a character make [`AppKey::Ctrl`]
+        /// or [`AppKey::Alt`]; other modifiers are dropped.
+        fn from(key: KeyEvent) -> Self {
+            let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+            let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+            let alt = key.modifiers.contains(KeyModifiers::ALT);
+            match key.code {
+                KeyCode::Up if shift => AppKey::ShiftUp,
+                KeyCode::Down if shift => AppKey::ShiftDown,
+                KeyCode::Left if shift => AppKey::ShiftLeft,
+                KeyCode::Right if shift => AppKey::ShiftRight,
+                KeyCode::Char(c) if ctrl => AppKey::Ctrl(c),
+                KeyCode::Char(c) if alt => AppKey::Alt(c),
+                KeyCode::Char('q') => AppKey::Q,
+                KeyCode::Esc => AppKey::Esc,
+                KeyCode::Tab => AppKey::Tab,
+                KeyCode::Enter => AppKey::Enter,
+                KeyCode::Backspace => AppKey::Backspace,
+                KeyCode::Up => AppKey::Up,
+                KeyCode::Down => AppKey::Down,
+                KeyCode::Left => AppKey::Left,
+                KeyCode::Right => AppKey::Right,
+                KeyCode::PageUp => AppKey::PageUp,
+                KeyCode::PageDown => AppKey::PageDown,
+                KeyCode::Char(c) => AppKey::Char(c),
+                _ => AppKey::Other,
+            }
+        }
+    }
+
+    impl From<CrosstermMouseButton> for MouseButton {
+        fn from(button: CrosstermMouseButton) -> Self {
+            match button {
+                CrosstermMouseButton::Left => MouseButton::Left,
+                CrosstermMouseButton::Right => MouseButton::Right,
+                CrosstermMouseButton::Middle => MouseButton::Middle,
+            }
+        }
+    }
+
+    impl From<Event> for AppEvent {
+        /// Convert a crossterm event.
+        ///
+        /// Key releases, plain mouse moves and horizontal scrolling become
+        /// [`AppEvent::Other`].
+        fn from(event: Event) -> Self {
+            match event {
+                Event::Key(key) if key.kind == KeyEventKind::Release => AppEvent::Other,
+                Event::Key(key) => AppEvent::Key(key.into()),
+                Event::Mouse(mouse) => mouse_event(mouse).map_or(AppEvent::Other, AppEvent::Mouse),
+                Event::Paste(text) => AppEvent::Paste(text),
+                Event::FocusGained => AppEvent::FocusGained,
+                Event::FocusLost => AppEvent::FocusLost,
+                Event::Resize(width, height) => AppEvent::Resize(width, height),
+            }
+        }
+    }
+
+    /// Convert a mouse event the app handles.
+    fn mouse_event(mouse: CrosstermMouseEvent) -> Option<MouseEvent> {
+        let kind = match mouse.kind {
+            CrosstermMouseEventKind::Down(button) => MouseEventKind::Down(button.into()),
+            CrosstermMouseEventKind::Up(button) => MouseEventKind::Up(button.into()),
+            CrosstermMouseEventKind::Drag(button) => MouseEventKind::Drag(button.into()),
+            CrosstermMouseEventKind::ScrollUp => MouseEventKind::ScrollUp,
+            CrosstermMouseEventKind::ScrollDown => MouseEventKind::ScrollDown,
+            _ => return None,
+        };
+        Some(MouseEvent::new(kind, mouse.column, mouse.row))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -62,7 +244,7 @@ mod tests {
     #[test]
     fn test_app_key_from_char() {
         assert_eq!(AppKey::from_char('q'), AppKey::Q);
-        assert_eq!(AppKey::from_char('Q'), AppKey::Q);
+        assert_eq!(AppKey::from_char('Q'), AppKey::Char('Q'));
         assert_eq!(AppKey::from_char('\t'), AppKey::Tab);
         assert_eq!(AppKey::from_char('a'), AppKey::Char('a'));
     }
@@ -73,4 +255,82 @@ mod tests {
         assert_eq!(AppEvent::Resize(80, 24), AppEvent::Resize(80, 24));
         assert_ne!(AppEvent::Key(AppKey::Q), AppEvent::Key(AppKey::Esc));
     }
+
+    #[cfg(feature = "crossterm")]
+    #[test]
+    fn test_crossterm_events_convert() {
+        use crossterm::event::{
+            Event, KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers,
+            MouseEvent as CrosstermMouseEvent, MouseEventKind as CrosstermMouseEventKind,
+        };
+
+        let key = |code, modifiers| Event::Key(KeyEvent::new(code, modifiers));
+        assert_eq!(
+            AppEvent::from(key(KeyCode::Char('s'), KeyModifiers::CONTROL)),
+            AppEvent::Key(AppKey::Ctrl('s'))
+        );
+        assert_eq!(
+            AppEvent::from(key(KeyCode::Char('x'), KeyModifiers::ALT)),
+            AppEvent::Key(AppKey::Alt('x'))
+        );
+        assert_eq!(
+            AppEvent::from(key(KeyCode::Left, KeyModifiers::SHIFT)),
+            AppEvent::Key(AppKey::ShiftLeft)
+        );
+        assert_eq!(
+            AppEvent::from(key(KeyCode::Char('Q'), KeyModifiers::SHIFT)),
+            AppEvent::Key(AppKey::Char('Q'))
+        );
+        let release = KeyEvent::new_with_kind_and_state(
+            KeyCode::Char('a'),
+            KeyModifiers::NONE,
+            KeyEventKind::Release,
+            KeyEventState::NONE,
+        );
+        assert_eq!(AppEvent::from(Event::Key(release)), AppEvent::Other);
+
+        let mouse = |kind| {
+            Event::Mouse(CrosstermMouseEvent {
+                kind,
+                column: 3,
+                row: 4,
+                modifiers: KeyModifiers::NONE,
+            })
+        };
+        assert_eq!(
+            AppEvent::from(mouse(CrosstermMouseEventKind::Drag(
+                crossterm::event::MouseButton::Right
+            ))),
+            AppEvent::Mouse(MouseEvent::new(
+                MouseEventKind::Drag(MouseButton::Right),
+                3,
+                4
+            ))
+        );
+        assert_eq!(
+            AppEvent::from(mouse(CrosstermMouseEventKind::Moved)),
+            AppEvent::Other
+        );
+        assert_eq!(
+            AppEvent::from(Event::Paste("a\nb".to_string())),
+            AppEvent::Paste("a\nb".to_string())
+        );
+        assert_eq!(AppEvent::from(Event::FocusLost), AppEvent::FocusLost);
+        assert_eq!(
+            AppEvent::from(Event::Resize(80, 24)),
+            AppEvent::Resize(80, 24)
+        );
+    }
+
+    #[test]
+    fn test_mouse_event_equality() {
+        let click = MouseEvent::new(MouseEventKind::Down(MouseButton::Left), 3, 4);
+        assert_eq!(click.column, 3);
+        assert_eq!(click.row, 4);
+        assert_eq!(AppEvent::Mouse(click), AppEvent::Mouse(click));
+        assert_ne!(
+            click,
+            MouseEvent::new(MouseEventKind::Down(MouseButton::Right), 3, 4)
+        );
+    }
 }
@@ -3,10 +3,25 @@
 //! The `KeybindingRouter` manages key-to-action mappings and dispatches
 //! key events to the appropriate handlers. It supports global bindings
 //! (always active) and context-aware routing based on focus state.
+//!
+//! Context bindings take precedence over global bindings: when a key is
+//! bound both globally and for the focused window's context, the context
+//! binding wins. Keys without a context binding fall back to the globals.
+//! Buffer-local bindings (see [`crate::buffer_options`]) sit above both and
+//! are passed to [`KeybindingRouter::dispatch_with_buffer`] by the App.
+//! While modal editing is on, the editor's [`EditingMode`] bindings sit
+//! above all of them; see [`KeybindingRouter::dispatch_in_mode`].
+//!
+//! Conditional bindings carry a `when` expression over the context keys of
+//! a [`ContextKeyService`] and sit above everything else while it holds;
+//! see [`KeybindingRouter::dispatch_when`].
 
 use std::collections::HashMap;
 
+use cli_ide_platform::context::context_key_service::{ContextKeyExpr, ContextKeyService};
+
 use crate::input::AppKey;
+use crate::layout::PaneDirection;
 
 /// Actions that can be triggered by keybindings.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -19,18 +34,519 @@ pub enum Action {
     FocusNext,
     /// Move focus to the previous window.
     FocusPrev,
+    /// Move focus to the window beside the focused one in a direction.
+    FocusDirection(PaneDirection),
+    /// Close the focused window; its neighbors take over the space.
+    CloseWindow,
+    /// Open the command palette.
+    OpenCommandPalette,
+    /// Open the ex-style command line.
+    OpenCommandLine,
+    /// Open the context menu of the focused window.
+    ShowContextMenu,
+    /// Grow the editor selection to the enclosing word, brackets or lines.
+    ExpandBracketSelection,
+    /// Shrink the editor selection to its previous range.
+    ShrinkBracketSelection,
+    /// Show the quick-fix menu of code actions for the editor selection.
+    ShowCodeActions,
+    /// Peek the definition of the symbol at the editor selection.
+    PeekDefinition,
+    /// Rename the symbol at the editor selection.
+    RenameSymbol,
+    /// Open the link under the editor cursor.
+    OpenLink,
+    /// Open the editor's find and replace input.
+    Find,
+    /// Select the next match of the editor's find input.
+    FindNext,
+    /// Select the previous match of the editor's find input.
+    FindPrevious,
+    /// Replace the selected match of the editor's find input.
+    ReplaceOne,
+    /// Replace every match of the editor's find input.
+    ReplaceAll,
+    /// Undo the last edit in the editor.
+    Undo,
+    /// Redo the last undone edit in the editor.
+    Redo,
+    /// Scroll the editor up a page, moving the cursor with it.
+    ScrollPageUp,
+    /// Scroll the editor down a page, moving the cursor with it.
+    ScrollPageDown,
+    /// Scroll the editor up a line, leaving the cursor where it is.
+    ScrollLineUp,
+    /// Scroll the editor down a line, leaving the cursor where it is.
+    ScrollLineDown,
+    /// Switch to the next built-in color theme.
+    ToggleTheme,
+    /// Open an empty untitled buffer in the focused editor.
+    NewUntitledFile,
+    /// Save the focused editor, prompting for a path if it has none.
+    Save,
+    /// Export the focused editor's selection, or its whole document,
+    /// highlighted as ANSI text, prompting for a path.
+    ExportAsAnsi,
+    /// Export the focused editor's selection, or its whole document,
+    /// highlighted as an HTML page, prompting for a path.
+    ExportAsHtml,
+    /// Pick a workspace file to open by typing part of its path.
+    QuickOpen,
+    /// Open the file explorer, or close it if it is open.
+    ToggleExplorer,
+    /// Create a file in the explorer's folder under the cursor.
+    ExplorerNewFile,
+    /// Create a folder in the explorer's folder under the cursor.
+    ExplorerNewFolder,
+    /// Rename the explorer entry under the cursor.
+    ExplorerRename,
+    /// Move the marked explorer entries, or the one under the cursor, to
+    /// the trash.
+    ExplorerDelete,
+    /// Move the marked explorer entries into the folder under the cursor.
+    ExplorerMove,
+    /// Read the explorer's folders from disk again.
+    ExplorerRefresh,
+    /// Prompt for a folder to add as a root of the workspace.
+    AddRootFolder,
+    /// Remove the workspace root under the explorer's cursor, unless it is
+    /// the primary root.
+    RemoveRootFolder,
+    /// Open the search panel, or close it if it is open.
+    ToggleSearch,
+    /// Replace the included matches in the search panel.
+    ReplaceInFiles,
+    /// Open the Find in Files panel, or close it if it is open.
+    FindInFiles,
+    /// Show the editor's next tab.
+    NextTab,
+    /// Show the editor's previous tab.
+    PrevTab,
+    /// Close the editor's active tab.
+    CloseTab,
+    /// Open the most recently closed editor tab again.
+    ReopenClosedEditor,
+    /// Close the editor's tabs except the active one and the pinned ones.
+    CloseOtherTabs,
+    /// Pin the editor's active tab, or unpin it.
+    TogglePinnedTab,
+    /// Move the editor's active tab one place left.
+    MoveTabLeft,
+    /// Move the editor's active tab one place right.
+    MoveTabRight,
+    /// Move the editor's active tab to the next editor.
+    MoveTabToNextGroup,
+    /// Show the editor's document in a second editor to its right.
+    SplitEditorRight,
+    /// Show the editor's document in a second editor below it.
+    SplitEditorDown,
+    /// Widen the focused pane, narrowing its neighbor.
+    IncreaseViewWidth,
+    /// Narrow the focused pane, widening its neighbor.
+    DecreaseViewWidth,
+    /// Make the focused pane taller, its neighbor shorter.
+    IncreaseViewHeight,
+    /// Make the focused pane shorter, its neighbor taller.
+    DecreaseViewHeight,
+    /// Open the regex tester, or close it if it is open.
+    ToggleRegexTester,
+    /// Open the undo tree of the focused editor's document, or close it if
+    /// it is open.
+    ToggleUndoTree,
+    /// Open the list of active keybindings, or close it if it is open.
+    ShowKeybindings,
+    /// Open the snippet manager, or close it if it is open.
+    ToggleSnippets,
+    /// Open the list of TODO comments in the workspace, or close it if it
+    /// is open.
+    ToggleTodos,
+    /// Open the git panel, or close it if it is open.
+    ToggleGitPanel,
+    /// Read the git status and history again.
+    GitRefresh,
+    /// Stage the hunk selected in the diff view.
+    StageHunk,
+    /// Unstage the hunk selected in the diff view.
+    UnstageHunk,
+    /// Undo the hunk selected in the diff view in the work tree.
+    RevertHunk,
+    /// Switch the diff view between unified and side by side.
+    ToggleDiffLayout,
+    /// Show the unsaved changes of the active file in the diff view.
+    CompareWithSaved,
+    /// Show the changes of the active file since `HEAD` in the diff view.
+    CompareWithHead,
+    /// Show the active document against the clipboard in the diff view.
+    CompareWithClipboard,
+    /// Prompt for a file and show the active document against it in the
+    /// diff view.
+    CompareWithFile,
+    /// Open the git history, or close it if it is open.
+    ToggleGitLog,
+    /// Pick a branch to check out, or create one.
+    CheckoutBranch,
+    /// Stash the local changes.
+    StashChanges,
+    /// Apply and drop the newest stash.
+    PopLatestStash,
+    /// Pick a stash to apply and drop.
+    PopStash,
+    /// Run `cargo build` on the workspace.
+    CargoBuild,
+    /// Run `cargo test` on the workspace.
+    CargoTest,
+    /// Run `cargo run` on the workspace.
+    CargoRun,
+    /// Run `cargo clippy` on the workspace.
+    CargoClippy,
+    /// Kill the running cargo command or task.
+    CargoStop,
+    /// Pick a configured task to run.
+    RunTask,
+    /// Show or hide the plugin window with this index in the App's
+    /// [`WorkbenchRegistry`](crate::plugin::WorkbenchRegistry).
+    TogglePluginWindow(usize),
+    /// Run the command of the plugin keybinding with this index in the
+    /// App's [`WorkbenchRegistry`](crate::plugin::WorkbenchRegistry).
+    RunPluginCommand(usize),
+    /// Open the Problems panel, or close it if it is open.
+    ToggleProblems,
+    /// Open the Diagnostics panel, or close it if it is open.
+    ToggleDiagnostics,
+    /// Move the editor cursor to the next problem in its document.
+    NextDiagnostic,
+    /// Move the editor cursor to the previous problem in its document.
+    PreviousDiagnostic,
+    /// Open the output pane, or close it if it is open.
+    ToggleOutput,
+    /// Open the log viewer, or close it if it is open.
+    ToggleLogs,
+    /// Open the terminal capability report, or close it if it is open.
+    ToggleTerminalCapabilities,
+    /// Open the presentation mirror of the focused editor, or close it if
+    /// it is open.
+    ToggleMirror,
+    /// Open the Markdown preview of the focused editor's document, or
+    /// close it if it is open.
+    ToggleMarkdownPreview,
+    /// Show or hide the scrollbars of scrollable windows.
+    ToggleScrollbars,
+    /// Show or hide the line, word and character counts of the editor's
+    /// document or selection in the status bar.
+    ToggleWordCount,
+    /// Draw the focused pane compact, or with all its borders and
+    /// decorations again.
+    ToggleCompactMode,
+    /// Switch the UI density between comfortable and compact.
+    ToggleDensity,
+    /// Cancel the newest running operation that can be cancelled.
+    CancelProgress,
+    /// Open the notification center, or close it if it is open.
+    ToggleNotifications,
+    /// Turn "do not disturb" on or off.
+    ToggleDoNotDisturb,
+    /// Remove every notification from the notification center.
+    ClearNotifications,
+    /// Dismiss every notification toast; they stay in the center.
+    HideNotificationToasts,
+    /// Start the guided tutorial, or stop it if it is running.
+    ToggleTutorial,
+    /// Turn modal (vim-style) editing on or off.
+    ToggleModalEditing,
+    /// Switch modal editing to Normal mode.
+    EnterNormalMode,
+    /// Switch modal editing to Insert mode, where keys type text.
+    EnterInsertMode,
+    /// Switch modal editing to Visual mode, where moves extend the selection.
+    EnterVisualMode,
+    /// Move the editor's cursor one character left.
+    CursorLeft,
+    /// Move the editor's cursor one line down.
+    CursorDown,
+    /// Move the editor's cursor one line up.
+    CursorUp,
+    /// Move the editor's cursor one character right.
+    CursorRight,
+    /// Extend the editor's selection one character left, moving its head.
+    SelectLeft,
+    /// Extend the editor's selection one line down.
+    SelectDown,
+    /// Extend the editor's selection one line up.
+    SelectUp,
+    /// Extend the editor's selection one character right.
+    SelectRight,
+    /// Delete the selection, or the character under the cursor.
+    DeleteCharacter,
+    /// Delete the selection, or the character before the cursor.
+    DeleteLeft,
+    /// Copy the editor's selection to the clipboard.
+    Copy,
+    /// Copy the editor's selection to the clipboard and delete it.
+    Cut,
+    /// Paste the clipboard into the focused terminal, or over the editor's
+    /// selection.
+    Paste,
     /// No action (key was handled but no action taken).
     None,
 }
 
+/// The kind of window a context-specific binding applies to.
+///
+/// The App maps the currently focused window to a `WindowContext` and passes
+/// it to [`KeybindingRouter::dispatch_in_context`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WindowContext {
+    /// The editor pane.
+    Editor,
+    /// The terminal pane.
+    Terminal,
+    /// The file explorer.
+    Explorer,
+    /// The search panel.
+    Search,
+    /// The git panel.
+    Git,
+    /// The diff view.
+    Diff,
+    /// The git history.
+    GitLog,
+    /// The Problems panel.
+    Problems,
+    /// The log viewer.
+    Logs,
+    /// The Markdown preview.
+    MarkdownPreview,
+}
+
+impl WindowContext {
+    /// Every context, in the order bindings are listed.
+    pub const ALL: [WindowContext; 10] = [
+        WindowContext::Editor,
+        WindowContext::Terminal,
+        WindowContext::Explorer,
+        WindowContext::Search,
+        WindowContext::Git,
+        WindowContext::Diff,
+        WindowContext::GitLog,
+        WindowContext::Problems,
+        WindowContext::Logs,
+        WindowContext::MarkdownPreview,
+    ];
+
+    /// Name of the context in settings, e.g. `gitLog` for
+    /// `[keybindings.gitLog]`.
+    pub fn name(self) -> &'static str {
+        match self {
+            WindowContext::Editor => "editor",
+            WindowContext::Terminal => "terminal",
+            WindowContext::Explorer => "explorer",
+            WindowContext::Search => "search",
+            WindowContext::Git => "git",
+            WindowContext::Diff => "diff",
+            WindowContext::GitLog => "gitLog",
+            WindowContext::Problems => "problems",
+            WindowContext::Logs => "logs",
+            WindowContext::MarkdownPreview => "markdownPreview",
+        }
+    }
+
+    /// Find the context named `name` in settings.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|context| context.name() == name)
+    }
+
+    /// Name of the context's window, as the status bar shows it.
+    pub fn label(self) -> &'static str {
+        match self {
+            WindowContext::Editor => "Editor",
+            WindowContext::Terminal => "Terminal",
+            WindowContext::Explorer => "Explorer",
+            WindowContext::Search => "Search",
+            WindowContext::Git => "Git",
+            WindowContext::Diff => "Diff",
+            WindowContext::GitLog => "Git History",
+            WindowContext::Problems => "Problems",
+            WindowContext::Logs => "Logs",
+            WindowContext::MarkdownPreview => "Markdown Preview",
+        }
+    }
+}
+
+/// Mode of the optional vim-style modal editing layer.
+///
+/// Each mode has its own bindings in the router. In Normal mode keys run
+/// commands, in Insert mode unbound keys type text, and in Visual mode
+/// cursor moves extend the selection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum EditingMode {
+    /// Keys run commands.
+    #[default]
+    Normal,
+    /// Keys type text.
+    Insert,
+    /// Cursor moves extend the selection.
+    Visual,
+}
+
+impl EditingMode {
+    /// Every mode, in the order bindings are listed.
+    pub const ALL: [EditingMode; 3] = [
+        EditingMode::Normal,
+        EditingMode::Insert,
+        EditingMode::Visual,
+    ];
+
+    /// Name of the mode.
+    pub fn label(self) -> &'static str {
+        match self {
+            EditingMode::Normal => "Normal",
+            EditingMode::Insert => "Insert",
+            EditingMode::Visual => "Visual",
+        }
+    }
+}
+
+/// Which layer of the App currently receives keys.
+///
+/// Modal overlays capture every key; otherwise keys go through the router.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum KeybindingMode {
+    /// Keys are dispatched through the router.
+    #[default]
+    Normal,
+    /// The command palette is open.
+    CommandPalette,
+    /// The command line is open.
+    CommandLine,
+    /// The quick-fix menu is open.
+    QuickFix,
+    /// A context menu is open.
+    ContextMenu,
+    /// The quick-open file picker is open.
+    QuickOpen,
+    /// The focused editor has a peek window open.
+    Peek,
+    /// The editor's rename input is open.
+    Rename,
+    /// The focused editor's find input is editing its query.
+    Find,
+    /// An input box such as the "Save As" prompt is open.
+    Input,
+    /// The focused explorer is filtering its entries.
+    Filter,
+    /// The focused search or Find in Files panel is editing its query.
+    Search,
+    /// The branch picker is open.
+    BranchPicker,
+    /// The stash picker is open.
+    StashPicker,
+    /// The task picker is open.
+    TaskPicker,
+    /// A modal dialog is open.
+    Dialog,
+    /// Modal editing is on and the focused editor is in Insert mode.
+    Insert,
+    /// Modal editing is on and the focused editor is in Visual mode.
+    Visual,
+}
+
+impl KeybindingMode {
+    /// Short label shown in the status bar.
+    pub fn label(self) -> &'static str {
+        match self {
+            KeybindingMode::Normal => "NORMAL",
+            KeybindingMode::CommandPalette => "PALETTE",
+            KeybindingMode::CommandLine => "COMMAND",
+            KeybindingMode::QuickFix => "QUICK FIX",
+            KeybindingMode::ContextMenu => "MENU",
+            KeybindingMode::QuickOpen => "QUICK OPEN",
+            KeybindingMode::Peek => "PEEK",
+            KeybindingMode::Rename => "RENAME",
+            KeybindingMode::Find => "FIND",
+            KeybindingMode::Input => "INPUT",
+            KeybindingMode::Filter => "FILTER",
+            KeybindingMode::Search => "SEARCH",
+            KeybindingMode::BranchPicker => "BRANCH",
+            KeybindingMode::StashPicker => "STASH",
+            KeybindingMode::TaskPicker => "TASK",
+            KeybindingMode::Dialog => "DIALOG",
+            KeybindingMode::Insert => "INSERT",
+            KeybindingMode::Visual => "VISUAL",
+        }
+    }
+}
+
+/// Where a binding registered with the router is active.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BindingScope {
+    /// Always.
+    Global,
+    /// While a window of the context has focus.
+    Context(WindowContext),
+    /// While modal editing is on and the focused editor is in the mode.
+    Mode(EditingMode),
+}
+
+impl BindingScope {
+    /// Name of the scope, as the keybindings list heads its group.
+    pub fn label(self) -> String {
+        match self {
+            BindingScope::Global => "Global".to_string(),
+            BindingScope::Context(context) => context.label().to_string(),
+            BindingScope::Mode(mode) => format!("{} mode", mode.label()),
+        }
+    }
+}
+
+/// A key bound to an action in a scope.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Binding {
+    /// Where the binding is active.
+    pub scope: BindingScope,
+    /// The bound key.
+    pub key: AppKey,
+    /// The action the key runs.
+    pub action: Action,
+}
+
+/// A key bound in two scopes that are active together, to different
+/// actions: the narrower binding hides the wider one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeybindingConflict {
+    /// The binding that wins.
+    pub binding: Binding,
+    /// The binding it hides.
+    pub shadowed: Binding,
+}
+
+/// A key bound to an action while a `when` expression holds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConditionalBinding {
+    /// Context the binding is limited to, or `None` for every window.
+    pub context: Option<WindowContext>,
+    /// The bound key.
+    pub key: AppKey,
+    /// Condition over the context keys.
+    pub when: ContextKeyExpr,
+    /// The action the key runs.
+    pub action: Action,
+}
+
 /// Routes key events to actions based on registered bindings.
 ///
 /// The router maintains a set of global bindings that are always active
-/// regardless of which window has focus. Future versions will support
-/// context-specific bindings based on the focused window.
+/// regardless of which window has focus, plus per-context bindings that are
+/// only active while a window of that context is focused, plus per-mode
+/// bindings for the editor while modal editing is on.
 pub struct KeybindingRouter {
     /// Global keybindings (always active).
     global_bindings: HashMap<AppKey, Action>,
+    /// Context-specific keybindings (active only when the context is focused).
+    context_bindings: HashMap<WindowContext, HashMap<AppKey, Action>>,
+    /// Modal editing keybindings (active only in their mode).
+    mode_bindings: HashMap<EditingMode, HashMap<AppKey, Action>>,
+    /// Keybindings active while their condition holds, oldest first.
+    conditional_bindings: Vec<ConditionalBinding>,
 }
 
 impl Default for KeybindingRouter {
@@ -45,15 +561,188 @@ impl KeybindingRouter {
     /// Default bindings:
     /// - `Q` / `Esc` → Quit
     /// - `Tab` → ToggleFocus
+    /// - `:` → OpenCommandPalette
+    /// - `L` → ToggleLogs
+    /// - `M` → ShowContextMenu
+    /// - `?` → ToggleTutorial
+    /// - `>` / `<` → IncreaseViewWidth / DecreaseViewWidth
+    /// - `+` / `-` → IncreaseViewHeight / DecreaseViewHeight
+    /// - `PageUp` / `PageDown` → ScrollPageUp / ScrollPageDown, scrolling
+    ///   the focused window
+    ///
+    /// Default editor context bindings:
+    /// - `.` → ShowCodeActions
+    /// - `]` / `[` → NextTab / PrevTab
+    /// - `w` / `W` → CloseTab / ReopenClosedEditor
+    /// - `{` / `}` → MoveTabLeft / MoveTabRight
+    /// - `/` → Find
+    /// - `n` / `N` → FindNext / FindPrevious
+    /// - `r` / `R` → ReplaceOne / ReplaceAll
+    /// - `u` / `U` → Undo / Redo
+    /// - `PageUp` / `PageDown` → ScrollPageUp / ScrollPageDown
+    /// - arrows → CursorLeft / CursorDown / CursorUp / CursorRight
+    /// - `Shift` + arrows → SelectLeft / SelectDown / SelectUp / SelectRight
+    /// - `Backspace` → DeleteLeft
+    /// - `y` / `d` / `p` → Copy / Cut / Paste
+    ///
+    /// Default terminal context bindings:
+    /// - `p` → Paste
+    ///
+    /// Default explorer context bindings:
+    /// - `a` / `A` → ExplorerNewFile / ExplorerNewFolder
+    /// - `r` → ExplorerRename
+    /// - `d` → ExplorerDelete
+    /// - `m` → ExplorerMove
+    /// - `R` → ExplorerRefresh
+    ///
+    /// Default Problems panel, log viewer and Markdown preview context
+    /// bindings:
+    /// - `y` → Copy
+    ///
+    /// Default search context bindings:
+    /// - `R` → ReplaceInFiles
+    ///
+    /// Default git panel and git history context bindings:
+    /// - `R` → GitRefresh
+    ///
+    /// Default diff view context bindings:
+    /// - `s` / `u` / `x` → StageHunk / UnstageHunk / RevertHunk
+    /// - `v` → ToggleDiffLayout
+    ///
+    /// Default modal editing bindings:
+    /// - Normal: `h` / `j` / `k` / `l` → CursorLeft / CursorDown / CursorUp /
+    ///   CursorRight, `i` → EnterInsertMode, `v` → EnterVisualMode,
+    ///   `x` → DeleteCharacter, `:` → OpenCommandLine, `Esc` → None
+    /// - Insert: `Esc` → EnterNormalMode
+    /// - Visual: `h` / `j` / `k` / `l` and `x` as in Normal mode, `Esc` /
+    ///   `v` → EnterNormalMode
     pub fn new() -> Self {
-        let mut router = Self {
-            global_bindings: HashMap::new(),
-        };
+        let mut router = Self::empty();
 
         // Register default bindings
         router.register_global(AppKey::Q, Action::Quit);
         router.register_global(AppKey::Esc, Action::Quit);
         router.register_global(AppKey::Tab, Action::ToggleFocus);
+        router.register_global(AppKey::Char(':'), Action::OpenCommandPalette);
+        router.register_global(AppKey::Char('L'), Action::ToggleLogs);
+        router.register_global(AppKey::Char('M'), Action::ShowContextMenu);
+        router.register_global(AppKey::Char('?'), Action::ToggleTutorial);
+        router.register_global(AppKey::Char('>'), Action::IncreaseViewWidth);
+        router.register_global(AppKey::Char('<'), Action::DecreaseViewWidth);
+        router.register_global(AppKey::Char('+'), Action::IncreaseViewHeight);
+        router.register_global(AppKey::Char('-'), Action::DecreaseViewHeight);
+        router.register_global(AppKey::PageUp, Action::ScrollPageUp);
+        router.register_global(AppKey::PageDown, Action::ScrollPageDown);
+        for (key, action) in [
+            ('.', Action::ShowCodeActions),
+            (']', Action::NextTab),
+            ('[', Action::PrevTab),
+            ('w', Action::CloseTab),
+            ('W', Action::ReopenClosedEditor),
+            ('{', Action::MoveTabLeft),
+            ('}', Action::MoveTabRight),
+            ('/', Action::Find),
+            ('n', Action::FindNext),
+            ('N', Action::FindPrevious),
+            ('r', Action::ReplaceOne),
+            ('R', Action::ReplaceAll),
+            ('u', Action::Undo),
+            ('U', Action::Redo),
+            ('y', Action::Copy),
+            ('d', Action::Cut),
+            ('p', Action::Paste),
+            ('e', Action::NextDiagnostic),
+            ('E', Action::PreviousDiagnostic),
+        ] {
+            router.register_for_context(WindowContext::Editor, AppKey::Char(key), action);
+        }
+        router.register_for_context(WindowContext::Editor, AppKey::PageUp, Action::ScrollPageUp);
+        router.register_for_context(
+            WindowContext::Editor,
+            AppKey::PageDown,
+            Action::ScrollPageDown,
+        );
+        for (key, action) in [
+            (AppKey::Left, Action::CursorLeft),
+            (AppKey::Down, Action::CursorDown),
+            (AppKey::Up, Action::CursorUp),
+            (AppKey::Right, Action::CursorRight),
+            (AppKey::ShiftLeft, Action::SelectLeft),
+            (AppKey::ShiftDown, Action::SelectDown),
+            (AppKey::ShiftUp, Action::SelectUp),
+            (AppKey::ShiftRight, Action::SelectRight),
+            (AppKey::Backspace, Action::DeleteLeft),
+        ] {
+            router.register_for_context(WindowContext::Editor, key, action);
+        }
+        for (key, action) in [
+            ('a', Action::ExplorerNewFile),
+            ('A', Action::ExplorerNewFolder),
+            ('r', Action::ExplorerRename),
+            ('d', Action::ExplorerDelete),
+            ('m', Action::ExplorerMove),
+            ('R', Action::ExplorerRefresh),
+        ] {
+            router.register_for_context(WindowContext::Explorer, AppKey::Char(key), action);
+        }
+        router.register_for_context(WindowContext::Terminal, AppKey::Char('p'), Action::Paste);
+        for context in [
+            WindowContext::Problems,
+            WindowContext::Logs,
+            WindowContext::MarkdownPreview,
+        ] {
+            router.register_for_context(context, AppKey::Char('y'), Action::Copy);
+        }
+        router.register_for_context(
+            WindowContext::Search,
+            AppKey::Char('R'),
+            Action::ReplaceInFiles,
+        );
+        for context in [WindowContext::Git, WindowContext::GitLog] {
+            router.register_for_context(context, AppKey::Char('R'), Action::GitRefresh);
+        }
+        for (key, action) in [
+            ('s', Action::StageHunk),
+            ('u', Action::UnstageHunk),
+            ('x', Action::RevertHunk),
+            ('v', Action::ToggleDiffLayout),
+        ] {
+            router.register_for_context(WindowContext::Diff, AppKey::Char(key), action);
+        }
+        for mode in [EditingMode::Normal, EditingMode::Visual] {
+            for (key, action) in [
+                ('h', Action::CursorLeft),
+                ('j', Action::CursorDown),
+                ('k', Action::CursorUp),
+                ('l', Action::CursorRight),
+                ('x', Action::DeleteCharacter),
+            ] {
+                router.register_for_mode(mode, AppKey::Char(key), action);
+            }
+        }
+        router.register_for_mode(
+            EditingMode::Normal,
+            AppKey::Char('i'),
+            Action::EnterInsertMode,
+        );
+        router.register_for_mode(
+            EditingMode::Normal,
+            AppKey::Char('v'),
+            Action::EnterVisualMode,
+        );
+        router.register_for_mode(
+            EditingMode::Normal,
+            AppKey::Char(':'),
+            Action::OpenCommandLine,
+        );
+        router.register_for_mode(EditingMode::Normal, AppKey::Esc, Action::None);
+        router.register_for_mode(EditingMode::Insert, AppKey::Esc, Action::EnterNormalMode);
+        router.register_for_mode(EditingMode::Visual, AppKey::Esc, Action::EnterNormalMode);
+        router.register_for_mode(
+            EditingMode::Visual,
+            AppKey::Char('v'),
+            Action::EnterNormalMode,
+        );
 
         router
     }
@@ -62,6 +751,9 @@ impl KeybindingRouter {
     pub fn empty() -> Self {
         Self {
             global_bindings: HashMap::new(),
+            context_bindings: HashMap::new(),
+            mode_bindings: HashMap::new(),
+            conditional_bindings: Vec::new(),
         }
     }
 
@@ -80,6 +772,114 @@ impl KeybindingRouter {
         self.global_bindings.remove(&key)
     }
 
+    /// Register a keybinding for a specific window context.
+    ///
+    /// The binding is only active while a window of that context has focus
+    /// and takes precedence over any global binding for the same key.
+    /// If the key was already bound in this context, the old binding is replaced.
+    pub fn register_for_context(&mut self, context: WindowContext, key: AppKey, action: Action) {
+        self.context_bindings
+            .entry(context)
+            .or_default()
+            .insert(key, action);
+    }
+
+    /// Unregister a keybinding from a specific window context.
+    ///
+    /// Returns the previously bound action, if any.
+    pub fn unregister_for_context(
+        &mut self,
+        context: WindowContext,
+        key: AppKey,
+    ) -> Option<Action> {
+        self.context_bindings
+            .get_mut(&context)
+            .and_then(|bindings| bindings.remove(&key))
+    }
+
+    /// Register a keybinding for a modal editing mode.
+    ///
+    /// The binding is only active while modal editing is on and the focused
+    /// editor is in `mode`, and takes precedence over every other binding
+    /// for the same key. If the key was already bound in this mode, the old
+    /// binding is replaced.
+    pub fn register_for_mode(&mut self, mode: EditingMode, key: AppKey, action: Action) {
+        self.mode_bindings
+            .entry(mode)
+            .or_default()
+            .insert(key, action);
+    }
+
+    /// Unregister a keybinding from a modal editing mode.
+    ///
+    /// Returns the previously bound action, if any.
+    pub fn unregister_for_mode(&mut self, mode: EditingMode, key: AppKey) -> Option<Action> {
+        self.mode_bindings
+            .get_mut(&mode)
+            .and_then(|bindings| bindings.remove(&key))
+    }
+
+    /// Register a keybinding active while `when` holds and, if `context`
+    /// is given, a window of that context has focus.
+    ///
+    /// Conditional bindings take precedence over every other binding for
+    /// the same key, and later ones over earlier ones. A binding for the
+    /// same context, key and condition is replaced.
+    pub fn register_when(
+        &mut self,
+        context: Option<WindowContext>,
+        key: AppKey,
+        when: ContextKeyExpr,
+        action: Action,
+    ) {
+        self.unregister_when(context, key, &when);
+        self.conditional_bindings.push(ConditionalBinding {
+            context,
+            key,
+            when,
+            action,
+        });
+    }
+
+    /// Unregister a conditional keybinding.
+    ///
+    /// Returns the previously bound action, if any.
+    pub fn unregister_when(
+        &mut self,
+        context: Option<WindowContext>,
+        key: AppKey,
+        when: &ContextKeyExpr,
+    ) -> Option<Action> {
+        let index = self.conditional_bindings.iter().position(|binding| {
+            binding.context == context && binding.key == key && binding.when == *when
+        })?;
+        Some(self.conditional_bindings.remove(index).action)
+    }
+
+    /// Get the conditional bindings, oldest first.
+    pub fn conditional_bindings(&self) -> &[ConditionalBinding] {
+        &self.conditional_bindings
+    }
+
+    /// Find the action of the latest conditional binding for `key` whose
+    /// condition holds in `keys`, limited to `context` or to none.
+    ///
+    /// The App tries this before the other dispatch methods.
+    pub fn dispatch_when(
+        &self,
+        keys: &ContextKeyService,
+        context: Option<WindowContext>,
+        key: AppKey,
+    ) -> Option<Action> {
+        self.conditional_bindings
+            .iter()
+            .rev()
+            .filter(|binding| binding.key == key)
+            .filter(|binding| binding.context.is_none() || binding.context == context)
+            .find(|binding| keys.evaluate(&binding.when))
+            .map(|binding| binding.action)
+    }
+
     /// Dispatch a key event and return the action to take.
     ///
     /// Returns `Some(Action)` if the key matches a global binding,
@@ -88,15 +888,165 @@ impl KeybindingRouter {
         self.global_bindings.get(&key).copied()
     }
 
+    /// Dispatch a key event for the given focus context.
+    ///
+    /// Context bindings are checked first; if the key is not bound in the
+    /// context (or no context is focused), global bindings are used.
+    pub fn dispatch_in_context(
+        &self,
+        context: Option<WindowContext>,
+        key: AppKey,
+    ) -> Option<Action> {
+        context
+            .and_then(|ctx| self.context_bindings.get(&ctx))
+            .and_then(|bindings| bindings.get(&key))
+            .copied()
+            .or_else(|| self.dispatch(key))
+    }
+
+    /// Dispatch a key event with buffer-local overrides.
+    ///
+    /// Bindings from `buffer` take precedence over context bindings, which
+    /// in turn take precedence over global bindings.
+    pub fn dispatch_with_buffer(
+        &self,
+        buffer: &HashMap<AppKey, Action>,
+        context: Option<WindowContext>,
+        key: AppKey,
+    ) -> Option<Action> {
+        buffer
+            .get(&key)
+            .copied()
+            .or_else(|| self.dispatch_in_context(context, key))
+    }
+
+    /// Dispatch a key event for the editor with modal editing on.
+    ///
+    /// Bindings for `mode` take precedence over buffer, context and global
+    /// bindings, in that order. In Insert mode, keys that type text
+    /// (characters, `Enter` and `Backspace`) go no further than the mode's
+    /// bindings: `None` tells the App to type them.
+    pub fn dispatch_in_mode(
+        &self,
+        mode: EditingMode,
+        buffer: &HashMap<AppKey, Action>,
+        key: AppKey,
+    ) -> Option<Action> {
+        let types_text = matches!(
+            key,
+            AppKey::Char(_) | AppKey::Q | AppKey::Enter | AppKey::Backspace
+        );
+        let bound = self
+            .mode_bindings
+            .get(&mode)
+            .and_then(|bindings| bindings.get(&key))
+            .copied();
+        if bound.is_some() || (mode == EditingMode::Insert && types_text) {
+            bound
+        } else {
+            self.dispatch_with_buffer(buffer, Some(WindowContext::Editor), key)
+        }
+    }
+
     /// Check if a key has a global binding.
     pub fn is_globally_bound(&self, key: AppKey) -> bool {
         self.global_bindings.contains_key(&key)
     }
 
+    /// Check if a key has a binding in the given context.
+    ///
+    /// Global bindings are not considered.
+    pub fn is_bound_in_context(&self, context: WindowContext, key: AppKey) -> bool {
+        self.context_bindings
+            .get(&context)
+            .is_some_and(|bindings| bindings.contains_key(&key))
+    }
+
     /// Get all global bindings.
     pub fn global_bindings(&self) -> &HashMap<AppKey, Action> {
         &self.global_bindings
     }
+
+    /// Get the bindings registered for a context, if any.
+    pub fn context_bindings(&self, context: WindowContext) -> Option<&HashMap<AppKey, Action>> {
+        self.context_bindings.get(&context)
+    }
+
+    /// Get the bindings registered for a modal editing mode, if any.
+    pub fn mode_bindings(&self, mode: EditingMode) -> Option<&HashMap<AppKey, Action>> {
+        self.mode_bindings.get(&mode)
+    }
+
+    /// List every binding: the global ones, then each context's in
+    /// [`WindowContext::ALL`] order, then each mode's, by key within a
+    /// scope.
+    pub fn bindings(&self) -> Vec<Binding> {
+        let scoped = |scope: BindingScope, bindings: Option<&HashMap<AppKey, Action>>| {
+            let mut bindings: Vec<Binding> = bindings
+                .into_iter()
+                .flatten()
+                .map(|(&key, &action)| Binding { scope, key, action })
+                .collect();
+            bindings.sort_by_key(|binding| binding.key.label());
+            bindings
+        };
+        let mut bindings = scoped(BindingScope::Global, Some(&self.global_bindings));
+        for context in WindowContext::ALL {
+            bindings.extend(scoped(
+                BindingScope::Context(context),
+                self.context_bindings(context),
+            ));
+        }
+        for mode in EditingMode::ALL {
+            bindings.extend(scoped(BindingScope::Mode(mode), self.mode_bindings(mode)));
+        }
+        bindings
+    }
+
+    /// Find a key running `action`, preferring a global binding, then the
+    /// order of [`KeybindingRouter::bindings`].
+    pub fn binding_for(&self, action: Action) -> Option<Binding> {
+        self.bindings()
+            .into_iter()
+            .find(|binding| binding.action == action)
+    }
+
+    /// Find keys bound to different actions in scopes active together.
+    ///
+    /// A context binding hides a global one for the same key. A mode
+    /// binding hides the editor's context binding, or the global one if
+    /// the editor has none.
+    pub fn conflicts(&self) -> Vec<KeybindingConflict> {
+        let global = |key: AppKey| {
+            self.global_bindings.get(&key).map(|&action| Binding {
+                scope: BindingScope::Global,
+                key,
+                action,
+            })
+        };
+        let in_context = |context: WindowContext, key: AppKey| {
+            self.context_bindings(context)
+                .and_then(|bindings| bindings.get(&key))
+                .map(|&action| Binding {
+                    scope: BindingScope::Context(context),
+                    key,
+                    action,
+                })
+        };
+        self.bindings()
+            .into_iter()
+            .filter_map(|binding| {
+                let shadowed = match binding.scope {
+                    BindingScope::Global => None,
+                    BindingScope::Context(_) => global(binding.key),
+                    BindingScope::Mode(_) => in_context(WindowContext::Editor, binding.key)
+                        .or_else(|| global(binding.key)),
+                }?;
+                (shadowed.action != binding.action)
+                    .then_some(KeybindingConflict { binding, shadowed })
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -110,6 +1060,11 @@ mod tests {
         assert_eq!(router.dispatch(AppKey::Q), Some(Action::Quit));
         assert_eq!(router.dispatch(AppKey::Esc), Some(Action::Quit));
         assert_eq!(router.dispatch(AppKey::Tab), Some(Action::ToggleFocus));
+        assert_eq!(
+            router.dispatch(AppKey::Char(':')),
+            Some(Action::OpenCommandPalette)
+        );
+        assert_eq!(router.dispatch(AppKey::Char('L')), Some(Action::ToggleLogs));
     }
 
     #[test]
@@ -184,13 +1139,302 @@ mod tests {
         let router = KeybindingRouter::new();
         let bindings = router.global_bindings();
 
-        assert_eq!(bindings.len(), 3);
+        assert_eq!(bindings.len(), 13);
         assert_eq!(bindings.get(&AppKey::Q), Some(&Action::Quit));
     }
 
+    #[test]
+    fn test_conflicts_and_reverse_lookup() {
+        let mut router = KeybindingRouter::empty();
+        router.register_global(AppKey::Q, Action::Quit);
+        router.register_global(AppKey::Char(':'), Action::OpenCommandPalette);
+        router.register_global(AppKey::PageUp, Action::ScrollPageUp);
+        router.register_for_context(WindowContext::Terminal, AppKey::Q, Action::None);
+        router.register_for_context(WindowContext::Editor, AppKey::PageUp, Action::ScrollPageUp);
+        router.register_for_context(WindowContext::Editor, AppKey::Char('x'), Action::Cut);
+        router.register_for_mode(
+            EditingMode::Normal,
+            AppKey::Char(':'),
+            Action::OpenCommandLine,
+        );
+        router.register_for_mode(
+            EditingMode::Normal,
+            AppKey::Char('x'),
+            Action::DeleteCharacter,
+        );
+
+        let conflicts: Vec<(BindingScope, AppKey, BindingScope)> = router
+            .conflicts()
+            .iter()
+            .map(|conflict| {
+                (
+                    conflict.binding.scope,
+                    conflict.binding.key,
+                    conflict.shadowed.scope,
+                )
+            })
+            .collect();
+        assert_eq!(
+            conflicts,
+            vec![
+                (
+                    BindingScope::Context(WindowContext::Terminal),
+                    AppKey::Q,
+                    BindingScope::Global
+                ),
+                (
+                    BindingScope::Mode(EditingMode::Normal),
+                    AppKey::Char(':'),
+                    BindingScope::Global
+                ),
+                (
+                    BindingScope::Mode(EditingMode::Normal),
+                    AppKey::Char('x'),
+                    BindingScope::Context(WindowContext::Editor)
+                ),
+            ]
+        );
+
+        assert_eq!(router.bindings().len(), 8);
+        assert_eq!(
+            router.binding_for(Action::ScrollPageUp),
+            Some(Binding {
+                scope: BindingScope::Global,
+                key: AppKey::PageUp,
+                action: Action::ScrollPageUp,
+            })
+        );
+        assert_eq!(
+            router.binding_for(Action::Cut).map(|binding| binding.scope),
+            Some(BindingScope::Context(WindowContext::Editor))
+        );
+        assert_eq!(router.binding_for(Action::Redo), None);
+    }
+
+    #[test]
+    fn test_context_binding_overrides_global() {
+        let mut router = KeybindingRouter::new();
+        router.register_for_context(WindowContext::Terminal, AppKey::Q, Action::None);
+
+        // Terminal context sees the override
+        assert_eq!(
+            router.dispatch_in_context(Some(WindowContext::Terminal), AppKey::Q),
+            Some(Action::None)
+        );
+        // Editor context still falls back to the global binding
+        assert_eq!(
+            router.dispatch_in_context(Some(WindowContext::Editor), AppKey::Q),
+            Some(Action::Quit)
+        );
+    }
+
+    #[test]
+    fn test_default_editor_context_bindings() {
+        let router = KeybindingRouter::new();
+        assert!(router.is_bound_in_context(WindowContext::Editor, AppKey::Char('.')));
+        assert_eq!(
+            router.dispatch_in_context(Some(WindowContext::Terminal), AppKey::Char('.')),
+            None
+        );
+    }
+
+    #[test]
+    fn test_buffer_binding_overrides_context_and_global() {
+        let mut router = KeybindingRouter::new();
+        router.register_for_context(WindowContext::Editor, AppKey::Enter, Action::FocusNext);
+        let buffer = HashMap::from([(AppKey::Enter, Action::ExpandBracketSelection)]);
+
+        assert_eq!(
+            router.dispatch_with_buffer(&buffer, Some(WindowContext::Editor), AppKey::Enter),
+            Some(Action::ExpandBracketSelection)
+        );
+        // Keys not overridden by the buffer fall through to the globals
+        assert_eq!(
+            router.dispatch_with_buffer(&buffer, Some(WindowContext::Editor), AppKey::Q),
+            Some(Action::Quit)
+        );
+    }
+
+    #[test]
+    fn test_mode_binding_overrides_buffer_context_and_global() {
+        let router = KeybindingRouter::new();
+        let buffer = HashMap::from([(AppKey::Char('u'), Action::None)]);
+
+        assert_eq!(
+            router.dispatch_in_mode(EditingMode::Normal, &buffer, AppKey::Char('j')),
+            Some(Action::CursorDown)
+        );
+        assert_eq!(
+            router.dispatch_in_mode(EditingMode::Insert, &buffer, AppKey::Esc),
+            Some(Action::EnterNormalMode)
+        );
+        // Insert mode leaves text keys to the App to type, even global ones
+        assert_eq!(
+            router.dispatch_in_mode(EditingMode::Insert, &buffer, AppKey::Char('j')),
+            None
+        );
+        assert_eq!(
+            router.dispatch_in_mode(EditingMode::Insert, &buffer, AppKey::Q),
+            None
+        );
+        assert_eq!(
+            router.dispatch_in_mode(EditingMode::Insert, &buffer, AppKey::Tab),
+            Some(Action::ToggleFocus)
+        );
+        assert_eq!(
+            router.dispatch_in_mode(EditingMode::Normal, &buffer, AppKey::Char(':')),
+            Some(Action::OpenCommandLine),
+            "`:` opens the command line rather than the palette"
+        );
+        // Keys without a mode binding fall through to the buffer and context
+        assert_eq!(
+            router.dispatch_in_mode(EditingMode::Normal, &buffer, AppKey::Char('u')),
+            Some(Action::None)
+        );
+        assert_eq!(
+            router.dispatch_in_mode(EditingMode::Visual, &buffer, AppKey::Char('/')),
+            Some(Action::Find)
+        );
+    }
+
+    #[test]
+    fn test_unregister_for_mode() {
+        let mut router = KeybindingRouter::new();
+        assert_eq!(
+            router.unregister_for_mode(EditingMode::Normal, AppKey::Char('x')),
+            Some(Action::DeleteCharacter)
+        );
+        assert!(!router
+            .mode_bindings(EditingMode::Normal)
+            .is_some_and(|bindings| bindings.contains_key(&AppKey::Char('x'))));
+        assert!(router.mode_bindings(EditingMode::Visual).is_some());
+    }
+
+    #[test]
+    fn test_same_key_different_contexts() {
+        let mut router = KeybindingRouter::empty();
+        router.register_for_context(WindowContext::Editor, AppKey::Enter, Action::FocusNext);
+        router.register_for_context(WindowContext::Terminal, AppKey::Enter, Action::FocusPrev);
+
+        assert_eq!(
+            router.dispatch_in_context(Some(WindowContext::Editor), AppKey::Enter),
+            Some(Action::FocusNext)
+        );
+        assert_eq!(
+            router.dispatch_in_context(Some(WindowContext::Terminal), AppKey::Enter),
+            Some(Action::FocusPrev)
+        );
+    }
+
+    #[test]
+    fn test_dispatch_in_context_falls_back_to_global() {
+        let router = KeybindingRouter::new();
+
+        assert_eq!(
+            router.dispatch_in_context(Some(WindowContext::Editor), AppKey::Tab),
+            Some(Action::ToggleFocus)
+        );
+        assert_eq!(
+            router.dispatch_in_context(None, AppKey::Esc),
+            Some(Action::Quit)
+        );
+        assert_eq!(
+            router.dispatch_in_context(Some(WindowContext::Editor), AppKey::Char('z')),
+            None
+        );
+    }
+
+    #[test]
+    fn test_context_binding_not_global() {
+        let mut router = KeybindingRouter::empty();
+        router.register_for_context(WindowContext::Editor, AppKey::Up, Action::FocusPrev);
+
+        assert_eq!(router.dispatch(AppKey::Up), None);
+        assert_eq!(router.dispatch_in_context(None, AppKey::Up), None);
+        assert!(router.is_bound_in_context(WindowContext::Editor, AppKey::Up));
+        assert!(!router.is_bound_in_context(WindowContext::Terminal, AppKey::Up));
+        assert!(!router.is_globally_bound(AppKey::Up));
+    }
+
+    #[test]
+    fn test_unregister_for_context() {
+        let mut router = KeybindingRouter::empty();
+        router.register_for_context(WindowContext::Editor, AppKey::Up, Action::FocusPrev);
+
+        let removed = router.unregister_for_context(WindowContext::Editor, AppKey::Up);
+        assert_eq!(removed, Some(Action::FocusPrev));
+        assert_eq!(
+            router.unregister_for_context(WindowContext::Terminal, AppKey::Up),
+            None
+        );
+        assert!(!router.is_bound_in_context(WindowContext::Editor, AppKey::Up));
+    }
+
+    #[test]
+    fn test_context_bindings_accessor() {
+        let mut router = KeybindingRouter::new();
+        let bindings = router.context_bindings(WindowContext::Problems).unwrap();
+        assert_eq!(bindings.get(&AppKey::Down), None);
+
+        router.register_for_context(WindowContext::Problems, AppKey::Down, Action::FocusNext);
+        let bindings = router.context_bindings(WindowContext::Problems).unwrap();
+        assert_eq!(bindings.get(&AppKey::Down), Some(&Action::FocusNext));
+    }
+
     #[test]
     fn test_action_equality() {
         assert_eq!(Action::Quit, Action::Quit);
         assert_ne!(Action::Quit, Action::ToggleFocus);
     }
+
+    #[test]
+    fn test_conditional_bindings_apply_while_their_condition_holds() {
+        let mut router = KeybindingRouter::new();
+        let keys = ContextKeyService::new();
+        let when = |text: &str| ContextKeyExpr::parse(text).unwrap();
+        router.register_when(
+            None,
+            AppKey::Char('x'),
+            when("mode == insert"),
+            Action::Quit,
+        );
+        router.register_when(
+            Some(WindowContext::Editor),
+            AppKey::Char('x'),
+            when("mode == insert && editorFocused"),
+            Action::Undo,
+        );
+        let editor = Some(WindowContext::Editor);
+        assert_eq!(router.dispatch_when(&keys, editor, AppKey::Char('x')), None);
+
+        keys.set("mode", "insert");
+        assert_eq!(
+            router.dispatch_when(&keys, editor, AppKey::Char('x')),
+            Some(Action::Quit)
+        );
+        keys.set("editorFocused", true);
+        assert_eq!(
+            router.dispatch_when(&keys, editor, AppKey::Char('x')),
+            Some(Action::Undo),
+            "the later binding wins"
+        );
+        assert_eq!(
+            router.dispatch_when(&keys, Some(WindowContext::Terminal), AppKey::Char('x')),
+            Some(Action::Quit)
+        );
+
+        assert_eq!(
+            router.unregister_when(
+                editor,
+                AppKey::Char('x'),
+                &when("mode == insert && editorFocused")
+            ),
+            Some(Action::Undo)
+        );
+        assert_eq!(router.conditional_bindings().len(), 1);
+        assert_eq!(
+            WindowContext::from_name("gitLog"),
+            Some(WindowContext::GitLog)
+        );
+    }
 }
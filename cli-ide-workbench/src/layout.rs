@@ -0,0 +1,527 @@
+//! Layout tree for tiling windows.
+//!
+//! A [`LayoutNode`] is either a single window or a split dividing its area
+//! between child nodes along one direction, in proportion to their
+//! weights. New splits are even; resizing a pane moves the edge it shares
+//! with a sibling and records the children's sizes as their weights, so
+//! the proportions hold when the terminal is resized. The tree decides
+//! where each window is drawn; the
+//! [`WindowManager`](crate::window_manager::WindowManager) owns the windows
+//! themselves.
+
+use cli_ide_platform::session::session_format::SessionSplit;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+
+use crate::window::WindowId;
+
+/// The fewest cells resizing leaves a pane along the resized direction:
+/// its two borders and a row or column of content.
+pub const MIN_PANE_SIZE: u16 = 3;
+
+/// How a split arranges its children.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SplitDirection {
+    /// Children side by side, left to right.
+    Horizontal,
+    /// Children stacked, top to bottom.
+    Vertical,
+}
+
+impl From<SplitDirection> for Direction {
+    fn from(direction: SplitDirection) -> Self {
+        match direction {
+            SplitDirection::Horizontal => Direction::Horizontal,
+            SplitDirection::Vertical => Direction::Vertical,
+        }
+    }
+}
+
+impl From<SplitDirection> for SessionSplit {
+    fn from(direction: SplitDirection) -> Self {
+        match direction {
+            SplitDirection::Horizontal => SessionSplit::Horizontal,
+            SplitDirection::Vertical => SessionSplit::Vertical,
+        }
+    }
+}
+
+impl From<SessionSplit> for SplitDirection {
+    fn from(direction: SessionSplit) -> Self {
+        match direction {
+            SessionSplit::Horizontal => SplitDirection::Horizontal,
+            SessionSplit::Vertical => SplitDirection::Vertical,
+        }
+    }
+}
+
+/// A direction to move from a pane to the one beside it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PaneDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// A node of the layout tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutNode {
+    /// A single window filling the node's area.
+    Window(WindowId),
+    /// A split of the node's area between `children`.
+    Split {
+        /// Direction children are laid out in.
+        direction: SplitDirection,
+        /// Child nodes, in layout order. Never fewer than two.
+        children: Vec<LayoutNode>,
+        /// Share of the area of each child, relative to the others; one
+        /// per child, none zero.
+        weights: Vec<u16>,
+    },
+}
+
+impl LayoutNode {
+    /// Create a split dividing its area evenly between `children`.
+    pub fn even_split(direction: SplitDirection, children: Vec<LayoutNode>) -> Self {
+        LayoutNode::Split {
+            direction,
+            weights: vec![1; children.len()],
+            children,
+        }
+    }
+
+    /// Get the window IDs in layout order (depth first, left to right).
+    ///
+    /// This is also the order focus cycles in.
+    pub fn windows(&self) -> Vec<WindowId> {
+        let mut ids = Vec::new();
+        self.collect_windows(&mut ids);
+        ids
+    }
+
+    /// Check whether the tree contains the given window.
+    pub fn contains(&self, id: WindowId) -> bool {
+        match self {
+            LayoutNode::Window(window) => *window == id,
+            LayoutNode::Split { children, .. } => children.iter().any(|child| child.contains(id)),
+        }
+    }
+
+    /// Compute the area of every window when the tree fills `area`.
+    pub fn rects(&self, area: Rect) -> Vec<(WindowId, Rect)> {
+        let mut rects = Vec::new();
+        self.collect_rects(area, &mut rects);
+        rects
+    }
+
+    /// Place `id` next to `target`, splitting in `direction`.
+    ///
+    /// If `target` is already in a split of the same direction, `id` is
+    /// inserted right after it with the same weight; otherwise `target` is
+    /// replaced by a new even split holding both. Returns `false` if
+    /// `target` is not in the tree.
+    pub fn split(&mut self, target: WindowId, id: WindowId, direction: SplitDirection) -> bool {
+        match self {
+            LayoutNode::Window(window) if *window == target => {
+                *self = LayoutNode::even_split(
+                    direction,
+                    vec![LayoutNode::Window(target), LayoutNode::Window(id)],
+                );
+                true
+            }
+            LayoutNode::Window(_) => false,
+            LayoutNode::Split {
+                direction: split_direction,
+                children,
+                weights,
+            } => {
+                let same_direction = *split_direction == direction;
+                let leaf = children
+                    .iter()
+                    .position(|child| *child == LayoutNode::Window(target));
+                match leaf {
+                    Some(index) if same_direction => {
+                        // The new window gets as much room as its target
+                        children.insert(index + 1, LayoutNode::Window(id));
+                        weights.insert(index + 1, weights[index]);
+                        true
+                    }
+                    _ => children
+                        .iter_mut()
+                        .any(|child| child.split(target, id, direction)),
+                }
+            }
+        }
+    }
+
+    /// Remove `id` from the tree, collapsing splits left with one child.
+    ///
+    /// Returns the remaining tree, or `None` if `id` was the only window.
+    pub fn remove(self, id: WindowId) -> Option<LayoutNode> {
+        match self {
+            LayoutNode::Window(window) if window == id => None,
+            LayoutNode::Window(_) => Some(self),
+            LayoutNode::Split {
+                direction,
+                children,
+                weights,
+            } => {
+                let (mut children, weights): (Vec<LayoutNode>, Vec<u16>) = children
+                    .into_iter()
+                    .zip(weights)
+                    .filter_map(|(child, weight)| Some((child.remove(id)?, weight)))
+                    .unzip();
+                match children.len() {
+                    0 => None,
+                    1 => children.pop(),
+                    _ => Some(LayoutNode::Split {
+                        direction,
+                        children,
+                        weights,
+                    }),
+                }
+            }
+        }
+    }
+
+    /// Grow the pane of `id` by `delta` cells along `direction`, or shrink
+    /// it if `delta` is negative, within `area` the tree fills.
+    ///
+    /// The innermost split in `direction` holding the pane gives the room:
+    /// the pane's branch takes it from the next sibling, or from the
+    /// previous one if it is the last. Neither is left smaller than
+    /// [`MIN_PANE_SIZE`]. Returns how many cells the pane grew, negative if
+    /// it shrank; zero if no split in `direction` holds it.
+    pub fn resize(
+        &mut self,
+        area: Rect,
+        id: WindowId,
+        direction: SplitDirection,
+        delta: i32,
+    ) -> i32 {
+        self.resize_in(area, id, direction, delta).unwrap_or(0)
+    }
+
+    /// Move the left edge (for [`SplitDirection::Horizontal`]) or the top
+    /// edge (for [`SplitDirection::Vertical`]) of the pane of `id` by
+    /// `delta` cells, right or down if positive, within `area` the tree
+    /// fills, as dragging the edge with the mouse does.
+    ///
+    /// The edge belongs to the innermost split in `direction` where the
+    /// pane's branch is not the first child; the branches either side of it
+    /// keep at least [`MIN_PANE_SIZE`]. Returns how far the edge moved;
+    /// zero if the pane's edge is the edge of `area`.
+    pub fn move_edge(
+        &mut self,
+        area: Rect,
+        id: WindowId,
+        direction: SplitDirection,
+        delta: i32,
+    ) -> i32 {
+        self.move_edge_in(area, id, direction, delta).unwrap_or(0)
+    }
+
+    /// Find the pane beside the pane of `id` in `direction` within `area`
+    /// the tree fills, wherever it sits in the tree.
+    ///
+    /// Of the panes entirely on that side, the nearest wins, then the one
+    /// sharing the longest stretch of the edge, then the first in layout
+    /// order. Returns `None` if the pane is at the edge of `area` or `id`
+    /// is not in the tree.
+    pub fn neighbor(&self, area: Rect, id: WindowId, direction: PaneDirection) -> Option<WindowId> {
+        let rects = self.rects(area);
+        let (_, from) = *rects.iter().find(|&&(open, _)| open == id)?;
+        let span = |start: u16, end: u16, other_start: u16, other_end: u16| {
+            i32::from(end.min(other_end)) - i32::from(start.max(other_start))
+        };
+        rects
+            .iter()
+            .filter_map(|&(other, rect)| {
+                let (gap, shared) = match direction {
+                    PaneDirection::Left => (
+                        i32::from(from.left()) - i32::from(rect.right()),
+                        span(from.top(), from.bottom(), rect.top(), rect.bottom()),
+                    ),
+                    PaneDirection::Right => (
+                        i32::from(rect.left()) - i32::from(from.right()),
+                        span(from.top(), from.bottom(), rect.top(), rect.bottom()),
+                    ),
+                    PaneDirection::Up => (
+                        i32::from(from.top()) - i32::from(rect.bottom()),
+                        span(from.left(), from.right(), rect.left(), rect.right()),
+                    ),
+                    PaneDirection::Down => (
+                        i32::from(rect.top()) - i32::from(from.bottom()),
+                        span(from.left(), from.right(), rect.left(), rect.right()),
+                    ),
+                };
+                (other != id && gap >= 0).then_some((gap, -shared, other))
+            })
+            .min_by_key(|&(gap, shared, _)| (gap, shared))
+            .map(|(_, _, other)| other)
+    }
+
+    /// Resize `id` in the innermost split in `direction` holding it, or
+    /// `None` if there is none.
+    fn resize_in(
+        &mut self,
+        area: Rect,
+        id: WindowId,
+        direction: SplitDirection,
+        delta: i32,
+    ) -> Option<i32> {
+        let LayoutNode::Split {
+            direction: split_direction,
+            children,
+            weights,
+        } = self
+        else {
+            return None;
+        };
+        let chunks = split_areas(*split_direction, weights, area);
+        let index = children.iter().position(|child| child.contains(id))?;
+        if let Some(moved) = children[index].resize_in(chunks[index], id, direction, delta) {
+            return Some(moved);
+        }
+        if *split_direction != direction {
+            return None;
+        }
+        let neighbor = if index + 1 < children.len() {
+            index + 1
+        } else {
+            index - 1
+        };
+        let mut sizes = lengths(direction, &chunks);
+        let moved = transfer(&mut sizes, index, neighbor, delta);
+        *weights = sizes.into_iter().map(|size| size.max(1)).collect();
+        Some(moved)
+    }
+
+    /// Move the leading edge of `id` in the innermost split in `direction`
+    /// where its branch is not the first child, or `None` if there is none.
+    fn move_edge_in(
+        &mut self,
+        area: Rect,
+        id: WindowId,
+        direction: SplitDirection,
+        delta: i32,
+    ) -> Option<i32> {
+        let LayoutNode::Split {
+            direction: split_direction,
+            children,
+            weights,
+        } = self
+        else {
+            return None;
+        };
+        let chunks = split_areas(*split_direction, weights, area);
+        let index = children.iter().position(|child| child.contains(id))?;
+        if let Some(moved) = children[index].move_edge_in(chunks[index], id, direction, delta) {
+            return Some(moved);
+        }
+        if *split_direction != direction || index == 0 {
+            return None;
+        }
+        let mut sizes = lengths(direction, &chunks);
+        let moved = transfer(&mut sizes, index - 1, index, delta);
+        *weights = sizes.into_iter().map(|size| size.max(1)).collect();
+        Some(moved)
+    }
+
+    fn collect_windows(&self, ids: &mut Vec<WindowId>) {
+        match self {
+            LayoutNode::Window(id) => ids.push(*id),
+            LayoutNode::Split { children, .. } => {
+                for child in children {
+                    child.collect_windows(ids);
+                }
+            }
+        }
+    }
+
+    fn collect_rects(&self, area: Rect, rects: &mut Vec<(WindowId, Rect)>) {
+        match self {
+            LayoutNode::Window(id) => rects.push((*id, area)),
+            LayoutNode::Split {
+                direction,
+                children,
+                weights,
+            } => {
+                let chunks = split_areas(*direction, weights, area);
+                for (child, chunk) in children.iter().zip(chunks) {
+                    child.collect_rects(chunk, rects);
+                }
+            }
+        }
+    }
+}
+
+/// Divide `area` in `direction` in proportion to `weights`.
+fn split_areas(direction: SplitDirection, weights: &[u16], area: Rect) -> Vec<Rect> {
+    let total: u32 = weights.iter().map(|&weight| u32::from(weight)).sum();
+    Layout::default()
+        .direction(direction.into())
+        .constraints(
+            weights
+                .iter()
+                .map(|&weight| Constraint::Ratio(u32::from(weight), total.max(1))),
+        )
+        .split(area)
+        .to_vec()
+}
+
+/// The widths (for [`SplitDirection::Horizontal`]) or heights of `areas`.
+fn lengths(direction: SplitDirection, areas: &[Rect]) -> Vec<u16> {
+    areas
+        .iter()
+        .map(|area| match direction {
+            SplitDirection::Horizontal => area.width,
+            SplitDirection::Vertical => area.height,
+        })
+        .collect()
+}
+
+/// Move up to `delta` cells from `sizes[from]` to `sizes[to]`, or the other
+/// way if `delta` is negative, leaving the giving side at least
+/// [`MIN_PANE_SIZE`]. Returns how many cells `to` gained, negative if it
+/// lost some.
+fn transfer(sizes: &mut [u16], to: usize, from: usize, delta: i32) -> i32 {
+    let (gainer, giver) = if delta >= 0 { (to, from) } else { (from, to) };
+    let room = sizes[giver].saturating_sub(MIN_PANE_SIZE);
+    let amount = u16::try_from(delta.unsigned_abs())
+        .unwrap_or(u16::MAX)
+        .min(room);
+    sizes[gainer] += amount;
+    sizes[giver] -= amount;
+    if delta >= 0 {
+        i32::from(amount)
+    } else {
+        -i32::from(amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids<const N: usize>() -> [WindowId; N] {
+        std::array::from_fn(|_| WindowId::new())
+    }
+
+    #[test]
+    fn test_split_window_creates_split() {
+        let [a, b] = ids();
+        let mut tree = LayoutNode::Window(a);
+        assert!(tree.split(a, b, SplitDirection::Horizontal));
+        assert_eq!(tree.windows(), vec![a, b]);
+
+        let rects = tree.rects(Rect::new(0, 0, 80, 24));
+        assert_eq!(rects[0], (a, Rect::new(0, 0, 40, 24)));
+        assert_eq!(rects[1], (b, Rect::new(40, 0, 40, 24)));
+    }
+
+    #[test]
+    fn test_split_same_direction_inserts_after_target() {
+        let [a, b, c] = ids();
+        let mut tree = LayoutNode::Window(a);
+        tree.split(a, b, SplitDirection::Horizontal);
+        tree.split(a, c, SplitDirection::Horizontal);
+
+        assert_eq!(tree.windows(), vec![a, c, b]);
+        assert!(matches!(&tree, LayoutNode::Split { children, .. } if children.len() == 3));
+    }
+
+    #[test]
+    fn test_split_other_direction_nests() {
+        let [a, b, c] = ids();
+        let mut tree = LayoutNode::Window(a);
+        tree.split(a, b, SplitDirection::Horizontal);
+        tree.split(b, c, SplitDirection::Vertical);
+
+        let rects = tree.rects(Rect::new(0, 0, 80, 24));
+        assert_eq!(rects[1], (b, Rect::new(40, 0, 40, 12)));
+        assert_eq!(rects[2], (c, Rect::new(40, 12, 40, 12)));
+        assert!(!tree.split(WindowId::new(), c, SplitDirection::Vertical));
+    }
+
+    #[test]
+    fn test_remove_collapses_single_child_splits() {
+        let [a, b, c] = ids();
+        let mut tree = LayoutNode::Window(a);
+        tree.split(a, b, SplitDirection::Horizontal);
+        tree.split(b, c, SplitDirection::Vertical);
+
+        let tree = tree.remove(c).unwrap();
+        assert_eq!(
+            tree,
+            LayoutNode::even_split(
+                SplitDirection::Horizontal,
+                vec![LayoutNode::Window(a), LayoutNode::Window(b)],
+            )
+        );
+        let tree = tree.remove(a).unwrap();
+        assert_eq!(tree, LayoutNode::Window(b));
+        assert!(tree.remove(b).is_none());
+    }
+
+    #[test]
+    fn test_neighbor_is_the_nearest_pane_sharing_the_most_edge() {
+        let [a, b, c, d] = ids();
+        // a beside b over c, with d under them
+        let top = LayoutNode::even_split(
+            SplitDirection::Horizontal,
+            vec![
+                LayoutNode::Window(a),
+                LayoutNode::even_split(
+                    SplitDirection::Vertical,
+                    vec![LayoutNode::Window(b), LayoutNode::Window(c)],
+                ),
+            ],
+        );
+        let tree =
+            LayoutNode::even_split(SplitDirection::Vertical, vec![top, LayoutNode::Window(d)]);
+        let area = Rect::new(0, 0, 80, 24);
+
+        assert_eq!(tree.neighbor(area, a, PaneDirection::Right), Some(b));
+        assert_eq!(tree.neighbor(area, c, PaneDirection::Left), Some(a));
+        assert_eq!(tree.neighbor(area, c, PaneDirection::Up), Some(b));
+        assert_eq!(tree.neighbor(area, c, PaneDirection::Down), Some(d));
+        assert_eq!(tree.neighbor(area, d, PaneDirection::Up), Some(a));
+        assert_eq!(tree.neighbor(area, a, PaneDirection::Left), None);
+        assert_eq!(
+            tree.neighbor(area, WindowId::new(), PaneDirection::Up),
+            None
+        );
+    }
+
+    #[test]
+    fn test_resizing_moves_the_shared_edge_and_keeps_the_proportions() {
+        let [a, b, c] = ids();
+        let area = Rect::new(0, 0, 80, 24);
+        let mut tree = LayoutNode::Window(a);
+        tree.split(a, b, SplitDirection::Horizontal);
+        tree.split(b, c, SplitDirection::Vertical);
+
+        // c grows into a, its left neighbor, as b's column is the last
+        assert_eq!(tree.resize(area, c, SplitDirection::Horizontal, 10), 10);
+        assert_eq!(tree.rects(area)[2], (c, Rect::new(30, 12, 50, 12)));
+        assert_eq!(tree.resize(area, b, SplitDirection::Vertical, -4), -4);
+        assert_eq!(tree.rects(area)[1], (b, Rect::new(30, 0, 50, 8)));
+        assert_eq!(
+            tree.resize(area, a, SplitDirection::Horizontal, -100),
+            -27,
+            "a keeps its borders and a column"
+        );
+        assert_eq!(tree.resize(area, a, SplitDirection::Vertical, 1), 0);
+
+        // Dragging c's left edge moves the edge between the columns
+        assert_eq!(tree.move_edge(area, c, SplitDirection::Horizontal, 37), 37);
+        assert_eq!(tree.rects(area)[0], (a, Rect::new(0, 0, 40, 24)));
+        assert_eq!(tree.move_edge(area, a, SplitDirection::Horizontal, 5), 0);
+        assert_eq!(tree.move_edge(area, b, SplitDirection::Vertical, -1), 0);
+
+        let half = tree.rects(Rect::new(0, 0, 40, 24));
+        assert_eq!(half[0], (a, Rect::new(0, 0, 20, 24)), "the ratio holds");
+        assert_eq!(half[2], (c, Rect::new(20, 8, 20, 16)));
+    }
+}
@@ -0,0 +1,158 @@
+//! Startup layouts declared in a workspace's settings.
+//!
+//! A workspace can check a standard arrangement of the IDE into its
+//! `.paradiddle.toml`, so everyone working in it starts from the same
+//! windows:
+//!
+//! ```toml
+//! [layout]
+//! windows = "explorer editor terminal"
+//! split = "h(0 v(1 2))"
+//! files = "src/main.rs, README.md"
+//! focus = 1
+//! ```
+//!
+//! `windows` lists window kinds, numbered from 0, using the names of the
+//! session file (`editor`, `terminal`, `explorer`, `search`, ...) and must
+//! include an editor. `split` arranges them in the session file's layout
+//! syntax and defaults to side by side columns. `files` are opened in the
+//! first editor, relative to the workspace root, and `focus` is the index
+//! of the window to focus. The App applies a [`LayoutPreset`] after
+//! restoring the session, with [`App::apply_layout_preset`].
+//!
+//! [`App::apply_layout_preset`]: crate::app::App::apply_layout_preset
+
+use std::path::PathBuf;
+
+use cli_ide_platform::config::config_service::Config;
+use cli_ide_platform::session::session_format::{SessionLayout, SessionSplit};
+
+/// Section holding the startup layout.
+pub const LAYOUT_SECTION: &str = "layout";
+
+/// A startup arrangement of windows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayoutPreset {
+    /// Kinds of the windows, e.g. `editor`, numbered by their position.
+    pub windows: Vec<String>,
+    /// Arrangement of the windows.
+    pub layout: SessionLayout,
+    /// Files to open in the first editor, relative to the workspace root.
+    pub files: Vec<PathBuf>,
+    /// Index of the window to focus, if set.
+    pub focus: Option<usize>,
+}
+
+impl LayoutPreset {
+    /// Read the preset from the `[layout]` section of `config`, or `None`
+    /// if the section does not set `windows`.
+    pub fn from_config(config: &Config) -> Result<Option<Self>, String> {
+        let setting = |key: &str| format!("{LAYOUT_SECTION}.{key}");
+        let windows = match config.get_string(&setting("windows")) {
+            Ok(Some(windows)) => windows,
+            Ok(None) => return Ok(None),
+            Err(error) => return Err(error.to_string()),
+        };
+        let windows: Vec<String> = windows.split_whitespace().map(str::to_string).collect();
+        if !windows.iter().any(|kind| kind == "editor") {
+            return Err(format!("`{}` must include an editor", setting("windows")));
+        }
+
+        let layout = match config
+            .get_string(&setting("split"))
+            .map_err(|error| error.to_string())?
+        {
+            Some(split) => {
+                let layout: SessionLayout = split
+                    .parse()
+                    .map_err(|error| format!("`{}`: {error}", setting("split")))?;
+                if !layout.fits(windows.len()) || layout.windows().len() != windows.len() {
+                    return Err(format!(
+                        "`{}`: `{split}` does not place each of the {} windows once",
+                        setting("split"),
+                        windows.len()
+                    ));
+                }
+                layout
+            }
+            None => SessionLayout::even_split(
+                SessionSplit::Horizontal,
+                (0..windows.len()).map(SessionLayout::Window).collect(),
+            ),
+        };
+
+        let files = config
+            .get_string(&setting("files"))
+            .map_err(|error| error.to_string())?
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|file| !file.is_empty())
+            .map(PathBuf::from)
+            .collect();
+
+        let focus = match config
+            .get_integer(&setting("focus"))
+            .map_err(|error| error.to_string())?
+        {
+            Some(focus) => Some(
+                usize::try_from(focus)
+                    .ok()
+                    .filter(|&index| index < windows.len())
+                    .ok_or_else(|| format!("`{}`: no window {focus}", setting("focus")))?,
+            ),
+            None => None,
+        };
+
+        Ok(Some(Self {
+            windows,
+            layout,
+            files,
+            focus,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preset_reads_the_layout_section() {
+        let config = Config::parse(
+            "[layout]\nwindows = \"explorer editor terminal\"\nsplit = \"h(0 v(1 2))\"\n\
+             files = \"src/main.rs, README.md\"\nfocus = 1\n",
+        )
+        .unwrap();
+        let preset = LayoutPreset::from_config(&config).unwrap().unwrap();
+        assert_eq!(preset.windows, ["explorer", "editor", "terminal"]);
+        assert_eq!(preset.layout.to_string(), "h(0 v(1 2))");
+        assert_eq!(
+            preset.files,
+            [PathBuf::from("src/main.rs"), PathBuf::from("README.md")]
+        );
+        assert_eq!(preset.focus, Some(1));
+
+        let columns = Config::parse("[layout]\nwindows = \"editor terminal\"\n").unwrap();
+        let preset = LayoutPreset::from_config(&columns).unwrap().unwrap();
+        assert_eq!(preset.layout.to_string(), "h(0 1)");
+        assert_eq!(LayoutPreset::from_config(&Config::new()), Ok(None));
+    }
+
+    #[test]
+    fn test_invalid_presets_are_reported() {
+        let problem = |text: &str| LayoutPreset::from_config(&Config::parse(text).unwrap());
+        assert_eq!(
+            problem("[layout]\nwindows = \"terminal\"\n"),
+            Err("`layout.windows` must include an editor".to_string())
+        );
+        assert_eq!(
+            problem("[layout]\nwindows = \"editor terminal\"\nsplit = \"h(0 2)\"\n"),
+            Err("`layout.split`: `h(0 2)` does not place each of the 2 windows once".to_string())
+        );
+        assert_eq!(
+            problem("[layout]\nwindows = \"editor\"\nfocus = 3\n"),
+            Err("`layout.focus`: no window 3".to_string())
+        );
+    }
+}
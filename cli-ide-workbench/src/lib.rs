@@ -3,8 +3,51 @@
 //! This crate defines the window abstractions, application core, and basic
 //! rendering pipeline.
 
+pub mod abbreviation;
 pub mod app;
+pub mod auto_save;
+pub mod bracket_pair;
+pub mod bracket_selection;
+pub mod buffer_manager;
+pub mod buffer_options;
+pub mod closed_editors;
+pub mod code_action;
+pub mod color_swatch;
+pub mod command_line;
+pub mod completion;
+pub mod context_menu;
+pub mod definition;
+pub mod density;
+pub mod dialog;
+pub mod document_link;
+pub mod editor_group;
+pub mod export;
 pub mod focus;
+pub mod frame_rate;
+pub mod fuzzy;
+pub mod grapheme;
+pub mod harness;
 pub mod input;
 pub mod keybinding;
+pub mod layout;
+pub mod layout_preset;
+pub mod line_length;
+pub mod list_selection;
+pub mod markdown;
+pub mod plugin;
+pub mod recording;
+pub mod rename;
+pub mod scrollbar;
+pub mod search;
+pub mod snippet;
+pub mod text_selection;
+pub mod theme;
+pub mod todo;
+pub mod truncate;
+pub mod tutorial;
+pub mod undo_tree;
+pub mod viewport;
 pub mod window;
+pub mod window_manager;
+pub mod word_count;
+pub mod workspace;
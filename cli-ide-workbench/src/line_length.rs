@@ -0,0 +1,233 @@
+//! Soft line-length limits for the editor.
+//!
+//! A soft limit does not wrap or reject text; it only marks the characters
+//! that run past the configured column so they can be highlighted, and
+//! optionally reports a warning per offending line. Limits are configured
+//! per language with a fallback default.
+//!
+//! Columns are terminal cells, as the editor draws them: a wide character
+//! (CJK, emoji) takes two and a combining mark none.
+
+use std::collections::HashMap;
+
+use crate::truncate::display_width;
+
+/// A soft line-length limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineLengthLimit {
+    /// Maximum number of columns per line before highlighting starts.
+    pub max_columns: usize,
+    /// Whether lines over the limit should also produce a warning.
+    pub warn: bool,
+}
+
+impl LineLengthLimit {
+    /// Create a limit that highlights overflow without producing warnings.
+    pub fn new(max_columns: usize) -> Self {
+        Self {
+            max_columns,
+            warn: false,
+        }
+    }
+
+    /// Enable or disable warnings for lines over the limit.
+    pub fn with_warnings(mut self, warn: bool) -> Self {
+        self.warn = warn;
+        self
+    }
+}
+
+/// The part of a line that runs past the limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineOverflow {
+    /// Zero-based line index.
+    pub line: usize,
+    /// Zero-based column where the overflow starts.
+    pub start_column: usize,
+    /// Number of columns past the limit.
+    pub length: usize,
+}
+
+/// A warning surfaced for a line that exceeds its soft limit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineLengthWarning {
+    /// Zero-based line index.
+    pub line: usize,
+    /// Actual width of the line in columns.
+    pub length: usize,
+    /// The limit that was exceeded.
+    pub max_columns: usize,
+}
+
+impl LineLengthWarning {
+    /// Human-readable description of the warning.
+    pub fn message(&self) -> String {
+        format!(
+            "Line {} is {} columns long (limit {})",
+            self.line + 1,
+            self.length,
+            self.max_columns
+        )
+    }
+}
+
+/// Per-language line-length configuration.
+///
+/// Language identifiers are free-form strings (e.g. `"rust"`, `"markdown"`).
+/// Languages without an explicit entry use the default limit, if any.
+#[derive(Debug, Clone, Default)]
+pub struct LineLengthConfig {
+    /// Limit applied when no language-specific limit is configured.
+    default: Option<LineLengthLimit>,
+    /// Language-specific limits. `None` disables the limit for that language.
+    languages: HashMap<String, Option<LineLengthLimit>>,
+}
+
+impl LineLengthConfig {
+    /// Create an empty configuration with no limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the default limit for languages without their own entry.
+    pub fn set_default(&mut self, limit: Option<LineLengthLimit>) {
+        self.default = limit;
+    }
+
+    /// Set the limit for a specific language.
+    ///
+    /// Passing `None` disables the limit for that language even if a
+    /// default is configured.
+    pub fn set_for_language(
+        &mut self,
+        language: impl Into<String>,
+        limit: Option<LineLengthLimit>,
+    ) {
+        self.languages.insert(language.into(), limit);
+    }
+
+    /// Resolve the limit for the given language.
+    pub fn limit_for(&self, language: Option<&str>) -> Option<LineLengthLimit> {
+        language
+            .and_then(|lang| self.languages.get(lang))
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
+
+/// Find every line in `text` that runs past `max_columns` columns.
+pub fn find_overflows(text: &str, max_columns: usize) -> Vec<LineOverflow> {
+    text.lines()
+        .enumerate()
+        .filter_map(|(line, content)| {
+            let length = display_width(content);
+            let overflow = length.checked_sub(max_columns).filter(|&n| n > 0)?;
+            Some(LineOverflow {
+                line,
+                start_column: max_columns,
+                length: overflow,
+            })
+        })
+        .collect()
+}
+
+/// Produce warnings for lines over the limit.
+///
+/// Returns an empty list when the limit has warnings disabled.
+pub fn warnings_for(text: &str, limit: LineLengthLimit) -> Vec<LineLengthWarning> {
+    if !limit.warn {
+        return Vec::new();
+    }
+    find_overflows(text, limit.max_columns)
+        .into_iter()
+        .map(|overflow| LineLengthWarning {
+            line: overflow.line,
+            length: overflow.start_column + overflow.length,
+            max_columns: limit.max_columns,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_overflows_reports_long_lines_only() {
+        let text = "short\nthis line is long\nok";
+        let overflows = find_overflows(text, 10);
+
+        assert_eq!(
+            overflows,
+            vec![LineOverflow {
+                line: 1,
+                start_column: 10,
+                length: 7,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_line_exactly_at_limit_is_not_overflow() {
+        assert!(find_overflows("0123456789", 10).is_empty());
+    }
+
+    #[test]
+    fn test_overflow_counts_characters_not_bytes() {
+        // Each 'é' is two bytes but one character
+        let overflows = find_overflows("éééé", 3);
+        assert_eq!(overflows[0].length, 1);
+    }
+
+    #[test]
+    fn test_overflow_counts_wide_characters_as_two_columns() {
+        // Each ideograph takes two cells; the combining accent takes none
+        let overflows = find_overflows("漢字漢字", 6);
+        assert_eq!(
+            overflows,
+            vec![LineOverflow {
+                line: 0,
+                start_column: 6,
+                length: 2,
+            }]
+        );
+        assert!(find_overflows("e\u{301}e\u{301}e\u{301}", 3).is_empty());
+    }
+
+    #[test]
+    fn test_warnings_disabled_by_default() {
+        let limit = LineLengthLimit::new(5);
+        assert!(warnings_for("a long line", limit).is_empty());
+    }
+
+    #[test]
+    fn test_warnings_enabled() {
+        let limit = LineLengthLimit::new(5).with_warnings(true);
+        let warnings = warnings_for("fine\na long line", limit);
+
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].line, 1);
+        assert_eq!(warnings[0].length, 11);
+        assert_eq!(warnings[0].message(), "Line 2 is 11 columns long (limit 5)");
+    }
+
+    #[test]
+    fn test_config_language_overrides_default() {
+        let mut config = LineLengthConfig::new();
+        config.set_default(Some(LineLengthLimit::new(80)));
+        config.set_for_language("rust", Some(LineLengthLimit::new(100)));
+        config.set_for_language("markdown", None);
+
+        assert_eq!(config.limit_for(Some("rust")).unwrap().max_columns, 100);
+        assert_eq!(config.limit_for(Some("python")).unwrap().max_columns, 80);
+        assert_eq!(config.limit_for(None).unwrap().max_columns, 80);
+        assert!(config.limit_for(Some("markdown")).is_none());
+    }
+
+    #[test]
+    fn test_empty_config_has_no_limits() {
+        let config = LineLengthConfig::new();
+        assert!(config.limit_for(Some("rust")).is_none());
+        assert!(config.limit_for(None).is_none());
+    }
+}
@@ -0,0 +1,310 @@
+//! Cursor and multi-selection state for list and tree views.
+//!
+//! A [`ListSelection`] tracks the rows a view shows, a cursor row and a set
+//! of marked items. `Space` marks or unmarks the item under the cursor and
+//! `Shift+Up`/`Shift+Down` mark the range from where the extension started.
+//! Marks are kept by item rather than by row, so a view that re-lays out
+//! its rows (filtering a list, expanding a folder) keeps them. Bulk
+//! operations act on [`ListSelection::targets`]: the marked items, or the
+//! item under the cursor if nothing is marked. Views keep the cursor row
+//! on screen with [`reveal_row`].
+
+use std::collections::BTreeSet;
+
+use crate::input::AppKey;
+use crate::viewport::Viewport;
+
+/// Cursor and marked items of a list view whose rows are keyed by `K`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListSelection<K> {
+    /// Items in display order.
+    items: Vec<K>,
+    /// Row of the cursor; meaningless while `items` is empty.
+    cursor: usize,
+    /// Row a `Shift` range started from, while extending.
+    anchor: Option<usize>,
+    /// Marks from before the current `Shift` range started.
+    range_base: BTreeSet<K>,
+    /// Marked items.
+    marked: BTreeSet<K>,
+}
+
+impl<K: Ord + Clone> Default for ListSelection<K> {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl<K: Ord + Clone> ListSelection<K> {
+    /// Create a selection over `items` with the cursor on the first row and
+    /// nothing marked.
+    pub fn new(items: Vec<K>) -> Self {
+        Self {
+            items,
+            cursor: 0,
+            anchor: None,
+            range_base: BTreeSet::new(),
+            marked: BTreeSet::new(),
+        }
+    }
+
+    /// Get the items in display order.
+    pub fn items(&self) -> &[K] {
+        &self.items
+    }
+
+    /// Replace the rows, e.g. after filtering or expanding a folder.
+    ///
+    /// The cursor stays on the same item if it is still shown, otherwise on
+    /// the same row (clamped). Marks on items no longer shown are dropped
+    /// and any `Shift` range ends.
+    pub fn set_items(&mut self, items: Vec<K>) {
+        let current = self.current().cloned();
+        self.items = items;
+        self.cursor = current
+            .and_then(|current| self.items.iter().position(|item| *item == current))
+            .unwrap_or_else(|| self.cursor.min(self.items.len().saturating_sub(1)));
+        let shown: BTreeSet<&K> = self.items.iter().collect();
+        self.marked.retain(|item| shown.contains(item));
+        self.end_range();
+    }
+
+    /// Number of rows.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Check whether there are no rows.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Get the cursor row, unless there are no rows.
+    pub fn cursor(&self) -> Option<usize> {
+        (!self.items.is_empty()).then_some(self.cursor)
+    }
+
+    /// Get the item under the cursor.
+    pub fn current(&self) -> Option<&K> {
+        self.items.get(self.cursor)
+    }
+
+    /// Move the cursor to `row` (clamped), ending any `Shift` range.
+    pub fn set_cursor(&mut self, row: usize) {
+        self.cursor = row.min(self.items.len().saturating_sub(1));
+        self.end_range();
+    }
+
+    /// Move the cursor by `delta` rows, stopping at the ends and ending any
+    /// `Shift` range.
+    pub fn move_by(&mut self, delta: isize) {
+        self.set_cursor(self.cursor.saturating_add_signed(delta));
+    }
+
+    /// Move the cursor by `delta` rows and mark every row from where the
+    /// range started to the cursor, on top of the marks made before it.
+    pub fn extend_by(&mut self, delta: isize) {
+        if self.items.is_empty() {
+            return;
+        }
+        if self.anchor.is_none() {
+            self.anchor = Some(self.cursor);
+            self.range_base = self.marked.clone();
+        }
+        let anchor = self.anchor.unwrap_or(self.cursor);
+        self.cursor = self
+            .cursor
+            .saturating_add_signed(delta)
+            .min(self.items.len() - 1);
+        let (start, end) = (anchor.min(self.cursor), anchor.max(self.cursor));
+        self.marked = self.range_base.clone();
+        self.marked.extend(self.items[start..=end].iter().cloned());
+    }
+
+    /// Put the cursor on `row` (clamped) with nothing marked, as a click
+    /// does, or with `extend` extend a range to it, as a drag does.
+    pub fn click(&mut self, row: usize, extend: bool) {
+        if extend {
+            let distance = |rows: usize| isize::try_from(rows).unwrap_or(isize::MAX);
+            let delta = if row >= self.cursor {
+                distance(row - self.cursor)
+            } else {
+                -distance(self.cursor - row)
+            };
+            self.extend_by(delta);
+        } else {
+            self.clear_marks();
+            self.set_cursor(row);
+        }
+    }
+
+    /// Mark or unmark the item under the cursor, ending any `Shift` range.
+    pub fn toggle_mark(&mut self) {
+        self.end_range();
+        if let Some(item) = self.current().cloned() {
+            if !self.marked.remove(&item) {
+                self.marked.insert(item);
+            }
+        }
+    }
+
+    /// Check whether `item` is marked.
+    pub fn is_marked(&self, item: &K) -> bool {
+        self.marked.contains(item)
+    }
+
+    /// Get the marked items in display order.
+    pub fn marked(&self) -> Vec<K> {
+        self.items
+            .iter()
+            .filter(|item| self.marked.contains(item))
+            .cloned()
+            .collect()
+    }
+
+    /// Unmark every item.
+    pub fn clear_marks(&mut self) {
+        self.marked.clear();
+        self.end_range();
+    }
+
+    /// The items a bulk operation applies to: the marked items in display
+    /// order, or the item under the cursor if nothing is marked.
+    pub fn targets(&self) -> Vec<K> {
+        if self.marked.is_empty() {
+            self.current().cloned().into_iter().collect()
+        } else {
+            self.marked()
+        }
+    }
+
+    /// Handle a navigation or marking key.
+    ///
+    /// `Up`/`Down` move the cursor, `Shift+Up`/`Shift+Down` extend a range
+    /// and `Space` toggles the mark under the cursor. Returns whether the
+    /// key was consumed.
+    pub fn handle_key(&mut self, key: AppKey) -> bool {
+        match key {
+            AppKey::Up => self.move_by(-1),
+            AppKey::Down => self.move_by(1),
+            AppKey::ShiftUp => self.extend_by(-1),
+            AppKey::ShiftDown => self.extend_by(1),
+            AppKey::Char(' ') => self.toggle_mark(),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Forget the current `Shift` range so the next one starts afresh.
+    fn end_range(&mut self) {
+        self.anchor = None;
+        self.range_base.clear();
+    }
+}
+
+/// Scroll a list showing `height` rows from `offset` as little as possible
+/// to show `row`, returning the new offset.
+pub fn reveal_row(offset: usize, row: usize, height: usize) -> usize {
+    let mut viewport = Viewport {
+        top_line: offset,
+        height,
+    };
+    viewport.reveal(row);
+    viewport.top_line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn selection() -> ListSelection<&'static str> {
+        ListSelection::new(vec!["a", "b", "c", "d", "e"])
+    }
+
+    #[test]
+    fn test_reveal_row_scrolls_minimally() {
+        assert_eq!(reveal_row(5, 2, 4), 2);
+        assert_eq!(reveal_row(5, 7, 4), 5, "visible rows do not scroll");
+        assert_eq!(reveal_row(5, 12, 4), 9);
+    }
+
+    #[test]
+    fn test_space_marks_and_targets_fall_back_to_cursor() {
+        let mut list = selection();
+        assert_eq!(list.targets(), vec!["a"]);
+
+        list.handle_key(AppKey::Down);
+        list.handle_key(AppKey::Char(' '));
+        list.handle_key(AppKey::Down);
+        list.handle_key(AppKey::Down);
+        list.handle_key(AppKey::Char(' '));
+        assert_eq!(list.targets(), vec!["b", "d"]);
+
+        list.handle_key(AppKey::Char(' '));
+        assert_eq!(list.marked(), vec!["b"]);
+    }
+
+    #[test]
+    fn test_shift_range_extends_on_top_of_marks() {
+        let mut list = selection();
+        list.toggle_mark();
+        list.set_cursor(2);
+        list.handle_key(AppKey::ShiftDown);
+        list.handle_key(AppKey::ShiftDown);
+        assert_eq!(list.marked(), vec!["a", "c", "d", "e"]);
+
+        // Shrinking the range unmarks rows it no longer covers
+        list.handle_key(AppKey::ShiftUp);
+        assert_eq!(list.marked(), vec!["a", "c", "d"]);
+        list.handle_key(AppKey::ShiftUp);
+        list.handle_key(AppKey::ShiftUp);
+        assert_eq!(list.marked(), vec!["a", "b", "c"]);
+
+        // Plain movement ends the range; the next one starts at the cursor
+        list.handle_key(AppKey::Down);
+        list.handle_key(AppKey::Down);
+        list.handle_key(AppKey::ShiftDown);
+        assert_eq!(list.marked(), vec!["a", "b", "c", "d", "e"]);
+    }
+
+    #[test]
+    fn test_click_moves_the_cursor_and_drag_marks_a_range() {
+        let mut list = selection();
+        list.toggle_mark();
+        list.click(1, false);
+        assert!(list.marked().is_empty());
+        list.click(3, true);
+        assert_eq!(list.marked(), vec!["b", "c", "d"]);
+        list.click(0, true);
+        assert_eq!(list.marked(), vec!["a", "b"]);
+        list.click(9, false);
+        assert_eq!(list.current(), Some(&"e"));
+    }
+
+    #[test]
+    fn test_shift_range_without_prior_marks_shrinks() {
+        let mut list = selection();
+        list.handle_key(AppKey::ShiftDown);
+        list.handle_key(AppKey::ShiftDown);
+        list.handle_key(AppKey::ShiftUp);
+        assert_eq!(list.marked(), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_set_items_keeps_cursor_item_and_visible_marks() {
+        let mut list = selection();
+        list.set_cursor(3);
+        list.toggle_mark();
+        list.set_cursor(1);
+        list.toggle_mark();
+
+        list.set_items(vec!["x", "b", "c", "y", "z", "e"]);
+        assert_eq!(list.current(), Some(&"b"));
+        assert_eq!(list.marked(), vec!["b"], "d is no longer shown");
+
+        list.set_items(Vec::new());
+        assert_eq!(list.cursor(), None);
+        assert!(list.targets().is_empty());
+        assert!(!list.handle_key(AppKey::Enter));
+    }
+}
@@ -0,0 +1,287 @@
+//! Markdown rendered for reading.
+//!
+//! [`render`] turns Markdown source into lines of styled text for the
+//! Markdown preview: headings, emphasis, inline code, fenced code blocks,
+//! block quotes, list bullets, rules and links lose their markers and keep
+//! a [`MarkdownStyle`] saying what they were. It covers what a README
+//! usually uses. Styles do not nest, tables, HTML and reference links are
+//! shown as written, and paragraphs are not reflowed: each source line is
+//! one rendered line, except the fences of code blocks, which are dropped.
+
+/// What a run of rendered text was in the source, which decides its style.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkdownStyle {
+    /// Plain text.
+    Text,
+    /// A heading.
+    Heading,
+    /// `**strong**` text.
+    Strong,
+    /// `*emphasized*` text.
+    Emphasis,
+    /// Inline code or a line of a code block.
+    Code,
+    /// The text of a link or the description of an image.
+    Link,
+    /// Text in a block quote, and its bar.
+    Quote,
+    /// A list bullet or number, or a rule.
+    Marker,
+}
+
+/// A run of rendered text in one style.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarkdownSpan {
+    /// The text, without its markers.
+    pub text: String,
+    /// What the text was in the source.
+    pub style: MarkdownStyle,
+}
+
+impl MarkdownSpan {
+    fn new(text: impl Into<String>, style: MarkdownStyle) -> Self {
+        Self {
+            text: text.into(),
+            style,
+        }
+    }
+}
+
+/// A rendered line.
+pub type MarkdownLine = Vec<MarkdownSpan>;
+
+/// Render `source` into lines of styled text.
+pub fn render(source: &str) -> Vec<MarkdownLine> {
+    let mut lines = Vec::new();
+    // The marker of the code block the line is in
+    let mut fence: Option<&str> = None;
+    for line in source.lines() {
+        let trimmed = line.trim_start();
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            } else {
+                lines.push(vec![MarkdownSpan::new(line, MarkdownStyle::Code)]);
+            }
+            continue;
+        }
+        fence = ["```", "~~~"]
+            .into_iter()
+            .find(|marker| trimmed.starts_with(marker));
+        if fence.is_none() {
+            lines.push(render_line(line));
+        }
+    }
+    lines
+}
+
+/// Render a line outside code blocks.
+fn render_line(line: &str) -> MarkdownLine {
+    let trimmed = line.trim_start();
+    let indent = &line[..line.len() - trimmed.len()];
+    let hashes = trimmed.bytes().take_while(|&byte| byte == b'#').count();
+    if (1..=6).contains(&hashes) && (trimmed.len() == hashes || trimmed[hashes..].starts_with(' '))
+    {
+        let text = trimmed[hashes..].trim().trim_end_matches('#').trim_end();
+        return inline(text, MarkdownStyle::Heading);
+    }
+    if is_rule(trimmed) {
+        let rule = "─".repeat(trimmed.chars().filter(|c| !c.is_whitespace()).count());
+        return vec![MarkdownSpan::new(rule, MarkdownStyle::Marker)];
+    }
+    if let Some(rest) = trimmed.strip_prefix('>') {
+        let rest = rest.strip_prefix(' ').unwrap_or(rest);
+        let mut spans = vec![MarkdownSpan::new(
+            format!("{indent}│ "),
+            MarkdownStyle::Quote,
+        )];
+        spans.extend(inline(rest, MarkdownStyle::Quote));
+        return spans;
+    }
+    if let Some(rest) = ["- ", "* ", "+ "]
+        .into_iter()
+        .find_map(|bullet| trimmed.strip_prefix(bullet))
+    {
+        let mut spans = vec![MarkdownSpan::new(
+            format!("{indent}• "),
+            MarkdownStyle::Marker,
+        )];
+        spans.extend(inline(rest, MarkdownStyle::Text));
+        return spans;
+    }
+    let digits = trimmed.bytes().take_while(u8::is_ascii_digit).count();
+    if (1..=9).contains(&digits)
+        && (trimmed[digits..].starts_with(". ") || trimmed[digits..].starts_with(") "))
+    {
+        let mut spans = vec![MarkdownSpan::new(
+            format!("{indent}{}", &trimmed[..digits + 2]),
+            MarkdownStyle::Marker,
+        )];
+        spans.extend(inline(&trimmed[digits + 2..], MarkdownStyle::Text));
+        return spans;
+    }
+    inline(line, MarkdownStyle::Text)
+}
+
+/// Check whether `trimmed` is a thematic break: three or more `-`, `*` or
+/// `_`, all the same, possibly with spaces between.
+fn is_rule(trimmed: &str) -> bool {
+    let mut marks = trimmed.chars().filter(|c| !c.is_whitespace());
+    let Some(first) = marks
+        .next()
+        .filter(|first| matches!(first, '-' | '*' | '_'))
+    else {
+        return false;
+    };
+    let rest: Vec<char> = marks.collect();
+    rest.len() >= 2 && rest.iter().all(|&mark| mark == first)
+}
+
+/// Render the inline markup of `text`, giving text outside any markup
+/// `base`.
+fn inline(text: &str, base: MarkdownStyle) -> MarkdownLine {
+    let mut spans = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+    let mut previous: Option<char> = None;
+    while let Some(c) = rest.chars().next() {
+        if c == '\\' {
+            if let Some(escaped) = rest[1..].chars().next().filter(char::is_ascii_punctuation) {
+                plain.push(escaped);
+                rest = &rest[1 + escaped.len_utf8()..];
+                previous = Some(escaped);
+                continue;
+            }
+        }
+        let in_word = previous.is_some_and(char::is_alphanumeric);
+        if let Some((span, after)) = styled_run(rest, in_word) {
+            if !plain.is_empty() {
+                spans.push(MarkdownSpan::new(std::mem::take(&mut plain), base));
+            }
+            previous = span.text.chars().next_back();
+            spans.push(span);
+            rest = after;
+            continue;
+        }
+        plain.push(c);
+        rest = &rest[c.len_utf8()..];
+        previous = Some(c);
+    }
+    if !plain.is_empty() || spans.is_empty() {
+        spans.push(MarkdownSpan::new(plain, base));
+    }
+    spans
+}
+
+/// The styled run `rest` starts with, if it opens one that is closed, and
+/// the text after it. `in_word` says whether `rest` follows a letter or
+/// digit, where `_` does not open emphasis, as in `snake_case`.
+fn styled_run(rest: &str, in_word: bool) -> Option<(MarkdownSpan, &str)> {
+    if rest.starts_with('`') {
+        let ticks = rest.bytes().take_while(|&byte| byte == b'`').count();
+        let end = rest[ticks..].find(&rest[..ticks])? + ticks;
+        let code = MarkdownSpan::new(&rest[ticks..end], MarkdownStyle::Code);
+        return Some((code, &rest[end + ticks..]));
+    }
+    for (marker, style) in [
+        ("**", MarkdownStyle::Strong),
+        ("__", MarkdownStyle::Strong),
+        ("*", MarkdownStyle::Emphasis),
+        ("_", MarkdownStyle::Emphasis),
+    ] {
+        if in_word && marker.starts_with('_') {
+            continue;
+        }
+        let Some(after) = rest.strip_prefix(marker) else {
+            continue;
+        };
+        if after.starts_with(char::is_whitespace) {
+            continue;
+        }
+        let Some(end) = after.find(marker).filter(|&end| end > 0) else {
+            continue;
+        };
+        return Some((
+            MarkdownSpan::new(&after[..end], style),
+            &after[end + marker.len()..],
+        ));
+    }
+    let start = if rest.starts_with("![") {
+        2
+    } else if rest.starts_with('[') {
+        1
+    } else {
+        return None;
+    };
+    let close = rest[start..].find("](")? + start;
+    let end = rest[close + 2..].find(')')? + close + 2;
+    let link = MarkdownSpan::new(&rest[start..close], MarkdownStyle::Link);
+    Some((link, &rest[end + 1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use MarkdownStyle::*;
+
+    /// The spans of each line as (text, style) pairs.
+    fn spans(source: &str) -> Vec<Vec<(String, MarkdownStyle)>> {
+        render(source)
+            .into_iter()
+            .map(|line| {
+                line.into_iter()
+                    .map(|span| (span.text, span.style))
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn span(text: &str, style: MarkdownStyle) -> (String, MarkdownStyle) {
+        (text.to_string(), style)
+    }
+
+    #[test]
+    fn test_blocks_lose_their_markers() {
+        let source = "# Title #\n\n> quoted\n- item\n  2. second\n---\n```rust\nlet x = *y;\n```\n";
+        assert_eq!(
+            spans(source),
+            [
+                vec![span("Title", Heading)],
+                vec![span("", Text)],
+                vec![span("│ ", Quote), span("quoted", Quote)],
+                vec![span("• ", Marker), span("item", Text)],
+                vec![span("  2. ", Marker), span("second", Text)],
+                vec![span("───", Marker)],
+                vec![span("let x = *y;", Code)],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_inline_markup_is_styled() {
+        assert_eq!(
+            spans("a **b** *c* `d*e` [f](http://g) ![h](i.png)"),
+            [vec![
+                span("a ", Text),
+                span("b", Strong),
+                span(" ", Text),
+                span("c", Emphasis),
+                span(" ", Text),
+                span("d*e", Code),
+                span(" ", Text),
+                span("f", Link),
+                span(" ", Text),
+                span("h", Link),
+            ]]
+        );
+    }
+
+    #[test]
+    fn test_unclosed_and_escaped_markers_stay_text() {
+        assert_eq!(
+            spans("2 * 3 and snake_case_name \\*not\\* [x]"),
+            [vec![span("2 * 3 and snake_case_name *not* [x]", Text)]]
+        );
+        assert_eq!(spans("#hashtag"), [vec![span("#hashtag", Text)]]);
+    }
+}
@@ -0,0 +1,158 @@
+//! Compiled-in plugins contributing commands, windows and keybindings.
+//!
+//! A crate extends the workbench by implementing [`Plugin`] and handing an
+//! instance to [`App::load_plugins`](crate::app::App::load_plugins) at
+//! startup. [`Plugin::activate`] resolves the services the plugin needs from
+//! the [`ServiceContainer`] and records what it contributes in the
+//! [`WorkbenchRegistry`]:
+//!
+//! - commands, which join the command palette and the command line;
+//! - windows, each shown and hidden by a command of its own;
+//! - keybindings, which run a command by ID, the plugin's or a built-in
+//!   one.
+//!
+//! The App keeps the registry, so contributions live as long as the App.
+//! Plugins are compiled in: the binary lists them, as the demo does.
+
+use std::sync::Arc;
+
+use cli_ide_platform::command::command_service::CommandHandler;
+use cli_ide_platform::di::service_container::ServiceContainer;
+
+use crate::input::AppKey;
+use crate::keybinding::WindowContext;
+use crate::window::Window;
+
+/// An extension of the workbench.
+pub trait Plugin {
+    /// Stable identifier, e.g. `example.greeter`, used in error messages.
+    fn id(&self) -> &str;
+
+    /// Contribute to the workbench.
+    ///
+    /// Called once, when the App loads the plugin, with the App's services.
+    fn activate(&mut self, services: &ServiceContainer, registry: &mut WorkbenchRegistry);
+}
+
+/// Builds a contributed window, resolving what it shows from the services.
+pub type WindowFactory = Box<dyn Fn(&ServiceContainer) -> Box<dyn Window>>;
+
+/// A command contributed by a plugin.
+pub struct CommandContribution {
+    /// Stable identifier, e.g. `example.sayHello`.
+    pub id: String,
+    /// Title shown in the command palette.
+    pub title: String,
+    /// Runs the command.
+    pub handler: CommandHandler,
+}
+
+/// A window contributed by a plugin.
+pub struct WindowContribution {
+    /// ID of the command showing and hiding the window.
+    pub id: String,
+    /// Title of that command in the command palette.
+    pub title: String,
+    /// Builds the window each time it is shown.
+    pub factory: WindowFactory,
+}
+
+/// A keybinding contributed by a plugin.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeybindingContribution {
+    /// Context the binding applies in, or `None` for a global binding.
+    pub context: Option<WindowContext>,
+    /// Key that runs the command.
+    pub key: AppKey,
+    /// ID of the command it runs.
+    pub command: String,
+}
+
+/// What the loaded plugins contribute, in the order they contributed it.
+#[derive(Default)]
+pub struct WorkbenchRegistry {
+    /// Contributed commands.
+    commands: Vec<CommandContribution>,
+    /// Contributed windows.
+    windows: Vec<WindowContribution>,
+    /// Contributed keybindings.
+    keybindings: Vec<KeybindingContribution>,
+}
+
+impl WorkbenchRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Contribute a command running `handler`.
+    pub fn register_command<F>(
+        &mut self,
+        id: impl Into<String>,
+        title: impl Into<String>,
+        handler: F,
+    ) where
+        F: Fn() + Send + Sync + 'static,
+    {
+        self.commands.push(CommandContribution {
+            id: id.into(),
+            title: title.into(),
+            handler: Arc::new(handler),
+        });
+    }
+
+    /// Contribute a window, shown and hidden by the command `id`.
+    pub fn register_window<F>(
+        &mut self,
+        id: impl Into<String>,
+        title: impl Into<String>,
+        factory: F,
+    ) where
+        F: Fn(&ServiceContainer) -> Box<dyn Window> + 'static,
+    {
+        self.windows.push(WindowContribution {
+            id: id.into(),
+            title: title.into(),
+            factory: Box::new(factory),
+        });
+    }
+
+    /// Contribute a global keybinding running the command `command`.
+    pub fn register_keybinding(&mut self, key: AppKey, command: impl Into<String>) {
+        self.keybindings.push(KeybindingContribution {
+            context: None,
+            key,
+            command: command.into(),
+        });
+    }
+
+    /// Contribute a keybinding running the command `command` while a window
+    /// of `context` has focus.
+    pub fn register_keybinding_for_context(
+        &mut self,
+        context: WindowContext,
+        key: AppKey,
+        command: impl Into<String>,
+    ) {
+        self.keybindings.push(KeybindingContribution {
+            context: Some(context),
+            key,
+            command: command.into(),
+        });
+    }
+
+    /// Get the contributed commands.
+    pub fn commands(&self) -> &[CommandContribution] {
+        &self.commands
+    }
+
+    /// Get the contributed windows.
+    pub fn windows(&self) -> &[WindowContribution] {
+        &self.windows
+    }
+
+    /// Get the contributed keybindings.
+    pub fn keybindings(&self) -> &[KeybindingContribution] {
+        &self.keybindings
+    }
+}
@@ -0,0 +1,426 @@
+//! Recording the events of a session and replaying them.
+//!
+//! An [`EventRecorder`] writes each [`AppEvent`] it is given to a file, one
+//! per line, after the milliseconds since recording started:
+//!
+//! ```text
+//! 0 resize 120 40
+//! 850 key char i
+//! 1210 key esc
+//! 2034 mouse down left 12 3
+//! 2500 paste fn main() {}\n
+//! ```
+//!
+//! A [`Replay`] reads such a file back and feeds the events to an App,
+//! either all at once (to reproduce a bug in a test) or as they fall due
+//! (to watch it happen). The format is meant to be trimmed and edited by
+//! hand into regression tests.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::app::App;
+use crate::input::{AppEvent, AppKey, MouseButton, MouseEvent, MouseEventKind};
+
+/// An event and when it happened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedEvent {
+    /// Time since recording started.
+    pub at: Duration,
+    /// The event.
+    pub event: AppEvent,
+}
+
+impl fmt::Display for RecordedEvent {
+    /// Write the event as a line of a recording, without the newline.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ", self.at.as_millis())?;
+        match &self.event {
+            AppEvent::Key(key) => write!(f, "key {}", key_name(*key)),
+            AppEvent::Mouse(mouse) => {
+                let (kind, button) = match mouse.kind {
+                    MouseEventKind::Down(button) => ("down ", Some(button)),
+                    MouseEventKind::Up(button) => ("up ", Some(button)),
+                    MouseEventKind::Drag(button) => ("drag ", Some(button)),
+                    MouseEventKind::ScrollUp => ("scroll-up", None),
+                    MouseEventKind::ScrollDown => ("scroll-down", None),
+                };
+                let button = button.map_or("", |button| match button {
+                    MouseButton::Left => "left",
+                    MouseButton::Right => "right",
+                    MouseButton::Middle => "middle",
+                });
+                write!(f, "mouse {kind}{button} {} {}", mouse.column, mouse.row)
+            }
+            AppEvent::Paste(text) => write!(f, "paste {}", escape(text)),
+            AppEvent::FocusGained => f.write_str("focus-gained"),
+            AppEvent::FocusLost => f.write_str("focus-lost"),
+            AppEvent::Resize(width, height) => write!(f, "resize {width} {height}"),
+            AppEvent::Tick => f.write_str("tick"),
+            AppEvent::Other => f.write_str("other"),
+        }
+    }
+}
+
+impl RecordedEvent {
+    /// Parse a line of a recording.
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let (at, event) = line.split_once(' ').unwrap_or((line, ""));
+        let at = at
+            .parse()
+            .map(Duration::from_millis)
+            .map_err(|_| format!("invalid time `{at}`"))?;
+        let event = match event.split_once(' ').unwrap_or((event, "")) {
+            ("key", name) => AppEvent::Key(parse_key_name(name)?),
+            ("mouse", mouse) => AppEvent::Mouse(parse_mouse(mouse)?),
+            ("resize", size) => {
+                let (width, height) = parse_pair(size)?;
+                AppEvent::Resize(width, height)
+            }
+            ("paste", text) => AppEvent::Paste(unescape(text)),
+            ("focus-gained", "") => AppEvent::FocusGained,
+            ("focus-lost", "") => AppEvent::FocusLost,
+            ("tick", "") => AppEvent::Tick,
+            ("other", "") => AppEvent::Other,
+            _ => return Err(format!("unknown event `{event}`")),
+        };
+        Ok(Self { at, event })
+    }
+}
+
+/// The name of `key` in a recording.
+///
+/// Control characters are written as their code point, e.g. `char U+0007`.
+fn key_name(key: AppKey) -> String {
+    let name = match key {
+        AppKey::Q => "q",
+        AppKey::Esc => "esc",
+        AppKey::Tab => "tab",
+        AppKey::Char(c) if c.is_control() => return format!("char U+{:04X}", u32::from(c)),
+        AppKey::Char(c) => return format!("char {c}"),
+        AppKey::Up => "up",
+        AppKey::Down => "down",
+        AppKey::Left => "left",
+        AppKey::Right => "right",
+        AppKey::ShiftUp => "shift-up",
+        AppKey::ShiftDown => "shift-down",
+        AppKey::ShiftLeft => "shift-left",
+        AppKey::ShiftRight => "shift-right",
+        AppKey::PageUp => "pageup",
+        AppKey::PageDown => "pagedown",
+        AppKey::Enter => "enter",
+        AppKey::Backspace => "backspace",
+        AppKey::Ctrl(c) => return format!("ctrl {c}"),
+        AppKey::Alt(c) => return format!("alt {c}"),
+        AppKey::Other => "other",
+    };
+    name.to_string()
+}
+
+/// Escape pasted text to fit on one line: `\`, newlines, carriage
+/// returns and tabs are written as `\\`, `\n`, `\r` and `\t`.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Undo [`escape`]; an unknown escape is kept as written.
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some('t') => unescaped.push('\t'),
+            Some('\\') => unescaped.push('\\'),
+            Some(other) => {
+                unescaped.push('\\');
+                unescaped.push(other);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+/// Parse a key name written by [`key_name`].
+fn parse_key_name(name: &str) -> Result<AppKey, String> {
+    let key = match name {
+        "q" => AppKey::Q,
+        "esc" => AppKey::Esc,
+        "tab" => AppKey::Tab,
+        "up" => AppKey::Up,
+        "down" => AppKey::Down,
+        "left" => AppKey::Left,
+        "right" => AppKey::Right,
+        "shift-up" => AppKey::ShiftUp,
+        "shift-down" => AppKey::ShiftDown,
+        "shift-left" => AppKey::ShiftLeft,
+        "shift-right" => AppKey::ShiftRight,
+        "pageup" => AppKey::PageUp,
+        "pagedown" => AppKey::PageDown,
+        "enter" => AppKey::Enter,
+        "backspace" => AppKey::Backspace,
+        "other" => AppKey::Other,
+        _ if name.starts_with("ctrl ") || name.starts_with("alt ") => {
+            let (modifier, key) = name.split_once(' ').unwrap_or_default();
+            let mut chars = key.chars();
+            let c = match (chars.next(), chars.next()) {
+                (Some(c), None) => c,
+                _ => return Err(format!("unknown key `{name}`")),
+            };
+            if modifier == "ctrl" {
+                AppKey::Ctrl(c)
+            } else {
+                AppKey::Alt(c)
+            }
+        }
+        _ => {
+            let c = name
+                .strip_prefix("char ")
+                .and_then(|c| {
+                    let mut chars = c.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) => Some(c),
+                        _ => c
+                            .strip_prefix("U+")
+                            .and_then(|code| u32::from_str_radix(code, 16).ok())
+                            .and_then(char::from_u32),
+                    }
+                })
+                .ok_or_else(|| format!("unknown key `{name}`"))?;
+            AppKey::Char(c)
+        }
+    };
+    Ok(key)
+}
+
+/// Parse a mouse event, e.g. `down left 12 3` or `scroll-up 12 3`.
+fn parse_mouse(text: &str) -> Result<MouseEvent, String> {
+    let (kind, rest) = text.split_once(' ').unwrap_or((text, ""));
+    let (kind, cell) = match kind {
+        "down" => parse_button(rest).map(|(button, cell)| (MouseEventKind::Down(button), cell))?,
+        "up" => parse_button(rest).map(|(button, cell)| (MouseEventKind::Up(button), cell))?,
+        "drag" => parse_button(rest).map(|(button, cell)| (MouseEventKind::Drag(button), cell))?,
+        "scroll-up" => (MouseEventKind::ScrollUp, rest),
+        "scroll-down" => (MouseEventKind::ScrollDown, rest),
+        _ => return Err(format!("unknown mouse event `{kind}`")),
+    };
+    let (column, row) = parse_pair(cell)?;
+    Ok(MouseEvent::new(kind, column, row))
+}
+
+/// Parse the mouse button `text` starts with, returning the rest.
+fn parse_button(text: &str) -> Result<(MouseButton, &str), String> {
+    let (button, rest) = text.split_once(' ').unwrap_or((text, ""));
+    let button = match button {
+        "left" => MouseButton::Left,
+        "right" => MouseButton::Right,
+        "middle" => MouseButton::Middle,
+        _ => return Err(format!("unknown mouse button `{button}`")),
+    };
+    Ok((button, rest))
+}
+
+/// Parse two numbers separated by a space.
+fn parse_pair(text: &str) -> Result<(u16, u16), String> {
+    text.split_once(' ')
+        .and_then(|(first, second)| Some((first.parse().ok()?, second.parse().ok()?)))
+        .ok_or_else(|| format!("expected two numbers, got `{text}`"))
+}
+
+/// Writes events to a recording as they happen.
+pub struct EventRecorder<W: Write = File> {
+    /// Where the lines go.
+    writer: W,
+    /// When recording started.
+    started: Instant,
+}
+
+impl EventRecorder<File> {
+    /// Start recording to the file at `path`, replacing it.
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        File::create(path).map(Self::new)
+    }
+}
+
+impl<W: Write> EventRecorder<W> {
+    /// Start recording to `writer`.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            started: Instant::now(),
+        }
+    }
+
+    /// Write `event`, timed from the start of the recording.
+    ///
+    /// Each line is flushed, so a recording survives a crash.
+    pub fn record(&mut self, event: AppEvent) -> io::Result<()> {
+        let recorded = RecordedEvent {
+            at: self.started.elapsed(),
+            event,
+        };
+        writeln!(self.writer, "{recorded}")?;
+        self.writer.flush()
+    }
+
+    /// Stop recording and get the writer back.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Feeds the events of a recording to an App.
+#[derive(Debug, Clone, Default)]
+pub struct Replay {
+    /// The recorded events, in order.
+    events: Vec<RecordedEvent>,
+    /// Index of the next event to feed.
+    next: usize,
+}
+
+impl Replay {
+    /// Replay `events`.
+    pub fn new(events: Vec<RecordedEvent>) -> Self {
+        Self { events, next: 0 }
+    }
+
+    /// Parse a recording.
+    ///
+    /// Blank lines and lines starting with `#` are skipped; an error names
+    /// the line it is on.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        text.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+            .map(|(index, line)| {
+                RecordedEvent::parse(line).map_err(|error| format!("line {}: {error}", index + 1))
+            })
+            .collect::<Result<_, _>>()
+            .map(Self::new)
+    }
+
+    /// Read the recording at `path`.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Self::parse(&text).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    /// Get the recorded events.
+    pub fn events(&self) -> &[RecordedEvent] {
+        &self.events
+    }
+
+    /// Check whether every event was fed.
+    pub fn is_finished(&self) -> bool {
+        self.next == self.events.len()
+    }
+
+    /// Take the next event if it is due `elapsed` after the replay
+    /// started.
+    pub fn next_due(&mut self, elapsed: Duration) -> Option<AppEvent> {
+        let recorded = self.events.get(self.next).filter(|e| e.at <= elapsed)?;
+        self.next += 1;
+        Some(recorded.event.clone())
+    }
+
+    /// Feed every remaining event to `app` at once, ignoring the times.
+    pub fn run(&mut self, app: &mut App) {
+        while let Some(event) = self.next_due(Duration::MAX) {
+            app.handle_event(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recorded_events_read_back_as_written() {
+        let events = [
+            AppEvent::Resize(120, 40),
+            AppEvent::Key(AppKey::Char(' ')),
+            AppEvent::Key(AppKey::Char('\u{7}')),
+            AppEvent::Key(AppKey::Q),
+            AppEvent::Key(AppKey::ShiftLeft),
+            AppEvent::Mouse(MouseEvent::new(
+                MouseEventKind::Drag(MouseButton::Middle),
+                12,
+                3,
+            )),
+            AppEvent::Mouse(MouseEvent::new(MouseEventKind::ScrollDown, 0, 9)),
+            AppEvent::Key(AppKey::Ctrl('s')),
+            AppEvent::Paste("a\\n\tb\n".to_string()),
+            AppEvent::FocusLost,
+            AppEvent::Tick,
+        ];
+        let mut recorder = EventRecorder::new(Vec::new());
+        for event in events.clone() {
+            recorder.record(event).unwrap();
+        }
+        let text = String::from_utf8(recorder.into_inner()).unwrap();
+        let lines: Vec<&str> = text
+            .lines()
+            .map(|line| line.split_once(' ').unwrap().1)
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "resize 120 40",
+                "key char  ",
+                "key char U+0007",
+                "key q",
+                "key shift-left",
+                "mouse drag middle 12 3",
+                "mouse scroll-down 0 9",
+                "key ctrl s",
+                "paste a\\\\n\\tb\\n",
+                "focus-lost",
+                "tick",
+            ]
+        );
+
+        let replay = Replay::parse(&format!("# header\n\n{text}")).unwrap();
+        let replayed: Vec<AppEvent> = replay.events().iter().map(|e| e.event.clone()).collect();
+        assert_eq!(replayed, events);
+        assert_eq!(
+            Replay::parse("0 key esc\n10 key f1").unwrap_err(),
+            "line 2: unknown key `f1`"
+        );
+    }
+
+    #[test]
+    fn test_next_due_waits_for_each_event_time() {
+        let mut replay = Replay::parse("0 key esc\n500 key tab").unwrap();
+        assert_eq!(
+            replay.next_due(Duration::ZERO),
+            Some(AppEvent::Key(AppKey::Esc))
+        );
+        assert_eq!(replay.next_due(Duration::from_millis(499)), None);
+        assert!(!replay.is_finished());
+        assert_eq!(
+            replay.next_due(Duration::from_millis(500)),
+            Some(AppEvent::Key(AppKey::Tab))
+        );
+        assert!(replay.is_finished());
+    }
+}
@@ -0,0 +1,77 @@
+//! Rename symbol support.
+//!
+//! A [`RenameProvider`] (typically backed by a language server's
+//! `textDocument/prepareRename` and `textDocument/rename`) decides whether
+//! the symbol at an offset can be renamed, validates candidate names while
+//! the user types, and computes the [`WorkspaceEdit`] that performs the
+//! rename.
+
+use std::ops::Range;
+use std::path::Path;
+
+use crate::code_action::WorkspaceEdit;
+
+/// The symbol a rename applies to, as returned by `prepare_rename`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrepareRename {
+    /// Byte range of the symbol in the document.
+    pub range: Range<usize>,
+    /// Initial text of the rename input, usually the current name.
+    pub placeholder: String,
+}
+
+/// Computes renames for the symbol at an offset.
+pub trait RenameProvider {
+    /// Check that the symbol at `offset` in the document at `path` can be
+    /// renamed, returning its range or a message explaining why not.
+    fn prepare_rename(
+        &self,
+        path: &Path,
+        text: &str,
+        offset: usize,
+    ) -> Result<PrepareRename, String>;
+
+    /// Check a candidate new name, returning a message if it is invalid.
+    ///
+    /// Called on every keystroke. The default accepts identifiers: a letter
+    /// or `_` followed by letters, digits or `_`.
+    fn validate_name(&self, name: &str) -> Result<(), String> {
+        is_identifier(name)
+            .then_some(())
+            .ok_or_else(|| format!("'{name}' is not a valid identifier"))
+    }
+
+    /// Compute the edit renaming the symbol at `offset` to `new_name`.
+    fn rename(
+        &self,
+        path: &Path,
+        text: &str,
+        offset: usize,
+        new_name: &str,
+    ) -> Result<WorkspaceEdit, String>;
+}
+
+/// Whether `name` is a letter or `_` followed by letters, digits or `_`.
+pub fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_alphabetic() || first == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_identifier() {
+        assert!(is_identifier("count"));
+        assert!(is_identifier("_private2"));
+        assert!(is_identifier("größe"));
+        assert!(!is_identifier(""));
+        assert!(!is_identifier("2fast"));
+        assert!(!is_identifier("has space"));
+        assert!(!is_identifier("dash-ed"));
+    }
+}
@@ -0,0 +1,119 @@
+//! Scrollbars drawn over the right border of scrollable windows.
+//!
+//! A window reports how far its content is scrolled as a [`ScrollPosition`]
+//! through [`Window::scroll_position`](crate::window::Window::scroll_position).
+//! When scrollbars are on, the App draws a thumb on the window's right
+//! border, between its corners, sized to the share of the content that
+//! fits. A click on that border scrolls to the matching part of the content
+//! with [`offset_at`], and the scroll actions move any focused window, so
+//! the bar can be worked from the keyboard too.
+
+use std::ops::Range;
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+
+/// Symbol of the thumb.
+const THUMB: &str = "┃";
+
+/// How far a window's content is scrolled, in lines or rows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScrollPosition {
+    /// Index of the first visible line.
+    pub offset: usize,
+    /// Number of lines of content.
+    pub content: usize,
+}
+
+/// The track of the scrollbar of a window drawn in `area`: its right border
+/// between the corners, or `None` if there is no room between them.
+pub fn track(area: Rect) -> Option<Rect> {
+    (area.width >= 2 && area.height >= 3).then(|| Rect {
+        x: area.right() - 1,
+        y: area.y + 1,
+        width: 1,
+        height: area.height - 2,
+    })
+}
+
+/// The rows of a `track` rows long track the thumb covers, or `None` when
+/// the content fits in the `viewport` lines and there is nothing to scroll.
+///
+/// The thumb is as long as the share of the content that is visible, at
+/// least one row, and reaches the end of the track at the last offset.
+pub fn thumb(position: ScrollPosition, viewport: usize, track: usize) -> Option<Range<usize>> {
+    if position.content <= viewport || track == 0 {
+        return None;
+    }
+    let len = (track * viewport / position.content).clamp(1, track);
+    let max_offset = position.content - viewport;
+    let offset = position.offset.min(max_offset);
+    let start = (offset * (track - len) + max_offset / 2) / max_offset;
+    Some(start..start + len)
+}
+
+/// The offset to scroll to for a click on `row` of a `track` rows long
+/// track: the top row scrolls to the start and the bottom row to the end.
+pub fn offset_at(position: ScrollPosition, viewport: usize, track: usize, row: usize) -> usize {
+    let max_offset = position.content.saturating_sub(viewport);
+    if track <= 1 {
+        return 0;
+    }
+    (row.min(track - 1) * max_offset + (track - 1) / 2) / (track - 1)
+}
+
+/// Draw the thumb for `position` over the right border of the window in
+/// `area`, whose `viewport` lines are visible.
+pub fn render(
+    buffer: &mut Buffer,
+    area: Rect,
+    position: ScrollPosition,
+    viewport: usize,
+    style: Style,
+) {
+    let Some(track) = track(area) else {
+        return;
+    };
+    let Some(rows) = thumb(position, viewport, usize::from(track.height)) else {
+        return;
+    };
+    for row in rows {
+        let y = track.y + u16::try_from(row).unwrap_or(u16::MAX);
+        if let Some(cell) = buffer.cell_mut((track.x, y)) {
+            cell.set_symbol(THUMB).set_style(style);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thumb_is_sized_and_placed_by_the_visible_share() {
+        let at = |offset| ScrollPosition {
+            offset,
+            content: 100,
+        };
+        assert_eq!(thumb(at(0), 10, 10), Some(0..1));
+        assert_eq!(thumb(at(90), 10, 10), Some(9..10));
+        assert_eq!(thumb(at(25), 50, 10), Some(3..8));
+        assert_eq!(thumb(at(500), 50, 10), Some(5..10), "offset is clamped");
+        assert_eq!(thumb(at(0), 100, 10), None, "everything fits");
+    }
+
+    #[test]
+    fn test_clicks_map_the_track_onto_the_offsets() {
+        let position = ScrollPosition {
+            offset: 0,
+            content: 100,
+        };
+        assert_eq!(offset_at(position, 10, 10, 0), 0);
+        assert_eq!(offset_at(position, 10, 10, 9), 90);
+        assert_eq!(offset_at(position, 10, 10, 20), 90);
+        assert_eq!(offset_at(position, 10, 10, 3), 30);
+        assert_eq!(track(Rect::new(5, 2, 20, 10)), Some(Rect::new(24, 3, 1, 8)));
+        assert_eq!(track(Rect::new(0, 0, 20, 2)), None);
+    }
+}
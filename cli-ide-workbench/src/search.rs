@@ -0,0 +1,203 @@
+//! Workspace text search and replace.
+//!
+//! [`search_workspace`] finds every occurrence of a literal string in the
+//! files under a directory that are not ignored, reading open documents
+//! from their buffers so unsaved edits are searched too. The matches of a
+//! file can be turned into [`TextEdit`]s with [`replace_edits`]; the App
+//! applies them through the `BufferManager` so open buffers stay in sync.
+
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use cli_ide_platform::files::ignore;
+
+use crate::code_action::{TextDocuments, TextEdit};
+
+/// One occurrence of the query in a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchMatch {
+    /// Byte range of the match in the document.
+    pub range: Range<usize>,
+    /// Zero-based line number.
+    pub line: usize,
+    /// Text of the line, without its line ending.
+    pub line_text: String,
+    /// Byte range of the match within `line_text`.
+    pub column: Range<usize>,
+}
+
+impl SearchMatch {
+    /// The line as it reads after replacing this match with `replacement`.
+    pub fn replaced_line(&self, replacement: &str) -> String {
+        format!(
+            "{}{replacement}{}",
+            &self.line_text[..self.column.start],
+            &self.line_text[self.column.end..]
+        )
+    }
+}
+
+/// The matches in one file, in document order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileMatches {
+    /// Path of the file.
+    pub path: PathBuf,
+    /// Matches in the file.
+    pub matches: Vec<SearchMatch>,
+}
+
+/// Find the non-overlapping occurrences of `query` in `text`.
+///
+/// The search is literal and case-sensitive. An empty query finds nothing.
+pub fn find_in_text(text: &str, query: &str) -> Vec<SearchMatch> {
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let mut matches = Vec::new();
+    let mut line = 0;
+    let mut line_start = 0;
+    let mut counted_to = 0;
+    for (start, found) in text.match_indices(query) {
+        let newlines = text[counted_to..start].match_indices('\n');
+        for (offset, _) in newlines {
+            line += 1;
+            line_start = counted_to + offset + 1;
+        }
+        counted_to = start;
+        let line_end = text[line_start..]
+            .find('\n')
+            .map_or(text.len(), |end| line_start + end);
+        let line_text = text[line_start..line_end].trim_end_matches('\r');
+        let column = start - line_start..start - line_start + found.len();
+        matches.push(SearchMatch {
+            range: start..start + found.len(),
+            line,
+            line_text: line_text.to_string(),
+            column: column.start.min(line_text.len())..column.end.min(line_text.len()),
+        });
+    }
+    matches
+}
+
+/// Search the files under `root` that are not ignored for `query`.
+///
+/// Documents open in `open` are searched as they are in their buffers;
+/// other files are read from disk, skipping any that are not UTF-8. At most
+/// `limit` files are searched. Returns the files with matches, in path
+/// order.
+pub fn search_workspace(
+    root: &Path,
+    query: &str,
+    open: &dyn TextDocuments,
+    limit: usize,
+) -> io::Result<Vec<FileMatches>> {
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut results = Vec::new();
+    for path in ignore::walk_files(root, limit)? {
+        let Some(text) = open.text(&path).or_else(|| fs::read_to_string(&path).ok()) else {
+            continue;
+        };
+        let matches = find_in_text(&text, query);
+        if !matches.is_empty() {
+            results.push(FileMatches { path, matches });
+        }
+    }
+    Ok(results)
+}
+
+/// `count` followed by `noun`, with an `s` unless `count` is one.
+pub(crate) fn counted(count: usize, noun: &str) -> String {
+    let suffix = if count == 1 { "" } else { "s" };
+    format!("{count} {noun}{suffix}")
+}
+
+/// Edits replacing each of `matches` with `replacement`.
+pub fn replace_edits<'a>(
+    matches: impl IntoIterator<Item = &'a SearchMatch>,
+    replacement: &str,
+) -> Vec<TextEdit> {
+    matches
+        .into_iter()
+        .map(|found| TextEdit::new(found.range.clone(), replacement))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code_action::{apply_edits, EditError};
+    use cli_ide_base::test_support::{temp_dir, Cleanup};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_find_in_text_reports_lines_and_columns() {
+        let text = "let foo = 1;\r\nfoo(foo);\nbar";
+        let matches = find_in_text(text, "foo");
+        let found: Vec<(usize, Range<usize>, &str)> = matches
+            .iter()
+            .map(|m| (m.line, m.column.clone(), m.line_text.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (0, 4..7, "let foo = 1;"),
+                (1, 0..3, "foo(foo);"),
+                (1, 4..7, "foo(foo);"),
+            ]
+        );
+        assert_eq!(&text[matches[2].range.clone()], "foo");
+        assert_eq!(matches[1].replaced_line("baz"), "baz(foo);");
+        assert!(find_in_text(text, "").is_empty());
+    }
+
+    #[test]
+    fn test_replace_edits_apply_to_selected_matches() {
+        let text = "aXbXc";
+        let matches = find_in_text(text, "X");
+        let edits = replace_edits(&matches[1..], "--");
+        assert_eq!(apply_edits(Path::new("t"), text, &edits).unwrap(), "aXb--c");
+    }
+
+    /// Open documents held in memory.
+    struct Documents(HashMap<PathBuf, String>);
+
+    impl TextDocuments for Documents {
+        fn text(&self, path: &Path) -> Option<String> {
+            self.0.get(path).cloned()
+        }
+
+        fn set_text(&mut self, path: &Path, _text: String) -> Result<(), EditError> {
+            Err(EditError::UnknownDocument(path.to_path_buf()))
+        }
+    }
+
+    #[test]
+    fn test_search_workspace_prefers_open_buffers_and_skips_ignored() {
+        let root = temp_dir("search");
+        let _cleanup = Cleanup::new([&root]);
+        fs::create_dir(root.join("target")).unwrap();
+        fs::write(root.join(".gitignore"), "target/\n").unwrap();
+        fs::write(root.join("a.txt"), "needle").unwrap();
+        fs::write(root.join("b.txt"), "hay").unwrap();
+        fs::write(root.join("target/c.txt"), "needle").unwrap();
+        fs::write(root.join("d.bin"), [0xff, 0xfe]).unwrap();
+        let open = Documents(HashMap::from([(
+            root.join("b.txt"),
+            "needle in an unsaved needle".to_string(),
+        )]));
+
+        let results = search_workspace(&root, "needle", &open, 100).unwrap();
+        let found: Vec<(PathBuf, usize)> = results
+            .iter()
+            .map(|file| (file.path.clone(), file.matches.len()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![(root.join("a.txt"), 1), (root.join("b.txt"), 2)]
+        );
+    }
+}
@@ -0,0 +1,292 @@
+//! User snippets, kept in a snippets file.
+//!
+//! A snippet is an [abbreviation](crate::abbreviation) managed from the
+//! IDE: a word, the language it expands in (or every language) and the
+//! body replacing it, which may span lines and mark the cursor with `$0`.
+//! [`Snippets`] are read from and written back to a file in the settings
+//! format, as `[abbreviations]` and `[abbreviations.<language>]` sections,
+//! so the file can still be edited by hand.
+//!
+//! Prompts edit a body on one line: [`escape_body`] writes line breaks and
+//! tabs as `\n` and `\t`, and [`unescape_body`] reads them back.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use cli_ide_platform::config::config_service::{Config, ABBREVIATIONS_SECTION};
+
+use crate::abbreviation::{self, Abbreviations};
+
+/// A user snippet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snippet {
+    /// Language the snippet expands in, or `None` for every file.
+    pub language: Option<String>,
+    /// Word that expands, e.g. `fn`.
+    pub word: String,
+    /// Text the word expands to.
+    pub body: String,
+}
+
+/// The user's snippets and the file they are kept in.
+///
+/// Snippets are ordered by language, those for every language first, then
+/// by word.
+#[derive(Debug, Clone, Default)]
+pub struct Snippets {
+    /// File the snippets are saved to; `None` keeps them in memory.
+    path: Option<PathBuf>,
+    /// The snippets, in order.
+    snippets: Vec<Snippet>,
+}
+
+impl Snippets {
+    /// Create an empty set of snippets kept in memory.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read the snippets in the file at `path`, which later saves write.
+    ///
+    /// A missing file holds no snippets. Fails with a message if the file
+    /// cannot be read or parsed, or holds an invalid snippet.
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, String> {
+        let path = path.into();
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(error) => return Err(format!("Cannot read {}: {error}", path.display())),
+        };
+        let describe = |error: String| format!("{}: {error}", path.display());
+        let abbreviations = Config::parse(&contents)
+            .and_then(|config| config.abbreviations())
+            .map_err(|error| describe(error.to_string()))?;
+        let mut snippets = Self {
+            path: Some(path.clone()),
+            snippets: Vec::new(),
+        };
+        for entry in abbreviations {
+            snippets
+                .set(entry.language.as_deref(), &entry.word, &entry.expansion)
+                .map_err(describe)?;
+        }
+        Ok(snippets)
+    }
+
+    /// Get the file the snippets are saved to, if any.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Get the snippets, in order.
+    pub fn snippets(&self) -> &[Snippet] {
+        &self.snippets
+    }
+
+    /// Get the snippet at `index`.
+    pub fn get(&self, index: usize) -> Option<&Snippet> {
+        self.snippets.get(index)
+    }
+
+    /// Expand `word` to `body` in files of `language`, or in every file if
+    /// `language` is `None`, replacing the body of the snippet for the
+    /// same word and language if there is one.
+    ///
+    /// Returns the index of the snippet, or a message if `word` is not a
+    /// word or `language` not a plain name.
+    pub fn set(&mut self, language: Option<&str>, word: &str, body: &str) -> Result<usize, String> {
+        if word.is_empty() || !word.chars().all(abbreviation::is_word_char) {
+            return Err(format!("`{word}` is not a word"));
+        }
+        if let Some(language) = language.filter(|language| !is_bare_key(language)) {
+            return Err(format!("`{language}` is not a language name"));
+        }
+        let snippet = Snippet {
+            language: language.map(str::to_string),
+            word: word.to_string(),
+            body: body.replace('\r', ""),
+        };
+        let key = |snippet: &Snippet| (snippet.language.clone(), snippet.word.clone());
+        match self.snippets.binary_search_by_key(&key(&snippet), key) {
+            Ok(index) => {
+                self.snippets[index] = snippet;
+                Ok(index)
+            }
+            Err(index) => {
+                self.snippets.insert(index, snippet);
+                Ok(index)
+            }
+        }
+    }
+
+    /// Remove the snippet at `index`, returning it.
+    pub fn remove(&mut self, index: usize) -> Option<Snippet> {
+        (index < self.snippets.len()).then(|| self.snippets.remove(index))
+    }
+
+    /// Write the snippets to their file, creating its folder if needed.
+    /// Does nothing for snippets kept in memory.
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(folder) = path.parent() {
+            fs::create_dir_all(folder)?;
+        }
+        fs::write(path, self.to_toml())
+    }
+
+    /// Format the snippets as the snippets file holds them: a section per
+    /// language, the one for every language first.
+    pub fn to_toml(&self) -> String {
+        let mut text = String::new();
+        let mut section = None;
+        for snippet in &self.snippets {
+            if section != Some(&snippet.language) {
+                if section.is_some() {
+                    text.push('\n');
+                }
+                match &snippet.language {
+                    Some(language) => {
+                        text.push_str(&format!("[{ABBREVIATIONS_SECTION}.{language}]\n"));
+                    }
+                    None => text.push_str(&format!("[{ABBREVIATIONS_SECTION}]\n")),
+                }
+                section = Some(&snippet.language);
+            }
+            let key = if is_bare_key(&snippet.word) {
+                snippet.word.clone()
+            } else {
+                quote(&snippet.word)
+            };
+            text.push_str(&format!("{key} = {}\n", quote(&snippet.body)));
+        }
+        text
+    }
+
+    /// Add the snippets to `abbreviations`, replacing abbreviations for the
+    /// same words and languages.
+    pub fn add_to(&self, abbreviations: &mut Abbreviations) {
+        for snippet in &self.snippets {
+            abbreviations.insert(
+                snippet.language.as_deref(),
+                snippet.word.clone(),
+                snippet.body.clone(),
+            );
+        }
+    }
+}
+
+/// Write `body` on one line: line breaks as `\n`, tabs as `\t` and
+/// backslashes as `\\`.
+pub fn escape_body(body: &str) -> String {
+    body.replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t")
+}
+
+/// Read a body written by [`escape_body`]. A backslash before any other
+/// character is kept.
+pub fn unescape_body(text: &str) -> String {
+    let mut body = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            body.push(c);
+            continue;
+        }
+        match chars.peek() {
+            Some('n') => body.push('\n'),
+            Some('t') => body.push('\t'),
+            Some('\\') => body.push('\\'),
+            _ => {
+                body.push('\\');
+                continue;
+            }
+        }
+        chars.next();
+    }
+    body
+}
+
+/// Parse the trigger of a new snippet: a word, or a language and a word
+/// separated by a space, e.g. `rust fn`.
+pub fn parse_trigger(text: &str) -> Result<(Option<String>, String), String> {
+    let mut parts = text.split_whitespace();
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(word), None, None) => Ok((None, word.to_string())),
+        (Some(language), Some(word), None) => Ok((Some(language.to_string()), word.to_string())),
+        _ => Err("Type a word, or a language and a word".to_string()),
+    }
+}
+
+/// Check whether `key` can be written without quotes in a settings file.
+fn is_bare_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Quote `text` as a settings file string.
+fn quote(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t");
+    format!("\"{escaped}\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cli_ide_base::test_support::temp_path;
+
+    #[test]
+    fn test_snippets_are_saved_by_language_and_read_back() {
+        let path = temp_path("snippets.toml");
+        let mut snippets = Snippets::load(&path).unwrap();
+        assert!(snippets.snippets().is_empty(), "a missing file is empty");
+        snippets
+            .set(Some("rust"), "fn", "fn $0() {\n\t\"body\"\n}")
+            .unwrap();
+        snippets.set(None, "teh", "the").unwrap();
+        snippets.set(Some("rust"), "été", "summer").unwrap();
+        assert_eq!(snippets.set(None, "teh", "THE"), Ok(0), "replaced");
+        assert!(snippets.set(None, "a-b", "c").is_err());
+        assert!(snippets.set(Some("c++"), "x", "y").is_err());
+        snippets.save().unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "[abbreviations]\nteh = \"THE\"\n\n[abbreviations.rust]\n\
+             fn = \"fn $0() {\\n\\t\\\"body\\\"\\n}\"\n\"été\" = \"summer\"\n"
+        );
+        let loaded = Snippets::load(&path).unwrap();
+        assert_eq!(loaded.snippets(), snippets.snippets());
+
+        let mut abbreviations = Abbreviations::new();
+        loaded.add_to(&mut abbreviations);
+        assert_eq!(abbreviations.get("teh", Some("rust")), Some("THE"));
+        assert_eq!(abbreviations.get("été", Some("rust")), Some("summer"));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_bodies_are_escaped_on_one_line_and_triggers_parsed() {
+        let body = "a\\b\n\tc";
+        assert_eq!(escape_body(body), "a\\\\b\\n\\tc");
+        assert_eq!(unescape_body(&escape_body(body)), body);
+        assert_eq!(unescape_body("C:\\dir"), "C:\\dir");
+
+        assert_eq!(parse_trigger("fn"), Ok((None, "fn".to_string())));
+        assert_eq!(
+            parse_trigger(" rust  fn "),
+            Ok((Some("rust".to_string()), "fn".to_string()))
+        );
+        assert!(parse_trigger("").is_err());
+        assert!(parse_trigger("a b c").is_err());
+    }
+}
@@ -0,0 +1,233 @@
+//! Character selection across the rows of a read-only view.
+//!
+//! Read-only panes select whole rows with a [`ListSelection`]; a
+//! [`TextSelection`] selects the characters between two points on those
+//! rows, the way the editor's selection does in a buffer: an anchor that
+//! stays put and a head that moves. A click puts both ends on the cell it
+//! hits, a drag moves the head and `Shift+Left`/`Shift+Right` move it a
+//! character along its row. Points are kept by row item, as marks are, so
+//! a view that appends or re-lays out rows keeps the selection while both
+//! of its rows are shown.
+//!
+//! [`ListSelection`]: crate::list_selection::ListSelection
+
+use std::ops::Range;
+
+use crate::grapheme;
+use crate::input::AppKey;
+
+/// A point in a view's text: a row item and a byte offset into its text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextPoint<K> {
+    /// Item of the row.
+    pub row: K,
+    /// Byte offset into the row's text.
+    pub index: usize,
+}
+
+/// Characters selected from an anchor to a head on rows keyed by `K`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextSelection<K> {
+    /// End that stays put when the selection is extended.
+    anchor: TextPoint<K>,
+    /// End that moves.
+    head: TextPoint<K>,
+}
+
+impl<K: PartialEq + Clone> TextSelection<K> {
+    /// Create an empty selection at `index` on the row `row`, as a click
+    /// does.
+    pub fn at(row: K, index: usize) -> Self {
+        let point = TextPoint { row, index };
+        Self {
+            anchor: point.clone(),
+            head: point,
+        }
+    }
+
+    /// Move the head to `index` on the row `row`, as a drag does.
+    pub fn extend_to(&mut self, row: K, index: usize) {
+        self.head = TextPoint { row, index };
+    }
+
+    /// Put an empty `selection` at `index` on the row `row`, as a click
+    /// does, or with `extend` move its head there, as a drag does.
+    pub fn click(selection: &mut Option<Self>, row: K, index: usize, extend: bool) {
+        match selection {
+            Some(selection) if extend => selection.extend_to(row, index),
+            _ => *selection = Some(Self::at(row, index)),
+        }
+    }
+
+    /// Handle `Shift+Left`/`Shift+Right` in a view whose cursor is on the
+    /// row `row`, with the text `line`: move the head of `selection` a
+    /// grapheme along it, starting at the start of the row unless the
+    /// head is already on it. Returns whether the key was consumed.
+    pub fn extend_on(selection: &mut Option<Self>, row: K, line: &str, key: AppKey) -> bool {
+        if !matches!(key, AppKey::ShiftLeft | AppKey::ShiftRight) {
+            return false;
+        }
+        let selection = match selection {
+            Some(selection) if selection.head.row == row => selection,
+            _ => selection.insert(Self::at(row, 0)),
+        };
+        selection.handle_key(key, line)
+    }
+
+    /// Get the end that moves.
+    pub fn head(&self) -> &TextPoint<K> {
+        &self.head
+    }
+
+    /// Check whether no characters are selected.
+    pub fn is_empty(&self) -> bool {
+        self.anchor == self.head
+    }
+
+    /// Byte range selected on the row shown `row` rows down `rows`, whose
+    /// text is `line`, or `None` if none of it is selected.
+    pub fn columns(&self, rows: &[K], row: usize, line: &str) -> Option<Range<usize>> {
+        let (start, end) = self.ordered(rows)?;
+        if row < start.0 || row > end.0 {
+            return None;
+        }
+        let from = if row == start.0 {
+            clamp_index(line, start.1)
+        } else {
+            0
+        };
+        let to = if row == end.0 {
+            clamp_index(line, end.1)
+        } else {
+            line.len()
+        };
+        (from < to).then_some(from..to)
+    }
+
+    /// The selected text, one line per row, taking each row's text from
+    /// `line`. `None` if nothing is selected or a row of either end is no
+    /// longer shown.
+    pub fn text(&self, rows: &[K], line: impl Fn(&K) -> String) -> Option<String> {
+        if self.is_empty() {
+            return None;
+        }
+        let (start, end) = self.ordered(rows)?;
+        let lines: Vec<String> = (start.0..=end.0)
+            .map(|row| {
+                let text = line(&rows[row]);
+                let columns = self.columns(rows, row, &text).unwrap_or_default();
+                text[columns].to_string()
+            })
+            .collect();
+        Some(lines.join("\n"))
+    }
+
+    /// Handle `Shift+Left`/`Shift+Right`, moving the head a grapheme along
+    /// `line`, its row's text. Returns whether the key was consumed.
+    pub fn handle_key(&mut self, key: AppKey, line: &str) -> bool {
+        let index = clamp_index(line, self.head.index);
+        self.head.index = match key {
+            AppKey::ShiftLeft => grapheme::previous_boundary(line, index),
+            AppKey::ShiftRight => grapheme::next_boundary(line, index),
+            _ => return false,
+        };
+        true
+    }
+
+    /// The start and end as row positions in `rows` and byte offsets, in
+    /// display order, or `None` if the row of either end is not shown.
+    fn ordered(&self, rows: &[K]) -> Option<((usize, usize), (usize, usize))> {
+        let position = |point: &TextPoint<K>| {
+            let row = rows.iter().position(|item| *item == point.row)?;
+            Some((row, point.index))
+        };
+        let (anchor, head) = (position(&self.anchor)?, position(&self.head)?);
+        Some((anchor.min(head), anchor.max(head)))
+    }
+}
+
+/// `index` clamped to `line` and moved back to a character boundary, for a
+/// point on a row whose text changed since it was set.
+fn clamp_index(line: &str, index: usize) -> usize {
+    let mut index = index.min(line.len());
+    while !line.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROWS: [&str; 3] = ["alpha", "beta", "gamma"];
+
+    fn line(row: &&str) -> String {
+        row.to_string()
+    }
+
+    #[test]
+    fn test_text_spans_rows_from_the_anchor_to_the_head() {
+        let mut selection = TextSelection::at("alpha", 2);
+        assert!(selection.is_empty());
+        assert_eq!(selection.text(&ROWS, line), None);
+
+        selection.extend_to("gamma", 3);
+        assert_eq!(selection.text(&ROWS, line).unwrap(), "pha\nbeta\ngam");
+        assert_eq!(selection.columns(&ROWS, 1, "beta"), Some(0..4));
+
+        // A head above the anchor selects the same way
+        let mut selection = TextSelection::at("beta", 3);
+        selection.extend_to("alpha", 4);
+        assert_eq!(selection.text(&ROWS, line).unwrap(), "a\nbet");
+        assert_eq!(selection.columns(&ROWS, 2, "gamma"), None);
+
+        // Without one of its rows there is no text
+        selection.extend_to("delta", 1);
+        assert_eq!(selection.text(&ROWS, line), None);
+    }
+
+    #[test]
+    fn test_click_selects_and_drag_extends() {
+        let mut selection = None;
+        TextSelection::click(&mut selection, "beta", 1, true);
+        TextSelection::click(&mut selection, "gamma", 2, true);
+        assert_eq!(
+            selection.as_ref().unwrap().text(&ROWS, line).unwrap(),
+            "eta\nga"
+        );
+
+        TextSelection::click(&mut selection, "alpha", 1, false);
+        assert!(selection.as_ref().unwrap().is_empty());
+
+        // Shift+Right starts at the start of the cursor row
+        assert!(TextSelection::extend_on(
+            &mut selection,
+            "beta",
+            "beta",
+            AppKey::ShiftRight
+        ));
+        assert_eq!(selection.as_ref().unwrap().text(&ROWS, line).unwrap(), "b");
+        assert!(!TextSelection::extend_on(
+            &mut selection,
+            "beta",
+            "beta",
+            AppKey::Up
+        ));
+    }
+
+    #[test]
+    fn test_shift_arrows_move_the_head_by_grapheme() {
+        let text = "né😀";
+        let mut selection = TextSelection::at("row", 0);
+        assert!(selection.handle_key(AppKey::ShiftRight, text));
+        assert!(selection.handle_key(AppKey::ShiftRight, text));
+        assert_eq!(selection.head().index, "né".len());
+        assert!(selection.handle_key(AppKey::ShiftRight, text));
+        assert!(selection.handle_key(AppKey::ShiftRight, text));
+        assert_eq!(selection.head().index, text.len(), "stops at the end");
+        assert!(selection.handle_key(AppKey::ShiftLeft, text));
+        assert_eq!(selection.columns(&["row"], 0, text), Some(0.."né".len()));
+        assert!(!selection.handle_key(AppKey::Left, text));
+    }
+}
@@ -0,0 +1,527 @@
+//! Color themes.
+//!
+//! A [`Theme`] holds the styles windows draw with. The App registers the
+//! active theme in its `ServiceContainer` and hands it to every window
+//! through [`Window::set_theme`](crate::window::Window::set_theme), so
+//! switching themes restyles the whole workbench at once.
+//!
+//! Besides the [built-in themes](BUILTIN_THEMES), a theme can be read from
+//! a file with [`Theme::load`]. The file starts from a built-in theme and
+//! overrides some of its styles:
+//!
+//! ```toml
+//! name = "dusk"
+//! base = "dark"
+//!
+//! [styles]
+//! keyword = "magenta bold"
+//! selection = "black on #e5c07b"
+//! ```
+//!
+//! A style lists a foreground color, `on` and a background color, and
+//! modifiers (`bold`, `dim`, `italic`, `underlined`, `reversed`). Colors
+//! are named (`red`, `lightblue`, `darkgray`, ...), `#rrggbb` or a 256-color
+//! index.
+
+use std::fs;
+use std::path::Path;
+
+use cli_ide_base::highlight::TokenKind;
+use cli_ide_platform::config::config_service::{Config, ConfigValue};
+use cli_ide_platform::problems::problem::Severity;
+use ratatui::style::{Color, Modifier, Style};
+
+use crate::window::DecorationKind;
+
+/// Names of the built-in themes, in the order [`Theme::next_builtin`]
+/// cycles through them.
+pub const BUILTIN_THEMES: &[&str] = &["dark", "light"];
+
+/// Names of the styles a theme file can set, as in its `[styles]` table.
+pub const STYLE_NAMES: &[&str] = &[
+    "text",
+    "border",
+    "focused_border",
+    "selection",
+    "overflow",
+    "status_bar",
+    "keyword",
+    "type_name",
+    "string",
+    "comment",
+    "number",
+    "bracket1",
+    "bracket2",
+    "bracket3",
+    "find_match",
+    "match_group",
+    "link",
+    "diff_added",
+    "diff_removed",
+    "diff_added_line",
+    "diff_removed_line",
+    "diff_modified",
+    "diff_hunk",
+    "error",
+    "warning",
+    "diagnostic_error",
+    "diagnostic_warning",
+];
+
+/// Styles used to draw the workbench.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    /// Name of the theme, e.g. `dark`.
+    pub name: String,
+    /// Window contents.
+    pub text: Style,
+    /// Borders of unfocused windows.
+    pub border: Style,
+    /// Borders of the focused window.
+    pub focused_border: Style,
+    /// Selected text.
+    pub selection: Style,
+    /// Characters past the soft line-length limit.
+    pub overflow: Style,
+    /// The status bar row.
+    pub status_bar: Style,
+    /// Keywords.
+    pub keyword: Style,
+    /// Type names.
+    pub type_name: Style,
+    /// String literals.
+    pub string: Style,
+    /// Comments.
+    pub comment: Style,
+    /// Numeric literals.
+    pub number: Style,
+    /// Outermost bracket pairs, and every third level inside them.
+    pub bracket1: Style,
+    /// Bracket pairs one level in, and every third level inside them.
+    pub bracket2: Style,
+    /// Bracket pairs two levels in, and every third level inside them.
+    pub bracket3: Style,
+    /// Pattern matches decorating the text.
+    pub find_match: Style,
+    /// Capture groups within a pattern match.
+    pub match_group: Style,
+    /// Links in the text, e.g. URLs.
+    pub link: Style,
+    /// Lines added in a diff.
+    pub diff_added: Style,
+    /// Lines removed in a diff.
+    pub diff_removed: Style,
+    /// Background of added lines in a highlighted diff, under the tokens.
+    pub diff_added_line: Style,
+    /// Background of removed lines in a highlighted diff, under the tokens.
+    pub diff_removed_line: Style,
+    /// Lines changed in place, marked in the editor gutter.
+    pub diff_modified: Style,
+    /// Hunk headers in a diff.
+    pub diff_hunk: Style,
+    /// Error problems.
+    pub error: Style,
+    /// Warning problems.
+    pub warning: Style,
+    /// Editor text an error is reported at.
+    pub diagnostic_error: Style,
+    /// Editor text a warning is reported at.
+    pub diagnostic_warning: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    /// Light text on the terminal's background.
+    pub fn dark() -> Self {
+        Self {
+            name: "dark".to_string(),
+            text: Style::default().fg(Color::Gray),
+            border: Style::default().fg(Color::DarkGray),
+            focused_border: Style::default().fg(Color::Cyan),
+            selection: Style::default().add_modifier(Modifier::REVERSED),
+            overflow: Style::default()
+                .fg(Color::Red)
+                .add_modifier(Modifier::UNDERLINED),
+            status_bar: Style::default().fg(Color::White).bg(Color::DarkGray),
+            keyword: Style::default().fg(Color::Magenta),
+            type_name: Style::default().fg(Color::Yellow),
+            string: Style::default().fg(Color::Green),
+            comment: Style::default()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::ITALIC),
+            number: Style::default().fg(Color::LightCyan),
+            bracket1: Style::default().fg(Color::Yellow),
+            bracket2: Style::default().fg(Color::LightMagenta),
+            bracket3: Style::default().fg(Color::LightBlue),
+            find_match: Style::default().fg(Color::Black).bg(Color::Yellow),
+            match_group: Style::default().fg(Color::Black).bg(Color::LightGreen),
+            link: Style::default().add_modifier(Modifier::UNDERLINED),
+            diff_added: Style::default().fg(Color::Green),
+            diff_removed: Style::default().fg(Color::Red),
+            diff_added_line: Style::default().bg(Color::Indexed(22)),
+            diff_removed_line: Style::default().bg(Color::Indexed(52)),
+            diff_modified: Style::default().fg(Color::Blue),
+            diff_hunk: Style::default().fg(Color::Cyan),
+            error: Style::default().fg(Color::LightRed),
+            warning: Style::default().fg(Color::Yellow),
+            diagnostic_error: Style::default()
+                .fg(Color::LightRed)
+                .add_modifier(Modifier::UNDERLINED),
+            diagnostic_warning: Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::UNDERLINED),
+        }
+    }
+
+    /// Dark text on a white background.
+    pub fn light() -> Self {
+        Self {
+            name: "light".to_string(),
+            text: Style::default().fg(Color::Black).bg(Color::White),
+            border: Style::default().fg(Color::Gray),
+            focused_border: Style::default().fg(Color::Blue),
+            selection: Style::default().fg(Color::Black).bg(Color::LightBlue),
+            overflow: Style::default()
+                .fg(Color::Red)
+                .add_modifier(Modifier::UNDERLINED),
+            status_bar: Style::default().fg(Color::White).bg(Color::Blue),
+            keyword: Style::default().fg(Color::Magenta),
+            type_name: Style::default().fg(Color::Blue),
+            string: Style::default().fg(Color::Green),
+            comment: Style::default()
+                .fg(Color::Gray)
+                .add_modifier(Modifier::ITALIC),
+            number: Style::default().fg(Color::Red),
+            bracket1: Style::default().fg(Color::Indexed(130)),
+            bracket2: Style::default().fg(Color::Magenta),
+            bracket3: Style::default().fg(Color::Blue),
+            find_match: Style::default().fg(Color::Black).bg(Color::LightYellow),
+            match_group: Style::default().fg(Color::Black).bg(Color::LightGreen),
+            link: Style::default().add_modifier(Modifier::UNDERLINED),
+            diff_added: Style::default().fg(Color::Green).bg(Color::White),
+            diff_removed: Style::default().fg(Color::Red).bg(Color::White),
+            diff_added_line: Style::default().bg(Color::Indexed(194)),
+            diff_removed_line: Style::default().bg(Color::Indexed(224)),
+            diff_modified: Style::default().fg(Color::Blue).bg(Color::White),
+            diff_hunk: Style::default().fg(Color::Blue).bg(Color::White),
+            error: Style::default().fg(Color::Red).bg(Color::White),
+            warning: Style::default().fg(Color::Yellow).bg(Color::White),
+            diagnostic_error: Style::default()
+                .fg(Color::Red)
+                .add_modifier(Modifier::UNDERLINED),
+            diagnostic_warning: Style::default()
+                .fg(Color::Magenta)
+                .add_modifier(Modifier::UNDERLINED),
+        }
+    }
+
+    /// Look up a built-in theme by name.
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            _ => None,
+        }
+    }
+
+    /// Read a theme file.
+    ///
+    /// The theme is named after the file unless it sets a `name`.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|error| format!("cannot read {}: {error}", path.display()))?;
+        let config =
+            Config::parse(&contents).map_err(|error| format!("{}: {error}", path.display()))?;
+        let stem = path.file_stem().map(|stem| stem.to_string_lossy());
+        Self::from_config(&config, stem.as_deref().unwrap_or("custom"))
+            .map_err(|error| format!("{}: {error}", path.display()))
+    }
+
+    /// Build a theme from the settings of a theme file, named `name` unless
+    /// they set a `name`.
+    pub fn from_config(config: &Config, name: &str) -> Result<Self, String> {
+        let base = config
+            .get_string("base")
+            .map_err(|error| error.to_string())?
+            .unwrap_or("dark");
+        let mut theme =
+            Self::builtin(base).ok_or_else(|| format!("unknown base theme `{base}`"))?;
+        theme.name = config
+            .get_string("name")
+            .map_err(|error| error.to_string())?
+            .unwrap_or(name)
+            .to_string();
+        for (key, value) in config.section(STYLES_SECTION) {
+            let ConfigValue::String(value) = value else {
+                return Err(format!("`{STYLES_SECTION}.{key}` expects a string"));
+            };
+            let style = theme
+                .style_mut(key)
+                .ok_or_else(|| format!("unknown style `{key}`"))?;
+            *style = parse_style(value).map_err(|error| format!("`{key}`: {error}"))?;
+        }
+        Ok(theme)
+    }
+
+    /// The style named `name` in a theme file, e.g. `focused_border`.
+    fn style_mut(&mut self, name: &str) -> Option<&mut Style> {
+        Some(match name {
+            "text" => &mut self.text,
+            "border" => &mut self.border,
+            "focused_border" => &mut self.focused_border,
+            "selection" => &mut self.selection,
+            "overflow" => &mut self.overflow,
+            "status_bar" => &mut self.status_bar,
+            "keyword" => &mut self.keyword,
+            "type_name" => &mut self.type_name,
+            "string" => &mut self.string,
+            "comment" => &mut self.comment,
+            "number" => &mut self.number,
+            "bracket1" => &mut self.bracket1,
+            "bracket2" => &mut self.bracket2,
+            "bracket3" => &mut self.bracket3,
+            "find_match" => &mut self.find_match,
+            "match_group" => &mut self.match_group,
+            "link" => &mut self.link,
+            "diff_added" => &mut self.diff_added,
+            "diff_removed" => &mut self.diff_removed,
+            "diff_added_line" => &mut self.diff_added_line,
+            "diff_removed_line" => &mut self.diff_removed_line,
+            "diff_modified" => &mut self.diff_modified,
+            "diff_hunk" => &mut self.diff_hunk,
+            "error" => &mut self.error,
+            "warning" => &mut self.warning,
+            "diagnostic_error" => &mut self.diagnostic_error,
+            "diagnostic_warning" => &mut self.diagnostic_warning,
+            _ => return None,
+        })
+    }
+
+    /// A variant of this theme with every style dimmed, e.g. for panes that
+    /// should recede while another has focus.
+    pub fn dimmed(&self) -> Self {
+        let mut theme = self.clone();
+        for name in STYLE_NAMES {
+            if let Some(style) = theme.style_mut(name) {
+                *style = style.add_modifier(Modifier::DIM);
+            }
+        }
+        theme
+    }
+
+    /// The built-in theme after this one in [`BUILTIN_THEMES`], wrapping
+    /// around. A custom theme is followed by the first built-in theme.
+    pub fn next_builtin(&self) -> Self {
+        let next = BUILTIN_THEMES
+            .iter()
+            .position(|&name| name == self.name)
+            .map_or(0, |index| (index + 1) % BUILTIN_THEMES.len());
+        Self::builtin(BUILTIN_THEMES[next]).expect("built-in theme names resolve")
+    }
+
+    /// Style of a highlighted token.
+    pub fn token_style(&self, kind: TokenKind) -> Style {
+        match kind {
+            TokenKind::Keyword => self.keyword,
+            TokenKind::Type => self.type_name,
+            TokenKind::String => self.string,
+            TokenKind::Comment => self.comment,
+            TokenKind::Number => self.number,
+        }
+    }
+
+    /// Style of a bracket whose pair is nested in `depth` others, cycling
+    /// through the three bracket styles; a closing bracket that closes
+    /// nothing, with no depth, is drawn as an error.
+    pub fn bracket_style(&self, depth: Option<usize>) -> Style {
+        match depth.map(|depth| depth % 3) {
+            Some(0) => self.bracket1,
+            Some(1) => self.bracket2,
+            Some(_) => self.bracket3,
+            None => self.error,
+        }
+    }
+
+    /// Style of a decorated range of editor text.
+    pub fn decoration_style(&self, kind: DecorationKind) -> Style {
+        match kind {
+            DecorationKind::Match => self.find_match,
+            DecorationKind::MatchGroup => self.match_group,
+            DecorationKind::ColorSwatch(color) => Style::default().fg(color),
+            DecorationKind::ImageSwatch => self.comment,
+        }
+    }
+
+    /// Style of editor text a problem of `severity` is reported at.
+    pub fn diagnostic_style(&self, severity: Severity) -> Style {
+        match severity {
+            Severity::Error => self.diagnostic_error,
+            Severity::Warning => self.diagnostic_warning,
+        }
+    }
+
+    /// Border style for a window with the given focus state.
+    pub fn border_style(&self, focused: bool) -> Style {
+        if focused {
+            self.focused_border
+        } else {
+            self.border
+        }
+    }
+}
+
+/// Section of a theme file holding its styles.
+const STYLES_SECTION: &str = "styles";
+
+/// Parse a style such as `white on #282c34 bold`.
+fn parse_style(text: &str) -> Result<Style, String> {
+    let mut style = Style::default();
+    let mut words = text.split_whitespace();
+    while let Some(word) = words.next() {
+        let modifier = match word {
+            "bold" => Modifier::BOLD,
+            "dim" => Modifier::DIM,
+            "italic" => Modifier::ITALIC,
+            "underlined" => Modifier::UNDERLINED,
+            "reversed" => Modifier::REVERSED,
+            "on" => {
+                let color = words.next().ok_or("`on` needs a color")?;
+                style = style.bg(parse_color(color)?);
+                continue;
+            }
+            color => {
+                style = style.fg(parse_color(color)?);
+                continue;
+            }
+        };
+        style = style.add_modifier(modifier);
+    }
+    Ok(style)
+}
+
+/// Parse a color name, `#rrggbb` or 256-color index.
+fn parse_color(text: &str) -> Result<Color, String> {
+    if let Some(hex) = text.strip_prefix('#') {
+        let rgb = u32::from_str_radix(hex, 16)
+            .ok()
+            .filter(|_| hex.len() == 6)
+            .ok_or_else(|| format!("invalid color `{text}`"))?;
+        let [_, r, g, b] = rgb.to_be_bytes();
+        return Ok(Color::Rgb(r, g, b));
+    }
+    if let Ok(index) = text.parse::<u8>() {
+        return Ok(Color::Indexed(index));
+    }
+    Ok(match text {
+        "reset" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" => Color::Gray,
+        "darkgray" => Color::DarkGray,
+        "lightred" => Color::LightRed,
+        "lightgreen" => Color::LightGreen,
+        "lightyellow" => Color::LightYellow,
+        "lightblue" => Color::LightBlue,
+        "lightmagenta" => Color::LightMagenta,
+        "lightcyan" => Color::LightCyan,
+        "white" => Color::White,
+        _ => return Err(format!("unknown color or modifier `{text}`")),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_themes_resolve_by_name() {
+        for &name in BUILTIN_THEMES {
+            assert_eq!(Theme::builtin(name).unwrap().name, name);
+        }
+        assert!(Theme::builtin("solarized").is_none());
+    }
+
+    #[test]
+    fn test_theme_files_override_their_base_theme() {
+        let config = Config::parse(
+            "base = \"light\"\n[styles]\nkeyword = \"magenta bold\"\n\
+             selection = \"black on #e5c07b\"\ncomment = \"244 italic\"\n",
+        )
+        .unwrap();
+        let theme = Theme::from_config(&config, "dusk").unwrap();
+        assert_eq!(theme.name, "dusk");
+        assert_eq!(theme.text, Theme::light().text);
+        assert_eq!(
+            theme.keyword,
+            Style::default()
+                .fg(Color::Magenta)
+                .add_modifier(Modifier::BOLD)
+        );
+        assert_eq!(
+            theme.selection,
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Rgb(0xe5, 0xc0, 0x7b))
+        );
+        assert_eq!(
+            theme.comment,
+            Style::default()
+                .fg(Color::Indexed(244))
+                .add_modifier(Modifier::ITALIC)
+        );
+
+        let error = |contents: &str| {
+            Theme::from_config(&Config::parse(contents).unwrap(), "bad").unwrap_err()
+        };
+        assert_eq!(
+            error("base = \"solarized\""),
+            "unknown base theme `solarized`"
+        );
+        assert_eq!(error("[styles]\nglow = \"red\""), "unknown style `glow`");
+        assert_eq!(
+            error("[styles]\ntext = \"red on\""),
+            "`text`: `on` needs a color"
+        );
+        assert_eq!(
+            error("[styles]\ntext = \"#12\""),
+            "`text`: invalid color `#12`"
+        );
+    }
+
+    #[test]
+    fn test_next_builtin_cycles() {
+        let light = Theme::dark().next_builtin();
+        assert_eq!(light.name, "light");
+        assert_eq!(light.next_builtin().name, "dark");
+
+        let custom = Theme {
+            name: "custom".to_string(),
+            ..Theme::light()
+        };
+        assert_eq!(custom.next_builtin().name, "dark");
+    }
+
+    #[test]
+    fn test_dimmed_theme_dims_every_style() {
+        let mut dark = Theme::dark();
+        let dimmed = dark.dimmed();
+        assert_eq!(dimmed.name, "dark");
+        assert_ne!(dimmed, dark);
+        let mut dimmed = dimmed;
+        for name in STYLE_NAMES {
+            let style = *dimmed.style_mut(name).expect("style names resolve");
+            assert!(style.add_modifier.contains(Modifier::DIM), "{name}");
+            assert_eq!(style.fg, dark.style_mut(name).unwrap().fg, "{name}");
+        }
+    }
+}
@@ -0,0 +1,141 @@
+//! TODO, FIXME and HACK comments.
+//!
+//! The TODO list collects the comments marking work left to do. The
+//! workspace is scanned with a regex [`query`] on the search service's
+//! background thread, then single files are scanned again with
+//! [`find_todos`] as they change. A tag only counts after a comment marker
+//! on its line (`//`, `#`, `/*`, `--`, `;`, `<!--`, or a `*` starting the
+//! line), so the words in prose and identifiers such as `TODO_LIST` are
+//! left out.
+
+use std::ops::Range;
+
+use cli_ide_platform::search::search_service::{find_matches, SearchQuery, TextMatch};
+
+/// Regex finding the tags, whole words in capitals.
+const TAG_PATTERN: &str = r"\b(TODO|FIXME|HACK)\b";
+
+/// Text starting a comment, anywhere before the tag on its line.
+const COMMENT_MARKERS: [&str; 6] = ["//", "#", "/*", "--", ";", "<!--"];
+
+/// The kind of a TODO comment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TodoTag {
+    /// Work left to do.
+    Todo,
+    /// Something known to be broken.
+    Fixme,
+    /// A workaround to clean up.
+    Hack,
+}
+
+impl TodoTag {
+    /// Parse the tag as written in a comment.
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "TODO" => Some(Self::Todo),
+            "FIXME" => Some(Self::Fixme),
+            "HACK" => Some(Self::Hack),
+            _ => None,
+        }
+    }
+
+    /// The tag as written in a comment.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Todo => "TODO",
+            Self::Fixme => "FIXME",
+            Self::Hack => "HACK",
+        }
+    }
+}
+
+/// A TODO comment in a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TodoItem {
+    /// The tag.
+    pub tag: TodoTag,
+    /// Zero-based line number.
+    pub line: usize,
+    /// Byte range of the tag in the file.
+    pub range: Range<usize>,
+    /// The comment from the tag to the end of the line, e.g.
+    /// `TODO: handle errors`.
+    pub text: String,
+}
+
+/// The query the workspace scan searches for.
+///
+/// It finds every tag; [`from_matches`] keeps those in comments.
+pub fn query() -> SearchQuery {
+    SearchQuery::regex(TAG_PATTERN)
+}
+
+/// The TODO comments in `text`, in document order.
+pub fn find_todos(text: &str) -> Vec<TodoItem> {
+    match query().compile() {
+        Ok(regex) => from_matches(find_matches(text, &regex)),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Turn the matches of [`query`] in a file into TODO comments, keeping the
+/// first tag in a comment on each line.
+pub fn from_matches(matches: Vec<TextMatch>) -> Vec<TodoItem> {
+    let mut items: Vec<TodoItem> = Vec::new();
+    for found in matches {
+        if items.last().is_some_and(|item| item.line == found.line) {
+            continue;
+        }
+        let before = &found.line_text[..found.column.start];
+        let in_comment = COMMENT_MARKERS.iter().any(|marker| before.contains(marker))
+            || before.trim_start().starts_with('*');
+        let Some(tag) = TodoTag::parse(&found.line_text[found.column.clone()]) else {
+            continue;
+        };
+        if !in_comment {
+            continue;
+        }
+        let text = found.line_text[found.column.start..]
+            .trim_end()
+            .trim_end_matches("*/")
+            .trim_end_matches("-->")
+            .trim_end()
+            .to_string();
+        items.push(TodoItem {
+            tag,
+            line: found.line,
+            range: found.range,
+            text,
+        });
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_tags_in_comments_are_collected() {
+        let text = "// TODO: one\n\
+                    let TODO_LIST = 1; // FIXME later TODO too\n\
+                    fix the TODO in prose\n\
+                    \x20* HACK around it */\n\
+                    <!-- TODO markup -->\n";
+        let items: Vec<(TodoTag, usize, String)> = find_todos(text)
+            .into_iter()
+            .map(|item| (item.tag, item.line, item.text))
+            .collect();
+        assert_eq!(
+            items,
+            vec![
+                (TodoTag::Todo, 0, "TODO: one".to_string()),
+                (TodoTag::Fixme, 1, "FIXME later TODO too".to_string()),
+                (TodoTag::Hack, 3, "HACK around it".to_string()),
+                (TodoTag::Todo, 4, "TODO markup".to_string()),
+            ]
+        );
+        assert_eq!(&text[find_todos(text)[1].range.clone()], "FIXME");
+    }
+}
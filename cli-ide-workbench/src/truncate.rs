@@ -0,0 +1,156 @@
+//! Shortening text to fit a number of terminal cells.
+//!
+//! Window titles, tabs and the status bar show paths and labels that can be
+//! wider than the pane drawing them. Rather than letting ratatui cut them
+//! off at the right edge, which loses a file's name and extension,
+//! [`truncate_middle`] keeps both ends of a text around an ellipsis, and
+//! [`truncate_segments`] first replaces whole segments from the middle of a
+//! path, or pieces of a bar, with one: `src/…/window/editor_window.rs`.
+
+use std::ops::Range;
+
+use unicode_width::UnicodeWidthChar;
+
+/// Marker standing in for the text that was left out.
+pub const ELLIPSIS: &str = "…";
+
+/// Number of cells `text` takes in a terminal.
+pub fn display_width(text: &str) -> usize {
+    text.chars().map(char_width).sum()
+}
+
+/// Number of cells `c` takes; control characters take none.
+fn char_width(c: char) -> usize {
+    c.width().unwrap_or(0)
+}
+
+/// Shorten `text` to at most `width` cells by replacing its middle with an
+/// ellipsis, keeping as much of both ends as fits.
+///
+/// The start gets the extra cell when the remaining width is odd. Text that
+/// already fits is returned unchanged.
+pub fn truncate_middle(text: &str, width: usize) -> String {
+    if display_width(text) <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+    let available = width - display_width(ELLIPSIS);
+    let head = take_width(text.chars(), available.div_ceil(2));
+    let tail_width = available - display_width(&head);
+    let tail: String = take_width(text.chars().rev(), tail_width)
+        .chars()
+        .rev()
+        .collect();
+    format!("{head}{ELLIPSIS}{tail}")
+}
+
+/// The leading characters of `chars` that fit in `width` cells.
+fn take_width(chars: impl Iterator<Item = char>, width: usize) -> String {
+    let mut taken = 0;
+    chars
+        .take_while(|&c| {
+            taken += char_width(c);
+            taken <= width
+        })
+        .collect()
+}
+
+/// Find the segments to replace with a single ellipsis so that `segments`,
+/// joined with `separator`, fit in `width` cells.
+///
+/// Segments are dropped from the middle outwards, starting just after the
+/// first one, so the first and last segments are kept as long as possible;
+/// the last one is never dropped. The range is empty when everything fits,
+/// and the text may still be too wide when only the last segment is left.
+pub fn dropped_segments(segments: &[&str], separator: &str, width: usize) -> Range<usize> {
+    let widths: Vec<usize> = segments
+        .iter()
+        .map(|segment| display_width(segment))
+        .collect();
+    let separator = display_width(separator);
+    let joined = |kept: &[usize]| -> usize {
+        kept.iter().sum::<usize>() + separator * kept.len().saturating_sub(1)
+    };
+    if joined(&widths) <= width || segments.len() < 2 {
+        return 0..0;
+    }
+    let ellipsis = display_width(ELLIPSIS);
+    let last = segments.len() - 1;
+    for end in 2..=last {
+        let kept = [&widths[..1], &[ellipsis], &widths[end..]].concat();
+        if joined(&kept) <= width {
+            return 1..end;
+        }
+    }
+    0..last
+}
+
+/// Shorten `segments`, joined with `separator`, to at most `width` cells.
+///
+/// Whole segments from the middle are replaced with an ellipsis first (see
+/// [`dropped_segments`]); if even the last segment alone is too wide, it is
+/// shortened with [`truncate_middle`] instead.
+pub fn truncate_segments(segments: &[&str], separator: &str, width: usize) -> String {
+    let mut kept = segments.to_vec();
+    let dropped = dropped_segments(segments, separator, width);
+    if !dropped.is_empty() {
+        kept.splice(dropped, [ELLIPSIS]);
+    }
+    let text = kept.join(separator);
+    match segments.last() {
+        Some(last) if display_width(&text) > width => truncate_middle(last, width),
+        _ => text,
+    }
+}
+
+/// Shorten a `/`-separated path to at most `width` cells, dropping
+/// directories from the middle before shortening the file name.
+pub fn truncate_path(path: &str, width: usize) -> String {
+    let segments: Vec<&str> = path.split('/').collect();
+    truncate_segments(&segments, "/", width)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_middle_keeps_both_ends() {
+        assert_eq!(truncate_middle("editor_window.rs", 20), "editor_window.rs");
+        assert_eq!(truncate_middle("editor_window.rs", 9), "edit…w.rs");
+        assert_eq!(truncate_middle("editor_window.rs", 8), "edit….rs");
+        assert_eq!(truncate_middle("editor_window.rs", 1), "…");
+        assert_eq!(truncate_middle("editor_window.rs", 0), "");
+        // Wide characters take two cells and are never split.
+        assert_eq!(truncate_middle("日本語のファイル", 7), "日…イル");
+    }
+
+    #[test]
+    fn test_truncate_path_drops_directories_from_the_middle() {
+        let path = "cli-ide-workbench/src/window/editor_window.rs";
+        assert_eq!(truncate_path(path, 60), path);
+        assert_eq!(
+            truncate_path(path, 43),
+            "cli-ide-workbench/…/window/editor_window.rs"
+        );
+        assert_eq!(
+            truncate_path(path, 40),
+            "cli-ide-workbench/…/editor_window.rs"
+        );
+        assert_eq!(truncate_path(path, 20), "…/editor_window.rs");
+        assert_eq!(truncate_path(path, 10), "edito…w.rs");
+    }
+
+    #[test]
+    fn test_dropped_segments() {
+        let pieces = ["Editor", "NORMAL", "Ln 3, Col 7", "rust"];
+        assert_eq!(dropped_segments(&pieces, " | ", 80), 0..0);
+        assert_eq!(dropped_segments(&pieces, " | ", 31), 1..2);
+        assert_eq!(dropped_segments(&pieces, " | ", 27), 1..3);
+        assert_eq!(dropped_segments(&pieces, " | ", 40), 0..0);
+        assert_eq!(dropped_segments(&pieces, " | ", 5), 0..3);
+        assert_eq!(dropped_segments(&["only"], " | ", 2), 0..0);
+    }
+}
@@ -0,0 +1,173 @@
+//! Guided tutorial for new users.
+//!
+//! The tutorial is a fixed list of [`TutorialStep`]s, each asking the user
+//! to do something with the real commands: switch focus, open the command
+//! palette, open a file and search the workspace. It never runs commands
+//! itself. After every event the App describes what the user can see in a
+//! [`TutorialState`], and [`Tutorial::update`] moves on once the current
+//! step's check holds against the state from when the step began.
+
+use std::path::PathBuf;
+
+use crate::keybinding::WindowContext;
+
+/// What the tutorial can check, as of one moment.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TutorialState {
+    /// Context of the focused window.
+    pub focused: Option<WindowContext>,
+    /// Whether the command palette is open.
+    pub palette_open: bool,
+    /// File shown in the target editor.
+    pub editor_path: Option<PathBuf>,
+    /// Text last searched for in the Find in Files panel.
+    pub searched: Option<String>,
+}
+
+/// One thing the tutorial asks the user to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TutorialStep {
+    /// Move focus to another pane.
+    SwitchFocus,
+    /// Open the command palette.
+    OpenPalette,
+    /// Open a file with Go to File.
+    OpenFile,
+    /// Search the workspace with Find in Files.
+    Search,
+}
+
+impl TutorialStep {
+    /// The steps, in the order they are taught.
+    pub const ALL: [TutorialStep; 4] = [
+        TutorialStep::SwitchFocus,
+        TutorialStep::OpenPalette,
+        TutorialStep::OpenFile,
+        TutorialStep::Search,
+    ];
+
+    /// Short name of the step.
+    pub fn title(self) -> &'static str {
+        match self {
+            TutorialStep::SwitchFocus => "Switch focus",
+            TutorialStep::OpenPalette => "Open the command palette",
+            TutorialStep::OpenFile => "Open a file",
+            TutorialStep::Search => "Search the workspace",
+        }
+    }
+
+    /// What to do to complete the step.
+    pub fn instruction(self) -> &'static str {
+        match self {
+            TutorialStep::SwitchFocus => {
+                "Press Tab to move focus to the next pane. The focused pane has a bright border."
+            }
+            TutorialStep::OpenPalette => {
+                "Press : to open the command palette. It lists every command; type to filter it."
+            }
+            TutorialStep::OpenFile => {
+                "In the palette, run \"Go to File...\", type part of a file name and press Enter."
+            }
+            TutorialStep::Search => {
+                "In the palette, run \"Find in Files\", type a word and press Enter."
+            }
+        }
+    }
+
+    /// Whether the user did what the step asks, going from `start` to
+    /// `now`.
+    pub fn is_done(self, start: &TutorialState, now: &TutorialState) -> bool {
+        match self {
+            TutorialStep::SwitchFocus => now.focused != start.focused,
+            TutorialStep::OpenPalette => now.palette_open,
+            TutorialStep::OpenFile => {
+                now.editor_path.is_some() && now.editor_path != start.editor_path
+            }
+            TutorialStep::Search => now.searched.is_some() && now.searched != start.searched,
+        }
+    }
+}
+
+/// Progress through the tutorial.
+#[derive(Debug, Clone)]
+pub struct Tutorial {
+    /// Index into [`TutorialStep::ALL`] of the current step.
+    step: usize,
+    /// State when the current step began.
+    start: TutorialState,
+}
+
+impl Tutorial {
+    /// Start the tutorial at its first step, from `state`.
+    pub fn new(state: TutorialState) -> Self {
+        Self {
+            step: 0,
+            start: state,
+        }
+    }
+
+    /// Get the current step, or `None` once every step is done.
+    pub fn current(&self) -> Option<TutorialStep> {
+        TutorialStep::ALL.get(self.step).copied()
+    }
+
+    /// Get the number of steps done.
+    pub fn completed(&self) -> usize {
+        self.step
+    }
+
+    /// Check whether every step is done.
+    pub fn is_finished(&self) -> bool {
+        self.current().is_none()
+    }
+
+    /// Move on to the next step if the current one is done in `state`.
+    ///
+    /// Returns whether it moved on.
+    pub fn update(&mut self, state: &TutorialState) -> bool {
+        let Some(step) = self.current() else {
+            return false;
+        };
+        if !step.is_done(&self.start, state) {
+            return false;
+        }
+        self.step += 1;
+        self.start = state.clone();
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_steps_advance_only_when_their_check_holds() {
+        let mut state = TutorialState {
+            focused: Some(WindowContext::Editor),
+            ..TutorialState::default()
+        };
+        let mut tutorial = Tutorial::new(state.clone());
+        assert_eq!(tutorial.current(), Some(TutorialStep::SwitchFocus));
+        assert!(!tutorial.update(&state));
+
+        state.focused = Some(WindowContext::Terminal);
+        assert!(tutorial.update(&state));
+        assert_eq!(tutorial.current(), Some(TutorialStep::OpenPalette));
+
+        state.palette_open = true;
+        assert!(tutorial.update(&state));
+        state.palette_open = false;
+        state.editor_path = Some(PathBuf::from("src/main.rs"));
+        assert!(tutorial.update(&state));
+        assert_eq!(tutorial.current(), Some(TutorialStep::Search));
+
+        // The file being open does not count as searching
+        assert!(!tutorial.update(&state));
+        state.searched = Some("fn".to_string());
+        assert!(tutorial.update(&state));
+        assert!(tutorial.is_finished());
+        assert_eq!(tutorial.completed(), 4);
+        assert!(!tutorial.update(&state));
+    }
+}
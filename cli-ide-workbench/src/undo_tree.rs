@@ -0,0 +1,247 @@
+//! Undo history as a tree.
+//!
+//! A linear undo stack forgets the undone changes as soon as a new edit is
+//! made. An [`UndoTree`] keeps them: every state of the document is a node,
+//! each change leads from a state to a child, and editing after undoing
+//! starts a new branch beside the old one. Undo and redo walk the current
+//! branch, redo following the child last visited, and
+//! [`UndoTree::step_towards`] finds the way to any other state, on
+//! whatever branch.
+//!
+//! States are numbered in the order they were made, the original text
+//! being state 0.
+
+/// A state of the document in an [`UndoTree`].
+struct Node<T> {
+    /// The state the change was made from; `None` for the original.
+    parent: Option<usize>,
+    /// The change leading here from the parent; `None` for the original.
+    change: Option<T>,
+    /// States made from this one, oldest first.
+    children: Vec<usize>,
+    /// The child redo goes to: the one last made or visited.
+    redo: Option<usize>,
+}
+
+impl<T> Node<T> {
+    fn new(parent: Option<usize>, change: Option<T>) -> Self {
+        Self {
+            parent,
+            change,
+            children: Vec::new(),
+            redo: None,
+        }
+    }
+}
+
+/// One move of [`UndoTree::step_towards`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UndoMove<'a, T> {
+    /// Revert this change, moving to its parent state.
+    Undo(&'a T),
+    /// Make this change again, moving to its child state.
+    Redo(&'a T),
+}
+
+/// A state of an [`UndoTree`] as listed by [`UndoTree::rows`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UndoTreeRow {
+    /// The state's number.
+    pub state: usize,
+    /// How many branch points lie between the original and the state.
+    pub depth: usize,
+    /// Description of the change leading to the state.
+    pub label: String,
+    /// Whether the document is in this state.
+    pub current: bool,
+}
+
+/// The states of a document and the changes between them.
+pub struct UndoTree<T> {
+    /// Every state, in the order they were made.
+    nodes: Vec<Node<T>>,
+    /// The state the document is in.
+    current: usize,
+}
+
+impl<T> Default for UndoTree<T> {
+    fn default() -> Self {
+        Self {
+            nodes: vec![Node::new(None, None)],
+            current: 0,
+        }
+    }
+}
+
+impl<T> UndoTree<T> {
+    /// Create a history holding only the original state.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the state the document is in.
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    /// Number of states, the original included.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Check whether there is no state but the original.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.len() == 1
+    }
+
+    /// Get the state `state` was made from, if it is not the original.
+    pub fn parent(&self, state: usize) -> Option<usize> {
+        self.nodes.get(state)?.parent
+    }
+
+    /// Get the states made from `state`, oldest first.
+    pub fn children(&self, state: usize) -> &[usize] {
+        self.nodes
+            .get(state)
+            .map_or(&[], |node| node.children.as_slice())
+    }
+
+    /// Record `change`, made from the current state, as a new state and
+    /// move to it. Returns the new state.
+    pub fn push(&mut self, change: T) -> usize {
+        let state = self.nodes.len();
+        self.nodes.push(Node::new(Some(self.current), Some(change)));
+        let parent = &mut self.nodes[self.current];
+        parent.children.push(state);
+        parent.redo = Some(state);
+        self.current = state;
+        state
+    }
+
+    /// Check whether there is a change to undo.
+    pub fn can_undo(&self) -> bool {
+        self.current != 0
+    }
+
+    /// Check whether there is an undone change to redo.
+    pub fn can_redo(&self) -> bool {
+        self.nodes[self.current].redo.is_some()
+    }
+
+    /// Move to the parent state, returning the change to revert.
+    pub fn undo(&mut self) -> Option<&T> {
+        let state = self.current;
+        let parent = self.nodes[state].parent?;
+        self.nodes[parent].redo = Some(state);
+        self.current = parent;
+        self.nodes[state].change.as_ref()
+    }
+
+    /// Move to the child last made or visited, returning the change to
+    /// make.
+    pub fn redo(&mut self) -> Option<&T> {
+        let child = self.nodes[self.current].redo?;
+        self.current = child;
+        self.nodes[child].change.as_ref()
+    }
+
+    /// Move one state towards `target`: down to the child on the way to it
+    /// if the current state is an ancestor, up to the parent otherwise.
+    ///
+    /// Returns the change to revert or make, or `None` once the document
+    /// is in `target` or if there is no such state.
+    pub fn step_towards(&mut self, target: usize) -> Option<UndoMove<'_, T>> {
+        if target >= self.nodes.len() || target == self.current {
+            return None;
+        }
+        let mut child = target;
+        while let Some(parent) = self.nodes[child].parent {
+            if parent == self.current {
+                self.nodes[parent].redo = Some(child);
+                self.current = child;
+                return self.nodes[child].change.as_ref().map(UndoMove::Redo);
+            }
+            child = parent;
+        }
+        self.undo().map(UndoMove::Undo)
+    }
+
+    /// Forget every state but the current one, which becomes the original.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// List the states as an outline, the original first.
+    ///
+    /// Each state is followed by the states made from it; where a state
+    /// has several, each branch is indented one level deeper. `label`
+    /// describes a change.
+    pub fn rows(&self, label: impl Fn(&T) -> String) -> Vec<UndoTreeRow> {
+        let mut rows = Vec::with_capacity(self.nodes.len());
+        let mut pending = vec![(0, 0)];
+        while let Some((state, depth)) = pending.pop() {
+            let node = &self.nodes[state];
+            rows.push(UndoTreeRow {
+                state,
+                depth,
+                label: node
+                    .change
+                    .as_ref()
+                    .map_or_else(|| "Original".to_string(), &label),
+                current: state == self.current,
+            });
+            let branch_depth = if node.children.len() > 1 {
+                depth + 1
+            } else {
+                depth
+            };
+            pending.extend(
+                node.children
+                    .iter()
+                    .rev()
+                    .map(|&child| (child, branch_depth)),
+            );
+        }
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_editing_after_undo_branches_and_keeps_the_old_branch() {
+        let mut tree = UndoTree::new();
+        tree.push("a");
+        tree.push("b");
+        assert_eq!(tree.undo(), Some(&"b"));
+        assert_eq!(tree.push("c"), 3);
+        assert_eq!(tree.children(1), &[2, 3]);
+        assert!(!tree.can_redo());
+
+        // Redo follows the branch last visited
+        tree.undo();
+        assert_eq!(tree.redo(), Some(&"c"));
+        let mut moves = Vec::new();
+        while let Some(step) = tree.step_towards(2) {
+            moves.push(match step {
+                UndoMove::Undo(change) => format!("undo {change}"),
+                UndoMove::Redo(change) => format!("redo {change}"),
+            });
+        }
+        assert_eq!(moves, ["undo c", "redo b"]);
+        assert_eq!(tree.current(), 2);
+        tree.undo();
+        assert_eq!(tree.redo(), Some(&"b"));
+        assert_eq!(tree.step_towards(9), None);
+
+        let rows: Vec<(usize, usize)> = tree
+            .rows(|change| change.to_string())
+            .iter()
+            .map(|row| (row.state, row.depth))
+            .collect();
+        assert_eq!(rows, vec![(0, 0), (1, 0), (2, 1), (3, 1)]);
+        assert!(tree.rows(|change| change.to_string())[2].current);
+    }
+}
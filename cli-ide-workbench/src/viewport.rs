@@ -0,0 +1,76 @@
+//! The lines of a document an editor shows.
+//!
+//! A [`Viewport`] is the editor's window onto its document: the first
+//! visible line and how many lines fit. The editor only turns the lines in
+//! [`Viewport::lines`] into styled text when it draws, so drawing costs the
+//! same for a large file as for a small one. Moving the cursor
+//! [`reveals`](Viewport::reveal) its line by scrolling as little as
+//! possible, and paging moves by [`Viewport::page`] lines so one line of
+//! context stays on screen.
+
+use std::ops::Range;
+
+/// The first visible line of a document and how many lines fit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Viewport {
+    /// Index of the first visible line.
+    pub top_line: usize,
+    /// Number of lines that fit, as of the last render; 0 before it.
+    pub height: usize,
+}
+
+impl Viewport {
+    /// Get the indices of the visible lines, at least the top one.
+    pub fn lines(&self) -> Range<usize> {
+        self.top_line..self.top_line + self.height.max(1)
+    }
+
+    /// Number of lines a page moves: the height less one line of context,
+    /// at least one.
+    pub fn page(&self) -> usize {
+        self.height.saturating_sub(1).max(1)
+    }
+
+    /// Scroll by `lines` (up if negative), keeping the top line at or above
+    /// `last_line`.
+    pub fn scroll_by(&mut self, lines: isize, last_line: usize) {
+        self.top_line = self.top_line.saturating_add_signed(lines).min(last_line);
+    }
+
+    /// Scroll as little as possible to make `line` visible.
+    pub fn reveal(&mut self, line: usize) {
+        let height = self.height.max(1);
+        if line < self.top_line {
+            self.top_line = line;
+        } else if line >= self.top_line + height {
+            self.top_line = line + 1 - height;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scroll_is_clamped_and_reveal_scrolls_minimally() {
+        let mut viewport = Viewport {
+            top_line: 0,
+            height: 10,
+        };
+        assert_eq!(viewport.lines(), 0..10);
+        assert_eq!(viewport.page(), 9);
+
+        viewport.scroll_by(-3, 100);
+        assert_eq!(viewport.top_line, 0);
+        viewport.scroll_by(150, 100);
+        assert_eq!(viewport.top_line, 100);
+
+        viewport.reveal(42);
+        assert_eq!(viewport.top_line, 42);
+        viewport.reveal(60);
+        assert_eq!(viewport.lines(), 51..61);
+        viewport.reveal(55);
+        assert_eq!(viewport.top_line, 51, "visible lines do not scroll");
+    }
+}
@@ -0,0 +1,215 @@
+//! Implementation of the ex-style command line.
+
+use std::path::PathBuf;
+
+use ratatui::prelude::*;
+use ratatui::widgets::Paragraph;
+
+use super::{InputOutcome, Window};
+use crate::command_line::ExCompleter;
+use crate::completion::{self, Completion};
+use crate::input::AppKey;
+use crate::theme::Theme;
+
+/// The `:` prompt drawn over the status bar.
+///
+/// `Enter` accepts the line and `Esc`, or `Backspace` on an empty line,
+/// dismisses it. `Tab` completes the line, cycling through the candidates
+/// listed in a popup above while there are several, and `Up`/`Down` recall
+/// earlier lines.
+pub struct CommandLineWindow {
+    /// Typed line, without the `:`.
+    value: String,
+    /// Earlier lines, oldest first.
+    history: Vec<String>,
+    /// Index of the recalled line in `history`, while recalling.
+    recalled: Option<usize>,
+    /// Completes command names, paths and settings.
+    completer: ExCompleter,
+    /// Lines `Tab` cycles through.
+    completion: Completion,
+    /// Message explaining why the line failed, if it did.
+    error: Option<String>,
+    /// Styles to draw with.
+    theme: Theme,
+}
+
+impl CommandLineWindow {
+    /// Create an empty command line completing `commands` and paths in
+    /// `root`, recalling `history`.
+    pub fn new(commands: Vec<String>, root: impl Into<PathBuf>, history: Vec<String>) -> Self {
+        Self {
+            value: String::new(),
+            history,
+            recalled: None,
+            completer: ExCompleter::new(&commands, root),
+            completion: Completion::default(),
+            error: None,
+            theme: Theme::default(),
+        }
+    }
+
+    /// Get the typed line, without the `:`.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Get the error message, if any.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Show why the line failed.
+    pub fn set_error(&mut self, error: Option<String>) {
+        self.error = error;
+    }
+
+    /// Get the lines `Tab` cycles through, while there are several.
+    pub fn completions(&self) -> &[String] {
+        self.completion.candidates()
+    }
+
+    /// Handle a key press.
+    pub fn handle_key(&mut self, key: AppKey) -> InputOutcome {
+        match key {
+            AppKey::Esc => return InputOutcome::Dismissed,
+            AppKey::Enter => return InputOutcome::Accept(self.value.clone()),
+            AppKey::Backspace if self.value.is_empty() => return InputOutcome::Dismissed,
+            AppKey::Backspace => {
+                self.value.pop();
+                self.edited();
+            }
+            AppKey::Tab => self.complete(),
+            AppKey::Up => self.recall(true),
+            AppKey::Down => self.recall(false),
+            // `q` is translated to AppKey::Q; on the command line it is text.
+            AppKey::Q => self.type_char('q'),
+            AppKey::Char(c) => self.type_char(c),
+            _ => {}
+        }
+        InputOutcome::Pending
+    }
+
+    /// Append a typed character.
+    fn type_char(&mut self, c: char) {
+        self.value.push(c);
+        self.edited();
+    }
+
+    /// Forget the completions, recall position and error of the previous
+    /// line.
+    fn edited(&mut self) {
+        self.completion.reset();
+        self.recalled = None;
+        self.error = None;
+    }
+
+    /// Show the next completion of the line as typed before the first
+    /// `Tab`.
+    fn complete(&mut self) {
+        if let Some(line) = self.completion.next(&self.value, &self.completer) {
+            self.value = line;
+            self.error = None;
+        }
+    }
+
+    /// Show the line before (or after) the recalled one.
+    fn recall(&mut self, older: bool) {
+        let index = match (self.recalled, older) {
+            (None, true) => self.history.len().checked_sub(1),
+            (None, false) => None,
+            (Some(index), true) => Some(index.saturating_sub(1)),
+            (Some(index), false) => Some(index + 1).filter(|&index| index < self.history.len()),
+        };
+        self.value = index
+            .map(|index| self.history[index].clone())
+            .unwrap_or_default();
+        self.edited();
+        self.recalled = index;
+    }
+}
+
+impl Window for CommandLineWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.render_with_focus(frame, area, true);
+    }
+
+    fn render_with_focus(&mut self, frame: &mut Frame, area: Rect, _focused: bool) {
+        if area.height == 0 {
+            return;
+        }
+        let prompt = Rect {
+            y: area.bottom() - 1,
+            height: 1,
+            ..area
+        };
+        let mut spans = vec![
+            Span::raw(":"),
+            Span::raw(self.value.as_str()),
+            Span::styled(" ", self.theme.selection),
+        ];
+        if let Some(error) = &self.error {
+            spans.push(Span::styled(format!(" {error}"), self.theme.error));
+        }
+        frame.render_widget(
+            Paragraph::new(Line::from(spans)).style(self.theme.text),
+            prompt,
+        );
+
+        completion::render_suggestions(
+            frame,
+            prompt,
+            true,
+            &self.completion,
+            completion::label,
+            &self.theme,
+        );
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command_line() -> CommandLineWindow {
+        let commands = vec!["workbench.action.quit".to_string()];
+        let history = vec!["w".to_string(), "e a.rs".to_string()];
+        CommandLineWindow::new(commands, "/", history)
+    }
+
+    #[test]
+    fn test_tab_cycles_completions_and_arrows_recall_history() {
+        let mut line = command_line();
+        line.handle_key(AppKey::Char('s'));
+        line.handle_key(AppKey::Tab);
+        assert_eq!(line.value(), "set");
+        assert_eq!(line.completions(), ["set", "split"]);
+        line.handle_key(AppKey::Tab);
+        assert_eq!(line.value(), "split");
+        line.handle_key(AppKey::Tab);
+        assert_eq!(line.value(), "set");
+        line.handle_key(AppKey::Char(' '));
+        assert!(line.completions().is_empty(), "typing starts over");
+
+        line.handle_key(AppKey::Up);
+        assert_eq!(line.value(), "e a.rs");
+        line.handle_key(AppKey::Up);
+        line.handle_key(AppKey::Up);
+        assert_eq!(line.value(), "w");
+        line.handle_key(AppKey::Down);
+        line.handle_key(AppKey::Down);
+        assert_eq!(line.value(), "", "past the newest line");
+
+        line.handle_key(AppKey::Q);
+        assert_eq!(
+            line.handle_key(AppKey::Enter),
+            InputOutcome::Accept("q".to_string())
+        );
+        line.handle_key(AppKey::Backspace);
+        assert_eq!(line.handle_key(AppKey::Backspace), InputOutcome::Dismissed);
+    }
+}
@@ -0,0 +1,245 @@
+//! Implementation of the command palette window.
+
+use super::{QuickPickOutcome, QuickPickWindow, Window};
+use crate::completion::{self, Completion};
+use crate::input::AppKey;
+use crate::theme::Theme;
+use cli_ide_platform::command::command_service::CommandInfo;
+use ratatui::prelude::*;
+
+/// Result of feeding a key to the command palette.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaletteOutcome {
+    /// The palette consumed the key and stays open.
+    Pending,
+    /// The user picked a command; the palette should close and run it.
+    Execute(String),
+    /// The user dismissed the palette.
+    Dismissed,
+}
+
+/// A modal window that fuzzy-filters commands by title.
+///
+/// The palette works on a snapshot of the registered commands taken when it
+/// is opened. Typed characters edit the query, `Up`/`Down` move the
+/// selection, `Enter` picks the selected command and `Esc` dismisses. `Tab`
+/// completes the query to the titles of the matching commands, best match
+/// first, listed in a popup under the query while there are several.
+pub struct CommandPaletteWindow {
+    /// All commands available when the palette was opened.
+    commands: Vec<CommandInfo>,
+    /// The picker listing command titles.
+    picker: QuickPickWindow,
+    /// Titles `Tab` cycles through.
+    completion: Completion,
+    /// Styles to draw the completions with.
+    theme: Theme,
+}
+
+impl CommandPaletteWindow {
+    /// Create a palette over the given commands.
+    pub fn new(commands: Vec<CommandInfo>) -> Self {
+        let titles = commands.iter().map(|c| c.title.clone()).collect();
+        Self {
+            commands,
+            picker: QuickPickWindow::new("Command Palette", titles),
+            completion: Completion::default(),
+            theme: Theme::default(),
+        }
+    }
+
+    /// Get the current query text.
+    pub fn query(&self) -> &str {
+        self.picker.query()
+    }
+
+    /// Get the commands matching the current query, best match first.
+    pub fn matches(&self) -> Vec<&CommandInfo> {
+        self.picker
+            .matches()
+            .iter()
+            .map(|&i| &self.commands[i])
+            .collect()
+    }
+
+    /// Get the highlighted command, if any command matches.
+    pub fn selected(&self) -> Option<&CommandInfo> {
+        self.picker.selected().map(|i| &self.commands[i])
+    }
+
+    /// Get the titles `Tab` cycles through, while there are several.
+    pub fn completions(&self) -> &[String] {
+        self.completion.candidates()
+    }
+
+    /// Handle a key press.
+    pub fn handle_key(&mut self, key: AppKey) -> PaletteOutcome {
+        match key {
+            AppKey::Tab => {
+                self.complete();
+                return PaletteOutcome::Pending;
+            }
+            AppKey::Backspace | AppKey::Q | AppKey::Char(_) => self.completion.reset(),
+            _ => {}
+        }
+        match self.picker.handle_key(key) {
+            QuickPickOutcome::Pending => PaletteOutcome::Pending,
+            QuickPickOutcome::Accept(i) => PaletteOutcome::Execute(self.commands[i].id.clone()),
+            QuickPickOutcome::Dismissed => PaletteOutcome::Dismissed,
+        }
+    }
+
+    /// Replace the query with the next title completing it as typed before
+    /// the first `Tab`.
+    fn complete(&mut self) {
+        let titles: Vec<String> = self
+            .matches()
+            .iter()
+            .map(|command| command.title.clone())
+            .collect();
+        let completer = move |_: &str| titles.clone();
+        if let Some(title) = self.completion.next(self.picker.query(), &completer) {
+            self.picker.set_query(title);
+        }
+    }
+}
+
+impl Window for CommandPaletteWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.picker.render(frame, area);
+    }
+
+    fn render_with_focus(&mut self, frame: &mut Frame, area: Rect, focused: bool) {
+        self.picker.render_with_focus(frame, area, focused);
+        // The query is on the first row inside the border
+        let query = Rect {
+            x: area.x + 1,
+            y: area.y + 1,
+            width: area.width.saturating_sub(2),
+            height: 1,
+        };
+        completion::render_suggestions(
+            frame,
+            query,
+            false,
+            &self.completion,
+            |title| title,
+            &self.theme,
+        );
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(id: &str, title: &str) -> CommandInfo {
+        CommandInfo {
+            id: id.to_string(),
+            title: title.to_string(),
+        }
+    }
+
+    fn palette() -> CommandPaletteWindow {
+        CommandPaletteWindow::new(vec![
+            command("workbench.action.quit", "Quit"),
+            command("workbench.action.toggleFocus", "Toggle Focus"),
+            command("workbench.action.focusNext", "Focus Next Window"),
+        ])
+    }
+
+    #[test]
+    fn test_empty_query_lists_all() {
+        let palette = palette();
+        assert_eq!(palette.matches().len(), 3);
+        assert_eq!(palette.selected().unwrap().title, "Quit");
+    }
+
+    #[test]
+    fn test_typing_filters() {
+        let mut palette = palette();
+        palette.handle_key(AppKey::Char('f'));
+        palette.handle_key(AppKey::Char('o'));
+        assert_eq!(palette.matches().len(), 2);
+
+        palette.handle_key(AppKey::Char('n'));
+        let titles: Vec<&str> = palette.matches().iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(titles, vec!["Focus Next Window"]);
+        assert_eq!(palette.query(), "fon");
+    }
+
+    #[test]
+    fn test_q_key_is_typed() {
+        let mut palette = palette();
+        palette.handle_key(AppKey::Q);
+        assert_eq!(palette.query(), "q");
+        assert_eq!(palette.selected().unwrap().title, "Quit");
+    }
+
+    #[test]
+    fn test_backspace_widens_filter() {
+        let mut palette = palette();
+        palette.handle_key(AppKey::Char('z'));
+        assert!(palette.matches().is_empty());
+
+        palette.handle_key(AppKey::Backspace);
+        assert_eq!(palette.matches().len(), 3);
+    }
+
+    #[test]
+    fn test_navigation_is_clamped() {
+        let mut palette = palette();
+        palette.handle_key(AppKey::Up);
+        assert_eq!(palette.selected().unwrap().title, "Quit");
+
+        for _ in 0..5 {
+            palette.handle_key(AppKey::Down);
+        }
+        assert_eq!(palette.selected().unwrap().title, "Focus Next Window");
+    }
+
+    #[test]
+    fn test_enter_executes_selected() {
+        let mut palette = palette();
+        palette.handle_key(AppKey::Down);
+
+        assert_eq!(
+            palette.handle_key(AppKey::Enter),
+            PaletteOutcome::Execute("workbench.action.toggleFocus".to_string())
+        );
+    }
+
+    #[test]
+    fn test_enter_with_no_matches_stays_open() {
+        let mut palette = palette();
+        palette.handle_key(AppKey::Char('z'));
+        assert_eq!(palette.handle_key(AppKey::Enter), PaletteOutcome::Pending);
+    }
+
+    #[test]
+    fn test_tab_completes_to_matching_titles() {
+        let mut palette = palette();
+        palette.handle_key(AppKey::Char('f'));
+        palette.handle_key(AppKey::Char('o'));
+        palette.handle_key(AppKey::Tab);
+        let first = palette.query().to_string();
+        assert_eq!(palette.completions().len(), 2);
+        assert_eq!(palette.selected().unwrap().title, first);
+        palette.handle_key(AppKey::Tab);
+        assert_ne!(palette.query(), first);
+        palette.handle_key(AppKey::Tab);
+        assert_eq!(palette.query(), first, "cycles back to the first title");
+        palette.handle_key(AppKey::Backspace);
+        assert!(palette.completions().is_empty());
+    }
+
+    #[test]
+    fn test_esc_dismisses() {
+        let mut palette = palette();
+        assert_eq!(palette.handle_key(AppKey::Esc), PaletteOutcome::Dismissed);
+    }
+}
@@ -0,0 +1,117 @@
+//! Implementation of the confirmation dialog.
+
+use super::Window;
+use crate::input::AppKey;
+use crate::theme::Theme;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Borders, Paragraph};
+
+/// Result of feeding a key to a confirmation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfirmOutcome {
+    /// The dialog consumed the key and stays open.
+    Pending,
+    /// The user answered yes.
+    Yes,
+    /// The user answered no.
+    No,
+    /// The user backed out without answering.
+    Cancel,
+}
+
+/// A yes/no question, such as whether to quit while work is still running.
+///
+/// Lists the things the question is about, one per line, above a warning.
+/// `Enter` or `y` answers yes, `n` no, and `Esc` cancels.
+pub struct ConfirmWindow {
+    /// The question, shown on the border.
+    title: String,
+    /// What the question is about, e.g. `cargo build (process #3)`.
+    items: Vec<String>,
+    /// Warning below the items.
+    warning: String,
+    /// Keys and what they do, shown on the bottom border.
+    hint: String,
+    /// Styles to draw with.
+    theme: Theme,
+}
+
+impl ConfirmWindow {
+    /// Create a dialog asking `title` about `items`.
+    pub fn new(title: impl Into<String>, items: Vec<String>) -> Self {
+        Self {
+            title: title.into(),
+            items,
+            warning: String::new(),
+            hint: "Enter: yes  n: no  Esc: cancel".to_string(),
+            theme: Theme::default(),
+        }
+    }
+
+    /// Show `warning` below the items.
+    pub fn with_warning(mut self, warning: impl Into<String>) -> Self {
+        self.warning = warning.into();
+        self
+    }
+
+    /// Describe the keys with `hint` instead of the generic yes/no/cancel.
+    pub fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = hint.into();
+        self
+    }
+
+    /// Get the question.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Get what the question is about.
+    pub fn items(&self) -> &[String] {
+        &self.items
+    }
+
+    /// Rows the dialog needs, including its border.
+    pub fn preferred_height(&self) -> u16 {
+        u16::try_from(self.items.len())
+            .unwrap_or(u16::MAX)
+            .saturating_add(4)
+    }
+
+    /// Handle a key press.
+    pub fn handle_key(&mut self, key: AppKey) -> ConfirmOutcome {
+        match key {
+            AppKey::Enter | AppKey::Char('y') => ConfirmOutcome::Yes,
+            AppKey::Char('n') => ConfirmOutcome::No,
+            AppKey::Esc => ConfirmOutcome::Cancel,
+            _ => ConfirmOutcome::Pending,
+        }
+    }
+}
+
+impl Window for ConfirmWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.render_with_focus(frame, area, true);
+    }
+
+    fn render_with_focus(&mut self, frame: &mut Frame, area: Rect, focused: bool) {
+        let block = Block::default()
+            .title(self.title.as_str())
+            .title_bottom(self.hint.as_str())
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(self.theme.border_style(focused));
+        let mut lines: Vec<Line> = self
+            .items
+            .iter()
+            .map(|item| Line::raw(format!("  {item}")))
+            .collect();
+        lines.push(Line::raw(""));
+        lines.push(Line::styled(self.warning.as_str(), self.theme.warning));
+        let paragraph = Paragraph::new(lines).style(self.theme.text).block(block);
+        frame.render_widget(paragraph, area);
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+}
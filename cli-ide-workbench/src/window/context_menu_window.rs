@@ -0,0 +1,200 @@
+//! Implementation of the context menu popup.
+
+use cli_ide_platform::command::command_service::CommandInfo;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Borders, List, ListItem, ListState};
+
+use super::Window;
+use crate::input::AppKey;
+use crate::list_selection::ListSelection;
+use crate::theme::Theme;
+use crate::truncate::{display_width, truncate_middle};
+
+/// Result of feeding a key to a context menu.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContextMenuOutcome {
+    /// The menu consumed the key and stays open.
+    Pending,
+    /// The user chose a command; the menu should close and run it.
+    Execute(String),
+    /// The user dismissed the menu.
+    Dismissed,
+}
+
+/// A small list of commands drawn next to where it was opened.
+///
+/// `Up`/`Down` move the highlight, `Enter` runs the highlighted command and
+/// `Esc` dismisses; clicking an item runs it.
+pub struct ContextMenuWindow {
+    /// Commands offered, in the order they are shown.
+    commands: Vec<CommandInfo>,
+    /// Highlighted row.
+    selection: ListSelection<usize>,
+    /// Styles to draw with.
+    theme: Theme,
+}
+
+impl ContextMenuWindow {
+    /// Create a menu offering `commands`.
+    pub fn new(commands: Vec<CommandInfo>) -> Self {
+        let selection = ListSelection::new((0..commands.len()).collect());
+        Self {
+            commands,
+            selection,
+            theme: Theme::default(),
+        }
+    }
+
+    /// Get the commands offered.
+    pub fn commands(&self) -> &[CommandInfo] {
+        &self.commands
+    }
+
+    /// Get the highlighted command, unless the menu is empty.
+    pub fn selected(&self) -> Option<&CommandInfo> {
+        self.selection.current().map(|&index| &self.commands[index])
+    }
+
+    /// Handle a key press.
+    pub fn handle_key(&mut self, key: AppKey) -> ContextMenuOutcome {
+        match key {
+            AppKey::Esc => return ContextMenuOutcome::Dismissed,
+            AppKey::Enter => {
+                return match self.selected() {
+                    Some(command) => ContextMenuOutcome::Execute(command.id.clone()),
+                    None => ContextMenuOutcome::Dismissed,
+                };
+            }
+            AppKey::Up => self.selection.move_by(-1),
+            AppKey::Down => self.selection.move_by(1),
+            _ => {}
+        }
+        ContextMenuOutcome::Pending
+    }
+
+    /// Where to draw the menu when opened at `anchor`: its top left corner
+    /// is at the anchor, moved left and up as needed to stay inside
+    /// `bounds`.
+    pub fn area(&self, anchor: Position, bounds: Rect) -> Rect {
+        let widest = self
+            .commands
+            .iter()
+            .map(|command| display_width(&command.title))
+            .max()
+            .unwrap_or(0);
+        let width = u16::try_from(widest + 4)
+            .unwrap_or(u16::MAX)
+            .min(bounds.width);
+        let height = u16::try_from(self.commands.len() + 2)
+            .unwrap_or(u16::MAX)
+            .min(bounds.height);
+        Rect {
+            x: anchor.x.min(bounds.right() - width).max(bounds.x),
+            y: anchor.y.min(bounds.bottom() - height).max(bounds.y),
+            width,
+            height,
+        }
+    }
+
+    /// Find the command drawn at `position` when the menu is drawn in
+    /// `area`.
+    pub fn command_at(&self, area: Rect, position: Position) -> Option<&CommandInfo> {
+        let inner = Self::block().inner(area);
+        if !inner.contains(position) {
+            return None;
+        }
+        self.commands.get(usize::from(position.y - inner.y))
+    }
+
+    /// The border around the items.
+    fn block() -> Block<'static> {
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+    }
+}
+
+impl Window for ContextMenuWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.render_with_focus(frame, area, true);
+    }
+
+    fn render_with_focus(&mut self, frame: &mut Frame, area: Rect, focused: bool) {
+        let block = Self::block().border_style(self.theme.border_style(focused));
+        let room = usize::from(block.inner(area).width.saturating_sub(2));
+        let items: Vec<ListItem> = self
+            .commands
+            .iter()
+            .map(|command| ListItem::new(format!(" {} ", truncate_middle(&command.title, room))))
+            .collect();
+        let list = List::new(items)
+            .style(self.theme.text)
+            .highlight_style(self.theme.selection)
+            .block(block);
+        let mut state = ListState::default().with_selected(self.selection.cursor());
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn menu() -> ContextMenuWindow {
+        ContextMenuWindow::new(vec![
+            CommandInfo {
+                id: "editor.action.clipboardCopyAction".to_string(),
+                title: "Copy".to_string(),
+            },
+            CommandInfo {
+                id: "editor.action.clipboardPasteAction".to_string(),
+                title: "Paste".to_string(),
+            },
+        ])
+    }
+
+    #[test]
+    fn test_keys_move_and_run_the_highlighted_command() {
+        let mut menu = menu();
+        assert_eq!(
+            menu.handle_key(AppKey::Char('x')),
+            ContextMenuOutcome::Pending
+        );
+        menu.handle_key(AppKey::Down);
+        assert_eq!(
+            menu.handle_key(AppKey::Enter),
+            ContextMenuOutcome::Execute("editor.action.clipboardPasteAction".to_string())
+        );
+        assert_eq!(menu.handle_key(AppKey::Esc), ContextMenuOutcome::Dismissed);
+        let mut empty = ContextMenuWindow::new(Vec::new());
+        assert_eq!(
+            empty.handle_key(AppKey::Enter),
+            ContextMenuOutcome::Dismissed
+        );
+    }
+
+    #[test]
+    fn test_area_stays_inside_the_bounds() {
+        let menu = menu();
+        let bounds = Rect::new(0, 0, 40, 10);
+        assert_eq!(
+            menu.area(Position::new(5, 2), bounds),
+            Rect::new(5, 2, 9, 4)
+        );
+        assert_eq!(
+            menu.area(Position::new(38, 9), bounds),
+            Rect::new(31, 6, 9, 4)
+        );
+        let area = Rect::new(5, 2, 9, 4);
+        assert_eq!(
+            menu.command_at(area, Position::new(7, 4))
+                .map(|c| c.title.as_str()),
+            Some("Paste")
+        );
+        assert_eq!(menu.command_at(area, Position::new(7, 2)), None);
+    }
+}
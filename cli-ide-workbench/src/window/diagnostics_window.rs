@@ -0,0 +1,429 @@
+//! Implementation of the Diagnostics panel.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use cli_ide_platform::problems::problem::{summarize, Problem, Severity};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Paragraph};
+
+use super::{highlight_text, line_text, pane_borders, ProblemsOutcome, Window};
+use crate::grapheme;
+use crate::input::AppKey;
+use crate::keybinding::WindowContext;
+use crate::list_selection::{reveal_row, ListSelection};
+use crate::scrollbar::ScrollPosition;
+use crate::text_selection::TextSelection;
+use crate::theme::Theme;
+
+/// A row: the index of a file, and of a problem within it for problem
+/// rows.
+type RowKey = (usize, Option<usize>);
+
+/// A panel listing problems grouped by file.
+///
+/// The first line counts the errors and warnings; below it each file with
+/// problems has a row counting its own, followed by its problems in line
+/// order. `Up`/`Down` move between rows, `Left`/`Right` collapse and expand
+/// the file under the cursor and `Enter` opens the problem under the cursor
+/// or toggles the file. `Shift+Up`/`Shift+Down` and `Space` select rows to
+/// copy, and a drag or `Shift+Left`/`Shift+Right` the characters of part
+/// of them.
+pub struct DiagnosticsWindow {
+    /// Directory paths are shown relative to.
+    root: PathBuf,
+    /// Files with problems, by path, and their problems in line order.
+    files: Vec<(PathBuf, Vec<Problem>)>,
+    /// Count of the errors and warnings, on the first line.
+    summary: String,
+    /// Files whose problems are hidden.
+    collapsed: BTreeSet<PathBuf>,
+    /// Rows and the cursor among them.
+    rows: ListSelection<RowKey>,
+    /// Characters selected within or across rows.
+    text: Option<TextSelection<RowKey>>,
+    /// Index of the first visible row.
+    scroll_offset: usize,
+    /// Whether the next render scrolls the cursor into view.
+    follow_cursor: bool,
+    /// Styles to draw with.
+    theme: Theme,
+    /// Whether the panel is drawn with only its top border.
+    compact: bool,
+}
+
+impl DiagnosticsWindow {
+    /// Create an empty panel showing paths relative to `root`.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            files: Vec::new(),
+            summary: summarize(&[]),
+            collapsed: BTreeSet::new(),
+            rows: ListSelection::default(),
+            text: None,
+            scroll_offset: 0,
+            follow_cursor: true,
+            theme: Theme::default(),
+            compact: false,
+        }
+    }
+
+    /// Get the directory paths are shown relative to.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Get the files with problems and their problems, in display order.
+    pub fn files(&self) -> &[(PathBuf, Vec<Problem>)] {
+        &self.files
+    }
+
+    /// Show `problems` instead of the current ones, grouped by file.
+    ///
+    /// Collapsed files stay collapsed and the cursor stays on the same row,
+    /// clamped to the new rows.
+    pub fn set_problems(&mut self, problems: Vec<Problem>) {
+        self.summary = summarize(&problems);
+        let mut files: Vec<(PathBuf, Vec<Problem>)> = Vec::new();
+        for problem in problems {
+            match files.iter_mut().find(|(path, _)| *path == problem.path) {
+                Some((_, found)) => found.push(problem),
+                None => files.push((problem.path.clone(), vec![problem])),
+            }
+        }
+        files.sort_by(|(a, _), (b, _)| a.cmp(b));
+        for (_, problems) in &mut files {
+            problems.sort_by_key(|problem| (problem.line, problem.column));
+        }
+        self.files = files;
+        self.collapsed
+            .retain(|path| self.files.iter().any(|(file, _)| file == path));
+        self.update_rows();
+        self.text = None;
+        self.follow_cursor = true;
+    }
+
+    /// Check whether the problems of the file at `path` are hidden.
+    pub fn is_collapsed(&self, path: &Path) -> bool {
+        self.collapsed.contains(path)
+    }
+
+    /// The problem under the cursor, if the cursor is on a problem row.
+    pub fn selected(&self) -> Option<&Problem> {
+        match *self.rows.current()? {
+            (file, Some(index)) => Some(&self.files[file].1[index]),
+            (_, None) => None,
+        }
+    }
+
+    /// Handle a key press.
+    pub fn handle_key(&mut self, key: AppKey) -> ProblemsOutcome {
+        match key {
+            AppKey::Left => self.set_collapsed(true),
+            AppKey::Right => self.set_collapsed(false),
+            AppKey::Enter => {
+                if let Some(problem) = self.selected() {
+                    return ProblemsOutcome::Open(problem.clone());
+                }
+                let collapsed = self
+                    .current_file()
+                    .is_some_and(|path| self.collapsed.contains(path));
+                self.set_collapsed(!collapsed);
+            }
+            AppKey::ShiftLeft | AppKey::ShiftRight => {
+                if let Some(&key_row) = self.rows.current() {
+                    let line = self.row_text(key_row);
+                    TextSelection::extend_on(&mut self.text, key_row, &line, key);
+                }
+            }
+            key => {
+                if !self.rows.handle_key(key) {
+                    return ProblemsOutcome::Ignored;
+                }
+                self.text = None;
+            }
+        }
+        self.follow_cursor = true;
+        ProblemsOutcome::Handled
+    }
+
+    /// Path of the file of the row under the cursor.
+    fn current_file(&self) -> Option<&PathBuf> {
+        let &(file, _) = self.rows.current()?;
+        Some(&self.files[file].0)
+    }
+
+    /// Collapse or expand the file of the row under the cursor, moving the
+    /// cursor to the file's row.
+    fn set_collapsed(&mut self, collapsed: bool) {
+        let Some(&(file, _)) = self.rows.current() else {
+            return;
+        };
+        let path = self.files[file].0.clone();
+        if collapsed {
+            self.collapsed.insert(path);
+        } else {
+            self.collapsed.remove(&path);
+        }
+        self.update_rows();
+        if let Some(row) = self
+            .rows
+            .items()
+            .iter()
+            .position(|&key| key == (file, None))
+        {
+            self.rows.set_cursor(row);
+        }
+    }
+
+    /// Lay out the rows: each file, followed by its problems unless it is
+    /// collapsed.
+    fn update_rows(&mut self) {
+        let mut rows = Vec::new();
+        for (file, (path, problems)) in self.files.iter().enumerate() {
+            rows.push((file, None));
+            if !self.collapsed.contains(path) {
+                rows.extend((0..problems.len()).map(|index| (file, Some(index))));
+            }
+        }
+        self.rows.set_items(rows);
+    }
+
+    /// The text of the line showing the row `key`.
+    fn row_text(&self, key: RowKey) -> String {
+        line_text(&self.row_line(key, self.theme.text))
+    }
+
+    /// The line showing the row `key`.
+    fn row_line(&self, (file, index): RowKey, style: Style) -> Line<'static> {
+        let (path, problems) = &self.files[file];
+        let Some(index) = index else {
+            let marker = if self.collapsed.contains(path) {
+                "▸"
+            } else {
+                "▾"
+            };
+            let path = path.strip_prefix(&self.root).unwrap_or(path);
+            return Line::styled(
+                format!("{marker} {} ({})", path.display(), summarize(problems)),
+                style,
+            );
+        };
+        let problem = &problems[index];
+        let (letter, severity_style) = match problem.severity {
+            Severity::Error => ("E", self.theme.error),
+            Severity::Warning => ("W", self.theme.warning),
+        };
+        let code = problem
+            .code
+            .as_ref()
+            .map(|code| format!(" [{code}]"))
+            .unwrap_or_default();
+        Line::from(vec![
+            Span::styled("    ", style),
+            Span::styled(format!("{letter} "), severity_style.patch(style)),
+            Span::styled(
+                format!(
+                    "{}:{} {}{code}",
+                    problem.line, problem.column, problem.message
+                ),
+                style,
+            ),
+        ])
+    }
+}
+
+impl Window for DiagnosticsWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.render_with_focus(frame, area, false);
+    }
+
+    fn render_with_focus(&mut self, frame: &mut Frame, area: Rect, focused: bool) {
+        let border_type = if focused {
+            BorderType::Thick
+        } else {
+            BorderType::Plain
+        };
+        let title = if focused {
+            "Diagnostics [*]"
+        } else {
+            "Diagnostics"
+        };
+        let block = Block::default()
+            .title(title)
+            .borders(pane_borders(self.compact))
+            .border_type(border_type)
+            .border_style(self.theme.border_style(focused));
+        // The summary line stays at the top
+        let height = usize::from(block.inner(area).height).saturating_sub(1);
+
+        if self.follow_cursor && height > 0 {
+            if let Some(cursor) = self.rows.cursor() {
+                self.scroll_offset = reveal_row(self.scroll_offset, cursor, height);
+            }
+            self.follow_cursor = false;
+        }
+        self.scroll_offset = self
+            .scroll_offset
+            .min(self.rows.len().saturating_sub(height));
+
+        let mut lines = vec![Line::styled(
+            self.summary.clone(),
+            self.theme.text.add_modifier(Modifier::BOLD),
+        )];
+        let selecting = self.text.as_ref().is_some_and(|text| !text.is_empty());
+        for (row, &key) in self
+            .rows
+            .items()
+            .iter()
+            .enumerate()
+            .skip(self.scroll_offset)
+            .take(height)
+        {
+            let on_cursor = focused && !selecting && self.rows.cursor() == Some(row);
+            let style = if on_cursor || self.rows.is_marked(&key) {
+                self.theme.selection
+            } else {
+                self.theme.text
+            };
+            lines.push(highlight_text(
+                self.row_line(key, style),
+                self.text.as_ref(),
+                self.rows.items(),
+                row,
+                self.theme.selection,
+            ));
+        }
+        let paragraph = Paragraph::new(lines).style(self.theme.text).block(block);
+        frame.render_widget(paragraph, area);
+    }
+
+    fn scroll_by(&mut self, lines: i32) {
+        let offset = i64::try_from(self.scroll_offset).unwrap_or(i64::MAX) + i64::from(lines);
+        self.scroll_offset = usize::try_from(offset.max(0)).unwrap_or(usize::MAX);
+        self.follow_cursor = false;
+    }
+
+    fn select_at(&mut self, row: u16, column: u16, extend: bool) {
+        // The summary line is above the rows
+        let row = self.scroll_offset + usize::from(row.saturating_sub(1));
+        let Some(row) = self.rows.len().checked_sub(1).map(|last| row.min(last)) else {
+            return;
+        };
+        let key = self.rows.items()[row];
+        let line = self.row_text(key);
+        let point = grapheme::index_at_display_column(&line, usize::from(column));
+        TextSelection::click(&mut self.text, key, point, extend);
+        if extend {
+            self.rows.set_cursor(row);
+        } else {
+            self.rows.click(row, false);
+        }
+    }
+
+    fn selected_text(&self) -> Option<String> {
+        let selected = self
+            .text
+            .as_ref()
+            .and_then(|text| text.text(self.rows.items(), |&key| self.row_text(key)));
+        if selected.is_some() {
+            return selected;
+        }
+        let lines: Vec<String> = self
+            .rows
+            .targets()
+            .into_iter()
+            .map(|key| self.row_text(key))
+            .collect();
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+
+    fn scroll_position(&self) -> Option<ScrollPosition> {
+        // The summary line does not scroll
+        Some(ScrollPosition {
+            offset: self.scroll_offset,
+            content: self.rows.len() + 1,
+        })
+    }
+
+    fn context(&self) -> Option<WindowContext> {
+        Some(WindowContext::Problems)
+    }
+
+    fn set_compact(&mut self, compact: bool) {
+        self.compact = compact;
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problem(path: &str, line: usize, severity: Severity) -> Problem {
+        Problem {
+            path: PathBuf::from("/ws").join(path),
+            line,
+            column: 1,
+            severity,
+            message: "oops".to_string(),
+            code: None,
+        }
+    }
+
+    #[test]
+    fn test_problems_are_grouped_by_file_in_line_order() {
+        let mut panel = DiagnosticsWindow::new("/ws");
+        panel.set_problems(vec![
+            problem("src/b.rs", 9, Severity::Warning),
+            problem("src/a.rs", 4, Severity::Error),
+            problem("src/b.rs", 2, Severity::Error),
+        ]);
+        let files: Vec<(&Path, Vec<usize>)> = panel
+            .files()
+            .iter()
+            .map(|(path, problems)| {
+                let lines = problems.iter().map(|problem| problem.line).collect();
+                (path.as_path(), lines)
+            })
+            .collect();
+        assert_eq!(
+            files,
+            [
+                (Path::new("/ws/src/a.rs"), vec![4]),
+                (Path::new("/ws/src/b.rs"), vec![2, 9]),
+            ]
+        );
+
+        // The cursor starts on the first file's row
+        assert_eq!(panel.selected(), None);
+        panel.handle_key(AppKey::Down);
+        assert_eq!(
+            panel.handle_key(AppKey::Enter),
+            ProblemsOutcome::Open(problem("src/a.rs", 4, Severity::Error))
+        );
+
+        // Collapsing hides a file's problems and moves to its row
+        panel.handle_key(AppKey::Left);
+        assert!(panel.is_collapsed(Path::new("/ws/src/a.rs")));
+        panel.handle_key(AppKey::Down);
+        panel.handle_key(AppKey::Down);
+        assert_eq!(
+            panel.selected().map(|problem| problem.line),
+            Some(2),
+            "the collapsed file's problem is skipped"
+        );
+        panel.handle_key(AppKey::Up);
+        panel.handle_key(AppKey::Up);
+        assert_eq!(panel.handle_key(AppKey::Enter), ProblemsOutcome::Handled);
+        assert!(!panel.is_collapsed(Path::new("/ws/src/a.rs")));
+        assert_eq!(
+            panel.handle_key(AppKey::Char('x')),
+            ProblemsOutcome::Ignored
+        );
+    }
+}
@@ -0,0 +1,560 @@
+//! Implementation of the diff view.
+
+use std::path::{Path, PathBuf};
+
+use cli_ide_base::highlight::{HighlightSpan, Highlighter, LexicalHighlighter, LineState};
+use cli_ide_platform::git::diff::{DiffLine, DiffLineKind, FileDiff, Hunk};
+use cli_ide_platform::git::repository::DiffBase;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Borders, Paragraph};
+
+use super::Window;
+use crate::input::AppKey;
+use crate::keybinding::WindowContext;
+use crate::theme::Theme;
+use crate::truncate::{display_width, truncate_path};
+
+/// Width of a line number column.
+const NUMBER_WIDTH: usize = 4;
+
+/// How the diff view lays out the two sides of a change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DiffLayout {
+    /// One column with removed lines above the added lines replacing them.
+    #[default]
+    Unified,
+    /// The old side on the left and the new side on the right.
+    SideBySide,
+}
+
+/// What a diff view compares.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffSubject {
+    /// The file's changes in git, against the index or `HEAD`.
+    Git(DiffBase),
+    /// Two texts compared directly, e.g. two buffers or a buffer and the
+    /// file on disk, named for the title.
+    Compare {
+        /// Name of the old side, e.g. `a.txt (Saved)`.
+        old: String,
+        /// Name of the new side.
+        new: String,
+    },
+}
+
+impl From<DiffBase> for DiffSubject {
+    fn from(base: DiffBase) -> Self {
+        DiffSubject::Git(base)
+    }
+}
+
+/// A view of the changes to one file, against the index or `HEAD`, or
+/// between two texts.
+///
+/// `Up`/`Down` select the previous or next hunk, which the stage, unstage
+/// and revert actions act on for git changes. The layout switches between
+/// unified and side by side. Files in a language with a highlighter have
+/// their tokens colored over the background of added and removed lines.
+pub struct DiffWindow {
+    /// Directory the file is shown relative to.
+    root: PathBuf,
+    /// The changed file.
+    path: PathBuf,
+    /// What the diff compares.
+    subject: DiffSubject,
+    /// The changes.
+    diff: FileDiff,
+    /// Highlighter for the file's language, if it has one.
+    highlighter: Option<LexicalHighlighter>,
+    /// Unified or side by side.
+    layout: DiffLayout,
+    /// Index of the selected hunk.
+    hunk: usize,
+    /// Index of the first visible row.
+    scroll_offset: usize,
+    /// Whether the next render scrolls the selected hunk into view.
+    follow_hunk: bool,
+    /// Styles to draw with.
+    theme: Theme,
+}
+
+impl DiffWindow {
+    /// Create a view of `diff`, the changes to `path` described by
+    /// `subject`, showing the path relative to `root`.
+    pub fn new(
+        root: impl Into<PathBuf>,
+        path: impl Into<PathBuf>,
+        subject: impl Into<DiffSubject>,
+        diff: FileDiff,
+    ) -> Self {
+        let path = path.into();
+        Self {
+            root: root.into(),
+            highlighter: highlighter_for(&path),
+            path,
+            subject: subject.into(),
+            diff,
+            layout: DiffLayout::default(),
+            hunk: 0,
+            scroll_offset: 0,
+            follow_hunk: true,
+            theme: Theme::default(),
+        }
+    }
+
+    /// Get the changed file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get what the diff compares.
+    pub fn subject(&self) -> &DiffSubject {
+        &self.subject
+    }
+
+    /// Get what the git changes are against, or `None` for a comparison
+    /// of two texts.
+    pub fn base(&self) -> Option<DiffBase> {
+        match self.subject {
+            DiffSubject::Git(base) => Some(base),
+            DiffSubject::Compare { .. } => None,
+        }
+    }
+
+    /// Get the changes.
+    pub fn diff(&self) -> &FileDiff {
+        &self.diff
+    }
+
+    /// Show the changes to another file, or another subject, from the top.
+    pub fn show(
+        &mut self,
+        path: impl Into<PathBuf>,
+        subject: impl Into<DiffSubject>,
+        diff: FileDiff,
+    ) {
+        self.path = path.into();
+        self.highlighter = highlighter_for(&self.path);
+        self.subject = subject.into();
+        self.diff = diff;
+        self.hunk = 0;
+        self.scroll_offset = 0;
+        self.follow_hunk = true;
+    }
+
+    /// Replace the changes of the same file, e.g. after staging a hunk.
+    ///
+    /// The selection stays at the same hunk index, or the last hunk.
+    pub fn set_diff(&mut self, diff: FileDiff) {
+        self.diff = diff;
+        self.hunk = self.hunk.min(self.diff.hunks.len().saturating_sub(1));
+        self.follow_hunk = true;
+    }
+
+    /// Get the index of the selected hunk, if there are any.
+    pub fn selected_hunk(&self) -> Option<usize> {
+        (self.hunk < self.diff.hunks.len()).then_some(self.hunk)
+    }
+
+    /// Get the layout.
+    pub fn layout(&self) -> DiffLayout {
+        self.layout
+    }
+
+    /// Switch between the unified and side-by-side layouts.
+    pub fn toggle_layout(&mut self) {
+        self.layout = match self.layout {
+            DiffLayout::Unified => DiffLayout::SideBySide,
+            DiffLayout::SideBySide => DiffLayout::Unified,
+        };
+        self.follow_hunk = true;
+    }
+
+    /// Handle a key press. Returns whether the key was consumed.
+    pub fn handle_key(&mut self, key: AppKey) -> bool {
+        match key {
+            AppKey::Up => self.hunk = self.hunk.saturating_sub(1),
+            AppKey::Down => {
+                self.hunk = (self.hunk + 1).min(self.diff.hunks.len().saturating_sub(1));
+            }
+            _ => return false,
+        }
+        self.follow_hunk = true;
+        true
+    }
+
+    /// The title for a pane `width` cells wide: the file and what it is
+    /// compared with, dropping directories from the middle of the path when
+    /// it does not fit.
+    fn title(&self, focused: bool, width: u16) -> String {
+        let marker = if focused { " [*]" } else { "" };
+        let base = match &self.subject {
+            DiffSubject::Git(DiffBase::Index) => "Working Tree",
+            DiffSubject::Git(DiffBase::Head) => "Staged",
+            DiffSubject::Compare { old, new } => {
+                let frame = display_width(&format!("Diff:  ↔ {marker}"));
+                let room = usize::from(width.saturating_sub(2)).saturating_sub(frame) / 2;
+                return format!(
+                    "Diff: {} ↔ {}{marker}",
+                    truncate_path(old, room),
+                    truncate_path(new, room)
+                );
+            }
+        };
+        let path = self.path.strip_prefix(&self.root).unwrap_or(&self.path);
+        let frame = display_width(&format!("Diff:  ({base}){marker}"));
+        let room = usize::from(width.saturating_sub(2)).saturating_sub(frame);
+        let path = truncate_path(&path.display().to_string(), room);
+        format!("Diff: {path} ({base}){marker}")
+    }
+
+    /// Style of a line of the given kind.
+    fn line_style(&self, kind: DiffLineKind) -> Style {
+        match kind {
+            DiffLineKind::Context => self.theme.text,
+            DiffLineKind::Added => self.theme.diff_added,
+            DiffLineKind::Removed => self.theme.diff_removed,
+        }
+    }
+
+    /// Style of the text of a line of the given kind, under its tokens.
+    ///
+    /// Without a highlighter the whole line takes the diff color.
+    fn text_style(&self, kind: DiffLineKind) -> Style {
+        if self.highlighter.is_none() {
+            return self.line_style(kind);
+        }
+        match kind {
+            DiffLineKind::Context => self.theme.text,
+            DiffLineKind::Added => self.theme.text.patch(self.theme.diff_added_line),
+            DiffLineKind::Removed => self.theme.text.patch(self.theme.diff_removed_line),
+        }
+    }
+
+    /// The tokens of each line of `hunk`, highlighting the old and new
+    /// sides separately from the start of the hunk.
+    fn tokens(&self, hunk: &Hunk) -> Vec<Vec<HighlightSpan>> {
+        let Some(highlighter) = &self.highlighter else {
+            return vec![Vec::new(); hunk.lines.len()];
+        };
+        let (mut old_state, mut new_state) = (LineState::default(), LineState::default());
+        hunk.lines
+            .iter()
+            .map(|line| {
+                let state = match line.kind {
+                    DiffLineKind::Added => new_state,
+                    _ => old_state,
+                };
+                let (tokens, state) = highlighter.highlight_line(&line.text, state);
+                match line.kind {
+                    DiffLineKind::Context => (old_state, new_state) = (state, state),
+                    DiffLineKind::Added => new_state = state,
+                    DiffLineKind::Removed => old_state = state,
+                }
+                tokens
+            })
+            .collect()
+    }
+
+    /// Spans of at most `limit` characters of `text`, in `style` with the
+    /// colors of `tokens` on top.
+    fn text_spans(
+        &self,
+        text: &str,
+        tokens: &[HighlightSpan],
+        style: Style,
+        limit: usize,
+    ) -> Vec<Span<'static>> {
+        let mut spans = Vec::new();
+        let mut run = String::new();
+        let mut run_style = style;
+        for (index, character) in text.char_indices().take(limit) {
+            let character_style = tokens
+                .iter()
+                .find(|token| token.range.contains(&index))
+                .map_or(style, |token| {
+                    style.patch(self.theme.token_style(token.kind))
+                });
+            if character_style != run_style && !run.is_empty() {
+                spans.push(Span::styled(std::mem::take(&mut run), run_style));
+            }
+            run_style = character_style;
+            run.push(character);
+        }
+        if !run.is_empty() {
+            spans.push(Span::styled(run, run_style));
+        }
+        spans
+    }
+
+    /// The rows of the diff for a pane `width` columns wide, and the row
+    /// of each hunk header.
+    fn rows(&self, width: usize, focused: bool) -> (Vec<Line<'static>>, Vec<usize>) {
+        if self.diff.is_binary() {
+            return (
+                vec![Line::styled("Binary file", self.theme.text)],
+                Vec::new(),
+            );
+        }
+        if self.diff.is_empty() {
+            return (
+                vec![Line::styled("No changes", self.theme.text)],
+                Vec::new(),
+            );
+        }
+        let mut rows = Vec::new();
+        let mut headers = Vec::new();
+        for (index, hunk) in self.diff.hunks.iter().enumerate() {
+            headers.push(rows.len());
+            let style = if focused && index == self.hunk {
+                self.theme.selection
+            } else {
+                self.theme.diff_hunk
+            };
+            let marker = if index == self.hunk { '>' } else { ' ' };
+            rows.push(Line::styled(format!("{marker}{}", hunk.header()), style));
+            let tokens = self.tokens(hunk);
+            match self.layout {
+                DiffLayout::Unified => {
+                    rows.extend(
+                        hunk.lines
+                            .iter()
+                            .zip(&tokens)
+                            .map(|(line, tokens)| self.unified_row(line, tokens)),
+                    );
+                }
+                DiffLayout::SideBySide => {
+                    // Both halves and the separator fill the width
+                    let half = width.saturating_sub(1) / 2;
+                    let tokens_of = |line: &DiffLine| {
+                        hunk.lines
+                            .iter()
+                            .position(|other| std::ptr::eq(other, line))
+                            .map_or(&[][..], |index| &tokens[index])
+                    };
+                    rows.extend(hunk.side_by_side().into_iter().map(|(old, new)| {
+                        self.side_by_side_row(
+                            old.map(|line| (line, tokens_of(line))),
+                            new.map(|line| (line, tokens_of(line))),
+                            half,
+                        )
+                    }));
+                }
+            }
+        }
+        (rows, headers)
+    }
+
+    /// A unified row: both line numbers, the prefix and the text.
+    fn unified_row(&self, line: &DiffLine, tokens: &[HighlightSpan]) -> Line<'static> {
+        let number = |number: Option<usize>| {
+            number.map_or(" ".repeat(NUMBER_WIDTH), |number| {
+                format!("{number:>NUMBER_WIDTH$}")
+            })
+        };
+        let mut spans = vec![Span::styled(
+            format!(
+                "{} {} {}",
+                number(line.old_line),
+                number(line.new_line),
+                line.kind.prefix()
+            ),
+            self.line_style(line.kind),
+        )];
+        spans.extend(self.text_spans(&line.text, tokens, self.text_style(line.kind), usize::MAX));
+        Line::from(spans)
+    }
+
+    /// A side-by-side row with each side cut or padded to `half` columns.
+    fn side_by_side_row(
+        &self,
+        old: Option<(&DiffLine, &[HighlightSpan])>,
+        new: Option<(&DiffLine, &[HighlightSpan])>,
+        half: usize,
+    ) -> Line<'static> {
+        let side = |line: Option<(&DiffLine, &[HighlightSpan])>,
+                    number: fn(&DiffLine) -> Option<usize>| {
+            let Some((line, tokens)) = line else {
+                return vec![Span::styled(" ".repeat(half), self.theme.text)];
+            };
+            let style = self.line_style(line.kind);
+            let number = number(line).map_or(String::new(), |n| n.to_string());
+            let number: String = format!("{number:>NUMBER_WIDTH$} ")
+                .chars()
+                .take(half)
+                .collect();
+            let room = half - number.chars().count();
+            let text = self.text_spans(&line.text, tokens, self.text_style(line.kind), room);
+            let used: usize = text.iter().map(|span| span.content.chars().count()).sum();
+            let mut spans = vec![Span::styled(number, style)];
+            spans.extend(text);
+            spans.push(Span::styled(" ".repeat(room - used), style));
+            spans
+        };
+        let mut spans = side(old, |line| line.old_line);
+        spans.push(Span::styled("│", self.theme.border));
+        spans.extend(side(new, |line| line.new_line));
+        Line::from(spans)
+    }
+}
+
+/// The built-in highlighter for the language of `path`, if there is one.
+fn highlighter_for(path: &Path) -> Option<LexicalHighlighter> {
+    LexicalHighlighter::language_for_path(path).and_then(LexicalHighlighter::for_language)
+}
+
+impl Window for DiffWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.render_with_focus(frame, area, false);
+    }
+
+    fn render_with_focus(&mut self, frame: &mut Frame, area: Rect, focused: bool) {
+        let border_type = if focused {
+            BorderType::Thick
+        } else {
+            BorderType::Plain
+        };
+        let block = Block::default()
+            .title(self.title(focused, area.width))
+            .borders(Borders::ALL)
+            .border_type(border_type)
+            .border_style(self.theme.border_style(focused));
+        let inner = block.inner(area);
+        let height = usize::from(inner.height);
+
+        let (rows, headers) = self.rows(usize::from(inner.width), focused);
+        if self.follow_hunk && height > 0 {
+            if let Some(&header) = headers.get(self.hunk) {
+                let end = headers.get(self.hunk + 1).copied().unwrap_or(rows.len());
+                // Show the whole hunk if it fits, and its header in any case
+                if header < self.scroll_offset {
+                    self.scroll_offset = header;
+                } else if end > self.scroll_offset + height {
+                    self.scroll_offset = header.min(end - height);
+                }
+            }
+            self.follow_hunk = false;
+        }
+        let scroll_offset = self.scroll_offset.min(rows.len().saturating_sub(height));
+        self.scroll_offset = scroll_offset;
+
+        let visible: Vec<Line> = rows.into_iter().skip(scroll_offset).take(height).collect();
+        let paragraph = Paragraph::new(visible).style(self.theme.text).block(block);
+        frame.render_widget(paragraph, area);
+    }
+
+    fn scroll_by(&mut self, lines: i32) {
+        let offset = i64::try_from(self.scroll_offset).unwrap_or(i64::MAX) + i64::from(lines);
+        self.scroll_offset = usize::try_from(offset.max(0)).unwrap_or(usize::MAX);
+        self.follow_hunk = false;
+    }
+
+    fn context(&self) -> Option<WindowContext> {
+        Some(WindowContext::Diff)
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diff() -> FileDiff {
+        FileDiff::parse(
+            "\
+--- a/a.txt
++++ b/a.txt
+@@ -1,2 +1,2 @@
+-one
++ONE
+ two
+@@ -9,2 +9,2 @@
+ nine
+-ten
++TEN
+",
+        )
+    }
+
+    #[test]
+    fn test_title_drops_directories_when_narrow() {
+        let view = DiffWindow::new(
+            "/repo",
+            "/repo/src/window/panes/editor_window.rs",
+            DiffBase::Index,
+            diff(),
+        );
+        assert_eq!(
+            view.title(false, 80),
+            "Diff: src/window/panes/editor_window.rs (Working Tree)"
+        );
+        assert_eq!(
+            view.title(false, 45),
+            "Diff: src/…/editor_window.rs (Working Tree)"
+        );
+    }
+
+    #[test]
+    fn test_up_and_down_select_hunks_within_bounds() {
+        let mut view = DiffWindow::new("/repo", "/repo/a.txt", DiffBase::Index, diff());
+        assert_eq!(view.selected_hunk(), Some(0));
+        assert!(view.handle_key(AppKey::Down));
+        assert!(view.handle_key(AppKey::Down));
+        assert_eq!(view.selected_hunk(), Some(1));
+        assert!(!view.handle_key(AppKey::Char('s')));
+
+        // Staging the last hunk leaves the one before selected
+        let mut remaining = diff();
+        remaining.hunks.pop();
+        view.set_diff(remaining);
+        assert_eq!(view.selected_hunk(), Some(0));
+        view.set_diff(FileDiff::default());
+        assert_eq!(view.selected_hunk(), None);
+    }
+
+    #[test]
+    fn test_comparison_highlights_tokens_over_the_line_color() {
+        let changes = FileDiff::compare("fn old() {}\n", "fn new() {}\n");
+        let subject = DiffSubject::Compare {
+            old: "main.rs (Saved)".to_string(),
+            new: "main.rs".to_string(),
+        };
+        let view = DiffWindow::new("/repo", "/repo/main.rs", subject, changes);
+        assert_eq!(view.base(), None);
+        assert_eq!(view.title(true, 80), "Diff: main.rs (Saved) ↔ main.rs [*]");
+
+        let (rows, headers) = view.rows(80, false);
+        assert_eq!(headers, [0]);
+        let theme = Theme::default();
+        let keyword = rows[2]
+            .spans
+            .iter()
+            .find(|span| span.content == "fn")
+            .unwrap();
+        assert_eq!(
+            keyword.style,
+            theme.text.patch(theme.diff_added_line).patch(theme.keyword)
+        );
+        let plain = DiffWindow::new("/repo", "/repo/a.txt", DiffBase::Index, diff());
+        let (rows, _) = plain.rows(80, false);
+        assert!(rows[1]
+            .spans
+            .iter()
+            .all(|span| span.style == theme.diff_removed));
+    }
+
+    #[test]
+    fn test_show_replaces_file_and_resets_selection() {
+        let mut view = DiffWindow::new("/repo", "/repo/a.txt", DiffBase::Index, diff());
+        view.handle_key(AppKey::Down);
+        view.toggle_layout();
+        view.show("/repo/b.txt", DiffBase::Head, diff());
+        assert_eq!(view.path(), Path::new("/repo/b.txt"));
+        assert_eq!(view.base(), Some(DiffBase::Head));
+        assert_eq!(view.selected_hunk(), Some(0));
+        assert_eq!(view.layout(), DiffLayout::SideBySide);
+    }
+}
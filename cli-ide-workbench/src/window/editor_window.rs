@@ -1,21 +1,1593 @@
 //! Implementation of an editor window.
 
-use super::Window;
+use std::cell::RefCell;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use cli_ide_base::highlight::{HighlightCache, Highlighter, LexicalHighlighter};
+use cli_ide_platform::git::diff::{LineChange, LineChangeKind};
+use cli_ide_platform::problems::problem::{Problem, Severity};
+
+use super::{
+    pane_borders, FindOutcome, FindWindow, PeekWindow, RenameInputWindow, Window, FIND_HEIGHT,
+    FIND_WIDTH, PEEK_HEIGHT,
+};
+use crate::abbreviation::{Abbreviations, Expansion};
+use crate::bracket_pair::BracketPairCache;
+use crate::bracket_selection::{
+    BracketSelectionProvider, SelectionExpansion, SelectionRangeProvider,
+};
+use crate::buffer_manager::{SharedBuffer, TextBuffer};
+use crate::buffer_options::{BufferOptions, OptionOverrides, OptionsError};
+use crate::code_action::{EditError, TextDocuments, TextEdit};
+use crate::color_swatch;
+use crate::document_link::{self, DocumentLink};
+use crate::editor_group::{EditorGroup, EditorTab};
+use crate::export::{self, ExportFormat};
+use crate::grapheme;
+use crate::input::AppKey;
+use crate::keybinding::WindowContext;
+use crate::line_length::{self, LineLengthConfig, LineLengthLimit, LineLengthWarning};
+use crate::scrollbar::ScrollPosition;
+use crate::theme::Theme;
+use crate::truncate::{display_width, truncate_middle};
+use crate::viewport::Viewport;
 use ratatui::prelude::*;
-use ratatui::widgets::{Block, BorderType, Borders, Paragraph};
+use ratatui::widgets::{Block, BorderType, Clear, Paragraph, Wrap};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// How a decorated range of editor text is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecorationKind {
+    /// A pattern match, e.g. of a search.
+    Match,
+    /// A capture group within a match.
+    MatchGroup,
+    /// A swatch of a color value, drawn in the color before the range.
+    ColorSwatch(Color),
+    /// A marker before an image value, e.g. a `data:image/...` URI.
+    ImageSwatch,
+}
+
+impl DecorationKind {
+    /// The cell drawn before the decorated range, for kinds that insert one
+    /// instead of restyling the range.
+    pub fn marker(self) -> Option<&'static str> {
+        match self {
+            DecorationKind::Match | DecorationKind::MatchGroup => None,
+            DecorationKind::ColorSwatch(_) => Some("■"),
+            DecorationKind::ImageSwatch => Some("▣"),
+        }
+    }
+}
+
+/// A byte range of the editor text drawn with a theme style on top of the
+/// syntax highlighting, e.g. to mark search matches, or marked by a cell
+/// drawn before it, e.g. a color swatch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decoration {
+    /// Byte range in the text.
+    pub range: Range<usize>,
+    /// How the range is drawn.
+    pub kind: DecorationKind,
+}
 
 /// A simple editor window stub.
+///
+/// The editor shows one tab of its [`EditorGroup`] at a time; a tab strip
+/// above the text lists them while more than one is open.
 pub struct EditorWindow {
-    /// Contents of the editor. In Phase 1 this is static; later it will be
-    /// backed by a rope data structure.
-    buffer: String,
+    /// Open documents and the editor's selection and scroll position in
+    /// each. Buffers are shared with other editors showing the same file;
+    /// later they will be backed by a rope data structure.
+    group: EditorGroup,
+    /// Language identifier of the buffer, used to resolve per-language settings.
+    language: Option<String>,
+    /// Soft line-length limit; characters past it are highlighted.
+    line_length_limit: Option<LineLengthLimit>,
+    /// History of selection expansions, used to shrink the selection.
+    selection_expansion: SelectionExpansion,
+    /// Source of the ranges a selection expands to.
+    selection_provider: Box<dyn SelectionRangeProvider>,
+    /// Resolved buffer-local options (wrap, indentation, keybindings).
+    options: BufferOptions,
+    /// Embedded peek view (e.g. peek definition), when open.
+    peek: Option<PeekWindow>,
+    /// Inline rename input anchored at a symbol, when open.
+    rename: Option<RenameInputWindow>,
+    /// Find and replace input, when open; its matches are decorated.
+    find: Option<FindWindow>,
+    /// Styles to draw with.
+    theme: Theme,
+    /// Syntax highlighter, if the language has one.
+    highlighter: Option<Box<dyn Highlighter>>,
+    /// Whether the highlighter was set explicitly rather than chosen from
+    /// the language.
+    custom_highlighter: bool,
+    /// Language the built-in highlighter was chosen for.
+    highlighted_language: Option<String>,
+    /// Highlighted spans kept between renders.
+    highlight_cache: HighlightCache,
+    /// Bracket depths kept between renders.
+    bracket_pairs: BracketPairCache,
+    /// Ranges drawn with decoration styles, later ones on top.
+    decorations: Vec<Decoration>,
+    /// Links of the shown document from a [`DocumentLinkProvider`], or
+    /// `None` to detect them in the text.
+    ///
+    /// [`DocumentLinkProvider`]: crate::document_link::DocumentLinkProvider
+    document_links: Option<Vec<DocumentLink>>,
+    /// Title of the border, before the focus marker.
+    title: String,
+    /// Number of text rows that fit, as of the last render.
+    height: usize,
+    /// Whether the editor is drawn without side borders, gutter and
+    /// underlined problems.
+    compact: bool,
 }
 
 impl Default for EditorWindow {
     fn default() -> Self {
+        Self::with_text("Welcome to Paradiddle.rs!\n\nPress ? for a guided tutorial.")
+    }
+}
+
+impl EditorWindow {
+    /// Create an editor window displaying the given text.
+    pub fn with_text(text: impl Into<String>) -> Self {
+        Self::with_buffer(Rc::new(RefCell::new(TextBuffer::new(text))))
+    }
+
+    /// Create an editor window showing a shared buffer.
+    pub fn with_buffer(buffer: SharedBuffer) -> Self {
         Self {
-            buffer: String::from("Welcome to Paradiddle.rs!"),
+            group: EditorGroup::new(buffer),
+            language: None,
+            line_length_limit: None,
+            selection_expansion: SelectionExpansion::new(),
+            selection_provider: Box::new(BracketSelectionProvider),
+            options: BufferOptions::default(),
+            peek: None,
+            rename: None,
+            find: None,
+            theme: Theme::default(),
+            highlighter: None,
+            custom_highlighter: false,
+            highlighted_language: None,
+            highlight_cache: HighlightCache::new(),
+            bracket_pairs: BracketPairCache::new(),
+            decorations: Vec::new(),
+            document_links: None,
+            title: "Editor".to_string(),
+            height: 0,
+            compact: false,
+        }
+    }
+
+    /// Get a copy of the editor contents.
+    pub fn text(&self) -> String {
+        self.buffer().borrow().text().to_string()
+    }
+
+    /// Get the buffer shown in the editor.
+    pub fn buffer(&self) -> &SharedBuffer {
+        &self.group.active().buffer
+    }
+
+    /// Show a different buffer in the active tab.
+    ///
+    /// The selection and scroll position move to the start and any peek or
+    /// rename input is closed. If another tab already shows the buffer, it
+    /// is activated and the active tab closed instead.
+    pub fn set_buffer(&mut self, buffer: SharedBuffer) {
+        self.group.replace_active(buffer);
+        self.tab_changed();
+    }
+
+    /// Show a buffer in a new tab after the active one, or activate the tab
+    /// already showing it.
+    pub fn open_buffer(&mut self, buffer: SharedBuffer) {
+        self.group.open(buffer);
+        self.tab_changed();
+    }
+
+    /// Get the open tabs and which one is active.
+    pub fn group(&self) -> &EditorGroup {
+        &self.group
+    }
+
+    /// Activate the tab at `index` (clamped).
+    pub fn activate_tab(&mut self, index: usize) {
+        self.group.activate(index);
+        self.tab_changed();
+    }
+
+    /// Activate the next tab, wrapping around.
+    pub fn next_tab(&mut self) {
+        self.group.next();
+        self.tab_changed();
+    }
+
+    /// Activate the previous tab, wrapping around.
+    pub fn prev_tab(&mut self) {
+        self.group.prev();
+        self.tab_changed();
+    }
+
+    /// Close the active tab, unless it is the only one.
+    ///
+    /// Unsaved changes stay in the buffer, which the buffer manager keeps
+    /// open. Returns the closed tab.
+    pub fn close_tab(&mut self) -> Option<EditorTab> {
+        let closed = self.group.close_active();
+        if closed.is_some() {
+            self.tab_changed();
+        }
+        closed
+    }
+
+    /// Close every tab except the active one and the pinned ones. Returns
+    /// the closed tabs.
+    pub fn close_other_tabs(&mut self) -> Vec<EditorTab> {
+        self.group.close_others()
+    }
+
+    /// Pin or unpin the active tab. Returns whether it is pinned now.
+    pub fn toggle_pinned_tab(&mut self) -> bool {
+        self.group.toggle_pinned()
+    }
+
+    /// Move the active tab `offset` places right, or left if negative,
+    /// among the pinned or the unpinned tabs. Returns whether it moved.
+    pub fn move_tab(&mut self, offset: isize) -> bool {
+        self.group.move_active(offset)
+    }
+
+    /// Show `tab`, e.g. one moved from another editor, keeping its
+    /// selection and scroll position, or activate the tab already showing
+    /// its document.
+    pub fn insert_tab(&mut self, tab: EditorTab) {
+        self.group.insert(tab);
+        self.tab_changed();
+    }
+
+    /// Get the decorated ranges.
+    pub fn decorations(&self) -> &[Decoration] {
+        &self.decorations
+    }
+
+    /// Replace the decorated ranges of the shown document.
+    ///
+    /// Decorations are cleared when another tab is shown.
+    pub fn set_decorations(&mut self, decorations: Vec<Decoration>) {
+        self.decorations = decorations;
+    }
+
+    /// Get the links of the shown document: the ones set with
+    /// [`EditorWindow::set_document_links`], or else the URLs and relative
+    /// paths [`document_link::detect_links`] finds in the text.
+    pub fn document_links(&self) -> Vec<DocumentLink> {
+        match &self.document_links {
+            Some(links) => links.clone(),
+            None => document_link::detect_document_links(&self.text()),
+        }
+    }
+
+    /// Use `links`, e.g. from a language server, as the links of the shown
+    /// document, or detect them in the text again with `None`.
+    ///
+    /// The links are dropped when another tab is shown.
+    pub fn set_document_links(&mut self, links: Option<Vec<DocumentLink>>) {
+        self.document_links = links;
+    }
+
+    /// Get the link under the cursor, if any.
+    pub fn link_under_cursor(&self) -> Option<DocumentLink> {
+        document_link::link_at(&self.document_links(), self.selection_head()).cloned()
+    }
+
+    /// Mark `changes` in the gutter of every tab showing the file at
+    /// `path`, or hide the gutter of those tabs with `None`.
+    pub fn set_line_changes(&mut self, path: &Path, changes: Option<Vec<LineChange>>) {
+        for tab in self.group.tabs_mut() {
+            if tab.buffer.borrow().path() == Some(path) {
+                tab.line_changes = changes.clone();
+            }
+        }
+    }
+
+    /// Get the lines marked in the gutter of the shown document, if it has
+    /// a gutter.
+    pub fn line_changes(&self) -> Option<&[LineChange]> {
+        self.group.active().line_changes.as_deref()
+    }
+
+    /// Underline the problems among `problems` in the tabs showing their
+    /// files, replacing the ones underlined before.
+    pub fn set_diagnostics(&mut self, problems: &[Problem]) {
+        for tab in self.group.tabs_mut() {
+            let buffer = tab.buffer.borrow();
+            let path = buffer.path();
+            let diagnostics = problems
+                .iter()
+                .filter(|problem| path == Some(problem.path.as_path()))
+                .cloned()
+                .collect();
+            drop(buffer);
+            tab.diagnostics = diagnostics;
+        }
+    }
+
+    /// Get the problems underlined in the shown document.
+    pub fn diagnostics(&self) -> &[Problem] {
+        &self.group.active().diagnostics
+    }
+
+    /// Move the cursor to the next problem in the shown document after the
+    /// cursor, or with `forward` false the previous one before it, wrapping
+    /// around at the ends. Returns the problem, or `None` if there are no
+    /// problems in the document.
+    pub fn go_to_diagnostic(&mut self, forward: bool) -> Option<Problem> {
+        let cursor = self.cursor_position();
+        let mut positions: Vec<&Problem> = self.diagnostics().iter().collect();
+        positions.sort_by_key(|problem| (problem.line, problem.column));
+        let problem = if forward {
+            positions
+                .iter()
+                .find(|problem| (problem.line, problem.column) > cursor)
+                .or(positions.first())
+        } else {
+            positions
+                .iter()
+                .rev()
+                .find(|problem| (problem.line, problem.column) < cursor)
+                .or(positions.last())
+        };
+        let problem = (*problem?).clone();
+        self.set_cursor_position(problem.line, problem.column);
+        Some(problem)
+    }
+
+    /// Set the title drawn on the border (`Editor` by default).
+    pub fn set_title(&mut self, title: impl Into<String>) {
+        self.title = title.into();
+    }
+
+    /// Check whether the editor is drawn compact.
+    pub fn is_compact(&self) -> bool {
+        self.compact
+    }
+
+    /// Create another view of the active document: an editor showing the
+    /// same buffer with this editor's selection, scroll position, options,
+    /// title and compact mode. The views share edits and undo history but move their
+    /// cursors and scroll independently.
+    pub fn split_view(&self) -> Self {
+        let mut view = Self::with_buffer(Rc::clone(self.buffer()));
+        let tab = self.group.active();
+        let view_tab = view.group.active_mut();
+        view_tab.selection = tab.selection.clone();
+        view_tab.head_at_end = tab.head_at_end;
+        view_tab.viewport = tab.viewport;
+        view_tab.diagnostics = tab.diagnostics.clone();
+        view.language = self.language.clone();
+        view.line_length_limit = self.line_length_limit;
+        view.options = self.options.clone();
+        view.theme = self.theme.clone();
+        view.title = self.title.clone();
+        view.height = self.height;
+        view.compact = self.compact;
+        view
+    }
+
+    /// Move the selection of every tab along with the changes other
+    /// editors made to its document. If the shown document changed, the
+    /// scroll position is kept within it and find matches are updated.
+    pub fn follow_changes(&mut self) {
+        if self.group.follow_changes() {
+            self.scroll_by(0);
+            self.update_find();
+        }
+    }
+
+    /// Reset the state that belongs to the previously shown document.
+    fn tab_changed(&mut self) {
+        self.decorations.clear();
+        self.document_links = None;
+        self.selection_expansion.reset();
+        self.peek = None;
+        self.rename = None;
+        self.find = None;
+    }
+
+    /// Replace the editor contents.
+    ///
+    /// The selection is clamped to the new text and the scroll position is
+    /// kept within the new line count. Marks the buffer dirty if the text
+    /// changed.
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        self.group.active().buffer.borrow_mut().set_text(text);
+        self.text_changed(self.group.active().selection.clone());
+    }
+
+    /// Apply `edits` to the shown document as one step of its undo history.
+    ///
+    /// Edits refer to offsets in the current text and must not overlap.
+    /// Returns the ranges the replacement texts occupy afterwards; the
+    /// selection moves to the start of the first one.
+    pub fn edit(&mut self, edits: &[TextEdit]) -> Result<Vec<Range<usize>>, EditError> {
+        let ranges = self.buffer().borrow_mut().edit(edits)?;
+        let start = ranges.first().map_or(0, |range| range.start);
+        self.text_changed(start..start);
+        Ok(ranges)
+    }
+
+    /// Revert the most recent change to the shown document and select the
+    /// restored text.
+    ///
+    /// Returns whether there was a change to undo.
+    pub fn undo(&mut self) -> bool {
+        let restored = self.buffer().borrow_mut().undo();
+        restored.is_some_and(|range| {
+            self.text_changed(range);
+            self.reveal_selection();
+            true
+        })
+    }
+
+    /// Make the most recently undone change to the shown document again
+    /// and select its text.
+    ///
+    /// Returns whether there was a change to redo.
+    pub fn redo(&mut self) -> bool {
+        let restored = self.buffer().borrow_mut().redo();
+        restored.is_some_and(|range| {
+            self.text_changed(range);
+            self.reveal_selection();
+            true
+        })
+    }
+
+    /// Bring the shown document to `state` of its undo history, on
+    /// whatever branch, and select the text the last change restored.
+    ///
+    /// Returns whether the document changed.
+    pub fn go_to_undo_state(&mut self, state: usize) -> bool {
+        let restored = self.buffer().borrow_mut().go_to_state(state);
+        restored.is_some_and(|range| {
+            self.text_changed(range);
+            self.reveal_selection();
+            true
+        })
+    }
+
+    /// Clamp the state that depends on the text after it changed, setting
+    /// the selection to `selection`.
+    fn text_changed(&mut self, selection: Range<usize>) {
+        self.group.active_mut().skip_changes();
+        self.set_selection(selection);
+        self.scroll_by(0);
+        self.update_find();
+    }
+
+    /// Load the file at `path` into a new, unshared buffer shown in the
+    /// active tab.
+    ///
+    /// The buffer starts clean and the selection moves to the start. Use a
+    /// [`BufferManager`](crate::buffer_manager::BufferManager) and
+    /// [`set_buffer`](EditorWindow::set_buffer) to share the file with
+    /// other editors.
+    pub fn open_file(&mut self, path: impl Into<PathBuf>) -> io::Result<()> {
+        let buffer = TextBuffer::from_file(path)?;
+        self.set_buffer(Rc::new(RefCell::new(buffer)));
+        Ok(())
+    }
+
+    /// Check whether the buffer has unsaved changes.
+    pub fn is_dirty(&self) -> bool {
+        self.buffer().borrow().is_dirty()
+    }
+
+    /// Write the buffer to its path and mark it clean.
+    ///
+    /// Fails with [`io::ErrorKind::NotFound`] if the editor has no path.
+    pub fn save(&mut self) -> io::Result<()> {
+        self.buffer().borrow_mut().save()
+    }
+
+    /// Get the path of the file shown in the editor, if any.
+    pub fn path(&self) -> Option<PathBuf> {
+        self.buffer().borrow().path().map(Path::to_path_buf)
+    }
+
+    /// Set the path of the file shown in the editor.
+    pub fn set_path(&mut self, path: Option<PathBuf>) {
+        self.buffer().borrow_mut().set_path(path);
+    }
+
+    /// Get the language identifier of the buffer, if known.
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /// Set the language identifier of the buffer.
+    pub fn set_language(&mut self, language: Option<String>) {
+        self.language = language;
+    }
+
+    /// Get the language the buffer is edited as: the one set, or the one
+    /// its file extension implies.
+    pub fn effective_language(&self) -> Option<String> {
+        self.language.clone().or_else(|| {
+            self.path()
+                .and_then(|path| LexicalHighlighter::language_for_path(&path))
+                .map(str::to_string)
+        })
+    }
+
+    /// Replace the syntax highlighter; `None` turns highlighting off.
+    ///
+    /// By default the editor uses the built-in highlighter for its language,
+    /// or for its file extension if no language is set.
+    pub fn set_highlighter(&mut self, highlighter: Option<Box<dyn Highlighter>>) {
+        self.highlighter = highlighter;
+        self.custom_highlighter = true;
+        self.highlight_cache.clear();
+    }
+
+    /// Get the highlighted spans as of the last render.
+    pub fn highlight_cache(&self) -> &HighlightCache {
+        &self.highlight_cache
+    }
+
+    /// Export the selection, or the whole document if nothing is selected,
+    /// highlighted as the editor draws it, in `format`.
+    ///
+    /// An HTML page is titled with the file name.
+    pub fn export(&mut self, format: ExportFormat) -> String {
+        self.update_highlighter();
+        let selection = self.selection();
+        let buffer = self.buffer().borrow();
+        let text = buffer.text();
+        let range = if selection.is_empty() {
+            0..text.len()
+        } else {
+            selection
+        };
+        let title = buffer.path().and_then(Path::file_name).map_or_else(
+            || "Untitled".to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+        export::export(
+            text,
+            range,
+            self.highlighter.as_deref(),
+            &self.theme,
+            format,
+            &title,
+        )
+    }
+
+    /// Pick the built-in highlighter again if the language or file
+    /// extension changed since the last render.
+    fn update_highlighter(&mut self) {
+        if self.custom_highlighter {
+            return;
+        }
+        let language = self.effective_language();
+        if language != self.highlighted_language {
+            self.highlighter = language
+                .as_deref()
+                .and_then(LexicalHighlighter::for_language)
+                .map(|highlighter| Box::new(highlighter) as Box<dyn Highlighter>);
+            self.highlight_cache.clear();
+            self.bracket_pairs.clear();
+            self.highlighted_language = language;
+        }
+    }
+
+    /// Get the active soft line-length limit.
+    pub fn line_length_limit(&self) -> Option<LineLengthLimit> {
+        self.line_length_limit
+    }
+
+    /// Set the soft line-length limit directly.
+    pub fn set_line_length_limit(&mut self, limit: Option<LineLengthLimit>) {
+        self.line_length_limit = limit;
+    }
+
+    /// Resolve the soft line-length limit for this buffer's language.
+    pub fn apply_line_length_config(&mut self, config: &LineLengthConfig) {
+        self.line_length_limit = config.limit_for(self.language());
+    }
+
+    /// Warnings for lines that exceed the soft limit.
+    ///
+    /// Empty when no limit is set or the limit has warnings disabled.
+    pub fn line_length_warnings(&self) -> Vec<LineLengthWarning> {
+        self.line_length_limit
+            .map(|limit| line_length::warnings_for(self.buffer().borrow().text(), limit))
+            .unwrap_or_default()
+    }
+
+    /// Get the resolved buffer-local options.
+    pub fn options(&self) -> &BufferOptions {
+        &self.options
+    }
+
+    /// Set the buffer-local options directly.
+    ///
+    /// A `line_length` option replaces the language-derived soft limit.
+    pub fn set_options(&mut self, options: BufferOptions) {
+        if let Some(max_columns) = options.line_length {
+            self.line_length_limit = Some(LineLengthLimit::new(max_columns));
+        }
+        self.options = options;
+    }
+
+    /// Resolve options from the workspace layer, an optional `.paradiddle`
+    /// layer and this buffer's modeline, then apply them.
+    ///
+    /// Leaves the current options untouched if the modeline is invalid.
+    pub fn resolve_options(
+        &mut self,
+        workspace: &OptionOverrides,
+        project_file: Option<&OptionOverrides>,
+    ) -> Result<(), OptionsError> {
+        let modeline = OptionOverrides::parse_modeline(self.buffer().borrow().text())?;
+        self.set_options(BufferOptions::resolve(
+            workspace,
+            project_file,
+            modeline.as_ref(),
+        ));
+        Ok(())
+    }
+
+    /// Get the index of the first visible line.
+    pub fn scroll_offset(&self) -> u16 {
+        u16::try_from(self.viewport().top_line).unwrap_or(u16::MAX)
+    }
+
+    /// Get the lines of the active tab that are shown, as of the last
+    /// render.
+    pub fn viewport(&self) -> Viewport {
+        Viewport {
+            height: self.height,
+            ..self.group.active().viewport
+        }
+    }
+
+    /// Scroll so `line` is the first visible line, clamped to the buffer.
+    pub fn set_scroll_offset(&mut self, line: usize) {
+        self.viewport_mut().top_line = 0;
+        self.scroll_lines(isize::try_from(line).unwrap_or(isize::MAX));
+    }
+
+    /// Scroll by `lines` (up if negative), leaving the cursor where it is.
+    pub fn scroll_lines(&mut self, lines: isize) {
+        let last_line = self.last_line();
+        self.viewport_mut().scroll_by(lines, last_line);
+    }
+
+    /// Scroll a page down (up if `down` is false) and move the cursor by
+    /// the same number of lines, keeping its column where the line allows.
+    pub fn scroll_page(&mut self, down: bool) {
+        let page = self.viewport().page();
+        let (line, column) = self.cursor_position();
+        let line = if down {
+            line.saturating_add(page)
+        } else {
+            line.saturating_sub(page).max(1)
+        };
+        let lines = isize::try_from(page).unwrap_or(isize::MAX);
+        self.scroll_lines(if down { lines } else { -lines });
+        self.set_cursor_position(line, column);
+    }
+
+    /// The viewport of the active tab, sized to the last rendered height.
+    fn viewport_mut(&mut self) -> &mut Viewport {
+        let height = self.height;
+        let viewport = &mut self.group.active_mut().viewport;
+        viewport.height = height;
+        viewport
+    }
+
+    /// Index of the last line of the buffer.
+    fn last_line(&self) -> usize {
+        self.buffer().borrow().text().matches('\n').count()
+    }
+
+    /// Get the current selection as a byte range.
+    ///
+    /// The range is clamped to the buffer, which another editor showing the
+    /// same buffer may have shortened.
+    pub fn selection(&self) -> Range<usize> {
+        let tab = self.group.active();
+        let buffer = tab.buffer.borrow();
+        let start = clamp_offset(buffer.text(), tab.selection.start);
+        let end = clamp_offset(buffer.text(), tab.selection.end).max(start);
+        start..end
+    }
+
+    /// Get a copy of the selected text.
+    pub fn selected_text(&self) -> String {
+        self.buffer().borrow().text()[self.selection()].to_string()
+    }
+
+    /// Get the byte offset of the selection's anchor, the end that stays
+    /// put when the selection is extended.
+    pub fn selection_anchor(&self) -> usize {
+        let selection = self.selection();
+        if self.group.active().head_at_end {
+            selection.start
+        } else {
+            selection.end
+        }
+    }
+
+    /// Get the byte offset of the selection's head, where the cursor is:
+    /// the selection start unless it was extended forwards.
+    pub fn selection_head(&self) -> usize {
+        let selection = self.selection();
+        if self.group.active().head_at_end {
+            selection.end
+        } else {
+            selection.start
+        }
+    }
+
+    /// Get the one-based `(line, column)` of the cursor (the selection head).
+    ///
+    /// Columns count characters, not bytes.
+    pub fn cursor_position(&self) -> (usize, usize) {
+        let (line, column) = self.line_and_column(self.selection_head());
+        (line + 1, column + 1)
+    }
+
+    /// Move the cursor to the one-based `line` and character `column`,
+    /// clamped to the line and the buffer, and scroll it into view.
+    pub fn set_cursor_position(&mut self, line: usize, column: usize) {
+        let offset = self.offset_at(line, column);
+        self.set_selection(offset..offset);
+        self.reveal_selection();
+    }
+
+    /// Move the cursor by `lines` (up if negative) and then by `columns`
+    /// (left if negative), staying on its line, and scroll it into view.
+    ///
+    /// Collapses the selection to its head first.
+    pub fn move_cursor(&mut self, lines: isize, columns: isize) {
+        let offset = self.moved_offset(self.selection_head(), lines, columns);
+        self.set_selection(offset..offset);
+        self.reveal_selection();
+    }
+
+    /// The byte offset reached from `offset` by moving `lines` (up if
+    /// negative) and then `columns` (left if negative), staying on the
+    /// line and within the buffer.
+    ///
+    /// Moving between lines keeps the screen column, so the cursor stays
+    /// above or below where it was across wide characters, and moving
+    /// along the line steps over whole grapheme clusters.
+    pub fn moved_offset(&self, offset: usize, lines: isize, columns: isize) -> usize {
+        let (line, _) = self.line_and_column(offset);
+        let target = line.saturating_add_signed(lines).min(self.last_line());
+        let buffer = self.buffer().borrow();
+        let text = buffer.text();
+        let offset = clamp_offset(text, offset);
+        let mut range = line_range(text, line);
+        let mut index = offset.min(range.end) - range.start;
+        if target != line {
+            let column = grapheme::display_column(&text[range], index);
+            range = line_range(text, target);
+            index = grapheme::index_at_display_column(&text[range.clone()], column);
+        }
+        let line_text = &text[range.clone()];
+        for _ in 0..columns.unsigned_abs() {
+            index = if columns < 0 {
+                grapheme::previous_boundary(line_text, index)
+            } else {
+                grapheme::next_boundary(line_text, index)
+            };
+        }
+        range.start + index
+    }
+
+    /// Select from `anchor` to `head` and scroll `head` into view.
+    pub fn select(&mut self, anchor: usize, head: usize) {
+        self.set_selection(anchor.min(head)..anchor.max(head));
+        self.group.active_mut().head_at_end = head > anchor;
+        let (line, _) = self.line_and_column(head);
+        self.viewport_mut().reveal(line);
+    }
+
+    /// Move the selection's head by `lines` (up if negative) and then by
+    /// `columns` (left if negative), as Shift with an arrow key does,
+    /// keeping its anchor.
+    pub fn extend_selection(&mut self, lines: isize, columns: isize) {
+        let head = self.moved_offset(self.selection_head(), lines, columns);
+        self.select(self.selection_anchor(), head);
+    }
+
+    /// Select from `anchor` to `head` inclusive, as Visual mode does: the
+    /// character at the later of the two is selected too. Scrolls `head`
+    /// into view.
+    pub fn select_inclusive(&mut self, anchor: usize, head: usize) {
+        let start = anchor.min(head);
+        let end = {
+            let buffer = self.buffer().borrow();
+            let text = buffer.text();
+            grapheme::next_boundary(text, clamp_offset(text, anchor.max(head)))
+        };
+        self.set_selection(start..end);
+        let (line, _) = self.line_and_column(head);
+        self.viewport_mut().reveal(line);
+    }
+
+    /// Replace the selection with `text`, leaving the cursor after it.
+    pub fn insert_text(&mut self, text: &str) {
+        let edit = TextEdit::new(self.selection(), text);
+        let inserted = self
+            .edit(&[edit])
+            .expect("the selection is a valid range of the text");
+        let end = inserted[0].end;
+        self.set_selection(end..end);
+        self.reveal_selection();
+    }
+
+    /// Replace the abbreviation right before the cursor with its expansion,
+    /// if there is one and nothing is selected.
+    ///
+    /// The cursor moves after the expansion, or to where it marks. Returns
+    /// the expansion made.
+    pub fn expand_abbreviation(&mut self, abbreviations: &Abbreviations) -> Option<Expansion> {
+        let cursor = self.selection();
+        if !cursor.is_empty() || abbreviations.is_empty() {
+            return None;
+        }
+        let (length, expansion) = {
+            let buffer = self.buffer().borrow();
+            let before = &buffer.text()[..cursor.start];
+            let line = &before[before.rfind('\n').map_or(0, |newline| newline + 1)..];
+            abbreviations.expand(line, self.effective_language().as_deref())?
+        };
+        let start = cursor.start - length;
+        let edit = TextEdit::new(start..cursor.start, expansion.text.as_str());
+        let inserted = self
+            .edit(&[edit])
+            .expect("the abbreviation is a valid range of the text");
+        let end = expansion
+            .cursor
+            .map_or(inserted[0].end, |offset| start + offset);
+        self.set_selection(end..end);
+        self.reveal_selection();
+        Some(expansion)
+    }
+
+    /// Delete the selection, or the character (grapheme cluster) before
+    /// the cursor.
+    pub fn delete_backward(&mut self) {
+        let selection = self.selection();
+        let range = if selection.is_empty() {
+            grapheme::previous_boundary(&self.text(), selection.start)..selection.end
+        } else {
+            selection
+        };
+        self.delete(range);
+    }
+
+    /// Delete the selection, or the character (grapheme cluster) under the
+    /// cursor unless it ends the line.
+    pub fn delete_forward(&mut self) {
+        let selection = self.selection();
+        let range = if selection.is_empty() {
+            let text = self.text();
+            let next = grapheme::next_boundary(&text, selection.start);
+            if text[selection.start..next].contains('\n') {
+                selection
+            } else {
+                selection.start..next
+            }
+        } else {
+            selection
+        };
+        self.delete(range);
+    }
+
+    /// Delete `range`, if it is not empty, as one step of the undo history.
+    fn delete(&mut self, range: Range<usize>) {
+        if range.is_empty() {
+            return;
         }
+        self.edit(&[TextEdit::new(range, "")])
+            .expect("the range is a valid range of the text");
+        self.reveal_selection();
+    }
+
+    /// Byte offset of the one-based `line` and character `column`, clamped
+    /// to the line and the buffer.
+    fn offset_at(&self, line: usize, column: usize) -> usize {
+        let buffer = self.buffer().borrow();
+        let text = buffer.text();
+        let line_start: usize = text
+            .split_inclusive('\n')
+            .take(line.saturating_sub(1))
+            .map(str::len)
+            .sum();
+        let line_text = text[line_start..].split('\n').next().unwrap_or("");
+        line_start
+            + line_text
+                .char_indices()
+                .nth(column.saturating_sub(1))
+                .map_or(line_text.len(), |(index, _)| index)
+    }
+
+    /// Zero-based line and character column of a byte offset.
+    fn line_and_column(&self, offset: usize) -> (usize, usize) {
+        let buffer = self.buffer().borrow();
+        let text = buffer.text();
+        let before = &text[..clamp_offset(text, offset)];
+        let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+        let line = before.matches('\n').count();
+        (line, before[line_start..].chars().count())
+    }
+
+    /// Number of cells the text of its line before `offset` takes on
+    /// screen.
+    fn screen_column(&self, offset: usize) -> usize {
+        let buffer = self.buffer().borrow();
+        let text = buffer.text();
+        let offset = clamp_offset(text, offset);
+        let line_start = text[..offset].rfind('\n').map_or(0, |newline| newline + 1);
+        grapheme::display_column(&text[line_start..], offset - line_start)
+    }
+
+    /// Row of `line` relative to the top of the viewport.
+    fn visible_row(&self, line: usize) -> u16 {
+        let row = line.saturating_sub(self.group.active().viewport.top_line);
+        u16::try_from(row).unwrap_or(u16::MAX)
+    }
+
+    /// Set the selection, clamped to the buffer and snapped to char boundaries.
+    ///
+    /// The cursor goes to its start. Resets the selection expansion
+    /// history.
+    pub fn set_selection(&mut self, selection: Range<usize>) {
+        let tab = self.group.active_mut();
+        let text = tab.buffer.borrow();
+        let start = clamp_offset(text.text(), selection.start);
+        let end = clamp_offset(text.text(), selection.end).max(start);
+        drop(text);
+        tab.selection = start..end;
+        tab.head_at_end = false;
+        self.selection_expansion.reset();
+    }
+
+    /// Replace the provider of the ranges a selection expands to.
+    pub fn set_selection_provider(&mut self, provider: Box<dyn SelectionRangeProvider>) {
+        self.selection_provider = provider;
+        self.selection_expansion.reset();
+    }
+
+    /// Grow the selection to the enclosing range of the selection provider.
+    pub fn expand_selection(&mut self) {
+        let selection = self.selection();
+        let buffer = Rc::clone(self.buffer());
+        let buffer = buffer.borrow();
+        self.update_highlighter();
+        if let Some(highlighter) = &self.highlighter {
+            self.highlight_cache
+                .highlight(highlighter.as_ref(), buffer.text());
+        }
+        let tokens = self.highlighter.is_some().then_some(&self.highlight_cache);
+        let expanded = self.selection_expansion.expand(
+            self.selection_provider.as_ref(),
+            buffer.text(),
+            tokens,
+            selection,
+        );
+        drop(buffer);
+        self.group.active_mut().selection = expanded;
+    }
+
+    /// Undo the most recent expansion, if any.
+    pub fn shrink_selection(&mut self) {
+        if let Some(previous) = self.selection_expansion.shrink() {
+            self.group.active_mut().selection = previous;
+        }
+    }
+
+    /// Show a peek window below the selection.
+    pub fn open_peek(&mut self, peek: PeekWindow) {
+        self.peek = Some(peek);
+    }
+
+    /// Close the peek window, if open.
+    pub fn close_peek(&mut self) {
+        self.peek = None;
+    }
+
+    /// Get the peek window, if open.
+    pub fn peek(&self) -> Option<&PeekWindow> {
+        self.peek.as_ref()
+    }
+
+    /// Get the peek window mutably, if open.
+    pub fn peek_mut(&mut self) -> Option<&mut PeekWindow> {
+        self.peek.as_mut()
+    }
+
+    /// Show an inline rename input at its symbol.
+    pub fn open_rename(&mut self, input: RenameInputWindow) {
+        self.rename = Some(input);
+    }
+
+    /// Close the rename input, if open.
+    pub fn close_rename(&mut self) {
+        self.rename = None;
+    }
+
+    /// Get the rename input, if open.
+    pub fn rename_input(&self) -> Option<&RenameInputWindow> {
+        self.rename.as_ref()
+    }
+
+    /// Get the rename input mutably, if open.
+    pub fn rename_input_mut(&mut self) -> Option<&mut RenameInputWindow> {
+        self.rename.as_mut()
+    }
+
+    /// Open the find input, or start editing its query again if it is open.
+    ///
+    /// A new input starts with the selected text if it is on one line.
+    pub fn open_find(&mut self) {
+        match self.find.as_mut() {
+            Some(find) => find.edit(),
+            None => {
+                let selected = self.selected_text();
+                let query = if selected.contains('\n') {
+                    String::new()
+                } else {
+                    selected
+                };
+                let mut find = FindWindow::new(query);
+                find.set_theme(&self.theme);
+                self.find = Some(find);
+            }
+        }
+        self.update_find();
+    }
+
+    /// Close the find input, if open, and remove its highlights.
+    pub fn close_find(&mut self) {
+        if self.find.take().is_some() {
+            self.decorations.clear();
+        }
+    }
+
+    /// Get the find input, if open.
+    pub fn find(&self) -> Option<&FindWindow> {
+        self.find.as_ref()
+    }
+
+    /// Handle a key while the find input is editing.
+    ///
+    /// The selection follows the query as it is typed, moving to the first
+    /// match at or after it.
+    pub fn handle_find_key(&mut self, key: AppKey) -> FindOutcome {
+        let Some(find) = self.find.as_mut() else {
+            return FindOutcome::Dismissed;
+        };
+        let outcome = find.handle_key(key);
+        match outcome {
+            FindOutcome::Pending => {}
+            FindOutcome::QueryChanged => {
+                self.update_find();
+                let start = self.selection().start;
+                if let Some(found) = self.find.as_ref().and_then(|find| find.next_match(start)) {
+                    self.select_match(found);
+                }
+            }
+            FindOutcome::Next => {
+                self.find_next();
+            }
+            FindOutcome::ReplaceOne => {
+                self.replace_one();
+            }
+            FindOutcome::Dismissed => self.close_find(),
+        }
+        outcome
+    }
+
+    /// Select the next match of the find input after the selection start,
+    /// wrapping around at the end of the text.
+    ///
+    /// Returns whether there was a match.
+    pub fn find_next(&mut self) -> bool {
+        let selection = self.selection();
+        let offset = selection.start + usize::from(!selection.is_empty());
+        self.find
+            .as_ref()
+            .and_then(|find| find.next_match(offset))
+            .map(|found| self.select_match(found))
+            .is_some()
+    }
+
+    /// Select the previous match of the find input before the selection
+    /// start, wrapping around at the start of the text.
+    ///
+    /// Returns whether there was a match.
+    pub fn find_previous(&mut self) -> bool {
+        let start = self.selection().start;
+        self.find
+            .as_ref()
+            .and_then(|find| find.previous_match(start))
+            .map(|found| self.select_match(found))
+            .is_some()
+    }
+
+    /// Replace the selected match of the find input and select the next
+    /// one.
+    ///
+    /// If the selection is not a match, the next match is selected instead,
+    /// so it can be seen before it is replaced. Returns whether a match was
+    /// replaced.
+    pub fn replace_one(&mut self) -> bool {
+        let Some(find) = self.find.as_ref() else {
+            return false;
+        };
+        if find.current().is_none() {
+            self.find_next();
+            return false;
+        }
+        let edit = TextEdit::new(self.selection(), find.replacement());
+        let replaced = self
+            .edit(&[edit])
+            .expect("the selection is a valid range of the text");
+        let end = replaced[0].end;
+        if let Some(found) = self.find.as_ref().and_then(|find| find.next_match(end)) {
+            self.select_match(found);
+        }
+        true
+    }
+
+    /// Replace every match of the find input as one undoable step.
+    ///
+    /// Returns the number of matches replaced.
+    pub fn replace_all(&mut self) -> usize {
+        let Some(find) = self.find.as_ref() else {
+            return 0;
+        };
+        let edits: Vec<TextEdit> = find
+            .matches()
+            .iter()
+            .map(|found| TextEdit::new(found.clone(), find.replacement()))
+            .collect();
+        if edits.is_empty() {
+            return 0;
+        }
+        self.edit(&edits)
+            .expect("find matches are valid, disjoint ranges of the text");
+        self.reveal_selection();
+        edits.len()
+    }
+
+    /// Find the query of the find input in the text again and highlight
+    /// the matches.
+    fn update_find(&mut self) {
+        let selection = self.selection();
+        let Some(find) = self.find.as_mut() else {
+            return;
+        };
+        find.update(self.group.active().buffer.borrow().text(), &selection);
+        self.decorations = find
+            .matches()
+            .iter()
+            .map(|found| Decoration {
+                range: found.clone(),
+                kind: DecorationKind::Match,
+            })
+            .collect();
+    }
+
+    /// Select a match of the find input and scroll it into view.
+    fn select_match(&mut self, found: Range<usize>) {
+        self.set_selection(found.clone());
+        if let Some(find) = self.find.as_mut() {
+            find.select(&found);
+        }
+        self.reveal_selection();
+    }
+
+    /// Scroll so the line with the selection start is visible, as of the
+    /// last rendered height.
+    fn reveal_selection(&mut self) {
+        let (line, _) = self.line_and_column(self.selection().start);
+        self.viewport_mut().reveal(line);
+    }
+
+    /// The gutter rows of the `visible` lines: a bar beside added and
+    /// modified lines and a low line under lines followed by a removal.
+    /// `None` if the shown document has no gutter or the editor is compact.
+    fn gutter(&self, visible: Range<usize>) -> Option<Vec<Line<'static>>> {
+        if self.compact {
+            return None;
+        }
+        let changes = self.group.active().line_changes.as_ref()?;
+        let rows = visible
+            .map(|index| {
+                let change = changes.iter().find(|change| change.line == index + 1);
+                match change.map(|change| change.kind) {
+                    Some(LineChangeKind::Added) => Line::styled("▎", self.theme.diff_added),
+                    Some(LineChangeKind::Modified) => Line::styled("▎", self.theme.diff_modified),
+                    Some(LineChangeKind::Removed) => Line::styled("▁", self.theme.diff_removed),
+                    None => Line::raw(" "),
+                }
+            })
+            .collect();
+        Some(rows)
+    }
+
+    /// The character columns to underline on the line at `line_index`, the
+    /// text of which is `line`, with the severity of their problems: from
+    /// each problem's column to the end of the word there, or the one
+    /// character there. A problem past the end of the line underlines its
+    /// last character. Nothing is underlined while the editor is compact.
+    fn squiggles(&self, line_index: usize, line: &str) -> Vec<(Range<usize>, Severity)> {
+        let chars: Vec<char> = line.chars().collect();
+        if chars.is_empty() || self.compact {
+            return Vec::new();
+        }
+        self.diagnostics()
+            .iter()
+            .filter(|problem| problem.line == line_index + 1)
+            .map(|problem| {
+                let start = problem.column.saturating_sub(1).min(chars.len() - 1);
+                let is_word = |c: &char| c.is_alphanumeric() || *c == '_';
+                let word = chars[start..].iter().take_while(|c| is_word(c)).count();
+                (start..start + word.max(1), problem.severity)
+            })
+            .collect()
+    }
+
+    /// Area of the find input: the top right of the editor's inner area.
+    fn find_area(inner: Rect) -> Rect {
+        let width = FIND_WIDTH.min(inner.width);
+        Rect {
+            x: inner.right() - width,
+            y: inner.y,
+            width,
+            height: FIND_HEIGHT.min(inner.height),
+        }
+    }
+
+    /// Area of the rename input within the editor's inner area.
+    ///
+    /// The input starts at the symbol's column on the line below it, or on
+    /// the line above when there is not enough room below.
+    fn rename_area(&self, inner: Rect, input: &RenameInputWindow) -> Rect {
+        let (line, _) = self.line_and_column(input.range().start);
+        let row = inner.y.saturating_add(self.visible_row(line));
+        let height = 3.min(inner.height);
+        let width = input.preferred_width().min(inner.width);
+        let column = self.screen_column(input.range().start);
+        let column = u16::try_from(column).unwrap_or(u16::MAX);
+        let x = inner.x.saturating_add(column).min(inner.right() - width);
+        let y = if row.saturating_add(1 + height) <= inner.bottom() {
+            row + 1
+        } else {
+            row.saturating_sub(height).max(inner.y)
+        };
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Area of the peek window within the editor's inner area.
+    ///
+    /// The peek sits just below the line containing the selection start, or
+    /// at the bottom of the pane when there is not enough room below.
+    fn peek_area(&self, inner: Rect) -> Rect {
+        let height = PEEK_HEIGHT.min(inner.height);
+        let (line, _) = self.line_and_column(self.selection().start);
+        let below = inner
+            .y
+            .saturating_add(self.visible_row(line))
+            .saturating_add(1);
+        let y = below.min(inner.bottom() - height);
+        Rect { y, height, ..inner }
+    }
+
+    /// Index of the tab showing the file at `path`.
+    fn tab_for_path(&self, path: &Path) -> Option<usize> {
+        self.group
+            .tabs()
+            .iter()
+            .position(|tab| tab.buffer.borrow().path() == Some(path))
+    }
+
+    /// The tab strip for a pane `width` cells wide: each tab's title, with
+    /// `◆` before pinned tabs, `●` for unsaved changes and the active tab
+    /// highlighted.
+    ///
+    /// When the tabs do not fit, every title is shortened in the middle to
+    /// an equal share of the strip.
+    fn tab_strip(&self, width: u16) -> Line<'static> {
+        let tabs: Vec<(String, &str)> = self
+            .group
+            .tabs()
+            .iter()
+            .map(|tab| {
+                let dirty = if tab.buffer.borrow().is_dirty() {
+                    " ●"
+                } else {
+                    ""
+                };
+                let title = if tab.pinned {
+                    format!("◆ {}", tab.title())
+                } else {
+                    tab.title()
+                };
+                (title, dirty)
+            })
+            .collect();
+        let needed: usize = tabs
+            .iter()
+            .map(|(title, dirty)| display_width(title) + display_width(dirty) + 2)
+            .sum();
+        let share = (needed > usize::from(width)).then(|| usize::from(width) / tabs.len());
+        let spans: Vec<Span> = tabs
+            .into_iter()
+            .enumerate()
+            .map(|(index, (title, dirty))| {
+                let title = match share {
+                    Some(share) => {
+                        let room = share.saturating_sub(display_width(dirty) + 2);
+                        truncate_middle(&title, room.max(1))
+                    }
+                    None => title,
+                };
+                let style = if index == self.group.active_index() {
+                    self.theme.selection
+                } else {
+                    self.theme.text
+                };
+                Span::styled(format!(" {title}{dirty} "), style)
+            })
+            .collect();
+        Line::from(spans)
+    }
+
+    /// Find the tab drawn at `position` when the editor is drawn in `area`.
+    ///
+    /// There is no tab strip, so no tab is found, while the editor has a
+    /// single tab.
+    pub fn tab_at(&self, area: Rect, position: Position) -> Option<usize> {
+        let inner = Block::default()
+            .borders(pane_borders(self.compact))
+            .inner(area);
+        if self.group.len() < 2 || position.y != inner.y || !inner.contains(position) {
+            return None;
+        }
+        let mut right = inner.x;
+        self.tab_strip(inner.width).spans.iter().position(|span| {
+            right = right.saturating_add(u16::try_from(span.width()).unwrap_or(u16::MAX));
+            position.x < right
+        })
+    }
+
+    /// Build the styled lines for the buffer.
+    ///
+    /// Highlighted tokens are drawn with the theme's token styles, as of the
+    /// last highlighting pass, and brackets with the theme's bracket style
+    /// for their depth if bracket pairs are colored, with links underlined
+    /// in the theme's link style. Characters past the soft line-length limit,
+    /// problems, decorated ranges and the selection are drawn with the
+    /// theme's overflow, diagnostic, decoration and selection styles on top,
+    /// in that order. Decorations with a [`DecorationKind::marker`], and the
+    /// color swatches found in the line if they are shown, insert their
+    /// marker cell before their range instead.
+    /// Grapheme clusters are styled as a whole, so a span never splits one,
+    /// and consecutive characters with the same style are grouped into a
+    /// single span.
+    ///
+    /// Only the lines in `visible` are styled, so the cost does not grow
+    /// with the length of the buffer.
+    fn styled_lines<'a>(&self, text: &'a str, visible: Range<usize>) -> Vec<Line<'a>> {
+        let overflow_style = self.theme.overflow;
+        let max_columns = self.line_length_limit.map(|limit| limit.max_columns);
+        let selection = self.selection();
+
+        let mut lines = Vec::new();
+        let mut line_start = 0;
+        for (line_index, raw_line) in text.split('\n').enumerate() {
+            if line_index < visible.start {
+                line_start += raw_line.len() + 1;
+                continue;
+            }
+            if line_index >= visible.end {
+                break;
+            }
+            let line = raw_line.strip_suffix('\r').unwrap_or(raw_line);
+            let tokens = if self.highlighter.is_some() {
+                self.highlight_cache.line(line_index)
+            } else {
+                &[]
+            };
+            let brackets = if self.options.bracket_pairs {
+                self.bracket_pairs.line(line_index)
+            } else {
+                &[]
+            };
+            let squiggles = self.squiggles(line_index, line);
+            let line_end = line_start + line.len();
+            let links: Vec<Range<usize>> = match &self.document_links {
+                Some(links) => links
+                    .iter()
+                    .filter(|link| link.range.start < line_end && link.range.end > line_start)
+                    .map(|link| link.range.clone())
+                    .collect(),
+                None => document_link::detect_links(line, line_start)
+                    .into_iter()
+                    .map(|link| link.range)
+                    .collect(),
+            };
+            let swatches = if self.options.color_swatches {
+                color_swatch::find_swatches(line, line_start)
+            } else {
+                Vec::new()
+            };
+            let mut markers: Vec<(usize, DecorationKind)> = self
+                .decorations
+                .iter()
+                .chain(&swatches)
+                .filter(|decoration| decoration.kind.marker().is_some())
+                .filter(|decoration| (line_start..=line_end).contains(&decoration.range.start))
+                .map(|decoration| (decoration.range.start - line_start, decoration.kind))
+                .collect();
+            markers.sort_by_key(|(index, _)| *index);
+            let mut marker_index = 0;
+            let mut token_index = 0;
+            let mut bracket_index = 0;
+            let mut spans: Vec<Span> = Vec::new();
+            let mut run_start = 0;
+            let mut run_style = Style::default();
+
+            let mut column = 0;
+            let mut cells = 0;
+            for (index, cluster) in line.grapheme_indices(true) {
+                let width = grapheme::width(cluster);
+                let mut style = Style::default();
+                while tokens
+                    .get(token_index)
+                    .is_some_and(|token| token.range.end <= index)
+                {
+                    token_index += 1;
+                }
+                if let Some(token) = tokens
+                    .get(token_index)
+                    .filter(|token| token.range.start <= index)
+                {
+                    style = self.theme.token_style(token.kind);
+                }
+                while brackets
+                    .get(bracket_index)
+                    .is_some_and(|bracket| bracket.index < index)
+                {
+                    bracket_index += 1;
+                }
+                if let Some(bracket) = brackets
+                    .get(bracket_index)
+                    .filter(|bracket| bracket.index == index)
+                {
+                    style = style.patch(self.theme.bracket_style(bracket.depth));
+                }
+                if links
+                    .iter()
+                    .any(|link| link.contains(&(line_start + index)))
+                {
+                    style = style.patch(self.theme.link);
+                }
+                if max_columns.is_some_and(|max| cells + width > max) {
+                    style = style.patch(overflow_style);
+                }
+                for (columns, severity) in &squiggles {
+                    if columns.contains(&column) {
+                        style = style.patch(self.theme.diagnostic_style(*severity));
+                    }
+                }
+                for decoration in &self.decorations {
+                    if decoration.kind.marker().is_none()
+                        && decoration.range.contains(&(line_start + index))
+                    {
+                        style = style.patch(self.theme.decoration_style(decoration.kind));
+                    }
+                }
+                if selection.contains(&(line_start + index)) {
+                    style = style.patch(self.theme.selection);
+                }
+                let marked = markers
+                    .get(marker_index)
+                    .is_some_and(|(marker, _)| *marker <= index);
+                if style != run_style || marked {
+                    if index > run_start {
+                        spans.push(Span::styled(&line[run_start..index], run_style));
+                    }
+                    run_start = index;
+                    run_style = style;
+                }
+                while let Some((_, kind)) = markers
+                    .get(marker_index)
+                    .filter(|(marker, _)| *marker <= index)
+                {
+                    spans.push(self.marker_span(*kind));
+                    marker_index += 1;
+                }
+                column += cluster.chars().count();
+                cells += width;
+            }
+            if line.len() > run_start {
+                spans.push(Span::styled(&line[run_start..], run_style));
+            }
+            for (_, kind) in &markers[marker_index..] {
+                spans.push(self.marker_span(*kind));
+            }
+
+            lines.push(Line::from(spans));
+            line_start += raw_line.len() + 1;
+        }
+        lines
+    }
+
+    /// The cell drawn before a decorated range of a marker `kind`.
+    fn marker_span(&self, kind: DecorationKind) -> Span<'static> {
+        Span::styled(
+            kind.marker().unwrap_or_default(),
+            self.theme.decoration_style(kind),
+        )
+    }
+}
+
+/// Byte range of the zero-based `line` of `text`, without its line ending,
+/// or an empty range at the end of the text past the last line.
+fn line_range(text: &str, line: usize) -> Range<usize> {
+    let start: usize = text.split_inclusive('\n').take(line).map(str::len).sum();
+    let rest = &text[start.min(text.len())..];
+    let line_text = rest.split('\n').next().unwrap_or("");
+    let line_text = line_text.strip_suffix('\r').unwrap_or(line_text);
+    start..start + line_text.len()
+}
+
+/// Clamp a byte offset to `text` and snap it back to a char boundary.
+fn clamp_offset(text: &str, offset: usize) -> usize {
+    let mut offset = offset.min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+impl TextDocuments for EditorWindow {
+    fn text(&self, path: &Path) -> Option<String> {
+        let tab = &self.group.tabs()[self.tab_for_path(path)?];
+        Some(tab.buffer.borrow().text().to_string())
+    }
+
+    fn set_text(&mut self, path: &Path, text: String) -> Result<(), EditError> {
+        let tab = self
+            .tab_for_path(path)
+            .ok_or_else(|| EditError::UnknownDocument(path.to_path_buf()))?;
+        if tab == self.group.active_index() {
+            EditorWindow::set_text(self, text);
+        } else {
+            self.group.tabs()[tab].buffer.borrow_mut().set_text(text);
+        }
+        Ok(())
     }
 }
 
@@ -25,20 +1597,106 @@ impl Window for EditorWindow {
     }
 
     fn render_with_focus(&mut self, frame: &mut Frame, area: Rect, focused: bool) {
+        self.follow_changes();
         let border_type = if focused {
             BorderType::Thick
         } else {
             BorderType::Plain
         };
 
-        let title = if focused { "Editor [*]" } else { "Editor" };
+        let marker = if focused { " [*]" } else { "" };
+        let room = usize::from(area.width.saturating_sub(2)).saturating_sub(display_width(marker));
+        let title = format!("{}{marker}", truncate_middle(&self.title, room));
 
-        let paragraph = Paragraph::new(self.buffer.clone()).block(
-            Block::default()
-                .title(title)
-                .borders(Borders::ALL)
-                .border_type(border_type),
-        );
-        frame.render_widget(paragraph, area);
+        let block = Block::default()
+            .title(title)
+            .borders(pane_borders(self.compact))
+            .border_type(border_type)
+            .border_style(self.theme.border_style(focused))
+            .style(self.theme.text);
+        let mut inner = block.inner(area);
+        frame.render_widget(block, area);
+        if self.group.len() > 1 && inner.height > 0 {
+            let strip = Rect { height: 1, ..inner };
+            frame.render_widget(Paragraph::new(self.tab_strip(strip.width)), strip);
+            inner.y += 1;
+            inner.height -= 1;
+        }
+
+        let buffer = Rc::clone(self.buffer());
+        let buffer = buffer.borrow();
+        self.update_highlighter();
+        if let Some(highlighter) = &self.highlighter {
+            self.highlight_cache
+                .highlight(highlighter.as_ref(), buffer.text());
+        }
+        if self.options.bracket_pairs {
+            let tokens = self.highlighter.is_some().then_some(&self.highlight_cache);
+            self.bracket_pairs.update(buffer.text(), tokens);
+        }
+        self.height = usize::from(inner.height);
+        let visible = self.viewport().lines();
+        if inner.width > 1 {
+            if let Some(gutter) = self.gutter(visible.clone()) {
+                frame.render_widget(Paragraph::new(gutter), Rect { width: 1, ..inner });
+                inner.x += 1;
+                inner.width -= 1;
+            }
+        }
+        let mut paragraph =
+            Paragraph::new(self.styled_lines(buffer.text(), visible)).style(self.theme.text);
+        if self.options.wrap {
+            paragraph = paragraph.wrap(Wrap { trim: false });
+        }
+        frame.render_widget(paragraph, inner);
+
+        if inner.height > 0 {
+            let peek_area = self.peek_area(inner);
+            if let Some(peek) = self.peek.as_mut() {
+                frame.render_widget(Clear, peek_area);
+                peek.render(frame, peek_area);
+            }
+
+            if let Some(find) = self.find.as_mut() {
+                let find_area = Self::find_area(inner);
+                frame.render_widget(Clear, find_area);
+                find.render(frame, find_area);
+            }
+
+            let rename_area = self
+                .rename
+                .as_ref()
+                .map(|input| self.rename_area(inner, input));
+            if let (Some(rename_area), Some(input)) = (rename_area, self.rename.as_mut()) {
+                frame.render_widget(Clear, rename_area);
+                input.render(frame, rename_area);
+            }
+        }
+    }
+
+    fn scroll_by(&mut self, lines: i32) {
+        self.scroll_lines(isize::try_from(lines).unwrap_or(0));
+    }
+
+    fn scroll_position(&self) -> Option<ScrollPosition> {
+        Some(ScrollPosition {
+            offset: self.viewport().top_line,
+            content: self.last_line() + 1,
+        })
+    }
+
+    fn context(&self) -> Option<WindowContext> {
+        Some(WindowContext::Editor)
+    }
+
+    fn set_compact(&mut self, compact: bool) {
+        self.compact = compact;
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        if let Some(find) = self.find.as_mut() {
+            find.set_theme(theme);
+        }
     }
 }
@@ -0,0 +1,698 @@
+//! Implementation of the file explorer window.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use cli_ide_platform::files::ignore::IgnoreMatcher;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Paragraph};
+
+use super::{pane_borders, Window};
+use crate::fuzzy::fuzzy_score;
+use crate::input::AppKey;
+use crate::keybinding::WindowContext;
+use crate::list_selection::{reveal_row, ListSelection};
+use crate::scrollbar::ScrollPosition;
+use crate::theme::Theme;
+
+/// Result of feeding a key to the file tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileTreeOutcome {
+    /// The tree does not handle the key; it falls through to the bindings.
+    Ignored,
+    /// The tree consumed the key.
+    Handled,
+    /// The user asked to open this file.
+    Open(PathBuf),
+}
+
+/// One visible row of the tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeRow {
+    /// Path of the entry.
+    pub path: PathBuf,
+    /// Nesting level below the root; top-level entries are at depth 0, or
+    /// the roots themselves when the tree has several.
+    pub depth: usize,
+    /// Whether the entry is a directory.
+    pub is_dir: bool,
+}
+
+/// A directory entry read from disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TreeEntry {
+    /// Path of the entry.
+    path: PathBuf,
+    /// Whether the entry is a directory.
+    is_dir: bool,
+}
+
+/// The workspace as an expandable tree, with files ignored by `.gitignore`
+/// or `.ignore` left out.
+///
+/// A workspace with several roots shows each root as a top-level folder.
+///
+/// Directories are read when first expanded. `Up`/`Down` move the cursor,
+/// `Right`/`Left` expand and collapse, `Enter` opens a file or toggles a
+/// directory, and `Space`/`Shift+Up`/`Shift+Down` mark entries for bulk
+/// operations. `/` starts a filter: typed characters then keep the loaded
+/// entries whose names fuzzy-match, along with their folders, until `Esc`.
+pub struct FileTreeWindow {
+    /// Directories the tree shows, never empty.
+    roots: Vec<PathBuf>,
+    /// Entries of each directory read so far, directories first.
+    listings: HashMap<PathBuf, Vec<TreeEntry>>,
+    /// Directories whose entries are shown.
+    expanded: BTreeSet<PathBuf>,
+    /// Visible rows, in display order.
+    rows: Vec<TreeRow>,
+    /// Cursor and marked entries.
+    selection: ListSelection<PathBuf>,
+    /// Filter text while the filter is active.
+    filter: Option<String>,
+    /// Index of the first visible row.
+    scroll_offset: usize,
+    /// Whether the next render scrolls the cursor into view.
+    follow_cursor: bool,
+    /// Styles to draw with.
+    theme: Theme,
+    /// Whether the explorer is drawn with only its top border.
+    compact: bool,
+}
+
+impl FileTreeWindow {
+    /// Create a tree over `root` with its top-level entries loaded.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self::with_roots(vec![root.into()])
+    }
+
+    /// Create a tree over each of `roots` with their top-level entries
+    /// loaded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `roots` is empty.
+    pub fn with_roots(roots: Vec<PathBuf>) -> Self {
+        assert!(!roots.is_empty(), "a file tree needs a root");
+        let mut tree = Self {
+            expanded: roots.iter().cloned().collect(),
+            roots,
+            listings: HashMap::new(),
+            rows: Vec::new(),
+            selection: ListSelection::default(),
+            filter: None,
+            scroll_offset: 0,
+            follow_cursor: true,
+            theme: Theme::default(),
+            compact: false,
+        };
+        for root in tree.roots.clone() {
+            tree.load(&root);
+        }
+        tree.relayout();
+        tree
+    }
+
+    /// Get the first directory the tree shows.
+    pub fn root(&self) -> &Path {
+        &self.roots[0]
+    }
+
+    /// Get the directories the tree shows.
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    /// Show the directory `root` after the others, expanded.
+    pub fn add_root(&mut self, root: impl Into<PathBuf>) {
+        let root = root.into();
+        if self.roots.contains(&root) {
+            return;
+        }
+        self.load(&root);
+        self.expanded.insert(root.clone());
+        self.roots.push(root);
+        self.relayout();
+    }
+
+    /// Stop showing the directory `root`.
+    ///
+    /// The last root cannot be removed.
+    pub fn remove_root(&mut self, root: &Path) {
+        if self.roots.len() == 1 || !self.roots.iter().any(|other| other == root) {
+            return;
+        }
+        self.roots.retain(|other| other != root);
+        let roots = &self.roots;
+        let shown = |dir: &PathBuf| roots.iter().any(|root| dir.starts_with(root));
+        self.listings.retain(|dir, _| shown(dir));
+        self.expanded.retain(shown);
+        self.relayout();
+    }
+
+    /// The root holding `path`, the innermost one if roots are nested.
+    fn root_of(&self, path: &Path) -> Option<&Path> {
+        self.roots
+            .iter()
+            .filter(|root| path.starts_with(root))
+            .max_by_key(|root| root.components().count())
+            .map(PathBuf::as_path)
+    }
+
+    /// Get the visible rows in display order.
+    pub fn rows(&self) -> &[TreeRow] {
+        &self.rows
+    }
+
+    /// Get the cursor and marked entries.
+    pub fn selection(&self) -> &ListSelection<PathBuf> {
+        &self.selection
+    }
+
+    /// Get the row under the cursor.
+    pub fn selected(&self) -> Option<&TreeRow> {
+        self.selection.cursor().and_then(|row| self.rows.get(row))
+    }
+
+    /// Get the entries a bulk operation applies to: the marked entries, or
+    /// the one under the cursor if none are marked.
+    pub fn targets(&self) -> Vec<PathBuf> {
+        self.selection.targets()
+    }
+
+    /// Unmark every entry.
+    pub fn clear_marks(&mut self) {
+        self.selection.clear_marks();
+    }
+
+    /// The folder new entries go in: the directory under the cursor, the
+    /// folder of the file under the cursor, or the first root.
+    pub fn target_dir(&self) -> PathBuf {
+        match self.selected() {
+            Some(row) if row.is_dir => row.path.clone(),
+            Some(row) => row
+                .path
+                .parent()
+                .map_or_else(|| self.root().to_path_buf(), Path::to_path_buf),
+            None => self.root().to_path_buf(),
+        }
+    }
+
+    /// Check whether the directory at `path` is expanded.
+    pub fn is_expanded(&self, path: &Path) -> bool {
+        self.expanded.contains(path)
+    }
+
+    /// Show the entries of the directory at `path`, reading it if needed.
+    pub fn expand(&mut self, path: &Path) {
+        if !self.listings.contains_key(path) {
+            self.load(path);
+        }
+        self.expanded.insert(path.to_path_buf());
+        self.relayout();
+    }
+
+    /// Hide the entries of the directory at `path`.
+    ///
+    /// The root of a tree with one root cannot be collapsed.
+    pub fn collapse(&mut self, path: &Path) {
+        if self.roots.len() > 1 || path != self.root() {
+            self.expanded.remove(path);
+            self.relayout();
+        }
+    }
+
+    /// Expand the folders above `path` and move the cursor to it.
+    pub fn reveal(&mut self, path: &Path) {
+        let Some(root) = self.root_of(path).map(Path::to_path_buf) else {
+            return;
+        };
+        let Ok(relative) = path.strip_prefix(&root) else {
+            return;
+        };
+        self.expanded.insert(root.clone());
+        let mut dir = root;
+        let components: Vec<_> = relative.components().collect();
+        for component in components.iter().take(components.len().saturating_sub(1)) {
+            dir.push(component);
+            if !self.listings.contains_key(&dir) {
+                self.load(&dir);
+            }
+            self.expanded.insert(dir.clone());
+        }
+        self.relayout();
+        if let Some(row) = self.rows.iter().position(|row| row.path == path) {
+            self.selection.set_cursor(row);
+            self.follow_cursor = true;
+        }
+    }
+
+    /// Read every loaded directory again, e.g. after files changed.
+    ///
+    /// Expanded directories that no longer exist are forgotten; the cursor
+    /// and marks stay on entries that still exist.
+    pub fn refresh(&mut self) {
+        let loaded: Vec<PathBuf> = self.listings.keys().cloned().collect();
+        self.listings.clear();
+        for dir in loaded {
+            if self.roots.contains(&dir) || dir.is_dir() {
+                self.load(&dir);
+            }
+        }
+        let listings = &self.listings;
+        self.expanded.retain(|dir| listings.contains_key(dir));
+        self.relayout();
+    }
+
+    /// Get the filter text, if the filter is active.
+    pub fn filter(&self) -> Option<&str> {
+        self.filter.as_deref()
+    }
+
+    /// Get the index of the first visible row.
+    pub fn scroll_offset(&self) -> usize {
+        self.scroll_offset
+    }
+
+    /// Move the cursor to the row drawn at `position` when the explorer is
+    /// drawn in `area`, returning whether there is one.
+    pub fn select_row_at(&mut self, area: Rect, position: Position) -> bool {
+        let inner = Block::default()
+            .borders(pane_borders(self.compact))
+            .inner(area);
+        let row = self.scroll_offset + usize::from(position.y.saturating_sub(inner.y));
+        if !inner.contains(position) || row >= self.rows.len() {
+            return false;
+        }
+        self.selection.set_cursor(row);
+        true
+    }
+
+    /// Handle a key press.
+    ///
+    /// While the filter is active every key is consumed.
+    pub fn handle_key(&mut self, key: AppKey) -> FileTreeOutcome {
+        if self.filter.is_some() {
+            if let Some(outcome) = self.handle_filter_key(key) {
+                return outcome;
+            }
+        }
+        match key {
+            AppKey::Up | AppKey::Down | AppKey::ShiftUp | AppKey::ShiftDown => {
+                self.selection.handle_key(key);
+            }
+            AppKey::Char(' ') if self.filter.is_none() => self.selection.toggle_mark(),
+            AppKey::Char('/') if self.filter.is_none() => {
+                self.filter = Some(String::new());
+                self.relayout();
+            }
+            AppKey::Right => match self.selected().cloned() {
+                Some(row) if row.is_dir && self.is_expanded(&row.path) => {
+                    self.selection.move_by(1);
+                }
+                Some(row) if row.is_dir => self.expand(&row.path),
+                _ => {}
+            },
+            AppKey::Left => match self.selected().cloned() {
+                Some(row) if row.is_dir && self.is_expanded(&row.path) => {
+                    self.collapse(&row.path);
+                }
+                Some(row) => {
+                    let parent = self
+                        .rows
+                        .iter()
+                        .position(|candidate| Some(candidate.path.as_path()) == row.path.parent());
+                    if let Some(parent) = parent {
+                        self.selection.set_cursor(parent);
+                    }
+                }
+                None => {}
+            },
+            AppKey::Enter => match self.selected().cloned() {
+                Some(row) if row.is_dir && self.is_expanded(&row.path) => {
+                    self.collapse(&row.path);
+                }
+                Some(row) if row.is_dir => self.expand(&row.path),
+                Some(row) => return FileTreeOutcome::Open(row.path),
+                None => {}
+            },
+            AppKey::Esc if !self.selection.marked().is_empty() => self.selection.clear_marks(),
+            _ if self.filter.is_some() => {}
+            _ => return FileTreeOutcome::Ignored,
+        }
+        self.follow_cursor = true;
+        FileTreeOutcome::Handled
+    }
+
+    /// Handle a key that edits the filter, returning `None` for keys that
+    /// behave as outside the filter.
+    fn handle_filter_key(&mut self, key: AppKey) -> Option<FileTreeOutcome> {
+        let filter = self.filter.as_mut()?;
+        match key {
+            AppKey::Backspace => {
+                filter.pop();
+            }
+            // `q` is translated to AppKey::Q; inside the filter it is text.
+            AppKey::Q => filter.push('q'),
+            AppKey::Char(c) => filter.push(c),
+            AppKey::Esc => self.filter = None,
+            _ => return None,
+        }
+        self.relayout();
+        self.follow_cursor = true;
+        Some(FileTreeOutcome::Handled)
+    }
+
+    /// Read the entries of `dir` that are not ignored.
+    ///
+    /// An unreadable directory lists no entries.
+    fn load(&mut self, dir: &Path) {
+        let root = self.root_of(dir).unwrap_or(dir);
+        let matcher = IgnoreMatcher::for_dir(root, dir);
+        let mut entries: Vec<TreeEntry> = fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .filter_map(Result::ok)
+                    .map(|entry| TreeEntry {
+                        is_dir: entry.path().is_dir(),
+                        path: entry.path(),
+                    })
+                    .filter(|entry| !matcher.is_ignored(&entry.path, entry.is_dir))
+                    .collect()
+            })
+            .unwrap_or_default();
+        entries.sort_by_key(|entry| {
+            let name = entry
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_lowercase());
+            (!entry.is_dir, name)
+        });
+        self.listings.insert(dir.to_path_buf(), entries);
+    }
+
+    /// Rebuild the visible rows from the expanded directories and the
+    /// filter.
+    fn relayout(&mut self) {
+        let mut rows = Vec::new();
+        if let [root] = self.roots.as_slice() {
+            self.collect_rows(root, 0, &mut rows);
+        } else {
+            for root in &self.roots {
+                rows.push(TreeRow {
+                    path: root.clone(),
+                    depth: 0,
+                    is_dir: true,
+                });
+                if self.expanded.contains(root) {
+                    self.collect_rows(root, 1, &mut rows);
+                }
+            }
+        }
+        if let Some(filter) = self.filter.as_deref().filter(|filter| !filter.is_empty()) {
+            let matches: Vec<&Path> = rows
+                .iter()
+                .filter(|row| {
+                    let name = row.path.file_name().unwrap_or_default().to_string_lossy();
+                    fuzzy_score(filter, &name).is_some()
+                })
+                .map(|row| row.path.as_path())
+                .collect();
+            let keep: BTreeSet<PathBuf> = rows
+                .iter()
+                .filter(|row| matches.iter().any(|path| path.starts_with(&row.path)))
+                .map(|row| row.path.clone())
+                .collect();
+            rows.retain(|row| keep.contains(&row.path));
+        }
+        self.selection
+            .set_items(rows.iter().map(|row| row.path.clone()).collect());
+        self.rows = rows;
+    }
+
+    /// Append the rows of the expanded directory `dir` at `depth`.
+    fn collect_rows(&self, dir: &Path, depth: usize, rows: &mut Vec<TreeRow>) {
+        let Some(entries) = self.listings.get(dir) else {
+            return;
+        };
+        for entry in entries {
+            rows.push(TreeRow {
+                path: entry.path.clone(),
+                depth,
+                is_dir: entry.is_dir,
+            });
+            if entry.is_dir && self.expanded.contains(&entry.path) {
+                self.collect_rows(&entry.path, depth + 1, rows);
+            }
+        }
+    }
+
+    /// Format a row as its marker, indentation, expansion arrow and name.
+    fn row_label(&self, row: &TreeRow) -> String {
+        let marker = if self.selection.is_marked(&row.path) {
+            "* "
+        } else {
+            "  "
+        };
+        let arrow = match (row.is_dir, self.is_expanded(&row.path)) {
+            (true, true) => "▾ ",
+            (true, false) => "▸ ",
+            (false, _) => "  ",
+        };
+        let name = row.path.file_name().unwrap_or_default().to_string_lossy();
+        let slash = if row.is_dir { "/" } else { "" };
+        format!("{marker}{}{arrow}{name}{slash}", "  ".repeat(row.depth))
+    }
+}
+
+impl Window for FileTreeWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.render_with_focus(frame, area, false);
+    }
+
+    fn render_with_focus(&mut self, frame: &mut Frame, area: Rect, focused: bool) {
+        let border_type = if focused {
+            BorderType::Thick
+        } else {
+            BorderType::Plain
+        };
+        let title = if focused { "Explorer [*]" } else { "Explorer" };
+        let mut block = Block::default()
+            .title(title)
+            .borders(pane_borders(self.compact))
+            .border_type(border_type)
+            .border_style(self.theme.border_style(focused));
+        if let Some(filter) = &self.filter {
+            block = block.title_bottom(format!("/{filter}"));
+        }
+        let height = usize::from(block.inner(area).height);
+
+        if self.follow_cursor && height > 0 {
+            if let Some(cursor) = self.selection.cursor() {
+                self.scroll_offset = reveal_row(self.scroll_offset, cursor, height);
+            }
+            self.follow_cursor = false;
+        }
+        self.scroll_offset = self
+            .scroll_offset
+            .min(self.rows.len().saturating_sub(height));
+
+        let cursor = self.selection.cursor();
+        let lines: Vec<Line> = self
+            .rows
+            .iter()
+            .enumerate()
+            .skip(self.scroll_offset)
+            .take(height)
+            .map(|(index, row)| {
+                let style = if focused && Some(index) == cursor {
+                    self.theme.selection
+                } else {
+                    self.theme.text
+                };
+                Line::styled(self.row_label(row), style)
+            })
+            .collect();
+        let paragraph = Paragraph::new(lines).style(self.theme.text).block(block);
+        frame.render_widget(paragraph, area);
+    }
+
+    fn scroll_by(&mut self, lines: i32) {
+        let last_row = self.rows.len().saturating_sub(1);
+        let offset = i64::try_from(self.scroll_offset).unwrap_or(i64::MAX) + i64::from(lines);
+        self.scroll_offset = usize::try_from(offset.max(0))
+            .unwrap_or(usize::MAX)
+            .min(last_row);
+        self.follow_cursor = false;
+    }
+
+    fn scroll_position(&self) -> Option<ScrollPosition> {
+        Some(ScrollPosition {
+            offset: self.scroll_offset,
+            content: self.rows.len(),
+        })
+    }
+
+    fn context(&self) -> Option<WindowContext> {
+        Some(WindowContext::Explorer)
+    }
+
+    fn set_compact(&mut self, compact: bool) {
+        self.compact = compact;
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cli_ide_base::test_support::{temp_dir, Cleanup};
+
+    /// A workspace with `src/{lib.rs,nested/}`, `README.md`, an ignored
+    /// `target/` and its `.gitignore`, and the guard removing it.
+    fn workspace(name: &str) -> (PathBuf, Cleanup) {
+        let dir = temp_dir(name);
+        let cleanup = Cleanup::new([&dir]);
+        fs::create_dir_all(dir.join("src").join("nested")).unwrap();
+        fs::create_dir_all(dir.join("target")).unwrap();
+        fs::write(dir.join("src").join("lib.rs"), "").unwrap();
+        fs::write(dir.join("README.md"), "").unwrap();
+        fs::write(dir.join(".gitignore"), "target/\n").unwrap();
+        (fs::canonicalize(dir).unwrap(), cleanup)
+    }
+
+    fn names(tree: &FileTreeWindow) -> Vec<String> {
+        tree.rows()
+            .iter()
+            .map(|row| {
+                let name = row.path.file_name().unwrap().to_string_lossy();
+                format!("{}{name}", "  ".repeat(row.depth))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_lists_directories_first_without_ignored_entries() {
+        let (root, _cleanup) = workspace("tree-list");
+        let tree = FileTreeWindow::new(&root);
+        assert_eq!(names(&tree), vec!["src", ".gitignore", "README.md"]);
+        assert!(!tree.is_expanded(&root.join("src")));
+    }
+
+    #[test]
+    fn test_expand_collapse_and_open() {
+        let (root, _cleanup) = workspace("tree-keys");
+        let mut tree = FileTreeWindow::new(&root);
+
+        assert_eq!(tree.handle_key(AppKey::Right), FileTreeOutcome::Handled);
+        assert_eq!(
+            names(&tree),
+            vec!["src", "  nested", "  lib.rs", ".gitignore", "README.md"]
+        );
+        tree.handle_key(AppKey::Down);
+        tree.handle_key(AppKey::Down);
+        assert_eq!(
+            tree.handle_key(AppKey::Enter),
+            FileTreeOutcome::Open(root.join("src").join("lib.rs"))
+        );
+
+        // Left on a file jumps to its folder, then collapses it
+        tree.handle_key(AppKey::Left);
+        assert_eq!(tree.selected().unwrap().path, root.join("src"));
+        tree.handle_key(AppKey::Left);
+        assert_eq!(names(&tree), vec!["src", ".gitignore", "README.md"]);
+        assert_eq!(tree.handle_key(AppKey::Tab), FileTreeOutcome::Ignored);
+    }
+
+    #[test]
+    fn test_filter_keeps_matches_and_their_folders() {
+        let (root, _cleanup) = workspace("tree-filter");
+        let mut tree = FileTreeWindow::new(&root);
+        tree.expand(&root.join("src"));
+
+        tree.handle_key(AppKey::Char('/'));
+        tree.handle_key(AppKey::Char('l'));
+        tree.handle_key(AppKey::Char('i'));
+        assert_eq!(tree.filter(), Some("li"));
+        assert_eq!(names(&tree), vec!["src", "  lib.rs"]);
+        // Keys that are bindings elsewhere are text while filtering
+        assert_eq!(tree.handle_key(AppKey::Q), FileTreeOutcome::Handled);
+        assert!(tree.rows().is_empty());
+
+        tree.handle_key(AppKey::Esc);
+        assert_eq!(tree.filter(), None);
+        assert_eq!(names(&tree).len(), 5);
+    }
+
+    #[test]
+    fn test_marks_survive_refresh_and_reveal_expands_parents() {
+        let (root, _cleanup) = workspace("tree-refresh");
+        let mut tree = FileTreeWindow::new(&root);
+        tree.handle_key(AppKey::Down);
+        tree.handle_key(AppKey::Char(' '));
+        tree.handle_key(AppKey::Down);
+        tree.handle_key(AppKey::Char(' '));
+        assert_eq!(
+            tree.targets(),
+            vec![root.join(".gitignore"), root.join("README.md")]
+        );
+
+        fs::write(root.join("src").join("nested").join("new.rs"), "").unwrap();
+        tree.refresh();
+        tree.reveal(&root.join("src").join("nested").join("new.rs"));
+        assert_eq!(
+            tree.selected().unwrap().path,
+            root.join("src").join("nested").join("new.rs")
+        );
+        assert_eq!(tree.target_dir(), root.join("src").join("nested"));
+        assert_eq!(tree.selection().marked().len(), 2);
+
+        assert_eq!(tree.handle_key(AppKey::Esc), FileTreeOutcome::Handled);
+        assert!(tree.selection().marked().is_empty());
+        assert_eq!(tree.handle_key(AppKey::Esc), FileTreeOutcome::Ignored);
+    }
+
+    #[test]
+    fn test_several_roots_show_as_top_level_folders() {
+        let (app, _app_cleanup) = workspace("tree-root-app");
+        let (lib, _lib_cleanup) = workspace("tree-root-lib");
+        fs::write(lib.join("lib.toml"), "").unwrap();
+        let mut tree = FileTreeWindow::new(&app);
+        tree.add_root(&lib);
+        assert_eq!(tree.roots(), [app.clone(), lib.clone()]);
+        let app_name = app.file_name().unwrap().to_string_lossy().into_owned();
+        let lib_name = lib.file_name().unwrap().to_string_lossy().into_owned();
+        assert_eq!(
+            names(&tree),
+            vec![
+                app_name.clone(),
+                "  src".to_string(),
+                "  .gitignore".to_string(),
+                "  README.md".to_string(),
+                lib_name.clone(),
+                "  src".to_string(),
+                "  .gitignore".to_string(),
+                "  lib.toml".to_string(),
+                "  README.md".to_string(),
+            ]
+        );
+
+        // Roots collapse, and revealing a file expands its root again
+        tree.collapse(&app);
+        assert_eq!(names(&tree)[..2], [app_name.clone(), lib_name.clone()]);
+        tree.reveal(&app.join("src").join("lib.rs"));
+        assert_eq!(
+            tree.selected().unwrap().path,
+            app.join("src").join("lib.rs")
+        );
+        assert_eq!(tree.selected().unwrap().depth, 2);
+
+        tree.remove_root(&lib);
+        assert_eq!(tree.roots(), [app.as_path()]);
+        assert_eq!(names(&tree)[0], "src", "one root shows its entries");
+    }
+}
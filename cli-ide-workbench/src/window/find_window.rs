@@ -0,0 +1,269 @@
+//! Implementation of the editor's find and replace input.
+
+use std::ops::Range;
+
+use super::Window;
+use crate::input::AppKey;
+use crate::theme::Theme;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Borders, Paragraph};
+
+/// Width of the find input, including its border.
+pub const FIND_WIDTH: u16 = 36;
+
+/// Height of the find input, including its border.
+pub const FIND_HEIGHT: u16 = 4;
+
+/// Result of feeding a key to a find input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FindOutcome {
+    /// The input consumed the key.
+    Pending,
+    /// The text to find changed; the matches need to be found again.
+    QueryChanged,
+    /// The user accepted the query and asked for the next match.
+    Next,
+    /// The user asked to replace the current match.
+    ReplaceOne,
+    /// The user dismissed the input.
+    Dismissed,
+}
+
+/// The part of the find input that receives typed keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FindField {
+    /// The text to find.
+    Find,
+    /// The text to replace matches with.
+    Replace,
+}
+
+/// A find and replace input drawn over the top right of the editor.
+///
+/// While editing, typing goes to the Find or Replace field and `Up`/`Down`
+/// switch between them. `Enter` in the Find field stops editing and moves
+/// to the next match, leaving the matches highlighted for `n`/`N`; `Enter`
+/// in the Replace field replaces the current match. `Esc` dismisses the
+/// input. Queries are plain text, matched case-sensitively.
+pub struct FindWindow {
+    /// Text to find.
+    query: String,
+    /// Text to replace matches with.
+    replacement: String,
+    /// Field receiving typed keys.
+    field: FindField,
+    /// Whether the input captures typed keys.
+    editing: bool,
+    /// Byte ranges of the matches in the editor text, in order.
+    matches: Vec<Range<usize>>,
+    /// Index of the match at the editor selection, if any.
+    current: Option<usize>,
+    /// Styles to draw with.
+    theme: Theme,
+}
+
+impl FindWindow {
+    /// Create an input editing the query `query`.
+    pub fn new(query: impl Into<String>) -> Self {
+        Self {
+            query: query.into(),
+            replacement: String::new(),
+            field: FindField::Find,
+            editing: true,
+            matches: Vec::new(),
+            current: None,
+            theme: Theme::default(),
+        }
+    }
+
+    /// Get the text to find.
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Get the text to replace matches with.
+    pub fn replacement(&self) -> &str {
+        &self.replacement
+    }
+
+    /// Get the field receiving typed keys.
+    pub fn field(&self) -> FindField {
+        self.field
+    }
+
+    /// Whether the input captures typed keys.
+    pub fn is_editing(&self) -> bool {
+        self.editing
+    }
+
+    /// Capture typed keys again, in the Find field.
+    pub fn edit(&mut self) {
+        self.editing = true;
+        self.field = FindField::Find;
+    }
+
+    /// Get the byte ranges of the matches, in order.
+    pub fn matches(&self) -> &[Range<usize>] {
+        &self.matches
+    }
+
+    /// Get the index of the match at the editor selection, if any.
+    pub fn current(&self) -> Option<usize> {
+        self.current
+    }
+
+    /// Find the query in `text` again and mark the match at `selection`
+    /// as current.
+    pub fn update(&mut self, text: &str, selection: &Range<usize>) {
+        self.matches = if self.query.is_empty() {
+            Vec::new()
+        } else {
+            text.match_indices(&self.query)
+                .map(|(start, found)| start..start + found.len())
+                .collect()
+        };
+        self.select(selection);
+    }
+
+    /// Mark the match equal to `selection` as current, if there is one.
+    pub fn select(&mut self, selection: &Range<usize>) {
+        self.current = self.matches.iter().position(|found| found == selection);
+    }
+
+    /// The first match starting at or after `offset`, wrapping around to
+    /// the first match.
+    pub fn next_match(&self, offset: usize) -> Option<Range<usize>> {
+        self.matches
+            .iter()
+            .find(|found| found.start >= offset)
+            .or_else(|| self.matches.first())
+            .cloned()
+    }
+
+    /// The last match starting before `offset`, wrapping around to the
+    /// last match.
+    pub fn previous_match(&self, offset: usize) -> Option<Range<usize>> {
+        self.matches
+            .iter()
+            .rev()
+            .find(|found| found.start < offset)
+            .or_else(|| self.matches.last())
+            .cloned()
+    }
+
+    /// Handle a key press while editing.
+    pub fn handle_key(&mut self, key: AppKey) -> FindOutcome {
+        let field = match self.field {
+            FindField::Find => &mut self.query,
+            FindField::Replace => &mut self.replacement,
+        };
+        match key {
+            AppKey::Esc => return FindOutcome::Dismissed,
+            AppKey::Enter if self.field == FindField::Find => {
+                self.editing = false;
+                return FindOutcome::Next;
+            }
+            AppKey::Enter => return FindOutcome::ReplaceOne,
+            AppKey::Up => self.field = FindField::Find,
+            AppKey::Down => self.field = FindField::Replace,
+            AppKey::Backspace => {
+                field.pop();
+            }
+            // `q` is translated to AppKey::Q; inside an input it is text.
+            AppKey::Q => field.push('q'),
+            AppKey::Char(c) => field.push(c),
+            _ => {}
+        }
+        if self.field == FindField::Find
+            && matches!(key, AppKey::Backspace | AppKey::Q | AppKey::Char(_))
+        {
+            FindOutcome::QueryChanged
+        } else {
+            FindOutcome::Pending
+        }
+    }
+
+    /// The title: the position of the current match among all of them.
+    fn title(&self) -> String {
+        match (self.current, self.matches.len()) {
+            (_, 0) if self.query.is_empty() => "Find".to_string(),
+            (_, 0) => "Find: no results".to_string(),
+            (Some(current), total) => format!("Find: {} of {total}", current + 1),
+            (None, total) => format!("Find: {total} results"),
+        }
+    }
+
+    /// A labelled field, highlighted while it receives typed keys.
+    fn field_line<'a>(&self, field: FindField, label: &'a str, text: &'a str) -> Line<'a> {
+        let style = if self.editing && self.field == field {
+            self.theme.selection
+        } else {
+            self.theme.text
+        };
+        Line::from(vec![Span::styled(label, style), Span::raw(text)])
+    }
+}
+
+impl Window for FindWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.render_with_focus(frame, area, self.editing);
+    }
+
+    fn render_with_focus(&mut self, frame: &mut Frame, area: Rect, focused: bool) {
+        let block = Block::default()
+            .title(self.title())
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(self.theme.border_style(focused));
+        let lines = vec![
+            self.field_line(FindField::Find, "Find:    ", &self.query),
+            self.field_line(FindField::Replace, "Replace: ", &self.replacement),
+        ];
+        let paragraph = Paragraph::new(lines).style(self.theme.text).block(block);
+        frame.render_widget(paragraph, area);
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_typing_updates_matches_and_navigation_wraps() {
+        let text = "one two one two one";
+        let mut find = FindWindow::new("");
+        assert_eq!(
+            find.handle_key(AppKey::Char('o')),
+            FindOutcome::QueryChanged
+        );
+        assert_eq!(
+            find.handle_key(AppKey::Char('n')),
+            FindOutcome::QueryChanged
+        );
+        find.update(text, &(0..0));
+        assert_eq!(find.matches(), [0..2, 8..10, 16..18]);
+        assert_eq!(find.current(), None);
+
+        assert_eq!(find.next_match(1), Some(8..10));
+        assert_eq!(find.next_match(17), Some(0..2));
+        assert_eq!(find.previous_match(8), Some(0..2));
+        assert_eq!(find.previous_match(0), Some(16..18));
+        find.select(&(8..10));
+        assert_eq!(find.current(), Some(1));
+
+        // Typing in the Replace field leaves the matches alone
+        assert_eq!(find.handle_key(AppKey::Down), FindOutcome::Pending);
+        assert_eq!(find.handle_key(AppKey::Q), FindOutcome::Pending);
+        assert_eq!(find.replacement(), "q");
+        assert_eq!(find.handle_key(AppKey::Enter), FindOutcome::ReplaceOne);
+        assert!(find.is_editing());
+
+        find.handle_key(AppKey::Up);
+        assert_eq!(find.handle_key(AppKey::Enter), FindOutcome::Next);
+        assert!(!find.is_editing());
+    }
+}
@@ -0,0 +1,422 @@
+//! Implementation of the git history browser.
+
+use std::path::{Path, PathBuf};
+
+use cli_ide_platform::git::log::{Commit, CommitDetail};
+use cli_ide_platform::git::repository::ChangeKind;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Borders, Paragraph};
+
+use super::Window;
+use crate::input::AppKey;
+use crate::keybinding::WindowContext;
+use crate::list_selection::{reveal_row, ListSelection};
+use crate::theme::Theme;
+
+/// Widest the author column of the commit list grows.
+const MAX_AUTHOR_WIDTH: usize = 20;
+
+/// Result of feeding a key to the history browser.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GitLogOutcome {
+    /// The browser does not handle the key; it falls through to the
+    /// bindings.
+    Ignored,
+    /// The browser consumed the key.
+    Handled,
+    /// The user asked for the details of the commit with this id.
+    ShowCommit(String),
+    /// The user asked to see the file at `path` as it was at `revision`.
+    OpenFile {
+        /// The revision to read the file at.
+        revision: String,
+        /// Absolute path of the file.
+        path: PathBuf,
+    },
+}
+
+/// The commit whose details are shown and the cursor among its files.
+struct CommitView {
+    /// The commit, its message and files.
+    detail: CommitDetail,
+    /// Indices into the commit's files.
+    files: ListSelection<usize>,
+}
+
+/// A window listing the commits of a git repository.
+///
+/// `Up`/`Down` move between commits and `Enter` shows the commit under the
+/// cursor: its author, date, full message and changed files. There `Enter`
+/// opens the file under the cursor as it was at the commit, and `Esc` or
+/// `Left` go back to the list.
+pub struct GitLogWindow {
+    /// Top-level directory of the work tree; paths are shown relative to it.
+    root: PathBuf,
+    /// Commits, newest first.
+    commits: Vec<Commit>,
+    /// Indices into the commits and the cursor among them.
+    rows: ListSelection<usize>,
+    /// Why the history could not be read, if it could not.
+    error: Option<String>,
+    /// The commit being shown, if any.
+    detail: Option<CommitView>,
+    /// Index of the first visible line.
+    scroll_offset: usize,
+    /// Whether the next render scrolls the cursor into view.
+    follow_cursor: bool,
+    /// Styles to draw with.
+    theme: Theme,
+}
+
+impl GitLogWindow {
+    /// Create an empty history for the work tree at `root`.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            commits: Vec::new(),
+            rows: ListSelection::default(),
+            error: None,
+            detail: None,
+            scroll_offset: 0,
+            follow_cursor: true,
+            theme: Theme::default(),
+        }
+    }
+
+    /// Get the top-level directory of the work tree.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Get the listed commits, newest first.
+    pub fn commits(&self) -> &[Commit] {
+        &self.commits
+    }
+
+    /// Get why the history could not be read, if it could not.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Show the history, or why it could not be read.
+    ///
+    /// The cursor stays on the same commit if it is still listed.
+    pub fn set_commits(&mut self, commits: Result<Vec<Commit>, String>) {
+        let current = self.selected().map(|commit| commit.id.clone());
+        let (commits, error) = match commits {
+            Ok(commits) => (commits, None),
+            Err(error) => (Vec::new(), Some(error)),
+        };
+        let cursor = current.and_then(|id| commits.iter().position(|commit| commit.id == id));
+        self.rows.set_items((0..commits.len()).collect());
+        self.commits = commits;
+        if let Some(cursor) = cursor {
+            self.rows.set_cursor(cursor);
+        }
+        self.error = error;
+        self.follow_cursor = true;
+    }
+
+    /// The commit under the cursor of the list.
+    pub fn selected(&self) -> Option<&Commit> {
+        self.rows.current().map(|&index| &self.commits[index])
+    }
+
+    /// Get the commit being shown, if any.
+    pub fn detail(&self) -> Option<&CommitDetail> {
+        self.detail.as_ref().map(|view| &view.detail)
+    }
+
+    /// Show the details of a commit instead of the list.
+    pub fn show_commit(&mut self, detail: CommitDetail) {
+        let files = ListSelection::new((0..detail.files.len()).collect());
+        self.detail = Some(CommitView { detail, files });
+        self.scroll_offset = 0;
+        self.follow_cursor = true;
+    }
+
+    /// Handle a key press.
+    pub fn handle_key(&mut self, key: AppKey) -> GitLogOutcome {
+        let outcome = match &mut self.detail {
+            Some(view) => match key {
+                AppKey::Up => {
+                    view.files.move_by(-1);
+                    GitLogOutcome::Handled
+                }
+                AppKey::Down => {
+                    view.files.move_by(1);
+                    GitLogOutcome::Handled
+                }
+                AppKey::Enter => match view.files.current() {
+                    Some(&index) => {
+                        let file = &view.detail.files[index];
+                        let commit = &view.detail.commit;
+                        // A deleted file is shown as it was just before
+                        let revision = if file.change == ChangeKind::Deleted {
+                            format!("{}^", commit.short_id)
+                        } else {
+                            commit.short_id.clone()
+                        };
+                        GitLogOutcome::OpenFile {
+                            revision,
+                            path: file.path.clone(),
+                        }
+                    }
+                    None => GitLogOutcome::Handled,
+                },
+                AppKey::Esc | AppKey::Left => {
+                    self.detail = None;
+                    GitLogOutcome::Handled
+                }
+                _ => GitLogOutcome::Ignored,
+            },
+            None => match key {
+                AppKey::Up => {
+                    self.rows.move_by(-1);
+                    GitLogOutcome::Handled
+                }
+                AppKey::Down => {
+                    self.rows.move_by(1);
+                    GitLogOutcome::Handled
+                }
+                AppKey::Enter => match self.selected() {
+                    Some(commit) => GitLogOutcome::ShowCommit(commit.id.clone()),
+                    None => GitLogOutcome::Handled,
+                },
+                _ => GitLogOutcome::Ignored,
+            },
+        };
+        if outcome == GitLogOutcome::Handled {
+            self.follow_cursor = true;
+        }
+        outcome
+    }
+
+    /// The title: the shown commit, if any.
+    fn title(&self, focused: bool) -> String {
+        let marker = if focused { " [*]" } else { "" };
+        match &self.detail {
+            Some(view) => format!("Git History: {}{marker}", view.detail.commit.short_id),
+            None => format!("Git History{marker}"),
+        }
+    }
+
+    /// The lines of the list and the index of the cursor's line, which is
+    /// highlighted while `focused`.
+    fn list_lines(&self, focused: bool) -> (Vec<Line<'static>>, Option<usize>) {
+        if let Some(error) = &self.error {
+            return (vec![Line::styled(error.clone(), self.theme.overflow)], None);
+        }
+        if self.commits.is_empty() {
+            return (vec![Line::styled("No commits", self.theme.text)], None);
+        }
+        let width = self
+            .commits
+            .iter()
+            .map(|commit| commit.author.chars().count())
+            .max()
+            .unwrap_or(0)
+            .min(MAX_AUTHOR_WIDTH);
+        let lines = self
+            .commits
+            .iter()
+            .enumerate()
+            .map(|(row, commit)| {
+                let style = if focused && self.rows.cursor() == Some(row) {
+                    self.theme.selection
+                } else {
+                    self.theme.text
+                };
+                let author: String = commit.author.chars().take(width).collect();
+                Line::styled(
+                    format!(
+                        "{} {} {author:<width$} {}",
+                        commit.short_id, commit.date, commit.subject
+                    ),
+                    style,
+                )
+            })
+            .collect();
+        (lines, self.rows.cursor())
+    }
+
+    /// The lines of a commit's details and the index of the cursor's line.
+    fn detail_lines(
+        &self,
+        view: &CommitView,
+        focused: bool,
+    ) -> (Vec<Line<'static>>, Option<usize>) {
+        let commit = &view.detail.commit;
+        let mut lines = vec![
+            Line::styled(format!("commit {}", commit.id), self.theme.diff_hunk),
+            Line::styled(format!("Author: {}", commit.author), self.theme.text),
+            Line::styled(format!("Date:   {}", commit.date), self.theme.text),
+            Line::default(),
+            Line::styled(
+                format!("    {}", commit.subject),
+                self.theme.text.add_modifier(Modifier::BOLD),
+            ),
+        ];
+        if !view.detail.body.is_empty() {
+            lines.push(Line::default());
+            lines.extend(
+                view.detail
+                    .body
+                    .lines()
+                    .map(|line| Line::styled(format!("    {line}"), self.theme.text)),
+            );
+        }
+        lines.push(Line::default());
+        lines.push(Line::styled(
+            format!("Files ({})", view.detail.files.len()),
+            self.theme.text.add_modifier(Modifier::BOLD),
+        ));
+        let first_file = lines.len();
+        for (row, &index) in view.files.items().iter().enumerate() {
+            let file = &view.detail.files[index];
+            let style = if focused && view.files.cursor() == Some(row) {
+                self.theme.selection
+            } else {
+                self.theme.text
+            };
+            let path = file.path.strip_prefix(&self.root).unwrap_or(&file.path);
+            lines.push(Line::styled(
+                format!("  {} {}", file.change.letter(), path.display()),
+                style,
+            ));
+        }
+        (lines, view.files.cursor().map(|row| first_file + row))
+    }
+}
+
+impl Window for GitLogWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.render_with_focus(frame, area, false);
+    }
+
+    fn render_with_focus(&mut self, frame: &mut Frame, area: Rect, focused: bool) {
+        let border_type = if focused {
+            BorderType::Thick
+        } else {
+            BorderType::Plain
+        };
+        let block = Block::default()
+            .title(self.title(focused))
+            .borders(Borders::ALL)
+            .border_type(border_type)
+            .border_style(self.theme.border_style(focused));
+        let height = usize::from(block.inner(area).height);
+
+        let (lines, cursor_line) = match &self.detail {
+            Some(view) => self.detail_lines(view, focused),
+            None => self.list_lines(focused),
+        };
+        if self.follow_cursor && height > 0 {
+            if let Some(cursor) = cursor_line {
+                self.scroll_offset = reveal_row(self.scroll_offset, cursor, height);
+            }
+            self.follow_cursor = false;
+        }
+        let scroll_offset = self.scroll_offset.min(lines.len().saturating_sub(height));
+        let visible: Vec<Line> = lines.into_iter().skip(scroll_offset).take(height).collect();
+        self.scroll_offset = scroll_offset;
+        let paragraph = Paragraph::new(visible).style(self.theme.text).block(block);
+        frame.render_widget(paragraph, area);
+    }
+
+    fn scroll_by(&mut self, lines: i32) {
+        let offset = i64::try_from(self.scroll_offset).unwrap_or(i64::MAX) + i64::from(lines);
+        self.scroll_offset = usize::try_from(offset.max(0)).unwrap_or(usize::MAX);
+        self.follow_cursor = false;
+    }
+
+    fn context(&self) -> Option<WindowContext> {
+        Some(WindowContext::GitLog)
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cli_ide_platform::git::log::CommitFile;
+
+    fn commit(id: &str, subject: &str) -> Commit {
+        Commit {
+            id: id.repeat(8),
+            short_id: id.to_string(),
+            author: "Ada".to_string(),
+            date: "2026-10-15".to_string(),
+            subject: subject.to_string(),
+        }
+    }
+
+    fn log() -> GitLogWindow {
+        let mut log = GitLogWindow::new("/repo");
+        log.set_commits(Ok(vec![commit("b", "second"), commit("a", "first")]));
+        log
+    }
+
+    #[test]
+    fn test_enter_shows_commit_then_opens_files_at_it() {
+        let mut log = log();
+        log.handle_key(AppKey::Down);
+        assert_eq!(
+            log.handle_key(AppKey::Enter),
+            GitLogOutcome::ShowCommit("aaaaaaaa".to_string())
+        );
+        log.show_commit(CommitDetail {
+            commit: commit("a", "first"),
+            body: String::new(),
+            files: vec![
+                CommitFile {
+                    path: PathBuf::from("/repo/gone.rs"),
+                    change: ChangeKind::Deleted,
+                },
+                CommitFile {
+                    path: PathBuf::from("/repo/lib.rs"),
+                    change: ChangeKind::Modified,
+                },
+            ],
+        });
+        assert_eq!(
+            log.handle_key(AppKey::Enter),
+            GitLogOutcome::OpenFile {
+                revision: "a^".to_string(),
+                path: PathBuf::from("/repo/gone.rs"),
+            }
+        );
+        log.handle_key(AppKey::Down);
+        assert_eq!(
+            log.handle_key(AppKey::Enter),
+            GitLogOutcome::OpenFile {
+                revision: "a".to_string(),
+                path: PathBuf::from("/repo/lib.rs"),
+            }
+        );
+
+        // Esc goes back to the list, where it falls through
+        assert_eq!(log.handle_key(AppKey::Esc), GitLogOutcome::Handled);
+        assert!(log.detail().is_none());
+        assert_eq!(log.handle_key(AppKey::Esc), GitLogOutcome::Ignored);
+    }
+
+    #[test]
+    fn test_refreshing_keeps_cursor_on_the_same_commit() {
+        let mut log = log();
+        log.handle_key(AppKey::Down);
+        log.set_commits(Ok(vec![
+            commit("c", "third"),
+            commit("b", "second"),
+            commit("a", "first"),
+        ]));
+        assert_eq!(log.selected().map(|c| c.subject.as_str()), Some("first"));
+
+        log.set_commits(Err("git failed".to_string()));
+        assert_eq!(log.error(), Some("git failed"));
+        assert!(log.selected().is_none());
+    }
+}
@@ -0,0 +1,316 @@
+//! Implementation of the git panel.
+
+use std::path::{Path, PathBuf};
+
+use cli_ide_platform::git::repository::{ChangeKind, DiffBase, StatusEntry};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Borders, Paragraph};
+
+use super::Window;
+use crate::input::AppKey;
+use crate::keybinding::WindowContext;
+use crate::list_selection::{reveal_row, ListSelection};
+use crate::theme::Theme;
+
+/// Result of feeding a key to the git panel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GitPanelOutcome {
+    /// The panel does not handle the key; it falls through to the bindings.
+    Ignored,
+    /// The panel consumed the key.
+    Handled,
+    /// The user asked to see the changes of this file against this base.
+    Open(PathBuf, DiffBase),
+}
+
+/// A file row: the group it is in and the index of its entry.
+type RowKey = (DiffBase, usize);
+
+/// A panel listing the changed files of a git repository.
+///
+/// Files are grouped into staged changes and changes in the work tree; a
+/// file with both appears in each group. `Up`/`Down` move between files
+/// and `Enter` opens the diff of the file under the cursor for its group.
+pub struct GitPanelWindow {
+    /// Top-level directory of the work tree; paths are shown relative to it.
+    root: PathBuf,
+    /// Changed files, in path order.
+    entries: Vec<StatusEntry>,
+    /// File rows and the cursor among them.
+    rows: ListSelection<RowKey>,
+    /// Why the status could not be read, if it could not.
+    error: Option<String>,
+    /// Index of the first visible line.
+    scroll_offset: usize,
+    /// Whether the next render scrolls the cursor into view.
+    follow_cursor: bool,
+    /// Styles to draw with.
+    theme: Theme,
+}
+
+impl GitPanelWindow {
+    /// Create an empty panel for the work tree at `root`.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            entries: Vec::new(),
+            rows: ListSelection::default(),
+            error: None,
+            scroll_offset: 0,
+            follow_cursor: true,
+            theme: Theme::default(),
+        }
+    }
+
+    /// Get the top-level directory of the work tree.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Get the changed files.
+    pub fn entries(&self) -> &[StatusEntry] {
+        &self.entries
+    }
+
+    /// Get why the status could not be read, if it could not.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Show the status of the work tree, or why it could not be read.
+    ///
+    /// The cursor stays on the same file and group if it is still listed.
+    pub fn set_status(&mut self, status: Result<Vec<StatusEntry>, String>) {
+        let current = self
+            .selected()
+            .map(|(path, base)| (path.to_path_buf(), base));
+        let (entries, error) = match status {
+            Ok(entries) => (entries, None),
+            Err(error) => (Vec::new(), Some(error)),
+        };
+        self.entries = entries;
+        self.error = error;
+        let group = |base: DiffBase| {
+            self.entries
+                .iter()
+                .enumerate()
+                .filter(move |(_, entry)| match base {
+                    DiffBase::Head => entry.staged.is_some(),
+                    DiffBase::Index => entry.unstaged.is_some(),
+                })
+                .map(move |(index, _)| (base, index))
+        };
+        let rows: Vec<RowKey> = group(DiffBase::Head)
+            .chain(group(DiffBase::Index))
+            .collect();
+        let cursor = current.and_then(|(path, base)| {
+            rows.iter()
+                .position(|&(row_base, index)| row_base == base && self.entries[index].path == path)
+        });
+        self.rows.set_items(rows);
+        if let Some(cursor) = cursor {
+            self.rows.set_cursor(cursor);
+        }
+        self.follow_cursor = true;
+    }
+
+    /// The file under the cursor and the group it is in.
+    pub fn selected(&self) -> Option<(&Path, DiffBase)> {
+        self.rows
+            .current()
+            .map(|&(base, index)| (self.entries[index].path.as_path(), base))
+    }
+
+    /// Handle a key press.
+    pub fn handle_key(&mut self, key: AppKey) -> GitPanelOutcome {
+        match key {
+            AppKey::Up => self.rows.move_by(-1),
+            AppKey::Down => self.rows.move_by(1),
+            AppKey::Enter => {
+                return match self.selected() {
+                    Some((path, base)) => GitPanelOutcome::Open(path.to_path_buf(), base),
+                    None => GitPanelOutcome::Handled,
+                };
+            }
+            _ => return GitPanelOutcome::Ignored,
+        }
+        self.follow_cursor = true;
+        GitPanelOutcome::Handled
+    }
+
+    /// The lines of the panel and the index of the cursor's line, which is
+    /// highlighted while `focused`.
+    fn lines(&self, focused: bool) -> (Vec<Line<'static>>, Option<usize>) {
+        if let Some(error) = &self.error {
+            return (vec![Line::styled(error.clone(), self.theme.overflow)], None);
+        }
+        if self.rows.is_empty() {
+            return (vec![Line::styled("No changes", self.theme.text)], None);
+        }
+        let mut lines = Vec::new();
+        let mut cursor_line = None;
+        let mut heading = None;
+        for (row, &(base, index)) in self.rows.items().iter().enumerate() {
+            if heading != Some(base) {
+                heading = Some(base);
+                let count = self
+                    .rows
+                    .items()
+                    .iter()
+                    .filter(|(row_base, _)| *row_base == base)
+                    .count();
+                let title = match base {
+                    DiffBase::Head => "Staged Changes",
+                    DiffBase::Index => "Changes",
+                };
+                lines.push(Line::styled(
+                    format!("{title} ({count})"),
+                    self.theme.text.add_modifier(Modifier::BOLD),
+                ));
+            }
+            let entry = &self.entries[index];
+            let change = match base {
+                DiffBase::Head => entry.staged,
+                DiffBase::Index => entry.unstaged,
+            };
+            let is_cursor = self.rows.cursor() == Some(row);
+            if is_cursor {
+                cursor_line = Some(lines.len());
+            }
+            let style = if focused && is_cursor {
+                self.theme.selection
+            } else {
+                self.theme.text
+            };
+            let path = entry.path.strip_prefix(&self.root).unwrap_or(&entry.path);
+            let letter = change.map_or(' ', ChangeKind::letter);
+            lines.push(Line::styled(
+                format!("  {letter} {}", path.display()),
+                style,
+            ));
+        }
+        (lines, cursor_line)
+    }
+}
+
+impl Window for GitPanelWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.render_with_focus(frame, area, false);
+    }
+
+    fn render_with_focus(&mut self, frame: &mut Frame, area: Rect, focused: bool) {
+        let border_type = if focused {
+            BorderType::Thick
+        } else {
+            BorderType::Plain
+        };
+        let title = if focused { "Git [*]" } else { "Git" };
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_type(border_type)
+            .border_style(self.theme.border_style(focused));
+        let height = usize::from(block.inner(area).height);
+
+        let (lines, cursor_line) = self.lines(focused);
+        if self.follow_cursor && height > 0 {
+            if let Some(cursor) = cursor_line {
+                self.scroll_offset = if cursor < self.scroll_offset {
+                    // Keep the group heading above the first file visible
+                    cursor.saturating_sub(1)
+                } else {
+                    reveal_row(self.scroll_offset, cursor, height)
+                };
+            }
+            self.follow_cursor = false;
+        }
+        let scroll_offset = self.scroll_offset.min(lines.len().saturating_sub(height));
+        let visible: Vec<Line> = lines.into_iter().skip(scroll_offset).take(height).collect();
+        self.scroll_offset = scroll_offset;
+        let paragraph = Paragraph::new(visible).style(self.theme.text).block(block);
+        frame.render_widget(paragraph, area);
+    }
+
+    fn scroll_by(&mut self, lines: i32) {
+        let offset = i64::try_from(self.scroll_offset).unwrap_or(i64::MAX) + i64::from(lines);
+        self.scroll_offset = usize::try_from(offset.max(0)).unwrap_or(usize::MAX);
+        self.follow_cursor = false;
+    }
+
+    fn context(&self) -> Option<WindowContext> {
+        Some(WindowContext::Git)
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, staged: Option<ChangeKind>, unstaged: Option<ChangeKind>) -> StatusEntry {
+        StatusEntry {
+            path: PathBuf::from("/repo").join(path),
+            staged,
+            unstaged,
+        }
+    }
+
+    fn panel() -> GitPanelWindow {
+        let mut panel = GitPanelWindow::new("/repo");
+        panel.set_status(Ok(vec![
+            entry(
+                "a.rs",
+                Some(ChangeKind::Modified),
+                Some(ChangeKind::Modified),
+            ),
+            entry("b.rs", None, Some(ChangeKind::Untracked)),
+        ]));
+        panel
+    }
+
+    #[test]
+    fn test_staged_files_are_listed_first_and_open_their_group() {
+        let mut panel = panel();
+        assert_eq!(
+            panel.handle_key(AppKey::Enter),
+            GitPanelOutcome::Open(PathBuf::from("/repo/a.rs"), DiffBase::Head)
+        );
+        panel.handle_key(AppKey::Down);
+        assert_eq!(
+            panel.selected(),
+            Some((Path::new("/repo/a.rs"), DiffBase::Index))
+        );
+        assert_eq!(
+            panel.handle_key(AppKey::Char('x')),
+            GitPanelOutcome::Ignored
+        );
+    }
+
+    #[test]
+    fn test_refreshing_keeps_cursor_on_the_same_file() {
+        let mut panel = panel();
+        panel.handle_key(AppKey::Down);
+        panel.handle_key(AppKey::Down);
+        assert_eq!(
+            panel.selected(),
+            Some((Path::new("/repo/b.rs"), DiffBase::Index))
+        );
+
+        // a.rs is no longer staged, so b.rs moves up a row
+        panel.set_status(Ok(vec![
+            entry("a.rs", None, Some(ChangeKind::Modified)),
+            entry("b.rs", None, Some(ChangeKind::Untracked)),
+        ]));
+        assert_eq!(
+            panel.selected(),
+            Some((Path::new("/repo/b.rs"), DiffBase::Index))
+        );
+
+        panel.set_status(Err("git failed".to_string()));
+        assert_eq!(panel.error(), Some("git failed"));
+        assert!(panel.selected().is_none());
+    }
+}
@@ -0,0 +1,226 @@
+//! Implementation of the input box overlay.
+
+use super::Window;
+use crate::completion::{self, Completer, Completion};
+use crate::input::AppKey;
+use crate::theme::Theme;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Borders, Paragraph};
+
+/// Height of the input box, including its border.
+pub const INPUT_BOX_HEIGHT: u16 = 3;
+
+/// Result of feeding a key to an input box.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputOutcome {
+    /// The input consumed the key and stays open.
+    Pending,
+    /// The user accepted this value.
+    Accept(String),
+    /// The user dismissed the input.
+    Dismissed,
+}
+
+/// A titled one-line text input, such as the "Save As" prompt.
+///
+/// Starts with an initial value; the first typed character replaces it.
+/// `Enter` accepts the value unless an error is shown and `Esc` dismisses.
+/// Editing the value clears the error. An input with a completer completes
+/// the value with `Tab`, listing the candidates in a popup below it while
+/// there are several.
+pub struct InputBoxWindow {
+    /// Title shown on the border.
+    title: String,
+    /// Current value.
+    value: String,
+    /// Whether the value is still the untouched initial value.
+    pristine: bool,
+    /// Message explaining why the value was rejected, if it was.
+    error: Option<String>,
+    /// Lists the values completing the typed one, if the input completes.
+    completer: Option<Box<dyn Completer>>,
+    /// Values `Tab` cycles through.
+    completion: Completion,
+    /// Styles to draw the completions with.
+    theme: Theme,
+}
+
+impl InputBoxWindow {
+    /// Create an input titled `title`, prefilled with `value`.
+    pub fn new(title: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            value: value.into(),
+            pristine: true,
+            error: None,
+            completer: None,
+            completion: Completion::default(),
+            theme: Theme::default(),
+        }
+    }
+
+    /// Complete the value with `completer` on `Tab`.
+    pub fn with_completer(mut self, completer: impl Completer + 'static) -> Self {
+        self.completer = Some(Box::new(completer));
+        self
+    }
+
+    /// Get the title.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Get the current value.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Get the error message, if any.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Show why the current value was rejected.
+    pub fn set_error(&mut self, error: Option<String>) {
+        self.error = error;
+    }
+
+    /// Get the values `Tab` cycles through, while there are several.
+    pub fn completions(&self) -> &[String] {
+        self.completion.candidates()
+    }
+
+    /// Handle a key press.
+    pub fn handle_key(&mut self, key: AppKey) -> InputOutcome {
+        match key {
+            AppKey::Esc => return InputOutcome::Dismissed,
+            AppKey::Enter if self.error.is_none() => {
+                return InputOutcome::Accept(self.value.clone());
+            }
+            AppKey::Backspace => {
+                self.value.pop();
+                self.pristine = false;
+                self.error = None;
+                self.completion.reset();
+            }
+            AppKey::Tab => self.complete(),
+            // `q` is translated to AppKey::Q; inside an input it is text.
+            AppKey::Q => self.type_char('q'),
+            AppKey::Char(c) => self.type_char(c),
+            _ => {}
+        }
+        InputOutcome::Pending
+    }
+
+    /// Append a typed character, replacing the untouched initial value.
+    fn type_char(&mut self, c: char) {
+        if self.pristine {
+            self.value.clear();
+            self.pristine = false;
+        }
+        self.value.push(c);
+        self.error = None;
+        self.completion.reset();
+    }
+
+    /// Show the next completion of the value as typed before the first
+    /// `Tab`.
+    fn complete(&mut self) {
+        let Some(completer) = &self.completer else {
+            return;
+        };
+        if let Some(value) = self.completion.next(&self.value, completer.as_ref()) {
+            self.value = value;
+            self.pristine = false;
+            self.error = None;
+        }
+    }
+}
+
+impl Window for InputBoxWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.render_with_focus(frame, area, true);
+    }
+
+    fn render_with_focus(&mut self, frame: &mut Frame, area: Rect, _focused: bool) {
+        let mut block = Block::default()
+            .title(self.title.as_str())
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded);
+        if let Some(error) = &self.error {
+            block = block
+                .border_style(Style::default().fg(Color::Red))
+                .title_bottom(error.as_str());
+        }
+        let value_style = if self.pristine {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default()
+        };
+        let paragraph = Paragraph::new(Line::styled(self.value.as_str(), value_style)).block(block);
+        frame.render_widget(paragraph, area);
+        completion::render_suggestions(
+            frame,
+            area,
+            false,
+            &self.completion,
+            completion::label,
+            &self.theme,
+        );
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::completion::WordCompleter;
+
+    #[test]
+    fn test_first_typed_char_replaces_initial_value() {
+        let mut input = InputBoxWindow::new("Save As", "Untitled-1");
+        input.handle_key(AppKey::Char('a'));
+        input.handle_key(AppKey::Q);
+        assert_eq!(input.value(), "aq");
+        assert_eq!(
+            input.handle_key(AppKey::Enter),
+            InputOutcome::Accept("aq".to_string())
+        );
+    }
+
+    #[test]
+    fn test_error_blocks_enter_until_edited() {
+        let mut input = InputBoxWindow::new("Save As", "a.txt");
+        input.set_error(Some("Permission denied".to_string()));
+        assert_eq!(input.handle_key(AppKey::Enter), InputOutcome::Pending);
+
+        input.handle_key(AppKey::Backspace);
+        assert_eq!(input.error(), None);
+        assert_eq!(
+            input.handle_key(AppKey::Enter),
+            InputOutcome::Accept("a.tx".to_string())
+        );
+        assert_eq!(input.handle_key(AppKey::Esc), InputOutcome::Dismissed);
+    }
+
+    #[test]
+    fn test_tab_completes_the_value() {
+        let branches = WordCompleter::new(["main", "master"]);
+        let mut input = InputBoxWindow::new("New Branch", "").with_completer(branches);
+        input.handle_key(AppKey::Char('m'));
+        input.handle_key(AppKey::Tab);
+        assert_eq!(input.value(), "main");
+        assert_eq!(input.completions(), ["main", "master"]);
+        input.handle_key(AppKey::Tab);
+        assert_eq!(input.value(), "master");
+        input.handle_key(AppKey::Backspace);
+        assert!(input.completions().is_empty(), "editing starts over");
+
+        let mut plain = InputBoxWindow::new("Stash Message", "wip");
+        plain.handle_key(AppKey::Tab);
+        assert_eq!(plain.value(), "wip");
+    }
+}
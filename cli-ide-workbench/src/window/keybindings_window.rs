@@ -0,0 +1,123 @@
+//! Implementation of the keybindings list.
+
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Borders, Paragraph};
+
+use super::{scroll_offset_by, Window};
+use crate::theme::Theme;
+
+/// A binding as the keybindings list shows it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeybindingEntry {
+    /// Where the binding is active, e.g. `Editor`; entries with the same
+    /// scope are listed together under it.
+    pub scope: String,
+    /// Name of the key.
+    pub key: String,
+    /// What the key does.
+    pub action: String,
+    /// The binding in a wider scope this one hides, if any.
+    pub shadows: Option<String>,
+}
+
+/// A read-only pane listing the active bindings grouped by where they
+/// apply, marking the ones that hide a binding of the same key in a wider
+/// scope.
+pub struct KeybindingsWindow {
+    /// The bindings, grouped by scope.
+    entries: Vec<KeybindingEntry>,
+    /// Index of the first visible line.
+    scroll_offset: u16,
+    /// Styles to draw with.
+    theme: Theme,
+}
+
+impl KeybindingsWindow {
+    /// Create a list of `entries`, which are grouped by scope.
+    pub fn new(entries: Vec<KeybindingEntry>) -> Self {
+        Self {
+            entries,
+            scroll_offset: 0,
+            theme: Theme::default(),
+        }
+    }
+
+    /// Get the listed bindings.
+    pub fn entries(&self) -> &[KeybindingEntry] {
+        &self.entries
+    }
+
+    /// The lines of the list: a heading per scope and a line per binding.
+    fn lines(&self) -> Vec<Line<'static>> {
+        if self.entries.is_empty() {
+            return vec![Line::styled("No keys are bound", self.theme.comment)];
+        }
+        let key_width = self
+            .entries
+            .iter()
+            .map(|entry| entry.key.chars().count())
+            .max()
+            .unwrap_or(0);
+        let mut lines = Vec::new();
+        let mut scope = None;
+        for entry in &self.entries {
+            if scope != Some(entry.scope.as_str()) {
+                if scope.is_some() {
+                    lines.push(Line::raw(""));
+                }
+                lines.push(Line::styled(entry.scope.clone(), self.theme.keyword));
+                scope = Some(entry.scope.as_str());
+            }
+            let mut spans = vec![
+                Span::styled(format!("  {:<key_width$}  ", entry.key), self.theme.comment),
+                Span::raw(entry.action.clone()),
+            ];
+            if let Some(shadows) = &entry.shadows {
+                spans.push(Span::styled(
+                    format!("  (hides {shadows})"),
+                    self.theme.warning,
+                ));
+            }
+            lines.push(Line::from(spans));
+        }
+        lines
+    }
+}
+
+impl Window for KeybindingsWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.render_with_focus(frame, area, false);
+    }
+
+    fn render_with_focus(&mut self, frame: &mut Frame, area: Rect, focused: bool) {
+        let border_type = if focused {
+            BorderType::Thick
+        } else {
+            BorderType::Plain
+        };
+        let title = if focused {
+            "Keybindings [*]"
+        } else {
+            "Keybindings"
+        };
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_type(border_type)
+            .border_style(self.theme.border_style(focused));
+        let paragraph = Paragraph::new(self.lines())
+            .style(self.theme.text)
+            .block(block)
+            .scroll((self.scroll_offset, 0));
+        frame.render_widget(paragraph, area);
+    }
+
+    fn scroll_by(&mut self, lines: i32) {
+        let last_line = self.lines().len().saturating_sub(1);
+        self.scroll_offset = scroll_offset_by(self.scroll_offset, lines, last_line);
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+}
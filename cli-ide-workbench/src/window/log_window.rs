@@ -0,0 +1,282 @@
+//! Implementation of the log viewer.
+
+use cli_ide_platform::logging::log_service::{LogLevel, LogRecord, MAX_RECORDS};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Paragraph};
+
+use super::{highlight_text, pane_borders, Window};
+use crate::grapheme;
+use crate::input::AppKey;
+use crate::keybinding::WindowContext;
+use crate::list_selection::{reveal_row, ListSelection};
+use crate::scrollbar::ScrollPosition;
+use crate::text_selection::TextSelection;
+use crate::theme::Theme;
+
+/// A read-only pane showing the records written to the log service.
+///
+/// Each record is one line, as it appears in the log file; errors and
+/// warnings are highlighted. Like the output pane, the view follows new
+/// records until it is scrolled up. Only the last [`MAX_RECORDS`] records
+/// are kept.
+///
+/// Records can be selected to copy them: `Up`/`Down` move the cursor and
+/// `Shift+Up`/`Shift+Down` and `Space` mark records. A drag selects the
+/// characters it passes over, from the cell it started on, and
+/// `Shift+Left`/`Shift+Right` select characters along the cursor's record.
+pub struct LogWindow {
+    /// The records, oldest first.
+    records: Vec<LogRecord>,
+    /// Number of records dropped from the top so far.
+    dropped: usize,
+    /// Rows keyed by the number of records before them ever appended, and
+    /// the cursor and marks among them.
+    rows: ListSelection<usize>,
+    /// Characters selected within or across records, by the same keys.
+    text: Option<TextSelection<usize>>,
+    /// Whether the next render scrolls the cursor into view.
+    follow_cursor: bool,
+    /// Index of the first visible record.
+    scroll_offset: usize,
+    /// Whether the view sticks to the last record.
+    follow: bool,
+    /// Number of records that fit, as of the last render.
+    height: usize,
+    /// Styles to draw with.
+    theme: Theme,
+    /// Whether the panel is drawn with only its top border.
+    compact: bool,
+}
+
+impl LogWindow {
+    /// Create a viewer showing `records`.
+    pub fn new(records: Vec<LogRecord>) -> Self {
+        let mut window = Self {
+            records: Vec::new(),
+            dropped: 0,
+            rows: ListSelection::default(),
+            text: None,
+            follow_cursor: false,
+            scroll_offset: 0,
+            follow: true,
+            height: 0,
+            theme: Theme::default(),
+            compact: false,
+        };
+        for record in records {
+            window.append(record);
+        }
+        window
+    }
+
+    /// Get the records, oldest first.
+    pub fn records(&self) -> &[LogRecord] {
+        &self.records
+    }
+
+    /// Append a record at the bottom.
+    ///
+    /// While the view follows new records, so does a cursor on the last
+    /// record.
+    pub fn append(&mut self, record: LogRecord) {
+        let on_last = self
+            .rows
+            .cursor()
+            .is_none_or(|cursor| cursor + 1 == self.rows.len());
+        self.records.push(record);
+        if self.records.len() > MAX_RECORDS {
+            let dropped = self.records.len() - MAX_RECORDS;
+            self.records.drain(..dropped);
+            self.dropped += dropped;
+            self.scroll_offset = self.scroll_offset.saturating_sub(dropped);
+        }
+        self.rows
+            .set_items((self.dropped..self.dropped + self.records.len()).collect());
+        if self.follow && on_last {
+            self.rows.set_cursor(self.records.len() - 1);
+        }
+    }
+
+    /// Get the record under the cursor.
+    pub fn selected(&self) -> Option<&LogRecord> {
+        self.rows
+            .current()
+            .map(|&key| &self.records[key - self.dropped])
+    }
+
+    /// Handle a key press, returning whether it was consumed.
+    ///
+    /// Moving the cursor scrolls it into view; the view follows new
+    /// records again once the cursor is on the last one.
+    pub fn handle_key(&mut self, key: AppKey) -> bool {
+        if let Some(&row) = self.rows.current() {
+            let line = self.row_text(row);
+            if TextSelection::extend_on(&mut self.text, row, &line, key) {
+                return true;
+            }
+        }
+        if !self.rows.handle_key(key) {
+            return false;
+        }
+        self.text = None;
+        self.follow = self
+            .rows
+            .cursor()
+            .is_some_and(|cursor| cursor + 1 == self.rows.len());
+        self.follow_cursor = true;
+        true
+    }
+
+    /// Whether the view sticks to the last record.
+    pub fn is_following(&self) -> bool {
+        self.follow
+    }
+
+    /// The text of the record keyed `key`.
+    fn row_text(&self, key: usize) -> String {
+        self.records[key - self.dropped].to_string()
+    }
+
+    /// The largest useful scroll offset for the last rendered height.
+    fn max_offset(&self) -> usize {
+        self.records.len().saturating_sub(self.height.max(1))
+    }
+
+    /// The style of a record at `level`.
+    fn level_style(&self, level: LogLevel) -> Style {
+        match level {
+            LogLevel::Error => self.theme.error,
+            LogLevel::Warn => self.theme.warning,
+            LogLevel::Info | LogLevel::Debug | LogLevel::Trace => self.theme.text,
+        }
+    }
+}
+
+impl Window for LogWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.render_with_focus(frame, area, false);
+    }
+
+    fn render_with_focus(&mut self, frame: &mut Frame, area: Rect, focused: bool) {
+        let border_type = if focused {
+            BorderType::Thick
+        } else {
+            BorderType::Plain
+        };
+        let title = if focused { "Logs [*]" } else { "Logs" };
+        let block = Block::default()
+            .title(title)
+            .borders(pane_borders(self.compact))
+            .border_type(border_type)
+            .border_style(self.theme.border_style(focused));
+        self.height = usize::from(block.inner(area).height);
+
+        if self.follow_cursor && !self.follow {
+            if let Some(cursor) = self.rows.cursor() {
+                self.scroll_offset = reveal_row(self.scroll_offset, cursor, self.height);
+            }
+        }
+        self.follow_cursor = false;
+        self.scroll_offset = if self.follow {
+            self.max_offset()
+        } else {
+            self.scroll_offset.min(self.max_offset())
+        };
+        let selecting = self.text.as_ref().is_some_and(|text| !text.is_empty());
+        let cursor = self.rows.cursor().filter(|_| focused && !selecting);
+        let visible: Vec<Line> = self
+            .records
+            .iter()
+            .zip(self.rows.items())
+            .enumerate()
+            .skip(self.scroll_offset)
+            .take(self.height)
+            .map(|(row, (record, key))| {
+                let mut style = self.level_style(record.level);
+                if cursor == Some(row) || self.rows.is_marked(key) {
+                    style = style.patch(self.theme.selection);
+                }
+                highlight_text(
+                    Line::styled(record.to_string(), style),
+                    self.text.as_ref(),
+                    self.rows.items(),
+                    row,
+                    self.theme.selection,
+                )
+            })
+            .collect();
+        let paragraph = Paragraph::new(visible).style(self.theme.text).block(block);
+        frame.render_widget(paragraph, area);
+    }
+
+    fn scroll_by(&mut self, lines: i32) {
+        let start = if self.follow {
+            self.max_offset()
+        } else {
+            self.scroll_offset
+        };
+        let offset = i64::try_from(start).unwrap_or(i64::MAX) + i64::from(lines);
+        self.scroll_offset = usize::try_from(offset.max(0))
+            .unwrap_or(usize::MAX)
+            .min(self.max_offset());
+        self.follow = self.scroll_offset >= self.max_offset();
+    }
+
+    fn select_at(&mut self, row: u16, column: u16, extend: bool) {
+        let row = self.scroll_offset + usize::from(row);
+        let Some(row) = self.rows.len().checked_sub(1).map(|last| row.min(last)) else {
+            return;
+        };
+        let key = self.rows.items()[row];
+        let line = self.row_text(key);
+        let point = grapheme::index_at_display_column(&line, usize::from(column));
+        TextSelection::click(&mut self.text, key, point, extend);
+        if extend {
+            self.rows.set_cursor(row);
+        } else {
+            self.rows.click(row, false);
+        }
+        self.follow = false;
+    }
+
+    fn selected_text(&self) -> Option<String> {
+        let selected = self
+            .text
+            .as_ref()
+            .and_then(|text| text.text(self.rows.items(), |&key| self.row_text(key)));
+        if selected.is_some() {
+            return selected;
+        }
+        let lines: Vec<String> = self
+            .rows
+            .targets()
+            .into_iter()
+            .map(|key| self.row_text(key))
+            .collect();
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+
+    fn scroll_position(&self) -> Option<ScrollPosition> {
+        let offset = if self.follow {
+            self.max_offset()
+        } else {
+            self.scroll_offset
+        };
+        Some(ScrollPosition {
+            offset,
+            content: self.records.len(),
+        })
+    }
+
+    fn context(&self) -> Option<WindowContext> {
+        Some(WindowContext::Logs)
+    }
+
+    fn set_compact(&mut self, compact: bool) {
+        self.compact = compact;
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+}
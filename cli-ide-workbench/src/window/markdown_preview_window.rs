@@ -0,0 +1,277 @@
+//! Implementation of the Markdown preview.
+
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Paragraph};
+
+use super::{highlight_text, line_text, pane_borders, Window};
+use crate::grapheme;
+use crate::input::AppKey;
+use crate::keybinding::WindowContext;
+use crate::list_selection::{reveal_row, ListSelection};
+use crate::markdown::{self, MarkdownLine, MarkdownStyle};
+use crate::scrollbar::ScrollPosition;
+use crate::text_selection::TextSelection;
+use crate::theme::Theme;
+
+/// A read-only pane showing an editor's document rendered as Markdown,
+/// titled `Preview: file`.
+///
+/// The App calls [`MarkdownPreviewWindow::follow`] with the last focused
+/// editor's document after every event, so the preview keeps up with
+/// typing. As in the log viewer, `Up`/`Down` move the cursor and
+/// `Shift+Up`/`Shift+Down` and `Space` mark lines to copy, and a drag or
+/// `Shift+Left`/`Shift+Right` select characters of the rendered text.
+pub struct MarkdownPreviewWindow {
+    /// Title of the followed document.
+    title: String,
+    /// The source of the rendered lines.
+    source: String,
+    /// The rendered lines.
+    lines: Vec<MarkdownLine>,
+    /// Indices of the lines and the cursor and marks among them.
+    rows: ListSelection<usize>,
+    /// Characters selected within or across lines.
+    text: Option<TextSelection<usize>>,
+    /// Index of the first visible line.
+    scroll_offset: usize,
+    /// Whether the next render scrolls the cursor into view.
+    follow_cursor: bool,
+    /// Styles to draw with.
+    theme: Theme,
+    /// Whether the pane is drawn with only its top border.
+    compact: bool,
+}
+
+impl Default for MarkdownPreviewWindow {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MarkdownPreviewWindow {
+    /// Create a preview showing nothing until it follows an editor.
+    pub fn new() -> Self {
+        Self {
+            title: "Preview".to_string(),
+            source: String::new(),
+            lines: Vec::new(),
+            rows: ListSelection::default(),
+            text: None,
+            scroll_offset: 0,
+            follow_cursor: false,
+            theme: Theme::default(),
+            compact: false,
+        }
+    }
+
+    /// Show `source`, the document titled `title`, rendered.
+    ///
+    /// The source is only rendered again when it changed; then the
+    /// selected characters are dropped and the cursor stays on the same
+    /// line, clamped.
+    pub fn follow(&mut self, title: &str, source: &str) {
+        self.title = format!("Preview: {title}");
+        if self.source == source {
+            return;
+        }
+        self.source = source.to_string();
+        self.lines = markdown::render(source);
+        self.rows.set_items((0..self.lines.len()).collect());
+        self.text = None;
+    }
+
+    /// Get the text of the rendered lines, without their styles.
+    pub fn lines(&self) -> Vec<String> {
+        (0..self.lines.len())
+            .map(|row| self.row_text(row))
+            .collect()
+    }
+
+    /// Handle a key press, returning whether it was consumed.
+    pub fn handle_key(&mut self, key: AppKey) -> bool {
+        if let Some(&row) = self.rows.current() {
+            let line = self.row_text(row);
+            if TextSelection::extend_on(&mut self.text, row, &line, key) {
+                return true;
+            }
+        }
+        if !self.rows.handle_key(key) {
+            return false;
+        }
+        self.text = None;
+        self.follow_cursor = true;
+        true
+    }
+
+    /// The text of the line `row`.
+    fn row_text(&self, row: usize) -> String {
+        line_text(&self.line(row, Style::default()))
+    }
+
+    /// The line `row`, drawn in `style` under the styles of its spans.
+    fn line(&self, row: usize, style: Style) -> Line<'static> {
+        let spans: Vec<Span<'static>> = self.lines[row]
+            .iter()
+            .map(|span| Span::styled(span.text.clone(), self.span_style(span.style).patch(style)))
+            .collect();
+        Line::from(spans)
+    }
+
+    /// The style text rendered from `style` is drawn in.
+    fn span_style(&self, style: MarkdownStyle) -> Style {
+        match style {
+            MarkdownStyle::Text => self.theme.text,
+            MarkdownStyle::Heading => self.theme.keyword.add_modifier(Modifier::BOLD),
+            MarkdownStyle::Strong => self.theme.text.add_modifier(Modifier::BOLD),
+            MarkdownStyle::Emphasis => self.theme.text.add_modifier(Modifier::ITALIC),
+            MarkdownStyle::Code => self.theme.string,
+            MarkdownStyle::Link => self.theme.link,
+            MarkdownStyle::Quote => self.theme.comment,
+            MarkdownStyle::Marker => self.theme.type_name,
+        }
+    }
+}
+
+impl Window for MarkdownPreviewWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.render_with_focus(frame, area, false);
+    }
+
+    fn render_with_focus(&mut self, frame: &mut Frame, area: Rect, focused: bool) {
+        let border_type = if focused {
+            BorderType::Thick
+        } else {
+            BorderType::Plain
+        };
+        let title = if focused {
+            format!("{} [*]", self.title)
+        } else {
+            self.title.clone()
+        };
+        let block = Block::default()
+            .title(title)
+            .borders(pane_borders(self.compact))
+            .border_type(border_type)
+            .border_style(self.theme.border_style(focused));
+        let height = usize::from(block.inner(area).height);
+
+        if self.follow_cursor && height > 0 {
+            if let Some(cursor) = self.rows.cursor() {
+                self.scroll_offset = reveal_row(self.scroll_offset, cursor, height);
+            }
+            self.follow_cursor = false;
+        }
+        self.scroll_offset = self
+            .scroll_offset
+            .min(self.lines.len().saturating_sub(height));
+
+        let selecting = self.text.as_ref().is_some_and(|text| !text.is_empty());
+        let cursor = self.rows.cursor().filter(|_| focused && !selecting);
+        let visible: Vec<Line> = (self.scroll_offset..self.lines.len())
+            .take(height)
+            .map(|row| {
+                let style = if cursor == Some(row) || self.rows.is_marked(&row) {
+                    self.theme.selection
+                } else {
+                    Style::default()
+                };
+                highlight_text(
+                    self.line(row, style),
+                    self.text.as_ref(),
+                    self.rows.items(),
+                    row,
+                    self.theme.selection,
+                )
+            })
+            .collect();
+        let paragraph = Paragraph::new(visible).style(self.theme.text).block(block);
+        frame.render_widget(paragraph, area);
+    }
+
+    fn scroll_by(&mut self, lines: i32) {
+        let offset = i64::try_from(self.scroll_offset).unwrap_or(i64::MAX) + i64::from(lines);
+        self.scroll_offset = usize::try_from(offset.max(0)).unwrap_or(usize::MAX);
+        self.follow_cursor = false;
+    }
+
+    fn select_at(&mut self, row: u16, column: u16, extend: bool) {
+        let row = self.scroll_offset + usize::from(row);
+        let Some(row) = self.rows.len().checked_sub(1).map(|last| row.min(last)) else {
+            return;
+        };
+        let line = self.row_text(row);
+        let point = grapheme::index_at_display_column(&line, usize::from(column));
+        TextSelection::click(&mut self.text, row, point, extend);
+        if extend {
+            self.rows.set_cursor(row);
+        } else {
+            self.rows.click(row, false);
+        }
+    }
+
+    fn selected_text(&self) -> Option<String> {
+        let selected = self
+            .text
+            .as_ref()
+            .and_then(|text| text.text(self.rows.items(), |&row| self.row_text(row)));
+        if selected.is_some() {
+            return selected;
+        }
+        let lines: Vec<String> = self
+            .rows
+            .targets()
+            .into_iter()
+            .map(|row| self.row_text(row))
+            .collect();
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+
+    fn scroll_position(&self) -> Option<ScrollPosition> {
+        Some(ScrollPosition {
+            offset: self.scroll_offset,
+            content: self.lines.len(),
+        })
+    }
+
+    fn context(&self) -> Option<WindowContext> {
+        Some(WindowContext::MarkdownPreview)
+    }
+
+    fn set_compact(&mut self, compact: bool) {
+        self.compact = compact;
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_follow_renders_and_selects_rendered_text() {
+        let mut preview = MarkdownPreviewWindow::new();
+        preview.follow("README.md", "# Title\n\nSome **bold** text\n");
+        assert_eq!(preview.lines(), ["Title", "", "Some bold text"]);
+
+        // A drag selects the rendered characters, not the markers
+        preview.select_at(2, 5, false);
+        preview.select_at(2, 9, true);
+        assert_eq!(preview.selected_text().unwrap(), "bold");
+
+        // Keys move the cursor and copy whole lines again
+        assert!(preview.handle_key(AppKey::Up));
+        assert!(preview.handle_key(AppKey::ShiftUp));
+        assert_eq!(preview.selected_text().unwrap(), "Title\n");
+        assert!(preview.handle_key(AppKey::ShiftRight));
+        assert_eq!(preview.selected_text().unwrap(), "T");
+
+        // An unchanged source keeps the selection; a new one drops it
+        preview.follow("README.md", "# Title\n\nSome **bold** text\n");
+        assert_eq!(preview.selected_text().unwrap(), "T");
+        preview.follow("README.md", "# Other\n");
+        assert_eq!(preview.selected_text().unwrap(), "Other");
+    }
+}
@@ -0,0 +1,101 @@
+//! Implementation of the presentation mirror.
+
+use std::rc::Rc;
+
+use ratatui::prelude::*;
+
+use super::{EditorWindow, Window};
+use crate::theme::Theme;
+
+/// A read-only copy of an editor's view for presenting or pairing: the same
+/// document, selection and scroll position, titled `Mirror: file`.
+///
+/// The App calls [`MirrorWindow::follow`] with the last focused editor after
+/// every event. The mirror has no keybinding context and the App sends it no
+/// keys, so it can be put on a second screen without anyone typing into it.
+pub struct MirrorWindow {
+    /// The copy drawn, showing the followed editor's buffer.
+    view: EditorWindow,
+}
+
+impl Default for MirrorWindow {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MirrorWindow {
+    /// Create a mirror showing nothing until it follows an editor.
+    pub fn new() -> Self {
+        let mut view = EditorWindow::with_text("");
+        view.set_title("Mirror");
+        Self { view }
+    }
+
+    /// Get the copy of the followed editor.
+    pub fn view(&self) -> &EditorWindow {
+        &self.view
+    }
+
+    /// Show what `source` shows: its active document, selection and scroll
+    /// position.
+    ///
+    /// While the document stays the same only the selection and scroll
+    /// position are copied, so the highlighting is not redone.
+    pub fn follow(&mut self, source: &EditorWindow) {
+        let title = format!("Mirror: {}", source.group().active().title());
+        if Rc::ptr_eq(self.view.buffer(), source.buffer())
+            && self.view.language() == source.language()
+        {
+            self.view
+                .select(source.selection_anchor(), source.selection_head());
+            self.view.set_scroll_offset(source.viewport().top_line);
+        } else {
+            self.view = source.split_view();
+        }
+        self.view.set_title(title);
+    }
+}
+
+impl Window for MirrorWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.render_with_focus(frame, area, false);
+    }
+
+    fn render_with_focus(&mut self, frame: &mut Frame, area: Rect, focused: bool) {
+        self.view.render_with_focus(frame, area, focused);
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.view.set_theme(theme);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_follow_copies_the_document_selection_and_scroll() {
+        let text: String = (1..=40).map(|line| format!("line {line}\n")).collect();
+        let mut source = EditorWindow::with_text(text);
+        source.select(12, 16);
+        source.set_scroll_offset(5);
+        let mut mirror = MirrorWindow::new();
+        mirror.follow(&source);
+        assert!(Rc::ptr_eq(mirror.view().buffer(), source.buffer()));
+        assert_eq!(mirror.view().selection(), 12..16);
+        assert_eq!(mirror.view().scroll_offset(), 5);
+
+        source.select(3, 0);
+        source.set_scroll_offset(20);
+        mirror.follow(&source);
+        assert_eq!(mirror.view().scroll_offset(), 20);
+        assert_eq!(mirror.view().selection_head(), 0);
+
+        let other = EditorWindow::with_text("other");
+        mirror.follow(&other);
+        assert_eq!(mirror.view().text(), "other");
+        assert_eq!(mirror.view().scroll_offset(), 0);
+    }
+}
@@ -1,25 +1,125 @@
 //! Window abstractions for the IDE.
 //!
 //! A `Window` can render itself onto a [`ratatui::Frame`].  Concrete
-//! implementations include an `EditorWindow` and a `TerminalWindow`.  In
-//! future phases the window system will support layouts, split panes, and
-//! tiling algorithms.
+//! implementations include an `EditorWindow`, a `TerminalWindow`, the
+//! `StatusBarWindow`, the `FileTreeWindow` explorer, the `OutputWindow`,
+//! `ProblemsWindow`, `DiagnosticsWindow`, `UndoTreeWindow`, `KeybindingsWindow`, `SnippetsWindow`, `TodosWindow` and `MarkdownPreviewWindow` panes, the
+//! `ProgressToastWindow` and `NotificationToastWindow` toasts, the `NotificationsWindow` notification
+//! center, the `ContextMenuWindow` popup and modal overlays built
+//! on `QuickPickWindow` such as the `CommandPaletteWindow`. Open windows are owned and laid out by the
+//! [`WindowManager`](crate::window_manager::WindowManager).
+
+use std::any::Any;
+use std::ops::Range;
 
 use ratatui::prelude::*;
+use ratatui::widgets::Borders;
+
+use crate::keybinding::WindowContext;
+use crate::scrollbar::ScrollPosition;
+use crate::text_selection::TextSelection;
+use crate::theme::Theme;
 
+mod command_line_window;
+mod command_palette_window;
+mod confirm_window;
+mod context_menu_window;
+mod diagnostics_window;
+mod diff_window;
 mod editor_window;
+mod file_tree_window;
+mod find_window;
+mod git_log_window;
+mod git_panel_window;
+mod input_box_window;
+mod keybindings_window;
+mod log_window;
+mod markdown_preview_window;
+mod mirror_window;
+mod notification_toast_window;
+mod notifications_window;
+mod output_window;
+mod peek_window;
+mod problems_window;
+mod progress_toast_window;
+mod quick_pick_window;
+mod regex_tester_window;
+mod rename_input_window;
+mod search_results_window;
+mod search_window;
+mod snippets_window;
+mod status_bar_window;
+mod terminal_report_window;
 mod terminal_window;
+mod todos_window;
+mod tutorial_window;
+mod undo_tree_window;
 mod window_id;
 
 #[cfg(test)]
 mod snapshot_tests;
 
-pub use editor_window::EditorWindow;
+pub use command_line_window::CommandLineWindow;
+pub use command_palette_window::{CommandPaletteWindow, PaletteOutcome};
+pub use confirm_window::{ConfirmOutcome, ConfirmWindow};
+pub use context_menu_window::{ContextMenuOutcome, ContextMenuWindow};
+pub use diagnostics_window::DiagnosticsWindow;
+pub use diff_window::{DiffLayout, DiffSubject, DiffWindow};
+pub use editor_window::{Decoration, DecorationKind, EditorWindow};
+pub use file_tree_window::{FileTreeOutcome, FileTreeWindow, TreeRow};
+pub use find_window::{FindField, FindOutcome, FindWindow, FIND_HEIGHT, FIND_WIDTH};
+pub use git_log_window::{GitLogOutcome, GitLogWindow};
+pub use git_panel_window::{GitPanelOutcome, GitPanelWindow};
+pub use input_box_window::{InputBoxWindow, InputOutcome, INPUT_BOX_HEIGHT};
+pub use keybindings_window::{KeybindingEntry, KeybindingsWindow};
+pub use log_window::LogWindow;
+pub use markdown_preview_window::MarkdownPreviewWindow;
+pub use mirror_window::MirrorWindow;
+pub use notification_toast_window::{NotificationToastWindow, NOTIFICATION_HEIGHT};
+pub use notifications_window::NotificationsWindow;
+pub use output_window::OutputWindow;
+pub use peek_window::{PeekWindow, PEEK_HEIGHT};
+pub use problems_window::{ProblemsOutcome, ProblemsWindow};
+pub use progress_toast_window::{ProgressToastWindow, TOAST_HEIGHT, TOAST_WIDTH};
+pub use quick_pick_window::{QuickPickOutcome, QuickPickWindow};
+pub use regex_tester_window::{RegexField, RegexTesterOutcome, RegexTesterWindow};
+pub use rename_input_window::{RenameInputWindow, RenameOutcome};
+pub use search_results_window::{SearchResultsField, SearchResultsOutcome, SearchResultsWindow};
+pub use search_window::{SearchField, SearchOutcome, SearchWindow};
+pub use snippets_window::{SnippetsOutcome, SnippetsWindow};
+pub use status_bar_window::{
+    StatusBarAlignment, StatusBarItem, StatusBarTextProvider, StatusBarUpdate, StatusBarWindow,
+};
+pub use terminal_report_window::TerminalReportWindow;
 pub use terminal_window::TerminalWindow;
+pub use todos_window::{TodosOutcome, TodosWindow};
+pub use tutorial_window::{TutorialWindow, TUTORIAL_HEIGHT, TUTORIAL_WIDTH};
+pub use undo_tree_window::{UndoTreeOutcome, UndoTreeWindow};
 pub use window_id::WindowId;
 
+/// Access to a value as [`Any`], so boxed windows can be downcast.
+///
+/// Implemented for every `'static` type; window implementations never need
+/// to implement it themselves.
+pub trait AsAny {
+    /// Get `self` as `&dyn Any`.
+    fn as_any(&self) -> &dyn Any;
+    /// Get `self` as `&mut dyn Any`.
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Any> AsAny for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
 /// A trait representing a drawable window.
-pub trait Window {
+pub trait Window: AsAny {
     /// Render the window onto the given frame in the specified area.
     ///
     /// The `area` indicates the rectangular region of the terminal where the
@@ -38,4 +138,134 @@ pub trait Window {
         let _ = focused; // Default implementation ignores focus
         self.render(frame, area);
     }
+
+    /// Scroll the window's content by `lines` (negative scrolls up).
+    ///
+    /// Default implementation ignores scrolling.
+    fn scroll_by(&mut self, lines: i32) {
+        let _ = lines; // Default implementation does not scroll
+    }
+
+    /// How far the content is scrolled, for the window's scrollbar.
+    ///
+    /// Default implementation has no scrollbar.
+    fn scroll_position(&self) -> Option<ScrollPosition> {
+        None
+    }
+
+    /// Select at the cell `column` cells right of and `row` rows below the
+    /// top left of the window's inner area, or with `extend` extend the
+    /// selection to it. A click on the window selects and a drag extends.
+    /// List views select the row; read-only text selects from the cell.
+    ///
+    /// Default implementation has no selectable text.
+    fn select_at(&mut self, row: u16, column: u16, extend: bool) {
+        let _ = (row, column, extend); // Default implementation selects nothing
+    }
+
+    /// The selected text of a read-only window, for copying, or `None` if
+    /// the window has none.
+    ///
+    /// Default implementation has no selectable text.
+    fn selected_text(&self) -> Option<String> {
+        None
+    }
+
+    /// Draw compact, with a top border only and without gutters or
+    /// underlined problems, or undo that with `false`.
+    ///
+    /// Default implementation ignores compact mode.
+    fn set_compact(&mut self, compact: bool) {
+        let _ = compact; // Default implementation is always drawn the same
+    }
+
+    /// The keybinding context that applies while this window has focus.
+    ///
+    /// Default implementation has no context, so only global bindings apply.
+    fn context(&self) -> Option<WindowContext> {
+        None
+    }
+
+    /// Switch to the styles of `theme`.
+    ///
+    /// Default implementation ignores themes.
+    fn set_theme(&mut self, theme: &Theme) {
+        let _ = theme; // Default implementation keeps its own styles
+    }
+
+    /// Whether the window's content changed on its own since it was last
+    /// rendered, e.g. the next frame of an animation or output arriving
+    /// from another thread. Changes made by the App's event handling need
+    /// no report; the App redraws after those anyway.
+    ///
+    /// Default implementation only changes through the App.
+    fn needs_redraw(&self) -> bool {
+        false
+    }
+}
+
+/// The borders of a pane: all four, or in compact mode only the top one,
+/// which carries the title.
+pub fn pane_borders(compact: bool) -> Borders {
+    if compact {
+        Borders::TOP
+    } else {
+        Borders::ALL
+    }
+}
+
+/// The text of `line`, without its styles.
+fn line_text(line: &Line) -> String {
+    line.spans
+        .iter()
+        .map(|span| span.content.as_ref())
+        .collect()
+}
+
+/// `line`, the row shown `row` rows down `rows`, with the characters
+/// `selection` covers on it patched with `style`.
+fn highlight_text<K: PartialEq + Clone>(
+    line: Line<'static>,
+    selection: Option<&TextSelection<K>>,
+    rows: &[K],
+    row: usize,
+    style: Style,
+) -> Line<'static> {
+    match selection.and_then(|selection| selection.columns(rows, row, &line_text(&line))) {
+        Some(range) => highlight_range(line, range, style),
+        None => line,
+    }
+}
+
+/// `line` with the bytes `range` of its text patched with `style`.
+fn highlight_range(line: Line<'static>, range: Range<usize>, style: Style) -> Line<'static> {
+    let mut spans = Vec::new();
+    let mut start = 0;
+    for span in line.spans {
+        let text = span.content.as_ref();
+        let end = start + text.len();
+        let from = range.start.clamp(start, end) - start;
+        let to = range.end.clamp(start, end) - start;
+        let parts = [(0..from, false), (from..to, true), (to..text.len(), false)];
+        for (part, selected) in parts.map(|(part, selected)| (&text[part], selected)) {
+            if part.is_empty() {
+                continue;
+            }
+            let part_style = if selected {
+                span.style.patch(style)
+            } else {
+                span.style
+            };
+            spans.push(Span::styled(part.to_string(), part_style));
+        }
+        start = end;
+    }
+    Line::from(spans).style(line.style)
+}
+
+/// Apply a scroll delta to `offset`, keeping it within `0..=last_line`.
+fn scroll_offset_by(offset: u16, lines: i32, last_line: usize) -> u16 {
+    let max = u16::try_from(last_line).unwrap_or(u16::MAX);
+    let next = i32::from(offset).saturating_add(lines).max(0);
+    u16::try_from(next).unwrap_or(u16::MAX).min(max)
 }
@@ -0,0 +1,69 @@
+//! Implementation of the notification toast.
+
+use cli_ide_platform::notification::notification_service::{Notification, NotificationSeverity};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Borders, Paragraph};
+
+use super::Window;
+use crate::theme::Theme;
+
+/// Height of a notification toast, including its border.
+pub const NOTIFICATION_HEIGHT: u16 = 3;
+
+/// A small box showing one notification over the bottom right of the
+/// workbench.
+///
+/// The border is titled with the notification's source and drawn in the
+/// style of its severity; the message fills the single row inside.
+pub struct NotificationToastWindow {
+    /// The notification shown.
+    notification: Notification,
+    /// Styles to draw with.
+    theme: Theme,
+}
+
+impl NotificationToastWindow {
+    /// Create a toast showing `notification`.
+    pub fn new(notification: Notification) -> Self {
+        Self {
+            notification,
+            theme: Theme::default(),
+        }
+    }
+
+    /// Get the notification shown.
+    pub fn notification(&self) -> &Notification {
+        &self.notification
+    }
+}
+
+/// The style of a notification with `severity`.
+pub(crate) fn severity_style(theme: &Theme, severity: NotificationSeverity) -> Style {
+    match severity {
+        NotificationSeverity::Error => theme.error,
+        NotificationSeverity::Warning => theme.warning,
+        NotificationSeverity::Info => theme.border,
+    }
+}
+
+impl Window for NotificationToastWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.render_with_focus(frame, area, false);
+    }
+
+    fn render_with_focus(&mut self, frame: &mut Frame, area: Rect, _focused: bool) {
+        let block = Block::default()
+            .title(self.notification.source.as_str())
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(severity_style(&self.theme, self.notification.severity));
+        let paragraph = Paragraph::new(self.notification.message.as_str())
+            .style(self.theme.text)
+            .block(block);
+        frame.render_widget(paragraph, area);
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+}
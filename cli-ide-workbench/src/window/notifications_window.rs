@@ -0,0 +1,118 @@
+//! Implementation of the notification center.
+
+use cli_ide_platform::notification::notification_service::Notification;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Borders, Paragraph};
+
+use super::notification_toast_window::severity_style;
+use super::{scroll_offset_by, Window};
+use crate::theme::Theme;
+
+/// A read-only pane listing the notifications the notification service
+/// kept, newest first.
+///
+/// Each notification is one line with its severity, source and message.
+/// Notifications the filter kept from being shown as toasts are listed too,
+/// marked `(muted)`, and the title says when "do not disturb" is on.
+pub struct NotificationsWindow {
+    /// The notifications, oldest first.
+    notifications: Vec<Notification>,
+    /// Whether "do not disturb" is on.
+    do_not_disturb: bool,
+    /// Index of the first visible line.
+    scroll_offset: u16,
+    /// Styles to draw with.
+    theme: Theme,
+}
+
+impl NotificationsWindow {
+    /// Create a pane listing `notifications`.
+    pub fn new(notifications: Vec<Notification>) -> Self {
+        Self {
+            notifications,
+            do_not_disturb: false,
+            scroll_offset: 0,
+            theme: Theme::default(),
+        }
+    }
+
+    /// Get the notifications, oldest first.
+    pub fn notifications(&self) -> &[Notification] {
+        &self.notifications
+    }
+
+    /// Replace the notifications listed.
+    pub fn set_notifications(&mut self, notifications: Vec<Notification>) {
+        self.notifications = notifications;
+    }
+
+    /// Set whether the title says "do not disturb" is on.
+    pub fn set_do_not_disturb(&mut self, on: bool) {
+        self.do_not_disturb = on;
+    }
+
+    /// The line listing `notification`.
+    fn line<'a>(&self, notification: &'a Notification) -> Line<'a> {
+        let mut spans = vec![
+            Span::styled(
+                format!("{:<8}", notification.severity),
+                severity_style(&self.theme, notification.severity),
+            ),
+            Span::raw(format!("{}: ", notification.source)),
+            Span::raw(notification.message.as_str()),
+        ];
+        if notification.suppressed {
+            spans.push(Span::styled(" (muted)", self.theme.comment));
+        }
+        Line::from(spans)
+    }
+}
+
+impl Window for NotificationsWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.render_with_focus(frame, area, false);
+    }
+
+    fn render_with_focus(&mut self, frame: &mut Frame, area: Rect, focused: bool) {
+        let border_type = if focused {
+            BorderType::Thick
+        } else {
+            BorderType::Plain
+        };
+        let mut title = format!("Notifications ({})", self.notifications.len());
+        if self.do_not_disturb {
+            title.push_str(" - Do Not Disturb");
+        }
+        if focused {
+            title.push_str(" [*]");
+        }
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_type(border_type)
+            .border_style(self.theme.border_style(focused));
+        let lines: Vec<Line> = if self.notifications.is_empty() {
+            vec![Line::styled("No notifications", self.theme.comment)]
+        } else {
+            self.notifications
+                .iter()
+                .rev()
+                .map(|notification| self.line(notification))
+                .collect()
+        };
+        let paragraph = Paragraph::new(lines)
+            .style(self.theme.text)
+            .block(block)
+            .scroll((self.scroll_offset, 0));
+        frame.render_widget(paragraph, area);
+    }
+
+    fn scroll_by(&mut self, lines: i32) {
+        let last_line = self.notifications.len().saturating_sub(1);
+        self.scroll_offset = scroll_offset_by(self.scroll_offset, lines, last_line);
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+}
@@ -0,0 +1,289 @@
+//! Implementation of the output pane.
+
+use std::sync::Arc;
+
+use cli_ide_platform::output::output_service::{OutputChannel, OutputService};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState, Paragraph};
+
+use super::{pane_borders, Window};
+use crate::input::AppKey;
+use crate::scrollbar::ScrollPosition;
+use crate::theme::Theme;
+
+/// Most channels the channel list shows at once.
+const MAX_PICKER_ROWS: u16 = 8;
+
+/// A read-only pane showing one output channel.
+///
+/// The lines are read from the channel when the pane is drawn, so whatever
+/// features write shows up without the pane being told. New lines are
+/// appended at the bottom and the pane follows them until it is scrolled
+/// up; scrolling back to the bottom follows them again. `Enter` drops down
+/// the list of channels, where `Up`/`Down` and `Enter` pick another one and
+/// `Esc` keeps the current one.
+pub struct OutputWindow {
+    /// Source of the channels.
+    service: Arc<OutputService>,
+    /// The channel shown, if any.
+    channel: Option<OutputChannel>,
+    /// Cursor in the channel list, while it is dropped down.
+    picker: Option<usize>,
+    /// Index of the first visible line.
+    scroll_offset: usize,
+    /// Whether the view sticks to the last line.
+    follow: bool,
+    /// Number of lines that fit, as of the last render.
+    height: usize,
+    /// Styles to draw with.
+    theme: Theme,
+    /// Whether the panel is drawn with only its top border.
+    compact: bool,
+}
+
+impl OutputWindow {
+    /// Create a pane showing the first channel of `service`, if any.
+    pub fn new(service: Arc<OutputService>) -> Self {
+        let channel = service
+            .channel_names()
+            .first()
+            .map(|name| service.channel(name));
+        Self {
+            service,
+            channel,
+            picker: None,
+            scroll_offset: 0,
+            follow: true,
+            height: 0,
+            theme: Theme::default(),
+            compact: false,
+        }
+    }
+
+    /// Get the name of the channel shown, if any.
+    pub fn channel(&self) -> Option<&str> {
+        self.channel.as_ref().map(OutputChannel::name)
+    }
+
+    /// Show the channel named `name`, creating it if needed, and follow
+    /// its new lines.
+    pub fn show(&mut self, name: &str) {
+        if self.channel() != Some(name) {
+            self.channel = Some(self.service.channel(name));
+            self.scroll_offset = 0;
+        }
+        self.picker = None;
+        self.follow = true;
+    }
+
+    /// Get the lines of the channel shown, oldest first.
+    pub fn lines(&self) -> Vec<String> {
+        self.channel
+            .as_ref()
+            .map(OutputChannel::lines)
+            .unwrap_or_default()
+    }
+
+    /// Whether the view sticks to the last line.
+    pub fn is_following(&self) -> bool {
+        self.follow
+    }
+
+    /// Whether the channel list is dropped down.
+    pub fn is_picking(&self) -> bool {
+        self.picker.is_some()
+    }
+
+    /// Handle a key press, returning whether it was consumed.
+    ///
+    /// While the channel list is dropped down every key except `Tab` is
+    /// consumed.
+    pub fn handle_key(&mut self, key: AppKey) -> bool {
+        let Some(cursor) = self.picker else {
+            if key != AppKey::Enter {
+                return false;
+            }
+            let names = self.service.channel_names();
+            let current = self
+                .channel()
+                .and_then(|name| names.iter().position(|other| other == name));
+            self.picker = Some(current.unwrap_or(0));
+            return true;
+        };
+        let names = self.service.channel_names();
+        match key {
+            AppKey::Tab => return false,
+            AppKey::Up => self.picker = Some(cursor.saturating_sub(1)),
+            AppKey::Down => {
+                self.picker = Some((cursor + 1).min(names.len().saturating_sub(1)));
+            }
+            AppKey::Enter => match names.get(cursor) {
+                Some(name) => self.show(name),
+                None => self.picker = None,
+            },
+            AppKey::Esc => self.picker = None,
+            _ => {}
+        }
+        true
+    }
+
+    /// Number of lines in the channel shown.
+    fn line_count(&self) -> usize {
+        self.channel.as_ref().map_or(0, OutputChannel::len)
+    }
+
+    /// The largest useful scroll offset for the last rendered height.
+    fn max_offset(&self) -> usize {
+        self.line_count().saturating_sub(self.height.max(1))
+    }
+
+    /// Draw the channel list over the top of `area`.
+    fn render_picker(&self, frame: &mut Frame, area: Rect, cursor: usize) {
+        let names = self.service.channel_names();
+        let rows = u16::try_from(names.len())
+            .unwrap_or(u16::MAX)
+            .clamp(1, MAX_PICKER_ROWS);
+        let width = names
+            .iter()
+            .map(|name| u16::try_from(name.chars().count()).unwrap_or(u16::MAX))
+            .max()
+            .unwrap_or(0)
+            .saturating_add(4)
+            .max(16);
+        let popup = Rect {
+            x: area.x,
+            y: area.y,
+            width: width.min(area.width),
+            height: rows.saturating_add(2).min(area.height),
+        };
+        let items: Vec<ListItem> = if names.is_empty() {
+            vec![ListItem::new("No channels")]
+        } else {
+            names.into_iter().map(ListItem::new).collect()
+        };
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .title("Channels")
+                    .borders(Borders::ALL)
+                    .border_style(self.theme.border_style(true)),
+            )
+            .style(self.theme.text)
+            .highlight_style(self.theme.selection);
+        let mut state = ListState::default().with_selected(Some(cursor));
+        frame.render_widget(Clear, popup);
+        frame.render_stateful_widget(list, popup, &mut state);
+    }
+}
+
+impl Window for OutputWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.render_with_focus(frame, area, false);
+    }
+
+    fn render_with_focus(&mut self, frame: &mut Frame, area: Rect, focused: bool) {
+        let border_type = if focused {
+            BorderType::Thick
+        } else {
+            BorderType::Plain
+        };
+        let name = self.channel().unwrap_or("no channel");
+        let title = if focused {
+            format!("Output: {name} \u{25be} [*]")
+        } else {
+            format!("Output: {name} \u{25be}")
+        };
+        let block = Block::default()
+            .title(title)
+            .borders(pane_borders(self.compact))
+            .border_type(border_type)
+            .border_style(self.theme.border_style(focused));
+        let inner = block.inner(area);
+        self.height = usize::from(inner.height);
+
+        self.scroll_offset = if self.follow {
+            self.max_offset()
+        } else {
+            self.scroll_offset.min(self.max_offset())
+        };
+        let visible: Vec<Line> = self
+            .channel
+            .as_ref()
+            .map(|channel| channel.lines_in(self.scroll_offset..self.scroll_offset + self.height))
+            .unwrap_or_default()
+            .into_iter()
+            .map(Line::raw)
+            .collect();
+        let paragraph = Paragraph::new(visible).style(self.theme.text).block(block);
+        frame.render_widget(paragraph, area);
+        if let Some(cursor) = self.picker {
+            self.render_picker(frame, inner, cursor);
+        }
+    }
+
+    fn scroll_by(&mut self, lines: i32) {
+        let start = if self.follow {
+            self.max_offset()
+        } else {
+            self.scroll_offset
+        };
+        let offset = i64::try_from(start).unwrap_or(i64::MAX) + i64::from(lines);
+        self.scroll_offset = usize::try_from(offset.max(0))
+            .unwrap_or(usize::MAX)
+            .min(self.max_offset());
+        self.follow = self.scroll_offset >= self.max_offset();
+    }
+
+    fn scroll_position(&self) -> Option<ScrollPosition> {
+        let offset = if self.follow {
+            self.max_offset()
+        } else {
+            self.scroll_offset
+        };
+        Some(ScrollPosition {
+            offset,
+            content: self.line_count(),
+        })
+    }
+
+    fn set_compact(&mut self, compact: bool) {
+        self.compact = compact;
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channel_list_switches_the_channel_shown() {
+        let service = Arc::new(OutputService::new());
+        service.channel("Cargo").append_line("Compiling demo");
+        service.channel("Git").append_line("fatal: bad revision");
+        let mut window = OutputWindow::new(Arc::clone(&service));
+        assert_eq!(window.channel(), Some("Cargo"));
+        assert_eq!(window.lines(), ["Compiling demo"]);
+
+        assert!(!window.handle_key(AppKey::Char('x')));
+        assert!(window.handle_key(AppKey::Enter));
+        assert!(window.is_picking());
+        assert!(window.handle_key(AppKey::Down));
+        assert!(window.handle_key(AppKey::Down));
+        assert!(window.handle_key(AppKey::Enter));
+        assert!(!window.is_picking());
+        assert_eq!(window.channel(), Some("Git"));
+
+        // Lines written after the pane opened are shown
+        service.channel("Git").append_line("done");
+        assert_eq!(window.lines(), ["fatal: bad revision", "done"]);
+
+        window.handle_key(AppKey::Enter);
+        window.handle_key(AppKey::Up);
+        window.handle_key(AppKey::Esc);
+        assert_eq!(window.channel(), Some("Git"));
+    }
+}
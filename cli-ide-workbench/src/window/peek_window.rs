@@ -0,0 +1,138 @@
+//! Implementation of the peek window shown inside the editor.
+
+use super::{scroll_offset_by, Window};
+use crate::definition::Location;
+use crate::truncate::truncate_path;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Borders, Paragraph};
+
+/// Preferred height of a peek window, including its border.
+pub const PEEK_HEIGHT: u16 = 8;
+
+/// An embedded, scrollable view of another location.
+///
+/// Shown inside the editor pane (e.g. for peek definition) so the user can
+/// read the target without navigating away. Lines overlapping the target
+/// range are highlighted.
+pub struct PeekWindow {
+    /// The location being shown.
+    location: Location,
+    /// Text of the target document.
+    text: String,
+    /// Zero-based lines spanned by the target range.
+    target_lines: std::ops::RangeInclusive<usize>,
+    /// Index of the first visible line.
+    scroll_offset: u16,
+}
+
+impl PeekWindow {
+    /// Create a peek window showing `location` within `text`.
+    ///
+    /// The view starts scrolled so the target is on the second visible line.
+    pub fn new(location: Location, text: impl Into<String>) -> Self {
+        let text = text.into();
+        let line_of = |offset: usize| text[..offset.min(text.len())].matches('\n').count();
+        let first = line_of(location.range.start);
+        let last = line_of(location.range.end).max(first);
+        let mut peek = Self {
+            location,
+            text,
+            target_lines: first..=last,
+            scroll_offset: 0,
+        };
+        peek.scroll_by(first as i32 - 1);
+        peek
+    }
+
+    /// Get the location being shown.
+    pub fn location(&self) -> &Location {
+        &self.location
+    }
+
+    /// Get the index of the first visible line.
+    pub fn scroll_offset(&self) -> u16 {
+        self.scroll_offset
+    }
+
+    /// Title shown on the border: `path:line` (one-based).
+    pub fn title(&self) -> String {
+        format!(
+            "{}:{}",
+            self.location.path.display(),
+            self.target_lines.start() + 1
+        )
+    }
+}
+
+impl Window for PeekWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.render_with_focus(frame, area, true);
+    }
+
+    fn render_with_focus(&mut self, frame: &mut Frame, area: Rect, _focused: bool) {
+        let target_style = Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD);
+        let lines: Vec<Line> = self
+            .text
+            .split('\n')
+            .enumerate()
+            .map(|(index, line)| {
+                if self.target_lines.contains(&index) {
+                    Line::styled(line, target_style)
+                } else {
+                    Line::raw(line)
+                }
+            })
+            .collect();
+
+        let paragraph = Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .title(truncate_path(
+                        &self.title(),
+                        usize::from(area.width.saturating_sub(2)),
+                    ))
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded),
+            )
+            .scroll((self.scroll_offset, 0));
+        frame.render_widget(paragraph, area);
+    }
+
+    fn scroll_by(&mut self, lines: i32) {
+        let last_line = self.text.split('\n').count().saturating_sub(1);
+        self.scroll_offset = scroll_offset_by(self.scroll_offset, lines, last_line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "mod a;\n\n/// Docs\nfn target() {\n    body();\n}\n";
+
+    #[test]
+    fn test_starts_scrolled_to_target() {
+        let offset = TEXT.find("fn target").unwrap();
+        let peek = PeekWindow::new(Location::new("lib.rs", offset..offset + 9), TEXT);
+
+        assert_eq!(peek.title(), "lib.rs:4");
+        assert_eq!(peek.scroll_offset(), 2);
+    }
+
+    #[test]
+    fn test_target_on_first_line_is_not_scrolled() {
+        let peek = PeekWindow::new(Location::new("lib.rs", 0..3), TEXT);
+        assert_eq!(peek.scroll_offset(), 0);
+    }
+
+    #[test]
+    fn test_scroll_is_clamped() {
+        let mut peek = PeekWindow::new(Location::new("lib.rs", 0..3), TEXT);
+        peek.scroll_by(-5);
+        assert_eq!(peek.scroll_offset(), 0);
+        peek.scroll_by(100);
+        assert_eq!(peek.scroll_offset(), 6);
+    }
+}
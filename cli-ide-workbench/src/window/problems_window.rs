@@ -0,0 +1,325 @@
+//! Implementation of the Problems panel.
+
+use std::path::{Path, PathBuf};
+
+use cli_ide_platform::problems::problem::{summarize, Problem, Severity};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Paragraph};
+
+use super::{highlight_text, line_text, pane_borders, Window};
+use crate::grapheme;
+use crate::input::AppKey;
+use crate::keybinding::WindowContext;
+use crate::list_selection::{reveal_row, ListSelection};
+use crate::scrollbar::ScrollPosition;
+use crate::text_selection::TextSelection;
+use crate::theme::Theme;
+
+/// Result of feeding a key to the Problems panel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProblemsOutcome {
+    /// The panel does not handle the key; it falls through to the bindings.
+    Ignored,
+    /// The panel consumed the key.
+    Handled,
+    /// The user asked to go to this problem.
+    Open(Problem),
+}
+
+/// A panel listing problems such as compiler errors.
+///
+/// The first line counts the errors and warnings; below it each problem
+/// shows its severity, location and message. `Up`/`Down` move between
+/// problems and `Enter` opens the one under the cursor. `Shift+Up`/
+/// `Shift+Down` and `Space` select problems to copy, and a drag or
+/// `Shift+Left`/`Shift+Right` the characters of part of them.
+pub struct ProblemsWindow {
+    /// Directory paths are shown relative to.
+    root: PathBuf,
+    /// The problems, in the order they were reported.
+    problems: Vec<Problem>,
+    /// Indices of the problems and the cursor among them.
+    rows: ListSelection<usize>,
+    /// Characters selected within or across problems.
+    text: Option<TextSelection<usize>>,
+    /// Index of the first visible problem.
+    scroll_offset: usize,
+    /// Whether the next render scrolls the cursor into view.
+    follow_cursor: bool,
+    /// Styles to draw with.
+    theme: Theme,
+    /// Whether the panel is drawn with only its top border.
+    compact: bool,
+}
+
+impl ProblemsWindow {
+    /// Create an empty panel showing paths relative to `root`.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            problems: Vec::new(),
+            rows: ListSelection::default(),
+            text: None,
+            scroll_offset: 0,
+            follow_cursor: true,
+            theme: Theme::default(),
+            compact: false,
+        }
+    }
+
+    /// Get the directory paths are shown relative to.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Get the problems.
+    pub fn problems(&self) -> &[Problem] {
+        &self.problems
+    }
+
+    /// Show `problems` instead of the current ones.
+    ///
+    /// The cursor stays on the same row, clamped to the new problems.
+    pub fn set_problems(&mut self, problems: Vec<Problem>) {
+        self.problems = problems;
+        self.rows.set_items((0..self.problems.len()).collect());
+        self.text = None;
+        self.follow_cursor = true;
+    }
+
+    /// Count the problems of `severity`.
+    pub fn count(&self, severity: Severity) -> usize {
+        self.problems
+            .iter()
+            .filter(|problem| problem.severity == severity)
+            .count()
+    }
+
+    /// The problem under the cursor.
+    pub fn selected(&self) -> Option<&Problem> {
+        self.rows.current().map(|&index| &self.problems[index])
+    }
+
+    /// Handle a key press.
+    pub fn handle_key(&mut self, key: AppKey) -> ProblemsOutcome {
+        match key {
+            AppKey::Enter => {
+                return match self.selected() {
+                    Some(problem) => ProblemsOutcome::Open(problem.clone()),
+                    None => ProblemsOutcome::Handled,
+                };
+            }
+            AppKey::ShiftLeft | AppKey::ShiftRight => {
+                if let Some(&index) = self.rows.current() {
+                    let line = self.row_text(index);
+                    TextSelection::extend_on(&mut self.text, index, &line, key);
+                }
+            }
+            key => {
+                if !self.rows.handle_key(key) {
+                    return ProblemsOutcome::Ignored;
+                }
+                self.text = None;
+            }
+        }
+        self.follow_cursor = true;
+        ProblemsOutcome::Handled
+    }
+
+    /// The text of the line showing the problem at `index`.
+    fn row_text(&self, index: usize) -> String {
+        line_text(&self.problem_line(&self.problems[index], self.theme.text))
+    }
+
+    /// The line showing `problem`.
+    fn problem_line(&self, problem: &Problem, style: Style) -> Line<'static> {
+        let (letter, severity_style) = match problem.severity {
+            Severity::Error => ("E", self.theme.error),
+            Severity::Warning => ("W", self.theme.warning),
+        };
+        let path = problem
+            .path
+            .strip_prefix(&self.root)
+            .unwrap_or(&problem.path);
+        let code = problem
+            .code
+            .as_ref()
+            .map(|code| format!(" [{code}]"))
+            .unwrap_or_default();
+        Line::from(vec![
+            Span::styled(format!("{letter} "), severity_style.patch(style)),
+            Span::styled(
+                format!(
+                    "{}:{}:{} {}{code}",
+                    path.display(),
+                    problem.line,
+                    problem.column,
+                    problem.message
+                ),
+                style,
+            ),
+        ])
+    }
+}
+
+impl Window for ProblemsWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.render_with_focus(frame, area, false);
+    }
+
+    fn render_with_focus(&mut self, frame: &mut Frame, area: Rect, focused: bool) {
+        let border_type = if focused {
+            BorderType::Thick
+        } else {
+            BorderType::Plain
+        };
+        let title = if focused { "Problems [*]" } else { "Problems" };
+        let block = Block::default()
+            .title(title)
+            .borders(pane_borders(self.compact))
+            .border_type(border_type)
+            .border_style(self.theme.border_style(focused));
+        // The summary line stays at the top
+        let height = usize::from(block.inner(area).height).saturating_sub(1);
+
+        if self.follow_cursor && height > 0 {
+            if let Some(cursor) = self.rows.cursor() {
+                self.scroll_offset = reveal_row(self.scroll_offset, cursor, height);
+            }
+            self.follow_cursor = false;
+        }
+        self.scroll_offset = self
+            .scroll_offset
+            .min(self.problems.len().saturating_sub(height));
+
+        let mut lines = vec![Line::styled(
+            summarize(&self.problems),
+            self.theme.text.add_modifier(Modifier::BOLD),
+        )];
+        let selecting = self.text.as_ref().is_some_and(|text| !text.is_empty());
+        for (row, &index) in self
+            .rows
+            .items()
+            .iter()
+            .enumerate()
+            .skip(self.scroll_offset)
+            .take(height)
+        {
+            let on_cursor = focused && !selecting && self.rows.cursor() == Some(row);
+            let style = if on_cursor || self.rows.is_marked(&index) {
+                self.theme.selection
+            } else {
+                self.theme.text
+            };
+            lines.push(highlight_text(
+                self.problem_line(&self.problems[index], style),
+                self.text.as_ref(),
+                self.rows.items(),
+                row,
+                self.theme.selection,
+            ));
+        }
+        let paragraph = Paragraph::new(lines).style(self.theme.text).block(block);
+        frame.render_widget(paragraph, area);
+    }
+
+    fn scroll_by(&mut self, lines: i32) {
+        let offset = i64::try_from(self.scroll_offset).unwrap_or(i64::MAX) + i64::from(lines);
+        self.scroll_offset = usize::try_from(offset.max(0)).unwrap_or(usize::MAX);
+        self.follow_cursor = false;
+    }
+
+    fn select_at(&mut self, row: u16, column: u16, extend: bool) {
+        // The summary line is above the problems
+        let row = self.scroll_offset + usize::from(row.saturating_sub(1));
+        let Some(row) = self.rows.len().checked_sub(1).map(|last| row.min(last)) else {
+            return;
+        };
+        let index = self.rows.items()[row];
+        let line = self.row_text(index);
+        let point = grapheme::index_at_display_column(&line, usize::from(column));
+        TextSelection::click(&mut self.text, index, point, extend);
+        if extend {
+            self.rows.set_cursor(row);
+        } else {
+            self.rows.click(row, false);
+        }
+    }
+
+    fn selected_text(&self) -> Option<String> {
+        let selected = self
+            .text
+            .as_ref()
+            .and_then(|text| text.text(self.rows.items(), |&index| self.row_text(index)));
+        if selected.is_some() {
+            return selected;
+        }
+        let lines: Vec<String> = self
+            .rows
+            .targets()
+            .into_iter()
+            .map(|index| self.row_text(index))
+            .collect();
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+
+    fn scroll_position(&self) -> Option<ScrollPosition> {
+        // The summary line does not scroll
+        Some(ScrollPosition {
+            offset: self.scroll_offset,
+            content: self.problems.len() + 1,
+        })
+    }
+
+    fn context(&self) -> Option<WindowContext> {
+        Some(WindowContext::Problems)
+    }
+
+    fn set_compact(&mut self, compact: bool) {
+        self.compact = compact;
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problem(path: &str, line: usize, severity: Severity) -> Problem {
+        Problem {
+            path: PathBuf::from("/ws").join(path),
+            line,
+            column: 1,
+            severity,
+            message: "oops".to_string(),
+            code: None,
+        }
+    }
+
+    #[test]
+    fn test_enter_opens_the_problem_under_the_cursor() {
+        let mut panel = ProblemsWindow::new("/ws");
+        assert_eq!(panel.handle_key(AppKey::Enter), ProblemsOutcome::Handled);
+
+        panel.set_problems(vec![
+            problem("src/a.rs", 1, Severity::Error),
+            problem("src/b.rs", 2, Severity::Warning),
+        ]);
+        assert_eq!(panel.count(Severity::Error), 1);
+        panel.handle_key(AppKey::Down);
+        assert_eq!(
+            panel.handle_key(AppKey::Enter),
+            ProblemsOutcome::Open(problem("src/b.rs", 2, Severity::Warning))
+        );
+        assert_eq!(
+            panel.handle_key(AppKey::Char('x')),
+            ProblemsOutcome::Ignored
+        );
+
+        panel.set_problems(vec![problem("src/a.rs", 1, Severity::Error)]);
+        assert_eq!(panel.selected().map(|problem| problem.line), Some(1));
+    }
+}
@@ -0,0 +1,88 @@
+//! Implementation of the progress toast.
+
+use cli_ide_platform::progress::progress_service::ProgressItem;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Borders, Gauge, Paragraph};
+
+use super::Window;
+use crate::theme::Theme;
+
+/// Width of a progress toast, including its border.
+pub const TOAST_WIDTH: u16 = 40;
+
+/// Height of a progress toast, including its border.
+pub const TOAST_HEIGHT: u16 = 4;
+
+/// A small box showing the progress of one operation over the bottom right
+/// of the workbench.
+///
+/// The first row shows the operation's message and the second a gauge of
+/// its percentage, or `working...` while the progress is indeterminate.
+/// Cancellable operations say so on the bottom border.
+pub struct ProgressToastWindow {
+    /// The progress shown.
+    item: ProgressItem,
+    /// Styles to draw with.
+    theme: Theme,
+}
+
+impl ProgressToastWindow {
+    /// Create a toast showing `item`.
+    pub fn new(item: ProgressItem) -> Self {
+        Self {
+            item,
+            theme: Theme::default(),
+        }
+    }
+
+    /// Get the progress shown.
+    pub fn item(&self) -> &ProgressItem {
+        &self.item
+    }
+}
+
+impl Window for ProgressToastWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.render_with_focus(frame, area, false);
+    }
+
+    fn render_with_focus(&mut self, frame: &mut Frame, area: Rect, _focused: bool) {
+        let mut block = Block::default()
+            .title(self.item.title.as_str())
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(self.theme.border)
+            .style(self.theme.text);
+        if self.item.cancellable {
+            block = block.title_bottom("cancellable");
+        }
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+        if inner.height == 0 {
+            return;
+        }
+        let message = self.item.message.as_deref().unwrap_or("");
+        frame.render_widget(Paragraph::new(message), Rect { height: 1, ..inner });
+        if inner.height < 2 {
+            return;
+        }
+        let bar = Rect {
+            y: inner.y + 1,
+            height: 1,
+            ..inner
+        };
+        match self.item.percentage {
+            Some(percentage) => frame.render_widget(
+                Gauge::default()
+                    .gauge_style(self.theme.selection)
+                    .percent(u16::from(percentage)),
+                bar,
+            ),
+            None => frame.render_widget(Paragraph::new("working..."), bar),
+        }
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+}
@@ -0,0 +1,179 @@
+//! Implementation of a generic quick-pick list window.
+
+use super::Window;
+use crate::fuzzy::fuzzy_filter;
+use crate::input::AppKey;
+use crate::list_selection::ListSelection;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Borders, List, ListItem, ListState, Paragraph};
+
+/// Result of feeding a key to a quick pick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuickPickOutcome {
+    /// The picker consumed the key and stays open.
+    Pending,
+    /// The user accepted the item at this index into the original items.
+    Accept(usize),
+    /// The user dismissed the picker.
+    Dismissed,
+}
+
+/// A modal list that fuzzy-filters its items by label.
+///
+/// Typed characters edit the query, `Up`/`Down` move the selection, `Enter`
+/// accepts the selected item and `Esc` dismisses. Pickers such as the
+/// command palette and the quick-fix menu are built on top of this window.
+pub struct QuickPickWindow {
+    /// Title shown on the border.
+    title: String,
+    /// Labels of all items, in their original order.
+    items: Vec<String>,
+    /// Current filter text.
+    query: String,
+    /// Indices into `items` that match the query, best first, and the
+    /// highlighted one among them.
+    matches: ListSelection<usize>,
+}
+
+impl QuickPickWindow {
+    /// Create a picker over the given item labels.
+    pub fn new(title: impl Into<String>, items: Vec<String>) -> Self {
+        let mut picker = Self {
+            title: title.into(),
+            items,
+            query: String::new(),
+            matches: ListSelection::default(),
+        };
+        picker.refilter();
+        picker
+    }
+
+    /// Get the current query text.
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Replace the query, e.g. with a completion of it.
+    pub fn set_query(&mut self, query: impl Into<String>) {
+        self.query = query.into();
+        self.refilter();
+    }
+
+    /// Get the indices of the items matching the query, best match first.
+    pub fn matches(&self) -> &[usize] {
+        self.matches.items()
+    }
+
+    /// Get the index of the highlighted item, if any item matches.
+    pub fn selected(&self) -> Option<usize> {
+        self.matches.current().copied()
+    }
+
+    /// Handle a key press.
+    pub fn handle_key(&mut self, key: AppKey) -> QuickPickOutcome {
+        match key {
+            AppKey::Esc => return QuickPickOutcome::Dismissed,
+            AppKey::Enter => {
+                return match self.selected() {
+                    Some(index) => QuickPickOutcome::Accept(index),
+                    None => QuickPickOutcome::Pending,
+                };
+            }
+            AppKey::Up => self.matches.move_by(-1),
+            AppKey::Down => self.matches.move_by(1),
+            AppKey::Backspace => {
+                self.query.pop();
+                self.refilter();
+            }
+            // `q` is translated to AppKey::Q; inside a picker it is text.
+            AppKey::Q => {
+                self.query.push('q');
+                self.refilter();
+            }
+            AppKey::Char(c) => {
+                self.query.push(c);
+                self.refilter();
+            }
+            _ => {}
+        }
+        QuickPickOutcome::Pending
+    }
+
+    /// Recompute matches for the current query and reset the selection.
+    fn refilter(&mut self) {
+        self.matches = ListSelection::new(fuzzy_filter(&self.query, &self.items, |label| {
+            label.as_str()
+        }));
+    }
+}
+
+impl Window for QuickPickWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.render_with_focus(frame, area, true);
+    }
+
+    fn render_with_focus(&mut self, frame: &mut Frame, area: Rect, _focused: bool) {
+        let block = Block::default()
+            .title(self.title.as_str())
+            .borders(Borders::ALL)
+            .border_type(BorderType::Thick);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        if inner.height == 0 {
+            return;
+        }
+
+        let input_area = Rect { height: 1, ..inner };
+        frame.render_widget(Paragraph::new(format!("> {}", self.query)), input_area);
+
+        let list_area = Rect {
+            y: inner.y + 1,
+            height: inner.height - 1,
+            ..inner
+        };
+        let items: Vec<ListItem> = self
+            .matches
+            .items()
+            .iter()
+            .map(|&index| ListItem::new(self.items[index].as_str()))
+            .collect();
+        let list =
+            List::new(items).highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        let mut state = ListState::default().with_selected(self.matches.cursor());
+        frame.render_stateful_widget(list, list_area, &mut state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn picker() -> QuickPickWindow {
+        QuickPickWindow::new(
+            "Pick",
+            vec!["Import HashMap".to_string(), "Remove unused".to_string()],
+        )
+    }
+
+    #[test]
+    fn test_accept_returns_original_index() {
+        let mut picker = picker();
+        picker.handle_key(AppKey::Char('r'));
+        picker.handle_key(AppKey::Char('u'));
+
+        assert_eq!(picker.matches(), &[1]);
+        assert_eq!(
+            picker.handle_key(AppKey::Enter),
+            QuickPickOutcome::Accept(1)
+        );
+    }
+
+    #[test]
+    fn test_empty_picker_stays_open_on_enter() {
+        let mut picker = QuickPickWindow::new("Pick", Vec::new());
+        assert_eq!(picker.selected(), None);
+        assert_eq!(picker.handle_key(AppKey::Enter), QuickPickOutcome::Pending);
+        assert_eq!(picker.handle_key(AppKey::Esc), QuickPickOutcome::Dismissed);
+    }
+}
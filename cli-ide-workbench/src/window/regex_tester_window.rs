@@ -0,0 +1,331 @@
+//! Implementation of the regex tester.
+
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Borders, Paragraph};
+use regex::Regex;
+
+use super::{Decoration, DecorationKind, EditorWindow, Window};
+use crate::input::AppKey;
+use crate::theme::Theme;
+
+/// Rows above the sample text: the pattern and the match summary.
+const HEADER_ROWS: u16 = 2;
+
+/// Result of feeding a key to the regex tester.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegexTesterOutcome {
+    /// The tester does not handle the key; it falls through to the bindings.
+    Ignored,
+    /// The tester consumed the key.
+    Handled,
+    /// The user asked to close the tester.
+    Close,
+}
+
+/// The part of the regex tester that receives typed keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegexField {
+    /// The regular expression.
+    Pattern,
+    /// The text the expression is matched against.
+    Sample,
+}
+
+/// A scratch window for composing a regular expression against sample
+/// text.
+///
+/// Typing edits the pattern or the sample; `Up`/`Down` (or `Enter` in the
+/// pattern) switch between them and `Esc` closes the tester. After every
+/// edit the pattern is compiled and its matches and capture groups are
+/// drawn as decorations on the sample, which is shown in an
+/// [`EditorWindow`].
+pub struct RegexTesterWindow {
+    /// The regular expression as typed.
+    pattern: String,
+    /// Editor showing the sample text and its match decorations.
+    sample: EditorWindow,
+    /// Part receiving typed keys.
+    field: RegexField,
+    /// Number of matches in the sample, or why the pattern does not compile.
+    status: Result<usize, String>,
+    /// Styles to draw with.
+    theme: Theme,
+}
+
+impl Default for RegexTesterWindow {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RegexTesterWindow {
+    /// Create a tester with an empty pattern and sample.
+    pub fn new() -> Self {
+        let mut sample = EditorWindow::with_text("");
+        sample.set_title("Sample");
+        Self {
+            pattern: String::new(),
+            sample,
+            field: RegexField::Pattern,
+            status: Ok(0),
+            theme: Theme::default(),
+        }
+    }
+
+    /// Get the pattern.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Replace the pattern and match it against the sample.
+    pub fn set_pattern(&mut self, pattern: impl Into<String>) {
+        self.pattern = pattern.into();
+        self.update_matches();
+    }
+
+    /// Get the sample editor, whose decorations mark the matches.
+    pub fn sample(&self) -> &EditorWindow {
+        &self.sample
+    }
+
+    /// Replace the sample text and match the pattern against it.
+    pub fn set_sample(&mut self, text: impl Into<String>) {
+        self.sample.set_text(text);
+        self.update_matches();
+    }
+
+    /// Get the part receiving typed keys.
+    pub fn field(&self) -> RegexField {
+        self.field
+    }
+
+    /// Number of matches in the sample; zero while the pattern is invalid.
+    pub fn match_count(&self) -> usize {
+        self.status.as_ref().copied().unwrap_or(0)
+    }
+
+    /// Why the pattern does not compile, if it does not.
+    pub fn error(&self) -> Option<&str> {
+        self.status.as_ref().err().map(String::as_str)
+    }
+
+    /// Handle a key press.
+    ///
+    /// Every key except `Tab` is consumed.
+    pub fn handle_key(&mut self, key: AppKey) -> RegexTesterOutcome {
+        match (key, self.field) {
+            (AppKey::Tab, _) => return RegexTesterOutcome::Ignored,
+            (AppKey::Esc, _) => return RegexTesterOutcome::Close,
+            (AppKey::Up, _) => self.field = RegexField::Pattern,
+            (AppKey::Down | AppKey::Enter, RegexField::Pattern) => self.field = RegexField::Sample,
+            (AppKey::Enter, RegexField::Sample) => self.edit(|text| text.push('\n')),
+            (AppKey::Backspace, _) => self.edit(|text| {
+                text.pop();
+            }),
+            // `q` is translated to AppKey::Q; here it is text.
+            (AppKey::Q, _) => self.edit(|text| text.push('q')),
+            (AppKey::Char(c), _) => self.edit(|text| text.push(c)),
+            _ => {}
+        }
+        RegexTesterOutcome::Handled
+    }
+
+    /// Apply `change` to the active field's text and match again.
+    fn edit(&mut self, change: impl FnOnce(&mut String)) {
+        match self.field {
+            RegexField::Pattern => change(&mut self.pattern),
+            RegexField::Sample => {
+                let mut text = self.sample.text();
+                change(&mut text);
+                self.sample.set_text(text);
+            }
+        }
+        self.update_matches();
+    }
+
+    /// Compile the pattern and decorate its matches in the sample.
+    ///
+    /// An empty pattern matches nothing rather than everywhere.
+    fn update_matches(&mut self) {
+        let mut decorations = Vec::new();
+        self.status = if self.pattern.is_empty() {
+            Ok(0)
+        } else {
+            match Regex::new(&self.pattern) {
+                Ok(regex) => {
+                    let text = self.sample.text();
+                    let mut count = 0;
+                    for captures in regex.captures_iter(&text) {
+                        count += 1;
+                        decorations.extend(captures.iter().enumerate().filter_map(
+                            |(group, found)| {
+                                let kind = if group == 0 {
+                                    DecorationKind::Match
+                                } else {
+                                    DecorationKind::MatchGroup
+                                };
+                                found.map(|found| Decoration {
+                                    range: found.range(),
+                                    kind,
+                                })
+                            },
+                        ));
+                    }
+                    Ok(count)
+                }
+                // Syntax errors span several lines ending with the reason
+                Err(error) => Err(error
+                    .to_string()
+                    .lines()
+                    .last()
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string()),
+            }
+        };
+        self.sample.set_decorations(decorations);
+    }
+
+    /// The line showing the pattern, its label highlighted while active.
+    fn pattern_line(&self, focused: bool) -> Line<'_> {
+        let label_style = if focused && self.field == RegexField::Pattern {
+            self.theme.selection
+        } else {
+            self.theme.text
+        };
+        Line::from(vec![
+            Span::styled("Pattern: ", label_style),
+            Span::styled(self.pattern.as_str(), self.theme.text),
+        ])
+    }
+
+    /// The line counting the matches or showing the pattern's error.
+    fn status_line(&self) -> Line<'_> {
+        match &self.status {
+            Ok(0) => Line::styled("No matches", self.theme.text),
+            Ok(1) => Line::styled("1 match", self.theme.text),
+            Ok(count) => Line::styled(format!("{count} matches"), self.theme.text),
+            Err(error) => Line::styled(error.as_str(), self.theme.overflow),
+        }
+    }
+}
+
+impl Window for RegexTesterWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.render_with_focus(frame, area, false);
+    }
+
+    fn render_with_focus(&mut self, frame: &mut Frame, area: Rect, focused: bool) {
+        let border_type = if focused {
+            BorderType::Thick
+        } else {
+            BorderType::Plain
+        };
+        let title = if focused {
+            "Regex Tester [*]"
+        } else {
+            "Regex Tester"
+        };
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_type(border_type)
+            .border_style(self.theme.border_style(focused));
+        let inner = block.inner(area);
+        let header = Paragraph::new(vec![self.pattern_line(focused), self.status_line()])
+            .style(self.theme.text)
+            .block(block);
+        frame.render_widget(header, area);
+
+        let sample_area = Rect {
+            y: inner.y.saturating_add(HEADER_ROWS),
+            height: inner.height.saturating_sub(HEADER_ROWS),
+            ..inner
+        };
+        if sample_area.height > 0 {
+            self.sample.render_with_focus(
+                frame,
+                sample_area,
+                focused && self.field == RegexField::Sample,
+            );
+        }
+    }
+
+    fn scroll_by(&mut self, lines: i32) {
+        self.sample.scroll_by(lines);
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.sample.set_theme(theme);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn type_text(tester: &mut RegexTesterWindow, text: &str) {
+        for c in text.chars() {
+            tester.handle_key(AppKey::Char(c));
+        }
+    }
+
+    fn decorations(tester: &RegexTesterWindow) -> Vec<(std::ops::Range<usize>, DecorationKind)> {
+        tester
+            .sample()
+            .decorations()
+            .iter()
+            .map(|decoration| (decoration.range.clone(), decoration.kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_typing_updates_matches_and_groups_live() {
+        let mut tester = RegexTesterWindow::new();
+        tester.handle_key(AppKey::Down);
+        type_text(&mut tester, "a1 b2");
+        tester.handle_key(AppKey::Up);
+        type_text(&mut tester, "[a-z]");
+        assert_eq!(tester.match_count(), 2);
+
+        type_text(&mut tester, "(\\d)");
+        assert_eq!(tester.pattern(), "[a-z](\\d)");
+        assert_eq!(
+            decorations(&tester),
+            vec![
+                (0..2, DecorationKind::Match),
+                (1..2, DecorationKind::MatchGroup),
+                (3..5, DecorationKind::Match),
+                (4..5, DecorationKind::MatchGroup),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_pattern_reports_error_and_clears_matches() {
+        let mut tester = RegexTesterWindow::new();
+        tester.set_sample("((x");
+        tester.set_pattern("x");
+        assert_eq!(tester.match_count(), 1);
+
+        tester.handle_key(AppKey::Backspace);
+        type_text(&mut tester, "(");
+        assert_eq!(tester.error(), Some("error: unclosed group"));
+        assert_eq!(tester.match_count(), 0);
+        assert!(tester.sample().decorations().is_empty());
+    }
+
+    #[test]
+    fn test_sample_accepts_newlines_and_q_and_esc_closes() {
+        let mut tester = RegexTesterWindow::new();
+        tester.handle_key(AppKey::Enter);
+        assert_eq!(tester.field(), RegexField::Sample);
+        tester.handle_key(AppKey::Q);
+        tester.handle_key(AppKey::Enter);
+        tester.handle_key(AppKey::Q);
+        assert_eq!(tester.sample().text(), "q\nq");
+        assert_eq!(tester.handle_key(AppKey::Tab), RegexTesterOutcome::Ignored);
+        assert_eq!(tester.handle_key(AppKey::Esc), RegexTesterOutcome::Close);
+    }
+}
@@ -0,0 +1,173 @@
+//! Implementation of the inline rename input.
+
+use std::ops::Range;
+
+use super::Window;
+use crate::input::AppKey;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Borders, Paragraph};
+
+/// Minimum width of the rename input, including its border.
+const MIN_WIDTH: u16 = 24;
+
+/// Result of feeding a key to a rename input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenameOutcome {
+    /// The input consumed the key and stays open.
+    Pending,
+    /// The user accepted this name.
+    Accept(String),
+    /// The user dismissed the input.
+    Dismissed,
+}
+
+/// A one-line text input anchored at the symbol being renamed.
+///
+/// Starts with the current name; the first typed character replaces it.
+/// `Enter` accepts the name unless it has a validation error and `Esc`
+/// dismisses.
+pub struct RenameInputWindow {
+    /// Byte range of the symbol in the editor buffer.
+    range: Range<usize>,
+    /// Current name.
+    name: String,
+    /// Whether the name is still the untouched placeholder.
+    pristine: bool,
+    /// Validation message for the current name, if it is invalid.
+    error: Option<String>,
+}
+
+impl RenameInputWindow {
+    /// Create an input for the symbol at `range`, prefilled with `placeholder`.
+    pub fn new(range: Range<usize>, placeholder: impl Into<String>) -> Self {
+        Self {
+            range,
+            name: placeholder.into(),
+            pristine: true,
+            error: None,
+        }
+    }
+
+    /// Get the byte range of the symbol being renamed.
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// Get the current name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the validation message for the current name, if any.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Set the validation message for the current name.
+    pub fn set_error(&mut self, error: Option<String>) {
+        self.error = error;
+    }
+
+    /// Width the input needs to show the name and any error message.
+    pub fn preferred_width(&self) -> u16 {
+        let content = self.name.chars().count().max(
+            self.error
+                .as_deref()
+                .map_or(0, |error| error.chars().count()),
+        );
+        u16::try_from(content + 4)
+            .unwrap_or(u16::MAX)
+            .max(MIN_WIDTH)
+    }
+
+    /// Handle a key press.
+    pub fn handle_key(&mut self, key: AppKey) -> RenameOutcome {
+        match key {
+            AppKey::Esc => return RenameOutcome::Dismissed,
+            AppKey::Enter if self.error.is_none() => {
+                return RenameOutcome::Accept(self.name.clone());
+            }
+            AppKey::Backspace => {
+                self.name.pop();
+                self.pristine = false;
+            }
+            // `q` is translated to AppKey::Q; inside an input it is text.
+            AppKey::Q => self.type_char('q'),
+            AppKey::Char(c) => self.type_char(c),
+            _ => {}
+        }
+        RenameOutcome::Pending
+    }
+
+    /// Append a typed character, replacing the untouched placeholder.
+    fn type_char(&mut self, c: char) {
+        if self.pristine {
+            self.name.clear();
+            self.pristine = false;
+        }
+        self.name.push(c);
+    }
+}
+
+impl Window for RenameInputWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.render_with_focus(frame, area, true);
+    }
+
+    fn render_with_focus(&mut self, frame: &mut Frame, area: Rect, _focused: bool) {
+        let mut block = Block::default()
+            .title("Rename")
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded);
+        if let Some(error) = &self.error {
+            block = block
+                .border_style(Style::default().fg(Color::Red))
+                .title_bottom(error.as_str());
+        }
+        let name_style = if self.pristine {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default()
+        };
+        let paragraph = Paragraph::new(Line::styled(self.name.as_str(), name_style)).block(block);
+        frame.render_widget(paragraph, area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_typed_char_replaces_placeholder() {
+        let mut input = RenameInputWindow::new(4..5, "x");
+        input.handle_key(AppKey::Q);
+        input.handle_key(AppKey::Char('2'));
+        assert_eq!(input.name(), "q2");
+
+        input.handle_key(AppKey::Backspace);
+        assert_eq!(input.name(), "q");
+    }
+
+    #[test]
+    fn test_backspace_edits_placeholder() {
+        let mut input = RenameInputWindow::new(0..5, "count");
+        input.handle_key(AppKey::Backspace);
+        input.handle_key(AppKey::Char('s'));
+        assert_eq!(input.name(), "couns");
+    }
+
+    #[test]
+    fn test_enter_blocked_while_invalid() {
+        let mut input = RenameInputWindow::new(0..1, "x");
+        input.set_error(Some("invalid".to_string()));
+        assert_eq!(input.handle_key(AppKey::Enter), RenameOutcome::Pending);
+
+        input.set_error(None);
+        assert_eq!(
+            input.handle_key(AppKey::Enter),
+            RenameOutcome::Accept("x".to_string())
+        );
+        assert_eq!(input.handle_key(AppKey::Esc), RenameOutcome::Dismissed);
+    }
+}
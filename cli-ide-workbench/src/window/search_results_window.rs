@@ -0,0 +1,496 @@
+//! Implementation of the Find in Files panel.
+
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use cli_ide_platform::search::search_service::{SearchQuery, SearchSummary, TextMatch};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Borders, Paragraph};
+
+use super::Window;
+use crate::input::AppKey;
+use crate::list_selection::{reveal_row, ListSelection};
+use crate::scrollbar::ScrollPosition;
+use crate::search;
+use crate::theme::Theme;
+use crate::workspace::WorkspaceFolders;
+
+/// Rows above the results: the query, the options and the summary.
+const HEADER_ROWS: u16 = 3;
+
+/// Result of feeding a key to the Find in Files panel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchResultsOutcome {
+    /// The panel does not handle the key; it falls through to the bindings.
+    Ignored,
+    /// The panel consumed the key.
+    Handled,
+    /// The user asked to search the workspace for this query.
+    Search(SearchQuery),
+    /// The user asked to open this file with this byte range selected.
+    Open(PathBuf, Range<usize>),
+}
+
+/// The part of the Find in Files panel that receives typed keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchResultsField {
+    /// The text or regex to search for.
+    Query,
+    /// The option making the query a regex.
+    Regex,
+    /// The option making the search case-sensitive.
+    MatchCase,
+    /// The list of matches.
+    Results,
+}
+
+/// A result row: the index of a file, and of a match within it for match
+/// rows.
+type RowKey = (usize, Option<usize>);
+
+/// A panel listing the matches of a workspace search as they stream in.
+///
+/// Typing edits the query and `Enter` searches; `Up`/`Down` move between
+/// the query, the Regex and Match Case options, which `Space` toggles, and
+/// the results. `Enter` on a result opens its file at the match. While the
+/// search runs the summary says so and results are appended at the
+/// bottom, leaving the cursor where it is.
+pub struct SearchResultsWindow {
+    /// Folders searched, which result paths are shown relative to.
+    folders: WorkspaceFolders,
+    /// Text or regex to search for.
+    query: String,
+    /// Whether the query is a regex.
+    is_regex: bool,
+    /// Whether the search is case-sensitive.
+    case_sensitive: bool,
+    /// Part receiving typed keys.
+    field: SearchResultsField,
+    /// Query the results are for, once a search started.
+    searched: Option<SearchQuery>,
+    /// Files with matches, in the order they were found.
+    results: Vec<(PathBuf, Vec<TextMatch>)>,
+    /// Result rows and the cursor among them.
+    rows: ListSelection<RowKey>,
+    /// How the search ended, once it has.
+    summary: Option<SearchSummary>,
+    /// Why the last search could not start, if it could not.
+    error: Option<String>,
+    /// Index of the first visible result row.
+    scroll_offset: usize,
+    /// Whether the next render scrolls the cursor into view.
+    follow_cursor: bool,
+    /// Styles to draw with.
+    theme: Theme,
+}
+
+impl SearchResultsWindow {
+    /// Create an empty panel showing paths relative to `root`.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self::with_folders(WorkspaceFolders::new(root))
+    }
+
+    /// Create an empty panel searching the roots of `folders`, showing
+    /// paths relative to them.
+    pub fn with_folders(folders: WorkspaceFolders) -> Self {
+        Self {
+            folders,
+            query: String::new(),
+            is_regex: false,
+            case_sensitive: false,
+            field: SearchResultsField::Query,
+            searched: None,
+            results: Vec::new(),
+            rows: ListSelection::default(),
+            summary: None,
+            error: None,
+            scroll_offset: 0,
+            follow_cursor: true,
+            theme: Theme::default(),
+        }
+    }
+
+    /// Get the primary root, which result paths are shown relative to.
+    pub fn root(&self) -> &Path {
+        self.folders.primary()
+    }
+
+    /// Get the folders searched.
+    pub fn folders(&self) -> &WorkspaceFolders {
+        &self.folders
+    }
+
+    /// Search the roots of `folders` from the next search on.
+    pub fn set_folders(&mut self, folders: WorkspaceFolders) {
+        self.folders = folders;
+    }
+
+    /// Get the query as currently typed, with the options.
+    pub fn query(&self) -> SearchQuery {
+        SearchQuery {
+            pattern: self.query.clone(),
+            is_regex: self.is_regex,
+            case_sensitive: self.case_sensitive,
+        }
+    }
+
+    /// Get the part receiving typed keys.
+    pub fn field(&self) -> SearchResultsField {
+        self.field
+    }
+
+    /// Check whether typed keys edit the query or its options.
+    pub fn is_editing(&self) -> bool {
+        self.field != SearchResultsField::Results
+    }
+
+    /// Get the query the results are for, if a search started.
+    pub fn searched(&self) -> Option<&SearchQuery> {
+        self.searched.as_ref()
+    }
+
+    /// Get the files with matches, in the order they were found.
+    pub fn results(&self) -> &[(PathBuf, Vec<TextMatch>)] {
+        &self.results
+    }
+
+    /// Check whether a search is running.
+    pub fn is_searching(&self) -> bool {
+        self.searched.is_some() && self.summary.is_none() && self.error.is_none()
+    }
+
+    /// Clear the results for a search for `query` that is starting.
+    pub fn start(&mut self, query: SearchQuery) {
+        self.searched = Some(query);
+        self.results.clear();
+        self.rows = ListSelection::default();
+        self.summary = None;
+        self.error = None;
+        self.scroll_offset = 0;
+        self.follow_cursor = true;
+    }
+
+    /// Append the matches found in the file at `path`.
+    pub fn add_matches(&mut self, path: PathBuf, matches: Vec<TextMatch>) {
+        let file = self.results.len();
+        let mut rows = self.rows.items().to_vec();
+        rows.push((file, None));
+        rows.extend((0..matches.len()).map(|found| (file, Some(found))));
+        self.results.push((path, matches));
+        self.rows.set_items(rows);
+    }
+
+    /// Mark the search as ended.
+    pub fn finish(&mut self, summary: SearchSummary) {
+        self.summary = Some(summary);
+    }
+
+    /// Show why the search for `query` could not start.
+    pub fn fail(&mut self, query: SearchQuery, error: impl Into<String>) {
+        self.start(query);
+        self.error = Some(error.into());
+    }
+
+    /// Handle a key press.
+    ///
+    /// While the query or an option is active every key except `Tab` is
+    /// consumed.
+    pub fn handle_key(&mut self, key: AppKey) -> SearchResultsOutcome {
+        let outcome = match self.field {
+            SearchResultsField::Results => self.handle_results_key(key),
+            _ => self.handle_field_key(key),
+        };
+        if outcome != SearchResultsOutcome::Ignored {
+            self.follow_cursor = true;
+        }
+        outcome
+    }
+
+    /// Handle a key while the query or an option is active.
+    fn handle_field_key(&mut self, key: AppKey) -> SearchResultsOutcome {
+        match (key, self.field) {
+            (AppKey::Tab, _) => return SearchResultsOutcome::Ignored,
+            (AppKey::Enter, _) => return SearchResultsOutcome::Search(self.query()),
+            (AppKey::Backspace, SearchResultsField::Query) => {
+                self.query.pop();
+            }
+            // `q` is translated to AppKey::Q; inside the query it is text.
+            (AppKey::Q, SearchResultsField::Query) => self.query.push('q'),
+            (AppKey::Char(c), SearchResultsField::Query) => self.query.push(c),
+            (AppKey::Char(' '), SearchResultsField::Regex) => self.is_regex = !self.is_regex,
+            (AppKey::Char(' '), SearchResultsField::MatchCase) => {
+                self.case_sensitive = !self.case_sensitive;
+            }
+            (AppKey::Up, SearchResultsField::MatchCase) => self.field = SearchResultsField::Regex,
+            (AppKey::Up, _) => self.field = SearchResultsField::Query,
+            (AppKey::Down, SearchResultsField::Query) => self.field = SearchResultsField::Regex,
+            (AppKey::Down, SearchResultsField::Regex) => {
+                self.field = SearchResultsField::MatchCase;
+            }
+            (AppKey::Down | AppKey::Esc, _) if !self.rows.is_empty() => {
+                self.field = SearchResultsField::Results;
+            }
+            _ => {}
+        }
+        SearchResultsOutcome::Handled
+    }
+
+    /// Handle a key while the results are active.
+    fn handle_results_key(&mut self, key: AppKey) -> SearchResultsOutcome {
+        match key {
+            AppKey::Up if self.rows.cursor().unwrap_or(0) == 0 => {
+                self.field = SearchResultsField::MatchCase;
+            }
+            AppKey::Up | AppKey::Down => self.rows.move_by(if key == AppKey::Up { -1 } else { 1 }),
+            AppKey::Enter => {
+                let Some(&(file, found)) = self.rows.current() else {
+                    return SearchResultsOutcome::Handled;
+                };
+                let (path, matches) = &self.results[file];
+                let range = matches[found.unwrap_or(0)].range.clone();
+                return SearchResultsOutcome::Open(path.clone(), range);
+            }
+            _ => return SearchResultsOutcome::Ignored,
+        }
+        SearchResultsOutcome::Handled
+    }
+
+    /// The style of a field's label, highlighted while it is active.
+    fn label_style(&self, field: SearchResultsField, focused: bool) -> Style {
+        if focused && self.field == field {
+            self.theme.selection
+        } else {
+            self.theme.text
+        }
+    }
+
+    /// The line showing the query.
+    fn query_line(&self, focused: bool) -> Line<'_> {
+        Line::from(vec![
+            Span::styled(
+                "Find: ",
+                self.label_style(SearchResultsField::Query, focused),
+            ),
+            Span::styled(self.query.as_str(), self.theme.text),
+        ])
+    }
+
+    /// The line showing the Regex and Match Case options.
+    fn options_line(&self, focused: bool) -> Line<'_> {
+        let check = |on: bool| if on { "[x]" } else { "[ ]" };
+        Line::from(vec![
+            Span::styled(
+                format!("{} Regex", check(self.is_regex)),
+                self.label_style(SearchResultsField::Regex, focused),
+            ),
+            Span::styled("  ", self.theme.text),
+            Span::styled(
+                format!("{} Match Case", check(self.case_sensitive)),
+                self.label_style(SearchResultsField::MatchCase, focused),
+            ),
+        ])
+    }
+
+    /// The line counting the results and saying whether the search runs.
+    fn summary_line(&self) -> Line<'_> {
+        if let Some(error) = &self.error {
+            return Line::styled(error.as_str(), self.theme.error);
+        }
+        if self.searched.is_none() {
+            return Line::default();
+        }
+        let matches: usize = self.results.iter().map(|(_, matches)| matches.len()).sum();
+        let mut text = if matches == 0 {
+            "No results".to_string()
+        } else {
+            format!(
+                "{} in {}",
+                search::counted(matches, "result"),
+                search::counted(self.results.len(), "file")
+            )
+        };
+        match &self.summary {
+            None => text.push_str(" (searching...)"),
+            Some(summary) if summary.cancelled => text.push_str(" (stopped)"),
+            Some(SearchSummary {
+                error: Some(error), ..
+            }) => text = format!("Search failed: {error}"),
+            Some(_) => {}
+        }
+        Line::styled(text, self.theme.text)
+    }
+
+    /// The line for a result row.
+    fn result_line(&self, (file, found): RowKey, selected: bool) -> Line<'_> {
+        let style = if selected {
+            self.theme.selection
+        } else {
+            self.theme.text
+        };
+        let (path, matches) = &self.results[file];
+        let Some(found) = found else {
+            let path = self.folders.label(path);
+            return Line::styled(format!("{path} ({})", matches.len()), style);
+        };
+        let TextMatch {
+            line,
+            line_text,
+            column,
+            ..
+        } = &matches[found];
+        let indent = line_text.len() - line_text.trim_start().len();
+        let start = indent.min(column.start);
+        Line::from(vec![
+            Span::styled(format!("    {}: ", line + 1), style),
+            Span::styled(&line_text[start..column.start], style),
+            Span::styled(
+                &line_text[column.clone()],
+                style.add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(&line_text[column.end..], style),
+        ])
+    }
+}
+
+impl Window for SearchResultsWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.render_with_focus(frame, area, false);
+    }
+
+    fn render_with_focus(&mut self, frame: &mut Frame, area: Rect, focused: bool) {
+        let border_type = if focused {
+            BorderType::Thick
+        } else {
+            BorderType::Plain
+        };
+        let title = if focused {
+            "Find in Files [*]"
+        } else {
+            "Find in Files"
+        };
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_type(border_type)
+            .border_style(self.theme.border_style(focused));
+        let height = usize::from(block.inner(area).height.saturating_sub(HEADER_ROWS));
+
+        if self.follow_cursor && height > 0 {
+            if let Some(cursor) = self.rows.cursor() {
+                self.scroll_offset = reveal_row(self.scroll_offset, cursor, height);
+            }
+            self.follow_cursor = false;
+        }
+        self.scroll_offset = self
+            .scroll_offset
+            .min(self.rows.len().saturating_sub(height));
+
+        let cursor = self.rows.cursor();
+        let results_focused = focused && self.field == SearchResultsField::Results;
+        let mut lines = vec![
+            self.query_line(focused),
+            self.options_line(focused),
+            self.summary_line(),
+        ];
+        lines.extend(
+            self.rows
+                .items()
+                .iter()
+                .enumerate()
+                .skip(self.scroll_offset)
+                .take(height)
+                .map(|(index, &row)| {
+                    self.result_line(row, results_focused && Some(index) == cursor)
+                }),
+        );
+        let paragraph = Paragraph::new(lines).style(self.theme.text).block(block);
+        frame.render_widget(paragraph, area);
+    }
+
+    fn scroll_by(&mut self, lines: i32) {
+        let last_row = self.rows.len().saturating_sub(1);
+        let offset = i64::try_from(self.scroll_offset).unwrap_or(i64::MAX) + i64::from(lines);
+        self.scroll_offset = usize::try_from(offset.max(0))
+            .unwrap_or(usize::MAX)
+            .min(last_row);
+        self.follow_cursor = false;
+    }
+
+    fn scroll_position(&self) -> Option<ScrollPosition> {
+        Some(ScrollPosition {
+            offset: self.scroll_offset,
+            content: self.rows.len(),
+        })
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cli_ide_platform::search::search_service::find_matches;
+
+    fn matches(text: &str, query: &str) -> Vec<TextMatch> {
+        find_matches(text, &SearchQuery::literal(query).compile().unwrap())
+    }
+
+    fn type_text(panel: &mut SearchResultsWindow, text: &str) {
+        for c in text.chars() {
+            panel.handle_key(AppKey::Char(c));
+        }
+    }
+
+    #[test]
+    fn test_query_and_options_build_the_search() {
+        let mut panel = SearchResultsWindow::new("/ws");
+        type_text(&mut panel, "fo+");
+        panel.handle_key(AppKey::Q);
+        panel.handle_key(AppKey::Down);
+        panel.handle_key(AppKey::Char(' '));
+        assert_eq!(panel.field(), SearchResultsField::Regex);
+        panel.handle_key(AppKey::Down);
+        panel.handle_key(AppKey::Char(' '));
+        assert_eq!(
+            panel.handle_key(AppKey::Enter),
+            SearchResultsOutcome::Search(SearchQuery::regex("fo+q"))
+        );
+        // No results to move to, and Tab leaves the panel
+        assert_eq!(
+            panel.handle_key(AppKey::Down),
+            SearchResultsOutcome::Handled
+        );
+        assert!(panel.is_editing());
+        assert_eq!(panel.handle_key(AppKey::Tab), SearchResultsOutcome::Ignored);
+    }
+
+    #[test]
+    fn test_streamed_results_keep_the_cursor_and_enter_opens() {
+        let mut panel = SearchResultsWindow::new("/ws");
+        panel.start(SearchQuery::literal("foo"));
+        assert!(panel.is_searching());
+        panel.add_matches(PathBuf::from("/ws/a.rs"), matches("foo foo", "foo"));
+        panel.handle_key(AppKey::Esc);
+        panel.handle_key(AppKey::Down);
+        panel.handle_key(AppKey::Down);
+        panel.add_matches(PathBuf::from("/ws/b.rs"), matches("\nfoo", "foo"));
+        assert_eq!(
+            panel.handle_key(AppKey::Enter),
+            SearchResultsOutcome::Open(PathBuf::from("/ws/a.rs"), 4..7)
+        );
+        panel.handle_key(AppKey::Down);
+        panel.handle_key(AppKey::Down);
+        assert_eq!(
+            panel.handle_key(AppKey::Enter),
+            SearchResultsOutcome::Open(PathBuf::from("/ws/b.rs"), 1..4)
+        );
+        panel.finish(SearchSummary::default());
+        assert!(!panel.is_searching());
+        assert_eq!(
+            panel.handle_key(AppKey::Char('x')),
+            SearchResultsOutcome::Ignored
+        );
+    }
+}
@@ -0,0 +1,504 @@
+//! Implementation of the workspace search panel.
+
+use std::collections::BTreeSet;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Borders, Paragraph};
+
+use super::Window;
+use crate::code_action::WorkspaceEdit;
+use crate::input::AppKey;
+use crate::keybinding::WindowContext;
+use crate::list_selection::{reveal_row, ListSelection};
+use crate::search::{self, FileMatches, SearchMatch};
+use crate::theme::Theme;
+use crate::workspace::WorkspaceFolders;
+
+/// Rows above the results: the find and replace fields and the summary.
+const HEADER_ROWS: u16 = 3;
+
+/// Result of feeding a key to the search panel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchOutcome {
+    /// The panel does not handle the key; it falls through to the bindings.
+    Ignored,
+    /// The panel consumed the key.
+    Handled,
+    /// The user asked to search the workspace for this text.
+    Search(String),
+    /// The user asked to open this file with this byte range selected.
+    Open(PathBuf, Range<usize>),
+}
+
+/// The part of the search panel that receives typed keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchField {
+    /// The text to find.
+    Find,
+    /// The text to replace matches with.
+    Replace,
+    /// The list of matches.
+    Results,
+}
+
+/// A result row: the index of a file, and of a match within it for match
+/// rows.
+type RowKey = (usize, Option<usize>);
+
+/// A panel that searches the workspace and replaces matches selectively.
+///
+/// Typing edits the find or replace field and `Enter` searches; `Up`/`Down`
+/// move between the fields and the results. In the results, `Space`
+/// includes or excludes the file or match under the cursor and `Enter`
+/// opens a match. Each match shows how its line reads after replacement.
+pub struct SearchWindow {
+    /// Folders searched, which result paths are shown relative to.
+    folders: WorkspaceFolders,
+    /// Text to find.
+    find: String,
+    /// Replacement text.
+    replace: String,
+    /// Part receiving typed keys.
+    field: SearchField,
+    /// Query the results are for, once a search ran.
+    searched: Option<String>,
+    /// Files with matches, in path order.
+    results: Vec<FileMatches>,
+    /// Result rows and the cursor among them.
+    rows: ListSelection<RowKey>,
+    /// Files and matches left out of the replacement.
+    excluded: BTreeSet<RowKey>,
+    /// Index of the first visible result row.
+    scroll_offset: usize,
+    /// Whether the next render scrolls the cursor into view.
+    follow_cursor: bool,
+    /// Styles to draw with.
+    theme: Theme,
+}
+
+impl SearchWindow {
+    /// Create an empty panel showing paths relative to `root`.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self::with_folders(WorkspaceFolders::new(root))
+    }
+
+    /// Create an empty panel searching the roots of `folders`, showing
+    /// paths relative to them.
+    pub fn with_folders(folders: WorkspaceFolders) -> Self {
+        Self {
+            folders,
+            find: String::new(),
+            replace: String::new(),
+            field: SearchField::Find,
+            searched: None,
+            results: Vec::new(),
+            rows: ListSelection::default(),
+            excluded: BTreeSet::new(),
+            scroll_offset: 0,
+            follow_cursor: true,
+            theme: Theme::default(),
+        }
+    }
+
+    /// Get the primary root, which result paths are shown relative to.
+    pub fn root(&self) -> &Path {
+        self.folders.primary()
+    }
+
+    /// Get the folders searched.
+    pub fn folders(&self) -> &WorkspaceFolders {
+        &self.folders
+    }
+
+    /// Search the roots of `folders` from the next search on.
+    pub fn set_folders(&mut self, folders: WorkspaceFolders) {
+        self.folders = folders;
+    }
+
+    /// Get the text to find.
+    pub fn find(&self) -> &str {
+        &self.find
+    }
+
+    /// Get the replacement text.
+    pub fn replace(&self) -> &str {
+        &self.replace
+    }
+
+    /// Get the part receiving typed keys.
+    pub fn field(&self) -> SearchField {
+        self.field
+    }
+
+    /// Check whether typed keys edit the find or replace text.
+    pub fn is_editing(&self) -> bool {
+        self.field != SearchField::Results
+    }
+
+    /// Get the query the results are for, if a search ran.
+    pub fn searched(&self) -> Option<&str> {
+        self.searched.as_deref()
+    }
+
+    /// Get the files with matches.
+    pub fn results(&self) -> &[FileMatches] {
+        &self.results
+    }
+
+    /// Show the results of searching for `query`.
+    ///
+    /// Every match starts out included.
+    pub fn set_results(&mut self, query: impl Into<String>, results: Vec<FileMatches>) {
+        self.searched = Some(query.into());
+        self.results = results;
+        self.excluded.clear();
+        let rows = self
+            .results
+            .iter()
+            .enumerate()
+            .flat_map(|(file, matches)| {
+                std::iter::once((file, None))
+                    .chain((0..matches.matches.len()).map(move |found| (file, Some(found))))
+            })
+            .collect();
+        self.rows = ListSelection::new(rows);
+        self.scroll_offset = 0;
+        self.follow_cursor = true;
+    }
+
+    /// Check whether the match `found` of file `file` will be replaced.
+    pub fn is_included(&self, file: usize, found: usize) -> bool {
+        !self.excluded.contains(&(file, None)) && !self.excluded.contains(&(file, Some(found)))
+    }
+
+    /// The edit replacing every included match with the replacement text.
+    pub fn replace_edit(&self) -> WorkspaceEdit {
+        let mut edit = WorkspaceEdit::new();
+        for (file, matches) in self.results.iter().enumerate() {
+            let included = matches
+                .matches
+                .iter()
+                .enumerate()
+                .filter(|&(found, _)| self.is_included(file, found))
+                .map(|(_, found)| found);
+            for text_edit in search::replace_edits(included, &self.replace) {
+                edit.push(matches.path.clone(), text_edit);
+            }
+        }
+        edit
+    }
+
+    /// Handle a key press.
+    ///
+    /// While a field is active every key except `Tab` is consumed.
+    pub fn handle_key(&mut self, key: AppKey) -> SearchOutcome {
+        let outcome = match self.field {
+            SearchField::Find | SearchField::Replace => self.handle_field_key(key),
+            SearchField::Results => self.handle_results_key(key),
+        };
+        if outcome != SearchOutcome::Ignored {
+            self.follow_cursor = true;
+        }
+        outcome
+    }
+
+    /// Handle a key while the find or replace field is active.
+    fn handle_field_key(&mut self, key: AppKey) -> SearchOutcome {
+        let text = match self.field {
+            SearchField::Find => &mut self.find,
+            _ => &mut self.replace,
+        };
+        match key {
+            AppKey::Tab => return SearchOutcome::Ignored,
+            AppKey::Enter => return SearchOutcome::Search(self.find.clone()),
+            AppKey::Backspace => {
+                text.pop();
+            }
+            // `q` is translated to AppKey::Q; inside a field it is text.
+            AppKey::Q => text.push('q'),
+            AppKey::Char(c) => text.push(c),
+            AppKey::Up => self.field = SearchField::Find,
+            AppKey::Down if self.field == SearchField::Find => self.field = SearchField::Replace,
+            AppKey::Down | AppKey::Esc if !self.rows.is_empty() => {
+                self.field = SearchField::Results;
+            }
+            _ => {}
+        }
+        SearchOutcome::Handled
+    }
+
+    /// Handle a key while the results are active.
+    fn handle_results_key(&mut self, key: AppKey) -> SearchOutcome {
+        match key {
+            AppKey::Up if self.rows.cursor().unwrap_or(0) == 0 => {
+                self.field = SearchField::Replace;
+            }
+            AppKey::Up | AppKey::Down => self.rows.move_by(if key == AppKey::Up { -1 } else { 1 }),
+            AppKey::Char(' ') => {
+                if let Some(&row) = self.rows.current() {
+                    if !self.excluded.remove(&row) {
+                        self.excluded.insert(row);
+                    }
+                }
+            }
+            AppKey::Enter => {
+                let Some(&(file, found)) = self.rows.current() else {
+                    return SearchOutcome::Handled;
+                };
+                let matches = &self.results[file];
+                let range = matches.matches[found.unwrap_or(0)].range.clone();
+                return SearchOutcome::Open(matches.path.clone(), range);
+            }
+            _ => return SearchOutcome::Ignored,
+        }
+        SearchOutcome::Handled
+    }
+
+    /// The line showing a field's label and text, highlighted while active.
+    fn field_line(&self, field: SearchField, focused: bool) -> Line<'_> {
+        let (label, text) = match field {
+            SearchField::Find => ("Find:    ", &self.find),
+            _ => ("Replace: ", &self.replace),
+        };
+        let label_style = if focused && self.field == field {
+            self.theme.selection
+        } else {
+            self.theme.text
+        };
+        Line::from(vec![
+            Span::styled(label, label_style),
+            Span::styled(text.as_str(), self.theme.text),
+        ])
+    }
+
+    /// The line counting the results.
+    fn summary_line(&self) -> Line<'_> {
+        let matches: usize = self.results.iter().map(|file| file.matches.len()).sum();
+        let text = match (&self.searched, matches) {
+            (None, _) => String::new(),
+            (Some(_), 0) => "No results".to_string(),
+            (Some(_), _) => format!(
+                "{} in {}",
+                search::counted(matches, "result"),
+                search::counted(self.results.len(), "file")
+            ),
+        };
+        Line::styled(text, self.theme.text)
+    }
+
+    /// The line for a result row.
+    fn result_line(&self, (file, found): RowKey, selected: bool) -> Line<'_> {
+        let style = if selected {
+            self.theme.selection
+        } else {
+            self.theme.text
+        };
+        let matches = &self.results[file];
+        let Some(found) = found else {
+            let check = if self.excluded.contains(&(file, None)) {
+                "[ ]"
+            } else {
+                "[x]"
+            };
+            let path = self.folders.label(&matches.path);
+            let text = format!("{check} {path} ({})", matches.matches.len());
+            return Line::styled(text, style);
+        };
+        let check = if self.is_included(file, found) {
+            "[x]"
+        } else {
+            "[ ]"
+        };
+        let SearchMatch {
+            line,
+            line_text,
+            column,
+            ..
+        } = &matches.matches[found];
+        let indent = line_text.len() - line_text.trim_start().len();
+        let start = indent.min(column.start);
+        let mut spans = vec![
+            Span::styled(format!("    {check} {}: ", line + 1), style),
+            Span::styled(&line_text[start..column.start], style),
+        ];
+        let old = &line_text[column.clone()];
+        if self.replace.is_empty() {
+            spans.push(Span::styled(old, style.add_modifier(Modifier::BOLD)));
+        } else {
+            spans.push(Span::styled(old, style.add_modifier(Modifier::CROSSED_OUT)));
+            spans.push(Span::styled(
+                self.replace.as_str(),
+                style.add_modifier(Modifier::BOLD),
+            ));
+        }
+        spans.push(Span::styled(&line_text[column.end..], style));
+        Line::from(spans)
+    }
+}
+
+impl Window for SearchWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.render_with_focus(frame, area, false);
+    }
+
+    fn render_with_focus(&mut self, frame: &mut Frame, area: Rect, focused: bool) {
+        let border_type = if focused {
+            BorderType::Thick
+        } else {
+            BorderType::Plain
+        };
+        let title = if focused { "Search [*]" } else { "Search" };
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_type(border_type)
+            .border_style(self.theme.border_style(focused));
+        let height = usize::from(block.inner(area).height.saturating_sub(HEADER_ROWS));
+
+        if self.follow_cursor && height > 0 {
+            if let Some(cursor) = self.rows.cursor() {
+                self.scroll_offset = reveal_row(self.scroll_offset, cursor, height);
+            }
+            self.follow_cursor = false;
+        }
+        self.scroll_offset = self
+            .scroll_offset
+            .min(self.rows.len().saturating_sub(height));
+
+        let cursor = self.rows.cursor();
+        let results_focused = focused && self.field == SearchField::Results;
+        let mut lines = vec![
+            self.field_line(SearchField::Find, focused),
+            self.field_line(SearchField::Replace, focused),
+            self.summary_line(),
+        ];
+        lines.extend(
+            self.rows
+                .items()
+                .iter()
+                .enumerate()
+                .skip(self.scroll_offset)
+                .take(height)
+                .map(|(index, &row)| {
+                    self.result_line(row, results_focused && Some(index) == cursor)
+                }),
+        );
+        let paragraph = Paragraph::new(lines).style(self.theme.text).block(block);
+        frame.render_widget(paragraph, area);
+    }
+
+    fn scroll_by(&mut self, lines: i32) {
+        let last_row = self.rows.len().saturating_sub(1);
+        let offset = i64::try_from(self.scroll_offset).unwrap_or(i64::MAX) + i64::from(lines);
+        self.scroll_offset = usize::try_from(offset.max(0))
+            .unwrap_or(usize::MAX)
+            .min(last_row);
+        self.follow_cursor = false;
+    }
+
+    fn context(&self) -> Option<WindowContext> {
+        Some(WindowContext::Search)
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::find_in_text;
+
+    fn panel() -> SearchWindow {
+        let mut panel = SearchWindow::new("/ws");
+        let results = vec![
+            FileMatches {
+                path: PathBuf::from("/ws/a.rs"),
+                matches: find_in_text("foo foo", "foo"),
+            },
+            FileMatches {
+                path: PathBuf::from("/ws/b.rs"),
+                matches: find_in_text("\nfoo", "foo"),
+            },
+        ];
+        panel.set_results("foo", results);
+        panel
+    }
+
+    fn type_text(panel: &mut SearchWindow, text: &str) {
+        for c in text.chars() {
+            panel.handle_key(AppKey::Char(c));
+        }
+    }
+
+    #[test]
+    fn test_fields_capture_typing_and_enter_searches() {
+        let mut panel = SearchWindow::new("/ws");
+        type_text(&mut panel, "fo");
+        panel.handle_key(AppKey::Q);
+        assert_eq!(panel.find(), "foq");
+        panel.handle_key(AppKey::Down);
+        type_text(&mut panel, "bar");
+        assert_eq!(panel.replace(), "bar");
+        assert_eq!(
+            panel.handle_key(AppKey::Enter),
+            SearchOutcome::Search("foq".to_string())
+        );
+        // No results to move to, and Tab leaves the panel
+        assert_eq!(panel.handle_key(AppKey::Down), SearchOutcome::Handled);
+        assert!(panel.is_editing());
+        assert_eq!(panel.handle_key(AppKey::Tab), SearchOutcome::Ignored);
+    }
+
+    #[test]
+    fn test_excluded_files_and_matches_are_not_replaced() {
+        let mut panel = panel();
+        panel.handle_key(AppKey::Down);
+        type_text(&mut panel, "x");
+        panel.handle_key(AppKey::Esc);
+        assert_eq!(panel.field(), SearchField::Results);
+
+        // Exclude the second match of a.rs and all of b.rs
+        panel.handle_key(AppKey::Down);
+        panel.handle_key(AppKey::Down);
+        panel.handle_key(AppKey::Char(' '));
+        panel.handle_key(AppKey::Down);
+        panel.handle_key(AppKey::Char(' '));
+        assert!(panel.is_included(0, 0));
+        assert!(!panel.is_included(0, 1));
+        assert!(!panel.is_included(1, 0));
+
+        let edit = panel.replace_edit();
+        assert_eq!(edit.changes.len(), 1);
+        assert_eq!(
+            edit.changes[Path::new("/ws/a.rs")],
+            vec![crate::code_action::TextEdit::new(0..3, "x")]
+        );
+    }
+
+    #[test]
+    fn test_enter_opens_match_and_up_returns_to_fields() {
+        let mut panel = panel();
+        panel.handle_key(AppKey::Down);
+        panel.handle_key(AppKey::Down);
+        assert_eq!(panel.field(), SearchField::Results);
+        for _ in 0..3 {
+            panel.handle_key(AppKey::Down);
+        }
+        assert_eq!(
+            panel.handle_key(AppKey::Enter),
+            SearchOutcome::Open(PathBuf::from("/ws/b.rs"), 1..4)
+        );
+        assert_eq!(panel.handle_key(AppKey::Char('R')), SearchOutcome::Ignored);
+
+        for _ in 0..4 {
+            panel.handle_key(AppKey::Up);
+        }
+        assert_eq!(panel.field(), SearchField::Replace);
+        panel.handle_key(AppKey::Up);
+        assert_eq!(panel.field(), SearchField::Find);
+    }
+}
@@ -6,9 +6,9 @@
 
 #[cfg(test)]
 mod tests {
+    use crate::harness::buffer_to_string;
     use crate::window::{EditorWindow, TerminalWindow, Window};
     use ratatui::backend::TestBackend;
-    use ratatui::buffer::Buffer;
     use ratatui::layout::{Constraint, Direction, Layout, Rect};
     use ratatui::Terminal;
 
@@ -52,23 +52,6 @@ mod tests {
         buffer_to_string(terminal.backend().buffer())
     }
 
-    /// Convert a ratatui Buffer to a string representation.
-    /// Each row is separated by a newline.
-    fn buffer_to_string(buffer: &Buffer) -> String {
-        let area = buffer.area;
-        let mut result = String::new();
-
-        for y in 0..area.height {
-            for x in 0..area.width {
-                let cell = buffer.cell((x, y)).unwrap();
-                result.push_str(cell.symbol());
-            }
-            result.push('\n');
-        }
-
-        result
-    }
-
     // ============================================================
     // Test: EditorWindow renders correctly
     // ============================================================
@@ -285,4 +268,885 @@ mod tests {
             );
         }
     }
+
+    // ============================================================
+    // Test: Soft line-length limit highlighting
+    // ============================================================
+
+    #[test]
+    fn editor_highlights_characters_past_line_limit() {
+        use crate::line_length::LineLengthLimit;
+        use ratatui::style::{Color, Modifier};
+
+        let mut editor = EditorWindow::with_text("abcdefghij");
+        editor.set_line_length_limit(Some(LineLengthLimit::new(5)));
+
+        let backend = TestBackend::new(20, 3);
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal
+            .draw(|f| {
+                let area = f.area();
+                editor.render(f, area);
+            })
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        // Content starts at (1, 1) inside the border; column 5 is 'f'
+        let within = buffer.cell((5, 1)).unwrap();
+        let past = buffer.cell((6, 1)).unwrap();
+
+        assert_eq!(within.symbol(), "e");
+        assert_ne!(within.fg, Color::Red);
+        assert_eq!(past.symbol(), "f");
+        assert_eq!(past.fg, Color::Red);
+        assert!(past.modifier.contains(Modifier::UNDERLINED));
+    }
+
+    #[test]
+    fn editor_renders_selection_reversed() {
+        use ratatui::style::Modifier;
+
+        let mut editor = EditorWindow::with_text("foo(bar)");
+        editor.set_selection(5..5);
+        editor.expand_selection();
+        assert_eq!(editor.selected_text(), "bar");
+
+        let backend = TestBackend::new(20, 3);
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal
+            .draw(|f| {
+                let area = f.area();
+                editor.render(f, area);
+            })
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        // Content starts at x = 1; "bar" occupies x = 5..8
+        assert!(!buffer
+            .cell((4, 1))
+            .unwrap()
+            .modifier
+            .contains(Modifier::REVERSED));
+        for x in 5..8 {
+            assert!(buffer
+                .cell((x, 1))
+                .unwrap()
+                .modifier
+                .contains(Modifier::REVERSED));
+        }
+        assert!(!buffer
+            .cell((8, 1))
+            .unwrap()
+            .modifier
+            .contains(Modifier::REVERSED));
+    }
+
+    // ============================================================
+    // Test: Buffer-local options
+    // ============================================================
+
+    #[test]
+    fn editor_wraps_long_lines_when_modeline_enables_wrap() {
+        use crate::buffer_options::OptionOverrides;
+
+        let mut editor = EditorWindow::with_text("paradiddle: wrap\nabcdefghijklmnop");
+        let output = render_window_to_string(&mut editor, 12, 6);
+        assert!(!output.contains("klmnop"), "Unwrapped line is truncated");
+
+        editor
+            .resolve_options(&OptionOverrides::new(), None)
+            .unwrap();
+        assert!(editor.options().wrap);
+        let output = render_window_to_string(&mut editor, 12, 6);
+        assert!(output.contains("abcdefghij"));
+        assert!(
+            output.contains("klmnop"),
+            "Wrapped remainder should be visible"
+        );
+    }
+
+    // ============================================================
+    // Test: Peek window
+    // ============================================================
+
+    #[test]
+    fn editor_renders_peek_below_selection_line() {
+        use crate::definition::Location;
+        use crate::window::PeekWindow;
+
+        let mut editor = EditorWindow::with_text("call();\nafter");
+        let target = "// docs\nfn callee() {}\n";
+        let start = target.find("fn").unwrap();
+        editor.open_peek(PeekWindow::new(
+            Location::new("lib.rs", start..start + 2),
+            target,
+        ));
+
+        let output = render_window_to_string(&mut editor, 30, 14);
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines[1].contains("call();"), "Editor line stays visible");
+        assert!(lines[2].contains("lib.rs:2"), "Peek opens below line");
+        assert!(lines[3].contains("// docs"));
+        assert!(lines[4].contains("fn callee()"));
+        assert!(!output.contains("after"), "Peek covers following lines");
+
+        editor.close_peek();
+        let output = render_window_to_string(&mut editor, 30, 14);
+        assert!(output.contains("after"));
+    }
+
+    // ============================================================
+    // Test: Rename input
+    // ============================================================
+
+    #[test]
+    fn editor_renders_rename_input_below_symbol() {
+        use crate::window::RenameInputWindow;
+
+        let mut editor = EditorWindow::with_text("one\n  let value = 1;\nthree");
+        editor.open_rename(RenameInputWindow::new(10..15, "value"));
+
+        let output = render_window_to_string(&mut editor, 40, 10);
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines[2].contains("let value = 1;"), "Symbol line visible");
+        // Input box starts at the symbol's column (border + 6)
+        let column = lines[3].chars().position(|c| c == '╭');
+        assert_eq!(column, Some(7));
+        assert!(lines[3].contains("╭Rename"));
+        assert!(lines[4].contains("│value"));
+    }
+
+    #[test]
+    fn editor_renders_rename_input_above_symbol_on_last_line() {
+        use crate::window::RenameInputWindow;
+
+        let mut editor = EditorWindow::with_text("a\nb\nc\nlast");
+        editor.open_rename(RenameInputWindow::new(6..10, "last"));
+
+        let output = render_window_to_string(&mut editor, 40, 6);
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines[1].contains("╭Rename"));
+        assert!(lines[4].contains("last"));
+    }
+
+    // ============================================================
+    // Test: Syntax highlighting
+    // ============================================================
+
+    #[test]
+    fn editor_highlights_tokens_by_language() {
+        use crate::theme::Theme;
+
+        let mut editor = EditorWindow::with_text("fn main() {} // hi");
+        editor.set_language(Some("rust".to_string()));
+
+        let backend = TestBackend::new(30, 3);
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal
+            .draw(|f| {
+                let area = f.area();
+                editor.render_with_focus(f, area, true);
+            })
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let theme = Theme::dark();
+        assert_eq!(buffer.cell((1, 1)).unwrap().fg, theme.keyword.fg.unwrap());
+        assert_eq!(buffer.cell((4, 1)).unwrap().fg, theme.text.fg.unwrap());
+        assert_eq!(buffer.cell((14, 1)).unwrap().fg, theme.comment.fg.unwrap());
+    }
+
+    #[test]
+    fn editor_colors_bracket_pairs_by_depth() {
+        use crate::buffer_options::BufferOptions;
+        use crate::theme::Theme;
+
+        let mut editor = EditorWindow::with_text("f(a[0]) }");
+        let theme = Theme::dark();
+        let render = |editor: &mut EditorWindow| {
+            let backend = TestBackend::new(20, 3);
+            let mut terminal = Terminal::new(backend).unwrap();
+            terminal
+                .draw(|f| {
+                    let area = f.area();
+                    editor.render_with_focus(f, area, true);
+                })
+                .unwrap();
+            terminal.backend().buffer().clone()
+        };
+
+        let buffer = render(&mut editor);
+        let fg = |x: u16| buffer.cell((x, 1)).unwrap().fg;
+        assert_eq!(fg(2), theme.bracket1.fg.unwrap());
+        assert_eq!(fg(4), theme.bracket2.fg.unwrap());
+        assert_eq!(fg(6), theme.bracket2.fg.unwrap());
+        assert_eq!(fg(7), theme.bracket1.fg.unwrap());
+        assert_eq!(fg(9), theme.error.fg.unwrap(), "unmatched bracket");
+
+        editor.set_options(BufferOptions {
+            bracket_pairs: false,
+            ..BufferOptions::default()
+        });
+        let buffer = render(&mut editor);
+        assert_eq!(buffer.cell((2, 1)).unwrap().fg, theme.text.fg.unwrap());
+    }
+
+    #[test]
+    fn editor_shows_swatches_before_color_values() {
+        use crate::buffer_options::BufferOptions;
+        use crate::theme::Theme;
+
+        let mut editor = EditorWindow::with_text("a: #ff0000;");
+        let theme = Theme::dark();
+        let render = |editor: &mut EditorWindow| {
+            let backend = TestBackend::new(20, 3);
+            let mut terminal = Terminal::new(backend).unwrap();
+            terminal
+                .draw(|f| {
+                    let area = f.area();
+                    editor.render_with_focus(f, area, true);
+                })
+                .unwrap();
+            terminal.backend().buffer().clone()
+        };
+
+        let buffer = render(&mut editor);
+        let swatch = buffer.cell((4, 1)).unwrap();
+        assert_eq!(swatch.symbol(), "■");
+        assert_eq!(swatch.fg, ratatui::style::Color::Rgb(255, 0, 0));
+        assert_eq!(buffer.cell((5, 1)).unwrap().symbol(), "#");
+        assert_eq!(buffer.cell((5, 1)).unwrap().fg, theme.text.fg.unwrap());
+
+        editor.set_options(BufferOptions {
+            color_swatches: false,
+            ..BufferOptions::default()
+        });
+        let buffer = render(&mut editor);
+        assert_eq!(buffer.cell((4, 1)).unwrap().symbol(), "#");
+    }
+
+    #[test]
+    fn editor_underlines_links() {
+        let mut editor = EditorWindow::with_text("at https://a.io.");
+        let backend = TestBackend::new(30, 3);
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal
+            .draw(|f| {
+                let area = f.area();
+                editor.render_with_focus(f, area, true);
+            })
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let underlined = |x: u16| {
+            buffer
+                .cell((x, 1))
+                .unwrap()
+                .modifier
+                .contains(ratatui::style::Modifier::UNDERLINED)
+        };
+        assert!(!underlined(3));
+        assert!((4..16).all(underlined));
+        assert!(!underlined(16), "trailing period");
+    }
+
+    #[test]
+    fn editor_styles_grapheme_clusters_as_a_whole() {
+        let mut editor = EditorWindow::with_text("e\u{301}漢x");
+        editor.set_selection(0..1);
+        let backend = TestBackend::new(20, 3);
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal
+            .draw(|f| {
+                let area = f.area();
+                editor.render_with_focus(f, area, true);
+            })
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let selected = |x: u16| {
+            buffer
+                .cell((x, 1))
+                .unwrap()
+                .modifier
+                .contains(ratatui::style::Modifier::REVERSED)
+        };
+        assert_eq!(buffer.cell((1, 1)).unwrap().symbol(), "e\u{301}");
+        assert!(selected(1) && !selected(2));
+        assert_eq!(buffer.cell((2, 1)).unwrap().symbol(), "漢");
+        assert_eq!(buffer.cell((4, 1)).unwrap().symbol(), "x");
+    }
+
+    #[test]
+    fn editor_rerender_reuses_unchanged_lines() {
+        let mut editor = EditorWindow::with_text("fn a() {}\nlet b = 1;\nlet c = 2;");
+        editor.set_path(Some(std::path::PathBuf::from("lib.rs")));
+        render_window_to_string(&mut editor, 30, 6);
+        assert_eq!(editor.highlight_cache().last_tokenized(), 3);
+
+        render_window_to_string(&mut editor, 30, 6);
+        assert_eq!(editor.highlight_cache().last_tokenized(), 0);
+
+        editor.set_text("fn a() {}\nlet b = 10;\nlet c = 2;");
+        render_window_to_string(&mut editor, 30, 6);
+        assert_eq!(editor.highlight_cache().last_tokenized(), 1);
+    }
+
+    // ============================================================
+    // Test: Input box
+    // ============================================================
+
+    #[test]
+    fn input_box_renders_title_value_and_error() {
+        use crate::window::InputBoxWindow;
+
+        let mut input = InputBoxWindow::new("Save As", "Untitled-1");
+        let output = render_window_to_string(&mut input, 30, 3);
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines[0].contains("╭Save As"));
+        assert!(lines[1].contains("│Untitled-1"));
+
+        input.set_error(Some("Permission denied".to_string()));
+        let output = render_window_to_string(&mut input, 30, 3);
+        assert!(output.lines().nth(2).unwrap().contains("Permission denied"));
+    }
+
+    #[test]
+    fn command_line_lists_completions_in_a_popup_above_the_prompt() {
+        use crate::input::AppKey;
+        use crate::window::CommandLineWindow;
+
+        let mut line = CommandLineWindow::new(Vec::new(), "/", Vec::new());
+        line.handle_key(AppKey::Char('s'));
+        line.handle_key(AppKey::Tab);
+        let output = render_window_to_string(&mut line, 30, 6);
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines[1].starts_with("╭"));
+        assert!(lines[2].contains("│ set "));
+        assert!(lines[3].contains("│ split "));
+        assert!(lines[4].starts_with("╰"));
+        assert!(lines[5].starts_with(":set"));
+    }
+
+    // ============================================================
+    // Test: File explorer
+    // ============================================================
+
+    #[test]
+    fn file_tree_renders_indented_rows_and_scrolls_to_cursor() {
+        use crate::input::AppKey;
+        use crate::window::FileTreeWindow;
+        use cli_ide_base::test_support::{temp_dir, Cleanup};
+
+        let root = temp_dir("tree-render");
+        let _cleanup = Cleanup::new([&root]);
+        std::fs::create_dir(root.join("src")).unwrap();
+        for name in ["a.rs", "b.rs", "c.rs"] {
+            std::fs::write(root.join("src").join(name), "").unwrap();
+        }
+        let mut tree = FileTreeWindow::new(&root);
+        tree.handle_key(AppKey::Right);
+        tree.handle_key(AppKey::Down);
+        tree.handle_key(AppKey::Char(' '));
+
+        let output = render_window_to_string(&mut tree, 20, 4);
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines[0].contains("Explorer"));
+        assert!(lines[1].contains("  ▾ src/"));
+        assert!(lines[2].contains("*     a.rs"));
+
+        // Moving past the last visible row scrolls it into view
+        tree.handle_key(AppKey::Down);
+        tree.handle_key(AppKey::Down);
+        let output = render_window_to_string(&mut tree, 20, 4);
+        assert_eq!(tree.scroll_offset(), 2);
+        assert!(output.lines().nth(2).unwrap().contains("c.rs"));
+    }
+
+    #[test]
+    fn editor_with_several_tabs_draws_tab_strip() {
+        use crate::buffer_manager::TextBuffer;
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let mut editor = EditorWindow::with_text("first");
+        editor.set_path(Some("/ws/a.rs".into()));
+        let mut second = TextBuffer::new("second");
+        second.set_path(Some("/ws/b.rs".into()));
+        second.set_text("second, edited");
+        editor.open_buffer(Rc::new(RefCell::new(second)));
+
+        let output = render_window_to_string(&mut editor, 30, 4);
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines[1].contains(" a.rs  b.rs ● "));
+        assert!(lines[2].contains("second, edited"));
+
+        // A single tab needs no strip
+        editor.close_tab();
+        let output = render_window_to_string(&mut editor, 30, 4);
+        assert!(output.lines().nth(1).unwrap().contains("first"));
+    }
+
+    #[test]
+    fn editor_tabs_and_title_are_shortened_when_narrow() {
+        use crate::buffer_manager::TextBuffer;
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let mut editor = EditorWindow::with_text("first");
+        editor.set_path(Some("/ws/configuration_loader.rs".into()));
+        editor.set_title("Editor: configuration_loader.rs");
+        let mut second = TextBuffer::new("second");
+        second.set_path(Some("/ws/keybinding_router.rs".into()));
+        editor.open_buffer(Rc::new(RefCell::new(second)));
+
+        let output = render_window_to_string(&mut editor, 30, 4);
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines[0].contains("Editor: config…ion_loader.rs"));
+        assert!(lines[1].contains(" config…er.rs  keybin…er.rs "));
+    }
+
+    #[test]
+    fn search_panel_previews_replacements() {
+        use crate::input::AppKey;
+        use crate::search::{find_in_text, FileMatches};
+        use crate::window::SearchWindow;
+
+        let mut panel = SearchWindow::new("/ws");
+        panel.handle_key(AppKey::Down);
+        for c in "new".chars() {
+            panel.handle_key(AppKey::Char(c));
+        }
+        panel.set_results(
+            "old",
+            vec![FileMatches {
+                path: "/ws/src/lib.rs".into(),
+                matches: find_in_text("    let old = 1;", "old"),
+            }],
+        );
+
+        let output = render_window_to_string(&mut panel, 40, 7);
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines[0].contains("Search"));
+        assert!(lines[1].contains("Find:"));
+        assert!(lines[2].contains("Replace: new"));
+        assert!(lines[3].contains("1 result in 1 file"));
+        assert!(lines[4].contains("[x] src/lib.rs (1)"));
+        assert!(lines[5].contains("[x] 1: let oldnew = 1;"));
+    }
+
+    #[test]
+    fn regex_tester_highlights_matches_in_sample() {
+        use crate::theme::Theme;
+        use crate::window::RegexTesterWindow;
+
+        let mut tester = RegexTesterWindow::new();
+        tester.set_sample("ab12");
+        tester.set_pattern("[a-z](\\d)?");
+
+        let backend = TestBackend::new(24, 7);
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal
+            .draw(|f| {
+                let area = f.area();
+                tester.render(f, area);
+            })
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let row = |y: u16| -> String {
+            (0..24)
+                .map(|x| buffer.cell((x, y)).unwrap().symbol())
+                .collect()
+        };
+        assert!(row(0).contains("Regex Tester"));
+        assert!(row(1).contains("Pattern: [a-z](\\d)?"));
+        assert!(row(2).contains("2 matches"));
+        assert!(row(3).contains("Sample"));
+        assert!(row(4).contains("ab12"));
+
+        // "a" and "b1" match; the group "1" is drawn on top of its match
+        let theme = Theme::default();
+        let style = |x: u16| buffer.cell((x, 4)).unwrap().style();
+        assert_eq!(style(2).bg, theme.find_match.bg);
+        assert_eq!(style(3).bg, theme.find_match.bg);
+        assert_eq!(style(4).bg, theme.match_group.bg);
+        assert_ne!(style(5).bg, theme.find_match.bg);
+    }
+
+    #[test]
+    fn editor_underlines_diagnostics_from_their_column_to_the_word_end() {
+        use crate::theme::Theme;
+        use cli_ide_platform::problems::problem::{Problem, Severity};
+        use ratatui::style::Modifier;
+
+        let mut editor = EditorWindow::with_text("let x = 1;\nfoo();\n");
+        editor.set_path(Some("/ws/a.rs".into()));
+        let problem = |line, column, severity| Problem {
+            path: "/ws/a.rs".into(),
+            line,
+            column,
+            severity,
+            message: "oops".to_string(),
+            code: None,
+        };
+        editor.set_diagnostics(&[
+            problem(1, 5, Severity::Error),
+            problem(2, 1, Severity::Warning),
+        ]);
+
+        let backend = TestBackend::new(30, 5);
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal
+            .draw(|f| {
+                let area = f.area();
+                editor.render(f, area);
+            })
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let theme = Theme::default();
+        let start = (0..30)
+            .find(|&x| buffer.cell((x, 1)).unwrap().symbol() == "l")
+            .unwrap();
+        let style = |x: u16, y: u16| buffer.cell((start + x, y)).unwrap().style();
+        let underlined = |x: u16, y: u16| style(x, y).add_modifier.contains(Modifier::UNDERLINED);
+        assert!(underlined(4, 1), "the `x` the error is at");
+        assert_eq!(style(4, 1).fg, theme.diagnostic_error.fg);
+        assert!(!underlined(3, 1) && !underlined(5, 1));
+        assert!((0..3).all(|x| underlined(x, 2)), "the word `foo`");
+        assert_eq!(style(0, 2).fg, theme.diagnostic_warning.fg);
+        assert!(!underlined(3, 2));
+    }
+
+    #[test]
+    fn output_pane_follows_new_lines_until_scrolled_up() {
+        use crate::window::OutputWindow;
+        use cli_ide_platform::output::output_service::OutputService;
+        use std::sync::Arc;
+
+        let service = Arc::new(OutputService::new());
+        let channel = service.channel("Cargo");
+        for line in ["a", "b", "c", "d", "e"] {
+            channel.append_line(line);
+        }
+        let mut output = OutputWindow::new(service);
+        let visible = |output: &mut OutputWindow| -> Vec<String> {
+            render_window_to_string(output, 20, 5)
+                .lines()
+                .skip(1)
+                .take(3)
+                .map(|line| line.trim_matches(|c| c == '│' || c == ' ').to_string())
+                .collect()
+        };
+        assert_eq!(visible(&mut output), ["c", "d", "e"]);
+
+        output.scroll_by(-1);
+        assert!(!output.is_following());
+        channel.append_line("f");
+        assert_eq!(visible(&mut output), ["b", "c", "d"]);
+
+        output.scroll_by(10);
+        assert!(output.is_following());
+        assert_eq!(visible(&mut output), ["d", "e", "f"]);
+    }
+
+    #[test]
+    fn log_window_highlights_errors_and_warnings() {
+        use crate::theme::Theme;
+        use crate::window::LogWindow;
+        use cli_ide_platform::logging::log_service::{LogLevel, LogRecord};
+
+        let mut logs = LogWindow::new(vec![
+            LogRecord::new(LogLevel::Info, "app", "started"),
+            LogRecord::new(LogLevel::Error, "app", "save failed"),
+        ]);
+        logs.append(LogRecord::new(LogLevel::Warn, "app", "config ignored"));
+
+        let screen = render_window_to_string(&mut logs, 80, 5);
+        let rows: Vec<&str> = screen.lines().collect();
+        assert!(rows[1].contains(" INFO app: started"));
+        assert!(rows[2].contains("ERROR app: save failed"));
+        assert!(rows[3].contains(" WARN app: config ignored"));
+
+        let backend = TestBackend::new(80, 5);
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal.draw(|f| logs.render(f, f.area())).unwrap();
+        let buffer = terminal.backend().buffer();
+        let theme = Theme::default();
+        assert_eq!(buffer.cell((1, 1)).unwrap().fg, theme.text.fg.unwrap());
+        assert_eq!(buffer.cell((1, 2)).unwrap().fg, theme.error.fg.unwrap());
+        assert_eq!(buffer.cell((1, 3)).unwrap().fg, theme.warning.fg.unwrap());
+    }
+
+    #[test]
+    fn log_window_highlights_the_dragged_characters() {
+        use crate::window::LogWindow;
+        use cli_ide_platform::logging::log_service::{LogLevel, LogRecord};
+        use ratatui::style::Modifier;
+
+        let mut logs = LogWindow::new(vec![
+            LogRecord::new(LogLevel::Info, "app", "started"),
+            LogRecord::new(LogLevel::Info, "app", "stopped"),
+        ]);
+        logs.select_at(0, 3, false);
+        logs.select_at(1, 2, true);
+        assert_eq!(
+            logs.selected_text().unwrap(),
+            format!(
+                "{}\n{}",
+                &logs.records()[0].to_string()[3..],
+                &logs.records()[1].to_string()[..2]
+            )
+        );
+
+        let backend = TestBackend::new(80, 4);
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal
+            .draw(|f| logs.render_with_focus(f, f.area(), true))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let selected = |x: u16, y: u16| {
+            let modifier = buffer.cell((x, y)).unwrap().modifier;
+            modifier.contains(Modifier::REVERSED)
+        };
+        assert!(!selected(3, 1) && selected(4, 1) && selected(40, 1));
+        assert!(selected(1, 2) && selected(2, 2) && !selected(3, 2));
+    }
+
+    // ============================================================
+    // Test: Themes
+    // ============================================================
+
+    #[test]
+    fn editor_draws_with_light_theme() {
+        use crate::theme::Theme;
+        use ratatui::style::Color;
+
+        let mut editor = EditorWindow::with_text("light");
+        editor.set_theme(&Theme::light());
+
+        let backend = TestBackend::new(20, 3);
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal
+            .draw(|f| {
+                let area = f.area();
+                editor.render_with_focus(f, area, true);
+            })
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let border = buffer.cell((0, 0)).unwrap();
+        let text = buffer.cell((1, 1)).unwrap();
+        let blank = buffer.cell((10, 1)).unwrap();
+        assert_eq!(border.fg, Color::Blue, "focused border");
+        assert_eq!(text.symbol(), "l");
+        assert_eq!(text.fg, Color::Black);
+        assert_eq!(blank.bg, Color::White, "background fills the pane");
+    }
+
+    // ============================================================
+    // Test: Tutorial panel
+    // ============================================================
+
+    #[test]
+    fn tutorial_panel_checks_done_steps_and_shows_the_next() {
+        use crate::tutorial::{Tutorial, TutorialState};
+        use crate::window::{TutorialWindow, TUTORIAL_HEIGHT, TUTORIAL_WIDTH};
+
+        let mut tutorial = Tutorial::new(TutorialState::default());
+        tutorial.update(&TutorialState {
+            focused: Some(crate::keybinding::WindowContext::Terminal),
+            ..TutorialState::default()
+        });
+        let mut panel = TutorialWindow::new(tutorial);
+        let output = render_window_to_string(&mut panel, TUTORIAL_WIDTH, TUTORIAL_HEIGHT);
+
+        assert!(output.contains("Tutorial 1/4"), "Output:\n{output}");
+        assert!(output.contains("✓ Switch focus"), "Output:\n{output}");
+        assert!(
+            output.contains("▸ Open the command palette"),
+            "Output:\n{output}"
+        );
+        assert!(output.contains("Press : to open"), "Output:\n{output}");
+        assert!(
+            output.contains("type to filter"),
+            "instruction fits.\nOutput:\n{output}"
+        );
+    }
+
+    // ============================================================
+    // Test: Terminal capability report
+    // ============================================================
+
+    #[test]
+    fn terminal_report_lists_capabilities_and_test_patterns() {
+        use crate::window::TerminalReportWindow;
+        use cli_ide_platform::environment::environment_service::EnvironmentService;
+
+        let environment = EnvironmentService::new([("TERM", "xterm"), ("COLORTERM", "truecolor")]);
+        let mut report =
+            TerminalReportWindow::new(environment.capabilities(), environment.variables().clone());
+        let output = render_window_to_string(&mut report, 60, 40);
+
+        assert!(
+            output.contains("Terminal Capabilities"),
+            "Output:\n{output}"
+        );
+        assert!(output.contains("24-bit color"), "Output:\n{output}");
+        assert!(output.contains("┏━┳━┓"), "box drawing.\nOutput:\n{output}");
+        assert!(output.contains("COLORTERM=truecolor"), "Output:\n{output}");
+    }
+
+    // ============================================================
+    // Test: Keybindings list
+    // ============================================================
+
+    #[test]
+    fn keybindings_list_groups_bindings_and_marks_hidden_ones() {
+        use crate::window::{KeybindingEntry, KeybindingsWindow};
+
+        let entry = |scope: &str, key: &str, action: &str, shadows: Option<&str>| KeybindingEntry {
+            scope: scope.to_string(),
+            key: key.to_string(),
+            action: action.to_string(),
+            shadows: shadows.map(str::to_string),
+        };
+        let mut list = KeybindingsWindow::new(vec![
+            entry("Global", ":", "Show All Commands", None),
+            entry("Global", "PageUp", "Scroll Page Up", None),
+            entry(
+                "Normal mode",
+                ":",
+                "Open Command Line",
+                Some("Show All Commands"),
+            ),
+        ]);
+        let output = render_window_to_string(&mut list, 70, 10);
+
+        assert!(output.contains("Keybindings"), "Output:\n{output}");
+        assert!(
+            output.contains(":       Show All Commands"),
+            "keys are padded to a column.\nOutput:\n{output}"
+        );
+        assert!(output.contains("Normal mode"), "Output:\n{output}");
+        assert!(
+            output.contains("Open Command Line  (hides Show All Commands)"),
+            "Output:\n{output}"
+        );
+    }
+
+    #[test]
+    fn snippets_list_shows_language_word_and_body_on_one_line() {
+        use crate::snippet::Snippet;
+        use crate::window::SnippetsWindow;
+
+        let snippet = |language: Option<&str>, word: &str, body: &str| Snippet {
+            language: language.map(str::to_string),
+            word: word.to_string(),
+            body: body.to_string(),
+        };
+        let mut list = SnippetsWindow::new(&[
+            snippet(None, "teh", "the"),
+            snippet(Some("rust"), "fn", "fn $0() {\n}"),
+        ]);
+        let output = render_window_to_string(&mut list, 50, 6);
+
+        assert!(output.contains("Snippets"), "Output:\n{output}");
+        assert!(output.contains("all   teh  the"), "Output:\n{output}");
+        assert!(
+            output.contains("rust  fn   fn $0() {\\n}"),
+            "line breaks are written as \\n.\nOutput:\n{output}"
+        );
+
+        let mut empty = SnippetsWindow::new(&[]);
+        let output = render_window_to_string(&mut empty, 50, 6);
+        assert!(output.contains("No snippets"), "Output:\n{output}");
+    }
+
+    #[test]
+    fn editor_gutter_marks_changed_lines() {
+        use crate::theme::Theme;
+        use cli_ide_base::test_support::{temp_path, Cleanup};
+        use cli_ide_platform::git::diff::{LineChange, LineChangeKind};
+
+        let path = temp_path("gutter.txt");
+        let _cleanup = Cleanup::new([&path]);
+        std::fs::write(&path, "one\ntwo\nthree\nfour\n").unwrap();
+        let mut editor = EditorWindow::default();
+        editor.open_file(&path).unwrap();
+        let unchanged = render_window_to_string(&mut editor, 20, 6);
+        assert!(
+            unchanged.contains("│one"),
+            "no gutter yet.\nOutput:\n{unchanged}"
+        );
+
+        let change = |line, kind| LineChange { line, kind };
+        editor.set_line_changes(
+            &path,
+            Some(vec![
+                change(1, LineChangeKind::Added),
+                change(2, LineChangeKind::Modified),
+                change(3, LineChangeKind::Removed),
+            ]),
+        );
+        let backend = TestBackend::new(20, 6);
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal
+            .draw(|f| {
+                let area = f.area();
+                editor.render(f, area);
+            })
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let output = buffer_to_string(buffer);
+        assert!(output.contains("│▎one"), "Output:\n{output}");
+        assert!(output.contains("│▎two"), "Output:\n{output}");
+        assert!(output.contains("│▁three"), "Output:\n{output}");
+        assert!(output.contains("│ four"), "Output:\n{output}");
+
+        let theme = Theme::default();
+        assert_eq!(
+            buffer.cell((1, 1)).unwrap().fg,
+            theme.diff_added.fg.unwrap()
+        );
+        assert_eq!(
+            buffer.cell((1, 2)).unwrap().fg,
+            theme.diff_modified.fg.unwrap()
+        );
+    }
+
+    #[test]
+    fn compact_editor_drops_side_borders_and_gutter() {
+        use cli_ide_base::test_support::{temp_path, Cleanup};
+        use cli_ide_platform::git::diff::{LineChange, LineChangeKind};
+
+        let path = temp_path("compact.txt");
+        let _cleanup = Cleanup::new([&path]);
+        std::fs::write(&path, "one\ntwo\n").unwrap();
+        let mut editor = EditorWindow::default();
+        editor.open_file(&path).unwrap();
+        editor.set_line_changes(
+            &path,
+            Some(vec![LineChange {
+                line: 1,
+                kind: LineChangeKind::Added,
+            }]),
+        );
+        editor.set_compact(true);
+
+        let output = render_window_to_string(&mut editor, 20, 4);
+        let rows: Vec<&str> = output.lines().collect();
+        assert!(
+            rows[0].starts_with("Editor──"),
+            "the title row stays.\nOutput:\n{output}"
+        );
+        assert_eq!(rows[1].trim_end(), "one", "Output:\n{output}");
+        assert_eq!(rows[2].trim_end(), "two", "Output:\n{output}");
+        assert_eq!(
+            rows[3].trim_end(),
+            "",
+            "no bottom border.\nOutput:\n{output}"
+        );
+    }
 }
@@ -0,0 +1,269 @@
+//! Implementation of the snippet manager.
+
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Borders, Paragraph};
+
+use super::Window;
+use crate::input::AppKey;
+use crate::list_selection::{reveal_row, ListSelection};
+use crate::snippet::{escape_body, Snippet};
+use crate::theme::Theme;
+
+/// Label of the language column for snippets expanding in every file.
+const ALL_LANGUAGES: &str = "all";
+
+/// Result of feeding a key to the snippet manager.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnippetsOutcome {
+    /// The manager does not handle the key; it falls through to the
+    /// bindings.
+    Ignored,
+    /// The manager consumed the key.
+    Handled,
+    /// The user asked to add a snippet.
+    New,
+    /// The user asked to edit the body of the snippet at this index.
+    Edit(usize),
+    /// The user asked to delete the snippet at this index.
+    Delete(usize),
+    /// The user asked to close the manager.
+    Close,
+}
+
+/// A window listing the user's snippets by language.
+///
+/// Each row shows a snippet's language, its word and the start of its
+/// body. `Up`/`Down` move the cursor, `a` adds a snippet, `Enter` or `e`
+/// edits the body of the one under the cursor, `d` deletes it and `Esc`
+/// closes the manager. The App acts on the outcomes and shows the updated
+/// snippets with [`SnippetsWindow::set_snippets`].
+pub struct SnippetsWindow {
+    /// The snippets, in order.
+    snippets: Vec<Snippet>,
+    /// The language and word of each snippet, and the cursor among them.
+    rows: ListSelection<(Option<String>, String)>,
+    /// Index of the first visible row.
+    scroll_offset: usize,
+    /// Whether the next render scrolls the cursor into view.
+    follow_cursor: bool,
+    /// Styles to draw with.
+    theme: Theme,
+}
+
+impl SnippetsWindow {
+    /// Create a manager showing `snippets`.
+    pub fn new(snippets: &[Snippet]) -> Self {
+        let mut window = Self {
+            snippets: Vec::new(),
+            rows: ListSelection::default(),
+            scroll_offset: 0,
+            follow_cursor: true,
+            theme: Theme::default(),
+        };
+        window.set_snippets(snippets);
+        window
+    }
+
+    /// Get the shown snippets.
+    pub fn snippets(&self) -> &[Snippet] {
+        &self.snippets
+    }
+
+    /// Index of the snippet under the cursor.
+    pub fn selected(&self) -> Option<usize> {
+        self.rows.cursor().filter(|_| !self.rows.is_empty())
+    }
+
+    /// Show `snippets`, keeping the cursor on the same snippet if it is
+    /// still there.
+    pub fn set_snippets(&mut self, snippets: &[Snippet]) {
+        self.snippets = snippets.to_vec();
+        self.rows.set_items(
+            snippets
+                .iter()
+                .map(|snippet| (snippet.language.clone(), snippet.word.clone()))
+                .collect(),
+        );
+        self.follow_cursor = true;
+    }
+
+    /// Move the cursor to the snippet for `word` in `language`, if shown.
+    pub fn select(&mut self, language: Option<&str>, word: &str) {
+        if let Some(row) = self
+            .snippets
+            .iter()
+            .position(|snippet| snippet.language.as_deref() == language && snippet.word == word)
+        {
+            self.rows.set_cursor(row);
+            self.follow_cursor = true;
+        }
+    }
+
+    /// Handle a key press.
+    pub fn handle_key(&mut self, key: AppKey) -> SnippetsOutcome {
+        let outcome = match key {
+            AppKey::Up => {
+                self.rows.move_by(-1);
+                SnippetsOutcome::Handled
+            }
+            AppKey::Down => {
+                self.rows.move_by(1);
+                SnippetsOutcome::Handled
+            }
+            AppKey::Char('a') => SnippetsOutcome::New,
+            AppKey::Enter | AppKey::Char('e') => self
+                .selected()
+                .map_or(SnippetsOutcome::Handled, SnippetsOutcome::Edit),
+            AppKey::Char('d') => self
+                .selected()
+                .map_or(SnippetsOutcome::Handled, SnippetsOutcome::Delete),
+            AppKey::Esc => SnippetsOutcome::Close,
+            _ => SnippetsOutcome::Ignored,
+        };
+        self.follow_cursor = true;
+        outcome
+    }
+
+    /// The lines of the list; the cursor's is highlighted while `focused`.
+    fn lines(&self, focused: bool) -> Vec<Line<'static>> {
+        if self.snippets.is_empty() {
+            return vec![Line::styled(
+                "No snippets. Press a to add one.",
+                self.theme.comment,
+            )];
+        }
+        let language_width = self
+            .snippets
+            .iter()
+            .map(|snippet| {
+                snippet
+                    .language
+                    .as_deref()
+                    .unwrap_or(ALL_LANGUAGES)
+                    .chars()
+                    .count()
+            })
+            .max()
+            .unwrap_or(0);
+        let word_width = self
+            .snippets
+            .iter()
+            .map(|snippet| snippet.word.chars().count())
+            .max()
+            .unwrap_or(0);
+        self.snippets
+            .iter()
+            .enumerate()
+            .map(|(index, snippet)| {
+                let language = format!(
+                    "{:<language_width$}  ",
+                    snippet.language.as_deref().unwrap_or(ALL_LANGUAGES)
+                );
+                let rest = format!(
+                    "{:<word_width$}  {}",
+                    snippet.word,
+                    escape_body(&snippet.body)
+                );
+                if focused && self.rows.cursor() == Some(index) {
+                    Line::styled(language + &rest, self.theme.selection)
+                } else {
+                    Line::from(vec![
+                        Span::styled(language, self.theme.comment),
+                        Span::raw(rest),
+                    ])
+                }
+            })
+            .collect()
+    }
+}
+
+impl Window for SnippetsWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.render_with_focus(frame, area, false);
+    }
+
+    fn render_with_focus(&mut self, frame: &mut Frame, area: Rect, focused: bool) {
+        let border_type = if focused {
+            BorderType::Thick
+        } else {
+            BorderType::Plain
+        };
+        let title = if focused { "Snippets [*]" } else { "Snippets" };
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_type(border_type)
+            .border_style(self.theme.border_style(focused));
+        let height = usize::from(block.inner(area).height);
+
+        let lines = self.lines(focused);
+        if self.follow_cursor && height > 0 {
+            if let Some(cursor) = self.selected() {
+                self.scroll_offset = reveal_row(self.scroll_offset, cursor, height);
+            }
+            self.follow_cursor = false;
+        }
+        let scroll_offset = self.scroll_offset.min(lines.len().saturating_sub(height));
+        let visible: Vec<Line> = lines.into_iter().skip(scroll_offset).take(height).collect();
+        self.scroll_offset = scroll_offset;
+        let paragraph = Paragraph::new(visible).style(self.theme.text).block(block);
+        frame.render_widget(paragraph, area);
+    }
+
+    fn scroll_by(&mut self, lines: i32) {
+        let offset = i64::try_from(self.scroll_offset).unwrap_or(i64::MAX) + i64::from(lines);
+        self.scroll_offset = usize::try_from(offset.max(0)).unwrap_or(usize::MAX);
+        self.follow_cursor = false;
+    }
+
+    fn select_at(&mut self, row: u16, _column: u16, _extend: bool) {
+        let row = self.scroll_offset + usize::from(row);
+        if row < self.snippets.len() {
+            self.rows.set_cursor(row);
+        }
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snippet(language: Option<&str>, word: &str) -> Snippet {
+        Snippet {
+            language: language.map(str::to_string),
+            word: word.to_string(),
+            body: format!("{word}!"),
+        }
+    }
+
+    #[test]
+    fn test_keys_act_on_the_snippet_under_the_cursor_and_it_is_kept() {
+        let mut window = SnippetsWindow::new(&[]);
+        assert_eq!(
+            window.handle_key(AppKey::Char('d')),
+            SnippetsOutcome::Handled
+        );
+        assert_eq!(window.handle_key(AppKey::Char('a')), SnippetsOutcome::New);
+
+        window.set_snippets(&[snippet(None, "teh"), snippet(Some("rust"), "fn")]);
+        window.handle_key(AppKey::Down);
+        assert_eq!(window.handle_key(AppKey::Enter), SnippetsOutcome::Edit(1));
+        window.set_snippets(&[
+            snippet(None, "teh"),
+            snippet(Some("rust"), "for"),
+            snippet(Some("rust"), "fn"),
+        ]);
+        assert_eq!(window.selected(), Some(2), "the cursor stays on `fn`");
+        window.select(Some("rust"), "for");
+        assert_eq!(
+            window.handle_key(AppKey::Char('d')),
+            SnippetsOutcome::Delete(1)
+        );
+        assert_eq!(window.handle_key(AppKey::Esc), SnippetsOutcome::Close);
+        assert_eq!(window.handle_key(AppKey::Tab), SnippetsOutcome::Ignored);
+    }
+}
@@ -0,0 +1,458 @@
+//! Implementation of the status bar window.
+
+use std::fmt;
+use std::sync::Arc;
+
+use super::Window;
+use crate::theme::Theme;
+use crate::truncate::{display_width, dropped_segments, ELLIPSIS};
+use ratatui::prelude::*;
+use ratatui::widgets::Paragraph;
+
+/// Separator drawn between status bar items.
+const SEPARATOR: &str = " | ";
+
+/// A change to the contributed segments of a status bar.
+///
+/// Components emit these on the App's status bar event (see
+/// `App::status_bar_updates`) instead of holding the status bar itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StatusBarUpdate {
+    /// Add the segment `id`, or replace its text if it already exists.
+    SetSegment {
+        /// Identifier of the segment, e.g. `"git.branch"`.
+        id: String,
+        /// Text to show.
+        text: String,
+    },
+    /// Remove the segment `id`, if present.
+    RemoveSegment(String),
+}
+
+/// Which side of the status bar an item is drawn on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StatusBarAlignment {
+    /// After the focused window, mode and cursor.
+    Left,
+    /// Before the contributed segments and the terminal size.
+    Right,
+}
+
+/// Text drawn on the status bar, with the item it shows, if any.
+type Piece<'a> = (String, Option<&'a StatusBarItem>);
+
+/// Computes the text of a status bar item.
+pub type StatusBarTextProvider = Arc<dyn Fn() -> String + Send + Sync>;
+
+/// An entry a feature contributes to the status bar, e.g. the encoding of
+/// the editor's file or the state of a language server.
+///
+/// Its text comes from a provider the status bar calls on every refresh, so
+/// the feature does not push updates; an empty text hides the item. Clicking
+/// the item runs its command, if it has one.
+#[derive(Clone)]
+pub struct StatusBarItem {
+    /// Identifier of the item, e.g. `"editor.encoding"`.
+    pub id: String,
+    /// Side of the bar the item is drawn on.
+    pub alignment: StatusBarAlignment,
+    /// Items with higher priorities are drawn further left; equal
+    /// priorities keep the order the items were added in.
+    pub priority: i32,
+    /// Id of the command clicking the item runs.
+    pub command: Option<String>,
+    /// Source of the item's text.
+    text: StatusBarTextProvider,
+}
+
+impl StatusBarItem {
+    /// Create an item `id` on the `alignment` side with priority 0 and no
+    /// command, whose text `text` computes.
+    pub fn new(
+        id: impl Into<String>,
+        alignment: StatusBarAlignment,
+        text: impl Fn() -> String + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            alignment,
+            priority: 0,
+            command: None,
+            text: Arc::new(text),
+        }
+    }
+
+    /// Set the item's priority.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Run the command `id` when the item is clicked.
+    pub fn with_command(mut self, id: impl Into<String>) -> Self {
+        self.command = Some(id.into());
+        self
+    }
+
+    /// Compute the item's text.
+    pub fn text(&self) -> String {
+        (self.text)()
+    }
+}
+
+impl fmt::Debug for StatusBarItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StatusBarItem")
+            .field("id", &self.id)
+            .field("alignment", &self.alignment)
+            .field("priority", &self.priority)
+            .field("command", &self.command)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A single-row bar describing the workbench state.
+///
+/// The left side shows the focused window, the keybinding mode and the editor
+/// cursor, followed by the left-aligned [`StatusBarItem`]s; the right side
+/// shows the right-aligned items, then contributed segments, in the order
+/// they were first set, and the terminal size. When a side does not fit, the
+/// pieces in its middle are replaced with an ellipsis; the right side takes
+/// the room it needs first.
+#[derive(Debug, Clone, Default)]
+pub struct StatusBarWindow {
+    /// Name of the focused window.
+    focused: String,
+    /// Label of the current keybinding mode.
+    mode: String,
+    /// One-based `(line, column)` of the editor cursor, if known.
+    cursor: Option<(usize, usize)>,
+    /// Terminal size as `(width, height)`.
+    size: (u16, u16),
+    /// Contributed segments as `(id, text)`.
+    segments: Vec<(String, String)>,
+    /// Contributed items, sorted by priority, and their text as of the last
+    /// refresh.
+    items: Vec<(StatusBarItem, String)>,
+    /// Styles to draw with.
+    theme: Theme,
+}
+
+impl StatusBarWindow {
+    /// Create an empty status bar.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the name of the focused window.
+    pub fn set_focused(&mut self, name: impl Into<String>) {
+        self.focused = name.into();
+    }
+
+    /// Set the keybinding mode label.
+    pub fn set_mode(&mut self, mode: impl Into<String>) {
+        self.mode = mode.into();
+    }
+
+    /// Set the one-based `(line, column)` of the editor cursor.
+    pub fn set_cursor(&mut self, cursor: Option<(usize, usize)>) {
+        self.cursor = cursor;
+    }
+
+    /// Set the terminal size.
+    pub fn set_size(&mut self, width: u16, height: u16) {
+        self.size = (width, height);
+    }
+
+    /// Get the contributed segments as `(id, text)`, in display order.
+    pub fn segments(&self) -> &[(String, String)] {
+        &self.segments
+    }
+
+    /// Apply an update to the contributed segments.
+    pub fn apply(&mut self, update: StatusBarUpdate) {
+        match update {
+            StatusBarUpdate::SetSegment { id, text } => {
+                match self
+                    .segments
+                    .iter_mut()
+                    .find(|(existing, _)| *existing == id)
+                {
+                    Some((_, existing)) => *existing = text,
+                    None => self.segments.push((id, text)),
+                }
+            }
+            StatusBarUpdate::RemoveSegment(id) => {
+                self.segments.retain(|(existing, _)| *existing != id);
+            }
+        }
+    }
+
+    /// Add `item`, replacing an item with the same id, and compute its
+    /// text.
+    pub fn add_item(&mut self, item: StatusBarItem) {
+        self.remove_item(&item.id);
+        let text = item.text();
+        let index = self
+            .items
+            .partition_point(|(existing, _)| existing.priority >= item.priority);
+        self.items.insert(index, (item, text));
+    }
+
+    /// Remove the item `id`, returning whether it was present.
+    pub fn remove_item(&mut self, id: &str) -> bool {
+        let count = self.items.len();
+        self.items.retain(|(item, _)| item.id != id);
+        self.items.len() != count
+    }
+
+    /// Get the text of the item `id` as of the last refresh.
+    pub fn item_text(&self, id: &str) -> Option<&str> {
+        self.items
+            .iter()
+            .find(|(item, _)| item.id == id)
+            .map(|(_, text)| text.as_str())
+    }
+
+    /// Compute the text of every item again.
+    pub fn refresh_items(&mut self) {
+        for (item, text) in &mut self.items {
+            *text = item.text();
+        }
+    }
+
+    /// Find the item drawn at `column` of a bar `width` cells wide.
+    pub fn item_at(&self, column: u16, width: u16) -> Option<&StatusBarItem> {
+        let (left, right) = self.layout(width);
+        let right_width = text_width(&format!(" {} ", join(&right)));
+        let right_start = width.saturating_sub(right_width).saturating_add(1);
+        item_in(left, 1, column).or_else(|| item_in(right, right_start, column))
+    }
+
+    /// The pieces drawn on the left and right of a bar `width` cells wide.
+    fn layout(&self, width: u16) -> (Vec<Piece<'_>>, Vec<Piece<'_>>) {
+        let width = usize::from(width);
+        let right = fit(self.right_pieces(), width.saturating_sub(2));
+        // A leading space, then a space before the right side's padding.
+        let right_width = display_width(&join(&right)) + 2;
+        let left = fit(self.left_pieces(), width.saturating_sub(right_width + 2));
+        (left, right)
+    }
+
+    /// The shown text of the items with `alignment`, with the items.
+    fn item_pieces(&self, alignment: StatusBarAlignment) -> Vec<Piece<'_>> {
+        self.items
+            .iter()
+            .filter(|(item, text)| item.alignment == alignment && !text.is_empty())
+            .map(|(item, text)| (text.clone(), Some(item)))
+            .collect()
+    }
+
+    /// The pieces of the left side, with the items they show.
+    fn left_pieces(&self) -> Vec<Piece<'_>> {
+        let mut pieces = vec![(self.focused.clone(), None), (self.mode.clone(), None)];
+        if let Some((line, column)) = self.cursor {
+            pieces.push((format!("Ln {line}, Col {column}"), None));
+        }
+        pieces.retain(|(text, _)| !text.is_empty());
+        pieces.extend(self.item_pieces(StatusBarAlignment::Left));
+        pieces
+    }
+
+    /// The pieces of the right side, with the items they show.
+    fn right_pieces(&self) -> Vec<Piece<'_>> {
+        let (width, height) = self.size;
+        let mut pieces = self.item_pieces(StatusBarAlignment::Right);
+        pieces.extend(self.segments.iter().map(|(_, text)| (text.clone(), None)));
+        pieces.push((format!("{width}x{height}"), None));
+        pieces
+    }
+
+    /// Text of the left-aligned items.
+    pub fn left_text(&self) -> String {
+        join(&self.left_pieces())
+    }
+
+    /// Text of the right-aligned items.
+    pub fn right_text(&self) -> String {
+        join(&self.right_pieces())
+    }
+}
+
+/// Join the text of `pieces` with separators.
+fn join(pieces: &[Piece]) -> String {
+    pieces
+        .iter()
+        .map(|(text, _)| text.as_str())
+        .collect::<Vec<_>>()
+        .join(SEPARATOR)
+}
+
+/// Replace pieces from the middle of `pieces` with an ellipsis until they
+/// fit in `width` cells.
+fn fit(mut pieces: Vec<Piece>, width: usize) -> Vec<Piece> {
+    let texts: Vec<&str> = pieces.iter().map(|(text, _)| text.as_str()).collect();
+    let dropped = dropped_segments(&texts, SEPARATOR, width);
+    if !dropped.is_empty() {
+        pieces.splice(dropped, [(ELLIPSIS.to_string(), None)]);
+    }
+    pieces
+}
+
+/// Find the item of the piece drawn at `column`, with `pieces` drawn from
+/// `start` on.
+fn item_in(pieces: Vec<Piece<'_>>, start: u16, column: u16) -> Option<&StatusBarItem> {
+    let mut x = start;
+    for (text, item) in pieces {
+        let end = x.saturating_add(text_width(&text));
+        if (x..end).contains(&column) {
+            return item;
+        }
+        x = end.saturating_add(text_width(SEPARATOR));
+    }
+    None
+}
+
+/// Number of cells `text` takes.
+fn text_width(text: &str) -> u16 {
+    u16::try_from(display_width(text)).unwrap_or(u16::MAX)
+}
+
+impl Window for StatusBarWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        let style = self.theme.status_bar;
+        let area = Rect {
+            height: area.height.min(1),
+            ..area
+        };
+        let (left, right) = self.layout(area.width);
+        frame.render_widget(
+            Paragraph::new(format!(" {} ", join(&right)))
+                .style(style)
+                .alignment(Alignment::Right),
+            area,
+        );
+        frame.render_widget(Paragraph::new(format!(" {}", join(&left))), area);
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(id: &str, text: &str) -> StatusBarUpdate {
+        StatusBarUpdate::SetSegment {
+            id: id.to_string(),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_left_text_includes_cursor_when_known() {
+        let mut bar = StatusBarWindow::new();
+        bar.set_focused("Editor");
+        bar.set_mode("NORMAL");
+        assert_eq!(bar.left_text(), "Editor | NORMAL");
+
+        bar.set_cursor(Some((3, 7)));
+        assert_eq!(bar.left_text(), "Editor | NORMAL | Ln 3, Col 7");
+    }
+
+    #[test]
+    fn test_segments_keep_first_set_order() {
+        let mut bar = StatusBarWindow::new();
+        bar.set_size(80, 24);
+        bar.apply(set("git", "main"));
+        bar.apply(set("lsp", "rust-analyzer"));
+        bar.apply(set("git", "feature"));
+
+        assert_eq!(bar.right_text(), "feature | rust-analyzer | 80x24");
+
+        bar.apply(StatusBarUpdate::RemoveSegment("git".to_string()));
+        assert_eq!(bar.right_text(), "rust-analyzer | 80x24");
+    }
+
+    #[test]
+    fn test_items_are_ordered_by_priority_and_clickable() {
+        let encoding = Arc::new(std::sync::Mutex::new("UTF-8".to_string()));
+        let mut bar = StatusBarWindow::new();
+        bar.set_focused("Editor");
+        bar.set_size(80, 24);
+        bar.apply(set("git", "main"));
+        let text = Arc::clone(&encoding);
+        bar.add_item(
+            StatusBarItem::new("encoding", StatusBarAlignment::Right, move || {
+                text.lock().unwrap().clone()
+            })
+            .with_command("workbench.action.editor.changeEncoding"),
+        );
+        bar.add_item(
+            StatusBarItem::new("lsp", StatusBarAlignment::Right, || "rust-analyzer".into())
+                .with_priority(10),
+        );
+        bar.add_item(StatusBarItem::new(
+            "hidden",
+            StatusBarAlignment::Left,
+            String::new,
+        ));
+        assert_eq!(bar.left_text(), "Editor");
+        assert_eq!(bar.right_text(), "rust-analyzer | UTF-8 | main | 80x24");
+
+        *encoding.lock().unwrap() = "UTF-16".to_string();
+        assert_eq!(bar.item_text("encoding"), Some("UTF-8"));
+        bar.refresh_items();
+        assert_eq!(bar.item_text("encoding"), Some("UTF-16"));
+
+        // " rust-analyzer | UTF-16 | main | 80x24 " ends at the right edge
+        let start = 80 - 39;
+        assert_eq!(
+            bar.item_at(start + 1, 80).map(|item| item.id.as_str()),
+            Some("lsp")
+        );
+        let encoding_item = bar.item_at(start + 17, 80).unwrap();
+        assert_eq!(
+            encoding_item.command.as_deref(),
+            Some("workbench.action.editor.changeEncoding")
+        );
+        assert!(
+            bar.item_at(start + 23, 80).is_none(),
+            "separators are not items"
+        );
+        assert!(
+            bar.item_at(start + 27, 80).is_none(),
+            "segments are not items"
+        );
+        assert!(bar.item_at(2, 80).is_none());
+
+        assert!(bar.remove_item("lsp"));
+        assert!(!bar.remove_item("lsp"));
+        assert_eq!(bar.right_text(), "UTF-16 | main | 80x24");
+    }
+
+    #[test]
+    fn test_pieces_are_dropped_from_the_middle_when_narrow() {
+        let mut bar = StatusBarWindow::new();
+        bar.set_focused("Editor");
+        bar.set_mode("NORMAL");
+        bar.set_cursor(Some((3, 7)));
+        bar.set_size(40, 24);
+        bar.apply(set("git", "main"));
+        let (left, right) = bar.layout(40);
+        assert_eq!(join(&left), "Editor | … | Ln 3, Col 7");
+        assert_eq!(join(&right), "main | 40x24");
+        assert_eq!(bar.left_text(), "Editor | NORMAL | Ln 3, Col 7");
+
+        bar.add_item(
+            StatusBarItem::new("lsp", StatusBarAlignment::Left, || "rust-analyzer".into())
+                .with_command("lsp.showStatus"),
+        );
+        let (left, _) = bar.layout(40);
+        assert_eq!(join(&left), "… | rust-analyzer");
+        assert_eq!(bar.item_at(5, 40).map(|item| item.id.as_str()), Some("lsp"));
+        assert!(bar.item_at(1, 40).is_none(), "the ellipsis is not an item");
+    }
+}
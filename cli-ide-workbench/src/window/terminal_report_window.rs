@@ -0,0 +1,190 @@
+//! Implementation of the terminal capability report.
+
+use std::collections::BTreeMap;
+
+use cli_ide_platform::environment::environment_service::TerminalCapabilities;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Borders, Paragraph};
+
+use super::{scroll_offset_by, Window};
+use crate::theme::Theme;
+
+/// Box-drawing and block characters, in the styles the workbench uses and
+/// a few it does not.
+const BOX_DRAWING: [&str; 3] = [
+    "┌─┬─┐ ╭─┬─╮ ┏━┳━┓ ╔═╦═╗ ░▒▓█",
+    "├─┼─┤ │ │ │ ┣━╋━┫ ╠═╬═╣ ▁▃▅▇",
+    "└─┴─┘ ╰─┴─╯ ┗━┻━┛ ╚═╩═╝ ▏▎▍▌",
+];
+
+/// Emoji, including a modifier and a joined sequence that some terminals
+/// draw as several glyphs.
+const EMOJI: &str = "😀 🚀 ✅ ⚠️ 👍🏽 👨‍👩‍👧";
+
+/// Double-width characters above ASCII of the same width: the bars line up
+/// when the terminal agrees with the IDE about character widths.
+const WIDE_CHARACTERS: [&str; 2] = ["漢字テスト한글|", "0123456789abcd|"];
+
+/// A read-only pane for debugging rendering problems: what the
+/// `EnvironmentService` detected about the terminal, followed by color
+/// ramps, box-drawing, emoji and double-width test patterns, and the
+/// environment variables the detection used.
+pub struct TerminalReportWindow {
+    /// What the terminal is detected to support.
+    capabilities: TerminalCapabilities,
+    /// Environment variables the detection read, by name.
+    variables: BTreeMap<String, String>,
+    /// Index of the first visible line.
+    scroll_offset: u16,
+    /// Styles to draw with.
+    theme: Theme,
+}
+
+impl TerminalReportWindow {
+    /// Create a report of `capabilities`, detected from `variables`.
+    pub fn new(capabilities: TerminalCapabilities, variables: BTreeMap<String, String>) -> Self {
+        Self {
+            capabilities,
+            variables,
+            scroll_offset: 0,
+            theme: Theme::default(),
+        }
+    }
+
+    /// Get what the terminal is detected to support.
+    pub fn capabilities(&self) -> &TerminalCapabilities {
+        &self.capabilities
+    }
+
+    /// The lines of the report, for a pane `width` cells wide inside its
+    /// border.
+    fn lines(&self, width: u16) -> Vec<Line<'static>> {
+        let heading = |text: &'static str| Line::styled(text, self.theme.keyword);
+        let field = |name: &'static str, value: String| {
+            Line::from(vec![
+                Span::styled(format!("{name:<12}"), self.theme.comment),
+                Span::raw(value),
+            ])
+        };
+        let unknown = || "unknown".to_string();
+        let capabilities = &self.capabilities;
+        let mut lines = vec![
+            heading("Detected"),
+            field(
+                "Terminal",
+                capabilities.term.clone().unwrap_or_else(unknown),
+            ),
+            field(
+                "Program",
+                capabilities.program.clone().unwrap_or_else(unknown),
+            ),
+            field("Colors", capabilities.color.to_string()),
+            field(
+                "Unicode",
+                if capabilities.unicode { "yes" } else { "no" }.to_string(),
+            ),
+            field(
+                "Multiplexer",
+                capabilities
+                    .multiplexer
+                    .clone()
+                    .unwrap_or_else(|| "none".to_string()),
+            ),
+            Line::raw(""),
+            heading("Colors"),
+            color_ramp((0..16).map(Color::Indexed)),
+            color_ramp((16..52).map(Color::Indexed)),
+            color_ramp((232..=255).map(Color::Indexed)),
+            color_ramp(rgb_ramp(width)),
+            Line::raw(""),
+            heading("Box drawing"),
+        ];
+        lines.extend(BOX_DRAWING.iter().map(|&row| Line::raw(row)));
+        lines.push(Line::raw(""));
+        lines.push(heading("Emoji"));
+        lines.push(Line::raw(EMOJI));
+        lines.push(Line::raw(""));
+        lines.push(heading("Wide characters"));
+        lines.extend(WIDE_CHARACTERS.iter().map(|&row| Line::raw(row)));
+        lines.push(Line::raw(""));
+        lines.push(heading("Environment"));
+        if self.variables.is_empty() {
+            lines.push(Line::styled(
+                "No terminal variables set",
+                self.theme.comment,
+            ));
+        }
+        lines.extend(
+            self.variables
+                .iter()
+                .map(|(name, value)| Line::raw(format!("{name}={value}"))),
+        );
+        lines
+    }
+}
+
+/// A row of two-cell swatches, one per color.
+fn color_ramp(colors: impl Iterator<Item = Color>) -> Line<'static> {
+    colors
+        .map(|color| Span::styled("  ", Style::default().bg(color)))
+        .collect()
+}
+
+/// Colors sweeping through the hues in 24-bit RGB, enough swatches to
+/// fill `width` cells.
+fn rgb_ramp(width: u16) -> impl Iterator<Item = Color> {
+    let swatches = (width / 2).max(1);
+    (0..swatches).map(move |index| {
+        let hue = u32::from(index) * 1536 / u32::from(swatches);
+        // Six segments of 256 steps between the primary and secondary hues
+        let rising = u8::try_from(hue % 256).unwrap_or(u8::MAX);
+        let falling = u8::MAX - rising;
+        match hue / 256 {
+            0 => Color::Rgb(255, rising, 0),
+            1 => Color::Rgb(falling, 255, 0),
+            2 => Color::Rgb(0, 255, rising),
+            3 => Color::Rgb(0, falling, 255),
+            4 => Color::Rgb(rising, 0, 255),
+            _ => Color::Rgb(255, 0, falling),
+        }
+    })
+}
+
+impl Window for TerminalReportWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.render_with_focus(frame, area, false);
+    }
+
+    fn render_with_focus(&mut self, frame: &mut Frame, area: Rect, focused: bool) {
+        let border_type = if focused {
+            BorderType::Thick
+        } else {
+            BorderType::Plain
+        };
+        let title = if focused {
+            "Terminal Capabilities [*]"
+        } else {
+            "Terminal Capabilities"
+        };
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_type(border_type)
+            .border_style(self.theme.border_style(focused));
+        let lines = self.lines(block.inner(area).width);
+        let paragraph = Paragraph::new(lines)
+            .style(self.theme.text)
+            .block(block)
+            .scroll((self.scroll_offset, 0));
+        frame.render_widget(paragraph, area);
+    }
+
+    fn scroll_by(&mut self, lines: i32) {
+        let last_line = self.lines(0).len().saturating_sub(1);
+        self.scroll_offset = scroll_offset_by(self.scroll_offset, lines, last_line);
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+}
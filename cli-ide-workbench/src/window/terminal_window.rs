@@ -1,24 +1,84 @@
 //! Implementation of a terminal window.
 
-use super::Window;
+use super::{pane_borders, scroll_offset_by, Window};
+use crate::keybinding::WindowContext;
+use crate::scrollbar::ScrollPosition;
+use crate::theme::Theme;
 use ratatui::prelude::*;
-use ratatui::widgets::{Block, BorderType, Borders, Paragraph};
+use ratatui::widgets::{Block, BorderType, Paragraph};
 
 /// A stub terminal window. In later phases this will spawn a PTY and render
-/// shell output; for now it displays placeholder text.
+/// shell output; for now it displays placeholder text, or the output of
+/// tasks run in the terminal.
+///
+/// Pasted text collects on an input line below the output, where a shell
+/// would receive it.
 pub struct TerminalWindow {
-    /// Placeholder output.
+    /// Output: the placeholder, or lines appended by tasks.
     buffer: String,
+    /// Text pasted into the terminal, not yet sent to a shell.
+    input: String,
+    /// Index of the first visible line.
+    scroll_offset: u16,
+    /// Whether the next render scrolls to the last line, after output was
+    /// appended.
+    follow_output: bool,
+    /// Styles to draw with.
+    theme: Theme,
+    /// Whether the terminal is drawn with only its top border.
+    compact: bool,
 }
 
 impl Default for TerminalWindow {
     fn default() -> Self {
         Self {
             buffer: String::from("Terminal output will appear here."),
+            input: String::new(),
+            scroll_offset: 0,
+            follow_output: false,
+            theme: Theme::default(),
+            compact: false,
         }
     }
 }
 
+impl TerminalWindow {
+    /// Get the index of the first visible line.
+    pub fn scroll_offset(&self) -> u16 {
+        self.scroll_offset
+    }
+
+    /// Get the text pasted into the terminal.
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    /// Paste `text` at the end of the input line.
+    pub fn paste(&mut self, text: &str) {
+        self.input.push_str(text);
+    }
+
+    /// Get the output shown above the input line.
+    pub fn output(&self) -> &str {
+        &self.buffer
+    }
+
+    /// Remove the output, e.g. the placeholder before a task runs.
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+        self.scroll_offset = 0;
+    }
+
+    /// Append `line` to the output and scroll to it.
+    pub fn append_line(&mut self, line: &str) {
+        if !self.buffer.is_empty() {
+            self.buffer.push('\n');
+        }
+        self.buffer.push_str(line);
+        self.follow_output = true;
+    }
+}
+
 impl Window for TerminalWindow {
     fn render(&mut self, frame: &mut Frame, area: Rect) {
         self.render_with_focus(frame, area, false);
@@ -33,12 +93,50 @@ impl Window for TerminalWindow {
 
         let title = if focused { "Terminal [*]" } else { "Terminal" };
 
-        let paragraph = Paragraph::new(self.buffer.clone()).block(
-            Block::default()
-                .title(title)
-                .borders(Borders::ALL)
-                .border_type(border_type),
-        );
+        let mut text = self.buffer.clone();
+        if !self.input.is_empty() {
+            text.push_str("\n$ ");
+            text.push_str(&self.input);
+        }
+        let block = Block::default()
+            .title(title)
+            .borders(pane_borders(self.compact))
+            .border_type(border_type)
+            .border_style(self.theme.border_style(focused));
+        if self.follow_output {
+            let lines = u16::try_from(text.lines().count()).unwrap_or(u16::MAX);
+            self.scroll_offset = lines.saturating_sub(block.inner(area).height);
+            self.follow_output = false;
+        }
+        let paragraph = Paragraph::new(text)
+            .style(self.theme.text)
+            .block(block)
+            .scroll((self.scroll_offset, 0));
         frame.render_widget(paragraph, area);
     }
+
+    fn scroll_by(&mut self, lines: i32) {
+        let last_line = self.buffer.lines().count().saturating_sub(1);
+        self.follow_output = false;
+        self.scroll_offset = scroll_offset_by(self.scroll_offset, lines, last_line);
+    }
+
+    fn scroll_position(&self) -> Option<ScrollPosition> {
+        Some(ScrollPosition {
+            offset: usize::from(self.scroll_offset),
+            content: self.buffer.lines().count(),
+        })
+    }
+
+    fn context(&self) -> Option<WindowContext> {
+        Some(WindowContext::Terminal)
+    }
+
+    fn set_compact(&mut self, compact: bool) {
+        self.compact = compact;
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
 }
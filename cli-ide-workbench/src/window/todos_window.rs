@@ -0,0 +1,363 @@
+//! Implementation of the TODO list.
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use cli_ide_platform::search::search_service::SearchSummary;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Borders, Paragraph};
+
+use super::Window;
+use crate::input::AppKey;
+use crate::list_selection::{reveal_row, ListSelection};
+use crate::scrollbar::ScrollPosition;
+use crate::search;
+use crate::theme::Theme;
+use crate::todo::{TodoItem, TodoTag};
+use crate::workspace::WorkspaceFolders;
+
+/// Rows above the list: the summary.
+const HEADER_ROWS: u16 = 1;
+
+/// Result of feeding a key to the TODO list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TodosOutcome {
+    /// The list does not handle the key; it falls through to the bindings.
+    Ignored,
+    /// The list consumed the key.
+    Handled,
+    /// The user asked to open this file with this byte range selected.
+    Open(PathBuf, Range<usize>),
+    /// The user asked to scan the workspace again.
+    Refresh,
+    /// The user asked to close the list.
+    Close,
+}
+
+/// A row: a file, and the line of a comment in it for comment rows.
+type RowKey = (PathBuf, Option<usize>);
+
+/// A panel listing the TODO, FIXME and HACK comments of the workspace,
+/// grouped by file in path order.
+///
+/// Files are added as the workspace scan finds them and replaced as they
+/// change, leaving the cursor on the same comment. `Up`/`Down` move the
+/// cursor, `Enter` opens the file at the comment, `r` scans the workspace
+/// again and `Esc` closes the list.
+pub struct TodosWindow {
+    /// Folders scanned, which paths are shown relative to.
+    folders: WorkspaceFolders,
+    /// The comments of each file with any.
+    files: BTreeMap<PathBuf, Vec<TodoItem>>,
+    /// Rows and the cursor among them.
+    rows: ListSelection<RowKey>,
+    /// How the last scan ended, once it has.
+    summary: Option<SearchSummary>,
+    /// Why the last scan could not start, if it could not.
+    error: Option<String>,
+    /// Index of the first visible row.
+    scroll_offset: usize,
+    /// Whether the next render scrolls the cursor into view.
+    follow_cursor: bool,
+    /// Styles to draw with.
+    theme: Theme,
+}
+
+impl TodosWindow {
+    /// Create an empty list of the comments in the roots of `folders`.
+    pub fn new(folders: WorkspaceFolders) -> Self {
+        Self {
+            folders,
+            files: BTreeMap::new(),
+            rows: ListSelection::default(),
+            summary: None,
+            error: None,
+            scroll_offset: 0,
+            follow_cursor: true,
+            theme: Theme::default(),
+        }
+    }
+
+    /// Get the folders scanned.
+    pub fn folders(&self) -> &WorkspaceFolders {
+        &self.folders
+    }
+
+    /// Get the comments of each file with any, in path order.
+    pub fn files(&self) -> &BTreeMap<PathBuf, Vec<TodoItem>> {
+        &self.files
+    }
+
+    /// Number of comments listed.
+    pub fn len(&self) -> usize {
+        self.files.values().map(Vec::len).sum()
+    }
+
+    /// Check whether no comment is listed.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Check whether a scan is running.
+    pub fn is_scanning(&self) -> bool {
+        self.summary.is_none() && self.error.is_none()
+    }
+
+    /// Clear the list for a workspace scan that is starting.
+    pub fn start(&mut self) {
+        self.files.clear();
+        self.summary = None;
+        self.error = None;
+        self.update_rows();
+    }
+
+    /// Show `items` as the comments of the file at `path`, replacing those
+    /// listed for it; no items removes the file.
+    pub fn set_file(&mut self, path: PathBuf, items: Vec<TodoItem>) {
+        if items.is_empty() {
+            if self.files.remove(&path).is_none() {
+                return;
+            }
+        } else {
+            self.files.insert(path, items);
+        }
+        self.update_rows();
+    }
+
+    /// Remove the file at `path`, or the files under it if it is a folder.
+    pub fn remove(&mut self, path: &Path) {
+        let before = self.files.len();
+        self.files.retain(|file, _| !file.starts_with(path));
+        if self.files.len() != before {
+            self.update_rows();
+        }
+    }
+
+    /// Mark the scan as ended.
+    pub fn finish(&mut self, summary: SearchSummary) {
+        self.summary = Some(summary);
+    }
+
+    /// Show why the scan could not start.
+    pub fn fail(&mut self, error: impl Into<String>) {
+        self.start();
+        self.error = Some(error.into());
+    }
+
+    /// List a row for each file and each of its comments.
+    fn update_rows(&mut self) {
+        let rows = self
+            .files
+            .iter()
+            .flat_map(|(path, items)| {
+                std::iter::once((path.clone(), None))
+                    .chain(items.iter().map(|item| (path.clone(), Some(item.line))))
+            })
+            .collect();
+        self.rows.set_items(rows);
+        self.follow_cursor = true;
+    }
+
+    /// Handle a key press.
+    pub fn handle_key(&mut self, key: AppKey) -> TodosOutcome {
+        let outcome = match key {
+            AppKey::Up => {
+                self.rows.move_by(-1);
+                TodosOutcome::Handled
+            }
+            AppKey::Down => {
+                self.rows.move_by(1);
+                TodosOutcome::Handled
+            }
+            AppKey::Enter => {
+                let Some((path, line)) = self.rows.current() else {
+                    return TodosOutcome::Handled;
+                };
+                let items = &self.files[path];
+                let item = line
+                    .and_then(|line| items.iter().find(|item| item.line == line))
+                    .unwrap_or(&items[0]);
+                TodosOutcome::Open(path.clone(), item.range.clone())
+            }
+            AppKey::Char('r') => TodosOutcome::Refresh,
+            AppKey::Esc => TodosOutcome::Close,
+            _ => return TodosOutcome::Ignored,
+        };
+        self.follow_cursor = true;
+        outcome
+    }
+
+    /// The line counting the comments and saying whether the scan runs.
+    fn summary_line(&self) -> Line<'_> {
+        if let Some(error) = &self.error {
+            return Line::styled(error.as_str(), self.theme.error);
+        }
+        let mut text = if self.files.is_empty() {
+            "No TODOs".to_string()
+        } else {
+            format!(
+                "{} in {}",
+                search::counted(self.len(), "comment"),
+                search::counted(self.files.len(), "file")
+            )
+        };
+        match &self.summary {
+            None => text.push_str(" (scanning...)"),
+            Some(summary) if summary.cancelled => text.push_str(" (stopped)"),
+            Some(SearchSummary {
+                error: Some(error), ..
+            }) => text = format!("Scan failed: {error}"),
+            Some(_) => {}
+        }
+        Line::styled(text, self.theme.comment)
+    }
+
+    /// The line for a row.
+    fn row_line(&self, (path, line): &RowKey, selected: bool) -> Line<'_> {
+        let style = if selected {
+            self.theme.selection
+        } else {
+            self.theme.text
+        };
+        let items = &self.files[path];
+        let Some(item) = line.and_then(|line| items.iter().find(|item| item.line == line)) else {
+            let path = self.folders.label(path);
+            return Line::styled(format!("{path} ({})", items.len()), style);
+        };
+        let tag_style = if selected {
+            style
+        } else {
+            match item.tag {
+                TodoTag::Todo => self.theme.keyword,
+                TodoTag::Fixme => self.theme.error,
+                TodoTag::Hack => self.theme.warning,
+            }
+        };
+        let label = item.tag.label();
+        let rest = item.text.strip_prefix(label).unwrap_or(&item.text);
+        Line::from(vec![
+            Span::styled(format!("    {}: ", item.line + 1), style),
+            Span::styled(label, tag_style.add_modifier(Modifier::BOLD)),
+            Span::styled(rest, style),
+        ])
+    }
+}
+
+impl Window for TodosWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.render_with_focus(frame, area, false);
+    }
+
+    fn render_with_focus(&mut self, frame: &mut Frame, area: Rect, focused: bool) {
+        let border_type = if focused {
+            BorderType::Thick
+        } else {
+            BorderType::Plain
+        };
+        let title = if focused { "TODOs [*]" } else { "TODOs" };
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_type(border_type)
+            .border_style(self.theme.border_style(focused));
+        let height = usize::from(block.inner(area).height.saturating_sub(HEADER_ROWS));
+
+        if self.follow_cursor && height > 0 {
+            if let Some(cursor) = self.rows.cursor() {
+                self.scroll_offset = reveal_row(self.scroll_offset, cursor, height);
+            }
+            self.follow_cursor = false;
+        }
+        self.scroll_offset = self
+            .scroll_offset
+            .min(self.rows.len().saturating_sub(height));
+
+        let cursor = self.rows.cursor();
+        let mut lines = vec![self.summary_line()];
+        lines.extend(
+            self.rows
+                .items()
+                .iter()
+                .enumerate()
+                .skip(self.scroll_offset)
+                .take(height)
+                .map(|(index, row)| self.row_line(row, focused && Some(index) == cursor)),
+        );
+        let paragraph = Paragraph::new(lines).style(self.theme.text).block(block);
+        frame.render_widget(paragraph, area);
+    }
+
+    fn scroll_by(&mut self, lines: i32) {
+        let last_row = self.rows.len().saturating_sub(1);
+        let offset = i64::try_from(self.scroll_offset).unwrap_or(i64::MAX) + i64::from(lines);
+        self.scroll_offset = usize::try_from(offset.max(0))
+            .unwrap_or(usize::MAX)
+            .min(last_row);
+        self.follow_cursor = false;
+    }
+
+    fn select_at(&mut self, row: u16, _column: u16, _extend: bool) {
+        let Some(row) = row.checked_sub(HEADER_ROWS) else {
+            return;
+        };
+        let row = self.scroll_offset + usize::from(row);
+        if row < self.rows.len() {
+            self.rows.set_cursor(row);
+        }
+    }
+
+    fn scroll_position(&self) -> Option<ScrollPosition> {
+        Some(ScrollPosition {
+            offset: self.scroll_offset,
+            content: self.rows.len(),
+        })
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::todo::find_todos;
+
+    #[test]
+    fn test_files_are_replaced_in_place_and_enter_opens_the_comment() {
+        let mut list = TodosWindow::new(WorkspaceFolders::new("/ws"));
+        list.start();
+        list.set_file(PathBuf::from("/ws/b.rs"), find_todos("// TODO b"));
+        list.set_file(PathBuf::from("/ws/a.rs"), find_todos("x\n// FIXME a"));
+        list.finish(SearchSummary::default());
+        assert!(!list.is_scanning());
+        // Files found later are listed in path order around the cursor
+        list.handle_key(AppKey::Down);
+        assert_eq!(
+            list.handle_key(AppKey::Enter),
+            TodosOutcome::Open(PathBuf::from("/ws/b.rs"), 3..7)
+        );
+        list.handle_key(AppKey::Up);
+        list.handle_key(AppKey::Up);
+        assert_eq!(
+            list.handle_key(AppKey::Enter),
+            TodosOutcome::Open(PathBuf::from("/ws/a.rs"), 5..10)
+        );
+
+        // A rescan of the file keeps the cursor on the same comment
+        list.set_file(
+            PathBuf::from("/ws/a.rs"),
+            find_todos("// TODO new\n// FIXME a"),
+        );
+        assert_eq!(
+            list.handle_key(AppKey::Enter),
+            TodosOutcome::Open(PathBuf::from("/ws/a.rs"), 15..20)
+        );
+        list.set_file(PathBuf::from("/ws/a.rs"), Vec::new());
+        list.remove(Path::new("/ws"));
+        assert!(list.is_empty());
+        assert_eq!(list.handle_key(AppKey::Char('r')), TodosOutcome::Refresh);
+        assert_eq!(list.handle_key(AppKey::Tab), TodosOutcome::Ignored);
+    }
+}
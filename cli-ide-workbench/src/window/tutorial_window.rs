@@ -0,0 +1,85 @@
+//! Implementation of the tutorial panel.
+
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Borders, Paragraph, Wrap};
+
+use super::Window;
+use crate::theme::Theme;
+use crate::tutorial::{Tutorial, TutorialStep};
+
+/// Width of the tutorial panel, including its border.
+pub const TUTORIAL_WIDTH: u16 = 40;
+
+/// Height of the tutorial panel, including its border: a row per step, a
+/// blank row and three rows of instruction.
+pub const TUTORIAL_HEIGHT: u16 = TutorialStep::ALL.len() as u16 + 6;
+
+/// A box over the top right of the workbench showing the tutorial's steps
+/// and what to do next.
+///
+/// Done steps are checked, the current one is marked and its instruction
+/// is shown below the list. The panel takes no input: the user completes
+/// steps with the real keys and commands.
+pub struct TutorialWindow {
+    /// Progress shown.
+    tutorial: Tutorial,
+    /// Styles to draw with.
+    theme: Theme,
+}
+
+impl TutorialWindow {
+    /// Create a panel showing `tutorial`.
+    pub fn new(tutorial: Tutorial) -> Self {
+        Self {
+            tutorial,
+            theme: Theme::default(),
+        }
+    }
+}
+
+impl Window for TutorialWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.render_with_focus(frame, area, false);
+    }
+
+    fn render_with_focus(&mut self, frame: &mut Frame, area: Rect, _focused: bool) {
+        let title = format!(
+            "Tutorial {}/{}",
+            self.tutorial.completed(),
+            TutorialStep::ALL.len()
+        );
+        let block = Block::default()
+            .title(title)
+            .title_bottom("?: stop")
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(self.theme.border_style(true));
+        let current = self.tutorial.current();
+        let mut lines: Vec<Line> = TutorialStep::ALL
+            .iter()
+            .enumerate()
+            .map(|(index, &step)| {
+                if index < self.tutorial.completed() {
+                    Line::raw(format!("✓ {}", step.title()))
+                } else if Some(step) == current {
+                    Line::styled(format!("▸ {}", step.title()), self.theme.selection)
+                } else {
+                    Line::raw(format!("  {}", step.title()))
+                }
+            })
+            .collect();
+        lines.push(Line::raw(""));
+        if let Some(step) = current {
+            lines.push(Line::raw(step.instruction()));
+        }
+        let paragraph = Paragraph::new(lines)
+            .style(self.theme.text)
+            .wrap(Wrap { trim: true })
+            .block(block);
+        frame.render_widget(paragraph, area);
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+}
@@ -0,0 +1,242 @@
+//! Implementation of the undo tree viewer.
+
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Borders, Paragraph};
+
+use super::Window;
+use crate::input::AppKey;
+use crate::list_selection::{reveal_row, ListSelection};
+use crate::theme::Theme;
+use crate::undo_tree::UndoTreeRow;
+
+/// Result of feeding a key to the undo tree viewer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UndoTreeOutcome {
+    /// The viewer does not handle the key; it falls through to the
+    /// bindings.
+    Ignored,
+    /// The viewer consumed the key.
+    Handled,
+    /// The user asked to bring the document to this state.
+    GoTo(usize),
+    /// The user asked to close the viewer.
+    Close,
+}
+
+/// A window showing the undo history of a document as a tree.
+///
+/// Each row is a state of the document, labeled with the change leading to
+/// it; where edits were made after undoing, the branches are indented
+/// under the state they start from. `●` marks the state the document is
+/// in. `Up`/`Down` move the cursor, `Enter` brings the document to the
+/// state under it, whatever branch it is on, and `Esc` closes the viewer.
+pub struct UndoTreeWindow {
+    /// Name of the document, for the title.
+    name: String,
+    /// The states, in outline order.
+    rows: Vec<UndoTreeRow>,
+    /// State numbers and the cursor among them.
+    states: ListSelection<usize>,
+    /// Index of the first visible row.
+    scroll_offset: usize,
+    /// Whether the next render scrolls the cursor into view.
+    follow_cursor: bool,
+    /// Styles to draw with.
+    theme: Theme,
+}
+
+impl UndoTreeWindow {
+    /// Create a viewer with no history.
+    pub fn new() -> Self {
+        Self {
+            name: String::new(),
+            rows: Vec::new(),
+            states: ListSelection::default(),
+            scroll_offset: 0,
+            follow_cursor: true,
+            theme: Theme::default(),
+        }
+    }
+
+    /// Get the shown states, in outline order.
+    pub fn rows(&self) -> &[UndoTreeRow] {
+        &self.rows
+    }
+
+    /// The state under the cursor.
+    pub fn selected(&self) -> Option<usize> {
+        self.states.current().copied()
+    }
+
+    /// Show the history `rows` of the document `name`.
+    ///
+    /// The cursor stays on the same state of the same document, and moves
+    /// to the current state when the document or its current state
+    /// changed.
+    pub fn set_tree(&mut self, name: &str, rows: Vec<UndoTreeRow>) {
+        if name == self.name && rows == self.rows {
+            return;
+        }
+        let current = |rows: &[UndoTreeRow]| rows.iter().position(|row| row.current);
+        let keep = (name == self.name && current(&rows) == current(&self.rows))
+            .then(|| self.selected())
+            .flatten()
+            .and_then(|state| rows.iter().position(|row| row.state == state));
+        self.states
+            .set_items(rows.iter().map(|row| row.state).collect());
+        if let Some(row) = keep.or_else(|| current(&rows)) {
+            self.states.set_cursor(row);
+        }
+        self.name = name.to_string();
+        self.rows = rows;
+        self.follow_cursor = true;
+    }
+
+    /// Handle a key press.
+    pub fn handle_key(&mut self, key: AppKey) -> UndoTreeOutcome {
+        let outcome = match key {
+            AppKey::Up => {
+                self.states.move_by(-1);
+                UndoTreeOutcome::Handled
+            }
+            AppKey::Down => {
+                self.states.move_by(1);
+                UndoTreeOutcome::Handled
+            }
+            AppKey::Enter => match self.selected() {
+                Some(state) => UndoTreeOutcome::GoTo(state),
+                None => UndoTreeOutcome::Handled,
+            },
+            AppKey::Esc => UndoTreeOutcome::Close,
+            _ => UndoTreeOutcome::Ignored,
+        };
+        self.follow_cursor = true;
+        outcome
+    }
+
+    /// The lines of the tree; the cursor's is highlighted while `focused`.
+    fn lines(&self, focused: bool) -> Vec<Line<'static>> {
+        self.rows
+            .iter()
+            .enumerate()
+            .map(|(index, row)| {
+                let style = if focused && self.states.cursor() == Some(index) {
+                    self.theme.selection
+                } else if row.current {
+                    self.theme.text.add_modifier(Modifier::BOLD)
+                } else {
+                    self.theme.text
+                };
+                let marker = if row.current { '●' } else { '○' };
+                let indent = "  ".repeat(row.depth);
+                Line::styled(
+                    format!("{indent}{marker} {:>3} {}", row.state, row.label),
+                    style,
+                )
+            })
+            .collect()
+    }
+}
+
+impl Default for UndoTreeWindow {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Window for UndoTreeWindow {
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.render_with_focus(frame, area, false);
+    }
+
+    fn render_with_focus(&mut self, frame: &mut Frame, area: Rect, focused: bool) {
+        let border_type = if focused {
+            BorderType::Thick
+        } else {
+            BorderType::Plain
+        };
+        let marker = if focused { " [*]" } else { "" };
+        let title = if self.name.is_empty() {
+            format!("Undo Tree{marker}")
+        } else {
+            format!("Undo Tree: {}{marker}", self.name)
+        };
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_type(border_type)
+            .border_style(self.theme.border_style(focused));
+        let height = usize::from(block.inner(area).height);
+
+        let lines = self.lines(focused);
+        if self.follow_cursor && height > 0 {
+            if let Some(cursor) = self.states.cursor() {
+                self.scroll_offset = reveal_row(self.scroll_offset, cursor, height);
+            }
+            self.follow_cursor = false;
+        }
+        let scroll_offset = self.scroll_offset.min(lines.len().saturating_sub(height));
+        let visible: Vec<Line> = lines.into_iter().skip(scroll_offset).take(height).collect();
+        self.scroll_offset = scroll_offset;
+        let paragraph = Paragraph::new(visible).style(self.theme.text).block(block);
+        frame.render_widget(paragraph, area);
+    }
+
+    fn scroll_by(&mut self, lines: i32) {
+        let offset = i64::try_from(self.scroll_offset).unwrap_or(i64::MAX) + i64::from(lines);
+        self.scroll_offset = usize::try_from(offset.max(0)).unwrap_or(usize::MAX);
+        self.follow_cursor = false;
+    }
+
+    fn select_at(&mut self, row: u16, _column: u16, _extend: bool) {
+        let row = self.scroll_offset + usize::from(row);
+        if row < self.rows.len() {
+            self.states.set_cursor(row);
+        }
+    }
+
+    fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(state: usize, depth: usize, current: bool) -> UndoTreeRow {
+        UndoTreeRow {
+            state,
+            depth,
+            label: format!("Insert \"{state}\""),
+            current,
+        }
+    }
+
+    #[test]
+    fn test_cursor_starts_on_the_current_state_and_enter_goes_to_its_state() {
+        let mut tree = UndoTreeWindow::new();
+        tree.set_tree(
+            "a.rs",
+            vec![row(0, 0, false), row(1, 1, false), row(2, 1, true)],
+        );
+        assert_eq!(tree.selected(), Some(2));
+        tree.handle_key(AppKey::Up);
+        assert_eq!(tree.handle_key(AppKey::Enter), UndoTreeOutcome::GoTo(1));
+
+        // The same tree keeps the cursor; a new current state moves it
+        tree.set_tree(
+            "a.rs",
+            vec![row(0, 0, false), row(1, 1, false), row(2, 1, true)],
+        );
+        assert_eq!(tree.selected(), Some(1));
+        tree.handle_key(AppKey::Up);
+        tree.set_tree(
+            "a.rs",
+            vec![row(0, 0, false), row(1, 1, true), row(2, 1, false)],
+        );
+        assert_eq!(tree.selected(), Some(1));
+        assert_eq!(tree.handle_key(AppKey::Esc), UndoTreeOutcome::Close);
+        assert_eq!(tree.handle_key(AppKey::Tab), UndoTreeOutcome::Ignored);
+    }
+}
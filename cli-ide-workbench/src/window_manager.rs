@@ -0,0 +1,382 @@
+//! Window manager for the CLI IDE.
+//!
+//! The `WindowManager` owns the open windows, keyed by [`WindowId`], and the
+//! [`LayoutNode`] tree that positions them. Windows can be opened and closed
+//! at runtime; focus cycling follows the layout order and is applied to a
+//! [`FocusManager`].
+
+use std::collections::{HashMap, HashSet};
+
+use ratatui::layout::{Position, Rect};
+use ratatui::Frame;
+
+use crate::focus::FocusManager;
+use crate::layout::{LayoutNode, PaneDirection, SplitDirection};
+use crate::window::{Window, WindowId};
+
+/// Owns the open windows and the layout tree.
+#[derive(Default)]
+pub struct WindowManager {
+    /// Open windows.
+    windows: HashMap<WindowId, Box<dyn Window>>,
+    /// Layout of the open windows; `None` when no window is open.
+    layout: Option<LayoutNode>,
+}
+
+impl WindowManager {
+    /// Create a window manager with no windows.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open a window as a new column on the right and return its ID.
+    pub fn open(&mut self, window: Box<dyn Window>) -> WindowId {
+        let id = WindowId::new();
+        let column = LayoutNode::Window(id);
+        self.layout = Some(match self.layout.take() {
+            None => column,
+            Some(LayoutNode::Split {
+                direction: SplitDirection::Horizontal,
+                mut children,
+                mut weights,
+            }) => {
+                // The new column gets an average share
+                let total: u32 = weights.iter().map(|&weight| u32::from(weight)).sum();
+                let average = total / weights.len().max(1) as u32;
+                weights.push(u16::try_from(average).unwrap_or(u16::MAX).max(1));
+                children.push(column);
+                LayoutNode::Split {
+                    direction: SplitDirection::Horizontal,
+                    children,
+                    weights,
+                }
+            }
+            Some(root) => LayoutNode::even_split(SplitDirection::Horizontal, vec![root, column]),
+        });
+        self.windows.insert(id, window);
+        id
+    }
+
+    /// Open a window next to `target`, splitting it in `direction`.
+    ///
+    /// Returns the new window's ID, or `None` (dropping `window`) if
+    /// `target` is not open.
+    pub fn split(
+        &mut self,
+        target: WindowId,
+        direction: SplitDirection,
+        window: Box<dyn Window>,
+    ) -> Option<WindowId> {
+        let id = WindowId::new();
+        if !self.layout.as_mut()?.split(target, id, direction) {
+            return None;
+        }
+        self.windows.insert(id, window);
+        Some(id)
+    }
+
+    /// Close a window, returning it if it was open.
+    ///
+    /// The layout collapses so its neighbours take over the space.
+    pub fn close(&mut self, id: WindowId) -> Option<Box<dyn Window>> {
+        let window = self.windows.remove(&id)?;
+        self.layout = self.layout.take().and_then(|layout| layout.remove(id));
+        Some(window)
+    }
+
+    /// Check whether a window is open.
+    pub fn contains(&self, id: WindowId) -> bool {
+        self.windows.contains_key(&id)
+    }
+
+    /// Number of open windows.
+    pub fn len(&self) -> usize {
+        self.windows.len()
+    }
+
+    /// Check whether no window is open.
+    pub fn is_empty(&self) -> bool {
+        self.windows.is_empty()
+    }
+
+    /// Get the open window IDs in layout order.
+    pub fn ids(&self) -> Vec<WindowId> {
+        self.layout
+            .as_ref()
+            .map(LayoutNode::windows)
+            .unwrap_or_default()
+    }
+
+    /// Get the layout tree, if any window is open.
+    pub fn layout(&self) -> Option<&LayoutNode> {
+        self.layout.as_ref()
+    }
+
+    /// Replace the layout tree, e.g. with one restored from a session.
+    ///
+    /// The tree must hold every open window exactly once; otherwise the
+    /// layout is left as it is and `false` is returned.
+    pub fn set_layout(&mut self, layout: LayoutNode) -> bool {
+        let ids = layout.windows();
+        let unique: HashSet<WindowId> = ids.iter().copied().collect();
+        if unique.len() != ids.len()
+            || ids.len() != self.windows.len()
+            || !ids.iter().all(|id| self.windows.contains_key(id))
+        {
+            return false;
+        }
+        self.layout = Some(layout);
+        true
+    }
+
+    /// Grow the pane of `id` by `delta` cells along `direction`, or shrink
+    /// it if negative, when the layout fills `area`; see
+    /// [`LayoutNode::resize`]. Returns how many cells it grew.
+    pub fn resize(
+        &mut self,
+        area: Rect,
+        id: WindowId,
+        direction: SplitDirection,
+        delta: i32,
+    ) -> i32 {
+        self.layout
+            .as_mut()
+            .map_or(0, |layout| layout.resize(area, id, direction, delta))
+    }
+
+    /// Move the left or top edge of the pane of `id` by `delta` cells when
+    /// the layout fills `area`; see [`LayoutNode::move_edge`]. Returns how
+    /// far the edge moved.
+    pub fn move_edge(
+        &mut self,
+        area: Rect,
+        id: WindowId,
+        direction: SplitDirection,
+        delta: i32,
+    ) -> i32 {
+        self.layout
+            .as_mut()
+            .map_or(0, |layout| layout.move_edge(area, id, direction, delta))
+    }
+
+    /// Find the window beside `id` in `direction` when the layout fills
+    /// `area`; see [`LayoutNode::neighbor`].
+    pub fn neighbor(&self, area: Rect, id: WindowId, direction: PaneDirection) -> Option<WindowId> {
+        self.layout.as_ref()?.neighbor(area, id, direction)
+    }
+
+    /// Get a window.
+    pub fn get(&self, id: WindowId) -> Option<&dyn Window> {
+        self.windows.get(&id).map(|window| window.as_ref())
+    }
+
+    /// Get a window mutably.
+    pub fn get_mut(&mut self, id: WindowId) -> Option<&mut dyn Window> {
+        match self.windows.get_mut(&id) {
+            Some(window) => Some(window.as_mut()),
+            None => None,
+        }
+    }
+
+    /// Get a window as its concrete type, if it is open and is a `W`.
+    pub fn window<W: Window + 'static>(&self, id: WindowId) -> Option<&W> {
+        self.windows.get(&id)?.as_ref().as_any().downcast_ref()
+    }
+
+    /// Get a window mutably as its concrete type, if it is open and is a `W`.
+    pub fn window_mut<W: Window + 'static>(&mut self, id: WindowId) -> Option<&mut W> {
+        self.windows
+            .get_mut(&id)?
+            .as_mut()
+            .as_any_mut()
+            .downcast_mut()
+    }
+
+    /// Compute the area of every open window within `area`, in layout order.
+    pub fn rects(&self, area: Rect) -> Vec<(WindowId, Rect)> {
+        self.layout
+            .as_ref()
+            .map(|layout| layout.rects(area))
+            .unwrap_or_default()
+    }
+
+    /// Find the window under `position` when the layout fills `area`.
+    pub fn window_at(&self, area: Rect, position: Position) -> Option<WindowId> {
+        self.rects(area)
+            .into_iter()
+            .find(|(_, rect)| rect.contains(position))
+            .map(|(id, _)| id)
+    }
+
+    /// Get the window after `id` in layout order, wrapping around.
+    ///
+    /// Returns the first window if `id` is not open.
+    pub fn next(&self, id: WindowId) -> Option<WindowId> {
+        let ids = self.ids();
+        match ids.iter().position(|&open| open == id) {
+            Some(index) => ids.get((index + 1) % ids.len()).copied(),
+            None => ids.first().copied(),
+        }
+    }
+
+    /// Get the window before `id` in layout order, wrapping around.
+    ///
+    /// Returns the last window if `id` is not open.
+    pub fn previous(&self, id: WindowId) -> Option<WindowId> {
+        let ids = self.ids();
+        match ids.iter().position(|&open| open == id) {
+            Some(index) => ids.get((index + ids.len() - 1) % ids.len()).copied(),
+            None => ids.last().copied(),
+        }
+    }
+
+    /// Move focus to the next window in layout order.
+    pub fn focus_next(&self, focus: &mut FocusManager) {
+        let next = match focus.focused() {
+            Some(id) => self.next(id),
+            None => self.ids().first().copied(),
+        };
+        if let Some(next) = next {
+            focus.set_focus(next);
+        }
+    }
+
+    /// Move focus to the previous window in layout order.
+    pub fn focus_prev(&self, focus: &mut FocusManager) {
+        let previous = match focus.focused() {
+            Some(id) => self.previous(id),
+            None => self.ids().last().copied(),
+        };
+        if let Some(previous) = previous {
+            focus.set_focus(previous);
+        }
+    }
+
+    /// Render every open window into its layout area within `area`.
+    pub fn render(&mut self, frame: &mut Frame, area: Rect, focused: Option<WindowId>) {
+        for (id, rect) in self.rects(area) {
+            if let Some(window) = self.windows.get_mut(&id) {
+                window.render_with_focus(frame, rect, focused == Some(id));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window::{EditorWindow, TerminalWindow};
+
+    fn manager_with(count: usize) -> (WindowManager, Vec<WindowId>) {
+        let mut manager = WindowManager::new();
+        let ids = (0..count)
+            .map(|_| manager.open(Box::new(TerminalWindow::default())))
+            .collect();
+        (manager, ids)
+    }
+
+    #[test]
+    fn test_open_adds_columns() {
+        let (manager, ids) = manager_with(3);
+        assert_eq!(manager.ids(), ids);
+
+        let widths: Vec<u16> = manager
+            .rects(Rect::new(0, 0, 90, 10))
+            .iter()
+            .map(|(_, rect)| rect.width)
+            .collect();
+        assert_eq!(widths, vec![30, 30, 30]);
+    }
+
+    #[test]
+    fn test_close_removes_window_and_layout() {
+        let (mut manager, ids) = manager_with(2);
+
+        assert!(manager.close(ids[0]).is_some());
+        assert!(manager.close(ids[0]).is_none());
+        assert_eq!(manager.ids(), vec![ids[1]]);
+        assert_eq!(
+            manager.rects(Rect::new(0, 0, 80, 24)),
+            vec![(ids[1], Rect::new(0, 0, 80, 24))]
+        );
+
+        manager.close(ids[1]);
+        assert!(manager.is_empty());
+        assert!(manager.layout().is_none());
+    }
+
+    #[test]
+    fn test_set_layout_requires_every_open_window() {
+        let (mut manager, ids) = manager_with(3);
+        let stacked = LayoutNode::even_split(
+            SplitDirection::Vertical,
+            vec![
+                LayoutNode::Window(ids[2]),
+                LayoutNode::Window(ids[0]),
+                LayoutNode::Window(ids[1]),
+            ],
+        );
+        assert!(!manager.set_layout(LayoutNode::Window(ids[0])));
+        assert_eq!(manager.ids(), ids);
+
+        assert!(manager.set_layout(stacked));
+        assert_eq!(manager.ids(), vec![ids[2], ids[0], ids[1]]);
+    }
+
+    #[test]
+    fn test_split_requires_open_target() {
+        let (mut manager, ids) = manager_with(1);
+        let below = manager.split(
+            ids[0],
+            SplitDirection::Vertical,
+            Box::new(TerminalWindow::default()),
+        );
+        assert!(below.is_some());
+        assert_eq!(manager.len(), 2);
+
+        let missing = manager.split(
+            WindowId::new(),
+            SplitDirection::Vertical,
+            Box::new(TerminalWindow::default()),
+        );
+        assert!(missing.is_none());
+        assert_eq!(manager.len(), 2);
+    }
+
+    #[test]
+    fn test_downcast_to_concrete_window() {
+        let mut manager = WindowManager::new();
+        let editor = manager.open(Box::new(EditorWindow::with_text("hello")));
+
+        assert_eq!(
+            manager.window::<EditorWindow>(editor).unwrap().text(),
+            "hello"
+        );
+        assert!(manager.window::<TerminalWindow>(editor).is_none());
+
+        manager
+            .window_mut::<EditorWindow>(editor)
+            .unwrap()
+            .set_text("bye");
+        assert_eq!(
+            manager.window::<EditorWindow>(editor).unwrap().text(),
+            "bye"
+        );
+    }
+
+    #[test]
+    fn test_focus_cycles_in_layout_order() {
+        let (manager, ids) = manager_with(3);
+        let mut focus = FocusManager::new();
+
+        manager.focus_next(&mut focus);
+        assert_eq!(focus.focused(), Some(ids[0]));
+        manager.focus_next(&mut focus);
+        manager.focus_next(&mut focus);
+        manager.focus_next(&mut focus);
+        assert_eq!(focus.focused(), Some(ids[0]), "wraps around");
+
+        manager.focus_prev(&mut focus);
+        assert_eq!(focus.focused(), Some(ids[2]));
+    }
+}
@@ -0,0 +1,176 @@
+//! Line, word and character counts of a document, for the status bar.
+//!
+//! [`TextCounts`] counts a piece of text and estimates how long it takes
+//! to read. A [`WordCounter`] keeps the counts of the shown document, or
+//! of its selection, and counts again only when the document changes (it
+//! follows the buffer's [`TextChange`]s), another document is shown or
+//! the selection moves.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::ops::Range;
+use std::rc::{Rc, Weak};
+
+use cli_ide_base::Subscription;
+
+use crate::buffer_manager::{SharedBuffer, TextBuffer, TextChange};
+
+/// Reading speed the reading time is estimated at.
+pub const WORDS_PER_MINUTE: usize = 200;
+
+/// How much text there is.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TextCounts {
+    /// Lines, not counting an empty line after a final new line.
+    pub lines: usize,
+    /// Runs of characters between whitespace.
+    pub words: usize,
+    /// Characters, new lines included.
+    pub chars: usize,
+}
+
+impl TextCounts {
+    /// Count `text`.
+    pub fn of(text: &str) -> Self {
+        Self {
+            lines: text.lines().count(),
+            words: text.split_whitespace().count(),
+            chars: text.chars().count(),
+        }
+    }
+
+    /// Estimated minutes to read the words at [`WORDS_PER_MINUTE`],
+    /// rounded up; no words take no time.
+    pub fn reading_minutes(&self) -> usize {
+        self.words.div_ceil(WORDS_PER_MINUTE)
+    }
+}
+
+impl fmt::Display for TextCounts {
+    /// E.g. `12 lines, 1 word, 80 chars`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plural = |count: usize, one: &str| {
+            if count == 1 {
+                format!("{count} {one}")
+            } else {
+                format!("{count} {one}s")
+            }
+        };
+        write!(
+            f,
+            "{}, {}, {}",
+            plural(self.lines, "line"),
+            plural(self.words, "word"),
+            plural(self.chars, "char")
+        )
+    }
+}
+
+/// Keeps the counts of the shown document up to date.
+#[derive(Default)]
+pub struct WordCounter {
+    /// The counted document, if any.
+    buffer: Weak<RefCell<TextBuffer>>,
+    /// Changes to the counted document not counted yet.
+    changes: Option<Subscription<TextChange>>,
+    /// The counted selection; empty counts the whole document.
+    selection: Range<usize>,
+    /// The segment text for the last count.
+    text: String,
+}
+
+impl WordCounter {
+    /// Create a counter that has counted nothing yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the status bar text for `buffer` with `selection`, counting
+    /// again only if either changed since the last call.
+    ///
+    /// A non-empty selection is counted on its own, e.g.
+    /// `Selected: 1 line, 2 words, 11 chars`; otherwise the whole
+    /// document is, with its reading time, e.g.
+    /// `40 lines, 410 words, 2600 chars, 3 min read`.
+    pub fn update(&mut self, buffer: &SharedBuffer, selection: Range<usize>) -> &str {
+        let same_buffer = self
+            .buffer
+            .upgrade()
+            .is_some_and(|counted| Rc::ptr_eq(&counted, buffer));
+        let changed = match self.changes.as_ref() {
+            Some(changes) if same_buffer => changes.try_iter().count() > 0,
+            _ => true,
+        };
+        // Moving the cursor without selecting leaves the counts as they are
+        let selection = if selection.is_empty() {
+            0..0
+        } else {
+            selection
+        };
+        if !same_buffer {
+            self.buffer = Rc::downgrade(buffer);
+            self.changes = Some(buffer.borrow().on_did_change().subscribe());
+        }
+        if changed || selection != self.selection || self.text.is_empty() {
+            self.selection = selection;
+            self.text = count_text(buffer.borrow().text(), &self.selection);
+        }
+        &self.text
+    }
+}
+
+/// The status bar text for `selection` of `text`, or for all of it if the
+/// selection is empty.
+fn count_text(text: &str, selection: &Range<usize>) -> String {
+    if !selection.is_empty() {
+        if let Some(selected) = text.get(selection.clone()) {
+            return format!("Selected: {}", TextCounts::of(selected));
+        }
+    }
+    let counts = TextCounts::of(text);
+    format!("{counts}, {} min read", counts.reading_minutes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_and_reading_time() {
+        let counts = TextCounts::of("fn main() {\n    println!(\"hi\");\n}\n");
+        assert_eq!(
+            counts,
+            TextCounts {
+                lines: 3,
+                words: 5,
+                chars: 34
+            }
+        );
+        assert_eq!(counts.to_string(), "3 lines, 5 words, 34 chars");
+        assert_eq!(TextCounts::of("").reading_minutes(), 0);
+        assert_eq!(TextCounts::of(&"word ".repeat(201)).reading_minutes(), 2);
+        assert_eq!(TextCounts::of("é").to_string(), "1 line, 1 word, 1 char");
+    }
+
+    #[test]
+    fn test_counter_follows_edits_and_the_selection() {
+        let buffer: SharedBuffer = Rc::new(RefCell::new(TextBuffer::new("one two")));
+        let mut counter = WordCounter::new();
+        assert_eq!(
+            counter.update(&buffer, 0..0),
+            "1 line, 2 words, 7 chars, 1 min read"
+        );
+        buffer
+            .borrow_mut()
+            .edit(&[crate::code_action::TextEdit::new(7..7, " three")])
+            .unwrap();
+        assert_eq!(
+            counter.update(&buffer, 0..0),
+            "1 line, 3 words, 13 chars, 1 min read"
+        );
+        assert_eq!(
+            counter.update(&buffer, 4..13),
+            "Selected: 1 line, 2 words, 9 chars"
+        );
+    }
+}
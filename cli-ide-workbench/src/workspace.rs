@@ -0,0 +1,189 @@
+//! The root folders of a workspace.
+//!
+//! A workspace has a primary root, the folder the IDE was started in, and
+//! may have further roots added to it, e.g. a library checked out next to
+//! the application using it. The explorer shows every root, search and
+//! quick open span them, and each root's `.paradiddle.toml` applies to the
+//! files in it.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use cli_ide_platform::files::ignore;
+
+/// The root folders of a workspace, the primary root first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceFolders {
+    /// The roots, never empty.
+    roots: Vec<PathBuf>,
+}
+
+impl WorkspaceFolders {
+    /// Create a workspace with the single root `root`.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            roots: vec![root.into()],
+        }
+    }
+
+    /// Get the primary root.
+    pub fn primary(&self) -> &Path {
+        &self.roots[0]
+    }
+
+    /// Replace the primary root, keeping the others.
+    pub fn set_primary(&mut self, root: impl Into<PathBuf>) {
+        let root = root.into();
+        self.roots.retain(|other| *other != root);
+        match self.roots.first_mut() {
+            Some(primary) => *primary = root,
+            None => self.roots.push(root),
+        }
+    }
+
+    /// Get the roots, the primary root first.
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    /// Get the roots after the primary one.
+    pub fn extra_roots(&self) -> &[PathBuf] {
+        &self.roots[1..]
+    }
+
+    /// Check whether the workspace has more than one root.
+    pub fn is_multi_root(&self) -> bool {
+        self.roots.len() > 1
+    }
+
+    /// Add the root `root` after the others.
+    ///
+    /// Returns `false` if it already is a root.
+    pub fn add(&mut self, root: impl Into<PathBuf>) -> bool {
+        let root = root.into();
+        if self.roots.contains(&root) {
+            return false;
+        }
+        self.roots.push(root);
+        true
+    }
+
+    /// Remove the root `root`.
+    ///
+    /// Returns `false` if it is not a root or is the primary root, which
+    /// cannot be removed.
+    pub fn remove(&mut self, root: &Path) -> bool {
+        match self.roots.iter().position(|other| other == root) {
+            Some(index) if index > 0 => {
+                self.roots.remove(index);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Get the root holding `path`, the innermost one if roots are nested.
+    pub fn root_of(&self, path: &Path) -> Option<&Path> {
+        self.roots
+            .iter()
+            .filter(|root| path.starts_with(root))
+            .max_by_key(|root| root.components().count())
+            .map(PathBuf::as_path)
+    }
+
+    /// The workspace with each root canonicalized, where possible.
+    pub fn canonical(&self) -> Self {
+        Self {
+            roots: self
+                .roots
+                .iter()
+                .map(|root| fs::canonicalize(root).unwrap_or_else(|_| root.clone()))
+                .collect(),
+        }
+    }
+
+    /// The label of `path` in lists: its path relative to its root with
+    /// `/` separators, prefixed with the root's folder name when the
+    /// workspace has several roots.
+    ///
+    /// A path outside every root is labelled as it is.
+    pub fn label(&self, path: &Path) -> String {
+        let Some(root) = self.root_of(path) else {
+            return path.display().to_string();
+        };
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        let mut parts: Vec<String> = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        if self.is_multi_root() {
+            parts.insert(0, folder_name(root));
+        }
+        parts.join("/")
+    }
+
+    /// List the files that are not ignored under every root, root by root
+    /// and in path order within each, up to `limit` in all.
+    pub fn walk_files(&self, limit: usize) -> io::Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        for root in &self.roots {
+            let remaining = limit.saturating_sub(files.len());
+            if remaining == 0 {
+                break;
+            }
+            files.extend(ignore::walk_files(root, remaining)?);
+        }
+        Ok(files)
+    }
+}
+
+/// The name of the folder `root`, or the whole path for a root such as `/`.
+pub fn folder_name(root: &Path) -> String {
+    root.file_name().map_or_else(
+        || root.display().to_string(),
+        |name| name.to_string_lossy().into_owned(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_labels_name_the_root_once_there_are_several() {
+        let mut folders = WorkspaceFolders::new("/work/app");
+        assert_eq!(
+            folders.label(Path::new("/work/app/src/main.rs")),
+            "src/main.rs"
+        );
+        assert!(folders.add("/work/lib"));
+        assert!(!folders.add("/work/lib"));
+        assert!(folders.add("/work/app/vendor"));
+        assert!(folders.is_multi_root());
+
+        assert_eq!(
+            folders.label(Path::new("/work/app/src/main.rs")),
+            "app/src/main.rs"
+        );
+        assert_eq!(folders.label(Path::new("/work/lib/lib.rs")), "lib/lib.rs");
+        assert_eq!(
+            folders.label(Path::new("/work/app/vendor/x.rs")),
+            "vendor/x.rs",
+            "the innermost root"
+        );
+        assert_eq!(folders.label(Path::new("/tmp/a.rs")), "/tmp/a.rs");
+
+        assert!(!folders.remove(Path::new("/work/app")), "the primary root");
+        assert!(folders.remove(Path::new("/work/lib")));
+        assert_eq!(folders.extra_roots(), [PathBuf::from("/work/app/vendor")]);
+        folders.set_primary("/work/other");
+        assert_eq!(
+            folders.roots(),
+            [
+                PathBuf::from("/work/other"),
+                PathBuf::from("/work/app/vendor")
+            ]
+        );
+    }
+}
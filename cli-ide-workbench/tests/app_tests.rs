@@ -4,41 +4,9 @@
 //! verifying state transitions and rendering output.
 
 use cli_ide_workbench::app::{App, FocusedPane};
+use cli_ide_workbench::harness::{render_to_string, AppHarness};
 use cli_ide_workbench::input::{AppEvent, AppKey};
-use ratatui::backend::TestBackend;
 use ratatui::layout::Rect;
-use ratatui::Terminal;
-
-/// Helper to render the app to a string buffer.
-fn render_app_to_string(app: &mut App, width: u16, height: u16) -> String {
-    let backend = TestBackend::new(width, height);
-    let mut terminal = Terminal::new(backend).unwrap();
-
-    terminal
-        .draw(|frame| {
-            let area = frame.area();
-            app.render(frame, area);
-        })
-        .unwrap();
-
-    buffer_to_string(terminal.backend().buffer())
-}
-
-/// Convert a ratatui Buffer to a string.
-fn buffer_to_string(buffer: &ratatui::buffer::Buffer) -> String {
-    let area = buffer.area;
-    let mut result = String::new();
-
-    for y in 0..area.height {
-        for x in 0..area.width {
-            let cell = buffer.cell((x, y)).unwrap();
-            result.push_str(cell.symbol());
-        }
-        result.push('\n');
-    }
-
-    result
-}
 
 // ============================================================
 // State Transition Tests
@@ -92,6 +60,32 @@ fn app_toggles_focus_on_tab() {
     assert_eq!(app.focused(), FocusedPane::Editor);
 }
 
+#[test]
+fn app_replays_a_recorded_session() {
+    use cli_ide_workbench::recording::Replay;
+
+    // A recording trimmed by hand, as a regression test would keep it
+    let mut replay = Replay::parse(
+        "# resize, move focus to the terminal and back, then quit\n\
+         0 resize 120 40\n\
+         310 key tab\n\
+         620 key tab\n\
+         1500 key q\n",
+    )
+    .unwrap();
+    let mut app = App::new();
+
+    replay.run(&mut app);
+
+    assert!(replay.is_finished());
+    assert_eq!(app.size(), (120, 40));
+    assert_eq!(app.focused(), FocusedPane::Editor);
+    assert!(
+        !app.is_running(),
+        "App should quit after the recorded Q key"
+    );
+}
+
 #[test]
 fn app_ignores_other_keys() {
     let mut app = App::new();
@@ -116,7 +110,7 @@ fn app_ignores_other_keys() {
 #[test]
 fn app_renders_both_windows() {
     let mut app = App::new();
-    let output = render_app_to_string(&mut app, 80, 24);
+    let output = render_to_string(&mut app, 80, 24);
 
     assert!(
         output.contains("Editor"),
@@ -133,7 +127,7 @@ fn app_renders_both_windows() {
 #[test]
 fn app_renders_window_content() {
     let mut app = App::new();
-    let output = render_app_to_string(&mut app, 80, 24);
+    let output = render_to_string(&mut app, 80, 24);
 
     assert!(
         output.contains("Welcome"),
@@ -154,7 +148,7 @@ fn app_renders_after_resize() {
     // Resize to larger dimensions
     app.handle_event(AppEvent::Resize(100, 30));
 
-    let output = render_app_to_string(&mut app, 100, 30);
+    let output = render_to_string(&mut app, 100, 30);
 
     // Should still render both windows correctly
     assert!(
@@ -167,7 +161,7 @@ fn app_renders_after_resize() {
 #[test]
 fn app_renders_at_small_size() {
     let mut app = App::new();
-    let output = render_app_to_string(&mut app, 40, 10);
+    let output = render_to_string(&mut app, 40, 10);
 
     // Should render without panicking and have some content
     assert!(
@@ -202,14 +196,22 @@ fn app_layout_splits_evenly() {
 }
 
 #[test]
-fn app_layout_uses_full_height() {
+fn app_layout_uses_full_height_above_status_bar() {
     let app = App::new();
     let area = Rect::new(0, 0, 80, 24);
 
     let (left, right) = app.layout_rects(area);
+    let status_bar = App::status_bar_rect(area);
 
-    assert_eq!(left.height, 24, "Left pane should use full height");
-    assert_eq!(right.height, 24, "Right pane should use full height");
+    assert_eq!(
+        left.height, 23,
+        "Left pane should fill all but the last row"
+    );
+    assert_eq!(
+        right.height, 23,
+        "Right pane should fill all but the last row"
+    );
+    assert_eq!(status_bar, Rect::new(0, 23, 80, 1));
 }
 
 // ============================================================
@@ -266,7 +268,7 @@ fn app_focused_editor_shows_indicator() {
     // Editor should be focused by default
     assert_eq!(app.focused(), FocusedPane::Editor);
 
-    let output = render_app_to_string(&mut app, 80, 24);
+    let output = render_to_string(&mut app, 80, 24);
 
     // Editor should show focus indicator
     assert!(
@@ -289,7 +291,7 @@ fn app_focused_terminal_shows_indicator() {
     app.handle_event(AppEvent::Key(AppKey::Tab));
     assert_eq!(app.focused(), FocusedPane::Terminal);
 
-    let output = render_app_to_string(&mut app, 80, 24);
+    let output = render_to_string(&mut app, 80, 24);
 
     // Terminal should show focus indicator
     assert!(
@@ -310,7 +312,7 @@ fn app_focus_toggle_changes_indicators() {
     let mut app = App::new();
 
     // Initial state: Editor focused
-    let output1 = render_app_to_string(&mut app, 80, 24);
+    let output1 = render_to_string(&mut app, 80, 24);
     assert!(
         output1.contains("Editor [*]"),
         "Initially Editor should be focused"
@@ -318,7 +320,7 @@ fn app_focus_toggle_changes_indicators() {
 
     // Toggle to Terminal
     app.handle_event(AppEvent::Key(AppKey::Tab));
-    let output2 = render_app_to_string(&mut app, 80, 24);
+    let output2 = render_to_string(&mut app, 80, 24);
     assert!(
         output2.contains("Terminal [*]"),
         "After Tab, Terminal should be focused"
@@ -326,7 +328,7 @@ fn app_focus_toggle_changes_indicators() {
 
     // Toggle back to Editor
     app.handle_event(AppEvent::Key(AppKey::Tab));
-    let output3 = render_app_to_string(&mut app, 80, 24);
+    let output3 = render_to_string(&mut app, 80, 24);
     assert!(
         output3.contains("Editor [*]"),
         "After second Tab, Editor should be focused again"
@@ -382,3 +384,229 @@ fn app_custom_keybinding_works() {
     app.handle_event(AppEvent::Key(AppKey::Char('x')));
     assert!(!app.is_running(), "Custom 'x' binding should quit the app");
 }
+
+#[test]
+fn app_context_binding_overrides_global_for_focused_pane() {
+    use cli_ide_workbench::keybinding::{Action, WindowContext};
+
+    let mut app = App::new();
+
+    // In the terminal, Q should be passed through instead of quitting
+    app.keybinding_router_mut().register_for_context(
+        WindowContext::Terminal,
+        AppKey::Q,
+        Action::None,
+    );
+
+    app.handle_event(AppEvent::Key(AppKey::Tab));
+    app.handle_event(AppEvent::Key(AppKey::Q));
+    assert!(
+        app.is_running(),
+        "Terminal context binding should shadow global Q"
+    );
+
+    // Back in the editor, the global binding applies again
+    app.handle_event(AppEvent::Key(AppKey::Tab));
+    app.handle_event(AppEvent::Key(AppKey::Q));
+    assert!(
+        !app.is_running(),
+        "Editor should fall back to global Q binding"
+    );
+}
+
+#[test]
+fn app_buffer_binding_applies_only_while_editor_focused() {
+    use cli_ide_workbench::buffer_options::{BufferOptions, OptionOverrides};
+
+    let mut app = App::new();
+    let modeline = OptionOverrides::parse_modeline("// paradiddle: key.q=none")
+        .unwrap()
+        .unwrap();
+    app.editor_mut().set_options(BufferOptions::resolve(
+        &OptionOverrides::new(),
+        None,
+        Some(&modeline),
+    ));
+
+    app.handle_event(AppEvent::Key(AppKey::Q));
+    assert!(app.is_running(), "Buffer binding should shadow global Q");
+
+    app.handle_event(AppEvent::Key(AppKey::Tab));
+    app.handle_event(AppEvent::Key(AppKey::Q));
+    assert!(
+        !app.is_running(),
+        "Terminal should not see the editor buffer's bindings"
+    );
+}
+
+#[test]
+fn app_click_moves_focus_indicator() {
+    use cli_ide_workbench::input::{MouseButton, MouseEvent, MouseEventKind};
+
+    let mut app = App::with_size(80, 24);
+    app.handle_event(AppEvent::Mouse(MouseEvent::new(
+        MouseEventKind::Down(MouseButton::Left),
+        50,
+        10,
+    )));
+
+    let output = render_to_string(&mut app, 80, 24);
+    assert!(output.contains("Terminal [*]"));
+    assert!(!output.contains("Editor [*]"));
+}
+
+// ============================================================
+// Command Palette Tests
+// ============================================================
+
+#[test]
+fn app_renders_command_palette_overlay() {
+    let mut app = App::new();
+    app.handle_event(AppEvent::Key(AppKey::Char(':')));
+    // Narrow the list so the assertion doesn't depend on the command count
+    for c in "toggle".chars() {
+        app.handle_event(AppEvent::Key(AppKey::Char(c)));
+    }
+
+    let output = render_to_string(&mut app, 80, 24);
+
+    assert!(
+        output.contains("Command Palette"),
+        "Open palette should render its title.\nOutput:\n{}",
+        output
+    );
+    assert!(
+        output.contains("Toggle Focus"),
+        "Open palette should list commands.\nOutput:\n{}",
+        output
+    );
+}
+
+#[test]
+fn app_palette_filters_and_runs_quit() {
+    let mut app = App::new();
+    app.handle_event(AppEvent::Key(AppKey::Char(':')));
+    app.handle_event(AppEvent::Key(AppKey::Q));
+    for c in "uit".chars() {
+        app.handle_event(AppEvent::Key(AppKey::Char(c)));
+    }
+
+    assert_eq!(
+        app.command_palette().unwrap().selected().unwrap().title,
+        "Quit"
+    );
+
+    app.handle_event(AppEvent::Key(AppKey::Enter));
+    assert!(
+        !app.is_running(),
+        "Selecting Quit in the palette should quit"
+    );
+}
+
+#[test]
+fn app_renders_quick_fix_menu() {
+    use cli_ide_workbench::code_action::{CodeAction, CodeActionProvider, WorkspaceEdit};
+    use std::ops::Range;
+    use std::path::Path;
+
+    struct Provider;
+
+    impl CodeActionProvider for Provider {
+        fn code_actions(&self, _path: &Path, _text: &str, _range: Range<usize>) -> Vec<CodeAction> {
+            vec![CodeAction {
+                title: "Add missing import".to_string(),
+                kind: Some("quickfix".to_string()),
+                edit: WorkspaceEdit::new(),
+            }]
+        }
+    }
+
+    let mut app = App::with_size(80, 24);
+    app.editor_mut().set_path(Some("main.rs".into()));
+    app.set_code_action_provider(Box::new(Provider));
+    app.handle_event(AppEvent::Key(AppKey::Char('.')));
+
+    let output = render_to_string(&mut app, 80, 24);
+    assert!(output.contains("Quick Fix"));
+    assert!(output.contains("Add missing import"));
+}
+
+// ============================================================
+// Status Bar Tests
+// ============================================================
+
+#[test]
+fn app_renders_status_bar_on_bottom_row() {
+    use cli_ide_workbench::window::StatusBarUpdate;
+
+    let mut app = App::with_size(80, 24);
+    app.status_bar_updates().emit(StatusBarUpdate::SetSegment {
+        id: "git.branch".to_string(),
+        text: "main".to_string(),
+    });
+    app.handle_event(AppEvent::Key(AppKey::Tab));
+
+    let output = render_to_string(&mut app, 80, 24);
+    let last = output.lines().last().unwrap();
+    assert!(
+        last.starts_with(" Terminal | NORMAL | Ln 1, Col 1"),
+        "Status bar left side.\nOutput:\n{}",
+        output
+    );
+    assert!(
+        last.ends_with("main | 80x24 "),
+        "Status bar right side.\nOutput:\n{}",
+        output
+    );
+}
+
+// ============================================================
+// Window Manager Tests
+// ============================================================
+
+#[test]
+fn app_renders_windows_opened_at_runtime() {
+    use cli_ide_workbench::window::EditorWindow;
+
+    let mut app = App::with_size(90, 24);
+    app.open_window(Box::new(EditorWindow::with_text("Second editor")));
+
+    let output = render_to_string(&mut app, 90, 24);
+    assert!(
+        output.contains("Second editor"),
+        "Opened window should render.\nOutput:\n{}",
+        output
+    );
+    assert!(output.contains("Editor [*]"), "Opened window takes focus");
+    assert!(output.contains("Terminal"));
+
+    let (left, right) = app.layout_rects(Rect::new(0, 0, 90, 24));
+    assert_eq!((left.width, right.width), (30, 30));
+}
+
+#[test]
+fn harness_drives_the_palette_and_reads_the_screen() {
+    let mut harness = AppHarness::new();
+    harness
+        .assert_contains("Editor [*]")
+        .press(AppKey::Tab)
+        .assert_contains("Terminal [*]")
+        .press(AppKey::Tab);
+
+    harness
+        .type_str(":toggle")
+        .assert_contains("Command Palette")
+        .assert_contains("Toggle Focus");
+    assert!(
+        harness.line(23).starts_with(" Editor | PALETTE"),
+        "status bar: {}",
+        harness.line(23)
+    );
+
+    // Enter runs the first match, Toggle Focus
+    harness
+        .type_str("\n")
+        .assert_not_contains("Command Palette")
+        .assert_contains("Terminal [*]");
+    assert!(harness.app().is_running());
+}